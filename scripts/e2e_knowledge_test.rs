@@ -39,6 +39,7 @@ use t_koma_db::{
 };
 use t_koma_gateway::chat::compaction::CompactionConfig;
 use t_koma_gateway::providers::anthropic::AnthropicClient;
+use t_koma_gateway::providers::azure_openai::{AzureAuth, AzureOpenAiClient};
 use t_koma_gateway::providers::gemini::GeminiClient;
 use t_koma_gateway::providers::openai_compatible::OpenAiCompatibleClient;
 use t_koma_gateway::providers::openrouter::OpenRouterClient;
@@ -361,6 +362,15 @@ fn content_block_to_json(block: &ContentBlock) -> serde_json::Value {
                 "size": size
             })
         }
+        ContentBlock::Thinking {
+            thinking, redacted, ..
+        } => {
+            serde_json::json!({
+                "type": "thinking",
+                "thinking": thinking,
+                "redacted": redacted
+            })
+        }
     }
 }
 
@@ -486,6 +496,22 @@ fn create_models_from_config(model_alias: &str) -> (HashMap<String, ModelEntry>,
                 "kimi_code",
             ))
         }
+        ProviderType::AzureOpenAi => {
+            let endpoint = model_config.base_url.clone().unwrap_or_else(|| {
+                eprintln!(
+                    "\n{}Error: azure_openai model requires base_url{}\n",
+                    style::RED,
+                    style::RESET
+                );
+                std::process::exit(1);
+            });
+            Arc::new(AzureOpenAiClient::new(
+                endpoint,
+                &model_config.model,
+                model_config.api_version.as_deref(),
+                AzureAuth::ApiKey(api_key),
+            ))
+        }
     };
 
     models.insert(
@@ -688,7 +714,7 @@ async fn run_reflection(
     operator: &t_koma_db::Operator,
     ghost: &t_koma_db::Ghost,
 ) -> Option<DetailedReflection> {
-    use t_koma_gateway::reflection::run_reflection_now;
+    use t_koma_gateway::reflection::run_queued_reflection;
 
     let started = Utc::now();
     let session = SessionRepository::get_active(pool.pool(), &ghost.id, &operator.id)
@@ -697,15 +723,9 @@ async fn run_reflection(
         .flatten()
         .expect("No active session");
 
-    run_reflection_now(
-        state,
-        &ghost.name,
-        &ghost.id,
-        &session.id,
-        &operator.id,
-        None,
-    )
-    .await;
+    if let Err(err) = run_queued_reflection(state, &ghost.id, &session.id, false).await {
+        info("Reflection error", &format!("{err:?}"));
+    }
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
     // Get the full job log with transcript
@@ -3,12 +3,28 @@ use std::pin::Pin;
 use futures::{SinkExt, Stream, StreamExt};
 use tokio::sync::mpsc;
 use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::{HeaderValue, header};
 use tracing::{error, info, warn};
 
 use t_koma_core::{WsMessage, WsResponse};
 
 type ResponseStream = Pin<Box<dyn Stream<Item = WsResponse> + Send>>;
 
+/// Authenticate a gateway WebSocket request with `T_KOMA_API_TOKEN` when set.
+///
+/// The gateway accepts token-less connections from loopback only.
+pub fn with_api_token(mut request: Request) -> Request {
+    let token = t_koma_core::Secrets::from_env()
+        .ok()
+        .and_then(|secrets| secrets.t_koma_api_token);
+    if let Some(value) = token.and_then(|t| HeaderValue::from_str(&format!("Bearer {t}")).ok()) {
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+    }
+    request
+}
+
 /// WebSocket client for connecting to the gateway
 pub struct WsClient;
 
@@ -23,8 +39,7 @@ impl WsClient {
 
         info!("Connecting to WebSocket server at {}", url);
 
-        // Use the string directly for connection
-        let (ws_stream, _) = connect_async(url).await?;
+        let (ws_stream, _) = connect_async(with_api_token(url.into_client_request()?)).await?;
         info!("WebSocket connection established");
 
         let (mut write, mut read) = ws_stream.split();
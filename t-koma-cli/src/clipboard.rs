@@ -0,0 +1,140 @@
+//! System clipboard access for the TUI.
+//!
+//! Copying pipes the text into the platform clipboard tool (`pbcopy`,
+//! `wl-copy`, `xclip`, `xsel`, `clip`) and falls back to an OSC 52 escape
+//! sequence, which most terminals (and tmux with `set-clipboard on`) forward to
+//! the local clipboard, also over SSH. Pasting reads from the same tools.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use base64::Engine;
+
+/// A clipboard tool: program plus the arguments to copy and to paste.
+struct Tool {
+    program: &'static str,
+    copy_args: &'static [&'static str],
+    paste: (&'static str, &'static [&'static str]),
+}
+
+#[cfg(target_os = "macos")]
+fn tools() -> Vec<Tool> {
+    vec![Tool {
+        program: "pbcopy",
+        copy_args: &[],
+        paste: ("pbpaste", &[]),
+    }]
+}
+
+#[cfg(windows)]
+fn tools() -> Vec<Tool> {
+    vec![Tool {
+        program: "clip",
+        copy_args: &[],
+        paste: ("powershell", &["-NoProfile", "-Command", "Get-Clipboard"]),
+    }]
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn tools() -> Vec<Tool> {
+    let mut tools = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(Tool {
+            program: "wl-copy",
+            copy_args: &[],
+            paste: ("wl-paste", &["--no-newline"]),
+        });
+    }
+    tools.push(Tool {
+        program: "xclip",
+        copy_args: &["-selection", "clipboard"],
+        paste: ("xclip", &["-selection", "clipboard", "-o"]),
+    });
+    tools.push(Tool {
+        program: "xsel",
+        copy_args: &["--clipboard", "--input"],
+        paste: ("xsel", &["--clipboard", "--output"]),
+    });
+    tools
+}
+
+/// Copy `text` to the clipboard. Returns how it was copied, for the status line.
+pub fn copy(text: &str) -> io::Result<&'static str> {
+    for tool in tools() {
+        if pipe_into(tool.program, tool.copy_args, text).is_ok() {
+            return Ok(tool.program);
+        }
+    }
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()?;
+    Ok("terminal (OSC 52)")
+}
+
+/// Read the clipboard as text.
+pub fn paste() -> io::Result<String> {
+    let mut last_error = None;
+    for tool in tools() {
+        let (program, args) = tool.paste;
+        match Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            Ok(output) => {
+                last_error = Some(io::Error::other(format!(
+                    "{} exited with {}",
+                    program, output.status
+                )));
+            }
+            Err(_) => {}
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "no clipboard tool found (install wl-clipboard, xclip or xsel)",
+        )
+    }))
+}
+
+fn pipe_into(program: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )))
+    }
+}
+
+/// OSC 52 "set clipboard" escape sequence for `text`.
+fn osc52_sequence(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{}\x07", encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi\n"), "\x1b]52;c;aGkK\x07");
+    }
+}
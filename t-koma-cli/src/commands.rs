@@ -0,0 +1,682 @@
+//! Subcommands.
+//!
+//! Without arguments `t-koma-cli` opens the TUI. `init` is the interactive
+//! first-run setup, see [`crate::init`], and `doctor` diagnoses an existing
+//! installation, see [`crate::doctor`]. The maintenance commands
+//! (`cron-validate`, `db-encrypt`, `audit`, `tool-usage`) work on local files
+//! and the database; `chat`, `sessions`, `knowledge` and `admin` script a
+//! running gateway through its REST API, so they need `T_KOMA_API_TOKEN`
+//! (with the `admin` scope for `admin`). `pipe` speaks the WebSocket protocol
+//! as JSON lines, see [`crate::pipe`]. `gateway` runs the gateway process
+//! itself, see [`crate::gateway_spawner`]. `local` chats with a local model
+//! without any gateway, see [`crate::local`].
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use t_koma_core::{ChatAttachment, GatewayMessageKind, WsResponse};
+
+use crate::{
+    gateway_spawner::{GatewayControl, UnitKind},
+    local::LocalOptions,
+};
+
+pub const USAGE: &str = "\
+Usage: t-koma-cli [COMMAND]
+
+Without a command, opens the TUI.
+
+Gateway commands (REST API, need T_KOMA_API_TOKEN):
+  chat --ghost <name> [--session <id>] [--model <alias>] [--attach <path>]... [message]
+                                  Run one turn; reads the message from stdin when omitted
+  sessions list --ghost <name>    List sessions with a GHOST
+  knowledge search --ghost <name> [--limit <n>] <query>
+                                  Search a GHOST's knowledge
+  admin approve <operator id>     Approve a pending OPERATOR (admin scope)
+  admin deny <operator id>        Deny a pending OPERATOR (admin scope)
+
+  --ghost defaults to T_KOMA_GHOST; --json prints the raw API response.
+
+  pipe                            WebSocket messages as JSON lines on stdin/stdout
+
+Gateway process:
+  gateway start|stop|status|restart
+                                  Run the gateway in the background (PID and log in the data dir)
+  gateway unit <systemd|launchd> [--install]
+                                  Print or install a service definition for the gateway
+
+Offline:
+  local [--ghost <name>] [--model <alias>] [--no-knowledge]
+                                  Chat with a local openai_compatible model (e.g. Ollama) and the
+                                  knowledge base in-process, without a gateway
+
+Local commands:
+  init                            Interactive first-run setup (provider, keys, Discord, knowledge)
+  doctor                          Check config, keys, database, disk and gateway, with fixes
+  cron-validate [path]            Validate CRON job files
+  db-encrypt [--store-key]        Encrypt a plaintext database with SQLCipher
+  audit [flags]                   Print the audit trail
+  tool-usage [flags]              Print tool usage statistics
+  help                            Show this message
+";
+
+/// Default GHOST for gateway commands.
+const GHOST_ENV: &str = "T_KOMA_GHOST";
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Tui,
+    Help,
+    Init,
+    Doctor,
+    CronValidate(Option<PathBuf>),
+    DbEncrypt {
+        store_key: bool,
+    },
+    /// Raw flags, parsed by the command itself.
+    Audit(Vec<String>),
+    /// Raw flags, parsed by the command itself.
+    ToolUsage(Vec<String>),
+    Gateway {
+        request: GatewayRequest,
+        json: bool,
+    },
+    Pipe,
+    GatewayControl(GatewayControl),
+    Local(LocalOptions),
+}
+
+/// A command that runs against the gateway's REST API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GatewayRequest {
+    Chat {
+        ghost: String,
+        session_id: Option<String>,
+        model: Option<String>,
+        /// Read from stdin when `None`.
+        message: Option<String>,
+        /// Local files sent with the message.
+        attachments: Vec<PathBuf>,
+    },
+    ListSessions {
+        ghost: String,
+    },
+    SearchKnowledge {
+        ghost: String,
+        query: String,
+        limit: Option<usize>,
+    },
+    ApproveOperator {
+        operator_id: String,
+    },
+    DenyOperator {
+        operator_id: String,
+    },
+}
+
+/// Flags and positional arguments of one subcommand.
+#[derive(Debug, Default)]
+struct Args {
+    flags: Vec<(String, String)>,
+    positional: Vec<String>,
+    json: bool,
+}
+
+impl Args {
+    /// Split `args` into `--flag value` pairs (only `value_flags` are
+    /// accepted), the `--json` switch and positional arguments. `--` ends
+    /// flag parsing.
+    fn parse(args: &[String], value_flags: &[&str]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--" => {
+                    parsed.positional.extend(iter.by_ref().cloned());
+                }
+                "--json" => parsed.json = true,
+                flag if flag.starts_with("--") => {
+                    if !value_flags.contains(&flag) {
+                        return Err(format!("Unknown flag: {flag}"));
+                    }
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| format!("Missing value for {flag}"))?;
+                    parsed.flags.push((flag.to_string(), value.clone()));
+                }
+                _ => parsed.positional.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> Option<String> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Every value of a repeatable flag, in order.
+    fn flag_values(&self, name: &str) -> Vec<String> {
+        self.flags
+            .iter()
+            .filter(|(flag, _)| flag == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// `--ghost`, falling back to `T_KOMA_GHOST`.
+    fn ghost(&self, env_ghost: Option<String>) -> Result<String, String> {
+        self.flag("--ghost")
+            .or(env_ghost)
+            .filter(|ghost| !ghost.is_empty())
+            .ok_or_else(|| format!("Missing --ghost (or set {GHOST_ENV})"))
+    }
+
+    fn no_positional(&self) -> Result<(), String> {
+        match self.positional.first() {
+            Some(extra) => Err(format!("Unexpected argument: {extra}")),
+            None => Ok(()),
+        }
+    }
+
+    /// The single positional argument.
+    fn one_positional(&self, what: &str) -> Result<String, String> {
+        match self.positional.as_slice() {
+            [value] => Ok(value.clone()),
+            [] => Err(format!("Missing {what}")),
+            [_, extra, ..] => Err(format!("Unexpected argument: {extra}")),
+        }
+    }
+}
+
+/// Parse the arguments after the program name.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    parse_with_env(args, std::env::var(GHOST_ENV).ok())
+}
+
+fn parse_with_env(args: &[String], env_ghost: Option<String>) -> Result<Command, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Command::Tui);
+    };
+    let gateway = |request, args: Args| {
+        Ok(Command::Gateway {
+            request,
+            json: args.json,
+        })
+    };
+
+    match command.as_str() {
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "init" => match rest.first() {
+            Some(extra) => Err(format!("Unexpected argument: {extra}")),
+            None => Ok(Command::Init),
+        },
+        "gateway" => {
+            let usage = "Usage: t-koma-cli gateway <start|stop|status|restart> \
+                         | gateway unit <systemd|launchd> [--install]";
+            let control = match rest {
+                [action] if action == "start" => GatewayControl::Start,
+                [action] if action == "stop" => GatewayControl::Stop,
+                [action] if action == "status" => GatewayControl::Status,
+                [action] if action == "restart" => GatewayControl::Restart,
+                [action, kind, flags @ ..] if action == "unit" => {
+                    let kind = UnitKind::parse(kind).ok_or(usage)?;
+                    let install = match flags {
+                        [] => false,
+                        [flag] if flag == "--install" => true,
+                        _ => return Err(usage.to_string()),
+                    };
+                    GatewayControl::Unit { kind, install }
+                }
+                _ => return Err(usage.to_string()),
+            };
+            Ok(Command::GatewayControl(control))
+        }
+        "doctor" => match rest.first() {
+            Some(extra) => Err(format!("Unexpected argument: {extra}")),
+            None => Ok(Command::Doctor),
+        },
+        "cron-validate" => Ok(Command::CronValidate(rest.first().map(PathBuf::from))),
+        "db-encrypt" => Ok(Command::DbEncrypt {
+            store_key: rest.iter().any(|arg| arg == "--store-key"),
+        }),
+        "audit" => Ok(Command::Audit(rest.to_vec())),
+        "tool-usage" => Ok(Command::ToolUsage(rest.to_vec())),
+        "pipe" => match rest.first() {
+            Some(extra) => Err(format!("Unexpected argument: {extra}")),
+            None => Ok(Command::Pipe),
+        },
+        "local" => {
+            let knowledge = !rest.iter().any(|arg| arg == "--no-knowledge");
+            let rest: Vec<String> = rest
+                .iter()
+                .filter(|arg| *arg != "--no-knowledge")
+                .cloned()
+                .collect();
+            let args = Args::parse(&rest, &["--ghost", "--model"])?;
+            args.no_positional()?;
+            Ok(Command::Local(LocalOptions {
+                ghost: args.flag("--ghost").or(env_ghost).filter(|g| !g.is_empty()),
+                model: args.flag("--model"),
+                knowledge,
+            }))
+        }
+        "chat" => {
+            let args = Args::parse(rest, &["--ghost", "--session", "--model", "--attach"])?;
+            let message = (!args.positional.is_empty()).then(|| args.positional.join(" "));
+            gateway(
+                GatewayRequest::Chat {
+                    ghost: args.ghost(env_ghost)?,
+                    session_id: args.flag("--session"),
+                    model: args.flag("--model"),
+                    message,
+                    attachments: args
+                        .flag_values("--attach")
+                        .into_iter()
+                        .map(PathBuf::from)
+                        .collect(),
+                },
+                args,
+            )
+        }
+        "sessions" => match rest.split_first() {
+            Some((action, rest)) if action == "list" => {
+                let args = Args::parse(rest, &["--ghost"])?;
+                args.no_positional()?;
+                gateway(
+                    GatewayRequest::ListSessions {
+                        ghost: args.ghost(env_ghost)?,
+                    },
+                    args,
+                )
+            }
+            _ => Err("Usage: t-koma-cli sessions list --ghost <name>".to_string()),
+        },
+        "knowledge" => match rest.split_first() {
+            Some((action, rest)) if action == "search" => {
+                let args = Args::parse(rest, &["--ghost", "--limit"])?;
+                if args.positional.is_empty() {
+                    return Err("Missing search query".to_string());
+                }
+                let limit = args
+                    .flag("--limit")
+                    .map(|value| {
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid --limit: {value}"))
+                    })
+                    .transpose()?;
+                gateway(
+                    GatewayRequest::SearchKnowledge {
+                        ghost: args.ghost(env_ghost)?,
+                        query: args.positional.join(" "),
+                        limit,
+                    },
+                    args,
+                )
+            }
+            _ => Err("Usage: t-koma-cli knowledge search --ghost <name> <query>".to_string()),
+        },
+        "admin" => match rest.split_first() {
+            Some((action, rest)) if action == "approve" || action == "deny" => {
+                let args = Args::parse(rest, &[])?;
+                let operator_id = args.one_positional("operator id")?;
+                let request = if action == "approve" {
+                    GatewayRequest::ApproveOperator { operator_id }
+                } else {
+                    GatewayRequest::DenyOperator { operator_id }
+                };
+                gateway(request, args)
+            }
+            _ => Err("Usage: t-koma-cli admin <approve|deny> <operator id>".to_string()),
+        },
+        other => Err(format!("Unknown command: {other}\n\n{USAGE}")),
+    }
+}
+
+/// Read a local file into an inline chat attachment. The gateway guesses
+/// the MIME type from the file name.
+pub fn read_attachment(path: &Path) -> Result<ChatAttachment, String> {
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Not a file: {}", path.display()))?;
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Ok(ChatAttachment {
+        filename,
+        mime_type: None,
+        data: Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        url: None,
+    })
+}
+
+/// The path of an `/attach <path>` input line (pipe mode and the TUI chat
+/// pane), an error for a bare `/attach`, or `None` for any other line.
+pub fn attach_command(line: &str) -> Option<Result<PathBuf, String>> {
+    let line = line.trim();
+    if line == "/attach" {
+        return Some(Err("Usage: /attach <path>".to_string()));
+    }
+    line.strip_prefix("/attach ")
+        .map(|path| Ok(PathBuf::from(path.trim())))
+}
+
+/// REST API base URL for a gateway WebSocket URL (`ws://host:port/ws`).
+pub(crate) fn api_base_url(ws_url: &str) -> Result<url::Url, String> {
+    let mut url = url::Url::parse(ws_url).map_err(|e| format!("Invalid gateway URL: {e}"))?;
+    let scheme = match url.scheme() {
+        "ws" => "http",
+        "wss" => "https",
+        other => return Err(format!("Unsupported gateway URL scheme: {other}")),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| format!("Invalid gateway URL: {ws_url}"))?;
+    url.set_path("/");
+    url.set_query(None);
+    Ok(url)
+}
+
+/// Run a gateway command and print its result.
+pub async fn run_gateway(
+    request: GatewayRequest,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let settings = t_koma_core::Settings::load()?;
+    let base = api_base_url(&settings.ws_url())?;
+    let token = t_koma_core::Secrets::from_env()?
+        .t_koma_api_token
+        .ok_or("T_KOMA_API_TOKEN is not set: gateway commands need an API token")?;
+    let client = reqwest::Client::new();
+
+    let builder = match &request {
+        GatewayRequest::Chat {
+            ghost,
+            session_id,
+            model,
+            message,
+            attachments,
+        } => {
+            let content = match message {
+                Some(message) => message.clone(),
+                None if attachments.is_empty() => std::io::read_to_string(std::io::stdin())?,
+                None => String::new(),
+            };
+            if content.trim().is_empty() && attachments.is_empty() {
+                return Err("Empty message".into());
+            }
+            let attachments = attachments
+                .iter()
+                .map(|path| read_attachment(path))
+                .collect::<Result<Vec<_>, _>>()?;
+            client
+                .post(base.join("api/chat")?)
+                .json(&serde_json::json!({
+                    "ghost": ghost,
+                    "session_id": session_id,
+                    "model": model,
+                    "content": content,
+                    "attachments": attachments,
+                }))
+        }
+        GatewayRequest::ListSessions { ghost } => client
+            .get(base.join("api/sessions")?)
+            .query(&[("ghost", ghost)]),
+        GatewayRequest::SearchKnowledge {
+            ghost,
+            query,
+            limit,
+        } => {
+            let mut builder = client
+                .get(base.join("api/knowledge/search")?)
+                .query(&[("ghost", ghost), ("q", query)]);
+            if let Some(limit) = limit {
+                builder = builder.query(&[("limit", limit)]);
+            }
+            builder
+        }
+        GatewayRequest::ApproveOperator { operator_id } => {
+            client.post(base.join(&format!("api/admin/operators/{operator_id}/approve"))?)
+        }
+        GatewayRequest::DenyOperator { operator_id } => {
+            client.post(base.join(&format!("api/admin/operators/{operator_id}/deny"))?)
+        }
+    };
+
+    let response = builder.bearer_auth(token).send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        let error = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| value["error"].as_str().map(str::to_string))
+            .unwrap_or(body);
+        return Err(format!("Gateway returned {status}: {error}").into());
+    }
+    if json {
+        println!("{body}");
+        return Ok(());
+    }
+
+    match request {
+        GatewayRequest::Chat { .. } => {
+            #[derive(serde::Deserialize)]
+            struct ChatReply {
+                session_id: String,
+                responses: Vec<WsResponse>,
+            }
+            let reply: ChatReply = serde_json::from_str(&body)?;
+            eprintln!("session {}", reply.session_id);
+            print_responses(&reply.responses);
+        }
+        GatewayRequest::ListSessions { .. } | GatewayRequest::SearchKnowledge { .. } => {
+            print_responses(&[serde_json::from_str(&body)?]);
+        }
+        GatewayRequest::ApproveOperator { operator_id } => {
+            println!("Approved {operator_id}.");
+        }
+        GatewayRequest::DenyOperator { operator_id } => {
+            println!("Denied {operator_id}.");
+        }
+    }
+    Ok(())
+}
+
+/// Print gateway responses as plain text; errors go to stderr.
+fn print_responses(responses: &[WsResponse]) {
+    for response in responses {
+        match response {
+            WsResponse::Response { message, .. }
+            | WsResponse::UsageBudgetExceeded { message, .. } => {
+                if message.kind == GatewayMessageKind::Error {
+                    eprintln!("{}", message.text_fallback);
+                } else {
+                    println!("{}", message.text_fallback);
+                }
+            }
+            WsResponse::SessionList { sessions } => {
+                if sessions.is_empty() {
+                    println!("No sessions.");
+                }
+                for session in sessions {
+                    println!(
+                        "{} {} {} {:>4} msgs  {}",
+                        if session.is_active { "*" } else { " " },
+                        session.id,
+                        session.updated_at.format("%Y-%m-%d %H:%M"),
+                        session.message_count,
+                        session.title.as_deref().unwrap_or("")
+                    );
+                }
+            }
+            WsResponse::KnowledgeSearchResults { results } => {
+                if results.is_empty() {
+                    println!("No results.");
+                }
+                for result in results {
+                    println!("{} [{}] {}", result.id, result.scope, result.title);
+                    let snippet = result.snippet.split_whitespace().collect::<Vec<_>>();
+                    if !snippet.is_empty() {
+                        println!("    {}", snippet.join(" "));
+                    }
+                }
+            }
+            other => println!("{}", serde_json::to_string(other).unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_with_env(&[], None), Ok(Command::Tui));
+        assert_eq!(
+            parse_with_env(&args("audit --limit 5"), None),
+            Ok(Command::Audit(args("--limit 5")))
+        );
+        assert_eq!(
+            parse_with_env(&args("chat --ghost alpha --json what is up"), None),
+            Ok(Command::Gateway {
+                request: GatewayRequest::Chat {
+                    ghost: "alpha".to_string(),
+                    session_id: None,
+                    model: None,
+                    message: Some("what is up".to_string()),
+                    attachments: vec![],
+                },
+                json: true,
+            })
+        );
+        assert_eq!(
+            parse_with_env(
+                &args("chat --ghost alpha --attach a.png --attach notes.md"),
+                None
+            ),
+            Ok(Command::Gateway {
+                request: GatewayRequest::Chat {
+                    ghost: "alpha".to_string(),
+                    session_id: None,
+                    model: None,
+                    message: None,
+                    attachments: vec![PathBuf::from("a.png"), PathBuf::from("notes.md")],
+                },
+                json: false,
+            })
+        );
+        assert_eq!(
+            parse_with_env(&args("sessions list"), Some("beta".to_string())),
+            Ok(Command::Gateway {
+                request: GatewayRequest::ListSessions {
+                    ghost: "beta".to_string(),
+                },
+                json: false,
+            })
+        );
+        assert_eq!(
+            parse_with_env(
+                &args("knowledge search --limit 3 --ghost alpha -- --rust"),
+                None
+            ),
+            Ok(Command::Gateway {
+                request: GatewayRequest::SearchKnowledge {
+                    ghost: "alpha".to_string(),
+                    query: "--rust".to_string(),
+                    limit: Some(3),
+                },
+                json: false,
+            })
+        );
+        assert_eq!(
+            parse_with_env(&args("admin approve op_1"), None),
+            Ok(Command::Gateway {
+                request: GatewayRequest::ApproveOperator {
+                    operator_id: "op_1".to_string(),
+                },
+                json: false,
+            })
+        );
+
+        assert_eq!(
+            parse_with_env(&args("local --no-knowledge --model qwen"), None),
+            Ok(Command::Local(LocalOptions {
+                ghost: None,
+                model: Some("qwen".to_string()),
+                knowledge: false,
+            }))
+        );
+        assert_eq!(
+            parse_with_env(&args("local"), Some("alpha".to_string())),
+            Ok(Command::Local(LocalOptions {
+                ghost: Some("alpha".to_string()),
+                model: None,
+                knowledge: true,
+            }))
+        );
+        assert!(parse_with_env(&args("local hello"), None).is_err());
+        assert_eq!(parse_with_env(&args("pipe"), None), Ok(Command::Pipe));
+        assert_eq!(parse_with_env(&args("init"), None), Ok(Command::Init));
+        assert_eq!(parse_with_env(&args("doctor"), None), Ok(Command::Doctor));
+        assert_eq!(
+            parse_with_env(&args("gateway restart"), None),
+            Ok(Command::GatewayControl(GatewayControl::Restart))
+        );
+        assert_eq!(
+            parse_with_env(&args("gateway unit launchd --install"), None),
+            Ok(Command::GatewayControl(GatewayControl::Unit {
+                kind: UnitKind::Launchd,
+                install: true,
+            }))
+        );
+        assert!(parse_with_env(&args("gateway unit upstart"), None).is_err());
+        assert!(parse_with_env(&args("gateway start now"), None).is_err());
+
+        assert!(parse_with_env(&args("sessions list"), None).is_err());
+        assert!(parse_with_env(&args("chat --ghost"), None).is_err());
+        assert!(parse_with_env(&args("chat --ghost a --verbose hi"), None).is_err());
+        assert!(parse_with_env(&args("admin approve op_1 op_2"), None).is_err());
+        assert!(parse_with_env(&args("frobnicate"), None).is_err());
+    }
+
+    #[test]
+    fn test_attach_command() {
+        assert_eq!(
+            attach_command("  /attach ~/notes/plan one.md "),
+            Some(Ok(PathBuf::from("~/notes/plan one.md")))
+        );
+        assert_eq!(
+            attach_command("/attach"),
+            Some(Err("Usage: /attach <path>".to_string()))
+        );
+        assert_eq!(attach_command("please /attach this"), None);
+        assert_eq!(attach_command("/attachments"), None);
+    }
+
+    #[test]
+    fn test_api_base_url() {
+        assert_eq!(
+            api_base_url("ws://127.0.0.1:3000/ws").unwrap().as_str(),
+            "http://127.0.0.1:3000/"
+        );
+        assert_eq!(
+            api_base_url("wss://koma.example.com/ws")
+                .unwrap()
+                .join("api/chat")
+                .unwrap()
+                .as_str(),
+            "https://koma.example.com/api/chat"
+        );
+        assert!(api_base_url("http://127.0.0.1:3000").is_err());
+    }
+}
@@ -0,0 +1,499 @@
+//! `t-koma-cli doctor`: installation diagnostics.
+//!
+//! Every check runs even when an earlier one fails, and each problem comes
+//! with a suggested fix. The database is opened read-only, so running it next
+//! to a live gateway is safe. Checks that need the gateway (model health and
+//! the knowledge index) are skipped when it is not running. Exits non-zero
+//! when any check fails.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use t_koma_core::{ProviderType, Settings, WsMessage, WsResponse};
+
+use crate::{
+    client::WsClient, commands::api_base_url, init::check_provider_key,
+    tui::app::onboarding::OnboardingState,
+};
+
+/// A database above this size gets a cold-storage hint.
+const LARGE_DB_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Check {
+    status: Status,
+    name: String,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(status: Status, name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name: name.into(),
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    fn render(&self) -> String {
+        let mut line = format!(
+            "{:<5} {:<18} {}",
+            self.status.label(),
+            self.name,
+            self.detail
+        );
+        if let Some(fix) = &self.fix {
+            line.push_str(&format!("\n      fix: {fix}"));
+        }
+        line
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| dir_size(&entry.path()))
+        .sum()
+}
+
+/// Parse `config.toml` without creating it, then validate it like the gateway.
+fn check_config(checks: &mut Vec<Check>) -> Option<Settings> {
+    let path = match Settings::config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            checks.push(Check::new(Status::Fail, "config", e.to_string()));
+            return None;
+        }
+    };
+    if !path.exists() {
+        checks.push(
+            Check::new(
+                Status::Fail,
+                "config",
+                format!("{} does not exist", path.display()),
+            )
+            .fix("run `t-koma-cli init`"),
+        );
+        return None;
+    }
+    let settings = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|raw| Settings::from_toml(&raw).map_err(|e| e.to_string()))
+    {
+        Ok(settings) => settings,
+        Err(e) => {
+            checks.push(
+                Check::new(Status::Fail, "config", format!("{}: {e}", path.display())).fix(
+                    "fix the TOML error above, or move the file away and run `t-koma-cli init`",
+                ),
+            );
+            return None;
+        }
+    };
+    checks.push(match t_koma_core::Config::load() {
+        Ok(config) => Check::new(
+            Status::Ok,
+            "config",
+            format!(
+                "{} models, default {}",
+                settings.models.len(),
+                config.default_model_alias()
+            ),
+        ),
+        Err(e) => Check::new(Status::Fail, "config", e.to_string())
+            .fix("run `t-koma-cli init` or edit config.toml"),
+    });
+    Some(settings)
+}
+
+/// One key check per distinct provider endpoint and key; the configured model
+/// ids are looked up in the returned model list.
+async fn check_providers(checks: &mut Vec<Check>, settings: &Settings, client: &reqwest::Client) {
+    let env_path = Settings::config_path()
+        .ok()
+        .and_then(|path| path.parent().map(|dir| dir.join(".env")))
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| ".env".to_string());
+    // Keyed by provider, base URL and key env var.
+    let mut results: HashMap<_, Result<Option<Vec<String>>, String>> = HashMap::new();
+
+    for (alias, model) in &settings.models {
+        let name = format!("model {alias}");
+        let env_var = model
+            .api_key_env
+            .clone()
+            .unwrap_or_else(|| OnboardingState::env_var_for_provider(model.provider).to_string());
+        let Ok(key) = std::env::var(&env_var) else {
+            if model.provider == ProviderType::AzureOpenAi {
+                checks.push(Check::new(
+                    Status::Skip,
+                    name,
+                    format!("{env_var} not set, assuming Azure AD credentials"),
+                ));
+            } else {
+                checks.push(
+                    Check::new(Status::Fail, name, format!("{env_var} is not set"))
+                        .fix(format!("add {env_var}=... to {env_path}")),
+                );
+            }
+            continue;
+        };
+
+        let cache_key = (
+            model.provider.as_str(),
+            model.base_url.clone(),
+            env_var.clone(),
+        );
+        if !results.contains_key(&cache_key) {
+            let result =
+                check_provider_key(client, model.provider, &key, model.base_url.as_deref()).await;
+            results.insert(cache_key.clone(), result);
+        }
+        checks.push(match &results[&cache_key] {
+            Ok(None) => Check::new(
+                Status::Skip,
+                name,
+                format!("{} keys cannot be checked here", model.provider),
+            ),
+            Ok(Some(ids)) if ids.is_empty() || ids.contains(&model.model) => Check::new(
+                Status::Ok,
+                name,
+                format!("{} {} reachable", model.provider, model.model),
+            ),
+            Ok(Some(_)) => Check::new(
+                Status::Warn,
+                name,
+                format!("{} does not list {}", model.provider, model.model),
+            )
+            .fix(format!("check the model id of [models.{alias}]")),
+            Err(e) => Check::new(Status::Fail, name, e.clone()).fix(format!(
+                "check {env_var} in {env_path} and the provider's status"
+            )),
+        });
+    }
+}
+
+async fn check_database(checks: &mut Vec<Check>) {
+    let path = match t_koma_db::KomaDbPool::db_path() {
+        Ok(path) => path,
+        Err(e) => {
+            checks.push(Check::new(Status::Fail, "database", e.to_string()));
+            return;
+        }
+    };
+    if !path.exists() {
+        checks.push(
+            Check::new(
+                Status::Warn,
+                "database",
+                format!("{} does not exist yet", path.display()),
+            )
+            .fix("start `t-koma-gateway` once to create it"),
+        );
+        return;
+    }
+    let expected = t_koma_db::KomaDbPool::expected_schema_version();
+    let db = match t_koma_db::KomaDbPool::open_read_only().await {
+        Ok(db) => db,
+        Err(e) => {
+            checks.push(
+                Check::new(Status::Fail, "database", e.to_string()).fix(format!(
+                    "start the gateway to migrate it; for an encrypted database check {}",
+                    t_koma_db::DB_KEY_ENV
+                )),
+            );
+            return;
+        }
+    };
+    checks.push(match db.schema_version().await {
+        Ok(applied) if applied > expected => Check::new(
+            Status::Warn,
+            "database",
+            format!("schema {applied} is newer than this build ({expected})"),
+        )
+        .fix("update t-koma-cli to match the gateway"),
+        Ok(applied) => Check::new(Status::Ok, "database", format!("schema {applied}")),
+        Err(e) => Check::new(Status::Fail, "database", e.to_string()),
+    });
+    db.close().await;
+}
+
+fn check_disk(checks: &mut Vec<Check>, settings: Option<&Settings>) {
+    let Some(data_dir) = t_koma_db::KomaDbPool::db_path()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    else {
+        return;
+    };
+    if !data_dir.exists() {
+        checks.push(Check::new(
+            Status::Skip,
+            "disk",
+            format!("{} does not exist yet", data_dir.display()),
+        ));
+        return;
+    }
+    let mut entries: Vec<(String, u64)> = std::fs::read_dir(&data_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                dir_size(&entry.path()),
+            )
+        })
+        .collect();
+    entries.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    let total: u64 = entries.iter().map(|(_, size)| size).sum();
+    let largest: Vec<String> = entries
+        .iter()
+        .take(3)
+        .map(|(name, size)| format!("{name} {}", format_bytes(*size)))
+        .collect();
+    let mut check = Check::new(
+        Status::Ok,
+        "disk",
+        format!(
+            "{} in {} ({})",
+            format_bytes(total),
+            data_dir.display(),
+            largest.join(", ")
+        ),
+    );
+    let db_size = dir_size(&data_dir.join("koma.sqlite3"));
+    if db_size > LARGE_DB_BYTES
+        && settings.is_some_and(|settings| settings.session_archive.max_idle_days == 0)
+    {
+        check.status = Status::Warn;
+        check =
+            check.fix("set [session_archive] max_idle_days to move idle sessions to cold storage");
+    }
+    checks.push(check);
+}
+
+/// `/health`, then the knowledge index stats over the WebSocket.
+async fn check_gateway(checks: &mut Vec<Check>, settings: &Settings, client: &reqwest::Client) {
+    let ws_url = settings.ws_url();
+    let health_url = match api_base_url(&ws_url).and_then(|base| {
+        base.join("health")
+            .map_err(|e| format!("Invalid gateway URL: {e}"))
+    }) {
+        Ok(url) => url,
+        Err(e) => {
+            checks.push(Check::new(Status::Fail, "gateway", e).fix("check [gateway] ws_url"));
+            return;
+        }
+    };
+    let health = match client.get(health_url.clone()).send().await {
+        Ok(response) if response.status().is_success() => response
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_or_default(),
+        Ok(response) => {
+            checks.push(Check::new(
+                Status::Fail,
+                "gateway",
+                format!("{health_url} returned {}", response.status()),
+            ));
+            return;
+        }
+        Err(_) => {
+            checks.push(
+                Check::new(
+                    Status::Warn,
+                    "gateway",
+                    format!("not reachable at {health_url}"),
+                )
+                .fix("start it with `t-koma-gateway`"),
+            );
+            checks.push(Check::new(Status::Skip, "knowledge", "needs the gateway"));
+            return;
+        }
+    };
+    checks.push(Check::new(
+        Status::Ok,
+        "gateway",
+        format!(
+            "version {} at {}",
+            health["version"].as_str().unwrap_or("?"),
+            ws_url
+        ),
+    ));
+    for model in health["models"].as_array().into_iter().flatten() {
+        let state = model["state"].as_str().unwrap_or("closed");
+        if state != "closed" {
+            checks.push(
+                Check::new(
+                    Status::Warn,
+                    format!("circuit {}", model["alias"].as_str().unwrap_or("?")),
+                    format!(
+                        "{state} after {} failures",
+                        model["consecutive_failures"].as_u64().unwrap_or(0)
+                    ),
+                )
+                .fix("check the gateway log; requests go to the fallback models meanwhile"),
+            );
+        }
+    }
+
+    checks.push(match knowledge_stats(&ws_url).await {
+        Ok(stats) if stats.orphaned_chunks > 0 => Check::new(
+            Status::Warn,
+            "knowledge",
+            format!("{} chunks belong to no note", stats.orphaned_chunks),
+        )
+        .fix("stop the gateway and delete shared/index.sqlite3 in the data directory to rebuild the index"),
+        Ok(stats) if stats.missing_embeddings > 0 => Check::new(
+            Status::Warn,
+            "knowledge",
+            format!(
+                "{} of {} chunks have no {} embedding",
+                stats.missing_embeddings, stats.total_chunks, stats.embedding_model
+            ),
+        )
+        .fix("check that the embedding server in [tools.knowledge] is up; the gateway embeds missing chunks in the background"),
+        Ok(stats) => Check::new(
+            Status::Ok,
+            "knowledge",
+            format!(
+                "{} notes, {} chunks, all embedded",
+                stats.total_notes, stats.total_chunks
+            ),
+        ),
+        Err(e) => Check::new(Status::Fail, "knowledge", e),
+    });
+}
+
+async fn knowledge_stats(ws_url: &str) -> Result<t_koma_core::KnowledgeIndexStats, String> {
+    let (tx, mut responses) = WsClient::connect(ws_url)
+        .await
+        .map_err(|e| format!("WebSocket: {e}"))?;
+    tx.send(WsMessage::GetKnowledgeStats)
+        .map_err(|_| "WebSocket closed".to_string())?;
+    let wait = async {
+        while let Some(response) = responses.next().await {
+            match response {
+                WsResponse::KnowledgeStats { stats } => return Ok(stats),
+                WsResponse::Response { message, .. }
+                    if message.kind == t_koma_core::GatewayMessageKind::Error =>
+                {
+                    return Err(message.text_fallback);
+                }
+                _ => {}
+            }
+        }
+        Err("WebSocket closed".to_string())
+    };
+    tokio::time::timeout(Duration::from_secs(15), wait)
+        .await
+        .map_err(|_| "no knowledge stats within 15s".to_string())?
+}
+
+pub async fn run_doctor() -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+    let mut checks = Vec::new();
+
+    let settings = check_config(&mut checks);
+    if let Some(settings) = &settings {
+        check_providers(&mut checks, settings, &client).await;
+    }
+    check_database(&mut checks).await;
+    check_disk(&mut checks, settings.as_ref());
+    if let Some(settings) = &settings {
+        check_gateway(&mut checks, settings, &client).await;
+    }
+
+    for check in &checks {
+        println!("{}", check.render());
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == Status::Fail)
+        .count();
+    let warned = checks
+        .iter()
+        .filter(|check| check.status == Status::Warn)
+        .count();
+    if failed > 0 {
+        return Err(format!("{failed} check(s) failed, {warned} warning(s)").into());
+    }
+    println!("\nAll checks passed ({warned} warning(s)).");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_format() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+
+        let check = Check::new(Status::Warn, "gateway", "not reachable").fix("start it");
+        assert_eq!(
+            check.render(),
+            "WARN  gateway            not reachable\n      fix: start it"
+        );
+        assert_eq!(
+            Check::new(Status::Ok, "disk", "1 B").render(),
+            "OK    disk               1 B"
+        );
+    }
+}
@@ -1,87 +1,432 @@
+//! `t-koma-cli gateway`: start, stop and inspect a background gateway.
+//!
+//! `start` runs `t-koma-gateway` detached, with stdout and stderr appended to
+//! `gateway.log` and its PID in `gateway.pid`, both in the data directory.
+//! `stop` sends SIGTERM, so the gateway drains like on Ctrl-C. A gateway
+//! started another way (a service manager, a terminal) is reported by
+//! `status` through `/health` but has no PID file to stop. `unit` prints a
+//! systemd user unit or a launchd agent for running it as a service instead.
+
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::Duration;
+
 use tokio::process::Command;
 use tokio::time::sleep;
-use tracing::{error, info, warn};
 
-/// Check if the gateway is already running at the given URL
-async fn is_gateway_running(ws_url: &str) -> bool {
-    // Extract host and port from ws:// URL
-    let http_url = ws_url.replace("ws://", "http://").replace("/ws", "/health");
+use crate::commands::api_base_url;
+
+const GATEWAY_BIN: &str = "t-koma-gateway";
+const PID_FILE: &str = "gateway.pid";
+const LOG_FILE: &str = "gateway.log";
+const LAUNCHD_LABEL: &str = "dev.t-koma.gateway";
+
+/// A `gateway` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GatewayControl {
+    Start,
+    Stop,
+    Status,
+    Restart,
+    /// Print (or with `install`, write) a service definition.
+    Unit {
+        kind: UnitKind,
+        install: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitKind {
+    Systemd,
+    Launchd,
+}
+
+impl UnitKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "systemd" => Some(Self::Systemd),
+            "launchd" => Some(Self::Launchd),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can occur when managing the gateway
+#[derive(Debug, thiserror::Error)]
+pub enum GatewaySpawnError {
+    #[error("Could not find the {GATEWAY_BIN} binary next to t-koma-cli or in PATH")]
+    BinaryNotFound,
+    #[error("Failed to spawn gateway process: {0}")]
+    SpawnFailed(std::io::Error),
+    #[error("Gateway did not become healthy, see {0}")]
+    StartupTimeout(PathBuf),
+    #[error("Gateway (PID {0}) did not stop within timeout")]
+    StopTimeout(u32),
+    #[error("Failed to signal PID {0}")]
+    SignalFailed(u32),
+    #[error(
+        "A gateway is answering at {0} but was not started by t-koma-cli; stop it where it was started"
+    )]
+    NotManaged(String),
+    #[error("{0}")]
+    Setup(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// PID and log file locations, next to the database.
+struct Paths {
+    pid: PathBuf,
+    log: PathBuf,
+}
+
+impl Paths {
+    fn resolve() -> Result<Self, GatewaySpawnError> {
+        let db_path = t_koma_db::KomaDbPool::db_path()
+            .map_err(|e| GatewaySpawnError::Setup(e.to_string()))?;
+        let dir = db_path
+            .parent()
+            .ok_or_else(|| GatewaySpawnError::Setup("Invalid data directory".to_string()))?;
+        Ok(Self {
+            pid: dir.join(PID_FILE),
+            log: dir.join(LOG_FILE),
+        })
+    }
+}
 
-    match reqwest::get(&http_url).await {
+/// Check if the gateway is answering `/health` for the given WebSocket URL
+async fn is_gateway_running(ws_url: &str) -> bool {
+    let Ok(health) = api_base_url(ws_url).and_then(|base| {
+        base.join("health")
+            .map_err(|e| format!("Invalid gateway URL: {e}"))
+    }) else {
+        return false;
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .unwrap_or_default();
+    match client.get(health).send().await {
         Ok(response) => response.status().is_success(),
         Err(_) => false,
     }
 }
 
-/// Spawn the gateway as a child process if not already running
-pub async fn ensure_gateway_running(
-    ws_url: &str,
-) -> Result<Option<tokio::process::Child>, GatewaySpawnError> {
-    // First check if gateway is already running
-    if is_gateway_running(ws_url).await {
-        info!("Gateway is already running at {}", ws_url);
-        return Ok(None);
+fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` is a live process.
+async fn is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
+    #[cfg(not(unix))]
+    {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+            .output()
+            .await
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
     }
+}
 
-    info!("Gateway not running, attempting to start...");
+async fn terminate(pid: u32) -> Result<(), GatewaySpawnError> {
+    #[cfg(unix)]
+    let status = Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status()
+        .await?;
+    #[cfg(not(unix))]
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()
+        .await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(GatewaySpawnError::SignalFailed(pid))
+    }
+}
+
+/// The gateway binary: next to this executable, then in PATH.
+fn gateway_binary() -> Result<PathBuf, GatewaySpawnError> {
+    if let Ok(exe) = std::env::current_exe() {
+        let sibling = exe.with_file_name(format!("{GATEWAY_BIN}{}", std::env::consts::EXE_SUFFIX));
+        if sibling.is_file() {
+            return Ok(sibling);
+        }
+    }
+    std::env::var_os("PATH")
+        .into_iter()
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .map(|dir| dir.join(format!("{GATEWAY_BIN}{}", std::env::consts::EXE_SUFFIX)))
+        .find(|path| path.is_file())
+        .ok_or(GatewaySpawnError::BinaryNotFound)
+}
 
-    // Try to spawn the gateway
-    let child = spawn_gateway().await?;
+/// Spawn the gateway detached from this terminal and wait until it answers.
+async fn start(ws_url: &str, paths: &Paths) -> Result<(), GatewaySpawnError> {
+    if is_gateway_running(ws_url).await {
+        println!("Gateway is already running at {ws_url}.");
+        return Ok(());
+    }
+
+    let binary = gateway_binary()?;
+    if let Some(parent) = paths.log.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&paths.log)?;
+    let mut command = Command::new(&binary);
+    command
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .env("NO_COLOR", "1");
+    // Own process group, so Ctrl-C in this terminal does not reach it.
+    #[cfg(unix)]
+    command.process_group(0);
+    let child = command.spawn().map_err(GatewaySpawnError::SpawnFailed)?;
+    let pid = child
+        .id()
+        .ok_or_else(|| GatewaySpawnError::Setup("Gateway exited immediately".to_string()))?;
+    std::fs::write(&paths.pid, pid.to_string())?;
 
-    // Wait for gateway to be ready (up to 10 seconds)
-    for i in 0..50 {
+    for _ in 0..50 {
         sleep(Duration::from_millis(200)).await;
         if is_gateway_running(ws_url).await {
-            info!("Gateway started successfully after {} attempts", i + 1);
-            return Ok(Some(child));
+            println!(
+                "Gateway started (PID {pid}) at {ws_url}, logging to {}.",
+                paths.log.display()
+            );
+            return Ok(());
+        }
+        if !is_alive(pid).await {
+            let _ = std::fs::remove_file(&paths.pid);
+            break;
         }
     }
+    Err(GatewaySpawnError::StartupTimeout(paths.log.clone()))
+}
+
+async fn stop(ws_url: &str, paths: &Paths) -> Result<(), GatewaySpawnError> {
+    let pid = match read_pid(&paths.pid) {
+        Some(pid) if is_alive(pid).await => pid,
+        stale => {
+            if stale.is_some() {
+                let _ = std::fs::remove_file(&paths.pid);
+            }
+            if is_gateway_running(ws_url).await {
+                return Err(GatewaySpawnError::NotManaged(ws_url.to_string()));
+            }
+            println!("Gateway is not running.");
+            return Ok(());
+        }
+    };
 
-    // If we get here, gateway didn't start properly
-    error!("Gateway failed to start within timeout");
-    Err(GatewaySpawnError::StartupTimeout)
+    terminate(pid).await?;
+    // The gateway drains in-flight turns before exiting.
+    for _ in 0..150 {
+        if !is_alive(pid).await {
+            let _ = std::fs::remove_file(&paths.pid);
+            println!("Gateway (PID {pid}) stopped.");
+            return Ok(());
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+    Err(GatewaySpawnError::StopTimeout(pid))
 }
 
-/// Spawn the gateway binary as a child process
-async fn spawn_gateway() -> Result<tokio::process::Child, GatewaySpawnError> {
-    // Try to find the gateway binary
-    let possible_paths = [
-        "./target/release/t-koma-gateway".to_string(),
-        "./target/debug/t-koma-gateway".to_string(),
-        "t-koma-gateway".to_string(), // Assume it's in PATH
-    ];
+async fn status(ws_url: &str, paths: &Paths) {
+    let pid = match read_pid(&paths.pid) {
+        Some(pid) if is_alive(pid).await => Some(pid),
+        _ => None,
+    };
+    let healthy = is_gateway_running(ws_url).await;
+    match (pid, healthy) {
+        (Some(pid), true) => println!("Gateway is running (PID {pid}) at {ws_url}."),
+        (Some(pid), false) => {
+            println!("Gateway process {pid} is alive but not answering at {ws_url}.")
+        }
+        (None, true) => println!("Gateway is running at {ws_url} (not started by t-koma-cli)."),
+        (None, false) => println!("Gateway is not running."),
+    }
+    println!("Log: {}", paths.log.display());
+}
 
-    for path in &possible_paths {
-        info!("Trying to spawn gateway from: {}", path);
+/// `T_KOMA_*` location overrides the service must inherit.
+fn service_env() -> Vec<(&'static str, String)> {
+    ["T_KOMA_CONFIG_DIR", "T_KOMA_DATA_DIR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok().map(|value| (name, value)))
+        .collect()
+}
 
-        let result = Command::new(path)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .kill_on_drop(true)
-            .spawn();
+/// A systemd user unit running `binary`.
+fn systemd_unit(binary: &Path, env: &[(&str, String)]) -> String {
+    let mut unit = format!(
+        "[Unit]\n\
+         Description=t-koma gateway\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         KillSignal=SIGTERM\n\
+         TimeoutStopSec=60\n",
+        binary.display()
+    );
+    for (name, value) in env {
+        unit.push_str(&format!("Environment=\"{name}={value}\"\n"));
+    }
+    unit.push_str("\n[Install]\nWantedBy=default.target\n");
+    unit
+}
 
-        match result {
-            Ok(child) => {
-                info!("Spawned gateway process (PID: {:?})", child.id());
-                return Ok(child);
-            }
-            Err(e) => {
-                warn!("Failed to spawn from {}: {}", path, e);
-            }
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A launchd agent running `binary`, logging to `log`.
+fn launchd_plist(binary: &Path, log: &Path, env: &[(&str, String)]) -> String {
+    let log = xml_escape(&log.display().to_string());
+    let mut plist = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20 <key>Label</key>\n\
+         \x20 <string>{LAUNCHD_LABEL}</string>\n\
+         \x20 <key>ProgramArguments</key>\n\
+         \x20 <array>\n\
+         \x20   <string>{}</string>\n\
+         \x20 </array>\n\
+         \x20 <key>RunAtLoad</key>\n\
+         \x20 <true/>\n\
+         \x20 <key>KeepAlive</key>\n\
+         \x20 <dict>\n\
+         \x20   <key>SuccessfulExit</key>\n\
+         \x20   <false/>\n\
+         \x20 </dict>\n\
+         \x20 <key>StandardOutPath</key>\n\
+         \x20 <string>{log}</string>\n\
+         \x20 <key>StandardErrorPath</key>\n\
+         \x20 <string>{log}</string>\n",
+        xml_escape(&binary.display().to_string())
+    );
+    if !env.is_empty() {
+        plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+        for (name, value) in env {
+            plist.push_str(&format!(
+                "    <key>{name}</key>\n    <string>{}</string>\n",
+                xml_escape(value)
+            ));
         }
+        plist.push_str("  </dict>\n");
     }
+    plist.push_str("</dict>\n</plist>\n");
+    plist
+}
 
-    error!("Could not spawn gateway from any known location");
-    Err(GatewaySpawnError::SpawnFailed)
+/// Where `install` writes the service definition, and how to enable it.
+fn unit_install_path(kind: UnitKind) -> Result<(PathBuf, String), GatewaySpawnError> {
+    let home = home_dir()?;
+    Ok(match kind {
+        UnitKind::Systemd => (
+            home.join(".config/systemd/user/t-koma-gateway.service"),
+            "systemctl --user daemon-reload && systemctl --user enable --now t-koma-gateway"
+                .to_string(),
+        ),
+        UnitKind::Launchd => {
+            let path = home.join(format!("Library/LaunchAgents/{LAUNCHD_LABEL}.plist"));
+            let enable = format!("launchctl load -w {}", path.display());
+            (path, enable)
+        }
+    })
 }
 
-/// Errors that can occur when spawning the gateway
-#[derive(Debug, thiserror::Error)]
-pub enum GatewaySpawnError {
-    #[error("Failed to spawn gateway process")]
-    SpawnFailed,
-    #[error("Gateway failed to start within timeout")]
-    StartupTimeout,
+fn home_dir() -> Result<PathBuf, GatewaySpawnError> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| GatewaySpawnError::Setup("HOME is not set".to_string()))
+}
+
+fn unit(kind: UnitKind, install: bool, paths: &Paths) -> Result<(), GatewaySpawnError> {
+    let binary = gateway_binary()?;
+    let env = service_env();
+    let content = match kind {
+        UnitKind::Systemd => systemd_unit(&binary, &env),
+        UnitKind::Launchd => launchd_plist(&binary, &paths.log, &env),
+    };
+    if !install {
+        print!("{content}");
+        return Ok(());
+    }
+    let (path, enable) = unit_install_path(kind)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content)?;
+    println!("Wrote {}. Enable it with:\n  {enable}", path.display());
+    Ok(())
+}
+
+pub async fn run_gateway_control(
+    command: GatewayControl,
+) -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let settings = t_koma_core::Settings::load()?;
+    let ws_url = settings.ws_url();
+    let paths = Paths::resolve()?;
+    match command {
+        GatewayControl::Start => start(&ws_url, &paths).await?,
+        GatewayControl::Stop => stop(&ws_url, &paths).await?,
+        GatewayControl::Status => status(&ws_url, &paths).await,
+        GatewayControl::Restart => {
+            stop(&ws_url, &paths).await?;
+            start(&ws_url, &paths).await?;
+        }
+        GatewayControl::Unit { kind, install } => unit(kind, install, &paths)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_definitions() {
+        let env = [("T_KOMA_DATA_DIR", "/srv/koma & co".to_string())];
+        let unit = systemd_unit(Path::new("/usr/bin/t-koma-gateway"), &env);
+        assert!(unit.contains("ExecStart=/usr/bin/t-koma-gateway\n"));
+        assert!(unit.contains("Environment=\"T_KOMA_DATA_DIR=/srv/koma & co\"\n"));
+        assert!(unit.ends_with("WantedBy=default.target\n"));
+
+        let plist = launchd_plist(
+            Path::new("/opt/t-koma-gateway"),
+            Path::new("/tmp/gateway.log"),
+            &env,
+        );
+        assert!(plist.contains("<string>/opt/t-koma-gateway</string>"));
+        assert!(
+            plist.contains("<key>StandardErrorPath</key>\n  <string>/tmp/gateway.log</string>")
+        );
+        assert!(plist.contains("<string>/srv/koma &amp; co</string>"));
+        assert!(plist.ends_with("</dict>\n</plist>\n"));
+    }
 }
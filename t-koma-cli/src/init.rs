@@ -0,0 +1,415 @@
+//! `t-koma-cli init`: first-run setup on the terminal.
+//!
+//! Asks for the same choices as the TUI onboarding ([`OnboardingState`]) and
+//! checks each answer against the live service before moving on: provider
+//! keys by listing models, the Discord token by fetching the bot user and
+//! Ollama by listing its local models. Keys are written to the `.env` next to
+//! `config.toml`; the result is then loaded the way the gateway loads it, so
+//! configuration errors show up here instead of at gateway start.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use t_koma_core::ProviderType;
+
+use crate::tui::app::onboarding::{EmbeddingChoice, OnboardingState};
+
+const OPENAI_URL: &str = "https://api.openai.com/v1";
+
+/// Print `label` and read one trimmed line; `default` is used for blank input.
+fn prompt(label: &str, default: Option<&str>) -> io::Result<String> {
+    match default {
+        Some(default) if !default.is_empty() => print!("{label} [{default}]: "),
+        _ => print!("{label}: "),
+    }
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Setup aborted",
+        ));
+    }
+    let input = input.trim();
+    Ok(match default {
+        Some(default) if input.is_empty() => default.to_string(),
+        _ => input.to_string(),
+    })
+}
+
+/// Ask until the answer is not blank.
+fn prompt_required(label: &str, default: Option<&str>) -> io::Result<String> {
+    loop {
+        let value = prompt(label, default)?;
+        if !value.is_empty() {
+            return Ok(value);
+        }
+    }
+}
+
+fn confirm(label: &str, default: bool) -> io::Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    loop {
+        match prompt(&format!("{label} [{hint}]"), None)?
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// Numbered menu; returns the chosen index.
+fn choose(label: &str, choices: &[&str]) -> io::Result<usize> {
+    println!("{label}");
+    for (idx, choice) in choices.iter().enumerate() {
+        println!("  {}) {}", idx + 1, choice);
+    }
+    loop {
+        if let Ok(choice) = prompt("Choice", Some("1"))?.parse::<usize>()
+            && (1..=choices.len()).contains(&choice)
+        {
+            return Ok(choice - 1);
+        }
+    }
+}
+
+/// Model listing endpoint and auth headers for `provider`; `None` when the
+/// key cannot be checked this way.
+fn models_request(
+    provider: ProviderType,
+    key: &str,
+    base_url: Option<&str>,
+) -> Option<(String, Vec<(&'static str, String)>)> {
+    let bearer = vec![("Authorization", format!("Bearer {key}"))];
+    match provider {
+        ProviderType::Anthropic => Some((
+            "https://api.anthropic.com/v1/models?limit=1000".to_string(),
+            vec![
+                ("x-api-key", key.to_string()),
+                ("anthropic-version", "2023-06-01".to_string()),
+            ],
+        )),
+        // `/models` is public, `/key` needs a valid key.
+        ProviderType::OpenRouter => Some(("https://openrouter.ai/api/v1/key".to_string(), bearer)),
+        ProviderType::Gemini => Some((
+            "https://generativelanguage.googleapis.com/v1beta/models?pageSize=1000".to_string(),
+            vec![("x-goog-api-key", key.to_string())],
+        )),
+        ProviderType::OpenAiCompatible => Some((
+            format!(
+                "{}/models",
+                base_url.unwrap_or(OPENAI_URL).trim_end_matches('/')
+            ),
+            bearer,
+        )),
+        ProviderType::KimiCode | ProviderType::AzureOpenAi => None,
+    }
+}
+
+/// Model ids from an OpenAI/Anthropic (`data[].id`), Gemini (`models[].name`)
+/// or Ollama (`models[].name`) listing.
+fn parse_model_ids(body: &serde_json::Value) -> Vec<String> {
+    let data = body["data"].as_array().into_iter().flatten();
+    let models = body["models"].as_array().into_iter().flatten();
+    data.filter_map(|model| model["id"].as_str())
+        .chain(models.filter_map(|model| model["name"].as_str()))
+        .map(|id| id.trim_start_matches("models/").to_string())
+        .collect()
+}
+
+async fn get_json(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &[(&str, String)],
+) -> Result<serde_json::Value, String> {
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let response = request.send().await.map_err(|e| format!("{url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("{url} returned {status}"));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("{url}: invalid response: {e}"))
+}
+
+/// Check `key` against the provider and return its model ids (empty when
+/// the provider cannot list them).
+pub(crate) async fn check_provider_key(
+    client: &reqwest::Client,
+    provider: ProviderType,
+    key: &str,
+    base_url: Option<&str>,
+) -> Result<Option<Vec<String>>, String> {
+    let Some((url, headers)) = models_request(provider, key, base_url) else {
+        return Ok(None);
+    };
+    let body = get_json(client, &url, &headers).await?;
+    if provider == ProviderType::OpenRouter {
+        let models = get_json(client, "https://openrouter.ai/api/v1/models", &[]).await?;
+        return Ok(Some(parse_model_ids(&models)));
+    }
+    Ok(Some(parse_model_ids(&body)))
+}
+
+/// Ask for a key until it passes `check`, or the OPERATOR keeps it anyway.
+/// `None` keeps the value already in `env_var`.
+async fn ask_key<F, Fut>(env_var: &str, check: F) -> io::Result<Option<String>>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String, String>>,
+{
+    if let Ok(existing) = std::env::var(env_var)
+        && !existing.is_empty()
+        && confirm(&format!("{env_var} is already set. Use it?"), true)?
+    {
+        match check(existing).await {
+            Ok(message) => println!("  OK: {message}"),
+            Err(error) => println!("  Check failed: {error}"),
+        }
+        return Ok(None);
+    }
+    loop {
+        let key = prompt_required(&format!("{env_var} (input is shown)"), None)?;
+        match check(key.clone()).await {
+            Ok(message) => {
+                println!("  OK: {message}");
+                return Ok(Some(key));
+            }
+            Err(error) => {
+                println!("  Check failed: {error}");
+                if confirm("Keep this value anyway?", false)? {
+                    return Ok(Some(key));
+                }
+            }
+        }
+    }
+}
+
+pub async fn run_init() -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let config_path = t_koma_core::Settings::config_path()?;
+    let mut settings = t_koma_core::Settings::load()?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()?;
+    let mut ob = OnboardingState::default();
+
+    println!("t-koma setup. Settings go to {}.", config_path.display());
+    if !settings.models.is_empty() {
+        println!("Existing settings are kept; the model set up here becomes the default model.\n");
+    }
+
+    // Provider and key
+    let labels: Vec<&str> = OnboardingState::provider_choices()
+        .iter()
+        .map(|(label, _)| *label)
+        .collect();
+    let provider = OnboardingState::provider_choices()[choose("Model provider:", &labels)?].1;
+    ob.provider = Some(provider);
+    match provider {
+        ProviderType::OpenAiCompatible => {
+            ob.base_url = Some(prompt_required("Base URL", Some(OPENAI_URL))?);
+        }
+        ProviderType::AzureOpenAi => {
+            ob.base_url = Some(prompt_required(
+                "Endpoint (https://<resource>.openai.azure.com)",
+                None,
+            )?);
+        }
+        _ => {}
+    }
+
+    println!("\n{}", OnboardingState::api_key_instructions(provider));
+    let env_var = OnboardingState::env_var_for_provider(provider);
+    let base_url = ob.base_url.clone();
+    let models = std::sync::Mutex::new(Vec::new());
+    ob.api_key = ask_key(env_var, |key| {
+        let (client, base_url, models) = (&client, base_url.clone(), &models);
+        async move {
+            match check_provider_key(client, provider, &key, base_url.as_deref()).await? {
+                Some(ids) => {
+                    let message = format!("key accepted, {} models available", ids.len());
+                    *models.lock().unwrap_or_else(|e| e.into_inner()) = ids;
+                    Ok(message)
+                }
+                None => Ok(format!("{provider} keys cannot be checked here")),
+            }
+        }
+    })
+    .await?;
+    let models = models.into_inner().unwrap_or_else(|e| e.into_inner());
+
+    // Default model
+    let (default_alias, default_model) = OnboardingState::default_model_for_provider(provider);
+    println!();
+    loop {
+        let model = prompt_required("Model id", Some(default_model))?;
+        if models.is_empty() || models.contains(&model) {
+            ob.model_id = model;
+            break;
+        }
+        let similar: Vec<&String> = models
+            .iter()
+            .filter(|id| id.contains(model.as_str()))
+            .take(10)
+            .collect();
+        println!("  {model} is not in the provider's model list.");
+        if !similar.is_empty() {
+            println!(
+                "  Similar: {}",
+                similar
+                    .iter()
+                    .map(|id| id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        if confirm("Use it anyway?", false)? {
+            ob.model_id = model;
+            break;
+        }
+    }
+    ob.model_alias = prompt_required("Alias for this model", Some(default_alias))?;
+
+    // Discord
+    println!();
+    ob.discord_enabled = confirm("Enable the Discord bot?", false)?;
+    if ob.discord_enabled {
+        ob.discord_token = ask_key("DISCORD_BOT_TOKEN", |token| {
+            let client = &client;
+            async move {
+                let user = get_json(
+                    client,
+                    "https://discord.com/api/v10/users/@me",
+                    &[("Authorization", format!("Bot {token}"))],
+                )
+                .await?;
+                Ok(format!(
+                    "bot user {}",
+                    user["username"].as_str().unwrap_or("?")
+                ))
+            }
+        })
+        .await?;
+    }
+
+    // Knowledge
+    println!();
+    let labels: Vec<&str> = OnboardingState::embedding_choices()
+        .iter()
+        .map(|(label, _)| *label)
+        .collect();
+    ob.embedding_provider =
+        OnboardingState::embedding_choices()[choose("Knowledge embeddings:", &labels)?].1;
+    let knowledge = t_koma_core::config::KnowledgeSettings::from(&settings.tools.knowledge);
+    match ob.embedding_provider {
+        EmbeddingChoice::Ollama => loop {
+            let url = prompt_required("Ollama URL", Some(&knowledge.embedding_url))?;
+            let model = prompt_required("Embedding model", Some(&knowledge.embedding_model))?;
+            let check = get_json(
+                &client,
+                &format!("{}/api/tags", url.trim_end_matches('/')),
+                &[],
+            )
+            .await
+            .map(|body| parse_model_ids(&body));
+            let problem = match check {
+                Ok(ids)
+                    if ids
+                        .iter()
+                        .any(|id| *id == model || *id == format!("{model}:latest")) =>
+                {
+                    None
+                }
+                Ok(_) => Some(format!(
+                    "{model} is not pulled yet (run `ollama pull {model}`)"
+                )),
+                Err(error) => Some(error),
+            };
+            let keep = match &problem {
+                None => {
+                    println!("  OK: Ollama has {model}");
+                    true
+                }
+                Some(problem) => {
+                    println!("  Check failed: {problem}");
+                    confirm("Keep these settings anyway?", false)?
+                }
+            };
+            if keep {
+                ob.embedding_url = Some(url);
+                ob.embedding_model = Some(model);
+                break;
+            }
+        },
+        EmbeddingChoice::OpenAi => {
+            ob.openai_embedding_key = ask_key("OPENAI_API_KEY", |key| {
+                let client = &client;
+                async move {
+                    check_provider_key(client, ProviderType::OpenAiCompatible, &key, None).await?;
+                    Ok("key accepted".to_string())
+                }
+            })
+            .await?;
+        }
+        EmbeddingChoice::Skip => {}
+    }
+    let db_path = prompt(
+        "Knowledge index path (blank for the default data directory)",
+        settings
+            .tools
+            .knowledge
+            .knowledge_db_path_override
+            .as_deref(),
+    )?;
+    if !db_path.is_empty() {
+        ob.knowledge_db_path = Some(db_path);
+    }
+
+    println!();
+    if !confirm("Write the configuration?", true)? {
+        println!("Aborted, nothing written.");
+        return Ok(());
+    }
+    println!("{}", ob.apply(&mut settings)?);
+
+    // Load it back as the gateway will.
+    t_koma_core::load_dotenv();
+    match t_koma_core::Config::load() {
+        Ok(_) => println!("Configuration is valid. Start the gateway with `t-koma-gateway`."),
+        Err(error) => println!("The gateway will not start yet: {error}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models_request_and_ids() {
+        let (url, headers) = models_request(ProviderType::Anthropic, "sk", None).unwrap();
+        assert!(url.starts_with("https://api.anthropic.com/v1/models"));
+        assert_eq!(headers[0], ("x-api-key", "sk".to_string()));
+        let (url, headers) =
+            models_request(ProviderType::OpenAiCompatible, "k", Some("http://host/v1/")).unwrap();
+        assert_eq!(url, "http://host/v1/models");
+        assert_eq!(headers, [("Authorization", "Bearer k".to_string())]);
+        assert!(models_request(ProviderType::KimiCode, "k", None).is_none());
+
+        let openai = serde_json::json!({"data": [{"id": "gpt-4o"}, {"id": "o3"}]});
+        assert_eq!(parse_model_ids(&openai), ["gpt-4o", "o3"]);
+        let gemini = serde_json::json!({"models": [{"name": "models/gemini-2.5-flash"}]});
+        assert_eq!(parse_model_ids(&gemini), ["gemini-2.5-flash"]);
+        assert!(parse_model_ids(&serde_json::json!({"error": "nope"})).is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+//! Offline local mode: `t-koma-cli local`.
+//!
+//! A line-based chat that talks straight to an `openai_compatible` model
+//! (typically Ollama) and opens the knowledge engine in-process, for machines
+//! without a gateway or network. Each message is answered with the most
+//! relevant knowledge entries in the system prompt. There are no tools, no
+//! stored sessions and no OPERATORS or interfaces.
+
+use std::io::Write;
+
+use serde_json::{Value, json};
+use t_koma_core::{ModelConfig, ProviderType, Settings};
+use t_koma_knowledge::{
+    KnowledgeEngine, KnowledgeSearchQuery, KnowledgeSearchResult, KnowledgeSettings,
+    OwnershipScope, models::SearchOptions,
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Used when an `openai_compatible` model has no `base_url`.
+const OLLAMA_BASE_URL: &str = "http://127.0.0.1:11434/v1";
+/// Knowledge entries added to the system prompt per message.
+const CONTEXT_RESULTS: usize = 4;
+
+const HELP: &str = "\
+/search <query>    Show knowledge entries matching <query>
+/knowledge on|off  Add matching knowledge to each message (default: on)
+/reset             Forget the conversation
+/exit              Quit (also Ctrl+D)";
+
+/// Options of `t-koma-cli local`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalOptions {
+    /// Whose private notes and diary are searched; shared knowledge only when
+    /// unset.
+    pub ghost: Option<String>,
+    /// Model alias; defaults to the first `openai_compatible` model.
+    pub model: Option<String>,
+    pub knowledge: bool,
+}
+
+pub async fn run_local(options: LocalOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::load()?;
+    let (alias, model) = resolve_model(&settings, options.model.as_deref())?;
+    let endpoint = chat_completions_url(model.base_url.as_deref().unwrap_or(OLLAMA_BASE_URL));
+    let api_key = model
+        .api_key_env
+        .as_deref()
+        .and_then(|name| std::env::var(name).ok());
+
+    let engine = if options.knowledge {
+        let knowledge = KnowledgeSettings::from(&settings.tools.knowledge);
+        match KnowledgeEngine::open(knowledge).await {
+            Ok(engine) => Some(engine),
+            Err(e) => {
+                eprintln!("Knowledge unavailable ({e}); chatting without it.");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    println!(
+        "T-KOMA local mode: {} ({}) at {}",
+        alias, model.model, endpoint
+    );
+    if !is_local_url(&endpoint) {
+        println!("Note: this endpoint is not on this machine or the local network.");
+    }
+    println!(
+        "No gateway: no tools, sessions are not saved, no OPERATORS or interfaces. \
+         Type /help for commands."
+    );
+
+    let http = reqwest::Client::new();
+    let mut auto_context = engine.is_some();
+    let mut history: Vec<Value> = Vec::new();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            return Ok(());
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(command) = line.strip_prefix('/') {
+            let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+            match (name, arg.trim()) {
+                ("exit" | "quit", _) => return Ok(()),
+                ("help", _) => println!("{HELP}"),
+                ("reset", _) => {
+                    history.clear();
+                    println!("Conversation cleared.");
+                }
+                ("knowledge", "on") if engine.is_some() => auto_context = true,
+                ("knowledge", "on") => println!("Knowledge is unavailable."),
+                ("knowledge", "off") => auto_context = false,
+                ("search", "") => println!("Usage: /search <query>"),
+                ("search", query) => match &engine {
+                    Some(engine) => {
+                        match search(engine, options.ghost.as_deref(), query, 10).await {
+                            Ok(result) => print_results(&result),
+                            Err(e) => println!("Search failed: {e}"),
+                        }
+                    }
+                    None => println!("Knowledge is unavailable."),
+                },
+                _ => println!("Unknown command. {HELP}"),
+            }
+            continue;
+        }
+
+        let context = match (&engine, auto_context) {
+            (Some(engine), true) => {
+                match search(engine, options.ghost.as_deref(), line, CONTEXT_RESULTS).await {
+                    Ok(result) => knowledge_context(&result),
+                    Err(e) => {
+                        eprintln!("(knowledge search failed: {e})");
+                        String::new()
+                    }
+                }
+            }
+            _ => String::new(),
+        };
+        let mut messages = vec![json!({
+            "role": "system",
+            "content": system_prompt(options.ghost.as_deref(), &context),
+        })];
+        messages.extend(history.iter().cloned());
+        messages.push(json!({"role": "user", "content": line}));
+
+        match stream_reply(&http, &endpoint, api_key.as_deref(), &model.model, messages).await {
+            Ok(reply) => {
+                history.push(json!({"role": "user", "content": line}));
+                history.push(json!({"role": "assistant", "content": reply}));
+            }
+            Err(e) => eprintln!("\nModel request failed: {e}"),
+        }
+    }
+}
+
+/// The requested alias, or the first `openai_compatible` model in
+/// `default_model`, then among all models. Other providers need the network,
+/// so they are refused.
+fn resolve_model<'a>(
+    settings: &'a Settings,
+    alias: Option<&str>,
+) -> Result<(String, &'a ModelConfig), String> {
+    if let Some(alias) = alias {
+        let model = settings
+            .models
+            .get(alias)
+            .ok_or_else(|| format!("Unknown model alias: {alias}"))?;
+        if model.provider != ProviderType::OpenAiCompatible {
+            return Err(format!(
+                "Local mode needs an openai_compatible model (e.g. Ollama); '{alias}' uses {}",
+                model.provider
+            ));
+        }
+        return Ok((alias.to_string(), model));
+    }
+    settings
+        .default_model
+        .iter()
+        .chain(settings.models.keys().map(String::as_str))
+        .find_map(|alias| {
+            let model = settings.models.get(alias)?;
+            (model.provider == ProviderType::OpenAiCompatible).then(|| (alias.to_string(), model))
+        })
+        .ok_or_else(|| {
+            "No openai_compatible model configured. Add one, e.g.\n\n\
+             [models.local]\nprovider = \"openai_compatible\"\nmodel = \"qwen3\"\n\
+             base_url = \"http://127.0.0.1:11434/v1\""
+                .to_string()
+        })
+}
+
+fn chat_completions_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/v1") {
+        format!("{base}/chat/completions")
+    } else {
+        format!("{base}/v1/chat/completions")
+    }
+}
+
+/// Whether `url` points at this machine or a private network.
+fn is_local_url(url: &str) -> bool {
+    let Some(host) = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+    else {
+        return false;
+    };
+    let host = host.trim_matches(['[', ']']);
+    if host == "localhost" || host.ends_with(".local") {
+        return true;
+    }
+    match host.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(std::net::IpAddr::V6(ip)) => ip.is_loopback(),
+        Err(_) => false,
+    }
+}
+
+async fn search(
+    engine: &KnowledgeEngine,
+    ghost: Option<&str>,
+    query: &str,
+    max_results: usize,
+) -> Result<KnowledgeSearchResult, t_koma_knowledge::KnowledgeError> {
+    let query = KnowledgeSearchQuery {
+        query: query.to_string(),
+        categories: None,
+        scope: if ghost.is_some() {
+            OwnershipScope::All
+        } else {
+            OwnershipScope::Shared
+        },
+        topic: None,
+        archetype: None,
+        options: SearchOptions {
+            max_results: Some(max_results),
+            ..SearchOptions::default()
+        },
+    };
+    engine.knowledge_search(ghost.unwrap_or(""), query).await
+}
+
+/// Search hits as `(title, snippet)`, notes and references first.
+fn result_entries(result: &KnowledgeSearchResult) -> Vec<(String, String)> {
+    let notes = result
+        .notes
+        .iter()
+        .chain(&result.references.results)
+        .map(|note| (note.summary.title.clone(), note.summary.snippet.clone()));
+    let topics = result
+        .topics
+        .iter()
+        .map(|topic| (format!("Topic: {}", topic.title), topic.snippet.clone()));
+    let diary = result
+        .diary
+        .iter()
+        .map(|entry| (format!("Diary {}", entry.date), entry.snippet.clone()));
+    notes.chain(topics).chain(diary).collect()
+}
+
+fn print_results(result: &KnowledgeSearchResult) {
+    let entries = result_entries(result);
+    if entries.is_empty() {
+        println!("No matches.");
+    }
+    for (title, snippet) in entries {
+        println!("• {title}\n  {}", snippet.replace('\n', " "));
+    }
+}
+
+/// Search hits formatted for the system prompt; empty when nothing matched.
+fn knowledge_context(result: &KnowledgeSearchResult) -> String {
+    result_entries(result)
+        .into_iter()
+        .map(|(title, snippet)| format!("## {title}\n{}", snippet.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn system_prompt(ghost: Option<&str>, context: &str) -> String {
+    let mut prompt = match ghost {
+        Some(ghost) => format!("You are {ghost}, a T-KOMA GHOST"),
+        None => "You are a T-KOMA assistant".to_string(),
+    };
+    prompt.push_str(
+        ", running offline in local mode. You have no tools; answer from the \
+         conversation and the knowledge below.",
+    );
+    if !context.is_empty() {
+        prompt.push_str("\n\n# Relevant knowledge\n\n");
+        prompt.push_str(context);
+    }
+    prompt
+}
+
+/// Stream a chat completion to stdout and return the full reply.
+async fn stream_reply(
+    http: &reqwest::Client,
+    endpoint: &str,
+    api_key: Option<&str>,
+    model: &str,
+    messages: Vec<Value>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut request = http.post(endpoint).json(&json!({
+        "model": model,
+        "messages": messages,
+        "stream": true,
+    }));
+    if let Some(api_key) = api_key {
+        request = request.bearer_auth(api_key);
+    }
+    let mut response = request.send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{status}: {}", body.trim()).into());
+    }
+
+    let mut reply = String::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut stdout = std::io::stdout();
+    'stream: while let Some(chunk) = response.chunk().await? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            match parse_stream_line(&String::from_utf8_lossy(&line)) {
+                StreamLine::Delta(text) => {
+                    print!("{text}");
+                    stdout.flush()?;
+                    reply.push_str(&text);
+                }
+                StreamLine::Done => break 'stream,
+                StreamLine::Error(message) => return Err(message.into()),
+                StreamLine::Other => {}
+            }
+        }
+    }
+    println!();
+    Ok(reply)
+}
+
+/// One line of an OpenAI-style server-sent event stream.
+#[derive(Debug, PartialEq, Eq)]
+enum StreamLine {
+    Delta(String),
+    Done,
+    Error(String),
+    Other,
+}
+
+fn parse_stream_line(line: &str) -> StreamLine {
+    let Some(data) = line.trim().strip_prefix("data:") else {
+        return StreamLine::Other;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return StreamLine::Done;
+    }
+    let Ok(event) = serde_json::from_str::<Value>(data) else {
+        return StreamLine::Other;
+    };
+    if let Some(error) = event.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return StreamLine::Error(message);
+    }
+    match event
+        .pointer("/choices/0/delta/content")
+        .and_then(Value::as_str)
+    {
+        Some(text) if !text.is_empty() => StreamLine::Delta(text.to_string()),
+        _ => StreamLine::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream_line() {
+        assert_eq!(
+            parse_stream_line(r#"data: {"choices":[{"delta":{"content":"Hi"}}]}"#),
+            StreamLine::Delta("Hi".to_string())
+        );
+        assert_eq!(
+            parse_stream_line(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#),
+            StreamLine::Other
+        );
+        assert_eq!(parse_stream_line("data: [DONE]\n"), StreamLine::Done);
+        assert_eq!(
+            parse_stream_line(r#"data: {"error":{"message":"model not found"}}"#),
+            StreamLine::Error("model not found".to_string())
+        );
+        assert_eq!(parse_stream_line(": keep-alive"), StreamLine::Other);
+    }
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://127.0.0.1:11434/v1/chat/completions"));
+        assert!(is_local_url("http://localhost:8080/v1"));
+        assert!(is_local_url("http://192.168.1.20:11434/v1"));
+        assert!(is_local_url("http://[::1]:11434/v1"));
+        assert!(!is_local_url("https://openrouter.ai/api/v1"));
+    }
+
+    #[test]
+    fn test_resolve_model_prefers_openai_compatible() {
+        let settings = Settings::from_toml(
+            r#"
+default_model = ["cloud", "local"]
+
+[models.cloud]
+provider = "anthropic"
+model = "claude"
+
+[models.local]
+provider = "openai_compatible"
+model = "qwen3"
+base_url = "http://127.0.0.1:11434/v1"
+"#,
+        )
+        .unwrap();
+        let (alias, model) = resolve_model(&settings, None).unwrap();
+        assert_eq!((alias.as_str(), model.model.as_str()), ("local", "qwen3"));
+        assert!(resolve_model(&settings, Some("cloud")).is_err());
+        assert_eq!(
+            chat_completions_url(model.base_url.as_deref().unwrap()),
+            "http://127.0.0.1:11434/v1/chat/completions"
+        );
+    }
+}
@@ -1,27 +1,47 @@
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
-use cron::Schedule;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::prelude::*;
 use tracing::{error, info, warn};
 
 mod client;
+mod clipboard;
+mod commands;
+mod doctor;
+mod gateway_spawner;
+mod init;
+mod local;
+mod pipe;
 mod tui;
 
+use commands::Command;
 use tui::app::TuiApp;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(cmd) = std::env::args().nth(1)
-        && cmd == "cron-validate"
-    {
-        let target = std::env::args().nth(2).map(PathBuf::from);
-        return run_cron_validate(target).await;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match commands::parse(&args)? {
+        Command::Tui => {}
+        Command::Help => {
+            print!("{}", commands::USAGE);
+            return Ok(());
+        }
+        Command::Init => return init::run_init().await,
+        Command::Doctor => return doctor::run_doctor().await,
+        Command::CronValidate(target) => return run_cron_validate(target).await,
+        Command::DbEncrypt { store_key } => return run_db_encrypt(store_key).await,
+        Command::Audit(args) => return run_audit(&args).await,
+        Command::ToolUsage(args) => return run_tool_usage(&args).await,
+        Command::Gateway { request, json } => return commands::run_gateway(request, json).await,
+        Command::Pipe => return pipe::run_pipe().await,
+        Command::GatewayControl(control) => {
+            return gateway_spawner::run_gateway_control(control).await;
+        }
+        Command::Local(options) => return local::run_local(options).await,
     }
 
     tracing_subscriber::fmt()
@@ -85,12 +105,13 @@ async fn run_cron_validate(target: Option<PathBuf>) -> Result<(), Box<dyn std::e
         let raw = std::fs::read_to_string(&file)?;
         match t_koma_core::parse_cron_job_markdown(&file, &raw) {
             Ok(parsed) => {
-                let schedule = format!("0 {}", parsed.schedule);
-                if let Err(err) = Schedule::from_str(&schedule) {
-                    failed += 1;
-                    eprintln!("INVALID {}: {}", file.display(), err);
-                } else {
-                    println!("OK      {}", file.display());
+                match t_koma_core::CronSchedule::parse(&parsed.schedule, parsed.timezone.as_deref())
+                {
+                    Ok(schedule) => println!("OK      {} ({schedule})", file.display()),
+                    Err(err) => {
+                        failed += 1;
+                        eprintln!("INVALID {}: {}", file.display(), err);
+                    }
                 }
             }
             Err(err) => {
@@ -111,10 +132,179 @@ async fn run_cron_validate(target: Option<PathBuf>) -> Result<(), Box<dyn std::e
     Ok(())
 }
 
+/// One-shot conversion of a plaintext `koma.sqlite3` to SQLCipher.
+///
+/// The gateway must be stopped: the file is replaced in place.
+async fn run_db_encrypt(store_key: bool) -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let key = t_koma_db::DbKey::resolve()?.ok_or_else(|| {
+        format!(
+            "No database key: set {} (or store one in the OS keyring)",
+            t_koma_db::DB_KEY_ENV
+        )
+    })?;
+    let path = t_koma_db::KomaDbPool::db_path()?;
+
+    print!(
+        "Encrypt {}? Stop the gateway first. [y/N]: ",
+        path.display()
+    );
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    t_koma_db::encrypt_database(&path, &key).await?;
+    println!("Encrypted {}.", path.display());
+
+    if store_key {
+        key.store_in_keyring()?;
+        println!("Stored the key in the OS keyring.");
+    }
+    Ok(())
+}
+
+/// Print the audit trail (`events` table), oldest first.
+///
+/// Flags: `--kind <kind|subject type>`, `--subject <id>`, `--actor <id>`,
+/// `--after <event id>`, `--limit <n>`.
+async fn run_audit(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let mut filters = t_koma_db::EventFilters::default();
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("Missing value for {flag}"))?
+            .clone();
+        match flag.as_str() {
+            "--kind" => filters.kind = Some(value),
+            "--subject" => filters.subject_id = Some(value),
+            "--actor" => filters.actor_id = Some(value),
+            "--after" => filters.after_id = Some(value.parse()?),
+            "--limit" => filters.limit = Some(value.parse()?),
+            other => return Err(format!("Unknown audit flag: {other}").into()),
+        }
+    }
+
+    let db = t_koma_db::KomaDbPool::new().await?;
+    let events = t_koma_db::EventRepository::query(db.pool(), &filters).await?;
+    if events.is_empty() {
+        println!("No events.");
+        return Ok(());
+    }
+    for event in events {
+        let when = chrono::DateTime::from_timestamp(event.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| event.created_at.to_string());
+        let data = event.data.map(|data| data.to_string()).unwrap_or_default();
+        println!(
+            "#{:<6} {} {:<34} {} by {} {}",
+            event.id,
+            when,
+            event.kind,
+            event.subject_id,
+            event.actor_id.as_deref().unwrap_or("-"),
+            data
+        );
+    }
+    Ok(())
+}
+
+async fn run_tool_usage(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let mut ghost = None;
+    let mut filters = t_koma_db::ToolUsageFilters::default();
+    let mut failures_limit = 10;
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("Missing value for {flag}"))?
+            .clone();
+        match flag.as_str() {
+            "--ghost" => ghost = Some(value),
+            "--tool" => filters.tool_name = Some(value),
+            "--since-days" => {
+                let days: i64 = value.parse()?;
+                filters.since = chrono::Utc::now().timestamp() - days * 86_400;
+            }
+            "--failures" => failures_limit = value.parse()?,
+            other => return Err(format!("Unknown tool-usage flag: {other}").into()),
+        }
+    }
+
+    let db = t_koma_db::KomaDbPool::new().await?;
+    if let Some(name) = ghost {
+        let ghost = t_koma_db::GhostRepository::get_by_name(db.pool(), &name)
+            .await?
+            .ok_or_else(|| format!("Unknown ghost: {name}"))?;
+        filters.ghost_id = Some(ghost.id);
+    }
+
+    let stats = t_koma_db::ToolInvocationRepository::stats(db.pool(), &filters).await?;
+    if stats.is_empty() {
+        println!("No tool calls.");
+        return Ok(());
+    }
+    let format_time = |timestamp: i64| {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| timestamp.to_string())
+    };
+    println!(
+        "{:<28} {:>7} {:>8} {:>6} {:>9} {:>9} {:>11}  last used",
+        "tool", "calls", "failures", "fail%", "avg ms", "max ms", "out bytes"
+    );
+    for tool in &stats {
+        println!(
+            "{:<28} {:>7} {:>8} {:>5.1}% {:>9.0} {:>9} {:>11}  {}",
+            tool.tool_name,
+            tool.calls,
+            tool.failures,
+            tool.failure_rate() * 100.0,
+            tool.avg_duration_ms,
+            tool.max_duration_ms,
+            tool.output_bytes,
+            format_time(tool.last_used_at)
+        );
+    }
+
+    if failures_limit == 0 {
+        return Ok(());
+    }
+    let failures =
+        t_koma_db::ToolInvocationRepository::recent_failures(db.pool(), &filters, failures_limit)
+            .await?;
+    if failures.is_empty() {
+        return Ok(());
+    }
+    println!("\nRecent failures:");
+    for failure in failures {
+        println!(
+            "{} {:<24} {:<16} {}ms {}",
+            format_time(failure.created_at),
+            failure.tool_name,
+            failure.ghost_name,
+            failure.duration_ms,
+            failure.error.as_deref().unwrap_or("-").replace('\n', " ")
+        );
+    }
+    Ok(())
+}
+
 async fn run_cyberdeck() -> Result<(), Box<dyn std::error::Error>> {
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
-    crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    crossterm::execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -135,7 +325,8 @@ async fn run_cyberdeck() -> Result<(), Box<dyn std::error::Error>> {
     crossterm::execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -64,6 +64,7 @@ pub fn apply_gateway_selection(
         context_window: None,
         headers: None,
         retry_on_empty: None,
+        pricing: None,
     };
 
     settings.models.insert(alias.clone(), entry);
@@ -200,6 +201,7 @@ fn add_or_update_model(
             context_window: None,
             headers: None,
             retry_on_empty: None,
+            pricing: None,
         },
     );
 
@@ -0,0 +1,169 @@
+//! `t-koma-cli pipe`: the gateway WebSocket as JSON lines.
+//!
+//! Each stdin line is a `WsMessage` (`{"type": "chat", ...}`), forwarded over
+//! one gateway connection; every `WsResponse` is written to stdout as one
+//! line. Lines that are not valid messages get an error response and are not
+//! sent. `/attach <path>` reads a local file and adds it to the next `chat`
+//! message. At end of input the connection stays open until the gateway has
+//! answered everything sent: the gateway handles one connection's frames in
+//! order, so a final ping's pong marks the end.
+
+use std::{io::Write, path::PathBuf};
+
+use futures::StreamExt;
+use t_koma_core::{ChatAttachment, GatewayMessage, GatewayMessageKind, WsMessage, WsResponse};
+use tokio::io::AsyncBufReadExt;
+
+use crate::{
+    client::WsClient,
+    commands::{attach_command, read_attachment},
+};
+
+/// One input line.
+#[derive(Debug)]
+enum PipeLine {
+    Message(WsMessage),
+    Attach(PathBuf),
+}
+
+/// Parse one input line; `None` for blank lines.
+fn parse_line(line: &str) -> Option<Result<PipeLine, String>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    if let Some(path) = attach_command(line) {
+        return Some(path.map(PipeLine::Attach));
+    }
+    Some(
+        serde_json::from_str(line)
+            .map(PipeLine::Message)
+            .map_err(|e| format!("Invalid request: {e}")),
+    )
+}
+
+fn local_response(kind: GatewayMessageKind, text: String) -> WsResponse {
+    let id = format!("pipe_{}", uuid::Uuid::new_v4());
+    WsResponse::Response {
+        id: id.clone(),
+        message: GatewayMessage::text_only(id, kind, text),
+        done: true,
+        usage: None,
+    }
+}
+
+fn error_response(text: String) -> WsResponse {
+    local_response(GatewayMessageKind::Error, text)
+}
+
+fn write_response(response: &WsResponse) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, response)?;
+    stdout.write_all(b"\n")?;
+    stdout.flush()
+}
+
+pub async fn run_pipe() -> Result<(), Box<dyn std::error::Error>> {
+    t_koma_core::load_dotenv();
+    let settings = t_koma_core::Settings::load()?;
+    let (tx, mut responses) = WsClient::connect(&settings.ws_url()).await?;
+
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    let mut input_open = true;
+    // Pings sent (the caller's and the final one) and pongs received, so the
+    // final pong is recognized and not echoed.
+    let mut pings = 0usize;
+    let mut pongs = 0usize;
+    // Files from `/attach`, sent with the next chat message.
+    let mut pending_attachments: Vec<ChatAttachment> = Vec::new();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line(), if input_open => {
+                let mut message = match line? {
+                    Some(line) => match parse_line(&line) {
+                        Some(Ok(PipeLine::Message(message))) => message,
+                        Some(Ok(PipeLine::Attach(path))) => {
+                            let response = match read_attachment(&path) {
+                                Ok(attachment) => {
+                                    let text = format!(
+                                        "Attached {} to the next chat message",
+                                        attachment.filename
+                                    );
+                                    pending_attachments.push(attachment);
+                                    local_response(GatewayMessageKind::Info, text)
+                                }
+                                Err(error) => error_response(error),
+                            };
+                            write_response(&response)?;
+                            continue;
+                        }
+                        Some(Err(error)) => {
+                            write_response(&error_response(error))?;
+                            continue;
+                        }
+                        None => continue,
+                    },
+                    None => {
+                        input_open = false;
+                        WsMessage::Ping
+                    }
+                };
+                if matches!(message, WsMessage::Ping) {
+                    pings += 1;
+                }
+                if let WsMessage::Chat { attachments, .. } = &mut message {
+                    attachments.append(&mut pending_attachments);
+                }
+                tx.send(message)
+                    .map_err(|_| "Gateway connection closed")?;
+            }
+            response = responses.next() => {
+                let Some(response) = response else {
+                    return if input_open {
+                        Err("Gateway connection closed".into())
+                    } else {
+                        Ok(())
+                    };
+                };
+                if matches!(response, WsResponse::Pong) {
+                    pongs += 1;
+                    if !input_open && pongs == pings {
+                        return Ok(());
+                    }
+                }
+                write_response(&response)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert!(parse_line("   ").is_none());
+        assert!(matches!(
+            parse_line(r#"{"type": "ping"}"#),
+            Some(Ok(PipeLine::Message(WsMessage::Ping)))
+        ));
+        assert!(matches!(
+            parse_line(r#"{"type": "list_sessions", "ghost_name": "alpha"}"#),
+            Some(Ok(PipeLine::Message(WsMessage::ListSessions { ghost_name }))) if ghost_name == "alpha"
+        ));
+        assert!(matches!(
+            parse_line("/attach  ./shots/screen one.png "),
+            Some(Ok(PipeLine::Attach(path))) if path.as_path() == std::path::Path::new("./shots/screen one.png")
+        ));
+        assert!(matches!(
+            parse_line("/attach"),
+            Some(Err(error)) if error.starts_with("Usage")
+        ));
+        assert!(matches!(
+            parse_line(r#"{"type": "launch_missiles"}"#),
+            Some(Err(error)) if error.starts_with("Invalid request")
+        ));
+    }
+}
@@ -3,12 +3,11 @@ use std::{
     io::{self, Write},
     path::{Path, PathBuf},
     process::Command,
-    time::{SystemTime, UNIX_EPOCH},
 };
 
 use chrono::Utc;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     execute,
     terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -16,24 +15,39 @@ use futures::StreamExt;
 use tempfile::NamedTempFile;
 
 use t_koma_core::{
-    GatewayMessageKind, ModelConfig, ProviderType, Settings, WsMessage, WsResponse,
-    parse_cron_job_markdown,
+    CronSchedule, GatewayMessageKind, GhostCloneScope, ModelConfig, ProviderType,
+    SchedulerEntryInfo, Settings, WsMessage, WsResponse, config::backups, parse_cron_job_markdown,
 };
 use t_koma_db::{
-    ContentBlock, Ghost, GhostRepository, JobKind as DbJobKind, JobLog, JobLogRepository, Message,
-    OperatorAccessLevel, OperatorRepository, OperatorStatus, Platform, SessionRepository,
-    ghosts::ghost_workspace_path,
+    ApiTokenScope, ContentBlock, Ghost, GhostRepository, JobKind as DbJobKind, JobLog,
+    JobLogRepository, Message, MessageSearchFilters, OperatorAccessLevel, OperatorPermission,
+    OperatorRepository, OperatorStatus, PathAccess, PathApprovalRepository, Platform,
+    PromptCacheRepository, ScheduledTaskRepository, SessionRepository, TodoItem, ToolPolicy,
+    ToolPolicyRepository, ToolPolicySubject, TranscriptEntry, UsageGrouping, UsageLogRepository,
+    ghosts::ghost_workspace_path, path_approvals::format_path_approvals,
+    tool_policies::format_policies,
 };
 
-use crate::client::WsClient;
+use crate::{
+    client::WsClient,
+    clipboard,
+    tui::{
+        state::Category,
+        theme::{self, Palette},
+    },
+};
 
 use super::{
     TuiApp,
     state::{
-        ContentView, CronFileRow, GhostRow, Metrics, OperatorView, PromptKind, SelectionAction,
-        SelectionItem, SelectionModal,
+        ContentView, CronFileRow, GhostRow, KnowledgePreview, KnowledgeRow, Metrics, OperatorView,
+        PromptKind, SelectionAction, SelectionItem, SelectionModal, TaskRow,
     },
-    util::{load_disk_config, shell_quote, ws_url_for_cli},
+    transcript::{
+        code_blocks, last_ghost_text, message_from_info, search_transcript, transcript_lines,
+        transcript_markdown,
+    },
+    util::{line_diff, load_disk_config, shell_quote, todo_section_height, ws_url_for_cli},
 };
 
 const HEARTBEAT_IDLE_SECONDS: i64 = 15 * 60;
@@ -98,14 +112,31 @@ fn is_heartbeat_ok(message: &Message) -> bool {
     normalized == HEARTBEAT_OK_TOKEN
 }
 
-fn format_heartbeat_status(next_due: Option<i64>) -> Option<String> {
+/// Earliest heartbeat the gateway has scheduled for any session of
+/// `ghost_name`, with that session's idle delay.
+fn gateway_heartbeat_due<'a>(
+    entries: &'a [SchedulerEntryInfo],
+    ghost_name: &str,
+) -> Option<&'a SchedulerEntryInfo> {
+    entries
+        .iter()
+        .filter(|entry| entry.kind == "Heartbeat")
+        .filter(|entry| entry.key.split(':').nth(1) == Some(ghost_name))
+        .min_by_key(|entry| entry.next_due)
+}
+
+fn format_heartbeat_status(next_due: Option<i64>, interval_secs: Option<i64>) -> Option<String> {
     let now = Utc::now().timestamp();
     let due = next_due?;
-    if due <= now {
-        return Some("HEARTBEAT DUE".to_string());
+    let mut status = if due <= now {
+        "HEARTBEAT DUE".to_string()
+    } else {
+        format!("HEARTBEAT IN {}m", (due - now + 59) / 60)
+    };
+    if let Some(interval) = interval_secs {
+        status.push_str(&format!(" (idle {}m)", (interval + 59) / 60));
     }
-    let remaining = (due - now + 59) / 60;
-    Some(format!("HEARTBEAT IN {}m", remaining))
+    Some(status)
 }
 
 impl TuiApp {
@@ -137,10 +168,32 @@ impl TuiApp {
             }
         }
 
+        let midnight = Utc::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .map(|t| t.and_utc().timestamp())
+            .unwrap_or_default();
+        let today = UsageLogRepository::aggregate(db.pool(), UsageGrouping::Day, midnight)
+            .await
+            .ok()
+            .and_then(|days| days.into_iter().next())
+            .map(|day| day.totals)
+            .unwrap_or_default();
+        let cache = PromptCacheRepository::stats(db.pool(), midnight)
+            .await
+            .unwrap_or_default();
+
         self.metrics = Metrics {
             operator_count,
             ghost_count,
             recent_message_count,
+            today_cost_usd: today.cost_usd,
+            today_tokens: today.input_tokens
+                + today.output_tokens
+                + today.cache_read_tokens
+                + today.cache_creation_tokens,
+            cache_hit_rate: cache.hit_rate(),
+            cache_savings_usd: cache.net_savings_usd,
         };
     }
 
@@ -176,9 +229,19 @@ impl TuiApp {
 
         match GhostRepository::list_all(db.pool()).await {
             Ok(list) => {
+                let scheduled = self.scheduler_entries().await;
                 let mut rows = Vec::with_capacity(list.len());
                 for ghost in list {
-                    let heartbeat = self.compute_ghost_heartbeat_status(&ghost).await;
+                    let heartbeat = match &scheduled {
+                        Some(entries) => {
+                            let entry = gateway_heartbeat_due(entries, &ghost.name);
+                            format_heartbeat_status(
+                                entry.map(|entry| entry.next_due),
+                                entry.and_then(|entry| entry.interval_secs),
+                            )
+                        }
+                        None => self.compute_ghost_heartbeat_status(&ghost).await,
+                    };
                     rows.push(GhostRow { ghost, heartbeat });
                 }
                 self.ghosts = rows;
@@ -190,6 +253,15 @@ impl TuiApp {
         }
     }
 
+    /// The gateway's scheduler entries, or `None` when it isn't reachable.
+    async fn scheduler_entries(&self) -> Option<Vec<SchedulerEntryInfo>> {
+        match self.ws_query(WsMessage::GetSchedulerState).await {
+            Ok(WsResponse::SchedulerState { entries }) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Estimate from the DB alone, for when the gateway isn't running.
     async fn compute_ghost_heartbeat_status(&self, ghost: &Ghost) -> Option<String> {
         let db = self.db.as_ref()?;
         let sessions = SessionRepository::list(db.pool(), &ghost.id, &ghost.owner_operator_id)
@@ -217,7 +289,7 @@ impl TuiApp {
             });
         }
 
-        format_heartbeat_status(next_due)
+        format_heartbeat_status(next_due, None)
     }
 
     pub(super) async fn add_operator(&mut self, input: &str) {
@@ -456,6 +528,270 @@ impl TuiApp {
         }
     }
 
+    /// Issue a gateway API token; Puppet Masters also get the admin scope.
+    ///
+    /// The plaintext token is only shown here, in the status line.
+    pub(super) async fn issue_operator_api_token(
+        &mut self,
+        operator_id: &str,
+        access_level: OperatorAccessLevel,
+    ) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+
+        let scopes: &[ApiTokenScope] = if access_level == OperatorAccessLevel::PuppetMaster {
+            &[ApiTokenScope::Chat, ApiTokenScope::Admin]
+        } else {
+            &[ApiTokenScope::Chat]
+        };
+        let name = format!("tui-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+        match OperatorRepository::issue_api_token(db.pool(), operator_id, &name, scopes, None).await
+        {
+            Ok((_, secret)) => {
+                self.status = format!("API token (shown once): {}", secret);
+            }
+            Err(e) => self.status = format!("Token issue failed: {}", e),
+        }
+    }
+
+    pub(super) async fn revoke_operator_api_tokens(&mut self, operator_id: &str) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+
+        let tokens = match OperatorRepository::list_api_tokens(db.pool(), operator_id).await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                self.status = format!("Token revoke failed: {}", e);
+                return;
+            }
+        };
+        let mut revoked = 0;
+        for token in tokens.iter().filter(|t| t.revoked_at.is_none()) {
+            match OperatorRepository::revoke_api_token(db.pool(), &token.id).await {
+                Ok(true) => revoked += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    self.status = format!("Token revoke failed: {}", e);
+                    return;
+                }
+            }
+        }
+        self.status = format!("Revoked {} API token(s) for {}", revoked, operator_id);
+    }
+
+    pub(super) async fn show_operator_permissions(&mut self, operator_id: &str) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+
+        self.status = match OperatorRepository::get_permissions(db.pool(), operator_id).await {
+            Ok(permissions) => format!("Permissions: {}", permissions),
+            Err(e) => format!("Load permissions failed: {}", e),
+        };
+    }
+
+    /// Apply `name=on|off|default` overrides; `default` restores the
+    /// access-level default.
+    pub(super) async fn set_operator_permissions(&mut self, operator_id: &str, input: &str) {
+        let mut changes = Vec::new();
+        for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((name, value)) = part.split_once('=') else {
+                self.status = "Use: name=on|off|default, ...".to_string();
+                return;
+            };
+            let permission = match name.trim().parse::<OperatorPermission>() {
+                Ok(permission) => permission,
+                Err(e) => {
+                    self.status = e.to_string();
+                    return;
+                }
+            };
+            let allowed = match value.trim().to_lowercase().as_str() {
+                "on" | "allow" | "true" => Some(true),
+                "off" | "deny" | "false" => Some(false),
+                "default" | "reset" => None,
+                other => {
+                    self.status = format!("Invalid value '{}': use on, off or default", other);
+                    return;
+                }
+            };
+            changes.push((permission, allowed));
+        }
+        if changes.is_empty() {
+            self.status = "No permission changes".to_string();
+            return;
+        }
+
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+
+        for (permission, allowed) in &changes {
+            if let Err(e) =
+                OperatorRepository::set_permission(db.pool(), operator_id, permission, *allowed)
+                    .await
+            {
+                self.status = format!("Set permissions failed: {}", e);
+                return;
+            }
+        }
+        self.show_operator_permissions(operator_id).await;
+    }
+
+    /// Show the operator's tool policies and those of each of their ghosts.
+    pub(super) async fn show_tool_policies(&mut self, operator_id: &str) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        let pool = db.pool();
+
+        let summary = async {
+            let operator = ToolPolicySubject::Operator(operator_id.to_string());
+            let mut parts = vec![format!(
+                "operator: {}",
+                format_policies(&ToolPolicyRepository::list(pool, &operator).await?)
+            )];
+            for ghost in GhostRepository::list_by_operator(pool, operator_id).await? {
+                let policies =
+                    ToolPolicyRepository::list(pool, &ToolPolicySubject::Ghost(ghost.id)).await?;
+                if !policies.is_empty() {
+                    parts.push(format!("{}: {}", ghost.name, format_policies(&policies)));
+                }
+            }
+            Ok::<_, t_koma_db::DbError>(parts.join(" | "))
+        }
+        .await;
+        self.status = match summary {
+            Ok(summary) => format!("Tool policies: {}", summary),
+            Err(e) => format!("Load tool policies failed: {}", e),
+        };
+    }
+
+    /// Apply `[ghost_name:] tool=allow|deny|auto|default, ...` to the
+    /// operator, or to one of their ghosts when prefixed with its name.
+    /// Tool names may end in `*` to match a prefix.
+    pub(super) async fn set_tool_policies(&mut self, operator_id: &str, input: &str) {
+        let (ghost_name, entries) = match input.split_once(':') {
+            Some((ghost_name, entries)) => (Some(ghost_name.trim()), entries),
+            None => (None, input),
+        };
+        let mut changes = Vec::new();
+        for part in entries.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((tool, value)) = part.split_once('=') else {
+                self.status = "Use: [ghost:] tool=allow|deny|auto|default, ...".to_string();
+                return;
+            };
+            let policy = match value.trim().to_lowercase().as_str() {
+                "default" | "reset" => None,
+                other => match other.parse::<ToolPolicy>() {
+                    Ok(policy) => Some(policy),
+                    Err(_) => {
+                        self.status = format!(
+                            "Invalid policy '{}': use allow, deny, auto or default",
+                            other
+                        );
+                        return;
+                    }
+                },
+            };
+            changes.push((tool.trim().to_string(), policy));
+        }
+        if changes.is_empty() {
+            self.status = "No tool policy changes".to_string();
+            return;
+        }
+
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        let pool = db.pool();
+
+        let subject = match ghost_name {
+            Some(ghost_name) => match GhostRepository::get_by_name(pool, ghost_name).await {
+                Ok(Some(ghost)) if ghost.owner_operator_id == operator_id => {
+                    ToolPolicySubject::Ghost(ghost.id)
+                }
+                Ok(_) => {
+                    self.status = format!("Operator has no ghost named {}", ghost_name);
+                    return;
+                }
+                Err(e) => {
+                    self.status = format!("Set tool policies failed: {}", e);
+                    return;
+                }
+            },
+            None => ToolPolicySubject::Operator(operator_id.to_string()),
+        };
+        for (tool, policy) in &changes {
+            if let Err(e) = ToolPolicyRepository::set(pool, &subject, tool, *policy).await {
+                self.status = format!("Set tool policies failed: {}", e);
+                return;
+            }
+        }
+        self.show_tool_policies(operator_id).await;
+    }
+
+    /// Show the operator's path approval rules.
+    pub(super) async fn show_path_approvals(&mut self, operator_id: &str) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        self.status = match PathApprovalRepository::list(db.pool(), operator_id).await {
+            Ok(approvals) => format!("Path approvals: {}", format_path_approvals(&approvals)),
+            Err(e) => format!("Load path approvals failed: {}", e),
+        };
+    }
+
+    /// Apply `path=read|write|default, ...` to the operator's path approval
+    /// rules.
+    pub(super) async fn set_path_approvals(&mut self, operator_id: &str, input: &str) {
+        let mut changes = Vec::new();
+        for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((path, value)) = part.rsplit_once('=') else {
+                self.status = "Use: path=read|write|default, ...".to_string();
+                return;
+            };
+            let access = match value.trim().to_lowercase().as_str() {
+                "default" | "reset" => None,
+                other => match other.parse::<PathAccess>() {
+                    Ok(access) => Some(access),
+                    Err(_) => {
+                        self.status =
+                            format!("Invalid access '{}': use read, write or default", other);
+                        return;
+                    }
+                },
+            };
+            changes.push((path.trim().to_string(), access));
+        }
+        if changes.is_empty() {
+            self.status = "No path approval changes".to_string();
+            return;
+        }
+
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        for (path, access) in &changes {
+            if let Err(e) = PathApprovalRepository::set(db.pool(), operator_id, path, *access).await
+            {
+                self.status = format!("Set path approvals failed: {}", e);
+                return;
+            }
+        }
+        self.show_path_approvals(operator_id).await;
+    }
+
     pub(super) async fn add_ghost(&mut self, input: &str) {
         let parts: Vec<&str> = input.split(',').map(|v| v.trim()).collect();
         if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
@@ -531,7 +867,11 @@ impl TuiApp {
                 routing: None,
                 context_window: None,
                 headers: None,
+                api_version: None,
                 retry_on_empty: None,
+                thinking_budget: None,
+                generation: Default::default(),
+                pricing: None,
             },
         );
         self.settings_dirty = true;
@@ -565,29 +905,93 @@ impl TuiApp {
                 self.disk_toml = load_disk_config().unwrap_or_else(|| self.settings_toml.clone());
                 self.settings_dirty = false;
                 self.status = "Reloaded settings".to_string();
+                self.load_keymap();
+                self.load_theme();
             }
             Err(e) => self.status = format!("Reload failed: {}", e),
         }
     }
 
+    /// Write the settings to `config.toml`. With unsaved changes the first
+    /// Save opens the diff against disk; Save again from there writes it.
     pub(super) fn save_settings(&mut self) {
+        if self.settings_dirty && self.content_view != ContentView::ConfigDiff {
+            self.open_config_diff();
+            self.status = "Review the changes, then Save again to write config.toml".to_string();
+            return;
+        }
         match self.settings.save() {
             Ok(()) => {
                 self.settings_dirty = false;
                 self.disk_toml = self.settings_toml.clone();
+                if self.content_view == ContentView::ConfigDiff {
+                    self.content_view = ContentView::List;
+                    self.config_scroll = 0;
+                }
                 self.status = "Settings saved".to_string();
             }
             Err(e) => self.status = format!("Save failed: {}", e),
         }
     }
 
-    pub(super) fn restore_backup(&mut self) {
-        let Some(path) = self.backup_path.clone() else {
-            self.status = "No backup available".to_string();
+    pub(super) fn open_config_diff(&mut self) {
+        if let Some(disk) = load_disk_config() {
+            self.disk_toml = disk;
+        }
+        self.content_view = ContentView::ConfigDiff;
+        self.config_scroll = 0;
+        let changed = line_diff(&self.disk_toml, &self.settings_toml)
+            .iter()
+            .filter(|row| row.changed)
+            .count();
+        self.status = if changed == 0 {
+            "No differences from config.toml".to_string()
+        } else {
+            format!("{} line(s) differ from config.toml", changed)
+        };
+    }
+
+    pub(super) fn open_config_backups(&mut self) {
+        let config_path = match Settings::config_path() {
+            Ok(path) => path,
+            Err(e) => {
+                self.status = format!("Config path failed: {}", e);
+                return;
+            }
+        };
+        if let Some(disk) = load_disk_config() {
+            self.disk_toml = disk;
+        }
+        self.config_backups = backups::list_backups(&config_path);
+        self.content_view = ContentView::ConfigBackups;
+        self.content_idx = 0;
+        self.load_config_backup_preview();
+        self.status = if self.config_backups.is_empty() {
+            "No config backups yet; Save takes one before writing".to_string()
+        } else {
+            format!("{} config backups", self.config_backups.len())
+        };
+    }
+
+    /// Load the selected backup for the side-by-side preview.
+    pub(super) fn load_config_backup_preview(&mut self) {
+        self.config_scroll = 0;
+        self.config_backup_preview = self
+            .config_backups
+            .get(self.content_idx)
+            .and_then(|backup| fs::read_to_string(&backup.path).ok())
+            .unwrap_or_default();
+    }
+
+    /// Restore the selected backup over `config.toml`. The current file is
+    /// backed up first, so a restore can itself be undone.
+    pub(super) fn restore_selected_backup(&mut self) {
+        let Some(backup) = self.config_backups.get(self.content_idx).cloned() else {
+            self.status = "No backup selected".to_string();
             return;
         };
 
-        let content = match fs::read_to_string(&path) {
+        let content = match fs::read_to_string(&backup.path) {
             Ok(content) => content,
             Err(e) => {
                 self.status = format!("Backup read failed: {}", e);
@@ -611,16 +1015,32 @@ impl TuiApp {
             }
         };
 
-        if let Err(e) = fs::write(&config_path, content) {
+        if let Err(e) = backups::create_backup(&config_path) {
+            self.status = format!("Backing up current config failed: {}", e);
+            return;
+        }
+        if let Err(e) = fs::write(&config_path, &content) {
             self.status = format!("Restore write failed: {}", e);
             return;
         }
 
         self.settings = parsed;
         self.refresh_settings_toml();
-        self.disk_toml = self.settings_toml.clone();
+        self.disk_toml = content;
         self.settings_dirty = false;
-        self.status = format!("Restored backup {}", path.display());
+        self.config_backups = backups::list_backups(&config_path);
+        self.content_idx = self
+            .config_backups
+            .iter()
+            .position(|b| b.path == backup.path)
+            .unwrap_or(0);
+        self.load_config_backup_preview();
+        self.status = format!(
+            "Restored backup from {}",
+            backup.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        self.load_keymap();
+        self.load_theme();
     }
 
     pub(super) fn edit_in_editor(&mut self) -> Result<(), String> {
@@ -634,24 +1054,7 @@ impl TuiApp {
             .map_err(|e| e.to_string())?;
         let temp_path = temp_file.path().to_path_buf();
 
-        terminal::disable_raw_mode().map_err(|e| e.to_string())?;
-        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
-
-        let escaped_path = shell_quote(temp_path.to_string_lossy().as_ref());
-        let editor_cmd = format!("${{EDITOR:-vi}} {}", escaped_path);
-        let status = Command::new("sh")
-            .arg("-lc")
-            .arg(editor_cmd)
-            .status()
-            .map_err(|e| e.to_string())?;
-
-        let _ = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture);
-        terminal::enable_raw_mode().map_err(|e| e.to_string())?;
-        let _ = execute!(io::stdout(), terminal::Clear(ClearType::All));
-
-        if !status.success() {
-            return Err("Editor exited with non-zero status".to_string());
-        }
+        run_editor(temp_path.to_string_lossy().as_ref())?;
 
         let edited = fs::read_to_string(&temp_path).map_err(|e| e.to_string())?;
         match Settings::from_toml(&edited) {
@@ -660,22 +1063,14 @@ impl TuiApp {
                 self.refresh_settings_toml();
                 self.settings_dirty = self.settings_toml != self.disk_toml;
                 self.status = "Edited config loaded. Press Save to persist.".to_string();
+                self.load_keymap();
+                self.load_theme();
                 Ok(())
             }
             Err(e) => {
-                let stamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map_err(|err| err.to_string())?
-                    .as_millis();
-                let backup_name = format!("config.toml.bak.{}", stamp);
-                let backup_path = config_path
-                    .parent()
-                    .unwrap_or_else(|| std::path::Path::new("."))
-                    .join(backup_name);
-                if config_path.exists() {
-                    fs::copy(&config_path, &backup_path)
-                        .map_err(|copy_err| copy_err.to_string())?;
-                    self.backup_path = Some(backup_path.clone());
+                if let Some(backup_path) = backups::create_backup(&config_path)
+                    .map_err(|backup_err| backup_err.to_string())?
+                {
                     self.status = format!(
                         "Invalid TOML rejected. Backup saved at {}",
                         backup_path.display()
@@ -717,6 +1112,151 @@ impl TuiApp {
         }
     }
 
+    /// Ask the gateway to re-read config.toml without restarting.
+    pub(super) async fn reload_config(&mut self) {
+        let ws_url = ws_url_for_cli(&self.settings.ws_url());
+        let (tx, mut rx) = match WsClient::connect(&ws_url).await {
+            Ok(pair) => pair,
+            Err(e) => {
+                self.status = format!("Gateway connect failed: {}", e);
+                self.gate_connected = false;
+                return;
+            }
+        };
+
+        if tx.send(WsMessage::ReloadConfig).is_err() {
+            self.status = "Reload command send failed".to_string();
+            return;
+        }
+
+        match tokio::time::timeout(std::time::Duration::from_secs(10), rx.next()).await {
+            Ok(Some(WsResponse::ConfigReloaded {
+                applied,
+                restart_required,
+            })) => {
+                self.status = if applied.is_empty() && restart_required.is_empty() {
+                    "Config reloaded: no changes".to_string()
+                } else if restart_required.is_empty() {
+                    format!("Config reloaded: {}", applied.join(", "))
+                } else {
+                    format!(
+                        "Config reloaded: {} (restart needed for {})",
+                        if applied.is_empty() {
+                            "nothing applied".to_string()
+                        } else {
+                            applied.join(", ")
+                        },
+                        restart_required.join(", ")
+                    )
+                };
+            }
+            Ok(Some(WsResponse::Response { message, .. }))
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Reload failed: {}", message.text_fallback);
+            }
+            _ => {
+                self.status = "Reload requested".to_string();
+            }
+        }
+    }
+
+    /// Rename through the gateway, which also moves the workspace and
+    /// rewrites the knowledge index.
+    pub(super) async fn rename_ghost(&mut self, ghost_name: &str, new_name: &str) {
+        let ws_url = ws_url_for_cli(&self.settings.ws_url());
+        let (tx, mut rx) = match WsClient::connect(&ws_url).await {
+            Ok(pair) => pair,
+            Err(e) => {
+                self.status = format!("Gateway connect failed: {}", e);
+                self.gate_connected = false;
+                return;
+            }
+        };
+
+        let request = WsMessage::RenameGhost {
+            ghost_name: ghost_name.to_string(),
+            new_name: new_name.to_string(),
+        };
+        if tx.send(request).is_err() {
+            self.status = "Rename command send failed".to_string();
+            return;
+        }
+
+        match tokio::time::timeout(std::time::Duration::from_secs(10), rx.next()).await {
+            Ok(Some(WsResponse::GhostRenamed { old_name, new_name })) => {
+                self.status = format!("Renamed ghost {} to {}", old_name, new_name);
+                self.refresh_ghosts().await;
+            }
+            Ok(Some(WsResponse::Response { message, .. })) => {
+                self.status = format!("Rename failed: {}", message.text_fallback);
+            }
+            _ => {
+                self.status = "Rename failed: no response from gateway".to_string();
+            }
+        }
+    }
+
+    /// Clone through the gateway. Input is `new_name` optionally followed by
+    /// comma-separated parts to copy; without parts the gateway default is used.
+    pub(super) async fn clone_ghost(&mut self, source: &str, input: &str) {
+        let mut parts = input.split(',').map(str::trim);
+        let new_name = parts.next().unwrap_or_default().to_string();
+        let mut copy = Vec::new();
+        for part in parts.filter(|p| !p.is_empty()) {
+            match serde_json::from_value::<GhostCloneScope>(serde_json::Value::String(
+                part.to_ascii_lowercase(),
+            )) {
+                Ok(scope) => copy.push(scope),
+                Err(_) => {
+                    self.status = format!("Unknown clone part: {}", part);
+                    return;
+                }
+            }
+        }
+
+        let ws_url = ws_url_for_cli(&self.settings.ws_url());
+        let (tx, mut rx) = match WsClient::connect(&ws_url).await {
+            Ok(pair) => pair,
+            Err(e) => {
+                self.status = format!("Gateway connect failed: {}", e);
+                self.gate_connected = false;
+                return;
+            }
+        };
+
+        let request = WsMessage::CloneGhost {
+            source: source.to_string(),
+            new_name,
+            owner_operator_id: None,
+            copy: (!copy.is_empty()).then_some(copy),
+        };
+        if tx.send(request).is_err() {
+            self.status = "Clone command send failed".to_string();
+            return;
+        }
+
+        match tokio::time::timeout(std::time::Duration::from_secs(30), rx.next()).await {
+            Ok(Some(WsResponse::GhostCloned {
+                source,
+                name,
+                knowledge_files,
+            })) => {
+                self.status = format!(
+                    "Cloned ghost {} into {} ({} knowledge files)",
+                    source, name, knowledge_files
+                );
+                self.refresh_ghosts().await;
+            }
+            Ok(Some(WsResponse::Response { message, .. })) => {
+                self.status = format!("Clone failed: {}", message.text_fallback);
+            }
+            _ => {
+                self.status = "Clone failed: no response from gateway".to_string();
+            }
+        }
+    }
+
     // ── Modal actions ────────────────────────────────────────────────
 
     pub(super) fn open_access_level_modal(
@@ -744,12 +1284,59 @@ impl TuiApp {
             selected_idx: selected,
             on_select: SelectionAction::SetAccessLevel,
             context: Some(operator_id),
+            detail: None,
         });
     }
 
-    pub(super) fn open_provider_selection_modal(&mut self) {
+    /// Apply `[cli] theme`, reporting bad palette entries in the status line.
+    pub(super) fn load_theme(&mut self) {
+        let (palette, errors) = Palette::resolve(&self.settings.cli, &self.settings.cli.theme);
+        theme::apply(palette);
+        if !errors.is_empty() {
+            self.status = format!("[cli] theme: {}", errors.join(", "));
+        }
+    }
+
+    pub(super) fn open_theme_modal(&mut self) {
+        let names = Palette::names(&self.settings.cli);
+        let selected_idx = names
+            .iter()
+            .position(|name| *name == self.settings.cli.theme)
+            .unwrap_or(0);
         self.modal = Some(SelectionModal {
-            title: "Select Provider".to_string(),
+            title: "Select Theme".to_string(),
+            items: names
+                .into_iter()
+                .map(|name| SelectionItem {
+                    label: name.replace('_', " "),
+                    value: name,
+                })
+                .collect(),
+            selected_idx,
+            on_select: SelectionAction::SelectTheme,
+            context: None,
+            detail: None,
+        });
+    }
+
+    pub(super) fn select_theme(&mut self, name: &str) {
+        if !Palette::names(&self.settings.cli)
+            .iter()
+            .any(|known| known == name)
+        {
+            self.status = format!("Unknown theme: {}", name);
+            return;
+        }
+        self.settings.cli.theme = name.to_string();
+        self.settings_dirty = true;
+        self.refresh_settings_toml();
+        self.status = format!("Theme set to {} (Save to keep it)", name);
+        self.load_theme();
+    }
+
+    pub(super) fn open_provider_selection_modal(&mut self) {
+        self.modal = Some(SelectionModal {
+            title: "Select Provider".to_string(),
             items: vec![
                 SelectionItem {
                     label: "Anthropic".to_string(),
@@ -771,10 +1358,15 @@ impl TuiApp {
                     label: "Kimi Code".to_string(),
                     value: "kimi_code".to_string(),
                 },
+                SelectionItem {
+                    label: "Azure OpenAI".to_string(),
+                    value: "azure_openai".to_string(),
+                },
             ],
             selected_idx: 0,
             on_select: SelectionAction::SelectProvider,
             context: None,
+            detail: None,
         });
     }
 
@@ -794,6 +1386,14 @@ impl TuiApp {
             SelectionAction::SelectProvider => {
                 self.show_provider_instructions_and_prompt(&selected.value);
             }
+            SelectionAction::ResolveApproval => {
+                let value = selected.value.clone();
+                self.handle_approval_selection(modal.context, &value);
+            }
+            SelectionAction::SelectTheme => {
+                let name = selected.value.clone();
+                self.select_theme(&name);
+            }
         }
     }
 
@@ -812,6 +1412,7 @@ impl TuiApp {
             "gemini" => "GEMINI_API_KEY",
             "openai_compatible" => "OPENAI_API_KEY",
             "kimi_code" => "KIMI_API_KEY",
+            "azure_openai" => "AZURE_OPENAI_API_KEY",
             _ => {
                 self.status = format!("Unknown provider: {}", provider);
                 return;
@@ -875,7 +1476,10 @@ impl TuiApp {
             Ok(summaries) => {
                 self.job_view.mode = super::state::JobViewMode::Logs;
                 self.job_view.summaries = summaries;
+                self.job_view.ghost_filter = ghost_id.map(str::to_string);
+                self.job_view.live_lookups.clear();
                 self.job_view.cron_jobs.clear();
+                self.job_view.tasks.clear();
                 self.job_view.detail = None;
                 self.content_view = ContentView::List;
                 self.content_idx = 0;
@@ -891,6 +1495,7 @@ impl TuiApp {
             return;
         };
 
+        let scheduled = self.scheduler_entries().await;
         let cron_jobs = match GhostRepository::list_all(db.pool()).await {
             Ok(ghosts) => {
                 let mut out = Vec::new();
@@ -911,13 +1516,33 @@ impl TuiApp {
                         let Ok(parsed) = parse_cron_job_markdown(&path, &raw) else {
                             continue;
                         };
+                        let schedule =
+                            CronSchedule::parse(&parsed.schedule, parsed.timezone.as_deref()).ok();
+                        let next_run = match &scheduled {
+                            Some(entries) => {
+                                let rel = path
+                                    .strip_prefix(&workspace_root)
+                                    .unwrap_or(&path)
+                                    .display();
+                                let key = format!("{}:{rel}", ghost.id);
+                                entries
+                                    .iter()
+                                    .find(|entry| entry.kind == "Cron" && entry.key == key)
+                                    .map(|entry| entry.next_due)
+                            }
+                            None => schedule
+                                .as_ref()
+                                .filter(|_| parsed.enabled)
+                                .and_then(|s| s.next_at_or_after(Utc::now())),
+                        };
                         out.push(CronFileRow {
                             ghost_name: ghost.name.clone(),
                             name: parsed.name,
-                            schedule: parsed.schedule,
+                            schedule: schedule.map(|s| s.to_string()).unwrap_or(parsed.schedule),
                             enabled: parsed.enabled,
                             carry_last_output: parsed.carry_last_output,
                             path: path.display().to_string(),
+                            next_run,
                         });
                     }
                 }
@@ -941,6 +1566,7 @@ impl TuiApp {
             Ok(summaries) => {
                 self.job_view.mode = super::state::JobViewMode::Cron;
                 self.job_view.cron_jobs = cron_jobs;
+                self.job_view.tasks.clear();
                 self.job_view.summaries = summaries;
                 self.job_view.detail = None;
                 self.content_view = ContentView::List;
@@ -955,18 +1581,121 @@ impl TuiApp {
         }
     }
 
-    pub(super) async fn drill_into_job(&mut self) {
-        if self.job_view.mode == super::state::JobViewMode::Cron
-            && self.content_idx < self.job_view.cron_jobs.len()
-        {
+    pub(super) async fn refresh_tasks_view(&mut self) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
             return;
+        };
+
+        let tasks = match ScheduledTaskRepository::list_all(db.pool()).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                self.status = format!("Task load failed: {}", e);
+                return;
+            }
+        };
+        let ghosts = GhostRepository::list_all(db.pool())
+            .await
+            .unwrap_or_default();
+        let rows: Vec<TaskRow> = tasks
+            .into_iter()
+            .map(|task| TaskRow {
+                ghost_name: ghosts
+                    .iter()
+                    .find(|g| g.id == task.ghost_id)
+                    .map(|g| g.name.clone())
+                    .unwrap_or_else(|| task.ghost_id.clone()),
+                task,
+            })
+            .collect();
+
+        let summaries = JobLogRepository::list_recent(db.pool(), 400)
+            .await
+            .map(|logs| {
+                logs.into_iter()
+                    .filter(|j| j.job_kind == DbJobKind::Task)
+                    .collect::<Vec<_>>()
+            });
+
+        match summaries {
+            Ok(summaries) => {
+                self.job_view.mode = super::state::JobViewMode::Tasks;
+                self.job_view.tasks = rows;
+                self.job_view.cron_jobs.clear();
+                self.job_view.summaries = summaries;
+                self.job_view.detail = None;
+                self.content_view = ContentView::List;
+                self.content_idx = 0;
+                self.status = format!(
+                    "{} scheduled tasks, {} task runs",
+                    self.job_view.tasks.len(),
+                    self.job_view.summaries.len()
+                );
+            }
+            Err(e) => self.status = format!("Task logs failed: {}", e),
         }
-        let log_idx = if self.job_view.mode == super::state::JobViewMode::Cron {
-            self.content_idx
-                .saturating_sub(self.job_view.cron_jobs.len())
+    }
+
+    /// Pause the selected scheduled task, or resume it from the next occurrence.
+    pub(super) async fn toggle_selected_task(&mut self) {
+        let Some(row) = self.job_view.tasks.get(self.content_idx) else {
+            self.status = "No task selected".to_string();
+            return;
+        };
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        let task = &row.task;
+        let enabled = !task.enabled;
+        let next_due = if enabled {
+            CronSchedule::parse(&task.schedule, None)
+                .ok()
+                .and_then(|s| s.next_after(Utc::now().timestamp()))
         } else {
-            self.content_idx
+            task.next_due
+        };
+        match ScheduledTaskRepository::set_enabled(db.pool(), &task.id, enabled, next_due).await {
+            Ok(true) => {
+                let verb = if enabled { "resumed" } else { "paused" };
+                let name = task.name.clone();
+                let idx = self.content_idx;
+                self.refresh_tasks_view().await;
+                self.content_idx = idx.min(self.job_view.tasks.len().saturating_sub(1));
+                self.status = format!("Task '{}' {}", name, verb);
+            }
+            Ok(false) => self.status = "Task no longer exists".to_string(),
+            Err(e) => self.status = format!("Task update failed: {}", e),
+        }
+    }
+
+    pub(super) async fn delete_selected_task(&mut self) {
+        let Some(row) = self.job_view.tasks.get(self.content_idx) else {
+            self.status = "No task selected".to_string();
+            return;
+        };
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
         };
+        let (id, name) = (row.task.id.clone(), row.task.name.clone());
+        match ScheduledTaskRepository::delete_by_id(db.pool(), &id).await {
+            Ok(_) => {
+                let idx = self.content_idx;
+                self.refresh_tasks_view().await;
+                self.content_idx = idx.min(self.job_view.tasks.len().saturating_sub(1));
+                self.status = format!("Task '{}' deleted", name);
+            }
+            Err(e) => self.status = format!("Task delete failed: {}", e),
+        }
+    }
+
+    pub(super) async fn drill_into_job(&mut self) {
+        let definitions = self.job_view.definition_count();
+        if self.content_idx < definitions {
+            return;
+        }
+        let log_idx = self.content_idx - definitions;
         let Some(job) = self.job_view.summaries.get(log_idx) else {
             return;
         };
@@ -990,6 +1719,147 @@ impl TuiApp {
         }
     }
 
+    fn viewing_job(&self, job_id: &str) -> bool {
+        matches!(&self.content_view, ContentView::JobDetail { job_id: id } if id == job_id)
+    }
+
+    /// List a job streamed by the gateway that started after the job log
+    /// list was loaded. Each unknown job is looked up once.
+    async fn track_live_job(&mut self, job_id: &str) {
+        if self.job_view.mode != super::state::JobViewMode::Logs
+            || self.job_view.summaries.iter().any(|job| job.id == job_id)
+            || !self.job_view.live_lookups.insert(job_id.to_string())
+        {
+            return;
+        }
+        let Some(db) = &self.db else {
+            return;
+        };
+        let result = match self.job_view.ghost_filter.as_deref() {
+            Some(gid) => JobLogRepository::list_for_ghost(db.pool(), gid, 200).await,
+            None => JobLogRepository::list_recent(db.pool(), 200).await,
+        };
+        let Ok(summaries) = result else {
+            return;
+        };
+        // Keep the cursor on the same job as new rows are added above it.
+        let browsing =
+            self.selected_category() == Category::Jobs && self.content_view == ContentView::List;
+        let selected = browsing
+            .then(|| self.job_view.summaries.get(self.content_idx))
+            .flatten()
+            .map(|job| job.id.clone());
+        self.job_view.summaries = summaries;
+        if let Some(id) = selected
+            && let Some(idx) = self.job_view.summaries.iter().position(|job| job.id == id)
+        {
+            self.content_idx = idx;
+        }
+    }
+
+    /// Tail a running job: append a streamed entry to the open detail view
+    /// and refresh the list preview.
+    pub(super) async fn append_live_job_entry(&mut self, job_id: &str, entry: TranscriptEntry) {
+        self.track_live_job(job_id).await;
+        if let Some(summary) = self.job_view.summaries.iter_mut().find(|j| j.id == job_id) {
+            summary.last_message = entry.content.iter().find_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.clone()),
+                _ => None,
+            });
+        }
+        if !self.viewing_job(job_id) {
+            return;
+        }
+        if let Some(detail) = self.job_view.detail.as_mut() {
+            let follow = self.job_detail_scroll >= last_entry_line_offset(detail);
+            detail.transcript.push(entry);
+            if follow {
+                self.job_detail_scroll = last_entry_line_offset(detail);
+            }
+        }
+    }
+
+    /// Apply a streamed TODO list to the job's list row and open detail view.
+    pub(super) async fn update_live_job_todos(&mut self, job_id: &str, todos: Vec<TodoItem>) {
+        self.track_live_job(job_id).await;
+        if let Some(summary) = self.job_view.summaries.iter_mut().find(|j| j.id == job_id) {
+            summary.todo_list = todos.clone();
+        }
+        if !self.viewing_job(job_id) {
+            return;
+        }
+        if let Some(detail) = self.job_view.detail.as_mut() {
+            let follow = self.job_detail_scroll >= last_entry_line_offset(detail);
+            detail.todo_list = todos;
+            if follow {
+                self.job_detail_scroll = last_entry_line_offset(detail);
+            }
+        }
+    }
+
+    /// Reload a job once it finishes (status, handoff note) for its list row
+    /// and the open detail view.
+    pub(super) async fn reload_live_job_detail(&mut self, job_id: &str) {
+        let listed = self.job_view.summaries.iter().any(|j| j.id == job_id);
+        if !listed && !self.viewing_job(job_id) {
+            return;
+        }
+        let Some(db) = &self.db else {
+            return;
+        };
+        let Ok(Some(log)) = JobLogRepository::get(db.pool(), job_id).await else {
+            return;
+        };
+        if let Some(summary) = self.job_view.summaries.iter_mut().find(|j| j.id == job_id) {
+            summary.status = log.status.clone();
+            summary.finished_at = log.finished_at;
+            summary.todo_list = log.todo_list.clone();
+        }
+        if self.viewing_job(job_id) {
+            self.job_view.detail = Some(log);
+        }
+    }
+
+    /// ID of the open or selected job when it is a running heartbeat or
+    /// reflection, which the gateway can cancel.
+    pub(super) fn cancellable_job(&self) -> Option<&str> {
+        let (kind, finished_at, id) = match &self.content_view {
+            ContentView::JobDetail { .. } => {
+                let job = self.job_view.detail.as_ref()?;
+                (job.job_kind, job.finished_at, job.id.as_str())
+            }
+            ContentView::List => {
+                let idx = self
+                    .content_idx
+                    .checked_sub(self.job_view.definition_count())?;
+                let job = self.job_view.summaries.get(idx)?;
+                (job.job_kind, job.finished_at, job.id.as_str())
+            }
+            _ => return None,
+        };
+        let cancellable = matches!(kind, DbJobKind::Heartbeat | DbJobKind::Reflection);
+        (cancellable && finished_at.is_none()).then_some(id)
+    }
+
+    pub(super) async fn cancel_selected_job(&mut self) {
+        let Some(job_id) = self.cancellable_job().map(str::to_string) else {
+            self.status = "No running heartbeat or reflection selected".to_string();
+            return;
+        };
+        match self.ws_query(WsMessage::CancelJob { job_id }).await {
+            Ok(WsResponse::JobCancelled { job_id }) => {
+                self.status = format!("Cancelling {}", job_id);
+            }
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Cancel: {}", message.text_fallback);
+            }
+            Ok(_) => self.status = "Unexpected cancel response".to_string(),
+            Err(e) => self.status = format!("Cancel: {}", e),
+        }
+    }
+
     // ── Session viewer actions ───────────────────────────────────────
 
     pub(super) async fn drill_into_ghost_sessions(&mut self) {
@@ -1028,21 +1898,366 @@ impl TuiApp {
             _ => "?".to_string(),
         };
 
+        if let Some(messages) = self.load_session_messages(&ghost_name, &session_id).await {
+            self.session_view.scroll =
+                last_message_line_offset(&messages, self.session_view.tools_expanded);
+            self.session_view.messages = messages;
+            self.content_view = ContentView::SessionMessages {
+                ghost_name,
+                session_id,
+            };
+        }
+    }
+
+    /// Fetch a session's full history from the gateway and reset the
+    /// transcript search.
+    pub(super) async fn load_session_messages(
+        &mut self,
+        ghost_name: &str,
+        session_id: &str,
+    ) -> Option<Vec<Message>> {
+        match self
+            .ws_query(WsMessage::GetSessionMessages {
+                ghost_name: ghost_name.to_string(),
+                session_id: session_id.to_string(),
+            })
+            .await
+        {
+            Ok(WsResponse::SessionMessages {
+                session_id,
+                messages,
+            }) => {
+                self.session_view.search = None;
+                self.session_view.search_matches.clear();
+                self.session_view.search_idx = 0;
+                Some(
+                    messages
+                        .into_iter()
+                        .map(|info| message_from_info(info, &session_id))
+                        .collect(),
+                )
+            }
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Messages fetch failed: {}", message.text_fallback);
+                None
+            }
+            Ok(_) => {
+                self.status = "Unexpected messages response".to_string();
+                None
+            }
+            Err(e) => {
+                self.status = format!("Messages fetch failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Search the viewed transcript and jump to the first match (blank clears).
+    pub(super) fn search_transcript_view(&mut self, query: &str) {
+        let view = &mut self.session_view;
+        let lines = transcript_lines(&view.messages, view.tools_expanded);
+        view.search_matches = search_transcript(&lines, query);
+        view.search_idx = 0;
+        view.search = (!query.is_empty()).then(|| query.to_string());
+        self.status = match (&view.search, view.search_matches.first()) {
+            (None, _) => "Search cleared".to_string(),
+            (Some(query), None) => format!("No matches for '{}'", query),
+            (Some(query), Some(&line)) => {
+                view.scroll = line;
+                format!("1/{} matches for '{}'", view.search_matches.len(), query)
+            }
+        };
+    }
+
+    /// Jump to the next (or previous) transcript search match, wrapping around.
+    pub(super) fn next_transcript_match(&mut self, forward: bool) {
+        let view = &mut self.session_view;
+        let count = view.search_matches.len();
+        if count == 0 {
+            self.status = "No search matches".to_string();
+            return;
+        }
+        view.search_idx = if forward {
+            (view.search_idx + 1) % count
+        } else {
+            (view.search_idx + count - 1) % count
+        };
+        view.scroll = view.search_matches[view.search_idx];
+        self.status = format!("{}/{} matches", view.search_idx + 1, count);
+    }
+
+    /// Fold or unfold tool calls, keeping the message at the top in view.
+    pub(super) fn toggle_transcript_tools(&mut self) {
+        let view = &mut self.session_view;
+        let top = message_index_at_line(&view.messages, view.scroll, view.tools_expanded);
+        view.tools_expanded = !view.tools_expanded;
+        view.scroll = top
+            .map(|index| {
+                message_line_offset(
+                    &view.messages,
+                    &view.messages[index].id,
+                    view.tools_expanded,
+                )
+            })
+            .unwrap_or(0);
+        if let Some(query) = view.search.clone() {
+            let lines = transcript_lines(&view.messages, view.tools_expanded);
+            view.search_matches = search_transcript(&lines, &query);
+            view.search_idx = 0;
+        }
+        self.status = if view.tools_expanded {
+            "Tool calls expanded".to_string()
+        } else {
+            "Tool calls folded".to_string()
+        };
+    }
+
+    /// Export the viewed transcript to `<session_id>.md` in the working directory.
+    pub(super) fn export_transcript_markdown(&mut self) {
+        let ContentView::SessionMessages {
+            ghost_name,
+            session_id,
+        } = &self.content_view
+        else {
+            return;
+        };
+        let markdown = transcript_markdown(&self.session_view.messages, ghost_name, session_id);
+        let path = PathBuf::from(format!("{}.md", session_id));
+        self.status = match fs::write(&path, markdown) {
+            Ok(()) => format!("Exported {} to {}", session_id, path.display()),
+            Err(e) => format!("Markdown export failed: {}", e),
+        };
+    }
+
+    /// Copy the text of the last GHOST reply in the viewed transcript.
+    pub(super) fn copy_last_ghost_reply(&mut self) {
+        match last_ghost_text(&self.session_view.messages) {
+            Some(text) => self.copy_to_clipboard("last reply", &text),
+            None => self.status = "No GHOST reply to copy".to_string(),
+        }
+    }
+
+    /// Copy the transcript as laid out in the viewer (tool folding included).
+    pub(super) fn copy_transcript(&mut self) {
+        let view = &self.session_view;
+        let text = transcript_lines(&view.messages, view.tools_expanded)
+            .into_iter()
+            .map(|line| line.text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.copy_to_clipboard("transcript", &text);
+    }
+
+    /// Copy the first code block at or below the top of the viewport, or the
+    /// last one above it.
+    pub(super) fn copy_code_block(&mut self) {
+        let view = &self.session_view;
+        let blocks = code_blocks(&transcript_lines(&view.messages, view.tools_expanded));
+        let block = blocks
+            .iter()
+            .find(|block| block.end >= view.scroll)
+            .or(blocks.last());
+        match block {
+            Some(block) => {
+                let (text, line) = (block.text.clone(), block.start + 1);
+                self.copy_to_clipboard(&format!("code block (line {})", line), &text);
+            }
+            None => self.status = "No code block in this transcript".to_string(),
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, what: &str, text: &str) {
+        self.status = match clipboard::copy(text) {
+            Ok(via) => format!(
+                "Copied {} ({} chars) via {}",
+                what,
+                text.chars().count(),
+                via
+            ),
+            Err(e) => format!("Copy failed: {}", e),
+        };
+    }
+
+    /// Export the selected session to `<session_id>.jsonl` in the working directory.
+    pub(super) async fn export_selected_session(&mut self) {
+        let Some(session_id) = self
+            .session_view
+            .sessions
+            .get(self.content_idx)
+            .map(|s| s.id.clone())
+        else {
+            self.status = "No session selected".to_string();
+            return;
+        };
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+
+        let jsonl = match SessionRepository::export(db.pool(), &session_id).await {
+            Ok(jsonl) => jsonl,
+            Err(e) => {
+                self.status = format!("Session export failed: {}", e);
+                return;
+            }
+        };
+        let path = PathBuf::from(format!("{}.jsonl", session_id));
+        self.status = match fs::write(&path, jsonl) {
+            Ok(()) => format!("Exported {} to {}", session_id, path.display()),
+            Err(e) => format!("Session export failed: {}", e),
+        };
+    }
+
+    /// Import a JSONL session export into `ghost_name` (owned by the ghost's operator).
+    pub(super) async fn import_session_file(&mut self, ghost_name: &str, path: &str) {
+        let jsonl = match fs::read_to_string(path) {
+            Ok(jsonl) => jsonl,
+            Err(e) => {
+                self.status = format!("Failed to read {}: {}", path, e);
+                return;
+            }
+        };
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        let ghost = match GhostRepository::get_by_name(db.pool(), ghost_name).await {
+            Ok(Some(ghost)) => ghost,
+            Ok(None) => {
+                self.status = format!("Unknown ghost: {}", ghost_name);
+                return;
+            }
+            Err(e) => {
+                self.status = format!("Ghost lookup failed: {}", e);
+                return;
+            }
+        };
+
+        match SessionRepository::import(db.pool(), &ghost.id, &ghost.owner_operator_id, &jsonl)
+            .await
+        {
+            Ok(session) => {
+                if let Ok(sessions) = SessionRepository::list_for_ghost(db.pool(), &ghost.id).await
+                {
+                    self.session_view.sessions = sessions;
+                }
+                self.status = format!("Imported session {}", session.id);
+            }
+            Err(e) => self.status = format!("Session import failed: {}", e),
+        }
+    }
+
+    /// Fork the viewed session at the message shown at the top of the view.
+    pub(super) async fn fork_viewed_session(&mut self) {
+        let ContentView::SessionMessages {
+            ghost_name,
+            session_id,
+        } = &self.content_view
+        else {
+            return;
+        };
+        let Some(index) = message_index_at_line(
+            &self.session_view.messages,
+            self.session_view.scroll,
+            self.session_view.tools_expanded,
+        ) else {
+            self.status = "No message to fork at".to_string();
+            return;
+        };
+        let message_id = self.session_view.messages[index].id.clone();
+        let (ghost_name, session_id) = (ghost_name.clone(), session_id.clone());
+
         let Some(db) = &self.db else {
             self.status = "DB unavailable".to_string();
             return;
         };
 
-        match SessionRepository::get_messages(db.pool(), &session_id).await {
-            Ok(messages) => {
-                self.session_view.scroll = last_message_line_offset(&messages);
+        match SessionRepository::fork(db.pool(), &session_id, &message_id).await {
+            Ok(fork) => {
+                let messages = SessionRepository::get_messages(db.pool(), &fork.id)
+                    .await
+                    .unwrap_or_default();
+                if let Ok(sessions) =
+                    SessionRepository::list_for_ghost(db.pool(), &fork.ghost_id).await
+                {
+                    self.session_view.sessions = sessions;
+                }
+                self.session_view.scroll =
+                    last_message_line_offset(&messages, self.session_view.tools_expanded);
                 self.session_view.messages = messages;
+                self.session_view.search = None;
+                self.session_view.search_matches.clear();
+                self.status = format!(
+                    "Forked {} at message {} -> {}",
+                    session_id,
+                    index + 1,
+                    fork.id
+                );
                 self.content_view = ContentView::SessionMessages {
                     ghost_name,
-                    session_id,
+                    session_id: fork.id,
+                };
+            }
+            Err(e) => self.status = format!("Session fork failed: {}", e),
+        }
+    }
+
+    pub(super) async fn search_sessions(&mut self, ghost_name: &str, query: &str) {
+        let Some(db) = &self.db else {
+            self.status = "DB unavailable".to_string();
+            return;
+        };
+        let ghost = match GhostRepository::get_by_name(db.pool(), ghost_name).await {
+            Ok(Some(ghost)) => ghost,
+            Ok(None) => {
+                self.status = format!("Unknown ghost: {}", ghost_name);
+                return;
+            }
+            Err(e) => {
+                self.status = format!("Ghost lookup failed: {}", e);
+                return;
+            }
+        };
+
+        let filters = MessageSearchFilters {
+            limit: Some(50),
+            ..Default::default()
+        };
+        match SessionRepository::search_messages(db.pool(), &ghost.id, query, &filters).await {
+            Ok(hits) => {
+                self.status = format!("{} matches for '{}'", hits.len(), query);
+                self.session_view.search_hits = hits;
+                self.content_view = ContentView::SessionSearch {
+                    ghost_name: ghost_name.to_string(),
+                    query: query.to_string(),
                 };
+                self.content_idx = 0;
             }
-            Err(e) => self.status = format!("Messages fetch failed: {}", e),
+            Err(e) => self.status = format!("Session search failed: {}", e),
+        }
+    }
+
+    /// Open the session of the selected search hit, scrolled to the matching message.
+    pub(super) async fn open_session_search_hit(&mut self) {
+        let Some(hit) = self.session_view.search_hits.get(self.content_idx) else {
+            return;
+        };
+        let (session_id, message_id) = (hit.session_id.clone(), hit.message_id.clone());
+        let ghost_name = match &self.content_view {
+            ContentView::SessionSearch { ghost_name, .. } => ghost_name.clone(),
+            _ => "?".to_string(),
+        };
+
+        if let Some(messages) = self.load_session_messages(&ghost_name, &session_id).await {
+            self.session_view.scroll =
+                message_line_offset(&messages, &message_id, self.session_view.tools_expanded);
+            self.session_view.messages = messages;
+            self.content_view = ContentView::SessionMessages {
+                ghost_name,
+                session_id,
+            };
         }
     }
 
@@ -1053,11 +2268,12 @@ impl TuiApp {
         self.ghosts.first().map(|g| g.ghost.name.clone())
     }
 
+    /// Reload the browser: recent notes across scopes plus reference topics.
     pub(super) async fn refresh_knowledge_recent(&mut self) {
         match self
             .ws_query(WsMessage::ListRecentNotes {
                 ghost_name: self.first_ghost_name(),
-                limit: Some(50),
+                limit: Some(200),
             })
             .await
         {
@@ -1065,6 +2281,7 @@ impl TuiApp {
                 self.knowledge_view.notes = notes;
                 self.knowledge_view.detail_title = None;
                 self.knowledge_view.detail_body = None;
+                self.knowledge_view.previews.clear();
                 self.content_idx = 0;
                 self.status = format!("{} notes loaded", self.knowledge_view.notes.len());
             }
@@ -1072,10 +2289,36 @@ impl TuiApp {
                 if message.kind == GatewayMessageKind::Error =>
             {
                 self.status = format!("Knowledge: {}", message.text_fallback);
+                return;
+            }
+            Ok(_) => {
+                self.status = "Unexpected knowledge response".to_string();
+                return;
+            }
+            Err(e) => {
+                self.status = format!("Knowledge: {}", e);
+                return;
+            }
+        }
+
+        match self
+            .ws_query(WsMessage::ListKnowledgeTopics {
+                include_archived: true,
+            })
+            .await
+        {
+            Ok(WsResponse::KnowledgeTopics { topics }) => {
+                self.knowledge_view.topics = topics;
+            }
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Topics: {}", message.text_fallback);
             }
-            Ok(_) => self.status = "Unexpected knowledge response".to_string(),
-            Err(e) => self.status = format!("Knowledge: {}", e),
+            Ok(_) => self.status = "Unexpected topics response".to_string(),
+            Err(e) => self.status = format!("Topics: {}", e),
         }
+        self.load_knowledge_preview().await;
     }
 
     pub(super) async fn search_knowledge(&mut self, query: &str) {
@@ -1094,8 +2337,10 @@ impl TuiApp {
         {
             Ok(WsResponse::KnowledgeSearchResults { results }) => {
                 self.knowledge_view.notes = results;
+                self.knowledge_view.topics.clear();
                 self.content_idx = 0;
                 self.status = format!("{} search results", self.knowledge_view.notes.len());
+                self.load_knowledge_preview().await;
             }
             Ok(WsResponse::Response { message, .. })
                 if message.kind == GatewayMessageKind::Error =>
@@ -1107,32 +2352,202 @@ impl TuiApp {
         }
     }
 
-    pub(super) async fn drill_into_knowledge_entry(&mut self) {
-        let Some(note) = self.knowledge_view.notes.get(self.content_idx) else {
+    pub(super) fn selected_knowledge_row(&self) -> Option<KnowledgeRow> {
+        self.knowledge_view.rows().into_iter().nth(self.content_idx)
+    }
+
+    /// ID of the selected tree row when it is an entry.
+    fn selected_knowledge_id(&self) -> Option<String> {
+        match self.selected_knowledge_row() {
+            Some(KnowledgeRow::Entry { id, .. }) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Apply a new fuzzy filter (blank clears it) and select the first match.
+    pub(super) async fn set_knowledge_filter(&mut self, filter: &str) {
+        self.knowledge_view.filter = (!filter.is_empty()).then(|| filter.to_string());
+        let rows = self.knowledge_view.rows();
+        self.content_idx = rows
+            .iter()
+            .position(|row| matches!(row, KnowledgeRow::Entry { .. }))
+            .unwrap_or(0);
+        self.status = match &self.knowledge_view.filter {
+            Some(filter) => format!("Filter \"{}\": {} rows", filter, rows.len()),
+            None => "Filter cleared".to_string(),
+        };
+        self.load_knowledge_preview().await;
+    }
+
+    /// Collapse or expand the selected group or topic.
+    pub(super) fn toggle_knowledge_node(&mut self) {
+        let key = match self.selected_knowledge_row() {
+            Some(KnowledgeRow::Group { key, .. }) | Some(KnowledgeRow::Topic { key, .. }) => key,
+            _ => return,
+        };
+        if !self.knowledge_view.collapsed.remove(&key) {
+            self.knowledge_view.collapsed.insert(key);
+        }
+    }
+
+    /// Fetch the selected entry for the preview pane unless already loaded.
+    pub(super) async fn load_knowledge_preview(&mut self) {
+        let Some(id) = self.selected_knowledge_id() else {
             return;
         };
-        let note_id = note.id.clone();
+        if self.knowledge_view.previews.contains_key(&id) {
+            return;
+        }
+        if let Some(preview) = self.fetch_knowledge_entry(&id).await {
+            self.knowledge_view.previews.insert(id, preview);
+        }
+    }
 
+    async fn fetch_knowledge_entry(&mut self, id: &str) -> Option<KnowledgePreview> {
         match self
             .ws_query(WsMessage::GetKnowledgeEntry {
-                id: note_id.clone(),
+                id: id.to_string(),
                 max_chars: None,
             })
             .await
         {
-            Ok(WsResponse::KnowledgeEntry { title, body, .. }) => {
-                self.knowledge_view.detail_title = Some(title);
-                self.knowledge_view.detail_body = Some(body);
-                self.knowledge_view.scroll = 0;
-                self.content_view = ContentView::KnowledgeDetail { note_id };
-            }
+            Ok(WsResponse::KnowledgeEntry {
+                title,
+                entry_type,
+                body,
+                path,
+                trust_score,
+                ..
+            }) => Some(KnowledgePreview {
+                title,
+                entry_type,
+                body,
+                path,
+                trust_score,
+            }),
             Ok(WsResponse::Response { message, .. })
                 if message.kind == GatewayMessageKind::Error =>
             {
                 self.status = format!("Knowledge: {}", message.text_fallback);
+                None
+            }
+            Ok(_) => {
+                self.status = "Unexpected knowledge response".to_string();
+                None
             }
-            Ok(_) => self.status = "Unexpected knowledge response".to_string(),
-            Err(e) => self.status = format!("Knowledge: {}", e),
+            Err(e) => {
+                self.status = format!("Knowledge: {}", e);
+                None
+            }
+        }
+    }
+
+    pub(super) async fn drill_into_knowledge_entry(&mut self) {
+        let Some(note_id) = self.selected_knowledge_id() else {
+            return;
+        };
+        self.load_knowledge_preview().await;
+        let Some(preview) = self.knowledge_view.previews.get(&note_id) else {
+            return;
+        };
+        self.knowledge_view.detail_title = Some(preview.title.clone());
+        self.knowledge_view.detail_body = Some(preview.body.clone());
+        self.knowledge_view.scroll = 0;
+        self.content_view = ContentView::KnowledgeDetail { note_id };
+    }
+
+    /// Open the selected entry's file in `$EDITOR`. The gateway's knowledge
+    /// watcher re-indexes the file once it is saved.
+    pub(super) async fn edit_knowledge_entry(&mut self) {
+        let Some(id) = self.selected_knowledge_id() else {
+            self.status = "No knowledge entry selected".to_string();
+            return;
+        };
+        self.load_knowledge_preview().await;
+        let Some(path) = self
+            .knowledge_view
+            .previews
+            .get(&id)
+            .and_then(|p| p.path.clone())
+        else {
+            self.status = "Entry path unknown".to_string();
+            return;
+        };
+        if !Path::new(&path).exists() {
+            self.status = format!("{} is not on this machine", path);
+            return;
+        }
+
+        if let Err(e) = run_editor(&path) {
+            self.status = format!("Editor failed: {}", e);
+            return;
+        }
+        self.knowledge_view.previews.remove(&id);
+        self.load_knowledge_preview().await;
+        self.status = format!("Edited {}", path);
+    }
+
+    pub(super) fn begin_knowledge_prompt(&mut self, kind: PromptKind) {
+        match self.selected_knowledge_id() {
+            Some(id) => self.begin_prompt(kind, None, Some(id)),
+            None => self.status = "No knowledge entry selected".to_string(),
+        }
+    }
+
+    pub(super) async fn delete_knowledge_entry(&mut self, id: &str, confirmation: &str) {
+        if confirmation != "DELETE" {
+            self.status = "Delete aborted".to_string();
+            return;
+        }
+        match self
+            .ws_query(WsMessage::DeleteKnowledgeEntry { id: id.to_string() })
+            .await
+        {
+            Ok(WsResponse::KnowledgeEntryDeleted { id }) => {
+                self.knowledge_view.notes.retain(|n| n.id != id);
+                for topic in &mut self.knowledge_view.topics {
+                    topic.files.retain(|f| f.id != id);
+                }
+                self.knowledge_view.previews.remove(&id);
+                let len = self.knowledge_view.rows().len();
+                self.content_idx = self.content_idx.min(len.saturating_sub(1));
+                self.status = format!("Deleted {}", id);
+            }
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Delete: {}", message.text_fallback);
+            }
+            Ok(_) => self.status = "Unexpected delete response".to_string(),
+            Err(e) => self.status = format!("Delete: {}", e),
+        }
+    }
+
+    pub(super) async fn set_knowledge_trust(&mut self, id: &str, input: &str) {
+        let Ok(trust_score) = input.parse::<i64>() else {
+            self.status = "Trust must be a number from 0 to 10".to_string();
+            return;
+        };
+        match self
+            .ws_query(WsMessage::SetKnowledgeTrust {
+                id: id.to_string(),
+                trust_score,
+            })
+            .await
+        {
+            Ok(WsResponse::KnowledgeTrustSet { id, trust_score }) => {
+                if let Some(preview) = self.knowledge_view.previews.get_mut(&id) {
+                    preview.trust_score = Some(trust_score);
+                }
+                self.status = format!("Trust of {} set to {}", id, trust_score);
+            }
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Trust: {}", message.text_fallback);
+            }
+            Ok(_) => self.status = "Unexpected trust response".to_string(),
+            Err(e) => self.status = format!("Trust: {}", e),
         }
     }
 
@@ -1155,7 +2570,7 @@ impl TuiApp {
 
     // ── WS query helper ──────────────────────────────────────────────
 
-    async fn ws_query(&self, message: WsMessage) -> Result<WsResponse, String> {
+    pub(super) async fn ws_query(&self, message: WsMessage) -> Result<WsResponse, String> {
         let ws_url = ws_url_for_cli(&self.settings.ws_url());
         let (tx, mut rx) = WsClient::connect(&ws_url)
             .await
@@ -1185,7 +2600,8 @@ impl TuiApp {
 }
 
 fn last_entry_line_offset(job: &JobLog) -> u16 {
-    let mut offset: u16 = 3; // header lines: title, status, blank
+    // header lines: title, status, blank, then the TODO section
+    let mut offset: u16 = 3 + todo_section_height(&job.todo_list);
     let mut last_start = offset;
     for entry in &job.transcript {
         last_start = offset;
@@ -1198,18 +2614,30 @@ fn last_entry_line_offset(job: &JobLog) -> u16 {
     last_start
 }
 
-fn last_message_line_offset(messages: &[Message]) -> u16 {
-    let mut offset: u16 = 0;
-    let mut last_start: u16 = 0;
-    for msg in messages {
-        last_start = offset;
-        offset += 1; // role header
-        for block in &msg.content {
-            offset += content_block_line_count(block);
-        }
-        offset += 1; // blank separator
-    }
-    last_start
+fn last_message_line_offset(messages: &[Message], tools_expanded: bool) -> u16 {
+    let lines = transcript_lines(messages, tools_expanded);
+    let last = messages.len().saturating_sub(1);
+    lines.iter().position(|l| l.message == last).unwrap_or(0) as u16
+}
+
+/// First rendered line of the message with `message_id` (0 if absent).
+fn message_line_offset(messages: &[Message], message_id: &str, tools_expanded: bool) -> u16 {
+    let Some(index) = messages.iter().position(|m| m.id == message_id) else {
+        return 0;
+    };
+    transcript_lines(messages, tools_expanded)
+        .iter()
+        .position(|l| l.message == index)
+        .unwrap_or(0) as u16
+}
+
+/// Index of the message rendered at `line` (inverse of the layout above).
+fn message_index_at_line(messages: &[Message], line: u16, tools_expanded: bool) -> Option<usize> {
+    let lines = transcript_lines(messages, tools_expanded);
+    lines
+        .get(usize::from(line))
+        .or(lines.last())
+        .map(|l| l.message)
 }
 
 fn content_block_line_count(block: &ContentBlock) -> u16 {
@@ -1218,13 +2646,86 @@ fn content_block_line_count(block: &ContentBlock) -> u16 {
         ContentBlock::ToolUse { .. }
         | ContentBlock::ToolResult { .. }
         | ContentBlock::Image { .. }
-        | ContentBlock::File { .. } => 1,
+        | ContentBlock::File { .. }
+        | ContentBlock::Thinking { .. } => 1,
+    }
+}
+
+/// Suspend the TUI and edit `path` with `$EDITOR` (default `vi`).
+fn run_editor(path: &str) -> Result<(), String> {
+    terminal::disable_raw_mode().map_err(|e| e.to_string())?;
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    );
+
+    let editor_cmd = format!("${{EDITOR:-vi}} {}", shell_quote(path));
+    let status = Command::new("sh")
+        .arg("-lc")
+        .arg(editor_cmd)
+        .status()
+        .map_err(|e| e.to_string())?;
+
+    let _ = execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    );
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    let _ = execute!(io::stdout(), terminal::Clear(ClearType::All));
+
+    if !status.success() {
+        return Err("Editor exited with non-zero status".to_string());
     }
+    Ok(())
 }
 
 fn is_substantive_response(resp: &WsResponse) -> bool {
     !matches!(
         resp,
-        WsResponse::Pong | WsResponse::GhostList { .. } | WsResponse::GhostSelected { .. }
+        WsResponse::Pong
+            | WsResponse::GhostList { .. }
+            | WsResponse::GhostSelected { .. }
+            | WsResponse::PendingApprovals { .. }
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t_koma_db::MessageRole;
+
+    fn message(id: &str, text: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            session_id: "sess".to_string(),
+            role: MessageRole::Operator,
+            content: vec![ContentBlock::Text {
+                text: text.to_string(),
+            }],
+            model: None,
+            created_at: 0,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn message_index_follows_rendered_lines() {
+        // "a": header + 2 lines + blank = lines 0..4; "b": lines 4..7.
+        let messages = vec![message("a", "one\ntwo"), message("b", "three")];
+        assert_eq!(message_index_at_line(&messages, 0, false), Some(0));
+        assert_eq!(message_index_at_line(&messages, 3, false), Some(0));
+        assert_eq!(message_index_at_line(&messages, 4, false), Some(1));
+        assert_eq!(message_index_at_line(&messages, 99, false), Some(1));
+        assert_eq!(
+            message_index_at_line(&messages, last_message_line_offset(&messages, false), false),
+            Some(1)
+        );
+        assert_eq!(message_index_at_line(&[], 0, false), None);
+        assert_eq!(message_line_offset(&messages, "b", false), 4);
+        assert_eq!(message_line_offset(&messages, "missing", false), 0);
+    }
+}
@@ -0,0 +1,272 @@
+//! Live tool approvals: a gateway connection kept open for the operator's
+//! `PendingApprovals` pushes, and the modal that resolves them. The split chat
+//! view sends its messages over the same connection.
+
+use std::collections::VecDeque;
+
+use futures::StreamExt;
+use t_koma_core::{
+    ApprovalDecision, GatewayMessageKind, PendingApprovalInfo, PendingApprovalKind, WsMessage,
+    WsResponse,
+};
+use tokio::sync::mpsc;
+
+use crate::client::WsClient;
+
+use super::{
+    TuiApp,
+    state::{
+        ApprovalEvent, PromptKind, ReplyRoute, SelectionAction, SelectionItem, SelectionModal,
+    },
+    util::{truncate_for_message, ws_url_for_cli},
+};
+
+impl TuiApp {
+    /// The gateway handles one message per connection at a time, in order, so
+    /// every tracked message is followed by a `Ping`: its `Pong` ends the
+    /// replies to that message and they can be routed to whoever sent it.
+    pub(super) fn start_approvals_stream(&mut self) {
+        let ws_url = ws_url_for_cli(&self.settings.ws_url());
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<(ReplyRoute, WsMessage)>();
+        self.approvals_rx = Some(event_rx);
+        self.approvals_out = Some(out_tx);
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((tx, mut responses)) = WsClient::connect(&ws_url).await else {
+                    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                    continue;
+                };
+                let _ = tx.send(WsMessage::ListPendingApprovals);
+                let mut routes: VecDeque<ReplyRoute> = VecDeque::new();
+
+                loop {
+                    tokio::select! {
+                        response = responses.next() => match response {
+                            Some(WsResponse::PendingApprovals { approvals }) => {
+                                let _ = event_tx.send(ApprovalEvent::Snapshot(approvals));
+                            }
+                            Some(WsResponse::Pong) => {
+                                if let Some(ReplyRoute::ChatPane(pane)) = routes.pop_front() {
+                                    let _ = event_tx.send(ApprovalEvent::ChatDone { pane });
+                                }
+                            }
+                            Some(WsResponse::SessionCreated { session_id }) => {
+                                if let Some(ReplyRoute::ChatPane(pane)) = routes.front() {
+                                    let _ = event_tx.send(ApprovalEvent::ChatSession {
+                                        pane: *pane,
+                                        session_id,
+                                    });
+                                }
+                            }
+                            Some(WsResponse::Response { message, .. }) => {
+                                let event = match routes.front() {
+                                    Some(ReplyRoute::ChatPane(pane)) => ApprovalEvent::ChatReply {
+                                        pane: *pane,
+                                        kind: message.kind,
+                                        text: message.text_fallback,
+                                    },
+                                    _ if message.kind != GatewayMessageKind::Error => {
+                                        ApprovalEvent::Reply(message.text_fallback)
+                                    }
+                                    _ => ApprovalEvent::Reply(format!(
+                                        "Approval failed: {}",
+                                        message.text_fallback
+                                    )),
+                                };
+                                let _ = event_tx.send(event);
+                            }
+                            Some(WsResponse::UsageBudgetExceeded { message, .. }) => {
+                                let event = match routes.front() {
+                                    Some(ReplyRoute::ChatPane(pane)) => ApprovalEvent::ChatReply {
+                                        pane: *pane,
+                                        kind: GatewayMessageKind::Error,
+                                        text: message.text_fallback,
+                                    },
+                                    _ => ApprovalEvent::Reply(message.text_fallback),
+                                };
+                                let _ = event_tx.send(event);
+                            }
+                            Some(_) => {}
+                            None => break,
+                        },
+                        outgoing = out_rx.recv() => match outgoing {
+                            Some((route, message)) => {
+                                let _ = tx.send(message);
+                                if route != ReplyRoute::Untracked {
+                                    let _ = tx.send(WsMessage::Ping);
+                                    routes.push_back(route);
+                                }
+                            }
+                            None => return,
+                        },
+                    }
+                }
+
+                // Approvals seen on a dead connection may be stale.
+                let _ = event_tx.send(ApprovalEvent::Snapshot(Vec::new()));
+                for route in routes.drain(..) {
+                    if let ReplyRoute::ChatPane(pane) = route {
+                        let _ = event_tx.send(ApprovalEvent::ChatReply {
+                            pane,
+                            kind: GatewayMessageKind::Error,
+                            text: "Gateway connection lost".to_string(),
+                        });
+                        let _ = event_tx.send(ApprovalEvent::ChatDone { pane });
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        });
+    }
+
+    pub(super) fn apply_approval_event(&mut self, event: ApprovalEvent) {
+        match event {
+            ApprovalEvent::Snapshot(approvals) => {
+                if approvals.len() > self.pending_approvals.len() {
+                    self.status = format!(
+                        "{} pending approval(s) — press A to review",
+                        approvals.len()
+                    );
+                }
+                self.pending_approvals = approvals;
+            }
+            ApprovalEvent::Reply(text) => {
+                self.status = truncate_for_message(text.lines().next().unwrap_or(""), 160);
+            }
+            ApprovalEvent::ChatReply { pane, kind, text } => {
+                self.append_chat_reply(pane, kind, &text);
+            }
+            ApprovalEvent::ChatSession { pane, session_id } => {
+                self.set_chat_session(pane, session_id);
+            }
+            ApprovalEvent::ChatDone { pane } => self.finish_chat_turn(pane),
+        }
+    }
+
+    /// Open the approval modal for the oldest pending approval.
+    pub(super) fn open_approval_modal(&mut self) {
+        let Some(approval) = self.pending_approvals.first() else {
+            self.status = "No pending approvals".to_string();
+            return;
+        };
+        self.modal = Some(approval_modal(approval));
+    }
+
+    pub(super) fn handle_approval_selection(&mut self, context: Option<String>, value: &str) {
+        let Some((ghost_name, session_id)) = context
+            .as_deref()
+            .and_then(|context| context.split_once(':'))
+        else {
+            return;
+        };
+        let decision = match value {
+            "approve" => ApprovalDecision::Approve,
+            "deny" => ApprovalDecision::Deny,
+            _ => {
+                self.begin_prompt(
+                    PromptKind::ApprovalSteps,
+                    Some(ghost_name.to_string()),
+                    Some(session_id.to_string()),
+                );
+                return;
+            }
+        };
+        self.resolve_approval(ghost_name, session_id, decision);
+    }
+
+    pub(super) fn resolve_approval_steps(
+        &mut self,
+        ghost_name: &str,
+        session_id: &str,
+        input: &str,
+    ) {
+        match input.parse::<usize>() {
+            Ok(steps) if steps > 0 => {
+                self.resolve_approval(ghost_name, session_id, ApprovalDecision::Steps(steps))
+            }
+            _ => self.status = "Steps must be a positive number".to_string(),
+        }
+    }
+
+    fn resolve_approval(&mut self, ghost_name: &str, session_id: &str, decision: ApprovalDecision) {
+        let message = WsMessage::ResolvePendingApproval {
+            ghost_name: ghost_name.to_string(),
+            session_id: session_id.to_string(),
+            decision,
+        };
+        // A resumed turn of a session open in the chat view answers there.
+        let route = self
+            .chat_view
+            .panes
+            .iter()
+            .find(|pane| pane.ghost_name == ghost_name && pane.session_id == session_id)
+            .map_or(ReplyRoute::Status, |pane| ReplyRoute::ChatPane(pane.id));
+        let sent = self.send_operator_message(route, message);
+        let action = match decision {
+            ApprovalDecision::Approve => "approve".to_string(),
+            ApprovalDecision::Deny => "deny".to_string(),
+            ApprovalDecision::Steps(steps) => format!("steps {}", steps),
+        };
+        self.status = if sent {
+            format!("Sent {} for {} — waiting for the ghost", action, ghost_name)
+        } else {
+            "Gateway approval connection is closed".to_string()
+        };
+    }
+}
+
+fn approval_modal(approval: &PendingApprovalInfo) -> SelectionModal {
+    let item = |label: &str, value: &str| SelectionItem {
+        label: label.to_string(),
+        value: value.to_string(),
+    };
+    let (title, items) = match approval.kind {
+        PendingApprovalKind::ToolApproval => (
+            format!("Approve tool for {}?", approval.ghost_name),
+            vec![item("Approve", "approve"), item("Deny", "deny")],
+        ),
+        PendingApprovalKind::ToolLoop => (
+            format!("Continue {}'s tool loop?", approval.ghost_name),
+            vec![
+                item("Approve (continue)", "approve"),
+                item("Deny (stop)", "deny"),
+                item("Steps…", "steps"),
+            ],
+        ),
+    };
+    SelectionModal {
+        title,
+        items,
+        selected_idx: 0,
+        on_select: SelectionAction::ResolveApproval,
+        context: Some(format!("{}:{}", approval.ghost_name, approval.session_id)),
+        detail: Some(approval.summary.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_modal_actions() {
+        let mut approval = PendingApprovalInfo {
+            ghost_name: "alpha".to_string(),
+            session_id: "sess_1".to_string(),
+            kind: PendingApprovalKind::ToolApproval,
+            summary: "Access /etc/hosts?".to_string(),
+        };
+        let modal = approval_modal(&approval);
+        let values: Vec<&str> = modal.items.iter().map(|i| i.value.as_str()).collect();
+        assert_eq!(values, ["approve", "deny"]);
+        assert_eq!(modal.context.as_deref(), Some("alpha:sess_1"));
+        assert_eq!(modal.detail.as_deref(), Some("Access /etc/hosts?"));
+
+        approval.kind = PendingApprovalKind::ToolLoop;
+        let modal = approval_modal(&approval);
+        let values: Vec<&str> = modal.items.iter().map(|i| i.value.as_str()).collect();
+        assert_eq!(values, ["approve", "deny", "steps"]);
+    }
+}
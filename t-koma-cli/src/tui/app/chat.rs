@@ -0,0 +1,380 @@
+//! Split chat view: up to two sessions, possibly of different GHOSTs, side by
+//! side with their own input. Messages go over the operator connection opened
+//! for approvals; replies are routed back to the pane that sent them.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use t_koma_core::{GatewayMessageKind, WsMessage};
+use t_koma_db::{Message, MessageRole};
+
+use crate::{clipboard, commands, tui::state::FocusPane};
+
+use super::{
+    TuiApp,
+    state::{ChatLine, ChatLineKind, ChatPane, ContentView, ReplyRoute},
+    transcript::{TranscriptLineKind, transcript_lines},
+};
+
+/// Panes shown side by side.
+const MAX_CHAT_PANES: usize = 2;
+
+impl TuiApp {
+    /// Open the chat view on the GHOST selected in the Ghosts list: its pane
+    /// when it has one, otherwise a new pane on its active session.
+    pub(super) fn open_chat_for_selected_ghost(&mut self) {
+        let Some(ghost_name) = self
+            .ghosts
+            .get(self.content_idx)
+            .map(|g| g.ghost.name.clone())
+        else {
+            self.status = "No ghost selected".to_string();
+            return;
+        };
+        let session_id = self
+            .chat_view
+            .panes
+            .iter()
+            .find(|pane| pane.ghost_name == ghost_name)
+            .map_or_else(|| "active".to_string(), |pane| pane.session_id.clone());
+        self.open_chat_pane(ghost_name, session_id, &[]);
+    }
+
+    /// Open the session selected in a GHOST's session list in a chat pane,
+    /// with its history.
+    pub(super) async fn open_chat_for_selected_session(&mut self) {
+        let ContentView::GhostSessions { ghost_name, .. } = &self.content_view else {
+            return;
+        };
+        let ghost_name = ghost_name.clone();
+        let Some(session_id) = self
+            .session_view
+            .sessions
+            .get(self.content_idx)
+            .map(|s| s.id.clone())
+        else {
+            self.status = "No session selected".to_string();
+            return;
+        };
+        let history = self
+            .load_session_messages(&ghost_name, &session_id)
+            .await
+            .unwrap_or_default();
+        self.open_chat_pane(ghost_name, session_id, &history);
+    }
+
+    /// Focus the pane already showing this session, or open it in a free pane
+    /// (replacing the focused one when both are taken).
+    fn open_chat_pane(&mut self, ghost_name: String, session_id: String, history: &[Message]) {
+        let view = &mut self.chat_view;
+        if let Some(idx) = view
+            .panes
+            .iter()
+            .position(|p| p.ghost_name == ghost_name && p.session_id == session_id)
+        {
+            view.focused = idx;
+        } else {
+            let mut lines = history_lines(history);
+            if lines.is_empty() {
+                let session = if session_id == "active" {
+                    "active session".to_string()
+                } else {
+                    format!("session {}", short_id(&session_id))
+                };
+                lines.push(muted(format!(
+                    "Chatting with {} in its {}",
+                    ghost_name, session
+                )));
+            }
+            view.next_id += 1;
+            let pane = ChatPane {
+                id: view.next_id,
+                ghost_name,
+                session_id,
+                lines,
+                input: String::new(),
+                attachments: Vec::new(),
+                scroll: 0,
+                in_flight: 0,
+            };
+            if view.panes.len() < MAX_CHAT_PANES {
+                view.panes.push(pane);
+                view.focused = view.panes.len() - 1;
+            } else {
+                view.panes[view.focused] = pane;
+            }
+        }
+        self.content_view = ContentView::Chat;
+        self.focus = FocusPane::Content;
+    }
+
+    pub(super) fn handle_chat_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let view = &mut self.chat_view;
+        let Some(pane) = view.panes.get_mut(view.focused) else {
+            if key.code == KeyCode::Esc {
+                self.content_view = ContentView::List;
+            }
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.content_view = ContentView::List,
+            KeyCode::Tab | KeyCode::BackTab => {
+                view.focused = (view.focused + 1) % view.panes.len();
+            }
+            KeyCode::Enter => self.send_chat_input(),
+            KeyCode::Backspace => {
+                pane.input.pop();
+            }
+            KeyCode::PageUp => pane.scroll = pane.scroll.saturating_add(10),
+            KeyCode::PageDown => pane.scroll = pane.scroll.saturating_sub(10),
+            KeyCode::Char('w') if ctrl => self.close_chat_pane(),
+            KeyCode::Char('v') if ctrl => match clipboard::paste() {
+                Ok(text) => pane.input.push_str(&text),
+                Err(e) => self.status = format!("Paste failed: {}", e),
+            },
+            KeyCode::Char('u') if ctrl => pane.input.clear(),
+            KeyCode::Char(c) if !ctrl && !alt => pane.input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Pasted text goes into the focused pane's input as is.
+    pub(super) fn paste_into_chat(&mut self, text: &str) -> bool {
+        let view = &mut self.chat_view;
+        match view.panes.get_mut(view.focused) {
+            Some(pane) => {
+                pane.input.push_str(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn send_chat_input(&mut self) {
+        let view = &mut self.chat_view;
+        let running = view.turns.front().copied();
+        let Some(pane) = view.panes.get_mut(view.focused) else {
+            return;
+        };
+        let content = pane.input.trim().to_string();
+        if content.is_empty() {
+            return;
+        }
+        if let Some(path) = commands::attach_command(&content) {
+            pane.input.clear();
+            match path.and_then(|path| commands::read_attachment(&path)) {
+                Ok(attachment) => {
+                    pane.lines.push(muted(format!(
+                        "Attached {} to the next message",
+                        attachment.filename
+                    )));
+                    pane.attachments.push(attachment);
+                }
+                Err(e) => pane.lines.push(error(e)),
+            }
+            return;
+        }
+        let id = pane.id;
+
+        // The gateway stops whichever turn is running on the connection.
+        let route = if content.eq_ignore_ascii_case("stop") && pane.in_flight > 0 {
+            if running != Some(id) {
+                self.status = format!("{} is queued behind the other pane", pane.ghost_name);
+                return;
+            }
+            ReplyRoute::Untracked
+        } else {
+            ReplyRoute::ChatPane(id)
+        };
+        let attachments = match route {
+            ReplyRoute::ChatPane(_) => std::mem::take(&mut pane.attachments),
+            _ => Vec::new(),
+        };
+        let message = WsMessage::Chat {
+            ghost_name: pane.ghost_name.clone(),
+            session_id: pane.session_id.clone(),
+            content: content.clone(),
+            attachments,
+        };
+
+        pane.input.clear();
+        pane.scroll = 0;
+        for (idx, line) in content.lines().enumerate() {
+            let prefix = if idx == 0 { "› " } else { "  " };
+            pane.lines.push(ChatLine {
+                kind: ChatLineKind::Operator,
+                text: format!("{}{}", prefix, line),
+            });
+        }
+        if !self.send_operator_message(route, message) {
+            self.status = "Gateway connection is closed".to_string();
+            if let Some(pane) = self.chat_pane_mut(id) {
+                pane.lines
+                    .push(error("Not sent: gateway connection is closed".to_string()));
+            }
+        }
+    }
+
+    /// Send `message` on the operator connection, tracking chat turns.
+    /// Returns false when the connection task is gone.
+    pub(super) fn send_operator_message(&mut self, route: ReplyRoute, message: WsMessage) -> bool {
+        let sent = self
+            .approvals_out
+            .as_ref()
+            .is_some_and(|out| out.send((route, message)).is_ok());
+        if sent && let ReplyRoute::ChatPane(id) = route {
+            self.chat_view.turns.push_back(id);
+            if let Some(pane) = self.chat_pane_mut(id) {
+                pane.in_flight += 1;
+            }
+        }
+        sent
+    }
+
+    fn close_chat_pane(&mut self) {
+        let view = &mut self.chat_view;
+        if view.focused < view.panes.len() {
+            let pane = view.panes.remove(view.focused);
+            self.status = format!("Closed chat with {}", pane.ghost_name);
+        }
+        view.focused = view.focused.min(view.panes.len().saturating_sub(1));
+        if view.panes.is_empty() {
+            self.content_view = ContentView::List;
+        }
+    }
+
+    pub(super) fn append_chat_reply(&mut self, pane: u64, kind: GatewayMessageKind, text: &str) {
+        let Some(pane) = self.chat_pane_mut(pane) else {
+            return;
+        };
+        let line_kind = match kind {
+            GatewayMessageKind::AssistantText => ChatLineKind::Ghost,
+            GatewayMessageKind::Error => ChatLineKind::Error,
+            _ => ChatLineKind::Muted,
+        };
+        pane.lines.extend(text.lines().map(|line| ChatLine {
+            kind: line_kind,
+            text: line.to_string(),
+        }));
+    }
+
+    pub(super) fn set_chat_session(&mut self, pane: u64, session_id: String) {
+        if let Some(pane) = self.chat_pane_mut(pane) {
+            pane.lines
+                .push(muted(format!("New session {}", short_id(&session_id))));
+            pane.session_id = session_id;
+        }
+    }
+
+    pub(super) fn finish_chat_turn(&mut self, pane: u64) {
+        let view = &mut self.chat_view;
+        if let Some(idx) = view.turns.iter().position(|id| *id == pane) {
+            view.turns.remove(idx);
+        }
+        if let Some(pane) = self.chat_pane_mut(pane) {
+            pane.in_flight = pane.in_flight.saturating_sub(1);
+            pane.lines.push(ChatLine {
+                kind: ChatLineKind::Muted,
+                text: String::new(),
+            });
+        }
+    }
+
+    fn chat_pane_mut(&mut self, id: u64) -> Option<&mut ChatPane> {
+        self.chat_view.panes.iter_mut().find(|pane| pane.id == id)
+    }
+}
+
+/// Stored messages as chat lines: text as sent, tool activity folded.
+fn history_lines(messages: &[Message]) -> Vec<ChatLine> {
+    transcript_lines(messages, false)
+        .into_iter()
+        .filter(|line| line.kind != TranscriptLineKind::Header)
+        .map(|line| {
+            let role = messages[line.message].role;
+            let kind = match line.kind {
+                TranscriptLineKind::Text if role == MessageRole::Operator => ChatLineKind::Operator,
+                TranscriptLineKind::Text => ChatLineKind::Ghost,
+                TranscriptLineKind::ToolError => ChatLineKind::Error,
+                _ => ChatLineKind::Muted,
+            };
+            let text = if kind == ChatLineKind::Operator {
+                format!("› {}", line.text)
+            } else {
+                line.text
+            };
+            ChatLine { kind, text }
+        })
+        .collect()
+}
+
+fn muted(text: String) -> ChatLine {
+    ChatLine {
+        kind: ChatLineKind::Muted,
+        text,
+    }
+}
+
+fn error(text: String) -> ChatLine {
+    ChatLine {
+        kind: ChatLineKind::Error,
+        text,
+    }
+}
+
+pub(super) fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t_koma_db::ContentBlock;
+
+    #[test]
+    fn test_history_lines_mark_roles_and_fold_tools() {
+        let message = |role, content| Message {
+            id: "m".to_string(),
+            session_id: "sess".to_string(),
+            role,
+            content,
+            model: None,
+            created_at: 0,
+            usage: None,
+        };
+        let messages = vec![
+            message(
+                MessageRole::Operator,
+                vec![ContentBlock::Text {
+                    text: "ping".to_string(),
+                }],
+            ),
+            message(
+                MessageRole::Ghost,
+                vec![
+                    ContentBlock::ToolUse {
+                        id: "tu".to_string(),
+                        name: "web_search".to_string(),
+                        input: serde_json::json!({}),
+                    },
+                    ContentBlock::Text {
+                        text: "pong".to_string(),
+                    },
+                ],
+            ),
+        ];
+        let lines = history_lines(&messages);
+        let summary: Vec<(ChatLineKind, &str)> =
+            lines.iter().map(|l| (l.kind, l.text.as_str())).collect();
+        assert_eq!(
+            summary,
+            [
+                (ChatLineKind::Operator, "› ping"),
+                (ChatLineKind::Muted, ""),
+                (ChatLineKind::Muted, "  ⚙ web_search({})"),
+                (ChatLineKind::Ghost, "pong"),
+                (ChatLineKind::Muted, ""),
+            ]
+        );
+    }
+}
@@ -1,10 +1,17 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use t_koma_core::KeyPreset;
 
-use crate::tui::state::{Category, FocusPane, GateFilter};
+use crate::{
+    clipboard,
+    tui::state::{Category, FocusPane, GateFilter},
+};
 
 use super::{
     TuiApp,
-    state::{ContentView, PromptKind},
+    input_onboarding::handle_char_input,
+    keymap::{KeyAction, PaletteCommand, parse_palette_command},
+    state::{ContentView, KnowledgeRow, PromptKind},
+    util::join_pasted_lines,
 };
 
 impl TuiApp {
@@ -19,31 +26,105 @@ impl TuiApp {
             return;
         }
 
+        if self.prompt.paste.is_some() {
+            self.handle_paste_key(key);
+            return;
+        }
+
         if self.prompt.kind.is_some() {
             self.handle_prompt_key(key).await;
             return;
         }
 
+        // The chat view takes every key as text except its own controls.
+        if self.focus == FocusPane::Content && self.content_view == ContentView::Chat {
+            self.handle_chat_key(key);
+            return;
+        }
+
+        if let Some(action) = self.keymap.action_for(&key)
+            && self.run_key_action(action).await
+        {
+            return;
+        }
+        // Unbound chords must not reach the letter shortcuts (Ctrl+d is not d).
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return;
+        }
+
         match key.code {
-            KeyCode::Char('q') => self.should_exit = true,
-            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => self.should_exit = true,
-            KeyCode::Esc => self.handle_esc(),
-            KeyCode::Tab => self.focus = self.focus.next(self.selected_category().has_options()),
-            KeyCode::Left | KeyCode::Char('h') => {
-                self.focus = self.focus.prev(self.selected_category().has_options())
+            KeyCode::Char('f')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.fork_viewed_session().await;
+            }
+            KeyCode::Char('n')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.next_transcript_match(true);
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.focus = self.focus.next(self.selected_category().has_options())
+            KeyCode::Char('N')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.next_transcript_match(false);
+            }
+            KeyCode::Char('t')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.toggle_transcript_tools();
+            }
+            KeyCode::Char('m')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.export_transcript_markdown();
+            }
+            KeyCode::Char('y')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.copy_last_ghost_reply();
             }
-            KeyCode::Up | KeyCode::Char('k') => self.navigate_up().await,
-            KeyCode::Down | KeyCode::Char('j') => self.navigate_down().await,
-            KeyCode::Char('u') if self.focus == FocusPane::Content => {
-                self.scroll_half_page_up();
+            KeyCode::Char('Y')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.copy_transcript();
             }
-            KeyCode::Char('d') if self.focus == FocusPane::Content => {
-                self.scroll_half_page_down();
+            KeyCode::Char('b')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::SessionMessages { .. }) =>
+            {
+                self.copy_code_block();
+            }
+            KeyCode::Char('e')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::GhostSessions { .. }) =>
+            {
+                self.export_selected_session().await;
+            }
+            KeyCode::Char('o')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::GhostSessions { .. }) =>
+            {
+                self.open_chat_for_selected_session().await;
+            }
+            KeyCode::Char('i')
+                if self.focus == FocusPane::Content
+                    && matches!(self.content_view, ContentView::GhostSessions { .. }) =>
+            {
+                if let ContentView::GhostSessions { ghost_name, .. } = &self.content_view {
+                    let ghost_name = ghost_name.clone();
+                    self.begin_prompt(PromptKind::SessionImport, Some(ghost_name), None);
+                }
             }
-            KeyCode::Enter => self.activate().await,
             KeyCode::Char(c) if self.focus == FocusPane::Options => {
                 if let Some(idx) = self.option_index_for_key(c) {
                     self.options_idx = idx;
@@ -61,9 +142,10 @@ impl TuiApp {
                 }
             }
             // Key priority for unmatched chars:
-            //  1. Context shortcuts (Gate filters, Operator approve/deny)
+            //  1. Context shortcuts (Gate filters, Operator approve/deny, task pause/delete,
+            //     job cancel, knowledge actions)
             //  2. Option letter keys (from Content — same as pressing in Options)
-            //  3. Category number keys 1-6 (from Content — jump + focus Options)
+            //  3. Category number keys 1-7 (from Content — jump + focus Options)
             _ => {
                 if !self.handle_category_shortcuts(key).await
                     && let KeyCode::Char(c) = key.code
@@ -80,6 +162,64 @@ impl TuiApp {
         }
     }
 
+    /// Run a keymap action. Returns `false` when it does not apply here, so
+    /// the key falls through to option letters and view shortcuts.
+    async fn run_key_action(&mut self, action: KeyAction) -> bool {
+        let has_options = self.selected_category().has_options();
+        match action {
+            KeyAction::Quit => self.should_exit = true,
+            KeyAction::Back => self.handle_esc().await,
+            KeyAction::FocusNext => self.focus = self.focus.next(has_options),
+            KeyAction::FocusPrev => self.focus = self.focus.prev(has_options),
+            KeyAction::Up => self.navigate_up().await,
+            KeyAction::Down => self.navigate_down().await,
+            KeyAction::PageUp if self.focus == FocusPane::Content => self.scroll_half_page_up(),
+            KeyAction::PageDown if self.focus == FocusPane::Content => self.scroll_half_page_down(),
+            KeyAction::PageUp | KeyAction::PageDown => return false,
+            KeyAction::Open => self.activate().await,
+            KeyAction::Approvals => self.open_approval_modal(),
+            KeyAction::Search => return self.begin_search(),
+            KeyAction::CommandPalette => self.begin_prompt(PromptKind::CommandPalette, None, None),
+        }
+        true
+    }
+
+    /// Open the search prompt that fits the current view, if any.
+    fn begin_search(&mut self) -> bool {
+        let in_content = self.focus == FocusPane::Content;
+        let kind = match (&self.content_view, self.selected_category()) {
+            (_, Category::Gate) => PromptKind::GateSearch,
+            (ContentView::SessionMessages { .. }, _) if in_content => PromptKind::TranscriptSearch,
+            (ContentView::GhostSessions { ghost_name, .. }, _) if in_content => {
+                let ghost_name = ghost_name.clone();
+                self.begin_prompt(PromptKind::SessionSearch, Some(ghost_name), None);
+                return true;
+            }
+            (ContentView::List, Category::Knowledge) if in_content => PromptKind::KnowledgeFilter,
+            _ => return false,
+        };
+        self.begin_prompt(kind, None, None);
+        true
+    }
+
+    async fn run_palette_command(&mut self, input: &str) {
+        let options = self.options_for(self.selected_category());
+        match parse_palette_command(input, &options) {
+            PaletteCommand::Quit => self.should_exit = true,
+            PaletteCommand::Approvals => self.open_approval_modal(),
+            PaletteCommand::Theme(None) => self.open_theme_modal(),
+            PaletteCommand::Theme(Some(name)) => self.select_theme(&name),
+            PaletteCommand::Category(idx) => self.jump_to_category(idx).await,
+            PaletteCommand::Option(idx) => {
+                self.options_idx = idx;
+                self.focus = FocusPane::Content;
+                self.activate_option().await;
+            }
+            PaletteCommand::Unknown if input.is_empty() => {}
+            PaletteCommand::Unknown => self.status = format!("Unknown command: {}", input),
+        }
+    }
+
     async fn jump_to_category(&mut self, idx: usize) {
         self.category_idx = idx;
         self.options_idx = 0;
@@ -93,12 +233,18 @@ impl TuiApp {
         self.sync_selection().await;
     }
 
-    fn handle_esc(&mut self) {
+    async fn handle_esc(&mut self) {
         if self.content_view != ContentView::List {
             self.pop_content_view();
             return;
         }
-        self.should_exit = true;
+        if self.selected_category() == Category::Knowledge && self.knowledge_view.filter.is_some() {
+            self.set_knowledge_filter("").await;
+            return;
+        }
+        if self.keymap.preset == KeyPreset::Default {
+            self.should_exit = true;
+        }
     }
 
     fn pop_content_view(&mut self) {
@@ -122,6 +268,7 @@ impl TuiApp {
         self.session_view.scroll = 0;
         self.knowledge_view.scroll = 0;
         self.job_detail_scroll = 0;
+        self.config_scroll = 0;
     }
 
     fn scroll_detail_up(&mut self, delta: u16) {
@@ -132,6 +279,9 @@ impl TuiApp {
             ContentView::KnowledgeDetail { .. } => {
                 self.knowledge_view.scroll = self.knowledge_view.scroll.saturating_sub(delta);
             }
+            ContentView::ConfigDiff | ContentView::ConfigBackups => {
+                self.config_scroll = self.config_scroll.saturating_sub(delta);
+            }
             _ => {}
         }
     }
@@ -144,6 +294,9 @@ impl TuiApp {
             ContentView::KnowledgeDetail { .. } => {
                 self.knowledge_view.scroll = self.knowledge_view.scroll.saturating_add(delta);
             }
+            ContentView::ConfigDiff | ContentView::ConfigBackups => {
+                self.config_scroll = self.config_scroll.saturating_add(delta);
+            }
             _ => {}
         }
     }
@@ -158,7 +311,10 @@ impl TuiApp {
     fn scroll_half_page_up(&mut self) {
         let delta = Self::half_page_height();
         match &self.content_view {
-            ContentView::JobDetail { .. } | ContentView::KnowledgeDetail { .. } => {
+            ContentView::JobDetail { .. }
+            | ContentView::KnowledgeDetail { .. }
+            | ContentView::ConfigDiff
+            | ContentView::ConfigBackups => {
                 self.scroll_detail_up(delta);
             }
             ContentView::SessionMessages { .. } => {
@@ -180,7 +336,10 @@ impl TuiApp {
     fn scroll_half_page_down(&mut self) {
         let delta = Self::half_page_height();
         match &self.content_view {
-            ContentView::JobDetail { .. } | ContentView::KnowledgeDetail { .. } => {
+            ContentView::JobDetail { .. }
+            | ContentView::KnowledgeDetail { .. }
+            | ContentView::ConfigDiff
+            | ContentView::ConfigBackups => {
                 self.scroll_detail_down(delta);
             }
             ContentView::SessionMessages { .. } => {
@@ -204,21 +363,21 @@ impl TuiApp {
             return;
         };
 
-        match key.code {
-            KeyCode::Esc => {
+        match self.keymap.action_for(&key) {
+            Some(KeyAction::Back) => {
                 self.modal = None;
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Some(KeyAction::Up) => {
                 if modal.selected_idx > 0 {
                     modal.selected_idx -= 1;
                 }
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Some(KeyAction::Down) => {
                 if modal.selected_idx + 1 < modal.items.len() {
                     modal.selected_idx += 1;
                 }
             }
-            KeyCode::Enter => {
+            Some(KeyAction::Open) => {
                 let modal = self.modal.take().unwrap();
                 self.handle_modal_selection(modal).await;
             }
@@ -247,15 +406,32 @@ impl TuiApp {
                 ContentView::List => match self.selected_category() {
                     Category::Config => self.config_scroll = self.config_scroll.saturating_sub(1),
                     Category::Gate => self.gate_scroll = self.gate_scroll.saturating_sub(1),
+                    Category::Metrics => {
+                        self.usage_view.scroll = self.usage_view.scroll.saturating_sub(1)
+                    }
+                    Category::Knowledge => {
+                        if self.content_idx > 0 {
+                            self.content_idx -= 1;
+                            self.load_knowledge_preview().await;
+                        }
+                    }
                     _ => {
                         if self.content_idx > 0 {
                             self.content_idx -= 1;
                         }
                     }
                 },
-                ContentView::JobDetail { .. } | ContentView::KnowledgeDetail { .. } => {
+                ContentView::JobDetail { .. }
+                | ContentView::KnowledgeDetail { .. }
+                | ContentView::ConfigDiff => {
                     self.scroll_detail_up(1);
                 }
+                ContentView::ConfigBackups => {
+                    if self.content_idx > 0 {
+                        self.content_idx -= 1;
+                        self.load_config_backup_preview();
+                    }
+                }
                 ContentView::SessionMessages { .. } => {
                     self.session_view.scroll = self.session_view.scroll.saturating_sub(1);
                 }
@@ -290,6 +466,9 @@ impl TuiApp {
                 ContentView::List => match self.selected_category() {
                     Category::Config => self.config_scroll = self.config_scroll.saturating_add(1),
                     Category::Gate => self.gate_scroll = self.gate_scroll.saturating_add(1),
+                    Category::Metrics => {
+                        self.usage_view.scroll = self.usage_view.scroll.saturating_add(1)
+                    }
                     Category::Operators => {
                         if self.content_idx + 1 < self.operators.len() {
                             self.content_idx += 1;
@@ -301,30 +480,41 @@ impl TuiApp {
                         }
                     }
                     Category::Jobs => {
-                        let content_len = if self.job_view.mode == super::state::JobViewMode::Cron {
-                            self.job_view.cron_jobs.len() + self.job_view.summaries.len()
-                        } else {
-                            self.job_view.summaries.len()
-                        };
+                        let content_len =
+                            self.job_view.definition_count() + self.job_view.summaries.len();
                         if self.content_idx + 1 < content_len {
                             self.content_idx += 1;
                         }
                     }
                     Category::Knowledge => {
-                        if self.content_idx + 1 < self.knowledge_view.notes.len() {
+                        if self.content_idx + 1 < self.knowledge_view.rows().len() {
                             self.content_idx += 1;
+                            self.load_knowledge_preview().await;
                         }
                     }
                 },
-                ContentView::JobDetail { .. } | ContentView::KnowledgeDetail { .. } => {
+                ContentView::JobDetail { .. }
+                | ContentView::KnowledgeDetail { .. }
+                | ContentView::ConfigDiff => {
                     self.scroll_detail_down(1);
                 }
-                ContentView::KnowledgeStats => {}
+                ContentView::ConfigBackups => {
+                    if self.content_idx + 1 < self.config_backups.len() {
+                        self.content_idx += 1;
+                        self.load_config_backup_preview();
+                    }
+                }
+                ContentView::KnowledgeStats | ContentView::Chat => {}
                 ContentView::GhostSessions { .. } => {
                     if self.content_idx + 1 < self.session_view.sessions.len() {
                         self.content_idx += 1;
                     }
                 }
+                ContentView::SessionSearch { .. } => {
+                    if self.content_idx + 1 < self.session_view.search_hits.len() {
+                        self.content_idx += 1;
+                    }
+                }
                 ContentView::SessionMessages { .. } => {
                     self.session_view.scroll = self.session_view.scroll.saturating_add(1);
                 }
@@ -357,6 +547,14 @@ impl TuiApp {
                 self.drill_into_session_messages().await;
                 return;
             }
+            ContentView::SessionSearch { .. } => {
+                self.open_session_search_hit().await;
+                return;
+            }
+            ContentView::ConfigBackups => {
+                self.restore_selected_backup();
+                return;
+            }
             ContentView::List => {}
             _ => return,
         }
@@ -371,9 +569,11 @@ impl TuiApp {
             Category::Jobs => {
                 self.drill_into_job().await;
             }
-            Category::Knowledge => {
-                self.drill_into_knowledge_entry().await;
-            }
+            Category::Knowledge => match self.selected_knowledge_row() {
+                Some(KnowledgeRow::Entry { .. }) => self.drill_into_knowledge_entry().await,
+                Some(_) => self.toggle_knowledge_node(),
+                None => {}
+            },
             _ => {}
         }
     }
@@ -397,7 +597,9 @@ impl TuiApp {
                 }
                 5 => self.reload_settings(),
                 6 => self.save_settings(),
-                7 => self.restore_backup(),
+                7 => self.open_config_backups(),
+                8 => self.open_config_diff(),
+                9 => self.open_theme_modal(),
                 _ => {}
             },
             Category::Operators => match self.options_idx {
@@ -455,6 +657,54 @@ impl TuiApp {
                         self.status = "No operator selected".to_string();
                     }
                 }
+                7 => {
+                    if let Some(op) = self.operators.get(self.content_idx) {
+                        let (operator_id, access_level) = (op.id.clone(), op.access_level);
+                        self.issue_operator_api_token(&operator_id, access_level)
+                            .await;
+                    } else {
+                        self.status = "No operator selected".to_string();
+                    }
+                }
+                8 => {
+                    if let Some(op) = self.operators.get(self.content_idx) {
+                        let operator_id = op.id.clone();
+                        self.revoke_operator_api_tokens(&operator_id).await;
+                    } else {
+                        self.status = "No operator selected".to_string();
+                    }
+                }
+                9 => {
+                    if let Some(op) = self.operators.get(self.content_idx) {
+                        let operator_id = op.id.clone();
+                        self.show_operator_permissions(&operator_id).await;
+                        self.begin_prompt(
+                            PromptKind::SetOperatorPermissions,
+                            None,
+                            Some(operator_id),
+                        );
+                    } else {
+                        self.status = "No operator selected".to_string();
+                    }
+                }
+                10 => {
+                    if let Some(op) = self.operators.get(self.content_idx) {
+                        let operator_id = op.id.clone();
+                        self.show_tool_policies(&operator_id).await;
+                        self.begin_prompt(PromptKind::SetToolPolicies, None, Some(operator_id));
+                    } else {
+                        self.status = "No operator selected".to_string();
+                    }
+                }
+                11 => {
+                    if let Some(op) = self.operators.get(self.content_idx) {
+                        let operator_id = op.id.clone();
+                        self.show_path_approvals(&operator_id).await;
+                        self.begin_prompt(PromptKind::SetPathApprovals, None, Some(operator_id));
+                    } else {
+                        self.status = "No operator selected".to_string();
+                    }
+                }
                 _ => {}
             },
             Category::Ghosts => match self.options_idx {
@@ -474,13 +724,48 @@ impl TuiApp {
                         self.status = "No ghost selected".to_string();
                     }
                 }
+                4 => {
+                    if let Some(name) = self
+                        .ghosts
+                        .get(self.content_idx)
+                        .map(|g| g.ghost.name.clone())
+                    {
+                        self.begin_prompt(PromptKind::SessionSearch, Some(name), None);
+                    } else {
+                        self.status = "No ghost selected".to_string();
+                    }
+                }
+                5 => {
+                    if let Some(name) = self
+                        .ghosts
+                        .get(self.content_idx)
+                        .map(|g| g.ghost.name.clone())
+                    {
+                        self.begin_prompt(PromptKind::RenameGhost, Some(name), None);
+                    } else {
+                        self.status = "No ghost selected".to_string();
+                    }
+                }
+                6 => {
+                    if let Some(name) = self
+                        .ghosts
+                        .get(self.content_idx)
+                        .map(|g| g.ghost.name.clone())
+                    {
+                        self.begin_prompt(PromptKind::CloneGhost, Some(name), None);
+                    } else {
+                        self.status = "No ghost selected".to_string();
+                    }
+                }
+                7 => self.open_chat_for_selected_ghost(),
                 _ => {}
             },
             Category::Jobs => match self.options_idx {
                 0 => self.refresh_cron_jobs_view().await,
-                1 => self.refresh_jobs(None).await,
+                1 => self.refresh_tasks_view().await,
+                2 => self.refresh_jobs(None).await,
                 idx => {
-                    let ghost_id = self.ghosts.get(idx - 2).map(|g| g.ghost.id.clone());
+                    let ghost_id = self.ghosts.get(idx - 3).map(|g| g.ghost.id.clone());
                     self.refresh_jobs(ghost_id.as_deref()).await;
                 }
             },
@@ -490,16 +775,67 @@ impl TuiApp {
                 2 => self.refresh_knowledge_stats().await,
                 _ => {}
             },
+            Category::Metrics => {
+                if let Some(&days) = super::usage::USAGE_RANGES.get(self.options_idx) {
+                    self.refresh_usage_dashboard(days).await;
+                }
+            }
             Category::Gate => {}
         }
 
         self.refresh_metrics().await;
     }
 
-    /// Context-specific shortcuts (Gate filters, Operator approve/deny).
+    /// Context-specific shortcuts (Gate filters, Operator approve/deny,
+    /// scheduled task pause/delete, job cancel, knowledge browser actions).
     /// Returns `true` if the key was consumed.
     async fn handle_category_shortcuts(&mut self, key: KeyEvent) -> bool {
         if self.selected_category() != Category::Gate {
+            if self.selected_category() == Category::Jobs && self.focus == FocusPane::Content {
+                let on_task = self.content_view == ContentView::List
+                    && self.job_view.mode == super::state::JobViewMode::Tasks
+                    && self.content_idx < self.job_view.tasks.len();
+                match key.code {
+                    KeyCode::Char('p') if on_task => {
+                        self.toggle_selected_task().await;
+                        return true;
+                    }
+                    KeyCode::Char('x') if on_task => {
+                        self.delete_selected_task().await;
+                        return true;
+                    }
+                    KeyCode::Char('x')
+                        if matches!(
+                            self.content_view,
+                            ContentView::List | ContentView::JobDetail { .. }
+                        ) =>
+                    {
+                        self.cancel_selected_job().await;
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
+            if self.selected_category() == Category::Knowledge
+                && self.focus == FocusPane::Content
+                && self.content_view == ContentView::List
+            {
+                match key.code {
+                    KeyCode::Char('e') => {
+                        self.edit_knowledge_entry().await;
+                        return true;
+                    }
+                    KeyCode::Char('x') => {
+                        self.begin_knowledge_prompt(PromptKind::KnowledgeDeleteConfirm);
+                        return true;
+                    }
+                    KeyCode::Char('t') => {
+                        self.begin_knowledge_prompt(PromptKind::KnowledgeSetTrust);
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
             if self.selected_category() == Category::Operators
                 && self.focus == FocusPane::Content
                 && self.operator_view == super::state::OperatorView::Pending
@@ -521,7 +857,7 @@ impl TuiApp {
 
         match key.code {
             KeyCode::Char('r') => self.restart_gateway().await,
-            KeyCode::Char('/') => self.begin_prompt(PromptKind::GateSearch, None, None),
+            KeyCode::Char('l') => self.reload_config().await,
             KeyCode::Char(' ') => {
                 self.gate_paused = !self.gate_paused;
                 self.status = if self.gate_paused {
@@ -558,6 +894,68 @@ impl TuiApp {
         self.prompt.target_operator_id = target_operator_id;
     }
 
+    /// Pasted text (bracketed paste or Ctrl+V). Single lines go straight into
+    /// the open prompt; multi-line text opens the paste editor first.
+    pub(super) fn handle_paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        if self.onboarding.is_some() {
+            for c in text.trim().chars().filter(|c| !c.is_control()) {
+                handle_char_input(&mut self.onboarding, c);
+            }
+            return;
+        }
+        if let Some(paste) = &mut self.prompt.paste {
+            paste.push_str(&text);
+            return;
+        }
+        if self.prompt.kind.is_none() {
+            if !(self.content_view == ContentView::Chat && self.paste_into_chat(&text)) {
+                self.status = "Nothing to paste into: open a prompt first".to_string();
+            }
+            return;
+        }
+        let text = text.trim_end_matches('\n');
+        if text.contains('\n') {
+            self.prompt.paste = Some(text.to_string());
+        } else {
+            self.prompt.buffer.push_str(text);
+        }
+    }
+
+    /// Keys in the multi-line paste editor. Ctrl+S folds the lines into the
+    /// prompt buffer, which stays open for review.
+    fn handle_paste_key(&mut self, key: KeyEvent) {
+        let Some(paste) = &mut self.prompt.paste else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.prompt.paste = None;
+                self.status = "Paste discarded".to_string();
+            }
+            KeyCode::Char('s') if ctrl => {
+                let list = self.prompt.kind.is_some_and(PromptKind::takes_list);
+                let joined = join_pasted_lines(paste, list);
+                self.prompt.buffer.push_str(&joined);
+                self.prompt.paste = None;
+            }
+            KeyCode::Char('v') if ctrl => match clipboard::paste() {
+                Ok(text) => paste.push_str(&text),
+                Err(e) => self.status = format!("Paste failed: {}", e),
+            },
+            KeyCode::Char('u') if ctrl => paste.clear(),
+            KeyCode::Char(_) if ctrl => {}
+            KeyCode::Char(c) => paste.push(c),
+            KeyCode::Enter => paste.push('\n'),
+            KeyCode::Tab => paste.push('\t'),
+            KeyCode::Backspace => {
+                paste.pop();
+            }
+            _ => {}
+        }
+    }
+
     async fn handle_prompt_key(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Esc => {
@@ -566,6 +964,12 @@ impl TuiApp {
             KeyCode::Backspace => {
                 self.prompt.buffer.pop();
             }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                match clipboard::paste() {
+                    Ok(text) => self.handle_paste(&text),
+                    Err(e) => self.status = format!("Paste failed: {}", e),
+                }
+            }
             KeyCode::Char(c) => {
                 self.prompt.buffer.push(c);
             }
@@ -601,9 +1005,82 @@ impl TuiApp {
                             self.status = "No operator selected".to_string();
                         }
                     }
+                    Some(PromptKind::SetOperatorPermissions) => {
+                        if let Some(operator_id) = target_operator_id {
+                            self.set_operator_permissions(&operator_id, &input).await;
+                        } else {
+                            self.status = "No operator selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::SetToolPolicies) => {
+                        if let Some(operator_id) = target_operator_id {
+                            self.set_tool_policies(&operator_id, &input).await;
+                        } else {
+                            self.status = "No operator selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::SetPathApprovals) => {
+                        if let Some(operator_id) = target_operator_id {
+                            self.set_path_approvals(&operator_id, &input).await;
+                        } else {
+                            self.status = "No operator selected".to_string();
+                        }
+                    }
                     Some(PromptKind::KnowledgeSearch) => {
                         self.search_knowledge(&input).await;
                     }
+                    Some(PromptKind::KnowledgeFilter) => {
+                        self.set_knowledge_filter(&input).await;
+                    }
+                    Some(PromptKind::KnowledgeDeleteConfirm) => {
+                        if let Some(id) = target_operator_id {
+                            self.delete_knowledge_entry(&id, &input).await;
+                        } else {
+                            self.status = "No knowledge entry selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::KnowledgeSetTrust) => {
+                        if let Some(id) = target_operator_id {
+                            self.set_knowledge_trust(&id, &input).await;
+                        } else {
+                            self.status = "No knowledge entry selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::RenameGhost) => {
+                        if let Some(ghost_name) = target {
+                            self.rename_ghost(&ghost_name, &input).await;
+                        } else {
+                            self.status = "No ghost selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::CloneGhost) => {
+                        if let Some(ghost_name) = target {
+                            self.clone_ghost(&ghost_name, &input).await;
+                        } else {
+                            self.status = "No ghost selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::SessionImport) => {
+                        if let Some(ghost_name) = target {
+                            self.import_session_file(&ghost_name, &input).await;
+                        } else {
+                            self.status = "No ghost selected".to_string();
+                        }
+                    }
+                    Some(PromptKind::TranscriptSearch) => self.search_transcript_view(&input),
+                    Some(PromptKind::CommandPalette) => self.run_palette_command(&input).await,
+                    Some(PromptKind::ApprovalSteps) => {
+                        if let (Some(ghost_name), Some(session_id)) = (target, target_operator_id) {
+                            self.resolve_approval_steps(&ghost_name, &session_id, &input);
+                        }
+                    }
+                    Some(PromptKind::SessionSearch) => {
+                        if let Some(ghost_name) = target {
+                            self.search_sessions(&ghost_name, &input).await;
+                        } else {
+                            self.status = "No ghost selected".to_string();
+                        }
+                    }
                     Some(PromptKind::AddProviderApiKey) => {
                         if let Some(provider) = target {
                             self.write_provider_api_key(&provider, &input);
@@ -629,18 +1106,25 @@ impl TuiApp {
                 self.refresh_operators().await;
             }
             Category::Ghosts => self.refresh_ghosts().await,
-            Category::Jobs => {
-                if self.options_idx == 0 {
-                    self.refresh_cron_jobs_view().await;
-                } else {
-                    self.refresh_jobs(None).await;
-                }
-            }
+            Category::Jobs => match self.options_idx {
+                0 => self.refresh_cron_jobs_view().await,
+                1 => self.refresh_tasks_view().await,
+                _ => self.refresh_jobs(None).await,
+            },
             Category::Knowledge => {
                 if self.knowledge_view.notes.is_empty() {
                     self.refresh_knowledge_recent().await;
                 }
             }
+            Category::Metrics => {
+                let days = super::usage::USAGE_RANGES
+                    .get(self.options_idx)
+                    .copied()
+                    .unwrap_or(super::usage::USAGE_RANGES[0]);
+                if !self.usage_view.loaded || self.usage_view.since_days != days {
+                    self.refresh_usage_dashboard(days).await;
+                }
+            }
             Category::Config | Category::Gate => {}
         }
         self.refresh_metrics().await;
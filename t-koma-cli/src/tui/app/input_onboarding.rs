@@ -161,7 +161,7 @@ fn handle_selection_down(onboarding: &mut Option<OnboardingState>) {
     }
 }
 
-fn handle_char_input(onboarding: &mut Option<OnboardingState>, c: char) {
+pub(super) fn handle_char_input(onboarding: &mut Option<OnboardingState>, c: char) {
     let Some(ob) = onboarding else { return };
     match ob.step {
         OnboardingStep::EnterApiKey
@@ -0,0 +1,436 @@
+//! Global key bindings from `[cli.keys]`: a preset plus per-action
+//! overrides, and the `:` command palette.
+//!
+//! Only pane-independent keys go through the keymap; option letters,
+//! category numbers and view shortcuts (`f`, `t`, `x`, ...) stay fixed.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use t_koma_core::{KeyPreset, KeySettings};
+
+use crate::tui::state::Category;
+
+use super::{TuiApp, state::OptionDef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum KeyAction {
+    Quit,
+    Back,
+    FocusNext,
+    FocusPrev,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Open,
+    Approvals,
+    Search,
+    CommandPalette,
+}
+
+impl KeyAction {
+    pub(super) const ALL: [KeyAction; 12] = [
+        KeyAction::Quit,
+        KeyAction::Back,
+        KeyAction::FocusNext,
+        KeyAction::FocusPrev,
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::PageUp,
+        KeyAction::PageDown,
+        KeyAction::Open,
+        KeyAction::Approvals,
+        KeyAction::Search,
+        KeyAction::CommandPalette,
+    ];
+
+    /// Name used in `[cli.keys]`.
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Back => "back",
+            Self::FocusNext => "focus_next",
+            Self::FocusPrev => "focus_prev",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::PageUp => "page_up",
+            Self::PageDown => "page_down",
+            Self::Open => "open",
+            Self::Approvals => "approvals",
+            Self::Search => "search",
+            Self::CommandPalette => "command_palette",
+        }
+    }
+
+    fn preset_keys(self, preset: KeyPreset) -> &'static [&'static str] {
+        match (self, preset) {
+            (Self::Quit, _) => &["q", "ctrl+c"],
+            (Self::Back, _) => &["esc"],
+            (Self::FocusNext, _) => &["tab", "l", "right"],
+            (Self::FocusPrev, KeyPreset::Default) => &["h", "left"],
+            (Self::FocusPrev, KeyPreset::Vim) => &["shift+tab", "h", "left"],
+            (Self::Up, _) => &["k", "up"],
+            (Self::Down, _) => &["j", "down"],
+            (Self::PageUp, KeyPreset::Default) => &["u"],
+            (Self::PageUp, KeyPreset::Vim) => &["ctrl+u", "pageup"],
+            (Self::PageDown, KeyPreset::Default) => &["d"],
+            (Self::PageDown, KeyPreset::Vim) => &["ctrl+d", "pagedown"],
+            (Self::Open, _) => &["enter"],
+            (Self::Approvals, _) => &["A"],
+            (Self::Search, _) => &["/"],
+            (Self::CommandPalette, KeyPreset::Default) => &[],
+            (Self::CommandPalette, KeyPreset::Vim) => &[":"],
+        }
+    }
+}
+
+/// A key with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Parse `"j"`, `"A"`, `"ctrl+d"`, `"shift+tab"`, `"esc"`, `"pagedown"`, ...
+    pub(super) fn parse(spec: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec.trim();
+        // A lone "+" is a key, not a separator.
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                    KeyCode::BackTab
+                }
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                _ => return None,
+            },
+        };
+        Some(Self { code, modifiers })
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        // Terminals report shifted characters as the character itself,
+        // sometimes with SHIFT set.
+        let modifiers = match key.code {
+            KeyCode::Char(_) | KeyCode::BackTab => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        self.code == key.code && self.modifiers == modifiers
+    }
+
+    /// Short label for footer hints.
+    pub(super) fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "S-Tab".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("C-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("M-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("S-");
+        }
+        label.push_str(&key);
+        label
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Keymap {
+    pub(super) preset: KeyPreset,
+    bindings: Vec<(KeyAction, Vec<KeyChord>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_settings(&KeySettings::default()).0
+    }
+}
+
+impl Keymap {
+    /// Build the keymap; unknown actions and unparsable keys are skipped and
+    /// reported.
+    pub(super) fn from_settings(settings: &KeySettings) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        for name in settings.bindings.keys() {
+            if !KeyAction::ALL.iter().any(|action| action.name() == name) {
+                errors.push(format!("unknown action '{}'", name));
+            }
+        }
+
+        let bindings = KeyAction::ALL
+            .iter()
+            .map(|&action| {
+                let specs: Vec<&str> = match settings.bindings.get(action.name()) {
+                    Some(binding) => binding.keys().iter().map(String::as_str).collect(),
+                    None => action.preset_keys(settings.preset).to_vec(),
+                };
+                let chords = specs
+                    .into_iter()
+                    .filter_map(|spec| {
+                        let chord = KeyChord::parse(spec);
+                        if chord.is_none() {
+                            errors.push(format!("bad key '{}' for {}", spec, action.name()));
+                        }
+                        chord
+                    })
+                    .collect();
+                (action, chords)
+            })
+            .collect();
+
+        (
+            Self {
+                preset: settings.preset,
+                bindings,
+            },
+            errors,
+        )
+    }
+
+    /// The first action bound to `key`.
+    pub(super) fn action_for(&self, key: &KeyEvent) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, chords)| chords.iter().any(|chord| chord.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Label of the first key bound to `action` (empty when unbound).
+    pub(super) fn label(&self, action: KeyAction) -> String {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .and_then(|(_, chords)| chords.first())
+            .map(KeyChord::label)
+            .unwrap_or_default()
+    }
+}
+
+impl TuiApp {
+    /// Rebuild the keymap from `[cli.keys]`, reporting bad entries in the
+    /// status line.
+    pub(super) fn load_keymap(&mut self) {
+        let (keymap, errors) = Keymap::from_settings(&self.settings.cli.keys);
+        self.keymap = keymap;
+        if !errors.is_empty() {
+            self.status = format!("[cli.keys] ignored: {}", errors.join(", "));
+        }
+    }
+}
+
+/// What a command palette entry resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PaletteCommand {
+    Quit,
+    Approvals,
+    /// Open the theme picker, or switch straight to the named theme
+    Theme(Option<String>),
+    Category(usize),
+    Option(usize),
+    Unknown,
+}
+
+/// Resolve palette input: `q`/`quit`, `approvals`, `theme [name]`, a category
+/// name or number, or a prefix of one of the current category's option labels.
+pub(super) fn parse_palette_command(input: &str, options: &[OptionDef]) -> PaletteCommand {
+    let input = input.trim().trim_start_matches(':');
+    let command = input.to_lowercase();
+    if command.is_empty() {
+        return PaletteCommand::Unknown;
+    }
+    if let Some(name) = input.strip_prefix("theme")
+        && (name.is_empty() || name.starts_with(' '))
+    {
+        let name = name.trim();
+        return PaletteCommand::Theme((!name.is_empty()).then(|| name.to_string()));
+    }
+    match command.as_str() {
+        "q" | "q!" | "qa" | "quit" => return PaletteCommand::Quit,
+        "approvals" => return PaletteCommand::Approvals,
+        _ => {}
+    }
+    if let Some(idx) = Category::ALL.iter().position(|category| {
+        category.key().to_string() == command
+            || category
+                .label()
+                .rsplit(' ')
+                .next()
+                .is_some_and(|name| name.to_lowercase() == command)
+    }) {
+        return PaletteCommand::Category(idx);
+    }
+    options
+        .iter()
+        .position(|option| option.label.to_lowercase().starts_with(&command))
+        .map_or(PaletteCommand::Unknown, PaletteCommand::Option)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use t_koma_core::KeyBinding;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_key_chord_parse() {
+        let chord = KeyChord::parse("ctrl+d").unwrap();
+        assert!(chord.matches(&key(KeyCode::Char('d'), KeyModifiers::CONTROL)));
+        assert!(!chord.matches(&key(KeyCode::Char('d'), KeyModifiers::NONE)));
+        assert_eq!(chord.label(), "C-d");
+
+        assert!(
+            KeyChord::parse("A")
+                .unwrap()
+                .matches(&key(KeyCode::Char('A'), KeyModifiers::SHIFT))
+        );
+        assert!(
+            KeyChord::parse("shift+tab")
+                .unwrap()
+                .matches(&key(KeyCode::BackTab, KeyModifiers::SHIFT))
+        );
+        assert_eq!(KeyChord::parse("+").unwrap().code, KeyCode::Char('+'));
+        assert_eq!(KeyChord::parse("PageDown").unwrap().code, KeyCode::PageDown);
+        assert!(KeyChord::parse("hyper+x").is_none());
+        assert!(KeyChord::parse("nope").is_none());
+    }
+
+    #[test]
+    fn test_keymap_presets_and_overrides() {
+        let keymap = Keymap::default();
+        let d = key(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(keymap.action_for(&d), Some(KeyAction::PageDown));
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char(':'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.label(KeyAction::Down), "j");
+
+        let mut settings = KeySettings {
+            preset: KeyPreset::Vim,
+            bindings: BTreeMap::new(),
+        };
+        let (vim, errors) = Keymap::from_settings(&settings);
+        assert!(errors.is_empty());
+        assert_eq!(vim.action_for(&d), None);
+        assert_eq!(
+            vim.action_for(&key(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            Some(KeyAction::PageDown)
+        );
+        assert_eq!(
+            vim.action_for(&key(KeyCode::Char(':'), KeyModifiers::NONE)),
+            Some(KeyAction::CommandPalette)
+        );
+
+        settings
+            .bindings
+            .insert("quit".to_string(), KeyBinding::One("ctrl+q".to_string()));
+        settings.bindings.insert(
+            "down".to_string(),
+            KeyBinding::Many(vec!["n".to_string(), "bogus".to_string()]),
+        );
+        settings
+            .bindings
+            .insert("jump".to_string(), KeyBinding::One("x".to_string()));
+        let (custom, errors) = Keymap::from_settings(&settings);
+        assert_eq!(
+            errors,
+            ["unknown action 'jump'", "bad key 'bogus' for down"]
+        );
+        assert_eq!(
+            custom.action_for(&key(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            custom.action_for(&key(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(custom.label(KeyAction::Down), "n");
+    }
+
+    #[test]
+    fn test_parse_palette_command() {
+        let options = vec![
+            OptionDef {
+                label: "Add Model".to_string(),
+                key: 'm',
+            },
+            OptionDef {
+                label: "Reload".to_string(),
+                key: 'r',
+            },
+        ];
+        assert_eq!(parse_palette_command(":q", &options), PaletteCommand::Quit);
+        assert_eq!(parse_palette_command("q", &options), PaletteCommand::Quit);
+        assert_eq!(
+            parse_palette_command("Ghosts", &options),
+            PaletteCommand::Category(3)
+        );
+        assert_eq!(
+            parse_palette_command("7", &options),
+            PaletteCommand::Category(6)
+        );
+        assert_eq!(
+            parse_palette_command("rel", &options),
+            PaletteCommand::Option(1)
+        );
+        assert_eq!(
+            parse_palette_command("approvals", &options),
+            PaletteCommand::Approvals
+        );
+        assert_eq!(parse_palette_command("", &options), PaletteCommand::Unknown);
+        assert_eq!(
+            parse_palette_command("theme", &options),
+            PaletteCommand::Theme(None)
+        );
+        assert_eq!(
+            parse_palette_command("theme high_contrast", &options),
+            PaletteCommand::Theme(Some("high_contrast".to_string()))
+        );
+    }
+}
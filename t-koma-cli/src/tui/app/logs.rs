@@ -1,13 +1,14 @@
 use chrono::Utc;
 use futures::StreamExt;
 use ratatui::{
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
 };
 use tokio::sync::mpsc;
 use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 
-use crate::tui::state::GateFilter;
+use crate::tui::{state::GateFilter, theme};
 
 use super::{
     TuiApp,
@@ -23,8 +24,13 @@ impl TuiApp {
 
         tokio::spawn(async move {
             loop {
-                let connection = connect_async(&logs_url).await;
-                let Ok((stream, _)) = connection else {
+                let connection = match logs_url.as_str().into_client_request() {
+                    Ok(request) => connect_async(crate::client::with_api_token(request))
+                        .await
+                        .ok(),
+                    Err(_) => None,
+                };
+                let Some((stream, _)) = connection else {
                     let _ = tx.send(GateEvent::Status(false));
                     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
                     continue;
@@ -36,7 +42,9 @@ impl TuiApp {
                 loop {
                     match read.next().await {
                         Some(Ok(tokio_tungstenite::tungstenite::Message::Text(text))) => {
-                            if let Some(row) = parse_gate_row(text.as_str()) {
+                            if let Some(event) = parse_job_event(text.as_str()) {
+                                let _ = tx.send(event);
+                            } else if let Some(row) = parse_gate_row(text.as_str()) {
                                 let _ = tx.send(GateEvent::Log(row));
                             }
                         }
@@ -80,53 +88,55 @@ impl TuiApp {
 
         let mut lines = vec![Line::from(vec![Span::styled(
             "filters: [1]all [2]gateway [3]ghost [4]operator [5]transport [6]warn/error",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme::palette().muted),
         )])];
 
         if rows.is_empty() {
             lines.push(Line::from(Span::styled(
                 "No log data",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::palette().muted),
             )));
             return lines;
         }
 
         for row in rows {
             let source_style = match row.source.as_str() {
-                "ghost" => Style::default().fg(Color::Cyan),
-                "operator" => Style::default().fg(Color::Yellow),
-                "route" => Style::default().fg(Color::LightMagenta),
-                "ws" | "http" => Style::default().fg(Color::Magenta),
-                "trace" => Style::default().fg(Color::LightBlue),
-                _ => Style::default().fg(Color::White),
+                "ghost" => Style::default().fg(theme::palette().accent),
+                "operator" => Style::default().fg(theme::palette().highlight),
+                "route" => Style::default().fg(theme::palette().secondary_soft),
+                "ws" | "http" => Style::default().fg(theme::palette().secondary),
+                "trace" => Style::default().fg(theme::palette().info_soft),
+                _ => Style::default().fg(theme::palette().text),
             };
             let level_style = match row.level.as_str() {
-                "ERROR" => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                "ERROR" => Style::default()
+                    .fg(theme::palette().error)
+                    .add_modifier(Modifier::BOLD),
                 "WARN" => Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::palette().highlight)
                     .add_modifier(Modifier::BOLD),
-                "INFO" => Style::default().fg(Color::Green),
-                _ => Style::default().fg(Color::DarkGray),
+                "INFO" => Style::default().fg(theme::palette().success),
+                _ => Style::default().fg(theme::palette().muted),
             };
 
             lines.push(Line::from(vec![
                 Span::styled(
                     format!("{} ", row.time),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme::palette().muted),
                 ),
                 Span::styled(format!("{:>5} ", row.level), level_style),
                 Span::styled(format!("{:>9} ", row.source), source_style),
                 Span::styled(
                     format!(" {}", truncate_for_cell(&row.core, 120)),
-                    Style::default().fg(Color::LightBlue),
+                    Style::default().fg(theme::palette().info_soft),
                 ),
             ]));
 
             let body_style = match row.source.as_str() {
-                "operator" => Style::default().fg(Color::Yellow),
-                "ghost" => Style::default().fg(Color::Cyan),
-                "route" => Style::default().fg(Color::LightMagenta),
-                _ => Style::default().fg(Color::White),
+                "operator" => Style::default().fg(theme::palette().highlight),
+                "ghost" => Style::default().fg(theme::palette().accent),
+                "route" => Style::default().fg(theme::palette().secondary_soft),
+                _ => Style::default().fg(theme::palette().text),
             };
             let marker = match row.source.as_str() {
                 "operator" => "  ↳ ",
@@ -151,6 +161,28 @@ impl TuiApp {
     }
 }
 
+/// Live job transcript events feed the job detail view, not the gate log.
+fn parse_job_event(text: &str) -> Option<GateEvent> {
+    let json: serde_json::Value = serde_json::from_str(text).ok()?;
+    if json.get("type").and_then(|v| v.as_str()) != Some("log_entry") {
+        return None;
+    }
+    let entry = json.get("entry")?;
+    let job_id = entry.get("job_id")?.as_str()?.to_string();
+    match entry.get("kind").and_then(|v| v.as_str())? {
+        "job_entry" => Some(GateEvent::JobEntry {
+            job_id,
+            entry: serde_json::from_value(entry.get("entry")?.clone()).ok()?,
+        }),
+        "job_todos" => Some(GateEvent::JobTodos {
+            job_id,
+            todos: serde_json::from_value(entry.get("todos")?.clone()).ok()?,
+        }),
+        "job_finished" => Some(GateEvent::JobFinished { job_id }),
+        _ => None,
+    }
+}
+
 fn parse_gate_row(text: &str) -> Option<GateRow> {
     let json: serde_json::Value = serde_json::from_str(text).ok()?;
     if json.get("type") == Some(&serde_json::Value::String("connected".to_string())) {
@@ -166,7 +198,11 @@ fn parse_gate_row(text: &str) -> Option<GateRow> {
         let level = entry
             .get("level")
             .and_then(|v| v.as_str())
-            .unwrap_or("INFO")
+            .unwrap_or(if kind == "provider_retry" {
+                "WARN"
+            } else {
+                "INFO"
+            })
             .to_string();
         let source = match kind {
             "discord_message" => "operator",
@@ -177,6 +213,7 @@ fn parse_gate_row(text: &str) -> Option<GateRow> {
             "web_socket" => "ws",
             "http_request" => "http",
             "trace" => "trace",
+            "tool_output" => "tool",
             _ => "gateway",
         }
         .to_string();
@@ -221,7 +258,10 @@ fn parse_gate_row(text: &str) -> Option<GateRow> {
                     .get("client_id")
                     .and_then(|v| v.as_str())
                     .unwrap_or("client");
-                (client.to_string(), event.to_string())
+                match entry.get("identity").and_then(|v| v.as_str()) {
+                    Some(identity) => (format!("{} ({})", client, identity), event.to_string()),
+                    None => (client.to_string(), event.to_string()),
+                }
             }
             "http_request" => {
                 let method = entry.get("method").and_then(|v| v.as_str()).unwrap_or("-");
@@ -229,11 +269,50 @@ fn parse_gate_row(text: &str) -> Option<GateRow> {
                 let status = entry.get("status").and_then(|v| v.as_i64()).unwrap_or(0);
                 (format!("{} {}", method, path), status.to_string())
             }
+            "tool_output" => {
+                let field = |name: &str| entry.get(name).and_then(|v| v.as_str()).unwrap_or("");
+                (
+                    field("tool").to_string(),
+                    field("output").trim_end().to_string(),
+                )
+            }
             "trace" => {
                 let target = entry.get("target").and_then(|v| v.as_str()).unwrap_or("");
                 let message = entry.get("message").and_then(|v| v.as_str()).unwrap_or("");
                 (target.to_string(), message.to_string())
             }
+            "provider_retry" => {
+                let field = |name: &str| entry.get(name).and_then(|v| v.as_str()).unwrap_or("");
+                let number = |name: &str| entry.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+                (
+                    format!("{}/{}", field("provider"), field("model")),
+                    format!(
+                        "retry {}/{} in {}ms: {}",
+                        number("attempt"),
+                        number("max_attempts"),
+                        number("delay_ms"),
+                        field("reason")
+                    ),
+                )
+            }
+            "request_estimate" => {
+                let field = |name: &str| entry.get(name).and_then(|v| v.as_str()).unwrap_or("");
+                let number = |name: &str| entry.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+                let cost = entry
+                    .get("prompt_cost_usd")
+                    .and_then(|v| v.as_f64())
+                    .map(|cost| format!(", ~${cost:.4}"))
+                    .unwrap_or_default();
+                (
+                    format!("{}/{}", field("provider"), field("model")),
+                    format!(
+                        "~{}/{} tokens{}",
+                        number("prompt_tokens"),
+                        number("context_window"),
+                        cost
+                    ),
+                )
+            }
             "routing" => {
                 let operator_id = entry
                     .get("operator_id")
@@ -1,29 +1,32 @@
 mod actions;
+mod approvals;
+mod chat;
 mod input;
 mod input_onboarding;
+mod keymap;
 mod logs;
 pub(crate) mod onboarding;
 mod render;
 mod state;
+mod transcript;
+mod usage;
 mod util;
 
-use std::{
-    path::PathBuf,
-    time::{Duration, Instant},
-};
+use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event, KeyEventKind};
 use ratatui::prelude::*;
 use tokio::sync::mpsc;
 
-use t_koma_core::Settings;
+use t_koma_core::{ConfigBackup, Settings};
 use t_koma_db::{KomaDbPool, Operator};
 
 use crate::tui::state::{Category, FocusPane, GateFilter};
 
 use self::state::{
-    ContentView, GateEvent, GhostRow, JobViewState, KnowledgeViewState, Metrics, OperatorView,
-    OptionDef, PromptState, SelectionModal, SessionViewState,
+    ApprovalEvent, ChatViewState, ContentView, GateEvent, GhostRow, JobViewState,
+    KnowledgeViewState, Metrics, OperatorView, OptionDef, PromptState, ReplyRoute, SelectionModal,
+    SessionViewState, UsageViewState,
 };
 
 pub struct TuiApp {
@@ -33,12 +36,15 @@ pub struct TuiApp {
     content_idx: usize,
     should_exit: bool,
     status: String,
+    keymap: keymap::Keymap,
 
     settings: Settings,
     settings_toml: String,
     disk_toml: String,
     settings_dirty: bool,
-    backup_path: Option<PathBuf>,
+    config_backups: Vec<ConfigBackup>,
+    /// Contents of the selected backup in the backup browser
+    config_backup_preview: String,
 
     db: Option<KomaDbPool>,
     operators: Vec<Operator>,
@@ -54,6 +60,7 @@ pub struct TuiApp {
     job_detail_scroll: u16,
     session_view: SessionViewState,
     knowledge_view: KnowledgeViewState,
+    usage_view: UsageViewState,
 
     gate_connected: bool,
     gate_paused: bool,
@@ -63,6 +70,11 @@ pub struct TuiApp {
     gate_rx: Option<mpsc::UnboundedReceiver<GateEvent>>,
     gate_scroll: u16,
 
+    pending_approvals: Vec<t_koma_core::PendingApprovalInfo>,
+    approvals_rx: Option<mpsc::UnboundedReceiver<ApprovalEvent>>,
+    approvals_out: Option<mpsc::UnboundedSender<(ReplyRoute, t_koma_core::WsMessage)>>,
+    chat_view: ChatViewState,
+
     metrics: Metrics,
     metrics_last_refresh: Instant,
     anim_tick: usize,
@@ -76,7 +88,12 @@ impl TuiApp {
         let settings_toml = settings.to_toml().unwrap_or_default();
         let disk_toml = util::load_disk_config().unwrap_or_else(|| settings_toml.clone());
 
-        let db = KomaDbPool::new().await.ok();
+        // On a replica machine the DB is a copy; never migrate or write it.
+        let db = if settings.gateway.read_only {
+            KomaDbPool::open_read_only().await.ok()
+        } else {
+            KomaDbPool::new().await.ok()
+        };
 
         let mut app = Self {
             focus: FocusPane::Categories,
@@ -85,12 +102,14 @@ impl TuiApp {
             content_idx: 0,
             should_exit: false,
             status: "TUI ready".to_string(),
+            keymap: keymap::Keymap::default(),
 
             settings,
             settings_toml,
             disk_toml,
             settings_dirty: false,
-            backup_path: None,
+            config_backups: Vec::new(),
+            config_backup_preview: String::new(),
 
             db,
             operators: Vec::new(),
@@ -106,6 +125,7 @@ impl TuiApp {
             job_detail_scroll: 0,
             session_view: SessionViewState::default(),
             knowledge_view: KnowledgeViewState::default(),
+            usage_view: UsageViewState::default(),
 
             gate_connected: false,
             gate_paused: false,
@@ -115,6 +135,11 @@ impl TuiApp {
             gate_rx: None,
             gate_scroll: 0,
 
+            pending_approvals: Vec::new(),
+            approvals_rx: None,
+            approvals_out: None,
+            chat_view: ChatViewState::default(),
+
             metrics: Metrics::default(),
             metrics_last_refresh: Instant::now() - Duration::from_secs(30),
             anim_tick: 0,
@@ -127,7 +152,10 @@ impl TuiApp {
             app.onboarding = Some(onboarding::OnboardingState::default());
         }
 
+        app.load_keymap();
+        app.load_theme();
         app.start_logs_stream();
+        app.start_approvals_stream();
         app.sync_selection().await;
         app.refresh_metrics().await;
         app
@@ -169,7 +197,9 @@ impl TuiApp {
                 } else {
                     o('s', "Save")
                 },
-                o('b', "Restore Backup"),
+                o('b', "Backups"),
+                o('v', "Diff"),
+                o('c', "Theme"),
             ],
             Category::Operators => vec![
                 o('l', "List All"),
@@ -179,25 +209,35 @@ impl TuiApp {
                 o('r', "Set Rate Limits"),
                 o('x', "Disable Rate Limits"),
                 o('w', "Toggle Workspace Escape"),
+                o('t', "Issue API Token"),
+                o('y', "Revoke API Tokens"),
+                o('g', "Set Permissions"),
+                o('o', "Tool Policies"),
+                o('d', "Path Approvals"),
             ],
             Category::Ghosts => vec![
                 o('s', "Sessions"),
                 o('l', "List All"),
                 o('n', "New Ghost"),
                 o('x', "Delete"),
+                o('f', "Search Sessions"),
+                o('r', "Rename"),
+                o('c', "Clone"),
+                o('o', "Chat"),
             ],
             Category::Jobs => {
-                let mut opts = vec![o('c', "CRON"), o('a', "All Recent")];
+                let mut opts = vec![o('c', "CRON"), o('t', "Tasks"), o('a', "All Recent")];
                 for (i, g) in self.ghosts.iter().enumerate() {
                     let key = char::from(b'1' + i as u8).min('9');
                     opts.push(o(key, &format!("Ghost: {}", g.ghost.name)));
                 }
                 opts
             }
-            Category::Knowledge => vec![
-                o('r', "Recent Notes"),
-                o('s', "Search"),
-                o('i', "Index Stats"),
+            Category::Knowledge => vec![o('r', "Browse"), o('s', "Search"), o('i', "Index Stats")],
+            Category::Metrics => vec![
+                o('w', "Last 7 Days"),
+                o('m', "Last 30 Days"),
+                o('n', "Last 90 Days"),
             ],
         }
     }
@@ -237,18 +277,40 @@ impl TuiApp {
                         }
                     }
                 }
+                GateEvent::JobEntry { job_id, entry } => {
+                    self.append_live_job_entry(&job_id, entry).await;
+                }
+                GateEvent::JobTodos { job_id, todos } => {
+                    self.update_live_job_todos(&job_id, todos).await;
+                }
+                GateEvent::JobFinished { job_id } => {
+                    self.reload_live_job_detail(&job_id).await;
+                }
             }
         }
 
+        let mut approval_events = Vec::new();
+        if let Some(rx) = &mut self.approvals_rx {
+            while let Ok(event) = rx.try_recv() {
+                approval_events.push(event);
+            }
+        }
+        for event in approval_events {
+            self.apply_approval_event(event);
+        }
+
         if self.metrics_last_refresh.elapsed() > Duration::from_secs(8) {
             self.refresh_metrics().await;
         }
 
-        if event::poll(Duration::from_millis(50)).unwrap_or(false)
-            && let Ok(Event::Key(key)) = event::read()
-            && key.kind == KeyEventKind::Press
-        {
-            self.handle_key(key).await;
+        if event::poll(Duration::from_millis(50)).unwrap_or(false) {
+            match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    self.handle_key(key).await;
+                }
+                Ok(Event::Paste(text)) => self.handle_paste(&text),
+                _ => {}
+            }
         }
     }
 }
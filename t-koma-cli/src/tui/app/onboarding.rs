@@ -38,9 +38,16 @@ pub(crate) struct OnboardingState {
     pub api_key: Option<String>,
     pub model_alias: String,
     pub model_id: String,
+    /// Endpoint for OpenAI-compatible and Azure models.
+    pub base_url: Option<String>,
     pub embedding_provider: EmbeddingChoice,
     pub openai_embedding_key: Option<String>,
+    /// Ollama server and model; the configured defaults when unset.
+    pub embedding_url: Option<String>,
+    pub embedding_model: Option<String>,
+    pub knowledge_db_path: Option<String>,
     pub discord_enabled: bool,
+    pub discord_token: Option<String>,
     pub input_buffer: String,
     pub selection_idx: usize,
 }
@@ -60,9 +67,14 @@ impl Default for OnboardingState {
             api_key: None,
             model_alias: String::new(),
             model_id: String::new(),
+            base_url: None,
             embedding_provider: EmbeddingChoice::Ollama,
             openai_embedding_key: None,
+            embedding_url: None,
+            embedding_model: None,
+            knowledge_db_path: None,
             discord_enabled: false,
+            discord_token: None,
             input_buffer: String::new(),
             selection_idx: 0,
         }
@@ -79,6 +91,7 @@ impl OnboardingState {
             ("Google Gemini", ProviderType::Gemini),
             ("OpenAI Compatible", ProviderType::OpenAiCompatible),
             ("Kimi Code", ProviderType::KimiCode),
+            ("Azure OpenAI", ProviderType::AzureOpenAi),
         ]
     }
 
@@ -97,6 +110,7 @@ impl OnboardingState {
             ProviderType::Gemini => "GEMINI_API_KEY",
             ProviderType::OpenAiCompatible => "OPENAI_API_KEY",
             ProviderType::KimiCode => "KIMI_API_KEY",
+            ProviderType::AzureOpenAi => "AZURE_OPENAI_API_KEY",
         }
     }
 
@@ -132,6 +146,13 @@ Go to https://kimi.moonshot.cn/
 Sign in and navigate to API settings
 Create a new key and copy it"
             }
+            ProviderType::AzureOpenAi => {
+                "\
+Open your Azure OpenAI resource
+Copy a key from Keys and Endpoint
+Then set base_url (the endpoint) and
+use the deployment name as the model"
+            }
         }
     }
 
@@ -142,6 +163,7 @@ Create a new key and copy it"
             ProviderType::Gemini => ("gemini", "gemini-2.5-flash"),
             ProviderType::OpenAiCompatible => ("openai", "gpt-4o"),
             ProviderType::KimiCode => ("kimi", "kimi-latest"),
+            ProviderType::AzureOpenAi => ("azure", "gpt-4o"),
         }
     }
 
@@ -211,12 +233,16 @@ Create a new key and copy it"
             ModelConfig {
                 provider,
                 model: model_id,
-                base_url: None,
+                base_url: self.base_url.clone(),
                 api_key_env: None,
                 routing: None,
                 context_window: None,
                 headers: None,
+                api_version: None,
                 retry_on_empty: None,
+                thinking_budget: None,
+                generation: Default::default(),
+                pricing: None,
             },
         );
 
@@ -226,6 +252,12 @@ Create a new key and copy it"
         match self.embedding_provider {
             EmbeddingChoice::Ollama => {
                 settings.tools.knowledge.embedding_provider = Some("ollama".to_string());
+                if let Some(url) = &self.embedding_url {
+                    settings.tools.knowledge.embedding_url = Some(url.clone());
+                }
+                if let Some(model) = &self.embedding_model {
+                    settings.tools.knowledge.embedding_model = Some(model.clone());
+                }
             }
             EmbeddingChoice::OpenAi => {
                 settings.tools.knowledge.embedding_provider = Some("openai".to_string());
@@ -235,6 +267,10 @@ Create a new key and copy it"
             EmbeddingChoice::Skip => {}
         }
 
+        if let Some(path) = &self.knowledge_db_path {
+            settings.tools.knowledge.knowledge_db_path_override = Some(path.clone());
+        }
+
         settings.discord.enabled = self.discord_enabled;
 
         // Write .env file with API keys
@@ -247,21 +283,28 @@ Create a new key and copy it"
 
         // Provider API key
         if let Some(key) = &self.api_key {
-            let env_var = Self::env_var_for_provider(provider);
-            env_lines.retain(|l| !l.starts_with(&format!("{env_var}=")));
-            env_lines.push(format!("{env_var}={key}"));
+            set_env_line(&mut env_lines, Self::env_var_for_provider(provider), key);
         }
 
         // OpenAI embeddings key
         if let Some(key) = &self.openai_embedding_key {
-            env_lines.retain(|l| !l.starts_with("OPENAI_API_KEY="));
-            env_lines.push(format!("OPENAI_API_KEY={key}"));
+            set_env_line(&mut env_lines, "OPENAI_API_KEY", key);
+        }
+
+        if let Some(token) = &self.discord_token {
+            set_env_line(&mut env_lines, "DISCORD_BOT_TOKEN", token);
         }
 
         // Write .env
         if !env_lines.is_empty() {
             std::fs::create_dir_all(config_dir).map_err(|e| e.to_string())?;
             std::fs::write(&env_path, env_lines.join("\n") + "\n").map_err(|e| e.to_string())?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&env_path, std::fs::Permissions::from_mode(0o600))
+                    .map_err(|e| e.to_string())?;
+            }
         }
 
         // Save config
@@ -270,3 +313,34 @@ Create a new key and copy it"
         Ok(format!("Setup complete! Model '{}' configured.", alias))
     }
 }
+
+/// Replace or append `key=value` in `.env` lines.
+fn set_env_line(lines: &mut Vec<String>, key: &str, value: &str) {
+    lines.retain(|line| !line.starts_with(&format!("{key}=")));
+    lines.push(format!("{key}={value}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_env_line() {
+        let mut lines = vec![
+            "# keys".to_string(),
+            "ANTHROPIC_API_KEY=old".to_string(),
+            "ANTHROPIC_API_KEY_BACKUP=keep".to_string(),
+        ];
+        set_env_line(&mut lines, "ANTHROPIC_API_KEY", "new");
+        set_env_line(&mut lines, "DISCORD_BOT_TOKEN", "bot");
+        assert_eq!(
+            lines,
+            [
+                "# keys",
+                "ANTHROPIC_API_KEY_BACKUP=keep",
+                "ANTHROPIC_API_KEY=new",
+                "DISCORD_BOT_TOKEN=bot"
+            ]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::tui::theme;
+
+use super::super::{
+    TuiApp,
+    chat::short_id,
+    state::{ChatLineKind, ChatPane},
+    util::hard_wrap,
+};
+
+impl TuiApp {
+    pub(super) fn draw_chat(&self, frame: &mut Frame, inner: Rect) {
+        let panes = &self.chat_view.panes;
+        if panes.is_empty() {
+            let p = Paragraph::new("No chat open. Ghosts → Chat, or o on a session.")
+                .style(Style::default().fg(theme::palette().muted));
+            frame.render_widget(p, inner);
+            return;
+        }
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, panes.len() as u32); panes.len()])
+            .split(inner);
+        for (idx, (pane, area)) in panes.iter().zip(columns.iter()).enumerate() {
+            self.draw_chat_pane(frame, *area, pane, idx == self.chat_view.focused);
+        }
+    }
+
+    fn draw_chat_pane(&self, frame: &mut Frame, area: Rect, pane: &ChatPane, focused: bool) {
+        let session = if pane.session_id == "active" {
+            "active"
+        } else {
+            short_id(&pane.session_id)
+        };
+        let state = match pane.in_flight {
+            0 => String::new(),
+            1 => " · replying…".to_string(),
+            n => format!(" · {} queued", n),
+        };
+        let block = Block::default()
+            .title(format!(" {} · {}{} ", pane.ghost_name, session, state))
+            .borders(Borders::ALL)
+            .border_style(theme::border(focused));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let width = inner.width as usize;
+        let input_rows: Vec<String> = pane
+            .input
+            .split('\n')
+            .flat_map(|line| hard_wrap(line, width.saturating_sub(2)))
+            .collect();
+        let input_height = (input_rows.len() as u16).clamp(1, 5) + 1;
+        let [log_area, input_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(input_height)])
+            .areas(inner);
+
+        let mut lines: Vec<Line> = Vec::new();
+        for line in &pane.lines {
+            let style = match line.kind {
+                ChatLineKind::Operator => Style::default()
+                    .fg(theme::palette().highlight)
+                    .add_modifier(Modifier::BOLD),
+                ChatLineKind::Ghost => Style::default(),
+                ChatLineKind::Muted => Style::default().fg(theme::palette().muted),
+                ChatLineKind::Error => Style::default().fg(theme::palette().error),
+            };
+            lines.extend(
+                hard_wrap(&line.text, width)
+                    .into_iter()
+                    .map(|row| Line::styled(row, style)),
+            );
+        }
+        // Anchored to the bottom; `scroll` counts rows up from there.
+        let bottom = lines.len().saturating_sub(log_area.height as usize) as u16;
+        let top = bottom.saturating_sub(pane.scroll);
+        frame.render_widget(Paragraph::new(Text::from(lines)).scroll((top, 0)), log_area);
+
+        let cursor = if focused { "▏" } else { "" };
+        let shown = input_rows.len().saturating_sub(input_height as usize - 1);
+        let mut input_lines: Vec<Line> = input_rows[shown..]
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                let prefix = if shown + idx == 0 { "› " } else { "  " };
+                Line::from(format!("{}{}", prefix, row))
+            })
+            .collect();
+        if let Some(last) = input_lines.pop() {
+            input_lines.push(Line::from(format!("{}{}", last, cursor)));
+        }
+        let input = Paragraph::new(Text::from(input_lines)).block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(theme::border(focused)),
+        );
+        frame.render_widget(input, input_area);
+    }
+}
@@ -1,12 +1,12 @@
 use ratatui::{
     Frame,
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
 
-use t_koma_db::ContentBlock;
+use t_koma_db::{ContentBlock, TodoItem, TodoStatus};
 
 use crate::tui::{
     state::{Category, FocusPane},
@@ -15,8 +15,12 @@ use crate::tui::{
 
 use super::super::{
     TuiApp,
-    state::ContentView,
-    util::{border_glow, highlight_toml_with_diff, markdown_to_lines},
+    state::{ContentView, JobViewMode, KnowledgeRow},
+    transcript::{TranscriptLineKind, transcript_lines},
+    util::{
+        border_glow, format_message_usage, highlight_toml, highlight_toml_with_diff, line_diff,
+        markdown_to_lines, todo_marker, todo_progress, usage_weight,
+    },
 };
 
 impl TuiApp {
@@ -41,6 +45,16 @@ impl TuiApp {
                 self.draw_ghost_sessions(frame, inner, ghost_name)
             }
             ContentView::SessionMessages { .. } => self.draw_session_messages(frame, inner),
+            ContentView::SessionSearch { .. } => self.draw_session_search(frame, inner),
+            ContentView::ConfigDiff => self.draw_side_by_side(
+                frame,
+                inner,
+                &self.disk_toml,
+                &self.settings_toml,
+                ["config.toml (disk)", "unsaved settings"],
+            ),
+            ContentView::ConfigBackups => self.draw_config_backups(frame, inner),
+            ContentView::Chat => self.draw_chat(frame, inner),
         }
     }
 
@@ -62,7 +76,13 @@ impl TuiApp {
             ContentView::SessionMessages { ghost_name, .. } => {
                 format!("Messages: {}", ghost_name)
             }
+            ContentView::SessionSearch { ghost_name, query } => {
+                format!("Search: {} \"{}\"", ghost_name, query)
+            }
             ContentView::KnowledgeStats => "Index Stats".to_string(),
+            ContentView::ConfigDiff => "Config Diff".to_string(),
+            ContentView::ConfigBackups => "Config Backups".to_string(),
+            ContentView::Chat => "Chat".to_string(),
         }
     }
 
@@ -74,6 +94,7 @@ impl TuiApp {
             Category::Gate => self.draw_gate_content(frame, inner),
             Category::Jobs => self.draw_jobs_list(frame, inner),
             Category::Knowledge => self.draw_knowledge_list(frame, inner),
+            Category::Metrics => self.draw_usage_dashboard(frame, inner),
         }
     }
 
@@ -83,7 +104,7 @@ impl TuiApp {
             lines.push(Line::from(Span::styled(
                 "Unsaved changes. Use option: Save (required after changes).",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme::palette().highlight)
                     .add_modifier(Modifier::BOLD),
             )));
         }
@@ -100,6 +121,117 @@ impl TuiApp {
         frame.render_widget(p, inner);
     }
 
+    /// Two columns aligned by `line_diff`, scrolled together with
+    /// `config_scroll`. Lines only on the left are removed, lines only on the
+    /// right are added.
+    fn draw_side_by_side(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        left: &str,
+        right: &str,
+        titles: [&str; 2],
+    ) {
+        let rows = line_diff(left, right);
+        let muted = Style::default().fg(theme::palette().muted);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+
+        for (side, column) in columns.iter().enumerate() {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(*column);
+            let (marker, changed_color) = if side == 0 {
+                ("-", theme::palette().error)
+            } else {
+                ("+", theme::palette().success)
+            };
+
+            let mut number = 0;
+            let lines: Vec<Line> = rows
+                .iter()
+                .map(|row| {
+                    let text = if side == 0 { row.left } else { row.right };
+                    let Some(text) = text else {
+                        return Line::styled("     ~", muted);
+                    };
+                    number += 1;
+                    let (mut line, gutter) = if row.changed {
+                        let style = Style::default().fg(changed_color);
+                        (
+                            Line::styled(text.to_string(), style),
+                            Span::styled(
+                                format!("{:>4} {} ", number, marker),
+                                style.add_modifier(Modifier::BOLD),
+                            ),
+                        )
+                    } else {
+                        (
+                            highlight_toml(text)
+                                .into_iter()
+                                .next()
+                                .unwrap_or_else(|| Line::from("")),
+                            Span::styled(format!("{:>4}   ", number), muted),
+                        )
+                    };
+                    line.spans.insert(0, gutter);
+                    line
+                })
+                .collect();
+
+            frame.render_widget(
+                Paragraph::new(titles[side]).style(
+                    Style::default()
+                        .fg(theme::palette().accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                parts[0],
+            );
+            frame.render_widget(
+                Paragraph::new(Text::from(lines)).scroll((self.config_scroll, 0)),
+                parts[1],
+            );
+        }
+    }
+
+    fn draw_config_backups(&self, frame: &mut Frame, inner: Rect) {
+        if self.config_backups.is_empty() {
+            let p = Paragraph::new("No config backups yet. Save takes one before writing.")
+                .style(Style::default().fg(theme::palette().muted));
+            frame.render_widget(p, inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(26), Constraint::Min(0)])
+            .split(inner);
+        let items: Vec<ListItem> = self
+            .config_backups
+            .iter()
+            .enumerate()
+            .map(|(idx, backup)| {
+                let mut item =
+                    ListItem::new(backup.created_at.format("%Y-%m-%d %H:%M:%S").to_string());
+                if idx == self.content_idx && self.focus == FocusPane::Content {
+                    item = item.style(theme::selected());
+                }
+                item
+            })
+            .collect();
+        frame.render_widget(List::new(items), chunks[0]);
+        self.draw_side_by_side(
+            frame,
+            chunks[1],
+            &self.disk_toml,
+            &self.config_backup_preview,
+            ["config.toml (disk)", "backup"],
+        );
+    }
+
     fn draw_operators_content(&self, frame: &mut Frame, inner: Rect) {
         let items: Vec<ListItem> = self
             .operators
@@ -177,44 +309,96 @@ impl TuiApp {
 
     // ── Jobs ─────────────────────────────────────────────────────────
 
-    fn draw_jobs_list(&self, frame: &mut Frame, inner: Rect) {
-        if self.job_view.mode == super::super::state::JobViewMode::Cron {
-            if self.job_view.cron_jobs.is_empty() && self.job_view.summaries.is_empty() {
-                let p = Paragraph::new("No CRON definitions or logs")
-                    .style(Style::default().fg(Color::DarkGray));
-                frame.render_widget(p, inner);
-                return;
+    fn cron_definition_items(&self) -> Vec<ListItem<'static>> {
+        let mut items = Vec::new();
+        for (idx, job) in self.job_view.cron_jobs.iter().enumerate() {
+            let ghost = job.ghost_name.as_str();
+            let enabled = if job.enabled { "on" } else { "off" };
+            let carry = if job.carry_last_output {
+                "carry"
+            } else {
+                "no-carry"
+            };
+            let mut item = ListItem::new(Line::styled(
+                format!(
+                    "[CRON] {:16} ghost={:12} {} next={} {} {} [{}]",
+                    truncate_snippet(&job.name, 16),
+                    ghost,
+                    job.schedule,
+                    format_next_run(job.next_run),
+                    enabled,
+                    carry,
+                    truncate_snippet(&job.path, 24)
+                ),
+                Style::default().fg(theme::palette().accent),
+            ));
+            if idx == self.content_idx && self.focus == FocusPane::Content {
+                item = item.style(theme::selected());
             }
+            items.push(item);
+        }
+        items
+    }
 
-            let mut items: Vec<ListItem> = Vec::new();
-            for (idx, job) in self.job_view.cron_jobs.iter().enumerate() {
-                let ghost = job.ghost_name.as_str();
-                let enabled = if job.enabled { "on" } else { "off" };
-                let carry = if job.carry_last_output {
-                    "carry"
+    fn task_definition_items(&self) -> Vec<ListItem<'static>> {
+        let mut items = Vec::new();
+        for (idx, row) in self.job_view.tasks.iter().enumerate() {
+            let task = &row.task;
+            let state = if task.enabled { "on" } else { "paused" };
+            let next = if task.enabled {
+                format_next_run(task.next_due)
+            } else {
+                "-".to_string()
+            };
+            let mut lines = vec![Line::from(format!(
+                "[TASK] {:16} ghost={:12} {} next={} {}",
+                truncate_snippet(&task.name, 16),
+                row.ghost_name,
+                task.schedule,
+                next,
+                state,
+            ))];
+            lines.push(Line::styled(
+                format!("      \"{}\"", truncate_snippet(&task.prompt, 60)),
+                Style::default().fg(theme::palette().muted),
+            ));
+            let mut item = ListItem::new(Text::from(lines));
+            if idx == self.content_idx && self.focus == FocusPane::Content {
+                item = item.style(theme::selected());
+            } else {
+                item = item.style(Style::default().fg(if task.enabled {
+                    theme::palette().accent
                 } else {
-                    "no-carry"
-                };
-                let mut item = ListItem::new(Line::styled(
-                    format!(
-                        "[CRON] {:16} ghost={:12} {} {} {} [{}]",
-                        truncate_snippet(&job.name, 16),
-                        ghost,
-                        job.schedule,
-                        enabled,
-                        carry,
-                        truncate_snippet(&job.path, 24)
-                    ),
-                    Style::default().fg(Color::Cyan),
-                ));
-                if idx == self.content_idx && self.focus == FocusPane::Content {
-                    item = item.style(theme::selected());
-                }
-                items.push(item);
+                    theme::palette().muted
+                }));
+            }
+            items.push(item);
+        }
+        items
+    }
+
+    fn draw_jobs_list(&self, frame: &mut Frame, inner: Rect) {
+        let (mut items, empty_text) = match self.job_view.mode {
+            JobViewMode::Logs => (Vec::new(), None),
+            JobViewMode::Cron => (
+                self.cron_definition_items(),
+                Some("No CRON definitions or logs"),
+            ),
+            JobViewMode::Tasks => (
+                self.task_definition_items(),
+                Some("No scheduled tasks or runs"),
+            ),
+        };
+        if let Some(empty_text) = empty_text {
+            if items.is_empty() && self.job_view.summaries.is_empty() {
+                let p =
+                    Paragraph::new(empty_text).style(Style::default().fg(theme::palette().muted));
+                frame.render_widget(p, inner);
+                return;
             }
 
             for (idx, job) in self.job_view.summaries.iter().enumerate() {
-                let list_idx = self.job_view.cron_jobs.len() + idx;
+                let list_idx = self.job_view.definition_count() + idx;
                 let (status_icon, status_color) = job_status_style(job.status.as_deref());
                 let dur_str = job
                     .finished_at
@@ -235,17 +419,18 @@ impl TuiApp {
                     .map(|m| truncate_snippet(m, 60))
                     .unwrap_or_default();
                 let mut lines = vec![Line::from(format!(
-                    "{} [RUN] {:12} {:12} {:8} {}",
+                    "{} [RUN] {:12} {:12} {:8} {}{}",
                     status_icon,
-                    "cron",
+                    job.job_kind.to_string(),
                     ghost,
                     dur_str,
-                    job.status.as_deref().unwrap_or("-"),
+                    job.status.as_deref().unwrap_or("running"),
+                    todo_suffix(&job.todo_list),
                 ))];
                 if !preview.is_empty() {
                     lines.push(Line::styled(
                         format!("      \"{}\"", preview),
-                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(theme::palette().muted),
                     ));
                 }
                 let mut item = ListItem::new(Text::from(lines));
@@ -262,7 +447,8 @@ impl TuiApp {
         }
 
         if self.job_view.summaries.is_empty() {
-            let p = Paragraph::new("No job logs found").style(Style::default().fg(Color::DarkGray));
+            let p = Paragraph::new("No job logs found")
+                .style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         }
@@ -296,19 +482,20 @@ impl TuiApp {
                     .unwrap_or_default();
 
                 let line1 = format!(
-                    "{} {:12} {:12} {} {:8} {}",
+                    "{} {:12} {:12} {} {:8} {}{}",
                     status_icon,
                     kind_str,
                     ghost,
                     sess_short,
                     dur_str,
-                    job.status.as_deref().unwrap_or("-"),
+                    job.status.as_deref().unwrap_or("running"),
+                    todo_suffix(&job.todo_list),
                 );
                 let mut lines = vec![Line::from(line1)];
                 if !preview.is_empty() {
                     lines.push(Line::styled(
                         format!("          \"{}\"", preview),
-                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(theme::palette().muted),
                     ));
                 }
 
@@ -327,7 +514,8 @@ impl TuiApp {
 
     fn draw_job_detail(&self, frame: &mut Frame, inner: Rect) {
         let Some(job) = &self.job_view.detail else {
-            let p = Paragraph::new("Loading job...").style(Style::default().fg(Color::DarkGray));
+            let p =
+                Paragraph::new("Loading job...").style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         };
@@ -352,13 +540,13 @@ impl TuiApp {
             Line::styled(
                 format!("─── JOB: {:?} ─── {} ───", job.job_kind, ghost,),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme::palette().accent)
                     .add_modifier(Modifier::BOLD),
             ),
             Line::styled(
                 format!(
                     "Status: {}  Duration: {}  Session: {}",
-                    job.status.as_deref().unwrap_or("-"),
+                    job.status.as_deref().unwrap_or("running"),
                     dur_str,
                     &job.session_id[..16.min(job.session_id.len())],
                 ),
@@ -367,6 +555,34 @@ impl TuiApp {
             Line::from(""),
         ];
 
+        // Keep in sync with `todo_section_height`.
+        if !job.todo_list.is_empty() {
+            let (finished, total) = todo_progress(&job.todo_list);
+            lines.push(Line::styled(
+                format!("─── TODO {}/{} ───", finished, total),
+                Style::default()
+                    .fg(theme::palette().accent)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            for item in &job.todo_list {
+                let color = match item.status {
+                    TodoStatus::Done => theme::palette().success,
+                    TodoStatus::InProgress => theme::palette().highlight,
+                    TodoStatus::Pending | TodoStatus::Skipped => theme::palette().muted,
+                };
+                let note = item
+                    .note
+                    .as_deref()
+                    .map(|n| format!(" — {}", truncate_snippet(n, 60)))
+                    .unwrap_or_default();
+                lines.push(Line::styled(
+                    format!("  {} {}{}", todo_marker(&item.status), item.title, note),
+                    Style::default().fg(color),
+                ));
+            }
+            lines.push(Line::from(""));
+        }
+
         for entry in &job.transcript {
             let role_str = format!("{:?}", entry.role);
             let model_suffix = entry
@@ -375,8 +591,8 @@ impl TuiApp {
                 .map(|m| format!(" ({})", m))
                 .unwrap_or_default();
             let role_color = match entry.role {
-                t_koma_db::MessageRole::Operator => Color::Yellow,
-                t_koma_db::MessageRole::Ghost => Color::Cyan,
+                t_koma_db::MessageRole::Operator => theme::palette().highlight,
+                t_koma_db::MessageRole::Ghost => theme::palette().accent,
             };
 
             lines.push(Line::styled(
@@ -396,7 +612,7 @@ impl TuiApp {
                         let short_input = truncate_snippet(&input_str, 80);
                         lines.push(Line::styled(
                             format!("  ⚙ {}({})", name, short_input),
-                            Style::default().fg(Color::Magenta),
+                            Style::default().fg(theme::palette().secondary),
                         ));
                     }
                     ContentBlock::ToolResult {
@@ -408,9 +624,9 @@ impl TuiApp {
                             "  ┆ "
                         };
                         let color = if *is_error == Some(true) {
-                            Color::Red
+                            theme::palette().error
                         } else {
-                            Color::DarkGray
+                            theme::palette().muted
                         };
                         let short = truncate_snippet(content, 120);
                         lines.push(Line::styled(
@@ -421,13 +637,28 @@ impl TuiApp {
                     ContentBlock::Image { filename, .. } => {
                         lines.push(Line::styled(
                             format!("  📷 {}", filename),
-                            Style::default().fg(Color::Blue),
+                            Style::default().fg(theme::palette().info),
                         ));
                     }
                     ContentBlock::File { filename, .. } => {
                         lines.push(Line::styled(
                             format!("  📎 {}", filename),
-                            Style::default().fg(Color::Blue),
+                            Style::default().fg(theme::palette().info),
+                        ));
+                    }
+                    ContentBlock::Thinking {
+                        thinking, redacted, ..
+                    } => {
+                        let short = if *redacted {
+                            "(redacted)".to_string()
+                        } else {
+                            truncate_snippet(thinking, 120)
+                        };
+                        lines.push(Line::styled(
+                            format!("  💭 {}", short),
+                            Style::default()
+                                .fg(theme::palette().muted)
+                                .add_modifier(Modifier::ITALIC),
                         ));
                     }
                 }
@@ -444,39 +675,89 @@ impl TuiApp {
     // ── Knowledge ────────────────────────────────────────────────────
 
     fn draw_knowledge_list(&self, frame: &mut Frame, inner: Rect) {
-        if self.knowledge_view.notes.is_empty() {
-            let p =
-                Paragraph::new("No knowledge entries").style(Style::default().fg(Color::DarkGray));
+        let rows = self.knowledge_view.rows();
+        if rows.is_empty() {
+            let text = match &self.knowledge_view.filter {
+                Some(filter) => format!("No entries match \"{}\"", filter),
+                None => "No knowledge entries".to_string(),
+            };
+            let p = Paragraph::new(text).style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         }
 
-        let items: Vec<ListItem> = self
-            .knowledge_view
-            .notes
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(inner);
+        self.draw_knowledge_tree(frame, chunks[0], &rows);
+        self.draw_knowledge_preview(frame, chunks[1], rows.get(self.content_idx));
+    }
+
+    fn draw_knowledge_tree(&self, frame: &mut Frame, area: Rect, rows: &[KnowledgeRow]) {
+        let dim = Style::default().fg(theme::palette().muted);
+        let height = usize::from(area.height).max(1);
+        let offset = self.content_idx.saturating_sub(height - 1);
+        let width = usize::from(area.width);
+
+        let items: Vec<ListItem> = rows
             .iter()
             .enumerate()
-            .map(|(idx, note)| {
-                let tags_str = if note.tags.is_empty() {
-                    String::new()
-                } else {
-                    format!(
-                        " {}",
-                        note.tags
-                            .iter()
-                            .map(|t| format!("#{}", t))
-                            .collect::<Vec<_>>()
-                            .join(" ")
-                    )
+            .skip(offset)
+            .take(height)
+            .map(|(idx, row)| {
+                let line = match row {
+                    KnowledgeRow::Group {
+                        label,
+                        count,
+                        collapsed,
+                        ..
+                    } => Line::from(vec![
+                        Span::styled(if *collapsed { "▸ " } else { "▾ " }, dim),
+                        Span::styled(
+                            *label,
+                            Style::default()
+                                .fg(theme::palette().highlight)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(format!(" ({})", count), dim),
+                    ]),
+                    KnowledgeRow::Topic {
+                        title,
+                        count,
+                        archived,
+                        collapsed,
+                        ..
+                    } => Line::from(vec![
+                        Span::styled(if *collapsed { "  ▸ " } else { "  ▾ " }, dim),
+                        Span::styled(
+                            truncate_snippet(title, width.saturating_sub(12).max(8)),
+                            Style::default().fg(theme::palette().accent),
+                        ),
+                        Span::styled(
+                            format!(" ({}){}", count, if *archived { " archived" } else { "" }),
+                            dim,
+                        ),
+                    ]),
+                    KnowledgeRow::Entry {
+                        title,
+                        entry_type,
+                        depth,
+                        ..
+                    } => {
+                        let indent = "  ".repeat(usize::from(*depth) + 1);
+                        let tag = format!("[{}] ", entry_type.to_uppercase());
+                        let room = width
+                            .saturating_sub(indent.len() + tag.chars().count())
+                            .max(8);
+                        Line::from(vec![
+                            Span::raw(indent),
+                            Span::styled(tag, Style::default().fg(theme::palette().secondary)),
+                            Span::raw(truncate_snippet(title, room)),
+                        ])
+                    }
                 };
-                let text = format!(
-                    "[{}] {}{}\n        {}",
-                    note.entry_type.to_uppercase(),
-                    note.title,
-                    tags_str,
-                    truncate_snippet(&note.snippet, 60),
-                );
-                let mut item = ListItem::new(Text::from(text));
+                let mut item = ListItem::new(line);
                 if idx == self.content_idx && self.focus == FocusPane::Content {
                     item = item.style(theme::selected());
                 }
@@ -484,7 +765,58 @@ impl TuiApp {
             })
             .collect();
 
-        frame.render_widget(List::new(items), inner);
+        frame.render_widget(List::new(items), area);
+    }
+
+    fn draw_knowledge_preview(&self, frame: &mut Frame, area: Rect, row: Option<&KnowledgeRow>) {
+        let dim = Style::default().fg(theme::palette().muted);
+        let block = Block::default().borders(Borders::LEFT).border_style(dim);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let Some(KnowledgeRow::Entry { id, scope, .. }) = row else {
+            let p = Paragraph::new("Select an entry to preview it").style(dim);
+            frame.render_widget(p, inner);
+            return;
+        };
+        let Some(preview) = self.knowledge_view.previews.get(id) else {
+            let p = Paragraph::new("Loading...").style(dim);
+            frame.render_widget(p, inner);
+            return;
+        };
+
+        let mut meta = vec![
+            Span::styled(
+                &preview.entry_type,
+                Style::default().fg(theme::palette().secondary),
+            ),
+            Span::styled(" · ", dim),
+            Span::styled(scope, Style::default().fg(theme::palette().info)),
+        ];
+        if let Some(trust) = preview.trust_score {
+            meta.push(Span::styled(" · trust ", dim));
+            meta.push(Span::styled(
+                trust.to_string(),
+                Style::default().fg(theme::palette().accent),
+            ));
+        }
+        let mut lines = vec![
+            Line::from(Span::styled(
+                preview.title.clone(),
+                Style::default()
+                    .fg(theme::palette().text)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(meta),
+        ];
+        if let Some(path) = &preview.path {
+            lines.push(Line::from(Span::styled(path.clone(), dim)));
+        }
+        lines.push(Line::from(""));
+        lines.extend(markdown_to_lines(&preview.body));
+
+        let p = Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false });
+        frame.render_widget(p, inner);
     }
 
     fn draw_knowledge_detail(&self, frame: &mut Frame, inner: Rect) {
@@ -503,15 +835,16 @@ impl TuiApp {
 
     fn draw_knowledge_stats(&self, frame: &mut Frame, inner: Rect) {
         let Some(stats) = &self.knowledge_view.stats else {
-            let p = Paragraph::new("Loading stats...").style(Style::default().fg(Color::DarkGray));
+            let p = Paragraph::new("Loading stats...")
+                .style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         };
 
-        let dim = Style::default().fg(Color::DarkGray);
-        let accent = Style::default().fg(Color::Cyan);
+        let dim = Style::default().fg(theme::palette().muted);
+        let accent = Style::default().fg(theme::palette().accent);
         let header = Style::default()
-            .fg(Color::Yellow)
+            .fg(theme::palette().highlight)
             .add_modifier(Modifier::BOLD);
 
         let mut lines = vec![
@@ -537,6 +870,18 @@ impl TuiApp {
                 Span::styled(stats.total_embeddings.to_string(), accent),
             ]),
         ];
+        let warn = Style::default().fg(theme::palette().highlight);
+        for (label, count) in [
+            ("  Missing Embeds   ", stats.missing_embeddings),
+            ("  Orphaned Chunks  ", stats.orphaned_chunks),
+        ] {
+            if count > 0 {
+                lines.push(Line::from(vec![
+                    Span::styled(label, dim),
+                    Span::styled(count.to_string(), warn),
+                ]));
+            }
+        }
 
         if !stats.recent_entries.is_empty() {
             lines.push(Line::from(""));
@@ -545,13 +890,16 @@ impl TuiApp {
             for entry in &stats.recent_entries {
                 lines.push(Line::from(vec![
                     Span::styled("  [", dim),
-                    Span::styled(&entry.entry_type, Style::default().fg(Color::Magenta)),
+                    Span::styled(
+                        &entry.entry_type,
+                        Style::default().fg(theme::palette().secondary),
+                    ),
                     Span::styled("] ", dim),
                     Span::raw(&entry.title),
                 ]));
                 lines.push(Line::from(vec![
                     Span::styled("        ", dim),
-                    Span::styled(&entry.scope, Style::default().fg(Color::Blue)),
+                    Span::styled(&entry.scope, Style::default().fg(theme::palette().info)),
                     Span::styled("  ", dim),
                     Span::styled(&entry.updated_at, dim),
                 ]));
@@ -567,7 +915,7 @@ impl TuiApp {
     fn draw_ghost_sessions(&self, frame: &mut Frame, inner: Rect, ghost_name: &str) {
         if self.session_view.sessions.is_empty() {
             let p = Paragraph::new(format!("No sessions for {}", ghost_name))
-                .style(Style::default().fg(Color::DarkGray));
+                .style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         }
@@ -579,17 +927,23 @@ impl TuiApp {
             .enumerate()
             .map(|(idx, sess)| {
                 let active_marker = if sess.is_active { "▶" } else { " " };
-                let text = format!(
+                let mut text = format!(
                     "{} {}  {} msgs",
                     active_marker,
                     &sess.id[..16.min(sess.id.len())],
                     sess.message_count,
                 );
+                if let Some(title) = &sess.title {
+                    text.push_str(&format!("  {}", title));
+                }
+                if !sess.tags.is_empty() {
+                    text.push_str(&format!("  [{}]", sess.tags.join(", ")));
+                }
                 let style = if idx == self.content_idx && self.focus == FocusPane::Content {
                     theme::selected()
                 } else if sess.is_active {
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme::palette().success)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
@@ -601,79 +955,119 @@ impl TuiApp {
         frame.render_widget(List::new(items), inner);
     }
 
+    fn draw_session_search(&self, frame: &mut Frame, inner: Rect) {
+        if self.session_view.search_hits.is_empty() {
+            let p = Paragraph::new("No matching messages")
+                .style(Style::default().fg(theme::palette().muted));
+            frame.render_widget(p, inner);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .session_view
+            .search_hits
+            .iter()
+            .enumerate()
+            .map(|(idx, hit)| {
+                let when = chrono::DateTime::from_timestamp(hit.created_at, 0)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let text = format!(
+                    "{}  {:?}  {}\n        {}",
+                    when,
+                    hit.role,
+                    &hit.session_id[..16.min(hit.session_id.len())],
+                    truncate_snippet(&hit.snippet, 80),
+                );
+                let mut item = ListItem::new(Text::from(text));
+                if idx == self.content_idx && self.focus == FocusPane::Content {
+                    item = item.style(theme::selected());
+                }
+                item
+            })
+            .collect();
+
+        frame.render_widget(List::new(items), inner);
+    }
+
     fn draw_session_messages(&self, frame: &mut Frame, inner: Rect) {
         if self.session_view.messages.is_empty() {
-            let p = Paragraph::new("No messages").style(Style::default().fg(Color::DarkGray));
+            let p =
+                Paragraph::new("No messages").style(Style::default().fg(theme::palette().muted));
             frame.render_widget(p, inner);
             return;
         }
 
-        let mut lines: Vec<Line> = Vec::new();
-        for msg in &self.session_view.messages {
-            let role_color = match msg.role {
-                t_koma_db::MessageRole::Operator => Color::Yellow,
-                t_koma_db::MessageRole::Ghost => Color::Cyan,
-            };
-            let model_suffix = msg
-                .model
-                .as_deref()
-                .map(|m| format!(" ({})", m))
-                .unwrap_or_default();
-
-            lines.push(Line::styled(
-                format!("─── {:?}{} ───", msg.role, model_suffix),
-                Style::default().fg(role_color).add_modifier(Modifier::BOLD),
-            ));
+        // Flag the most expensive exchanges (top 3 by cost, or tokens when unpriced).
+        let mut weights: Vec<f64> = self
+            .session_view
+            .messages
+            .iter()
+            .filter_map(|m| m.usage.as_ref().map(usage_weight))
+            .collect();
+        weights.sort_by(|a, b| b.total_cmp(a));
+        let expensive_from = weights.get(2).or(weights.last()).copied();
 
-            for block in &msg.content {
-                match block {
-                    ContentBlock::Text { text } => {
-                        for line in text.lines() {
-                            lines.push(Line::from(line.to_string()));
-                        }
-                    }
-                    ContentBlock::ToolUse { name, input, .. } => {
-                        let input_str = serde_json::to_string(input).unwrap_or_default();
-                        let short = truncate_snippet(&input_str, 80);
-                        lines.push(Line::styled(
-                            format!("  ⚙ {}({})", name, short),
-                            Style::default().fg(Color::Magenta),
-                        ));
-                    }
-                    ContentBlock::ToolResult {
-                        content, is_error, ..
-                    } => {
-                        let prefix = if *is_error == Some(true) {
-                            "  ✗ "
-                        } else {
-                            "  ┆ "
-                        };
-                        let color = if *is_error == Some(true) {
-                            Color::Red
+        let view = &self.session_view;
+        let current_match = view.search_matches.get(view.search_idx).copied();
+        let mut lines: Vec<Line> = Vec::new();
+        for (idx, line) in transcript_lines(&view.messages, view.tools_expanded)
+            .into_iter()
+            .enumerate()
+        {
+            let msg = &view.messages[line.message];
+            let mut rendered = match line.kind {
+                TranscriptLineKind::Header => {
+                    let role_color = match msg.role {
+                        t_koma_db::MessageRole::Operator => theme::palette().highlight,
+                        t_koma_db::MessageRole::Ghost => theme::palette().accent,
+                    };
+                    let mut header = vec![Span::styled(
+                        line.text,
+                        Style::default().fg(role_color).add_modifier(Modifier::BOLD),
+                    )];
+                    if let Some(usage) = &msg.usage {
+                        let expensive = weights.len() > 1
+                            && expensive_from.is_some_and(|from| usage_weight(usage) >= from);
+                        let (marker, color) = if expensive {
+                            ("▲ ", theme::palette().error)
                         } else {
-                            Color::DarkGray
+                            ("", theme::palette().muted)
                         };
-                        let short = truncate_snippet(content, 120);
-                        lines.push(Line::styled(
-                            format!("{}{}", prefix, short),
+                        header.push(Span::styled(
+                            format!(" {marker}{}", format_message_usage(usage)),
                             Style::default().fg(color),
                         ));
                     }
-                    ContentBlock::Image { filename, .. } => {
-                        lines.push(Line::styled(
-                            format!("  📷 {}", filename),
-                            Style::default().fg(Color::Blue),
-                        ));
-                    }
-                    ContentBlock::File { filename, .. } => {
-                        lines.push(Line::styled(
-                            format!("  📎 {}", filename),
-                            Style::default().fg(Color::Blue),
-                        ));
-                    }
+                    Line::from(header)
+                }
+                TranscriptLineKind::Text | TranscriptLineKind::Blank => Line::from(line.text),
+                TranscriptLineKind::ToolUse => {
+                    Line::styled(line.text, Style::default().fg(theme::palette().secondary))
                 }
+                TranscriptLineKind::ToolResult => {
+                    Line::styled(line.text, Style::default().fg(theme::palette().muted))
+                }
+                TranscriptLineKind::ToolError => {
+                    Line::styled(line.text, Style::default().fg(theme::palette().error))
+                }
+                TranscriptLineKind::Attachment => {
+                    Line::styled(line.text, Style::default().fg(theme::palette().info))
+                }
+                TranscriptLineKind::Thinking => Line::styled(
+                    line.text,
+                    Style::default()
+                        .fg(theme::palette().muted)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            };
+            let idx = idx as u16;
+            if current_match == Some(idx) {
+                rendered = rendered.patch_style(Style::default().bg(theme::palette().match_bg));
+            } else if view.search_matches.binary_search(&idx).is_ok() {
+                rendered = rendered.patch_style(Style::default().bg(theme::palette().match_dim_bg));
             }
-            lines.push(Line::from(""));
+            lines.push(rendered);
         }
 
         let p = Paragraph::new(Text::from(lines))
@@ -685,14 +1079,32 @@ impl TuiApp {
 
 fn job_status_style(status: Option<&str>) -> (&'static str, Color) {
     match status {
-        Some("ran") | Some("ok") => ("✓", Color::Green),
+        Some("ran") | Some("ok") => ("✓", theme::palette().success),
         Some(s) if s.starts_with("ok ") || s.starts_with("ok[") || s.starts_with("ok (") => {
-            ("✓", Color::Green)
+            ("✓", theme::palette().success)
         }
-        Some(s) if s.starts_with("error") => ("✗", Color::Red),
-        Some("skipped") | Some("suppressed") => ("·", Color::Yellow),
-        _ => ("?", Color::DarkGray),
+        Some(s) if s.starts_with("error") => ("✗", theme::palette().error),
+        Some("skipped") | Some("suppressed") => ("·", theme::palette().highlight),
+        Some("cancelled") => ("■", theme::palette().highlight),
+        None => ("▶", theme::palette().info),
+        _ => ("?", theme::palette().muted),
+    }
+}
+
+/// Compact TODO progress appended to a job list row.
+fn todo_suffix(todos: &[TodoItem]) -> String {
+    if todos.is_empty() {
+        return String::new();
     }
+    let (finished, total) = todo_progress(todos);
+    format!("  [todo {}/{}]", finished, total)
+}
+
+fn format_next_run(next_run: Option<i64>) -> String {
+    next_run
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
 }
 
 fn truncate_snippet(s: &str, max: usize) -> String {
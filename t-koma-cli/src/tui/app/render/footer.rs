@@ -1,14 +1,19 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
 };
 
-use crate::tui::state::{Category, FocusPane};
+use t_koma_core::KeyPreset;
 
-use super::super::{TuiApp, state::ContentView};
+use crate::tui::{
+    state::{Category, FocusPane},
+    theme,
+};
+
+use super::super::{TuiApp, keymap::KeyAction, state::ContentView};
 
 impl TuiApp {
     pub(super) fn draw_footer(&self, frame: &mut Frame, area: Rect) {
@@ -17,43 +22,83 @@ impl TuiApp {
 
         for (idx, (key, desc)) in hints.iter().enumerate() {
             if idx > 0 {
-                spans.push(Span::styled(" ", Style::default().fg(Color::DarkGray)));
+                spans.push(Span::styled(
+                    " ",
+                    Style::default().fg(theme::palette().muted),
+                ));
             }
             spans.push(Span::styled(
                 format!(" {} ", key),
                 Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Rgb(60, 80, 90))
+                    .fg(theme::palette().on_accent)
+                    .bg(theme::palette().hint_bg)
                     .add_modifier(Modifier::BOLD),
             ));
             spans.push(Span::styled(
                 format!(" {}", desc),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::palette().muted),
             ));
         }
 
         let status_text = format!("  {}", self.status);
         spans.push(Span::styled(
             status_text,
-            Style::default().fg(Color::Rgb(80, 100, 110)),
+            Style::default().fg(theme::palette().status_text),
         ));
 
         frame.render_widget(Paragraph::new(Line::from(spans)), area);
     }
 
-    fn current_hints(&self) -> Vec<(&'static str, &'static str)> {
+    fn current_hints(&self) -> Vec<(String, &'static str)> {
+        let key = |action| self.keymap.label(action);
+        let nav = format!("{}/{}", key(KeyAction::Down), key(KeyAction::Up));
         if self.modal.is_some() {
-            return vec![("j/k", "Nav"), ("Enter", "Select"), ("Esc", "Cancel")];
+            return vec![
+                (nav, "Nav"),
+                (key(KeyAction::Open), "Select"),
+                (key(KeyAction::Back), "Cancel"),
+            ];
         }
 
+        if self.prompt.paste.is_some() {
+            return vec![
+                ("Enter".into(), "Newline"),
+                ("Ctrl+S".into(), "Apply"),
+                ("Esc".into(), "Discard"),
+            ];
+        }
         if self.prompt.kind.is_some() {
-            return vec![("Enter", "Submit"), ("Esc", "Cancel")];
+            return vec![
+                ("Enter".into(), "Submit"),
+                ("Ctrl+V".into(), "Paste"),
+                ("Esc".into(), "Cancel"),
+            ];
         }
 
-        let mut hints = vec![("Tab", "Pane"), ("j/k", "Nav"), ("q", "Quit")];
+        if self.focus == FocusPane::Content && self.content_view == ContentView::Chat {
+            let mut hints = vec![("Enter".into(), "Send")];
+            if self.chat_view.panes.len() > 1 {
+                hints.push(("Tab".into(), "Switch chat"));
+            }
+            hints.extend([
+                ("PgUp/PgDn".into(), "Scroll"),
+                ("Ctrl+W".into(), "Close chat"),
+                ("Esc".into(), "Back"),
+            ]);
+            return hints;
+        }
+
+        let mut hints = vec![
+            (key(KeyAction::FocusNext), "Pane"),
+            (nav, "Nav"),
+            (key(KeyAction::Quit), "Quit"),
+        ];
+        if self.keymap.preset == KeyPreset::Vim {
+            hints.push((key(KeyAction::CommandPalette), "Command"));
+        }
 
         if self.content_view != ContentView::List {
-            hints.push(("Esc", "Back"));
+            hints.push((key(KeyAction::Back), "Back"));
         }
 
         let scrollable_content = self.focus == FocusPane::Content
@@ -61,37 +106,111 @@ impl TuiApp {
                 (&self.content_view, self.selected_category()),
                 (ContentView::JobDetail { .. }, _)
                     | (ContentView::KnowledgeDetail { .. }, _)
+                    | (ContentView::ConfigDiff, _)
+                    | (ContentView::ConfigBackups, _)
                     | (ContentView::SessionMessages { .. }, _)
                     | (ContentView::List, Category::Config)
                     | (ContentView::List, Category::Gate)
             );
         if scrollable_content {
-            hints.push(("u/d", "Page"));
+            hints.push((
+                format!("{}/{}", key(KeyAction::PageUp), key(KeyAction::PageDown)),
+                "Page",
+            ));
+        }
+        if self.focus == FocusPane::Content
+            && matches!(self.content_view, ContentView::SessionMessages { .. })
+        {
+            hints.push(("f".into(), "Fork here"));
+            hints.push((key(KeyAction::Search), "Search"));
+            if !self.session_view.search_matches.is_empty() {
+                hints.push(("n/N".into(), "Next/prev"));
+            }
+            hints.push((
+                "t".into(),
+                if self.session_view.tools_expanded {
+                    "Fold tools"
+                } else {
+                    "Unfold tools"
+                },
+            ));
+            hints.push(("m".into(), "Export .md"));
+            hints.push(("y/Y".into(), "Copy reply/all"));
+            hints.push(("b".into(), "Copy code"));
+        }
+        if self.focus == FocusPane::Content
+            && matches!(self.content_view, ContentView::GhostSessions { .. })
+        {
+            hints.push(("e".into(), "Export"));
+            hints.push(("i".into(), "Import"));
+            hints.push(("o".into(), "Chat"));
         }
 
         match self.selected_category() {
+            Category::Config
+                if self.focus == FocusPane::Content
+                    && self.content_view == ContentView::ConfigDiff =>
+            {
+                if self.settings_dirty {
+                    hints.push(("s".into(), "Save"));
+                }
+            }
+            Category::Config
+                if self.focus == FocusPane::Content
+                    && self.content_view == ContentView::ConfigBackups =>
+            {
+                hints.push((key(KeyAction::Open), "Restore"));
+            }
             Category::Gate => {
-                hints.push(("r", "Restart"));
-                hints.push(("/", "Search"));
-                hints.push(("1-6", "Filter"));
+                hints.push(("r".into(), "Restart"));
+                hints.push(("l".into(), "Reload config"));
+                hints.push((key(KeyAction::Search), "Search"));
+                hints.push(("1-6".into(), "Filter"));
             }
             Category::Operators
                 if self.focus == FocusPane::Content
                     && self.operator_view == super::super::state::OperatorView::Pending =>
             {
-                hints.push(("a", "Approve"));
-                hints.push(("d", "Deny"));
+                hints.push(("a".into(), "Approve"));
+                hints.push(("d".into(), "Deny"));
+            }
+            Category::Knowledge
+                if self.focus == FocusPane::Content && self.content_view == ContentView::List =>
+            {
+                hints.push((key(KeyAction::Open), "Open/Fold"));
+                hints.push((key(KeyAction::Search), "Filter"));
+                hints.push(("e".into(), "Edit"));
+                hints.push(("t".into(), "Trust"));
+                hints.push(("x".into(), "Delete"));
+            }
+            Category::Jobs
+                if self.focus == FocusPane::Content
+                    && self.content_view == ContentView::List
+                    && self.job_view.mode == super::super::state::JobViewMode::Tasks
+                    && self.content_idx < self.job_view.tasks.len() =>
+            {
+                hints.push((key(KeyAction::Open), "Open run"));
+                hints.push(("p".into(), "Pause/resume"));
+                hints.push(("x".into(), "Delete"));
+            }
+            Category::Jobs
+                if self.focus == FocusPane::Content && self.cancellable_job().is_some() =>
+            {
+                if self.content_view == ContentView::List {
+                    hints.push((key(KeyAction::Open), "Open"));
+                }
+                hints.push(("x".into(), "Cancel job"));
             }
             _ => match self.focus {
                 FocusPane::Categories => {
-                    hints.push(("1-6", "Jump"));
+                    hints.push(("1-7".into(), "Jump"));
                 }
                 FocusPane::Options => {
-                    hints.push(("a-z", "Select"));
-                    hints.push(("Enter", "Open"));
+                    hints.push(("a-z".into(), "Select"));
+                    hints.push((key(KeyAction::Open), "Open"));
                 }
                 FocusPane::Content => {
-                    hints.push(("Enter", "Open"));
+                    hints.push((key(KeyAction::Open), "Open"));
                 }
             },
         }
@@ -1,7 +1,7 @@
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
@@ -36,17 +36,38 @@ impl TuiApp {
             Span::raw(" | "),
             Span::styled(
                 format!("󰀄 {}", self.metrics.operator_count),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme::palette().success),
             ),
             Span::raw(" | "),
             Span::styled(
                 format!("󰊠 {}", self.metrics.ghost_count),
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(theme::palette().accent),
             ),
             Span::raw(" | "),
             Span::styled(
                 format!("󰭻/5m {}", self.metrics.recent_message_count),
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(theme::palette().highlight),
+            ),
+            Span::raw(" | "),
+            Span::styled(
+                format!(
+                    "$/day {:.2} ({}k tok)",
+                    self.metrics.today_cost_usd,
+                    self.metrics.today_tokens / 1000
+                ),
+                Style::default().fg(theme::palette().success_soft),
+            ),
+            Span::raw(" | "),
+            Span::styled(
+                match self.metrics.cache_hit_rate {
+                    Some(rate) => format!(
+                        "cache {:.0}% ${:+.2}",
+                        rate * 100.0,
+                        self.metrics.cache_savings_usd
+                    ),
+                    None => "cache -".to_string(),
+                },
+                Style::default().fg(theme::palette().accent_soft),
             ),
         ]);
 
@@ -66,9 +87,16 @@ impl TuiApp {
                 gate_style.add_modifier(Modifier::BOLD),
             ),
             Span::raw(" | "),
-            Span::styled(format!("󰒓 {}", model), Style::default().fg(Color::Magenta)),
+            Span::styled(
+                format!("󰒓 {}", model),
+                Style::default().fg(theme::palette().secondary),
+            ),
             Span::raw(" | "),
-            Span::styled(marquee, Style::default().fg(Color::LightBlue)),
+            if self.pending_approvals.is_empty() {
+                Span::styled(marquee, Style::default().fg(theme::palette().info_soft))
+            } else {
+                approval_banner(self.pending_approvals.len(), self.anim_tick)
+            },
         ]);
 
         let p = Paragraph::new(vec![top, second]).block(
@@ -81,7 +109,7 @@ impl TuiApp {
         let dot_style = if self.gate_connected {
             Style::default().fg(dot_color).add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(theme::palette().muted)
         };
         let pulse_lines = vec![
             Line::from(Span::styled("   ╭──────╮   ", dot_style)),
@@ -95,3 +123,18 @@ impl TuiApp {
         frame.render_widget(pulse_widget, chunks[1]);
     }
 }
+
+/// Flashing reminder that approvals are waiting; `A` opens them.
+fn approval_banner(count: usize, tick: usize) -> Span<'static> {
+    let style = if (tick / 8).is_multiple_of(2) {
+        Style::default()
+            .fg(theme::palette().on_accent)
+            .bg(theme::palette().highlight)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+            .fg(theme::palette().highlight)
+            .add_modifier(Modifier::BOLD)
+    };
+    Span::styled(format!(" ⚠ {} APPROVAL(S) PENDING · A ", count), style)
+}
@@ -1,3 +1,4 @@
+mod chat;
 mod content;
 mod footer;
 mod header;
@@ -5,6 +6,7 @@ mod modal;
 mod onboarding;
 mod prompt;
 mod sidebar;
+mod usage;
 
 use ratatui::{
     Frame,
@@ -53,6 +55,9 @@ impl TuiApp {
         if let Some(kind) = self.prompt.kind {
             self.draw_prompt_overlay(frame, kind);
         }
+        if let Some(paste) = &self.prompt.paste {
+            self.draw_paste_overlay(frame, paste);
+        }
 
         if self.modal.is_some() {
             self.draw_modal(frame);
@@ -1,8 +1,8 @@
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    widgets::{Block, Borders, Clear, List, ListItem},
+    style::Style,
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
 };
 
 use crate::tui::theme;
@@ -15,8 +15,12 @@ impl TuiApp {
             return;
         };
 
-        let height = (modal.items.len() as u16 + 4).min(20);
-        let width = 40u16;
+        // Detail text wraps above the choices in a wider box.
+        let (width, detail_height) = match &modal.detail {
+            Some(detail) => (64u16, (detail.chars().count() as u16 / 60 + 2).min(8)),
+            None => (40u16, 0),
+        };
+        let height = (modal.items.len() as u16 + 4 + detail_height).min(24);
         let area = centered_fixed(width, height, frame.area());
         frame.render_widget(Clear, area);
 
@@ -24,9 +28,23 @@ impl TuiApp {
             .title(format!(" {} ", modal.title))
             .borders(Borders::ALL)
             .border_style(theme::border(true));
-        let inner = block.inner(area);
+        let mut inner = block.inner(area);
         frame.render_widget(block, area);
 
+        if let Some(detail) = &modal.detail {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(detail_height), Constraint::Min(0)])
+                .split(inner);
+            frame.render_widget(
+                Paragraph::new(detail.as_str())
+                    .wrap(Wrap { trim: true })
+                    .style(Style::default().fg(theme::palette().highlight)),
+                chunks[0],
+            );
+            inner = chunks[1];
+        }
+
         let items: Vec<ListItem> = modal
             .items
             .iter()
@@ -35,7 +53,7 @@ impl TuiApp {
                 let style = if idx == modal.selected_idx {
                     theme::selected()
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(theme::palette().text)
                 };
                 let prefix = if idx == modal.selected_idx {
                     "▸ "
@@ -1,7 +1,7 @@
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
@@ -27,7 +27,7 @@ impl TuiApp {
             .borders(Borders::ALL)
             .border_style(
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme::palette().accent)
                     .add_modifier(Modifier::BOLD),
             );
         let inner = outer.inner(area);
@@ -51,9 +51,9 @@ impl TuiApp {
 fn draw_progress_bar(frame: &mut Frame, area: Rect, ob: &OnboardingState) {
     let current = ob.step.index();
     let total = OnboardingStep::total();
-    let dim = Style::default().fg(Color::DarkGray);
+    let dim = Style::default().fg(theme::palette().muted);
     let active = Style::default()
-        .fg(Color::Cyan)
+        .fg(theme::palette().accent)
         .add_modifier(Modifier::BOLD);
 
     let steps: Vec<Span> = (0..total)
@@ -90,7 +90,7 @@ fn draw_step_content(frame: &mut Frame, area: Rect, ob: &OnboardingState) {
 }
 
 fn draw_footer(frame: &mut Frame, area: Rect, ob: &OnboardingState) {
-    let dim = Style::default().fg(Color::DarkGray);
+    let dim = Style::default().fg(theme::palette().muted);
     let hint = match &ob.step {
         OnboardingStep::Welcome => "Enter: Continue  |  Esc: Skip setup",
         OnboardingStep::Summary => "Enter: Apply & Save  |  Backspace: Go back  |  Esc: Cancel",
@@ -108,12 +108,14 @@ fn draw_footer(frame: &mut Frame, area: Rect, ob: &OnboardingState) {
 
 fn build_step_lines(ob: &OnboardingState) -> Vec<Line<'static>> {
     let heading = Style::default()
-        .fg(Color::Cyan)
+        .fg(theme::palette().accent)
+        .add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(theme::palette().muted);
+    let normal = Style::default().fg(theme::palette().text);
+    let accent = Style::default().fg(theme::palette().highlight);
+    let warning = Style::default()
+        .fg(theme::palette().error)
         .add_modifier(Modifier::BOLD);
-    let dim = Style::default().fg(Color::DarkGray);
-    let normal = Style::default().fg(Color::White);
-    let accent = Style::default().fg(Color::Yellow);
-    let warning = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
 
     match &ob.step {
         OnboardingStep::Welcome => vec![
@@ -324,9 +326,9 @@ fn build_step_lines(ob: &OnboardingState) -> Vec<Line<'static>> {
                     Span::styled(
                         if has_key { "set" } else { "not set" }.to_string(),
                         if has_key {
-                            Style::default().fg(Color::Green)
+                            Style::default().fg(theme::palette().success)
                         } else {
-                            Style::default().fg(Color::Red)
+                            Style::default().fg(theme::palette().error)
                         },
                     ),
                 ]),
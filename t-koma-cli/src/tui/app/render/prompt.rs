@@ -5,7 +5,11 @@ use ratatui::{
 
 use crate::tui::theme;
 
-use super::super::{TuiApp, state::PromptKind, util::centered_rect};
+use super::super::{
+    TuiApp,
+    state::PromptKind,
+    util::{centered_rect, join_pasted_lines},
+};
 
 impl TuiApp {
     pub(super) fn draw_prompt_overlay(&self, frame: &mut Frame, kind: PromptKind) {
@@ -38,7 +42,30 @@ impl TuiApp {
                     PromptKind::DeleteGhostConfirmTwo => "Type ghost name",
                     PromptKind::GateSearch => "Search logs (blank clears)",
                     PromptKind::SetOperatorRateLimits => "Rate limits: 5m,1h or 'none'",
+                    PromptKind::SetOperatorPermissions => {
+                        "Permissions: name=on|off|default, ... (use_shell, tool:web_fetch, ...)"
+                    }
+                    PromptKind::SetToolPolicies => {
+                        "Tool policies: [ghost:] tool=allow|deny|auto|default, ... (web_*, mcp__github__*)"
+                    }
+                    PromptKind::SetPathApprovals => {
+                        "Path approvals: path=read|write|default, ... (~/projects/foo=read)"
+                    }
                     PromptKind::KnowledgeSearch => "Search knowledge",
+                    PromptKind::KnowledgeFilter => "Filter titles (fuzzy, blank clears)",
+                    PromptKind::KnowledgeDeleteConfirm => "Type DELETE to remove the entry",
+                    PromptKind::KnowledgeSetTrust => "Trust score (0-10)",
+                    PromptKind::SessionSearch => "Search session messages",
+                    PromptKind::TranscriptSearch => "Search this transcript (blank clears)",
+                    PromptKind::ApprovalSteps => "Continue the tool loop for N steps",
+                    PromptKind::SessionImport => "Path to session .jsonl export",
+                    PromptKind::RenameGhost => "New ghost name",
+                    PromptKind::CloneGhost => {
+                        "New name[, soul, skills, settings, notes, diary, references]"
+                    }
+                    PromptKind::CommandPalette => {
+                        ": q, a category (ghosts, 7), approvals, theme [name] or an option (reload)"
+                    }
                     PromptKind::AddProviderApiKey => unreachable!(),
                 };
                 format!("{}\n\n{}", title, self.prompt.buffer)
@@ -49,6 +76,27 @@ impl TuiApp {
         frame.render_widget(p, inner);
     }
 
+    /// Multi-line paste editor drawn over the prompt it feeds.
+    pub(super) fn draw_paste_overlay(&self, frame: &mut Frame, paste: &str) {
+        let area = centered_rect(80, 60, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Paste · {} lines", paste.lines().count()))
+            .borders(Borders::ALL)
+            .border_style(theme::border(true));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let list = self.prompt.kind.is_some_and(PromptKind::takes_list);
+        let body = format!("{}▏\n\n→ {}", paste, join_pasted_lines(paste, list));
+        // Keep the end of a long paste (where typing happens) in view.
+        let overflow = body.lines().count().saturating_sub(inner.height as usize);
+        let p = Paragraph::new(body)
+            .wrap(Wrap { trim: false })
+            .scroll((overflow as u16, 0));
+        frame.render_widget(p, inner);
+    }
+
     fn provider_api_key_instructions(&self) -> String {
         let provider = self.prompt.target_ghost.as_deref().unwrap_or("unknown");
 
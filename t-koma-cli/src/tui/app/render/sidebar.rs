@@ -1,7 +1,7 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem},
 };
@@ -24,11 +24,11 @@ impl TuiApp {
             .map(|(idx, cat)| {
                 let selected = idx == self.category_idx;
                 let key_style = Style::default()
-                    .fg(Color::Black)
+                    .fg(theme::palette().on_accent)
                     .bg(if selected {
                         glow_color(self.anim_tick)
                     } else {
-                        Color::Rgb(60, 80, 90)
+                        theme::palette().hint_bg
                     })
                     .add_modifier(Modifier::BOLD);
                 let label_style = if selected {
@@ -65,11 +65,11 @@ impl TuiApp {
             .map(|(idx, opt)| {
                 let selected = idx == self.options_idx && self.focus == FocusPane::Options;
                 let key_style = Style::default()
-                    .fg(Color::Black)
+                    .fg(theme::palette().on_accent)
                     .bg(if selected {
-                        Color::Cyan
+                        theme::palette().accent
                     } else {
-                        Color::Rgb(60, 80, 90)
+                        theme::palette().hint_bg
                     })
                     .add_modifier(Modifier::BOLD);
                 let label_style = if selected {
@@ -0,0 +1,169 @@
+use chrono::Utc;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Sparkline},
+};
+
+use crate::tui::theme;
+
+use super::super::{
+    TuiApp,
+    usage::{
+        UsageSeries, budget_ratio, daily_values, day_range, progress_bar, row_tokens,
+        sparkline_text, usage_series,
+    },
+    util::truncate_for_cell,
+};
+
+impl TuiApp {
+    pub(super) fn draw_usage_dashboard(&self, frame: &mut Frame, inner: Rect) {
+        let view = &self.usage_view;
+        if !view.loaded {
+            frame.render_widget(
+                Paragraph::new("Select a range to load usage (needs the gateway)")
+                    .style(Style::default().fg(theme::palette().muted)),
+                inner,
+            );
+            return;
+        }
+
+        let days = day_range(Utc::now().date_naive(), view.since_days);
+        let day_rows: Vec<_> = view.days.iter().collect();
+        let daily_tokens: Vec<u64> = daily_values(&day_rows, &days, |row| row_tokens(row) as f64)
+            .into_iter()
+            .map(|v| v as u64)
+            .collect();
+        // Cents, so small daily costs still show.
+        let daily_cents: Vec<u64> = daily_values(&day_rows, &days, |row| row.cost_usd * 100.0)
+            .into_iter()
+            .map(|v| v.round() as u64)
+            .collect();
+        let total_tokens: u64 = daily_tokens.iter().sum();
+        let total_cost: f64 = view.days.iter().map(|row| row.cost_usd).sum();
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Min(0),
+            ])
+            .split(inner);
+
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::default().borders(Borders::TOP).title(format!(
+                    " Tokens/day · {} days · {}k total ",
+                    view.since_days,
+                    total_tokens / 1000
+                )))
+                .data(&daily_tokens)
+                .style(Style::default().fg(theme::palette().accent)),
+            chunks[0],
+        );
+        frame.render_widget(
+            Sparkline::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::TOP)
+                        .title(format!(" Cost/day · ${:.2} total ", total_cost)),
+                )
+                .data(&daily_cents)
+                .style(Style::default().fg(theme::palette().success_soft)),
+            chunks[1],
+        );
+
+        let mut lines = Vec::new();
+        push_series(&mut lines, "Models", &usage_series(&view.models, &days));
+        push_series(&mut lines, "Ghosts", &usage_series(&view.ghosts, &days));
+
+        lines.push(section_header("Budgets (month to date)"));
+        if view.budgets.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  No budgets set",
+                Style::default().fg(theme::palette().muted),
+            )));
+        }
+        for budget in &view.budgets {
+            let ratio = budget_ratio(budget);
+            let color = if ratio >= 1.0 {
+                theme::palette().error
+            } else if ratio >= budget.warn_ratio {
+                theme::palette().highlight
+            } else {
+                theme::palette().success
+            };
+            let mut limits = Vec::new();
+            if let Some(limit) = budget.token_limit {
+                limits.push(format!(
+                    "{}k/{}k tok",
+                    budget.used_tokens / 1000,
+                    limit / 1000
+                ));
+            }
+            if let Some(limit) = budget.cost_limit_usd {
+                limits.push(format!("${:.2}/${:.2}", budget.used_cost_usd, limit));
+            }
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "  {:<20} ",
+                    truncate_for_cell(
+                        &format!("{:?}: {}", budget.scope, budget.label).to_lowercase(),
+                        20
+                    )
+                )),
+                Span::styled(progress_bar(ratio, 20), Style::default().fg(color)),
+                Span::styled(
+                    format!(" {:>4.0}% ", ratio * 100.0),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    limits.join(" · "),
+                    Style::default().fg(theme::palette().muted),
+                ),
+            ]));
+        }
+
+        frame.render_widget(Paragraph::new(lines).scroll((view.scroll, 0)), chunks[2]);
+    }
+}
+
+fn section_header(title: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        title.to_string(),
+        Style::default()
+            .fg(theme::palette().secondary)
+            .add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn push_series(lines: &mut Vec<Line<'static>>, title: &str, series: &[UsageSeries]) {
+    lines.push(section_header(title));
+    if series.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No usage in range",
+            Style::default().fg(theme::palette().muted),
+        )));
+    }
+    for entry in series {
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {:<20} ", truncate_for_cell(&entry.label, 20))),
+            Span::styled(
+                format!("{:>8}k tok ", entry.tokens / 1000),
+                Style::default().fg(theme::palette().accent),
+            ),
+            Span::styled(
+                format!("${:>8.2} ", entry.cost_usd),
+                Style::default().fg(theme::palette().success_soft),
+            ),
+            Span::styled(
+                sparkline_text(&entry.daily_tokens),
+                Style::default().fg(theme::palette().highlight),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+}
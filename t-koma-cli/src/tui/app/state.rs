@@ -1,5 +1,10 @@
-use t_koma_core::{KnowledgeIndexStats, KnowledgeResultInfo};
-use t_koma_db::{Ghost, JobLog, JobLogSummary, SessionInfo};
+use std::collections::{HashMap, HashSet};
+
+use t_koma_core::{
+    ChatAttachment, KnowledgeIndexStats, KnowledgeResultInfo, KnowledgeTopicInfo, UsageBudgetInfo,
+    UsageReportRow,
+};
+use t_koma_db::{Ghost, JobLog, JobLogSummary, MessageSearchHit, ScheduledTask, SessionInfo};
 
 /// A single option in the options panel with a hotkey for which-key navigation.
 #[derive(Debug, Clone)]
@@ -24,7 +29,20 @@ pub(super) enum PromptKind {
     DeleteGhostConfirmTwo,
     GateSearch,
     SetOperatorRateLimits,
+    SetOperatorPermissions,
+    SetToolPolicies,
+    SetPathApprovals,
     KnowledgeSearch,
+    KnowledgeFilter,
+    KnowledgeDeleteConfirm,
+    KnowledgeSetTrust,
+    SessionSearch,
+    TranscriptSearch,
+    ApprovalSteps,
+    SessionImport,
+    RenameGhost,
+    CloneGhost,
+    CommandPalette,
     AddProviderApiKey, // Enter API key for selected provider
 }
 
@@ -34,6 +52,21 @@ pub(super) struct PromptState {
     pub(super) buffer: String,
     pub(super) target_ghost: Option<String>,
     pub(super) target_operator_id: Option<String>,
+    /// Multi-line paste being edited before it is folded into `buffer`.
+    pub(super) paste: Option<String>,
+}
+
+impl PromptKind {
+    /// Prompts taking a comma-separated list, so pasted lines join with ", ".
+    pub(super) fn takes_list(self) -> bool {
+        matches!(
+            self,
+            PromptKind::SetOperatorPermissions
+                | PromptKind::SetToolPolicies
+                | PromptKind::SetPathApprovals
+                | PromptKind::CloneGhost
+        )
+    }
 }
 
 /// Current content sub-view for drill-down navigation.
@@ -49,6 +82,10 @@ pub(super) enum ContentView {
         ghost_name: String,
         session_id: String,
     },
+    SessionSearch {
+        ghost_name: String,
+        query: String,
+    },
     JobDetail {
         job_id: String,
     },
@@ -56,6 +93,12 @@ pub(super) enum ContentView {
         note_id: String,
     },
     KnowledgeStats,
+    /// Side-by-side diff of the in-memory settings against `config.toml`
+    ConfigDiff,
+    /// Timestamped `config.toml` backups, restorable one by one
+    ConfigBackups,
+    /// Up to two live chat sessions side by side
+    Chat,
 }
 
 /// Selection modal for choosing from a list (e.g. access level).
@@ -66,6 +109,8 @@ pub(super) struct SelectionModal {
     pub(super) selected_idx: usize,
     pub(super) on_select: SelectionAction,
     pub(super) context: Option<String>,
+    /// Text shown above the choices
+    pub(super) detail: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +123,8 @@ pub(super) struct SelectionItem {
 pub(super) enum SelectionAction {
     SetAccessLevel,
     SelectProvider,
+    ResolveApproval,
+    SelectTheme,
 }
 
 #[derive(Debug, Clone)]
@@ -89,10 +136,94 @@ pub(super) struct GateRow {
     pub(super) message: String,
 }
 
+#[derive(Debug)]
+pub(super) enum ApprovalEvent {
+    /// The operator's current pending approvals
+    Snapshot(Vec<t_koma_core::PendingApprovalInfo>),
+    /// Gateway reply on the approvals connection (e.g. the resumed turn)
+    Reply(String),
+    /// Gateway reply to a message sent from a chat pane
+    ChatReply {
+        pane: u64,
+        kind: t_koma_core::GatewayMessageKind,
+        text: String,
+    },
+    /// The gateway moved a chat pane to a new session (`new`)
+    ChatSession { pane: u64, session_id: String },
+    /// The gateway finished handling a chat pane's message
+    ChatDone { pane: u64 },
+}
+
+/// Who gets the replies to a message sent on the operator connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ReplyRoute {
+    /// The status line (approval resolutions, notices)
+    Status,
+    ChatPane(u64),
+    /// Nothing is expected back (`stop` while a turn runs)
+    Untracked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ChatLineKind {
+    Operator,
+    Ghost,
+    Muted,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ChatLine {
+    pub(super) kind: ChatLineKind,
+    pub(super) text: String,
+}
+
+/// One pane of the split chat view.
+#[derive(Debug)]
+pub(super) struct ChatPane {
+    /// Stable id the operator connection routes replies by
+    pub(super) id: u64,
+    pub(super) ghost_name: String,
+    /// Session id, or `active` for the operator's active session
+    pub(super) session_id: String,
+    pub(super) lines: Vec<ChatLine>,
+    pub(super) input: String,
+    /// Files from `/attach`, sent with the next message.
+    pub(super) attachments: Vec<ChatAttachment>,
+    /// Lines scrolled up from the bottom; 0 follows new replies.
+    pub(super) scroll: u16,
+    /// Messages sent and not yet answered in full.
+    pub(super) in_flight: usize,
+}
+
+/// View state for the split chat view.
+#[derive(Debug, Default)]
+pub(super) struct ChatViewState {
+    pub(super) panes: Vec<ChatPane>,
+    pub(super) focused: usize,
+    pub(super) next_id: u64,
+    /// Panes with messages on the connection, in the order the gateway
+    /// handles them; the front one is the turn running now.
+    pub(super) turns: std::collections::VecDeque<u64>,
+}
+
 #[derive(Debug)]
 pub(super) enum GateEvent {
     Status(bool),
     Log(GateRow),
+    /// A transcript entry appended to a running job.
+    JobEntry {
+        job_id: String,
+        entry: t_koma_db::TranscriptEntry,
+    },
+    /// The TODO list of a running job changed.
+    JobTodos {
+        job_id: String,
+        todos: Vec<t_koma_db::TodoItem>,
+    },
+    JobFinished {
+        job_id: String,
+    },
 }
 
 #[derive(Debug, Default, Clone)]
@@ -100,6 +231,12 @@ pub(super) struct Metrics {
     pub(super) operator_count: usize,
     pub(super) ghost_count: usize,
     pub(super) recent_message_count: i64,
+    /// API cost (USD) and tokens recorded since UTC midnight.
+    pub(super) today_cost_usd: f64,
+    pub(super) today_tokens: i64,
+    /// Prompt cache hit rate and net savings since UTC midnight.
+    pub(super) cache_hit_rate: Option<f64>,
+    pub(super) cache_savings_usd: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -116,6 +253,14 @@ pub(super) struct CronFileRow {
     pub(super) enabled: bool,
     pub(super) carry_last_output: bool,
     pub(super) path: String,
+    /// Unix timestamp of the next run, from the gateway when reachable.
+    pub(super) next_run: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TaskRow {
+    pub(super) ghost_name: String,
+    pub(super) task: ScheduledTask,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -123,6 +268,7 @@ pub(super) enum JobViewMode {
     #[default]
     Logs,
     Cron,
+    Tasks,
 }
 
 /// View state for the job viewer.
@@ -131,7 +277,24 @@ pub(super) struct JobViewState {
     pub(super) mode: JobViewMode,
     pub(super) summaries: Vec<JobLogSummary>,
     pub(super) cron_jobs: Vec<CronFileRow>,
+    pub(super) tasks: Vec<TaskRow>,
     pub(super) detail: Option<JobLog>,
+    /// Ghost ID the job log list is filtered by (`None` for all ghosts).
+    pub(super) ghost_filter: Option<String>,
+    /// Job IDs streamed by the gateway that were already looked up, so a job
+    /// outside the current filter is not reloaded on every event.
+    pub(super) live_lookups: HashSet<String>,
+}
+
+impl JobViewState {
+    /// Rows listed above the job logs (CRON files or scheduled tasks).
+    pub(super) fn definition_count(&self) -> usize {
+        match self.mode {
+            JobViewMode::Logs => 0,
+            JobViewMode::Cron => self.cron_jobs.len(),
+            JobViewMode::Tasks => self.tasks.len(),
+        }
+    }
 }
 
 /// View state for session drill-down.
@@ -139,6 +302,28 @@ pub(super) struct JobViewState {
 pub(super) struct SessionViewState {
     pub(super) sessions: Vec<SessionInfo>,
     pub(super) messages: Vec<t_koma_db::Message>,
+    pub(super) search_hits: Vec<MessageSearchHit>,
+    pub(super) scroll: u16,
+    /// Show tool calls, tool results and reasoning in full.
+    pub(super) tools_expanded: bool,
+    /// In-transcript search: query, matching line indexes and current match.
+    pub(super) search: Option<String>,
+    pub(super) search_matches: Vec<u16>,
+    pub(super) search_idx: usize,
+}
+
+/// Usage report rows behind the Metrics dashboard.
+#[derive(Debug, Default)]
+pub(super) struct UsageViewState {
+    pub(super) since_days: u32,
+    /// Daily totals
+    pub(super) days: Vec<UsageReportRow>,
+    /// Per-model usage split per day
+    pub(super) models: Vec<UsageReportRow>,
+    /// Per-ghost usage split per day
+    pub(super) ghosts: Vec<UsageReportRow>,
+    pub(super) budgets: Vec<UsageBudgetInfo>,
+    pub(super) loaded: bool,
     pub(super) scroll: u16,
 }
 
@@ -146,8 +331,217 @@ pub(super) struct SessionViewState {
 #[derive(Debug, Default)]
 pub(super) struct KnowledgeViewState {
     pub(super) notes: Vec<KnowledgeResultInfo>,
+    pub(super) topics: Vec<KnowledgeTopicInfo>,
+    /// Fuzzy filter on titles; matching rows are shown even when collapsed.
+    pub(super) filter: Option<String>,
+    /// Collapsed group and topic keys.
+    pub(super) collapsed: HashSet<String>,
+    /// Loaded entries for the preview pane, by note ID.
+    pub(super) previews: HashMap<String, KnowledgePreview>,
     pub(super) detail_title: Option<String>,
     pub(super) detail_body: Option<String>,
     pub(super) scroll: u16,
     pub(super) stats: Option<KnowledgeIndexStats>,
 }
+
+/// A knowledge entry as shown in the preview pane.
+#[derive(Debug, Clone)]
+pub(super) struct KnowledgePreview {
+    pub(super) title: String,
+    pub(super) entry_type: String,
+    pub(super) body: String,
+    pub(super) path: Option<String>,
+    pub(super) trust_score: Option<i64>,
+}
+
+/// One visible row of the knowledge tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum KnowledgeRow {
+    Group {
+        key: String,
+        label: &'static str,
+        count: usize,
+        collapsed: bool,
+    },
+    Topic {
+        key: String,
+        title: String,
+        count: usize,
+        archived: bool,
+        collapsed: bool,
+    },
+    Entry {
+        id: String,
+        title: String,
+        entry_type: String,
+        scope: String,
+        depth: u8,
+    },
+}
+
+/// Tree groups in display order, keyed by the scope names the gateway sends.
+const KNOWLEDGE_GROUPS: [(&str, &str, &[&str]); 4] = [
+    ("shared", "Shared", &["SharedNote"]),
+    ("private", "Private", &["GhostNote"]),
+    ("diary", "Diary", &["GhostDiary"]),
+    (
+        "references",
+        "References",
+        &["SharedReference", "GhostReference"],
+    ),
+];
+
+impl KnowledgeViewState {
+    /// Visible tree rows: scope groups, reference topics, then entries.
+    pub(super) fn rows(&self) -> Vec<KnowledgeRow> {
+        let filter = self.filter.as_deref().unwrap_or_default();
+        let matches = |title: &str| super::util::fuzzy_match(filter, title);
+        let is_collapsed = |key: &str| filter.is_empty() && self.collapsed.contains(key);
+        let topic_file_ids: HashSet<&str> = self
+            .topics
+            .iter()
+            .flat_map(|t| t.files.iter().map(|f| f.id.as_str()))
+            .collect();
+
+        let mut rows = Vec::new();
+        for (key, label, scopes) in KNOWLEDGE_GROUPS {
+            let mut children = Vec::new();
+            let mut count = 0;
+            if key == "references" {
+                for topic in &self.topics {
+                    let files: Vec<_> = if matches(&topic.title) {
+                        topic.files.iter().collect()
+                    } else {
+                        topic.files.iter().filter(|f| matches(&f.title)).collect()
+                    };
+                    if files.is_empty() && !matches(&topic.title) {
+                        continue;
+                    }
+                    count += files.len();
+                    let topic_key = format!("topic:{}", topic.id);
+                    let collapsed = is_collapsed(&topic_key);
+                    children.push(KnowledgeRow::Topic {
+                        key: topic_key,
+                        title: topic.title.clone(),
+                        count: files.len(),
+                        archived: topic.archived,
+                        collapsed,
+                    });
+                    if !collapsed {
+                        children.extend(files.into_iter().map(|f| entry_row(f, 2)));
+                    }
+                }
+            }
+            for note in self.notes.iter().filter(|n| {
+                scopes.contains(&n.scope.as_str())
+                    && !topic_file_ids.contains(n.id.as_str())
+                    && matches(&n.title)
+            }) {
+                count += 1;
+                children.push(entry_row(note, 1));
+            }
+            if children.is_empty() {
+                continue;
+            }
+
+            let collapsed = is_collapsed(key);
+            rows.push(KnowledgeRow::Group {
+                key: key.to_string(),
+                label,
+                count,
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(children);
+            }
+        }
+        rows
+    }
+}
+
+fn entry_row(note: &KnowledgeResultInfo, depth: u8) -> KnowledgeRow {
+    KnowledgeRow::Entry {
+        id: note.id.clone(),
+        title: note.title.clone(),
+        entry_type: note.entry_type.clone(),
+        scope: note.scope.clone(),
+        depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str, title: &str, scope: &str) -> KnowledgeResultInfo {
+        KnowledgeResultInfo {
+            id: id.to_string(),
+            title: title.to_string(),
+            entry_type: "Note".to_string(),
+            scope: scope.to_string(),
+            snippet: String::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    fn labels(rows: &[KnowledgeRow]) -> Vec<String> {
+        rows.iter()
+            .map(|row| match row {
+                KnowledgeRow::Group { label, count, .. } => format!("{label} ({count})"),
+                KnowledgeRow::Topic { title, .. } => format!("> {title}"),
+                KnowledgeRow::Entry { title, depth, .. } => format!("{depth} {title}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_knowledge_rows() {
+        let mut view = KnowledgeViewState {
+            notes: vec![
+                note("n1", "Rust lifetimes", "SharedNote"),
+                note("n2", "Grocery list", "GhostNote"),
+                note("r1", "tokio/README.md", "SharedReference"),
+            ],
+            topics: vec![KnowledgeTopicInfo {
+                id: "t1".to_string(),
+                title: "Tokio".to_string(),
+                tags: Vec::new(),
+                archived: false,
+                files: vec![note("r1", "tokio/README.md", "SharedReference")],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            labels(&view.rows()),
+            [
+                "Shared (1)",
+                "1 Rust lifetimes",
+                "Private (1)",
+                "1 Grocery list",
+                "References (1)",
+                "> Tokio",
+                "2 tokio/README.md",
+            ]
+        );
+
+        view.collapsed.insert("topic:t1".to_string());
+        view.collapsed.insert("shared".to_string());
+        assert_eq!(
+            labels(&view.rows()),
+            [
+                "Shared (1)",
+                "Private (1)",
+                "1 Grocery list",
+                "References (1)",
+                "> Tokio",
+            ]
+        );
+
+        // Filtering shows matches even inside collapsed nodes.
+        view.filter = Some("rdm".to_string());
+        assert_eq!(
+            labels(&view.rows()),
+            ["References (1)", "> Tokio", "2 tokio/README.md"]
+        );
+    }
+}
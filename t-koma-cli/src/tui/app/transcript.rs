@@ -0,0 +1,466 @@
+//! Session transcript layout shared by the viewer, its search and the
+//! markdown export.
+//!
+//! Tool calls, tool results and reasoning are folded to one line each unless
+//! `tools_expanded` is set; scroll offsets and search matches are computed on
+//! the same lines the viewer draws.
+
+use chrono::{DateTime, Utc};
+use t_koma_core::{SessionMessageInfo, TranscriptBlock};
+use t_koma_db::{ContentBlock, Message, MessageRole, MessageUsage, TokenUsage};
+
+use super::util::truncate_for_cell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TranscriptLineKind {
+    Header,
+    Text,
+    ToolUse,
+    ToolResult,
+    ToolError,
+    Attachment,
+    Thinking,
+    Blank,
+}
+
+/// One rendered transcript line and the message it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TranscriptLine {
+    pub(super) message: usize,
+    pub(super) kind: TranscriptLineKind,
+    pub(super) text: String,
+}
+
+/// Convert a gateway transcript message into the stored message shape.
+pub(super) fn message_from_info(info: SessionMessageInfo, session_id: &str) -> Message {
+    let content = info
+        .blocks
+        .into_iter()
+        .map(|block| match block {
+            TranscriptBlock::Text { text } => ContentBlock::Text { text },
+            TranscriptBlock::Thinking { text, redacted } => ContentBlock::Thinking {
+                thinking: text,
+                signature: None,
+                redacted,
+            },
+            TranscriptBlock::ToolUse { id, name, input } => {
+                ContentBlock::ToolUse { id, name, input }
+            }
+            TranscriptBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error: is_error.then_some(true),
+            },
+            TranscriptBlock::Attachment {
+                filename,
+                path,
+                mime_type: Some(mime_type),
+            } => ContentBlock::Image {
+                path,
+                mime_type,
+                filename,
+            },
+            TranscriptBlock::Attachment {
+                filename,
+                path,
+                mime_type: None,
+            } => ContentBlock::File {
+                path,
+                filename,
+                size: 0,
+            },
+        })
+        .collect();
+    let usage = info.usage.map(|usage| MessageUsage {
+        tokens: TokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_read_tokens: usage.cache_read_tokens.unwrap_or(0),
+            cache_creation_tokens: usage.cache_creation_tokens.unwrap_or(0),
+        },
+        cost_usd: info.cost_usd,
+    });
+    Message {
+        id: info.id,
+        session_id: session_id.to_string(),
+        role: match info.role {
+            t_koma_core::MessageRole::Ghost => MessageRole::Ghost,
+            t_koma_core::MessageRole::Operator | t_koma_core::MessageRole::System => {
+                MessageRole::Operator
+            }
+        },
+        content,
+        model: info.model,
+        created_at: info.created_at.timestamp(),
+        usage,
+    }
+}
+
+/// Lay out `messages`: a header, the content blocks and a blank separator each.
+pub(super) fn transcript_lines(messages: &[Message], tools_expanded: bool) -> Vec<TranscriptLine> {
+    let mut lines = Vec::new();
+    for (index, msg) in messages.iter().enumerate() {
+        let mut push = |kind, text: String| {
+            lines.push(TranscriptLine {
+                message: index,
+                kind,
+                text,
+            })
+        };
+        let model_suffix = msg
+            .model
+            .as_deref()
+            .map(|m| format!(" ({})", m))
+            .unwrap_or_default();
+        push(
+            TranscriptLineKind::Header,
+            format!("─── {:?}{} ───", msg.role, model_suffix),
+        );
+
+        for block in &msg.content {
+            match block {
+                ContentBlock::Text { text } => {
+                    if text.is_empty() {
+                        push(TranscriptLineKind::Text, String::new());
+                    }
+                    for line in text.lines() {
+                        push(TranscriptLineKind::Text, line.to_string());
+                    }
+                }
+                ContentBlock::ToolUse { name, input, .. } if tools_expanded => {
+                    push(TranscriptLineKind::ToolUse, format!("  ⚙ {}", name));
+                    let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+                    for line in pretty.lines() {
+                        push(TranscriptLineKind::ToolUse, format!("    {}", line));
+                    }
+                }
+                ContentBlock::ToolUse { name, input, .. } => {
+                    let input_str = serde_json::to_string(input).unwrap_or_default();
+                    push(
+                        TranscriptLineKind::ToolUse,
+                        format!("  ⚙ {}({})", name, first_line(&input_str, 80)),
+                    );
+                }
+                ContentBlock::ToolResult {
+                    content, is_error, ..
+                } => {
+                    let (kind, prefix) = if *is_error == Some(true) {
+                        (TranscriptLineKind::ToolError, "  ✗ ")
+                    } else {
+                        (TranscriptLineKind::ToolResult, "  ┆ ")
+                    };
+                    if tools_expanded && !content.is_empty() {
+                        for line in content.lines() {
+                            push(kind, format!("{}{}", prefix, line));
+                        }
+                    } else {
+                        push(kind, format!("{}{}", prefix, first_line(content, 120)));
+                    }
+                }
+                ContentBlock::Image { filename, .. } => {
+                    push(TranscriptLineKind::Attachment, format!("  📷 {}", filename));
+                }
+                ContentBlock::File { filename, .. } => {
+                    push(TranscriptLineKind::Attachment, format!("  📎 {}", filename));
+                }
+                ContentBlock::Thinking {
+                    thinking, redacted, ..
+                } => {
+                    if *redacted {
+                        push(TranscriptLineKind::Thinking, "  💭 (redacted)".to_string());
+                    } else if tools_expanded && !thinking.is_empty() {
+                        for line in thinking.lines() {
+                            push(TranscriptLineKind::Thinking, format!("  💭 {}", line));
+                        }
+                    } else {
+                        push(
+                            TranscriptLineKind::Thinking,
+                            format!("  💭 {}", first_line(thinking, 120)),
+                        );
+                    }
+                }
+            }
+        }
+        push(TranscriptLineKind::Blank, String::new());
+    }
+    lines
+}
+
+/// First line of `s`, cut to `max_chars`.
+fn first_line(s: &str, max_chars: usize) -> String {
+    truncate_for_cell(s.lines().next().unwrap_or(""), max_chars)
+}
+
+/// Indexes of the lines containing `query`, case-insensitively.
+pub(super) fn search_transcript(lines: &[TranscriptLine], query: &str) -> Vec<u16> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.text.to_lowercase().contains(&query))
+        .map(|(idx, _)| idx as u16)
+        .collect()
+}
+
+/// A fenced code block in the laid-out transcript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CodeBlock {
+    /// Line indexes of the opening and closing fences.
+    pub(super) start: u16,
+    pub(super) end: u16,
+    pub(super) text: String,
+}
+
+/// Fenced code blocks in message text, in transcript order. A block left open
+/// at the end of its message closes there.
+pub(super) fn code_blocks(lines: &[TranscriptLine]) -> Vec<CodeBlock> {
+    let block = |start: usize, end: usize, body: &[&str]| CodeBlock {
+        start: start as u16,
+        end: end as u16,
+        text: body.join("\n"),
+    };
+    let mut blocks = Vec::new();
+    // Opening fence line, its message and the lines seen so far.
+    let mut open: Option<(usize, usize, Vec<&str>)> = None;
+    for (idx, line) in lines.iter().enumerate() {
+        let is_fence =
+            line.kind == TranscriptLineKind::Text && line.text.trim_start().starts_with("```");
+        if let Some((start, message, mut body)) = open.take() {
+            if line.message != message || line.kind == TranscriptLineKind::Blank {
+                blocks.push(block(start, idx - 1, &body));
+            } else if is_fence {
+                blocks.push(block(start, idx, &body));
+                continue;
+            } else {
+                if line.kind == TranscriptLineKind::Text {
+                    body.push(&line.text);
+                }
+                open = Some((start, message, body));
+                continue;
+            }
+        }
+        if is_fence {
+            open = Some((idx, line.message, Vec::new()));
+        }
+    }
+    if let Some((start, _, body)) = open {
+        blocks.push(block(start, lines.len() - 1, &body));
+    }
+    blocks
+}
+
+/// Text of the last GHOST reply, without tool calls or reasoning.
+pub(super) fn last_ghost_text(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .filter(|msg| msg.role == MessageRole::Ghost)
+        .map(|msg| {
+            msg.content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.trim()),
+                    _ => None,
+                })
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .find(|text| !text.is_empty())
+}
+
+/// Full transcript as markdown, with tool calls and results in collapsible
+/// `<details>` blocks.
+pub(super) fn transcript_markdown(
+    messages: &[Message],
+    ghost_name: &str,
+    session_id: &str,
+) -> String {
+    let mut out = format!(
+        "# Session {}\n\nGHOST: {} · {} messages\n",
+        session_id,
+        ghost_name,
+        messages.len()
+    );
+    for msg in messages {
+        let time = DateTime::<Utc>::from_timestamp(msg.created_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default();
+        let model = msg
+            .model
+            .as_deref()
+            .map(|m| format!(" ({})", m))
+            .unwrap_or_default();
+        out.push_str(&format!("\n## {:?}{} — {}\n", msg.role, model, time));
+
+        for block in &msg.content {
+            out.push('\n');
+            match block {
+                ContentBlock::Text { text } => {
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+                ContentBlock::ToolUse { name, input, .. } => {
+                    let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+                    out.push_str(&format!(
+                        "<details><summary>⚙ {}</summary>\n\n```json\n{}\n```\n\n</details>\n",
+                        name, pretty
+                    ));
+                }
+                ContentBlock::ToolResult {
+                    content, is_error, ..
+                } => {
+                    let label = if *is_error == Some(true) {
+                        "✗ Tool error"
+                    } else {
+                        "Tool result"
+                    };
+                    out.push_str(&format!(
+                        "<details><summary>{}</summary>\n\n```\n{}\n```\n\n</details>\n",
+                        label,
+                        content.trim_end()
+                    ));
+                }
+                ContentBlock::Image { filename, path, .. }
+                | ContentBlock::File { filename, path, .. } => {
+                    out.push_str(&format!("📎 {} (`{}`)\n", filename, path));
+                }
+                ContentBlock::Thinking {
+                    thinking, redacted, ..
+                } => {
+                    if *redacted {
+                        out.push_str("> 💭 (redacted)\n");
+                    } else {
+                        for line in thinking.lines() {
+                            out.push_str(&format!("> 💭 {}\n", line));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message {
+                id: "a".to_string(),
+                session_id: "sess".to_string(),
+                role: MessageRole::Operator,
+                content: vec![ContentBlock::Text {
+                    text: "Weather in Kyoto?".to_string(),
+                }],
+                model: None,
+                created_at: 1_700_000_000,
+                usage: None,
+            },
+            Message {
+                id: "b".to_string(),
+                session_id: "sess".to_string(),
+                role: MessageRole::Ghost,
+                content: vec![
+                    ContentBlock::ToolUse {
+                        id: "tu_1".to_string(),
+                        name: "web_search".to_string(),
+                        input: serde_json::json!({"query": "kyoto weather"}),
+                    },
+                    ContentBlock::ToolResult {
+                        tool_use_id: "tu_1".to_string(),
+                        content: "Sunny\n24C".to_string(),
+                        is_error: None,
+                    },
+                    ContentBlock::Text {
+                        text: "Sunny, 24C.".to_string(),
+                    },
+                ],
+                model: Some("primary".to_string()),
+                created_at: 1_700_000_060,
+                usage: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_transcript_lines_fold_tools() {
+        let messages = messages();
+        let folded = transcript_lines(&messages, false);
+        let texts: Vec<&str> = folded.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "─── Operator ───",
+                "Weather in Kyoto?",
+                "",
+                "─── Ghost (primary) ───",
+                r#"  ⚙ web_search({"query":"kyoto weather"})"#,
+                "  ┆ Sunny",
+                "Sunny, 24C.",
+                "",
+            ]
+        );
+        assert_eq!(folded[4].message, 1);
+
+        let expanded = transcript_lines(&messages, true);
+        assert_eq!(expanded.len(), folded.len() + 4);
+        assert!(expanded.iter().any(|l| l.text == "  ┆ 24C"));
+
+        assert_eq!(search_transcript(&folded, "SUNNY"), [5, 6]);
+        assert_eq!(search_transcript(&expanded, "24c"), [9, 10]);
+        assert!(search_transcript(&folded, "").is_empty());
+    }
+
+    #[test]
+    fn test_code_blocks_and_last_ghost_text() {
+        let mut messages = messages();
+        messages.push(Message {
+            id: "c".to_string(),
+            session_id: "sess".to_string(),
+            role: MessageRole::Ghost,
+            content: vec![ContentBlock::Text {
+                text: "Run:\n```sh\ncurl wttr.in/Kyoto\nexit\n```\nthen\n```\nopen".to_string(),
+            }],
+            model: None,
+            created_at: 1_700_000_120,
+            usage: None,
+        });
+        let lines = transcript_lines(&messages, false);
+        let blocks = code_blocks(&lines);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(lines[blocks[0].start as usize].text, "```sh");
+        assert_eq!(blocks[0].text, "curl wttr.in/Kyoto\nexit");
+        assert_eq!(lines[blocks[0].end as usize].text, "```");
+        // Unclosed fence ends with its message.
+        assert_eq!(blocks[1].text, "open");
+
+        assert_eq!(
+            last_ghost_text(&messages).as_deref(),
+            Some("Run:\n```sh\ncurl wttr.in/Kyoto\nexit\n```\nthen\n```\nopen")
+        );
+        assert_eq!(
+            last_ghost_text(&messages[..2]).as_deref(),
+            Some("Sunny, 24C.")
+        );
+    }
+
+    #[test]
+    fn test_transcript_markdown() {
+        let markdown = transcript_markdown(&messages(), "alpha", "sess");
+        assert!(markdown.starts_with("# Session sess\n\nGHOST: alpha · 2 messages\n"));
+        assert!(markdown.contains("\n## Operator — 2023-11-14 22:13 UTC\n\nWeather in Kyoto?\n"));
+        assert!(markdown.contains("## Ghost (primary) — 2023-11-14 22:14 UTC"));
+        assert!(markdown.contains("<details><summary>⚙ web_search</summary>"));
+        assert!(markdown.contains("```\nSunny\n24C\n```"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Usage and cost dashboard (Metrics category): daily totals, per-model and
+//! per-ghost breakdowns with text sparklines, and budget progress.
+
+use chrono::{Duration, NaiveDate};
+use t_koma_core::{
+    GatewayMessageKind, UsageBudgetInfo, UsageReportGrouping, UsageReportRow, WsMessage, WsResponse,
+};
+
+use super::{TuiApp, state::UsageViewState};
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Totals of one model or ghost over the dashboard range.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct UsageSeries {
+    pub(super) label: String,
+    pub(super) tokens: i64,
+    pub(super) cost_usd: f64,
+    /// Tokens per day, oldest first
+    pub(super) daily_tokens: Vec<u64>,
+}
+
+/// Dashboard range for each Metrics option, in days.
+pub(super) const USAGE_RANGES: [u32; 3] = [7, 30, 90];
+
+impl TuiApp {
+    pub(super) async fn refresh_usage_dashboard(&mut self, since_days: u32) {
+        let query = |group_by, daily, include_budgets| WsMessage::GetUsageReport {
+            group_by,
+            since_days: Some(since_days),
+            daily,
+            include_budgets,
+        };
+        let (days, budgets) = match self
+            .ws_query(query(UsageReportGrouping::Day, false, true))
+            .await
+        {
+            Ok(WsResponse::UsageReport { rows, budgets, .. }) => (rows, budgets),
+            Ok(WsResponse::Response { message, .. })
+                if message.kind == GatewayMessageKind::Error =>
+            {
+                self.status = format!("Usage: {}", message.text_fallback);
+                return;
+            }
+            Ok(_) => {
+                self.status = "Unexpected usage response".to_string();
+                return;
+            }
+            Err(e) => {
+                self.status = format!("Usage: {}", e);
+                return;
+            }
+        };
+        let mut split = Vec::new();
+        for group_by in [UsageReportGrouping::Model, UsageReportGrouping::Ghost] {
+            match self.ws_query(query(group_by, true, false)).await {
+                Ok(WsResponse::UsageReport { rows, .. }) => split.push(rows),
+                Ok(_) => {
+                    self.status = "Unexpected usage response".to_string();
+                    return;
+                }
+                Err(e) => {
+                    self.status = format!("Usage: {}", e);
+                    return;
+                }
+            }
+        }
+        let ghosts = split.pop().unwrap_or_default();
+        let models = split.pop().unwrap_or_default();
+
+        self.usage_view = UsageViewState {
+            since_days,
+            days,
+            models,
+            ghosts,
+            budgets,
+            loaded: true,
+            scroll: 0,
+        };
+        self.status = format!("Usage for the last {} days loaded", since_days);
+    }
+}
+
+/// The last `count` UTC days ending at `today`, oldest first.
+pub(super) fn day_range(today: NaiveDate, count: u32) -> Vec<String> {
+    (0..i64::from(count))
+        .rev()
+        .map(|back| {
+            (today - Duration::days(back))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect()
+}
+
+pub(super) fn row_tokens(row: &UsageReportRow) -> i64 {
+    row.input_tokens + row.output_tokens + row.cache_read_tokens + row.cache_creation_tokens
+}
+
+/// Per-day values of `rows` (keyed by `day`, or by `key` for day groupings)
+/// over `days`.
+pub(super) fn daily_values(
+    rows: &[&UsageReportRow],
+    days: &[String],
+    value: impl Fn(&UsageReportRow) -> f64,
+) -> Vec<f64> {
+    days.iter()
+        .map(|day| {
+            rows.iter()
+                .filter(|row| row.day.as_deref().unwrap_or(&row.key) == day)
+                .map(|row| value(row))
+                .sum()
+        })
+        .collect()
+}
+
+/// Collapse a daily-split report into one series per group, costliest first.
+pub(super) fn usage_series(rows: &[UsageReportRow], days: &[String]) -> Vec<UsageSeries> {
+    let mut keys: Vec<&str> = Vec::new();
+    for row in rows {
+        if !keys.contains(&row.key.as_str()) {
+            keys.push(&row.key);
+        }
+    }
+    let mut series: Vec<UsageSeries> = keys
+        .into_iter()
+        .map(|key| {
+            let group: Vec<&UsageReportRow> = rows.iter().filter(|row| row.key == key).collect();
+            UsageSeries {
+                label: group[0].label.clone(),
+                tokens: group.iter().map(|row| row_tokens(row)).sum(),
+                cost_usd: group.iter().map(|row| row.cost_usd).sum(),
+                daily_tokens: daily_values(&group, days, |row| row_tokens(row) as f64)
+                    .into_iter()
+                    .map(|v| v as u64)
+                    .collect(),
+            }
+        })
+        .collect();
+    series.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then(b.tokens.cmp(&a.tokens))
+    });
+    series
+}
+
+/// One block character per value, scaled to the largest.
+pub(super) fn sparkline_text(values: &[u64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            if max == 0 || value == 0 {
+                ' '
+            } else {
+                let level = (value * (SPARK_LEVELS.len() as u64 - 1)).div_ceil(max) as usize;
+                SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// Highest used/limit ratio of a budget.
+pub(super) fn budget_ratio(budget: &UsageBudgetInfo) -> f64 {
+    let tokens = budget
+        .token_limit
+        .filter(|limit| *limit > 0)
+        .map(|limit| budget.used_tokens as f64 / limit as f64);
+    let cost = budget
+        .cost_limit_usd
+        .filter(|limit| *limit > 0.0)
+        .map(|limit| budget.used_cost_usd / limit);
+    tokens.into_iter().chain(cost).fold(0.0, f64::max)
+}
+
+/// `width`-cell progress bar for a 0..1 ratio (overflow fills the bar).
+pub(super) fn progress_bar(ratio: f64, width: usize) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(key: &str, day: Option<&str>, tokens: i64, cost_usd: f64) -> UsageReportRow {
+        UsageReportRow {
+            key: key.to_string(),
+            label: key.to_uppercase(),
+            day: day.map(str::to_string),
+            request_count: 1,
+            input_tokens: tokens,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            cost_usd,
+            unpriced_requests: 0,
+        }
+    }
+
+    #[test]
+    fn test_usage_series() {
+        let days = day_range(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(), 3);
+        assert_eq!(days, ["2026-02-28", "2026-03-01", "2026-03-02"]);
+
+        let rows = vec![
+            row("sonnet", Some("2026-03-02"), 300, 0.3),
+            row("haiku", Some("2026-03-02"), 900, 0.1),
+            row("sonnet", Some("2026-02-28"), 100, 0.1),
+        ];
+        let series = usage_series(&rows, &days);
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].label, "SONNET");
+        assert_eq!(series[0].tokens, 400);
+        assert_eq!(series[0].daily_tokens, [100, 0, 300]);
+        assert_eq!(series[1].daily_tokens, [0, 0, 900]);
+
+        let day_rows = [row("2026-03-01", None, 50, 0.0)];
+        let refs: Vec<&UsageReportRow> = day_rows.iter().collect();
+        assert_eq!(
+            daily_values(&refs, &days, |r| row_tokens(r) as f64),
+            [0.0, 50.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_sparkline_and_progress() {
+        assert_eq!(sparkline_text(&[0, 1, 4, 8]), " ▂▅█");
+        assert_eq!(sparkline_text(&[0, 0]), "  ");
+        assert_eq!(progress_bar(0.5, 4), "██░░");
+        assert_eq!(progress_bar(1.7, 4), "████");
+
+        let budget = UsageBudgetInfo {
+            scope: t_koma_core::UsageBudgetScope::Ghost,
+            subject: "ghost_1".to_string(),
+            label: "alpha".to_string(),
+            used_tokens: 500,
+            token_limit: Some(1000),
+            used_cost_usd: 3.0,
+            cost_limit_usd: Some(4.0),
+            warn_ratio: 0.8,
+        };
+        assert!((budget_ratio(&budget) - 0.75).abs() < 1e-9);
+    }
+}
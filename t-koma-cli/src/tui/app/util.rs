@@ -7,6 +7,9 @@ use ratatui::{
 };
 
 use t_koma_core::Settings;
+use t_koma_db::{MessageUsage, TodoItem, TodoStatus};
+
+use crate::tui::theme;
 
 pub(super) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = ratatui::layout::Layout::default()
@@ -48,7 +51,31 @@ pub(super) fn shell_quote(value: &str) -> String {
     format!("'{}'", escaped)
 }
 
+/// Fold a multi-line paste into one prompt line: trimmed non-empty lines,
+/// joined with ", " for list prompts (dropping trailing commas) and with
+/// spaces otherwise.
+pub(super) fn join_pasted_lines(text: &str, list: bool) -> String {
+    let separator = if list { ", " } else { " " };
+    text.lines()
+        .map(|line| {
+            let line = line.trim();
+            if list {
+                line.trim_end_matches(',').trim_end()
+            } else {
+                line
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Pulsing accent, or the plain accent when the theme does not glow.
 pub(super) fn glow_color(tick: usize) -> Color {
+    let palette = theme::palette();
+    if !palette.glow {
+        return palette.accent;
+    }
     let phase = tick % 200;
     let up = if phase <= 100 { phase } else { 200 - phase } as u8;
     let boost = (up as u16 * 90 / 100) as u8;
@@ -56,6 +83,10 @@ pub(super) fn glow_color(tick: usize) -> Color {
 }
 
 pub(super) fn pulse_red(tick: usize) -> Color {
+    let palette = theme::palette();
+    if !palette.glow {
+        return palette.error;
+    }
     let phase = tick % 200;
     let up = if phase <= 100 { phase } else { 200 - phase } as u8;
     let boost = (up as u16 * 130 / 100) as u8;
@@ -68,7 +99,7 @@ pub(super) fn border_glow(has_focus: bool, tick: usize) -> Style {
             .fg(glow_color(tick))
             .add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Rgb(45, 60, 68))
+        Style::default().fg(theme::palette().border)
     }
 }
 
@@ -92,7 +123,7 @@ pub(super) fn highlight_toml(content: &str) -> Vec<Line<'static>> {
             if trimmed.starts_with('#') {
                 return Line::from(Span::styled(
                     line.to_string(),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme::palette().muted),
                 ));
             }
 
@@ -100,18 +131,21 @@ pub(super) fn highlight_toml(content: &str) -> Vec<Line<'static>> {
                 return Line::from(Span::styled(
                     line.to_string(),
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme::palette().accent)
                         .add_modifier(Modifier::BOLD),
                 ));
             }
 
             if let Some((key, value)) = line.split_once('=') {
-                let key_span = Span::styled(key.to_string(), Style::default().fg(Color::Yellow));
+                let key_span = Span::styled(
+                    key.to_string(),
+                    Style::default().fg(theme::palette().highlight),
+                );
                 let eq_span = Span::raw("=");
                 let value_style = if value.trim().starts_with('"') {
-                    Style::default().fg(Color::Green)
+                    Style::default().fg(theme::palette().success)
                 } else {
-                    Style::default().fg(Color::Magenta)
+                    Style::default().fg(theme::palette().secondary)
                 };
                 let value_span = Span::styled(value.to_string(), value_style);
                 return Line::from(vec![key_span, eq_span, value_span]);
@@ -132,10 +166,10 @@ pub(super) fn highlight_toml_with_diff(content: &str, disk_content: &str) -> Vec
         let marker = if changed { "▋" } else { " " };
         let marker_style = if changed {
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme::palette().highlight)
                 .add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(theme::palette().muted)
         };
 
         let mut rendered = highlight_toml(line)
@@ -152,6 +186,88 @@ pub(super) fn highlight_toml_with_diff(content: &str, disk_content: &str) -> Vec
     lines
 }
 
+/// One row of a side-by-side line diff. `None` pads the side without a
+/// counterpart line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct DiffRow<'a> {
+    pub(super) left: Option<&'a str>,
+    pub(super) right: Option<&'a str>,
+    pub(super) changed: bool,
+}
+
+/// Align the lines of `left` and `right` on their longest common subsequence.
+/// Removed and added lines between two common lines are paired row by row.
+pub(super) fn line_diff<'a>(left: &'a str, right: &'a str) -> Vec<DiffRow<'a>> {
+    let a: Vec<&str> = left.lines().collect();
+    let b: Vec<&str> = right.lines().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let am = &a[prefix..a.len() - suffix];
+    let bm = &b[prefix..b.len() - suffix];
+
+    let same = |line: &'a str| DiffRow {
+        left: Some(line),
+        right: Some(line),
+        changed: false,
+    };
+    let mut rows: Vec<DiffRow<'a>> = a[..prefix].iter().map(|l| same(l)).collect();
+    let mut removed: Vec<&'a str> = Vec::new();
+    let mut added: Vec<&'a str> = Vec::new();
+    let flush =
+        |rows: &mut Vec<DiffRow<'a>>, removed: &mut Vec<&'a str>, added: &mut Vec<&'a str>| {
+            for idx in 0..removed.len().max(added.len()) {
+                rows.push(DiffRow {
+                    left: removed.get(idx).copied(),
+                    right: added.get(idx).copied(),
+                    changed: true,
+                });
+            }
+            removed.clear();
+            added.clear();
+        };
+
+    // The LCS table is quadratic; very different large files are shown as
+    // one changed block instead.
+    if am.len().saturating_mul(bm.len()) > 1_000_000 {
+        removed.extend(am);
+        added.extend(bm);
+    } else {
+        let mut lcs = vec![vec![0u32; bm.len() + 1]; am.len() + 1];
+        for i in (0..am.len()).rev() {
+            for j in (0..bm.len()).rev() {
+                lcs[i][j] = if am[i] == bm[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < am.len() || j < bm.len() {
+            if i < am.len() && j < bm.len() && am[i] == bm[j] {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(same(am[i]));
+                i += 1;
+                j += 1;
+            } else if j < bm.len() && (i == am.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                added.push(bm[j]);
+                j += 1;
+            } else {
+                removed.push(am[i]);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    rows.extend(a[a.len() - suffix..].iter().map(|l| same(l)));
+    rows
+}
+
 pub(super) fn markdown_to_lines(message: &str) -> Vec<Line<'static>> {
     message
         .lines()
@@ -164,12 +280,15 @@ pub(super) fn markdown_to_lines(message: &str) -> Vec<Line<'static>> {
                 return Line::from(Span::styled(
                     trimmed.trim_start_matches('#').trim().to_string(),
                     Style::default()
-                        .fg(Color::LightCyan)
+                        .fg(theme::palette().accent_soft)
                         .add_modifier(Modifier::BOLD),
                 ));
             }
             if let Some(rest) = trimmed.strip_prefix("- ") {
-                let mut spans = vec![Span::styled("• ", Style::default().fg(Color::Magenta))];
+                let mut spans = vec![Span::styled(
+                    "• ",
+                    Style::default().fg(theme::palette().secondary),
+                )];
                 spans.extend(parse_inline_markdown(rest));
                 return Line::from(spans);
             }
@@ -190,7 +309,7 @@ fn parse_inline_markdown(input: &str) -> Vec<Span<'static>> {
             spans.push(Span::styled(
                 bold.to_string(),
                 Style::default()
-                    .fg(Color::White)
+                    .fg(theme::palette().text)
                     .add_modifier(Modifier::BOLD),
             ));
             rest = &stripped[end + 2..];
@@ -204,8 +323,8 @@ fn parse_inline_markdown(input: &str) -> Vec<Span<'static>> {
             spans.push(Span::styled(
                 code.to_string(),
                 Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Rgb(20, 30, 45)),
+                    .fg(theme::palette().highlight)
+                    .bg(theme::palette().code_bg),
             ));
             rest = &stripped[end + 1..];
             continue;
@@ -232,6 +351,30 @@ fn parse_inline_markdown(input: &str) -> Vec<Span<'static>> {
     }
 }
 
+/// Case-insensitive subsequence match: every character of `pattern` appears
+/// in `text` in order. An empty pattern matches everything.
+pub(super) fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|p| chars.any(|c| c == p))
+}
+
+/// Split `s` into rows of at most `width` chars, so a bottom-anchored view
+/// knows its exact height.
+pub(super) fn hard_wrap(s: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
 pub(super) fn truncate_for_cell(s: &str, max_chars: usize) -> String {
     let chars: Vec<char> = s.chars().collect();
     if chars.len() <= max_chars {
@@ -254,9 +397,158 @@ pub(super) fn truncate_for_message(s: &str, max_chars: usize) -> String {
     }
 }
 
+fn short_count(n: u32) -> String {
+    if n < 1000 {
+        n.to_string()
+    } else {
+        format!("{:.1}k", f64::from(n) / 1000.0)
+    }
+}
+
+/// One-line token/cost summary for a message header.
+pub(super) fn format_message_usage(usage: &MessageUsage) -> String {
+    let tokens = &usage.tokens;
+    let mut parts = vec![format!("{} in", short_count(tokens.input_tokens))];
+    if tokens.cache_read_tokens > 0 || tokens.cache_creation_tokens > 0 {
+        parts.push(format!(
+            "cache {}r/{}w",
+            short_count(tokens.cache_read_tokens),
+            short_count(tokens.cache_creation_tokens)
+        ));
+    }
+    parts.push(format!("{} out", short_count(tokens.output_tokens)));
+    if let Some(cost) = usage.cost_usd {
+        parts.push(format!("${cost:.4}"));
+    }
+    parts.join(" · ")
+}
+
+/// Relative weight used to rank expensive messages: cost when priced,
+/// otherwise tokens sent plus generated.
+pub(super) fn usage_weight(usage: &MessageUsage) -> f64 {
+    usage
+        .cost_usd
+        .unwrap_or_else(|| f64::from(usage.context_tokens()) / 1_000_000.0)
+}
+
+/// Finished (done or skipped) and total items of a job's TODO list.
+pub(super) fn todo_progress(todos: &[TodoItem]) -> (usize, usize) {
+    let finished = todos
+        .iter()
+        .filter(|t| matches!(t.status, TodoStatus::Done | TodoStatus::Skipped))
+        .count();
+    (finished, todos.len())
+}
+
+/// Checkbox shown in front of a TODO item.
+pub(super) fn todo_marker(status: &TodoStatus) -> &'static str {
+    match status {
+        TodoStatus::Pending => "[ ]",
+        TodoStatus::InProgress => "[>]",
+        TodoStatus::Done => "[x]",
+        TodoStatus::Skipped => "[-]",
+    }
+}
+
+/// Lines of the TODO section in the job detail view: a heading, one line per
+/// item and a blank separator (none without items).
+pub(super) fn todo_section_height(todos: &[TodoItem]) -> u16 {
+    if todos.is_empty() {
+        0
+    } else {
+        todos.len() as u16 + 2
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ws_url_for_cli;
+    use super::{
+        format_message_usage, fuzzy_match, hard_wrap, join_pasted_lines, line_diff, todo_progress,
+        ws_url_for_cli,
+    };
+    use t_koma_db::{MessageUsage, TodoItem, TodoStatus, TokenUsage};
+
+    #[test]
+    fn test_hard_wrap() {
+        assert_eq!(hard_wrap("", 4), [""]);
+        assert_eq!(hard_wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(hard_wrap("›é", 0), ["›", "é"]);
+    }
+
+    #[test]
+    fn test_join_pasted_lines() {
+        let pasted = "web_fetch=allow,\n\n  shell=deny\n";
+        assert_eq!(
+            join_pasted_lines(pasted, true),
+            "web_fetch=allow, shell=deny"
+        );
+        assert_eq!(
+            join_pasted_lines("find the\nkyoto notes", false),
+            "find the kyoto notes"
+        );
+    }
+
+    #[test]
+    fn test_line_diff_pairs_changes_between_common_lines() {
+        let disk = "[gateway]\nport = 3000\nhost = \"a\"\n[cli]\n";
+        let memory = "[gateway]\nport = 3001\nhost = \"a\"\nws = 1\n[cli]\n";
+        let rows = line_diff(disk, memory);
+        let summary: Vec<(Option<&str>, Option<&str>, bool)> =
+            rows.iter().map(|r| (r.left, r.right, r.changed)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("[gateway]"), Some("[gateway]"), false),
+                (Some("port = 3000"), Some("port = 3001"), true),
+                (Some("host = \"a\""), Some("host = \"a\""), false),
+                (None, Some("ws = 1"), true),
+                (Some("[cli]"), Some("[cli]"), false),
+            ]
+        );
+        assert!(line_diff("a\nb\n", "a\nb\n").iter().all(|r| !r.changed));
+    }
+
+    #[test]
+    fn test_todo_progress_counts_done_and_skipped() {
+        let mut todos: Vec<TodoItem> = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|title| TodoItem::pending(title, None))
+            .collect();
+        todos[0].status = TodoStatus::Done;
+        todos[1].status = TodoStatus::Skipped;
+        todos[2].status = TodoStatus::InProgress;
+        assert_eq!(todo_progress(&todos), (2, 4));
+        assert_eq!(todo_progress(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_format_message_usage() {
+        let mut usage = MessageUsage {
+            tokens: TokenUsage {
+                input_tokens: 320,
+                output_tokens: 1260,
+                cache_read_tokens: 48_000,
+                cache_creation_tokens: 0,
+            },
+            cost_usd: Some(0.0321),
+        };
+        assert_eq!(
+            format_message_usage(&usage),
+            "320 in · cache 48.0kr/0w · 1.3k out · $0.0321"
+        );
+        usage.tokens.cache_read_tokens = 0;
+        usage.cost_usd = None;
+        assert_eq!(format_message_usage(&usage), "320 in · 1.3k out");
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("", "anything"));
+        assert!(fuzzy_match("rsl", "Rust lifetimes"));
+        assert!(fuzzy_match("RUST life", "rust lifetimes"));
+        assert!(!fuzzy_match("lr", "Rust"));
+        assert!(!fuzzy_match("rustt", "Rust"));
+    }
 
     #[test]
     fn test_ws_url_for_cli_adds_client_query() {
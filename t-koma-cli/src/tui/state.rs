@@ -33,16 +33,18 @@ pub enum Category {
     Ghosts,
     Jobs,
     Knowledge,
+    Metrics,
 }
 
 impl Category {
-    pub const ALL: [Category; 6] = [
+    pub const ALL: [Category; 7] = [
         Category::Gate,
         Category::Config,
         Category::Operators,
         Category::Ghosts,
         Category::Jobs,
         Category::Knowledge,
+        Category::Metrics,
     ];
 
     pub fn label(self) -> &'static str {
@@ -53,6 +55,7 @@ impl Category {
             Self::Ghosts => "󰊠 Ghosts",
             Self::Jobs => "󰜎 Jobs",
             Self::Knowledge => "󰘦 Knowledge",
+            Self::Metrics => "󰄪 Metrics",
         }
     }
 
@@ -64,6 +67,7 @@ impl Category {
             Self::Ghosts => '4',
             Self::Jobs => '5',
             Self::Knowledge => '6',
+            Self::Metrics => '7',
         }
     }
 
@@ -1,32 +1,309 @@
+//! TUI colour palettes. Widgets read the active palette through
+//! [`palette`]; [`apply`] swaps it at runtime.
+
+use std::{str::FromStr, sync::RwLock};
+
 use ratatui::style::{Color, Modifier, Style};
+use t_koma_core::CliSettings;
+
+/// Semantic colour slots shared by every TUI widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Focused borders, titles, GHOST text
+    pub accent: Color,
+    /// Markdown headings
+    pub accent_soft: Color,
+    /// Secondary text, empty states, unfocused hints
+    pub muted: Color,
+    /// Primary text
+    pub text: Color,
+    /// OPERATOR text, keys, warnings
+    pub highlight: Color,
+    pub success: Color,
+    /// Costs and token totals
+    pub success_soft: Color,
+    pub error: Color,
+    /// Section headers, tags, tool calls
+    pub secondary: Color,
+    pub secondary_soft: Color,
+    /// Scopes, tool results
+    pub info: Color,
+    pub info_soft: Color,
+    /// Text drawn on accent or highlight backgrounds
+    pub on_accent: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Footer key hint background
+    pub hint_bg: Color,
+    /// Footer status line
+    pub status_text: Color,
+    /// Unfocused borders
+    pub border: Color,
+    /// Inline code background
+    pub code_bg: Color,
+    /// Current and other search match backgrounds
+    pub match_bg: Color,
+    pub match_dim_bg: Color,
+    /// Animate focused borders and the header dot
+    pub glow: bool,
+}
+
+impl Palette {
+    pub const CYBERPUNK: Palette = Palette {
+        accent: Color::Cyan,
+        accent_soft: Color::LightCyan,
+        muted: Color::DarkGray,
+        text: Color::White,
+        highlight: Color::Yellow,
+        success: Color::Green,
+        success_soft: Color::LightGreen,
+        error: Color::Red,
+        secondary: Color::Magenta,
+        secondary_soft: Color::LightMagenta,
+        info: Color::Blue,
+        info_soft: Color::LightBlue,
+        on_accent: Color::Black,
+        selection_fg: Color::White,
+        selection_bg: Color::Rgb(0, 60, 90),
+        hint_bg: Color::Rgb(60, 80, 90),
+        status_text: Color::Rgb(80, 100, 110),
+        border: Color::Rgb(45, 60, 68),
+        code_bg: Color::Rgb(20, 30, 45),
+        match_bg: Color::Rgb(90, 70, 20),
+        match_dim_bg: Color::Rgb(45, 40, 25),
+        glow: true,
+    };
+
+    /// For light terminal backgrounds.
+    pub const LIGHT: Palette = Palette {
+        accent: Color::Rgb(0, 95, 135),
+        accent_soft: Color::Rgb(0, 120, 160),
+        muted: Color::Rgb(120, 120, 120),
+        text: Color::Black,
+        highlight: Color::Rgb(150, 90, 0),
+        success: Color::Rgb(0, 120, 40),
+        success_soft: Color::Rgb(30, 140, 70),
+        error: Color::Rgb(180, 0, 0),
+        secondary: Color::Rgb(140, 40, 140),
+        secondary_soft: Color::Rgb(170, 70, 170),
+        info: Color::Rgb(30, 60, 170),
+        info_soft: Color::Rgb(60, 100, 200),
+        on_accent: Color::White,
+        selection_fg: Color::Black,
+        selection_bg: Color::Rgb(190, 215, 235),
+        hint_bg: Color::Rgb(70, 90, 100),
+        status_text: Color::Rgb(90, 100, 110),
+        border: Color::Rgb(180, 190, 195),
+        code_bg: Color::Rgb(230, 232, 238),
+        match_bg: Color::Rgb(250, 215, 110),
+        match_dim_bg: Color::Rgb(250, 240, 200),
+        glow: false,
+    };
+
+    /// Bright ANSI colours only, no animation.
+    pub const HIGH_CONTRAST: Palette = Palette {
+        accent: Color::LightCyan,
+        accent_soft: Color::LightCyan,
+        muted: Color::Gray,
+        text: Color::White,
+        highlight: Color::LightYellow,
+        success: Color::LightGreen,
+        success_soft: Color::LightGreen,
+        error: Color::LightRed,
+        secondary: Color::LightMagenta,
+        secondary_soft: Color::LightMagenta,
+        info: Color::LightBlue,
+        info_soft: Color::LightBlue,
+        on_accent: Color::Black,
+        selection_fg: Color::Black,
+        selection_bg: Color::LightYellow,
+        hint_bg: Color::White,
+        status_text: Color::White,
+        border: Color::Gray,
+        code_bg: Color::Black,
+        match_bg: Color::Blue,
+        match_dim_bg: Color::DarkGray,
+        glow: false,
+    };
+
+    /// Built-in palettes by config name.
+    pub const BUILT_IN: [(&'static str, Palette); 3] = [
+        ("cyberpunk", Palette::CYBERPUNK),
+        ("light", Palette::LIGHT),
+        ("high_contrast", Palette::HIGH_CONTRAST),
+    ];
+
+    fn built_in(name: &str) -> Option<Palette> {
+        Self::BUILT_IN
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+            .map(|(_, palette)| *palette)
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "accent" => &mut self.accent,
+            "accent_soft" => &mut self.accent_soft,
+            "muted" => &mut self.muted,
+            "text" => &mut self.text,
+            "highlight" => &mut self.highlight,
+            "success" => &mut self.success,
+            "success_soft" => &mut self.success_soft,
+            "error" => &mut self.error,
+            "secondary" => &mut self.secondary,
+            "secondary_soft" => &mut self.secondary_soft,
+            "info" => &mut self.info,
+            "info_soft" => &mut self.info_soft,
+            "on_accent" => &mut self.on_accent,
+            "selection_fg" => &mut self.selection_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "hint_bg" => &mut self.hint_bg,
+            "status_text" => &mut self.status_text,
+            "border" => &mut self.border,
+            "code_bg" => &mut self.code_bg,
+            "match_bg" => &mut self.match_bg,
+            "match_dim_bg" => &mut self.match_dim_bg,
+            _ => return None,
+        })
+    }
+
+    /// Resolve `name` against the built-ins and the custom palettes in
+    /// `[cli.themes]`. Unknown names, slots and colours are reported and
+    /// fall back to the cyberpunk palette or leave the slot unchanged.
+    pub fn resolve(settings: &CliSettings, name: &str) -> (Palette, Vec<String>) {
+        let mut errors = Vec::new();
+        let Some(custom) = settings.themes.get(name) else {
+            return match Self::built_in(name) {
+                Some(palette) => (palette, errors),
+                None => (Self::CYBERPUNK, vec![format!("unknown theme '{}'", name)]),
+            };
+        };
+
+        let base = custom.base.as_deref().unwrap_or("cyberpunk");
+        let mut palette = Self::built_in(base).unwrap_or_else(|| {
+            errors.push(format!("unknown base theme '{}'", base));
+            Self::CYBERPUNK
+        });
+        if let Some(glow) = custom.glow {
+            palette.glow = glow;
+        }
+        for (slot, value) in &custom.colors {
+            let Some(color) = palette.slot_mut(slot) else {
+                errors.push(format!("unknown colour slot '{}'", slot));
+                continue;
+            };
+            match Color::from_str(value) {
+                Ok(parsed) => *color = parsed,
+                Err(_) => errors.push(format!("bad colour '{}' for {}", value, slot)),
+            }
+        }
+        (palette, errors)
+    }
+
+    /// Built-in then custom theme names.
+    pub fn names(settings: &CliSettings) -> Vec<String> {
+        Self::BUILT_IN
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .chain(
+                settings
+                    .themes
+                    .keys()
+                    .filter(|name| Self::built_in(name).is_none())
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+static ACTIVE: RwLock<Palette> = RwLock::new(Palette::CYBERPUNK);
+
+/// The active palette.
+pub fn palette() -> Palette {
+    *ACTIVE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Make `palette` the active one.
+pub fn apply(palette: Palette) {
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = palette;
+}
 
 pub fn border(has_focus: bool) -> Style {
+    let p = palette();
     if has_focus {
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD)
+        Style::default().fg(p.accent).add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(p.muted)
     }
 }
 
 pub fn selected() -> Style {
+    let p = palette();
     Style::default()
-        .bg(Color::Rgb(0, 60, 90))
-        .fg(Color::White)
+        .bg(p.selection_bg)
+        .fg(p.selection_fg)
         .add_modifier(Modifier::BOLD)
 }
 
 pub fn header_title() -> Style {
     Style::default()
-        .fg(Color::Cyan)
+        .fg(palette().accent)
         .add_modifier(Modifier::BOLD)
 }
 
 pub fn status_ok() -> Style {
-    Style::default().fg(Color::Green)
+    Style::default().fg(palette().success)
 }
 
 pub fn status_err() -> Style {
-    Style::default().fg(Color::Red)
+    Style::default().fg(palette().error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t_koma_core::ThemeSettings;
+
+    #[test]
+    fn test_resolve_theme() {
+        let mut settings = CliSettings::default();
+        assert_eq!(
+            Palette::resolve(&settings, "light"),
+            (Palette::LIGHT, Vec::new())
+        );
+        let (palette, errors) = Palette::resolve(&settings, "solarized");
+        assert_eq!(palette, Palette::CYBERPUNK);
+        assert_eq!(errors, ["unknown theme 'solarized'"]);
+
+        let mut colors = std::collections::BTreeMap::new();
+        colors.insert("accent".to_string(), "#ff8700".to_string());
+        colors.insert("muted".to_string(), "dark gray".to_string());
+        colors.insert("sparkle".to_string(), "red".to_string());
+        colors.insert("error".to_string(), "reddish".to_string());
+        settings.themes.insert(
+            "paper".to_string(),
+            ThemeSettings {
+                base: Some("light".to_string()),
+                glow: Some(true),
+                colors,
+            },
+        );
+        let (palette, errors) = Palette::resolve(&settings, "paper");
+        assert_eq!(palette.accent, Color::Rgb(255, 135, 0));
+        assert_eq!(palette.muted, Color::DarkGray);
+        assert_eq!(palette.text, Color::Black);
+        assert_eq!(palette.error, Palette::LIGHT.error);
+        assert!(palette.glow);
+        assert_eq!(
+            errors,
+            [
+                "bad colour 'reddish' for error",
+                "unknown colour slot 'sparkle'"
+            ]
+        );
+        assert_eq!(
+            Palette::names(&settings),
+            ["cyberpunk", "light", "high_contrast", "paper"]
+        );
+    }
 }
@@ -0,0 +1,125 @@
+//! Rotating, timestamped backups of `config.toml`.
+//!
+//! Every [`Settings::save`](super::Settings::save) copies the file it is about
+//! to overwrite into `backups/` next to it. The newest [`MAX_CONFIG_BACKUPS`]
+//! are kept, so a bad save can always be rolled back a few steps.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Number of backups kept; older ones are deleted on the next backup.
+pub const MAX_CONFIG_BACKUPS: usize = 10;
+
+const BACKUP_PREFIX: &str = "config-";
+const BACKUP_SUFFIX: &str = ".toml";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// A backup file of `config.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigBackup {
+    pub path: PathBuf,
+    /// When the backup was taken, parsed from the file name.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Directory holding the backups of the config file at `config_path`.
+pub fn backup_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// Copy the config file at `config_path` into the backup directory and prune
+/// old backups. Returns `None` when there is no config file yet, or when it
+/// matches the newest backup already.
+pub fn create_backup(config_path: &Path) -> io::Result<Option<PathBuf>> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(config_path)?;
+    let dir = backup_dir(config_path);
+    if let Some(newest) = list_backups(config_path).first()
+        && fs::read(&newest.path).is_ok_and(|previous| previous == content)
+    {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(&dir)?;
+    let stamp = Utc::now().format(STAMP_FORMAT);
+    let path = dir.join(format!("{BACKUP_PREFIX}{stamp}{BACKUP_SUFFIX}"));
+    fs::write(&path, content)?;
+    prune_backups(config_path, MAX_CONFIG_BACKUPS)?;
+    Ok(Some(path))
+}
+
+/// Backups of the config file at `config_path`, newest first.
+pub fn list_backups(config_path: &Path) -> Vec<ConfigBackup> {
+    let Ok(entries) = fs::read_dir(backup_dir(config_path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<ConfigBackup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let created_at = parse_backup_name(path.file_name()?.to_str()?)?;
+            Some(ConfigBackup { path, created_at })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    backups
+}
+
+/// Delete all but the newest `keep` backups.
+fn prune_backups(config_path: &Path, keep: usize) -> io::Result<()> {
+    for backup in list_backups(config_path).into_iter().skip(keep) {
+        fs::remove_file(&backup.path)?;
+    }
+    Ok(())
+}
+
+fn parse_backup_name(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backups_rotate_and_skip_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+        assert_eq!(create_backup(&config).unwrap(), None);
+
+        fs::write(&config, "a = 1\n").unwrap();
+        assert!(create_backup(&config).unwrap().is_some());
+        // Unchanged since the newest backup: nothing new is written.
+        assert_eq!(create_backup(&config).unwrap(), None);
+
+        for i in 0..MAX_CONFIG_BACKUPS + 2 {
+            fs::write(&config, format!("a = {}\n", i + 2)).unwrap();
+            create_backup(&config).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let backups = list_backups(&config);
+        assert_eq!(backups.len(), MAX_CONFIG_BACKUPS);
+        let newest = fs::read_to_string(&backups[0].path).unwrap();
+        assert_eq!(newest, format!("a = {}\n", MAX_CONFIG_BACKUPS + 3));
+    }
+
+    #[test]
+    fn test_parse_backup_name() {
+        let parsed = parse_backup_name("config-20260301T101500.250Z.toml").unwrap();
+        assert_eq!(parsed.timestamp_millis(), 1_772_360_100_250);
+        assert!(parse_backup_name("config.toml.bak.123").is_none());
+    }
+}
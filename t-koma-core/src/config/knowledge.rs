@@ -4,13 +4,14 @@
 //! `t-koma-knowledge`. They are created from the user-facing
 //! `KnowledgeToolsSettings` TOML structs via `From`.
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
-use super::settings::{KnowledgeSearchSettings, KnowledgeToolsSettings};
+use super::settings::{KnowledgeLanguageSettings, KnowledgeSearchSettings, KnowledgeToolsSettings};
 
 /// Which embedding backend to use.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -19,6 +20,10 @@ pub enum EmbeddingProviderKind {
     #[default]
     Ollama,
     OpenRouter,
+    /// OpenAI `/embeddings` API and compatible servers (Voyage AI, llama.cpp,
+    /// text-embeddings-inference, vLLM, ...)
+    #[serde(rename = "openai")]
+    OpenAi,
 }
 
 impl fmt::Display for EmbeddingProviderKind {
@@ -26,6 +31,7 @@ impl fmt::Display for EmbeddingProviderKind {
         match self {
             Self::Ollama => write!(f, "ollama"),
             Self::OpenRouter => write!(f, "openrouter"),
+            Self::OpenAi => write!(f, "openai"),
         }
     }
 }
@@ -36,13 +42,86 @@ impl FromStr for EmbeddingProviderKind {
         match s {
             "ollama" => Ok(Self::Ollama),
             "openrouter" | "open_router" => Ok(Self::OpenRouter),
+            "openai" | "openai_compatible" => Ok(Self::OpenAi),
             other => Err(format!("unknown embedding provider: {other}")),
         }
     }
 }
 
+/// How existing embeddings are replaced when the embedding model changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReembedMode {
+    /// Drop all vectors and re-embed everything before dense search works again.
+    #[default]
+    Full,
+    /// Keep old vectors serving queries and replace them in the background,
+    /// recently accessed notes first. Requires an unchanged dimension.
+    Gradual,
+}
+
+impl fmt::Display for ReembedMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Gradual => write!(f, "gradual"),
+        }
+    }
+}
+
+impl FromStr for ReembedMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "gradual" => Ok(Self::Gradual),
+            other => Err(format!("unknown reembed mode: {other}")),
+        }
+    }
+}
+
+/// How chunk text is split into BM25 (FTS5) tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Bm25Tokenizer {
+    /// Whitespace/punctuation word splitting (FTS5 `unicode61`).
+    #[default]
+    Words,
+    /// Overlapping character bigrams for CJK runs, which have no word spacing.
+    Bigram,
+}
+
+impl fmt::Display for Bm25Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Words => write!(f, "words"),
+            Self::Bigram => write!(f, "bigram"),
+        }
+    }
+}
+
+impl FromStr for Bm25Tokenizer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "words" | "unicode61" => Ok(Self::Words),
+            "bigram" | "cjk" => Ok(Self::Bigram),
+            other => Err(format!("unknown bm25 tokenizer: {other}")),
+        }
+    }
+}
+
+/// Resolved per-language overrides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LanguageSettings {
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    #[serde(default)]
+    pub bm25_tokenizer: Option<Bm25Tokenizer>,
+}
+
 /// Resolved knowledge engine settings (all values filled with defaults).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct KnowledgeSettings {
     #[serde(default)]
     pub embedding_provider: EmbeddingProviderKind,
@@ -50,6 +129,9 @@ pub struct KnowledgeSettings {
     pub embedding_url: String,
     #[serde(default = "default_embedding_model")]
     pub embedding_model: String,
+    /// Env var holding the embedding API key (provider default when unset).
+    #[serde(default)]
+    pub embedding_api_key_env: Option<String>,
     #[serde(default)]
     pub embedding_dim: Option<usize>,
     #[serde(default = "default_embedding_batch")]
@@ -57,6 +139,8 @@ pub struct KnowledgeSettings {
     #[serde(default = "default_reconcile_seconds")]
     pub reconcile_seconds: u64,
     #[serde(default)]
+    pub reembed_mode: ReembedMode,
+    #[serde(default)]
     pub knowledge_db_path_override: Option<PathBuf>,
     /// Override the root data directory for all knowledge paths.
     /// When set, all paths (shared notes, references, ghost dirs) derive from
@@ -65,6 +149,9 @@ pub struct KnowledgeSettings {
     pub data_root_override: Option<PathBuf>,
     #[serde(default)]
     pub search: SearchDefaults,
+    /// Per-language overrides keyed by ISO 639-1 code.
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageSettings>,
 }
 
 impl Default for KnowledgeSettings {
@@ -73,12 +160,15 @@ impl Default for KnowledgeSettings {
             embedding_provider: EmbeddingProviderKind::default(),
             embedding_url: default_embedding_url(),
             embedding_model: default_embedding_model(),
+            embedding_api_key_env: None,
             embedding_dim: None,
             embedding_batch: default_embedding_batch(),
             reconcile_seconds: default_reconcile_seconds(),
+            reembed_mode: ReembedMode::default(),
             knowledge_db_path_override: None,
             data_root_override: None,
             search: SearchDefaults::default(),
+            languages: BTreeMap::new(),
         }
     }
 }
@@ -87,12 +177,52 @@ impl KnowledgeSettings {
     /// Composite key that identifies the current embedding configuration.
     /// When this changes, all existing embeddings must be recomputed.
     pub fn embedding_fingerprint(&self) -> String {
-        format!("{}:{}", self.embedding_provider, self.embedding_model)
+        let mut fingerprint = format!("{}:{}", self.embedding_provider, self.embedding_model);
+        for (language, overrides) in &self.languages {
+            if let Some(model) = &overrides.embedding_model {
+                fingerprint.push_str(&format!(";{language}={model}"));
+            }
+        }
+        fingerprint
+    }
+
+    /// Env var holding the embedding API key, if the provider needs one.
+    pub fn embedding_api_key_env(&self) -> Option<&str> {
+        match (&self.embedding_api_key_env, self.embedding_provider) {
+            (Some(env), _) => Some(env),
+            (None, EmbeddingProviderKind::OpenRouter) => Some("OPENROUTER_API_KEY"),
+            (None, EmbeddingProviderKind::OpenAi) => Some("OPENAI_API_KEY"),
+            (None, EmbeddingProviderKind::Ollama) => None,
+        }
+    }
+
+    /// Embedding model for text in `language` (falls back to `embedding_model`).
+    pub fn embedding_model_for(&self, language: Option<&str>) -> &str {
+        language
+            .and_then(|code| self.languages.get(code))
+            .and_then(|overrides| overrides.embedding_model.as_deref())
+            .unwrap_or(&self.embedding_model)
+    }
+
+    /// BM25 tokenization for text in `language`.
+    ///
+    /// Defaults to bigrams for Chinese, Japanese, and Korean, words otherwise.
+    pub fn bm25_tokenizer_for(&self, language: Option<&str>) -> Bm25Tokenizer {
+        if let Some(tokenizer) = language
+            .and_then(|code| self.languages.get(code))
+            .and_then(|overrides| overrides.bm25_tokenizer)
+        {
+            return tokenizer;
+        }
+        match language {
+            Some("zh" | "ja" | "ko") => Bm25Tokenizer::Bigram,
+            _ => Bm25Tokenizer::Words,
+        }
     }
 }
 
 /// Resolved search tuning knobs.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchDefaults {
     #[serde(default = "default_rrf_k")]
     pub rrf_k: usize,
@@ -109,6 +239,12 @@ pub struct SearchDefaults {
     /// Boost multiplier for documentation files in reference search.
     #[serde(default = "default_doc_boost")]
     pub doc_boost: f32,
+    /// Seconds a cached `knowledge_search` result stays valid (0 disables caching).
+    #[serde(default = "default_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+    /// Maximum number of cached `knowledge_search` results.
+    #[serde(default = "default_cache_max_entries")]
+    pub cache_max_entries: usize,
 }
 
 impl Default for SearchDefaults {
@@ -121,6 +257,8 @@ impl Default for SearchDefaults {
             bm25_limit: default_bm25_limit(),
             dense_limit: default_dense_limit(),
             doc_boost: default_doc_boost(),
+            cache_ttl_seconds: default_cache_ttl_seconds(),
+            cache_max_entries: default_cache_max_entries(),
         }
     }
 }
@@ -133,6 +271,10 @@ fn default_openrouter_embedding_url() -> String {
     "https://openrouter.ai/api/v1".to_string()
 }
 
+fn default_openai_embedding_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
 fn default_embedding_model() -> String {
     "qwen3-embedding:8b".to_string()
 }
@@ -173,6 +315,14 @@ fn default_doc_boost() -> f32 {
     1.5
 }
 
+fn default_cache_ttl_seconds() -> u64 {
+    120
+}
+
+fn default_cache_max_entries() -> usize {
+    256
+}
+
 impl From<&KnowledgeToolsSettings> for KnowledgeSettings {
     fn from(value: &KnowledgeToolsSettings) -> Self {
         let mut settings = KnowledgeSettings::default();
@@ -185,6 +335,11 @@ impl From<&KnowledgeToolsSettings> for KnowledgeSettings {
             settings.embedding_url = url.clone();
         } else if settings.embedding_provider == EmbeddingProviderKind::OpenRouter {
             settings.embedding_url = default_openrouter_embedding_url();
+        } else if settings.embedding_provider == EmbeddingProviderKind::OpenAi {
+            settings.embedding_url = default_openai_embedding_url();
+        }
+        if let Some(env) = &value.embedding_api_key_env {
+            settings.embedding_api_key_env = Some(env.clone());
         }
         if let Some(model) = &value.embedding_model {
             settings.embedding_model = model.clone();
@@ -198,10 +353,18 @@ impl From<&KnowledgeToolsSettings> for KnowledgeSettings {
         if let Some(seconds) = value.reconcile_seconds {
             settings.reconcile_seconds = seconds;
         }
+        if let Some(mode) = &value.reembed_mode {
+            settings.reembed_mode = mode.parse().unwrap_or_default();
+        }
         if let Some(path) = &value.knowledge_db_path_override {
             settings.knowledge_db_path_override = Some(PathBuf::from(path));
         }
         apply_search_overrides(&mut settings.search, &value.search);
+        settings.languages = value
+            .languages
+            .iter()
+            .map(|(code, overrides)| (code.to_ascii_lowercase(), resolve_language(overrides)))
+            .collect();
         settings
     }
 }
@@ -228,4 +391,20 @@ fn apply_search_overrides(search: &mut SearchDefaults, overrides: &KnowledgeSear
     if let Some(doc_boost) = overrides.doc_boost {
         search.doc_boost = doc_boost;
     }
+    if let Some(ttl) = overrides.cache_ttl_seconds {
+        search.cache_ttl_seconds = ttl;
+    }
+    if let Some(max_entries) = overrides.cache_max_entries {
+        search.cache_max_entries = max_entries;
+    }
+}
+
+fn resolve_language(overrides: &KnowledgeLanguageSettings) -> LanguageSettings {
+    LanguageSettings {
+        embedding_model: overrides.embedding_model.clone(),
+        bm25_tokenizer: overrides
+            .bm25_tokenizer
+            .as_deref()
+            .and_then(|value| value.parse().ok()),
+    }
 }
@@ -10,9 +10,16 @@
 //! - `OPENROUTER_API_KEY` - OpenRouter API key
 //! - `OPENAI_API_KEY` - Optional OpenAI-compatible API key
 //! - `KIMI_API_KEY` - Kimi Code API key
+//! - `AZURE_OPENAI_API_KEY` - Azure OpenAI API key, or `AZURE_TENANT_ID` +
+//!   `AZURE_CLIENT_ID` + `AZURE_CLIENT_SECRET` for Azure AD auth
 //! - `DISCORD_BOT_TOKEN` - Discord bot token
+//! - `TELEGRAM_BOT_TOKEN` - Telegram bot token
+//! - `SLACK_BOT_TOKEN` + `SLACK_APP_TOKEN` - Slack bot and Socket Mode tokens
+//! - `EMAIL_PASSWORD` - IMAP/SMTP password for the email bridge
 //! - `BRAVE_API_KEY` - Brave Search API key
 //! - `PERPLEXITY_API_KEY` - Perplexity Sonar API key
+//! - `TAVILY_API_KEY` - Tavily Search API key
+//! - `GOOGLE_CSE_API_KEY` + `GOOGLE_CSE_ID` - Google Programmable Search key and engine ID
 //!
 //! ## Settings (TOML File)
 //! Located at `~/.config/t-koma/config.toml`:
@@ -31,22 +38,45 @@
 //! [discord]
 //! enabled = false
 //!
+//! [telegram]
+//! enabled = false
+//!
+//! [slack]
+//! enabled = false
+//!
+//! [email]
+//! enabled = false
+//!
 //! [logging]
 //! level = "info"
 //! ```
 
+pub mod backups;
 pub mod knowledge;
 mod secrets;
 mod settings;
 
 use crate::message::ProviderType;
 
-pub use knowledge::{EmbeddingProviderKind, KnowledgeSettings, SearchDefaults};
-pub use secrets::{Secrets, SecretsError};
+pub use backups::{ConfigBackup, MAX_CONFIG_BACKUPS};
+pub use knowledge::{
+    Bm25Tokenizer, EmbeddingProviderKind, KnowledgeSettings, LanguageSettings, ReembedMode,
+    SearchDefaults,
+};
+pub use secrets::{AzureAdCredentials, Secrets, SecretsError};
 pub use settings::{
-    GatewaySettings, HeartbeatTimingSettings, KnowledgeSearchSettings, KnowledgeToolsSettings,
-    ModelAliases, ModelConfig, OpenRouterSettings, ReflectionTimingSettings, Settings,
-    SettingsError,
+    BrowserSettings, CliSettings, ClientLimitSettings, CompactionSettings, DiscordSettings,
+    EmailSettings, GatewaySettings, GenerationParams, HeartbeatAdaptiveSettings,
+    HeartbeatTimingSettings, HttpRequestSettings, InjectionAction, JobLogRetentionSettings,
+    JobQueueSettings, KeyBinding, KeyPreset, KeySettings, KnowledgeLanguageSettings,
+    KnowledgeSearchSettings, KnowledgeToolsSettings, McpServerSettings, McpSettings, ModelAliases,
+    ModelConfig, ModelPricingConfig, OpenRouterSettings, OutputFilterOverride, OutputFilterPolicy,
+    OutputFilterSettings, PromptCacheSettings, ProviderRetryOverride, ProviderRetrySettings,
+    ProviderTimeoutSettings, RedactionPattern, ReflectionQualitySettings, ReflectionTimingSettings,
+    SessionArchiveSettings, Settings, SettingsError, ShellToolSettings, ThemeSettings,
+    ThinkingDisplay, ThinkingSettings, ToolOutputSettings, ToolTimeoutSettings, ToolsSettings,
+    TranscriptionSettings, UntrustedContentSettings, WebCacheSettings, WebDomainPolicy,
+    WebDomainSettings, WebRenderSettings, WebSearchSettings, WebhookToolSettings,
 };
 
 #[cfg(test)]
@@ -107,6 +137,21 @@ pub enum ConfigError {
 
     #[error("OpenRouter routing alias '{alias}' has empty order")]
     OpenRouterProviderOrderEmpty { alias: String },
+
+    #[error("Model '{alias}' thinking_budget must be at least 1024 tokens for Anthropic")]
+    ThinkingBudgetTooSmall { alias: String },
+
+    #[error("Output moderation model alias '{0}' not found in config")]
+    ModerationModelNotFound(String),
+
+    #[error("Invalid heartbeat_timing.continue_schedule: {0}")]
+    InvalidHeartbeatSchedule(#[from] crate::CronScheduleError),
+
+    #[error("Invalid heartbeat_timing.adaptive: {0}")]
+    InvalidHeartbeatAdaptive(&'static str),
+
+    #[error("Reflection quality model alias '{0}' not found in config")]
+    ReflectionQualityModelNotFound(String),
 }
 
 impl Config {
@@ -161,6 +206,34 @@ impl Config {
             }
         }
 
+        for alias in settings.output_filters.moderation_models() {
+            if !settings.models.contains_key(alias) {
+                return Err(ConfigError::ModerationModelNotFound(alias.to_string()));
+            }
+        }
+
+        if let Some(schedule) = &settings.heartbeat_timing.continue_schedule {
+            crate::CronSchedule::parse(schedule, None)?;
+        }
+
+        if let Some(alias) = &settings.reflection.quality.model
+            && !settings.models.contains_key(alias)
+        {
+            return Err(ConfigError::ReflectionQualityModelNotFound(alias.clone()));
+        }
+
+        let adaptive = &settings.heartbeat_timing.adaptive;
+        if adaptive.backoff_factor.is_nan() || adaptive.backoff_factor < 1.0 {
+            return Err(ConfigError::InvalidHeartbeatAdaptive(
+                "backoff_factor must be at least 1",
+            ));
+        }
+        if adaptive.min_idle_minutes > adaptive.max_idle_minutes {
+            return Err(ConfigError::InvalidHeartbeatAdaptive(
+                "min_idle_minutes exceeds max_idle_minutes",
+            ));
+        }
+
         Ok(Self { secrets, settings })
     }
 
@@ -197,6 +270,14 @@ impl Config {
             }
         };
 
+        if model.provider == ProviderType::Anthropic
+            && model.thinking_budget.is_some_and(|budget| budget < 1024)
+        {
+            return Err(ConfigError::ThinkingBudgetTooSmall {
+                alias: alias.to_string(),
+            });
+        }
+
         match model.provider {
             ProviderType::Anthropic | ProviderType::Gemini | ProviderType::KimiCode => {
                 if model.routing.is_some() {
@@ -249,6 +330,26 @@ impl Config {
                 }
                 let _ = Self::resolve_api_key_for_model(secrets, alias, model)?;
             }
+            ProviderType::AzureOpenAi => {
+                let has_endpoint = model
+                    .base_url
+                    .as_deref()
+                    .is_some_and(|value| !value.trim().is_empty());
+                if !has_endpoint {
+                    return Err(misconfigured());
+                }
+                if model.routing.is_some() {
+                    return Err(ConfigError::OpenRouterProviderOnNonOpenRouterModel {
+                        alias: alias.to_string(),
+                        provider: model.provider.to_string(),
+                    });
+                }
+                let has_key = Self::resolve_api_key_for_model(secrets, alias, model)?
+                    .is_some_and(|key| !key.trim().is_empty());
+                if !has_key && secrets.azure_ad.is_none() {
+                    return Err(not_configured());
+                }
+            }
         }
 
         Ok(())
@@ -274,6 +375,7 @@ impl Config {
                 ProviderType::Anthropic => "ANTHROPIC_API_KEY",
                 ProviderType::Gemini => "GEMINI_API_KEY",
                 ProviderType::KimiCode => "KIMI_API_KEY",
+                ProviderType::AzureOpenAi => "AZURE_OPENAI_API_KEY",
             });
         match std::env::var(env_var) {
             Ok(value) => Ok(Some(value)),
@@ -355,11 +457,32 @@ impl Config {
         self.secrets.kimi_api_key.as_deref()
     }
 
+    /// Get the Azure AD service principal (if fully configured).
+    pub fn azure_ad_credentials(&self) -> Option<&AzureAdCredentials> {
+        self.secrets.azure_ad.as_ref()
+    }
+
     /// Get the OpenRouter API key (if configured).
     pub fn openrouter_api_key(&self) -> Option<&str> {
         self.secrets.openrouter_api_key.as_deref()
     }
 
+    /// Resolve the API key for the knowledge embedding provider.
+    ///
+    /// OpenRouter embeddings share the chat models' `OPENROUTER_API_KEY`
+    /// unless `embedding_api_key_env` points elsewhere.
+    pub fn embedding_api_key(&self) -> Option<String> {
+        let settings = KnowledgeSettings::from(&self.settings.tools.knowledge);
+        if settings.embedding_api_key_env.is_none()
+            && settings.embedding_provider == EmbeddingProviderKind::OpenRouter
+        {
+            return self.secrets.openrouter_api_key.clone();
+        }
+        settings
+            .embedding_api_key_env()
+            .and_then(|env_var| std::env::var(env_var).ok())
+    }
+
     /// Resolve API key for a configured model alias.
     pub fn api_key_for_alias(&self, alias: &str) -> Result<Option<String>, ConfigError> {
         let model = self
@@ -375,6 +498,31 @@ impl Config {
         self.secrets.discord_bot_token.as_deref()
     }
 
+    /// Get the Telegram bot token (if configured).
+    pub fn telegram_bot_token(&self) -> Option<&str> {
+        self.secrets.telegram_bot_token.as_deref()
+    }
+
+    /// Get the Slack bot token (if configured).
+    pub fn slack_bot_token(&self) -> Option<&str> {
+        self.secrets.slack_bot_token.as_deref()
+    }
+
+    /// Get the Slack app-level token (if configured).
+    pub fn slack_app_token(&self) -> Option<&str> {
+        self.secrets.slack_app_token.as_deref()
+    }
+
+    /// Get the email bridge password (if configured).
+    pub fn email_password(&self) -> Option<&str> {
+        self.secrets.email_password.as_deref()
+    }
+
+    /// Get the shared gateway secret (if configured).
+    pub fn gateway_secret(&self) -> Option<&str> {
+        self.secrets.gateway_secret.as_deref()
+    }
+
     /// Get the Brave Search API key (if configured).
     pub fn brave_api_key(&self) -> Option<&str> {
         self.secrets.brave_api_key.as_deref()
@@ -384,6 +532,28 @@ impl Config {
     pub fn discord_enabled(&self) -> bool {
         self.settings.discord.enabled && self.secrets.discord_bot_token.is_some()
     }
+
+    /// Check if Telegram bot is enabled and has a token.
+    pub fn telegram_enabled(&self) -> bool {
+        self.settings.telegram.enabled && self.secrets.telegram_bot_token.is_some()
+    }
+
+    /// Check if the Slack app is enabled and has both tokens.
+    pub fn slack_enabled(&self) -> bool {
+        self.settings.slack.enabled
+            && self.secrets.slack_bot_token.is_some()
+            && self.secrets.slack_app_token.is_some()
+    }
+
+    /// Check if the email bridge is enabled, has servers and a password.
+    pub fn email_enabled(&self) -> bool {
+        let email = &self.settings.email;
+        email.enabled
+            && !email.address.is_empty()
+            && !email.imap_host.is_empty()
+            && !email.smtp_host.is_empty()
+            && self.secrets.email_password.is_some()
+    }
 }
 
 /// Load .env files if they exist (for development convenience).
@@ -414,7 +584,15 @@ mod tests {
             env::remove_var("OPENROUTER_API_KEY");
             env::remove_var("OPENAI_API_KEY");
             env::remove_var("DISCORD_BOT_TOKEN");
+            env::remove_var("TELEGRAM_BOT_TOKEN");
+            env::remove_var("SLACK_BOT_TOKEN");
+            env::remove_var("SLACK_APP_TOKEN");
+            env::remove_var("EMAIL_PASSWORD");
             env::remove_var("BRAVE_API_KEY");
+            env::remove_var("AZURE_OPENAI_API_KEY");
+            env::remove_var("AZURE_TENANT_ID");
+            env::remove_var("AZURE_CLIENT_ID");
+            env::remove_var("AZURE_CLIENT_SECRET");
         }
     }
 
@@ -427,7 +605,11 @@ mod tests {
             routing: None,
             context_window: None,
             headers: None,
+            api_version: None,
             retry_on_empty: None,
+            thinking_budget: None,
+            generation: Default::default(),
+            pricing: None,
         }
     }
 
@@ -458,6 +640,40 @@ mod tests {
         assert!(config.has_provider(ProviderType::Anthropic));
         assert_eq!(config.default_provider(), ProviderType::Anthropic);
         assert_eq!(config.default_model_id(), "test-model");
+
+        // Case 3: Anthropic thinking budget below the API minimum
+        let mut thinking_settings = config.settings.clone();
+        if let Some(m) = thinking_settings.models.get_mut("default") {
+            m.thinking_budget = Some(512);
+        }
+        let err = Config::from_parts(config.secrets.clone(), thinking_settings).unwrap_err();
+        assert!(matches!(err, ConfigError::ThinkingBudgetTooSmall { .. }));
+
+        // Case 4: Output moderation pointing at an unknown alias
+        let mut moderation_settings = config.settings.clone();
+        moderation_settings.output_filters.moderation_model = Some("missing".to_string());
+        let err = Config::from_parts(config.secrets.clone(), moderation_settings).unwrap_err();
+        assert!(matches!(err, ConfigError::ModerationModelNotFound(alias) if alias == "missing"));
+
+        // Case 5: Heartbeat continue schedule that isn't a cron expression
+        let mut schedule_settings = config.settings.clone();
+        schedule_settings.heartbeat_timing.continue_schedule = Some("every day".to_string());
+        let err = Config::from_parts(config.secrets.clone(), schedule_settings).unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidHeartbeatSchedule(_)));
+
+        // Case 6: Adaptive heartbeat cadence that shrinks on no-op heartbeats
+        let mut adaptive_settings = config.settings.clone();
+        adaptive_settings.heartbeat_timing.adaptive.backoff_factor = 0.5;
+        let err = Config::from_parts(config.secrets.clone(), adaptive_settings).unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidHeartbeatAdaptive(_)));
+
+        // Case 7: Reflection quality model pointing at an unknown alias
+        let mut quality_settings = config.settings.clone();
+        quality_settings.reflection.quality.model = Some("missing".to_string());
+        let err = Config::from_parts(config.secrets.clone(), quality_settings).unwrap_err();
+        assert!(
+            matches!(err, ConfigError::ReflectionQualityModelNotFound(alias) if alias == "missing")
+        );
     }
 
     #[test]
@@ -512,6 +728,108 @@ mod tests {
         assert!(config.discord_enabled());
     }
 
+    #[test]
+    fn test_telegram_enabled() {
+        let _lock = crate::config::ENV_MUTEX.lock().unwrap();
+        clear_env();
+        unsafe {
+            env::set_var("ANTHROPIC_API_KEY", "sk-test");
+        }
+
+        let mut settings = Settings::default();
+        settings.telegram.enabled = true;
+
+        // Enabled in settings but no token
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings: settings.clone(),
+        };
+        assert!(!config.telegram_enabled());
+
+        unsafe {
+            env::set_var("TELEGRAM_BOT_TOKEN", "token");
+        }
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings,
+        };
+        assert!(config.telegram_enabled());
+        assert_eq!(config.telegram_bot_token(), Some("token"));
+
+        unsafe {
+            env::remove_var("TELEGRAM_BOT_TOKEN");
+        }
+    }
+
+    #[test]
+    fn test_slack_enabled_needs_both_tokens() {
+        let _lock = crate::config::ENV_MUTEX.lock().unwrap();
+        clear_env();
+        unsafe {
+            env::set_var("ANTHROPIC_API_KEY", "sk-test");
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+        }
+
+        let mut settings = Settings::default();
+        settings.slack.enabled = true;
+
+        // Bot token alone cannot open a Socket Mode connection
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings: settings.clone(),
+        };
+        assert!(!config.slack_enabled());
+
+        unsafe {
+            env::set_var("SLACK_APP_TOKEN", "xapp-test");
+        }
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings,
+        };
+        assert!(config.slack_enabled());
+        assert_eq!(config.slack_app_token(), Some("xapp-test"));
+
+        unsafe {
+            env::remove_var("SLACK_BOT_TOKEN");
+            env::remove_var("SLACK_APP_TOKEN");
+        }
+    }
+
+    #[test]
+    fn test_email_enabled_needs_servers_and_password() {
+        let _lock = crate::config::ENV_MUTEX.lock().unwrap();
+        clear_env();
+        unsafe {
+            env::set_var("ANTHROPIC_API_KEY", "sk-test");
+            env::set_var("EMAIL_PASSWORD", "hunter2");
+        }
+
+        let mut settings = Settings::default();
+        settings.email.enabled = true;
+        settings.email.address = "ghost@example.com".to_string();
+        settings.email.imap_host = "imap.example.com".to_string();
+
+        // No SMTP host: replies could not be sent
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings: settings.clone(),
+        };
+        assert!(!config.email_enabled());
+
+        settings.email.smtp_host = "smtp.example.com".to_string();
+        let config = Config {
+            secrets: Secrets::from_env_inner().unwrap(),
+            settings,
+        };
+        assert!(config.email_enabled());
+        assert_eq!(config.email_password(), Some("hunter2"));
+
+        unsafe {
+            env::remove_var("EMAIL_PASSWORD");
+        }
+    }
+
     #[test]
     fn test_openrouter_provider_routing_validation() {
         let _lock = crate::config::ENV_MUTEX.lock().unwrap();
@@ -587,6 +905,36 @@ mod tests {
         assert_eq!(config.default_provider(), ProviderType::OpenAiCompatible);
     }
 
+    #[test]
+    fn test_azure_openai_validation() {
+        let _lock = crate::config::ENV_MUTEX.lock().unwrap();
+        clear_env();
+
+        let mut azure = model(ProviderType::AzureOpenAi, "gpt4o-prod");
+        azure.base_url = Some("https://res.openai.azure.com".to_string());
+        let mut settings = Settings::default();
+        settings.models.insert("azure".to_string(), azure);
+        settings.default_model = ModelAliases::single("azure");
+
+        let secrets = Secrets::from_env_inner().unwrap();
+        let err = Config::from_parts(secrets, settings.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::DefaultModelProviderNotConfigured { .. }
+        ));
+
+        unsafe {
+            env::set_var("AZURE_TENANT_ID", "tenant");
+            env::set_var("AZURE_CLIENT_ID", "client");
+            env::set_var("AZURE_CLIENT_SECRET", "secret");
+        }
+        let secrets = Secrets::from_env_inner().unwrap();
+        let config = Config::from_parts(secrets, settings).expect("azure AD config");
+        assert_eq!(config.default_provider(), ProviderType::AzureOpenAi);
+        assert_eq!(config.azure_ad_credentials().unwrap().tenant_id, "tenant");
+        clear_env();
+    }
+
     #[test]
     fn test_model_api_key_env_override() {
         let _lock = crate::config::ENV_MUTEX.lock().unwrap();
@@ -25,14 +25,48 @@ pub struct Secrets {
     /// Kimi Code API key (env: KIMI_API_KEY)
     pub kimi_api_key: Option<String>,
 
+    /// Azure OpenAI API key (env: AZURE_OPENAI_API_KEY)
+    pub azure_openai_api_key: Option<String>,
+
+    /// Azure AD service principal for Azure OpenAI (env: AZURE_TENANT_ID,
+    /// AZURE_CLIENT_ID, AZURE_CLIENT_SECRET; all three required)
+    pub azure_ad: Option<AzureAdCredentials>,
+
     /// Discord bot token (env: DISCORD_BOT_TOKEN)
     pub discord_bot_token: Option<String>,
 
+    /// Telegram bot token (env: TELEGRAM_BOT_TOKEN)
+    pub telegram_bot_token: Option<String>,
+
+    /// Slack bot token, `xoxb-...` (env: SLACK_BOT_TOKEN)
+    pub slack_bot_token: Option<String>,
+
+    /// Slack app-level token for Socket Mode, `xapp-...` (env: SLACK_APP_TOKEN)
+    pub slack_app_token: Option<String>,
+
+    /// IMAP/SMTP password for the email bridge (env: EMAIL_PASSWORD)
+    pub email_password: Option<String>,
+
     /// Brave Search API key (env: BRAVE_API_KEY)
     pub brave_api_key: Option<String>,
 
     /// Perplexity API key (env: PERPLEXITY_API_KEY)
     pub perplexity_api_key: Option<String>,
+
+    /// Gateway API token used by CLI clients (env: T_KOMA_API_TOKEN)
+    pub t_koma_api_token: Option<String>,
+
+    /// Shared secret accepted by the gateway in place of an API token on
+    /// `/ws` and `/logs` (env: T_KOMA_GATEWAY_SECRET)
+    pub gateway_secret: Option<String>,
+}
+
+/// Azure AD client-credentials service principal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AzureAdCredentials {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
 }
 
 /// Errors that can occur when loading secrets
@@ -60,9 +94,38 @@ impl Secrets {
             openrouter_api_key: env::var("OPENROUTER_API_KEY").ok(),
             gemini_api_key: env::var("GEMINI_API_KEY").ok(),
             kimi_api_key: env::var("KIMI_API_KEY").ok(),
+            azure_openai_api_key: env::var("AZURE_OPENAI_API_KEY").ok(),
+            azure_ad: match (
+                env::var("AZURE_TENANT_ID"),
+                env::var("AZURE_CLIENT_ID"),
+                env::var("AZURE_CLIENT_SECRET"),
+            ) {
+                (Ok(tenant_id), Ok(client_id), Ok(client_secret)) => Some(AzureAdCredentials {
+                    tenant_id,
+                    client_id,
+                    client_secret,
+                }),
+                _ => None,
+            },
             discord_bot_token: env::var("DISCORD_BOT_TOKEN").ok(),
+            telegram_bot_token: env::var("TELEGRAM_BOT_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            slack_bot_token: env::var("SLACK_BOT_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            slack_app_token: env::var("SLACK_APP_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            email_password: env::var("EMAIL_PASSWORD")
+                .ok()
+                .filter(|password| !password.is_empty()),
             brave_api_key: env::var("BRAVE_API_KEY").ok(),
             perplexity_api_key: env::var("PERPLEXITY_API_KEY").ok(),
+            t_koma_api_token: env::var("T_KOMA_API_TOKEN").ok(),
+            gateway_secret: env::var("T_KOMA_GATEWAY_SECRET")
+                .ok()
+                .filter(|secret| !secret.is_empty()),
         };
 
         Ok(secrets)
@@ -83,6 +146,9 @@ impl Secrets {
             ProviderType::OpenAiCompatible => true,
             ProviderType::Gemini => self.gemini_api_key.is_some(),
             ProviderType::KimiCode => self.kimi_api_key.is_some(),
+            ProviderType::AzureOpenAi => {
+                self.azure_openai_api_key.is_some() || self.azure_ad.is_some()
+            }
         }
     }
 
@@ -98,6 +164,9 @@ impl Secrets {
         if self.kimi_api_key.is_some() {
             providers.push(ProviderType::KimiCode);
         }
+        if self.has_provider_type(ProviderType::AzureOpenAi) {
+            providers.push(ProviderType::AzureOpenAi);
+        }
         providers
     }
 }
@@ -112,6 +181,10 @@ mod tests {
             env::remove_var("OPENROUTER_API_KEY");
             env::remove_var("OPENAI_API_KEY");
             env::remove_var("DISCORD_BOT_TOKEN");
+            env::remove_var("TELEGRAM_BOT_TOKEN");
+            env::remove_var("SLACK_BOT_TOKEN");
+            env::remove_var("SLACK_APP_TOKEN");
+            env::remove_var("EMAIL_PASSWORD");
             env::remove_var("BRAVE_API_KEY");
         }
     }
@@ -168,6 +241,10 @@ mod tests {
             env::set_var("OPENROUTER_API_KEY", "sk-or");
             env::set_var("OPENAI_API_KEY", "openai-key");
             env::set_var("DISCORD_BOT_TOKEN", "discord-token");
+            env::set_var("TELEGRAM_BOT_TOKEN", "telegram-token");
+            env::set_var("SLACK_BOT_TOKEN", "xoxb-slack");
+            env::set_var("SLACK_APP_TOKEN", "xapp-slack");
+            env::set_var("EMAIL_PASSWORD", "mail-pass");
             env::set_var("BRAVE_API_KEY", "brave-token");
         }
 
@@ -175,6 +252,13 @@ mod tests {
         assert!(secrets.anthropic_api_key.is_some());
         assert!(secrets.openrouter_api_key.is_some());
         assert_eq!(secrets.discord_bot_token, Some("discord-token".to_string()));
+        assert_eq!(
+            secrets.telegram_bot_token,
+            Some("telegram-token".to_string())
+        );
+        assert_eq!(secrets.slack_bot_token, Some("xoxb-slack".to_string()));
+        assert_eq!(secrets.slack_app_token, Some("xapp-slack".to_string()));
+        assert_eq!(secrets.email_password, Some("mail-pass".to_string()));
         assert_eq!(secrets.brave_api_key, Some("brave-token".to_string()));
 
         let providers = secrets.available_providers();
@@ -7,6 +7,7 @@
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::de::{self, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -140,6 +141,9 @@ const DEFAULT_CONFIG_TOML: &str = r#"# t-koma configuration file
 #   - GEMINI_API_KEY
 #   - OPENAI_API_KEY (optional, for openai_compatible models)
 #   - DISCORD_BOT_TOKEN
+#   - TELEGRAM_BOT_TOKEN
+#   - SLACK_BOT_TOKEN, SLACK_APP_TOKEN
+#   - EMAIL_PASSWORD
 
 # Default model alias or fallback chain (must exist under [models])
 # Single model:   default_model = "kimi25"
@@ -165,6 +169,29 @@ port = 3000
 
 [discord]
 enabled = true
+# threads = true  # one thread (and session) per conversation in guild channels
+# search_embeds = true  # knowledge_search results as a card with open buttons (/log verbose)
+
+[telegram]
+enabled = false
+# poll_timeout_secs = 30
+
+[slack]
+enabled = false
+
+[email]
+enabled = false
+# address = "ghost@example.com"
+# username = "ghost@example.com"  # IMAP/SMTP login, defaults to address
+# imap_host = "imap.example.com"
+# imap_port = 993
+# mailbox = "INBOX"
+# smtp_host = "smtp.example.com"
+# smtp_port = 465
+# smtp_starttls = false  # true for port 587
+# poll_interval_secs = 60
+# ingest_topic = "email"  # knowledge topic for attachments (must exist)
+# allowed_senders = ["me@example.com"]
 
 [logging]
 level = "info"
@@ -203,6 +230,8 @@ graph_depth = 1
 graph_max = 20
 bm25_limit = 20
 dense_limit = 20
+cache_ttl_seconds = 120
+cache_max_entries = 256
 "#;
 
 /// Settings loaded from TOML configuration file.
@@ -231,10 +260,26 @@ pub struct Settings {
     #[serde(default)]
     pub gateway: GatewaySettings,
 
+    /// Per-client request and connection limits for the HTTP/WS server
+    #[serde(default)]
+    pub client_limits: ClientLimitSettings,
+
     /// Discord bot configuration
     #[serde(default)]
     pub discord: DiscordSettings,
 
+    /// Telegram bot configuration
+    #[serde(default)]
+    pub telegram: TelegramSettings,
+
+    /// Slack app configuration
+    #[serde(default)]
+    pub slack: SlackSettings,
+
+    /// Email bridge configuration (IMAP + SMTP)
+    #[serde(default)]
+    pub email: EmailSettings,
+
     /// Logging configuration
     #[serde(default)]
     pub logging: LoggingSettings,
@@ -258,6 +303,50 @@ pub struct Settings {
     /// Reflection timing settings
     #[serde(default)]
     pub reflection: ReflectionTimingSettings,
+
+    /// Job log retention settings
+    #[serde(default)]
+    pub job_logs: JobLogRetentionSettings,
+
+    /// Background job queue settings
+    #[serde(default)]
+    pub job_queue: JobQueueSettings,
+
+    /// Prompt cache eviction settings
+    #[serde(default)]
+    pub prompt_cache: PromptCacheSettings,
+
+    /// Cold-storage session archiving settings
+    #[serde(default)]
+    pub session_archive: SessionArchiveSettings,
+
+    /// Transient-error retry settings for provider requests
+    #[serde(default)]
+    pub provider_retry: ProviderRetrySettings,
+
+    /// Provider HTTP request timeouts
+    #[serde(default)]
+    pub provider_timeouts: ProviderTimeoutSettings,
+
+    /// Audio attachment transcription settings
+    #[serde(default)]
+    pub transcription: TranscriptionSettings,
+
+    /// Extended thinking / reasoning output settings
+    #[serde(default)]
+    pub thinking: ThinkingSettings,
+
+    /// Redaction and moderation applied to GHOST replies
+    #[serde(default)]
+    pub output_filters: OutputFilterSettings,
+
+    /// External Model Context Protocol servers whose tools GHOSTs can use
+    #[serde(default)]
+    pub mcp: McpSettings,
+
+    /// Terminal UI settings
+    #[serde(default)]
+    pub cli: CliSettings,
 }
 
 /// Model configuration entry
@@ -286,21 +375,113 @@ pub struct ModelConfig {
     /// Extra HTTP headers to send with every request for this model.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
+    /// Azure OpenAI `api-version` query parameter (default: `2024-10-21`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
     /// Number of times to retry when the provider returns an empty response.
     /// Some providers (OpenRouter, Kimi) occasionally return empty content;
     /// setting this to e.g. 2 will silently retry up to that many times.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub retry_on_empty: Option<u32>,
+    /// Token budget for extended thinking (Anthropic) or reasoning
+    /// (OpenRouter). Unset disables it; Anthropic requires at least 1024.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
+    /// Token pricing used to compute per-request cost in the usage log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricingConfig>,
+    /// Sampling parameters sent with every request for this model.
+    #[serde(flatten)]
+    pub generation: GenerationParams,
+}
+
+/// Sampling parameters for a model request; unset fields keep the provider
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Maximum tokens in the reply (default: 4096, excluding any thinking budget).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    /// Stop sequences.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// Provider-specific fields deep-merged into the request body
+    /// (e.g. `{ top_k = 40 }` for Anthropic).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_params: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl GenerationParams {
+    /// True when no parameter is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These parameters with every field set in `overrides` replacing ours;
+    /// `extra_params` are merged key by key.
+    pub fn overlay(&self, overrides: &GenerationParams) -> GenerationParams {
+        let extra_params = match (&self.extra_params, &overrides.extra_params) {
+            (Some(base), Some(over)) => {
+                let mut merged = base.clone();
+                merged.extend(over.clone());
+                Some(merged)
+            }
+            (base, over) => over.clone().or_else(|| base.clone()),
+        };
+        GenerationParams {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            max_output_tokens: overrides.max_output_tokens.or(self.max_output_tokens),
+            stop: overrides.stop.clone().or_else(|| self.stop.clone()),
+            extra_params,
+        }
+    }
+}
+
+/// Model token pricing, in USD per million tokens.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ModelPricingConfig {
+    #[serde(default)]
+    pub input: f64,
+    #[serde(default)]
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+    #[serde(default)]
+    pub cache_write: f64,
 }
 
 /// OpenRouter-specific settings
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OpenRouterSettings {
     /// HTTP Referer header for OpenRouter rankings
     pub http_referer: Option<String>,
 
     /// App name for OpenRouter rankings
     pub app_name: Option<String>,
+
+    /// Hours between refreshes of the cached `/models` catalog (context
+    /// windows, pricing, modalities); 0 disables (default: 24).
+    #[serde(default = "default_openrouter_catalog_refresh_hours")]
+    pub catalog_refresh_hours: u64,
+}
+
+impl Default for OpenRouterSettings {
+    fn default() -> Self {
+        Self {
+            http_referer: None,
+            app_name: None,
+            catalog_refresh_hours: default_openrouter_catalog_refresh_hours(),
+        }
+    }
+}
+
+fn default_openrouter_catalog_refresh_hours() -> u64 {
+    24
 }
 
 /// Gateway server settings
@@ -316,14 +497,183 @@ pub struct GatewaySettings {
 
     /// WebSocket URL (computed from host/port if null)
     pub ws_url: Option<String>,
+
+    /// Open the koma DB read-only and serve only read requests (replica of
+    /// a primary gateway that owns writes and background jobs)
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Seconds to let in-flight chats and jobs finish after SIGTERM/SIGINT
+    /// before they are cancelled
+    #[serde(default = "default_shutdown_drain_secs")]
+    pub shutdown_drain_secs: u64,
+
+    /// Claim per-session leases in the koma DB before running heartbeats and
+    /// reflections, so several gateways can share one DB without doubling
+    /// background jobs
+    #[serde(default)]
+    pub coordinate_jobs: bool,
+
+    /// Seconds a job lease stays valid; a gateway that dies mid-job frees
+    /// its sessions after this long
+    #[serde(default = "default_job_lease_secs")]
+    pub job_lease_secs: u64,
 }
 
 /// Discord bot settings
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DiscordSettings {
     /// Whether Discord bot is enabled
     #[serde(default)]
     pub enabled: bool,
+    /// Run each guild-channel conversation in its own thread and session
+    /// (default: true). DMs always use the active session.
+    #[serde(default = "default_discord_threads")]
+    pub threads: bool,
+    /// Show `knowledge_search` calls as a results card with open buttons
+    /// when tool calls are shown (`/log verbose`) (default: true).
+    #[serde(default = "default_discord_search_embeds")]
+    pub search_embeds: bool,
+}
+
+impl Default for DiscordSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threads: default_discord_threads(),
+            search_embeds: default_discord_search_embeds(),
+        }
+    }
+}
+
+fn default_discord_threads() -> bool {
+    true
+}
+
+fn default_discord_search_embeds() -> bool {
+    true
+}
+
+/// Telegram bot settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelegramSettings {
+    /// Whether the Telegram bot is enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds each `getUpdates` long poll waits for new updates (default: 30).
+    #[serde(default = "default_telegram_poll_timeout_secs")]
+    pub poll_timeout_secs: u64,
+}
+
+impl Default for TelegramSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_timeout_secs: default_telegram_poll_timeout_secs(),
+        }
+    }
+}
+
+fn default_telegram_poll_timeout_secs() -> u64 {
+    30
+}
+
+/// Slack app settings
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlackSettings {
+    /// Whether the Slack app is enabled (Socket Mode)
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Email bridge settings (IMAP polling + SMTP sending)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmailSettings {
+    /// Whether the email bridge is enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// Mailbox address the ghosts send from and operators write to
+    #[serde(default)]
+    pub address: String,
+    /// IMAP/SMTP login (defaults to `address`)
+    pub username: Option<String>,
+    /// IMAP server host (implicit TLS)
+    #[serde(default)]
+    pub imap_host: String,
+    /// IMAP server port (default: 993)
+    #[serde(default = "default_email_imap_port")]
+    pub imap_port: u16,
+    /// Mailbox polled for new mail (default: INBOX)
+    #[serde(default = "default_email_mailbox")]
+    pub mailbox: String,
+    /// SMTP server host
+    #[serde(default)]
+    pub smtp_host: String,
+    /// SMTP server port (default: 465)
+    #[serde(default = "default_email_smtp_port")]
+    pub smtp_port: u16,
+    /// Upgrade a plain SMTP connection with STARTTLS instead of implicit TLS
+    #[serde(default)]
+    pub smtp_starttls: bool,
+    /// Seconds between IMAP polls (default: 60)
+    #[serde(default = "default_email_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Knowledge topic that receives mail attachments (skipped when unset)
+    pub ingest_topic: Option<String>,
+    /// Sender addresses accepted by the bridge; empty accepts anyone
+    #[serde(default)]
+    pub allowed_senders: Vec<String>,
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: String::new(),
+            username: None,
+            imap_host: String::new(),
+            imap_port: default_email_imap_port(),
+            mailbox: default_email_mailbox(),
+            smtp_host: String::new(),
+            smtp_port: default_email_smtp_port(),
+            smtp_starttls: false,
+            poll_interval_secs: default_email_poll_interval_secs(),
+            ingest_topic: None,
+            allowed_senders: Vec::new(),
+        }
+    }
+}
+
+impl EmailSettings {
+    /// Login used for both IMAP and SMTP.
+    pub fn login(&self) -> &str {
+        self.username.as_deref().unwrap_or(&self.address)
+    }
+
+    /// Whether `sender` may talk to the bridge (case-insensitive).
+    pub fn sender_allowed(&self, sender: &str) -> bool {
+        self.allowed_senders.is_empty()
+            || self
+                .allowed_senders
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(sender))
+    }
+}
+
+fn default_email_imap_port() -> u16 {
+    993
+}
+
+fn default_email_mailbox() -> String {
+    "INBOX".to_string()
+}
+
+fn default_email_smtp_port() -> u16 {
+    465
+}
+
+fn default_email_poll_interval_secs() -> u64 {
+    60
 }
 
 /// Logging settings
@@ -355,144 +705,1323 @@ pub struct ToolsSettings {
     /// Knowledge tools settings
     #[serde(default)]
     pub knowledge: KnowledgeToolsSettings,
-}
 
-/// Web tools configuration
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub struct WebToolsSettings {
-    /// Enable all web tools
+    /// Prompt-injection screening for web results and references
     #[serde(default)]
-    pub enabled: bool,
+    pub untrusted_content: UntrustedContentSettings,
 
-    /// Web search settings
+    /// Generic HTTP requests to operator-approved APIs
     #[serde(default)]
-    pub search: WebSearchSettings,
+    pub http_request: HttpRequestSettings,
 
-    /// Web fetch settings
+    /// Time budgets for tool calls
     #[serde(default)]
-    pub fetch: WebFetchSettings,
+    pub timeouts: ToolTimeoutSettings,
+
+    /// `run_shell_command` settings
+    #[serde(default)]
+    pub shell: ShellToolSettings,
+
+    /// Paging of large tool results
+    #[serde(default)]
+    pub output: ToolOutputSettings,
+
+    /// Tools served by operator HTTP endpoints, keyed by tool name
+    /// (`[tools.webhooks.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub webhooks: BTreeMap<String, WebhookToolSettings>,
 }
 
-/// Knowledge tools configuration
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub struct KnowledgeToolsSettings {
-    /// Embedding backend: "ollama" (default) or "openrouter".
-    pub embedding_provider: Option<String>,
+/// A tool defined in config and served by an HTTP endpoint. Calls `POST` the
+/// ghost's arguments as JSON to `url` and return the response body.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WebhookToolSettings {
+    /// Register this tool at startup (default: true)
+    #[serde(default = "default_webhook_enabled")]
+    pub enabled: bool,
 
-    /// Embedding provider base URL (auto-resolved for known providers)
-    pub embedding_url: Option<String>,
+    /// What the tool does, shown to the model
+    pub description: String,
 
-    /// Embedding model name
-    pub embedding_model: Option<String>,
+    /// Endpoint receiving the calls
+    pub url: String,
 
-    /// Embedding dimension (if known)
-    pub embedding_dim: Option<usize>,
+    /// JSON Schema of the arguments (default: any object)
+    #[serde(default = "default_webhook_input_schema")]
+    pub input_schema: serde_json::Value,
 
-    /// Embedding batch size
-    pub embedding_batch: Option<usize>,
+    /// Header carrying credentials, e.g. `Authorization`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
 
-    /// Reconciliation interval in seconds
-    pub reconcile_seconds: Option<u64>,
+    /// Environment variable holding the `auth_header` value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_value_env: Option<String>,
 
-    /// Optional override for knowledge index DB path
-    pub knowledge_db_path_override: Option<String>,
+    /// Environment variable holding a secret; requests are then signed with
+    /// HMAC-SHA256 (`X-T-Koma-Signature`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret_env: Option<String>,
 
-    /// Search defaults
+    /// Ask the operator before each call (default: true)
+    #[serde(default = "default_webhook_require_approval")]
+    pub require_approval: bool,
+
+    /// Request timeout in seconds (default: 30)
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_webhook_enabled() -> bool {
+    true
+}
+
+fn default_webhook_input_schema() -> serde_json::Value {
+    serde_json::json!({"type": "object", "properties": {}})
+}
+
+fn default_webhook_require_approval() -> bool {
+    true
+}
+
+fn default_webhook_timeout_seconds() -> u64 {
+    30
+}
+
+/// Paging of large tool results. Output longer than `page_chars` is cached
+/// by the gateway and handed to the ghost one page at a time; the ghost asks
+/// for the next page with `tool_output_continue`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ToolOutputSettings {
+    /// Max characters of tool output per page (0 disables paging)
+    #[serde(default = "default_tool_output_page_chars")]
+    pub page_chars: usize,
+
+    /// Paged outputs kept for continuation; the oldest are dropped first
+    #[serde(default = "default_tool_output_cached_outputs")]
+    pub cached_outputs: usize,
+}
+
+impl Default for ToolOutputSettings {
+    fn default() -> Self {
+        Self {
+            page_chars: default_tool_output_page_chars(),
+            cached_outputs: default_tool_output_cached_outputs(),
+        }
+    }
+}
+
+fn default_tool_output_page_chars() -> usize {
+    16_000
+}
+
+fn default_tool_output_cached_outputs() -> usize {
+    32
+}
+
+/// `run_shell_command` settings. Its run time is limited by
+/// `[tools.timeouts]`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ShellToolSettings {
+    /// Run commands in a pseudo-terminal, so programs behave as they do in a
+    /// terminal (line-buffered output, password and confirmation prompts)
+    #[serde(default = "default_shell_pty")]
+    pub pty: bool,
+
+    /// Max command output in bytes kept for the ghost; the middle of longer
+    /// output is dropped
+    #[serde(default = "default_shell_max_output_bytes")]
+    pub max_output_bytes: usize,
+
+    /// Broadcast output to the gateway log while a command runs
     #[serde(default)]
-    pub search: KnowledgeSearchSettings,
+    pub stream_progress: bool,
 }
 
-/// Knowledge search defaults
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub struct KnowledgeSearchSettings {
-    pub rrf_k: Option<usize>,
-    pub max_results: Option<usize>,
-    pub graph_depth: Option<u8>,
-    pub graph_max: Option<usize>,
-    pub bm25_limit: Option<usize>,
-    pub dense_limit: Option<usize>,
-    /// Boost multiplier for documentation files in reference search.
-    pub doc_boost: Option<f32>,
+impl Default for ShellToolSettings {
+    fn default() -> Self {
+        Self {
+            pty: default_shell_pty(),
+            max_output_bytes: default_shell_max_output_bytes(),
+            stream_progress: false,
+        }
+    }
 }
 
-/// Context compaction settings
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct CompactionSettings {
-    /// Fraction of context window at which compaction triggers (default: 0.85).
-    #[serde(default = "default_compaction_threshold")]
-    pub threshold: f32,
-    /// Number of recent messages kept verbatim during compaction (default: 20).
-    #[serde(default = "default_compaction_keep_window")]
-    pub keep_window: usize,
-    /// Characters retained from masked tool results (default: 100).
-    #[serde(default = "default_compaction_mask_preview_chars")]
-    pub mask_preview_chars: usize,
+fn default_shell_pty() -> bool {
+    true
 }
 
-impl Default for CompactionSettings {
+fn default_shell_max_output_bytes() -> usize {
+    64 * 1024
+}
+
+/// Per-tool time budgets. A tool still running when its budget runs out is
+/// stopped and returns whatever output it produced so far.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ToolTimeoutSettings {
+    /// Budget in seconds for tools without an entry in `per_tool`
+    /// (0 disables the timeout)
+    #[serde(default = "default_tool_timeout_seconds")]
+    pub default_seconds: u64,
+
+    /// Budgets in seconds keyed by tool name (0 disables the timeout)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub per_tool: BTreeMap<String, u64>,
+}
+
+impl ToolTimeoutSettings {
+    /// Budget for `tool`; `None` when it may run without limit.
+    pub fn budget_for(&self, tool: &str) -> Option<Duration> {
+        let seconds = self
+            .per_tool
+            .get(tool)
+            .copied()
+            .unwrap_or(self.default_seconds);
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+}
+
+impl Default for ToolTimeoutSettings {
     fn default() -> Self {
         Self {
-            threshold: default_compaction_threshold(),
-            keep_window: default_compaction_keep_window(),
-            mask_preview_chars: default_compaction_mask_preview_chars(),
+            default_seconds: default_tool_timeout_seconds(),
+            per_tool: BTreeMap::new(),
         }
     }
 }
 
-/// Heartbeat timing configuration
+fn default_tool_timeout_seconds() -> u64 {
+    120
+}
+
+/// `http_request` tool settings (calls to user APIs such as home automation
+/// or internal services)
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct HeartbeatTimingSettings {
-    /// Minutes of session inactivity before a heartbeat triggers (default: 4).
-    #[serde(default = "default_heartbeat_idle_minutes")]
-    pub idle_minutes: u64,
-    /// Seconds between heartbeat polling checks (default: 60).
-    #[serde(default = "default_heartbeat_check_seconds")]
-    pub check_seconds: u64,
-    /// Minutes to reschedule after a HEARTBEAT_CONTINUE response (default: 30).
-    #[serde(default = "default_heartbeat_continue_minutes")]
-    pub continue_minutes: u64,
+pub struct HttpRequestSettings {
+    /// Enable the http_request tool
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Hosts the tool may call, subdomains included. Unlike the browser
+    /// allowlist, an empty list allows nothing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_domains: Vec<String>,
+
+    /// Max request body size in bytes
+    #[serde(default = "default_http_request_max_request_bytes")]
+    pub max_request_bytes: usize,
+
+    /// Max response body size in bytes; longer bodies are truncated
+    #[serde(default = "default_http_request_max_response_bytes")]
+    pub max_response_bytes: usize,
+
+    /// Request timeout in seconds
+    #[serde(default = "default_web_fetch_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl HttpRequestSettings {
+    /// Whether `host` is covered by the allowlist.
+    pub fn allows_host(&self, host: &str) -> bool {
+        host_in_domains(&self.allowed_domains, host)
+    }
+}
+
+impl Default for HttpRequestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_domains: Vec::new(),
+            max_request_bytes: default_http_request_max_request_bytes(),
+            max_response_bytes: default_http_request_max_response_bytes(),
+            timeout_seconds: default_web_fetch_timeout_seconds(),
+        }
+    }
+}
+
+fn default_http_request_max_request_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_http_request_max_response_bytes() -> usize {
+    256 * 1024
+}
+
+/// Screening of external content (web fetch/search results, reference files)
+/// before it reaches the model
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UntrustedContentSettings {
+    /// Scan and wrap external content in delimited blocks (default: true).
+    #[serde(default = "default_untrusted_content_enabled")]
+    pub enabled: bool,
+    /// What to do with instruction-like passages (default: flag).
+    #[serde(default)]
+    pub action: InjectionAction,
+    /// Extra case-insensitive regexes treated as injection attempts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+impl Default for UntrustedContentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_untrusted_content_enabled(),
+            action: InjectionAction::default(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+fn default_untrusted_content_enabled() -> bool {
+    true
+}
+
+/// Handling of detected prompt-injection passages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectionAction {
+    /// Keep the passage, marked as a suspected injection
+    #[default]
+    Flag,
+    /// Replace the passage with a placeholder
+    Strip,
+}
+
+/// Web tools configuration
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WebToolsSettings {
+    /// Enable all web tools
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Web search settings
+    #[serde(default)]
+    pub search: WebSearchSettings,
+
+    /// Web fetch settings
+    #[serde(default)]
+    pub fetch: WebFetchSettings,
+
+    /// Persistent search/fetch cache limits
+    #[serde(default)]
+    pub cache: WebCacheSettings,
+
+    /// Headless browser settings
+    #[serde(default)]
+    pub browser: BrowserSettings,
+
+    /// Domain allow/deny policy for all web tools
+    #[serde(default)]
+    pub domains: WebDomainSettings,
+}
+
+/// Domains web search results, `web_fetch` and the browser may reach. Deny
+/// entries win over allow entries; an empty allow list allows every domain
+/// that is not denied.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebDomainSettings {
+    /// Allowed domains, subdomains included (empty = any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Blocked domains, subdomains included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Ask the operator to approve a blocked fetch or navigation instead of
+    /// refusing it (default: true)
+    #[serde(default = "default_web_domains_approve_blocked")]
+    pub approve_blocked: bool,
+    /// Per-ghost overrides keyed by ghost name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub ghosts: HashMap<String, WebDomainOverride>,
+}
+
+impl Default for WebDomainSettings {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            approve_blocked: default_web_domains_approve_blocked(),
+            ghosts: HashMap::new(),
+        }
+    }
+}
+
+/// Per-ghost domain policy overrides; unset fields inherit the global value
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WebDomainOverride {
+    /// Replaces the global allow list.
+    pub allow: Option<Vec<String>>,
+    /// Added to the global deny list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    pub approve_blocked: Option<bool>,
+}
+
+/// Domain policy in effect for one ghost
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebDomainPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub approve_blocked: bool,
+    /// Hosts the operator approved for this call, allowed despite the lists.
+    pub approved: Vec<String>,
+}
+
+impl WebDomainSettings {
+    /// Effective policy for `ghost_name`, with its override applied.
+    pub fn for_ghost(&self, ghost_name: &str) -> WebDomainPolicy {
+        let ghost_override = self.ghosts.get(ghost_name);
+        let mut deny = self.deny.clone();
+        if let Some(o) = ghost_override {
+            deny.extend(o.deny.iter().cloned());
+        }
+        WebDomainPolicy {
+            allow: ghost_override
+                .and_then(|o| o.allow.clone())
+                .unwrap_or_else(|| self.allow.clone()),
+            deny,
+            approve_blocked: ghost_override
+                .and_then(|o| o.approve_blocked)
+                .unwrap_or(self.approve_blocked),
+            approved: Vec::new(),
+        }
+    }
+}
+
+impl WebDomainPolicy {
+    /// Whether `host` may be reached.
+    pub fn allows_host(&self, host: &str) -> bool {
+        if host_in_domains(&self.approved, host) {
+            return true;
+        }
+        !host_in_domains(&self.deny, host)
+            && (self.allow.is_empty() || host_in_domains(&self.allow, host))
+    }
+
+    /// Let `host` through once the operator approved it.
+    pub fn approve_host(&mut self, host: &str) {
+        self.approved.push(host.to_string());
+    }
+}
+
+/// Knowledge tools configuration
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KnowledgeToolsSettings {
+    /// Embedding backend: "ollama" (default), "openrouter" or "openai"
+    /// (any OpenAI-compatible `/embeddings` API).
+    pub embedding_provider: Option<String>,
+
+    /// Embedding provider base URL (auto-resolved for known providers)
+    pub embedding_url: Option<String>,
+
+    /// Env var holding the embedding API key (default: `OPENROUTER_API_KEY`
+    /// for openrouter, `OPENAI_API_KEY` for openai)
+    pub embedding_api_key_env: Option<String>,
+
+    /// Embedding model name
+    pub embedding_model: Option<String>,
+
+    /// Embedding dimension (if known)
+    pub embedding_dim: Option<usize>,
+
+    /// Embedding batch size
+    pub embedding_batch: Option<usize>,
+
+    /// Reconciliation interval in seconds
+    pub reconcile_seconds: Option<u64>,
+
+    /// How to re-embed after an embedding model change: "full" (default) or "gradual".
+    pub reembed_mode: Option<String>,
+
+    /// Optional override for knowledge index DB path
+    pub knowledge_db_path_override: Option<String>,
+
+    /// Search defaults
+    #[serde(default)]
+    pub search: KnowledgeSearchSettings,
+
+    /// Per-language overrides keyed by ISO 639-1 code (e.g. "ja", "fr").
+    #[serde(default)]
+    pub languages: BTreeMap<String, KnowledgeLanguageSettings>,
+}
+
+/// Per-language knowledge indexing overrides
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KnowledgeLanguageSettings {
+    /// Embedding model for chunks in this language (same provider and dimension).
+    pub embedding_model: Option<String>,
+    /// BM25 tokenization: "words" or "bigram" (for scripts without word spacing).
+    pub bm25_tokenizer: Option<String>,
+}
+
+/// Knowledge search defaults
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KnowledgeSearchSettings {
+    pub rrf_k: Option<usize>,
+    pub max_results: Option<usize>,
+    pub graph_depth: Option<u8>,
+    pub graph_max: Option<usize>,
+    pub bm25_limit: Option<usize>,
+    pub dense_limit: Option<usize>,
+    /// Boost multiplier for documentation files in reference search.
+    pub doc_boost: Option<f32>,
+    /// Seconds a cached `knowledge_search` result stays valid (0 disables caching).
+    pub cache_ttl_seconds: Option<u64>,
+    /// Maximum number of cached `knowledge_search` results.
+    pub cache_max_entries: Option<usize>,
+}
+
+/// Context compaction settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompactionSettings {
+    /// Fraction of context window at which compaction triggers (default: 0.85).
+    #[serde(default = "default_compaction_threshold")]
+    pub threshold: f32,
+    /// Number of recent messages kept verbatim during compaction (default: 20).
+    #[serde(default = "default_compaction_keep_window")]
+    pub keep_window: usize,
+    /// Characters retained from masked tool results (default: 100).
+    #[serde(default = "default_compaction_mask_preview_chars")]
+    pub mask_preview_chars: usize,
+}
+
+impl Default for CompactionSettings {
+    fn default() -> Self {
+        Self {
+            threshold: default_compaction_threshold(),
+            keep_window: default_compaction_keep_window(),
+            mask_preview_chars: default_compaction_mask_preview_chars(),
+        }
+    }
+}
+
+/// Heartbeat timing configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HeartbeatTimingSettings {
+    /// Minutes of session inactivity before a heartbeat triggers (default: 4).
+    #[serde(default = "default_heartbeat_idle_minutes")]
+    pub idle_minutes: u64,
+    /// Seconds between heartbeat polling checks (default: 60).
+    #[serde(default = "default_heartbeat_check_seconds")]
+    pub check_seconds: u64,
+    /// Minutes to reschedule after a HEARTBEAT_CONTINUE response (default: 30).
+    #[serde(default = "default_heartbeat_continue_minutes")]
+    pub continue_minutes: u64,
+    /// Cron expression for the run after a HEARTBEAT_CONTINUE response, used
+    /// instead of `continue_minutes` (e.g. `"TZ=+01:00 0 9 * * *"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continue_schedule: Option<String>,
+    /// Sampling overrides applied on top of the model's parameters for heartbeats.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+    /// Per-session adjustment of `idle_minutes` from recent activity and
+    /// heartbeat outcomes.
+    #[serde(default)]
+    pub adaptive: HeartbeatAdaptiveSettings,
+}
+
+impl Default for HeartbeatTimingSettings {
+    fn default() -> Self {
+        Self {
+            idle_minutes: default_heartbeat_idle_minutes(),
+            check_seconds: default_heartbeat_check_seconds(),
+            continue_minutes: default_heartbeat_continue_minutes(),
+            continue_schedule: None,
+            generation: GenerationParams::default(),
+            adaptive: HeartbeatAdaptiveSettings::default(),
+        }
+    }
+}
+
+/// Adaptive heartbeat cadence (`[heartbeat_timing.adaptive]`).
+///
+/// Each consecutive no-op heartbeat (HEARTBEAT_OK) of a session multiplies
+/// its idle delay by `backoff_factor`; a session whose last heartbeat did
+/// work, or with at least `active_messages` messages in the last
+/// `active_window_minutes`, has it divided by `backoff_factor` instead.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HeartbeatAdaptiveSettings {
+    /// Adapt the idle delay per session (default: false).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Shortest idle delay in minutes (default: 2).
+    #[serde(default = "default_heartbeat_adaptive_min_idle_minutes")]
+    pub min_idle_minutes: u64,
+    /// Longest idle delay in minutes (default: 240).
+    #[serde(default = "default_heartbeat_adaptive_max_idle_minutes")]
+    pub max_idle_minutes: u64,
+    /// Factor applied per no-op heartbeat, and once for active sessions
+    /// (default: 2.0, must be at least 1).
+    #[serde(default = "default_heartbeat_adaptive_backoff_factor")]
+    pub backoff_factor: f64,
+    /// Most recent heartbeats of the session that are considered (default: 5).
+    #[serde(default = "default_heartbeat_adaptive_history")]
+    pub history: u32,
+    /// Messages within `active_window_minutes` that make a session active
+    /// (default: 10).
+    #[serde(default = "default_heartbeat_adaptive_active_messages")]
+    pub active_messages: u64,
+    /// Window over which recent messages are counted (default: 60).
+    #[serde(default = "default_heartbeat_adaptive_active_window_minutes")]
+    pub active_window_minutes: u64,
+}
+
+impl Default for HeartbeatAdaptiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_idle_minutes: default_heartbeat_adaptive_min_idle_minutes(),
+            max_idle_minutes: default_heartbeat_adaptive_max_idle_minutes(),
+            backoff_factor: default_heartbeat_adaptive_backoff_factor(),
+            history: default_heartbeat_adaptive_history(),
+            active_messages: default_heartbeat_adaptive_active_messages(),
+            active_window_minutes: default_heartbeat_adaptive_active_window_minutes(),
+        }
+    }
+}
+
+fn default_heartbeat_adaptive_min_idle_minutes() -> u64 {
+    2
+}
+
+fn default_heartbeat_adaptive_max_idle_minutes() -> u64 {
+    240
+}
+
+fn default_heartbeat_adaptive_backoff_factor() -> f64 {
+    2.0
+}
+
+fn default_heartbeat_adaptive_history() -> u32 {
+    5
+}
+
+fn default_heartbeat_adaptive_active_messages() -> u64 {
+    10
+}
+
+fn default_heartbeat_adaptive_active_window_minutes() -> u64 {
+    60
+}
+
+fn default_heartbeat_idle_minutes() -> u64 {
+    4
+}
+
+fn default_heartbeat_check_seconds() -> u64 {
+    60
+}
+
+fn default_heartbeat_continue_minutes() -> u64 {
+    30
+}
+
+/// Reflection timing configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReflectionTimingSettings {
+    /// Minutes of session inactivity before reflection triggers (default: 4).
+    #[serde(default = "default_reflection_idle_minutes")]
+    pub idle_minutes: u64,
+    /// Sampling overrides applied on top of the model's parameters for
+    /// reflection (which usually wants a low temperature).
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+    /// Validation of the notes and diary entries a reflection run wrote.
+    #[serde(default)]
+    pub quality: ReflectionQualitySettings,
+}
+
+impl Default for ReflectionTimingSettings {
+    fn default() -> Self {
+        Self {
+            idle_minutes: default_reflection_idle_minutes(),
+            generation: GenerationParams::default(),
+            quality: ReflectionQualitySettings::default(),
+        }
+    }
+}
+
+fn default_reflection_idle_minutes() -> u64 {
+    4
+}
+
+/// Reflection output quality gate (`[reflection.quality]`).
+///
+/// Notes and diary entries written by a reflection run are checked against
+/// the expected structure and scored by `model`; a run below `min_score` is
+/// undone and retried once, then logged as degraded.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReflectionQualitySettings {
+    /// Gate reflection output (default: false).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Model alias scoring the output; structure checks only when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Lowest accepted score, 0-10 (default: 6).
+    #[serde(default = "default_reflection_quality_min_score")]
+    pub min_score: u8,
+}
+
+impl Default for ReflectionQualitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            min_score: default_reflection_quality_min_score(),
+        }
+    }
+}
+
+fn default_reflection_quality_min_score() -> u8 {
+    6
+}
+
+/// Job log retention configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobLogRetentionSettings {
+    /// Delete job logs older than this many days; 0 keeps them forever (default: 30).
+    #[serde(default = "default_job_log_max_age_days")]
+    pub max_age_days: u32,
+    /// Keep at most this many job logs per ghost; 0 means no limit (default: 1000).
+    #[serde(default = "default_job_log_max_per_ghost")]
+    pub max_per_ghost: u32,
+    /// Minutes between prune runs (default: 60).
+    #[serde(default = "default_job_log_prune_interval_minutes")]
+    pub prune_interval_minutes: u64,
+}
+
+impl Default for JobLogRetentionSettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: default_job_log_max_age_days(),
+            max_per_ghost: default_job_log_max_per_ghost(),
+            prune_interval_minutes: default_job_log_prune_interval_minutes(),
+        }
+    }
+}
+
+fn default_job_log_max_age_days() -> u32 {
+    30
+}
+
+fn default_job_log_max_per_ghost() -> u32 {
+    1000
+}
+
+fn default_job_log_prune_interval_minutes() -> u64 {
+    60
+}
+
+/// Background job queue configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobQueueSettings {
+    /// Jobs run at the same time; takes effect on restart (default: 2).
+    #[serde(default = "default_job_queue_workers")]
+    pub workers: usize,
+    /// Attempts before a failing job is moved to the dead letters (default: 3).
+    #[serde(default = "default_job_queue_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each later one (default: 30).
+    #[serde(default = "default_job_queue_backoff_base_seconds")]
+    pub backoff_base_seconds: u64,
+    /// Longest delay between retries (default: 3600).
+    #[serde(default = "default_job_queue_backoff_max_seconds")]
+    pub backoff_max_seconds: u64,
+    /// How long a worker holds a job before another gateway may take it
+    /// over (default: 3600).
+    #[serde(default = "default_job_queue_lease_seconds")]
+    pub lease_seconds: u64,
+}
+
+impl Default for JobQueueSettings {
+    fn default() -> Self {
+        Self {
+            workers: default_job_queue_workers(),
+            max_attempts: default_job_queue_max_attempts(),
+            backoff_base_seconds: default_job_queue_backoff_base_seconds(),
+            backoff_max_seconds: default_job_queue_backoff_max_seconds(),
+            lease_seconds: default_job_queue_lease_seconds(),
+        }
+    }
+}
+
+fn default_job_queue_workers() -> usize {
+    2
+}
+
+fn default_job_queue_max_attempts() -> u32 {
+    3
+}
+
+fn default_job_queue_backoff_base_seconds() -> u64 {
+    30
+}
+
+fn default_job_queue_backoff_max_seconds() -> u64 {
+    3600
+}
+
+fn default_job_queue_lease_seconds() -> u64 {
+    3600
+}
+
+/// Prompt cache eviction configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PromptCacheSettings {
+    /// Evict entries unused for this many minutes; 0 disables (default: 60).
+    #[serde(default = "default_prompt_cache_max_idle_minutes")]
+    pub max_idle_minutes: u32,
+    /// Keep at most this many entries, least recently used first out; 0 means
+    /// no limit (default: 500).
+    #[serde(default = "default_prompt_cache_max_entries")]
+    pub max_entries: u32,
+    /// Keep at most this many KiB of cached prompts; 0 means no limit (default: 0).
+    #[serde(default)]
+    pub max_total_kib: u32,
+    /// Minutes between eviction runs (default: 15).
+    #[serde(default = "default_prompt_cache_evict_interval_minutes")]
+    pub evict_interval_minutes: u64,
+}
+
+impl Default for PromptCacheSettings {
+    fn default() -> Self {
+        Self {
+            max_idle_minutes: default_prompt_cache_max_idle_minutes(),
+            max_entries: default_prompt_cache_max_entries(),
+            max_total_kib: 0,
+            evict_interval_minutes: default_prompt_cache_evict_interval_minutes(),
+        }
+    }
+}
+
+fn default_prompt_cache_max_idle_minutes() -> u32 {
+    60
+}
+
+fn default_prompt_cache_max_entries() -> u32 {
+    500
+}
+
+fn default_prompt_cache_evict_interval_minutes() -> u64 {
+    15
+}
+
+/// Session archiving configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionArchiveSettings {
+    /// Archive inactive sessions not updated for this many days; 0 disables
+    /// (default: 90).
+    #[serde(default = "default_session_archive_max_idle_days")]
+    pub max_idle_days: u32,
+    /// Sessions archived per run (default: 20).
+    #[serde(default = "default_session_archive_batch_size")]
+    pub batch_size: u32,
+    /// Minutes between archive runs (default: 360).
+    #[serde(default = "default_session_archive_interval_minutes")]
+    pub interval_minutes: u64,
+}
+
+impl Default for SessionArchiveSettings {
+    fn default() -> Self {
+        Self {
+            max_idle_days: default_session_archive_max_idle_days(),
+            batch_size: default_session_archive_batch_size(),
+            interval_minutes: default_session_archive_interval_minutes(),
+        }
+    }
+}
+
+fn default_session_archive_max_idle_days() -> u32 {
+    90
+}
+
+fn default_session_archive_batch_size() -> u32 {
+    20
+}
+
+fn default_session_archive_interval_minutes() -> u64 {
+    360
+}
+
+/// Per-client limits on the HTTP/WS server, counted per peer IP and, when a
+/// request sends an API token, per token as well. Independent of operator
+/// chat rate limits.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ClientLimitSettings {
+    /// Apply the limits (default: true).
+    #[serde(default = "default_client_limits_enabled")]
+    pub enabled: bool,
+    /// Skip the limits for loopback peers (default: false). A reverse proxy
+    /// on the same host connects from loopback, so this also exempts every
+    /// client behind it.
+    #[serde(default = "default_client_limits_exempt_loopback")]
+    pub exempt_loopback: bool,
+    /// HTTP requests, WebSocket upgrades included, per client per minute;
+    /// 0 means no limit (default: 120).
+    #[serde(default = "default_client_requests_per_minute")]
+    pub requests_per_minute: u32,
+    /// Open WebSocket connections per client; 0 means no limit (default: 8).
+    #[serde(default = "default_client_max_connections")]
+    pub max_connections: u32,
+    /// Messages per WebSocket client per minute, across its connections;
+    /// 0 means no limit (default: 60).
+    #[serde(default = "default_client_ws_messages_per_minute")]
+    pub ws_messages_per_minute: u32,
+}
+
+impl Default for ClientLimitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_client_limits_enabled(),
+            exempt_loopback: default_client_limits_exempt_loopback(),
+            requests_per_minute: default_client_requests_per_minute(),
+            max_connections: default_client_max_connections(),
+            ws_messages_per_minute: default_client_ws_messages_per_minute(),
+        }
+    }
+}
+
+fn default_client_limits_enabled() -> bool {
+    true
+}
+
+fn default_client_limits_exempt_loopback() -> bool {
+    false
+}
+
+fn default_client_requests_per_minute() -> u32 {
+    120
+}
+
+fn default_client_max_connections() -> u32 {
+    8
+}
+
+fn default_client_ws_messages_per_minute() -> u32 {
+    60
+}
+
+/// Retry settings for transient provider errors (429, 500-503, connection
+/// failures)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderRetrySettings {
+    /// Attempts per request, including the first; 1 disables retries
+    /// (default: 3).
+    #[serde(default = "default_provider_retry_max_attempts")]
+    pub max_attempts: u32,
+    /// Backoff before the first retry in milliseconds, doubled on each
+    /// further retry and jittered (default: 500).
+    #[serde(default = "default_provider_retry_backoff_base_ms")]
+    pub backoff_base_ms: u64,
+    /// Longest wait between attempts in milliseconds (default: 30000). A
+    /// longer `Retry-After` fails the request instead, so the model fallback
+    /// chain can take over.
+    #[serde(default = "default_provider_retry_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// Per-provider overrides keyed by provider type (e.g. `openrouter`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub providers: HashMap<String, ProviderRetryOverride>,
+}
+
+/// Per-provider override of [`ProviderRetrySettings`]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ProviderRetryOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_base_ms: Option<u64>,
+}
+
+impl Default for ProviderRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_provider_retry_max_attempts(),
+            backoff_base_ms: default_provider_retry_backoff_base_ms(),
+            max_backoff_ms: default_provider_retry_max_backoff_ms(),
+            providers: HashMap::new(),
+        }
+    }
+}
+
+impl ProviderRetrySettings {
+    /// Effective `(max_attempts, backoff_base_ms)` for a provider type.
+    pub fn for_provider(&self, provider: &str) -> (u32, u64) {
+        let provider_override = self.providers.get(provider);
+        (
+            provider_override
+                .and_then(|o| o.max_attempts)
+                .unwrap_or(self.max_attempts),
+            provider_override
+                .and_then(|o| o.backoff_base_ms)
+                .unwrap_or(self.backoff_base_ms),
+        )
+    }
+}
+
+fn default_provider_retry_max_attempts() -> u32 {
+    3
+}
+
+fn default_provider_retry_backoff_base_ms() -> u64 {
+    500
+}
+
+fn default_provider_retry_max_backoff_ms() -> u64 {
+    30_000
+}
+
+/// Timeouts for provider HTTP requests
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderTimeoutSettings {
+    /// Seconds one provider request (one attempt) may take before it is
+    /// aborted (default: 120).
+    #[serde(default = "default_provider_request_timeout_seconds")]
+    pub request_seconds: u64,
+    /// Per-provider `request_seconds` overrides keyed by provider type
+    /// (e.g. `gemini = 300`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub providers: HashMap<String, u64>,
+}
+
+impl Default for ProviderTimeoutSettings {
+    fn default() -> Self {
+        Self {
+            request_seconds: default_provider_request_timeout_seconds(),
+            providers: HashMap::new(),
+        }
+    }
+}
+
+impl ProviderTimeoutSettings {
+    /// Effective request timeout in seconds for a provider type.
+    pub fn for_provider(&self, provider: &str) -> u64 {
+        self.providers
+            .get(provider)
+            .copied()
+            .unwrap_or(self.request_seconds)
+    }
+}
+
+fn default_provider_request_timeout_seconds() -> u64 {
+    120
+}
+
+/// Filters run on every GHOST reply before it is stored and delivered
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OutputFilterSettings {
+    /// Redact API keys, tokens and private keys (default: false).
+    #[serde(default)]
+    pub redact_secrets: bool,
+    /// Redact email addresses, phone numbers and card numbers (default: false).
+    #[serde(default)]
+    pub redact_pii: bool,
+    /// Extra regex redactions, applied after the built-in ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<RedactionPattern>,
+    /// Model alias asked to review each reply; flagged replies are withheld.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation_model: Option<String>,
+    /// Per-ghost overrides keyed by ghost name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub ghosts: HashMap<String, OutputFilterOverride>,
+}
+
+/// A regex whose matches are replaced in GHOST replies
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RedactionPattern {
+    pub pattern: String,
+    #[serde(default = "default_redaction_replacement")]
+    pub replacement: String,
+}
+
+/// Per-ghost output filter overrides; unset fields inherit the global value
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OutputFilterOverride {
+    pub redact_secrets: Option<bool>,
+    pub redact_pii: Option<bool>,
+    /// Added to the global patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<RedactionPattern>,
+    /// Moderation model alias for this ghost; `""` turns moderation off.
+    pub moderation_model: Option<String>,
+}
+
+/// Output filters in effect for one ghost
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputFilterPolicy {
+    pub redact_secrets: bool,
+    pub redact_pii: bool,
+    pub patterns: Vec<RedactionPattern>,
+    pub moderation_model: Option<String>,
+}
+
+impl OutputFilterSettings {
+    /// Policy for ghosts without an override.
+    pub fn global(&self) -> OutputFilterPolicy {
+        self.resolve(None)
+    }
+
+    /// Effective policy for `ghost_name`, with its override applied.
+    pub fn for_ghost(&self, ghost_name: &str) -> OutputFilterPolicy {
+        self.resolve(self.ghosts.get(ghost_name))
+    }
+
+    fn resolve(&self, ghost_override: Option<&OutputFilterOverride>) -> OutputFilterPolicy {
+        let mut patterns = self.patterns.clone();
+        if let Some(o) = ghost_override {
+            patterns.extend(o.patterns.iter().cloned());
+        }
+        OutputFilterPolicy {
+            redact_secrets: ghost_override
+                .and_then(|o| o.redact_secrets)
+                .unwrap_or(self.redact_secrets),
+            redact_pii: ghost_override
+                .and_then(|o| o.redact_pii)
+                .unwrap_or(self.redact_pii),
+            patterns,
+            moderation_model: ghost_override
+                .and_then(|o| o.moderation_model.clone())
+                .or_else(|| self.moderation_model.clone())
+                .filter(|alias| !alias.trim().is_empty()),
+        }
+    }
+
+    /// Every moderation model alias referenced, globally or per ghost.
+    pub fn moderation_models(&self) -> impl Iterator<Item = &str> {
+        self.moderation_model
+            .iter()
+            .chain(
+                self.ghosts
+                    .values()
+                    .filter_map(|o| o.moderation_model.as_ref()),
+            )
+            .map(String::as_str)
+            .filter(|alias| !alias.trim().is_empty())
+    }
+}
+
+fn default_redaction_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+/// Audio transcription through a Whisper-compatible
+/// `POST {base_url}/audio/transcriptions` endpoint
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TranscriptionSettings {
+    /// Transcribe audio attachments and voice messages (default: false).
+    #[serde(default)]
+    pub enabled: bool,
+    /// API base URL (default: `https://api.openai.com/v1`).
+    #[serde(default = "default_transcription_base_url")]
+    pub base_url: String,
+    /// Transcription model (default: `whisper-1`).
+    #[serde(default = "default_transcription_model")]
+    pub model: String,
+    /// Environment variable holding the API key (default: `OPENAI_API_KEY`).
+    /// Requests are sent without auth when it is unset, for local servers.
+    #[serde(default = "default_transcription_api_key_env")]
+    pub api_key_env: String,
+    /// ISO-639-1 language hint; auto-detected when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Request timeout in seconds (default: 120).
+    #[serde(default = "default_transcription_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_url: default_transcription_base_url(),
+            model: default_transcription_model(),
+            api_key_env: default_transcription_api_key_env(),
+            language: None,
+            timeout_seconds: default_transcription_timeout_seconds(),
+        }
+    }
+}
+
+/// Model Context Protocol client settings
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct McpSettings {
+    /// MCP servers keyed by name (`[mcp.servers.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub servers: BTreeMap<String, McpServerSettings>,
+}
+
+/// One MCP server, launched as a child process speaking JSON-RPC over stdio
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpServerSettings {
+    /// Connect to this server at startup (default: true)
+    #[serde(default = "default_mcp_enabled")]
+    pub enabled: bool,
+
+    /// Executable to launch
+    pub command: String,
+
+    /// Command-line arguments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Extra environment variables for the server process
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Ask the operator before each tool call (default: true)
+    #[serde(default = "default_mcp_require_approval")]
+    pub require_approval: bool,
+
+    /// Tools (server-side names) that never need approval
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_approve: Vec<String>,
+
+    /// Startup and per-call timeout in seconds (default: 60)
+    #[serde(default = "default_mcp_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl McpServerSettings {
+    /// Whether calling `tool` needs operator approval.
+    pub fn needs_approval(&self, tool: &str) -> bool {
+        self.require_approval && !self.auto_approve.iter().any(|name| name == tool)
+    }
+}
+
+fn default_mcp_enabled() -> bool {
+    true
+}
+
+fn default_mcp_require_approval() -> bool {
+    true
+}
+
+fn default_mcp_timeout_seconds() -> u64 {
+    60
+}
+
+/// Terminal UI settings (`[cli]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CliSettings {
+    /// TUI colour theme: `cyberpunk` (default), `light`, `high_contrast` or
+    /// a name from `themes`
+    #[serde(default = "default_cli_theme")]
+    pub theme: String,
+    /// Custom palettes keyed by name (`[cli.themes.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, ThemeSettings>,
+    /// TUI key bindings (`[cli.keys]`)
+    #[serde(default)]
+    pub keys: KeySettings,
+}
+
+impl Default for CliSettings {
+    fn default() -> Self {
+        Self {
+            theme: default_cli_theme(),
+            themes: BTreeMap::new(),
+            keys: KeySettings::default(),
+        }
+    }
+}
+
+fn default_cli_theme() -> String {
+    "cyberpunk".to_string()
+}
+
+/// A custom TUI palette: a built-in theme with some colour slots replaced,
+/// e.g. `base = "light"`, `accent = "#005f87"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ThemeSettings {
+    /// Built-in theme the palette starts from (default: `cyberpunk`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Animate focused borders (default: the base theme's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glow: Option<bool>,
+    /// Colour overrides keyed by slot name (`accent`, `muted`, ...)
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Built-in TUI key binding sets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPreset {
+    /// Arrows and hjkl, `u`/`d` paging, `Esc` quits from the top level
+    #[default]
+    Default,
+    /// `Ctrl+u`/`Ctrl+d` paging, `:` command palette, `Esc` never quits
+    Vim,
+}
+
+/// TUI key bindings: a preset plus per-action overrides, e.g.
+/// `quit = "ctrl+q"` or `down = ["j", "down"]`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KeySettings {
+    /// Base binding set (default: `default`)
+    #[serde(default)]
+    pub preset: KeyPreset,
+    /// Overrides keyed by action name; each replaces the preset's keys
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, KeyBinding>,
 }
 
-impl Default for HeartbeatTimingSettings {
-    fn default() -> Self {
-        Self {
-            idle_minutes: default_heartbeat_idle_minutes(),
-            check_seconds: default_heartbeat_check_seconds(),
-            continue_minutes: default_heartbeat_continue_minutes(),
+/// One key (`"ctrl+d"`) or several (`["j", "down"]`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    /// The bound key names.
+    pub fn keys(&self) -> &[String] {
+        match self {
+            Self::One(key) => std::slice::from_ref(key),
+            Self::Many(keys) => keys,
         }
     }
 }
 
-fn default_heartbeat_idle_minutes() -> u64 {
-    4
+/// How a model's thinking is shown to operators
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThinkingDisplay {
+    /// Stored in the session only
+    #[default]
+    Hidden,
+    /// First lines quoted above the reply
+    Summary,
+    /// Whole thinking quoted above the reply
+    Full,
 }
 
-fn default_heartbeat_check_seconds() -> u64 {
-    60
+/// Extended thinking output. Budgets are set per model (`thinking_budget`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ThinkingSettings {
+    /// What chat replies include of the model's thinking (default: hidden).
+    #[serde(default)]
+    pub display: ThinkingDisplay,
 }
 
-fn default_heartbeat_continue_minutes() -> u64 {
-    30
+fn default_transcription_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
 }
 
-/// Reflection timing configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ReflectionTimingSettings {
-    /// Minutes of session inactivity before reflection triggers (default: 4).
-    #[serde(default = "default_reflection_idle_minutes")]
-    pub idle_minutes: u64,
+fn default_transcription_model() -> String {
+    "whisper-1".to_string()
 }
 
-impl Default for ReflectionTimingSettings {
-    fn default() -> Self {
-        Self {
-            idle_minutes: default_reflection_idle_minutes(),
-        }
-    }
+fn default_transcription_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
 }
 
-fn default_reflection_idle_minutes() -> u64 {
-    4
+fn default_transcription_timeout_seconds() -> u64 {
+    120
 }
 
 fn default_compaction_threshold() -> f32 {
@@ -514,10 +2043,26 @@ pub struct WebSearchSettings {
     #[serde(default)]
     pub enabled: bool,
 
-    /// Provider name ("brave" or "perplexity")
+    /// Provider name ("brave", "perplexity", "tavily", "searxng" or "google")
     #[serde(default = "default_web_search_provider")]
     pub provider: String,
 
+    /// Providers a GHOST may pick per query; its default is always allowed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_providers: Vec<String>,
+
+    /// Per-ghost default provider keyed by ghost name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub ghost_providers: HashMap<String, String>,
+
+    /// Base URL of a SearxNG instance (required for "searxng")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub searxng_url: Option<String>,
+
+    /// Programmable Search Engine ID (`cx`) for "google"; falls back to GOOGLE_CSE_ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google_cse_id: Option<String>,
+
     /// Maximum results to return
     #[serde(default = "default_web_search_max_results")]
     pub max_results: usize,
@@ -546,7 +2091,7 @@ pub struct WebFetchSettings {
     #[serde(default = "default_web_fetch_provider")]
     pub provider: String,
 
-    /// Output mode ("text" or "markdown")
+    /// Output mode ("text", "markdown" or "render")
     #[serde(default = "default_web_fetch_mode")]
     pub mode: String,
 
@@ -561,6 +2106,123 @@ pub struct WebFetchSettings {
     /// Cache TTL in minutes
     #[serde(default = "default_web_fetch_cache_ttl_minutes")]
     pub cache_ttl_minutes: u64,
+
+    /// How `mode = "render"` renders JavaScript pages
+    #[serde(default)]
+    pub render: WebRenderSettings,
+}
+
+/// JavaScript rendering for `web_fetch` in `render` mode. Failed renders fall
+/// back to the plain HTTP fetch.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebRenderSettings {
+    /// "browser" (headless Chromium, using `[tools.web.browser]` executable and
+    /// allowlist) or "reader" (a Jina-style reader API)
+    #[serde(default = "default_web_render_backend")]
+    pub backend: String,
+
+    /// Reader API prefix; the page URL is appended to it. The optional API
+    /// key is read from READER_API_KEY.
+    #[serde(default = "default_web_render_reader_url")]
+    pub reader_url: String,
+}
+
+impl Default for WebRenderSettings {
+    fn default() -> Self {
+        Self {
+            backend: default_web_render_backend(),
+            reader_url: default_web_render_reader_url(),
+        }
+    }
+}
+
+/// Size limits of the persistent web search/fetch cache (koma DB). Entry
+/// lifetimes come from each tool's `cache_ttl_minutes`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebCacheSettings {
+    /// Keep at most this many entries, least recently used first out; 0 means
+    /// no limit (default: 2000).
+    #[serde(default = "default_web_cache_max_entries")]
+    pub max_entries: u32,
+    /// Keep at most this many KiB of cached results; 0 means no limit
+    /// (default: 51200).
+    #[serde(default = "default_web_cache_max_total_kib")]
+    pub max_total_kib: u32,
+}
+
+impl Default for WebCacheSettings {
+    fn default() -> Self {
+        Self {
+            max_entries: default_web_cache_max_entries(),
+            max_total_kib: default_web_cache_max_total_kib(),
+        }
+    }
+}
+
+/// Headless browser (Chromium over CDP) settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BrowserSettings {
+    /// Enable the browser tool
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Chromium/Chrome executable (default: first of `chromium`,
+    /// `chromium-browser`, `google-chrome` found on PATH)
+    pub executable: Option<String>,
+
+    /// Domains the browser may visit, subdomains included (empty = any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_domains: Vec<String>,
+
+    /// Ask the operator before interactive actions such as clicks (default: true)
+    #[serde(default = "default_browser_approve_interactive")]
+    pub approve_interactive: bool,
+
+    /// Max extracted text length in characters
+    #[serde(default = "default_web_fetch_max_chars")]
+    pub max_chars: usize,
+
+    /// Page load / command timeout in seconds
+    #[serde(default = "default_web_fetch_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl BrowserSettings {
+    /// Whether `host` is covered by the allowlist.
+    pub fn allows_host(&self, host: &str) -> bool {
+        self.allowed_domains.is_empty() || host_in_domains(&self.allowed_domains, host)
+    }
+}
+
+/// Whether `host` equals or is a subdomain of one of `domains` (`*.` prefixes
+/// are accepted and ignored).
+fn host_in_domains(domains: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches("*.").to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    })
+}
+
+impl Default for BrowserSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            executable: None,
+            allowed_domains: Vec::new(),
+            approve_interactive: default_browser_approve_interactive(),
+            max_chars: default_web_fetch_max_chars(),
+            timeout_seconds: default_web_fetch_timeout_seconds(),
+        }
+    }
+}
+
+fn default_web_domains_approve_blocked() -> bool {
+    true
+}
+
+fn default_browser_approve_interactive() -> bool {
+    true
 }
 
 // Default value functions
@@ -573,6 +2235,14 @@ fn default_gateway_port() -> u16 {
     3000
 }
 
+fn default_shutdown_drain_secs() -> u64 {
+    30
+}
+
+fn default_job_lease_secs() -> u64 {
+    900
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
@@ -597,6 +2267,44 @@ fn default_web_search_min_interval_ms() -> u64 {
     1000
 }
 
+impl WebSearchSettings {
+    /// Default provider for `ghost_name`.
+    pub fn provider_for_ghost(&self, ghost_name: &str) -> &str {
+        self.ghost_providers
+            .get(ghost_name)
+            .map(String::as_str)
+            .unwrap_or(&self.provider)
+    }
+
+    /// Provider to use for one query. `requested` must be the ghost's default
+    /// or listed in `allowed_providers`.
+    pub fn resolve_provider<'a>(
+        &'a self,
+        ghost_name: &str,
+        requested: Option<&'a str>,
+    ) -> Result<&'a str, String> {
+        let default = self.provider_for_ghost(ghost_name);
+        match requested {
+            None => Ok(default),
+            Some(name) if name == default || self.allowed_providers.iter().any(|p| p == name) => {
+                Ok(name)
+            }
+            Some(name) => Err(format!(
+                "web_search provider '{name}' is not allowed (allowed: {})",
+                std::iter::once(default)
+                    .chain(
+                        self.allowed_providers
+                            .iter()
+                            .map(String::as_str)
+                            .filter(|p| *p != default)
+                    )
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
 fn default_web_fetch_provider() -> String {
     "http".to_string()
 }
@@ -617,12 +2325,32 @@ fn default_web_fetch_cache_ttl_minutes() -> u64 {
     15
 }
 
+fn default_web_render_backend() -> String {
+    "browser".to_string()
+}
+
+fn default_web_render_reader_url() -> String {
+    "https://r.jina.ai/".to_string()
+}
+
+fn default_web_cache_max_entries() -> u32 {
+    2000
+}
+
+fn default_web_cache_max_total_kib() -> u32 {
+    51200
+}
+
 impl Default for GatewaySettings {
     fn default() -> Self {
         Self {
             host: default_gateway_host(),
             port: default_gateway_port(),
             ws_url: None,
+            read_only: false,
+            shutdown_drain_secs: default_shutdown_drain_secs(),
+            coordinate_jobs: false,
+            job_lease_secs: default_job_lease_secs(),
         }
     }
 }
@@ -643,6 +2371,10 @@ impl Default for WebSearchSettings {
         Self {
             enabled: false,
             provider: default_web_search_provider(),
+            allowed_providers: Vec::new(),
+            ghost_providers: HashMap::new(),
+            searxng_url: None,
+            google_cse_id: None,
             max_results: default_web_search_max_results(),
             timeout_seconds: default_web_search_timeout_seconds(),
             cache_ttl_minutes: default_web_search_cache_ttl_minutes(),
@@ -660,6 +2392,7 @@ impl Default for WebFetchSettings {
             max_chars: default_web_fetch_max_chars(),
             timeout_seconds: default_web_fetch_timeout_seconds(),
             cache_ttl_minutes: default_web_fetch_cache_ttl_minutes(),
+            render: WebRenderSettings::default(),
         }
     }
 }
@@ -763,9 +2496,11 @@ impl Settings {
         Ok(())
     }
 
-    /// Save settings to the default configuration file path.
+    /// Save settings to the default configuration file path, backing up the
+    /// previous file first (see [`super::backups`]).
     pub fn save(&self) -> Result<(), SettingsError> {
         let config_path = Self::config_path()?;
+        super::backups::create_backup(&config_path)?;
         self.save_to_path(&config_path)
     }
 
@@ -815,6 +2550,16 @@ mod tests {
         assert_eq!(settings.gateway.port, 3000);
 
         assert!(!settings.discord.enabled);
+        assert!(settings.discord.threads);
+        assert!(settings.discord.search_embeds);
+        assert!(!settings.telegram.enabled);
+        assert_eq!(settings.telegram.poll_timeout_secs, 30);
+        assert!(!settings.slack.enabled);
+        assert!(!settings.email.enabled);
+        assert_eq!(settings.email.imap_port, 993);
+        assert_eq!(settings.email.smtp_port, 465);
+        assert_eq!(settings.email.mailbox, "INBOX");
+        assert_eq!(settings.email.poll_interval_secs, 60);
 
         assert_eq!(settings.logging.level, "info");
         assert!(!settings.logging.file_enabled);
@@ -822,6 +2567,7 @@ mod tests {
 
         assert!(settings.openrouter.http_referer.is_none());
         assert!(settings.openrouter.app_name.is_none());
+        assert_eq!(settings.openrouter.catalog_refresh_hours, 24);
 
         assert!(!settings.tools.web.enabled);
         assert!(!settings.tools.web.search.enabled);
@@ -831,6 +2577,128 @@ mod tests {
         assert!(!settings.tools.web.fetch.enabled);
         assert_eq!(settings.tools.web.fetch.provider, "http");
         assert_eq!(settings.tools.web.fetch.mode, "markdown");
+        assert!(!settings.tools.web.browser.enabled);
+        assert!(settings.tools.web.browser.approve_interactive);
+
+        assert!(settings.tools.untrusted_content.enabled);
+        assert_eq!(
+            settings.tools.untrusted_content.action,
+            InjectionAction::Flag
+        );
+    }
+
+    #[test]
+    fn test_browser_allowlist_matches_subdomains() {
+        let mut browser = BrowserSettings::default();
+        assert!(browser.allows_host("anything.example"));
+
+        browser.allowed_domains = vec!["example.com".to_string(), "*.rust-lang.org".to_string()];
+        assert!(browser.allows_host("example.com"));
+        assert!(browser.allows_host("Docs.Example.com"));
+        assert!(browser.allows_host("doc.rust-lang.org"));
+        assert!(!browser.allows_host("notexample.com"));
+        assert!(!browser.allows_host("example.com.evil.test"));
+    }
+
+    #[test]
+    fn test_mcp_servers_from_toml() {
+        let settings = Settings::from_toml(
+            r#"
+[mcp.servers.github]
+command = "npx"
+args = ["-y", "@modelcontextprotocol/server-github"]
+env = { GITHUB_TOKEN = "x" }
+auto_approve = ["search_repositories"]
+"#,
+        )
+        .unwrap();
+        let github = &settings.mcp.servers["github"];
+        assert!(github.enabled);
+        assert_eq!(github.args.len(), 2);
+        assert_eq!(github.timeout_seconds, 60);
+        assert!(!github.needs_approval("search_repositories"));
+        assert!(github.needs_approval("create_issue"));
+        assert!(Settings::default().mcp.servers.is_empty());
+    }
+
+    #[test]
+    fn test_tool_timeouts() {
+        let settings = Settings::from_toml(
+            r#"
+[tools.timeouts]
+default_seconds = 60
+
+[tools.timeouts.per_tool]
+run_shell_command = 600
+knowledge_search = 0
+"#,
+        )
+        .unwrap();
+        let timeouts = &settings.tools.timeouts;
+        assert_eq!(
+            timeouts.budget_for("web_fetch"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            timeouts.budget_for("run_shell_command"),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(timeouts.budget_for("knowledge_search"), None);
+        assert_eq!(settings.tools.shell, ShellToolSettings::default());
+        assert!(settings.tools.shell.pty);
+        assert_eq!(settings.tools.output.page_chars, 16_000);
+        assert_eq!(
+            Settings::default().tools.timeouts.budget_for("web_fetch"),
+            Some(Duration::from_secs(120))
+        );
+    }
+
+    #[test]
+    fn test_webhook_tools() {
+        let settings = Settings::from_toml(
+            r#"
+[tools.webhooks.lights_on]
+description = "Turn on a room's lights"
+url = "http://homeassistant.local:8123/hooks/lights"
+auth_header = "Authorization"
+auth_value_env = "HASS_TOKEN"
+signing_secret_env = "HASS_HOOK_SECRET"
+require_approval = false
+
+[tools.webhooks.lights_on.input_schema]
+type = "object"
+required = ["room"]
+properties = { room = { type = "string" } }
+
+[tools.webhooks.ping]
+description = "Ping"
+url = "https://example.com/ping"
+"#,
+        )
+        .unwrap();
+        let lights = &settings.tools.webhooks["lights_on"];
+        assert!(lights.enabled && !lights.require_approval);
+        assert_eq!(lights.input_schema["required"][0], "room");
+        assert_eq!(lights.auth_value_env.as_deref(), Some("HASS_TOKEN"));
+
+        let ping = &settings.tools.webhooks["ping"];
+        assert!(ping.require_approval);
+        assert_eq!(ping.timeout_seconds, 30);
+        assert_eq!(ping.input_schema["type"], "object");
+        assert_eq!(ping.signing_secret_env, None);
+    }
+
+    #[test]
+    fn test_http_request_allowlist_denies_by_default() {
+        let mut http = HttpRequestSettings::default();
+        assert!(!http.enabled);
+        assert!(!http.allows_host("example.com"));
+
+        http.allowed_domains = vec!["homeassistant.local".to_string(), "10.0.0.5".to_string()];
+        assert!(http.allows_host("homeassistant.local"));
+        assert!(http.allows_host("10.0.0.5"));
+        assert!(!http.allows_host("10.0.0.50"));
+        assert!(!http.allows_host("example.com"));
     }
 
     #[test]
@@ -880,6 +2748,25 @@ port = 8080
 
 [discord]
 enabled = true
+threads = false
+search_embeds = false
+
+[telegram]
+enabled = true
+poll_timeout_secs = 50
+
+[slack]
+enabled = true
+
+[email]
+enabled = true
+address = "ghost@example.com"
+imap_host = "imap.example.com"
+smtp_host = "smtp.example.com"
+smtp_port = 587
+smtp_starttls = true
+ingest_topic = "mail"
+allowed_senders = ["Me@Example.com"]
 
 [logging]
 level = "debug"
@@ -938,6 +2825,19 @@ cache_ttl_minutes = 2
         assert_eq!(settings.gateway.port, 8080);
 
         assert!(settings.discord.enabled);
+        assert!(!settings.discord.threads);
+        assert!(!settings.discord.search_embeds);
+        assert!(settings.telegram.enabled);
+        assert_eq!(settings.telegram.poll_timeout_secs, 50);
+        assert!(settings.slack.enabled);
+        assert!(settings.email.enabled);
+        assert_eq!(settings.email.login(), "ghost@example.com");
+        assert_eq!(settings.email.imap_port, 993);
+        assert_eq!(settings.email.smtp_port, 587);
+        assert!(settings.email.smtp_starttls);
+        assert_eq!(settings.email.ingest_topic.as_deref(), Some("mail"));
+        assert!(settings.email.sender_allowed("me@example.com"));
+        assert!(!settings.email.sender_allowed("other@example.com"));
 
         assert_eq!(settings.logging.level, "debug");
 
@@ -979,7 +2879,14 @@ host = "0.0.0.0"
                 routing: Some(vec!["anthropic".to_string()]),
                 context_window: None,
                 headers: None,
+                api_version: None,
                 retry_on_empty: None,
+                thinking_budget: None,
+                pricing: None,
+                generation: GenerationParams {
+                    temperature: Some(0.5),
+                    ..Default::default()
+                },
             },
         );
         settings.default_model = ModelAliases::single("kimi25");
@@ -1075,6 +2982,136 @@ host = "0.0.0.0"
         assert_eq!(roundtrip.default_model.len(), 2);
     }
 
+    #[test]
+    fn test_model_pricing_parsing() {
+        let toml = r#"
+[models.primary]
+provider = "anthropic"
+model = "claude-sonnet-4-5"
+pricing = { input = 3.0, output = 15.0, cache_read = 0.3 }
+"#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let pricing = settings.models["primary"].pricing.clone().unwrap();
+        assert_eq!(pricing.input, 3.0);
+        assert_eq!(pricing.output, 15.0);
+        assert_eq!(pricing.cache_read, 0.3);
+        assert_eq!(pricing.cache_write, 0.0);
+    }
+
+    #[test]
+    fn test_transcription_settings() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert!(!settings.transcription.enabled);
+        assert_eq!(settings.transcription.model, "whisper-1");
+
+        let toml = r#"
+[transcription]
+enabled = true
+base_url = "http://127.0.0.1:9000/v1"
+api_key_env = "WHISPER_KEY"
+language = "fr"
+"#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let transcription = &settings.transcription;
+        assert!(transcription.enabled);
+        assert_eq!(transcription.base_url, "http://127.0.0.1:9000/v1");
+        assert_eq!(transcription.api_key_env, "WHISPER_KEY");
+        assert_eq!(transcription.language.as_deref(), Some("fr"));
+        assert_eq!(transcription.timeout_seconds, 120);
+    }
+
+    #[test]
+    fn test_cli_key_settings() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert_eq!(settings.cli.theme, "cyberpunk");
+        assert_eq!(settings.cli.keys.preset, KeyPreset::Default);
+        assert!(settings.cli.keys.bindings.is_empty());
+
+        let toml = r#"
+[cli.keys]
+preset = "vim"
+quit = "ctrl+q"
+down = ["j", "down"]
+"#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let keys = &settings.cli.keys;
+        assert_eq!(keys.preset, KeyPreset::Vim);
+        assert_eq!(keys.bindings["quit"].keys(), ["ctrl+q"]);
+        assert_eq!(keys.bindings["down"].keys(), ["j", "down"]);
+
+        let round_trip: Settings = toml::from_str(&settings.to_toml().unwrap()).unwrap();
+        assert_eq!(round_trip.cli.keys.bindings, keys.bindings);
+    }
+
+    #[test]
+    fn test_cli_theme_settings() {
+        let toml = r##"
+[cli]
+theme = "paper"
+
+[cli.themes.paper]
+base = "light"
+glow = true
+accent = "#005f87"
+"##;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        assert_eq!(settings.cli.theme, "paper");
+        let paper = &settings.cli.themes["paper"];
+        assert_eq!(paper.base.as_deref(), Some("light"));
+        assert_eq!(paper.glow, Some(true));
+        assert_eq!(paper.colors["accent"], "#005f87");
+    }
+
+    #[test]
+    fn test_thinking_settings() {
+        let settings: Settings = toml::from_str("").unwrap();
+        assert_eq!(settings.thinking.display, ThinkingDisplay::Hidden);
+
+        let toml = r#"
+[thinking]
+display = "summary"
+
+[models.claude]
+provider = "anthropic"
+model = "claude-sonnet-4-5"
+thinking_budget = 4096
+"#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        assert_eq!(settings.thinking.display, ThinkingDisplay::Summary);
+        assert_eq!(settings.models["claude"].thinking_budget, Some(4096));
+    }
+
+    #[test]
+    fn test_generation_params() {
+        let toml = r#"
+[models.claude]
+provider = "anthropic"
+model = "claude-sonnet-4-5"
+temperature = 0.7
+max_output_tokens = 8192
+stop = ["END"]
+extra_params = { top_k = 40, metadata = { user_id = "ops" } }
+
+[reflection.generation]
+temperature = 0.2
+extra_params = { top_k = 10 }
+"#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let model = &settings.models["claude"].generation;
+        assert_eq!(model.temperature, Some(0.7));
+        assert_eq!(model.max_output_tokens, Some(8192));
+        assert_eq!(model.stop.as_deref(), Some(&["END".to_string()][..]));
+        assert!(settings.heartbeat_timing.generation.is_empty());
+        assert!(!settings.heartbeat_timing.adaptive.enabled);
+
+        let reflection = model.overlay(&settings.reflection.generation);
+        assert_eq!(reflection.temperature, Some(0.2));
+        assert_eq!(reflection.max_output_tokens, Some(8192));
+        let extra = reflection.extra_params.unwrap();
+        assert_eq!(extra["top_k"], 10);
+        assert_eq!(extra["metadata"]["user_id"], "ops");
+    }
+
     #[test]
     fn test_heartbeat_model_list_parsing() {
         let toml = r#"
@@ -1086,4 +3123,208 @@ heartbeat_model = ["alpha", "kimi25"]
         assert_eq!(hb.len(), 2);
         assert_eq!(hb.first(), Some("alpha"));
     }
+
+    #[test]
+    fn test_knowledge_language_overrides() {
+        let toml = r#"
+[tools.knowledge]
+embedding_model = "base"
+[tools.knowledge.languages.JA]
+embedding_model = "multilingual"
+[tools.knowledge.languages.fr]
+bm25_tokenizer = "bigram"
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let knowledge = crate::config::KnowledgeSettings::from(&settings.tools.knowledge);
+
+        assert_eq!(knowledge.embedding_model_for(Some("ja")), "multilingual");
+        assert_eq!(knowledge.embedding_model_for(Some("en")), "base");
+        assert_eq!(knowledge.embedding_model_for(None), "base");
+        assert_eq!(
+            knowledge.bm25_tokenizer_for(Some("ja")),
+            crate::config::Bm25Tokenizer::Bigram
+        );
+        assert_eq!(
+            knowledge.bm25_tokenizer_for(Some("fr")),
+            crate::config::Bm25Tokenizer::Bigram
+        );
+        assert_eq!(
+            knowledge.bm25_tokenizer_for(Some("en")),
+            crate::config::Bm25Tokenizer::Words
+        );
+        assert!(
+            knowledge
+                .embedding_fingerprint()
+                .ends_with(";ja=multilingual")
+        );
+    }
+
+    #[test]
+    fn test_knowledge_openai_embeddings() {
+        let toml = r#"
+[tools.knowledge]
+embedding_provider = "openai"
+embedding_model = "voyage-3"
+embedding_url = "https://api.voyageai.com/v1"
+embedding_api_key_env = "VOYAGE_API_KEY"
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let knowledge = crate::config::KnowledgeSettings::from(&settings.tools.knowledge);
+        assert_eq!(
+            knowledge.embedding_provider,
+            crate::config::EmbeddingProviderKind::OpenAi
+        );
+        assert_eq!(knowledge.embedding_api_key_env(), Some("VOYAGE_API_KEY"));
+        assert!(
+            knowledge
+                .embedding_fingerprint()
+                .starts_with("openai:voyage-3")
+        );
+
+        let settings =
+            Settings::from_toml("[tools.knowledge]\nembedding_provider = \"openai\"\n").unwrap();
+        let knowledge = crate::config::KnowledgeSettings::from(&settings.tools.knowledge);
+        assert_eq!(knowledge.embedding_url, "https://api.openai.com/v1");
+        assert_eq!(knowledge.embedding_api_key_env(), Some("OPENAI_API_KEY"));
+        assert_eq!(
+            crate::config::KnowledgeSettings::default().embedding_api_key_env(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_provider_retry_overrides() {
+        let toml = r#"
+[provider_retry]
+backoff_base_ms = 250
+[provider_retry.providers.openrouter]
+max_attempts = 5
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let retry = &settings.provider_retry;
+
+        assert_eq!(retry.for_provider("openrouter"), (5, 250));
+        assert_eq!(retry.for_provider("anthropic"), (3, 250));
+        assert_eq!(retry.max_backoff_ms, 30_000);
+    }
+
+    #[test]
+    fn test_output_filter_ghost_overrides() {
+        let toml = r#"
+default_model = "a"
+
+[models.a]
+provider = "openrouter"
+model = "x"
+
+[output_filters]
+redact_secrets = true
+moderation_model = "a"
+patterns = [{ pattern = "ACME-\\d+" }]
+
+[output_filters.ghosts.Alpha]
+redact_pii = true
+moderation_model = ""
+patterns = [{ pattern = "project-x", replacement = "[CODENAME]" }]
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let filters = &settings.output_filters;
+
+        let default = filters.for_ghost("Beta");
+        assert!(default.redact_secrets);
+        assert!(!default.redact_pii);
+        assert_eq!(default.moderation_model.as_deref(), Some("a"));
+        assert_eq!(default.patterns.len(), 1);
+        assert_eq!(default.patterns[0].pattern, "ACME-\\d+");
+        assert_eq!(default.patterns[0].replacement, "[REDACTED]");
+
+        let alpha = filters.for_ghost("Alpha");
+        assert!(alpha.redact_secrets);
+        assert!(alpha.redact_pii);
+        assert_eq!(alpha.moderation_model, None);
+        assert_eq!(alpha.patterns.len(), 2);
+        assert_eq!(alpha.patterns[1].replacement, "[CODENAME]");
+
+        assert_eq!(filters.moderation_models().collect::<Vec<_>>(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_web_domain_policy() {
+        let toml = r#"
+[tools.web.domains]
+deny = ["ads.example.com", "tracker.test"]
+
+[tools.web.domains.ghosts.Alpha]
+allow = ["example.com"]
+deny = ["private.example.com"]
+approve_blocked = false
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let domains = &settings.tools.web.domains;
+
+        let beta = domains.for_ghost("Beta");
+        assert!(beta.approve_blocked);
+        assert!(beta.allows_host("docs.rs"));
+        assert!(!beta.allows_host("cdn.tracker.test"));
+        assert!(beta.allows_host("example.com"));
+        assert!(!beta.allows_host("ads.example.com"));
+
+        let mut alpha = domains.for_ghost("Alpha");
+        assert!(!alpha.approve_blocked);
+        assert!(alpha.allows_host("www.example.com"));
+        assert!(!alpha.allows_host("docs.rs"));
+        assert!(!alpha.allows_host("ads.example.com"));
+        assert!(!alpha.allows_host("private.example.com"));
+        alpha.approve_host("docs.rs");
+        assert!(alpha.allows_host("docs.rs"));
+    }
+
+    #[test]
+    fn test_web_search_provider_selection() {
+        let toml = r#"
+[tools.web.search]
+enabled = true
+provider = "brave"
+allowed_providers = ["searxng"]
+searxng_url = "http://localhost:8888"
+
+[tools.web.search.ghost_providers]
+Alpha = "tavily"
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let search = &settings.tools.web.search;
+
+        assert_eq!(search.provider_for_ghost("Beta"), "brave");
+        assert_eq!(search.provider_for_ghost("Alpha"), "tavily");
+        assert_eq!(search.resolve_provider("Alpha", None), Ok("tavily"));
+        assert_eq!(
+            search.resolve_provider("Alpha", Some("searxng")),
+            Ok("searxng")
+        );
+        assert_eq!(search.resolve_provider("Beta", Some("brave")), Ok("brave"));
+        let err = search.resolve_provider("Beta", Some("google")).unwrap_err();
+        assert!(err.contains("brave, searxng"), "{err}");
+    }
+
+    #[test]
+    fn test_provider_timeout_overrides() {
+        let toml = r#"
+[provider_timeouts]
+request_seconds = 90
+[provider_timeouts.providers]
+gemini = 300
+"#;
+        let settings = Settings::from_toml(toml).unwrap();
+        let timeouts = &settings.provider_timeouts;
+
+        assert_eq!(timeouts.for_provider("gemini"), 300);
+        assert_eq!(timeouts.for_provider("anthropic"), 90);
+        assert_eq!(
+            Settings::from_toml("")
+                .unwrap()
+                .provider_timeouts
+                .request_seconds,
+            120
+        );
+    }
 }
@@ -1,5 +1,8 @@
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 use serde::Deserialize;
 
 use crate::ModelAliases;
@@ -16,6 +19,8 @@ struct CronFrontmatter {
     #[serde(default)]
     pub name: Option<String>,
     pub schedule: String,
+    #[serde(default)]
+    pub timezone: Option<String>,
     #[serde(default = "default_true")]
     pub enabled: bool,
     #[serde(default = "default_true")]
@@ -31,6 +36,7 @@ pub struct ParsedCronJobFile {
     pub id: String,
     pub name: String,
     pub schedule: String,
+    pub timezone: Option<String>,
     pub prompt: String,
     pub enabled: bool,
     pub carry_last_output: bool,
@@ -78,6 +84,7 @@ pub fn parse_cron_job_markdown(
         id: stem.to_string(),
         name: fm.name.unwrap_or_else(|| stem.to_string()),
         schedule: fm.schedule.trim().to_string(),
+        timezone: fm.timezone,
         prompt: body.trim().to_string(),
         enabled: fm.enabled,
         carry_last_output: fm.carry_last_output,
@@ -85,3 +92,308 @@ pub fn parse_cron_job_markdown(
         pre_tools: fm.pre_tools,
     })
 }
+
+/// Time zone a cron expression is evaluated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CronTimezone {
+    #[default]
+    Utc,
+    /// The gateway host's zone, daylight saving time included.
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl FromStr for CronTimezone {
+    type Err = CronScheduleError;
+
+    /// Accepts `UTC`, `local` and offsets such as `+02:00`, `-0530` or
+    /// `UTC+2`. Named IANA zones are not supported.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let unsupported = || CronScheduleError::UnsupportedTimezone(trimmed.to_string());
+        match trimmed.to_ascii_lowercase().as_str() {
+            "utc" | "gmt" | "z" => return Ok(Self::Utc),
+            "local" => return Ok(Self::Local),
+            _ => {}
+        }
+
+        let offset = trimmed
+            .strip_prefix("UTC")
+            .or_else(|| trimmed.strip_prefix("GMT"))
+            .unwrap_or(trimmed);
+        let (sign, digits) = match offset.as_bytes().first() {
+            Some(b'+') => (1, &offset[1..]),
+            Some(b'-') => (-1, &offset[1..]),
+            _ => return Err(unsupported()),
+        };
+        let (hours, minutes) = match digits.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if digits.len() == 4 => digits.split_at(2),
+            None => (digits, "0"),
+        };
+        let hours: i32 = hours.parse().map_err(|_| unsupported())?;
+        let minutes: i32 = minutes.parse().map_err(|_| unsupported())?;
+        if hours > 14 || minutes > 59 {
+            return Err(unsupported());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(unsupported)
+    }
+}
+
+impl fmt::Display for CronTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => write!(f, "UTC"),
+            Self::Local => write!(f, "local"),
+            Self::Fixed(offset) => write!(f, "UTC{offset}"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CronScheduleError {
+    #[error("invalid cron expression '{0}': {1}")]
+    InvalidExpression(String, String),
+    #[error("unsupported time zone '{0}' (use UTC, local or an offset like +02:00)")]
+    UnsupportedTimezone(String),
+    #[error("time zone given both in the expression and separately")]
+    ConflictingTimezone,
+}
+
+/// A parsed cron schedule.
+///
+/// Accepts standard five-field expressions (`minute hour day month weekday`,
+/// weekdays 0-7 with 0 and 7 both Sunday), the `@hourly`, `@daily`/`@midnight`,
+/// `@weekly`, `@monthly` and `@yearly`/`@annually` nicknames, and Quartz-style
+/// six- or seven-field expressions (seconds first, optional year, weekday 1 =
+/// Sunday). A `TZ=` or `CRON_TZ=` prefix sets the time zone.
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    expression: String,
+    timezone: CronTimezone,
+    schedule: cron::Schedule,
+}
+
+impl CronSchedule {
+    /// Parse `expression`, evaluated in `timezone` (default UTC).
+    pub fn parse(expression: &str, timezone: Option<&str>) -> Result<Self, CronScheduleError> {
+        let mut expression = expression.trim();
+        let mut prefix_tz = None;
+        for prefix in ["CRON_TZ=", "TZ="] {
+            if let Some(rest) = expression.strip_prefix(prefix) {
+                let (tz, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                prefix_tz = Some(tz);
+                expression = rest.trim();
+                break;
+            }
+        }
+        let timezone = match (prefix_tz, timezone.filter(|tz| !tz.trim().is_empty())) {
+            (Some(_), Some(_)) => return Err(CronScheduleError::ConflictingTimezone),
+            (Some(tz), None) | (None, Some(tz)) => tz.parse()?,
+            (None, None) => CronTimezone::Utc,
+        };
+
+        let invalid =
+            |reason: String| CronScheduleError::InvalidExpression(expression.to_string(), reason);
+        let normalized = normalize_expression(expression).map_err(invalid)?;
+        let schedule = cron::Schedule::from_str(&normalized).map_err(|e| invalid(e.to_string()))?;
+        Ok(Self {
+            expression: expression.to_string(),
+            timezone,
+            schedule,
+        })
+    }
+
+    /// The expression as written, without any time zone prefix.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn timezone(&self) -> CronTimezone {
+        self.timezone
+    }
+
+    /// First run strictly after the unix timestamp `after`, truncated to the
+    /// minute.
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        let base = DateTime::from_timestamp(after, 0)?;
+        let next = match self.timezone {
+            CronTimezone::Utc => first_after(&self.schedule, &base),
+            CronTimezone::Local => first_after(&self.schedule, &base.with_timezone(&Local)),
+            CronTimezone::Fixed(offset) => {
+                first_after(&self.schedule, &base.with_timezone(&offset))
+            }
+        }?;
+        Some(next - next.rem_euclid(60))
+    }
+
+    /// First run in the minute of `now` or later.
+    pub fn next_at_or_after(&self, now: DateTime<Utc>) -> Option<i64> {
+        let ts = now.timestamp();
+        self.next_after(ts - ts.rem_euclid(60) - 1)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.timezone {
+            CronTimezone::Utc => write!(f, "{}", self.expression),
+            tz => write!(f, "{} ({tz})", self.expression),
+        }
+    }
+}
+
+fn first_after<Z: TimeZone>(schedule: &cron::Schedule, base: &DateTime<Z>) -> Option<i64> {
+    schedule.after(base).next().map(|dt| dt.timestamp())
+}
+
+/// Rewrite `expression` into the seconds-first syntax of the `cron` crate.
+fn normalize_expression(expression: &str) -> Result<String, String> {
+    if expression.starts_with('@') {
+        return Ok(match expression {
+            "@midnight" => "@daily".to_string(),
+            "@annually" => "@yearly".to_string(),
+            other => other.to_string(),
+        });
+    }
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    match fields.len() {
+        5 => Ok(format!(
+            "0 {} {} {} {} {}",
+            fields[0],
+            fields[1],
+            fields[2],
+            fields[3],
+            standard_weekdays(fields[4])?
+        )),
+        6 | 7 => Ok(fields.join(" ")),
+        n => Err(format!("expected 5 fields, got {n}")),
+    }
+}
+
+/// Translate a standard weekday field (0-7, Sunday = 0 or 7) to the `cron`
+/// crate's numbering (1-7, Sunday = 1). Names are left as they are.
+fn standard_weekdays(field: &str) -> Result<String, String> {
+    let ordinal = |value: &str| -> Result<u32, String> {
+        match value.parse::<u32>() {
+            Ok(day @ 0..=7) => Ok(day),
+            _ => Err(format!("invalid weekday '{value}'")),
+        }
+    };
+    let mut parts = Vec::new();
+    for item in field.split(',') {
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => (
+                base,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in '{item}'"))?,
+            ),
+            None => (item, 1),
+        };
+        if base.chars().any(|c| c.is_ascii_alphabetic()) {
+            parts.push(item.to_string());
+            continue;
+        }
+        let (start, end) = match base {
+            "*" | "?" => (0, 6),
+            _ => match base.split_once('-') {
+                Some((start, end)) => (ordinal(start)?, ordinal(end)?),
+                None if step > 1 => (ordinal(base)?, 6),
+                None => {
+                    let day = ordinal(base)?;
+                    (day, day)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("invalid weekday range '{base}'"));
+        }
+        for day in (start..=end).step_by(step) {
+            parts.push((day % 7 + 1).to_string());
+        }
+    }
+    parts.sort();
+    parts.dedup();
+    Ok(parts.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> i64 {
+        DateTime::parse_from_rfc3339(s).unwrap().timestamp()
+    }
+
+    #[test]
+    fn test_standard_weekdays_follow_posix_numbering() {
+        // 2026-03-06 is a Friday.
+        let weekdays = CronSchedule::parse("0 9 * * 1-5", None).unwrap();
+        assert_eq!(
+            weekdays.next_after(ts("2026-03-06T10:00:00Z")),
+            Some(ts("2026-03-09T09:00:00Z"))
+        );
+        let sunday = CronSchedule::parse("30 8 * * 7", None).unwrap();
+        assert_eq!(
+            sunday.next_after(ts("2026-03-06T10:00:00Z")),
+            Some(ts("2026-03-08T08:30:00Z"))
+        );
+        assert_eq!(standard_weekdays("0,6").unwrap(), "1,7");
+        assert_eq!(standard_weekdays("1-5/2").unwrap(), "2,4,6");
+        assert_eq!(standard_weekdays("MON-FRI").unwrap(), "MON-FRI");
+        assert!(standard_weekdays("8").is_err());
+    }
+
+    #[test]
+    fn test_nicknames_and_quartz_syntax() {
+        let daily = CronSchedule::parse("@midnight", None).unwrap();
+        assert_eq!(
+            daily.next_after(ts("2026-03-06T10:00:00Z")),
+            Some(ts("2026-03-07T00:00:00Z"))
+        );
+        let quartz = CronSchedule::parse("0 15 10 * * *", None).unwrap();
+        assert_eq!(
+            quartz.next_after(ts("2026-03-06T10:00:00Z")),
+            Some(ts("2026-03-06T10:15:00Z"))
+        );
+        assert!(matches!(
+            CronSchedule::parse("* * *", None),
+            Err(CronScheduleError::InvalidExpression(..))
+        ));
+    }
+
+    #[test]
+    fn test_timezones() {
+        let paris = CronSchedule::parse("TZ=+01:00 0 9 * * *", None).unwrap();
+        assert_eq!(paris.expression(), "0 9 * * *");
+        assert_eq!(paris.to_string(), "0 9 * * * (UTC+01:00)");
+        assert_eq!(
+            paris.next_after(ts("2026-03-06T07:00:00Z")),
+            Some(ts("2026-03-06T08:00:00Z"))
+        );
+
+        let new_york = CronSchedule::parse("0 9 * * *", Some("UTC-5")).unwrap();
+        assert_eq!(
+            new_york.next_after(ts("2026-03-06T07:00:00Z")),
+            Some(ts("2026-03-06T14:00:00Z"))
+        );
+
+        assert_eq!(
+            "-0530".parse::<CronTimezone>().unwrap().to_string(),
+            "UTC-05:30"
+        );
+        assert_eq!("local".parse::<CronTimezone>(), Ok(CronTimezone::Local));
+        assert!(matches!(
+            "Europe/Paris".parse::<CronTimezone>(),
+            Err(CronScheduleError::UnsupportedTimezone(_))
+        ));
+        assert_eq!(
+            CronSchedule::parse("TZ=UTC @daily", Some("local")).unwrap_err(),
+            CronScheduleError::ConflictingTimezone
+        );
+    }
+}
@@ -11,16 +11,31 @@ pub use skills::{Skill, SkillError};
 
 // Config re-exports
 pub use config::{
-    Config, ConfigError, GatewaySettings, HeartbeatTimingSettings, ModelAliases, ModelConfig,
-    OpenRouterSettings, ReflectionTimingSettings, Secrets, SecretsError, Settings, SettingsError,
-    load_dotenv,
+    AzureAdCredentials, BrowserSettings, CliSettings, ClientLimitSettings, CompactionSettings,
+    Config, ConfigBackup, ConfigError, DiscordSettings, EmailSettings, GatewaySettings,
+    GenerationParams, HeartbeatAdaptiveSettings, HeartbeatTimingSettings, HttpRequestSettings,
+    InjectionAction, JobLogRetentionSettings, JobQueueSettings, KeyBinding, KeyPreset, KeySettings,
+    McpServerSettings, McpSettings, ModelAliases, ModelConfig, OpenRouterSettings,
+    OutputFilterOverride, OutputFilterPolicy, OutputFilterSettings, PromptCacheSettings,
+    ProviderRetryOverride, ProviderRetrySettings, ProviderTimeoutSettings, RedactionPattern,
+    ReflectionQualitySettings, ReflectionTimingSettings, Secrets, SecretsError,
+    SessionArchiveSettings, Settings, SettingsError, ShellToolSettings, ThemeSettings,
+    ThinkingDisplay, ThinkingSettings, ToolOutputSettings, ToolTimeoutSettings,
+    TranscriptionSettings, UntrustedContentSettings, WebCacheSettings, WebDomainPolicy,
+    WebDomainSettings, WebRenderSettings, WebSearchSettings, WebhookToolSettings, load_dotenv,
+};
+pub use cron::{
+    CronParseError, CronPreToolCall, CronSchedule, CronScheduleError, CronTimezone,
+    ParsedCronJobFile, parse_cron_job_markdown,
 };
-pub use cron::{CronParseError, CronPreToolCall, ParsedCronJobFile, parse_cron_job_markdown};
 
 // Message re-exports
 pub use message::{
-    ChatMessage, GatewayAction, GatewayActionStyle, GatewayChoice, GatewayInputKind,
-    GatewayInputRequest, GatewayMessage, GatewayMessageKind, GatewayMessageText,
-    KnowledgeIndexStats, KnowledgeResultInfo, KnowledgeStatsEntry, MessageRole, ModelInfo,
-    ProviderType, SchedulerEntryInfo, WsMessage, WsResponse,
+    ApprovalDecision, ChatAttachment, ChatMessage, GatewayAction, GatewayActionStyle,
+    GatewayChoice, GatewayInputKind, GatewayInputRequest, GatewayMessage, GatewayMessageKind,
+    GatewayMessageText, GhostCloneScope, KnowledgeIndexStats, KnowledgeResultInfo,
+    KnowledgeStatsEntry, KnowledgeTopicInfo, MessageRole, ModelInfo, PendingApprovalInfo,
+    PendingApprovalKind, PendingOperatorInfo, ProviderType, SchedulerEntryInfo, SessionMessageInfo,
+    TranscriptBlock, UsageBudgetInfo, UsageBudgetScope, UsageReportGrouping, UsageReportRow,
+    WsMessage, WsResponse,
 };
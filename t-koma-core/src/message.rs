@@ -19,6 +19,8 @@ pub enum ProviderType {
     OpenAiCompatible,
     Gemini,
     KimiCode,
+    #[serde(rename = "azure_openai")]
+    AzureOpenAi,
 }
 
 impl ProviderType {
@@ -29,6 +31,7 @@ impl ProviderType {
             ProviderType::OpenAiCompatible => "openai_compatible",
             ProviderType::Gemini => "gemini",
             ProviderType::KimiCode => "kimi_code",
+            ProviderType::AzureOpenAi => "azure_openai",
         }
     }
 }
@@ -51,6 +54,7 @@ impl std::str::FromStr for ProviderType {
             }
             "gemini" => Ok(ProviderType::Gemini),
             "kimi_code" | "kimi-code" | "kimicode" => Ok(ProviderType::KimiCode),
+            "azure_openai" | "azure-openai" | "azure" => Ok(ProviderType::AzureOpenAi),
             _ => Err(format!("Unknown provider: {}", s)),
         }
     }
@@ -245,6 +249,154 @@ pub struct SessionInfo {
     pub next_heartbeat_due: Option<DateTime<Utc>>,
     pub message_count: i64,
     pub is_active: bool,
+    /// Generated session title, if the titling job has run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A session message matching a full-text search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    pub message_id: String,
+    pub role: MessageRole,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    /// Matching excerpt with hits wrapped in `[` `]`
+    pub snippet: String,
+}
+
+/// One stored session message, for transcript viewers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessageInfo {
+    pub id: String,
+    pub role: MessageRole,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub blocks: Vec<TranscriptBlock>,
+    /// Usage of the request that produced this (ghost) message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Content block of a session message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+        #[serde(default)]
+        redacted: bool,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default)]
+        is_error: bool,
+    },
+    /// Uploaded image or file
+    Attachment {
+        filename: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+}
+
+/// Dimension a usage report is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageReportGrouping {
+    Operator,
+    Ghost,
+    Model,
+    /// UTC calendar day
+    Day,
+    /// Individual assistant messages, costliest first
+    Message,
+}
+
+/// One group of a usage report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReportRow {
+    /// Operator id, ghost id, model or `YYYY-MM-DD`
+    pub key: String,
+    pub label: String,
+    /// UTC day (`YYYY-MM-DD`) of the row in `daily` reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<String>,
+    pub request_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cost_usd: f64,
+    /// Requests for models without configured pricing
+    pub unpriced_requests: i64,
+}
+
+/// A monthly usage budget and its month-to-date usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBudgetInfo {
+    pub scope: UsageBudgetScope,
+    /// Operator id or ghost id
+    pub subject: String,
+    /// Operator or ghost name
+    pub label: String,
+    pub used_tokens: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_limit: Option<i64>,
+    pub used_cost_usd: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_limit_usd: Option<f64>,
+    pub warn_ratio: f64,
+}
+
+/// What a monthly usage budget applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageBudgetScope {
+    Operator,
+    Ghost,
+}
+
+/// Part of a ghost copied by `WsMessage::CloneGhost`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostCloneScope {
+    Soul,
+    Skills,
+    /// Model alias overrides and statusline
+    Settings,
+    /// Private knowledge notes (cloned with fresh IDs)
+    Notes,
+    Diary,
+    /// Private reference files
+    References,
+}
+
+impl GhostCloneScope {
+    /// Copied when a clone request does not list scopes.
+    pub const DEFAULT: &[GhostCloneScope] = &[
+        GhostCloneScope::Soul,
+        GhostCloneScope::Skills,
+        GhostCloneScope::Settings,
+        GhostCloneScope::Notes,
+    ];
 }
 
 /// Ghost info for listing
@@ -253,6 +405,65 @@ pub struct GhostInfo {
     pub name: String,
 }
 
+/// Operator waiting for approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOperatorInfo {
+    pub id: String,
+    pub name: String,
+    /// Platform the operator signed up from (`discord`, `api`, `cli`)
+    pub platform: String,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a pending approval is waiting for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingApprovalKind {
+    /// A tool call needs the operator's approval
+    ToolApproval,
+    /// The tool loop hit its step limit and needs permission to continue
+    ToolLoop,
+}
+
+/// A tool approval or tool-loop continuation waiting on the operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingApprovalInfo {
+    pub ghost_name: String,
+    pub session_id: String,
+    pub kind: PendingApprovalKind,
+    /// The approval prompt, as chat interfaces show it
+    pub summary: String,
+}
+
+/// Operator decision on a pending approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approve,
+    Deny,
+    /// Continue a paused tool loop for this many more steps
+    Steps(usize),
+}
+
+/// File attached to a chat message.
+///
+/// Exactly one of `data` and `url` is set. Images sent by `url` are passed to
+/// the provider by reference; everything else is stored in the ghost workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatAttachment {
+    pub filename: String,
+    /// MIME type; guessed from `filename` when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Base64-encoded file contents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Public `http(s)` URL of an image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
 /// WebSocket message from client to T-KOMA
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -262,6 +473,8 @@ pub enum WsMessage {
         ghost_name: String,
         session_id: String,
         content: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attachments: Vec<ChatAttachment>,
     },
     /// Choose whether this interface binds to a new or existing operator
     SelectInterface { choice: String },
@@ -279,6 +492,35 @@ pub enum WsMessage {
         ghost_name: String,
         session_id: String,
     },
+    /// Full-text search over the operator's session messages with a ghost
+    SearchSessions {
+        ghost_name: String,
+        query: String,
+        /// Restrict to a single session
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_results: Option<usize>,
+    },
+    /// Full message history of a session, oldest first
+    GetSessionMessages {
+        ghost_name: String,
+        session_id: String,
+    },
+    /// List the operator's pending tool approvals and tool-loop continuations
+    ListPendingApprovals,
+    /// Approve, deny or extend a pending approval, like the chat commands
+    ResolvePendingApproval {
+        ghost_name: String,
+        session_id: String,
+        decision: ApprovalDecision,
+    },
+    /// Fork a session into a new active session whose history ends at `message_id`
+    ForkSession {
+        ghost_name: String,
+        session_id: String,
+        message_id: String,
+    },
     /// Select active ghost for the connection
     SelectGhost { ghost_name: String },
     /// List available ghosts for the operator
@@ -292,8 +534,58 @@ pub enum WsMessage {
     ListAvailableModels { provider: ProviderType },
     /// Request gateway restart
     RestartGateway,
+    /// Re-read config.toml and apply it without restarting (admin)
+    ReloadConfig,
     /// Approve an operator (CLI/admin flow handled by gateway)
     ApproveOperator { operator_id: String },
+    /// List operators waiting for approval (CLI/admin)
+    ListPendingOperators,
+    /// Export a session as JSONL (CLI/admin)
+    ExportSession { session_id: String },
+    /// Import a JSONL session export into a ghost, owned by its operator (CLI/admin)
+    ImportSession { ghost_name: String, jsonl: String },
+    /// Aggregate API usage and cost (CLI/admin)
+    GetUsageReport {
+        group_by: UsageReportGrouping,
+        /// Only include the last N days (all time when omitted)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        since_days: Option<u32>,
+        /// Split each group per UTC day
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        daily: bool,
+        /// Also return every usage budget with its month-to-date usage
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        include_budgets: bool,
+    },
+    /// Set a monthly usage budget; omitting both limits removes it (CLI/admin)
+    SetUsageBudget {
+        scope: UsageBudgetScope,
+        /// Operator id, or ghost name
+        subject: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        monthly_token_limit: Option<i64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        monthly_cost_limit_usd: Option<f64>,
+        /// Fraction of a limit at which chats get a warning (default 0.8)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        warn_ratio: Option<f64>,
+    },
+    /// Rename a ghost, moving its workspace and knowledge index (CLI/admin)
+    RenameGhost {
+        ghost_name: String,
+        new_name: String,
+    },
+    /// Create a new ghost as a copy of an existing one (CLI/admin)
+    CloneGhost {
+        source: String,
+        new_name: String,
+        /// Owner of the copy; defaults to the source ghost's owner
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        owner_operator_id: Option<String>,
+        /// What to copy; defaults to `GhostCloneScope::DEFAULT`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        copy: Option<Vec<GhostCloneScope>>,
+    },
     /// Search knowledge entries via gateway
     SearchKnowledge {
         ghost_name: Option<String>,
@@ -314,14 +606,50 @@ pub enum WsMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         max_chars: Option<usize>,
     },
+    /// List reference topics with their files (CLI/admin)
+    ListKnowledgeTopics {
+        #[serde(default)]
+        include_archived: bool,
+    },
+    /// Delete a note or reference file by ID (CLI/admin)
+    DeleteKnowledgeEntry { id: String },
+    /// Set a note's trust score, 0-10 (CLI/admin)
+    SetKnowledgeTrust { id: String, trust_score: i64 },
     /// Get knowledge index statistics
     GetKnowledgeStats,
     /// Get current scheduler state
     GetSchedulerState,
+    /// Cancel a running heartbeat or reflection job by job log ID (CLI/admin)
+    CancelJob { job_id: String },
     /// Ping to keep connection alive
     Ping,
 }
 
+impl WsMessage {
+    /// Whether the message only reads state, so a read-only replica gateway
+    /// can serve it.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::ListSessions { .. }
+                | Self::SearchSessions { .. }
+                | Self::GetSessionMessages { .. }
+                | Self::ListGhosts
+                | Self::ListAvailableModels { .. }
+                | Self::ListPendingOperators
+                | Self::ExportSession { .. }
+                | Self::GetUsageReport { .. }
+                | Self::SearchKnowledge { .. }
+                | Self::ListRecentNotes { .. }
+                | Self::GetKnowledgeEntry { .. }
+                | Self::ListKnowledgeTopics { .. }
+                | Self::GetKnowledgeStats
+                | Self::GetSchedulerState
+                | Self::Ping
+        )
+    }
+}
+
 /// WebSocket response from T-KOMA to client
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -347,6 +675,20 @@ pub enum WsResponse {
     SessionSwitched { session_id: String },
     /// Session deleted successfully
     SessionDeleted { session_id: String },
+    /// Session message search results, best match first
+    SessionSearchResults { results: Vec<SessionSearchHit> },
+    /// Message history of a session, oldest first
+    SessionMessages {
+        session_id: String,
+        messages: Vec<SessionMessageInfo>,
+    },
+    /// The operator's pending approvals; also pushed whenever they change
+    PendingApprovals { approvals: Vec<PendingApprovalInfo> },
+    /// Session forked successfully; the fork is now the active session
+    SessionForked {
+        session_id: String,
+        source_session_id: String,
+    },
     /// Provider selection confirmation
     ProviderSelected { provider: String, model: String },
     /// Available models list
@@ -358,11 +700,55 @@ pub enum WsResponse {
     GatewayRestarting,
     /// Gateway restart flow completed
     GatewayRestarted,
+    /// Config reloaded: sections now in effect and those needing a restart
+    ConfigReloaded {
+        applied: Vec<String>,
+        restart_required: Vec<String>,
+    },
+    /// Operators waiting for approval, oldest first
+    PendingOperators { operators: Vec<PendingOperatorInfo> },
     /// Operator approved successfully (gateway may also have dispatched follow-up prompts)
     OperatorApproved {
         operator_id: String,
         discord_notified: bool,
     },
+    /// JSONL export of a session
+    SessionExported { session_id: String, jsonl: String },
+    /// Session imported as a new, inactive session
+    SessionImported { session_id: String },
+    /// Aggregated usage report
+    UsageReport {
+        group_by: UsageReportGrouping,
+        rows: Vec<UsageReportRow>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        budgets: Vec<UsageBudgetInfo>,
+    },
+    /// Usage budget stored (or removed when `cleared`)
+    UsageBudgetSet {
+        scope: UsageBudgetScope,
+        subject: String,
+        cleared: bool,
+    },
+    /// Ghost cloned
+    GhostCloned {
+        source: String,
+        name: String,
+        /// Knowledge files copied into the new ghost
+        knowledge_files: usize,
+    },
+    /// Ghost renamed
+    GhostRenamed { old_name: String, new_name: String },
+    /// Chat refused: the operator or ghost reached its monthly usage budget
+    UsageBudgetExceeded {
+        scope: UsageBudgetScope,
+        used_tokens: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token_limit: Option<i64>,
+        used_cost_usd: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cost_limit_usd: Option<f64>,
+        message: GatewayMessage,
+    },
     /// Knowledge search results
     KnowledgeSearchResults { results: Vec<KnowledgeResultInfo> },
     /// Recent notes listing
@@ -373,9 +759,22 @@ pub enum WsResponse {
         title: String,
         entry_type: String,
         body: String,
+        /// File on the gateway host
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trust_score: Option<i64>,
     },
+    /// Reference topics with their files
+    KnowledgeTopics { topics: Vec<KnowledgeTopicInfo> },
+    /// Knowledge entry deleted
+    KnowledgeEntryDeleted { id: String },
+    /// Knowledge entry trust score changed
+    KnowledgeTrustSet { id: String, trust_score: i64 },
     /// Current scheduler state
     SchedulerState { entries: Vec<SchedulerEntryInfo> },
+    /// Running job cancelled
+    JobCancelled { job_id: String },
     /// Knowledge index statistics
     KnowledgeStats { stats: KnowledgeIndexStats },
     /// Pong response to ping
@@ -388,6 +787,12 @@ pub struct KnowledgeIndexStats {
     pub total_notes: i64,
     pub total_chunks: i64,
     pub total_embeddings: i64,
+    /// Chunks not yet embedded with the current model.
+    #[serde(default)]
+    pub missing_embeddings: i64,
+    /// Chunks whose note is no longer indexed.
+    #[serde(default)]
+    pub orphaned_chunks: i64,
     pub embedding_model: String,
     pub embedding_dim: u32,
     /// Most recently updated entries (title, entry_type, scope, updated_at).
@@ -415,12 +820,30 @@ pub struct KnowledgeResultInfo {
     pub tags: Vec<String>,
 }
 
+/// Reference topic and its files for TUI display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeTopicInfo {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+    pub files: Vec<KnowledgeResultInfo>,
+}
+
 /// Scheduler entry info for TUI display.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchedulerEntryInfo {
     pub kind: String,
     pub key: String,
     pub next_due: i64,
+    /// Cron expression for recurring entries, with its time zone when not UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Effective idle delay in seconds of heartbeat entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<i64>,
 }
 
 /// Simple UUID generation helper
@@ -451,10 +874,12 @@ mod tests {
             content: "Hello".to_string(),
             ghost_name: "Alpha".to_string(),
             session_id: "sess_123".to_string(),
+            attachments: vec![],
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(json.contains("\"type\":\"chat\""));
         assert!(json.contains("\"content\":\"Hello\""));
+        assert!(!json.contains("attachments"));
 
         let decoded: WsMessage = serde_json::from_str(&json).unwrap();
         match decoded {
@@ -462,6 +887,7 @@ mod tests {
                 content,
                 session_id,
                 ghost_name,
+                ..
             } => {
                 assert_eq!(content, "Hello");
                 assert_eq!(session_id, "sess_123".to_string());
@@ -471,6 +897,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ws_chat_attachments_deserialize() {
+        let json = r#"{"type":"chat","ghost_name":"Alpha","session_id":"active","content":"look",
+            "attachments":[{"filename":"cat.png","data":"iVBORw0="},{"filename":"dog.jpg","url":"https://example.com/dog.jpg"}]}"#;
+        let WsMessage::Chat { attachments, .. } = serde_json::from_str(json).unwrap() else {
+            panic!("Expected Chat variant");
+        };
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].data.as_deref(), Some("iVBORw0="));
+        assert_eq!(attachments[0].mime_type, None);
+        assert_eq!(
+            attachments[1].url.as_deref(),
+            Some("https://example.com/dog.jpg")
+        );
+    }
+
+    #[test]
+    fn test_ws_message_is_read_only() {
+        assert!(WsMessage::Ping.is_read_only());
+        assert!(
+            WsMessage::ListSessions {
+                ghost_name: "Alpha".to_string()
+            }
+            .is_read_only()
+        );
+        assert!(
+            !WsMessage::CreateSession {
+                ghost_name: "Alpha".to_string()
+            }
+            .is_read_only()
+        );
+        assert!(
+            !WsMessage::SelectGhost {
+                ghost_name: "Alpha".to_string()
+            }
+            .is_read_only()
+        );
+    }
+
     #[test]
     fn test_ws_message_session_commands() {
         let msg = WsMessage::CreateSession {
@@ -478,6 +943,139 @@ mod tests {
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(json.contains("\"type\":\"create_session\""));
+
+        let msg = WsMessage::ForkSession {
+            ghost_name: "Alpha".to_string(),
+            session_id: "sess_1".to_string(),
+            message_id: "msg_2".to_string(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"fork_session\""));
+        assert!(json.contains("\"message_id\":\"msg_2\""));
+
+        let msg = WsMessage::SearchSessions {
+            ghost_name: "Alpha".to_string(),
+            query: "kyoto".to_string(),
+            session_id: None,
+            max_results: None,
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"search_sessions\""));
+        assert!(!json.contains("session_id"));
+
+        let msg = WsMessage::ExportSession {
+            session_id: "sess_1".to_string(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"export_session\""));
+
+        let msg = WsMessage::GetUsageReport {
+            group_by: UsageReportGrouping::Day,
+            since_days: Some(7),
+            daily: false,
+            include_budgets: false,
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"get_usage_report\""));
+        assert!(json.contains("\"group_by\":\"day\""));
+        assert!(!json.contains("daily"));
+
+        let msg: WsMessage = serde_json::from_str(
+            r#"{"type":"get_usage_report","group_by":"model","daily":true,"include_budgets":true}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            WsMessage::GetUsageReport {
+                group_by: UsageReportGrouping::Model,
+                daily: true,
+                include_budgets: true,
+                ..
+            }
+        ));
+
+        let msg = WsMessage::SetUsageBudget {
+            scope: UsageBudgetScope::Ghost,
+            subject: "Alpha".to_string(),
+            monthly_token_limit: None,
+            monthly_cost_limit_usd: Some(20.0),
+            warn_ratio: None,
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"set_usage_budget\""));
+        assert!(json.contains("\"scope\":\"ghost\""));
+        assert!(!json.contains("monthly_token_limit"));
+
+        let msg = WsMessage::RenameGhost {
+            ghost_name: "Alpha".to_string(),
+            new_name: "Beta".to_string(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"rename_ghost\""));
+        assert!(json.contains("\"new_name\":\"Beta\""));
+
+        let msg: WsMessage = serde_json::from_str(
+            r#"{"type":"clone_ghost","source":"Alpha","new_name":"Gamma","copy":["soul","diary"]}"#,
+        )
+        .unwrap();
+        match msg {
+            WsMessage::CloneGhost {
+                owner_operator_id,
+                copy,
+                ..
+            } => {
+                assert!(owner_operator_id.is_none());
+                assert_eq!(
+                    copy,
+                    Some(vec![GhostCloneScope::Soul, GhostCloneScope::Diary])
+                );
+            }
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_ws_session_messages_serialization() {
+        let msg = WsMessage::GetSessionMessages {
+            ghost_name: "Alpha".to_string(),
+            session_id: "sess_1".to_string(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"type\":\"get_session_messages\""));
+        assert!(msg.is_read_only());
+
+        let resp = WsResponse::SessionMessages {
+            session_id: "sess_1".to_string(),
+            messages: vec![SessionMessageInfo {
+                id: "msg_1".to_string(),
+                role: MessageRole::Ghost,
+                created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                model: None,
+                blocks: vec![
+                    TranscriptBlock::ToolUse {
+                        id: "tu_1".to_string(),
+                        name: "web_search".to_string(),
+                        input: serde_json::json!({"query": "kyoto"}),
+                    },
+                    TranscriptBlock::Text {
+                        text: "Found it".to_string(),
+                    },
+                ],
+                usage: None,
+                cost_usd: None,
+            }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"session_messages\""));
+        assert!(json.contains("\"type\":\"tool_use\""));
+        assert!(json.contains("\"created_at\":1700000000000"));
+        let decoded: WsResponse = serde_json::from_str(&json).unwrap();
+        match decoded {
+            WsResponse::SessionMessages { messages, .. } => {
+                assert_eq!(messages[0].blocks.len(), 2);
+            }
+            _ => panic!("wrong variant"),
+        }
     }
 
     #[test]
@@ -490,6 +1088,74 @@ mod tests {
         assert!(matches!(decoded, WsMessage::RestartGateway));
     }
 
+    #[test]
+    fn test_ws_config_reload_serialization() {
+        let json = serde_json::to_string(&WsMessage::ReloadConfig).unwrap();
+        assert_eq!(json, r#"{"type":"reload_config"}"#);
+
+        let resp = WsResponse::ConfigReloaded {
+            applied: vec!["compaction".to_string()],
+            restart_required: vec!["gateway".to_string()],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"config_reloaded\""));
+        let decoded: WsResponse = serde_json::from_str(&json).unwrap();
+        match decoded {
+            WsResponse::ConfigReloaded {
+                applied,
+                restart_required,
+            } => {
+                assert_eq!(applied, vec!["compaction"]);
+                assert_eq!(restart_required, vec!["gateway"]);
+            }
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_ws_pending_approvals_serialization() {
+        let msg = WsMessage::ResolvePendingApproval {
+            ghost_name: "alpha".to_string(),
+            session_id: "sess_1".to_string(),
+            decision: ApprovalDecision::Steps(20),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"resolve_pending_approval","ghost_name":"alpha","session_id":"sess_1","decision":{"steps":20}}"#
+        );
+        let decoded: WsMessage = serde_json::from_str(
+            r#"{"type":"resolve_pending_approval","ghost_name":"alpha","session_id":"sess_1","decision":"approve"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            decoded,
+            WsMessage::ResolvePendingApproval {
+                decision: ApprovalDecision::Approve,
+                ..
+            }
+        ));
+
+        let resp = WsResponse::PendingApprovals {
+            approvals: vec![PendingApprovalInfo {
+                ghost_name: "alpha".to_string(),
+                session_id: "sess_1".to_string(),
+                kind: PendingApprovalKind::ToolLoop,
+                summary: "Tool loop paused".to_string(),
+            }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"pending_approvals\""));
+        assert!(json.contains("\"kind\":\"tool_loop\""));
+        let decoded: WsResponse = serde_json::from_str(&json).unwrap();
+        match decoded {
+            WsResponse::PendingApprovals { approvals } => {
+                assert_eq!(approvals[0].kind, PendingApprovalKind::ToolLoop);
+            }
+            _ => panic!("wrong variant"),
+        }
+    }
+
     #[test]
     fn test_ws_response_serialization() {
         let resp = WsResponse::Response {
@@ -525,4 +1191,23 @@ mod tests {
         let decoded: WsResponse = serde_json::from_str(&json).unwrap();
         assert!(matches!(decoded, WsResponse::GatewayRestarting));
     }
+
+    #[test]
+    fn test_ws_pending_operators_serialization() {
+        assert!(WsMessage::ListPendingOperators.is_read_only());
+        let json = serde_json::to_string(&WsMessage::ListPendingOperators).unwrap();
+        assert_eq!(json, "{\"type\":\"list_pending_operators\"}");
+
+        let resp = WsResponse::PendingOperators {
+            operators: vec![PendingOperatorInfo {
+                id: "op_1".to_string(),
+                name: "Newcomer".to_string(),
+                platform: "discord".to_string(),
+                created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("\"type\":\"pending_operators\""));
+        assert!(json.contains("\"created_at\":1700000000000"));
+    }
 }
@@ -0,0 +1,50 @@
+-- Full-text index over the text blocks of session messages.
+--
+-- Rows share the message rowid so triggers can maintain them cheaply;
+-- `message_id` is kept for joins back to `messages`.
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+  text,
+  message_id UNINDEXED,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+CREATE TRIGGER IF NOT EXISTS messages_fts_insert
+AFTER INSERT ON messages BEGIN
+  INSERT INTO messages_fts (rowid, text, message_id)
+  SELECT NEW.rowid, body, NEW.id
+  FROM (
+      SELECT group_concat(json_extract(value, '$.text'), char(10)) AS body
+      FROM json_each(NEW.content)
+      WHERE json_extract(value, '$.type') = 'text'
+    )
+  WHERE body IS NOT NULL;
+END;
+CREATE TRIGGER IF NOT EXISTS messages_fts_delete
+AFTER DELETE ON messages BEGIN
+  DELETE FROM messages_fts WHERE rowid = OLD.rowid;
+END;
+CREATE TRIGGER IF NOT EXISTS messages_fts_update
+AFTER UPDATE OF content ON messages BEGIN
+  DELETE FROM messages_fts WHERE rowid = OLD.rowid;
+  INSERT INTO messages_fts (rowid, text, message_id)
+  SELECT NEW.rowid, body, NEW.id
+  FROM (
+      SELECT group_concat(json_extract(value, '$.text'), char(10)) AS body
+      FROM json_each(NEW.content)
+      WHERE json_extract(value, '$.type') = 'text'
+    )
+  WHERE body IS NOT NULL;
+END;
+-- Backfill existing history.
+INSERT INTO messages_fts (rowid, text, message_id)
+SELECT m.rowid, body, m.id
+FROM (
+    SELECT messages.rowid AS rid,
+      (
+        SELECT group_concat(json_extract(value, '$.text'), char(10))
+        FROM json_each(messages.content)
+        WHERE json_extract(value, '$.type') = 'text'
+      ) AS body
+    FROM messages
+  ) AS extracted
+  JOIN messages m ON m.rowid = extracted.rid
+WHERE body IS NOT NULL;
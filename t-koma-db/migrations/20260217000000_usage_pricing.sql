@@ -0,0 +1,13 @@
+-- Per-model token pricing (USD per million tokens) and per-request cost.
+CREATE TABLE IF NOT EXISTS model_pricing (
+  model TEXT PRIMARY KEY,
+  input_per_mtok REAL NOT NULL DEFAULT 0,
+  output_per_mtok REAL NOT NULL DEFAULT 0,
+  cache_read_per_mtok REAL NOT NULL DEFAULT 0,
+  cache_write_per_mtok REAL NOT NULL DEFAULT 0,
+  updated_at INTEGER NOT NULL
+);
+ALTER TABLE
+  usage_log
+ADD
+  COLUMN cost_usd REAL;
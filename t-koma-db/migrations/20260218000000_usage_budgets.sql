@@ -0,0 +1,10 @@
+-- Monthly token/cost budgets per operator or ghost.
+CREATE TABLE IF NOT EXISTS usage_budgets (
+  scope TEXT NOT NULL CHECK (scope IN ('operator', 'ghost')),
+  subject_id TEXT NOT NULL,
+  monthly_token_limit INTEGER,
+  monthly_cost_limit_usd REAL,
+  warn_ratio REAL NOT NULL DEFAULT 0.8,
+  updated_at INTEGER NOT NULL,
+  PRIMARY KEY (scope, subject_id)
+);
@@ -0,0 +1,12 @@
+-- Per-operator permission overrides. Missing rows fall back to the defaults
+-- of the operator's access level. `permission` is a capability name
+-- (create_ghosts, use_shell, write_shared_knowledge, manage_operators) or
+-- `tool:<tool_name>` for a per-tool grant.
+CREATE TABLE IF NOT EXISTS operator_permissions (
+  operator_id TEXT NOT NULL,
+  permission TEXT NOT NULL,
+  allowed INTEGER NOT NULL,
+  updated_at INTEGER NOT NULL,
+  PRIMARY KEY (operator_id, permission),
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Generated session titles and tags (NULL until the titling job has run).
+ALTER TABLE sessions ADD COLUMN title TEXT;
+-- JSON array of short lowercase tags.
+ALTER TABLE sessions ADD COLUMN tags TEXT;
@@ -0,0 +1,14 @@
+-- Daily aggregates of pruned job logs, so long-term stats survive retention.
+CREATE TABLE IF NOT EXISTS job_log_stats (
+  ghost_id TEXT NOT NULL,
+  job_kind TEXT NOT NULL,
+  -- UTC date of the jobs' started_at (YYYY-MM-DD)
+  day TEXT NOT NULL,
+  run_count INTEGER NOT NULL DEFAULT 0,
+  ok_count INTEGER NOT NULL DEFAULT 0,
+  error_count INTEGER NOT NULL DEFAULT 0,
+  total_duration_secs INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (ghost_id, job_kind, day),
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_job_logs_ghost_started ON job_logs(ghost_id, started_at DESC);
@@ -0,0 +1,40 @@
+-- Append-only audit trail, written in the same transaction as the state
+-- change it records. No foreign keys: events outlive their subjects.
+CREATE TABLE IF NOT EXISTS events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  -- "<subject type>.<action>", e.g. operator.approved
+  kind TEXT NOT NULL,
+  subject_id TEXT NOT NULL,
+  -- Operator who caused the change; NULL for the local admin or the system
+  actor_id TEXT,
+  -- JSON object with kind-specific details
+  data TEXT,
+  created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_events_kind ON events(kind);
+CREATE INDEX IF NOT EXISTS idx_events_subject ON events(subject_id);
+CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at);
+
+CREATE TRIGGER IF NOT EXISTS events_no_update
+BEFORE UPDATE ON events
+BEGIN
+  SELECT RAISE(ABORT, 'events are append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS events_no_delete
+BEFORE DELETE ON events
+BEGIN
+  SELECT RAISE(ABORT, 'events are append-only');
+END;
+
+-- operator_events cascaded away with its operator; carry the surviving rows over.
+INSERT INTO events (kind, subject_id, actor_id, data, created_at)
+SELECT 'operator.' || event_type,
+       operator_id,
+       NULL,
+       CASE WHEN event_data IS NULL THEN NULL ELSE json_object('reason', event_data) END,
+       created_at
+FROM operator_events
+ORDER BY id;
+
+DROP TABLE IF EXISTS operator_events;
@@ -0,0 +1,17 @@
+-- Per-entry size and usage, for LRU/TTL eviction.
+ALTER TABLE prompt_cache ADD COLUMN size_bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE prompt_cache ADD COLUMN hit_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE prompt_cache ADD COLUMN last_hit_at INTEGER;
+UPDATE prompt_cache SET size_bytes = length(CAST(system_blocks_json AS BLOB));
+
+-- Daily hit/miss/eviction counters; kept after entries are evicted.
+CREATE TABLE IF NOT EXISTS prompt_cache_stats (
+  ghost_id TEXT NOT NULL,
+  -- UTC date (YYYY-MM-DD)
+  day TEXT NOT NULL,
+  hits INTEGER NOT NULL DEFAULT 0,
+  misses INTEGER NOT NULL DEFAULT 0,
+  evictions INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (ghost_id, day),
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Cold storage: an archived session keeps its row as a stub while its
+-- messages live in a gzipped JSONL export under the ghost workspace.
+-- Path relative to the ghost workspace (survives ghost renames)
+ALTER TABLE sessions ADD COLUMN archive_path TEXT;
+ALTER TABLE sessions ADD COLUMN archived_at INTEGER;
+-- Message count at archive time, for session listings
+ALTER TABLE sessions ADD COLUMN archived_message_count INTEGER;
+CREATE INDEX IF NOT EXISTS idx_sessions_archived_at ON sessions(archived_at);
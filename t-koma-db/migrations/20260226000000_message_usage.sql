@@ -0,0 +1,8 @@
+-- Provider-reported usage of the request that produced each ghost message.
+-- NULL for operator messages and for messages stored before this migration.
+ALTER TABLE messages ADD COLUMN input_tokens INTEGER;
+ALTER TABLE messages ADD COLUMN output_tokens INTEGER;
+ALTER TABLE messages ADD COLUMN cache_read_tokens INTEGER;
+ALTER TABLE messages ADD COLUMN cache_creation_tokens INTEGER;
+-- USD at the model's pricing when the message was stored; NULL if unpriced
+ALTER TABLE messages ADD COLUMN cost_usd REAL;
@@ -0,0 +1,15 @@
+-- Provider model catalog (e.g. OpenRouter `/models`), refreshed by the gateway.
+CREATE TABLE IF NOT EXISTS model_catalog (
+  provider TEXT NOT NULL,
+  model TEXT NOT NULL,
+  name TEXT NOT NULL,
+  context_length INTEGER,
+  input_per_mtok REAL NOT NULL DEFAULT 0,
+  output_per_mtok REAL NOT NULL DEFAULT 0,
+  cache_read_per_mtok REAL NOT NULL DEFAULT 0,
+  cache_write_per_mtok REAL NOT NULL DEFAULT 0,
+  input_modalities TEXT NOT NULL DEFAULT '[]',
+  output_modalities TEXT NOT NULL DEFAULT '[]',
+  fetched_at INTEGER NOT NULL,
+  PRIMARY KEY (provider, model)
+);
@@ -0,0 +1,20 @@
+-- Interface a session was last chatted from ('discord' or 'ws'), so
+-- background deliveries (reminders) can reach the operator there.
+ALTER TABLE sessions ADD COLUMN origin TEXT;
+
+-- One-shot reminders a ghost scheduled for its operator.
+CREATE TABLE IF NOT EXISTS reminders (
+  id TEXT PRIMARY KEY,
+  ghost_id TEXT NOT NULL,
+  operator_id TEXT NOT NULL,
+  session_id TEXT NOT NULL,
+  message TEXT NOT NULL,
+  due_at INTEGER NOT NULL,
+  created_at INTEGER NOT NULL,
+  delivered_at INTEGER,
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE,
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_reminders_pending ON reminders(delivered_at, due_at);
+CREATE INDEX IF NOT EXISTS idx_reminders_ghost_id ON reminders(ghost_id);
@@ -0,0 +1,25 @@
+-- Per-operator and per-ghost tool policies. Exactly one of operator_id and
+-- ghost_id is set. `tool_name` is a tool name or a prefix pattern ending in
+-- `*` (`mcp__github__*`, `*`); `policy` is allow, deny or auto_approve.
+CREATE TABLE IF NOT EXISTS tool_policies (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  operator_id TEXT,
+  ghost_id TEXT,
+  tool_name TEXT NOT NULL,
+  policy TEXT NOT NULL CHECK (policy IN ('allow', 'deny', 'auto_approve')),
+  updated_at INTEGER NOT NULL,
+  CHECK ((operator_id IS NULL) != (ghost_id IS NULL)),
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE,
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_policies_operator
+  ON tool_policies(operator_id, tool_name) WHERE operator_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_policies_ghost
+  ON tool_policies(ghost_id, tool_name) WHERE ghost_id IS NOT NULL;
+
+-- Per-tool operator grants (`tool:<name>` permissions) become operator policies.
+INSERT INTO tool_policies (operator_id, tool_name, policy, updated_at)
+SELECT operator_id, substr(permission, 6), CASE WHEN allowed THEN 'allow' ELSE 'deny' END, updated_at
+FROM operator_permissions
+WHERE permission LIKE 'tool:%';
+DELETE FROM operator_permissions WHERE permission LIKE 'tool:%';
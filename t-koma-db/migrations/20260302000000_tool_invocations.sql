@@ -0,0 +1,20 @@
+-- One row per tool call made by a ghost, for tool usage reports.
+-- `session_id` is NULL for background jobs; `error` keeps the start of the
+-- error text of failed calls.
+CREATE TABLE IF NOT EXISTS tool_invocations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  ghost_id TEXT NOT NULL,
+  session_id TEXT,
+  tool_name TEXT NOT NULL,
+  duration_ms INTEGER NOT NULL,
+  success INTEGER NOT NULL,
+  output_bytes INTEGER NOT NULL,
+  error TEXT,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE SET NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tool_invocations_created
+  ON tool_invocations(created_at);
+CREATE INDEX IF NOT EXISTS idx_tool_invocations_ghost
+  ON tool_invocations(ghost_id, created_at);
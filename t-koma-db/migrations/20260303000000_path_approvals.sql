@@ -0,0 +1,13 @@
+-- Per-operator approval rules for paths outside ghost workspaces.
+-- `path` is an absolute directory (or file); `access` is read or write, and
+-- a write rule also covers reads.
+CREATE TABLE IF NOT EXISTS path_approvals (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  operator_id TEXT NOT NULL,
+  path TEXT NOT NULL,
+  access TEXT NOT NULL CHECK (access IN ('read', 'write')),
+  updated_at INTEGER NOT NULL,
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_path_approvals_operator
+  ON path_approvals(operator_id, path);
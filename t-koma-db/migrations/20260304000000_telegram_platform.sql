@@ -0,0 +1,16 @@
+-- Allow the `telegram` platform on operators and interfaces.
+--
+-- SQLite cannot alter a CHECK constraint, and the usual rebuild (create a new
+-- table, copy, drop the old one) is off the table here: sqlx runs every
+-- migration inside a transaction, where `PRAGMA foreign_keys = OFF` is a
+-- no-op, so dropping `operators` would cascade through every child table.
+-- Rewriting the stored table definition keeps the rows untouched; the widened
+-- constraint accepts everything the old one did.
+PRAGMA writable_schema = ON;
+UPDATE sqlite_master
+SET sql = replace(sql, '(''discord'', ''api'', ''cli'')', '(''discord'', ''api'', ''cli'', ''telegram'')')
+WHERE type = 'table' AND name IN ('operators', 'interfaces');
+PRAGMA writable_schema = RESET;
+-- Bump the schema cookie so open connections reload the definitions.
+CREATE TABLE _telegram_platform_bump (x INTEGER);
+DROP TABLE _telegram_platform_bump;
@@ -0,0 +1,12 @@
+-- Allow the `slack` platform on operators and interfaces.
+--
+-- Same approach as 20260304000000_telegram_platform.sql: rewrite the stored
+-- CHECK constraint instead of rebuilding the tables.
+PRAGMA writable_schema = ON;
+UPDATE sqlite_master
+SET sql = replace(sql, '(''discord'', ''api'', ''cli'', ''telegram'')', '(''discord'', ''api'', ''cli'', ''telegram'', ''slack'')')
+WHERE type = 'table' AND name IN ('operators', 'interfaces');
+PRAGMA writable_schema = RESET;
+-- Bump the schema cookie so open connections reload the definitions.
+CREATE TABLE _slack_platform_bump (x INTEGER);
+DROP TABLE _slack_platform_bump;
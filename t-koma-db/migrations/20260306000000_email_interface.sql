@@ -0,0 +1,22 @@
+-- Allow the `email` platform on operators and interfaces (same CHECK rewrite
+-- as 20260304000000_telegram_platform.sql).
+PRAGMA writable_schema = ON;
+UPDATE sqlite_master
+SET sql = replace(sql, '(''discord'', ''api'', ''cli'', ''telegram'', ''slack'')', '(''discord'', ''api'', ''cli'', ''telegram'', ''slack'', ''email'')')
+WHERE type = 'table' AND name IN ('operators', 'interfaces');
+PRAGMA writable_schema = RESET;
+-- Bump the schema cookie so open connections reload the definitions.
+CREATE TABLE _email_platform_bump (x INTEGER);
+DROP TABLE _email_platform_bump;
+
+-- Message-IDs of every mail in an email thread, mapped to the session the
+-- thread runs in. Replies are matched through In-Reply-To / References.
+CREATE TABLE IF NOT EXISTS email_messages (
+  message_id TEXT PRIMARY KEY,
+  session_id TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_email_messages_session
+  ON email_messages(session_id, created_at);
@@ -0,0 +1,8 @@
+-- Next due time of each background job, saved on graceful shutdown and
+-- loaded on start so daily jobs and CRON schedules survive restarts.
+CREATE TABLE IF NOT EXISTS scheduler_state (
+  kind TEXT NOT NULL,
+  key TEXT NOT NULL,
+  next_due INTEGER NOT NULL,
+  PRIMARY KEY (kind, key)
+);
@@ -0,0 +1,7 @@
+-- Short-lived claims on background jobs so several gateways sharing one koma
+-- DB never run the same session's heartbeat or reflection twice.
+CREATE TABLE IF NOT EXISTS job_leases (
+  key TEXT PRIMARY KEY,
+  holder TEXT NOT NULL,
+  expires_at INTEGER NOT NULL
+);
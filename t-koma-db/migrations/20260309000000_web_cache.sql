@@ -0,0 +1,16 @@
+-- Persistent web_search / web_fetch results shared by all GHOSTs, so
+-- repeated research survives gateway restarts. Entries past their TTL are
+-- kept for ETag/Last-Modified revalidation until LRU eviction drops them.
+CREATE TABLE IF NOT EXISTS web_cache (
+  kind TEXT NOT NULL,
+  cache_key TEXT NOT NULL,
+  value TEXT NOT NULL,
+  etag TEXT,
+  last_modified TEXT,
+  size_bytes INTEGER NOT NULL,
+  stored_at INTEGER NOT NULL,
+  accessed_at INTEGER NOT NULL,
+  PRIMARY KEY (kind, cache_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_web_cache_accessed_at ON web_cache(accessed_at);
@@ -0,0 +1,3 @@
+-- Cron expression (with time zone) behind recurring scheduler entries, so
+-- the schedule itself survives restarts and can be shown next to next_due.
+ALTER TABLE scheduler_state ADD COLUMN schedule TEXT;
@@ -0,0 +1,19 @@
+-- Recurring jobs a ghost registered for itself with the schedule_task tool.
+CREATE TABLE IF NOT EXISTS scheduled_tasks (
+  id TEXT PRIMARY KEY,
+  ghost_id TEXT NOT NULL,
+  operator_id TEXT NOT NULL,
+  session_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  prompt TEXT NOT NULL,
+  schedule TEXT NOT NULL,
+  enabled INTEGER NOT NULL DEFAULT 1,
+  next_due INTEGER,
+  last_run_at INTEGER,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE,
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_scheduled_tasks_due ON scheduled_tasks(enabled, next_due);
+CREATE INDEX IF NOT EXISTS idx_scheduled_tasks_ghost_id ON scheduled_tasks(ghost_id);
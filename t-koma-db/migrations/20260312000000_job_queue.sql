@@ -0,0 +1,21 @@
+-- Queue of background work (heartbeat, reflection, ingest, scheduled tasks).
+-- Finished jobs are deleted; jobs out of attempts stay with status 'dead'.
+CREATE TABLE IF NOT EXISTS job_queue (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL,
+  dedupe_key TEXT,
+  payload TEXT NOT NULL,
+  priority INTEGER NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'queued',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  max_attempts INTEGER NOT NULL,
+  run_after INTEGER NOT NULL,
+  locked_until INTEGER,
+  last_error TEXT,
+  created_at INTEGER NOT NULL,
+  updated_at INTEGER NOT NULL
+);
+-- At most one pending job per dedupe key.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_job_queue_dedupe ON job_queue(dedupe_key)
+  WHERE dedupe_key IS NOT NULL AND status IN ('queued', 'running');
+CREATE INDEX IF NOT EXISTS idx_job_queue_ready ON job_queue(status, priority, run_after);
@@ -0,0 +1,15 @@
+-- One row per compaction: the summary that replaced a range of messages.
+-- The raw messages stay in `messages`; the range is (previous_cursor_id, cursor_id].
+CREATE TABLE IF NOT EXISTS session_compactions (
+  id TEXT PRIMARY KEY,
+  session_id TEXT NOT NULL,
+  previous_cursor_id TEXT,
+  cursor_id TEXT NOT NULL,
+  message_count INTEGER NOT NULL,
+  summary TEXT NOT NULL,
+  details TEXT,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_session_compactions_session
+  ON session_compactions(session_id, created_at);
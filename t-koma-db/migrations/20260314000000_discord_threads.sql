@@ -0,0 +1,10 @@
+-- Discord threads mapped to the session they run. Messages in a mapped
+-- thread go to its session; `parent_channel_id` is the channel it was opened in.
+CREATE TABLE IF NOT EXISTS discord_threads (
+  thread_id TEXT PRIMARY KEY,
+  session_id TEXT NOT NULL,
+  parent_channel_id TEXT,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_discord_threads_session ON discord_threads(session_id);
@@ -0,0 +1,11 @@
+-- Guild channels bound to a ghost, per operator: the operator's messages in
+-- the channel always go to that ghost instead of the active one.
+CREATE TABLE IF NOT EXISTS discord_channel_ghosts (
+  channel_id TEXT NOT NULL,
+  operator_id TEXT NOT NULL,
+  ghost_id TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  PRIMARY KEY (channel_id, operator_id),
+  FOREIGN KEY (operator_id) REFERENCES operators(id) ON DELETE CASCADE,
+  FOREIGN KEY (ghost_id) REFERENCES ghosts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,394 @@
+//! Scoped bearer tokens for programmatic gateway access.
+//!
+//! A token is shown once when issued; only its SHA-256 hash is stored.
+//! Tokens belong to an operator and carry scopes that the gateway checks
+//! per request. Revoked or expired tokens never authenticate.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
+use crate::operators::{Operator, OperatorRepository};
+
+/// Prefix of every issued token, to make leaked tokens easy to spot.
+pub const API_TOKEN_PREFIX: &str = "tk_";
+
+/// What a token may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Chat, sessions and ghost selection as the token's operator.
+    Chat,
+    /// Admin commands (operator approval, exports, usage reports and budgets).
+    Admin,
+}
+
+impl std::fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiTokenScope::Chat => write!(f, "chat"),
+            ApiTokenScope::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl std::str::FromStr for ApiTokenScope {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chat" => Ok(ApiTokenScope::Chat),
+            "admin" => Ok(ApiTokenScope::Admin),
+            _ => Err(DbError::Serialization(format!("invalid token scope: {s}"))),
+        }
+    }
+}
+
+/// Stored metadata of an issued token (never the token itself).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiToken {
+    pub id: String,
+    pub operator_id: String,
+    pub name: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+}
+
+impl ApiToken {
+    pub fn has_scope(&self, scope: ApiTokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+impl OperatorRepository {
+    /// Issue a new token for an operator.
+    ///
+    /// Returns the stored metadata and the plaintext token, which cannot be
+    /// recovered later.
+    pub async fn issue_api_token(
+        pool: &SqlitePool,
+        operator_id: &str,
+        name: &str,
+        scopes: &[ApiTokenScope],
+        expires_at: Option<i64>,
+    ) -> DbResult<(ApiToken, String)> {
+        if Self::get_by_id(pool, operator_id).await?.is_none() {
+            return Err(DbError::OperatorNotFound(operator_id.to_string()));
+        }
+
+        let secret = format!(
+            "{API_TOKEN_PREFIX}{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+        let token = ApiToken {
+            id: format!("tok_{}", Uuid::new_v4()),
+            operator_id: operator_id.to_string(),
+            name: name.to_string(),
+            scopes: scopes.to_vec(),
+            created_at: Utc::now().timestamp(),
+            last_used_at: None,
+            expires_at,
+            revoked_at: None,
+        };
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO api_tokens (id, operator_id, name, token_hash, scopes, created_at, expires_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&token.id)
+        .bind(&token.operator_id)
+        .bind(&token.name)
+        .bind(hash_token(&secret))
+        .bind(join_scopes(&token.scopes))
+        .bind(token.created_at)
+        .bind(token.expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::ApiTokenIssued, operator_id).with_data(serde_json::json!({
+                "token_id": token.id,
+                "name": token.name,
+                "scopes": token.scopes,
+                "expires_at": token.expires_at,
+            })),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok((token, secret))
+    }
+
+    /// Revoke a token. Returns whether an active token was revoked.
+    pub async fn revoke_api_token(pool: &SqlitePool, token_id: &str) -> DbResult<bool> {
+        let mut tx = pool.begin().await?;
+        let operator_id: Option<String> = sqlx::query_scalar(
+            "UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL
+             RETURNING operator_id",
+        )
+        .bind(Utc::now().timestamp())
+        .bind(token_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(operator_id) = operator_id else {
+            return Ok(false);
+        };
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::ApiTokenRevoked, operator_id)
+                .with_data(serde_json::json!({ "token_id": token_id })),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// List an operator's tokens, newest first (including revoked ones).
+    pub async fn list_api_tokens(pool: &SqlitePool, operator_id: &str) -> DbResult<Vec<ApiToken>> {
+        let rows = sqlx::query_as::<_, ApiTokenRow>(
+            "SELECT id, operator_id, name, scopes, created_at, last_used_at, expires_at, revoked_at
+             FROM api_tokens
+             WHERE operator_id = ?
+             ORDER BY created_at DESC",
+        )
+        .bind(operator_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(ApiToken::try_from).collect()
+    }
+
+    /// Resolve a plaintext bearer token to its operator.
+    ///
+    /// Returns `None` for unknown, revoked or expired tokens. Successful
+    /// lookups update `last_used_at` when the database is writable; a
+    /// read-only replica still authenticates.
+    pub async fn authenticate_api_token(
+        pool: &SqlitePool,
+        token: &str,
+    ) -> DbResult<Option<(Operator, ApiToken)>> {
+        let now = Utc::now().timestamp();
+        let row = sqlx::query_as::<_, ApiTokenRow>(
+            "SELECT id, operator_id, name, scopes, created_at, last_used_at, expires_at, revoked_at
+             FROM api_tokens
+             WHERE token_hash = ?
+               AND revoked_at IS NULL
+               AND (expires_at IS NULL OR expires_at > ?)",
+        )
+        .bind(hash_token(token))
+        .bind(now)
+        .fetch_optional(pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let mut api_token = ApiToken::try_from(row)?;
+
+        let Some(operator) = Self::get_by_id(pool, &api_token.operator_id).await? else {
+            return Ok(None);
+        };
+
+        match sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(&api_token.id)
+            .execute(pool)
+            .await
+        {
+            Ok(_) => api_token.last_used_at = Some(now),
+            Err(e) => warn!("Failed to record API token use for {}: {}", api_token.id, e),
+        }
+
+        Ok(Some((operator, api_token)))
+    }
+}
+
+fn join_scopes(scopes: &[ApiTokenScope]) -> String {
+    scopes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ApiTokenRow {
+    id: String,
+    operator_id: String,
+    name: String,
+    scopes: String,
+    created_at: i64,
+    last_used_at: Option<i64>,
+    expires_at: Option<i64>,
+    revoked_at: Option<i64>,
+}
+
+impl TryFrom<ApiTokenRow> for ApiToken {
+    type Error = DbError;
+
+    fn try_from(row: ApiTokenRow) -> Result<Self, Self::Error> {
+        let scopes = row
+            .scopes
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(ApiToken {
+            id: row.id,
+            operator_id: row.operator_id,
+            name: row.name,
+            scopes,
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            expires_at: row.expires_at,
+            revoked_at: row.revoked_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::{EventFilters, OperatorAccessLevel, Platform, test_helpers::create_test_pool};
+
+    #[tokio::test]
+    async fn test_issue_authenticate_and_revoke() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TokenOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+
+        let (token, secret) = OperatorRepository::issue_api_token(
+            pool,
+            &operator.id,
+            "ci",
+            &[ApiTokenScope::Chat],
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(secret.starts_with(API_TOKEN_PREFIX));
+
+        let (found_operator, found_token) =
+            OperatorRepository::authenticate_api_token(pool, &secret)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(found_operator.id, operator.id);
+        assert!(found_token.has_scope(ApiTokenScope::Chat));
+        assert!(!found_token.has_scope(ApiTokenScope::Admin));
+        assert!(found_token.last_used_at.is_some());
+
+        assert!(
+            OperatorRepository::authenticate_api_token(pool, "tk_wrong")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        assert!(
+            OperatorRepository::revoke_api_token(pool, &token.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            OperatorRepository::authenticate_api_token(pool, &secret)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let listed = OperatorRepository::list_api_tokens(pool, &operator.id)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].revoked_at.is_some());
+
+        assert!(
+            !OperatorRepository::revoke_api_token(pool, &token.id)
+                .await
+                .unwrap()
+        );
+        let events = EventRepository::query(
+            pool,
+            &EventFilters {
+                kind: Some("api_token".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let kinds: Vec<_> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::ApiTokenIssued, EventKind::ApiTokenRevoked]
+        );
+        assert!(events.iter().all(|event| event.subject_id == operator.id));
+        assert_eq!(events[0].data.as_ref().unwrap()["scopes"], json!(["chat"]));
+        assert_eq!(
+            events[1].data.as_ref().unwrap()["token_id"],
+            json!(token.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TokenOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let (_, secret) = OperatorRepository::issue_api_token(
+            pool,
+            &operator.id,
+            "old",
+            &[ApiTokenScope::Chat, ApiTokenScope::Admin],
+            Some(Utc::now().timestamp() - 1),
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            OperatorRepository::authenticate_api_token(pool, &secret)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            OperatorRepository::issue_api_token(pool, "missing", "x", &[], None)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Guild channels bound to a ghost.
+//!
+//! An operator can bind a shared channel to one of their ghosts (`/bind`) so
+//! mentions there always reach it, without the ghost select menu. Bindings
+//! are per operator: each operator in the channel binds their own ghost.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// Repository for `discord_channel_ghosts`.
+pub struct DiscordChannelRepository;
+
+impl DiscordChannelRepository {
+    /// Bind `channel_id` to `ghost_id` for `operator_id`, replacing any
+    /// earlier binding.
+    pub async fn bind_ghost(
+        pool: &SqlitePool,
+        channel_id: &str,
+        operator_id: &str,
+        ghost_id: &str,
+    ) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO discord_channel_ghosts (channel_id, operator_id, ghost_id, created_at)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(channel_id, operator_id) DO UPDATE SET
+                 ghost_id = excluded.ghost_id,
+                 created_at = excluded.created_at",
+        )
+        .bind(channel_id)
+        .bind(operator_id)
+        .bind(ghost_id)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove the operator's binding for a channel. Returns whether one existed.
+    pub async fn unbind_ghost(
+        pool: &SqlitePool,
+        channel_id: &str,
+        operator_id: &str,
+    ) -> DbResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM discord_channel_ghosts WHERE channel_id = ? AND operator_id = ?",
+        )
+        .bind(channel_id)
+        .bind(operator_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Ghost ID the operator bound a channel to, if any.
+    pub async fn bound_ghost(
+        pool: &SqlitePool,
+        channel_id: &str,
+        operator_id: &str,
+    ) -> DbResult<Option<String>> {
+        let ghost_id = sqlx::query_scalar(
+            "SELECT ghost_id FROM discord_channel_ghosts WHERE channel_id = ? AND operator_id = ?",
+        )
+        .bind(channel_id)
+        .bind(operator_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(ghost_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_channel_binding() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let alice = OperatorRepository::create_new(
+            pool,
+            "Alice",
+            Platform::Discord,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let bob = OperatorRepository::create_new(
+            pool,
+            "Bob",
+            Platform::Discord,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let nova = GhostRepository::create(pool, &alice.id, "Nova")
+            .await
+            .unwrap();
+        let echo = GhostRepository::create(pool, &alice.id, "Echo")
+            .await
+            .unwrap();
+        let bobs = GhostRepository::create(pool, &bob.id, "Bobs")
+            .await
+            .unwrap();
+
+        DiscordChannelRepository::bind_ghost(pool, "900", &alice.id, &nova.id)
+            .await
+            .unwrap();
+        DiscordChannelRepository::bind_ghost(pool, "900", &bob.id, &bobs.id)
+            .await
+            .unwrap();
+        DiscordChannelRepository::bind_ghost(pool, "900", &alice.id, &echo.id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            DiscordChannelRepository::bound_ghost(pool, "900", &alice.id)
+                .await
+                .unwrap(),
+            Some(echo.id.clone())
+        );
+        assert_eq!(
+            DiscordChannelRepository::bound_ghost(pool, "900", &bob.id)
+                .await
+                .unwrap(),
+            Some(bobs.id.clone())
+        );
+        assert_eq!(
+            DiscordChannelRepository::bound_ghost(pool, "901", &alice.id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        assert!(
+            DiscordChannelRepository::unbind_ghost(pool, "900", &alice.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !DiscordChannelRepository::unbind_ghost(pool, "900", &alice.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            DiscordChannelRepository::bound_ghost(pool, "900", &bob.id)
+                .await
+                .unwrap(),
+            Some(bobs.id)
+        );
+    }
+}
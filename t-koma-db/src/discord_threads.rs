@@ -0,0 +1,152 @@
+//! Discord threads mapped to sessions.
+//!
+//! In guild channels each conversation runs in its own thread: the bot opens
+//! a thread from the message that mentions it (or adopts a thread the
+//! operator created) and starts a session for it. Later messages in the
+//! thread are routed back to that session.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// A thread and the session it runs.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct DiscordThread {
+    pub thread_id: String,
+    pub session_id: String,
+    pub parent_channel_id: Option<String>,
+    pub created_at: i64,
+}
+
+/// Repository for `discord_threads`.
+pub struct DiscordThreadRepository;
+
+impl DiscordThreadRepository {
+    /// Map `thread_id` to `session_id`, replacing any earlier mapping (`new`
+    /// in a thread moves it to a fresh session).
+    pub async fn record(
+        pool: &SqlitePool,
+        thread_id: &str,
+        session_id: &str,
+        parent_channel_id: Option<&str>,
+    ) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO discord_threads (thread_id, session_id, parent_channel_id, created_at)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(thread_id) DO UPDATE SET session_id = excluded.session_id",
+        )
+        .bind(thread_id)
+        .bind(session_id)
+        .bind(parent_channel_id)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Session a thread runs, if it is mapped.
+    pub async fn find_session(pool: &SqlitePool, thread_id: &str) -> DbResult<Option<String>> {
+        let session_id =
+            sqlx::query_scalar("SELECT session_id FROM discord_threads WHERE thread_id = ?")
+                .bind(thread_id)
+                .fetch_optional(pool)
+                .await?;
+
+        Ok(session_id)
+    }
+
+    /// Most recently mapped thread of a session, to post notices into it.
+    pub async fn thread_for_session(
+        pool: &SqlitePool,
+        session_id: &str,
+    ) -> DbResult<Option<DiscordThread>> {
+        let thread = sqlx::query_as::<_, DiscordThread>(
+            "SELECT thread_id, session_id, parent_channel_id, created_at
+             FROM discord_threads
+             WHERE session_id = ?
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT 1",
+        )
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(thread)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_thread_mapping() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Discord,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let first = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let second = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        DiscordThreadRepository::record(pool, "111", &first.id, Some("900"))
+            .await
+            .unwrap();
+        DiscordThreadRepository::record(pool, "222", &second.id, Some("900"))
+            .await
+            .unwrap();
+        assert_eq!(
+            DiscordThreadRepository::find_session(pool, "111")
+                .await
+                .unwrap(),
+            Some(first.id.clone())
+        );
+        assert_eq!(
+            DiscordThreadRepository::find_session(pool, "333")
+                .await
+                .unwrap(),
+            None
+        );
+
+        // Re-pointing a thread keeps where it was opened.
+        DiscordThreadRepository::record(pool, "111", &second.id, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            DiscordThreadRepository::find_session(pool, "111")
+                .await
+                .unwrap(),
+            Some(second.id.clone())
+        );
+        let thread = DiscordThreadRepository::thread_for_session(pool, &second.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(thread.parent_channel_id.as_deref(), Some("900"));
+        assert!(
+            DiscordThreadRepository::thread_for_session(pool, &first.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Email threads mapped to sessions.
+//!
+//! Every mail of a thread, inbound or sent by the gateway, is recorded by
+//! its Message-ID. A reply names earlier mails in `In-Reply-To` and
+//! `References`, which is enough to find the session it continues.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// The most recent mail of a session's thread.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct EmailThreadMessage {
+    pub message_id: String,
+    pub session_id: String,
+    pub subject: String,
+    pub created_at: i64,
+}
+
+/// Repository for `email_messages`.
+pub struct EmailThreadRepository;
+
+impl EmailThreadRepository {
+    /// Record a mail as part of `session_id`'s thread (idempotent).
+    pub async fn record(
+        pool: &SqlitePool,
+        message_id: &str,
+        session_id: &str,
+        subject: &str,
+    ) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO email_messages (message_id, session_id, subject, created_at)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(message_id) DO NOTHING",
+        )
+        .bind(message_id)
+        .bind(session_id)
+        .bind(subject)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Session of the first known mail among `message_ids`.
+    ///
+    /// Pass `In-Reply-To` first, then `References` newest to oldest, so the
+    /// closest known ancestor wins.
+    pub async fn find_session(
+        pool: &SqlitePool,
+        message_ids: &[String],
+    ) -> DbResult<Option<String>> {
+        for message_id in message_ids {
+            let session_id: Option<String> =
+                sqlx::query_scalar("SELECT session_id FROM email_messages WHERE message_id = ?")
+                    .bind(message_id)
+                    .fetch_optional(pool)
+                    .await?;
+            if session_id.is_some() {
+                return Ok(session_id);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Latest recorded mail of a session, to thread gateway notices into it.
+    pub async fn latest_in_session(
+        pool: &SqlitePool,
+        session_id: &str,
+    ) -> DbResult<Option<EmailThreadMessage>> {
+        let message = sqlx::query_as::<_, EmailThreadMessage>(
+            "SELECT message_id, session_id, subject, created_at
+             FROM email_messages
+             WHERE session_id = ?
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT 1",
+        )
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_thread_lookup() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Mail Operator",
+            Platform::Email,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "MailGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        EmailThreadRepository::record(pool, "<a@example.com>", &session.id, "Plans")
+            .await
+            .unwrap();
+        EmailThreadRepository::record(pool, "<b@koma.local>", &session.id, "Re: Plans")
+            .await
+            .unwrap();
+        // Recording twice is harmless
+        EmailThreadRepository::record(pool, "<a@example.com>", &session.id, "Plans")
+            .await
+            .unwrap();
+
+        let refs = vec!["<unknown@x>".to_string(), "<a@example.com>".to_string()];
+        assert_eq!(
+            EmailThreadRepository::find_session(pool, &refs)
+                .await
+                .unwrap(),
+            Some(session.id.clone())
+        );
+        assert_eq!(
+            EmailThreadRepository::find_session(pool, &["<nope@x>".to_string()])
+                .await
+                .unwrap(),
+            None
+        );
+
+        let latest = EmailThreadRepository::latest_in_session(pool, &session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.message_id, "<b@koma.local>");
+        assert_eq!(latest.subject, "Re: Plans");
+    }
+}
@@ -0,0 +1,293 @@
+//! Optional SQLCipher encryption for `koma.sqlite3`.
+//!
+//! Conversation history lives in the unified DB, so it is the file worth
+//! encrypting. The key comes from `T_KOMA_DB_KEY` or, with the `keyring`
+//! feature, the OS keyring. Encryption itself needs a SQLCipher build of
+//! SQLite (`sqlcipher` feature); setting a key on a plain SQLite build is an
+//! error rather than a silent no-op.
+//!
+//! Existing plaintext databases are converted once with [`encrypt_database`].
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqliteConnection};
+use tracing::info;
+
+use crate::error::{DbError, DbResult};
+
+/// Environment variable holding the database key.
+pub const DB_KEY_ENV: &str = "T_KOMA_DB_KEY";
+
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "t-koma";
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "koma-db";
+
+/// A database passphrase. Never printed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DbKey(String);
+
+impl fmt::Debug for DbKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DbKey(<redacted>)")
+    }
+}
+
+impl DbKey {
+    pub fn new(key: impl Into<String>) -> DbResult<Self> {
+        let key = key.into();
+        if key.trim().is_empty() {
+            return Err(DbError::Encryption("database key is empty".to_string()));
+        }
+        Ok(Self(key))
+    }
+
+    /// Key from `T_KOMA_DB_KEY`, falling back to the OS keyring.
+    ///
+    /// `None` means the database is not encrypted.
+    pub fn resolve() -> DbResult<Option<Self>> {
+        if let Ok(key) = std::env::var(DB_KEY_ENV)
+            && !key.is_empty()
+        {
+            return Self::new(key).map(Some);
+        }
+        Self::from_keyring()
+    }
+
+    #[cfg(feature = "keyring")]
+    fn keyring_entry() -> DbResult<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .map_err(|e| DbError::Encryption(format!("keyring unavailable: {e}")))
+    }
+
+    /// Key stored in the OS keyring, if any.
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring() -> DbResult<Option<Self>> {
+        match Self::keyring_entry()?.get_password() {
+            Ok(key) => Self::new(key).map(Some),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(DbError::Encryption(format!("keyring read failed: {e}"))),
+        }
+    }
+
+    /// Key stored in the OS keyring, if any (always `None` without the
+    /// `keyring` feature).
+    #[cfg(not(feature = "keyring"))]
+    pub fn from_keyring() -> DbResult<Option<Self>> {
+        Ok(None)
+    }
+
+    /// Save this key in the OS keyring so `T_KOMA_DB_KEY` can stay unset.
+    #[cfg(feature = "keyring")]
+    pub fn store_in_keyring(&self) -> DbResult<()> {
+        Self::keyring_entry()?
+            .set_password(&self.0)
+            .map_err(|e| DbError::Encryption(format!("keyring write failed: {e}")))
+    }
+
+    /// Save this key in the OS keyring so `T_KOMA_DB_KEY` can stay unset.
+    #[cfg(not(feature = "keyring"))]
+    pub fn store_in_keyring(&self) -> DbResult<()> {
+        Err(DbError::Encryption(
+            "built without OS keyring support (enable the `keyring` feature)".to_string(),
+        ))
+    }
+
+    /// The key as a quoted SQL string literal for `PRAGMA key` / `ATTACH ... KEY`.
+    fn sql_literal(&self) -> String {
+        format!("'{}'", self.0.replace('\'', "''"))
+    }
+
+    /// Apply this key to connection options (sqlx issues `PRAGMA key` first).
+    pub(crate) fn apply(&self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        options.pragma("key", self.sql_literal())
+    }
+}
+
+/// Fail unless SQLite was built with SQLCipher.
+pub(crate) async fn ensure_sqlcipher(conn: &mut SqliteConnection) -> DbResult<()> {
+    let version: Option<String> = sqlx::query_scalar("PRAGMA cipher_version")
+        .fetch_optional(&mut *conn)
+        .await?;
+    match version {
+        Some(version) if !version.is_empty() => Ok(()),
+        _ => Err(DbError::Encryption(format!(
+            "{DB_KEY_ENV} is set but t-koma was built without SQLCipher \
+             (rebuild with the `sqlcipher` feature)"
+        ))),
+    }
+}
+
+/// Check the key opens the database, with a hint for the usual mistakes.
+pub(crate) async fn verify_readable(conn: &mut SqliteConnection, keyed: bool) -> DbResult<()> {
+    match sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM sqlite_master")
+        .fetch_one(&mut *conn)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) if e.to_string().contains("file is not a database") => {
+            Err(DbError::Encryption(if keyed {
+                "cannot open database: wrong key, or the database is still plaintext \
+                 (run `t-koma-cli db-encrypt`)"
+                    .to_string()
+            } else {
+                format!(
+                    "database is encrypted: set {DB_KEY_ENV} or store the key in the OS keyring"
+                )
+            }))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Encrypt a plaintext database in place with `key`.
+///
+/// The data is exported into a new SQLCipher file next to `path`, which is
+/// verified with the key before it replaces the original (including its
+/// WAL/SHM files). Fails if the database is already encrypted.
+pub async fn encrypt_database(path: &Path, key: &DbKey) -> DbResult<()> {
+    if !path.exists() {
+        return Err(DbError::Encryption(format!(
+            "database not found: {}",
+            path.display()
+        )));
+    }
+    crate::sqlite_runtime::init_sqlite_vec_once()?;
+
+    let target = sidecar(path, ".encrypting");
+    if target.exists() {
+        std::fs::remove_file(&target)?;
+    }
+
+    // `create_if_missing` also lets ATTACH create the target file.
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .connect()
+        .await?;
+    ensure_sqlcipher(&mut conn).await?;
+    verify_readable(&mut conn, false).await?;
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query(&format!(
+        "ATTACH DATABASE ? AS encrypted KEY {}",
+        key.sql_literal()
+    ))
+    .bind(target.to_string_lossy().as_ref())
+    .execute(&mut conn)
+    .await?;
+    sqlx::query("SELECT sqlcipher_export('encrypted')")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("DETACH DATABASE encrypted")
+        .execute(&mut conn)
+        .await?;
+    conn.close().await?;
+
+    let mut check = key
+        .apply(SqliteConnectOptions::new().filename(&target))
+        .connect()
+        .await?;
+    verify_readable(&mut check, true).await?;
+    check.close().await?;
+
+    std::fs::rename(&target, path)?;
+    for suffix in ["-wal", "-shm"] {
+        let file = sidecar(path, suffix);
+        if file.exists() {
+            std::fs::remove_file(file)?;
+        }
+    }
+
+    info!("Encrypted database at {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ENV_MUTEX;
+
+    #[test]
+    fn test_key_is_redacted_and_quoted() {
+        let key = DbKey::new("it's secret").unwrap();
+        assert_eq!(format!("{key:?}"), "DbKey(<redacted>)");
+        assert_eq!(key.sql_literal(), "'it''s secret'");
+        assert!(DbKey::new("  ").is_err());
+    }
+
+    #[test]
+    fn test_resolve_reads_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        // SAFETY: test-scoped env mutation.
+        unsafe { std::env::set_var(DB_KEY_ENV, "hunter2") };
+        let key = DbKey::resolve();
+        // SAFETY: test-scoped env mutation cleanup.
+        unsafe { std::env::remove_var(DB_KEY_ENV) };
+        assert_eq!(key.unwrap(), Some(DbKey::new("hunter2").unwrap()));
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_key_requires_sqlcipher_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("koma.sqlite3");
+        let key = DbKey::new("hunter2").unwrap();
+        let err = crate::sqlite_runtime::create_file_pool(&path, 1, Some(&key))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("without SQLCipher"));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_encrypt_database_round_trip() {
+        use crate::sqlite_runtime::create_file_pool;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("koma.sqlite3");
+        let pool = create_file_pool(&path, 1, None).await.unwrap();
+        sqlx::query("CREATE TABLE notes (body TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO notes VALUES ('private')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let key = DbKey::new("hunter2").unwrap();
+        encrypt_database(&path, &key).await.unwrap();
+        assert!(
+            !std::fs::read(&path)
+                .unwrap()
+                .windows(7)
+                .any(|w| w == b"private")
+        );
+
+        let pool = create_file_pool(&path, 1, Some(&key)).await.unwrap();
+        let body: String = sqlx::query_scalar("SELECT body FROM notes")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(body, "private");
+        pool.close().await;
+
+        let err = create_file_pool(&path, 1, None).await.unwrap_err();
+        assert!(err.to_string().contains("database is encrypted"));
+        let wrong = DbKey::new("nope").unwrap();
+        assert!(create_file_pool(&path, 1, Some(&wrong)).await.is_err());
+        assert!(encrypt_database(&path, &key).await.is_err());
+    }
+}
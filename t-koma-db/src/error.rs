@@ -47,6 +47,10 @@ pub enum DbError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// Database encryption (SQLCipher key) error
+    #[error("Database encryption error: {0}")]
+    Encryption(String),
+
     /// SQLite vec initialization error
     #[error("SQLite-vec initialization error: {0}")]
     SqliteVec(String),
@@ -55,6 +59,10 @@ pub enum DbError {
     #[error("Session not found: {0}")]
     SessionNotFound(String),
 
+    /// Message not found in the given session
+    #[error("Message not found: {0}")]
+    MessageNotFound(String),
+
     /// Unauthorized access
     #[error("Unauthorized access")]
     Unauthorized,
@@ -0,0 +1,439 @@
+//! Append-only audit trail.
+//!
+//! Repositories write an [`Event`] in the same transaction as the state
+//! change it records, so the trail can't miss a committed change or record
+//! one that rolled back. Rows are never updated or deleted (triggers enforce
+//! it) and carry no foreign keys, so they outlive the operators and ghosts
+//! they describe.
+//!
+//! [`EventRepository::query`] with `after_id` doubles as an outbox cursor
+//! for consumers that want every event exactly once.
+
+use std::fmt;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{SqliteExecutor, SqlitePool};
+
+use crate::error::{DbError, DbResult};
+
+/// Default number of events returned by [`EventRepository::query`].
+pub const DEFAULT_EVENT_QUERY_LIMIT: i64 = 100;
+
+/// What happened. Stored as `"<subject type>.<action>"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum EventKind {
+    OperatorCreated,
+    OperatorApproved,
+    OperatorDenied,
+    OperatorWelcomed,
+    OperatorRemoved,
+    OperatorAccessLevelChanged,
+    OperatorRateLimitsChanged,
+    OperatorWorkspaceEscapeChanged,
+    OperatorPermissionChanged,
+    OperatorToolPolicyChanged,
+    OperatorPathApprovalChanged,
+    GhostCreated,
+    GhostRenamed,
+    GhostCloned,
+    GhostDeleted,
+    GhostModelAliasesChanged,
+    GhostStatuslineChanged,
+    GhostToolPolicyChanged,
+    ToolApproved,
+    ToolDenied,
+    ApiTokenIssued,
+    ApiTokenRevoked,
+    ConfigReloaded,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 23] = [
+        EventKind::OperatorCreated,
+        EventKind::OperatorApproved,
+        EventKind::OperatorDenied,
+        EventKind::OperatorWelcomed,
+        EventKind::OperatorRemoved,
+        EventKind::OperatorAccessLevelChanged,
+        EventKind::OperatorRateLimitsChanged,
+        EventKind::OperatorWorkspaceEscapeChanged,
+        EventKind::OperatorPermissionChanged,
+        EventKind::OperatorToolPolicyChanged,
+        EventKind::OperatorPathApprovalChanged,
+        EventKind::GhostCreated,
+        EventKind::GhostRenamed,
+        EventKind::GhostCloned,
+        EventKind::GhostDeleted,
+        EventKind::GhostModelAliasesChanged,
+        EventKind::GhostStatuslineChanged,
+        EventKind::GhostToolPolicyChanged,
+        EventKind::ToolApproved,
+        EventKind::ToolDenied,
+        EventKind::ApiTokenIssued,
+        EventKind::ApiTokenRevoked,
+        EventKind::ConfigReloaded,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::OperatorCreated => "operator.created",
+            EventKind::OperatorApproved => "operator.approved",
+            EventKind::OperatorDenied => "operator.denied",
+            EventKind::OperatorWelcomed => "operator.welcomed",
+            EventKind::OperatorRemoved => "operator.removed",
+            EventKind::OperatorAccessLevelChanged => "operator.access_level_changed",
+            EventKind::OperatorRateLimitsChanged => "operator.rate_limits_changed",
+            EventKind::OperatorWorkspaceEscapeChanged => "operator.workspace_escape_changed",
+            EventKind::OperatorPermissionChanged => "operator.permission_changed",
+            EventKind::OperatorToolPolicyChanged => "operator.tool_policy_changed",
+            EventKind::OperatorPathApprovalChanged => "operator.path_approval_changed",
+            EventKind::GhostCreated => "ghost.created",
+            EventKind::GhostRenamed => "ghost.renamed",
+            EventKind::GhostCloned => "ghost.cloned",
+            EventKind::GhostDeleted => "ghost.deleted",
+            EventKind::GhostModelAliasesChanged => "ghost.model_aliases_changed",
+            EventKind::GhostStatuslineChanged => "ghost.statusline_changed",
+            EventKind::GhostToolPolicyChanged => "ghost.tool_policy_changed",
+            EventKind::ToolApproved => "tool.approved",
+            EventKind::ToolDenied => "tool.denied",
+            EventKind::ApiTokenIssued => "api_token.issued",
+            EventKind::ApiTokenRevoked => "api_token.revoked",
+            EventKind::ConfigReloaded => "config.reloaded",
+        }
+    }
+
+    /// The subject type prefix (`operator`, `ghost`, `tool`, `api_token`, `config`).
+    pub fn subject_type(self) -> &'static str {
+        self.as_str()
+            .split_once('.')
+            .map(|(subject, _)| subject)
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for EventKind {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EventKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| DbError::Serialization(format!("invalid event kind: {s}")))
+    }
+}
+
+impl From<EventKind> for String {
+    fn from(kind: EventKind) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl TryFrom<String> for EventKind {
+    type Error = DbError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// A recorded event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub id: i64,
+    pub kind: EventKind,
+    /// Operator or ghost ID the event is about (`config` for config reloads).
+    pub subject_id: String,
+    /// Operator who caused it; `None` for the local admin or the system.
+    pub actor_id: Option<String>,
+    pub data: Option<Value>,
+    pub created_at: i64,
+}
+
+/// An event to record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEvent {
+    pub kind: EventKind,
+    pub subject_id: String,
+    pub actor_id: Option<String>,
+    pub data: Option<Value>,
+}
+
+impl NewEvent {
+    pub fn new(kind: EventKind, subject_id: impl Into<String>) -> Self {
+        Self {
+            kind,
+            subject_id: subject_id.into(),
+            actor_id: None,
+            data: None,
+        }
+    }
+
+    pub fn with_actor(mut self, actor_id: Option<&str>) -> Self {
+        self.actor_id = actor_id.map(str::to_string);
+        self
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// Optional filters for [`EventRepository::query`].
+#[derive(Debug, Clone, Default)]
+pub struct EventFilters {
+    /// Exact kind (`operator.approved`) or subject type (`operator`).
+    pub kind: Option<String>,
+    pub subject_id: Option<String>,
+    pub actor_id: Option<String>,
+    /// Unix seconds, inclusive.
+    pub since: Option<i64>,
+    /// Unix seconds, inclusive.
+    pub until: Option<i64>,
+    /// Only events with a larger ID (outbox cursor).
+    pub after_id: Option<i64>,
+    /// Maximum events (default [`DEFAULT_EVENT_QUERY_LIMIT`]).
+    pub limit: Option<i64>,
+}
+
+/// Event repository for database operations
+pub struct EventRepository;
+
+impl EventRepository {
+    /// Append an event. Pass the transaction that makes the state change.
+    pub async fn record<'e, E>(executor: E, event: &NewEvent) -> DbResult<i64>
+    where
+        E: SqliteExecutor<'e>,
+    {
+        let data = event
+            .data
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let id = sqlx::query_scalar(
+            "INSERT INTO events (kind, subject_id, actor_id, data, created_at)
+             VALUES (?, ?, ?, ?, ?)
+             RETURNING id",
+        )
+        .bind(event.kind.as_str())
+        .bind(&event.subject_id)
+        .bind(&event.actor_id)
+        .bind(data)
+        .bind(Utc::now().timestamp())
+        .fetch_one(executor)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Matching events in the order they were recorded.
+    ///
+    /// With `after_id` this returns the oldest events past the cursor;
+    /// without it, the newest `limit` events.
+    pub async fn query(pool: &SqlitePool, filters: &EventFilters) -> DbResult<Vec<Event>> {
+        let limit = filters.limit.unwrap_or(DEFAULT_EVENT_QUERY_LIMIT);
+        let kind_prefix = filters.kind.as_ref().map(|kind| format!("{kind}.%"));
+
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, kind, subject_id, actor_id, data, created_at FROM (
+                 SELECT id, kind, subject_id, actor_id, data, created_at
+                 FROM events
+                 WHERE (? IS NULL OR kind = ? OR kind LIKE ?)
+                   AND (? IS NULL OR subject_id = ?)
+                   AND (? IS NULL OR actor_id = ?)
+                   AND (? IS NULL OR created_at >= ?)
+                   AND (? IS NULL OR created_at <= ?)
+                   AND (? IS NULL OR id > ?)
+                 ORDER BY CASE WHEN ? IS NULL THEN -id ELSE id END
+                 LIMIT ?
+             )
+             ORDER BY id ASC",
+        )
+        .bind(&filters.kind)
+        .bind(&filters.kind)
+        .bind(&kind_prefix)
+        .bind(&filters.subject_id)
+        .bind(&filters.subject_id)
+        .bind(&filters.actor_id)
+        .bind(&filters.actor_id)
+        .bind(filters.since)
+        .bind(filters.since)
+        .bind(filters.until)
+        .bind(filters.until)
+        .bind(filters.after_id)
+        .bind(filters.after_id)
+        .bind(filters.after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Event::try_from).collect()
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct EventRow {
+    id: i64,
+    kind: String,
+    subject_id: String,
+    actor_id: Option<String>,
+    data: Option<String>,
+    created_at: i64,
+}
+
+impl TryFrom<EventRow> for Event {
+    type Error = DbError;
+
+    fn try_from(row: EventRow) -> Result<Self, Self::Error> {
+        Ok(Event {
+            id: row.id,
+            kind: row.kind.parse()?,
+            subject_id: row.subject_id,
+            actor_id: row.actor_id,
+            data: row.data.and_then(|data| serde_json::from_str(&data).ok()),
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    #[test]
+    fn test_event_kind_roundtrip() {
+        for kind in EventKind::ALL {
+            assert_eq!(kind.as_str().parse::<EventKind>().unwrap(), kind);
+        }
+        assert_eq!(EventKind::GhostRenamed.subject_type(), "ghost");
+        assert!("operator.exploded".parse::<EventKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_repository_changes_record_events() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Op",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        OperatorRepository::approve(pool, &operator.id)
+            .await
+            .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "Audited")
+            .await
+            .unwrap();
+
+        let kinds: Vec<EventKind> = EventRepository::query(pool, &EventFilters::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::OperatorCreated,
+                EventKind::OperatorApproved,
+                EventKind::GhostCreated
+            ]
+        );
+
+        let ghost_events = EventRepository::query(
+            pool,
+            &EventFilters {
+                kind: Some("ghost".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(ghost_events.len(), 1);
+        assert_eq!(ghost_events[0].subject_id, ghost.id);
+        assert_eq!(
+            ghost_events[0].actor_id.as_deref(),
+            Some(operator.id.as_str())
+        );
+
+        // Newest events without a cursor, oldest past it with one.
+        let newest = EventRepository::query(
+            pool,
+            &EventFilters {
+                limit: Some(1),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(newest[0].kind, EventKind::GhostCreated);
+        let after_first = EventRepository::query(
+            pool,
+            &EventFilters {
+                after_id: Some(ghost_events[0].id - 2),
+                limit: Some(1),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(after_first[0].kind, EventKind::OperatorApproved);
+    }
+
+    #[tokio::test]
+    async fn test_events_are_append_only() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let id = EventRepository::record(
+            pool,
+            &NewEvent::new(EventKind::ToolApproved, "ghost_x")
+                .with_data(serde_json::json!({"tools": ["shell"]})),
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            sqlx::query("UPDATE events SET kind = 'tool.denied' WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await
+                .is_err()
+        );
+        assert!(
+            sqlx::query("DELETE FROM events WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await
+                .is_err()
+        );
+
+        let events = EventRepository::query(pool, &EventFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].data,
+            Some(serde_json::json!({"tools": ["shell"]}))
+        );
+    }
+}
@@ -4,11 +4,12 @@ use chrono::Utc;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::info;
 use uuid::Uuid;
 
 use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
 
 const GHOST_NAME_MAX_LEN: usize = 64;
 
@@ -80,6 +81,34 @@ pub struct GhostToolState {
     pub cwd: Option<String>,
 }
 
+/// What `GhostRepository::clone_from` copies besides the new row.
+///
+/// Knowledge scopes are copied separately by the knowledge engine
+/// (`KnowledgeEngine::clone_ghost_knowledge`), since note IDs must be
+/// reassigned.
+#[derive(Debug, Clone)]
+pub struct GhostCloneOptions {
+    /// Owner of the new ghost; defaults to the source ghost's owner.
+    pub owner_operator_id: Option<String>,
+    /// Copy SOUL.md, with the source's name line rewritten.
+    pub soul: bool,
+    /// Copy the workspace `skills/` directory.
+    pub skills: bool,
+    /// Copy model alias overrides, the statusline flag and tool policies.
+    pub settings: bool,
+}
+
+impl Default for GhostCloneOptions {
+    fn default() -> Self {
+        Self {
+            owner_operator_id: None,
+            soul: true,
+            skills: true,
+            settings: true,
+        }
+    }
+}
+
 /// Ghost repository for database operations
 pub struct GhostRepository;
 
@@ -97,6 +126,7 @@ impl GhostRepository {
         let id = format!("ghost_{}", Uuid::new_v4());
         let now = Utc::now().timestamp();
 
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "INSERT INTO ghosts (id, name, owner_operator_id, cwd, created_at)
              VALUES (?, ?, ?, ?, ?)",
@@ -106,8 +136,17 @@ impl GhostRepository {
         .bind(owner_operator_id)
         .bind(&default_cwd)
         .bind(now)
-        .execute(pool)
+        .execute(&mut *tx)
+        .await?;
+
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostCreated, &id)
+                .with_actor(Some(owner_operator_id))
+                .with_data(serde_json::json!({ "name": name })),
+        )
         .await?;
+        tx.commit().await?;
 
         info!(
             "Created ghost: {} for operator: {}",
@@ -229,36 +268,27 @@ impl GhostRepository {
         name: &str,
         model_aliases: Option<&str>,
     ) -> DbResult<()> {
-        let updated = sqlx::query(
-            "UPDATE ghosts
-             SET model_aliases = ?
-             WHERE name = ?",
-        )
-        .bind(model_aliases)
-        .bind(name)
-        .execute(pool)
-        .await?
-        .rows_affected();
-
-        if updated == 0 {
-            return Err(DbError::GhostNotFound(name.to_string()));
-        }
-
-        Ok(())
+        Self::set_model_alias_column(pool, name, "model_aliases", model_aliases).await
     }
 
     /// Toggle the statusline preference for a ghost.
     pub async fn set_statusline(pool: &SqlitePool, name: &str, enabled: bool) -> DbResult<()> {
-        let updated = sqlx::query("UPDATE ghosts SET statusline = ? WHERE name = ?")
-            .bind(enabled)
-            .bind(name)
-            .execute(pool)
-            .await?
-            .rows_affected();
-
-        if updated == 0 {
-            return Err(DbError::GhostNotFound(name.to_string()));
-        }
+        let mut tx = pool.begin().await?;
+        let id: String =
+            sqlx::query_scalar("UPDATE ghosts SET statusline = ? WHERE name = ? RETURNING id")
+                .bind(enabled)
+                .bind(name)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| DbError::GhostNotFound(name.to_string()))?;
+
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostStatuslineChanged, id)
+                .with_data(serde_json::json!({ "enabled": enabled })),
+        )
+        .await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -268,22 +298,7 @@ impl GhostRepository {
         name: &str,
         model_aliases: Option<&str>,
     ) -> DbResult<()> {
-        let updated = sqlx::query(
-            "UPDATE ghosts
-             SET heartbeat_model_aliases = ?
-             WHERE name = ?",
-        )
-        .bind(model_aliases)
-        .bind(name)
-        .execute(pool)
-        .await?
-        .rows_affected();
-
-        if updated == 0 {
-            return Err(DbError::GhostNotFound(name.to_string()));
-        }
-
-        Ok(())
+        Self::set_model_alias_column(pool, name, "heartbeat_model_aliases", model_aliases).await
     }
 
     /// Update the reflection model alias override for a ghost by name.
@@ -292,40 +307,255 @@ impl GhostRepository {
         name: &str,
         model_aliases: Option<&str>,
     ) -> DbResult<()> {
-        let updated = sqlx::query(
+        Self::set_model_alias_column(pool, name, "reflection_model_aliases", model_aliases).await
+    }
+
+    /// Write one of the `*model_aliases` columns and record the change.
+    async fn set_model_alias_column(
+        pool: &SqlitePool,
+        name: &str,
+        column: &'static str,
+        model_aliases: Option<&str>,
+    ) -> DbResult<()> {
+        let mut tx = pool.begin().await?;
+        let id: String = sqlx::query_scalar(&format!(
             "UPDATE ghosts
-             SET reflection_model_aliases = ?
-             WHERE name = ?",
-        )
+             SET {column} = ?
+             WHERE name = ?
+             RETURNING id"
+        ))
         .bind(model_aliases)
         .bind(name)
-        .execute(pool)
+        .fetch_optional(&mut *tx)
         .await?
-        .rows_affected();
+        .ok_or_else(|| DbError::GhostNotFound(name.to_string()))?;
+
+        let value = model_aliases
+            .map(|raw| serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::from(raw)));
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostModelAliasesChanged, id)
+                .with_data(serde_json::json!({ "column": column, "value": value })),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
-        if updated == 0 {
-            return Err(DbError::GhostNotFound(name.to_string()));
+    /// Rename a ghost and move its workspace directory.
+    ///
+    /// The row update (name and a `cwd` inside the old workspace) runs in a
+    /// transaction that is only committed once the workspace directory has
+    /// been moved; a failed commit moves the directory back. Rows keyed by
+    /// ghost ID (sessions, jobs, usage, budgets) need no changes. Callers
+    /// own the knowledge index and any in-memory name mappings.
+    pub async fn rename(pool: &SqlitePool, old_name: &str, new_name: &str) -> DbResult<Ghost> {
+        let new_name = validate_ghost_name(new_name)?;
+        let ghost = Self::get_by_name(pool, old_name)
+            .await?
+            .ok_or_else(|| DbError::GhostNotFound(old_name.to_string()))?;
+        if ghost.name == new_name {
+            return Ok(ghost);
+        }
+        if let Some(existing) = Self::get_by_name(pool, &new_name).await? {
+            return Err(DbError::GhostNameTaken(existing.name));
         }
 
-        Ok(())
+        let old_path = ghost_workspace_path(&ghost.name)?;
+        let new_path = ghost_workspace_path(&new_name)?;
+        if tokio::fs::try_exists(&new_path).await? {
+            return Err(DbError::GhostNameTaken(format!(
+                "{} (workspace {} already exists)",
+                new_name,
+                new_path.display()
+            )));
+        }
+
+        let cwd = ghost.cwd.as_deref().map(|cwd| {
+            match std::path::Path::new(cwd).strip_prefix(&old_path) {
+                Ok(rest) if rest.as_os_str().is_empty() => new_path.to_string_lossy().to_string(),
+                Ok(rest) => new_path.join(rest).to_string_lossy().to_string(),
+                Err(_) => cwd.to_string(),
+            }
+        });
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE ghosts SET name = ?, cwd = ? WHERE id = ?")
+            .bind(&new_name)
+            .bind(&cwd)
+            .bind(&ghost.id)
+            .execute(&mut *tx)
+            .await?;
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostRenamed, &ghost.id)
+                .with_data(serde_json::json!({ "from": ghost.name, "to": new_name })),
+        )
+        .await?;
+
+        let moved = tokio::fs::try_exists(&old_path).await?;
+        if moved {
+            if let Some(parent) = new_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::rename(&old_path, &new_path).await?;
+        }
+
+        if let Err(e) = tx.commit().await {
+            if moved && let Err(io) = tokio::fs::rename(&new_path, &old_path).await {
+                tracing::error!(
+                    "Failed to move workspace back to {} after rename error: {}",
+                    old_path.display(),
+                    io
+                );
+            }
+            return Err(e.into());
+        }
+
+        info!("Renamed ghost {} to {}", ghost.name, new_name);
+
+        Self::get_by_id(pool, &ghost.id)
+            .await?
+            .ok_or_else(|| DbError::GhostNotFound(ghost.id))
+    }
+
+    /// Create `new_name` as a copy of `source`.
+    ///
+    /// Sessions, jobs and usage are never copied. If copying workspace files
+    /// fails, the new ghost row and workspace are removed again.
+    pub async fn clone_from(
+        pool: &SqlitePool,
+        source: &str,
+        new_name: &str,
+        options: &GhostCloneOptions,
+    ) -> DbResult<Ghost> {
+        let source = Self::get_by_name(pool, source)
+            .await?
+            .ok_or_else(|| DbError::GhostNotFound(source.to_string()))?;
+        let owner = options
+            .owner_operator_id
+            .as_deref()
+            .unwrap_or(&source.owner_operator_id);
+        let ghost = Self::create(pool, owner, new_name).await?;
+
+        let mut tx = pool.begin().await?;
+        if options.settings {
+            sqlx::query(
+                "UPDATE ghosts
+                 SET model_aliases = ?, heartbeat_model_aliases = ?,
+                     reflection_model_aliases = ?, statusline = ?
+                 WHERE id = ?",
+            )
+            .bind(&source.model_aliases)
+            .bind(&source.heartbeat_model_aliases)
+            .bind(&source.reflection_model_aliases)
+            .bind(source.statusline)
+            .bind(&ghost.id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO tool_policies (ghost_id, tool_name, policy, updated_at)
+                 SELECT ?, tool_name, policy, updated_at FROM tool_policies WHERE ghost_id = ?",
+            )
+            .bind(&ghost.id)
+            .bind(&source.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostCloned, &ghost.id)
+                .with_actor(options.owner_operator_id.as_deref())
+                .with_data(serde_json::json!({ "source_id": source.id, "source": source.name })),
+        )
+        .await?;
+        tx.commit().await?;
+
+        let source_path = ghost_workspace_path(&source.name)?;
+        let target_path = ghost_workspace_path(&ghost.name)?;
+        if let Err(e) = copy_workspace_files(
+            &source_path,
+            &target_path,
+            &source.name,
+            &ghost.name,
+            options,
+        )
+        .await
+        {
+            let _ = tokio::fs::remove_dir_all(&target_path).await;
+            Self::delete_by_name(pool, &ghost.name).await?;
+            return Err(e);
+        }
+
+        info!("Cloned ghost {} into {}", source.name, ghost.name);
+
+        Self::get_by_id(pool, &ghost.id)
+            .await?
+            .ok_or_else(|| DbError::GhostNotFound(ghost.id))
     }
 
     /// Delete a ghost by name.
     pub async fn delete_by_name(pool: &SqlitePool, name: &str) -> DbResult<()> {
-        let deleted = sqlx::query("DELETE FROM ghosts WHERE name = ?")
+        let mut tx = pool.begin().await?;
+        let id: String = sqlx::query_scalar("DELETE FROM ghosts WHERE name = ? RETURNING id")
             .bind(name)
-            .execute(pool)
+            .fetch_optional(&mut *tx)
             .await?
-            .rows_affected();
-
-        if deleted == 0 {
-            return Err(DbError::GhostNotFound(name.to_string()));
-        }
+            .ok_or_else(|| DbError::GhostNotFound(name.to_string()))?;
 
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::GhostDeleted, id)
+                .with_data(serde_json::json!({ "name": name })),
+        )
+        .await?;
+        tx.commit().await?;
         Ok(())
     }
 }
 
+async fn copy_workspace_files(
+    source: &Path,
+    target: &Path,
+    source_name: &str,
+    target_name: &str,
+    options: &GhostCloneOptions,
+) -> DbResult<()> {
+    if options.soul
+        && let Ok(soul) = tokio::fs::read_to_string(source.join("SOUL.md")).await
+    {
+        let soul = soul.replace(
+            &format!("I am called {}.", source_name),
+            &format!("I am called {}.", target_name),
+        );
+        tokio::fs::create_dir_all(target).await?;
+        tokio::fs::write(target.join("SOUL.md"), soul).await?;
+    }
+
+    if options.skills && tokio::fs::try_exists(source.join("skills")).await? {
+        let (from, to) = (source.join("skills"), target.join("skills"));
+        tokio::task::spawn_blocking(move || copy_dir_all(&from, &to))
+            .await
+            .map_err(std::io::Error::other)??;
+    }
+
+    Ok(())
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, sqlx::FromRow)]
 struct GhostRow {
     id: String,
@@ -435,6 +665,150 @@ mod tests {
         assert_eq!(remaining[0].name, "Beta");
     }
 
+    #[tokio::test]
+    async fn test_rename_ghost() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Test Operator",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+
+        let alpha = GhostRepository::create(pool, &operator.id, "Rename Alpha")
+            .await
+            .unwrap();
+        GhostRepository::create(pool, &operator.id, "Rename Beta")
+            .await
+            .unwrap();
+
+        let taken = GhostRepository::rename(pool, "Rename Alpha", "Rename Beta").await;
+        assert!(matches!(taken, Err(DbError::GhostNameTaken(_))));
+        let invalid = GhostRepository::rename(pool, "Rename Alpha", "../oops").await;
+        assert!(matches!(invalid, Err(DbError::InvalidGhostName(_))));
+        let missing = GhostRepository::rename(pool, "Nobody", "Rename Gamma").await;
+        assert!(matches!(missing, Err(DbError::GhostNotFound(_))));
+
+        let renamed = GhostRepository::rename(pool, "Rename Alpha", "Rename Gamma")
+            .await
+            .unwrap();
+        assert_eq!(renamed.id, alpha.id);
+        assert_eq!(renamed.name, "Rename Gamma");
+        let expected_cwd = ghost_workspace_path("Rename Gamma").unwrap();
+        assert_eq!(
+            renamed.cwd.as_deref(),
+            Some(expected_cwd.to_string_lossy().as_ref())
+        );
+        assert!(
+            GhostRepository::get_by_name(pool, "Rename Alpha")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clone_ghost_copies_settings() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Test Operator",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let other = OperatorRepository::create_new(
+            pool,
+            "Other Operator",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+
+        let source = GhostRepository::create(pool, &operator.id, "Clone Source")
+            .await
+            .unwrap();
+        let policy = crate::ToolPolicySubject::Ghost(source.id.clone());
+        crate::ToolPolicyRepository::set(
+            pool,
+            &policy,
+            "run_shell_command",
+            Some(crate::ToolPolicy::Deny),
+        )
+        .await
+        .unwrap();
+        GhostRepository::update_model_aliases(pool, "Clone Source", Some("\"fast\""))
+            .await
+            .unwrap();
+        GhostRepository::set_statusline(pool, "Clone Source", true)
+            .await
+            .unwrap();
+
+        let copy = GhostRepository::clone_from(
+            pool,
+            "Clone Source",
+            "Clone Copy",
+            &GhostCloneOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(copy.owner_operator_id, operator.id);
+        assert_eq!(copy.model_aliases.as_deref(), Some("\"fast\""));
+        assert!(copy.statusline);
+        let copied = crate::ToolPolicyRepository::list(
+            pool,
+            &crate::ToolPolicySubject::Ghost(copy.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            copied.get("run_shell_command"),
+            Some(&crate::ToolPolicy::Deny)
+        );
+
+        let bare = GhostRepository::clone_from(
+            pool,
+            "Clone Source",
+            "Clone Bare",
+            &GhostCloneOptions {
+                owner_operator_id: Some(other.id.clone()),
+                settings: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(bare.owner_operator_id, other.id);
+        assert!(bare.model_aliases.is_none());
+        assert!(!bare.statusline);
+        assert!(
+            crate::ToolPolicyRepository::list(
+                pool,
+                &crate::ToolPolicySubject::Ghost(bare.id.clone())
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+
+        let taken = GhostRepository::clone_from(
+            pool,
+            "Clone Source",
+            "Clone Copy",
+            &GhostCloneOptions::default(),
+        )
+        .await;
+        assert!(matches!(taken, Err(DbError::GhostNameTaken(_))));
+    }
+
     #[tokio::test]
     async fn test_set_statusline() {
         let db = create_test_pool().await.unwrap();
@@ -98,6 +98,26 @@ impl InterfaceRepository {
 
         Ok(rows.into_iter().map(Interface::from).collect())
     }
+
+    /// Delete an interface by ID; the operator keeps its other interfaces
+    pub async fn delete(pool: &SqlitePool, id: &str) -> DbResult<Interface> {
+        let row = sqlx::query_as::<_, InterfaceRow>(
+            "DELETE FROM interfaces
+             WHERE id = ?
+             RETURNING id, operator_id, platform, external_id, display_name, created_at",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DbError::InterfaceNotFound(id.to_string()))?;
+
+        let interface = Interface::from(row);
+        info!(
+            "Deleted interface {} of operator {} ({:?})",
+            interface.external_id, interface.operator_id, interface.platform
+        );
+        Ok(interface)
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -162,4 +182,111 @@ mod tests {
 
         assert_eq!(found.id, iface.id);
     }
+
+    #[tokio::test]
+    async fn test_telegram_interface() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Telegram Operator",
+            Platform::Telegram,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        InterfaceRepository::create(pool, &operator.id, Platform::Telegram, "4242", "tg user")
+            .await
+            .unwrap();
+
+        let found = InterfaceRepository::get_by_external_id(pool, Platform::Telegram, "4242")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.platform, Platform::Telegram);
+        let operator = OperatorRepository::get_by_id(pool, &operator.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(operator.platform, Platform::Telegram);
+        assert!(
+            InterfaceRepository::get_by_external_id(pool, Platform::Discord, "4242")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slack_interface() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Slack Operator",
+            Platform::Slack,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        InterfaceRepository::create(
+            pool,
+            &operator.id,
+            Platform::Slack,
+            "U024BE7LH",
+            "slack user",
+        )
+        .await
+        .unwrap();
+
+        let found = InterfaceRepository::get_by_external_id(pool, Platform::Slack, "U024BE7LH")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.platform, Platform::Slack);
+        let operator = OperatorRepository::get_by_id(pool, &operator.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(operator.platform, Platform::Slack);
+    }
+
+    #[tokio::test]
+    async fn test_delete_interface() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "Two Interfaces",
+            Platform::Discord,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let discord =
+            InterfaceRepository::create(pool, &operator.id, Platform::Discord, "d-1", "d user")
+                .await
+                .unwrap();
+        InterfaceRepository::create(pool, &operator.id, Platform::Telegram, "t-1", "t user")
+            .await
+            .unwrap();
+
+        let deleted = InterfaceRepository::delete(pool, &discord.id)
+            .await
+            .unwrap();
+        assert_eq!(deleted.external_id, "d-1");
+
+        let remaining = InterfaceRepository::list_by_operator(pool, &operator.id)
+            .await
+            .unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].platform, Platform::Telegram);
+        assert!(matches!(
+            InterfaceRepository::delete(pool, &discord.id).await,
+            Err(DbError::InterfaceNotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,132 @@
+//! Leases on background jobs shared by gateways using the same koma DB.
+//!
+//! A gateway claims a key (e.g. `heartbeat:<session_id>`) before running the
+//! job and releases it afterwards. A lease left behind by a crashed gateway
+//! expires on its own, so another instance can take over.
+
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// Repository for `job_leases`.
+pub struct JobLeaseRepository;
+
+impl JobLeaseRepository {
+    /// Claim `key` for `holder` until `now + ttl_secs`.
+    ///
+    /// Succeeds when the key is free, expired, or already held by `holder`
+    /// (which renews it). Returns `false` when another holder has it.
+    pub async fn try_acquire(
+        pool: &SqlitePool,
+        key: &str,
+        holder: &str,
+        now: i64,
+        ttl_secs: i64,
+    ) -> DbResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO job_leases (key, holder, expires_at) VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE
+              SET holder = excluded.holder, expires_at = excluded.expires_at
+              WHERE job_leases.holder = excluded.holder OR job_leases.expires_at <= ?
+            "#,
+        )
+        .bind(key)
+        .bind(holder)
+        .bind(now + ttl_secs)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Release `key` if `holder` still has it.
+    pub async fn release(pool: &SqlitePool, key: &str, holder: &str) -> DbResult<bool> {
+        let result = sqlx::query("DELETE FROM job_leases WHERE key = ? AND holder = ?")
+            .bind(key)
+            .bind(holder)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Release every lease `holder` has, e.g. on shutdown.
+    pub async fn release_all(pool: &SqlitePool, holder: &str) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM job_leases WHERE holder = ?")
+            .bind(holder)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete leases that expired at or before `now`.
+    pub async fn prune_expired(pool: &SqlitePool, now: i64) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM job_leases WHERE expires_at <= ?")
+            .bind(now)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_pool;
+
+    #[tokio::test]
+    async fn test_lease_is_exclusive_until_released_or_expired() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let key = "heartbeat:sess_1";
+
+        assert!(
+            JobLeaseRepository::try_acquire(pool, key, "a", 100, 60)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !JobLeaseRepository::try_acquire(pool, key, "b", 120, 60)
+                .await
+                .unwrap()
+        );
+        // The holder can renew its own lease.
+        assert!(
+            JobLeaseRepository::try_acquire(pool, key, "a", 130, 60)
+                .await
+                .unwrap()
+        );
+
+        // Only the holder can release it.
+        assert!(!JobLeaseRepository::release(pool, key, "b").await.unwrap());
+        assert!(JobLeaseRepository::release(pool, key, "a").await.unwrap());
+        assert!(
+            JobLeaseRepository::try_acquire(pool, key, "b", 140, 60)
+                .await
+                .unwrap()
+        );
+
+        // An expired lease can be taken over.
+        assert!(
+            JobLeaseRepository::try_acquire(pool, key, "a", 200, 60)
+                .await
+                .unwrap()
+        );
+
+        assert!(
+            JobLeaseRepository::try_acquire(pool, "reflection:sess_1", "a", 200, 60)
+                .await
+                .unwrap()
+        );
+        assert!(
+            JobLeaseRepository::try_acquire(pool, "cron:daily:300", "b", 200, 60)
+                .await
+                .unwrap()
+        );
+        assert_eq!(JobLeaseRepository::release_all(pool, "a").await.unwrap(), 2);
+        assert_eq!(
+            JobLeaseRepository::prune_expired(pool, 260).await.unwrap(),
+            1
+        );
+    }
+}
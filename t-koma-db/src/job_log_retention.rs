@@ -0,0 +1,312 @@
+//! Job log retention.
+//!
+//! Heartbeat and reflection write a row per run, so `job_logs` grows without
+//! bound. [`JobLogRepository::prune`] deletes rows past an age or per-ghost
+//! count limit, folding them into daily `job_log_stats` aggregates first so
+//! long-term counts survive.
+//!
+//! The newest successful run per session and job kind is always kept: the
+//! heartbeat skip guard and reflection handoff notes read it.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::error::DbResult;
+use crate::job_logs::{JobKind, JobLogRepository};
+
+/// Retention limits. `None` disables a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobLogRetention {
+    /// Delete runs started more than this many days ago.
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many runs per ghost (newest first).
+    pub max_per_ghost: Option<u32>,
+}
+
+impl JobLogRetention {
+    pub fn is_unbounded(&self) -> bool {
+        self.max_age_days.is_none() && self.max_per_ghost.is_none()
+    }
+}
+
+/// Run counts for one job kind, live rows plus pruned aggregates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobKindStats {
+    pub job_kind: JobKind,
+    pub run_count: i64,
+    pub ok_count: i64,
+    pub error_count: i64,
+    pub total_duration_secs: i64,
+}
+
+/// Rows selected for pruning. Binds: `?1` age cutoff (unix seconds),
+/// `?2` per-ghost row limit.
+const PRUNE_CANDIDATES: &str = "
+    WITH ranked AS (
+        SELECT id, started_at, finished_at,
+               ROW_NUMBER() OVER (
+                   PARTITION BY ghost_id ORDER BY started_at DESC, id DESC
+               ) AS ghost_rank
+        FROM job_logs
+    ),
+    latest_ok AS (
+        SELECT id FROM (
+            SELECT id,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY session_id, job_kind ORDER BY started_at DESC, id DESC
+                   ) AS rn
+            FROM job_logs
+            WHERE status IS NOT NULL AND status NOT LIKE 'error:%'
+        )
+        WHERE rn = 1
+    ),
+    doomed AS (
+        SELECT id FROM ranked
+        WHERE (started_at < ?1 OR (ghost_rank > ?2 AND finished_at IS NOT NULL))
+          AND id NOT IN (SELECT id FROM latest_ok)
+    )";
+
+impl JobLogRepository {
+    /// Delete job logs outside `policy`, aggregating them into
+    /// `job_log_stats` in the same transaction.
+    ///
+    /// Returns the number of deleted rows.
+    pub async fn prune(pool: &SqlitePool, policy: &JobLogRetention) -> DbResult<u64> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+        let cutoff = policy
+            .max_age_days
+            .map(|days| Utc::now().timestamp() - i64::from(days) * 86_400)
+            .unwrap_or(i64::MIN);
+        let max_rank = policy.max_per_ghost.map(i64::from).unwrap_or(i64::MAX);
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(&format!(
+            "{PRUNE_CANDIDATES}
+             INSERT INTO job_log_stats
+                 (ghost_id, job_kind, day, run_count, ok_count, error_count, total_duration_secs)
+             SELECT ghost_id, job_kind, date(started_at, 'unixepoch'),
+                    COUNT(*),
+                    SUM(CASE WHEN status IS NOT NULL AND status NOT LIKE 'error:%' THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN status LIKE 'error:%' THEN 1 ELSE 0 END),
+                    SUM(COALESCE(finished_at - started_at, 0))
+             FROM job_logs
+             WHERE id IN (SELECT id FROM doomed)
+             GROUP BY ghost_id, job_kind, date(started_at, 'unixepoch')
+             ON CONFLICT(ghost_id, job_kind, day) DO UPDATE SET
+                 run_count = run_count + excluded.run_count,
+                 ok_count = ok_count + excluded.ok_count,
+                 error_count = error_count + excluded.error_count,
+                 total_duration_secs = total_duration_secs + excluded.total_duration_secs"
+        ))
+        .bind(cutoff)
+        .bind(max_rank)
+        .execute(&mut *tx)
+        .await?;
+
+        let deleted = sqlx::query(&format!(
+            "{PRUNE_CANDIDATES}
+             DELETE FROM job_logs WHERE id IN (SELECT id FROM doomed)"
+        ))
+        .bind(cutoff)
+        .bind(max_rank)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        if deleted > 0 {
+            info!("Pruned {} job log(s)", deleted);
+        }
+        Ok(deleted)
+    }
+
+    /// All-time run counts per job kind for a ghost.
+    pub async fn stats(pool: &SqlitePool, ghost_id: &str) -> DbResult<Vec<JobKindStats>> {
+        let rows = sqlx::query_as::<_, (String, i64, i64, i64, i64)>(
+            "SELECT job_kind, SUM(run_count), SUM(ok_count), SUM(error_count),
+                    SUM(total_duration_secs)
+             FROM (
+                 SELECT job_kind, run_count, ok_count, error_count, total_duration_secs
+                 FROM job_log_stats
+                 WHERE ghost_id = ?
+                 UNION ALL
+                 SELECT job_kind, 1,
+                        CASE WHEN status IS NOT NULL AND status NOT LIKE 'error:%' THEN 1 ELSE 0 END,
+                        CASE WHEN status LIKE 'error:%' THEN 1 ELSE 0 END,
+                        COALESCE(finished_at - started_at, 0)
+                 FROM job_logs
+                 WHERE ghost_id = ?
+             )
+             GROUP BY job_kind
+             ORDER BY job_kind",
+        )
+        .bind(ghost_id)
+        .bind(ghost_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter()
+            .map(
+                |(job_kind, run_count, ok_count, error_count, total_duration_secs)| {
+                    Ok(JobKindStats {
+                        job_kind: job_kind.parse()?,
+                        run_count,
+                        ok_count,
+                        error_count,
+                        total_duration_secs,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_logs::JobLog;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        test_helpers::create_test_pool,
+    };
+
+    async fn insert_run(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        kind: JobKind,
+        started_at: i64,
+        status: &str,
+    ) -> String {
+        let mut log = JobLog::start(ghost_id, kind, session_id);
+        log.finish(status);
+        log.started_at = started_at;
+        log.finished_at = Some(started_at + 10);
+        JobLogRepository::insert(pool, &log).await.unwrap();
+        log.id
+    }
+
+    async fn count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM job_logs")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_age_and_count_keeps_latest_ok() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+        let day = 86_400;
+        // Old runs: two heartbeats (one error) and the only reflection.
+        insert_run(
+            pool,
+            &ghost.id,
+            &session.id,
+            JobKind::Heartbeat,
+            now - 40 * day,
+            "ok",
+        )
+        .await;
+        insert_run(
+            pool,
+            &ghost.id,
+            &session.id,
+            JobKind::Heartbeat,
+            now - 40 * day + 60,
+            "error: boom",
+        )
+        .await;
+        let reflection = insert_run(
+            pool,
+            &ghost.id,
+            &session.id,
+            JobKind::Reflection,
+            now - 35 * day,
+            "ok",
+        )
+        .await;
+        // Recent heartbeats.
+        for i in 0..4 {
+            insert_run(
+                pool,
+                &ghost.id,
+                &session.id,
+                JobKind::Heartbeat,
+                now - (4 - i) * 60,
+                "ran",
+            )
+            .await;
+        }
+        let before = JobLogRepository::stats(pool, &ghost.id).await.unwrap();
+
+        assert_eq!(
+            JobLogRepository::prune(pool, &JobLogRetention::default())
+                .await
+                .unwrap(),
+            0
+        );
+
+        let policy = JobLogRetention {
+            max_age_days: Some(30),
+            max_per_ghost: Some(3),
+        };
+        // Old heartbeats go by age; the newest recent heartbeat beyond the
+        // count limit goes too. The lone reflection is its kind's latest ok run.
+        assert_eq!(JobLogRepository::prune(pool, &policy).await.unwrap(), 3);
+        assert_eq!(count(pool).await, 4);
+        assert!(
+            JobLogRepository::get(pool, &reflection)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            JobLogRepository::latest_ok(pool, &ghost.id, &session.id, JobKind::Heartbeat)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // Aggregates keep the all-time totals intact.
+        let after = JobLogRepository::stats(pool, &ghost.id).await.unwrap();
+        assert_eq!(after, before);
+        let heartbeat = after
+            .iter()
+            .find(|s| s.job_kind == JobKind::Heartbeat)
+            .unwrap();
+        assert_eq!(heartbeat.run_count, 6);
+        assert_eq!(heartbeat.error_count, 1);
+        assert_eq!(heartbeat.total_duration_secs, 60);
+
+        // Pruning again is a no-op.
+        assert_eq!(JobLogRepository::prune(pool, &policy).await.unwrap(), 0);
+        assert_eq!(
+            JobLogRepository::stats(pool, &ghost.id).await.unwrap(),
+            before
+        );
+    }
+}
@@ -1,12 +1,12 @@
-//! Job log storage for background tasks (heartbeat, reflection, cron).
+//! Job log storage for background tasks (heartbeat, reflection, cron, ingest).
 //!
 //! Job lifecycle:
 //! 1. `insert_started()` — INSERT at job start (TUI sees "in progress")
-//! 2. `update_todos()` — UPDATE `todo_list` column mid-run (reflection observability)
+//! 2. `update_todos()` / `update_transcript()` — UPDATE mid-run (observability)
 //! 3. `finish()` — UPDATE `finished_at`, `status`, `transcript`, `handoff_note`
 //!
 //! The legacy `insert()` method persists a fully-populated row in one shot
-//! (used by heartbeat which is short-lived and doesn't need mid-run visibility).
+//! (used by cron jobs, which don't need mid-run visibility).
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,7 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::error::{DbError, DbResult};
+use crate::job_todos::TodoRecurrence;
 use crate::sessions::{ContentBlock, MessageRole};
 
 /// The kind of background job.
@@ -23,6 +24,8 @@ pub enum JobKind {
     Heartbeat,
     Reflection,
     Cron,
+    Ingest,
+    Task,
 }
 
 impl std::fmt::Display for JobKind {
@@ -31,6 +34,8 @@ impl std::fmt::Display for JobKind {
             JobKind::Heartbeat => write!(f, "heartbeat"),
             JobKind::Reflection => write!(f, "reflection"),
             JobKind::Cron => write!(f, "cron"),
+            JobKind::Ingest => write!(f, "ingest"),
+            JobKind::Task => write!(f, "task"),
         }
     }
 }
@@ -43,6 +48,8 @@ impl std::str::FromStr for JobKind {
             "heartbeat" => Ok(JobKind::Heartbeat),
             "reflection" => Ok(JobKind::Reflection),
             "cron" => Ok(JobKind::Cron),
+            "ingest" => Ok(JobKind::Ingest),
+            "task" => Ok(JobKind::Task),
             _ => Err(DbError::Serialization(format!("invalid job kind: {s}"))),
         }
     }
@@ -78,7 +85,10 @@ impl std::fmt::Display for TodoStatus {
     }
 }
 
-/// A single item in a reflection job's TODO list.
+/// A single item in a background job's TODO list.
+///
+/// Items with a `due_at` or `recurrence` are *scheduled*: they carry over
+/// into later heartbeat/reflection runs until done (see `job_todos`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoItem {
     pub title: String,
@@ -87,6 +97,29 @@ pub struct TodoItem {
     pub status: TodoStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    /// Unix timestamp (seconds) when the item becomes due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<TodoRecurrence>,
+    /// Titles of items in the same list that must be done first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_by: Vec<String>,
+}
+
+impl TodoItem {
+    /// A new pending, unscheduled item.
+    pub fn pending(title: impl Into<String>, description: Option<String>) -> Self {
+        Self {
+            title: title.into(),
+            description,
+            status: TodoStatus::Pending,
+            note: None,
+            due_at: None,
+            recurrence: None,
+            blocked_by: Vec::new(),
+        }
+    }
 }
 
 /// A job log row.
@@ -153,7 +186,7 @@ pub struct JobLogRepository;
 impl JobLogRepository {
     /// Insert a fully-populated job log in one shot.
     ///
-    /// Used by short-lived jobs (heartbeat) that don't need mid-run observability.
+    /// Used by short-lived jobs (cron) that don't need mid-run observability.
     pub async fn insert(pool: &SqlitePool, log: &JobLog) -> DbResult<()> {
         let transcript_json = serde_json::to_string(&log.transcript)
             .map_err(|e| DbError::Serialization(e.to_string()))?;
@@ -213,6 +246,24 @@ impl JobLogRepository {
         Ok(())
     }
 
+    /// UPDATE the transcript of a running job so far.
+    pub async fn update_transcript(
+        pool: &SqlitePool,
+        id: &str,
+        transcript: &[TranscriptEntry],
+    ) -> DbResult<()> {
+        let json =
+            serde_json::to_string(transcript).map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        sqlx::query("UPDATE job_logs SET transcript = ? WHERE id = ?")
+            .bind(&json)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
     /// UPDATE a started job log with final status, transcript, and handoff note.
     pub async fn finish(
         pool: &SqlitePool,
@@ -349,6 +400,33 @@ impl JobLogRepository {
 
         row.map(JobLog::try_from).transpose()
     }
+
+    /// Statuses of the last `limit` finished, non-error jobs of the given
+    /// kind, newest first.
+    pub async fn recent_statuses(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        kind: JobKind,
+        limit: u32,
+    ) -> DbResult<Vec<String>> {
+        let statuses = sqlx::query_scalar::<_, String>(
+            "SELECT status
+             FROM job_logs
+             WHERE ghost_id = ? AND session_id = ? AND job_kind = ?
+               AND status IS NOT NULL AND status NOT LIKE 'error:%'
+             ORDER BY started_at DESC
+             LIMIT ?",
+        )
+        .bind(ghost_id)
+        .bind(session_id)
+        .bind(kind.to_string())
+        .bind(i64::from(limit))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(statuses)
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -379,7 +457,7 @@ struct JobLogSummaryRow {
     handoff_note: Option<String>,
 }
 
-fn parse_optional_json<T: serde::de::DeserializeOwned>(json: Option<&str>) -> Vec<T> {
+pub(crate) fn parse_optional_json<T: serde::de::DeserializeOwned>(json: Option<&str>) -> Vec<T> {
     json.and_then(|s| serde_json::from_str(s).ok())
         .unwrap_or_default()
 }
@@ -542,6 +620,50 @@ mod tests {
         assert!(found.is_none());
     }
 
+    #[tokio::test]
+    async fn test_recent_statuses() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        for (offset, status) in ["ran", "error: boom", "ok", "ok"].iter().enumerate() {
+            let mut log = JobLog::start(&ghost.id, JobKind::Heartbeat, &session.id);
+            log.started_at += offset as i64;
+            log.finish(status);
+            JobLogRepository::insert(pool, &log).await.unwrap();
+        }
+        let unfinished = JobLog::start(&ghost.id, JobKind::Heartbeat, &session.id);
+        JobLogRepository::insert_started(pool, &unfinished)
+            .await
+            .unwrap();
+
+        let statuses =
+            JobLogRepository::recent_statuses(pool, &ghost.id, &session.id, JobKind::Heartbeat, 5)
+                .await
+                .unwrap();
+        assert_eq!(statuses, vec!["ok", "ok", "ran"]);
+
+        let statuses =
+            JobLogRepository::recent_statuses(pool, &ghost.id, &session.id, JobKind::Heartbeat, 1)
+                .await
+                .unwrap();
+        assert_eq!(statuses, vec!["ok"]);
+    }
+
     #[tokio::test]
     async fn test_insert_started_and_finish() {
         let db = create_test_pool().await.unwrap();
@@ -572,12 +694,9 @@ mod tests {
         assert!(fetched.finished_at.is_none());
 
         // Update todos mid-run
-        let todos = vec![TodoItem {
-            title: "Review conversation".to_string(),
-            description: None,
-            status: TodoStatus::InProgress,
-            note: None,
-        }];
+        let mut todo = TodoItem::pending("Review conversation", None);
+        todo.status = TodoStatus::InProgress;
+        let todos = vec![todo];
         JobLogRepository::update_todos(pool, &log_id, &todos)
             .await
             .unwrap();
@@ -586,14 +705,29 @@ mod tests {
         assert_eq!(fetched.todo_list.len(), 1);
         assert_eq!(fetched.todo_list[0].status, TodoStatus::InProgress);
 
+        // Transcript grows mid-run while the job stays in progress
+        let mut transcript = vec![TranscriptEntry {
+            role: MessageRole::Operator,
+            content: vec![ContentBlock::Text {
+                text: "reflect".to_string(),
+            }],
+            model: None,
+        }];
+        JobLogRepository::update_transcript(pool, &log_id, &transcript)
+            .await
+            .unwrap();
+        let fetched = JobLogRepository::get(pool, &log_id).await.unwrap().unwrap();
+        assert_eq!(fetched.transcript.len(), 1);
+        assert!(fetched.status.is_none());
+
         // Finish with transcript and handoff
-        let transcript = vec![TranscriptEntry {
+        transcript.push(TranscriptEntry {
             role: MessageRole::Ghost,
             content: vec![ContentBlock::Text {
                 text: "done".to_string(),
             }],
             model: None,
-        }];
+        });
         JobLogRepository::finish(
             pool,
             &log_id,
@@ -607,7 +741,7 @@ mod tests {
         let fetched = JobLogRepository::get(pool, &log_id).await.unwrap().unwrap();
         assert_eq!(fetched.status.as_deref(), Some("ok"));
         assert!(fetched.finished_at.is_some());
-        assert_eq!(fetched.transcript.len(), 1);
+        assert_eq!(fetched.transcript.len(), 2);
         assert_eq!(
             fetched.handoff_note.as_deref(),
             Some("Next: curate references")
@@ -663,19 +797,25 @@ mod tests {
                 description: Some("From web_fetch result".to_string()),
                 status: TodoStatus::Done,
                 note: Some("Saved to dioxus topic".to_string()),
+                due_at: Some(1_700_000_000),
+                recurrence: Some(TodoRecurrence::Weekly),
+                blocked_by: vec!["Update diary".to_string()],
             },
-            TodoItem {
-                title: "Update diary".to_string(),
-                description: None,
-                status: TodoStatus::Pending,
-                note: None,
-            },
+            TodoItem::pending("Update diary", None),
         ];
 
         let json = serde_json::to_string(&items).unwrap();
         let parsed: Vec<TodoItem> = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.len(), 2);
         assert_eq!(parsed[0].status, TodoStatus::Done);
+        assert_eq!(parsed[0].recurrence, Some(TodoRecurrence::Weekly));
+        assert_eq!(parsed[0].blocked_by, vec!["Update diary"]);
         assert_eq!(parsed[1].status, TodoStatus::Pending);
+        assert!(!json.contains("\"blocked_by\":[]"));
+
+        // Lists stored before scheduling fields existed still parse.
+        let legacy: Vec<TodoItem> =
+            serde_json::from_str(r#"[{"title":"Old","status":"pending"}]"#).unwrap();
+        assert!(!legacy[0].is_scheduled());
     }
 }
@@ -0,0 +1,579 @@
+//! Table-backed queue for background work.
+//!
+//! Producers `enqueue` a job with a JSON payload; workers `claim_next` the
+//! highest-priority ready job, then `complete` it (deleting the row) or
+//! `fail` it. A failed job is retried after a backoff until it runs out of
+//! attempts and is kept as `dead` for the operator to inspect or `retry`.
+//! A claim is a lease the worker `renew`s while it runs: a job whose worker
+//! died is requeued once it expires, and the old worker's `complete`/`fail`
+//! no longer touch the row.
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+use crate::job_logs::JobKind;
+
+/// Where a queued job is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueStatus {
+    /// Waiting for `run_after` and a free worker
+    Queued,
+    /// Claimed by a worker until `locked_until`
+    Running,
+    /// Out of attempts; kept until retried or deleted
+    Dead,
+}
+
+impl std::fmt::Display for QueueStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueueStatus::Queued => write!(f, "queued"),
+            QueueStatus::Running => write!(f, "running"),
+            QueueStatus::Dead => write!(f, "dead"),
+        }
+    }
+}
+
+impl std::str::FromStr for QueueStatus {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(QueueStatus::Queued),
+            "running" => Ok(QueueStatus::Running),
+            "dead" => Ok(QueueStatus::Dead),
+            _ => Err(DbError::Serialization(format!("invalid queue status: {s}"))),
+        }
+    }
+}
+
+/// A job to add to the queue.
+#[derive(Debug, Clone)]
+pub struct NewQueuedJob {
+    pub kind: JobKind,
+    /// While a job with this key is queued or running, another is not added.
+    pub dedupe_key: Option<String>,
+    /// JSON payload, interpreted by the worker for `kind`
+    pub payload: String,
+    /// Higher runs first
+    pub priority: i64,
+    pub max_attempts: u32,
+    /// Unix timestamp before which the job is not claimed
+    pub run_after: i64,
+}
+
+/// A job in the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedJob {
+    pub id: String,
+    pub kind: JobKind,
+    pub dedupe_key: Option<String>,
+    pub payload: String,
+    pub priority: i64,
+    pub status: QueueStatus,
+    /// Attempts started so far, including the running one
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub run_after: i64,
+    pub locked_until: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str = "id, kind, dedupe_key, payload, priority, status, attempts, max_attempts, run_after, locked_until, last_error, created_at, updated_at";
+
+/// Delay before retrying a job whose `attempts`-th attempt failed: `base_secs`
+/// doubled for every earlier attempt, capped at `max_secs`.
+pub fn retry_delay_secs(attempts: u32, base_secs: u64, max_secs: u64) -> i64 {
+    let factor = 1u64
+        .checked_shl(attempts.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    base_secs.saturating_mul(factor).min(max_secs) as i64
+}
+
+/// Repository for `job_queue`.
+pub struct JobQueueRepository;
+
+impl JobQueueRepository {
+    /// Add a job. Returns its ID, or `None` when a job with the same
+    /// `dedupe_key` is already queued or running.
+    pub async fn enqueue(
+        pool: &SqlitePool,
+        job: &NewQueuedJob,
+        now: i64,
+    ) -> DbResult<Option<String>> {
+        let id = format!("queue_{}", Uuid::new_v4());
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO job_queue
+             (id, kind, dedupe_key, payload, priority, status, attempts, max_attempts, run_after, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, 'queued', 0, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(job.kind.to_string())
+        .bind(&job.dedupe_key)
+        .bind(&job.payload)
+        .bind(job.priority)
+        .bind(job.max_attempts.max(1) as i64)
+        .bind(job.run_after)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok((result.rows_affected() == 1).then_some(id))
+    }
+
+    /// Claim the highest-priority job ready at `now` until `now + lease_secs`,
+    /// counting it as a new attempt.
+    pub async fn claim_next(
+        pool: &SqlitePool,
+        now: i64,
+        lease_secs: i64,
+    ) -> DbResult<Option<QueuedJob>> {
+        let row = sqlx::query_as::<_, QueuedJobRow>(&format!(
+            "UPDATE job_queue
+             SET status = 'running', attempts = attempts + 1, locked_until = ?, updated_at = ?
+             WHERE id = (
+               SELECT id FROM job_queue
+               WHERE status = 'queued' AND run_after <= ?
+               ORDER BY priority DESC, run_after, created_at
+               LIMIT 1
+             )
+             RETURNING {COLUMNS}"
+        ))
+        .bind(now + lease_secs)
+        .bind(now)
+        .bind(now)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(QueuedJob::try_from).transpose()
+    }
+
+    /// Extend the lease on a running `job` to `now + lease_secs`. Returns
+    /// `false` when the claim was lost (the lease expired and the job was
+    /// released or claimed again); `job.locked_until` is updated otherwise.
+    pub async fn renew(
+        pool: &SqlitePool,
+        job: &mut QueuedJob,
+        lease_secs: i64,
+        now: i64,
+    ) -> DbResult<bool> {
+        let locked_until = now + lease_secs;
+        let result = sqlx::query(
+            "UPDATE job_queue SET locked_until = ?, updated_at = ?
+             WHERE id = ? AND status = 'running' AND attempts = ? AND locked_until = ?",
+        )
+        .bind(locked_until)
+        .bind(now)
+        .bind(&job.id)
+        .bind(job.attempts as i64)
+        .bind(job.locked_until)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        job.locked_until = Some(locked_until);
+        Ok(true)
+    }
+
+    /// Remove a claimed `job` that finished. Returns `false` when the claim
+    /// was lost, leaving the row to whoever holds it now.
+    pub async fn complete(pool: &SqlitePool, job: &QueuedJob) -> DbResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM job_queue
+             WHERE id = ? AND status = 'running' AND attempts = ? AND locked_until = ?",
+        )
+        .bind(&job.id)
+        .bind(job.attempts as i64)
+        .bind(job.locked_until)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Record a failed attempt of a claimed `job`. The job is queued again at
+    /// `retry_at` when given and attempts remain, otherwise it is dead.
+    /// Returns the new status, or `None` if the job no longer exists or the
+    /// claim was lost.
+    pub async fn fail(
+        pool: &SqlitePool,
+        job: &QueuedJob,
+        error: &str,
+        retry_at: Option<i64>,
+        now: i64,
+    ) -> DbResult<Option<QueueStatus>> {
+        let status: Option<String> = sqlx::query_scalar(
+            "UPDATE job_queue
+             SET status = CASE WHEN ? IS NOT NULL AND attempts < max_attempts THEN 'queued' ELSE 'dead' END,
+                 run_after = COALESCE(?, run_after),
+                 locked_until = NULL,
+                 last_error = ?,
+                 updated_at = ?
+             WHERE id = ? AND status = 'running' AND attempts = ? AND locked_until = ?
+             RETURNING status",
+        )
+        .bind(retry_at)
+        .bind(retry_at)
+        .bind(error)
+        .bind(now)
+        .bind(&job.id)
+        .bind(job.attempts as i64)
+        .bind(job.locked_until)
+        .fetch_optional(pool)
+        .await?;
+
+        status.map(|s| s.parse()).transpose()
+    }
+
+    /// Requeue jobs whose worker lease expired at or before `now` (the
+    /// gateway running them stopped), or mark them dead when out of
+    /// attempts. Returns how many were released.
+    pub async fn release_expired(pool: &SqlitePool, now: i64) -> DbResult<u64> {
+        let result = sqlx::query(
+            "UPDATE job_queue
+             SET status = CASE WHEN attempts < max_attempts THEN 'queued' ELSE 'dead' END,
+                 locked_until = NULL,
+                 last_error = 'worker lease expired',
+                 updated_at = ?
+             WHERE status = 'running' AND locked_until <= ?",
+        )
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Jobs with `status` (all when `None`), next to run first.
+    pub async fn list(
+        pool: &SqlitePool,
+        status: Option<QueueStatus>,
+        limit: i64,
+    ) -> DbResult<Vec<QueuedJob>> {
+        let rows = sqlx::query_as::<_, QueuedJobRow>(&format!(
+            "SELECT {COLUMNS} FROM job_queue
+             WHERE ? IS NULL OR status = ?
+             ORDER BY priority DESC, run_after, created_at
+             LIMIT ?"
+        ))
+        .bind(status.map(|s| s.to_string()))
+        .bind(status.map(|s| s.to_string()))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(QueuedJob::try_from).collect()
+    }
+
+    /// Give a dead job a fresh set of attempts, runnable at `now`. Returns
+    /// `false` when it isn't dead or a job with its dedupe key is pending.
+    pub async fn retry(pool: &SqlitePool, id: &str, now: i64) -> DbResult<bool> {
+        let result = sqlx::query(
+            "UPDATE OR IGNORE job_queue
+             SET status = 'queued', attempts = 0, run_after = ?, updated_at = ?
+             WHERE id = ? AND status = 'dead'",
+        )
+        .bind(now)
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Delete a job that isn't running. Returns whether one was removed.
+    pub async fn delete(pool: &SqlitePool, id: &str) -> DbResult<bool> {
+        let result = sqlx::query("DELETE FROM job_queue WHERE id = ? AND status != 'running'")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct QueuedJobRow {
+    id: String,
+    kind: String,
+    dedupe_key: Option<String>,
+    payload: String,
+    priority: i64,
+    status: String,
+    attempts: i64,
+    max_attempts: i64,
+    run_after: i64,
+    locked_until: Option<i64>,
+    last_error: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<QueuedJobRow> for QueuedJob {
+    type Error = DbError;
+
+    fn try_from(row: QueuedJobRow) -> Result<Self, Self::Error> {
+        Ok(QueuedJob {
+            id: row.id,
+            kind: row.kind.parse()?,
+            dedupe_key: row.dedupe_key,
+            payload: row.payload,
+            priority: row.priority,
+            status: row.status.parse()?,
+            attempts: row.attempts as u32,
+            max_attempts: row.max_attempts as u32,
+            run_after: row.run_after,
+            locked_until: row.locked_until,
+            last_error: row.last_error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_pool;
+
+    fn job(kind: JobKind, dedupe_key: Option<&str>, priority: i64) -> NewQueuedJob {
+        NewQueuedJob {
+            kind,
+            dedupe_key: dedupe_key.map(str::to_string),
+            payload: "{}".to_string(),
+            priority,
+            max_attempts: 2,
+            run_after: 100,
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_cap() {
+        assert_eq!(retry_delay_secs(1, 30, 3600), 30);
+        assert_eq!(retry_delay_secs(2, 30, 3600), 60);
+        assert_eq!(retry_delay_secs(4, 30, 3600), 240);
+        assert_eq!(retry_delay_secs(20, 30, 3600), 3600);
+        assert_eq!(retry_delay_secs(200, 30, 3600), 3600);
+    }
+
+    #[tokio::test]
+    async fn test_claims_by_priority_and_dedupes_pending_jobs() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let ingest = JobQueueRepository::enqueue(pool, &job(JobKind::Ingest, None, 0), 100)
+            .await
+            .unwrap()
+            .unwrap();
+        let heartbeat = JobQueueRepository::enqueue(
+            pool,
+            &job(JobKind::Heartbeat, Some("heartbeat:s1"), 20),
+            100,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(
+            JobQueueRepository::enqueue(
+                pool,
+                &job(JobKind::Heartbeat, Some("heartbeat:s1"), 20),
+                100
+            )
+            .await
+            .unwrap()
+            .is_none()
+        );
+
+        // Nothing is ready before run_after.
+        assert!(
+            JobQueueRepository::claim_next(pool, 99, 60)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let claimed = JobQueueRepository::claim_next(pool, 100, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.id, heartbeat);
+        assert_eq!(claimed.status, QueueStatus::Running);
+        assert_eq!((claimed.attempts, claimed.locked_until), (1, Some(160)));
+        // Still pending while running.
+        assert!(
+            JobQueueRepository::enqueue(
+                pool,
+                &job(JobKind::Heartbeat, Some("heartbeat:s1"), 20),
+                100
+            )
+            .await
+            .unwrap()
+            .is_none()
+        );
+
+        assert!(JobQueueRepository::complete(pool, &claimed).await.unwrap());
+        assert!(
+            JobQueueRepository::enqueue(
+                pool,
+                &job(JobKind::Heartbeat, Some("heartbeat:s1"), 20),
+                100
+            )
+            .await
+            .unwrap()
+            .is_some()
+        );
+        let next = JobQueueRepository::claim_next(pool, 100, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.kind, JobKind::Heartbeat);
+        let last = JobQueueRepository::claim_next(pool, 100, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.id, ingest);
+    }
+
+    #[tokio::test]
+    async fn test_failed_jobs_retry_then_go_dead() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let id = JobQueueRepository::enqueue(pool, &job(JobKind::Task, Some("task:t1"), 10), 100)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let first = JobQueueRepository::claim_next(pool, 100, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            JobQueueRepository::fail(pool, &first, "rate limited", Some(130), 100)
+                .await
+                .unwrap(),
+            Some(QueueStatus::Queued)
+        );
+        assert!(
+            JobQueueRepository::claim_next(pool, 120, 60)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Second and last attempt: its worker dies, the lease expires.
+        let claimed = JobQueueRepository::claim_next(pool, 130, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.attempts, 2);
+        assert_eq!(
+            JobQueueRepository::release_expired(pool, 190)
+                .await
+                .unwrap(),
+            1
+        );
+        let dead = JobQueueRepository::list(pool, Some(QueueStatus::Dead), 10)
+            .await
+            .unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].last_error.as_deref(), Some("worker lease expired"));
+
+        // A dead job doesn't block its dedupe key.
+        let other =
+            JobQueueRepository::enqueue(pool, &job(JobKind::Task, Some("task:t1"), 10), 200)
+                .await
+                .unwrap()
+                .unwrap();
+        assert!(!JobQueueRepository::retry(pool, &id, 200).await.unwrap());
+        assert!(JobQueueRepository::delete(pool, &other).await.unwrap());
+        assert!(JobQueueRepository::retry(pool, &id, 200).await.unwrap());
+
+        let retried = JobQueueRepository::claim_next(pool, 200, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((retried.id.as_str(), retried.attempts), (id.as_str(), 1));
+        // Fatal failures skip the remaining attempts.
+        assert_eq!(
+            JobQueueRepository::fail(pool, &retried, "unknown topic", None, 200)
+                .await
+                .unwrap(),
+            Some(QueueStatus::Dead)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_fences_out_the_old_worker() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let id = JobQueueRepository::enqueue(pool, &job(JobKind::Ingest, None, 0), 100)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // A renewed lease survives past the original expiry.
+        let mut slow = JobQueueRepository::claim_next(pool, 100, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            JobQueueRepository::renew(pool, &mut slow, 60, 140)
+                .await
+                .unwrap()
+        );
+        assert_eq!(slow.locked_until, Some(200));
+        assert_eq!(
+            JobQueueRepository::release_expired(pool, 160)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // Without renewal it expires and another worker claims it.
+        assert_eq!(
+            JobQueueRepository::release_expired(pool, 200)
+                .await
+                .unwrap(),
+            1
+        );
+        let fresh = JobQueueRepository::claim_next(pool, 200, 60)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((fresh.id.as_str(), fresh.attempts), (id.as_str(), 2));
+
+        // The old worker can no longer renew, complete or fail the job.
+        assert!(
+            !JobQueueRepository::renew(pool, &mut slow, 60, 210)
+                .await
+                .unwrap()
+        );
+        assert!(!JobQueueRepository::complete(pool, &slow).await.unwrap());
+        assert_eq!(
+            JobQueueRepository::fail(pool, &slow, "late", None, 210)
+                .await
+                .unwrap(),
+            None
+        );
+        let running = JobQueueRepository::list(pool, Some(QueueStatus::Running), 10)
+            .await
+            .unwrap();
+        assert_eq!(running.len(), 1);
+
+        assert!(JobQueueRepository::complete(pool, &fresh).await.unwrap());
+        assert!(
+            JobQueueRepository::list(pool, None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Scheduled TODO items.
+//!
+//! A TODO item with a due date or recurrence rule outlives the job that
+//! created it: every heartbeat/reflection run starts from the open
+//! scheduled items of the session's previous run ([`JobLogRepository::carried_todos`]),
+//! and the heartbeat runner surfaces the ones that are due.
+//!
+//! Dependencies are expressed by title (`blocked_by`), so they survive
+//! items being dropped or reordered between runs. A blocker that is no
+//! longer in the list counts as resolved.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Months};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+use crate::job_logs::{JobLogRepository, TodoItem, TodoStatus, parse_optional_json};
+
+/// How a scheduled item repeats once done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoRecurrence {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl TodoRecurrence {
+    /// The occurrence following `at` (unix seconds).
+    pub fn step(self, at: i64) -> i64 {
+        match self {
+            TodoRecurrence::Hourly => at + 3_600,
+            TodoRecurrence::Daily => at + 86_400,
+            TodoRecurrence::Weekly => at + 7 * 86_400,
+            TodoRecurrence::Monthly => DateTime::from_timestamp(at, 0)
+                .and_then(|dt| dt.checked_add_months(Months::new(1)))
+                .map(|dt| dt.timestamp())
+                .unwrap_or(at + 30 * 86_400),
+        }
+    }
+
+    /// The first occurrence after `now`, counting from `from`.
+    pub fn next_after(self, from: i64, now: i64) -> i64 {
+        let mut next = self.step(from);
+        // Skip missed occurrences in one jump for fixed-length periods.
+        if next <= now && self != TodoRecurrence::Monthly {
+            let period = self.step(0);
+            next += ((now - next) / period + 1) * period;
+        }
+        while next <= now {
+            next = self.step(next);
+        }
+        next
+    }
+}
+
+impl fmt::Display for TodoRecurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoRecurrence::Hourly => write!(f, "hourly"),
+            TodoRecurrence::Daily => write!(f, "daily"),
+            TodoRecurrence::Weekly => write!(f, "weekly"),
+            TodoRecurrence::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+impl FromStr for TodoRecurrence {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hourly" => Ok(TodoRecurrence::Hourly),
+            "daily" => Ok(TodoRecurrence::Daily),
+            "weekly" => Ok(TodoRecurrence::Weekly),
+            "monthly" => Ok(TodoRecurrence::Monthly),
+            other => Err(DbError::Serialization(format!(
+                "Invalid recurrence '{other}'. Use: hourly, daily, weekly, monthly"
+            ))),
+        }
+    }
+}
+
+impl TodoItem {
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, TodoStatus::Pending | TodoStatus::InProgress)
+    }
+
+    pub fn is_scheduled(&self) -> bool {
+        self.due_at.is_some() || self.recurrence.is_some()
+    }
+
+    /// Set the item's status at `now`.
+    ///
+    /// Completing a recurring item reschedules it to its next occurrence and
+    /// reopens it instead of marking it done.
+    pub fn set_status(&mut self, status: TodoStatus, now: i64) {
+        match (&status, self.recurrence) {
+            (TodoStatus::Done, Some(recurrence)) => {
+                self.due_at = Some(recurrence.next_after(self.due_at.unwrap_or(now), now));
+                self.status = TodoStatus::Pending;
+            }
+            _ => self.status = status,
+        }
+    }
+}
+
+/// Titles of the open items blocking `item`.
+pub fn open_blockers<'a>(todos: &'a [TodoItem], item: &TodoItem) -> Vec<&'a str> {
+    item.blocked_by
+        .iter()
+        .filter_map(|title| {
+            todos
+                .iter()
+                .find(|t| &t.title == title && t.is_open())
+                .map(|t| t.title.as_str())
+        })
+        .collect()
+}
+
+/// Open, unblocked items whose due date has passed, earliest first.
+pub fn due_todos(todos: &[TodoItem], now: i64) -> Vec<&TodoItem> {
+    let mut due: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|t| t.is_open() && t.due_at.is_some_and(|at| at <= now))
+        .filter(|t| open_blockers(todos, t).is_empty())
+        .collect();
+    due.sort_by_key(|t| t.due_at);
+    due
+}
+
+/// Items a new run inherits: open scheduled items and the open items
+/// blocking them.
+pub fn carry_over(todos: &[TodoItem]) -> Vec<TodoItem> {
+    let scheduled: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|t| t.is_open() && t.is_scheduled())
+        .collect();
+    todos
+        .iter()
+        .filter(|t| {
+            t.is_open()
+                && (t.is_scheduled() || scheduled.iter().any(|s| s.blocked_by.contains(&t.title)))
+        })
+        .cloned()
+        .collect()
+}
+
+impl JobLogRepository {
+    /// Open scheduled items from the session's latest finished heartbeat or
+    /// reflection run with a TODO list.
+    pub async fn carried_todos(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+    ) -> DbResult<Vec<TodoItem>> {
+        let todo_list: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT todo_list FROM job_logs
+             WHERE ghost_id = ? AND session_id = ?
+               AND job_kind IN ('heartbeat', 'reflection')
+               AND finished_at IS NOT NULL
+               AND todo_list IS NOT NULL AND todo_list != '[]'
+             ORDER BY started_at DESC, id DESC
+             LIMIT 1",
+        )
+        .bind(ghost_id)
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+
+        let todos: Vec<TodoItem> = parse_optional_json(todo_list.flatten().as_deref());
+        Ok(carry_over(&todos))
+    }
+
+    /// Carried items that are due at `now` and not blocked.
+    pub async fn due_todos(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        now: i64,
+    ) -> DbResult<Vec<TodoItem>> {
+        let todos = Self::carried_todos(pool, ghost_id, session_id).await?;
+        Ok(due_todos(&todos, now).into_iter().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_logs::{JobKind, JobLog};
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        test_helpers::create_test_pool,
+    };
+
+    fn scheduled(title: &str, due_at: i64) -> TodoItem {
+        let mut item = TodoItem::pending(title, None);
+        item.due_at = Some(due_at);
+        item
+    }
+
+    #[test]
+    fn test_recurrence_next_after() {
+        let day = 86_400;
+        assert_eq!(TodoRecurrence::Daily.next_after(0, 10), day);
+        // Missed occurrences are skipped, keeping the original time of day.
+        assert_eq!(
+            TodoRecurrence::Daily.next_after(100, 3 * day),
+            3 * day + 100
+        );
+        assert_eq!(TodoRecurrence::Hourly.next_after(0, 3_600), 7_200);
+        // 2026-01-31 → 2026-02-28 → 2026-03-28
+        let jan_31 = 1_769_817_600;
+        let next = TodoRecurrence::Monthly.next_after(jan_31, jan_31 + 40 * day);
+        assert_eq!(next, jan_31 + (28 + 28) * day);
+        assert_eq!(
+            "weekly".parse::<TodoRecurrence>().unwrap(),
+            TodoRecurrence::Weekly
+        );
+        assert!("yearly".parse::<TodoRecurrence>().is_err());
+    }
+
+    #[test]
+    fn test_set_status_reschedules_recurring_items() {
+        let mut item = scheduled("Water plants", 1_000);
+        item.recurrence = Some(TodoRecurrence::Daily);
+        item.set_status(TodoStatus::Done, 5_000);
+        assert_eq!(item.status, TodoStatus::Pending);
+        assert_eq!(item.due_at, Some(1_000 + 86_400));
+
+        let mut once = scheduled("Renew domain", 1_000);
+        once.set_status(TodoStatus::Done, 5_000);
+        assert_eq!(once.status, TodoStatus::Done);
+        assert_eq!(once.due_at, Some(1_000));
+    }
+
+    #[test]
+    fn test_due_todos_respects_blockers() {
+        let mut deploy = scheduled("Deploy", 100);
+        deploy.blocked_by = vec!["Write changelog".to_string(), "Gone".to_string()];
+        let mut todos = vec![
+            scheduled("Later", 10_000),
+            deploy,
+            TodoItem::pending("Write changelog", None),
+            scheduled("Backup", 50),
+            TodoItem::pending("Unscheduled", None),
+        ];
+
+        let due: Vec<&str> = due_todos(&todos, 1_000)
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(due, vec!["Backup"]);
+
+        // Carry-over keeps scheduled items and their blockers only.
+        let carried: Vec<String> = carry_over(&todos).into_iter().map(|t| t.title).collect();
+        assert_eq!(
+            carried,
+            vec!["Later", "Deploy", "Write changelog", "Backup"]
+        );
+
+        todos[2].set_status(TodoStatus::Done, 1_000);
+        let due: Vec<&str> = due_todos(&todos, 1_000)
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(due, vec!["Backup", "Deploy"]);
+    }
+
+    #[tokio::test]
+    async fn test_carried_and_due_todos() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        assert!(
+            JobLogRepository::carried_todos(pool, &ghost.id, &session.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut done = scheduled("Old", 10);
+        done.status = TodoStatus::Done;
+        let mut log = JobLog::start(&ghost.id, JobKind::Reflection, &session.id);
+        log.started_at -= 10;
+        log.todo_list = vec![
+            done,
+            scheduled("Due", 100),
+            scheduled("Upcoming", 1_000_000),
+            TodoItem::pending("Plan step", None),
+        ];
+        log.finish("ok");
+        JobLogRepository::insert(pool, &log).await.unwrap();
+
+        // A running job's list is not inherited yet.
+        let mut running = JobLog::start(&ghost.id, JobKind::Heartbeat, &session.id);
+        running.todo_list = vec![scheduled("In flight", 100)];
+        JobLogRepository::insert(pool, &running).await.unwrap();
+
+        let carried: Vec<String> = JobLogRepository::carried_todos(pool, &ghost.id, &session.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(carried, vec!["Due", "Upcoming"]);
+
+        let due = JobLogRepository::due_todos(pool, &ghost.id, &session.id, 500)
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].title, "Due");
+    }
+}
@@ -1,19 +1,21 @@
 //! T-KOMA (ティーコマ) database connection pool and initialization.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use sqlx::SqlitePool;
 use tracing::info;
 
 use crate::{
+    encryption::DbKey,
     error::{DbError, DbResult},
-    sqlite_runtime::create_file_pool,
+    sqlite_runtime::{create_file_pool, create_read_only_file_pool},
 };
 
 /// T-KOMA database pool wrapper
 #[derive(Debug, Clone)]
 pub struct KomaDbPool {
     pool: SqlitePool,
+    read_only: bool,
 }
 
 impl KomaDbPool {
@@ -22,7 +24,7 @@ impl KomaDbPool {
     /// This function:
     /// 1. Ensures the data directory exists
     /// 2. Initializes sqlite-vec extension
-    /// 3. Creates/connects to the database
+    /// 3. Creates/connects to the database (keyed when a `DbKey` resolves)
     /// 4. Runs migrations
     pub async fn new() -> DbResult<Self> {
         let db_path = Self::db_path()?;
@@ -32,12 +34,51 @@ impl KomaDbPool {
             std::fs::create_dir_all(parent)?;
         }
 
-        let pool = create_file_pool(&db_path, 5).await?;
+        let key = DbKey::resolve()?;
+        if key.is_some() {
+            info!("Database encryption enabled");
+        }
+        let pool = create_file_pool(&db_path, 5, key.as_ref()).await?;
 
         Self::run_migrations(&pool).await?;
 
         info!("T-KOMA database initialized successfully");
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            read_only: false,
+        })
+    }
+
+    /// Open an existing database read-only, for a replica gateway or TUI
+    /// that reads a copy of the primary's DB.
+    ///
+    /// Never creates the file or runs migrations; fails if the database is
+    /// missing migrations this build expects.
+    pub async fn open_read_only() -> DbResult<Self> {
+        let db_path = Self::db_path()?;
+        let key = DbKey::resolve()?;
+        Self::open_read_only_at(&db_path, key.as_ref()).await
+    }
+
+    pub(crate) async fn open_read_only_at(db_path: &Path, key: Option<&DbKey>) -> DbResult<Self> {
+        info!(
+            "Opening T-KOMA database read-only at: {}",
+            db_path.display()
+        );
+        if !db_path.exists() {
+            return Err(DbError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} does not exist", db_path.display()),
+            )));
+        }
+
+        let pool = create_read_only_file_pool(db_path, 5, key).await?;
+        Self::check_schema_current(&pool).await?;
+
+        Ok(Self {
+            pool,
+            read_only: true,
+        })
     }
 
     /// Get the inner SQLx pool
@@ -45,6 +86,11 @@ impl KomaDbPool {
         &self.pool
     }
 
+    /// Whether the pool was opened with [`KomaDbPool::open_read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Get database file path
     pub fn db_path() -> DbResult<PathBuf> {
         if let Ok(override_dir) = std::env::var("T_KOMA_DATA_DIR") {
@@ -56,7 +102,7 @@ impl KomaDbPool {
     }
 
     /// Run database migrations using sqlx migrate macro
-    async fn run_migrations(pool: &SqlitePool) -> DbResult<()> {
+    pub(crate) async fn run_migrations(pool: &SqlitePool) -> DbResult<()> {
         sqlx::migrate!("./migrations")
             .run(pool)
             .await
@@ -66,6 +112,42 @@ impl KomaDbPool {
         Ok(())
     }
 
+    /// Latest migration this build ships.
+    pub fn expected_schema_version() -> i64 {
+        sqlx::migrate!("./migrations")
+            .iter()
+            .map(|m| m.version)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Latest migration applied to this database.
+    pub async fn schema_version(&self) -> DbResult<i64> {
+        Self::applied_schema_version(&self.pool).await
+    }
+
+    async fn applied_schema_version(pool: &SqlitePool) -> DbResult<i64> {
+        let applied: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(pool)
+                .await
+                .map_err(|e| DbError::Migration(e.to_string()))?;
+        Ok(applied.unwrap_or(0))
+    }
+
+    /// A read-only pool can't migrate, so refuse a DB older than this build.
+    async fn check_schema_current(pool: &SqlitePool) -> DbResult<()> {
+        let expected = Self::expected_schema_version();
+        let applied = Self::applied_schema_version(pool).await?;
+        if applied < expected {
+            return Err(DbError::Migration(format!(
+                "database schema is at {applied}, this build expects {expected}; \
+                 start the primary gateway to migrate it"
+            )));
+        }
+        Ok(())
+    }
+
     /// Close the pool gracefully
     pub async fn close(&self) {
         self.pool.close().await;
@@ -73,7 +155,10 @@ impl KomaDbPool {
 
     /// Create a KomaDbPool from an existing SqlitePool (for testing)
     pub fn from_pool(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            read_only: false,
+        }
     }
 }
 
@@ -81,6 +166,7 @@ impl KomaDbPool {
 mod tests {
     use super::KomaDbPool;
     use crate::ENV_MUTEX;
+    use crate::sqlite_runtime::create_file_pool;
 
     #[test]
     fn test_db_path_uses_env_override() {
@@ -94,4 +180,37 @@ mod tests {
         unsafe { std::env::remove_var("T_KOMA_DATA_DIR") };
         assert_eq!(path, dir.path().join("koma.sqlite3"));
     }
+
+    #[tokio::test]
+    async fn test_open_read_only_rejects_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("koma.sqlite3");
+        assert!(KomaDbPool::open_read_only_at(&path, None).await.is_err());
+        assert!(!path.exists());
+
+        let primary = create_file_pool(&path, 1, None).await.unwrap();
+        KomaDbPool::run_migrations(&primary).await.unwrap();
+        sqlx::query("INSERT INTO model_pricing (model, updated_at) VALUES ('m', 0)")
+            .execute(&primary)
+            .await
+            .unwrap();
+
+        let replica = KomaDbPool::open_read_only_at(&path, None).await.unwrap();
+        assert!(replica.is_read_only());
+        assert_eq!(
+            replica.schema_version().await.unwrap(),
+            KomaDbPool::expected_schema_version()
+        );
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM model_pricing WHERE model = 'm'")
+            .fetch_one(replica.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(
+            sqlx::query("DELETE FROM model_pricing")
+                .execute(replica.pool())
+                .await
+                .is_err()
+        );
+    }
 }
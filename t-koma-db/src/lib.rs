@@ -6,32 +6,92 @@
 //! - Platform-specific handling (Discord, API, CLI)
 //! - Audit trail via event logging
 
+pub mod api_tokens;
+pub mod discord_channels;
+pub mod discord_threads;
+pub mod email_threads;
+pub mod encryption;
 pub mod error;
+pub mod events;
 pub mod ghosts;
 pub mod interfaces;
+pub mod job_leases;
+pub mod job_log_retention;
 pub mod job_logs;
+pub mod job_queue;
+pub mod job_todos;
 pub mod koma_db;
+pub mod model_catalog;
+pub mod operator_permissions;
 pub mod operators;
+pub mod path_approvals;
 pub mod prompt_cache;
+pub mod reminders;
+pub mod scheduled_tasks;
+pub mod scheduler_state;
+pub mod session_archive;
+pub mod session_compactions;
+pub mod session_export;
 pub mod sessions;
 mod sqlite_runtime;
+pub mod tool_invocations;
+pub mod tool_policies;
+pub mod usage_budgets;
 pub mod usage_log;
+pub mod web_cache;
 
 // Re-export commonly used types
+pub use api_tokens::{API_TOKEN_PREFIX, ApiToken, ApiTokenScope};
+pub use discord_channels::DiscordChannelRepository;
+pub use discord_threads::{DiscordThread, DiscordThreadRepository};
+pub use email_threads::{EmailThreadMessage, EmailThreadRepository};
+pub use encryption::{DB_KEY_ENV, DbKey, encrypt_database};
 pub use error::{DbError, DbResult};
-pub use ghosts::{Ghost, GhostRepository};
+pub use events::{
+    DEFAULT_EVENT_QUERY_LIMIT, Event, EventFilters, EventKind, EventRepository, NewEvent,
+};
+pub use ghosts::{Ghost, GhostCloneOptions, GhostRepository};
 pub use interfaces::{Interface, InterfaceRepository};
+pub use job_leases::JobLeaseRepository;
+pub use job_log_retention::{JobKindStats, JobLogRetention};
 pub use job_logs::{
     JobKind, JobLog, JobLogRepository, JobLogSummary, TodoItem, TodoStatus, TranscriptEntry,
 };
+pub use job_queue::{JobQueueRepository, NewQueuedJob, QueueStatus, QueuedJob};
+pub use job_todos::TodoRecurrence;
 pub use koma_db::KomaDbPool;
+pub use model_catalog::{ModelCatalogEntry, ModelCatalogRepository};
+pub use operator_permissions::{OperatorPermission, OperatorPermissions};
 pub use operators::{
     DEFAULT_RATE_LIMIT_1H_MAX, DEFAULT_RATE_LIMIT_5M_MAX, Operator, OperatorAccessLevel,
     OperatorRepository, OperatorStatus, Platform,
 };
-pub use prompt_cache::{PromptCacheEntry, PromptCacheRepository};
-pub use sessions::{ContentBlock, Message, MessageRole, Session, SessionInfo, SessionRepository};
-pub use usage_log::{TokenUsage, UsageLog, UsageLogRepository, UsageTotals};
+pub use path_approvals::{PathAccess, PathApprovalRepository, PathApprovals};
+pub use prompt_cache::{
+    PromptCacheEntry, PromptCacheEviction, PromptCacheRepository, PromptCacheStats,
+};
+pub use reminders::{Reminder, ReminderRepository};
+pub use scheduled_tasks::{ScheduledTask, ScheduledTaskRepository};
+pub use scheduler_state::{ScheduledJob, SchedulerStateRepository};
+pub use session_compactions::SessionCompaction;
+pub use session_export::{SESSION_EXPORT_VERSION, SessionExportRecord};
+pub use sessions::{
+    ContentBlock, Message, MessageRole, MessageSearchFilters, MessageSearchHit, MessageUsage,
+    Session, SessionInfo, SessionOrigin, SessionRepository,
+};
+pub use tool_invocations::{
+    NewToolInvocation, ToolFailure, ToolInvocationRepository, ToolUsageFilters, ToolUsageStats,
+};
+pub use tool_policies::{ToolPolicies, ToolPolicy, ToolPolicyRepository, ToolPolicySubject};
+pub use usage_budgets::{
+    BudgetReport, BudgetScope, BudgetStatus, DEFAULT_BUDGET_WARN_RATIO, UsageBudget,
+    UsageBudgetRepository,
+};
+pub use usage_log::{
+    ModelPricing, TokenUsage, UsageAggregate, UsageGrouping, UsageLog, UsageLogRepository,
+    UsageTotals,
+};
+pub use web_cache::{WebCacheEntry, WebCacheRepository};
 
 // Re-export test helpers when running tests or when test-helpers feature is enabled
 #[cfg(any(test, feature = "test-helpers"))]
@@ -0,0 +1,250 @@
+//! Cached provider model catalogs.
+//!
+//! The gateway periodically fetches provider model lists (OpenRouter
+//! `/models`) and replaces that provider's rows here, so context windows,
+//! pricing and modalities stay available across restarts and while the
+//! provider is unreachable.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+use crate::usage_log::ModelPricing;
+
+/// One model as listed by a provider.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelCatalogEntry {
+    pub provider: String,
+    /// Provider model id (e.g. `anthropic/claude-sonnet-4`)
+    pub model: String,
+    pub name: String,
+    pub context_length: Option<u32>,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub input_modalities: Vec<String>,
+    pub output_modalities: Vec<String>,
+    /// Unix timestamp of the fetch that produced this row
+    pub fetched_at: i64,
+}
+
+impl ModelCatalogEntry {
+    /// Listed token prices, in the usage log pricing format.
+    pub fn pricing(&self) -> ModelPricing {
+        ModelPricing {
+            model: self.model.clone(),
+            input_per_mtok: self.input_per_mtok,
+            output_per_mtok: self.output_per_mtok,
+            cache_read_per_mtok: self.cache_read_per_mtok,
+            cache_write_per_mtok: self.cache_write_per_mtok,
+        }
+    }
+}
+
+/// Repository for `model_catalog`.
+pub struct ModelCatalogRepository;
+
+impl ModelCatalogRepository {
+    /// Replace all catalog rows of `provider` with `entries`.
+    ///
+    /// `fetched_at` is set to the current time on every row.
+    pub async fn replace_provider(
+        pool: &SqlitePool,
+        provider: &str,
+        entries: &[ModelCatalogEntry],
+    ) -> DbResult<()> {
+        let fetched_at = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM model_catalog WHERE provider = ?")
+            .bind(provider)
+            .execute(&mut *tx)
+            .await?;
+
+        for entry in entries {
+            let input_modalities = serde_json::to_string(&entry.input_modalities)
+                .map_err(|e| DbError::Serialization(e.to_string()))?;
+            let output_modalities = serde_json::to_string(&entry.output_modalities)
+                .map_err(|e| DbError::Serialization(e.to_string()))?;
+            sqlx::query(
+                "INSERT OR REPLACE INTO model_catalog (provider, model, name, context_length,
+                    input_per_mtok, output_per_mtok, cache_read_per_mtok, cache_write_per_mtok,
+                    input_modalities, output_modalities, fetched_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(provider)
+            .bind(&entry.model)
+            .bind(&entry.name)
+            .bind(entry.context_length.map(i64::from))
+            .bind(entry.input_per_mtok)
+            .bind(entry.output_per_mtok)
+            .bind(entry.cache_read_per_mtok)
+            .bind(entry.cache_write_per_mtok)
+            .bind(input_modalities)
+            .bind(output_modalities)
+            .bind(fetched_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get one catalog entry.
+    pub async fn get(
+        pool: &SqlitePool,
+        provider: &str,
+        model: &str,
+    ) -> DbResult<Option<ModelCatalogEntry>> {
+        let row = sqlx::query_as::<_, ModelCatalogRow>(
+            "SELECT provider, model, name, context_length, input_per_mtok, output_per_mtok,
+                cache_read_per_mtok, cache_write_per_mtok, input_modalities, output_modalities,
+                fetched_at
+             FROM model_catalog
+             WHERE provider = ? AND model = ?",
+        )
+        .bind(provider)
+        .bind(model)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(ModelCatalogEntry::from))
+    }
+
+    /// List a provider's catalog, ordered by model id.
+    pub async fn list(pool: &SqlitePool, provider: &str) -> DbResult<Vec<ModelCatalogEntry>> {
+        let rows = sqlx::query_as::<_, ModelCatalogRow>(
+            "SELECT provider, model, name, context_length, input_per_mtok, output_per_mtok,
+                cache_read_per_mtok, cache_write_per_mtok, input_modalities, output_modalities,
+                fetched_at
+             FROM model_catalog
+             WHERE provider = ?
+             ORDER BY model",
+        )
+        .bind(provider)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ModelCatalogEntry::from).collect())
+    }
+
+    /// Time of the last stored fetch for a provider, if any.
+    pub async fn last_fetched_at(pool: &SqlitePool, provider: &str) -> DbResult<Option<i64>> {
+        let fetched_at: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(fetched_at) FROM model_catalog WHERE provider = ?")
+                .bind(provider)
+                .fetch_one(pool)
+                .await?;
+
+        Ok(fetched_at)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ModelCatalogRow {
+    provider: String,
+    model: String,
+    name: String,
+    context_length: Option<i64>,
+    input_per_mtok: f64,
+    output_per_mtok: f64,
+    cache_read_per_mtok: f64,
+    cache_write_per_mtok: f64,
+    input_modalities: String,
+    output_modalities: String,
+    fetched_at: i64,
+}
+
+impl From<ModelCatalogRow> for ModelCatalogEntry {
+    fn from(row: ModelCatalogRow) -> Self {
+        ModelCatalogEntry {
+            provider: row.provider,
+            model: row.model,
+            name: row.name,
+            context_length: row.context_length.map(|n| n as u32),
+            input_per_mtok: row.input_per_mtok,
+            output_per_mtok: row.output_per_mtok,
+            cache_read_per_mtok: row.cache_read_per_mtok,
+            cache_write_per_mtok: row.cache_write_per_mtok,
+            input_modalities: serde_json::from_str(&row.input_modalities).unwrap_or_default(),
+            output_modalities: serde_json::from_str(&row.output_modalities).unwrap_or_default(),
+            fetched_at: row.fetched_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_pool;
+
+    fn entry(model: &str, context_length: Option<u32>) -> ModelCatalogEntry {
+        ModelCatalogEntry {
+            provider: "openrouter".to_string(),
+            model: model.to_string(),
+            name: model.to_string(),
+            context_length,
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            input_modalities: vec!["text".to_string(), "image".to_string()],
+            output_modalities: vec!["text".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_read_catalog() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        assert_eq!(
+            ModelCatalogRepository::last_fetched_at(pool, "openrouter")
+                .await
+                .unwrap(),
+            None
+        );
+
+        ModelCatalogRepository::replace_provider(
+            pool,
+            "openrouter",
+            &[entry("b/model", Some(200_000)), entry("a/model", None)],
+        )
+        .await
+        .unwrap();
+
+        let listed = ModelCatalogRepository::list(pool, "openrouter")
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].model, "a/model");
+        assert_eq!(listed[0].context_length, None);
+        assert_eq!(listed[1].context_length, Some(200_000));
+        assert_eq!(listed[1].input_modalities, vec!["text", "image"]);
+        assert_eq!(listed[1].pricing().output_per_mtok, 15.0);
+        assert!(
+            ModelCatalogRepository::last_fetched_at(pool, "openrouter")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // A refresh drops models the provider no longer lists.
+        ModelCatalogRepository::replace_provider(pool, "openrouter", &[entry("c/model", None)])
+            .await
+            .unwrap();
+        assert!(
+            ModelCatalogRepository::get(pool, "openrouter", "a/model")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            ModelCatalogRepository::get(pool, "openrouter", "c/model")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Fine-grained operator permissions.
+//!
+//! Each operator gets a permission set derived from their access level.
+//! Individual capabilities and per-tool grants can be overridden per
+//! operator; overrides are stored as rows and removing one restores the
+//! access-level default. Per-tool grants are operator tool policies (see
+//! [`crate::tool_policies`]).
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
+use crate::operators::{OperatorAccessLevel, OperatorRepository};
+use crate::tool_policies::{ToolPolicy, ToolPolicyRepository, ToolPolicySubject, most_specific};
+
+const TOOL_PREFIX: &str = "tool:";
+
+/// A single grantable capability.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperatorPermission {
+    CreateGhosts,
+    UseShell,
+    WriteSharedKnowledge,
+    ManageOperators,
+    /// Use of one tool by name (e.g. `web_fetch`).
+    Tool(String),
+}
+
+impl fmt::Display for OperatorPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperatorPermission::CreateGhosts => write!(f, "create_ghosts"),
+            OperatorPermission::UseShell => write!(f, "use_shell"),
+            OperatorPermission::WriteSharedKnowledge => write!(f, "write_shared_knowledge"),
+            OperatorPermission::ManageOperators => write!(f, "manage_operators"),
+            OperatorPermission::Tool(name) => write!(f, "{TOOL_PREFIX}{name}"),
+        }
+    }
+}
+
+impl std::str::FromStr for OperatorPermission {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create_ghosts" => Ok(OperatorPermission::CreateGhosts),
+            "use_shell" => Ok(OperatorPermission::UseShell),
+            "write_shared_knowledge" => Ok(OperatorPermission::WriteSharedKnowledge),
+            "manage_operators" => Ok(OperatorPermission::ManageOperators),
+            _ => match s.strip_prefix(TOOL_PREFIX) {
+                Some(name) if !name.trim().is_empty() => {
+                    Ok(OperatorPermission::Tool(name.trim().to_string()))
+                }
+                _ => Err(DbError::Serialization(format!("Invalid permission: {}", s))),
+            },
+        }
+    }
+}
+
+/// Effective permissions of an operator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperatorPermissions {
+    pub can_create_ghosts: bool,
+    pub can_use_shell: bool,
+    pub can_write_shared_knowledge: bool,
+    pub can_manage_operators: bool,
+    /// Explicit per-tool grants (names or `prefix*` patterns). Tools not
+    /// matched here are allowed.
+    pub tool_grants: BTreeMap<String, bool>,
+}
+
+impl OperatorPermissions {
+    /// Defaults for an access level: Puppet Masters hold everything,
+    /// standard operators everything except operator management.
+    pub fn for_access_level(level: OperatorAccessLevel) -> Self {
+        Self {
+            can_create_ghosts: true,
+            can_use_shell: true,
+            can_write_shared_knowledge: true,
+            can_manage_operators: level == OperatorAccessLevel::PuppetMaster,
+            tool_grants: BTreeMap::new(),
+        }
+    }
+
+    pub fn allows(&self, permission: &OperatorPermission) -> bool {
+        match permission {
+            OperatorPermission::CreateGhosts => self.can_create_ghosts,
+            OperatorPermission::UseShell => self.can_use_shell,
+            OperatorPermission::WriteSharedKnowledge => self.can_write_shared_knowledge,
+            OperatorPermission::ManageOperators => self.can_manage_operators,
+            OperatorPermission::Tool(name) => self.allows_tool(name),
+        }
+    }
+
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        most_specific(&self.tool_grants, tool_name)
+            .copied()
+            .unwrap_or(true)
+    }
+
+    fn apply(&mut self, permission: OperatorPermission, allowed: bool) {
+        match permission {
+            OperatorPermission::CreateGhosts => self.can_create_ghosts = allowed,
+            OperatorPermission::UseShell => self.can_use_shell = allowed,
+            OperatorPermission::WriteSharedKnowledge => self.can_write_shared_knowledge = allowed,
+            OperatorPermission::ManageOperators => self.can_manage_operators = allowed,
+            OperatorPermission::Tool(name) => {
+                self.tool_grants.insert(name, allowed);
+            }
+        }
+    }
+}
+
+impl Default for OperatorPermissions {
+    fn default() -> Self {
+        Self::for_access_level(OperatorAccessLevel::Standard)
+    }
+}
+
+impl fmt::Display for OperatorPermissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |allowed: bool| if allowed { "on" } else { "off" };
+        write!(
+            f,
+            "create_ghosts={} use_shell={} write_shared_knowledge={} manage_operators={}",
+            flag(self.can_create_ghosts),
+            flag(self.can_use_shell),
+            flag(self.can_write_shared_knowledge),
+            flag(self.can_manage_operators),
+        )?;
+        for (tool, allowed) in &self.tool_grants {
+            write!(f, " {TOOL_PREFIX}{}={}", tool, flag(*allowed))?;
+        }
+        Ok(())
+    }
+}
+
+impl OperatorRepository {
+    /// Effective permissions: access-level defaults plus stored overrides.
+    pub async fn get_permissions(
+        pool: &SqlitePool,
+        operator_id: &str,
+    ) -> DbResult<OperatorPermissions> {
+        let operator = Self::get_by_id(pool, operator_id)
+            .await?
+            .ok_or_else(|| DbError::OperatorNotFound(operator_id.to_string()))?;
+
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            "SELECT permission, allowed FROM operator_permissions WHERE operator_id = ?",
+        )
+        .bind(operator_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut permissions = OperatorPermissions::for_access_level(operator.access_level);
+        for (permission, allowed) in rows {
+            permissions.apply(permission.parse()?, allowed != 0);
+        }
+        let policies =
+            ToolPolicyRepository::list(pool, &ToolPolicySubject::Operator(operator.id)).await?;
+        for (tool, policy) in policies {
+            permissions.apply(OperatorPermission::Tool(tool), policy != ToolPolicy::Deny);
+        }
+        Ok(permissions)
+    }
+
+    /// Override one permission, or clear the override with `None`.
+    ///
+    /// Per-tool grants are stored as allow/deny operator tool policies.
+    /// Returns the resulting effective permissions.
+    pub async fn set_permission(
+        pool: &SqlitePool,
+        operator_id: &str,
+        permission: &OperatorPermission,
+        allowed: Option<bool>,
+    ) -> DbResult<OperatorPermissions> {
+        if let OperatorPermission::Tool(tool) = permission {
+            let policy = allowed.map(|allowed| {
+                if allowed {
+                    ToolPolicy::Allow
+                } else {
+                    ToolPolicy::Deny
+                }
+            });
+            let subject = ToolPolicySubject::Operator(operator_id.to_string());
+            ToolPolicyRepository::set(pool, &subject, tool, policy).await?;
+            return Self::get_permissions(pool, operator_id).await;
+        }
+        if Self::get_by_id(pool, operator_id).await?.is_none() {
+            return Err(DbError::OperatorNotFound(operator_id.to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        match allowed {
+            Some(allowed) => {
+                sqlx::query(
+                    "INSERT INTO operator_permissions (operator_id, permission, allowed, updated_at)
+                     VALUES (?, ?, ?, ?)
+                     ON CONFLICT(operator_id, permission)
+                     DO UPDATE SET allowed = excluded.allowed, updated_at = excluded.updated_at",
+                )
+                .bind(operator_id)
+                .bind(permission.to_string())
+                .bind(if allowed { 1 } else { 0 })
+                .bind(Utc::now().timestamp())
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "DELETE FROM operator_permissions WHERE operator_id = ? AND permission = ?",
+                )
+                .bind(operator_id)
+                .bind(permission.to_string())
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::OperatorPermissionChanged, operator_id).with_data(
+                serde_json::json!({ "permission": permission.to_string(), "allowed": allowed }),
+            ),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Self::get_permissions(pool, operator_id).await
+    }
+
+    /// Whether an operator holds `permission`. Unknown operators hold nothing.
+    pub async fn has_permission(
+        pool: &SqlitePool,
+        operator_id: &str,
+        permission: &OperatorPermission,
+    ) -> DbResult<bool> {
+        match Self::get_permissions(pool, operator_id).await {
+            Ok(permissions) => Ok(permissions.allows(permission)),
+            Err(DbError::OperatorNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Platform, test_helpers::create_test_pool};
+
+    #[test]
+    fn test_permission_parse_roundtrip() {
+        for permission in [
+            OperatorPermission::CreateGhosts,
+            OperatorPermission::UseShell,
+            OperatorPermission::WriteSharedKnowledge,
+            OperatorPermission::ManageOperators,
+            OperatorPermission::Tool("web_fetch".to_string()),
+        ] {
+            let parsed: OperatorPermission = permission.to_string().parse().unwrap();
+            assert_eq!(parsed, permission);
+        }
+        assert!("tool:".parse::<OperatorPermission>().is_err());
+        assert!("sudo".parse::<OperatorPermission>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_permission_overrides() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "PermOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+
+        let defaults = OperatorRepository::get_permissions(pool, &operator.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            defaults,
+            OperatorPermissions::for_access_level(OperatorAccessLevel::Standard)
+        );
+        assert!(!defaults.can_manage_operators);
+
+        OperatorRepository::set_permission(
+            pool,
+            &operator.id,
+            &OperatorPermission::UseShell,
+            Some(false),
+        )
+        .await
+        .unwrap();
+        let updated = OperatorRepository::set_permission(
+            pool,
+            &operator.id,
+            &OperatorPermission::Tool("web_fetch".to_string()),
+            Some(false),
+        )
+        .await
+        .unwrap();
+        assert!(!updated.can_use_shell);
+        assert!(!updated.allows_tool("web_fetch"));
+        assert!(updated.allows_tool("web_search"));
+
+        // Overrides survive an access-level change; defaults follow it.
+        OperatorRepository::set_access_level(pool, &operator.id, OperatorAccessLevel::PuppetMaster)
+            .await
+            .unwrap();
+        let promoted = OperatorRepository::get_permissions(pool, &operator.id)
+            .await
+            .unwrap();
+        assert!(promoted.can_manage_operators);
+        assert!(!promoted.can_use_shell);
+
+        let cleared = OperatorRepository::set_permission(
+            pool,
+            &operator.id,
+            &OperatorPermission::UseShell,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(cleared.can_use_shell);
+
+        assert!(
+            !OperatorRepository::has_permission(pool, "missing", &OperatorPermission::CreateGhosts)
+                .await
+                .unwrap()
+        );
+    }
+}
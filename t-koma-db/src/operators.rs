@@ -9,6 +9,7 @@ use tracing::{debug, info};
 use uuid::Uuid;
 
 use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
 
 /// Platform types for operators
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -16,6 +17,9 @@ pub enum Platform {
     Discord,
     Api,
     Cli,
+    Telegram,
+    Slack,
+    Email,
 }
 
 impl fmt::Display for Platform {
@@ -24,6 +28,9 @@ impl fmt::Display for Platform {
             Platform::Discord => write!(f, "discord"),
             Platform::Api => write!(f, "api"),
             Platform::Cli => write!(f, "cli"),
+            Platform::Telegram => write!(f, "telegram"),
+            Platform::Slack => write!(f, "slack"),
+            Platform::Email => write!(f, "email"),
         }
     }
 }
@@ -36,6 +43,9 @@ impl std::str::FromStr for Platform {
             "discord" => Ok(Platform::Discord),
             "api" => Ok(Platform::Api),
             "cli" => Ok(Platform::Cli),
+            "telegram" => Ok(Platform::Telegram),
+            "slack" => Ok(Platform::Slack),
+            "email" => Ok(Platform::Email),
             _ => Err(DbError::Serialization(format!("Invalid platform: {}", s))),
         }
     }
@@ -148,6 +158,7 @@ impl OperatorRepository {
             ),
         };
 
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "INSERT INTO operators (id, name, platform, status, access_level, rate_limit_5m_max, rate_limit_1h_max, allow_workspace_escape, verbose, created_at, updated_at, welcomed)
              VALUES (?, ?, ?, ?, ?, ?, ?, 0, 0, ?, ?, 0)",
@@ -161,10 +172,11 @@ impl OperatorRepository {
         .bind(rate_limit_1h)
         .bind(now)
         .bind(now)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-        Self::log_event(pool, &id, "created", None).await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorCreated, &id)).await?;
+        tx.commit().await?;
 
         info!("Created new operator: {} (platform: {})", id, platform);
 
@@ -200,6 +212,7 @@ impl OperatorRepository {
             ),
         };
 
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "INSERT INTO operators (id, name, platform, status, access_level, rate_limit_5m_max, rate_limit_1h_max, allow_workspace_escape, verbose, created_at, updated_at, welcomed)
              VALUES (?, ?, ?, ?, ?, ?, ?, 0, 0, ?, ?, 0)",
@@ -213,10 +226,11 @@ impl OperatorRepository {
         .bind(rate_limit_1h)
         .bind(now)
         .bind(now)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-        Self::log_event(pool, id, "created", None).await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorCreated, id)).await?;
+        tx.commit().await?;
 
         info!("Created new operator: {} (platform: {})", id, platform);
 
@@ -270,6 +284,7 @@ impl OperatorRepository {
         }
 
         let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET status = ?, approved_at = ?, updated_at = ?
@@ -279,10 +294,11 @@ impl OperatorRepository {
         .bind(now)
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-        Self::log_event(pool, id, "approved", None).await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorApproved, id)).await?;
+        tx.commit().await?;
 
         info!("Approved operator: {}", id);
 
@@ -309,6 +325,7 @@ impl OperatorRepository {
         }
 
         let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET status = ?, denied_at = ?, updated_at = ?
@@ -318,10 +335,11 @@ impl OperatorRepository {
         .bind(now)
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-        Self::log_event(pool, id, "denied", None).await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorDenied, id)).await?;
+        tx.commit().await?;
 
         info!("Denied operator: {}", id);
 
@@ -333,6 +351,7 @@ impl OperatorRepository {
     /// Mark operator as welcomed (typically for Discord operators)
     pub async fn mark_welcomed(pool: &SqlitePool, id: &str) -> DbResult<()> {
         let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET welcomed = 1, updated_at = ?
@@ -340,10 +359,11 @@ impl OperatorRepository {
         )
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
-        Self::log_event(pool, id, "welcomed", None).await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorWelcomed, id)).await?;
+        tx.commit().await?;
 
         info!("Marked operator as welcomed: {}", id);
         Ok(())
@@ -405,12 +425,14 @@ impl OperatorRepository {
             return Err(DbError::OperatorNotFound(id.to_string()));
         }
 
-        Self::log_event(pool, id, "removed", None).await?;
-
+        // The event has no foreign key, so it survives the cascade.
+        let mut tx = pool.begin().await?;
+        EventRepository::record(&mut *tx, &NewEvent::new(EventKind::OperatorRemoved, id)).await?;
         sqlx::query("DELETE FROM operators WHERE id = ?")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
 
         info!("Removed operator: {}", id);
         Ok(())
@@ -422,6 +444,7 @@ impl OperatorRepository {
         access_level: OperatorAccessLevel,
     ) -> DbResult<Operator> {
         let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET access_level = ?, updated_at = ?
@@ -430,9 +453,17 @@ impl OperatorRepository {
         .bind(access_level.to_string())
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::OperatorAccessLevelChanged, id)
+                .with_data(serde_json::json!({ "access_level": access_level.to_string() })),
+        )
+        .await?;
+        tx.commit().await?;
+
         Self::get_by_id(pool, id)
             .await?
             .ok_or_else(|| DbError::OperatorNotFound(id.to_string()))
@@ -445,6 +476,7 @@ impl OperatorRepository {
         rate_limit_1h_max: Option<i64>,
     ) -> DbResult<Operator> {
         let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET rate_limit_5m_max = ?, rate_limit_1h_max = ?, updated_at = ?
@@ -454,8 +486,18 @@ impl OperatorRepository {
         .bind(rate_limit_1h_max)
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
+        .await?;
+
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::OperatorRateLimitsChanged, id).with_data(serde_json::json!({
+                "rate_limit_5m_max": rate_limit_5m_max,
+                "rate_limit_1h_max": rate_limit_1h_max,
+            })),
+        )
         .await?;
+        tx.commit().await?;
 
         Self::get_by_id(pool, id)
             .await?
@@ -469,6 +511,7 @@ impl OperatorRepository {
     ) -> DbResult<Operator> {
         let now = Utc::now().timestamp();
         let allow = if allow_workspace_escape { 1 } else { 0 };
+        let mut tx = pool.begin().await?;
         sqlx::query(
             "UPDATE operators
              SET allow_workspace_escape = ?, updated_at = ?
@@ -477,8 +520,16 @@ impl OperatorRepository {
         .bind(allow)
         .bind(now)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
+        .await?;
+
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::OperatorWorkspaceEscapeChanged, id)
+                .with_data(serde_json::json!({ "allow": allow_workspace_escape })),
+        )
         .await?;
+        tx.commit().await?;
 
         Self::get_by_id(pool, id)
             .await?
@@ -508,26 +559,36 @@ impl OperatorRepository {
     /// Returns each operator paired with their Discord `external_id`.
     pub async fn list_puppet_masters_with_discord_interface(
         pool: &SqlitePool,
+    ) -> DbResult<Vec<(Operator, String)>> {
+        Self::list_puppet_masters_with_interface(pool, Platform::Discord).await
+    }
+
+    /// List approved puppet master operators that have an interface on
+    /// `platform`, each paired with that interface's `external_id`.
+    pub async fn list_puppet_masters_with_interface(
+        pool: &SqlitePool,
+        platform: Platform,
     ) -> DbResult<Vec<(Operator, String)>> {
         let rows = sqlx::query_as::<_, OperatorWithExternalIdRow>(
             "SELECT o.id, o.name, o.platform, o.status, o.access_level,
                     o.rate_limit_5m_max, o.rate_limit_1h_max,
                     o.allow_workspace_escape, o.verbose,
                     o.created_at, o.updated_at, o.approved_at, o.denied_at, o.welcomed,
-                    i.external_id AS discord_external_id
+                    i.external_id
              FROM operators o
              JOIN interfaces i ON i.operator_id = o.id
              WHERE o.access_level = 'puppet_master'
                AND o.status = 'approved'
-               AND i.platform = 'discord'",
+               AND i.platform = ?",
         )
+        .bind(platform.to_string())
         .fetch_all(pool)
         .await?;
 
         Ok(rows
             .into_iter()
             .map(|r| {
-                let ext_id = r.discord_external_id.clone();
+                let ext_id = r.external_id.clone();
                 (Operator::from(r), ext_id)
             })
             .collect())
@@ -537,21 +598,28 @@ impl OperatorRepository {
     pub async fn prune_pending(pool: &SqlitePool, hours: i64) -> DbResult<i64> {
         let cutoff = Utc::now().timestamp() - (hours * 3600);
 
+        let mut tx = pool.begin().await?;
         let pending_ids: Vec<(String,)> =
             sqlx::query_as("SELECT id FROM operators WHERE status = 'pending' AND created_at < ?")
                 .bind(cutoff)
-                .fetch_all(pool)
+                .fetch_all(&mut *tx)
                 .await?;
 
         for (operator_id,) in &pending_ids {
-            Self::log_event(pool, operator_id, "removed", Some("auto-prune")).await?;
+            EventRepository::record(
+                &mut *tx,
+                &NewEvent::new(EventKind::OperatorRemoved, operator_id)
+                    .with_data(serde_json::json!({ "reason": "auto-prune" })),
+            )
+            .await?;
         }
 
         let result =
             sqlx::query("DELETE FROM operators WHERE status = 'pending' AND created_at < ?")
                 .bind(cutoff)
-                .execute(pool)
+                .execute(&mut *tx)
                 .await?;
+        tx.commit().await?;
 
         let count = result.rows_affected() as i64;
         info!(
@@ -560,28 +628,6 @@ impl OperatorRepository {
         );
         Ok(count)
     }
-
-    async fn log_event(
-        pool: &SqlitePool,
-        operator_id: &str,
-        event_type: &str,
-        event_data: Option<&str>,
-    ) -> DbResult<()> {
-        let now = Utc::now().timestamp();
-
-        sqlx::query(
-            "INSERT INTO operator_events (operator_id, event_type, event_data, created_at)
-             VALUES (?, ?, ?, ?)",
-        )
-        .bind(operator_id)
-        .bind(event_type)
-        .bind(event_data)
-        .bind(now)
-        .execute(pool)
-        .await?;
-
-        Ok(())
-    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -618,7 +664,7 @@ struct OperatorWithExternalIdRow {
     approved_at: Option<i64>,
     denied_at: Option<i64>,
     welcomed: i64,
-    discord_external_id: String,
+    external_id: String,
 }
 
 impl From<OperatorRow> for Operator {
@@ -672,6 +718,7 @@ impl From<OperatorWithExternalIdRow> for Operator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::EventFilters;
     use crate::test_helpers::create_test_pool;
 
     #[tokio::test]
@@ -879,32 +926,10 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_remove_operator_logs_event_and_deletes_under_fk_enforcement() {
+    async fn test_remove_operator_keeps_event_and_deletes_under_fk_enforcement() {
         let db = create_test_pool().await.unwrap();
         let pool = db.pool();
 
-        sqlx::query(
-            "CREATE TABLE remove_event_probe (
-                event_type TEXT NOT NULL,
-                operator_id TEXT NOT NULL
-            )",
-        )
-        .execute(pool)
-        .await
-        .unwrap();
-
-        sqlx::query(
-            "CREATE TRIGGER capture_operator_events
-             AFTER INSERT ON operator_events
-             BEGIN
-                 INSERT INTO remove_event_probe (event_type, operator_id)
-                 VALUES (NEW.event_type, NEW.operator_id);
-             END;",
-        )
-        .execute(pool)
-        .await
-        .unwrap();
-
         OperatorRepository::get_or_create(
             pool,
             "operator1",
@@ -917,22 +942,20 @@ mod tests {
 
         OperatorRepository::remove(pool, "operator1").await.unwrap();
 
-        let removed_event_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM remove_event_probe
-             WHERE operator_id = 'operator1' AND event_type = 'removed'",
-        )
-        .fetch_one(pool)
-        .await
-        .unwrap();
-        assert_eq!(removed_event_count, 1);
-
-        let event_rows_left: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM operator_events WHERE operator_id = 'operator1'",
+        let events = EventRepository::query(
+            pool,
+            &EventFilters {
+                subject_id: Some("operator1".to_string()),
+                ..Default::default()
+            },
         )
-        .fetch_one(pool)
         .await
         .unwrap();
-        assert_eq!(event_rows_left, 0);
+        let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::OperatorCreated, EventKind::OperatorRemoved]
+        );
 
         let operator = OperatorRepository::get_by_id(pool, "operator1")
             .await
@@ -0,0 +1,283 @@
+//! Per-operator approval rules for paths outside ghost workspaces.
+//!
+//! A rule pre-approves reads (or reads and writes) under one absolute
+//! directory, for every ghost of the operator: tools touching a covered path
+//! run without the usual workspace escape prompt. Anything else still asks.
+
+use std::fmt;
+use std::path::{Component, Path};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
+use crate::operators::OperatorRepository;
+
+/// What a tool does with a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathAccess {
+    Read,
+    /// Create, modify or delete; also covers reads.
+    Write,
+}
+
+impl PathAccess {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PathAccess::Read => "read",
+            PathAccess::Write => "write",
+        }
+    }
+
+    /// Whether a rule granting `self` covers an access of kind `needed`.
+    pub fn covers(self, needed: PathAccess) -> bool {
+        self >= needed
+    }
+}
+
+impl fmt::Display for PathAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PathAccess {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(PathAccess::Read),
+            "write" => Ok(PathAccess::Write),
+            _ => Err(DbError::Serialization(format!(
+                "Invalid path access: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// One operator's rules, keyed by absolute path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathApprovals {
+    pub rules: std::collections::BTreeMap<String, PathAccess>,
+}
+
+impl PathApprovals {
+    /// Whether some rule at or above `path` grants `access`.
+    ///
+    /// `path` must already be absolute and normalized (symlinks resolved);
+    /// matching is per component, so `/srv/app` does not cover `/srv/apple`.
+    pub fn allows(&self, path: &Path, access: PathAccess) -> bool {
+        self.rules
+            .iter()
+            .any(|(root, granted)| granted.covers(access) && path.starts_with(root))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// `path=access` pairs separated by spaces, or `none`.
+pub fn format_path_approvals(approvals: &PathApprovals) -> String {
+    if approvals.is_empty() {
+        return "none".to_string();
+    }
+    approvals
+        .rules
+        .iter()
+        .map(|(path, access)| format!("{path}={access}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Rules need an absolute path without `.` or `..` components; a leading
+/// `~` stands for the home directory and a trailing slash is dropped.
+fn normalize_rule_path(path: &str) -> DbResult<String> {
+    let expanded = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+            .map(|home| format!("{}{}", home.display(), rest))
+            .unwrap_or_else(|| path.to_string()),
+        _ => path.to_string(),
+    };
+    let trimmed = if expanded.len() > 1 {
+        expanded.trim_end_matches('/')
+    } else {
+        &expanded
+    };
+    let valid = Path::new(trimmed).is_absolute()
+        && Path::new(trimmed)
+            .components()
+            .all(|c| matches!(c, Component::RootDir | Component::Normal(_)));
+    if valid {
+        Ok(trimmed.to_string())
+    } else {
+        Err(DbError::Serialization(format!(
+            "Path approvals need an absolute path: {}",
+            path
+        )))
+    }
+}
+
+/// Path approval repository for database operations
+pub struct PathApprovalRepository;
+
+impl PathApprovalRepository {
+    /// Rules stored for one operator.
+    pub async fn list(pool: &SqlitePool, operator_id: &str) -> DbResult<PathApprovals> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "SELECT path, access FROM path_approvals WHERE operator_id = ?",
+        )
+        .bind(operator_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(PathApprovals {
+            rules: rows
+                .into_iter()
+                .map(|(path, access)| Ok((path, access.parse()?)))
+                .collect::<DbResult<_>>()?,
+        })
+    }
+
+    /// Approve `access` under `path`, or remove the rule with `None`.
+    ///
+    /// Returns the operator's resulting rules.
+    pub async fn set(
+        pool: &SqlitePool,
+        operator_id: &str,
+        path: &str,
+        access: Option<PathAccess>,
+    ) -> DbResult<PathApprovals> {
+        let path = normalize_rule_path(path)?;
+        if OperatorRepository::get_by_id(pool, operator_id)
+            .await?
+            .is_none()
+        {
+            return Err(DbError::OperatorNotFound(operator_id.to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM path_approvals WHERE operator_id = ? AND path = ?")
+            .bind(operator_id)
+            .bind(&path)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(access) = access {
+            sqlx::query(
+                "INSERT INTO path_approvals (operator_id, path, access, updated_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(operator_id)
+            .bind(&path)
+            .bind(access.as_str())
+            .bind(Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+        }
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(EventKind::OperatorPathApprovalChanged, operator_id)
+                .with_data(serde_json::json!({ "path": path, "access": access })),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Self::list(pool, operator_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OperatorAccessLevel, Platform, test_helpers::create_test_pool};
+
+    #[test]
+    fn test_allows_matches_components_and_access() {
+        let approvals = PathApprovals {
+            rules: [
+                ("/home/op/projects/foo".to_string(), PathAccess::Read),
+                ("/tmp/scratch".to_string(), PathAccess::Write),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let allows = |path: &str, access| approvals.allows(Path::new(path), access);
+        assert!(allows("/home/op/projects/foo", PathAccess::Read));
+        assert!(allows(
+            "/home/op/projects/foo/src/main.rs",
+            PathAccess::Read
+        ));
+        assert!(!allows(
+            "/home/op/projects/foo/src/main.rs",
+            PathAccess::Write
+        ));
+        assert!(!allows("/home/op/projects/foobar", PathAccess::Read));
+        assert!(!allows("/home/op/projects", PathAccess::Read));
+        assert!(allows("/tmp/scratch/out.txt", PathAccess::Write));
+        assert!(allows("/tmp/scratch/out.txt", PathAccess::Read));
+
+        assert_eq!(normalize_rule_path("/srv/app/").unwrap(), "/srv/app");
+        assert_eq!(normalize_rule_path("/").unwrap(), "/");
+        assert!(normalize_rule_path("projects/foo").is_err());
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                normalize_rule_path("~/projects/foo/").unwrap(),
+                home.join("projects/foo").to_string_lossy()
+            );
+        }
+        assert!(normalize_rule_path("/srv/../etc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_and_clear_path_approvals() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let operator = OperatorRepository::create_new(
+            pool,
+            "PathOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+
+        PathApprovalRepository::set(pool, &operator.id, "/srv/app/", Some(PathAccess::Read))
+            .await
+            .unwrap();
+        let updated =
+            PathApprovalRepository::set(pool, &operator.id, "/srv/app", Some(PathAccess::Write))
+                .await
+                .unwrap();
+        assert_eq!(format_path_approvals(&updated), "/srv/app=write");
+        assert_eq!(
+            PathApprovalRepository::list(pool, &operator.id)
+                .await
+                .unwrap(),
+            updated
+        );
+
+        let cleared = PathApprovalRepository::set(pool, &operator.id, "/srv/app", None)
+            .await
+            .unwrap();
+        assert!(cleared.is_empty());
+
+        assert!(matches!(
+            PathApprovalRepository::set(pool, "missing", "/srv", Some(PathAccess::Read)).await,
+            Err(DbError::OperatorNotFound(_))
+        ));
+
+        let events = EventRepository::query(
+            pool,
+            &crate::EventFilters {
+                kind: Some(EventKind::OperatorPathApprovalChanged.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(events.len(), 3);
+    }
+}
@@ -1,8 +1,13 @@
 //! Prompt cache persistence for session system prompts.
 //!
-//! Stores rendered system prompt JSON to survive gateway restarts.
+//! Stores rendered system prompt JSON to survive gateway restarts, with
+//! per-entry size and hit accounting. [`PromptCacheRepository::evict`]
+//! applies a TTL/LRU policy; daily hit/miss/eviction counters live in
+//! `prompt_cache_stats` and survive eviction.
 
-use sqlx::SqlitePool;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tracing::info;
 use uuid::Uuid;
 
 use crate::error::DbResult;
@@ -16,6 +21,10 @@ pub struct PromptCacheEntry {
     pub system_blocks_json: String,
     pub context_hash: String,
     pub cached_at: i64,
+    /// Length of `system_blocks_json` in bytes.
+    pub size_bytes: i64,
+    pub hit_count: i64,
+    pub last_hit_at: Option<i64>,
 }
 
 impl PromptCacheEntry {
@@ -34,19 +43,95 @@ impl PromptCacheEntry {
             system_blocks_json: system_blocks_json.to_string(),
             context_hash: context_hash.to_string(),
             cached_at,
+            size_bytes: system_blocks_json.len() as i64,
+            hit_count: 0,
+            last_hit_at: None,
         }
     }
 }
 
+/// Eviction limits. `None` disables a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptCacheEviction {
+    /// Evict entries neither written nor hit for this many seconds.
+    pub max_idle_secs: Option<i64>,
+    /// Keep at most this many entries (least recently used go first).
+    pub max_entries: Option<u32>,
+    /// Keep at most this many bytes of prompt JSON (least recently used go first).
+    pub max_total_bytes: Option<i64>,
+}
+
+impl PromptCacheEviction {
+    pub fn is_unbounded(&self) -> bool {
+        self.max_idle_secs.is_none() && self.max_entries.is_none() && self.max_total_bytes.is_none()
+    }
+}
+
+/// Cache effectiveness over a period, plus current storage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptCacheStats {
+    pub entries: i64,
+    pub total_bytes: i64,
+    pub hits: i64,
+    pub misses: i64,
+    pub evictions: i64,
+    /// Provider-reported cache reads and writes (usage log).
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    /// Cache-read discount minus cache-write premium, in USD, for models
+    /// with pricing. Negative means caching cost more than it saved.
+    pub net_savings_usd: f64,
+}
+
+impl PromptCacheStats {
+    /// Share of prompt lookups served from the cache.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// Entries selected for eviction. Binds: `?1` idle cutoff (unix seconds),
+/// `?2` entry limit, `?3` byte limit. Superseded entries (older rows for a
+/// session) always go.
+const EVICTION_CANDIDATES: &str = "
+    WITH per_session AS (
+        SELECT id, size_bytes, COALESCE(last_hit_at, cached_at) AS last_used,
+               ROW_NUMBER() OVER (
+                   PARTITION BY session_id ORDER BY cached_at DESC, id DESC
+               ) AS session_rank
+        FROM prompt_cache
+    ),
+    ranked AS (
+        SELECT id, last_used,
+               ROW_NUMBER() OVER (ORDER BY last_used DESC, id DESC) AS lru_rank,
+               SUM(size_bytes) OVER (
+                   ORDER BY last_used DESC, id DESC ROWS UNBOUNDED PRECEDING
+               ) AS running_bytes
+        FROM per_session
+        WHERE session_rank = 1
+    ),
+    doomed AS (
+        SELECT id FROM per_session WHERE session_rank > 1
+        UNION
+        SELECT id FROM ranked WHERE last_used < ?1 OR lru_rank > ?2 OR running_bytes > ?3
+    )";
+
 /// Repository for the prompt_cache table.
 pub struct PromptCacheRepository;
 
 impl PromptCacheRepository {
-    /// Upsert a prompt cache entry.
+    /// Upsert a prompt cache entry, replacing the session's previous one.
     pub async fn upsert(pool: &SqlitePool, entry: &PromptCacheEntry) -> DbResult<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM prompt_cache WHERE session_id = ? AND id != ?")
+            .bind(&entry.session_id)
+            .bind(&entry.id)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query(
-            "INSERT OR REPLACE INTO prompt_cache (id, ghost_id, session_id, system_blocks_json, context_hash, cached_at)
-             VALUES (?, ?, ?, ?, ?, ?)",
+            "INSERT OR REPLACE INTO prompt_cache (id, ghost_id, session_id, system_blocks_json, context_hash, cached_at, size_bytes, hit_count, last_hit_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&entry.id)
         .bind(&entry.ghost_id)
@@ -54,11 +139,138 @@ impl PromptCacheRepository {
         .bind(&entry.system_blocks_json)
         .bind(&entry.context_hash)
         .bind(entry.cached_at)
-        .execute(pool)
+        .bind(entry.size_bytes)
+        .bind(entry.hit_count)
+        .bind(entry.last_hit_at)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Count a lookup served from the cache and mark the entry as used.
+    pub async fn record_hit(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        now: i64,
+    ) -> DbResult<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "UPDATE prompt_cache
+             SET hit_count = hit_count + 1, last_hit_at = ?
+             WHERE session_id = ?",
+        )
+        .bind(now)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        bump_stats(&mut *tx, ghost_id, now, 1, 0).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Count a lookup that had to rebuild the prompt.
+    pub async fn record_miss(pool: &SqlitePool, ghost_id: &str, now: i64) -> DbResult<()> {
+        bump_stats(pool, ghost_id, now, 0, 1).await
+    }
+
+    /// Delete entries outside `policy`, counting them as evictions.
+    ///
+    /// Returns the number of evicted entries.
+    pub async fn evict(pool: &SqlitePool, policy: &PromptCacheEviction, now: i64) -> DbResult<u64> {
+        let cutoff = policy
+            .max_idle_secs
+            .map(|secs| now - secs)
+            .unwrap_or(i64::MIN);
+        let max_entries = policy.max_entries.map(i64::from).unwrap_or(i64::MAX);
+        let max_bytes = policy.max_total_bytes.unwrap_or(i64::MAX);
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(&format!(
+            "{EVICTION_CANDIDATES}
+             INSERT INTO prompt_cache_stats (ghost_id, day, evictions)
+             SELECT ghost_id, date(?4, 'unixepoch'), COUNT(*)
+             FROM prompt_cache
+             WHERE id IN (SELECT id FROM doomed)
+             GROUP BY ghost_id
+             ON CONFLICT(ghost_id, day) DO UPDATE SET
+                 evictions = evictions + excluded.evictions"
+        ))
+        .bind(cutoff)
+        .bind(max_entries)
+        .bind(max_bytes)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let evicted = sqlx::query(&format!(
+            "{EVICTION_CANDIDATES}
+             DELETE FROM prompt_cache WHERE id IN (SELECT id FROM doomed)"
+        ))
+        .bind(cutoff)
+        .bind(max_entries)
+        .bind(max_bytes)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        if evicted > 0 {
+            info!("Evicted {} prompt cache entries", evicted);
+        }
+        Ok(evicted)
+    }
+
+    /// Cache effectiveness since `since` (unix seconds), across all ghosts.
+    ///
+    /// Entry count and size are current. Hit/miss/eviction counters cover
+    /// whole UTC days from `since`; usage figures start exactly at `since`.
+    pub async fn stats(pool: &SqlitePool, since: i64) -> DbResult<PromptCacheStats> {
+        let (entries, total_bytes): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM prompt_cache")
+                .fetch_one(pool)
+                .await?;
+
+        let (hits, misses, evictions): (i64, i64, i64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(hits), 0), COALESCE(SUM(misses), 0), COALESCE(SUM(evictions), 0)
+             FROM prompt_cache_stats
+             WHERE day >= date(?, 'unixepoch')",
+        )
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        let (cache_read_tokens, cache_creation_tokens, net_savings_usd): (i64, i64, f64) =
+            sqlx::query_as(
+                "SELECT COALESCE(SUM(u.cache_read_tokens), 0),
+                        COALESCE(SUM(u.cache_creation_tokens), 0),
+                        COALESCE(SUM(
+                            u.cache_read_tokens * (p.input_per_mtok - p.cache_read_per_mtok)
+                            - u.cache_creation_tokens * (p.cache_write_per_mtok - p.input_per_mtok)
+                        ), 0.0) / 1000000.0
+                 FROM usage_log u
+                 LEFT JOIN model_pricing p ON p.model = u.model
+                 WHERE u.created_at >= ?",
+            )
+            .bind(since)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(PromptCacheStats {
+            entries,
+            total_bytes,
+            hits,
+            misses,
+            evictions,
+            cache_read_tokens,
+            cache_creation_tokens,
+            net_savings_usd,
+        })
+    }
+
     /// Load all cached entries newer than `since_ts` for a ghost.
     pub async fn load_recent(
         pool: &SqlitePool,
@@ -66,9 +278,11 @@ impl PromptCacheRepository {
         since_ts: i64,
     ) -> DbResult<Vec<PromptCacheEntry>> {
         let rows = sqlx::query_as::<_, PromptCacheRow>(
-            "SELECT id, ghost_id, session_id, system_blocks_json, context_hash, cached_at
+            "SELECT id, ghost_id, session_id, system_blocks_json, context_hash, cached_at,
+                    size_bytes, hit_count, last_hit_at
              FROM prompt_cache
-             WHERE ghost_id = ? AND cached_at > ?",
+             WHERE ghost_id = ? AND cached_at > ?
+             ORDER BY cached_at ASC",
         )
         .bind(ghost_id)
         .bind(since_ts)
@@ -96,6 +310,36 @@ struct PromptCacheRow {
     system_blocks_json: String,
     context_hash: String,
     cached_at: i64,
+    size_bytes: i64,
+    hit_count: i64,
+    last_hit_at: Option<i64>,
+}
+
+/// Add to a ghost's counters for the UTC day containing `now`.
+async fn bump_stats<'e, E>(
+    executor: E,
+    ghost_id: &str,
+    now: i64,
+    hits: i64,
+    misses: i64,
+) -> DbResult<()>
+where
+    E: SqliteExecutor<'e>,
+{
+    sqlx::query(
+        "INSERT INTO prompt_cache_stats (ghost_id, day, hits, misses)
+         VALUES (?, date(?, 'unixepoch'), ?, ?)
+         ON CONFLICT(ghost_id, day) DO UPDATE SET
+             hits = hits + excluded.hits,
+             misses = misses + excluded.misses",
+    )
+    .bind(ghost_id)
+    .bind(now)
+    .bind(hits)
+    .bind(misses)
+    .execute(executor)
+    .await?;
+    Ok(())
 }
 
 impl From<PromptCacheRow> for PromptCacheEntry {
@@ -107,6 +351,9 @@ impl From<PromptCacheRow> for PromptCacheEntry {
             system_blocks_json: row.system_blocks_json,
             context_hash: row.context_hash,
             cached_at: row.cached_at,
+            size_bytes: row.size_bytes,
+            hit_count: row.hit_count,
+            last_hit_at: row.last_hit_at,
         }
     }
 }
@@ -192,4 +439,96 @@ mod tests {
         assert_eq!(loaded.len(), 1);
         assert_eq!(loaded[0].session_id, session2.id);
     }
+
+    #[tokio::test]
+    async fn test_evict_and_stats() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let mut sessions = Vec::new();
+        for _ in 0..4 {
+            sessions.push(
+                SessionRepository::create(pool, &ghost.id, &operator.id)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let now = 1_700_000_000;
+        // Session 0 is stale; session 1 is old but was hit recently.
+        for (session, cached_at, json) in [
+            (&sessions[0], now - 7200, "a".repeat(10)),
+            (&sessions[1], now - 7200, "b".repeat(10)),
+            (&sessions[2], now - 60, "c".repeat(10)),
+            (&sessions[3], now - 30, "d".repeat(10)),
+        ] {
+            let entry = PromptCacheEntry::new(&ghost.id, &session.id, &json, "h", cached_at);
+            PromptCacheRepository::upsert(pool, &entry).await.unwrap();
+            PromptCacheRepository::record_miss(pool, &ghost.id, now)
+                .await
+                .unwrap();
+        }
+        // Re-caching a session replaces its entry.
+        let entry = PromptCacheEntry::new(&ghost.id, &sessions[3].id, "dddd", "h2", now - 20);
+        PromptCacheRepository::upsert(pool, &entry).await.unwrap();
+        PromptCacheRepository::record_hit(pool, &ghost.id, &sessions[1].id, now - 10)
+            .await
+            .unwrap();
+
+        let stats = PromptCacheRepository::stats(pool, now - 86_400)
+            .await
+            .unwrap();
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.total_bytes, 34);
+        assert_eq!((stats.hits, stats.misses), (1, 4));
+        assert_eq!(stats.hit_rate(), Some(0.2));
+
+        assert_eq!(
+            PromptCacheRepository::evict(pool, &PromptCacheEviction::default(), now)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // TTL drops session 0; the byte cap then keeps the two most recently
+        // used entries (session 1 via its hit, session 3).
+        let policy = PromptCacheEviction {
+            max_idle_secs: Some(3600),
+            max_entries: Some(3),
+            max_total_bytes: Some(14),
+        };
+        assert_eq!(
+            PromptCacheRepository::evict(pool, &policy, now)
+                .await
+                .unwrap(),
+            2
+        );
+        let mut left: Vec<String> = PromptCacheRepository::load_recent(pool, &ghost.id, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.session_id)
+            .collect();
+        left.sort();
+        let mut expected = vec![sessions[1].id.clone(), sessions[3].id.clone()];
+        expected.sort();
+        assert_eq!(left, expected);
+
+        let stats = PromptCacheRepository::stats(pool, now - 86_400)
+            .await
+            .unwrap();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.evictions, 2);
+    }
 }
@@ -0,0 +1,235 @@
+//! One-shot reminders a ghost schedules for its operator.
+//!
+//! A reminder belongs to the session it was created in. The gateway
+//! scheduler picks up due reminders, posts them into that session and
+//! pushes them to the interface the session was last chatted from
+//! ([`SessionRepository::get_origin`](crate::SessionRepository::get_origin)).
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+
+/// A scheduled reminder.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct Reminder {
+    pub id: String,
+    pub ghost_id: String,
+    pub operator_id: String,
+    pub session_id: String,
+    pub message: String,
+    /// Unix timestamp at which the reminder fires
+    pub due_at: i64,
+    pub created_at: i64,
+    /// Unix timestamp of delivery; `None` while pending
+    pub delivered_at: Option<i64>,
+}
+
+/// Repository for `reminders`.
+pub struct ReminderRepository;
+
+impl ReminderRepository {
+    /// Schedule a reminder in `session_id`, addressed to the session's operator.
+    pub async fn create(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        message: &str,
+        due_at: i64,
+    ) -> DbResult<Reminder> {
+        let operator_id: Option<String> =
+            sqlx::query_scalar("SELECT operator_id FROM sessions WHERE id = ? AND ghost_id = ?")
+                .bind(session_id)
+                .bind(ghost_id)
+                .fetch_optional(pool)
+                .await?;
+        let operator_id =
+            operator_id.ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+
+        let reminder = Reminder {
+            id: format!("rem_{}", Uuid::new_v4()),
+            ghost_id: ghost_id.to_string(),
+            operator_id,
+            session_id: session_id.to_string(),
+            message: message.to_string(),
+            due_at,
+            created_at: Utc::now().timestamp(),
+            delivered_at: None,
+        };
+        sqlx::query(
+            "INSERT INTO reminders (id, ghost_id, operator_id, session_id, message, due_at, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&reminder.id)
+        .bind(&reminder.ghost_id)
+        .bind(&reminder.operator_id)
+        .bind(&reminder.session_id)
+        .bind(&reminder.message)
+        .bind(reminder.due_at)
+        .bind(reminder.created_at)
+        .execute(pool)
+        .await?;
+
+        Ok(reminder)
+    }
+
+    /// Pending reminders of a ghost, soonest first.
+    pub async fn list_pending(pool: &SqlitePool, ghost_id: &str) -> DbResult<Vec<Reminder>> {
+        let reminders = sqlx::query_as::<_, Reminder>(
+            "SELECT id, ghost_id, operator_id, session_id, message, due_at, created_at, delivered_at
+             FROM reminders
+             WHERE ghost_id = ? AND delivered_at IS NULL
+             ORDER BY due_at, created_at",
+        )
+        .bind(ghost_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    /// Delete a pending reminder of a ghost. Returns whether one was removed.
+    pub async fn cancel(pool: &SqlitePool, ghost_id: &str, id: &str) -> DbResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM reminders WHERE id = ? AND ghost_id = ? AND delivered_at IS NULL",
+        )
+        .bind(id)
+        .bind(ghost_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pending reminders due at or before `now`, oldest first.
+    pub async fn list_due(pool: &SqlitePool, now: i64, limit: i64) -> DbResult<Vec<Reminder>> {
+        let reminders = sqlx::query_as::<_, Reminder>(
+            "SELECT id, ghost_id, operator_id, session_id, message, due_at, created_at, delivered_at
+             FROM reminders
+             WHERE delivered_at IS NULL AND due_at <= ?
+             ORDER BY due_at, created_at
+             LIMIT ?",
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    /// Mark a reminder delivered.
+    pub async fn mark_delivered(pool: &SqlitePool, id: &str, delivered_at: i64) -> DbResult<()> {
+        sqlx::query("UPDATE reminders SET delivered_at = ? WHERE id = ?")
+            .bind(delivered_at)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Due time of the next pending reminder, if any.
+    pub async fn next_due(pool: &SqlitePool) -> DbResult<Option<i64>> {
+        let due_at: Option<i64> =
+            sqlx::query_scalar("SELECT MIN(due_at) FROM reminders WHERE delivered_at IS NULL")
+                .fetch_one(pool)
+                .await?;
+
+        Ok(due_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionOrigin,
+        SessionRepository, test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_reminder_lifecycle() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            ReminderRepository::create(pool, &ghost.id, "sess_missing", "x", 10).await,
+            Err(DbError::SessionNotFound(_))
+        ));
+
+        let later = ReminderRepository::create(pool, &ghost.id, &session.id, "later", 2_000)
+            .await
+            .unwrap();
+        let soon = ReminderRepository::create(pool, &ghost.id, &session.id, "soon", 1_000)
+            .await
+            .unwrap();
+        assert_eq!(soon.operator_id, operator.id);
+
+        let pending = ReminderRepository::list_pending(pool, &ghost.id)
+            .await
+            .unwrap();
+        assert_eq!(pending, vec![soon.clone(), later.clone()]);
+        assert_eq!(
+            ReminderRepository::next_due(pool).await.unwrap(),
+            Some(1_000)
+        );
+
+        let due = ReminderRepository::list_due(pool, 1_500, 10).await.unwrap();
+        assert_eq!(due, vec![soon.clone()]);
+        ReminderRepository::mark_delivered(pool, &soon.id, 1_500)
+            .await
+            .unwrap();
+        assert!(
+            ReminderRepository::list_due(pool, 1_500, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Delivered reminders cannot be cancelled; pending ones can.
+        assert!(
+            !ReminderRepository::cancel(pool, &ghost.id, &soon.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ReminderRepository::cancel(pool, &ghost.id, &later.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(ReminderRepository::next_due(pool).await.unwrap(), None);
+
+        assert_eq!(
+            SessionRepository::get_origin(pool, &session.id)
+                .await
+                .unwrap(),
+            None
+        );
+        SessionRepository::set_origin(pool, &session.id, SessionOrigin::Discord)
+            .await
+            .unwrap();
+        assert_eq!(
+            SessionRepository::get_origin(pool, &session.id)
+                .await
+                .unwrap(),
+            Some(SessionOrigin::Discord)
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Recurring tasks a ghost schedules for itself.
+//!
+//! A task belongs to the session it was created in: each run is a background
+//! job (logged in `job_logs`) whose response is posted into that session.
+//! The schedule is a cron expression parsed by the gateway, which also
+//! computes `next_due`; a task without a `next_due` never runs again.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+
+/// A recurring task.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ScheduledTask {
+    pub id: String,
+    pub ghost_id: String,
+    pub operator_id: String,
+    pub session_id: String,
+    pub name: String,
+    /// Instructions the ghost runs on each occurrence
+    pub prompt: String,
+    /// Cron expression, optionally with a `TZ=` prefix
+    pub schedule: String,
+    /// Paused tasks are kept but not run
+    pub enabled: bool,
+    /// Unix timestamp of the next run; `None` once the schedule has ended
+    pub next_due: Option<i64>,
+    pub last_run_at: Option<i64>,
+    pub created_at: i64,
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, ghost_id, operator_id, session_id, name, prompt, schedule, enabled, next_due, last_run_at, created_at FROM scheduled_tasks";
+
+/// Repository for `scheduled_tasks`.
+pub struct ScheduledTaskRepository;
+
+impl ScheduledTaskRepository {
+    /// Register a task in `session_id`, run on behalf of the session's operator.
+    pub async fn create(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        name: &str,
+        prompt: &str,
+        schedule: &str,
+        next_due: i64,
+    ) -> DbResult<ScheduledTask> {
+        let operator_id: Option<String> =
+            sqlx::query_scalar("SELECT operator_id FROM sessions WHERE id = ? AND ghost_id = ?")
+                .bind(session_id)
+                .bind(ghost_id)
+                .fetch_optional(pool)
+                .await?;
+        let operator_id =
+            operator_id.ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+
+        let task = ScheduledTask {
+            id: format!("task_{}", Uuid::new_v4()),
+            ghost_id: ghost_id.to_string(),
+            operator_id,
+            session_id: session_id.to_string(),
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            schedule: schedule.to_string(),
+            enabled: true,
+            next_due: Some(next_due),
+            last_run_at: None,
+            created_at: Utc::now().timestamp(),
+        };
+        sqlx::query(
+            "INSERT INTO scheduled_tasks
+             (id, ghost_id, operator_id, session_id, name, prompt, schedule, enabled, next_due, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?)",
+        )
+        .bind(&task.id)
+        .bind(&task.ghost_id)
+        .bind(&task.operator_id)
+        .bind(&task.session_id)
+        .bind(&task.name)
+        .bind(&task.prompt)
+        .bind(&task.schedule)
+        .bind(task.next_due)
+        .bind(task.created_at)
+        .execute(pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    pub async fn get(pool: &SqlitePool, id: &str) -> DbResult<Option<ScheduledTask>> {
+        let task = sqlx::query_as::<_, ScheduledTask>(&format!("{SELECT_COLUMNS} WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(task)
+    }
+
+    /// Tasks of a ghost, oldest first.
+    pub async fn list_for_ghost(pool: &SqlitePool, ghost_id: &str) -> DbResult<Vec<ScheduledTask>> {
+        let tasks = sqlx::query_as::<_, ScheduledTask>(&format!(
+            "{SELECT_COLUMNS} WHERE ghost_id = ? ORDER BY created_at, id"
+        ))
+        .bind(ghost_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Tasks of every ghost, grouped by ghost (for operator management).
+    pub async fn list_all(pool: &SqlitePool) -> DbResult<Vec<ScheduledTask>> {
+        let tasks = sqlx::query_as::<_, ScheduledTask>(&format!(
+            "{SELECT_COLUMNS} ORDER BY ghost_id, created_at, id"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Delete a task of a ghost. Returns whether one was removed.
+    pub async fn delete(pool: &SqlitePool, ghost_id: &str, id: &str) -> DbResult<bool> {
+        let result = sqlx::query("DELETE FROM scheduled_tasks WHERE id = ? AND ghost_id = ?")
+            .bind(id)
+            .bind(ghost_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a task regardless of its ghost (operator management).
+    pub async fn delete_by_id(pool: &SqlitePool, id: &str) -> DbResult<bool> {
+        let result = sqlx::query("DELETE FROM scheduled_tasks WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pause or resume a task. `next_due` replaces the stored one, so a
+    /// resumed task doesn't fire for occurrences missed while paused.
+    pub async fn set_enabled(
+        pool: &SqlitePool,
+        id: &str,
+        enabled: bool,
+        next_due: Option<i64>,
+    ) -> DbResult<bool> {
+        let result =
+            sqlx::query("UPDATE scheduled_tasks SET enabled = ?, next_due = ? WHERE id = ?")
+                .bind(enabled)
+                .bind(next_due)
+                .bind(id)
+                .execute(pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Enabled tasks due at or before `now`, oldest first.
+    pub async fn list_due(pool: &SqlitePool, now: i64, limit: i64) -> DbResult<Vec<ScheduledTask>> {
+        let tasks = sqlx::query_as::<_, ScheduledTask>(&format!(
+            "{SELECT_COLUMNS}
+             WHERE enabled = 1 AND next_due IS NOT NULL AND next_due <= ?
+             ORDER BY next_due, created_at
+             LIMIT ?"
+        ))
+        .bind(now)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Record a run started at `ran_at` and move the task to `next_due`.
+    pub async fn record_run(
+        pool: &SqlitePool,
+        id: &str,
+        ran_at: i64,
+        next_due: Option<i64>,
+    ) -> DbResult<()> {
+        sqlx::query("UPDATE scheduled_tasks SET last_run_at = ?, next_due = ? WHERE id = ?")
+            .bind(ran_at)
+            .bind(next_due)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_scheduled_task_lifecycle() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            ScheduledTaskRepository::create(pool, &ghost.id, "sess_missing", "x", "y", "@daily", 1)
+                .await,
+            Err(DbError::SessionNotFound(_))
+        ));
+
+        let rss = ScheduledTaskRepository::create(
+            pool,
+            &ghost.id,
+            &session.id,
+            "RSS digest",
+            "Summarize my RSS topic",
+            "0 7 * * *",
+            1_000,
+        )
+        .await
+        .unwrap();
+        let ci = ScheduledTaskRepository::create(
+            pool,
+            &ghost.id,
+            &session.id,
+            "CI check",
+            "Check CI",
+            "@daily",
+            2_000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rss.operator_id, operator.id);
+        assert_eq!(
+            ScheduledTaskRepository::list_for_ghost(pool, &ghost.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let due = ScheduledTaskRepository::list_due(pool, 1_500, 10)
+            .await
+            .unwrap();
+        assert_eq!(due, vec![rss.clone()]);
+        ScheduledTaskRepository::record_run(pool, &rss.id, 1_500, Some(3_000))
+            .await
+            .unwrap();
+        let rss = ScheduledTaskRepository::get(pool, &rss.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((rss.last_run_at, rss.next_due), (Some(1_500), Some(3_000)));
+
+        // Paused tasks are not due.
+        assert!(
+            ScheduledTaskRepository::set_enabled(pool, &ci.id, false, None)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ScheduledTaskRepository::list_due(pool, 5_000, 10)
+                .await
+                .unwrap()
+                .iter()
+                .all(|task| task.id == rss.id)
+        );
+
+        // A ghost can only delete its own tasks.
+        assert!(
+            !ScheduledTaskRepository::delete(pool, "ghost_other", &rss.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ScheduledTaskRepository::delete(pool, &ghost.id, &rss.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ScheduledTaskRepository::delete_by_id(pool, &ci.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ScheduledTaskRepository::list_all(pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
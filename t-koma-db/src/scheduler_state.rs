@@ -0,0 +1,111 @@
+//! Background job schedule kept across gateway restarts.
+//!
+//! The gateway holds its scheduler in memory; on graceful shutdown it saves
+//! a snapshot here and loads it again on the next start.
+
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// When one background job runs next.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ScheduledJob {
+    /// Job kind as named by the gateway (`heartbeat`, `cron`, ...)
+    pub kind: String,
+    /// Job-specific key (chat key, CRON job key, or a fixed name)
+    pub key: String,
+    /// Unix timestamp of the next run
+    pub next_due: i64,
+    /// Cron expression for recurring jobs, as displayed (`0 9 * * * (UTC+01:00)`)
+    pub schedule: Option<String>,
+}
+
+/// Repository for `scheduler_state`.
+pub struct SchedulerStateRepository;
+
+impl SchedulerStateRepository {
+    /// Replace the saved schedule with `jobs`.
+    pub async fn save(pool: &SqlitePool, jobs: &[ScheduledJob]) -> DbResult<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM scheduler_state")
+            .execute(&mut *tx)
+            .await?;
+        for job in jobs {
+            sqlx::query(
+                "INSERT INTO scheduler_state (kind, key, next_due, schedule) VALUES (?, ?, ?, ?)",
+            )
+            .bind(&job.kind)
+            .bind(&job.key)
+            .bind(job.next_due)
+            .bind(&job.schedule)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The schedule saved by the last graceful shutdown.
+    pub async fn load(pool: &SqlitePool) -> DbResult<Vec<ScheduledJob>> {
+        let jobs = sqlx::query_as::<_, ScheduledJob>(
+            "SELECT kind, key, next_due, schedule FROM scheduler_state ORDER BY kind, key",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(jobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_pool;
+
+    fn job(kind: &str, key: &str, next_due: i64) -> ScheduledJob {
+        ScheduledJob {
+            kind: kind.to_string(),
+            key: key.to_string(),
+            next_due,
+            schedule: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_replaces_schedule() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        SchedulerStateRepository::save(
+            pool,
+            &[
+                job("cron", "alpha:daily", 100),
+                job("job_log_prune", "job_logs", 200),
+            ],
+        )
+        .await
+        .unwrap();
+        SchedulerStateRepository::save(pool, &[job("cron", "alpha:daily", 300)])
+            .await
+            .unwrap();
+
+        let jobs = SchedulerStateRepository::load(pool).await.unwrap();
+        assert_eq!(jobs, vec![job("cron", "alpha:daily", 300)]);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_round_trips() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let daily = ScheduledJob {
+            schedule: Some("0 9 * * * (UTC+01:00)".to_string()),
+            ..job("cron", "alpha:daily", 100)
+        };
+        SchedulerStateRepository::save(pool, &[daily.clone(), job("reminder", "due", 50)])
+            .await
+            .unwrap();
+
+        let jobs = SchedulerStateRepository::load(pool).await.unwrap();
+        assert_eq!(jobs, vec![daily, job("reminder", "due", 50)]);
+    }
+}
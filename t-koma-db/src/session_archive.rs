@@ -0,0 +1,347 @@
+//! Cold storage for idle sessions.
+//!
+//! [`SessionRepository::archive`] writes a session's JSONL export (see
+//! `session_export`) gzipped to `archive/sessions/<id>.jsonl.gz` under the
+//! ghost workspace, then deletes its message rows. The session row stays as a
+//! stub, so listings, usage totals and job logs keep working.
+//!
+//! Message readers and [`SessionRepository::add_message`] call
+//! [`SessionRepository::rehydrate`] first, which restores the messages with
+//! their original IDs (compaction cursors and usage links stay valid) and
+//! removes the file. Full-text search only covers live sessions.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::error::{DbError, DbResult};
+use crate::ghosts::{GhostRepository, ghost_workspace_path};
+use crate::session_export::{SessionExportRecord, parse_export};
+use crate::sessions::{SessionRepository, bind_usage};
+
+/// Archive directory, relative to the ghost workspace.
+const ARCHIVE_DIR: &str = "archive/sessions";
+
+impl SessionRepository {
+    /// Move a session's messages to cold storage.
+    ///
+    /// Returns `false` when the session is already archived or has no
+    /// messages. Fails without deleting anything if a message arrives while
+    /// the archive is being written.
+    pub async fn archive(pool: &SqlitePool, session_id: &str) -> DbResult<bool> {
+        let session = Self::get_by_id(pool, session_id)
+            .await?
+            .ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+        if Self::archive_path(pool, session_id).await?.is_some() {
+            return Ok(false);
+        }
+        let message_count = Self::count_messages(pool, session_id).await?;
+        if message_count == 0 {
+            return Ok(false);
+        }
+
+        let jsonl = Self::export(pool, session_id).await?;
+        let relative = format!("{ARCHIVE_DIR}/{session_id}.jsonl.gz");
+        let path = Self::resolve_archive_path(pool, &session.ghost_id, &relative).await?;
+        let write_path = path.clone();
+        tokio::task::spawn_blocking(move || write_archive(&write_path, &jsonl))
+            .await
+            .map_err(|e| DbError::Io(std::io::Error::other(e)))??;
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM prompt_cache WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        let deleted = sqlx::query("DELETE FROM messages WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deleted as i64 != message_count {
+            drop(tx);
+            let _ = std::fs::remove_file(&path);
+            return Err(DbError::Serialization(format!(
+                "session {session_id} changed while archiving"
+            )));
+        }
+        sqlx::query(
+            "UPDATE sessions
+             SET archive_path = ?, archived_at = ?, archived_message_count = ?
+             WHERE id = ?",
+        )
+        .bind(&relative)
+        .bind(Utc::now().timestamp())
+        .bind(message_count)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!(
+            "Archived session {} ({} messages) to {}",
+            session_id,
+            message_count,
+            path.display()
+        );
+        Ok(true)
+    }
+
+    /// Restore an archived session's messages. No-op (`false`) for live sessions.
+    pub async fn rehydrate(pool: &SqlitePool, session_id: &str) -> DbResult<bool> {
+        let Some((ghost_id, relative)) = sqlx::query_as::<_, (String, String)>(
+            "SELECT ghost_id, archive_path FROM sessions
+             WHERE id = ? AND archive_path IS NOT NULL",
+        )
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        let path = Self::resolve_archive_path(pool, &ghost_id, &relative).await?;
+        let read_path = path.clone();
+        let jsonl = match tokio::task::spawn_blocking(move || read_archive(&read_path))
+            .await
+            .map_err(|e| DbError::Io(std::io::Error::other(e)))?
+        {
+            Ok(jsonl) => jsonl,
+            // A concurrent rehydrate finished first and removed the file.
+            Err(_) if Self::archive_path(pool, session_id).await?.is_none() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut restored = 0;
+        for record in parse_export(&jsonl)? {
+            let SessionExportRecord::Message {
+                id,
+                role,
+                content,
+                model,
+                created_at,
+                usage,
+            } = record
+            else {
+                continue;
+            };
+            let content_json = serde_json::to_string(&content)
+                .map_err(|e| DbError::Serialization(e.to_string()))?;
+            let insert = sqlx::query(
+                "INSERT OR IGNORE INTO messages
+                     (id, ghost_id, session_id, role, content, model, created_at, input_tokens,
+                      output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&id)
+            .bind(&ghost_id)
+            .bind(session_id)
+            .bind(role.to_string())
+            .bind(&content_json)
+            .bind(&model)
+            .bind(created_at);
+            restored += bind_usage(insert, usage.as_ref())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        let cleared = sqlx::query(
+            "UPDATE sessions
+             SET archive_path = NULL, archived_at = NULL, archived_message_count = NULL
+             WHERE id = ? AND archive_path IS NOT NULL",
+        )
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        if cleared > 0 {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to remove session archive {}: {}", path.display(), e);
+            }
+            info!("Rehydrated session {} ({} messages)", session_id, restored);
+        }
+        Ok(cleared > 0)
+    }
+
+    /// Archive up to `limit` inactive sessions last updated before
+    /// `idle_before` (unix seconds), oldest first.
+    ///
+    /// Returns the number of archived sessions; failures are logged and skipped.
+    pub async fn archive_idle(pool: &SqlitePool, idle_before: i64, limit: i64) -> DbResult<u64> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT s.id FROM sessions s
+             WHERE s.is_active = 0
+               AND s.archive_path IS NULL
+               AND s.updated_at < ?
+               AND EXISTS (SELECT 1 FROM messages m WHERE m.session_id = s.id)
+             ORDER BY s.updated_at ASC
+             LIMIT ?",
+        )
+        .bind(idle_before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        let mut archived = 0;
+        for id in ids {
+            match Self::archive(pool, &id).await {
+                Ok(true) => archived += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to archive session {}: {}", id, e),
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Workspace-relative archive path, if the session is archived.
+    pub async fn archive_path(pool: &SqlitePool, session_id: &str) -> DbResult<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT archive_path FROM sessions WHERE id = ?")
+                .bind(session_id)
+                .fetch_optional(pool)
+                .await?
+                .flatten(),
+        )
+    }
+
+    async fn resolve_archive_path(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        relative: &str,
+    ) -> DbResult<PathBuf> {
+        let ghost = GhostRepository::get_by_id(pool, ghost_id)
+            .await?
+            .ok_or_else(|| DbError::GhostNotFound(ghost_id.to_string()))?;
+        Ok(ghost_workspace_path(&ghost.name)?.join(relative))
+    }
+}
+
+fn write_archive(path: &Path, jsonl: &str) -> DbResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("gz.tmp");
+    let file = std::fs::File::create(&tmp)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(jsonl.as_bytes())?;
+    encoder.finish()?.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_archive(path: &Path) -> DbResult<String> {
+    let mut jsonl = String::new();
+    GzDecoder::new(std::fs::File::open(path)?).read_to_string(&mut jsonl)?;
+    Ok(jsonl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::{ContentBlock, MessageRole};
+    use crate::{
+        ENV_MUTEX, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    #[test]
+    fn test_archive_and_rehydrate_on_access() {
+        let dir = tempfile::tempdir().unwrap();
+        let _guard = ENV_MUTEX.lock().unwrap();
+        // SAFETY: test-scoped env mutation.
+        unsafe { std::env::set_var("T_KOMA_DATA_DIR", dir.path()) };
+        // Block on a local runtime so the env guard is never held across an await.
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(archive_and_rehydrate(dir.path()));
+        // SAFETY: test-scoped env mutation cleanup.
+        unsafe { std::env::remove_var("T_KOMA_DATA_DIR") };
+    }
+
+    async fn archive_and_rehydrate(data_dir: &Path) {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let first = SessionRepository::add_message(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Operator,
+            vec![ContentBlock::Text {
+                text: "Remember the garden plan".to_string(),
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+        SessionRepository::update_compaction(pool, &session.id, "summary", &first.id)
+            .await
+            .unwrap();
+        SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        let archived = SessionRepository::archive_idle(pool, i64::MAX, 10)
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+        let file = data_dir
+            .join("ghosts/TestGhost/archive/sessions")
+            .join(format!("{}.jsonl.gz", session.id));
+        assert!(file.exists());
+        let raw: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE session_id = ?")
+            .bind(&session.id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(raw, 0);
+        let listed = SessionRepository::list_for_ghost(pool, &ghost.id)
+            .await
+            .unwrap();
+        let stub = listed.iter().find(|s| s.id == session.id).unwrap();
+        assert_eq!(stub.message_count, 1);
+        assert!(!SessionRepository::archive(pool, &session.id).await.unwrap());
+
+        // Reading the history restores it with the original IDs.
+        let messages = SessionRepository::get_messages_after(pool, &session.id, &first.id)
+            .await
+            .unwrap();
+        assert!(messages.is_empty());
+        let messages = SessionRepository::list_messages(pool, &session.id)
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id, first.id);
+        assert!(!file.exists());
+        assert!(
+            SessionRepository::archive_path(pool, &session.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! History of a session's compactions.
+//!
+//! Each compaction folds the messages after the previous cursor, up to and
+//! including the new one, into a summary. [`SessionRepository::record_compaction`]
+//! moves the session's cursor and keeps a row per compaction, so the summary
+//! sent to the model can always be traced back to the raw messages it
+//! replaced. Those messages are never deleted.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+use crate::sessions::SessionRepository;
+
+/// One compaction of a session.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SessionCompaction {
+    pub id: String,
+    pub session_id: String,
+    /// Cursor before this compaction (`None` for the first one)
+    pub previous_cursor_id: Option<String>,
+    /// Last message folded into the summary
+    pub cursor_id: String,
+    /// Messages folded by this compaction
+    pub message_count: i64,
+    /// Summary as shown to the model
+    pub summary: String,
+    /// Structured summary (JSON), when the model produced one
+    pub details: Option<String>,
+    pub created_at: i64,
+}
+
+impl SessionRepository {
+    /// Move the session's compaction cursor to `cursor_id`, store `summary`,
+    /// and archive the compaction that produced it.
+    pub async fn record_compaction(
+        pool: &SqlitePool,
+        session_id: &str,
+        summary: &str,
+        details: Option<&str>,
+        cursor_id: &str,
+        message_count: i64,
+    ) -> DbResult<SessionCompaction> {
+        let mut tx = pool.begin().await?;
+        let previous_cursor_id: Option<Option<String>> =
+            sqlx::query_scalar("SELECT compaction_cursor_id FROM sessions WHERE id = ?")
+                .bind(session_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let previous_cursor_id =
+            previous_cursor_id.ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+
+        let compaction = SessionCompaction {
+            id: format!("cmp_{}", Uuid::new_v4()),
+            session_id: session_id.to_string(),
+            previous_cursor_id,
+            cursor_id: cursor_id.to_string(),
+            message_count,
+            summary: summary.to_string(),
+            details: details.map(str::to_string),
+            created_at: Utc::now().timestamp(),
+        };
+        sqlx::query(
+            "INSERT INTO session_compactions
+                 (id, session_id, previous_cursor_id, cursor_id, message_count, summary, details,
+                  created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&compaction.id)
+        .bind(&compaction.session_id)
+        .bind(&compaction.previous_cursor_id)
+        .bind(&compaction.cursor_id)
+        .bind(compaction.message_count)
+        .bind(&compaction.summary)
+        .bind(&compaction.details)
+        .bind(compaction.created_at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE sessions SET compaction_summary = ?, compaction_cursor_id = ? WHERE id = ?",
+        )
+        .bind(summary)
+        .bind(cursor_id)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(compaction)
+    }
+
+    /// A session's compactions, oldest first.
+    pub async fn list_compactions(
+        pool: &SqlitePool,
+        session_id: &str,
+    ) -> DbResult<Vec<SessionCompaction>> {
+        let compactions = sqlx::query_as::<_, SessionCompaction>(
+            "SELECT id, session_id, previous_cursor_id, cursor_id, message_count, summary,
+                    details, created_at
+             FROM session_compactions
+             WHERE session_id = ?
+             ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(compactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::{ContentBlock, MessageRole};
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_record_compaction_chains_cursors() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for text in ["one", "two", "three", "four"] {
+            let message = SessionRepository::add_message(
+                pool,
+                &ghost.id,
+                &session.id,
+                MessageRole::Operator,
+                vec![ContentBlock::Text {
+                    text: text.to_string(),
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+            ids.push(message.id);
+        }
+
+        assert!(matches!(
+            SessionRepository::record_compaction(pool, "sess_missing", "s", None, &ids[0], 1).await,
+            Err(DbError::SessionNotFound(_))
+        ));
+
+        let first = SessionRepository::record_compaction(
+            pool,
+            &session.id,
+            "first summary",
+            Some(r#"{"decisions":[],"open_tasks":[],"facts":["one"]}"#),
+            &ids[1],
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.previous_cursor_id, None);
+        let second = SessionRepository::record_compaction(
+            pool,
+            &session.id,
+            "second summary",
+            None,
+            &ids[2],
+            1,
+        )
+        .await
+        .unwrap();
+        assert_eq!(second.previous_cursor_id.as_deref(), Some(ids[1].as_str()));
+
+        let session = SessionRepository::get_by_id(pool, &session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.compaction_summary.as_deref(),
+            Some("second summary")
+        );
+        assert_eq!(
+            session.compaction_cursor_id.as_deref(),
+            Some(ids[2].as_str())
+        );
+
+        let compactions = SessionRepository::list_compactions(pool, &session.id)
+            .await
+            .unwrap();
+        assert_eq!(compactions, vec![first, second]);
+        // The raw messages stay in place.
+        assert_eq!(
+            SessionRepository::count_messages(pool, &session.id)
+                .await
+                .unwrap(),
+            4
+        );
+    }
+}
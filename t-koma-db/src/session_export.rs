@@ -0,0 +1,466 @@
+//! Session export/import as self-contained JSONL.
+//!
+//! The first line is a `session` header, followed by one `message` line per
+//! message (content blocks verbatim, including tool uses and results) and one
+//! `usage` line per recorded API request. Image/file blocks keep their
+//! original paths; the referenced files are not embedded.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+use crate::ghosts::GhostRepository;
+use crate::sessions::{
+    ContentBlock, MessageRole, MessageUsage, Session, SessionRepository, bind_usage,
+};
+use crate::usage_log::{TokenUsage, UsageLog, UsageLogRepository};
+
+/// Current export format version; bumped on incompatible changes.
+pub const SESSION_EXPORT_VERSION: u32 = 1;
+
+/// One line of a session export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionExportRecord {
+    Session {
+        format_version: u32,
+        id: String,
+        /// Name of the source ghost (informational).
+        ghost_name: Option<String>,
+        created_at: i64,
+        updated_at: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        compaction_summary: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        compaction_cursor_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    Message {
+        id: String,
+        role: MessageRole,
+        content: Vec<ContentBlock>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        created_at: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        usage: Option<MessageUsage>,
+    },
+    Usage {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+        request_at: i64,
+        model: String,
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_read_tokens: u32,
+        cache_creation_tokens: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cost_usd: Option<f64>,
+    },
+}
+
+impl SessionRepository {
+    /// Export a session (messages and usage) as JSONL.
+    pub async fn export(pool: &SqlitePool, session_id: &str) -> DbResult<String> {
+        let session = Self::get_by_id(pool, session_id)
+            .await?
+            .ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+        let ghost_name = GhostRepository::get_by_id(pool, &session.ghost_id)
+            .await?
+            .map(|g| g.name);
+
+        let mut records = vec![SessionExportRecord::Session {
+            format_version: SESSION_EXPORT_VERSION,
+            id: session.id.clone(),
+            ghost_name,
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            compaction_summary: session.compaction_summary,
+            compaction_cursor_id: session.compaction_cursor_id,
+            title: session.title,
+            tags: session.tags,
+        }];
+        for message in Self::list_messages(pool, session_id).await? {
+            records.push(SessionExportRecord::Message {
+                id: message.id,
+                role: message.role,
+                content: message.content,
+                model: message.model,
+                created_at: message.created_at,
+                usage: message.usage,
+            });
+        }
+        for usage in UsageLogRepository::list_for_session(pool, session_id).await? {
+            records.push(SessionExportRecord::Usage {
+                message_id: usage.message_id,
+                request_at: usage.request_at,
+                model: usage.model,
+                input_tokens: usage.tokens.input_tokens,
+                output_tokens: usage.tokens.output_tokens,
+                cache_read_tokens: usage.tokens.cache_read_tokens,
+                cache_creation_tokens: usage.tokens.cache_creation_tokens,
+                cost_usd: usage.cost_usd,
+            });
+        }
+
+        let mut out = String::new();
+        for record in &records {
+            let line =
+                serde_json::to_string(record).map_err(|e| DbError::Serialization(e.to_string()))?;
+            out.push_str(&line);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Import a JSONL export as a new, inactive session for `ghost_id`/`operator_id`.
+    ///
+    /// Sessions and messages get fresh IDs so an export can be imported more
+    /// than once; timestamps, compaction state, and usage links are preserved.
+    pub async fn import(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        operator_id: &str,
+        jsonl: &str,
+    ) -> DbResult<Session> {
+        let records = parse_export(jsonl)?;
+        let Some(SessionExportRecord::Session {
+            format_version,
+            id: source_id,
+            created_at,
+            updated_at,
+            compaction_summary,
+            compaction_cursor_id,
+            title,
+            tags,
+            ..
+        }) = records.first().cloned()
+        else {
+            return Err(DbError::Serialization(
+                "session export must start with a session record".to_string(),
+            ));
+        };
+        if format_version > SESSION_EXPORT_VERSION {
+            return Err(DbError::Serialization(format!(
+                "unsupported session export version {format_version}"
+            )));
+        }
+
+        let id = format!("sess_{}", Uuid::new_v4());
+        let mut message_ids: HashMap<String, String> = HashMap::new();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO sessions (id, ghost_id, operator_id, created_at, updated_at, is_active)
+             VALUES (?, ?, ?, ?, ?, 0)",
+        )
+        .bind(&id)
+        .bind(ghost_id)
+        .bind(operator_id)
+        .bind(created_at)
+        .bind(updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut usage = Vec::new();
+        for record in records.into_iter().skip(1) {
+            match record {
+                SessionExportRecord::Message {
+                    id: old_id,
+                    role,
+                    content,
+                    model,
+                    created_at,
+                    usage: message_usage,
+                } => {
+                    let new_id = format!("msg_{}", Uuid::new_v4());
+                    let content_json = serde_json::to_string(&content)
+                        .map_err(|e| DbError::Serialization(e.to_string()))?;
+                    let insert = sqlx::query(
+                        "INSERT INTO messages (id, ghost_id, session_id, role, content, model, created_at,
+                             input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
+                             cost_usd)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(&new_id)
+                    .bind(ghost_id)
+                    .bind(&id)
+                    .bind(role.to_string())
+                    .bind(&content_json)
+                    .bind(&model)
+                    .bind(created_at);
+                    bind_usage(insert, message_usage.as_ref())
+                        .execute(&mut *tx)
+                        .await?;
+                    message_ids.insert(old_id, new_id);
+                }
+                SessionExportRecord::Usage {
+                    message_id,
+                    request_at,
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_read_tokens,
+                    cache_creation_tokens,
+                    cost_usd,
+                } => usage.push(UsageLog {
+                    id: format!("usage_{}", Uuid::new_v4()),
+                    ghost_id: ghost_id.to_string(),
+                    session_id: id.clone(),
+                    message_id,
+                    request_at,
+                    model,
+                    tokens: TokenUsage {
+                        input_tokens,
+                        output_tokens,
+                        cache_read_tokens,
+                        cache_creation_tokens,
+                    },
+                    cost_usd,
+                }),
+                SessionExportRecord::Session { .. } => {
+                    return Err(DbError::Serialization(
+                        "session export contains more than one session record".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let cursor_id = compaction_cursor_id.and_then(|old| message_ids.get(&old).cloned());
+        let compaction_summary = cursor_id.as_ref().and(compaction_summary);
+        if let (Some(summary), Some(cursor)) = (&compaction_summary, &cursor_id) {
+            sqlx::query(
+                "UPDATE sessions SET compaction_summary = ?, compaction_cursor_id = ? WHERE id = ?",
+            )
+            .bind(summary)
+            .bind(cursor)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(title) = &title {
+            let tags_json =
+                serde_json::to_string(&tags).map_err(|e| DbError::Serialization(e.to_string()))?;
+            sqlx::query("UPDATE sessions SET title = ?, tags = ? WHERE id = ?")
+                .bind(title)
+                .bind(tags_json)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for mut log in usage {
+            log.message_id = log
+                .message_id
+                .and_then(|old| message_ids.get(&old).cloned());
+            sqlx::query(
+                "INSERT INTO usage_log (id, ghost_id, session_id, message_id, created_at, model,
+                    input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
+                    cost_usd)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&log.id)
+            .bind(&log.ghost_id)
+            .bind(&log.session_id)
+            .bind(&log.message_id)
+            .bind(log.request_at)
+            .bind(&log.model)
+            .bind(log.tokens.input_tokens)
+            .bind(log.tokens.output_tokens)
+            .bind(log.tokens.cache_read_tokens)
+            .bind(log.tokens.cache_creation_tokens)
+            .bind(log.cost_usd)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        info!(
+            "Imported session {} as {} ({} messages)",
+            source_id,
+            id,
+            message_ids.len()
+        );
+
+        Ok(Session {
+            id,
+            ghost_id: ghost_id.to_string(),
+            operator_id: operator_id.to_string(),
+            created_at,
+            updated_at,
+            is_active: false,
+            compaction_summary,
+            compaction_cursor_id: cursor_id,
+            tags: if title.is_some() { tags } else { Vec::new() },
+            title,
+        })
+    }
+}
+
+pub(crate) fn parse_export(jsonl: &str) -> DbResult<Vec<SessionExportRecord>> {
+    jsonl
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| DbError::Serialization(format!("line {}: {e}", index + 1)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        OperatorAccessLevel, OperatorRepository, Platform, test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        SessionRepository::add_message(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Operator,
+            vec![ContentBlock::Text {
+                text: "List files".to_string(),
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+        let reply = SessionRepository::add_message_with_usage(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Ghost,
+            vec![
+                ContentBlock::ToolUse {
+                    id: "tool_1".to_string(),
+                    name: "list_dir".to_string(),
+                    input: serde_json::json!({ "path": "." }),
+                },
+                ContentBlock::ToolResult {
+                    tool_use_id: "tool_1".to_string(),
+                    content: "a.txt".to_string(),
+                    is_error: None,
+                },
+            ],
+            Some("model-a"),
+            Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cache_read_tokens: 100,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        SessionRepository::update_compaction(pool, &session.id, "summary", &reply.id)
+            .await
+            .unwrap();
+        SessionRepository::set_title(pool, &session.id, "Listing files", &["files".to_string()])
+            .await
+            .unwrap();
+        UsageLogRepository::insert(
+            pool,
+            &UsageLog::new(
+                &ghost.id,
+                &session.id,
+                Some(&reply.id),
+                "model-a",
+                TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    ..Default::default()
+                },
+            ),
+        )
+        .await
+        .unwrap();
+
+        let jsonl = SessionRepository::export(pool, &session.id).await.unwrap();
+        assert_eq!(jsonl.lines().count(), 4);
+        assert!(jsonl.starts_with("{\"kind\":\"session\""));
+
+        let imported = SessionRepository::import(pool, &ghost.id, &operator.id, &jsonl)
+            .await
+            .unwrap();
+        assert_ne!(imported.id, session.id);
+        assert!(!imported.is_active);
+        assert_eq!(imported.compaction_summary.as_deref(), Some("summary"));
+        assert_eq!(imported.title.as_deref(), Some("Listing files"));
+        let stored = SessionRepository::get_by_id(pool, &imported.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tags, vec!["files".to_string()]);
+
+        let messages = SessionRepository::list_messages(pool, &imported.id)
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].model.as_deref(), Some("model-a"));
+        assert!(messages[0].usage.is_none());
+        assert_eq!(messages[1].usage, reply.usage);
+        assert_eq!(messages[1].usage.as_ref().unwrap().prompt_tokens(), 110);
+        assert!(matches!(
+            messages[1].content[1],
+            ContentBlock::ToolResult { .. }
+        ));
+        assert_eq!(
+            imported.compaction_cursor_id.as_deref(),
+            Some(messages[1].id.as_str())
+        );
+
+        let totals = UsageLogRepository::session_totals(pool, &imported.id)
+            .await
+            .unwrap();
+        assert_eq!(totals.request_count, 1);
+        assert_eq!(totals.input_tokens, 10);
+
+        let active = SessionRepository::get_active(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.id, session.id, "import must not switch sessions");
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_missing_header() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let jsonl = r#"{"kind":"message","id":"m","role":"Operator","content":[],"created_at":0}"#;
+
+        let result = SessionRepository::import(pool, "ghost", "operator", jsonl).await;
+        assert!(matches!(result, Err(DbError::Serialization(_))));
+    }
+}
@@ -7,6 +7,7 @@ use tracing::{debug, info};
 use uuid::Uuid;
 
 use crate::error::{DbError, DbResult};
+use crate::usage_log::{TokenUsage, UsageLogRepository};
 
 /// Message role types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,6 +37,59 @@ impl std::str::FromStr for MessageRole {
     }
 }
 
+/// Interface a session was last chatted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOrigin {
+    Discord,
+    Telegram,
+    Slack,
+    Email,
+    Ws,
+}
+
+impl SessionOrigin {
+    /// Origin for an operator-flow interface name (`None` is the WebSocket API).
+    pub fn from_interface(interface: Option<&str>) -> Self {
+        match interface {
+            Some("discord") => SessionOrigin::Discord,
+            Some("telegram") => SessionOrigin::Telegram,
+            Some("slack") => SessionOrigin::Slack,
+            Some("email") => SessionOrigin::Email,
+            _ => SessionOrigin::Ws,
+        }
+    }
+}
+
+impl std::fmt::Display for SessionOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionOrigin::Discord => write!(f, "discord"),
+            SessionOrigin::Telegram => write!(f, "telegram"),
+            SessionOrigin::Slack => write!(f, "slack"),
+            SessionOrigin::Email => write!(f, "email"),
+            SessionOrigin::Ws => write!(f, "ws"),
+        }
+    }
+}
+
+impl std::str::FromStr for SessionOrigin {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "discord" => Ok(SessionOrigin::Discord),
+            "telegram" => Ok(SessionOrigin::Telegram),
+            "slack" => Ok(SessionOrigin::Slack),
+            "email" => Ok(SessionOrigin::Email),
+            "ws" => Ok(SessionOrigin::Ws),
+            _ => Err(DbError::Serialization(format!(
+                "invalid session origin: {s}"
+            ))),
+        }
+    }
+}
+
 /// Content block types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -44,7 +98,7 @@ pub enum ContentBlock {
         text: String,
     },
     Image {
-        /// Absolute path to the image file on disk.
+        /// Absolute path to the image file on disk, or an `http(s)`/`data:` URL.
         path: String,
         /// MIME type (e.g. "image/png", "image/jpeg").
         mime_type: String,
@@ -70,6 +124,17 @@ pub enum ContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
     },
+    /// Model reasoning that preceded the reply, kept apart from its text.
+    Thinking {
+        /// Reasoning text, or the encrypted payload when `redacted`.
+        thinking: String,
+        /// Provider signature, required to send the block back (Anthropic).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+        /// The provider redacted the reasoning; `thinking` is opaque.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        redacted: bool,
+    },
 }
 
 /// A message in a session
@@ -81,6 +146,31 @@ pub struct Message {
     pub content: Vec<ContentBlock>,
     pub model: Option<String>,
     pub created_at: i64,
+    /// Usage of the request that produced this (ghost) message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<MessageUsage>,
+}
+
+/// Provider-reported usage attributed to a single message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageUsage {
+    #[serde(flatten)]
+    pub tokens: TokenUsage,
+    /// USD at the model's pricing when stored; `None` if unpriced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl MessageUsage {
+    /// Tokens the request sent: uncached input plus cache reads and writes.
+    pub fn prompt_tokens(&self) -> u32 {
+        self.tokens.input_tokens + self.tokens.cache_read_tokens + self.tokens.cache_creation_tokens
+    }
+
+    /// Prompt plus output tokens, i.e. the context this message leaves behind.
+    pub fn context_tokens(&self) -> u32 {
+        self.prompt_tokens() + self.tokens.output_tokens
+    }
 }
 
 /// A session (conversation container)
@@ -96,6 +186,10 @@ pub struct Session {
     pub compaction_summary: Option<String>,
     /// ID of the last message included in the compaction summary.
     pub compaction_cursor_id: Option<String>,
+    /// Short generated title (set once the session has a few exchanges).
+    pub title: Option<String>,
+    /// Generated topic tags.
+    pub tags: Vec<String>,
 }
 
 /// Session info for listing
@@ -106,8 +200,39 @@ pub struct SessionInfo {
     pub updated_at: i64,
     pub message_count: i64,
     pub is_active: bool,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Optional filters for [`SessionRepository::search_messages`].
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearchFilters {
+    /// Only sessions owned by this operator.
+    pub operator_id: Option<String>,
+    /// Only this session.
+    pub session_id: Option<String>,
+    pub role: Option<MessageRole>,
+    /// Unix seconds, inclusive.
+    pub since: Option<i64>,
+    /// Unix seconds, inclusive.
+    pub until: Option<i64>,
+    /// Maximum hits (default 20).
+    pub limit: Option<usize>,
+}
+
+/// A message matching a full-text search, best match first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSearchHit {
+    pub message_id: String,
+    pub session_id: String,
+    pub role: MessageRole,
+    pub created_at: i64,
+    /// Matching excerpt with hits wrapped in `[` `]`.
+    pub snippet: String,
 }
 
+const DEFAULT_MESSAGE_SEARCH_LIMIT: usize = 20;
+
 /// Session repository for database operations
 pub struct SessionRepository;
 
@@ -151,13 +276,15 @@ impl SessionRepository {
             is_active: true,
             compaction_summary: None,
             compaction_cursor_id: None,
+            title: None,
+            tags: Vec::new(),
         })
     }
 
     /// Get session by ID
     pub async fn get_by_id(pool: &SqlitePool, id: &str) -> DbResult<Option<Session>> {
         let row = sqlx::query_as::<_, SessionRow>(
-            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
              FROM sessions
              WHERE id = ?",
         )
@@ -175,7 +302,7 @@ impl SessionRepository {
         ghost_id: &str,
     ) -> DbResult<Option<Session>> {
         let row = sqlx::query_as::<_, SessionRow>(
-            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
              FROM sessions
              WHERE id = ? AND ghost_id = ?",
         )
@@ -194,7 +321,7 @@ impl SessionRepository {
         operator_id: &str,
     ) -> DbResult<Option<Session>> {
         let row = sqlx::query_as::<_, SessionRow>(
-            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
              FROM sessions
              WHERE ghost_id = ? AND operator_id = ? AND is_active = 1",
         )
@@ -234,8 +361,8 @@ impl SessionRepository {
         operator_id: &str,
     ) -> DbResult<Vec<SessionInfo>> {
         let rows = sqlx::query_as::<_, SessionInfoRow>(
-            "SELECT s.id, s.created_at, s.updated_at, s.is_active,
-                    COUNT(m.id) as message_count
+            "SELECT s.id, s.created_at, s.updated_at, s.is_active, s.title, s.tags,
+                    COALESCE(s.archived_message_count, COUNT(m.id)) as message_count
              FROM sessions s
              LEFT JOIN messages m ON s.id = m.session_id
              WHERE s.ghost_id = ? AND s.operator_id = ?
@@ -253,8 +380,8 @@ impl SessionRepository {
     /// List all sessions for a ghost (admin view, no operator filter).
     pub async fn list_for_ghost(pool: &SqlitePool, ghost_id: &str) -> DbResult<Vec<SessionInfo>> {
         let rows = sqlx::query_as::<_, SessionInfoRow>(
-            "SELECT s.id, s.created_at, s.updated_at, s.is_active,
-                    COUNT(m.id) as message_count
+            "SELECT s.id, s.created_at, s.updated_at, s.is_active, s.title, s.tags,
+                    COALESCE(s.archived_message_count, COUNT(m.id)) as message_count
              FROM sessions s
              LEFT JOIN messages m ON s.id = m.session_id
              WHERE s.ghost_id = ?
@@ -274,7 +401,7 @@ impl SessionRepository {
         before_unix_seconds: i64,
     ) -> DbResult<Vec<Session>> {
         let rows = sqlx::query_as::<_, SessionRow>(
-            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
              FROM sessions
              WHERE is_active = 1 AND updated_at <= ?
              ORDER BY updated_at ASC",
@@ -326,14 +453,44 @@ impl SessionRepository {
         content: Vec<ContentBlock>,
         model: Option<&str>,
     ) -> DbResult<Message> {
+        Self::add_message_with_usage(pool, ghost_id, session_id, role, content, model, None).await
+    }
+
+    /// Add a message along with the usage of the request that produced it.
+    ///
+    /// The cost is computed from the model's pricing row, if any.
+    pub async fn add_message_with_usage(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        session_id: &str,
+        role: MessageRole,
+        content: Vec<ContentBlock>,
+        model: Option<&str>,
+        tokens: Option<TokenUsage>,
+    ) -> DbResult<Message> {
+        Self::rehydrate(pool, session_id).await?;
         let id = format!("msg_{}", Uuid::new_v4());
         let now = Utc::now().timestamp();
         let content_json =
             serde_json::to_string(&content).map_err(|e| DbError::Serialization(e.to_string()))?;
+        let usage = match tokens {
+            Some(tokens) => {
+                let pricing = match model {
+                    Some(model) => UsageLogRepository::get_pricing(pool, model).await?,
+                    None => None,
+                };
+                Some(MessageUsage {
+                    cost_usd: pricing.map(|p| p.cost(&tokens)),
+                    tokens,
+                })
+            }
+            None => None,
+        };
 
-        sqlx::query(
-            "INSERT INTO messages (id, ghost_id, session_id, role, content, model, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        let mut insert = sqlx::query(
+            "INSERT INTO messages (id, ghost_id, session_id, role, content, model, created_at,
+                 input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&id)
         .bind(ghost_id)
@@ -341,9 +498,9 @@ impl SessionRepository {
         .bind(role.to_string())
         .bind(&content_json)
         .bind(model)
-        .bind(now)
-        .execute(pool)
-        .await?;
+        .bind(now);
+        insert = bind_usage(insert, usage.as_ref());
+        insert.execute(pool).await?;
 
         sqlx::query("UPDATE sessions SET updated_at = ? WHERE id = ?")
             .bind(now)
@@ -358,13 +515,16 @@ impl SessionRepository {
             content,
             model: model.map(|m| m.to_string()),
             created_at: now,
+            usage,
         })
     }
 
-    /// List messages for a session
+    /// List messages for a session (rehydrating it from cold storage if archived)
     pub async fn list_messages(pool: &SqlitePool, session_id: &str) -> DbResult<Vec<Message>> {
+        Self::rehydrate(pool, session_id).await?;
         let rows = sqlx::query_as::<_, MessageRow>(
-            "SELECT id, session_id, role, content, model, created_at
+            "SELECT id, session_id, role, content, model, created_at, input_tokens,
+                    output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd
              FROM messages
              WHERE session_id = ?
              ORDER BY created_at ASC",
@@ -383,8 +543,10 @@ impl SessionRepository {
         pool: &SqlitePool,
         session_id: &str,
     ) -> DbResult<Option<Message>> {
+        Self::rehydrate(pool, session_id).await?;
         let row = sqlx::query_as::<_, MessageRow>(
-            "SELECT id, session_id, role, content, model, created_at
+            "SELECT id, session_id, role, content, model, created_at, input_tokens,
+                    output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd
              FROM messages
              WHERE session_id = ?
              ORDER BY created_at DESC
@@ -400,7 +562,7 @@ impl SessionRepository {
     /// List all active sessions.
     pub async fn list_active(pool: &SqlitePool) -> DbResult<Vec<Session>> {
         let rows = sqlx::query_as::<_, SessionRow>(
-            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
              FROM sessions
              WHERE is_active = 1
              ORDER BY updated_at ASC",
@@ -440,8 +602,10 @@ impl SessionRepository {
         session_id: &str,
         since_unix_seconds: i64,
     ) -> DbResult<Vec<Message>> {
+        Self::rehydrate(pool, session_id).await?;
         let rows = sqlx::query_as::<_, MessageRow>(
-            "SELECT id, session_id, role, content, model, created_at
+            "SELECT id, session_id, role, content, model, created_at, input_tokens,
+                    output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd
              FROM messages
              WHERE session_id = ? AND created_at > ?
              ORDER BY created_at ASC",
@@ -472,6 +636,22 @@ impl SessionRepository {
         Ok(row.try_get::<i64, _>("count").unwrap_or(0))
     }
 
+    /// Count messages in one session since a unix timestamp (seconds)
+    pub async fn count_session_messages_since(
+        pool: &SqlitePool,
+        session_id: &str,
+        since_unix_seconds: i64,
+    ) -> DbResult<i64> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) as count FROM messages WHERE session_id = ? AND created_at >= ?",
+        )
+        .bind(session_id)
+        .bind(since_unix_seconds)
+        .fetch_one(pool)
+        .await?;
+        Ok(row.try_get::<i64, _>("count").unwrap_or(0))
+    }
+
     /// Delete a session if it belongs to the ghost and operator
     pub async fn delete(
         pool: &SqlitePool,
@@ -495,6 +675,117 @@ impl SessionRepository {
         Ok(())
     }
 
+    /// Fork a session into a new active session for the same ghost and operator.
+    ///
+    /// The fork's history is a copy of the source messages up to and including
+    /// `at_message_id`, with original timestamps preserved. The compaction
+    /// summary is carried over only when its cursor lies within the copied range.
+    /// Per-message usage stays with the source, like its `usage_log` rows.
+    /// The source session is left untouched.
+    pub async fn fork(
+        pool: &SqlitePool,
+        session_id: &str,
+        at_message_id: &str,
+    ) -> DbResult<Session> {
+        let source = Self::get_by_id(pool, session_id)
+            .await?
+            .ok_or_else(|| DbError::SessionNotFound(session_id.to_string()))?;
+        let messages = Self::list_messages(pool, session_id).await?;
+        let cut = messages
+            .iter()
+            .position(|m| m.id == at_message_id)
+            .ok_or_else(|| DbError::MessageNotFound(at_message_id.to_string()))?;
+
+        let id = format!("sess_{}", Uuid::new_v4());
+        let now = Utc::now().timestamp();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO sessions (id, ghost_id, operator_id, created_at, updated_at, is_active)
+             VALUES (?, ?, ?, ?, ?, 1)",
+        )
+        .bind(&id)
+        .bind(&source.ghost_id)
+        .bind(&source.operator_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut compaction = None;
+        for message in &messages[..=cut] {
+            let new_id = format!("msg_{}", Uuid::new_v4());
+            let content_json = serde_json::to_string(&message.content)
+                .map_err(|e| DbError::Serialization(e.to_string()))?;
+            sqlx::query(
+                "INSERT INTO messages (id, ghost_id, session_id, role, content, model, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&new_id)
+            .bind(&source.ghost_id)
+            .bind(&id)
+            .bind(message.role.to_string())
+            .bind(&content_json)
+            .bind(&message.model)
+            .bind(message.created_at)
+            .execute(&mut *tx)
+            .await?;
+
+            if source.compaction_cursor_id.as_deref() == Some(message.id.as_str()) {
+                compaction = source
+                    .compaction_summary
+                    .as_ref()
+                    .map(|summary| (summary.clone(), new_id));
+            }
+        }
+
+        if let Some((summary, cursor_id)) = &compaction {
+            sqlx::query(
+                "UPDATE sessions SET compaction_summary = ?, compaction_cursor_id = ? WHERE id = ?",
+            )
+            .bind(summary)
+            .bind(cursor_id)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE sessions
+             SET is_active = 0
+             WHERE ghost_id = ? AND operator_id = ? AND id != ?",
+        )
+        .bind(&source.ghost_id)
+        .bind(&source.operator_id)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!(
+            "Forked session {} at {} into {} ({} messages)",
+            session_id,
+            at_message_id,
+            id,
+            cut + 1
+        );
+
+        let (compaction_summary, compaction_cursor_id) = compaction.unzip();
+        Ok(Session {
+            id,
+            ghost_id: source.ghost_id,
+            operator_id: source.operator_id,
+            created_at: now,
+            updated_at: now,
+            is_active: true,
+            compaction_summary,
+            compaction_cursor_id,
+            title: None,
+            tags: Vec::new(),
+        })
+    }
+
     /// Get all tool uses in a session
     pub async fn get_tool_uses(
         pool: &SqlitePool,
@@ -541,6 +832,78 @@ impl SessionRepository {
         Ok(())
     }
 
+    /// Sessions without a title that hold at least `min_messages` messages,
+    /// most recently updated first.
+    pub async fn list_untitled(
+        pool: &SqlitePool,
+        min_messages: i64,
+        limit: i64,
+    ) -> DbResult<Vec<Session>> {
+        let rows = sqlx::query_as::<_, SessionRow>(
+            "SELECT id, ghost_id, operator_id, created_at, updated_at, is_active, compaction_summary, compaction_cursor_id, title, tags
+             FROM sessions s
+             WHERE title IS NULL
+               AND (SELECT COUNT(*) FROM messages m WHERE m.session_id = s.id) >= ?
+             ORDER BY updated_at DESC
+             LIMIT ?",
+        )
+        .bind(min_messages)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Session::from).collect())
+    }
+
+    /// Store a generated title and tags. Does not touch `updated_at`, so
+    /// titling never counts as session activity.
+    pub async fn set_title(
+        pool: &SqlitePool,
+        session_id: &str,
+        title: &str,
+        tags: &[String],
+    ) -> DbResult<()> {
+        let tags =
+            serde_json::to_string(tags).map_err(|e| DbError::Serialization(e.to_string()))?;
+        let result = sqlx::query("UPDATE sessions SET title = ?, tags = ? WHERE id = ?")
+            .bind(title)
+            .bind(tags)
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::SessionNotFound(session_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Record the interface the session is being chatted from.
+    pub async fn set_origin(
+        pool: &SqlitePool,
+        session_id: &str,
+        origin: SessionOrigin,
+    ) -> DbResult<()> {
+        sqlx::query("UPDATE sessions SET origin = ? WHERE id = ?")
+            .bind(origin.to_string())
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Interface the session was last chatted from, if recorded.
+    pub async fn get_origin(
+        pool: &SqlitePool,
+        session_id: &str,
+    ) -> DbResult<Option<SessionOrigin>> {
+        let origin: Option<String> = sqlx::query_scalar("SELECT origin FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+        origin.map(|origin| origin.parse()).transpose()
+    }
+
     /// Load messages after a compaction cursor (by created_at ordering).
     ///
     /// Returns messages whose `created_at` is strictly greater than the cursor
@@ -550,8 +913,10 @@ impl SessionRepository {
         session_id: &str,
         cursor_id: &str,
     ) -> DbResult<Vec<Message>> {
+        Self::rehydrate(pool, session_id).await?;
         let rows = sqlx::query_as::<_, MessageRow>(
-            "SELECT id, session_id, role, content, model, created_at
+            "SELECT id, session_id, role, content, model, created_at, input_tokens,
+                    output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd
              FROM messages
              WHERE session_id = ? AND created_at > (
                  SELECT created_at FROM messages WHERE id = ?
@@ -567,6 +932,91 @@ impl SessionRepository {
             .map(Message::try_from)
             .collect::<DbResult<Vec<_>>>()
     }
+
+    /// Full-text search over the text of a ghost's session messages.
+    ///
+    /// Each whitespace-separated term of `query` must appear (prefix operators
+    /// and FTS5 syntax are not interpreted). Returns an empty list for blank queries.
+    pub async fn search_messages(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        query: &str,
+        filters: &MessageSearchFilters,
+    ) -> DbResult<Vec<MessageSearchHit>> {
+        let Some(match_expr) = fts5_phrase_query(query) else {
+            return Ok(Vec::new());
+        };
+        let limit = filters.limit.unwrap_or(DEFAULT_MESSAGE_SEARCH_LIMIT) as i64;
+        let role = filters.role.map(|r| r.to_string());
+
+        let rows = sqlx::query_as::<_, MessageSearchRow>(
+            "SELECT m.id AS message_id, m.session_id, m.role, m.created_at,
+                    snippet(messages_fts, 0, '[', ']', '…', 12) AS snippet
+             FROM messages_fts f
+             JOIN messages m ON m.id = f.message_id
+             JOIN sessions s ON s.id = m.session_id
+             WHERE messages_fts MATCH ?
+               AND m.ghost_id = ?
+               AND (? IS NULL OR s.operator_id = ?)
+               AND (? IS NULL OR m.session_id = ?)
+               AND (? IS NULL OR m.role = ?)
+               AND (? IS NULL OR m.created_at >= ?)
+               AND (? IS NULL OR m.created_at <= ?)
+             ORDER BY bm25(messages_fts), m.created_at DESC
+             LIMIT ?",
+        )
+        .bind(&match_expr)
+        .bind(ghost_id)
+        .bind(&filters.operator_id)
+        .bind(&filters.operator_id)
+        .bind(&filters.session_id)
+        .bind(&filters.session_id)
+        .bind(&role)
+        .bind(&role)
+        .bind(filters.since)
+        .bind(filters.since)
+        .bind(filters.until)
+        .bind(filters.until)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter()
+            .map(MessageSearchHit::try_from)
+            .collect::<DbResult<Vec<_>>>()
+    }
+}
+
+/// Quote each term so user input is matched literally by FTS5.
+fn fts5_phrase_query(raw: &str) -> Option<String> {
+    let terms: Vec<String> = raw
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct MessageSearchRow {
+    message_id: String,
+    session_id: String,
+    role: String,
+    created_at: i64,
+    snippet: String,
+}
+
+impl TryFrom<MessageSearchRow> for MessageSearchHit {
+    type Error = DbError;
+
+    fn try_from(row: MessageSearchRow) -> Result<Self, Self::Error> {
+        Ok(MessageSearchHit {
+            message_id: row.message_id,
+            session_id: row.session_id,
+            role: row.role.parse()?,
+            created_at: row.created_at,
+            snippet: row.snippet,
+        })
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -579,6 +1029,14 @@ struct SessionRow {
     is_active: i64,
     compaction_summary: Option<String>,
     compaction_cursor_id: Option<String>,
+    title: Option<String>,
+    tags: Option<String>,
+}
+
+/// Decode the JSON `tags` column; missing or malformed values read as empty.
+fn parse_tags(raw: Option<&str>) -> Vec<String> {
+    raw.and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default()
 }
 
 impl From<SessionRow> for Session {
@@ -592,6 +1050,8 @@ impl From<SessionRow> for Session {
             is_active: row.is_active != 0,
             compaction_summary: row.compaction_summary,
             compaction_cursor_id: row.compaction_cursor_id,
+            tags: parse_tags(row.tags.as_deref()),
+            title: row.title,
         }
     }
 }
@@ -603,6 +1063,8 @@ struct SessionInfoRow {
     updated_at: i64,
     is_active: i64,
     message_count: i64,
+    title: Option<String>,
+    tags: Option<String>,
 }
 
 impl From<SessionInfoRow> for SessionInfo {
@@ -613,10 +1075,25 @@ impl From<SessionInfoRow> for SessionInfo {
             updated_at: row.updated_at,
             message_count: row.message_count,
             is_active: row.is_active != 0,
+            tags: parse_tags(row.tags.as_deref()),
+            title: row.title,
         }
     }
 }
 
+/// Bind the five usage columns (all `NULL` without usage).
+pub(crate) fn bind_usage<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    usage: Option<&MessageUsage>,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(usage.map(|u| u.tokens.input_tokens))
+        .bind(usage.map(|u| u.tokens.output_tokens))
+        .bind(usage.map(|u| u.tokens.cache_read_tokens))
+        .bind(usage.map(|u| u.tokens.cache_creation_tokens))
+        .bind(usage.and_then(|u| u.cost_usd))
+}
+
 #[derive(Debug, sqlx::FromRow)]
 struct MessageRow {
     id: String,
@@ -625,6 +1102,11 @@ struct MessageRow {
     content: String,
     model: Option<String>,
     created_at: i64,
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    cache_read_tokens: Option<u32>,
+    cache_creation_tokens: Option<u32>,
+    cost_usd: Option<f64>,
 }
 
 impl TryFrom<MessageRow> for Message {
@@ -641,6 +1123,15 @@ impl TryFrom<MessageRow> for Message {
             content,
             model: row.model,
             created_at: row.created_at,
+            usage: row.input_tokens.map(|input_tokens| MessageUsage {
+                tokens: TokenUsage {
+                    input_tokens,
+                    output_tokens: row.output_tokens.unwrap_or(0),
+                    cache_read_tokens: row.cache_read_tokens.unwrap_or(0),
+                    cache_creation_tokens: row.cache_creation_tokens.unwrap_or(0),
+                },
+                cost_usd: row.cost_usd,
+            }),
         })
     }
 }
@@ -725,6 +1216,258 @@ mod tests {
         assert_eq!(messages.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_list_untitled_and_set_title() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        for text in ["Hello", "Hi there"] {
+            SessionRepository::add_message(
+                pool,
+                &ghost.id,
+                &session.id,
+                MessageRole::Operator,
+                vec![ContentBlock::Text {
+                    text: text.to_string(),
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(
+            SessionRepository::list_untitled(pool, 3, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let untitled = SessionRepository::list_untitled(pool, 2, 10).await.unwrap();
+        assert_eq!(untitled.len(), 1);
+        assert_eq!(untitled[0].id, session.id);
+
+        let before = SessionRepository::get_by_id(pool, &session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let tags = vec!["greeting".to_string(), "smalltalk".to_string()];
+        SessionRepository::set_title(pool, &session.id, "Saying hello", &tags)
+            .await
+            .unwrap();
+
+        let titled = SessionRepository::get_by_id(pool, &session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(titled.title.as_deref(), Some("Saying hello"));
+        assert_eq!(titled.tags, tags);
+        assert_eq!(titled.updated_at, before.updated_at);
+
+        let listed = SessionRepository::list(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].title.as_deref(), Some("Saying hello"));
+        assert_eq!(listed[0].tags, tags);
+        assert!(
+            SessionRepository::list_untitled(pool, 2, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            SessionRepository::set_title(pool, "missing", "x", &[]).await,
+            Err(DbError::SessionNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_history_up_to_message() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for (role, text) in [
+            (MessageRole::Operator, "one"),
+            (MessageRole::Ghost, "two"),
+            (MessageRole::Operator, "three"),
+        ] {
+            let message = SessionRepository::add_message(
+                pool,
+                &ghost.id,
+                &session.id,
+                role,
+                vec![ContentBlock::Text {
+                    text: text.to_string(),
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+            ids.push(message.id);
+        }
+        SessionRepository::update_compaction(pool, &session.id, "summary", &ids[0])
+            .await
+            .unwrap();
+
+        let fork = SessionRepository::fork(pool, &session.id, &ids[1])
+            .await
+            .unwrap();
+        assert_ne!(fork.id, session.id);
+        assert!(fork.is_active);
+        assert_eq!(fork.compaction_summary.as_deref(), Some("summary"));
+
+        let forked = SessionRepository::list_messages(pool, &fork.id)
+            .await
+            .unwrap();
+        let texts: Vec<_> = forked
+            .iter()
+            .map(|m| match &m.content[0] {
+                ContentBlock::Text { text } => text.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(texts, vec!["one", "two"]);
+        assert_eq!(
+            fork.compaction_cursor_id.as_deref(),
+            Some(forked[0].id.as_str())
+        );
+
+        let original = SessionRepository::count_messages(pool, &session.id)
+            .await
+            .unwrap();
+        assert_eq!(original, 3);
+        let active = SessionRepository::get_active(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.id, fork.id);
+
+        let missing = SessionRepository::fork(pool, &session.id, "msg_missing").await;
+        assert!(matches!(missing, Err(DbError::MessageNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_messages() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "TestOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "TestGhost")
+            .await
+            .unwrap();
+        let first = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let second = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        for (session, role, text) in [
+            (&first, MessageRole::Operator, "Can we plan the Kyoto trip?"),
+            (
+                &first,
+                MessageRole::Ghost,
+                "Sure, Kyoto in autumn is lovely.",
+            ),
+            (&second, MessageRole::Operator, "Refactor the tokio runtime"),
+        ] {
+            SessionRepository::add_message(
+                pool,
+                &ghost.id,
+                &session.id,
+                role,
+                vec![ContentBlock::Text {
+                    text: text.to_string(),
+                }],
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let hits = SessionRepository::search_messages(
+            pool,
+            &ghost.id,
+            "kyoto",
+            &MessageSearchFilters::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.session_id == first.id));
+        assert!(hits[0].snippet.contains("[Kyoto]"));
+
+        let ghost_only = MessageSearchFilters {
+            role: Some(MessageRole::Ghost),
+            ..Default::default()
+        };
+        let hits = SessionRepository::search_messages(pool, &ghost.id, "kyoto", &ghost_only)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        // FTS5 syntax in user input is matched literally instead of erroring.
+        let hits = SessionRepository::search_messages(
+            pool,
+            &ghost.id,
+            "tokio\" OR (",
+            &MessageSearchFilters::default(),
+        )
+        .await
+        .unwrap();
+        assert!(hits.is_empty());
+
+        SessionRepository::delete(pool, &ghost.id, &operator.id, &first.id)
+            .await
+            .unwrap();
+        let hits = SessionRepository::search_messages(
+            pool,
+            &ghost.id,
+            "kyoto",
+            &MessageSearchFilters::default(),
+        )
+        .await
+        .unwrap();
+        assert!(hits.is_empty());
+    }
+
     #[tokio::test]
     async fn test_count_messages_since() {
         let db = create_test_pool().await.unwrap();
@@ -763,5 +1506,22 @@ mod tests {
                 .await
                 .unwrap();
         assert!(count >= 1);
+
+        let other = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let since = Utc::now().timestamp() - 300;
+        assert_eq!(
+            SessionRepository::count_session_messages_since(pool, &session.id, since)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            SessionRepository::count_session_messages_since(pool, &other.id, since)
+                .await
+                .unwrap(),
+            0
+        );
     }
 }
@@ -9,6 +9,7 @@ use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
 };
 
+use crate::encryption::{DbKey, ensure_sqlcipher, verify_readable};
 use crate::error::{DbError, DbResult};
 
 static SQLITE_VEC_INIT_RC: OnceLock<i32> = OnceLock::new();
@@ -32,13 +33,42 @@ pub(crate) fn init_sqlite_vec_once() -> DbResult<()> {
     }
 }
 
-pub(crate) async fn create_file_pool(db_path: &Path, max_connections: u32) -> DbResult<SqlitePool> {
-    let options = SqliteConnectOptions::new()
+/// Open a file-backed pool, keyed with `key` when the DB is encrypted.
+pub(crate) async fn create_file_pool(
+    db_path: &Path,
+    max_connections: u32,
+    key: Option<&DbKey>,
+) -> DbResult<SqlitePool> {
+    let mut options = SqliteConnectOptions::new()
         .filename(db_path)
         .create_if_missing(true)
         .foreign_keys(true);
+    if let Some(key) = key {
+        options = key.apply(options);
+    }
+
+    create_pool(options, max_connections, key).await
+}
 
-    create_pool(options, max_connections).await
+/// Open an existing file read-only (replica mode). Skips the WAL/synchronous
+/// pragmas, which need write access; the primary sets them.
+pub(crate) async fn create_read_only_file_pool(
+    db_path: &Path,
+    max_connections: u32,
+    key: Option<&DbKey>,
+) -> DbResult<SqlitePool> {
+    let mut options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .read_only(true);
+    if let Some(key) = key {
+        options = key.apply(options);
+    }
+
+    let pool = connect_verified(options, max_connections, key).await?;
+    sqlx::query("PRAGMA cache_size = -64000")
+        .execute(&pool)
+        .await?;
+    Ok(pool)
 }
 
 #[cfg(any(test, feature = "test-helpers"))]
@@ -47,10 +77,24 @@ pub(crate) async fn create_in_memory_pool(max_connections: u32) -> DbResult<Sqli
         .filename(":memory:")
         .foreign_keys(true);
 
-    create_pool(options, max_connections).await
+    create_pool(options, max_connections, None).await
+}
+
+async fn create_pool(
+    options: SqliteConnectOptions,
+    max_connections: u32,
+    key: Option<&DbKey>,
+) -> DbResult<SqlitePool> {
+    let pool = connect_verified(options, max_connections, key).await?;
+    apply_common_pragmas(&pool).await?;
+    Ok(pool)
 }
 
-async fn create_pool(options: SqliteConnectOptions, max_connections: u32) -> DbResult<SqlitePool> {
+async fn connect_verified(
+    options: SqliteConnectOptions,
+    max_connections: u32,
+    key: Option<&DbKey>,
+) -> DbResult<SqlitePool> {
     init_sqlite_vec_once()?;
 
     let pool = SqlitePoolOptions::new()
@@ -58,7 +102,15 @@ async fn create_pool(options: SqliteConnectOptions, max_connections: u32) -> DbR
         .connect_with(options)
         .await?;
 
-    apply_common_pragmas(&pool).await?;
+    // Check the key before any pragma touches the file, so a missing or wrong
+    // key surfaces as a readable error.
+    {
+        let mut conn = pool.acquire().await?;
+        if key.is_some() {
+            ensure_sqlcipher(&mut conn).await?;
+        }
+        verify_readable(&mut conn, key.is_some()).await?;
+    }
 
     Ok(pool)
 }
@@ -1,9 +1,11 @@
 //! Test helpers for T-KOMA database.
 
+use std::path::Path;
+
 use crate::{
     error::{DbError, DbResult},
     koma_db::KomaDbPool,
-    sqlite_runtime::create_in_memory_pool,
+    sqlite_runtime::{create_file_pool, create_in_memory_pool},
 };
 
 /// Create an in-memory T-KOMA database for testing
@@ -17,3 +19,16 @@ pub async fn create_test_pool() -> DbResult<KomaDbPool> {
 
     Ok(KomaDbPool::from_pool(pool))
 }
+
+/// Create a migrated T-KOMA database file at `db_path`, for tests that reopen
+/// it with [`open_read_only_test_pool`].
+pub async fn create_test_file_pool(db_path: &Path) -> DbResult<KomaDbPool> {
+    let pool = create_file_pool(db_path, 1, None).await?;
+    KomaDbPool::run_migrations(&pool).await?;
+    Ok(KomaDbPool::from_pool(pool))
+}
+
+/// Open the database at `db_path` read-only, like a replica gateway.
+pub async fn open_read_only_test_pool(db_path: &Path) -> DbResult<KomaDbPool> {
+    KomaDbPool::open_read_only_at(db_path, None).await
+}
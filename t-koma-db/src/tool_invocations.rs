@@ -0,0 +1,268 @@
+//! Per-call tool usage records.
+//!
+//! The gateway records each tool call a ghost makes (approval requests
+//! excluded) so operators can see which tools are actually used, how long
+//! they take and which ones keep failing (`t-koma-cli tool-usage`).
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// Longest error text kept per failed call.
+pub const MAX_TOOL_ERROR_CHARS: usize = 300;
+
+/// One tool call to record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewToolInvocation {
+    pub ghost_id: String,
+    /// `None` for background jobs
+    pub session_id: Option<String>,
+    pub tool_name: String,
+    pub duration_ms: i64,
+    pub success: bool,
+    /// Size of the result (or error) text in bytes
+    pub output_bytes: i64,
+    /// Error text of a failed call, cut to [`MAX_TOOL_ERROR_CHARS`]
+    pub error: Option<String>,
+}
+
+/// Aggregated calls of one tool.
+#[derive(Debug, Clone, Default, PartialEq, sqlx::FromRow)]
+pub struct ToolUsageStats {
+    pub tool_name: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub avg_duration_ms: f64,
+    pub max_duration_ms: i64,
+    pub output_bytes: i64,
+    /// Unix timestamp of the latest call
+    pub last_used_at: i64,
+}
+
+impl ToolUsageStats {
+    /// Share of calls that failed, from 0 to 1.
+    pub fn failure_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.calls as f64
+        }
+    }
+}
+
+/// A failed tool call.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ToolFailure {
+    pub tool_name: String,
+    pub ghost_id: String,
+    /// Ghost name, or the id when the ghost is gone
+    pub ghost_name: String,
+    pub session_id: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: i64,
+}
+
+/// Which calls a report covers.
+#[derive(Debug, Clone, Default)]
+pub struct ToolUsageFilters {
+    pub ghost_id: Option<String>,
+    pub tool_name: Option<String>,
+    /// Only calls at or after this unix timestamp
+    pub since: i64,
+}
+
+const FILTER_SQL: &str = "t.created_at >= ?
+    AND (? IS NULL OR t.ghost_id = ?)
+    AND (? IS NULL OR t.tool_name = ?)";
+
+/// Repository for `tool_invocations`.
+pub struct ToolInvocationRepository;
+
+impl ToolInvocationRepository {
+    pub async fn record(pool: &SqlitePool, invocation: &NewToolInvocation) -> DbResult<()> {
+        let error = invocation.error.as_deref().map(|error| {
+            match error.char_indices().nth(MAX_TOOL_ERROR_CHARS) {
+                Some((end, _)) => format!("{}…", &error[..end]),
+                None => error.to_string(),
+            }
+        });
+        sqlx::query(
+            "INSERT INTO tool_invocations
+                (ghost_id, session_id, tool_name, duration_ms, success, output_bytes, error, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&invocation.ghost_id)
+        .bind(&invocation.session_id)
+        .bind(&invocation.tool_name)
+        .bind(invocation.duration_ms)
+        .bind(invocation.success)
+        .bind(invocation.output_bytes)
+        .bind(error)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Per-tool totals, most called first.
+    pub async fn stats(
+        pool: &SqlitePool,
+        filters: &ToolUsageFilters,
+    ) -> DbResult<Vec<ToolUsageStats>> {
+        let sql = format!(
+            "SELECT t.tool_name,
+                COUNT(*) as calls,
+                COALESCE(SUM(t.success = 0), 0) as failures,
+                AVG(t.duration_ms) as avg_duration_ms,
+                MAX(t.duration_ms) as max_duration_ms,
+                COALESCE(SUM(t.output_bytes), 0) as output_bytes,
+                MAX(t.created_at) as last_used_at
+             FROM tool_invocations t
+             WHERE {FILTER_SQL}
+             GROUP BY t.tool_name
+             ORDER BY calls DESC, t.tool_name"
+        );
+        let stats = Self::bind_filters(sqlx::query_as::<_, ToolUsageStats>(&sql), filters)
+            .fetch_all(pool)
+            .await?;
+        Ok(stats)
+    }
+
+    /// Latest failed calls, newest first.
+    pub async fn recent_failures(
+        pool: &SqlitePool,
+        filters: &ToolUsageFilters,
+        limit: i64,
+    ) -> DbResult<Vec<ToolFailure>> {
+        let sql = format!(
+            "SELECT t.tool_name, t.ghost_id, COALESCE(g.name, t.ghost_id) as ghost_name,
+                t.session_id, t.error, t.duration_ms, t.created_at
+             FROM tool_invocations t LEFT JOIN ghosts g ON g.id = t.ghost_id
+             WHERE t.success = 0 AND {FILTER_SQL}
+             ORDER BY t.id DESC
+             LIMIT ?"
+        );
+        let failures = Self::bind_filters(sqlx::query_as::<_, ToolFailure>(&sql), filters)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+        Ok(failures)
+    }
+
+    fn bind_filters<'q, O>(
+        query: sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>>,
+        filters: &'q ToolUsageFilters,
+    ) -> sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>> {
+        query
+            .bind(filters.since)
+            .bind(filters.ghost_id.as_deref())
+            .bind(filters.ghost_id.as_deref())
+            .bind(filters.tool_name.as_deref())
+            .bind(filters.tool_name.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    fn invocation(ghost_id: &str, tool: &str, error: Option<&str>) -> NewToolInvocation {
+        NewToolInvocation {
+            ghost_id: ghost_id.to_string(),
+            session_id: None,
+            tool_name: tool.to_string(),
+            duration_ms: if error.is_some() { 900 } else { 100 },
+            success: error.is_none(),
+            output_bytes: 50,
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_report() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let operator = OperatorRepository::create_new(
+            pool,
+            "ToolsOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let alpha = GhostRepository::create(pool, &operator.id, "Alpha")
+            .await
+            .unwrap();
+        let beta = GhostRepository::create(pool, &operator.id, "Beta")
+            .await
+            .unwrap();
+
+        for record in [
+            invocation(&alpha.id, "read_file", None),
+            invocation(&alpha.id, "read_file", None),
+            invocation(&alpha.id, "web_fetch", Some("timeout")),
+            invocation(&beta.id, "web_fetch", None),
+            invocation(&beta.id, "web_fetch", Some(&"x".repeat(400))),
+        ] {
+            ToolInvocationRepository::record(pool, &record)
+                .await
+                .unwrap();
+        }
+
+        let stats = ToolInvocationRepository::stats(pool, &ToolUsageFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].tool_name, "web_fetch");
+        assert_eq!((stats[0].calls, stats[0].failures), (3, 2));
+        assert_eq!(stats[0].max_duration_ms, 900);
+        assert_eq!(stats[1].tool_name, "read_file");
+        assert_eq!(stats[1].failure_rate(), 0.0);
+        assert_eq!(stats[1].output_bytes, 100);
+
+        let alpha_only = ToolUsageFilters {
+            ghost_id: Some(alpha.id.clone()),
+            ..Default::default()
+        };
+        let stats = ToolInvocationRepository::stats(pool, &alpha_only)
+            .await
+            .unwrap();
+        assert_eq!(stats[0].tool_name, "read_file");
+        assert_eq!(stats[1].calls, 1);
+
+        let failures = ToolInvocationRepository::recent_failures(
+            pool,
+            &ToolUsageFilters {
+                tool_name: Some("web_fetch".to_string()),
+                ..Default::default()
+            },
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].ghost_name, "Beta");
+        assert_eq!(
+            failures[0].error.as_ref().unwrap().chars().count(),
+            MAX_TOOL_ERROR_CHARS + 1
+        );
+        assert_eq!(failures[1].error.as_deref(), Some("timeout"));
+
+        let later = ToolUsageFilters {
+            since: Utc::now().timestamp() + 60,
+            ..Default::default()
+        };
+        assert!(
+            ToolInvocationRepository::stats(pool, &later)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
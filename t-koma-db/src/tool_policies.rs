@@ -0,0 +1,377 @@
+//! Per-operator and per-ghost tool policies.
+//!
+//! A policy allows, denies or auto-approves one tool, or every tool whose
+//! name starts with a prefix (`mcp__github__*`, `*`); the most specific
+//! entry wins. Operator policies cover all of the operator's sessions, ghost
+//! policies every session of the ghost. A deny on either side wins over an
+//! auto-approve; tools without a matching policy are allowed and keep their
+//! usual approval prompts.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+use crate::events::{EventKind, EventRepository, NewEvent};
+use crate::ghosts::GhostRepository;
+use crate::operators::OperatorRepository;
+
+/// What happens when a ghost calls a matching tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPolicy {
+    Allow,
+    Deny,
+    /// Allowed, and approval requests from the tool are granted without
+    /// asking the operator.
+    AutoApprove,
+}
+
+impl ToolPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ToolPolicy::Allow => "allow",
+            ToolPolicy::Deny => "deny",
+            ToolPolicy::AutoApprove => "auto_approve",
+        }
+    }
+}
+
+impl fmt::Display for ToolPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ToolPolicy {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(ToolPolicy::Allow),
+            "deny" => Ok(ToolPolicy::Deny),
+            "auto_approve" | "auto" => Ok(ToolPolicy::AutoApprove),
+            _ => Err(DbError::Serialization(format!(
+                "Invalid tool policy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Who a policy applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolPolicySubject {
+    Operator(String),
+    Ghost(String),
+}
+
+impl ToolPolicySubject {
+    fn id(&self) -> &str {
+        match self {
+            ToolPolicySubject::Operator(id) | ToolPolicySubject::Ghost(id) => id,
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            ToolPolicySubject::Operator(_) => "operator_id",
+            ToolPolicySubject::Ghost(_) => "ghost_id",
+        }
+    }
+}
+
+/// Policies in force for one operator talking to one ghost.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPolicies {
+    pub operator: BTreeMap<String, ToolPolicy>,
+    pub ghost: BTreeMap<String, ToolPolicy>,
+}
+
+impl ToolPolicies {
+    /// Combined policy for `tool`; `None` when neither side has a match.
+    pub fn resolve(&self, tool: &str) -> Option<ToolPolicy> {
+        let sides = [
+            most_specific(&self.operator, tool).copied(),
+            most_specific(&self.ghost, tool).copied(),
+        ];
+        [ToolPolicy::Deny, ToolPolicy::AutoApprove, ToolPolicy::Allow]
+            .into_iter()
+            .find(|policy| sides.contains(&Some(*policy)))
+    }
+
+    pub fn allows(&self, tool: &str) -> bool {
+        self.resolve(tool) != Some(ToolPolicy::Deny)
+    }
+
+    pub fn auto_approves(&self, tool: &str) -> bool {
+        self.resolve(tool) == Some(ToolPolicy::AutoApprove)
+    }
+}
+
+/// Entry for `tool`: its exact name, else the longest matching `prefix*`.
+pub fn most_specific<'a, V>(entries: &'a BTreeMap<String, V>, tool: &str) -> Option<&'a V> {
+    entries.get(tool).or_else(|| {
+        entries
+            .iter()
+            .filter_map(|(pattern, value)| {
+                let prefix = pattern.strip_suffix('*')?;
+                tool.starts_with(prefix).then_some((prefix.len(), value))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, value)| value)
+    })
+}
+
+/// `name=policy` pairs separated by spaces, or `none`.
+pub fn format_policies(policies: &BTreeMap<String, ToolPolicy>) -> String {
+    if policies.is_empty() {
+        return "none".to_string();
+    }
+    policies
+        .iter()
+        .map(|(tool, policy)| format!("{tool}={policy}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Tool names match `^[a-zA-Z0-9_-]{1,64}$`; patterns may end in `*`.
+fn validate_tool_pattern(tool: &str) -> DbResult<()> {
+    let name = tool.strip_suffix('*').unwrap_or(tool);
+    let valid = tool.len() <= 64
+        && (!name.is_empty() || tool == "*")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(DbError::Serialization(format!(
+            "Invalid tool name or pattern: {}",
+            tool
+        )))
+    }
+}
+
+/// Tool policy repository for database operations
+pub struct ToolPolicyRepository;
+
+impl ToolPolicyRepository {
+    /// Policies stored for one operator or ghost.
+    pub async fn list(
+        pool: &SqlitePool,
+        subject: &ToolPolicySubject,
+    ) -> DbResult<BTreeMap<String, ToolPolicy>> {
+        let rows = sqlx::query_as::<_, (String, String)>(&format!(
+            "SELECT tool_name, policy FROM tool_policies WHERE {} = ?",
+            subject.column()
+        ))
+        .bind(subject.id())
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(tool, policy)| Ok((tool, policy.parse()?)))
+            .collect()
+    }
+
+    /// Policies for a session between `operator_id` and `ghost_id`.
+    pub async fn for_session(
+        pool: &SqlitePool,
+        operator_id: &str,
+        ghost_id: &str,
+    ) -> DbResult<ToolPolicies> {
+        Ok(ToolPolicies {
+            operator: Self::list(pool, &ToolPolicySubject::Operator(operator_id.to_string()))
+                .await?,
+            ghost: Self::list(pool, &ToolPolicySubject::Ghost(ghost_id.to_string())).await?,
+        })
+    }
+
+    /// Set the policy for `tool` (name or `prefix*`), or clear it with `None`.
+    ///
+    /// Returns the subject's resulting policies.
+    pub async fn set(
+        pool: &SqlitePool,
+        subject: &ToolPolicySubject,
+        tool: &str,
+        policy: Option<ToolPolicy>,
+    ) -> DbResult<BTreeMap<String, ToolPolicy>> {
+        validate_tool_pattern(tool)?;
+        let kind = match subject {
+            ToolPolicySubject::Operator(id) => {
+                if OperatorRepository::get_by_id(pool, id).await?.is_none() {
+                    return Err(DbError::OperatorNotFound(id.clone()));
+                }
+                EventKind::OperatorToolPolicyChanged
+            }
+            ToolPolicySubject::Ghost(id) => {
+                if GhostRepository::get_by_id(pool, id).await?.is_none() {
+                    return Err(DbError::GhostNotFound(id.clone()));
+                }
+                EventKind::GhostToolPolicyChanged
+            }
+        };
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(&format!(
+            "DELETE FROM tool_policies WHERE {} = ? AND tool_name = ?",
+            subject.column()
+        ))
+        .bind(subject.id())
+        .bind(tool)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(policy) = policy {
+            sqlx::query(&format!(
+                "INSERT INTO tool_policies ({}, tool_name, policy, updated_at) VALUES (?, ?, ?, ?)",
+                subject.column()
+            ))
+            .bind(subject.id())
+            .bind(tool)
+            .bind(policy.as_str())
+            .bind(Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+        }
+        EventRepository::record(
+            &mut *tx,
+            &NewEvent::new(kind, subject.id())
+                .with_data(serde_json::json!({ "tool": tool, "policy": policy })),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Self::list(pool, subject).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OperatorAccessLevel, Platform, test_helpers::create_test_pool};
+
+    fn policies(entries: &[(&str, ToolPolicy)]) -> BTreeMap<String, ToolPolicy> {
+        entries
+            .iter()
+            .map(|(tool, policy)| (tool.to_string(), *policy))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_most_specific_and_deny_wins() {
+        let resolved = ToolPolicies {
+            operator: policies(&[
+                ("*", ToolPolicy::Deny),
+                ("web_*", ToolPolicy::Allow),
+                ("web_fetch", ToolPolicy::AutoApprove),
+            ]),
+            ghost: policies(&[("web_search", ToolPolicy::Deny)]),
+        };
+        assert!(!resolved.allows("run_shell_command"));
+        assert_eq!(resolved.resolve("web_fetch"), Some(ToolPolicy::AutoApprove));
+        assert!(!resolved.allows("web_search"));
+        assert_eq!(resolved.resolve("web_cache"), Some(ToolPolicy::Allow));
+
+        let open = ToolPolicies {
+            operator: BTreeMap::new(),
+            ghost: policies(&[("mcp__github__*", ToolPolicy::AutoApprove)]),
+        };
+        assert!(open.auto_approves("mcp__github__create_issue"));
+        assert_eq!(open.resolve("browser"), None);
+        assert!(open.allows("browser"));
+
+        assert!(validate_tool_pattern("mcp__github__*").is_ok());
+        assert!(validate_tool_pattern("*").is_ok());
+        assert!(validate_tool_pattern("").is_err());
+        assert!(validate_tool_pattern("web fetch").is_err());
+        assert!(validate_tool_pattern("we*b").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_and_clear_policies() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+        let operator = OperatorRepository::create_new(
+            pool,
+            "PolicyOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "PolicyGhost")
+            .await
+            .unwrap();
+        let operator_subject = ToolPolicySubject::Operator(operator.id.clone());
+        let ghost_subject = ToolPolicySubject::Ghost(ghost.id.clone());
+
+        ToolPolicyRepository::set(
+            pool,
+            &operator_subject,
+            "web_fetch",
+            Some(ToolPolicy::AutoApprove),
+        )
+        .await
+        .unwrap();
+        ToolPolicyRepository::set(
+            pool,
+            &ghost_subject,
+            "run_shell_command",
+            Some(ToolPolicy::Deny),
+        )
+        .await
+        .unwrap();
+        let updated = ToolPolicyRepository::set(
+            pool,
+            &ghost_subject,
+            "run_shell_command",
+            Some(ToolPolicy::Allow),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            updated,
+            policies(&[("run_shell_command", ToolPolicy::Allow)])
+        );
+
+        let session = ToolPolicyRepository::for_session(pool, &operator.id, &ghost.id)
+            .await
+            .unwrap();
+        assert!(session.auto_approves("web_fetch"));
+        assert_eq!(
+            session.resolve("run_shell_command"),
+            Some(ToolPolicy::Allow)
+        );
+
+        let cleared = ToolPolicyRepository::set(pool, &ghost_subject, "run_shell_command", None)
+            .await
+            .unwrap();
+        assert!(cleared.is_empty());
+
+        assert!(matches!(
+            ToolPolicyRepository::set(
+                pool,
+                &ToolPolicySubject::Ghost("missing".to_string()),
+                "web_fetch",
+                Some(ToolPolicy::Deny),
+            )
+            .await,
+            Err(DbError::GhostNotFound(_))
+        ));
+
+        let events = EventRepository::query(
+            pool,
+            &crate::EventFilters {
+                kind: Some(EventKind::GhostToolPolicyChanged.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(events.len(), 3);
+    }
+}
@@ -0,0 +1,448 @@
+//! Monthly usage budgets for operators and ghosts.
+//!
+//! A budget caps tokens and/or cost (USD, from `usage_log.cost_usd`) for the
+//! current UTC calendar month. Operator budgets count usage in all of the
+//! operator's sessions; ghost budgets count everything the ghost spent,
+//! including background jobs.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::{DbError, DbResult};
+
+/// Default fraction of a limit at which a warning is raised.
+pub const DEFAULT_BUDGET_WARN_RATIO: f64 = 0.8;
+
+/// What a budget applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Operator,
+    Ghost,
+}
+
+impl std::fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetScope::Operator => write!(f, "operator"),
+            BudgetScope::Ghost => write!(f, "ghost"),
+        }
+    }
+}
+
+impl std::str::FromStr for BudgetScope {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "operator" => Ok(BudgetScope::Operator),
+            "ghost" => Ok(BudgetScope::Ghost),
+            _ => Err(DbError::Serialization(format!("invalid budget scope: {s}"))),
+        }
+    }
+}
+
+/// A monthly budget. A `None` limit is not enforced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageBudget {
+    pub scope: BudgetScope,
+    /// Operator id or ghost id.
+    pub subject_id: String,
+    pub monthly_token_limit: Option<i64>,
+    pub monthly_cost_limit_usd: Option<f64>,
+    /// Fraction of a limit (0–1) at which usage is reported as a warning.
+    pub warn_ratio: f64,
+}
+
+/// Month-to-date usage measured against a budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetReport {
+    pub scope: BudgetScope,
+    pub subject_id: String,
+    pub used_tokens: i64,
+    pub token_limit: Option<i64>,
+    pub used_cost_usd: f64,
+    pub cost_limit_usd: Option<f64>,
+}
+
+impl BudgetReport {
+    /// Highest used/limit ratio across the configured limits.
+    pub fn ratio(&self) -> f64 {
+        let tokens = self
+            .token_limit
+            .map(|limit| ratio(self.used_tokens as f64, limit as f64));
+        let cost = self
+            .cost_limit_usd
+            .map(|limit| ratio(self.used_cost_usd, limit));
+        tokens.into_iter().chain(cost).fold(0.0, f64::max)
+    }
+
+    /// Whether the cost limit (rather than the token limit) is the binding one.
+    pub fn cost_is_binding(&self) -> bool {
+        let tokens = self
+            .token_limit
+            .map(|limit| ratio(self.used_tokens as f64, limit as f64));
+        let cost = self
+            .cost_limit_usd
+            .map(|limit| ratio(self.used_cost_usd, limit));
+        match (tokens, cost) {
+            (Some(t), Some(c)) => c >= t,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+fn ratio(used: f64, limit: f64) -> f64 {
+    if limit <= 0.0 {
+        f64::INFINITY
+    } else {
+        used / limit
+    }
+}
+
+/// Outcome of a budget check before a provider call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetStatus {
+    WithinBudget,
+    /// Past the warning threshold of at least one budget.
+    Warning(BudgetReport),
+    /// At or over a limit; the request must be refused.
+    Exceeded(BudgetReport),
+}
+
+/// Repository for usage_budgets table operations.
+pub struct UsageBudgetRepository;
+
+impl UsageBudgetRepository {
+    /// Insert or replace a budget.
+    pub async fn set(pool: &SqlitePool, budget: &UsageBudget) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO usage_budgets (scope, subject_id, monthly_token_limit,
+                monthly_cost_limit_usd, warn_ratio, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(scope, subject_id) DO UPDATE SET
+                monthly_token_limit = excluded.monthly_token_limit,
+                monthly_cost_limit_usd = excluded.monthly_cost_limit_usd,
+                warn_ratio = excluded.warn_ratio,
+                updated_at = excluded.updated_at",
+        )
+        .bind(budget.scope.to_string())
+        .bind(&budget.subject_id)
+        .bind(budget.monthly_token_limit)
+        .bind(budget.monthly_cost_limit_usd)
+        .bind(budget.warn_ratio)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the budget for a subject, if any.
+    pub async fn get(
+        pool: &SqlitePool,
+        scope: BudgetScope,
+        subject_id: &str,
+    ) -> DbResult<Option<UsageBudget>> {
+        let row = sqlx::query_as::<_, UsageBudgetRow>(
+            "SELECT scope, subject_id, monthly_token_limit, monthly_cost_limit_usd, warn_ratio
+             FROM usage_budgets
+             WHERE scope = ? AND subject_id = ?",
+        )
+        .bind(scope.to_string())
+        .bind(subject_id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(UsageBudget::try_from).transpose()
+    }
+
+    /// List all budgets.
+    pub async fn list(pool: &SqlitePool) -> DbResult<Vec<UsageBudget>> {
+        let rows = sqlx::query_as::<_, UsageBudgetRow>(
+            "SELECT scope, subject_id, monthly_token_limit, monthly_cost_limit_usd, warn_ratio
+             FROM usage_budgets
+             ORDER BY scope, subject_id",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(UsageBudget::try_from).collect()
+    }
+
+    /// Every budget with its usage for the month containing `now`.
+    pub async fn list_reports(
+        pool: &SqlitePool,
+        now: DateTime<Utc>,
+    ) -> DbResult<Vec<(UsageBudget, BudgetReport)>> {
+        let since = month_start(now);
+        let mut reports = Vec::new();
+        for budget in Self::list(pool).await? {
+            let (used_tokens, used_cost_usd) =
+                Self::usage_since(pool, budget.scope, &budget.subject_id, since).await?;
+            let report = BudgetReport {
+                scope: budget.scope,
+                subject_id: budget.subject_id.clone(),
+                used_tokens,
+                token_limit: budget.monthly_token_limit,
+                used_cost_usd,
+                cost_limit_usd: budget.monthly_cost_limit_usd,
+            };
+            reports.push((budget, report));
+        }
+        Ok(reports)
+    }
+
+    /// Remove a budget. Returns whether one existed.
+    pub async fn delete(pool: &SqlitePool, scope: BudgetScope, subject_id: &str) -> DbResult<bool> {
+        let result = sqlx::query("DELETE FROM usage_budgets WHERE scope = ? AND subject_id = ?")
+            .bind(scope.to_string())
+            .bind(subject_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Check the operator and ghost budgets for the month containing `now`.
+    ///
+    /// Returns the most severe status; between two budgets at the same
+    /// severity, the one with the higher usage ratio wins.
+    pub async fn check(
+        pool: &SqlitePool,
+        ghost_id: &str,
+        operator_id: &str,
+        now: DateTime<Utc>,
+    ) -> DbResult<BudgetStatus> {
+        let since = month_start(now);
+        let mut status = BudgetStatus::WithinBudget;
+        for (scope, subject_id) in [
+            (BudgetScope::Operator, operator_id),
+            (BudgetScope::Ghost, ghost_id),
+        ] {
+            let Some(budget) = Self::get(pool, scope, subject_id).await? else {
+                continue;
+            };
+            let (used_tokens, used_cost_usd) =
+                Self::usage_since(pool, scope, subject_id, since).await?;
+            let report = BudgetReport {
+                scope,
+                subject_id: subject_id.to_string(),
+                used_tokens,
+                token_limit: budget.monthly_token_limit,
+                used_cost_usd,
+                cost_limit_usd: budget.monthly_cost_limit_usd,
+            };
+            let candidate = if report.ratio() >= 1.0 {
+                BudgetStatus::Exceeded(report)
+            } else if report.ratio() >= budget.warn_ratio {
+                BudgetStatus::Warning(report)
+            } else {
+                BudgetStatus::WithinBudget
+            };
+            if severity(&candidate) > severity(&status) {
+                status = candidate;
+            }
+        }
+        Ok(status)
+    }
+
+    /// Total tokens and cost recorded for a subject since `since` (unix seconds).
+    async fn usage_since(
+        pool: &SqlitePool,
+        scope: BudgetScope,
+        subject_id: &str,
+        since: i64,
+    ) -> DbResult<(i64, f64)> {
+        let sql = match scope {
+            BudgetScope::Operator => {
+                "SELECT COALESCE(SUM(u.input_tokens + u.output_tokens + u.cache_read_tokens
+                            + u.cache_creation_tokens), 0),
+                        COALESCE(SUM(u.cost_usd), 0.0)
+                 FROM usage_log u
+                 JOIN sessions s ON s.id = u.session_id
+                 WHERE s.operator_id = ? AND u.created_at >= ?"
+            }
+            BudgetScope::Ghost => {
+                "SELECT COALESCE(SUM(u.input_tokens + u.output_tokens + u.cache_read_tokens
+                            + u.cache_creation_tokens), 0),
+                        COALESCE(SUM(u.cost_usd), 0.0)
+                 FROM usage_log u
+                 WHERE u.ghost_id = ? AND u.created_at >= ?"
+            }
+        };
+        let row = sqlx::query_as::<_, (i64, f64)>(sql)
+            .bind(subject_id)
+            .bind(since)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(row)
+    }
+}
+
+/// Unix timestamp of the first second of `now`'s UTC month.
+fn month_start(now: DateTime<Utc>) -> i64 {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .map(|t| t.timestamp())
+        .unwrap_or_default()
+}
+
+fn severity(status: &BudgetStatus) -> (u8, f64) {
+    match status {
+        BudgetStatus::WithinBudget => (0, 0.0),
+        BudgetStatus::Warning(report) => (1, report.ratio()),
+        BudgetStatus::Exceeded(report) => (2, report.ratio()),
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UsageBudgetRow {
+    scope: String,
+    subject_id: String,
+    monthly_token_limit: Option<i64>,
+    monthly_cost_limit_usd: Option<f64>,
+    warn_ratio: f64,
+}
+
+impl TryFrom<UsageBudgetRow> for UsageBudget {
+    type Error = DbError;
+
+    fn try_from(row: UsageBudgetRow) -> Result<Self, Self::Error> {
+        Ok(UsageBudget {
+            scope: row.scope.parse()?,
+            subject_id: row.subject_id,
+            monthly_token_limit: row.monthly_token_limit,
+            monthly_cost_limit_usd: row.monthly_cost_limit_usd,
+            warn_ratio: row.warn_ratio,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GhostRepository, OperatorAccessLevel, OperatorRepository, Platform, SessionRepository,
+        TokenUsage, UsageLog, UsageLogRepository, test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_check_warns_then_blocks() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let operator = OperatorRepository::create_new(
+            pool,
+            "BudgetOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(pool, &operator.id, "BudgetGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let now = Utc::now();
+
+        assert_eq!(
+            UsageBudgetRepository::check(pool, &ghost.id, &operator.id, now)
+                .await
+                .unwrap(),
+            BudgetStatus::WithinBudget
+        );
+
+        UsageBudgetRepository::set(
+            pool,
+            &UsageBudget {
+                scope: BudgetScope::Operator,
+                subject_id: operator.id.clone(),
+                monthly_token_limit: Some(1000),
+                monthly_cost_limit_usd: None,
+                warn_ratio: DEFAULT_BUDGET_WARN_RATIO,
+            },
+        )
+        .await
+        .unwrap();
+
+        let tokens = |input_tokens| TokenUsage {
+            input_tokens,
+            ..Default::default()
+        };
+        // Usage from last month does not count.
+        let mut old = UsageLog::new(&ghost.id, &session.id, None, "m", tokens(5000));
+        old.request_at = month_start(now) - 1;
+        UsageLogRepository::insert(pool, &old).await.unwrap();
+        UsageLogRepository::insert(
+            pool,
+            &UsageLog::new(&ghost.id, &session.id, None, "m", tokens(850)),
+        )
+        .await
+        .unwrap();
+
+        match UsageBudgetRepository::check(pool, &ghost.id, &operator.id, now)
+            .await
+            .unwrap()
+        {
+            BudgetStatus::Warning(report) => {
+                assert_eq!(report.scope, BudgetScope::Operator);
+                assert_eq!(report.used_tokens, 850);
+                assert!(!report.cost_is_binding());
+            }
+            other => panic!("expected warning, got {other:?}"),
+        }
+
+        let mut priced = UsageLog::new(&ghost.id, &session.id, None, "m", tokens(10));
+        priced.cost_usd = Some(2.5);
+        UsageLogRepository::insert(pool, &priced).await.unwrap();
+        UsageBudgetRepository::set(
+            pool,
+            &UsageBudget {
+                scope: BudgetScope::Ghost,
+                subject_id: ghost.id.clone(),
+                monthly_token_limit: None,
+                monthly_cost_limit_usd: Some(2.0),
+                warn_ratio: DEFAULT_BUDGET_WARN_RATIO,
+            },
+        )
+        .await
+        .unwrap();
+
+        match UsageBudgetRepository::check(pool, &ghost.id, &operator.id, now)
+            .await
+            .unwrap()
+        {
+            BudgetStatus::Exceeded(report) => {
+                assert_eq!(report.scope, BudgetScope::Ghost);
+                assert!(report.cost_is_binding());
+                assert!((report.used_cost_usd - 2.5).abs() < 1e-9);
+            }
+            other => panic!("expected exceeded, got {other:?}"),
+        }
+
+        let reports = UsageBudgetRepository::list_reports(pool, now)
+            .await
+            .unwrap();
+        let used: Vec<(BudgetScope, i64)> = reports
+            .iter()
+            .map(|(budget, report)| (budget.scope, report.used_tokens))
+            .collect();
+        assert_eq!(
+            used,
+            vec![(BudgetScope::Ghost, 860), (BudgetScope::Operator, 860)]
+        );
+
+        assert!(
+            UsageBudgetRepository::delete(pool, BudgetScope::Ghost, &ghost.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(UsageBudgetRepository::list(pool).await.unwrap().len(), 1);
+    }
+}
@@ -2,15 +2,18 @@
 //!
 //! Each API request produces a `UsageLog` row capturing the actual
 //! token counts reported by the provider (input, output, cache hits).
+//! When the model has a row in `model_pricing`, the request cost in USD is
+//! computed at insert time and stored alongside the counts.
 
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::error::DbResult;
 
 /// Token counts from a single API request.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub input_tokens: u32,
     pub output_tokens: u32,
@@ -28,6 +31,8 @@ pub struct UsageLog {
     pub request_at: i64,
     pub model: String,
     pub tokens: TokenUsage,
+    /// Request cost in USD; `None` when the model had no pricing.
+    pub cost_usd: Option<f64>,
 }
 
 impl UsageLog {
@@ -47,10 +52,35 @@ impl UsageLog {
             request_at: Utc::now().timestamp(),
             model: model.to_string(),
             tokens,
+            cost_usd: None,
         }
     }
 }
 
+/// Token prices for a model, in USD per million tokens.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelPricing {
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+}
+
+impl ModelPricing {
+    /// Cost in USD of a request with the given token counts.
+    ///
+    /// Counts are treated as disjoint buckets, matching how providers
+    /// report uncached input separately from cache reads and writes.
+    pub fn cost(&self, tokens: &TokenUsage) -> f64 {
+        (f64::from(tokens.input_tokens) * self.input_per_mtok
+            + f64::from(tokens.output_tokens) * self.output_per_mtok
+            + f64::from(tokens.cache_read_tokens) * self.cache_read_per_mtok
+            + f64::from(tokens.cache_creation_tokens) * self.cache_write_per_mtok)
+            / 1_000_000.0
+    }
+}
+
 /// Aggregated usage totals for a session.
 #[derive(Debug, Clone, Default)]
 pub struct UsageTotals {
@@ -59,6 +89,39 @@ pub struct UsageTotals {
     pub output_tokens: i64,
     pub cache_read_tokens: i64,
     pub cache_creation_tokens: i64,
+    /// Summed cost of priced requests, in USD.
+    pub cost_usd: f64,
+    /// Requests whose model had no pricing (not included in `cost_usd`).
+    pub unpriced_requests: i64,
+}
+
+/// Dimension used to group usage in [`UsageLogRepository::aggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageGrouping {
+    Operator,
+    Ghost,
+    /// Model name as recorded on the request.
+    Model,
+    /// UTC calendar day (`YYYY-MM-DD`).
+    Day,
+    /// Individual assistant messages with recorded usage (top 50), so
+    /// expensive exchanges can be found.
+    Message,
+}
+
+/// Row cap for [`UsageGrouping::Message`] reports.
+const COSTLIEST_MESSAGES: i64 = 50;
+
+/// Usage totals for one group of an aggregation.
+#[derive(Debug, Clone, Default)]
+pub struct UsageAggregate {
+    /// Operator id, ghost id, model, `YYYY-MM-DD` or message id, depending on the grouping.
+    pub key: String,
+    /// Operator/ghost name, the model or day again, or `ghost · session · time` for messages.
+    pub label: String,
+    /// UTC day (`YYYY-MM-DD`) of the group, for [`UsageLogRepository::aggregate_daily`].
+    pub day: Option<String>,
+    pub totals: UsageTotals,
 }
 
 /// Repository for usage_log table operations.
@@ -66,11 +129,21 @@ pub struct UsageLogRepository;
 
 impl UsageLogRepository {
     /// Insert a usage log entry.
+    ///
+    /// If `log.cost_usd` is unset, the cost is computed from the model's
+    /// pricing row (left `NULL` when the model is not priced).
     pub async fn insert(pool: &SqlitePool, log: &UsageLog) -> DbResult<()> {
+        let cost_usd = match log.cost_usd {
+            Some(cost) => Some(cost),
+            None => Self::get_pricing(pool, &log.model)
+                .await?
+                .map(|pricing| pricing.cost(&log.tokens)),
+        };
+
         sqlx::query(
             "INSERT INTO usage_log (id, ghost_id, session_id, message_id, created_at, model,
-                input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&log.id)
         .bind(&log.ghost_id)
@@ -82,12 +155,81 @@ impl UsageLogRepository {
         .bind(log.tokens.output_tokens)
         .bind(log.tokens.cache_read_tokens)
         .bind(log.tokens.cache_creation_tokens)
+        .bind(cost_usd)
         .execute(pool)
         .await?;
 
         Ok(())
     }
 
+    /// Insert or replace the pricing for a model.
+    pub async fn upsert_pricing(pool: &SqlitePool, pricing: &ModelPricing) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO model_pricing (model, input_per_mtok, output_per_mtok,
+                cache_read_per_mtok, cache_write_per_mtok, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(model) DO UPDATE SET
+                input_per_mtok = excluded.input_per_mtok,
+                output_per_mtok = excluded.output_per_mtok,
+                cache_read_per_mtok = excluded.cache_read_per_mtok,
+                cache_write_per_mtok = excluded.cache_write_per_mtok,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&pricing.model)
+        .bind(pricing.input_per_mtok)
+        .bind(pricing.output_per_mtok)
+        .bind(pricing.cache_read_per_mtok)
+        .bind(pricing.cache_write_per_mtok)
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the pricing for a model, if any.
+    pub async fn get_pricing(pool: &SqlitePool, model: &str) -> DbResult<Option<ModelPricing>> {
+        let row = sqlx::query_as::<_, ModelPricingRow>(
+            "SELECT model, input_per_mtok, output_per_mtok, cache_read_per_mtok, cache_write_per_mtok
+             FROM model_pricing
+             WHERE model = ?",
+        )
+        .bind(model)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(ModelPricing::from))
+    }
+
+    /// List all model pricing rows, ordered by model.
+    pub async fn list_pricing(pool: &SqlitePool) -> DbResult<Vec<ModelPricing>> {
+        let rows = sqlx::query_as::<_, ModelPricingRow>(
+            "SELECT model, input_per_mtok, output_per_mtok, cache_read_per_mtok, cache_write_per_mtok
+             FROM model_pricing
+             ORDER BY model",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ModelPricing::from).collect())
+    }
+
+    /// List a session's usage entries in request order.
+    pub async fn list_for_session(pool: &SqlitePool, session_id: &str) -> DbResult<Vec<UsageLog>> {
+        let rows = sqlx::query_as::<_, UsageLogRow>(
+            "SELECT id, ghost_id, session_id, message_id, created_at, model,
+                    input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd
+             FROM usage_log
+             WHERE session_id = ?
+             ORDER BY created_at ASC",
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(UsageLog::from).collect())
+    }
+
     /// Get aggregated usage totals for a session.
     pub async fn session_totals(pool: &SqlitePool, session_id: &str) -> DbResult<UsageTotals> {
         let row = sqlx::query_as::<_, UsageTotalsRow>(
@@ -96,7 +238,9 @@ impl UsageLogRepository {
                 COALESCE(SUM(input_tokens), 0) as input_tokens,
                 COALESCE(SUM(output_tokens), 0) as output_tokens,
                 COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens,
-                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens
+                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens,
+                COALESCE(SUM(cost_usd), 0.0) as cost_usd,
+                COALESCE(SUM(cost_usd IS NULL), 0) as unpriced_requests
              FROM usage_log
              WHERE session_id = ?",
         )
@@ -106,6 +250,173 @@ impl UsageLogRepository {
 
         Ok(UsageTotals::from(row))
     }
+
+    /// Aggregate usage recorded at or after `since` (unix seconds).
+    ///
+    /// Operator, ghost, model and message groups are ordered by cost (highest
+    /// first); day groups are ordered newest first.
+    pub async fn aggregate(
+        pool: &SqlitePool,
+        grouping: UsageGrouping,
+        since: i64,
+    ) -> DbResult<Vec<UsageAggregate>> {
+        Self::aggregate_grouped(pool, grouping, since, false).await
+    }
+
+    /// Like [`Self::aggregate`], with each group split per UTC day (newest
+    /// day first). Message groupings are not split.
+    pub async fn aggregate_daily(
+        pool: &SqlitePool,
+        grouping: UsageGrouping,
+        since: i64,
+    ) -> DbResult<Vec<UsageAggregate>> {
+        Self::aggregate_grouped(pool, grouping, since, true).await
+    }
+
+    async fn aggregate_grouped(
+        pool: &SqlitePool,
+        grouping: UsageGrouping,
+        since: i64,
+        daily: bool,
+    ) -> DbResult<Vec<UsageAggregate>> {
+        let (key, label, join, order) = match grouping {
+            UsageGrouping::Operator => (
+                "s.operator_id",
+                "COALESCE(o.name, s.operator_id)",
+                "JOIN sessions s ON s.id = u.session_id \
+                 LEFT JOIN operators o ON o.id = s.operator_id",
+                "cost_usd DESC, key",
+            ),
+            UsageGrouping::Ghost => (
+                "u.ghost_id",
+                "COALESCE(g.name, u.ghost_id)",
+                "LEFT JOIN ghosts g ON g.id = u.ghost_id",
+                "cost_usd DESC, key",
+            ),
+            UsageGrouping::Model => ("u.model", "u.model", "", "cost_usd DESC, key"),
+            UsageGrouping::Day => (
+                "date(u.created_at, 'unixepoch')",
+                "date(u.created_at, 'unixepoch')",
+                "",
+                "key DESC",
+            ),
+            UsageGrouping::Message => return Self::costliest_messages(pool, since).await,
+        };
+        let (day, group, order) = if daily {
+            (
+                "date(u.created_at, 'unixepoch')",
+                format!("{key}, date(u.created_at, 'unixepoch')"),
+                format!("day DESC, {order}"),
+            )
+        } else {
+            ("NULL", key.to_string(), order.to_string())
+        };
+        let sql = format!(
+            "SELECT {key} as key, {label} as label, {day} as day,
+                COUNT(*) as request_count,
+                COALESCE(SUM(u.input_tokens), 0) as input_tokens,
+                COALESCE(SUM(u.output_tokens), 0) as output_tokens,
+                COALESCE(SUM(u.cache_read_tokens), 0) as cache_read_tokens,
+                COALESCE(SUM(u.cache_creation_tokens), 0) as cache_creation_tokens,
+                COALESCE(SUM(u.cost_usd), 0.0) as cost_usd,
+                COALESCE(SUM(u.cost_usd IS NULL), 0) as unpriced_requests
+             FROM usage_log u {join}
+             WHERE u.created_at >= ?
+             GROUP BY {group}
+             ORDER BY {order}"
+        );
+        let rows = sqlx::query_as::<_, UsageAggregateRow>(&sql)
+            .bind(since)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.into_iter().map(UsageAggregate::from).collect())
+    }
+
+    /// Per-message usage stored on `messages`, one group per assistant message.
+    async fn costliest_messages(pool: &SqlitePool, since: i64) -> DbResult<Vec<UsageAggregate>> {
+        let rows = sqlx::query_as::<_, UsageAggregateRow>(
+            "SELECT m.id as key,
+                COALESCE(g.name, m.ghost_id) || ' · ' || m.session_id || ' · '
+                    || datetime(m.created_at, 'unixepoch') as label,
+                NULL as day,
+                1 as request_count,
+                m.input_tokens as input_tokens,
+                COALESCE(m.output_tokens, 0) as output_tokens,
+                COALESCE(m.cache_read_tokens, 0) as cache_read_tokens,
+                COALESCE(m.cache_creation_tokens, 0) as cache_creation_tokens,
+                COALESCE(m.cost_usd, 0.0) as cost_usd,
+                (m.cost_usd IS NULL) as unpriced_requests
+             FROM messages m LEFT JOIN ghosts g ON g.id = m.ghost_id
+             WHERE m.input_tokens IS NOT NULL AND m.created_at >= ?
+             ORDER BY cost_usd DESC,
+                m.input_tokens + COALESCE(m.cache_read_tokens, 0)
+                    + COALESCE(m.cache_creation_tokens, 0) DESC
+             LIMIT ?",
+        )
+        .bind(since)
+        .bind(COSTLIEST_MESSAGES)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(UsageAggregate::from).collect())
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UsageLogRow {
+    id: String,
+    ghost_id: String,
+    session_id: String,
+    message_id: Option<String>,
+    created_at: i64,
+    model: String,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_read_tokens: i64,
+    cache_creation_tokens: i64,
+    cost_usd: Option<f64>,
+}
+
+impl From<UsageLogRow> for UsageLog {
+    fn from(row: UsageLogRow) -> Self {
+        UsageLog {
+            id: row.id,
+            ghost_id: row.ghost_id,
+            session_id: row.session_id,
+            message_id: row.message_id,
+            request_at: row.created_at,
+            model: row.model,
+            tokens: TokenUsage {
+                input_tokens: row.input_tokens as u32,
+                output_tokens: row.output_tokens as u32,
+                cache_read_tokens: row.cache_read_tokens as u32,
+                cache_creation_tokens: row.cache_creation_tokens as u32,
+            },
+            cost_usd: row.cost_usd,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ModelPricingRow {
+    model: String,
+    input_per_mtok: f64,
+    output_per_mtok: f64,
+    cache_read_per_mtok: f64,
+    cache_write_per_mtok: f64,
+}
+
+impl From<ModelPricingRow> for ModelPricing {
+    fn from(row: ModelPricingRow) -> Self {
+        ModelPricing {
+            model: row.model,
+            input_per_mtok: row.input_per_mtok,
+            output_per_mtok: row.output_per_mtok,
+            cache_read_per_mtok: row.cache_read_per_mtok,
+            cache_write_per_mtok: row.cache_write_per_mtok,
+        }
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -115,6 +426,8 @@ struct UsageTotalsRow {
     output_tokens: i64,
     cache_read_tokens: i64,
     cache_creation_tokens: i64,
+    cost_usd: f64,
+    unpriced_requests: i64,
 }
 
 impl From<UsageTotalsRow> for UsageTotals {
@@ -125,6 +438,28 @@ impl From<UsageTotalsRow> for UsageTotals {
             output_tokens: row.output_tokens,
             cache_read_tokens: row.cache_read_tokens,
             cache_creation_tokens: row.cache_creation_tokens,
+            cost_usd: row.cost_usd,
+            unpriced_requests: row.unpriced_requests,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UsageAggregateRow {
+    key: String,
+    label: String,
+    day: Option<String>,
+    #[sqlx(flatten)]
+    totals: UsageTotalsRow,
+}
+
+impl From<UsageAggregateRow> for UsageAggregate {
+    fn from(row: UsageAggregateRow) -> Self {
+        UsageAggregate {
+            key: row.key,
+            label: row.label,
+            day: row.day,
+            totals: UsageTotals::from(row.totals),
         }
     }
 }
@@ -254,4 +589,228 @@ mod tests {
         assert_eq!(totals.request_count, 1);
         assert_eq!(totals.input_tokens, 5000);
     }
+
+    #[tokio::test]
+    async fn test_cost_from_model_pricing() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let (operator, ghost) = create_test_operator_and_ghost(pool).await;
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        UsageLogRepository::upsert_pricing(
+            pool,
+            &ModelPricing {
+                model: "priced-model".to_string(),
+                input_per_mtok: 3.0,
+                output_per_mtok: 15.0,
+                cache_read_per_mtok: 0.3,
+                cache_write_per_mtok: 3.75,
+            },
+        )
+        .await
+        .unwrap();
+
+        let tokens = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 1_000_000,
+            cache_creation_tokens: 0,
+        };
+        UsageLogRepository::insert(
+            pool,
+            &UsageLog::new(&ghost.id, &session.id, None, "priced-model", tokens.clone()),
+        )
+        .await
+        .unwrap();
+        UsageLogRepository::insert(
+            pool,
+            &UsageLog::new(&ghost.id, &session.id, None, "unpriced-model", tokens),
+        )
+        .await
+        .unwrap();
+
+        let logs = UsageLogRepository::list_for_session(pool, &session.id)
+            .await
+            .unwrap();
+        let priced = logs.iter().find(|l| l.model == "priced-model").unwrap();
+        assert!((priced.cost_usd.unwrap() - 4.8).abs() < 1e-9);
+        let unpriced = logs.iter().find(|l| l.model == "unpriced-model").unwrap();
+        assert!(unpriced.cost_usd.is_none());
+
+        let totals = UsageLogRepository::session_totals(pool, &session.id)
+            .await
+            .unwrap();
+        assert!((totals.cost_usd - 4.8).abs() < 1e-9);
+        assert_eq!(totals.unpriced_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_by_operator_ghost_and_day() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let (operator, ghost) = create_test_operator_and_ghost(pool).await;
+        let other_ghost = GhostRepository::create(pool, &operator.id, "OtherGhost")
+            .await
+            .unwrap();
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        let other_session = SessionRepository::create(pool, &other_ghost.id, &operator.id)
+            .await
+            .unwrap();
+
+        let tokens = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            ..Default::default()
+        };
+        let mut old = UsageLog::new(&ghost.id, &session.id, None, "m", tokens.clone());
+        old.request_at = 86_400;
+        old.cost_usd = Some(1.0);
+        UsageLogRepository::insert(pool, &old).await.unwrap();
+        let mut recent = UsageLog::new(&ghost.id, &session.id, None, "m", tokens.clone());
+        recent.cost_usd = Some(0.5);
+        UsageLogRepository::insert(pool, &recent).await.unwrap();
+        let mut other = UsageLog::new(&other_ghost.id, &other_session.id, None, "m", tokens);
+        other.cost_usd = Some(2.0);
+        UsageLogRepository::insert(pool, &other).await.unwrap();
+
+        let by_operator = UsageLogRepository::aggregate(pool, UsageGrouping::Operator, 0)
+            .await
+            .unwrap();
+        assert_eq!(by_operator.len(), 1);
+        assert_eq!(by_operator[0].key, operator.id);
+        assert_eq!(by_operator[0].label, "TestOp");
+        assert_eq!(by_operator[0].totals.request_count, 3);
+        assert!((by_operator[0].totals.cost_usd - 3.5).abs() < 1e-9);
+
+        let by_ghost = UsageLogRepository::aggregate(pool, UsageGrouping::Ghost, 0)
+            .await
+            .unwrap();
+        let labels: Vec<&str> = by_ghost.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["OtherGhost", "TestGhost"]);
+
+        let by_day = UsageLogRepository::aggregate(pool, UsageGrouping::Day, 0)
+            .await
+            .unwrap();
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[1].key, "1970-01-02");
+        assert_eq!(by_day[0].totals.input_tokens, 200);
+
+        let recent_only = UsageLogRepository::aggregate(pool, UsageGrouping::Ghost, 1_000_000)
+            .await
+            .unwrap();
+        let total: i64 = recent_only.iter().map(|a| a.totals.request_count).sum();
+        assert_eq!(total, 2);
+
+        let by_model = UsageLogRepository::aggregate(pool, UsageGrouping::Model, 0)
+            .await
+            .unwrap();
+        assert_eq!(by_model.len(), 1);
+        assert_eq!(by_model[0].label, "m");
+        assert_eq!(by_model[0].day, None);
+
+        let ghost_days = UsageLogRepository::aggregate_daily(pool, UsageGrouping::Ghost, 0)
+            .await
+            .unwrap();
+        let groups: Vec<(&str, &str)> = ghost_days
+            .iter()
+            .map(|a| (a.label.as_str(), a.day.as_deref().unwrap_or("")))
+            .collect();
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        assert_eq!(
+            groups,
+            vec![
+                ("OtherGhost", today.as_str()),
+                ("TestGhost", today.as_str()),
+                ("TestGhost", "1970-01-02"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_by_message_ranks_costliest_exchanges() {
+        use crate::sessions::{ContentBlock, MessageRole};
+
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let (operator, ghost) = create_test_operator_and_ghost(pool).await;
+        let session = SessionRepository::create(pool, &ghost.id, &operator.id)
+            .await
+            .unwrap();
+        UsageLogRepository::upsert_pricing(
+            pool,
+            &ModelPricing {
+                model: "m".to_string(),
+                input_per_mtok: 1.0,
+                output_per_mtok: 1.0,
+                cache_read_per_mtok: 1.0,
+                cache_write_per_mtok: 1.0,
+            },
+        )
+        .await
+        .unwrap();
+
+        let reply = |text: &str| {
+            vec![ContentBlock::Text {
+                text: text.to_string(),
+            }]
+        };
+        SessionRepository::add_message(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Operator,
+            reply("hi"),
+            None,
+        )
+        .await
+        .unwrap();
+        let cheap = SessionRepository::add_message_with_usage(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Ghost,
+            reply("short"),
+            Some("m"),
+            Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 10,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let expensive = SessionRepository::add_message_with_usage(
+            pool,
+            &ghost.id,
+            &session.id,
+            MessageRole::Ghost,
+            reply("long"),
+            Some("m"),
+            Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 10,
+                cache_creation_tokens: 50_000,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let by_message = UsageLogRepository::aggregate(pool, UsageGrouping::Message, 0)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = by_message.iter().map(|a| a.key.as_str()).collect();
+        assert_eq!(keys, vec![expensive.id.as_str(), cheap.id.as_str()]);
+        assert!(by_message[0].label.starts_with("TestGhost · "));
+        assert_eq!(by_message[0].totals.cache_creation_tokens, 50_000);
+        assert!(by_message[0].totals.cost_usd > by_message[1].totals.cost_usd);
+        assert_eq!(by_message[0].totals.unpriced_requests, 0);
+    }
 }
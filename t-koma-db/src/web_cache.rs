@@ -0,0 +1,242 @@
+//! Persistent cache for web tool results (`web_search`, `web_fetch`).
+//!
+//! Entries are keyed by tool kind and request. `stored_at` is when the value
+//! was last fetched or revalidated; freshness against a TTL is decided by the
+//! caller. [`WebCacheRepository::evict`] keeps the table within entry and
+//! byte limits, least recently used first.
+
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// A cached web tool result.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct WebCacheEntry {
+    /// Tool kind (`search`, `fetch`)
+    pub kind: String,
+    pub cache_key: String,
+    /// Serialized response
+    pub value: String,
+    /// `ETag` validator returned by the origin, if any
+    pub etag: Option<String>,
+    /// `Last-Modified` validator returned by the origin, if any
+    pub last_modified: Option<String>,
+    /// Unix timestamp of the last fetch or revalidation
+    pub stored_at: i64,
+    /// Unix timestamp of the last read or write
+    pub accessed_at: i64,
+}
+
+/// Repository for `web_cache`.
+pub struct WebCacheRepository;
+
+impl WebCacheRepository {
+    /// Look up an entry and mark it used at `now`.
+    pub async fn get(
+        pool: &SqlitePool,
+        kind: &str,
+        cache_key: &str,
+        now: i64,
+    ) -> DbResult<Option<WebCacheEntry>> {
+        let entry = sqlx::query_as::<_, WebCacheEntry>(
+            r#"
+            UPDATE web_cache SET accessed_at = ?
+            WHERE kind = ? AND cache_key = ?
+            RETURNING kind, cache_key, value, etag, last_modified, stored_at, accessed_at
+            "#,
+        )
+        .bind(now)
+        .bind(kind)
+        .bind(cache_key)
+        .fetch_optional(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    /// Insert or replace an entry.
+    pub async fn put(pool: &SqlitePool, entry: &WebCacheEntry) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO web_cache
+              (kind, cache_key, value, etag, last_modified, size_bytes, stored_at, accessed_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(kind, cache_key) DO UPDATE SET
+              value = excluded.value,
+              etag = excluded.etag,
+              last_modified = excluded.last_modified,
+              size_bytes = excluded.size_bytes,
+              stored_at = excluded.stored_at,
+              accessed_at = excluded.accessed_at
+            "#,
+        )
+        .bind(&entry.kind)
+        .bind(&entry.cache_key)
+        .bind(&entry.value)
+        .bind(&entry.etag)
+        .bind(&entry.last_modified)
+        .bind(entry.value.len() as i64)
+        .bind(entry.stored_at)
+        .bind(entry.accessed_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that the origin confirmed an entry is unchanged (HTTP 304).
+    pub async fn mark_revalidated(
+        pool: &SqlitePool,
+        kind: &str,
+        cache_key: &str,
+        now: i64,
+    ) -> DbResult<()> {
+        sqlx::query(
+            "UPDATE web_cache SET stored_at = ?, accessed_at = ? WHERE kind = ? AND cache_key = ?",
+        )
+        .bind(now)
+        .bind(now)
+        .bind(kind)
+        .bind(cache_key)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Drop least recently used entries beyond `max_entries` or `max_total_bytes`
+    /// (`None` disables a limit). Returns how many were removed.
+    pub async fn evict(
+        pool: &SqlitePool,
+        max_entries: Option<u32>,
+        max_total_bytes: Option<i64>,
+    ) -> DbResult<u64> {
+        if max_entries.is_none() && max_total_bytes.is_none() {
+            return Ok(0);
+        }
+        let result = sqlx::query(
+            r#"
+            WITH ranked AS (
+                SELECT kind, cache_key,
+                       ROW_NUMBER() OVER (
+                           ORDER BY accessed_at DESC, kind, cache_key
+                       ) AS lru_rank,
+                       SUM(size_bytes) OVER (
+                           ORDER BY accessed_at DESC, kind, cache_key ROWS UNBOUNDED PRECEDING
+                       ) AS running_bytes
+                FROM web_cache
+            )
+            DELETE FROM web_cache WHERE (kind, cache_key) IN (
+                SELECT kind, cache_key FROM ranked WHERE lru_rank > ? OR running_bytes > ?
+            )
+            "#,
+        )
+        .bind(max_entries.map_or(i64::MAX, i64::from))
+        .bind(max_total_bytes.unwrap_or(i64::MAX))
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_pool;
+
+    fn entry(key: &str, value: &str, at: i64) -> WebCacheEntry {
+        WebCacheEntry {
+            kind: "fetch".to_string(),
+            cache_key: key.to_string(),
+            value: value.to_string(),
+            etag: Some(format!("\"{key}\"")),
+            last_modified: None,
+            stored_at: at,
+            accessed_at: at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_get_and_revalidate() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        assert!(
+            WebCacheRepository::get(pool, "fetch", "a", 10)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        WebCacheRepository::put(pool, &entry("a", "v1", 10))
+            .await
+            .unwrap();
+        WebCacheRepository::put(pool, &entry("a", "v2", 20))
+            .await
+            .unwrap();
+
+        let found = WebCacheRepository::get(pool, "fetch", "a", 30)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "v2");
+        assert_eq!((found.stored_at, found.accessed_at), (20, 30));
+        assert!(
+            WebCacheRepository::get(pool, "search", "a", 30)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        WebCacheRepository::mark_revalidated(pool, "fetch", "a", 40)
+            .await
+            .unwrap();
+        let found = WebCacheRepository::get(pool, "fetch", "a", 50)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.stored_at, 40);
+    }
+
+    #[tokio::test]
+    async fn test_evict_least_recently_used() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        for (key, at) in [("old", 1), ("mid", 2), ("new", 3)] {
+            WebCacheRepository::put(pool, &entry(key, "0123456789", at))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            WebCacheRepository::evict(pool, None, None).await.unwrap(),
+            0
+        );
+
+        // Reading "old" makes it the most recently used.
+        WebCacheRepository::get(pool, "fetch", "old", 4)
+            .await
+            .unwrap();
+        assert_eq!(
+            WebCacheRepository::evict(pool, Some(2), None)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            WebCacheRepository::get(pool, "fetch", "mid", 5)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(
+            WebCacheRepository::evict(pool, None, Some(15))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            WebCacheRepository::get(pool, "fetch", "old", 6)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,578 @@
+//! Admin REST API under `/api/admin`: operators, their interfaces and
+//! ghosts, for tokens with the `admin` scope. Each route mirrors a TUI or WS
+//! admin action.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post, put},
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use super::{ApiError, authenticate_api_scope, ensure_writable};
+use crate::content::ids;
+use crate::gateway_message;
+use crate::operator_flow;
+use crate::state::AppState;
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    gateway_message::from_content(id, None, vars).text_fallback
+}
+
+/// Routes under `/api/admin`.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/admin/operators", get(list_operators_handler))
+        .route(
+            "/api/admin/operators/{operator_id}/approve",
+            post(approve_operator_handler),
+        )
+        .route(
+            "/api/admin/operators/{operator_id}/deny",
+            post(deny_operator_handler),
+        )
+        .route(
+            "/api/admin/operators/{operator_id}/rate_limits",
+            put(rate_limits_handler),
+        )
+        .route(
+            "/api/admin/operators/{operator_id}/interfaces",
+            get(list_interfaces_handler),
+        )
+        .route(
+            "/api/admin/interfaces/{interface_id}",
+            delete(delete_interface_handler),
+        )
+        .route(
+            "/api/admin/ghosts",
+            get(list_ghosts_handler).post(create_ghost_handler),
+        )
+        .route("/api/admin/ghosts/{name}", delete(delete_ghost_handler))
+        .route(
+            "/api/admin/ghosts/{name}/rename",
+            post(rename_ghost_handler),
+        )
+        .route("/api/admin/ghosts/{name}/clone", post(clone_ghost_handler))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiOperatorsQuery {
+    /// `pending`, `approved` or `denied`; every operator when omitted.
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiRateLimitsRequest {
+    /// Messages per 5 minutes; `null` restores the default.
+    pub rate_limit_5m_max: Option<i64>,
+    /// Messages per hour; `null` restores the default.
+    pub rate_limit_1h_max: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiCreateGhostRequest {
+    pub owner_operator_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiRenameGhostRequest {
+    pub new_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiCloneGhostRequest {
+    pub new_name: String,
+    /// Owner of the clone; the source ghost's owner when omitted.
+    pub owner_operator_id: Option<String>,
+    /// Parts to copy; soul, skills and settings when omitted.
+    pub copy: Option<Vec<t_koma_core::GhostCloneScope>>,
+}
+
+/// Authenticate an admin REST API request (`/api/admin/...`).
+///
+/// Same rules as [`super::authenticate_api`], but the token needs the `admin`
+/// scope and its operator must still hold `manage_operators`, so a demotion or
+/// revoked permission takes effect on tokens issued earlier.
+async fn authenticate_admin(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<t_koma_db::Operator, ApiError> {
+    let operator = authenticate_api_scope(state, headers, t_koma_db::ApiTokenScope::Admin).await?;
+    let allowed = t_koma_db::OperatorRepository::has_permission(
+        state.koma_db.pool(),
+        &operator.id,
+        &t_koma_db::OperatorPermission::ManageOperators,
+    )
+    .await
+    .map_err(|e| ApiError::internal("permission lookup failed", e))?;
+    if !allowed {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "operator lacks the manage_operators permission",
+        ));
+    }
+    Ok(operator)
+}
+
+/// The ghost named `name`, for admin routes (no ownership check).
+async fn ghost_by_name(state: &AppState, name: &str) -> Result<t_koma_db::Ghost, ApiError> {
+    t_koma_db::GhostRepository::get_by_name(state.koma_db.pool(), name)
+        .await
+        .map_err(|e| ApiError::internal(&render_message(ids::FAILED_LOAD_GHOST, &[]), e))?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                render_message(ids::UNKNOWN_GHOST_NAME_SERVER, &[]),
+            )
+        })
+}
+
+/// `GET /api/admin/operators?status=<status>`: operators, oldest first.
+async fn list_operators_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ApiOperatorsQuery>,
+) -> Result<Json<Vec<t_koma_db::Operator>>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    let pool = state.koma_db.pool();
+    let operators = match query.status.as_deref() {
+        Some(status) => {
+            let status = status
+                .parse::<t_koma_db::OperatorStatus>()
+                .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
+            t_koma_db::OperatorRepository::list_by_status(pool, status, None).await
+        }
+        None => t_koma_db::OperatorRepository::list_all(pool).await,
+    }
+    .map_err(|e| ApiError::internal("Failed to list operators", e))?;
+    Ok(Json(operators))
+}
+
+/// `POST /api/admin/operators/{id}/approve`: like the WS `approve_operator` message.
+async fn approve_operator_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(operator_id): Path<String>,
+) -> Result<Json<t_koma_core::WsResponse>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    t_koma_db::OperatorRepository::approve(state.koma_db.pool(), &operator_id)
+        .await
+        .map_err(|e| ApiError::db("Approve failed", e))?;
+    let discord_notified =
+        operator_flow::welcome_approved_operator(state.as_ref(), &operator_id).await;
+    Ok(Json(t_koma_core::WsResponse::OperatorApproved {
+        operator_id,
+        discord_notified,
+    }))
+}
+
+/// `POST /api/admin/operators/{id}/deny`: reject a pending operator.
+async fn deny_operator_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(operator_id): Path<String>,
+) -> Result<Json<t_koma_db::Operator>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    let operator = t_koma_db::OperatorRepository::deny(state.koma_db.pool(), &operator_id)
+        .await
+        .map_err(|e| ApiError::db("Deny failed", e))?;
+    Ok(Json(operator))
+}
+
+/// `PUT /api/admin/operators/{id}/rate_limits`: per-operator message limits.
+async fn rate_limits_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(operator_id): Path<String>,
+    Json(request): Json<ApiRateLimitsRequest>,
+) -> Result<Json<t_koma_db::Operator>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    if request.rate_limit_5m_max.is_some_and(|max| max <= 0)
+        || request.rate_limit_1h_max.is_some_and(|max| max <= 0)
+    {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "rate limits must be positive",
+        ));
+    }
+    let pool = state.koma_db.pool();
+    if t_koma_db::OperatorRepository::get_by_id(pool, &operator_id)
+        .await
+        .map_err(|e| ApiError::internal("Operator lookup failed", e))?
+        .is_none()
+    {
+        return Err(ApiError::db(
+            "Rate limit update failed",
+            t_koma_db::DbError::OperatorNotFound(operator_id),
+        ));
+    }
+    let operator = t_koma_db::OperatorRepository::set_rate_limits(
+        pool,
+        &operator_id,
+        request.rate_limit_5m_max,
+        request.rate_limit_1h_max,
+    )
+    .await
+    .map_err(|e| ApiError::db("Rate limit update failed", e))?;
+    Ok(Json(operator))
+}
+
+/// `GET /api/admin/operators/{id}/interfaces`: the operator's linked interfaces.
+async fn list_interfaces_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(operator_id): Path<String>,
+) -> Result<Json<Vec<t_koma_db::Interface>>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    let interfaces =
+        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), &operator_id)
+            .await
+            .map_err(|e| ApiError::internal("Failed to list interfaces", e))?;
+    Ok(Json(interfaces))
+}
+
+/// `DELETE /api/admin/interfaces/{id}`: unlink an interface from its operator.
+///
+/// The next message from that account starts the NEW/EXISTING flow again.
+async fn delete_interface_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(interface_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    t_koma_db::InterfaceRepository::delete(state.koma_db.pool(), &interface_id)
+        .await
+        .map_err(|e| ApiError::db("Interface delete failed", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /api/admin/ghosts`: every ghost, whoever owns it.
+async fn list_ghosts_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<t_koma_db::Ghost>>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    let ghosts = t_koma_db::GhostRepository::list_all(state.koma_db.pool())
+        .await
+        .map_err(|e| ApiError::internal("Failed to list ghosts", e))?;
+    Ok(Json(ghosts))
+}
+
+/// `POST /api/admin/ghosts`: create a ghost for an operator, like the TUI.
+async fn create_ghost_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ApiCreateGhostRequest>,
+) -> Result<(StatusCode, Json<t_koma_db::Ghost>), ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    let pool = state.koma_db.pool();
+    if t_koma_db::OperatorRepository::get_by_id(pool, &request.owner_operator_id)
+        .await
+        .map_err(|e| ApiError::internal("Operator lookup failed", e))?
+        .is_none()
+    {
+        return Err(ApiError::db(
+            "Ghost create failed",
+            t_koma_db::DbError::OperatorNotFound(request.owner_operator_id),
+        ));
+    }
+    let ghost = t_koma_db::GhostRepository::create(pool, &request.owner_operator_id, &request.name)
+        .await
+        .map_err(|e| ApiError::db("Ghost create failed", e))?;
+    Ok((StatusCode::CREATED, Json(ghost)))
+}
+
+/// `POST /api/admin/ghosts/{name}/rename`: like the WS `rename_ghost` message.
+async fn rename_ghost_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(request): Json<ApiRenameGhostRequest>,
+) -> Result<Json<t_koma_core::WsResponse>, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    ghost_by_name(&state, &name).await?;
+    let ghost = state
+        .rename_ghost(&name, &request.new_name)
+        .await
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Ghost rename failed: {}", e),
+            )
+        })?;
+    Ok(Json(t_koma_core::WsResponse::GhostRenamed {
+        old_name: name,
+        new_name: ghost.name,
+    }))
+}
+
+/// `POST /api/admin/ghosts/{name}/clone`: like the WS `clone_ghost` message.
+async fn clone_ghost_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(request): Json<ApiCloneGhostRequest>,
+) -> Result<(StatusCode, Json<t_koma_core::WsResponse>), ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    ghost_by_name(&state, &name).await?;
+    let (options, knowledge_scopes) =
+        crate::server::ghost_clone_plan(request.owner_operator_id, request.copy.as_deref());
+    let (ghost, knowledge_files) = state
+        .clone_ghost(&name, &request.new_name, &options, &knowledge_scopes)
+        .await
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Ghost clone failed: {}", e),
+            )
+        })?;
+    Ok((
+        StatusCode::CREATED,
+        Json(t_koma_core::WsResponse::GhostCloned {
+            source: name,
+            name: ghost.name,
+            knowledge_files,
+        }),
+    ))
+}
+
+/// `DELETE /api/admin/ghosts/{name}`: delete a ghost and its workspace, like the TUI.
+async fn delete_ghost_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    authenticate_admin(&state, &headers).await?;
+    ensure_writable(&state)?;
+    t_koma_db::GhostRepository::delete_by_name(state.koma_db.pool(), &name)
+        .await
+        .map_err(|e| ApiError::db("Ghost delete failed", e))?;
+    if let Ok(workspace) = t_koma_db::ghosts::ghost_workspace_path(&name)
+        && let Err(e) = tokio::fs::remove_dir_all(&workspace).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!(
+            "Failed to remove workspace of deleted ghost {}: {}",
+            name, e
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::testing::{operator_with_token, send};
+    use serde_json::json;
+    use t_koma_db::{ApiTokenScope, OperatorAccessLevel, OperatorPermission, OperatorRepository};
+
+    #[tokio::test]
+    async fn test_admin_routes_require_admin_scope() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (_, chat) = operator_with_token(&db, "Chat", &[ApiTokenScope::Chat], true).await;
+        let (_, admin) = operator_with_token(&db, "Admin", &[ApiTokenScope::Admin], true).await;
+        let (state, _temp) = crate::state::test_app_state(db).await;
+
+        let (status, body) =
+            send(router(), &state, "GET", "/api/admin/operators", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["error"].is_string());
+
+        let (status, body) = send(
+            router(),
+            &state,
+            "GET",
+            "/api/admin/operators",
+            Some(&chat),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "API token lacks the admin scope");
+
+        let (status, body) = send(
+            router(),
+            &state,
+            "GET",
+            "/api/admin/operators",
+            Some(&admin),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().map(Vec::len), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_recheck_manage_operators() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (admin, token) = operator_with_token(&db, "Admin", &[ApiTokenScope::Admin], true).await;
+        let pool = db.pool().clone();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        let list = |state: Arc<AppState>| {
+            let token = token.clone();
+            async move {
+                send(
+                    router(),
+                    &state,
+                    "GET",
+                    "/api/admin/operators",
+                    Some(&token),
+                    None,
+                )
+                .await
+            }
+        };
+
+        let (status, _) = list(Arc::clone(&state)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        OperatorRepository::set_permission(
+            &pool,
+            &admin.id,
+            &OperatorPermission::ManageOperators,
+            Some(false),
+        )
+        .await
+        .unwrap();
+        let (status, body) = list(Arc::clone(&state)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(
+            body["error"],
+            "operator lacks the manage_operators permission"
+        );
+
+        OperatorRepository::set_permission(
+            &pool,
+            &admin.id,
+            &OperatorPermission::ManageOperators,
+            None,
+        )
+        .await
+        .unwrap();
+        OperatorRepository::set_access_level(&pool, &admin.id, OperatorAccessLevel::Standard)
+            .await
+            .unwrap();
+        let (status, _) = list(Arc::clone(&state)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_db_errors_map_to_404_and_409() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (owner, admin) = operator_with_token(&db, "Admin", &[ApiTokenScope::Admin], true).await;
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        let token = Some(admin.as_str());
+
+        let (status, _) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/admin/operators/op_missing/approve",
+            token,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(
+            router(),
+            &state,
+            "DELETE",
+            "/api/admin/interfaces/if_missing",
+            token,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/admin/ghosts",
+            token,
+            Some(json!({ "owner_operator_id": "op_missing", "name": "Alpha" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let create = json!({ "owner_operator_id": owner.id, "name": "Alpha" });
+        let (status, body) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/admin/ghosts",
+            token,
+            Some(create.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["name"], "Alpha");
+        let (status, _) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/admin/ghosts",
+            token,
+            Some(create),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Approved operators can't be denied.
+        let uri = format!("/api/admin/operators/{}/deny", owner.id);
+        let (status, _) = send(router(), &state, "POST", &uri, token, None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_admin_writes_refused_on_replica() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("koma.sqlite3");
+        let primary = t_koma_db::test_helpers::create_test_file_pool(&path)
+            .await
+            .unwrap();
+        let (owner, admin) =
+            operator_with_token(&primary, "Admin", &[ApiTokenScope::Admin], true).await;
+        primary.close().await;
+
+        let replica = t_koma_db::test_helpers::open_read_only_test_pool(&path)
+            .await
+            .unwrap();
+        let (state, _temp) = crate::state::test_app_state(replica).await;
+        let token = Some(admin.as_str());
+
+        let (status, body) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/admin/ghosts",
+            token,
+            Some(json!({ "owner_operator_id": owner.id, "name": "Alpha" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(
+            body["error"]
+                .as_str()
+                .is_some_and(|error| error.contains("read-only replica"))
+        );
+
+        // Reads still work.
+        let (status, _) = send(router(), &state, "GET", "/api/admin/ghosts", token, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
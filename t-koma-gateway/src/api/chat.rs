@@ -0,0 +1,438 @@
+//! Chat REST API under `/api`: sessions, chat turns and knowledge search,
+//! for tokens with the `chat` scope. Each route mirrors a WS message and
+//! returns the same response shapes.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, authenticate_api, ensure_writable};
+use crate::content::ids;
+use crate::gateway_message;
+use crate::operator_flow;
+use crate::server::{knowledge_results_to_dto, session_infos, ws_from_outbound};
+use crate::state::{AppState, LogEntry, RateLimitDecision};
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    gateway_message::from_content(id, None, vars).text_fallback
+}
+
+/// REST routes for `chat`-scoped tokens.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/chat", post(chat_handler))
+        .route(
+            "/api/sessions",
+            get(list_sessions_handler).post(create_session_handler),
+        )
+        .route("/api/knowledge/search", get(knowledge_search_handler))
+}
+
+/// The ghost named `ghost_name`, if `operator_id` owns it. Other operators'
+/// ghosts get the same 404 as unknown names, so their existence doesn't leak.
+async fn owned_ghost(
+    state: &AppState,
+    operator_id: &str,
+    ghost_name: &str,
+) -> Result<t_koma_db::Ghost, ApiError> {
+    t_koma_db::GhostRepository::get_by_name(state.koma_db.pool(), ghost_name)
+        .await
+        .map_err(|e| ApiError::internal(&render_message(ids::FAILED_LOAD_GHOST, &[]), e))?
+        .filter(|ghost| ghost.owner_operator_id == operator_id)
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                render_message(ids::UNKNOWN_GHOST_NAME_SERVER, &[]),
+            )
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiSessionsQuery {
+    pub ghost: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKnowledgeSearchQuery {
+    pub ghost: String,
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiCreateSessionRequest {
+    pub ghost: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiChatRequest {
+    pub ghost: String,
+    /// Session to continue; the active session when omitted or `"active"`.
+    pub session_id: Option<String>,
+    pub content: String,
+    /// Model alias for this turn instead of the ghost's default.
+    pub model: Option<String>,
+    /// Files sent with the message, as in the WS `chat` message.
+    #[serde(default)]
+    pub attachments: Vec<t_koma_core::ChatAttachment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiChatResponse {
+    pub session_id: String,
+    /// The same messages a `/ws` client receives for this turn.
+    pub responses: Vec<t_koma_core::WsResponse>,
+}
+
+/// `GET /api/sessions?ghost=<name>`: like the WS `list_sessions` message.
+async fn list_sessions_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ApiSessionsQuery>,
+) -> Result<Json<t_koma_core::WsResponse>, ApiError> {
+    let operator = authenticate_api(&state, &headers).await?;
+    let ghost = owned_ghost(&state, &operator.id, &query.ghost).await?;
+    let sessions = session_infos(&state, &operator.id, &ghost)
+        .await
+        .map_err(|e| ApiError::internal(&render_message(ids::FAILED_LIST_SESSIONS, &[]), e))?;
+    Ok(Json(t_koma_core::WsResponse::SessionList { sessions }))
+}
+
+/// `POST /api/sessions`: like the WS `create_session` message.
+async fn create_session_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ApiCreateSessionRequest>,
+) -> Result<(StatusCode, Json<t_koma_core::WsResponse>), ApiError> {
+    let operator = authenticate_api(&state, &headers).await?;
+    ensure_writable(&state)?;
+    let ghost = owned_ghost(&state, &operator.id, &request.ghost).await?;
+    let session =
+        t_koma_db::SessionRepository::create(state.koma_db.pool(), &ghost.id, &operator.id)
+            .await
+            .map_err(|e| ApiError::internal(&render_message(ids::FAILED_CREATE_SESSION, &[]), e))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(t_koma_core::WsResponse::SessionCreated {
+            session_id: session.id,
+        }),
+    ))
+}
+
+/// `GET /api/knowledge/search?ghost=<name>&q=<query>`: like the WS
+/// `search_knowledge` message, limited to the operator's own ghosts.
+async fn knowledge_search_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ApiKnowledgeSearchQuery>,
+) -> Result<Json<t_koma_core::WsResponse>, ApiError> {
+    let operator = authenticate_api(&state, &headers).await?;
+    let ghost = owned_ghost(&state, &operator.id, &query.ghost).await?;
+    let search_query = t_koma_knowledge::models::KnowledgeSearchQuery {
+        query: query.q,
+        categories: None,
+        scope: t_koma_knowledge::models::OwnershipScope::All,
+        topic: None,
+        archetype: None,
+        options: t_koma_knowledge::models::SearchOptions {
+            max_results: query.limit.or(Some(20)),
+            ..Default::default()
+        },
+    };
+    let results = state
+        .knowledge_engine()
+        .knowledge_search(&ghost.name, search_query)
+        .await
+        .map_err(|e| ApiError::internal("Knowledge search failed", e))?;
+    Ok(Json(t_koma_core::WsResponse::KnowledgeSearchResults {
+        results: knowledge_results_to_dto(&results),
+    }))
+}
+
+/// `POST /api/chat`: one chat turn, like the WS `chat` message.
+///
+/// `approve`, `deny` and `steps N` answer a pending tool approval or loop
+/// limit, as on other interfaces.
+async fn chat_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ApiChatRequest>,
+) -> Result<Json<ApiChatResponse>, ApiError> {
+    let operator = authenticate_api(&state, &headers).await?;
+    ensure_writable(&state)?;
+    let ghost = owned_ghost(&state, &operator.id, &request.ghost).await?;
+    let pool = state.koma_db.pool();
+
+    let session_id = match request.session_id.as_deref() {
+        None | Some("active") => {
+            t_koma_db::SessionRepository::get_or_create_active(pool, &ghost.id, &operator.id)
+                .await
+                .map_err(|e| ApiError::internal(&render_message(ids::FAILED_INIT_SESSION, &[]), e))?
+                .id
+        }
+        Some(session_id) => {
+            match t_koma_db::SessionRepository::get_by_id_for_ghost(pool, session_id, &ghost.id)
+                .await
+            {
+                Ok(Some(session)) if session.operator_id == operator.id => session.id,
+                _ => {
+                    return Err(ApiError::new(
+                        StatusCode::NOT_FOUND,
+                        render_message(ids::INVALID_SESSION, &[]),
+                    ));
+                }
+            }
+        }
+    };
+
+    let content = request.content.trim();
+    if let RateLimitDecision::Limited { retry_after } =
+        state.check_operator_rate_limit(&operator).await
+    {
+        if !content.eq_ignore_ascii_case("continue") {
+            state
+                .store_pending_message(&operator.id, &ghost.name, &session_id, content)
+                .await;
+        }
+        let retry_after = retry_after.as_secs().to_string();
+        return Err(ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            render_message(ids::RATE_LIMITED, &[("retry_after", retry_after.as_str())]),
+        ));
+    }
+
+    state
+        .log(LogEntry::Routing {
+            platform: "api".to_string(),
+            operator_id: operator.id.clone(),
+            ghost_name: ghost.name.clone(),
+            session_id: session_id.clone(),
+        })
+        .await;
+
+    let attachment_blocks = if request.attachments.is_empty() {
+        vec![]
+    } else {
+        let workspace_path = t_koma_db::ghosts::ghost_workspace_path(&ghost.name).map_err(|e| {
+            ApiError::internal(
+                &render_message(ids::ERROR_FAILED_INIT_GHOST_STORAGE, &[]),
+                e,
+            )
+        })?;
+        crate::attachments::ws_attachments_to_content_blocks(&request.attachments, &workspace_path)
+            .await
+    };
+
+    let model_alias = request.model.as_deref();
+    let messages = match operator_flow::run_tool_control_command(
+        state.as_ref(),
+        None,
+        model_alias,
+        &ghost.name,
+        &session_id,
+        &operator.id,
+        content,
+    )
+    .await?
+    {
+        Some(messages) => messages,
+        None => {
+            operator_flow::run_chat_with_pending_and_attachments(
+                state.as_ref(),
+                None,
+                model_alias,
+                &ghost.name,
+                &session_id,
+                &operator.id,
+                content,
+                attachment_blocks,
+                None,
+            )
+            .await?
+        }
+    };
+
+    Ok(Json(ApiChatResponse {
+        session_id,
+        responses: messages.into_iter().map(ws_from_outbound).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::testing::{operator_with_token, send};
+    use serde_json::json;
+    use t_koma_db::{ApiTokenScope, GhostRepository, OperatorRepository};
+
+    #[tokio::test]
+    async fn test_api_rejects_missing_invalid_and_revoked_tokens() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (operator, token) =
+            operator_with_token(&db, "Alice", &[ApiTokenScope::Chat], true).await;
+        GhostRepository::create(db.pool(), &operator.id, "alpha")
+            .await
+            .unwrap();
+        let (state, _temp) = crate::state::test_app_state(db.clone()).await;
+        let uri = "/api/sessions?ghost=alpha";
+
+        let (status, body) = send(router(), &state, "GET", uri, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "API token required");
+
+        let (status, _) = send(router(), &state, "GET", uri, Some("tk_bogus"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(router(), &state, "GET", uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let issued = OperatorRepository::list_api_tokens(db.pool(), &operator.id)
+            .await
+            .unwrap();
+        OperatorRepository::revoke_api_token(db.pool(), &issued[0].id)
+            .await
+            .unwrap();
+        let (status, body) = send(router(), &state, "GET", uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid or revoked API token");
+    }
+
+    #[tokio::test]
+    async fn test_api_requires_chat_scope_and_approved_operator() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (_, admin_only) =
+            operator_with_token(&db, "Admin", &[ApiTokenScope::Admin], true).await;
+        let (_, pending) = operator_with_token(&db, "Pending", &[ApiTokenScope::Chat], false).await;
+        let (denied_operator, denied) =
+            operator_with_token(&db, "Denied", &[ApiTokenScope::Chat], false).await;
+        OperatorRepository::deny(db.pool(), &denied_operator.id)
+            .await
+            .unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        let chat = json!({ "ghost": "alpha", "content": "hi" });
+
+        let (status, body) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/chat",
+            Some(&admin_only),
+            Some(chat.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "API token lacks the chat scope");
+
+        for token in [&pending, &denied] {
+            let (status, body) = send(
+                router(),
+                &state,
+                "POST",
+                "/api/chat",
+                Some(token),
+                Some(chat.clone()),
+            )
+            .await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert!(body["error"].is_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_hides_other_operators_ghosts() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (owner, _) = operator_with_token(&db, "Owner", &[ApiTokenScope::Chat], true).await;
+        let (_, other) = operator_with_token(&db, "Other", &[ApiTokenScope::Chat], true).await;
+        GhostRepository::create(db.pool(), &owner.id, "alpha")
+            .await
+            .unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+
+        let (foreign, _) = send(
+            router(),
+            &state,
+            "GET",
+            "/api/sessions?ghost=alpha",
+            Some(&other),
+            None,
+        )
+        .await;
+        let (unknown, _) = send(
+            router(),
+            &state,
+            "GET",
+            "/api/sessions?ghost=nobody",
+            Some(&other),
+            None,
+        )
+        .await;
+        assert_eq!(foreign, StatusCode::NOT_FOUND);
+        assert_eq!(unknown, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/chat",
+            Some(&other),
+            Some(json!({ "ghost": "alpha", "content": "hi" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_api_session_routes_return_ws_shapes() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (operator, token) =
+            operator_with_token(&db, "Alice", &[ApiTokenScope::Chat], true).await;
+        GhostRepository::create(db.pool(), &operator.id, "alpha")
+            .await
+            .unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+
+        let (status, created) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/sessions",
+            Some(&token),
+            Some(json!({ "ghost": "alpha" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["type"], "session_created");
+        let session_id = created["session_id"].as_str().unwrap();
+
+        let (status, listed) = send(
+            router(),
+            &state,
+            "GET",
+            "/api/sessions?ghost=alpha",
+            Some(&token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed["type"], "session_list");
+        let sessions = listed["sessions"].as_array().unwrap();
+        assert!(sessions.iter().any(|session| session["id"] == session_id));
+        assert!(sessions[0]["message_count"].is_i64());
+
+        let (status, _) = send(
+            router(),
+            &state,
+            "POST",
+            "/api/chat",
+            Some(&token),
+            Some(json!({ "ghost": "alpha", "session_id": "sess_missing", "content": "hi" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,220 @@
+//! REST API under `/api`: the `{"error": ...}` response type and bearer
+//! token authentication shared by every route. The chat routes live in
+//! [`chat`]; the admin routes in [`admin`].
+
+pub mod admin;
+pub mod chat;
+
+use axum::{
+    Json,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+use crate::content::ids;
+use crate::gateway_message;
+use crate::server::bearer_token;
+use crate::session::ChatError;
+use crate::state::AppState;
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    gateway_message::from_content(id, None, vars).text_fallback
+}
+
+/// Error response of the REST API: `{"error": "..."}`.
+pub(crate) struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn internal(context: &str, err: impl std::fmt::Display) -> Self {
+        error!("{}: {}", context, err);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, context)
+    }
+
+    /// Map a failed admin write: 404/409/400 for caller mistakes, 500 otherwise.
+    pub(crate) fn db(context: &str, err: t_koma_db::DbError) -> Self {
+        use t_koma_db::DbError;
+        match err {
+            DbError::OperatorNotFound(_)
+            | DbError::GhostNotFound(_)
+            | DbError::InterfaceNotFound(_) => Self::new(StatusCode::NOT_FOUND, err.to_string()),
+            DbError::GhostNameTaken(_)
+            | DbError::InterfaceAlreadyExists(_)
+            | DbError::InvalidTransition { .. } => Self::new(StatusCode::CONFLICT, err.to_string()),
+            DbError::InvalidGhostName(_) => Self::new(StatusCode::BAD_REQUEST, err.to_string()),
+            err => Self::internal(context, err),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(serde_json::json!({ "error": self.message })),
+        )
+            .into_response()
+    }
+}
+
+impl From<ChatError> for ApiError {
+    fn from(err: ChatError) -> Self {
+        match err {
+            ChatError::OverBudget(_) => Self::new(StatusCode::TOO_MANY_REQUESTS, err.to_string()),
+            err => {
+                error!("Provider API error: {}", err);
+                Self::new(StatusCode::BAD_GATEWAY, format!("Chat error: {}", err))
+            }
+        }
+    }
+}
+
+/// Authenticate a REST API request.
+///
+/// Unlike `/ws`, the REST API has no interface flow: every request needs a
+/// bearer API token with the `chat` scope, bound to an approved operator.
+pub(crate) async fn authenticate_api(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<t_koma_db::Operator, ApiError> {
+    authenticate_api_scope(state, headers, t_koma_db::ApiTokenScope::Chat).await
+}
+
+pub(crate) async fn authenticate_api_scope(
+    state: &AppState,
+    headers: &HeaderMap,
+    scope: t_koma_db::ApiTokenScope,
+) -> Result<t_koma_db::Operator, ApiError> {
+    let token = bearer_token(headers)
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "API token required"))?;
+    let (operator, token) =
+        t_koma_db::OperatorRepository::authenticate_api_token(state.koma_db.pool(), token)
+            .await
+            .map_err(|e| ApiError::internal("API token lookup failed", e))?
+            .ok_or_else(|| {
+                ApiError::new(StatusCode::UNAUTHORIZED, "invalid or revoked API token")
+            })?;
+    if !token.has_scope(scope) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("API token lacks the {} scope", scope),
+        ));
+    }
+    match operator.status {
+        t_koma_db::OperatorStatus::Approved => Ok(operator),
+        t_koma_db::OperatorStatus::Pending => Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            render_message(ids::ACCESS_PENDING, &[]),
+        )),
+        t_koma_db::OperatorStatus::Denied => Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            render_message(ids::ACCESS_DENIED, &[]),
+        )),
+    }
+}
+
+/// Refuse REST writes on a read-only replica.
+pub(crate) fn ensure_writable(state: &AppState) -> Result<(), ApiError> {
+    if state.koma_db.is_read_only() {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "This gateway is a read-only replica; send writes to the primary",
+        ));
+    }
+    Ok(())
+}
+
+/// Helpers for router-level tests of the REST API.
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::sync::Arc;
+
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use t_koma_db::{
+        ApiTokenScope, KomaDbPool, Operator, OperatorAccessLevel, OperatorRepository, Platform,
+    };
+    use tower::ServiceExt;
+
+    use crate::state::AppState;
+
+    /// A new operator, approved unless `approve` is false, and a token for it
+    /// with `scopes`. Operators with an `admin` token are PUPPET MASTERS.
+    pub(crate) async fn operator_with_token(
+        db: &KomaDbPool,
+        name: &str,
+        scopes: &[ApiTokenScope],
+        approve: bool,
+    ) -> (Operator, String) {
+        let pool = db.pool();
+        let access_level = if scopes.contains(&ApiTokenScope::Admin) {
+            OperatorAccessLevel::PuppetMaster
+        } else {
+            OperatorAccessLevel::Standard
+        };
+        let mut operator = OperatorRepository::create_new(pool, name, Platform::Api, access_level)
+            .await
+            .unwrap();
+        if approve {
+            operator = OperatorRepository::approve(pool, &operator.id)
+                .await
+                .unwrap();
+        }
+        let (_, secret) =
+            OperatorRepository::issue_api_token(pool, &operator.id, "test", scopes, None)
+                .await
+                .unwrap();
+        (operator, secret)
+    }
+
+    /// Send one request through `router` and return the status and JSON body
+    /// (`Null` when empty).
+    pub(crate) async fn send(
+        router: Router<Arc<AppState>>,
+        state: &Arc<AppState>,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        let response = router
+            .with_state(Arc::clone(state))
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json = if bytes.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap()
+        };
+        (status, json)
+    }
+}
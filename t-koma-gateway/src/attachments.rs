@@ -0,0 +1,230 @@
+//! Operator file attachments.
+//!
+//! Transports hand incoming files to [`store_attachment`], which writes them
+//! to `downloads/` in the ghost workspace and returns the content block for the
+//! operator message: [`ContentBlock::Image`] for images (sent to the model as
+//! vision input), [`ContentBlock::File`] for everything else. Audio files are
+//! transcribed afterwards by [`crate::transcription`].
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use t_koma_core::ChatAttachment;
+use t_koma_db::ContentBlock;
+use tracing::{error, warn};
+
+/// Largest attachment accepted from any transport.
+pub const MAX_ATTACHMENT_SIZE: usize = 25 * 1024 * 1024; // 25 MB
+
+const IMAGE_MIME_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "image/svg+xml",
+];
+
+pub fn mime_type_for_filename(filename: &str) -> String {
+    let ext = filename.rsplit('.').next().unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "webm" => "audio/webm",
+        "flac" => "audio/flac",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "txt" | "md" => "text/plain",
+        _ => "application/octet-stream",
+    }
+    .to_string()
+}
+
+pub fn is_image_mime(mime: &str) -> bool {
+    IMAGE_MIME_TYPES.contains(&mime)
+}
+
+pub fn is_audio_mime(mime: &str) -> bool {
+    mime.starts_with("audio/")
+}
+
+/// Whether a file is a plain-text document that can be saved as a reference.
+pub fn is_text_document(filename: &str) -> bool {
+    let mime = mime_type_for_filename(filename);
+    mime.starts_with("text/") || mime == "application/json"
+}
+
+/// Create (if needed) and return the workspace `downloads/` directory.
+pub async fn downloads_dir(workspace_path: &Path) -> std::io::Result<PathBuf> {
+    let dir = workspace_path.join("downloads");
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
+
+/// Write an attachment to `download_dir` and build its content block.
+///
+/// Only the last component of `filename` is used, so client-supplied names
+/// cannot escape the directory. Returns `None` (after logging why) when the
+/// file is too large or cannot be written.
+pub async fn store_attachment(
+    download_dir: &Path,
+    filename: &str,
+    mime_type: Option<String>,
+    bytes: &[u8],
+) -> Option<ContentBlock> {
+    let filename = Path::new(filename)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("attachment")
+        .to_string();
+    if bytes.len() > MAX_ATTACHMENT_SIZE {
+        warn!(
+            "Attachment {} exceeds {}MB limit ({} bytes), skipping",
+            filename,
+            MAX_ATTACHMENT_SIZE / (1024 * 1024),
+            bytes.len()
+        );
+        return None;
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let dest_path = download_dir.join(format!("{}_{}", timestamp, filename));
+    if let Err(e) = tokio::fs::write(&dest_path, bytes).await {
+        error!("Failed to write attachment {}: {}", filename, e);
+        return None;
+    }
+
+    let mime = mime_type.unwrap_or_else(|| mime_type_for_filename(&filename));
+    let path = dest_path.to_string_lossy().to_string();
+    Some(if is_image_mime(&mime) {
+        ContentBlock::Image {
+            path,
+            mime_type: mime,
+            filename,
+        }
+    } else {
+        ContentBlock::File {
+            path,
+            filename,
+            size: bytes.len() as u64,
+        }
+    })
+}
+
+/// Convert WebSocket chat attachments to content blocks.
+///
+/// Inline (`data`) attachments are stored in the workspace; images given by
+/// `url` are kept as references and fetched by the provider. Invalid
+/// attachments are logged and skipped.
+pub async fn ws_attachments_to_content_blocks(
+    attachments: &[ChatAttachment],
+    workspace_path: &Path,
+) -> Vec<ContentBlock> {
+    let mut blocks = Vec::new();
+    for attachment in attachments {
+        let mime = attachment
+            .mime_type
+            .clone()
+            .unwrap_or_else(|| mime_type_for_filename(&attachment.filename));
+        match (&attachment.data, &attachment.url) {
+            (Some(data), None) => {
+                let bytes = match base64::engine::general_purpose::STANDARD.decode(data) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        warn!(
+                            "Attachment {} is not valid base64: {}",
+                            attachment.filename, e
+                        );
+                        continue;
+                    }
+                };
+                let download_dir = match downloads_dir(workspace_path).await {
+                    Ok(dir) => dir,
+                    Err(e) => {
+                        error!("Failed to create downloads dir: {}", e);
+                        return blocks;
+                    }
+                };
+                if let Some(block) =
+                    store_attachment(&download_dir, &attachment.filename, Some(mime), &bytes).await
+                {
+                    blocks.push(block);
+                }
+            }
+            (None, Some(url))
+                if is_image_mime(&mime)
+                    && (url.starts_with("https://") || url.starts_with("http://")) =>
+            {
+                blocks.push(ContentBlock::Image {
+                    path: url.clone(),
+                    mime_type: mime,
+                    filename: attachment.filename.clone(),
+                });
+            }
+            _ => warn!(
+                "Skipping attachment {}: expected base64 data or an http(s) image URL",
+                attachment.filename
+            ),
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(filename: &str, data: Option<&str>, url: Option<&str>) -> ChatAttachment {
+        ChatAttachment {
+            filename: filename.to_string(),
+            mime_type: None,
+            data: data.map(str::to_string),
+            url: url.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ws_attachments_to_content_blocks() {
+        let workspace = tempfile::tempdir().unwrap();
+        let attachments = vec![
+            attachment("../../escape.png", Some("aGVsbG8="), None),
+            attachment("notes.txt", Some("aGVsbG8="), None),
+            attachment("remote.jpg", None, Some("https://example.com/remote.jpg")),
+            attachment("remote.pdf", None, Some("https://example.com/remote.pdf")),
+            attachment("broken.png", Some("not base64!"), None),
+        ];
+
+        let blocks = ws_attachments_to_content_blocks(&attachments, workspace.path()).await;
+        assert_eq!(blocks.len(), 3);
+
+        let ContentBlock::Image {
+            path,
+            mime_type,
+            filename,
+        } = &blocks[0]
+        else {
+            panic!("expected image block");
+        };
+        assert_eq!(mime_type, "image/png");
+        assert_eq!(filename, "escape.png");
+        let path = Path::new(path);
+        assert_eq!(path.parent().unwrap(), workspace.path().join("downloads"));
+        assert_eq!(std::fs::read(path).unwrap(), b"hello");
+
+        assert!(matches!(
+            &blocks[1],
+            ContentBlock::File { filename, size: 5, .. } if filename == "notes.txt"
+        ));
+        assert!(matches!(
+            &blocks[2],
+            ContentBlock::Image { path, .. } if path == "https://example.com/remote.jpg"
+        ));
+    }
+}
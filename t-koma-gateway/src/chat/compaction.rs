@@ -3,17 +3,21 @@
 //! Two-phase approach:
 //! - Phase 1 (observation masking): Replace verbose `ToolResult` blocks outside
 //!   the "keep window" with compact placeholders. Free, no LLM call.
-//! - Phase 2 (LLM summarization): Summarize the oldest messages into a single
-//!   summary block when masking alone isn't sufficient.
+//! - Phase 2 (LLM summarization): Fold the oldest messages into a structured
+//!   [`CompactionSummary`] (decisions, open tasks, facts) when masking alone
+//!   isn't sufficient. The summary is pinned in the system prompt
+//!   ([`summary_block`]) and each new summary merges the previous one.
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
 use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
 use crate::chat::token_budget::{compute_budget, estimate_history_tokens};
 use crate::prompt::render::{SystemBlock, build_simple_system_prompt};
-use crate::providers::provider::{Provider, ProviderError, extract_all_text};
+use crate::providers::provider::{Provider, ProviderError, ResponseFormat};
+use crate::providers::structured::send_structured_with_repair;
 use crate::tools::Tool;
 
 /// Configuration for compaction behavior.
@@ -37,6 +41,16 @@ impl Default for CompactionConfig {
     }
 }
 
+impl From<&t_koma_core::CompactionSettings> for CompactionConfig {
+    fn from(settings: &t_koma_core::CompactionSettings) -> Self {
+        Self {
+            threshold: settings.threshold,
+            keep_window: settings.keep_window,
+            mask_preview_chars: settings.mask_preview_chars,
+        }
+    }
+}
+
 /// Result of a compaction pass.
 #[derive(Debug)]
 pub struct CompactedHistory {
@@ -46,12 +60,81 @@ pub struct CompactedHistory {
     pub masked: bool,
     /// Whether Phase 2 (LLM summarization) was applied.
     pub summarized: bool,
-    /// The summary text if Phase 2 ran (to persist in DB).
-    pub summary: Option<String>,
+    /// The new summary if Phase 2 ran (to persist in DB).
+    pub summary: Option<CompactionSummary>,
     /// Number of messages consumed by the summary.
     pub compacted_count: usize,
 }
 
+/// What a long session has established so far, as written by the summarizer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionSummary {
+    /// Decisions made and preferences stated.
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    /// Work that was started or requested and isn't finished.
+    #[serde(default)]
+    pub open_tasks: Vec<String>,
+    /// Names, results and other context later messages rely on.
+    #[serde(default)]
+    pub facts: Vec<String>,
+}
+
+impl CompactionSummary {
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty() && self.open_tasks.is_empty() && self.facts.is_empty()
+    }
+
+    /// Render as markdown sections, the form stored on the session and
+    /// shown to the model.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (heading, items) in [
+            ("Decisions", &self.decisions),
+            ("Open tasks", &self.open_tasks),
+            ("Facts", &self.facts),
+        ] {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("### {heading}\n"));
+            if items.is_empty() {
+                out.push_str("- (none)\n");
+            }
+            for item in items {
+                out.push_str(&format!("- {}\n", item.trim()));
+            }
+        }
+        out
+    }
+}
+
+/// Schema the summarizer's reply must match.
+fn summary_format() -> ResponseFormat {
+    let list = serde_json::json!({"type": "array", "items": {"type": "string"}});
+    ResponseFormat {
+        name: "compaction_summary".to_string(),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "decisions": list,
+                "open_tasks": list,
+                "facts": list
+            },
+            "required": ["decisions", "open_tasks", "facts"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+/// The system block that pins a session's compaction summary in the prompt.
+pub fn summary_block(summary: &str) -> SystemBlock {
+    SystemBlock::new(format!(
+        "## Conversation summary\n\nEarlier messages of this session were compacted. \
+         This summary replaces them:\n\n{summary}"
+    ))
+}
+
 /// Build an index mapping `tool_use_id` → `tool_name` from the message history.
 fn build_tool_name_index(messages: &[ChatMessage]) -> HashMap<String, String> {
     let mut index = HashMap::new();
@@ -195,6 +278,8 @@ fn render_messages_for_summary(messages: &[ChatMessage]) -> String {
                         "[{role}] (attached file: {filename}, {size} bytes)\n\n"
                     ));
                 }
+                // Reasoning is not part of the conversation being summarized.
+                ChatContentBlock::Thinking { .. } => {}
             }
         }
     }
@@ -207,89 +292,89 @@ fn load_compaction_prompt() -> String {
     crate::content::prompt_text(crate::content::ids::PROMPT_COMPACTION, None, &[]).unwrap_or_else(
         |e| {
             warn!("Failed to load compaction prompt: {e}, using fallback");
-            "Summarize the following conversation in 200-400 words. \
-         Preserve key decisions, context, important tool results, and user preferences. \
-         Output only the summary."
+            "Summarize the following conversation as a JSON object \
+         {\"decisions\": [...], \"open_tasks\": [...], \"facts\": [...]} of short \
+         bullet strings, merging the previous summary if one is given."
                 .to_string()
         },
     )
 }
 
+/// Input for the summarizer: the summary being extended, then the messages.
+fn render_summary_input(previous_summary: Option<&str>, messages: &[ChatMessage]) -> String {
+    let conversation = render_messages_for_summary(messages);
+    match previous_summary {
+        Some(previous) => format!(
+            "<previous_summary>\n{previous}\n</previous_summary>\n\n\
+             <messages>\n{conversation}</messages>"
+        ),
+        None => format!("<messages>\n{conversation}</messages>"),
+    }
+}
+
 /// Phase 2: Summarize older messages via an LLM call.
 ///
-/// Splits messages at `keep_window`, sends the older portion to the LLM for
-/// summarization, and returns the summary + the kept messages.
+/// Splits messages at `keep_window`, asks `provider` for a structured summary
+/// of the older portion merged with `previous_summary`, and returns the
+/// summary + the kept messages. The summary is not part of the returned
+/// messages; callers pin it with [`summary_block`].
 pub async fn summarize_and_compact(
     messages: &[ChatMessage],
     keep_window: usize,
+    previous_summary: Option<&str>,
     provider: &dyn Provider,
 ) -> Result<CompactedHistory, ProviderError> {
     let split = messages.len().saturating_sub(keep_window);
+    let unchanged = || CompactedHistory {
+        messages: messages.to_vec(),
+        masked: false,
+        summarized: false,
+        summary: None,
+        compacted_count: 0,
+    };
     if split == 0 {
-        return Ok(CompactedHistory {
-            messages: messages.to_vec(),
-            masked: false,
-            summarized: false,
-            summary: None,
-            compacted_count: 0,
-        });
+        return Ok(unchanged());
     }
 
     let (to_summarize, to_keep) = messages.split_at(split);
 
-    let conversation_text = render_messages_for_summary(to_summarize);
-    let system_prompt = load_compaction_prompt();
-    let system_blocks = build_simple_system_prompt(system_prompt);
+    let input = render_summary_input(previous_summary, to_summarize);
+    let system_blocks = build_simple_system_prompt(load_compaction_prompt());
 
     debug!(
         messages_to_summarize = to_summarize.len(),
         messages_to_keep = to_keep.len(),
-        chars = conversation_text.len(),
+        chars = input.len(),
         "Phase 2: summarizing older messages"
     );
 
-    let response = provider
-        .send_conversation(
-            Some(system_blocks),
-            vec![],
-            vec![],
-            Some(&conversation_text),
-            None,
-            None,
-        )
-        .await?;
-
-    let summary = extract_all_text(&response);
-    if summary.is_empty() {
-        warn!("LLM returned empty summary — skipping Phase 2");
-        return Ok(CompactedHistory {
-            messages: messages.to_vec(),
-            masked: false,
-            summarized: false,
-            summary: None,
-            compacted_count: 0,
-        });
-    }
-
-    // Build the compacted history: summary as a synthetic user message + kept messages
-    let mut compacted = Vec::with_capacity(1 + to_keep.len());
-    compacted.push(ChatMessage {
-        role: ChatRole::User,
-        content: vec![ChatContentBlock::Text {
-            text: format!("[Conversation summary — earlier messages compacted]\n\n{summary}"),
-            cache_control: None,
-        }],
-    });
-    compacted.extend_from_slice(to_keep);
+    let reply =
+        send_structured_with_repair(provider, Some(system_blocks), &input, &summary_format())
+            .await?;
+    let summary = match reply.value.and_then(|value| {
+        serde_json::from_value::<CompactionSummary>(value).map_err(|e| e.to_string())
+    }) {
+        Ok(summary) if !summary.is_empty() => summary,
+        Ok(_) => {
+            warn!("LLM returned an empty summary — skipping Phase 2");
+            return Ok(unchanged());
+        }
+        Err(e) => {
+            warn!(error = %e, "LLM returned an unusable summary — skipping Phase 2");
+            return Ok(unchanged());
+        }
+    };
 
     debug!(
         compacted_count = to_summarize.len(),
-        summary_len = summary.len(),
+        decisions = summary.decisions.len(),
+        open_tasks = summary.open_tasks.len(),
+        facts = summary.facts.len(),
         "Phase 2 complete"
     );
 
     Ok(CompactedHistory {
-        messages: compacted,
+        messages: to_keep.to_vec(),
         masked: false,
         summarized: true,
         summary: Some(summary),
@@ -303,15 +388,25 @@ pub async fn summarize_and_compact(
 /// below threshold, that's sufficient. Otherwise Phase 2 (LLM summarization)
 /// is applied on top of the masked messages.
 ///
+/// `observed_context_tokens` is the provider-reported context size after the
+/// last stored exchange (see `MessageUsage::context_tokens`). When it exceeds
+/// the heuristic estimate, estimates are scaled up to match.
+///
+/// `system_blocks` should already hold the pinned summary (`previous_summary`),
+/// which Phase 2 folds into the new one. `summarizer` writes the summary.
+///
 /// Returns `None` if no compaction was needed.
+#[allow(clippy::too_many_arguments)]
 pub async fn compact_if_needed(
     model: &str,
     context_window_override: Option<u32>,
     system_blocks: &[SystemBlock],
     tools: &[&dyn Tool],
     messages: &[ChatMessage],
+    observed_context_tokens: Option<u32>,
+    previous_summary: Option<&str>,
     config: &CompactionConfig,
-    provider: &dyn Provider,
+    summarizer: &dyn Provider,
 ) -> Option<CompactedHistory> {
     let budget = compute_budget(
         model,
@@ -321,13 +416,16 @@ pub async fn compact_if_needed(
         messages,
         config.threshold,
     );
+    let calibration = usage_calibration(observed_context_tokens, budget.total_estimated);
+    let limit = budget.context_window as f64 * config.threshold as f64;
 
-    if !budget.needs_compaction {
+    if budget.total_estimated as f64 * calibration <= limit {
         return None;
     }
 
     debug!(
         total = budget.total_estimated,
+        observed = observed_context_tokens,
         window = budget.context_window,
         history = budget.history_tokens,
         "Compaction triggered — applying observation masking"
@@ -346,8 +444,7 @@ pub async fn compact_if_needed(
 
     // Check if masking alone is sufficient
     let total_after_mask = budget.system_tokens + budget.tool_tokens + masked_tokens;
-    let still_over =
-        total_after_mask as f64 > (budget.context_window as f64 * config.threshold as f64);
+    let still_over = total_after_mask as f64 * calibration > limit;
 
     if !still_over {
         return Some(CompactedHistory {
@@ -362,7 +459,7 @@ pub async fn compact_if_needed(
     // Phase 2: LLM summarization on top of masked messages
     debug!("Masking insufficient — proceeding to Phase 2 (LLM summarization)");
 
-    match summarize_and_compact(&masked, config.keep_window, provider).await {
+    match summarize_and_compact(&masked, config.keep_window, previous_summary, summarizer).await {
         Ok(mut result) => {
             result.masked = true; // Phase 1 was also applied
             Some(result)
@@ -380,11 +477,27 @@ pub async fn compact_if_needed(
     }
 }
 
+/// Factor (>= 1.0) by which the heuristic undercounts real usage.
+fn usage_calibration(observed: Option<u32>, estimated: u32) -> f64 {
+    observed
+        .map(|observed| f64::from(observed) / f64::from(estimated.max(1)))
+        .filter(|ratio| *ratio > 1.0)
+        .unwrap_or(1.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_usage_calibration_only_scales_up() {
+        assert_eq!(usage_calibration(None, 1000), 1.0);
+        assert_eq!(usage_calibration(Some(500), 1000), 1.0);
+        assert_eq!(usage_calibration(Some(1500), 1000), 1.5);
+        assert_eq!(usage_calibration(Some(10), 0), 10.0);
+    }
+
     fn user_text(text: &str) -> ChatMessage {
         ChatMessage {
             role: ChatRole::User,
@@ -610,4 +723,34 @@ mod tests {
         // Should not contain the full 1000-char result
         assert!(rendered.len() < 1000);
     }
+
+    #[test]
+    fn test_summary_render_lists_every_section() {
+        let summary = CompactionSummary {
+            decisions: vec!["Use SQLite for the cache".to_string()],
+            open_tasks: vec![],
+            facts: vec!["Repo lives at ~/src/app ".to_string()],
+        };
+        let rendered = summary.render();
+        assert_eq!(
+            rendered,
+            "### Decisions\n- Use SQLite for the cache\n\n\
+             ### Open tasks\n- (none)\n\n\
+             ### Facts\n- Repo lives at ~/src/app\n"
+        );
+        assert!(summary_block(&rendered).text.contains("### Open tasks"));
+        assert!(CompactionSummary::default().is_empty());
+    }
+
+    #[test]
+    fn test_summary_input_carries_previous_summary() {
+        let messages = vec![user_text("Let's ship on Friday")];
+        let fresh = render_summary_input(None, &messages);
+        assert!(!fresh.contains("<previous_summary>"));
+        assert!(fresh.contains("[Operator] Let's ship on Friday"));
+
+        let merged = render_summary_input(Some("### Facts\n- Deadline moved"), &messages);
+        assert!(merged.starts_with("<previous_summary>\n### Facts\n- Deadline moved"));
+        assert!(merged.contains("<messages>\n[Operator] Let's ship on Friday"));
+    }
 }
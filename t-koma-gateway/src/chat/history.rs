@@ -6,6 +6,7 @@ use serde_json::Value;
 use t_koma_db::{ContentBlock, Message, MessageRole, TranscriptEntry};
 
 use crate::prompt::CacheControl;
+use crate::providers::provider::ImageSource;
 
 /// Role in provider-neutral chat history.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -25,7 +26,7 @@ pub enum ChatContentBlock {
         cache_control: Option<CacheControl>,
     },
     Image {
-        /// Absolute path to the image file on disk.
+        /// Absolute path to the image file on disk, or an `http(s)`/`data:` URL.
         path: String,
         /// MIME type (e.g. "image/png").
         mime_type: String,
@@ -56,6 +57,13 @@ pub enum ChatContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    /// Earlier model reasoning; only providers that accept it back send it.
+    Thinking {
+        thinking: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+        redacted: bool,
+    },
 }
 
 /// Provider-neutral chat message.
@@ -183,6 +191,15 @@ fn convert_content_block(
             filename: filename.clone(),
             size: *size,
         },
+        ContentBlock::Thinking {
+            thinking,
+            signature,
+            redacted,
+        } => ChatContentBlock::Thinking {
+            thinking: thinking.clone(),
+            signature: signature.clone(),
+            redacted: *redacted,
+        },
     }
 }
 
@@ -259,6 +276,18 @@ pub async fn load_image_base64(path: &str) -> Option<(String, String)> {
     }
 }
 
+/// Resolve an image block's `path` to a provider image payload.
+///
+/// `http(s)` and `data:` references pass through unchanged; anything else is
+/// read from disk via [`load_image_base64`].
+pub async fn load_image_source(path: &str) -> Option<ImageSource> {
+    if let Some(source) = ImageSource::from_reference(path) {
+        return Some(source);
+    }
+    let (data, media_type) = load_image_base64(path).await?;
+    Some(ImageSource::Base64 { media_type, data })
+}
+
 /// Resize and compress an image if it exceeds [`IMAGE_MAX_DIMENSION`].
 ///
 /// Returns `Some((bytes, mime_type))` when compression was applied,
@@ -289,7 +318,7 @@ fn compress_image(data: &[u8]) -> Option<(Vec<u8>, String)> {
     Some((buf.into_inner(), "image/jpeg".to_string()))
 }
 
-fn mime_from_path(path: &str) -> &str {
+pub(crate) fn mime_from_path(path: &str) -> &str {
     let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
     match ext.as_str() {
         "jpg" | "jpeg" => "image/jpeg",
@@ -313,6 +342,7 @@ mod tests {
             content,
             model: None,
             created_at: 0,
+            usage: None,
         }
     }
 
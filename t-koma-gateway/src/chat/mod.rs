@@ -1,6 +1,8 @@
 pub mod compaction;
 pub mod history;
+pub mod output_filter;
 pub mod prompt_cache;
+pub mod thinking;
 pub mod token_budget;
 
 pub use history::{
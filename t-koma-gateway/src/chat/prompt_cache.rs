@@ -6,7 +6,9 @@
 //! that support it (e.g. Anthropic prompt caching, OpenRouter).
 //!
 //! The cache is backed by both an in-memory map (fast path) and a DB table
-//! (survives gateway restarts within the TTL window).
+//! (survives gateway restarts within the TTL window). Every lookup is counted
+//! as a hit or miss in the DB; stored entries are evicted on a schedule (see
+//! `crate::prompt_cache_eviction`).
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -112,6 +114,11 @@ impl PromptCacheManager {
                 && entry.context_hash == context_hash
             {
                 debug!(session_id, "Prompt cache hit (in-memory)");
+                if let Err(e) =
+                    PromptCacheRepository::record_hit(pool, ghost_id, session_id, now).await
+                {
+                    warn!(session_id, error = %e, "Failed to record prompt cache hit");
+                }
                 return entry.system_blocks.clone();
             }
         }
@@ -132,6 +139,9 @@ impl PromptCacheManager {
                 warn!(session_id, error = %e, "Failed to persist prompt cache");
             }
         }
+        if let Err(e) = PromptCacheRepository::record_miss(pool, ghost_id, now).await {
+            warn!(session_id, error = %e, "Failed to record prompt cache miss");
+        }
 
         // Store in memory
         {
@@ -148,6 +158,15 @@ impl PromptCacheManager {
         let mut cache = self.cache.write().await;
         cache.remove(session_id);
     }
+
+    /// Drop in-memory entries past the TTL. Returns how many were removed.
+    pub async fn evict_expired(&self) -> usize {
+        let now = Utc::now().timestamp();
+        let mut cache = self.cache.write().await;
+        let before = cache.len();
+        cache.retain(|_, entry| entry.is_valid(now));
+        before - cache.len()
+    }
 }
 
 impl Default for PromptCacheManager {
@@ -0,0 +1,68 @@
+//! Operator-facing rendering of model thinking.
+//!
+//! Thinking blocks are always stored with the reply (see
+//! `t_koma_db::ContentBlock::Thinking`); `[thinking] display` decides how much
+//! of them the operator sees above the reply text.
+
+use t_koma_core::ThinkingDisplay;
+
+/// Longest thinking excerpt shown with [`ThinkingDisplay::Summary`].
+const THINKING_SUMMARY_CHARS: usize = 280;
+
+/// Prefix a reply with the model's thinking as a quote, per `display`.
+pub fn render_reply_with_thinking(display: ThinkingDisplay, thinking: &str, text: &str) -> String {
+    let thinking = thinking.trim();
+    let quoted = match display {
+        _ if thinking.is_empty() => return text.to_string(),
+        ThinkingDisplay::Hidden => return text.to_string(),
+        ThinkingDisplay::Summary => {
+            let mut paragraphs = thinking.split("\n\n");
+            let first = paragraphs.next().unwrap_or_default();
+            let first = first.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut excerpt: String = first.chars().take(THINKING_SUMMARY_CHARS).collect();
+            if excerpt.len() < first.len() || paragraphs.next().is_some() {
+                excerpt = excerpt.trim_end().to_string();
+                excerpt.push('…');
+            }
+            format!("> *Thinking:* {excerpt}")
+        }
+        ThinkingDisplay::Full => {
+            let lines: Vec<String> = thinking
+                .lines()
+                .map(|line| format!("> {line}").trim_end().to_string())
+                .collect();
+            format!("> *Thinking:*\n{}", lines.join("\n"))
+        }
+    };
+    format!("{quoted}\n\n{text}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_reply_with_thinking() {
+        let thinking = "First, check   the logs.\nThen restart.\n\nSecond paragraph.";
+        assert_eq!(
+            render_reply_with_thinking(ThinkingDisplay::Hidden, thinking, "Done."),
+            "Done."
+        );
+        assert_eq!(
+            render_reply_with_thinking(ThinkingDisplay::Summary, thinking, "Done."),
+            "> *Thinking:* First, check the logs. Then restart.…\n\nDone."
+        );
+        assert_eq!(
+            render_reply_with_thinking(ThinkingDisplay::Full, thinking, "Done."),
+            "> *Thinking:*\n> First, check   the logs.\n> Then restart.\n>\n> Second paragraph.\n\nDone."
+        );
+        assert_eq!(
+            render_reply_with_thinking(ThinkingDisplay::Full, "  ", "Done."),
+            "Done."
+        );
+        assert_eq!(
+            render_reply_with_thinking(ThinkingDisplay::Summary, "Short.", "Done."),
+            "> *Thinking:* Short.\n\nDone."
+        );
+    }
+}
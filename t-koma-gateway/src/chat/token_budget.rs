@@ -41,6 +41,8 @@ pub fn estimate_history_tokens(messages: &[ChatMessage]) -> u32 {
                     // Images: ~85 tokens per 256x256 tile; estimate conservatively
                     ChatContentBlock::Image { .. } => 300,
                     ChatContentBlock::File { filename, .. } => estimate_tokens(filename) + 10,
+                    // Providers drop reasoning from earlier turns.
+                    ChatContentBlock::Thinking { .. } => 0,
                 })
                 .sum::<u32>()
         })
@@ -166,6 +168,38 @@ pub fn compute_budget(
     }
 }
 
+/// Pre-flight estimate for one provider request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RequestEstimate {
+    /// Estimated prompt tokens (system + tools + history)
+    pub prompt_tokens: u32,
+    /// Context window of the model
+    pub context_window: u32,
+    /// Prompt cost in USD at the model's input price; `None` when unpriced
+    pub prompt_cost_usd: Option<f64>,
+}
+
+impl RequestEstimate {
+    /// Estimate from a computed budget and the model's pricing, if known.
+    pub fn new(budget: &TokenBudget, pricing: Option<&t_koma_db::ModelPricing>) -> Self {
+        Self {
+            prompt_tokens: budget.total_estimated,
+            context_window: budget.context_window,
+            prompt_cost_usd: pricing.map(|pricing| {
+                pricing.cost(&t_koma_db::TokenUsage {
+                    input_tokens: budget.total_estimated,
+                    ..Default::default()
+                })
+            }),
+        }
+    }
+
+    /// Whether the prompt alone does not fit the context window.
+    pub fn exceeds_context(&self) -> bool {
+        self.prompt_tokens > self.context_window
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,6 +309,37 @@ mod tests {
         assert_eq!(budget.context_window, 50_000);
     }
 
+    #[test]
+    fn test_request_estimate() {
+        let blocks = vec![SystemBlock::new("System")];
+        let history = vec![ChatMessage {
+            role: crate::chat::ChatRole::User,
+            content: vec![ChatContentBlock::Text {
+                text: "word ".repeat(2_000),
+                cache_control: None,
+            }],
+        }];
+        let budget = compute_budget("gpt-4o", Some(1_000), &blocks, &[], &history, 0.85);
+        let pricing = t_koma_db::ModelPricing {
+            model: "gpt-4o".to_string(),
+            input_per_mtok: 2.5,
+            ..Default::default()
+        };
+
+        let estimate = RequestEstimate::new(&budget, Some(&pricing));
+        assert_eq!(estimate.prompt_tokens, budget.total_estimated);
+        assert!(estimate.exceeds_context());
+        let cost = estimate.prompt_cost_usd.unwrap();
+        assert!((cost - f64::from(budget.total_estimated) * 2.5e-6).abs() < 1e-12);
+
+        let unpriced = RequestEstimate::new(
+            &compute_budget("gpt-4o", None, &blocks, &[], &[], 0.85),
+            None,
+        );
+        assert!(!unpriced.exceeds_context());
+        assert_eq!(unpriced.prompt_cost_usd, None);
+    }
+
     #[test]
     fn test_estimate_system_tokens() {
         let blocks = vec![
@@ -1,15 +1,30 @@
 //! Per-model circuit breaker for multi-model fallback chains.
 //!
 //! When a provider returns a retryable error (rate limit or server error),
-//! the circuit breaker puts that model on cooldown so subsequent requests
-//! skip it and try the next model in the chain.
+//! the breaker for that model alias opens: subsequent requests skip it and try
+//! the next model in the chain. Once the cooldown elapses the breaker is
+//! half-open and the next request probes the model; success closes it, another
+//! failure reopens it with a longer cooldown. [`start_half_open_timer`] flips
+//! expired breakers to half-open in the background so `/health` and the logs
+//! reflect it without waiting for traffic.
 
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::state::{AppState, LogEntry};
+
+/// Seconds between half-open timer ticks.
+const HALF_OPEN_TICK_SECONDS: u64 = 10;
+/// Repeated failures double the cooldown, up to this many times.
+const MAX_COOLDOWN_DOUBLINGS: u32 = 2;
+
 /// Why a model was placed on cooldown.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CooldownReason {
     /// HTTP 429 — back off for a long time.
     RateLimited,
@@ -26,78 +41,189 @@ impl CooldownReason {
     }
 }
 
-struct CooldownEntry {
+/// Breaker position for one model alias.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Healthy; requests flow normally.
+    Closed,
+    /// Cooling down; the model is skipped.
+    Open,
+    /// Cooldown elapsed; the next request probes the model.
+    HalfOpen,
+}
+
+/// Point-in-time view of one model's breaker (served by `/health`).
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerSnapshot {
+    pub alias: String,
+    pub state: BreakerState,
+    /// Failures since the last success.
+    pub consecutive_failures: u32,
+    /// Failures since the gateway started.
+    pub total_failures: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reason: Option<CooldownReason>,
+    /// Seconds until an open breaker half-opens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub half_open_in_secs: Option<u64>,
+}
+
+struct BreakerEntry {
+    state: BreakerState,
     available_at: Instant,
-    reason: CooldownReason,
+    reason: Option<CooldownReason>,
+    consecutive_failures: u32,
+    total_failures: u64,
 }
 
-/// Shared, lock-based circuit breaker tracking per-model cooldowns.
+impl BreakerEntry {
+    /// State with an elapsed cooldown read as half-open, even before the
+    /// timer has flipped it.
+    fn effective_state(&self, now: Instant) -> BreakerState {
+        match self.state {
+            BreakerState::Open if now >= self.available_at => BreakerState::HalfOpen,
+            state => state,
+        }
+    }
+}
+
+/// Shared, lock-based circuit breaker tracking per-model state.
 ///
-/// Thread-safe via `RwLock`; contention is low because writes only happen
-/// on failures and reads are fast.
+/// Thread-safe via `RwLock`; contention is low because writes are short and
+/// reads are fast.
 pub struct CircuitBreaker {
-    states: RwLock<HashMap<String, CooldownEntry>>,
+    states: RwLock<HashMap<String, BreakerEntry>>,
 }
 
 impl CircuitBreaker {
-    /// Create a new circuit breaker with no models on cooldown.
+    /// Create a new circuit breaker with every model closed.
     pub fn new() -> Self {
         Self {
             states: RwLock::new(HashMap::new()),
         }
     }
 
-    /// Whether `alias` is currently available (not on cooldown or cooldown expired).
+    /// Whether `alias` may be used (breaker closed or half-open).
     pub fn is_available(&self, alias: &str) -> bool {
+        self.state(alias) != BreakerState::Open
+    }
+
+    /// Current breaker state for `alias`.
+    pub fn state(&self, alias: &str) -> BreakerState {
         let states = self.states.read().expect("CircuitBreaker lock poisoned");
-        match states.get(alias) {
-            None => true,
-            Some(entry) => Instant::now() >= entry.available_at,
-        }
+        states.get(alias).map_or(BreakerState::Closed, |entry| {
+            entry.effective_state(Instant::now())
+        })
     }
 
-    /// Record a failure for `alias`, placing it on cooldown.
+    /// Record a failure for `alias`, opening its breaker.
+    ///
+    /// Each consecutive failure (e.g. a failed half-open probe) doubles the
+    /// cooldown, up to four times the base duration for `reason`.
     pub fn record_failure(&self, alias: &str, reason: CooldownReason) {
         let mut states = self.states.write().expect("CircuitBreaker lock poisoned");
-        states.insert(
-            alias.to_string(),
-            CooldownEntry {
-                available_at: Instant::now() + reason.cooldown_duration(),
-                reason,
-            },
-        );
+        let entry = states
+            .entry(alias.to_string())
+            .or_insert_with(|| BreakerEntry {
+                state: BreakerState::Closed,
+                available_at: Instant::now(),
+                reason: None,
+                consecutive_failures: 0,
+                total_failures: 0,
+            });
+        entry.consecutive_failures += 1;
+        entry.total_failures += 1;
+        let doublings = (entry.consecutive_failures - 1).min(MAX_COOLDOWN_DOUBLINGS);
+        entry.state = BreakerState::Open;
+        entry.available_at = Instant::now() + reason.cooldown_duration() * 2u32.pow(doublings);
+        entry.reason = Some(reason);
     }
 
-    /// Record a success for `alias`, clearing any cooldown.
+    /// Record a success for `alias`, closing its breaker.
     pub fn record_success(&self, alias: &str) {
         let mut states = self.states.write().expect("CircuitBreaker lock poisoned");
-        states.remove(alias);
+        if let Some(entry) = states.get_mut(alias) {
+            entry.state = BreakerState::Closed;
+            entry.reason = None;
+            entry.consecutive_failures = 0;
+        }
     }
 
-    /// Return the first available alias from `aliases`, or `None` if all are on cooldown.
+    /// Return the first available alias from `aliases`, or `None` if all are open.
     pub fn first_available<'a>(&self, aliases: &'a [String]) -> Option<&'a str> {
         let states = self.states.read().expect("CircuitBreaker lock poisoned");
         let now = Instant::now();
         aliases.iter().find_map(|alias| {
-            let available = match states.get(alias.as_str()) {
-                None => true,
-                Some(entry) => now >= entry.available_at,
-            };
+            let available = states
+                .get(alias.as_str())
+                .is_none_or(|entry| entry.effective_state(now) != BreakerState::Open);
             available.then_some(alias.as_str())
         })
     }
 
-    /// Return the cooldown reason for a model, if it is currently on cooldown.
+    /// Return the cooldown reason for a model, if its breaker is open.
     pub fn cooldown_reason(&self, alias: &str) -> Option<CooldownReason> {
         let states = self.states.read().expect("CircuitBreaker lock poisoned");
         states.get(alias).and_then(|entry| {
-            if Instant::now() < entry.available_at {
-                Some(entry.reason)
+            if entry.effective_state(Instant::now()) == BreakerState::Open {
+                entry.reason
             } else {
                 None
             }
         })
     }
+
+    /// Move open breakers whose cooldown has elapsed to half-open.
+    ///
+    /// Returns the aliases that changed state.
+    pub fn half_open_expired(&self) -> Vec<String> {
+        let mut states = self.states.write().expect("CircuitBreaker lock poisoned");
+        let now = Instant::now();
+        let mut changed: Vec<String> = states
+            .iter_mut()
+            .filter(|(_, entry)| {
+                entry.state == BreakerState::Open && entry.effective_state(now) != entry.state
+            })
+            .map(|(alias, entry)| {
+                entry.state = BreakerState::HalfOpen;
+                alias.clone()
+            })
+            .collect();
+        changed.sort();
+        changed
+    }
+
+    /// Snapshot the breakers for `aliases`, in the given order.
+    pub fn snapshot(&self, aliases: &[String]) -> Vec<BreakerSnapshot> {
+        let states = self.states.read().expect("CircuitBreaker lock poisoned");
+        let now = Instant::now();
+        aliases
+            .iter()
+            .map(|alias| match states.get(alias) {
+                None => BreakerSnapshot {
+                    alias: alias.clone(),
+                    state: BreakerState::Closed,
+                    consecutive_failures: 0,
+                    total_failures: 0,
+                    last_reason: None,
+                    half_open_in_secs: None,
+                },
+                Some(entry) => {
+                    let state = entry.effective_state(now);
+                    BreakerSnapshot {
+                        alias: alias.clone(),
+                        state,
+                        consecutive_failures: entry.consecutive_failures,
+                        total_failures: entry.total_failures,
+                        last_reason: entry.reason,
+                        half_open_in_secs: (state == BreakerState::Open)
+                            .then(|| entry.available_at.saturating_duration_since(now).as_secs()),
+                    }
+                }
+            })
+            .collect()
+    }
 }
 
 impl Default for CircuitBreaker {
@@ -106,6 +232,23 @@ impl Default for CircuitBreaker {
     }
 }
 
+/// Spawn the timer that half-opens expired breakers and logs the change.
+pub fn start_half_open_timer(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(HALF_OPEN_TICK_SECONDS));
+        loop {
+            tick.tick().await;
+            for alias in state.circuit_breaker.half_open_expired() {
+                state
+                    .log(LogEntry::Info {
+                        message: format!("circuit half-open for model '{alias}'"),
+                    })
+                    .await;
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,6 +311,38 @@ mod tests {
         assert!(cb.cooldown_reason("a").is_none());
     }
 
+    #[test]
+    fn half_open_probe_failure_extends_cooldown() {
+        let cb = CircuitBreaker::new();
+        cb.record_failure("a", CooldownReason::ServerError);
+        assert_eq!(cb.state("a"), BreakerState::Open);
+
+        // Simulate the cooldown elapsing.
+        cb.states
+            .write()
+            .unwrap()
+            .get_mut("a")
+            .unwrap()
+            .available_at = Instant::now();
+        assert_eq!(cb.state("a"), BreakerState::HalfOpen);
+        assert!(cb.is_available("a"));
+        assert_eq!(cb.half_open_expired(), vec!["a".to_string()]);
+        assert!(cb.half_open_expired().is_empty());
+
+        cb.record_failure("a", CooldownReason::ServerError);
+        let snapshot = &cb.snapshot(&["a".to_string()])[0];
+        assert_eq!(snapshot.state, BreakerState::Open);
+        assert_eq!(snapshot.consecutive_failures, 2);
+        assert!(snapshot.half_open_in_secs.unwrap() > 5 * 60);
+
+        cb.record_success("a");
+        let snapshot = &cb.snapshot(&["a".to_string(), "b".to_string()]);
+        assert_eq!(snapshot[0].state, BreakerState::Closed);
+        assert_eq!(snapshot[0].consecutive_failures, 0);
+        assert_eq!(snapshot[0].total_failures, 2);
+        assert_eq!(snapshot[1].state, BreakerState::Closed);
+    }
+
     #[test]
     fn different_models_are_independent() {
         let cb = CircuitBreaker::new();
@@ -0,0 +1,375 @@
+//! Per-client request and connection limits for the HTTP/WS server.
+//!
+//! Applied by a middleware layer before any handler runs, so they also cover
+//! unauthenticated traffic. Every request counts against its peer IP; one that
+//! sends an API token also counts against the token (hashed). The token is not
+//! verified yet at this point, so the per-IP count is what stops a client
+//! rotating made-up tokens. These limits are separate from the per-operator
+//! chat rate limits checked in `AppState`.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use t_koma_core::ClientLimitSettings;
+
+use crate::state::RateLimitDecision;
+
+/// Length of the sliding window for request and message rates.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Drop idle client entries once the table grows past this many.
+const PRUNE_THRESHOLD: usize = 1024;
+
+#[derive(Debug, Default)]
+struct ClientState {
+    requests: VecDeque<Instant>,
+    messages: VecDeque<Instant>,
+    connections: u32,
+}
+
+impl ClientState {
+    fn is_idle(&self) -> bool {
+        self.connections == 0 && self.requests.is_empty() && self.messages.is_empty()
+    }
+}
+
+/// Why a request was turned away.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClientRejection {
+    TooManyRequests { retry_after: Duration },
+    TooManyConnections,
+}
+
+/// Tracks request rates, open WebSocket connections and message rates per
+/// client.
+pub struct ClientLimiter {
+    settings: RwLock<ClientLimitSettings>,
+    clients: Mutex<HashMap<String, ClientState>>,
+}
+
+impl ClientLimiter {
+    pub fn new(settings: ClientLimitSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_settings(&self, settings: ClientLimitSettings) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    fn settings(&self) -> ClientLimitSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Admit one HTTP request from `peer`. `upgrade` marks a WebSocket
+    /// handshake, which also takes a connection slot held by the returned
+    /// permit until it is dropped.
+    pub fn admit(
+        self: &Arc<Self>,
+        peer: IpAddr,
+        token: Option<&str>,
+        upgrade: bool,
+    ) -> Result<ClientPermit, ClientRejection> {
+        self.admit_at(peer, token, upgrade, Instant::now())
+    }
+
+    fn admit_at(
+        self: &Arc<Self>,
+        peer: IpAddr,
+        token: Option<&str>,
+        upgrade: bool,
+        now: Instant,
+    ) -> Result<ClientPermit, ClientRejection> {
+        let settings = self.settings();
+        if !settings.enabled || (settings.exempt_loopback && peer.is_loopback()) {
+            return Ok(ClientPermit::unlimited());
+        }
+
+        let keys = client_keys(peer, token);
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if clients.len() > PRUNE_THRESHOLD {
+            clients.retain(|_, client| {
+                trim_window(&mut client.requests, now);
+                trim_window(&mut client.messages, now);
+                !client.is_idle()
+            });
+        }
+
+        // The IP comes first: once it is limited, no token entry is created.
+        for key in &keys {
+            let client = clients.entry(key.clone()).or_default();
+            if let RateLimitDecision::Limited { retry_after } =
+                check_window(&mut client.requests, settings.requests_per_minute, now)
+            {
+                return Err(ClientRejection::TooManyRequests { retry_after });
+            }
+            if upgrade
+                && settings.max_connections > 0
+                && client.connections >= settings.max_connections
+            {
+                return Err(ClientRejection::TooManyConnections);
+            }
+        }
+        if upgrade {
+            for key in &keys {
+                if let Some(client) = clients.get_mut(key) {
+                    client.connections += 1;
+                }
+            }
+        }
+
+        Ok(ClientPermit {
+            inner: Some(Arc::new(PermitInner {
+                limiter: Arc::clone(self),
+                keys,
+                holds_connection: upgrade,
+            })),
+        })
+    }
+
+    fn check_message_at(&self, keys: &[String], now: Instant) -> RateLimitDecision {
+        let limit = self.settings().ws_messages_per_minute;
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        for key in keys {
+            let client = clients.entry(key.clone()).or_default();
+            if let limited @ RateLimitDecision::Limited { .. } =
+                check_window(&mut client.messages, limit, now)
+            {
+                return limited;
+            }
+        }
+        RateLimitDecision::Allowed
+    }
+
+    fn release_connection(&self, keys: &[String]) {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        for key in keys {
+            if let Some(client) = clients.get_mut(key) {
+                client.connections = client.connections.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// Admission granted by [`ClientLimiter::admit`], attached to the request as
+/// an extension. WebSocket handlers keep it for the life of the socket.
+#[derive(Clone)]
+pub struct ClientPermit {
+    inner: Option<Arc<PermitInner>>,
+}
+
+struct PermitInner {
+    limiter: Arc<ClientLimiter>,
+    keys: Vec<String>,
+    holds_connection: bool,
+}
+
+impl Drop for PermitInner {
+    fn drop(&mut self) {
+        if self.holds_connection {
+            self.limiter.release_connection(&self.keys);
+        }
+    }
+}
+
+impl ClientPermit {
+    /// A permit for clients the limits do not apply to.
+    pub fn unlimited() -> Self {
+        Self { inner: None }
+    }
+
+    /// Count one inbound WebSocket message against the client's rate.
+    pub fn check_message(&self) -> RateLimitDecision {
+        self.check_message_at(Instant::now())
+    }
+
+    fn check_message_at(&self, now: Instant) -> RateLimitDecision {
+        match &self.inner {
+            Some(inner) => inner.limiter.check_message_at(&inner.keys, now),
+            None => RateLimitDecision::Allowed,
+        }
+    }
+}
+
+/// Keys a request counts against: its IP, then a hash of its token if it sends
+/// one, so the table never holds credentials.
+fn client_keys(peer: IpAddr, token: Option<&str>) -> Vec<String> {
+    let mut keys = vec![format!("ip:{peer}")];
+    if let Some(token) = token {
+        let digest = Sha256::digest(token.as_bytes());
+        keys.push(format!("token:{}", hex::encode(&digest[..12])));
+    }
+    keys
+}
+
+fn trim_window(window: &mut VecDeque<Instant>, now: Instant) {
+    while let Some(&oldest) = window.front() {
+        if now.duration_since(oldest) >= WINDOW {
+            window.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Record a hit in `window` unless it already holds `limit` hits from the last
+/// minute. A limit of 0 disables the check.
+fn check_window(window: &mut VecDeque<Instant>, limit: u32, now: Instant) -> RateLimitDecision {
+    if limit == 0 {
+        return RateLimitDecision::Allowed;
+    }
+    trim_window(window, now);
+    if window.len() >= limit as usize
+        && let Some(&oldest) = window.front()
+    {
+        let retry_after = WINDOW.saturating_sub(now.duration_since(oldest));
+        return RateLimitDecision::Limited {
+            retry_after: retry_after.max(Duration::from_secs(1)),
+        };
+    }
+    window.push_back(now);
+    RateLimitDecision::Allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(settings: ClientLimitSettings) -> Arc<ClientLimiter> {
+        Arc::new(ClientLimiter::new(settings))
+    }
+
+    fn remote() -> IpAddr {
+        "203.0.113.7".parse().unwrap()
+    }
+
+    #[test]
+    fn request_rate_is_limited_per_client_and_recovers() {
+        let limiter = limiter(ClientLimitSettings {
+            requests_per_minute: 2,
+            ..Default::default()
+        });
+        let start = Instant::now();
+
+        assert!(limiter.admit_at(remote(), None, false, start).is_ok());
+        assert!(limiter.admit_at(remote(), None, false, start).is_ok());
+        assert!(matches!(
+            limiter.admit_at(remote(), None, false, start),
+            Err(ClientRejection::TooManyRequests { .. })
+        ));
+        // A token still counts against its IP.
+        assert!(
+            limiter
+                .admit_at(remote(), Some("tok"), false, start)
+                .is_err()
+        );
+        // Other IPs are other clients.
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        assert!(limiter.admit_at(other, None, false, start).is_ok());
+
+        let later = start + WINDOW;
+        assert!(limiter.admit_at(remote(), None, false, later).is_ok());
+    }
+
+    #[test]
+    fn rotating_tokens_from_one_ip_are_still_limited() {
+        let limiter = limiter(ClientLimitSettings {
+            requests_per_minute: 3,
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        let mut rejected = 0;
+        for i in 0..20 {
+            let token = format!("made-up-{i}");
+            if limiter
+                .admit_at(remote(), Some(&token), false, now)
+                .is_err()
+            {
+                rejected += 1;
+            }
+        }
+        assert_eq!(rejected, 17);
+        // Only the admitted requests left a token entry behind.
+        let clients = limiter.clients.lock().unwrap();
+        assert_eq!(clients.len(), 1 + 3);
+    }
+
+    #[test]
+    fn token_limit_applies_on_top_of_the_ip_limit() {
+        let limiter = limiter(ClientLimitSettings {
+            requests_per_minute: 2,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let first: IpAddr = "203.0.113.1".parse().unwrap();
+        let second: IpAddr = "203.0.113.2".parse().unwrap();
+
+        assert!(limiter.admit_at(first, Some("tok"), false, now).is_ok());
+        assert!(limiter.admit_at(second, Some("tok"), false, now).is_ok());
+        // Both IPs are under their limit, the shared token is not.
+        assert!(matches!(
+            limiter.admit_at(first, Some("tok"), false, now),
+            Err(ClientRejection::TooManyRequests { .. })
+        ));
+    }
+
+    #[test]
+    fn connection_slots_are_released_when_the_permit_drops() {
+        let limiter = limiter(ClientLimitSettings {
+            max_connections: 1,
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        let first = limiter.admit_at(remote(), None, true, now).unwrap();
+        assert_eq!(
+            limiter.admit_at(remote(), None, true, now).err(),
+            Some(ClientRejection::TooManyConnections)
+        );
+        // Plain requests don't need a connection slot.
+        assert!(limiter.admit_at(remote(), None, false, now).is_ok());
+
+        drop(first);
+        assert!(limiter.admit_at(remote(), None, true, now).is_ok());
+    }
+
+    #[test]
+    fn ws_messages_are_limited_and_loopback_is_exempt() {
+        let settings = ClientLimitSettings {
+            ws_messages_per_minute: 1,
+            exempt_loopback: true,
+            ..Default::default()
+        };
+        let limiter = limiter(settings);
+        let now = Instant::now();
+
+        let permit = limiter.admit_at(remote(), None, true, now).unwrap();
+        assert!(matches!(
+            permit.check_message_at(now),
+            RateLimitDecision::Allowed
+        ));
+        assert!(matches!(
+            permit.check_message_at(now),
+            RateLimitDecision::Limited { .. }
+        ));
+
+        let local = limiter
+            .admit_at(IpAddr::from([127, 0, 0, 1]), None, true, now)
+            .unwrap();
+        for _ in 0..5 {
+            assert!(matches!(
+                local.check_message_at(now),
+                RateLimitDecision::Allowed
+            ));
+        }
+    }
+}
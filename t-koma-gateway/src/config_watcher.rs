@@ -0,0 +1,84 @@
+//! Hot reload of `config.toml`.
+//!
+//! Watches the config directory (editors usually replace the file rather than
+//! write it in place) and calls [`AppState::reload_config`] once writes settle.
+//! The `ReloadConfig` WebSocket message triggers the same reload on demand.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Quiet period after the last change event before reloading.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Start watching `config.toml`. Returns `None` when the config path cannot be
+/// resolved or watched; the gateway keeps running without hot reload.
+pub fn start_config_watcher(state: Arc<AppState>) -> Option<tokio::task::JoinHandle<()>> {
+    let path = match t_koma_core::Settings::config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("config watcher disabled: {e}");
+            return None;
+        }
+    };
+    let dir = path.parent()?.to_path_buf();
+    let file_name = path.file_name()?.to_os_string();
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+    let mut watcher =
+        match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res
+                && (event.kind.is_modify() || event.kind.is_create())
+                && event
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name() == Some(file_name.as_os_str()))
+            {
+                let _ = tx.send(());
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!("config watcher disabled: {e}");
+                return None;
+            }
+        };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        warn!(
+            "config watcher disabled: cannot watch {}: {e}",
+            dir.display()
+        );
+        return None;
+    }
+
+    let handle = tokio::spawn(async move {
+        // Keep the watcher alive for as long as the task runs.
+        let _watcher = watcher;
+        while rx.recv().await.is_some() {
+            while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+            if state.is_shutting_down() {
+                break;
+            }
+            match state.reload_config(None).await {
+                Ok(reload) if reload.applied.is_empty() && reload.restart_required.is_empty() => {}
+                Ok(reload) => {
+                    if !reload.restart_required.is_empty() {
+                        warn!(
+                            "config.toml changes need a restart to apply: {}",
+                            reload.restart_required.join(", ")
+                        );
+                    }
+                }
+                Err(e) => warn!("config.toml reload failed, keeping previous settings: {e}"),
+            }
+        }
+    });
+
+    info!("config watcher started ({})", path.display());
+    Some(handle)
+}
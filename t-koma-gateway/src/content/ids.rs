@@ -10,6 +10,18 @@ pub const APPROVAL_REQUIRED_WITH_PATH: &str = "approval-required-with-path";
 /// content: messages/en/approvals.toml#approval-reference-import
 pub const APPROVAL_REFERENCE_IMPORT: &str = "approval-reference-import";
 
+/// content: messages/en/approvals.toml#approval-browser-action
+pub const APPROVAL_BROWSER_ACTION: &str = "approval-browser-action";
+
+/// content: messages/en/approvals.toml#approval-mcp-tool
+pub const APPROVAL_MCP_TOOL: &str = "approval-mcp-tool";
+
+/// content: messages/en/approvals.toml#approval-webhook-tool
+pub const APPROVAL_WEBHOOK_TOOL: &str = "approval-webhook-tool";
+
+/// content: messages/en/approvals.toml#approval-web-domain
+pub const APPROVAL_WEB_DOMAIN: &str = "approval-web-domain";
+
 /// content: messages/en/approvals.toml#no-pending-approval
 pub const NO_PENDING_APPROVAL: &str = "no-pending-approval";
 
@@ -31,6 +43,21 @@ pub const ADMIN_OPERATOR_APPROVED: &str = "admin-operator-approved";
 /// content: messages/en/discord.toml#admin-operator-denied
 pub const ADMIN_OPERATOR_DENIED: &str = "admin-operator-denied";
 
+/// content: messages/en/discord.toml#discord-attachment-ingest
+pub const DISCORD_ATTACHMENT_INGEST: &str = "discord-attachment-ingest";
+
+/// content: messages/en/discord.toml#discord-attachment-ingest-topic
+pub const DISCORD_ATTACHMENT_INGEST_TOPIC: &str = "discord-attachment-ingest-topic";
+
+/// content: messages/en/discord.toml#discord-attachment-ingest-no-topics
+pub const DISCORD_ATTACHMENT_INGEST_NO_TOPICS: &str = "discord-attachment-ingest-no-topics";
+
+/// content: messages/en/discord.toml#discord-attachment-ingested
+pub const DISCORD_ATTACHMENT_INGESTED: &str = "discord-attachment-ingested";
+
+/// content: messages/en/discord.toml#discord-attachment-ingest-failed
+pub const DISCORD_ATTACHMENT_INGEST_FAILED: &str = "discord-attachment-ingest-failed";
+
 /// content: messages/en/discord.toml#discord-existing-operator-todo
 pub const DISCORD_EXISTING_OPERATOR_TODO: &str = "discord-existing-operator-todo";
 
@@ -52,6 +79,18 @@ pub const ERROR_FAILED_LOAD_OPERATOR_DISCORD: &str = "error-failed-load-operator
 /// content: messages/en/discord.toml#error-init-session-discord
 pub const ERROR_INIT_SESSION_DISCORD: &str = "error-init-session-discord";
 
+/// content: messages/en/email.toml#email-ghost-name-prompt
+pub const EMAIL_GHOST_NAME_PROMPT: &str = "email-ghost-name-prompt";
+
+/// content: messages/en/email.toml#email-interface-prompt
+pub const EMAIL_INTERFACE_PROMPT: &str = "email-interface-prompt";
+
+/// content: messages/en/email.toml#email-new-operator-pending
+pub const EMAIL_NEW_OPERATOR_PENDING: &str = "email-new-operator-pending";
+
+/// content: messages/en/email.toml#email-select-ghost-prompt
+pub const EMAIL_SELECT_GHOST_PROMPT: &str = "email-select-ghost-prompt";
+
 /// content: messages/en/generic.toml#access-pending-discord
 pub const ACCESS_PENDING_DISCORD: &str = "access-pending-discord";
 
@@ -61,6 +100,18 @@ pub const CHAT_BUSY: &str = "chat-busy";
 /// content: messages/en/generic.toml#chat-continue-missing
 pub const CHAT_CONTINUE_MISSING: &str = "chat-continue-missing";
 
+/// content: messages/en/generic.toml#chat-shutting-down
+pub const CHAT_SHUTTING_DOWN: &str = "chat-shutting-down";
+
+/// content: messages/en/generic.toml#chat-stop-requested
+pub const CHAT_STOP_REQUESTED: &str = "chat-stop-requested";
+
+/// content: messages/en/generic.toml#chat-stopped
+pub const CHAT_STOPPED: &str = "chat-stopped";
+
+/// content: messages/en/generic.toml#response-withheld
+pub const RESPONSE_WITHHELD: &str = "response-withheld";
+
 /// content: messages/en/generic.toml#compaction-happened
 pub const COMPACTION_HAPPENED: &str = "compaction-happened";
 
@@ -70,9 +121,18 @@ pub const ERROR_GENERIC: &str = "error-generic";
 /// content: messages/en/generic.toml#error-processing-request
 pub const ERROR_PROCESSING_REQUEST: &str = "error-processing-request";
 
+/// content: messages/en/generic.toml#reminder-due
+pub const REMINDER_DUE: &str = "reminder-due";
+
 /// content: messages/en/generic.toml#session-started
 pub const SESSION_STARTED: &str = "session-started";
 
+/// content: messages/en/generic.toml#usage-budget-exceeded
+pub const USAGE_BUDGET_EXCEEDED: &str = "usage-budget-exceeded";
+
+/// content: messages/en/generic.toml#usage-budget-warning
+pub const USAGE_BUDGET_WARNING: &str = "usage-budget-warning";
+
 /// content: messages/en/ghosts.toml#active-ghost-set
 pub const ACTIVE_GHOST_SET: &str = "active-ghost-set";
 
@@ -100,6 +160,9 @@ pub const GHOST_LIST: &str = "ghost-list";
 /// content: messages/en/ghosts.toml#ghost-name-prompt
 pub const GHOST_NAME_PROMPT: &str = "ghost-name-prompt";
 
+/// content: messages/en/ghosts.toml#ghost-creation-not-permitted
+pub const GHOST_CREATION_NOT_PERMITTED: &str = "ghost-creation-not-permitted";
+
 /// content: messages/en/ghosts.toml#invalid-ghost-name
 pub const INVALID_GHOST_NAME: &str = "invalid-ghost-name";
 
@@ -109,6 +172,9 @@ pub const SELECT_GHOST_PROMPT: &str = "select-ghost-prompt";
 /// content: messages/en/ghosts.toml#unknown-ghost-name
 pub const UNKNOWN_GHOST_NAME: &str = "unknown-ghost-name";
 
+/// content: messages/en/models.toml#model-circuit-open
+pub const MODEL_CIRCUIT_OPEN: &str = "model-circuit-open";
+
 /// content: messages/en/models.toml#model-not-configured
 pub const MODEL_NOT_CONFIGURED: &str = "model-not-configured";
 
@@ -148,6 +214,9 @@ pub const FAILED_CREATE_SESSION: &str = "failed-create-session";
 /// content: messages/en/server.toml#failed-delete-session
 pub const FAILED_DELETE_SESSION: &str = "failed-delete-session";
 
+/// content: messages/en/server.toml#failed-fork-session
+pub const FAILED_FORK_SESSION: &str = "failed-fork-session";
+
 /// content: messages/en/server.toml#failed-init-ghost-db
 pub const FAILED_INIT_GHOST_DB: &str = "failed-init-ghost-db";
 
@@ -172,6 +241,9 @@ pub const FAILED_LOAD_INTERFACE: &str = "failed-load-interface";
 /// content: messages/en/server.toml#failed-load-operator
 pub const FAILED_LOAD_OPERATOR: &str = "failed-load-operator";
 
+/// content: messages/en/server.toml#failed-search-sessions
+pub const FAILED_SEARCH_SESSIONS: &str = "failed-search-sessions";
+
 /// content: messages/en/server.toml#failed-switch-session
 pub const FAILED_SWITCH_SESSION: &str = "failed-switch-session";
 
@@ -217,6 +289,24 @@ pub const UNKNOWN_GHOST_NAME_SERVER: &str = "unknown-ghost-name-server";
 /// content: messages/en/server.toml#unknown-operator-status
 pub const UNKNOWN_OPERATOR_STATUS: &str = "unknown-operator-status";
 
+/// content: messages/en/slack.toml#slack-ghost-name-prompt
+pub const SLACK_GHOST_NAME_PROMPT: &str = "slack-ghost-name-prompt";
+
+/// content: messages/en/slack.toml#slack-interface-prompt
+pub const SLACK_INTERFACE_PROMPT: &str = "slack-interface-prompt";
+
+/// content: messages/en/slack.toml#slack-steps-prompt
+pub const SLACK_STEPS_PROMPT: &str = "slack-steps-prompt";
+
+/// content: messages/en/telegram.toml#telegram-ghost-name-prompt
+pub const TELEGRAM_GHOST_NAME_PROMPT: &str = "telegram-ghost-name-prompt";
+
+/// content: messages/en/telegram.toml#telegram-interface-prompt
+pub const TELEGRAM_INTERFACE_PROMPT: &str = "telegram-interface-prompt";
+
+/// content: messages/en/telegram.toml#telegram-steps-prompt
+pub const TELEGRAM_STEPS_PROMPT: &str = "telegram-steps-prompt";
+
 /// content: prompts/system/bootstrap.md
 pub const PROMPT_BOOTSTRAP: &str = "bootstrap";
 
@@ -226,8 +316,29 @@ pub const PROMPT_COMPACTION: &str = "compaction-prompt";
 /// content: prompts/system/reflection-prompt.md
 pub const PROMPT_REFLECTION: &str = "reflection-prompt";
 
+/// content: prompts/system/reflection-quality-prompt.md
+pub const PROMPT_REFLECTION_QUALITY: &str = "reflection-quality-prompt";
+
+/// content: prompts/system/reflection-retry.md
+pub const PROMPT_REFLECTION_RETRY: &str = "reflection-retry";
+
+/// content: prompts/system/heartbeat-due-todos.md
+pub const PROMPT_HEARTBEAT_DUE_TODOS: &str = "heartbeat-due-todos";
+
+/// content: prompts/system/session-title-prompt.md
+pub const PROMPT_SESSION_TITLE: &str = "session-title-prompt";
+
+/// content: prompts/system/structured-output-repair.md
+pub const PROMPT_STRUCTURED_OUTPUT_REPAIR: &str = "structured-output-repair";
+
+/// content: prompts/system/output-moderation-prompt.md
+pub const PROMPT_OUTPUT_MODERATION: &str = "output-moderation-prompt";
+
 /// content: prompts/system/cron-prompt.md
 pub const PROMPT_CRON: &str = "cron-prompt";
 
+/// content: prompts/system/scheduled-task-prompt.md
+pub const PROMPT_SCHEDULED_TASK: &str = "scheduled-task-prompt";
+
 /// content: prompts/system/system-prompt.md
 pub const PROMPT_SYSTEM_PROMPT: &str = "system-prompt";
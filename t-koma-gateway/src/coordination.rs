@@ -0,0 +1,103 @@
+//! Coordination between gateways sharing one koma DB.
+//!
+//! With `[gateway] coordinate_jobs = true`, a gateway claims a lease in the
+//! `job_leases` table before running a session's heartbeat or reflection and
+//! skips the job when another instance holds it. Without coordination every
+//! lease is granted locally and nothing touches the DB.
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use t_koma_db::JobLeaseRepository;
+
+/// This gateway's identity in the lease table and how long its leases last.
+#[derive(Debug, Clone)]
+pub struct JobCoordination {
+    instance_id: String,
+    lease_secs: i64,
+}
+
+impl JobCoordination {
+    pub fn new(lease_secs: u64) -> Self {
+        Self {
+            instance_id: format!("gw_{}", uuid::Uuid::new_v4()),
+            lease_secs: lease_secs as i64,
+        }
+    }
+
+    /// Claim `key`. Returns `None` when another gateway holds it or the
+    /// lease table cannot be reached.
+    pub async fn acquire(&self, pool: &SqlitePool, key: &str) -> Option<JobLease> {
+        let now = Utc::now().timestamp();
+        match JobLeaseRepository::try_acquire(pool, key, &self.instance_id, now, self.lease_secs)
+            .await
+        {
+            Ok(true) => Some(JobLease {
+                held: Some(HeldLease {
+                    pool: pool.clone(),
+                    key: key.to_string(),
+                    holder: self.instance_id.clone(),
+                }),
+            }),
+            Ok(false) => None,
+            Err(err) => {
+                warn!("job lease {key}: failed to acquire: {err}");
+                None
+            }
+        }
+    }
+
+    /// Release every lease this gateway holds and drop expired ones.
+    pub async fn release_all(&self, pool: &SqlitePool) {
+        if let Err(err) = JobLeaseRepository::release_all(pool, &self.instance_id).await {
+            warn!("failed to release job leases: {err}");
+        }
+        let now = Utc::now().timestamp();
+        if let Err(err) = JobLeaseRepository::prune_expired(pool, now).await {
+            warn!("failed to prune expired job leases: {err}");
+        }
+    }
+}
+
+/// A claimed job; the lease is released when this is dropped.
+pub struct JobLease {
+    held: Option<HeldLease>,
+}
+
+struct HeldLease {
+    pool: SqlitePool,
+    key: String,
+    holder: String,
+}
+
+impl JobLease {
+    /// A lease for gateways running without coordination.
+    pub fn local() -> Self {
+        Self { held: None }
+    }
+
+    /// Leave the lease in place until it expires instead of releasing it.
+    pub fn keep(mut self) {
+        self.held = None;
+    }
+}
+
+impl Drop for JobLease {
+    fn drop(&mut self) {
+        let Some(lease) = self.held.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            // No runtime left to release on; the lease expires on its own.
+            return;
+        };
+        runtime.spawn(async move {
+            if let Err(err) =
+                JobLeaseRepository::release(&lease.pool, &lease.key, &lease.holder).await
+            {
+                warn!("job lease {}: failed to release: {err}", lease.key);
+            }
+        });
+    }
+}
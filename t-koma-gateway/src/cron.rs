@@ -1,11 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::{DateTime, Timelike, Utc};
-use cron::Schedule;
+use chrono::Utc;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::sync::mpsc;
 use tokio::time::{Instant, interval_at};
@@ -14,7 +12,7 @@ use tracing::{info, warn};
 use crate::scheduler::JobKind;
 use crate::state::{AppState, LogEntry};
 use crate::tools::ToolManager;
-use t_koma_core::{CronPreToolCall, ParsedCronJobFile, parse_cron_job_markdown};
+use t_koma_core::{CronPreToolCall, CronSchedule, ParsedCronJobFile, parse_cron_job_markdown};
 use t_koma_db::{
     ContentBlock, Ghost, GhostRepository, JobKind as DbJobKind, JobLog, JobLogRepository,
     MessageRole, Session, SessionRepository,
@@ -24,8 +22,7 @@ use t_koma_db::{
 struct CronScheduledJob {
     key: String,
     name: String,
-    schedule: Schedule,
-    schedule_raw: String,
+    schedule: CronSchedule,
     prompt: String,
     pre_tools: Vec<CronPreToolCall>,
     carry_last_output: bool,
@@ -40,33 +37,6 @@ struct CronRuntime {
     jobs: HashMap<String, CronScheduledJob>,
 }
 
-fn utc_minute(now: DateTime<Utc>) -> DateTime<Utc> {
-    now.with_second(0)
-        .and_then(|dt| dt.with_nanosecond(0))
-        .unwrap_or(now)
-}
-
-fn parse_schedule(expr: &str) -> Result<Schedule, String> {
-    Schedule::from_str(&format!("0 {}", expr.trim())).map_err(|e| e.to_string())
-}
-
-fn next_due_at_or_after(schedule: &Schedule, now: DateTime<Utc>) -> Option<i64> {
-    let now_min = utc_minute(now);
-    let prev = now_min - chrono::Duration::minutes(1);
-    schedule
-        .after(&prev)
-        .next()
-        .map(|dt| utc_minute(dt).timestamp())
-}
-
-fn next_due_after(schedule: &Schedule, base_unix: i64) -> Option<i64> {
-    let base = DateTime::from_timestamp(base_unix, 0)?;
-    schedule
-        .after(&base)
-        .next()
-        .map(|dt| utc_minute(dt).timestamp())
-}
-
 fn sanitize_key(input: &str) -> String {
     input
         .chars()
@@ -93,7 +63,8 @@ fn build_job_from_file(
     file_path: &Path,
     parsed: ParsedCronJobFile,
 ) -> Result<CronScheduledJob, String> {
-    let schedule = parse_schedule(&parsed.schedule)?;
+    let schedule = CronSchedule::parse(&parsed.schedule, parsed.timezone.as_deref())
+        .map_err(|e| e.to_string())?;
     let rel = file_path
         .strip_prefix(workspace_root)
         .ok()
@@ -104,7 +75,6 @@ fn build_job_from_file(
         key: key.clone(),
         name: parsed.name,
         schedule,
-        schedule_raw: parsed.schedule,
         prompt: parsed.prompt,
         pre_tools: parsed.pre_tools,
         carry_last_output: parsed.carry_last_output,
@@ -219,12 +189,13 @@ fn build_cron_prompt(
     pre_tool_results: &[(CronPreToolCall, String)],
 ) -> String {
     let pre_tools = format_pre_tool_results(pre_tool_results);
+    let schedule = job.schedule.to_string();
     crate::content::prompt_text(
         crate::content::ids::PROMPT_CRON,
         None,
         &[
             ("job_name", job.name.as_str()),
-            ("schedule", job.schedule_raw.as_str()),
+            ("schedule", schedule.as_str()),
             ("previous_output", previous_output),
             ("pre_tool_results", pre_tools.as_str()),
             ("job_prompt", job.prompt.as_str()),
@@ -233,7 +204,7 @@ fn build_cron_prompt(
     .unwrap_or_else(|_| {
         format!(
             "CRON job: {}\nSchedule: {}\nPrevious output:\n{}\n\nPre-tool results:\n{}\n\nTask:\n{}",
-            job.name, job.schedule_raw, previous_output, pre_tools, job.prompt
+            job.name, schedule, previous_output, pre_tools, job.prompt
         )
     })
 }
@@ -441,33 +412,48 @@ async fn run_cron_tick(state: Arc<AppState>, runtime: &mut CronRuntime) {
     let now = Utc::now();
     let now_ts = now.timestamp();
     for job in runtime.jobs.values() {
-        let Some(initial_due) = next_due_at_or_after(&job.schedule, now) else {
+        let schedule = job.schedule.to_string();
+        let Some(initial_due) = job.schedule.next_at_or_after(now) else {
             continue;
         };
-        if state.scheduler_get(JobKind::Cron, &job.key).await.is_none() {
-            state
-                .scheduler_set(JobKind::Cron, &job.key, Some(initial_due))
-                .await;
-        }
-
-        let due = state
-            .scheduler_get(JobKind::Cron, &job.key)
+        // A saved due time only holds if the schedule is unchanged; an edited
+        // expression or time zone starts over from the new one.
+        let saved = state
+            .scheduler_entry(JobKind::Cron, &job.key)
             .await
-            .unwrap_or(initial_due);
+            .filter(|entry| entry.schedule.as_deref() == Some(schedule.as_str()));
+        let due = match saved {
+            Some(entry) => entry.next_due,
+            None => {
+                state
+                    .scheduler_set_recurring(JobKind::Cron, &job.key, initial_due, &schedule)
+                    .await;
+                initial_due
+            }
+        };
         if now_ts >= due && now_ts < due + 60 {
-            run_single_cron_job(&state, job).await;
-            if let Some(next_due) = next_due_after(&job.schedule, due) {
+            // One lease per occurrence, left to expire rather than released,
+            // so a gateway sharing the DB can't repeat the run within the window.
+            match state
+                .acquire_job_lease(&format!("cron:{}:{due}", job.key))
+                .await
+            {
+                Some(lease) => {
+                    lease.keep();
+                    run_single_cron_job(&state, job).await;
+                }
+                None => info!("cron job {} already ran on another gateway", job.key),
+            }
+            if let Some(next_due) = job.schedule.next_after(due) {
                 state
-                    .scheduler_set(JobKind::Cron, &job.key, Some(next_due))
+                    .scheduler_set_recurring(JobKind::Cron, &job.key, next_due, &schedule)
                     .await;
             } else {
                 state.scheduler_set(JobKind::Cron, &job.key, None).await;
             }
-        } else if now_ts > due
-            && let Some(next_due) = next_due_at_or_after(&job.schedule, now)
-        {
+        } else if now_ts > due {
             state
-                .scheduler_set(JobKind::Cron, &job.key, Some(next_due))
+                .scheduler_set_recurring(JobKind::Cron, &job.key, initial_due, &schedule)
                 .await;
         }
     }
@@ -496,6 +482,10 @@ pub fn start_cron_runner(state: Arc<AppState>, check_seconds: u64) -> tokio::tas
         loop {
             tokio::select! {
                 _ = interval.tick() => {
+                    if state.is_shutting_down() {
+                        info!("cron runner stopped (shutting down)");
+                        break;
+                    }
                     run_cron_tick(Arc::clone(&state), &mut runtime).await;
                 }
                 evt = rx.recv() => {
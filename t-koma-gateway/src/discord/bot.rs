@@ -2,9 +2,9 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use serenity::async_trait;
-use serenity::builder::{CreateActionRow, CreateCommand, CreateCommandOption};
+use serenity::builder::{CreateActionRow, CreateCommand, CreateCommandOption, CreateThread};
 use serenity::model::application::{Command, CommandOptionType};
-use serenity::model::channel::Message;
+use serenity::model::channel::{Channel, Message};
 use serenity::model::gateway::Ready;
 use serenity::model::id::ChannelId;
 use serenity::prelude::*;
@@ -40,13 +40,17 @@ impl Drop for TimedTyping {
     }
 }
 
+use crate::attachments;
 use crate::content::{self, ids};
-use crate::operator_flow;
+use crate::operator_flow::{self, OutboundMessage};
+use crate::session::ChatError;
 use crate::state::{AppState, PendingGatewayAction, RateLimitDecision};
 
+use super::progress::LiveStatus;
+use super::search_card::SearchCards;
 use super::send::{
     WARNING_EMBED_COLOR, send_discord_message, send_gateway_embed, send_gateway_embed_colored,
-    send_interface_prompt, send_outbound_messages, send_tool_calls_v2,
+    send_interface_prompt, send_outbound_messages,
 };
 
 /// Discord bot handler
@@ -55,15 +59,63 @@ use super::send::{
 /// All chat handling is delegated to `state.session_chat.chat()`.
 pub struct Bot {
     pub(super) state: Arc<AppState>,
+    /// Run guild-channel conversations in their own threads (`[discord] threads`).
+    pub(super) threads: bool,
+    /// Render `knowledge_search` calls as result cards (`[discord] search_embeds`).
+    pub(super) search_embeds: bool,
 }
 
 impl Bot {
-    pub fn new(state: Arc<AppState>) -> Self {
-        Self { state }
+    pub fn new(state: Arc<AppState>, settings: &t_koma_core::DiscordSettings) -> Self {
+        Self {
+            state,
+            threads: settings.threads,
+            search_embeds: settings.search_embeds,
+        }
+    }
+}
+
+/// Longest thread name Discord accepts.
+const THREAD_NAME_MAX_CHARS: usize = 100;
+
+/// Where a message is answered and the session it belongs to.
+struct Route {
+    session: t_koma_db::Session,
+    /// Channel replies go to (the thread, when there is one).
+    channel_id: ChannelId,
+    /// Thread mapped to `session`.
+    thread_id: Option<ChannelId>,
+    /// Active session that a new thread session replaced.
+    previous_session_id: Option<String>,
+    /// `session` was created for this message.
+    fresh: bool,
+}
+
+/// Name for a thread opened from `content`: its first line without user,
+/// role and channel mentions, or the ghost name when nothing is left.
+fn thread_name(content: &str, ghost_name: &str) -> String {
+    let first_line = content
+        .lines()
+        .map(|line| {
+            line.split_whitespace()
+                .filter(|word| !(word.starts_with("<@") || word.starts_with("<#")))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .find(|line| !line.is_empty());
+    let Some(line) = first_line else {
+        return ghost_name.to_string();
+    };
+    if line.chars().count() <= THREAD_NAME_MAX_CHARS {
+        return line;
     }
+    let mut name: String = line.chars().take(THREAD_NAME_MAX_CHARS - 1).collect();
+    name = name.trim_end().to_string();
+    name.push('…');
+    name
 }
 
-pub(super) fn parse_ghost_selection(content: &str) -> Option<String> {
+pub(crate) fn parse_ghost_selection(content: &str) -> Option<String> {
     let trimmed = content.trim();
     let lower = trimmed.to_lowercase();
     if lower.starts_with("ghost:") || lower.starts_with("ghost ") {
@@ -73,7 +125,7 @@ pub(super) fn parse_ghost_selection(content: &str) -> Option<String> {
     }
 }
 
-pub(super) fn format_ghost_list_lines(ghosts: &[t_koma_db::Ghost]) -> String {
+pub(crate) fn format_ghost_list_lines(ghosts: &[t_koma_db::Ghost]) -> String {
     let mut lines = Vec::with_capacity(ghosts.len());
     for ghost in ghosts {
         lines.push(format!("- {}", ghost.name));
@@ -81,7 +133,7 @@ pub(super) fn format_ghost_list_lines(ghosts: &[t_koma_db::Ghost]) -> String {
     lines.join("\n")
 }
 
-pub(super) async fn persist_ghost_name_to_soul(workspace_path: &std::path::Path, ghost_name: &str) {
+pub(crate) async fn persist_ghost_name_to_soul(workspace_path: &std::path::Path, ghost_name: &str) {
     if let Err(err) = tokio::fs::create_dir_all(workspace_path).await {
         error!(
             "Failed to create ghost workspace directory for {}: {}",
@@ -196,9 +248,8 @@ pub(super) async fn handle_interface_choice(
     .await;
 
     let state = bot.state.clone();
-    let http = ctx.http.clone();
     tokio::spawn(async move {
-        super::send::send_new_operator_notification_to_pms(&state, &http, &operator).await;
+        crate::operator_flow::notify_new_operator(&state, &operator).await;
     });
 }
 
@@ -223,6 +274,19 @@ pub(super) async fn run_action_intent(
                 .unwrap_or(crate::session::DEFAULT_TOOL_LOOP_EXTRA);
             format!("steps {}", steps)
         }
+        "knowledge.ingest_prompt" => {
+            super::ingest::send_topic_select(bot.state.as_ref(), ctx, channel_id, pending).await;
+            return;
+        }
+        "knowledge.open" => {
+            super::search_card::open_note(bot.state.as_ref(), ctx, channel_id, pending).await;
+            return;
+        }
+        "knowledge.ingest" => {
+            super::ingest::ingest_into_topic(bot.state.as_ref(), ctx, channel_id, pending, payload)
+                .await;
+            return;
+        }
         "ghost.select" => {
             if let Some(ghost_name) = payload {
                 bot.state
@@ -240,33 +304,28 @@ pub(super) async fn run_action_intent(
             let Some(target_id) = pending.payload.as_deref() else {
                 return;
             };
+            if !crate::operator_flow::operator_has_permission(
+                bot.state.as_ref(),
+                &pending.operator_id,
+                &t_koma_db::OperatorPermission::ManageOperators,
+            )
+            .await
+            {
+                return;
+            }
             let pm = match t_koma_db::OperatorRepository::get_by_id(
                 bot.state.koma_db.pool(),
                 &pending.operator_id,
             )
             .await
             {
-                Ok(Some(op)) if op.access_level == t_koma_db::OperatorAccessLevel::PuppetMaster => {
-                    op
-                }
+                Ok(Some(op)) => op,
                 _ => return,
             };
             match t_koma_db::OperatorRepository::approve(bot.state.koma_db.pool(), target_id).await
             {
                 Ok(approved_op) => {
-                    if let Some(token) = bot.state.discord_bot_token().await
-                        && let Err(e) = super::send::send_approved_operator_ghost_prompt_dm(
-                            bot.state.as_ref(),
-                            &token,
-                            target_id,
-                        )
-                        .await
-                    {
-                        warn!(
-                            "Approved operator {}, but Discord welcome DM failed: {}",
-                            target_id, e
-                        );
-                    }
+                    crate::operator_flow::welcome_approved_operator(&bot.state, target_id).await;
                     let text = super::render_message(
                         ids::ADMIN_OPERATOR_APPROVED,
                         &[("operator_name", &approved_op.name)],
@@ -293,15 +352,22 @@ pub(super) async fn run_action_intent(
             let Some(target_id) = pending.payload.as_deref() else {
                 return;
             };
+            if !crate::operator_flow::operator_has_permission(
+                bot.state.as_ref(),
+                &pending.operator_id,
+                &t_koma_db::OperatorPermission::ManageOperators,
+            )
+            .await
+            {
+                return;
+            }
             let pm = match t_koma_db::OperatorRepository::get_by_id(
                 bot.state.koma_db.pool(),
                 &pending.operator_id,
             )
             .await
             {
-                Ok(Some(op)) if op.access_level == t_koma_db::OperatorAccessLevel::PuppetMaster => {
-                    op
-                }
+                Ok(Some(op)) => op,
                 _ => return,
             };
             match t_koma_db::OperatorRepository::deny(bot.state.koma_db.pool(), target_id).await {
@@ -374,8 +440,28 @@ impl EventHandler for Bot {
             return;
         }
 
-        // Check if we should respond (mention or DM)
-        let should_respond = msg.mentions_me(&ctx).await.unwrap_or(false) || msg.guild_id.is_none();
+        // A mapped thread is a conversation with the bot: no mention needed there.
+        let thread_session_id = if self.threads && msg.guild_id.is_some() {
+            match t_koma_db::DiscordThreadRepository::find_session(
+                self.state.koma_db.pool(),
+                &msg.channel_id.to_string(),
+            )
+            .await
+            {
+                Ok(found) => found,
+                Err(e) => {
+                    warn!("Failed to look up Discord thread {}: {}", msg.channel_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Check if we should respond (mention, DM or session thread)
+        let should_respond = thread_session_id.is_some()
+            || msg.mentions_me(&ctx).await.unwrap_or(false)
+            || msg.guild_id.is_none();
 
         if !should_respond {
             return;
@@ -563,7 +649,48 @@ impl EventHandler for Bot {
             return;
         }
 
-        let ghost_name = if ghosts.len() == 1 {
+        // Messages in a session thread go to that session (and its ghost); a
+        // thread running someone else's session is answered like the channel.
+        let thread_session = match &thread_session_id {
+            Some(session_id) => {
+                t_koma_db::SessionRepository::get_by_id(self.state.koma_db.pool(), session_id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .filter(|session| session.operator_id == operator_id)
+            }
+            None => None,
+        };
+        let thread_ghost = thread_session
+            .as_ref()
+            .and_then(|session| ghosts.iter().find(|g| g.id == session.ghost_id));
+
+        // A guild channel the operator bound to a ghost (`/bind`) always uses it.
+        let bound_ghost = if thread_ghost.is_none() && msg.guild_id.is_some() {
+            match t_koma_db::DiscordChannelRepository::bound_ghost(
+                self.state.koma_db.pool(),
+                &msg.channel_id.to_string(),
+                &operator_id,
+            )
+            .await
+            {
+                Ok(Some(ghost_id)) => ghosts.iter().find(|g| g.id == ghost_id),
+                Ok(None) => None,
+                Err(e) => {
+                    warn!(
+                        "Failed to look up ghost binding for {}: {}",
+                        msg.channel_id, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let ghost_name = if let Some(ghost) = thread_ghost.or(bound_ghost) {
+            ghost.name.clone()
+        } else if ghosts.len() == 1 {
             ghosts[0].name.clone()
         } else if let Some(active) = self.state.get_active_ghost(&operator_id).await {
             active
@@ -602,14 +729,34 @@ impl EventHandler for Bot {
                 }
             };
 
-        let session = match t_koma_db::SessionRepository::get_or_create_active(
-            self.state.koma_db.pool(),
-            &ghost.id,
-            &operator_id,
-        )
-        .await
-        {
-            Ok(s) => s,
+        let route = match thread_session.filter(|session| session.ghost_id == ghost.id) {
+            Some(session) => Ok(Route {
+                session,
+                channel_id: msg.channel_id,
+                thread_id: Some(msg.channel_id),
+                previous_session_id: None,
+                fresh: false,
+            }),
+            None if self.threads && msg.guild_id.is_some() && thread_session_id.is_none() => {
+                self.open_thread_session(&ctx, &msg, &ghost, &operator_id, clean_content)
+                    .await
+            }
+            None => t_koma_db::SessionRepository::get_or_create_active(
+                self.state.koma_db.pool(),
+                &ghost.id,
+                &operator_id,
+            )
+            .await
+            .map(|session| Route {
+                session,
+                channel_id: msg.channel_id,
+                thread_id: None,
+                previous_session_id: None,
+                fresh: false,
+            }),
+        };
+        let route = match route {
+            Ok(route) => route,
             Err(e) => {
                 error!(
                     "Failed to create session for operator {}: {}",
@@ -625,6 +772,8 @@ impl EventHandler for Bot {
                 return;
             }
         };
+        let session = &route.session;
+        let channel_id = route.channel_id;
 
         let operator =
             match t_koma_db::OperatorRepository::get_by_id(self.state.koma_db.pool(), &operator_id)
@@ -634,7 +783,7 @@ impl EventHandler for Bot {
                 Ok(None) => {
                     let _ = send_gateway_embed(
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &super::render_message("error-failed-load-operator-discord", &[]),
                         None,
                     )
@@ -645,7 +794,7 @@ impl EventHandler for Bot {
                     error!("Failed to load operator: {}", e);
                     let _ = send_gateway_embed(
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &super::render_message("error-failed-load-operator-discord", &[]),
                         None,
                     )
@@ -672,7 +821,7 @@ impl EventHandler for Bot {
                     ids::RATE_LIMITED,
                     &[("retry_after", retry_after.as_str())],
                 );
-                let _ = send_gateway_embed(&ctx, msg.channel_id, &message, None).await;
+                let _ = send_gateway_embed(&ctx, channel_id, &message, None).await;
                 return;
             }
         }
@@ -684,7 +833,7 @@ impl EventHandler for Bot {
                     error!("Failed to get workspace path: {}", e);
                     let _ = send_gateway_embed(
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &super::render_message("error-failed-init-ghost-storage", &[]),
                         None,
                     )
@@ -692,9 +841,26 @@ impl EventHandler for Bot {
                     return;
                 }
             };
+            if route.fresh {
+                // The thread opened for this message already runs a new session.
+                let _typing = TimedTyping::start(channel_id, &ctx.http);
+                self.start_new_session_core(
+                    &ctx,
+                    channel_id,
+                    &ghost_name,
+                    &ghost.id,
+                    &operator_id,
+                    &operator_external_id,
+                    route.previous_session_id.as_deref(),
+                    &session.id,
+                )
+                .await;
+                return;
+            }
             self.handle_new_session(
                 &ctx,
-                &msg,
+                channel_id,
+                route.thread_id,
                 &workspace_path,
                 &ghost.id,
                 &ghost_name,
@@ -714,7 +880,7 @@ impl EventHandler for Bot {
                     error!("Failed to get workspace path: {}", e);
                     let _ = send_gateway_embed(
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &super::render_message("error-failed-init-ghost-storage", &[]),
                         None,
                     )
@@ -726,6 +892,7 @@ impl EventHandler for Bot {
         } else {
             vec![]
         };
+        let ingest_files = super::ingest::ingest_candidates(&attachment_blocks);
 
         self.state
             .log(crate::LogEntry::Routing {
@@ -740,7 +907,7 @@ impl EventHandler for Bot {
             || clean_content.eq_ignore_ascii_case("deny")
             || operator_flow::parse_step_limit(clean_content).is_some()
         {
-            let _typing = TimedTyping::start(msg.channel_id, &ctx.http);
+            let _typing = TimedTyping::start(channel_id, &ctx.http);
             match operator_flow::run_tool_control_command(
                 self.state.as_ref(),
                 Some("discord"),
@@ -756,7 +923,7 @@ impl EventHandler for Bot {
                     send_outbound_messages(
                         self.state.as_ref(),
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &operator_external_id,
                         &operator_id,
                         &ghost_name,
@@ -770,7 +937,7 @@ impl EventHandler for Bot {
                     error!("[session:{}] Chat error: {}", session.id, e);
                     let _ = send_gateway_embed(
                         &ctx,
-                        msg.channel_id,
+                        channel_id,
                         &super::render_message("error-processing-request", &[]),
                         None,
                     )
@@ -780,32 +947,26 @@ impl EventHandler for Bot {
             return;
         }
 
-        let _typing = TimedTyping::start(msg.channel_id, &ctx.http);
+        let _typing = TimedTyping::start(channel_id, &ctx.http);
 
-        // Set up incremental tool call streaming when verbose mode is on
+        // Live status for long turns; also streams tool calls in verbose mode
         let verbose = self.state.is_verbose(&operator_id).await;
-        let (tool_tx, tool_rx): (
-            Option<tokio::sync::mpsc::UnboundedSender<Vec<crate::state::ToolCallSummary>>>,
-            Option<tokio::sync::mpsc::UnboundedReceiver<Vec<crate::state::ToolCallSummary>>>,
-        ) = if verbose {
-            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-            (Some(tx), Some(rx))
-        } else {
-            (None, None)
-        };
-
-        // Spawn a background task to send tool calls as they arrive
-        let tool_stream_handle = tool_rx.map(|mut rx| {
-            let http = ctx.http.clone();
-            let channel_id = msg.channel_id;
-            tokio::spawn(async move {
-                while let Some(calls) = rx.recv().await {
-                    let _ = send_tool_calls_v2(&http, channel_id, &calls).await;
-                }
-            })
+        let cards = self.search_embeds.then(|| SearchCards {
+            state: Arc::clone(&self.state),
+            action: PendingGatewayAction {
+                operator_id: operator_id.clone(),
+                ghost_name: ghost_name.clone(),
+                session_id: session.id.clone(),
+                external_id: operator_external_id.clone(),
+                channel_id: channel_id.get().to_string(),
+                intent: String::new(),
+                payload: None,
+                expires_at: 0,
+            },
         });
+        let status = LiveStatus::start(ctx.http.clone(), channel_id, verbose, cards);
 
-        match operator_flow::run_chat_with_pending_and_attachments(
+        let result = operator_flow::run_chat_with_pending_and_attachments(
             self.state.as_ref(),
             Some("discord"),
             None,
@@ -814,20 +975,17 @@ impl EventHandler for Bot {
             &operator_id,
             clean_content,
             attachment_blocks,
-            tool_tx.as_ref(),
+            Some(status.sender()),
         )
-        .await
-        {
+        .await;
+        status.finish().await;
+
+        match result {
             Ok(messages) => {
-                // Drop the sender so the receiver task sees channel close and exits
-                drop(tool_tx);
-                if let Some(handle) = tool_stream_handle {
-                    let _ = handle.await;
-                }
                 send_outbound_messages(
                     self.state.as_ref(),
                     &ctx,
-                    msg.channel_id,
+                    channel_id,
                     &operator_external_id,
                     &operator_id,
                     &ghost_name,
@@ -835,12 +993,42 @@ impl EventHandler for Bot {
                     messages,
                 )
                 .await;
+                super::ingest::offer_attachment_ingest(
+                    self.state.as_ref(),
+                    &ctx,
+                    channel_id,
+                    &operator_external_id,
+                    &operator_id,
+                    &ghost_name,
+                    &session.id,
+                    &ingest_files,
+                )
+                .await;
+            }
+            Err(ChatError::OverBudget(report)) => {
+                send_outbound_messages(
+                    self.state.as_ref(),
+                    &ctx,
+                    channel_id,
+                    &operator_external_id,
+                    &operator_id,
+                    &ghost_name,
+                    &session.id,
+                    vec![OutboundMessage::gateway(
+                        operator_flow::usage_budget_message(
+                            ids::USAGE_BUDGET_EXCEEDED,
+                            &report,
+                            Some("discord"),
+                        ),
+                    )],
+                )
+                .await;
             }
             Err(e) => {
                 error!("[session:{}] Chat error: {}", session.id, e);
                 let _ = send_gateway_embed(
                     &ctx,
-                    msg.channel_id,
+                    channel_id,
                     &super::render_message("error-processing-request", &[]),
                     None,
                 )
@@ -857,6 +1045,10 @@ impl EventHandler for Bot {
         self.handle_interaction(ctx, interaction).await;
     }
 
+    async fn reaction_add(&self, ctx: Context, reaction: serenity::model::channel::Reaction) {
+        self.handle_reaction(ctx, reaction).await;
+    }
+
     /// Bot is ready — register slash commands
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!("Discord bot connected as {}", ready.user.name);
@@ -871,6 +1063,7 @@ impl EventHandler for Bot {
                         .required(true),
                 ),
             CreateCommand::new("new").description("Start a new session with your ghost"),
+            CreateCommand::new("sessions").description("List your sessions with the active ghost"),
             CreateCommand::new("feedback")
                 .description("Send feedback to the operator")
                 .add_option(
@@ -903,6 +1096,12 @@ impl EventHandler for Bot {
                     )
                     .required(false),
                 ),
+            CreateCommand::new("bind")
+                .description("Bind this channel to one of your ghosts (leave empty to unbind)")
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::String, "ghost", "Ghost name")
+                        .required(false),
+                ),
             CreateCommand::new("statusline")
                 .description("Toggle metadata statusline on ghost responses")
                 .add_option(
@@ -988,6 +1187,23 @@ impl Bot {
         operator_id: &str,
         ghost_name: &str,
     ) {
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            operator_id,
+            &t_koma_db::OperatorPermission::CreateGhosts,
+        )
+        .await
+        {
+            let _ = send_gateway_embed(
+                ctx,
+                channel_id,
+                &super::render_message(ids::GHOST_CREATION_NOT_PERMITTED, &[]),
+                None,
+            )
+            .await;
+            return;
+        }
+
         let ghost = match t_koma_db::GhostRepository::create(
             self.state.koma_db.pool(),
             operator_id,
@@ -1101,6 +1317,116 @@ impl Bot {
         }
     }
 
+    /// Session for a guild message outside a session thread: a new session
+    /// in a thread opened from the message (or in the thread the operator
+    /// created). Falls back to the active session in the channel when
+    /// Discord refuses the thread.
+    async fn open_thread_session(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        ghost: &t_koma_db::Ghost,
+        operator_id: &str,
+        content: &str,
+    ) -> Result<Route, t_koma_db::DbError> {
+        let pool = self.state.koma_db.pool();
+        let in_thread = matches!(
+            msg.channel(ctx).await,
+            Ok(Channel::Guild(channel)) if channel.thread_metadata.is_some()
+        );
+        let thread = if in_thread {
+            Some((msg.channel_id, None))
+        } else {
+            match msg
+                .channel_id
+                .create_thread_from_message(
+                    &ctx.http,
+                    msg.id,
+                    CreateThread::new(thread_name(content, &ghost.name)),
+                )
+                .await
+            {
+                Ok(thread) => Some((thread.id, Some(msg.channel_id))),
+                Err(e) => {
+                    warn!("Failed to open a thread in {}: {}", msg.channel_id, e);
+                    None
+                }
+            }
+        };
+        let Some((thread_id, parent_id)) = thread else {
+            let session =
+                t_koma_db::SessionRepository::get_or_create_active(pool, &ghost.id, operator_id)
+                    .await?;
+            return Ok(Route {
+                session,
+                channel_id: msg.channel_id,
+                thread_id: None,
+                previous_session_id: None,
+                fresh: false,
+            });
+        };
+
+        let previous =
+            t_koma_db::SessionRepository::get_active(pool, &ghost.id, operator_id).await?;
+        let session = t_koma_db::SessionRepository::create(pool, &ghost.id, operator_id).await?;
+        t_koma_db::DiscordThreadRepository::record(
+            pool,
+            &thread_id.to_string(),
+            &session.id,
+            parent_id.map(|id| id.to_string()).as_deref(),
+        )
+        .await?;
+        if let Some(previous) = &previous {
+            operator_flow::queue_reflection_for_previous_session(
+                &self.state,
+                &ghost.id,
+                &previous.id,
+            )
+            .await;
+        }
+        info!(
+            "[session:{}] Discord thread {} opened for {}",
+            session.id, thread_id, ghost.name
+        );
+        Ok(Route {
+            session,
+            channel_id: thread_id,
+            thread_id: Some(thread_id),
+            previous_session_id: previous.map(|previous| previous.id),
+            fresh: true,
+        })
+    }
+
+    /// Point the operator's session thread `channel_id` at `session_id`,
+    /// returning the session it ran before (`None` outside session threads).
+    pub(super) async fn remap_session_thread(
+        &self,
+        channel_id: ChannelId,
+        operator_id: &str,
+        session_id: &str,
+    ) -> Option<String> {
+        if !self.threads {
+            return None;
+        }
+        let pool = self.state.koma_db.pool();
+        let thread_id = channel_id.to_string();
+        let previous_id = t_koma_db::DiscordThreadRepository::find_session(pool, &thread_id)
+            .await
+            .ok()
+            .flatten()?;
+        let previous = t_koma_db::SessionRepository::get_by_id(pool, &previous_id)
+            .await
+            .ok()
+            .flatten()
+            .filter(|session| session.operator_id == operator_id)?;
+        if let Err(e) =
+            t_koma_db::DiscordThreadRepository::record(pool, &thread_id, session_id, None).await
+        {
+            warn!("Failed to map Discord thread {}: {}", thread_id, e);
+        }
+        Some(previous.id)
+    }
+
     async fn is_operator_welcomed(&self, operator_id: &str) -> bool {
         match t_koma_db::OperatorRepository::get_by_id(self.state.koma_db.pool(), operator_id).await
         {
@@ -1160,11 +1486,13 @@ impl Bot {
         .await;
     }
 
+    /// Start a new session; in a session thread, the thread moves to it.
     #[allow(clippy::too_many_arguments)]
     async fn handle_new_session(
         &self,
         ctx: &Context,
-        msg: &Message,
+        channel_id: ChannelId,
+        thread_id: Option<ChannelId>,
         _workspace_path: &std::path::Path,
         ghost_id: &str,
         ghost_name: &str,
@@ -1187,7 +1515,7 @@ impl Bot {
                 );
                 let _ = send_gateway_embed(
                     ctx,
-                    msg.channel_id,
+                    channel_id,
                     &super::render_message("error-init-session-discord", &[]),
                     None,
                 )
@@ -1196,15 +1524,27 @@ impl Bot {
             }
         };
 
-        let _typing = TimedTyping::start(msg.channel_id, &ctx.http);
+        if let Some(thread_id) = thread_id
+            && let Err(e) = t_koma_db::DiscordThreadRepository::record(
+                self.state.koma_db.pool(),
+                &thread_id.to_string(),
+                &new_session.id,
+                None,
+            )
+            .await
+        {
+            warn!("Failed to map Discord thread {}: {}", thread_id, e);
+        }
+
+        let _typing = TimedTyping::start(channel_id, &ctx.http);
         self.start_new_session_core(
             ctx,
-            msg.channel_id,
+            channel_id,
             ghost_name,
             ghost_id,
             operator_id,
             operator_external_id,
-            previous_session_id,
+            Some(previous_session_id),
             &new_session.id,
         )
         .await;
@@ -1223,16 +1563,17 @@ impl Bot {
         ghost_id: &str,
         operator_id: &str,
         operator_external_id: &str,
-        previous_session_id: &str,
+        previous_session_id: Option<&str>,
         new_session_id: &str,
     ) {
-        operator_flow::spawn_reflection_for_previous_session(
-            &self.state,
-            ghost_name,
-            ghost_id,
-            operator_id,
-            previous_session_id,
-        );
+        if let Some(previous_session_id) = previous_session_id {
+            operator_flow::queue_reflection_for_previous_session(
+                &self.state,
+                ghost_id,
+                previous_session_id,
+            )
+            .await;
+        }
 
         match operator_flow::run_chat_with_pending(
             self.state.as_ref(),
@@ -1259,6 +1600,25 @@ impl Bot {
                 )
                 .await;
             }
+            Err(ChatError::OverBudget(report)) => {
+                send_outbound_messages(
+                    self.state.as_ref(),
+                    ctx,
+                    channel_id,
+                    operator_external_id,
+                    operator_id,
+                    ghost_name,
+                    new_session_id,
+                    vec![OutboundMessage::gateway(
+                        operator_flow::usage_budget_message(
+                            ids::USAGE_BUDGET_EXCEEDED,
+                            &report,
+                            Some("discord"),
+                        ),
+                    )],
+                )
+                .await;
+            }
             Err(e) => {
                 error!("[session:{}] Chat error: {}", new_session_id, e);
                 let _ = send_gateway_embed(
@@ -1277,92 +1637,34 @@ impl Bot {
 // File download handling
 // ---------------------------------------------------------------------------
 
-const IMAGE_MIME_TYPES: &[&str] = &[
-    "image/jpeg",
-    "image/png",
-    "image/gif",
-    "image/webp",
-    "image/svg+xml",
-];
-
-fn mime_type_for_filename(filename: &str) -> String {
-    let ext = filename.rsplit('.').next().unwrap_or("").to_lowercase();
-    match ext.as_str() {
-        "jpg" | "jpeg" => "image/jpeg",
-        "png" => "image/png",
-        "gif" => "image/gif",
-        "webp" => "image/webp",
-        "svg" => "image/svg+xml",
-        "csv" => "text/csv",
-        "json" => "application/json",
-        "pdf" => "application/pdf",
-        "txt" | "md" => "text/plain",
-        _ => "application/octet-stream",
-    }
-    .to_string()
-}
-
-fn is_image_mime(mime: &str) -> bool {
-    IMAGE_MIME_TYPES.contains(&mime)
-}
-
 async fn download_to_content_blocks(
     attachments: &[serenity::model::channel::Attachment],
     workspace_path: &std::path::Path,
 ) -> Vec<t_koma_db::ContentBlock> {
-    let download_dir = workspace_path.join("downloads");
-    if let Err(e) = tokio::fs::create_dir_all(&download_dir).await {
-        error!("Failed to create downloads dir: {}", e);
-        return Vec::new();
-    }
+    let download_dir = match attachments::downloads_dir(workspace_path).await {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("Failed to create downloads dir: {}", e);
+            return Vec::new();
+        }
+    };
 
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
     let client = reqwest::Client::new();
     let mut blocks = Vec::new();
 
     for attachment in attachments {
-        let dest_name = format!("{}_{}", timestamp, attachment.filename);
-        let dest_path = download_dir.join(&dest_name);
-
         match client.get(&attachment.url).send().await {
             Ok(resp) => match resp.bytes().await {
                 Ok(bytes) => {
-                    const MAX_FILE_SIZE: usize = 25 * 1024 * 1024; // 25 MB
-                    if bytes.len() > MAX_FILE_SIZE {
-                        warn!(
-                            "Attachment {} exceeds {}MB limit ({} bytes), skipping",
-                            attachment.filename,
-                            MAX_FILE_SIZE / (1024 * 1024),
-                            bytes.len()
-                        );
-                        continue;
-                    }
-
-                    let size = bytes.len() as u64;
-                    if let Err(e) = tokio::fs::write(&dest_path, &bytes).await {
-                        error!("Failed to write attachment {}: {}", dest_name, e);
-                        continue;
-                    }
-
-                    let mime = attachment
-                        .content_type
-                        .clone()
-                        .unwrap_or_else(|| mime_type_for_filename(&attachment.filename));
-
-                    let path_str = dest_path.to_string_lossy().to_string();
-
-                    if is_image_mime(&mime) {
-                        blocks.push(t_koma_db::ContentBlock::Image {
-                            path: path_str,
-                            mime_type: mime,
-                            filename: attachment.filename.clone(),
-                        });
-                    } else {
-                        blocks.push(t_koma_db::ContentBlock::File {
-                            path: path_str,
-                            filename: attachment.filename.clone(),
-                            size,
-                        });
+                    if let Some(block) = attachments::store_attachment(
+                        &download_dir,
+                        &attachment.filename,
+                        attachment.content_type.clone(),
+                        &bytes,
+                    )
+                    .await
+                    {
+                        blocks.push(block);
                     }
                 }
                 Err(e) => error!(
@@ -1376,3 +1678,21 @@ async fn download_to_content_blocks(
 
     blocks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_name_strips_mentions() {
+        assert_eq!(
+            thread_name("<@123> plan the <#456> migration\nmore details", "Nova"),
+            "plan the migration"
+        );
+        assert_eq!(thread_name("<@123>\n\n", "Nova"), "Nova");
+        let long = "word ".repeat(40);
+        let name = thread_name(&long, "Nova");
+        assert_eq!(name.chars().count(), THREAD_NAME_MAX_CHARS);
+        assert!(name.ends_with("word…"));
+    }
+}
@@ -6,7 +6,7 @@
 /// array as layout blocks rather than legacy action rows.
 use serenity::builder::CreateActionRow;
 use serenity::http::Http;
-use serenity::model::id::ChannelId;
+use serenity::model::id::{ChannelId, MessageId};
 use tracing::warn;
 
 /// Components v2 message flag (IS_COMPONENTS_V2 = 1 << 15).
@@ -100,6 +100,22 @@ pub async fn send_v2_message(
     http.send_message(channel_id, attachments, &payload).await
 }
 
+/// Replace the components of a v2 message sent with [`send_v2_message`].
+pub async fn edit_v2_message(
+    http: &Http,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    components: &[serde_json::Value],
+) -> serenity::Result<serenity::model::channel::Message> {
+    let payload = serde_json::json!({
+        "flags": V2_FLAG,
+        "components": components,
+    });
+
+    http.edit_message(channel_id, message_id, &payload, Vec::new())
+        .await
+}
+
 /// Group a flat list of v2 components into message-sized chunks of at most
 /// `MAX_V2_COMPONENTS` each.
 pub fn group_into_v2_messages(components: Vec<serde_json::Value>) -> Vec<Vec<serde_json::Value>> {
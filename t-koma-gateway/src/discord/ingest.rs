@@ -0,0 +1,312 @@
+//! Offer to save text attachments into a reference topic.
+//!
+//! After a turn with text documents attached, the bot posts an auth-gate embed
+//! with an `INGEST` button. The button asks for a topic (select menu of the
+//! existing ones) and each file is then saved with `reference_save`. Nothing
+//! is written to knowledge without the operator going through both steps.
+
+use serde::{Deserialize, Serialize};
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use serenity::model::application::ButtonStyle;
+use serenity::model::id::ChannelId;
+use serenity::prelude::*;
+use tracing::{info, warn};
+
+use crate::attachments;
+use crate::content::ids;
+use crate::state::{AppState, PendingGatewayAction};
+
+use super::send::{APPROVAL_EMBED_COLOR, send_gateway_embed, send_gateway_embed_colored};
+
+/// Model recorded as the author of ingested references.
+const INGEST_MODEL_ID: &str = "discord";
+
+/// Offers and topic choices expire after this many seconds.
+const INGEST_OFFER_TTL_SECS: i64 = 900;
+
+/// A stored attachment that can be saved as a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct IngestFile {
+    pub path: String,
+    pub filename: String,
+}
+
+/// Text documents among an operator message's attachments.
+pub(super) fn ingest_candidates(blocks: &[t_koma_db::ContentBlock]) -> Vec<IngestFile> {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            t_koma_db::ContentBlock::File { path, filename, .. }
+                if attachments::is_text_document(filename) =>
+            {
+                Some(IngestFile {
+                    path: path.clone(),
+                    filename: filename.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn file_list(files: &[IngestFile]) -> String {
+    files
+        .iter()
+        .map(|file| format!("- `{}`", file.filename))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Post the `INGEST` offer for `files` (no-op when there are none).
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn offer_attachment_ingest(
+    state: &AppState,
+    ctx: &Context,
+    channel_id: ChannelId,
+    external_id: &str,
+    operator_id: &str,
+    ghost_name: &str,
+    session_id: &str,
+    files: &[IngestFile],
+) {
+    if files.is_empty() {
+        return;
+    }
+    let payload = match serde_json::to_string(files) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to encode attachment ingest offer: {}", e);
+            return;
+        }
+    };
+
+    let token = uuid::Uuid::new_v4().to_string();
+    state
+        .set_pending_gateway_action(
+            &token,
+            PendingGatewayAction {
+                operator_id: operator_id.to_string(),
+                ghost_name: ghost_name.to_string(),
+                session_id: session_id.to_string(),
+                external_id: external_id.to_string(),
+                channel_id: channel_id.get().to_string(),
+                intent: "knowledge.ingest_prompt".to_string(),
+                payload: Some(payload),
+                expires_at: chrono::Utc::now().timestamp() + INGEST_OFFER_TTL_SECS,
+            },
+        )
+        .await;
+
+    let button = CreateButton::new(format!("tk:a:{}", token))
+        .label("INGEST")
+        .style(ButtonStyle::Primary);
+    let text = super::render_message(
+        ids::DISCORD_ATTACHMENT_INGEST,
+        &[("files", file_list(files).as_str())],
+    );
+    let _ = send_gateway_embed_colored(
+        ctx,
+        channel_id,
+        &text,
+        Some(vec![CreateActionRow::Buttons(vec![button])]),
+        Some(APPROVAL_EMBED_COLOR),
+    )
+    .await;
+}
+
+/// `INGEST` clicked: ask which topic the files go to.
+pub(super) async fn send_topic_select(
+    state: &AppState,
+    ctx: &Context,
+    channel_id: ChannelId,
+    pending: PendingGatewayAction,
+) {
+    let Some(files) = decode_files(pending.payload.as_deref()) else {
+        return;
+    };
+    let topics = match state.knowledge_engine().topic_list(false).await {
+        Ok(topics) => topics,
+        Err(e) => {
+            warn!("Failed to list topics for attachment ingest: {}", e);
+            let _ = send_gateway_embed(
+                ctx,
+                channel_id,
+                &super::render_message(ids::ERROR_PROCESSING_REQUEST, &[]),
+                None,
+            )
+            .await;
+            return;
+        }
+    };
+    if topics.is_empty() {
+        let _ = send_gateway_embed(
+            ctx,
+            channel_id,
+            &super::render_message(ids::DISCORD_ATTACHMENT_INGEST_NO_TOPICS, &[]),
+            None,
+        )
+        .await;
+        return;
+    }
+
+    let token = uuid::Uuid::new_v4().to_string();
+    state
+        .set_pending_gateway_action(
+            &token,
+            PendingGatewayAction {
+                intent: "knowledge.ingest".to_string(),
+                expires_at: chrono::Utc::now().timestamp() + INGEST_OFFER_TTL_SECS,
+                ..pending
+            },
+        )
+        .await;
+
+    let options = topics
+        .iter()
+        .take(25)
+        .map(|topic| CreateSelectMenuOption::new(topic.title.clone(), topic.title.clone()))
+        .collect();
+    let select = CreateSelectMenu::new(
+        format!("tk:s:{}", token),
+        CreateSelectMenuKind::String { options },
+    )
+    .placeholder("Choose a topic");
+    let text = super::render_message(
+        ids::DISCORD_ATTACHMENT_INGEST_TOPIC,
+        &[("files", file_list(&files).as_str())],
+    );
+    let _ = send_gateway_embed(
+        ctx,
+        channel_id,
+        &text,
+        Some(vec![CreateActionRow::SelectMenu(select)]),
+    )
+    .await;
+}
+
+/// Topic chosen: save each file into it with `reference_save`.
+pub(super) async fn ingest_into_topic(
+    state: &AppState,
+    ctx: &Context,
+    channel_id: ChannelId,
+    pending: PendingGatewayAction,
+    topic: Option<String>,
+) {
+    let (Some(files), Some(topic)) = (decode_files(pending.payload.as_deref()), topic) else {
+        return;
+    };
+    let engine = state.knowledge_engine();
+
+    let mut saved = Vec::new();
+    for file in files {
+        let result = match tokio::fs::read_to_string(&file.path).await {
+            Ok(content) => engine
+                .reference_save(
+                    &pending.ghost_name,
+                    INGEST_MODEL_ID,
+                    t_koma_knowledge::ReferenceSaveRequest {
+                        topic: topic.clone(),
+                        path: file.filename.clone(),
+                        content,
+                        source_url: None,
+                        role: Some(t_koma_knowledge::SourceRole::Docs),
+                        title: Some(file.filename.clone()),
+                    },
+                )
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(_) => saved.push(file),
+            Err(error) => {
+                warn!(
+                    "Failed to ingest Discord attachment {} into {}: {}",
+                    file.filename, topic, error
+                );
+                let text = super::render_message(
+                    ids::DISCORD_ATTACHMENT_INGEST_FAILED,
+                    &[
+                        ("filename", file.filename.as_str()),
+                        ("error", error.as_str()),
+                    ],
+                );
+                let _ = send_gateway_embed(ctx, channel_id, &text, None).await;
+            }
+        }
+    }
+
+    if saved.is_empty() {
+        return;
+    }
+    info!(
+        "[session:{}] Ingested {} Discord attachment(s) into {}",
+        pending.session_id,
+        saved.len(),
+        topic
+    );
+    let text = super::render_message(
+        ids::DISCORD_ATTACHMENT_INGESTED,
+        &[
+            ("topic", topic.as_str()),
+            ("files", file_list(&saved).as_str()),
+        ],
+    );
+    let _ = send_gateway_embed(ctx, channel_id, &text, None).await;
+}
+
+fn decode_files(payload: Option<&str>) -> Option<Vec<IngestFile>> {
+    let payload = payload?;
+    match serde_json::from_str(payload) {
+        Ok(files) => Some(files),
+        Err(e) => {
+            warn!("Invalid attachment ingest payload: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingest_candidates_keeps_text_documents() {
+        let blocks = vec![
+            t_koma_db::ContentBlock::File {
+                path: "/ws/downloads/1_notes.md".to_string(),
+                filename: "notes.md".to_string(),
+                size: 10,
+            },
+            t_koma_db::ContentBlock::File {
+                path: "/ws/downloads/1_voice.ogg".to_string(),
+                filename: "voice.ogg".to_string(),
+                size: 10,
+            },
+            t_koma_db::ContentBlock::Image {
+                path: "/ws/downloads/1_shot.png".to_string(),
+                mime_type: "image/png".to_string(),
+                filename: "shot.png".to_string(),
+            },
+            t_koma_db::ContentBlock::File {
+                path: "/ws/downloads/1_data.csv".to_string(),
+                filename: "data.csv".to_string(),
+                size: 10,
+            },
+        ];
+
+        let files = ingest_candidates(&blocks);
+        assert_eq!(
+            files
+                .iter()
+                .map(|f| f.filename.as_str())
+                .collect::<Vec<_>>(),
+            vec!["notes.md", "data.csv"]
+        );
+        let payload = serde_json::to_string(&files).unwrap();
+        assert_eq!(decode_files(Some(&payload)), Some(files));
+        assert_eq!(decode_files(Some("not json")), None);
+    }
+}
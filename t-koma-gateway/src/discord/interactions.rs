@@ -3,10 +3,11 @@ use serenity::builder::{
     CreateModal,
 };
 use serenity::model::application::{InputTextStyle, Interaction};
+use serenity::model::channel::{Reaction, ReactionType};
 use serenity::prelude::*;
 use tracing::error;
 
-use crate::state::PendingGatewayAction;
+use crate::state::{PendingGatewayAction, ReactionAction};
 
 use super::bot::{Bot, handle_interface_choice, run_action_intent};
 
@@ -228,12 +229,149 @@ impl Bot {
                 "new" => self.handle_new_command(&ctx, command).await,
                 "feedback" => self.handle_feedback_command(&ctx, command).await,
                 "model" => self.handle_model_command(&ctx, command).await,
+                "bind" => self.handle_bind_command(&ctx, command).await,
                 "statusline" => self.handle_statusline_command(&ctx, command).await,
+                "sessions" => self.handle_sessions_command(&ctx, command).await,
                 _ => {}
             }
         }
     }
 
+    /// ✅/❌ on an approval request: same as its Approve/Deny buttons.
+    pub(super) async fn handle_reaction(&self, ctx: Context, reaction: Reaction) {
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return;
+        };
+        let Some(user_id) = reaction.user_id else {
+            return;
+        };
+        let Some(action) = self
+            .state
+            .take_reaction_action(
+                &reaction.message_id.to_string(),
+                emoji,
+                &user_id.to_string(),
+            )
+            .await
+        else {
+            return;
+        };
+
+        let pending = match action {
+            ReactionAction::Ready(pending) => pending,
+            ReactionAction::Expired => {
+                let _ = reaction
+                    .channel_id
+                    .say(
+                        &ctx.http,
+                        "This action expired. Please send your command again.",
+                    )
+                    .await;
+                return;
+            }
+        };
+        if pending.channel_id != reaction.channel_id.get().to_string() {
+            return;
+        }
+
+        run_action_intent(
+            self,
+            &ctx,
+            reaction.channel_id,
+            pending.clone(),
+            &pending.intent,
+            None,
+        )
+        .await;
+    }
+
+    /// Handle `/bind`: route the operator's messages in this guild channel to
+    /// one ghost, or remove the binding when no ghost is given.
+    async fn handle_bind_command(
+        &self,
+        ctx: &Context,
+        command: &serenity::model::application::CommandInteraction,
+    ) {
+        let reply = self.bind_channel_reply(command).await;
+        let _ = command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(reply)
+                        .ephemeral(true),
+                ),
+            )
+            .await;
+    }
+
+    async fn bind_channel_reply(
+        &self,
+        command: &serenity::model::application::CommandInteraction,
+    ) -> String {
+        if command.guild_id.is_none() {
+            return "Channels can only be bound in a server.".to_string();
+        }
+        let external_id = command.user.id.to_string();
+        let Some(operator_id) = self.resolve_operator_id(&external_id).await else {
+            return "No operator found for your account.".to_string();
+        };
+        let pool = self.state.koma_db.pool();
+        let channel_id = command.channel_id.to_string();
+
+        let ghost_name = command
+            .data
+            .options
+            .first()
+            .and_then(|o| o.value.as_str())
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        let Some(ghost_name) = ghost_name else {
+            return match t_koma_db::DiscordChannelRepository::unbind_ghost(
+                pool,
+                &channel_id,
+                &operator_id,
+            )
+            .await
+            {
+                Ok(true) => "Channel unbound. Messages here go to your active ghost.".to_string(),
+                Ok(false) => "This channel is not bound to a ghost.".to_string(),
+                Err(e) => format!("Failed to unbind channel: {e}"),
+            };
+        };
+
+        let ghosts = match t_koma_db::GhostRepository::list_by_operator(pool, &operator_id).await {
+            Ok(ghosts) => ghosts,
+            Err(e) => return format!("Failed to load ghosts: {e}"),
+        };
+        let Some(ghost) = ghosts
+            .iter()
+            .find(|g| g.name.eq_ignore_ascii_case(ghost_name))
+        else {
+            let names = ghosts
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return format!("Unknown ghost `{ghost_name}`. Your ghosts: {names}");
+        };
+
+        match t_koma_db::DiscordChannelRepository::bind_ghost(
+            pool,
+            &channel_id,
+            &operator_id,
+            &ghost.id,
+        )
+        .await
+        {
+            Ok(()) => format!(
+                "Channel bound to **{}**. Your mentions here always go to it.",
+                ghost.name
+            ),
+            Err(e) => format!("Failed to bind channel: {e}"),
+        }
+    }
+
     /// Handle `/log` slash command: toggle tool call verbose mode.
     async fn handle_log_command(
         &self,
@@ -400,6 +538,12 @@ impl Bot {
             }
         };
 
+        // `/new` in a session thread moves the thread to the new session.
+        let previous_session_id = self
+            .remap_session_thread(command.channel_id, &operator_id, &new_session.id)
+            .await
+            .unwrap_or_else(|| current_session.id.clone());
+
         // Acknowledge immediately — the ghost response may take a while
         let _ = command
             .create_response(
@@ -419,7 +563,7 @@ impl Bot {
             &ghost.id,
             &operator_id,
             &external_id,
-            &current_session.id,
+            Some(&previous_session_id),
             &new_session.id,
         )
         .await;
@@ -796,6 +940,70 @@ impl Bot {
             .await;
     }
 
+    /// Handle `/sessions`: list recent sessions with the active ghost.
+    async fn handle_sessions_command(
+        &self,
+        ctx: &Context,
+        command: &serenity::model::application::CommandInteraction,
+    ) {
+        let external_id = command.user.id.to_string();
+        let reply = match self.resolve_operator_id(&external_id).await {
+            None => "No operator found for your account.".to_string(),
+            Some(operator_id) => match self.state.get_active_ghost(&operator_id).await {
+                None => "No active ghost. Send a message first to select one.".to_string(),
+                Some(ghost_name) => self.render_session_list(&operator_id, &ghost_name).await,
+            },
+        };
+
+        let _ = command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(reply)
+                        .ephemeral(true),
+                ),
+            )
+            .await;
+    }
+
+    async fn render_session_list(&self, operator_id: &str, ghost_name: &str) -> String {
+        const MAX_LISTED: usize = 10;
+
+        let pool = self.state.koma_db.pool();
+        let ghost = match t_koma_db::GhostRepository::get_by_name(pool, ghost_name).await {
+            Ok(Some(ghost)) => ghost,
+            Ok(None) => return format!("Ghost **{ghost_name}** not found."),
+            Err(e) => return format!("Failed to load ghost: {e}"),
+        };
+        let sessions = match t_koma_db::SessionRepository::list(pool, &ghost.id, operator_id).await
+        {
+            Ok(sessions) => sessions,
+            Err(e) => return format!("Failed to list sessions: {e}"),
+        };
+        if sessions.is_empty() {
+            return format!("No sessions with **{ghost_name}** yet.");
+        }
+
+        let mut lines = vec![format!("Sessions with **{ghost_name}**:")];
+        for session in sessions.iter().take(MAX_LISTED) {
+            let marker = if session.is_active { "▶" } else { "•" };
+            let title = session.title.as_deref().unwrap_or("Untitled");
+            let mut line = format!(
+                "{marker} **{title}** · `{}` · {} msgs · <t:{}:R>",
+                session.id, session.message_count, session.updated_at
+            );
+            if !session.tags.is_empty() {
+                line.push_str(&format!(" · {}", session.tags.join(", ")));
+            }
+            lines.push(line);
+        }
+        if sessions.len() > MAX_LISTED {
+            lines.push(format!("…and {} more", sessions.len() - MAX_LISTED));
+        }
+        lines.join("\n")
+    }
+
     /// Look up the operator ID from a Discord user's external ID.
     async fn resolve_operator_id(&self, external_id: &str) -> Option<String> {
         let iface = t_koma_db::InterfaceRepository::get_by_external_id(
@@ -1,7 +1,10 @@
 mod bot;
 pub(crate) mod components_v2;
+mod ingest;
 mod interactions;
 mod markdown;
+mod progress;
+mod search_card;
 mod send;
 mod table_image;
 
@@ -11,15 +14,23 @@ use serenity::prelude::*;
 use tracing::info;
 
 pub use bot::Bot;
-pub use send::send_approved_operator_ghost_prompt_dm;
+pub(crate) use bot::{format_ghost_list_lines, parse_ghost_selection, persist_ghost_name_to_soul};
+pub use send::{
+    send_approved_operator_ghost_prompt_dm, send_new_operator_notification_to_pms,
+    send_operator_gateway_dm, send_session_gateway_message,
+};
 
 fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
     crate::gateway_message::from_content(id, Some("discord"), vars).text_fallback
 }
 
 /// Start the Discord bot (optional - returns Ok(None) if no token)
+///
+/// With `settings.threads`, each guild-channel conversation runs in its own
+/// thread and session.
 pub async fn start_discord_bot(
     token: Option<String>,
+    settings: &t_koma_core::DiscordSettings,
     state: Arc<crate::state::AppState>,
 ) -> Result<Option<Client>, DiscordError> {
     let token = match token {
@@ -39,9 +50,11 @@ pub async fn start_discord_bot(
 
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+        | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
 
-    let bot = Bot::new(state);
+    let bot = Bot::new(state, settings);
 
     let client = Client::builder(&token, intents)
         .event_handler(bot)
@@ -0,0 +1,228 @@
+//! Live status message for long turns.
+//!
+//! A turn that runs past [`STATUS_EDIT_INTERVAL`] gets a muted status
+//! container that is edited on the same cadence with the elapsed time and the
+//! tool calls made so far, so a long tool loop visibly makes progress. When
+//! the turn ends the container is edited one last time into a short summary.
+//! Turns that finish before the first tick post nothing.
+//!
+//! In verbose mode (`/log`) the detailed tool call containers are still sent
+//! as separate messages (search cards for `knowledge_search`, see
+//! [`super::search_card`]); the status only lists tool names.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::state::ToolCallSummary;
+
+use super::components_v2::{
+    TEXT_DISPLAY_LIMIT, container, edit_v2_message, send_v2_message, text_display,
+};
+use super::search_card::{SearchCards, send_search_card};
+use super::send::send_tool_calls_v2;
+
+/// Delay before the status appears, and between edits.
+const STATUS_EDIT_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Tool calls listed in the status; older ones are counted, not shown.
+const MAX_STATUS_LINES: usize = 12;
+
+/// Same muted accent as the verbose tool call containers.
+const STATUS_COLOR: u32 = 0x4A_4A_52;
+
+const SPINNER: [&str; 4] = ["▰▱▱", "▱▰▱", "▱▱▰", "▱▰▱"];
+
+/// Status message for one turn; dropping the sender finalizes it.
+pub(super) struct LiveStatus {
+    tx: UnboundedSender<Vec<ToolCallSummary>>,
+    handle: JoinHandle<()>,
+}
+
+impl LiveStatus {
+    /// Start tracking a turn in `channel_id`. With `verbose`, tool calls are
+    /// also sent as detailed containers as they arrive, and `cards` renders
+    /// searches as result cards.
+    pub(super) fn start(
+        http: Arc<Http>,
+        channel_id: ChannelId,
+        verbose: bool,
+        cards: Option<SearchCards>,
+    ) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = tokio::spawn(run_status(http, channel_id, verbose, cards, rx));
+        Self { tx, handle }
+    }
+
+    /// Sender handed to the chat loop for tool call progress.
+    pub(super) fn sender(&self) -> &UnboundedSender<Vec<ToolCallSummary>> {
+        &self.tx
+    }
+
+    /// Finalize the status once the turn is over.
+    pub(super) async fn finish(self) {
+        drop(self.tx);
+        let _ = self.handle.await;
+    }
+}
+
+async fn run_status(
+    http: Arc<Http>,
+    channel_id: ChannelId,
+    verbose: bool,
+    cards: Option<SearchCards>,
+    mut rx: UnboundedReceiver<Vec<ToolCallSummary>>,
+) {
+    let started = Instant::now();
+    let mut ticker = tokio::time::interval_at(
+        tokio::time::Instant::now() + STATUS_EDIT_INTERVAL,
+        STATUS_EDIT_INTERVAL,
+    );
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let mut calls: Vec<ToolCallSummary> = Vec::new();
+    let mut message_id: Option<MessageId> = None;
+    let mut frame = 0usize;
+
+    loop {
+        tokio::select! {
+            received = rx.recv() => match received {
+                Some(batch) => {
+                    if verbose {
+                        send_tool_call_batch(&http, channel_id, cards.as_ref(), &batch).await;
+                    }
+                    calls.extend(batch);
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                let text = render_status(&calls, started.elapsed().as_secs(), Some(frame));
+                frame += 1;
+                message_id = publish(&http, channel_id, message_id, &text).await;
+            }
+        }
+    }
+
+    if message_id.is_some() {
+        let text = render_status(&calls, started.elapsed().as_secs(), None);
+        publish(&http, channel_id, message_id, &text).await;
+    }
+}
+
+/// Send a batch of tool calls in order, cards where they apply.
+async fn send_tool_call_batch(
+    http: &Http,
+    channel_id: ChannelId,
+    cards: Option<&SearchCards>,
+    batch: &[ToolCallSummary],
+) {
+    let mut plain: Vec<ToolCallSummary> = Vec::new();
+    for call in batch {
+        if let Some(cards) = cards
+            && call.output.is_some()
+        {
+            let _ = send_tool_calls_v2(http, channel_id, &plain).await;
+            plain.clear();
+            if send_search_card(http, channel_id, cards, call).await {
+                continue;
+            }
+        }
+        plain.push(call.clone());
+    }
+    let _ = send_tool_calls_v2(http, channel_id, &plain).await;
+}
+
+/// Post the status, or edit it in place once posted.
+async fn publish(
+    http: &Http,
+    channel_id: ChannelId,
+    message_id: Option<MessageId>,
+    text: &str,
+) -> Option<MessageId> {
+    let components = vec![container(vec![text_display(text)], Some(STATUS_COLOR))];
+    let result = match message_id {
+        Some(id) => edit_v2_message(http, channel_id, id, &components).await,
+        None => send_v2_message(http, channel_id, &components, Vec::new()).await,
+    };
+    match result {
+        Ok(message) => Some(message.id),
+        Err(e) => {
+            warn!("Failed to update Discord status message: {}", e);
+            message_id
+        }
+    }
+}
+
+/// Status text: a spinner while `frame` is set, a summary once it is `None`.
+fn render_status(calls: &[ToolCallSummary], elapsed_secs: u64, frame: Option<usize>) -> String {
+    let tools = match calls.len() {
+        1 => "1 tool call".to_string(),
+        n => format!("{} tool calls", n),
+    };
+    let Some(frame) = frame else {
+        return format!("`DONE` // 完了 · {}s · {}", elapsed_secs, tools);
+    };
+
+    let mut text = format!(
+        "`WORKING` // 処理中 {} {}s · {}",
+        SPINNER[frame % SPINNER.len()],
+        elapsed_secs,
+        tools
+    );
+    let hidden = calls.len().saturating_sub(MAX_STATUS_LINES);
+    if hidden > 0 {
+        text.push_str(&format!("\n… {} earlier", hidden));
+    }
+    for call in &calls[hidden..] {
+        let arrow = if call.is_error { "⚠" } else { "→" };
+        text.push_str(&format!("\n`{}` {}", call.name, arrow));
+    }
+    text.chars().take(TEXT_DISPLAY_LIMIT).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, is_error: bool) -> ToolCallSummary {
+        ToolCallSummary {
+            name: name.to_string(),
+            input_preview: String::new(),
+            output_preview: String::new(),
+            is_error,
+            output: None,
+        }
+    }
+
+    #[test]
+    fn test_render_status() {
+        assert_eq!(
+            render_status(&[], 2, Some(0)),
+            "`WORKING` // 処理中 ▰▱▱ 2s · 0 tool calls"
+        );
+        assert_eq!(
+            render_status(
+                &[call("web_search", false), call("web_fetch", true)],
+                5,
+                Some(1)
+            ),
+            "`WORKING` // 処理中 ▱▰▱ 5s · 2 tool calls\n`web_search` →\n`web_fetch` ⚠"
+        );
+        assert_eq!(
+            render_status(&[call("read_file", false)], 9, None),
+            "`DONE` // 完了 · 9s · 1 tool call"
+        );
+
+        let many: Vec<_> = (0..MAX_STATUS_LINES + 3)
+            .map(|i| call(&format!("tool_{}", i), false))
+            .collect();
+        let text = render_status(&many, 30, Some(2));
+        assert!(text.contains("\n… 3 earlier\n`tool_3` →"));
+        assert_eq!(text.lines().count(), MAX_STATUS_LINES + 2);
+    }
+}
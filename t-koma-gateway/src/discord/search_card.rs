@@ -0,0 +1,259 @@
+//! `knowledge_search` results as a card.
+//!
+//! When tool calls are shown (`/log verbose`) and `[discord] search_embeds` is
+//! on, a `knowledge_search` call is rendered as its top hits (title, scope,
+//! score) with an `OPEN` button per hit instead of the one-line output
+//! preview. A button fetches the note with `knowledge_get` and posts it.
+
+use std::sync::Arc;
+
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::http::Http;
+use serenity::model::application::ButtonStyle;
+use serenity::model::id::ChannelId;
+use serenity::prelude::*;
+use t_koma_knowledge::models::{KnowledgeGetQuery, KnowledgeScope, KnowledgeSearchResult};
+use tracing::warn;
+
+use crate::state::{AppState, PendingGatewayAction, ToolCallSummary};
+
+use super::components_v2::{action_row_to_json, container, send_v2_message, text_display};
+use super::send::{send_assistant_v2, send_gateway_embed};
+
+/// Hits shown on a card (one button row).
+const MAX_CARD_HITS: usize = 5;
+
+/// Characters of a note posted by `OPEN`.
+const OPEN_NOTE_MAX_CHARS: usize = 3500;
+
+/// Open buttons stay valid this long.
+const OPEN_BUTTON_TTL_SECS: i64 = 3600;
+
+/// Same accent as the tool call containers the card replaces.
+const CARD_COLOR: u32 = 0x4A_4A_52;
+
+/// Who the open buttons belong to: a template for their pending actions.
+pub(super) struct SearchCards {
+    pub state: Arc<AppState>,
+    pub action: PendingGatewayAction,
+}
+
+/// One search hit on the card.
+#[derive(Debug, Clone, PartialEq)]
+struct CardHit {
+    id: String,
+    title: String,
+    scope: &'static str,
+    score: f32,
+}
+
+fn scope_label(scope: KnowledgeScope) -> &'static str {
+    match scope {
+        KnowledgeScope::SharedNote => "shared",
+        KnowledgeScope::GhostNote => "private",
+        KnowledgeScope::SharedReference => "reference",
+        KnowledgeScope::GhostReference => "private reference",
+        KnowledgeScope::GhostDiary => "diary",
+    }
+}
+
+/// Best hits across all categories, highest score first.
+fn card_hits(results: &KnowledgeSearchResult) -> Vec<CardHit> {
+    let notes = results
+        .notes
+        .iter()
+        .chain(&results.references.results)
+        .map(|note| CardHit {
+            id: note.summary.id.clone(),
+            title: note.summary.title.clone(),
+            scope: scope_label(note.summary.scope),
+            score: note.summary.score,
+        });
+    let topics = results.topics.iter().map(|topic| CardHit {
+        id: topic.topic_id.clone(),
+        title: topic.title.clone(),
+        scope: "topic",
+        score: topic.score,
+    });
+    let diary = results.diary.iter().map(|entry| CardHit {
+        id: entry.note_id.clone(),
+        title: entry.date.clone(),
+        scope: "diary",
+        score: entry.score,
+    });
+
+    let mut hits: Vec<CardHit> = notes.chain(topics).chain(diary).collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(MAX_CARD_HITS);
+    hits
+}
+
+fn render_card(input_preview: &str, hits: &[CardHit]) -> String {
+    let mut text = format!("`knowledge_search({})`", input_preview);
+    if hits.is_empty() {
+        text.push_str("\nNo results.");
+    }
+    for (index, hit) in hits.iter().enumerate() {
+        text.push_str(&format!(
+            "\n{}. **{}** · {} · {:.2}",
+            index + 1,
+            hit.title,
+            hit.scope,
+            hit.score
+        ));
+    }
+    text
+}
+
+/// Send `call` as a card. Returns `false` (nothing sent) when it is not a
+/// `knowledge_search` call with readable output.
+pub(super) async fn send_search_card(
+    http: &Http,
+    channel_id: ChannelId,
+    cards: &SearchCards,
+    call: &ToolCallSummary,
+) -> bool {
+    if call.name != "knowledge_search" {
+        return false;
+    }
+    let Some(results) = call
+        .output
+        .as_deref()
+        .and_then(|output| serde_json::from_str::<KnowledgeSearchResult>(output).ok())
+    else {
+        return false;
+    };
+    let hits = card_hits(&results);
+
+    let mut buttons = Vec::with_capacity(hits.len());
+    for (index, hit) in hits.iter().enumerate() {
+        let token = uuid::Uuid::new_v4().to_string();
+        cards
+            .state
+            .set_pending_gateway_action(
+                &token,
+                PendingGatewayAction {
+                    intent: "knowledge.open".to_string(),
+                    payload: Some(hit.id.clone()),
+                    expires_at: chrono::Utc::now().timestamp() + OPEN_BUTTON_TTL_SECS,
+                    ..cards.action.clone()
+                },
+            )
+            .await;
+        buttons.push(
+            CreateButton::new(format!("tk:a:{}", token))
+                .label(format!("OPEN {}", index + 1))
+                .style(ButtonStyle::Secondary),
+        );
+    }
+
+    let mut inner = vec![text_display(&render_card(&call.input_preview, &hits))];
+    if !buttons.is_empty() {
+        inner.push(action_row_to_json(&CreateActionRow::Buttons(buttons)));
+    }
+    let components = vec![container(inner, Some(CARD_COLOR))];
+    if let Err(e) = send_v2_message(http, channel_id, &components, Vec::new()).await {
+        warn!("v2 search card failed: {}", e);
+    }
+    true
+}
+
+/// `OPEN` clicked: post the note behind a card hit.
+pub(super) async fn open_note(
+    state: &AppState,
+    ctx: &Context,
+    channel_id: ChannelId,
+    pending: PendingGatewayAction,
+) {
+    let Some(note_id) = pending.payload else {
+        return;
+    };
+    let query = KnowledgeGetQuery {
+        id: Some(note_id.clone()),
+        topic: None,
+        path: None,
+        max_chars: Some(OPEN_NOTE_MAX_CHARS),
+    };
+    match state
+        .knowledge_engine()
+        .knowledge_get(&pending.ghost_name, query)
+        .await
+    {
+        Ok(note) => {
+            let text = format!("## {}\n\n{}", note.title, note.body);
+            let _ = send_assistant_v2(&ctx.http, channel_id, &text).await;
+        }
+        Err(e) => {
+            warn!("Failed to open note {} from search card: {}", note_id, e);
+            let _ = send_gateway_embed(
+                ctx,
+                channel_id,
+                &format!("`NOTE` {} could not be opened: {}", note_id, e),
+                None,
+            )
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note(id: &str, title: &str, scope: &str, score: f32) -> serde_json::Value {
+        json!({
+            "summary": {
+                "id": id,
+                "title": title,
+                "entry_type": "Note",
+                "archetype": null,
+                "path": "x.md",
+                "scope": scope,
+                "trust_score": 5,
+                "score": score,
+                "snippet": "",
+            },
+            "parents": [],
+            "links_out": [],
+            "links_in": [],
+            "tags": [],
+        })
+    }
+
+    #[test]
+    fn test_card_hits_rank_across_categories() {
+        let output = json!({
+            "notes": [
+                note("n1", "Deploy checklist", "SharedNote", 0.4),
+                note("n2", "My habits", "GhostNote", 0.9),
+            ],
+            "diary": [{"date": "2026-03-01", "score": 0.5, "snippet": "", "note_id": "d1"}],
+            "references": {
+                "matched_topic": null,
+                "results": [note("r1", "api.md", "SharedReference", 0.7)],
+            },
+            "topics": [{"topic_id": "t1", "title": "Rust", "tags": [], "score": 0.2, "snippet": ""}],
+        });
+        let results: KnowledgeSearchResult = serde_json::from_value(output).unwrap();
+
+        let hits = card_hits(&results);
+        assert_eq!(
+            hits.iter()
+                .map(|hit| (hit.id.as_str(), hit.scope))
+                .collect::<Vec<_>>(),
+            vec![
+                ("n2", "private"),
+                ("r1", "reference"),
+                ("d1", "diary"),
+                ("n1", "shared"),
+                ("t1", "topic"),
+            ]
+        );
+        assert_eq!(
+            render_card("{\"query\":\"x\"}", &hits[..1]),
+            "`knowledge_search({\"query\":\"x\"})`\n1. **My habits** · private · 0.90"
+        );
+        assert_eq!(render_card("q", &[]), "`knowledge_search(q)`\nNo results.");
+    }
+}
@@ -1,6 +1,7 @@
 use serenity::builder::{CreateActionRow, CreateAttachment, CreateEmbed, CreateMessage};
 use serenity::http::Http;
-use serenity::model::id::ChannelId;
+use serenity::model::channel::ReactionType;
+use serenity::model::id::{ChannelId, MessageId};
 use serenity::prelude::*;
 use tracing::{debug, error, trace, warn};
 
@@ -18,6 +19,8 @@ const DISCORD_EMBED_DESC_LIMIT: usize = 4096;
 pub const GATEWAY_EMBED_COLOR: u32 = 0x12_83_D8;
 pub const WARNING_EMBED_COLOR: u32 = 0xE0_3B_24;
 pub const APPROVAL_EMBED_COLOR: u32 = 0xF2_99_4A;
+pub const APPROVE_REACTION: &str = "✅";
+pub const DENY_REACTION: &str = "❌";
 
 // ---------------------------------------------------------------------------
 // v2 assistant text (ghost responses)
@@ -123,6 +126,19 @@ pub async fn send_gateway_v2(
     action_rows: Option<Vec<CreateActionRow>>,
     color: Option<u32>,
 ) -> serenity::Result<()> {
+    send_gateway_v2_message(http, channel_id, content, action_rows, color)
+        .await
+        .map(|_| ())
+}
+
+/// [`send_gateway_v2`], returning the (first) message sent.
+async fn send_gateway_v2_message(
+    http: &Http,
+    channel_id: ChannelId,
+    content: &str,
+    action_rows: Option<Vec<CreateActionRow>>,
+    color: Option<u32>,
+) -> serenity::Result<Option<MessageId>> {
     let mut inner = vec![text_display(&format!(
         "**T-KOMA // ティコマ**\n\n{}",
         content
@@ -138,7 +154,7 @@ pub async fn send_gateway_v2(
     let message_components = vec![container(inner, Some(accent))];
 
     match send_v2_message(http, channel_id, &message_components, Vec::new()).await {
-        Ok(_) => Ok(()),
+        Ok(message) => Ok(Some(message.id)),
         Err(e) => {
             warn!("v2 gateway message failed, falling back to embed: {}", e);
             send_gateway_embed_http(http, channel_id, content, action_rows, color).await
@@ -365,8 +381,9 @@ async fn send_gateway_embed_http(
     content: &str,
     components: Option<Vec<CreateActionRow>>,
     color: Option<u32>,
-) -> serenity::Result<()> {
+) -> serenity::Result<Option<MessageId>> {
     let chunks = split_discord_embed_description(content);
+    let mut first = None;
     for (index, chunk) in chunks.iter().enumerate() {
         let title = if index == 0 {
             "T-KOMA // ティコマ"
@@ -384,9 +401,10 @@ async fn send_gateway_embed_http(
         {
             msg = msg.components(c);
         }
-        channel_id.send_message(http, msg).await?;
+        let sent = channel_id.send_message(http, msg).await?;
+        first.get_or_insert(sent.id);
     }
-    Ok(())
+    Ok(first)
 }
 
 // ---------------------------------------------------------------------------
@@ -405,11 +423,17 @@ pub async fn send_discord_gateway_message(
     message: t_koma_core::GatewayMessage,
 ) -> serenity::Result<()> {
     let mut action_rows: Vec<CreateActionRow> = Vec::new();
+    let mut reactions: Vec<(&str, String)> = Vec::new();
 
     if !message.actions.is_empty() {
         let mut buttons = Vec::new();
         for action in message.actions.iter().take(5) {
             let token = uuid::Uuid::new_v4().to_string();
+            if message.kind == t_koma_core::GatewayMessageKind::ApprovalRequest
+                && let Some(emoji) = reaction_for_intent(&action.intent)
+            {
+                reactions.push((emoji, token.clone()));
+            }
             state
                 .set_pending_gateway_action(
                     &token,
@@ -457,14 +481,44 @@ pub async fn send_discord_gateway_message(
         t_koma_core::GatewayMessageKind::Warning => Some(WARNING_EMBED_COLOR),
         _ => Some(GATEWAY_EMBED_COLOR),
     };
-    send_gateway_v2(
+    let sent = send_gateway_v2_message(
         &ctx.http,
         channel_id,
         &message.text_fallback,
         action_rows,
         color,
     )
-    .await
+    .await?;
+
+    // Reactions work like the buttons, and are easier to hit on mobile.
+    if let Some(message_id) = sent {
+        for (emoji, token) in reactions {
+            state
+                .set_reaction_action(&message_id.to_string(), emoji, &token)
+                .await;
+            let reaction = ReactionType::Unicode(emoji.to_string());
+            if let Err(e) = ctx
+                .http
+                .create_reaction(channel_id, message_id, &reaction)
+                .await
+            {
+                warn!(
+                    "Failed to add {} reaction to approval request: {}",
+                    emoji, e
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reaction standing in for an approval button.
+pub(super) fn reaction_for_intent(intent: &str) -> Option<&'static str> {
+    match intent {
+        "approval.approve" => Some(APPROVE_REACTION),
+        "approval.deny" => Some(DENY_REACTION),
+        _ => None,
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -696,28 +750,11 @@ pub async fn send_approved_operator_ghost_prompt_dm(
         return Ok(false);
     }
 
-    let interfaces =
-        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), operator_id)
-            .await
-            .map_err(|e| e.to_string())?;
-    let Some(discord_iface) = interfaces
-        .into_iter()
-        .find(|iface| iface.platform == t_koma_db::Platform::Discord)
-    else {
+    let http = serenity::http::Http::new(discord_bot_token);
+    let Some((external_id, dm)) = operator_dm_channel(state, &http, operator_id).await? else {
         return Ok(false);
     };
 
-    let user_id_raw: u64 = discord_iface
-        .external_id
-        .parse()
-        .map_err(|_| format!("invalid discord external_id for operator {}", operator_id))?;
-    let user_id = serenity::model::id::UserId::new(user_id_raw);
-    let http = serenity::http::Http::new(discord_bot_token);
-    let dm = user_id
-        .create_dm_channel(&http)
-        .await
-        .map_err(|e| e.to_string())?;
-
     let token = uuid::Uuid::new_v4().to_string();
     state
         .set_pending_gateway_action(
@@ -726,8 +763,8 @@ pub async fn send_approved_operator_ghost_prompt_dm(
                 operator_id: operator_id.to_string(),
                 ghost_name: String::new(),
                 session_id: String::new(),
-                external_id: discord_iface.external_id.clone(),
-                channel_id: dm.id.get().to_string(),
+                external_id,
+                channel_id: dm.get().to_string(),
                 intent: "ghost.name_prompt".to_string(),
                 payload: None,
                 expires_at: chrono::Utc::now().timestamp() + 900,
@@ -742,7 +779,7 @@ pub async fn send_approved_operator_ghost_prompt_dm(
     let text = super::render_message(ids::GHOST_NAME_PROMPT, &[]);
     send_gateway_v2(
         &http,
-        dm.id,
+        dm,
         &text,
         Some(vec![CreateActionRow::Buttons(vec![button])]),
         Some(GATEWAY_EMBED_COLOR),
@@ -756,3 +793,88 @@ pub async fn send_approved_operator_ghost_prompt_dm(
 
     Ok(true)
 }
+
+/// DM a gateway message to an operator outside of a chat turn.
+///
+/// Returns `Ok(false)` when the operator has no Discord interface.
+pub async fn send_operator_gateway_dm(
+    state: &AppState,
+    discord_bot_token: &str,
+    operator_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<bool, String> {
+    let http = serenity::http::Http::new(discord_bot_token);
+    let Some((_, dm)) = operator_dm_channel(state, &http, operator_id).await? else {
+        return Ok(false);
+    };
+    send_gateway_v2(
+        &http,
+        dm,
+        &message.text_fallback,
+        None,
+        Some(GATEWAY_EMBED_COLOR),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Post a gateway message about `session_id` outside of a chat turn: into the
+/// session's thread when it has one, otherwise as a DM to the operator.
+///
+/// Returns `Ok(false)` when neither is available.
+pub async fn send_session_gateway_message(
+    state: &AppState,
+    discord_bot_token: &str,
+    operator_id: &str,
+    session_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<bool, String> {
+    let thread =
+        t_koma_db::DiscordThreadRepository::thread_for_session(state.koma_db.pool(), session_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(thread_id) = thread.and_then(|thread| thread.thread_id.parse::<u64>().ok()) else {
+        return send_operator_gateway_dm(state, discord_bot_token, operator_id, message).await;
+    };
+    let http = serenity::http::Http::new(discord_bot_token);
+    send_gateway_v2(
+        &http,
+        ChannelId::new(thread_id),
+        &message.text_fallback,
+        None,
+        Some(GATEWAY_EMBED_COLOR),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Open a DM with the operator's Discord user, returning its external id and channel.
+async fn operator_dm_channel(
+    state: &AppState,
+    http: &Http,
+    operator_id: &str,
+) -> Result<Option<(String, ChannelId)>, String> {
+    let interfaces =
+        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), operator_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(discord_iface) = interfaces
+        .into_iter()
+        .find(|iface| iface.platform == t_koma_db::Platform::Discord)
+    else {
+        return Ok(None);
+    };
+
+    let user_id_raw: u64 = discord_iface
+        .external_id
+        .parse()
+        .map_err(|_| format!("invalid discord external_id for operator {}", operator_id))?;
+    let user_id = serenity::model::id::UserId::new(user_id_raw);
+    let dm = user_id
+        .create_dm_channel(http)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some((discord_iface.external_id, dm.id)))
+}
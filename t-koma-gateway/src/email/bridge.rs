@@ -0,0 +1,797 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, error, info, warn};
+
+use crate::attachments;
+use crate::content::{self, ids};
+use crate::discord::{format_ghost_list_lines, parse_ghost_selection, persist_ghost_name_to_soul};
+use crate::operator_flow::{self, OutboundMessage};
+use crate::session::ChatError;
+use crate::state::{AppState, RateLimitDecision};
+
+use super::mime::{self, ParsedMail};
+use super::send::{Thread, render_outbound, send_mail};
+use super::{EmailError, Mailer};
+
+/// Upper bound for one IMAP poll (login, search, fetch, logout).
+const POLL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Messages fetched per poll; the rest wait for the next one.
+const MAX_MAILS_PER_POLL: usize = 20;
+
+/// Model id recorded on knowledge ingested from mail attachments.
+const INGEST_MODEL_ID: &str = "email";
+
+/// Email bridge
+///
+/// Polls the mailbox over IMAP and answers over SMTP. Each email thread is
+/// its own session: a mail that replies to a known Message-ID continues
+/// that session, any other mail starts a new one. Like the chat bots, it
+/// only adapts mail to the shared operator flow.
+pub struct Bridge {
+    state: Arc<AppState>,
+    mailer: Mailer,
+}
+
+impl Bridge {
+    pub(super) fn new(state: Arc<AppState>, mailer: Mailer) -> Self {
+        Self { state, mailer }
+    }
+
+    /// Poll until the task is aborted.
+    pub async fn run(self: Arc<Self>) {
+        let interval = Duration::from_secs(self.mailer.settings().poll_interval_secs.max(10));
+        loop {
+            match tokio::time::timeout(POLL_TIMEOUT, self.fetch_unseen()).await {
+                Ok(Ok(mails)) => {
+                    for raw in mails {
+                        self.handle_mail(mime::parse(&raw)).await;
+                    }
+                }
+                Ok(Err(e)) => warn!("Email poll failed: {}", e),
+                Err(_) => warn!("Email poll timed out"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Fetch unseen mail and mark it seen before anything is handled, so a
+    /// failing turn is never answered twice.
+    async fn fetch_unseen(&self) -> Result<Vec<Vec<u8>>, EmailError> {
+        let mut session = self.mailer.open_mailbox().await?;
+        let mut mails = Vec::new();
+        for uid in session
+            .search_unseen()
+            .await?
+            .into_iter()
+            .take(MAX_MAILS_PER_POLL)
+        {
+            let raw = session.fetch(uid).await?;
+            session.mark_seen(uid).await?;
+            mails.extend(raw);
+        }
+        session.logout().await;
+        Ok(mails)
+    }
+
+    async fn send_text(&self, thread: &Thread, text: &str) {
+        if let Err(e) = send_mail(&self.state, &self.mailer, thread, None, text).await {
+            error!("Failed to send email to {}: {}", thread.to, e);
+        }
+    }
+
+    async fn send_content(&self, thread: &Thread, id: &str) {
+        self.send_text(thread, &super::render_message(id, &[]))
+            .await;
+    }
+
+    async fn handle_mail(&self, mail: ParsedMail) {
+        let Some(sender) = mail.from.clone() else {
+            debug!("Ignoring email without a sender");
+            return;
+        };
+        // Never answer bounces, auto-replies or our own mail: that is how
+        // mail loops start.
+        if mail.auto_generated || sender.address == self.mailer.address() {
+            debug!("Ignoring automatic email from {}", sender.address);
+            return;
+        }
+        if !self.mailer.settings().sender_allowed(&sender.address) {
+            info!(
+                "Ignoring email from {} (not in allowed_senders)",
+                sender.address
+            );
+            return;
+        }
+
+        let operator_external_id = sender.address.as_str();
+        let platform = t_koma_db::Platform::Email;
+        let content = mime::strip_quoted_reply(&mail.text);
+        let clean_content = content.as_str();
+        let mut thread = Thread::reply_to(&mail, operator_external_id);
+
+        info!(
+            event_kind = "chat_io",
+            "[session:-] Email from {} ({}): {}", operator_external_id, mail.subject, content
+        );
+
+        if clean_content.is_empty() && mail.attachments.is_empty() {
+            return;
+        }
+
+        let interface = match t_koma_db::InterfaceRepository::get_by_external_id(
+            self.state.koma_db.pool(),
+            platform,
+            operator_external_id,
+        )
+        .await
+        {
+            Ok(found) => found,
+            Err(e) => {
+                error!("Failed to load interface {}: {}", operator_external_id, e);
+                self.send_content(&thread, ids::ERROR_GENERIC).await;
+                return;
+            }
+        };
+
+        let Some(interface) = interface else {
+            if !self
+                .state
+                .is_interface_pending(platform, operator_external_id)
+                .await
+            {
+                self.state
+                    .set_interface_pending(platform, operator_external_id)
+                    .await;
+                self.send_content(&thread, ids::EMAIL_INTERFACE_PROMPT)
+                    .await;
+                return;
+            }
+            let choice = clean_content.split_whitespace().next().unwrap_or_default();
+            let operator_name = sender.name.as_deref().unwrap_or(operator_external_id);
+            self.handle_interface_choice(&thread, operator_external_id, operator_name, choice)
+                .await;
+            return;
+        };
+
+        let operator = match t_koma_db::OperatorRepository::get_by_id(
+            self.state.koma_db.pool(),
+            &interface.operator_id,
+        )
+        .await
+        {
+            Ok(Some(op)) => op,
+            Ok(None) => {
+                error!(
+                    "Interface references missing operator {}",
+                    interface.operator_id
+                );
+                self.send_content(&thread, ids::INTERFACE_INVALID_OPERATOR)
+                    .await;
+                return;
+            }
+            Err(e) => {
+                error!("Failed to load operator {}: {}", interface.operator_id, e);
+                self.send_content(&thread, ids::FAILED_LOAD_OPERATOR).await;
+                return;
+            }
+        };
+
+        match operator.status {
+            t_koma_db::OperatorStatus::Pending => {
+                self.send_content(&thread, ids::ACCESS_PENDING).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Denied => {
+                self.send_content(&thread, ids::ACCESS_DENIED).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Approved => {}
+        }
+
+        let operator_id = operator.id.clone();
+
+        let ghosts = match t_koma_db::GhostRepository::list_by_operator(
+            self.state.koma_db.pool(),
+            &operator_id,
+        )
+        .await
+        {
+            Ok(list) => list,
+            Err(e) => {
+                error!("Failed to list ghosts for operator {}: {}", operator_id, e);
+                self.send_content(&thread, ids::ERROR_FAILED_LOAD_GHOSTS)
+                    .await;
+                return;
+            }
+        };
+
+        if ghosts.is_empty() {
+            // The first non-empty line is the ghost name.
+            let ghost_name = clean_content.lines().next().unwrap_or_default().trim();
+            self.handle_no_ghosts(&thread, &operator, ghost_name).await;
+            return;
+        }
+
+        if let Some(selection) = parse_ghost_selection(clean_content) {
+            if let Some(ghost) = ghosts.iter().find(|g| g.name == selection) {
+                self.state.set_active_ghost(&operator_id, &ghost.name).await;
+                let response = super::render_message(
+                    ids::ACTIVE_GHOST_SET,
+                    &[("ghost_name", ghost.name.as_str())],
+                );
+                self.send_text(&thread, &response).await;
+                return;
+            }
+
+            let list_rows = format_ghost_list_lines(&ghosts);
+            let list =
+                super::render_message(ids::GHOST_LIST, &[("ghost_list", list_rows.as_str())]);
+            let response =
+                super::render_message(ids::UNKNOWN_GHOST_NAME, &[("ghost_list", list.as_str())]);
+            self.send_text(&thread, &response).await;
+            return;
+        }
+
+        let pool = self.state.koma_db.pool();
+        let known_session = match t_koma_db::EmailThreadRepository::find_session(
+            pool,
+            &mail.ancestor_ids(),
+        )
+        .await
+        {
+            Ok(Some(session_id)) => t_koma_db::SessionRepository::get_by_id(pool, &session_id)
+                .await
+                .ok()
+                .flatten()
+                // A forged References header must not reach someone else's session.
+                .filter(|session| session.operator_id == operator_id),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to look up email thread: {}", e);
+                None
+            }
+        };
+
+        let (ghost, session) = match known_session {
+            Some(session) => {
+                match t_koma_db::GhostRepository::get_by_id(pool, &session.ghost_id).await {
+                    Ok(Some(ghost)) => (ghost, session),
+                    _ => {
+                        error!("Ghost not found for session {}", session.id);
+                        self.send_content(&thread, ids::ERROR_FAILED_LOAD_GHOSTS)
+                            .await;
+                        return;
+                    }
+                }
+            }
+            None => {
+                let tagged = subject_tag(&mail.subject)
+                    .and_then(|tag| ghosts.iter().find(|g| g.name.eq_ignore_ascii_case(tag)));
+                let ghost = if let Some(ghost) = tagged {
+                    ghost.clone()
+                } else if ghosts.len() == 1 {
+                    ghosts[0].clone()
+                } else if let Some(active) = self.state.get_active_ghost(&operator_id).await
+                    && let Some(ghost) = ghosts.iter().find(|g| g.name == active)
+                {
+                    ghost.clone()
+                } else {
+                    let list_rows = format_ghost_list_lines(&ghosts);
+                    let prompt = super::render_message(
+                        ids::EMAIL_SELECT_GHOST_PROMPT,
+                        &[("ghost_list", list_rows.as_str())],
+                    );
+                    self.send_text(&thread, &prompt).await;
+                    return;
+                };
+                match self.start_thread_session(&ghost, &operator_id).await {
+                    Ok(session) => (ghost, session),
+                    Err(e) => {
+                        error!(
+                            "Failed to create session for operator {}: {}",
+                            operator_id, e
+                        );
+                        self.send_content(&thread, ids::FAILED_INIT_SESSION).await;
+                        return;
+                    }
+                }
+            }
+        };
+        let ghost_name = ghost.name.clone();
+
+        thread.session_id = Some(session.id.clone());
+        if let Some(message_id) = &mail.message_id
+            && let Err(e) = t_koma_db::EmailThreadRepository::record(
+                pool,
+                message_id,
+                &session.id,
+                &mail.subject,
+            )
+            .await
+        {
+            warn!("Failed to record email {}: {}", message_id, e);
+        }
+
+        match self.state.check_operator_rate_limit(&operator).await {
+            RateLimitDecision::Allowed => {}
+            RateLimitDecision::Limited { retry_after } => {
+                if !clean_content.eq_ignore_ascii_case("continue") {
+                    self.state
+                        .store_pending_message(
+                            &operator_id,
+                            &ghost_name,
+                            &session.id,
+                            clean_content,
+                        )
+                        .await;
+                }
+                let retry_after = retry_after.as_secs().to_string();
+                let message = super::render_message(
+                    ids::RATE_LIMITED,
+                    &[("retry_after", retry_after.as_str())],
+                );
+                self.send_text(&thread, &message).await;
+                return;
+            }
+        }
+
+        let attachment_blocks = if mail.attachments.is_empty() {
+            vec![]
+        } else {
+            let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+                Ok(path) => path,
+                Err(e) => {
+                    error!("Failed to get workspace path: {}", e);
+                    self.send_content(&thread, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                        .await;
+                    return;
+                }
+            };
+            let blocks = store_attachments(&mail, &workspace_path).await;
+            self.ingest_attachments(&ghost, &session.id, &blocks).await;
+            blocks
+        };
+
+        self.state
+            .log(crate::LogEntry::Routing {
+                platform: "email".to_string(),
+                operator_id: operator_id.clone(),
+                ghost_name: ghost_name.clone(),
+                session_id: session.id.clone(),
+            })
+            .await;
+
+        let result = if clean_content.eq_ignore_ascii_case("approve")
+            || clean_content.eq_ignore_ascii_case("deny")
+            || operator_flow::parse_step_limit(clean_content).is_some()
+        {
+            operator_flow::run_tool_control_command(
+                self.state.as_ref(),
+                Some("email"),
+                None,
+                &ghost_name,
+                &session.id,
+                &operator_id,
+                clean_content,
+            )
+            .await
+            .map(Option::unwrap_or_default)
+        } else {
+            self.run_chat(
+                &ghost_name,
+                &session.id,
+                &operator_id,
+                clean_content,
+                attachment_blocks,
+            )
+            .await
+        };
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(ChatError::OverBudget(report)) => vec![OutboundMessage::gateway(
+                operator_flow::usage_budget_message(
+                    ids::USAGE_BUDGET_EXCEEDED,
+                    &report,
+                    Some("email"),
+                ),
+            )],
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(&thread, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+                return;
+            }
+        };
+
+        // One reply per turn: mail is not a chat stream.
+        let body = render_outbound(&messages);
+        if body.is_empty() {
+            return;
+        }
+        if let Err(e) =
+            send_mail(&self.state, &self.mailer, &thread, Some(&ghost_name), &body).await
+        {
+            error!("[session:{}] Failed to send email: {}", session.id, e);
+        }
+    }
+
+    /// Run one chat turn; in verbose mode tool calls go into the same reply.
+    async fn run_chat(
+        &self,
+        ghost_name: &str,
+        session_id: &str,
+        operator_id: &str,
+        content: &str,
+        attachments: Vec<t_koma_db::ContentBlock>,
+    ) -> Result<Vec<OutboundMessage>, ChatError> {
+        let (tool_tx, mut tool_rx) = if self.state.is_verbose(operator_id).await {
+            let (tx, rx) =
+                tokio::sync::mpsc::unbounded_channel::<Vec<crate::state::ToolCallSummary>>();
+            (Some(tx), Some(rx))
+        } else {
+            (None, None)
+        };
+
+        let result = operator_flow::run_chat_with_pending_and_attachments(
+            self.state.as_ref(),
+            Some("email"),
+            None,
+            ghost_name,
+            session_id,
+            operator_id,
+            content,
+            attachments,
+            tool_tx.as_ref(),
+        )
+        .await;
+        drop(tool_tx);
+
+        let mut messages = Vec::new();
+        if let Some(rx) = tool_rx.as_mut() {
+            while let Ok(calls) = rx.try_recv() {
+                messages.push(OutboundMessage::ToolCalls(calls));
+            }
+        }
+        messages.extend(result?);
+        Ok(messages)
+    }
+
+    /// New thread, new session: reflect on the one it replaces.
+    async fn start_thread_session(
+        &self,
+        ghost: &t_koma_db::Ghost,
+        operator_id: &str,
+    ) -> Result<t_koma_db::Session, t_koma_db::DbError> {
+        let pool = self.state.koma_db.pool();
+        let previous =
+            t_koma_db::SessionRepository::get_active(pool, &ghost.id, operator_id).await?;
+        let session = t_koma_db::SessionRepository::create(pool, &ghost.id, operator_id).await?;
+        if let Some(previous) = previous {
+            operator_flow::queue_reflection_for_previous_session(
+                &self.state,
+                &ghost.id,
+                &previous.id,
+            )
+            .await;
+        }
+        Ok(session)
+    }
+
+    /// Queue text attachments for ingestion into the configured topic.
+    async fn ingest_attachments(
+        &self,
+        ghost: &t_koma_db::Ghost,
+        session_id: &str,
+        blocks: &[t_koma_db::ContentBlock],
+    ) {
+        let Some(topic) = self.mailer.settings().ingest_topic.clone() else {
+            return;
+        };
+        let items: Vec<t_koma_knowledge::IngestItem> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                t_koma_db::ContentBlock::File { path, filename, .. }
+                    if attachments::is_text_document(filename) =>
+                {
+                    Some(t_koma_knowledge::IngestItem {
+                        source: t_koma_knowledge::IngestSource::File {
+                            path: PathBuf::from(path),
+                        },
+                        path: None,
+                        title: Some(filename.clone()),
+                        role: None,
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+        let request = t_koma_knowledge::IngestBatchRequest {
+            topic: topic.clone(),
+            items,
+        };
+        if let Err(e) = self
+            .state
+            .knowledge_engine()
+            .check_ingest_batch(&request)
+            .await
+        {
+            warn!("Failed to ingest email attachments into {}: {}", topic, e);
+            return;
+        }
+        let owner = crate::ingest_job::IngestJobOwner {
+            ghost_id: ghost.id.clone(),
+            ghost_name: ghost.name.clone(),
+            session_id: session_id.to_string(),
+        };
+        if let Err(e) = crate::ingest_job::queue_ingest_job(
+            self.state.koma_db.pool(),
+            owner,
+            INGEST_MODEL_ID,
+            request,
+        )
+        .await
+        {
+            warn!("Failed to queue email ingest job: {}", e);
+        }
+    }
+
+    async fn handle_interface_choice(
+        &self,
+        thread: &Thread,
+        operator_external_id: &str,
+        operator_name: &str,
+        choice: &str,
+    ) {
+        let platform = t_koma_db::Platform::Email;
+        let normalized = choice.trim().to_lowercase();
+
+        if normalized == "existing" {
+            self.send_content(thread, ids::EXISTING_OPERATOR_TODO).await;
+            return;
+        }
+
+        if normalized != "new" {
+            self.send_content(thread, ids::EMAIL_INTERFACE_PROMPT).await;
+            return;
+        }
+
+        let operator = match t_koma_db::OperatorRepository::create_new(
+            self.state.koma_db.pool(),
+            operator_name,
+            platform,
+            t_koma_db::OperatorAccessLevel::Standard,
+        )
+        .await
+        {
+            Ok(op) => op,
+            Err(e) => {
+                error!("Failed to create operator: {}", e);
+                self.send_content(thread, ids::FAILED_CREATE_OPERATOR).await;
+                return;
+            }
+        };
+
+        if let Err(e) = t_koma_db::InterfaceRepository::create(
+            self.state.koma_db.pool(),
+            &operator.id,
+            platform,
+            operator_external_id,
+            operator_name,
+        )
+        .await
+        {
+            error!("Failed to create interface: {}", e);
+            self.send_content(thread, ids::FAILED_CREATE_INTERFACE)
+                .await;
+            return;
+        }
+
+        self.state
+            .clear_interface_pending(platform, operator_external_id)
+            .await;
+
+        self.send_content(thread, ids::OPERATOR_CREATED_AWAITING_APPROVAL)
+            .await;
+
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            crate::operator_flow::notify_new_operator(&state, &operator).await;
+        });
+    }
+
+    /// First contact after approval: ask for a ghost name, then take the
+    /// next mail's first line as that name.
+    async fn handle_no_ghosts(
+        &self,
+        thread: &Thread,
+        operator: &t_koma_db::Operator,
+        ghost_name: &str,
+    ) {
+        let prompted =
+            t_koma_db::OperatorRepository::get_by_id(self.state.koma_db.pool(), &operator.id)
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|op| op.welcomed);
+
+        if prompted && !ghost_name.is_empty() {
+            self.boot_new_ghost(thread, &operator.id, ghost_name).await;
+            return;
+        }
+
+        if !prompted
+            && let Err(e) = t_koma_db::OperatorRepository::mark_welcomed(
+                self.state.koma_db.pool(),
+                &operator.id,
+            )
+            .await
+        {
+            error!("Failed to mark operator {} as welcomed: {}", operator.id, e);
+        }
+        self.send_content(thread, ids::EMAIL_GHOST_NAME_PROMPT)
+            .await;
+    }
+
+    async fn boot_new_ghost(&self, thread: &Thread, operator_id: &str, ghost_name: &str) {
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            operator_id,
+            &t_koma_db::OperatorPermission::CreateGhosts,
+        )
+        .await
+        {
+            self.send_content(thread, ids::GHOST_CREATION_NOT_PERMITTED)
+                .await;
+            return;
+        }
+
+        let ghost = match t_koma_db::GhostRepository::create(
+            self.state.koma_db.pool(),
+            operator_id,
+            ghost_name,
+        )
+        .await
+        {
+            Ok(ghost) => ghost,
+            Err(e) => {
+                let error_text = e.to_string();
+                let invalid = super::render_message(
+                    ids::INVALID_GHOST_NAME,
+                    &[("error", error_text.as_str()), ("ghost_name_prompt", "")],
+                );
+                self.send_text(thread, &invalid).await;
+                return;
+            }
+        };
+
+        let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Failed to get workspace path: {}", e);
+                self.send_content(thread, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                    .await;
+                return;
+            }
+        };
+
+        persist_ghost_name_to_soul(&workspace_path, &ghost.name).await;
+
+        let session = match t_koma_db::SessionRepository::create(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            operator_id,
+        )
+        .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                error!("Failed to create session: {}", e);
+                self.send_content(thread, ids::FAILED_CREATE_SESSION).await;
+                return;
+            }
+        };
+
+        let bootstrap = match content::prompt_text(ids::PROMPT_BOOTSTRAP, None, &[]) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to load prompts/system/bootstrap.md: {}", e);
+                self.send_content(thread, ids::ERROR_MISSING_BOOTSTRAP)
+                    .await;
+                return;
+            }
+        };
+
+        let ghost_response = match self
+            .state
+            .chat(&ghost.name, &session.id, operator_id, &bootstrap)
+            .await
+        {
+            Ok(text) => text,
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(thread, ids::ERROR_GHOST_BOOT_FAILED)
+                    .await;
+                return;
+            }
+        };
+
+        self.state.set_active_ghost(operator_id, &ghost.name).await;
+
+        // The boot reply opens the ghost's first thread.
+        let header = super::render_message(
+            ids::GHOST_CREATED_HEADER_WITH_NAME,
+            &[("ghost_name", ghost.name.as_str())],
+        );
+        let thread = Thread {
+            to: thread.to.clone(),
+            subject: thread.subject.clone(),
+            in_reply_to: thread.in_reply_to.clone(),
+            references: thread.references.clone(),
+            session_id: Some(session.id.clone()),
+        };
+        let body = format!("{}\n\n{}", header.trim(), ghost_response.trim());
+        if let Err(e) =
+            send_mail(&self.state, &self.mailer, &thread, Some(&ghost.name), &body).await
+        {
+            error!("[session:{}] Failed to send email: {}", session.id, e);
+        }
+    }
+}
+
+/// `NAME` from a `[NAME]` tag anywhere in the subject.
+fn subject_tag(subject: &str) -> Option<&str> {
+    let start = subject.find('[')?;
+    let len = subject[start + 1..].find(']')?;
+    let tag = subject[start + 1..start + 1 + len].trim();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Store mail attachments in the ghost workspace `downloads/` directory.
+async fn store_attachments(
+    mail: &ParsedMail,
+    workspace_path: &std::path::Path,
+) -> Vec<t_koma_db::ContentBlock> {
+    let download_dir = match attachments::downloads_dir(workspace_path).await {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("Failed to create downloads dir: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut blocks = Vec::new();
+    for attachment in &mail.attachments {
+        blocks.extend(
+            attachments::store_attachment(
+                &download_dir,
+                &attachment.filename,
+                // Generic types fall back to the filename extension.
+                Some(attachment.mime_type.clone())
+                    .filter(|mime| mime != "application/octet-stream"),
+                &attachment.data,
+            )
+            .await,
+        );
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_tag() {
+        assert_eq!(subject_tag("[Alpha] weekly plan"), Some("Alpha"));
+        assert_eq!(subject_tag("Re: plan [ Beta ]"), Some("Beta"));
+        assert_eq!(subject_tag("no tag"), None);
+        assert_eq!(subject_tag("[]"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Minimal IMAP4rev1 client for polling one mailbox.
+//!
+//! Only the handful of commands the bridge needs: LOGIN, SELECT,
+//! `UID SEARCH UNSEEN`, `UID FETCH BODY.PEEK[]`, `UID STORE +FLAGS` and
+//! LOGOUT. Responses are read line by line; `{N}` literals are read as raw
+//! bytes and handed back alongside the line that announced them.
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+
+use super::EmailError;
+
+/// Longest response line accepted (literals excluded).
+const MAX_LINE: usize = 1024 * 1024;
+
+/// Largest literal accepted; bigger messages are refused, not truncated.
+const MAX_LITERAL: usize = 50 * 1024 * 1024;
+
+/// One untagged response with the literals it carried.
+struct Untagged {
+    line: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// An authenticated-or-not IMAP connection over implicit TLS.
+pub(super) struct ImapSession {
+    stream: BufReader<TlsStream<TcpStream>>,
+    tag: u32,
+}
+
+impl ImapSession {
+    pub(super) async fn connect(host: &str, port: u16) -> Result<Self, EmailError> {
+        let stream = super::connect_tls(host, port).await?;
+        let mut session = Self {
+            stream: BufReader::new(stream),
+            tag: 0,
+        };
+        let greeting = session.read_response_line().await?;
+        if !greeting.line.starts_with("* OK") && !greeting.line.starts_with("* PREAUTH") {
+            return Err(EmailError::Imap(format!(
+                "unexpected greeting: {}",
+                greeting.line
+            )));
+        }
+        Ok(session)
+    }
+
+    pub(super) async fn login(&mut self, username: &str, password: &str) -> Result<(), EmailError> {
+        self.command(&format!("LOGIN {} {}", quote(username), quote(password)))
+            .await
+            .map(|_| ())
+    }
+
+    pub(super) async fn select(&mut self, mailbox: &str) -> Result<(), EmailError> {
+        self.command(&format!("SELECT {}", quote(mailbox)))
+            .await
+            .map(|_| ())
+    }
+
+    /// UIDs of messages without the `\Seen` flag.
+    pub(super) async fn search_unseen(&mut self) -> Result<Vec<u32>, EmailError> {
+        let responses = self.command("UID SEARCH UNSEEN").await?;
+        Ok(responses
+            .iter()
+            .filter_map(|r| r.line.strip_prefix("* SEARCH"))
+            .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+            .collect())
+    }
+
+    /// Raw RFC 5322 bytes of a message, without setting `\Seen`.
+    pub(super) async fn fetch(&mut self, uid: u32) -> Result<Option<Vec<u8>>, EmailError> {
+        let responses = self
+            .command(&format!("UID FETCH {uid} BODY.PEEK[]"))
+            .await?;
+        Ok(responses
+            .into_iter()
+            .filter(|r| r.line.contains(" FETCH "))
+            .find_map(|r| r.literals.into_iter().next()))
+    }
+
+    pub(super) async fn mark_seen(&mut self, uid: u32) -> Result<(), EmailError> {
+        self.command(&format!("UID STORE {uid} +FLAGS.SILENT (\\Seen)"))
+            .await
+            .map(|_| ())
+    }
+
+    pub(super) async fn logout(mut self) {
+        let _ = self.command("LOGOUT").await;
+    }
+
+    /// Send a tagged command and collect untagged responses until its status.
+    async fn command(&mut self, command: &str) -> Result<Vec<Untagged>, EmailError> {
+        self.tag += 1;
+        let tag = format!("A{:04}", self.tag);
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(format!("{tag} {command}\r\n").as_bytes())
+            .await?;
+        stream.flush().await?;
+
+        // Never echo arguments back: LOGIN carries the password.
+        let verb = command.split_whitespace().take(2).collect::<Vec<_>>();
+        let verb = if verb.first() == Some(&"UID") {
+            verb.join(" ")
+        } else {
+            verb.first().copied().unwrap_or_default().to_string()
+        };
+
+        let mut untagged = Vec::new();
+        loop {
+            let response = self.read_response_line().await?;
+            let Some(status) = response.line.strip_prefix(&format!("{tag} ")) else {
+                if response.line.starts_with('*') {
+                    untagged.push(response);
+                }
+                continue;
+            };
+            return if status.starts_with("OK") {
+                Ok(untagged)
+            } else {
+                Err(EmailError::Imap(format!("{verb}: {status}")))
+            };
+        }
+    }
+
+    /// One logical response line, reading any `{N}` literals it announces.
+    async fn read_response_line(&mut self) -> Result<Untagged, EmailError> {
+        let mut response = Untagged {
+            line: String::new(),
+            literals: Vec::new(),
+        };
+        loop {
+            let mut raw = Vec::new();
+            let read = (&mut self.stream)
+                .take(MAX_LINE as u64)
+                .read_until(b'\n', &mut raw)
+                .await?;
+            if read == 0 {
+                return Err(EmailError::Imap("connection closed".to_string()));
+            }
+            if !raw.ends_with(b"\n") {
+                return Err(EmailError::Imap("response line too long".to_string()));
+            }
+            let text = String::from_utf8_lossy(&raw);
+            let text = text.trim_end_matches(['\r', '\n']);
+            response.line.push_str(text);
+
+            let Some(size) = literal_size(text) else {
+                return Ok(response);
+            };
+            if size > MAX_LITERAL {
+                return Err(EmailError::Imap(format!("literal of {size} bytes refused")));
+            }
+            let mut literal = vec![0; size];
+            self.stream.read_exact(&mut literal).await?;
+            response.literals.push(literal);
+        }
+    }
+}
+
+/// Size of a `{N}` (or `{N+}`) literal announced at the end of a line.
+fn literal_size(line: &str) -> Option<usize> {
+    let inner = line.strip_suffix('}')?;
+    let start = inner.rfind('{')?;
+    inner[start + 1..].trim_end_matches('+').parse().ok()
+}
+
+/// IMAP quoted string.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
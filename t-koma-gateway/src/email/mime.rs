@@ -0,0 +1,763 @@
+//! Just enough RFC 5322 / MIME to read operator mail and write replies.
+//!
+//! Parsing walks the part tree once: the first `text/plain` part (or an
+//! `html2text` rendering of `text/html`) becomes the message text, parts
+//! with a filename become attachments. Headers are unfolded and RFC 2047
+//! encoded words decoded; charsets go through `encoding_rs`.
+//!
+//! Outgoing mail is always a single `text/plain; charset=utf-8` part,
+//! base64 encoded so long lines and non-ASCII survive any relay.
+
+use base64::Engine;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+/// Lenient base64 for inbound parts: mail clients disagree on padding.
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Nesting limit for multipart bodies.
+const MAX_DEPTH: usize = 8;
+
+/// Most recent ids kept in an outgoing `References` header.
+const MAX_REFERENCES: usize = 10;
+
+/// A parsed inbound mail.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedMail {
+    pub message_id: Option<String>,
+    pub in_reply_to: Vec<String>,
+    pub references: Vec<String>,
+    pub from: Option<Mailbox>,
+    pub subject: String,
+    pub text: String,
+    pub attachments: Vec<MailAttachment>,
+    /// Auto-replies, bounces and list traffic (never answered).
+    pub auto_generated: bool,
+}
+
+impl ParsedMail {
+    /// Ids this mail replies to, closest ancestor first.
+    pub fn ancestor_ids(&self) -> Vec<String> {
+        let mut ids = self.in_reply_to.clone();
+        for id in self.references.iter().rev() {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        ids
+    }
+
+    /// `References` for a reply to this mail.
+    pub fn reply_references(&self) -> Vec<String> {
+        let mut refs = if self.references.is_empty() {
+            self.in_reply_to.clone()
+        } else {
+            self.references.clone()
+        };
+        refs.extend(self.message_id.clone());
+        refs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mailbox {
+    pub name: Option<String>,
+    /// Lowercased address.
+    pub address: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailAttachment {
+    pub filename: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Parse a raw RFC 5322 message.
+pub fn parse(raw: &[u8]) -> ParsedMail {
+    let (head, body) = split_head(raw);
+    let headers = parse_headers(head);
+
+    let mut mail = ParsedMail {
+        message_id: header(&headers, "message-id").and_then(|v| message_ids(v).into_iter().next()),
+        in_reply_to: header(&headers, "in-reply-to")
+            .map(message_ids)
+            .unwrap_or_default(),
+        references: header(&headers, "references")
+            .map(message_ids)
+            .unwrap_or_default(),
+        from: header(&headers, "from").and_then(parse_mailbox),
+        subject: header(&headers, "subject")
+            .map(decode_encoded_words)
+            .unwrap_or_default(),
+        auto_generated: is_auto_generated(&headers),
+        ..Default::default()
+    };
+
+    let mut plain = Vec::new();
+    let mut html = Vec::new();
+    walk_part(
+        &headers,
+        body,
+        0,
+        &mut plain,
+        &mut html,
+        &mut mail.attachments,
+    );
+    mail.text = if !plain.is_empty() {
+        plain.join("\n\n")
+    } else {
+        html.iter()
+            .map(|h| html2text::from_read(h.as_bytes(), 80))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+    mail
+}
+
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(pos) = find(raw, b"\r\n\r\n") {
+        (&raw[..pos], &raw[pos + 4..])
+    } else if let Some(pos) = find(raw, b"\n\n") {
+        (&raw[..pos], &raw[pos + 2..])
+    } else {
+        (raw, &[])
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Unfolded `(lowercase name, value)` pairs in order.
+fn parse_headers(head: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(head);
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    headers
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// RFC 3834 `Auto-Submitted`, plus the older `Precedence` convention.
+fn is_auto_generated(headers: &[(String, String)]) -> bool {
+    let auto_submitted =
+        header(headers, "auto-submitted").is_some_and(|v| !v.trim().eq_ignore_ascii_case("no"));
+    let precedence = header(headers, "precedence").is_some_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "bulk" | "junk" | "list"
+        )
+    });
+    auto_submitted || precedence
+}
+
+/// `<id>` tokens of a Message-ID style header.
+pub fn message_ids(value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let id = &rest[start..=start + len];
+        if id.len() > 2 && !id.contains(char::is_whitespace) {
+            ids.push(id.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    ids
+}
+
+/// First mailbox of an address header (`"Name" <a@b>` or `a@b`).
+pub fn parse_mailbox(value: &str) -> Option<Mailbox> {
+    let (name, address) = match (value.find('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => {
+            let name = value[..start].trim().trim_matches('"').trim();
+            let name = (!name.is_empty()).then(|| decode_encoded_words(name));
+            (name, value[start + 1..end].trim())
+        }
+        _ => (None, value.split(',').next().unwrap_or_default().trim()),
+    };
+    (address.contains('@') && !address.contains(char::is_whitespace)).then(|| Mailbox {
+        name,
+        address: address.to_ascii_lowercase(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Body parts
+// ---------------------------------------------------------------------------
+
+/// `type/subtype` (lowercased) and its parameters.
+struct ContentType {
+    mime: String,
+    params: Vec<(String, String)>,
+}
+
+impl ContentType {
+    fn parse(value: Option<&str>) -> Self {
+        let value = value.unwrap_or("text/plain");
+        let mut segments = split_params(value).into_iter();
+        let mime = segments
+            .next()
+            .map(|m| m.trim().to_ascii_lowercase())
+            .filter(|m| m.contains('/'))
+            .unwrap_or_else(|| "text/plain".to_string());
+        let params = segments
+            .filter_map(|segment| {
+                let (key, value) = segment.split_once('=')?;
+                Some((
+                    key.trim().to_ascii_lowercase(),
+                    value.trim().trim_matches('"').to_string(),
+                ))
+            })
+            .collect();
+        Self { mime, params }
+    }
+
+    /// Parameter value, including RFC 2231 `name*=` and `name*0*=` forms.
+    fn param(&self, name: &str) -> Option<String> {
+        if let Some((_, value)) = self.params.iter().find(|(k, _)| k == name) {
+            return Some(decode_encoded_words(value));
+        }
+        if let Some((_, value)) = self.params.iter().find(|(k, _)| *k == format!("{name}*")) {
+            return Some(decode_rfc2231(value));
+        }
+        let mut joined = String::new();
+        let mut encoded = false;
+        for index in 0.. {
+            let plain = format!("{name}*{index}");
+            let extended = format!("{plain}*");
+            match self
+                .params
+                .iter()
+                .find(|(k, _)| *k == plain || *k == extended)
+            {
+                Some((key, value)) => {
+                    encoded |= index == 0 && key.ends_with('*');
+                    joined.push_str(value);
+                }
+                None => break,
+            }
+        }
+        (!joined.is_empty()).then(|| {
+            if encoded {
+                decode_rfc2231(&joined)
+            } else {
+                joined
+            }
+        })
+    }
+}
+
+/// Split `a; b="x;y"; c` on semicolons outside quotes.
+fn split_params(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, ch) in value.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// `charset'lang'percent-encoded` (continuations are joined beforehand).
+fn decode_rfc2231(value: &str) -> String {
+    let mut parts = value.splitn(3, '\'');
+    let (charset, text) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(charset), Some(_lang), Some(text)) => (charset, text),
+        _ => ("utf-8", value),
+    };
+    let mut bytes = Vec::with_capacity(text.len());
+    let raw = text.as_bytes();
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'%'
+            && let Some(byte) = raw
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            bytes.push(byte);
+            i += 3;
+            continue;
+        }
+        bytes.push(raw[i]);
+        i += 1;
+    }
+    decode_charset(&bytes, charset)
+}
+
+fn walk_part(
+    headers: &[(String, String)],
+    body: &[u8],
+    depth: usize,
+    plain: &mut Vec<String>,
+    html: &mut Vec<String>,
+    attachments: &mut Vec<MailAttachment>,
+) {
+    let content_type = ContentType::parse(header(headers, "content-type"));
+
+    if content_type.mime.starts_with("multipart/") {
+        let Some(boundary) = content_type.param("boundary") else {
+            return;
+        };
+        if depth >= MAX_DEPTH {
+            return;
+        }
+        for part in split_multipart(body, &boundary) {
+            let (head, part_body) = split_head(part);
+            let part_headers = parse_headers(head);
+            walk_part(
+                &part_headers,
+                part_body,
+                depth + 1,
+                plain,
+                html,
+                attachments,
+            );
+        }
+        return;
+    }
+
+    let data = decode_transfer(header(headers, "content-transfer-encoding"), body);
+    let disposition = ContentType::parse(header(headers, "content-disposition"));
+    let filename = disposition
+        .param("filename")
+        .or_else(|| content_type.param("name"));
+    let is_attachment = header(headers, "content-disposition").is_some_and(|d| {
+        d.trim_start()
+            .to_ascii_lowercase()
+            .starts_with("attachment")
+    }) || filename.is_some();
+
+    if !is_attachment && content_type.mime == "text/plain" {
+        plain.push(decode_text(&data, &content_type));
+    } else if !is_attachment && content_type.mime == "text/html" {
+        html.push(decode_text(&data, &content_type));
+    } else if is_attachment || !content_type.mime.starts_with("text/") {
+        let filename = filename.unwrap_or_else(|| {
+            if content_type.mime == "message/rfc822" {
+                "forwarded.eml".to_string()
+            } else {
+                "attachment".to_string()
+            }
+        });
+        attachments.push(MailAttachment {
+            filename,
+            mime_type: content_type.mime.clone(),
+            data,
+        });
+    }
+}
+
+fn decode_text(data: &[u8], content_type: &ContentType) -> String {
+    let charset = content_type
+        .param("charset")
+        .unwrap_or_else(|| "utf-8".to_string());
+    decode_charset(data, &charset).replace("\r\n", "\n")
+}
+
+fn decode_charset(data: &[u8], charset: &str) -> String {
+    let encoding =
+        encoding_rs::Encoding::for_label(charset.trim().as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(data).0.into_owned()
+}
+
+/// Body parts between `--boundary` delimiter lines.
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut offset = 0;
+
+    while offset < body.len() {
+        let line_end = body[offset..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(body.len(), |pos| offset + pos + 1);
+        let line = trim_line_end(&body[offset..line_end]);
+        if line.starts_with(delimiter) {
+            if let Some(start) = current {
+                // The line break before a delimiter belongs to the delimiter.
+                parts.push(trim_line_end(&body[start..offset]));
+            }
+            if line[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            current = Some(line_end);
+        }
+        offset = line_end;
+    }
+    if let Some(start) = current {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+fn decode_transfer(encoding: Option<&str>, body: &[u8]) -> Vec<u8> {
+    match encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        Some("base64") => {
+            let compact: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            LENIENT_BASE64
+                .decode(&compact)
+                .unwrap_or_else(|_| body.to_vec())
+        }
+        Some("quoted-printable") => decode_quoted_printable(body, false),
+        _ => body.to_vec(),
+    }
+}
+
+/// Quoted-printable; `header` mode (RFC 2047 `Q`) also maps `_` to space.
+fn decode_quoted_printable(input: &[u8], header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'=' => {
+                let rest = &input[i + 1..];
+                if rest.starts_with(b"\r\n") {
+                    i += 3;
+                } else if rest.starts_with(b"\n") {
+                    i += 2;
+                } else if let Some(byte) = rest
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    out.push(byte);
+                    i += 3;
+                } else {
+                    out.push(b'=');
+                    i += 1;
+                }
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Decode RFC 2047 `=?charset?B|Q?text?=` words in a header value.
+pub fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut pending_space = String::new();
+    let mut after_word = false;
+
+    while !rest.is_empty() {
+        if let Some((decoded, len)) = rest.strip_prefix("=?").and_then(encoded_word) {
+            // Whitespace between two encoded words is dropped.
+            if !after_word {
+                out.push_str(&pending_space);
+            }
+            pending_space.clear();
+            out.push_str(&decoded);
+            rest = &rest[len + 2..];
+            after_word = true;
+            continue;
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        if ch.is_whitespace() {
+            pending_space.push(ch);
+        } else {
+            out.push_str(&pending_space);
+            pending_space.clear();
+            out.push(ch);
+            after_word = false;
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    out.push_str(&pending_space);
+    out
+}
+
+/// `charset?B?text?=` after the `=?`: decoded text and byte length.
+fn encoded_word(text: &str) -> Option<(String, usize)> {
+    let mut parts = text.splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let rest = parts.next()?;
+    let end = rest.find("?=")?;
+    let payload = &rest[..end];
+    if payload.contains(char::is_whitespace) {
+        return None;
+    }
+    let bytes = match encoding.to_ascii_lowercase().as_str() {
+        "b" => LENIENT_BASE64.decode(payload).ok()?,
+        "q" => decode_quoted_printable(payload.as_bytes(), true),
+        _ => return None,
+    };
+    let len = charset.len() + encoding.len() + end + 4;
+    // RFC 2231 allows a `*lang` suffix on the charset.
+    let charset = charset.split('*').next().unwrap_or(charset);
+    Some((decode_charset(&bytes, charset), len))
+}
+
+/// Drop the quoted history below a reply (`On ... wrote:` and what follows).
+pub fn strip_quoted_reply(text: &str) -> String {
+    let mut kept: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let attribution = trimmed.starts_with("On ") && trimmed.ends_with("wrote:");
+        if attribution || trimmed == "-----Original Message-----" {
+            break;
+        }
+        kept.push(line);
+    }
+    while kept
+        .last()
+        .is_some_and(|line| line.trim().is_empty() || line.trim_start().starts_with('>'))
+    {
+        kept.pop();
+    }
+    kept.join("\n").trim().to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Outgoing mail
+// ---------------------------------------------------------------------------
+
+/// A plain-text mail written by the gateway.
+pub struct OutgoingMail<'a> {
+    pub from: &'a Mailbox,
+    pub to: &'a str,
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub message_id: &'a str,
+    pub in_reply_to: Option<&'a str>,
+    pub references: &'a [String],
+}
+
+/// `<uuid@domain>` for a mail sent from `address`.
+pub fn new_message_id(address: &str) -> String {
+    let domain = address.rsplit_once('@').map_or("t-koma.local", |(_, d)| d);
+    format!("<{}@{}>", uuid::Uuid::new_v4(), domain)
+}
+
+/// `Re: subject`, unless it already is one.
+pub fn reply_subject(subject: &str) -> String {
+    let subject = subject.trim();
+    if subject.len() >= 3 && subject[..3].eq_ignore_ascii_case("re:") {
+        subject.to_string()
+    } else {
+        format!("Re: {subject}")
+    }
+}
+
+/// Render an outgoing mail as CRLF RFC 5322 bytes.
+pub fn build_message(mail: &OutgoingMail<'_>) -> Vec<u8> {
+    let from = match &mail.from.name {
+        Some(name) => format!("{} <{}>", encode_header_value(name), mail.from.address),
+        None => mail.from.address.clone(),
+    };
+    let mut head = vec![
+        format!("From: {from}"),
+        format!("To: {}", mail.to),
+        format!("Subject: {}", encode_header_value(mail.subject)),
+        format!("Date: {}", chrono::Utc::now().to_rfc2822()),
+        format!("Message-ID: {}", mail.message_id),
+    ];
+    if let Some(parent) = mail.in_reply_to {
+        head.push(format!("In-Reply-To: {parent}"));
+    }
+    if !mail.references.is_empty() {
+        let skip = mail.references.len().saturating_sub(MAX_REFERENCES);
+        // One id per folded line keeps the header under the line limit.
+        head.push(format!(
+            "References: {}",
+            mail.references[skip..].join("\r\n ")
+        ));
+    }
+    head.extend([
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+        "Content-Transfer-Encoding: base64".to_string(),
+    ]);
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(mail.body.as_bytes());
+    let mut message = head.join("\r\n");
+    message.push_str("\r\n\r\n");
+    for line in encoded.as_bytes().chunks(76) {
+        message.push_str(std::str::from_utf8(line).unwrap_or_default());
+        message.push_str("\r\n");
+    }
+    message.into_bytes()
+}
+
+/// RFC 2047 `B` encoding for non-ASCII header text.
+fn encode_header_value(value: &str) -> String {
+    let value = value.replace(['\r', '\n'], " ");
+    if value.is_ascii() {
+        return value;
+    }
+    // Keep each encoded word under 75 characters without splitting a char.
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for ch in value.chars() {
+        if chunk.len() + ch.len_utf8() > 45 {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(ch);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|word| {
+            format!(
+                "=?UTF-8?B?{}?=",
+                base64::engine::general_purpose::STANDARD.encode(word.as_bytes())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart_reply() {
+        let raw = concat!(
+            "From: =?UTF-8?Q?Ren=C3=A9e?= <Renee@Example.com>\r\n",
+            "To: ghost@example.com\r\n",
+            "Subject: Re: =?UTF-8?B?44Ky44O844OI?=\r\n",
+            " [Alpha]\r\n",
+            "Message-ID: <m2@example.com>\r\n",
+            "In-Reply-To: <m1@koma.local>\r\n",
+            "References: <m0@example.com>\r\n",
+            "\t<m1@koma.local>\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"outer\"\r\n",
+            "\r\n",
+            "preamble\r\n",
+            "--outer\r\n",
+            "Content-Type: multipart/alternative; boundary=inner\r\n",
+            "\r\n",
+            "--inner\r\n",
+            "Content-Type: text/plain; charset=iso-8859-1\r\n",
+            "Content-Transfer-Encoding: quoted-printable\r\n",
+            "\r\n",
+            "Caf=E9 plans, soft=\r\n",
+            "wrapped.\r\n",
+            "--inner\r\n",
+            "Content-Type: text/html\r\n",
+            "\r\n",
+            "<p>ignored</p>\r\n",
+            "--inner--\r\n",
+            "--outer\r\n",
+            "Content-Type: text/csv\r\n",
+            "Content-Disposition: attachment; filename*=utf-8''r%C3%A9sum%C3%A9.csv\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "\r\n",
+            "YSxi\r\n",
+            "CjEsMg==\r\n",
+            "--outer--\r\n",
+        );
+
+        let mail = parse(raw.as_bytes());
+        let from = mail.from.clone().unwrap();
+        assert_eq!(from.address, "renee@example.com");
+        assert_eq!(from.name.as_deref(), Some("Renée"));
+        assert_eq!(mail.subject, "Re: ゲート [Alpha]");
+        assert_eq!(mail.message_id.as_deref(), Some("<m2@example.com>"));
+        assert_eq!(
+            mail.ancestor_ids(),
+            vec![
+                "<m1@koma.local>".to_string(),
+                "<m0@example.com>".to_string()
+            ]
+        );
+        assert_eq!(mail.text, "Café plans, softwrapped.");
+        assert_eq!(mail.attachments.len(), 1);
+        assert_eq!(mail.attachments[0].filename, "résumé.csv");
+        assert_eq!(mail.attachments[0].data, b"a,b\n1,2");
+        assert!(!mail.auto_generated);
+    }
+
+    #[test]
+    fn test_html_only_and_auto_submitted() {
+        let raw = "From: bounce@example.com\nAuto-Submitted: auto-replied\nContent-Type: text/html; charset=utf-8\n\n<p>Out of <b>office</b></p>";
+        let mail = parse(raw.as_bytes());
+        assert!(mail.auto_generated);
+        assert!(mail.text.contains("office"));
+        assert!(!mail.text.contains("<p>"));
+    }
+
+    #[test]
+    fn test_strip_quoted_reply() {
+        let text = "Sounds good.\n\nOn Tue, 3 Mar 2026, Ghost <g@x> wrote:\n> earlier\n> text";
+        assert_eq!(strip_quoted_reply(text), "Sounds good.");
+        assert_eq!(strip_quoted_reply("line\n\n> trailing quote"), "line");
+    }
+
+    #[test]
+    fn test_build_reply() {
+        let from = Mailbox {
+            name: Some("ゴースト".to_string()),
+            address: "ghost@example.com".to_string(),
+        };
+        let refs = vec!["<a@x>".to_string(), "<b@x>".to_string()];
+        let raw = build_message(&OutgoingMail {
+            from: &from,
+            to: "me@example.com",
+            subject: &reply_subject("re: plans"),
+            body: "héllo",
+            message_id: "<c@example.com>",
+            in_reply_to: Some("<b@x>"),
+            references: &refs,
+        });
+
+        // Round-trips through the parser
+        let mail = parse(&raw);
+        assert_eq!(mail.from.unwrap().name.as_deref(), Some("ゴースト"));
+        assert_eq!(mail.subject, "re: plans");
+        assert_eq!(mail.text, "héllo");
+        assert_eq!(mail.in_reply_to, vec!["<b@x>".to_string()]);
+        assert_eq!(mail.references, refs);
+        assert!(new_message_id("ghost@example.com").ends_with("@example.com>"));
+    }
+}
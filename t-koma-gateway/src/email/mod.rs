@@ -0,0 +1,147 @@
+mod bridge;
+mod imap;
+mod mime;
+mod send;
+mod smtp;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use t_koma_core::EmailSettings;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls;
+use tracing::info;
+
+pub use bridge::Bridge;
+pub use send::{
+    send_approved_operator_ghost_prompt, send_new_operator_notification_to_pms,
+    send_operator_gateway_message,
+};
+
+/// Upper bound for one SMTP submission.
+const SEND_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    crate::gateway_message::from_content(id, Some("email"), vars).text_fallback
+}
+
+/// Mailbox credentials plus the SMTP side of the bridge.
+///
+/// Cheap to clone; held in `AppState` so notifications can be mailed from
+/// outside the polling loop.
+#[derive(Clone)]
+pub struct Mailer {
+    settings: EmailSettings,
+    password: String,
+}
+
+impl Mailer {
+    pub fn new(settings: EmailSettings, password: String) -> Self {
+        Self { settings, password }
+    }
+
+    pub fn settings(&self) -> &EmailSettings {
+        &self.settings
+    }
+
+    /// Lowercased address the bridge sends from.
+    pub fn address(&self) -> String {
+        self.settings.address.to_ascii_lowercase()
+    }
+
+    /// Submit a plain-text mail over SMTP.
+    pub(crate) async fn send(&self, mail: &mime::OutgoingMail<'_>) -> Result<(), EmailError> {
+        let data = mime::build_message(mail);
+        let submission = smtp::Submission {
+            host: &self.settings.smtp_host,
+            port: self.settings.smtp_port,
+            starttls: self.settings.smtp_starttls,
+            username: self.settings.login(),
+            password: &self.password,
+        };
+        tokio::time::timeout(
+            SEND_TIMEOUT,
+            smtp::send(&submission, &mail.from.address, mail.to, &data),
+        )
+        .await
+        .map_err(|_| EmailError::Smtp("timed out".to_string()))?
+    }
+
+    /// Log in to the mailbox and select it.
+    async fn open_mailbox(&self) -> Result<imap::ImapSession, EmailError> {
+        let mut session =
+            imap::ImapSession::connect(&self.settings.imap_host, self.settings.imap_port).await?;
+        session.login(self.settings.login(), &self.password).await?;
+        session.select(&self.settings.mailbox).await?;
+        Ok(session)
+    }
+}
+
+/// Start the email bridge (optional - returns Ok(None) without a mailer)
+///
+/// Checks the IMAP login once; the caller spawns [`Bridge::run`] to poll.
+pub async fn start_email_bridge(
+    mailer: Option<Mailer>,
+    state: Arc<crate::state::AppState>,
+) -> Result<Option<Arc<Bridge>>, EmailError> {
+    let Some(mailer) = mailer else {
+        info!("Email bridge not configured, skipping");
+        return Ok(None);
+    };
+
+    info!("Starting email bridge...");
+
+    let session = mailer.open_mailbox().await?;
+    session.logout().await;
+    info!(
+        "Email bridge logged in as {} ({})",
+        mailer.settings.login(),
+        mailer.settings.imap_host
+    );
+
+    Ok(Some(Arc::new(Bridge::new(state, mailer))))
+}
+
+/// Email-related errors
+#[derive(Debug, thiserror::Error)]
+pub enum EmailError {
+    #[error("Email connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Email TLS error: {0}")]
+    Tls(String),
+    #[error("IMAP error: {0}")]
+    Imap(String),
+    #[error("SMTP error: {0}")]
+    Smtp(String),
+}
+
+fn tls_connector() -> TlsConnector {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .expect("ring supports the default TLS versions")
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Open an implicit-TLS connection (IMAPS, SMTPS).
+async fn connect_tls(host: &str, port: u16) -> Result<TlsStream<TcpStream>, EmailError> {
+    let tcp = TcpStream::connect((host, port)).await?;
+    upgrade_tls(tcp, host).await
+}
+
+/// Start TLS on an open connection (SMTP STARTTLS).
+async fn upgrade_tls(tcp: TcpStream, host: &str) -> Result<TlsStream<TcpStream>, EmailError> {
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|e| EmailError::Tls(e.to_string()))?;
+    tls_connector()
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| EmailError::Tls(e.to_string()))
+}
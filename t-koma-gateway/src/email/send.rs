@@ -0,0 +1,273 @@
+use tracing::{debug, warn};
+
+use crate::content::ids;
+use crate::operator_flow::OutboundMessage;
+use crate::state::AppState;
+
+use super::mime::{self, Mailbox, OutgoingMail, ParsedMail};
+use super::{EmailError, Mailer};
+
+/// Sender name on mail that does not come from a ghost.
+const GATEWAY_NAME: &str = "T-KOMA";
+
+/// Subject of mail that starts a thread (notifications, reminders).
+const GATEWAY_SUBJECT: &str = "T-KOMA // ティコマ";
+
+/// Where a mail goes and which thread it continues.
+pub(super) struct Thread {
+    pub to: String,
+    pub subject: String,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    /// Session the thread runs in, once known; sent mail is recorded there.
+    pub session_id: Option<String>,
+}
+
+impl Thread {
+    /// Answer an inbound mail.
+    pub(super) fn reply_to(mail: &ParsedMail, to: &str) -> Self {
+        Self {
+            to: to.to_string(),
+            subject: mime::reply_subject(&mail.subject),
+            in_reply_to: mail.message_id.clone(),
+            references: mail.reply_references(),
+            session_id: None,
+        }
+    }
+
+    /// Continue a session's thread, or start one when it has no mail yet.
+    async fn for_session(
+        state: &AppState,
+        to: &str,
+        session_id: Option<&str>,
+    ) -> Result<Self, String> {
+        let latest = match session_id {
+            Some(session_id) => t_koma_db::EmailThreadRepository::latest_in_session(
+                state.koma_db.pool(),
+                session_id,
+            )
+            .await
+            .map_err(|e| e.to_string())?,
+            None => None,
+        };
+        Ok(match latest {
+            Some(latest) => Self {
+                to: to.to_string(),
+                subject: mime::reply_subject(&latest.subject),
+                in_reply_to: Some(latest.message_id.clone()),
+                references: vec![latest.message_id],
+                session_id: session_id.map(str::to_string),
+            },
+            None => Self {
+                to: to.to_string(),
+                subject: GATEWAY_SUBJECT.to_string(),
+                in_reply_to: None,
+                references: Vec::new(),
+                session_id: session_id.map(str::to_string),
+            },
+        })
+    }
+}
+
+/// Send one mail in `thread` and remember it as part of the session.
+pub(super) async fn send_mail(
+    state: &AppState,
+    mailer: &Mailer,
+    thread: &Thread,
+    from_name: Option<&str>,
+    body: &str,
+) -> Result<(), EmailError> {
+    let from = Mailbox {
+        name: Some(from_name.unwrap_or(GATEWAY_NAME).to_string()),
+        address: mailer.address(),
+    };
+    let message_id = mime::new_message_id(&from.address);
+    mailer
+        .send(&OutgoingMail {
+            from: &from,
+            to: &thread.to,
+            subject: &thread.subject,
+            body,
+            message_id: &message_id,
+            in_reply_to: thread.in_reply_to.as_deref(),
+            references: &thread.references,
+        })
+        .await?;
+
+    if let Some(session_id) = &thread.session_id
+        && let Err(e) = t_koma_db::EmailThreadRepository::record(
+            state.koma_db.pool(),
+            &message_id,
+            session_id,
+            &thread.subject,
+        )
+        .await
+    {
+        warn!("Failed to record sent mail {}: {}", message_id, e);
+    }
+    Ok(())
+}
+
+/// Render a chat turn as one mail body.
+///
+/// Email cannot carry buttons, so gateway messages use their text fallback,
+/// which spells out the replies (`APPROVE`, `steps N`, ...) that work instead.
+pub(super) fn render_outbound(messages: &[OutboundMessage]) -> String {
+    let mut sections = Vec::with_capacity(messages.len());
+    for message in messages {
+        match message {
+            OutboundMessage::AssistantText(text) => sections.push(text.trim().to_string()),
+            OutboundMessage::Gateway(msg) => sections.push(msg.text_fallback.trim().to_string()),
+            OutboundMessage::ToolCalls(calls) => {
+                let lines: Vec<String> = calls
+                    .iter()
+                    .map(|call| {
+                        let arrow = if call.is_error { "⚠" } else { "→" };
+                        format!(
+                            "{}({}) {} {}",
+                            call.name, call.input_preview, arrow, call.output_preview
+                        )
+                    })
+                    .collect();
+                if !lines.is_empty() {
+                    sections.push(lines.join("\n"));
+                }
+            }
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    sections.join("\n\n")
+}
+
+/// Email address of the operator's email interface, if any.
+async fn operator_address(state: &AppState, operator_id: &str) -> Result<Option<String>, String> {
+    let interfaces =
+        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), operator_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(interfaces
+        .into_iter()
+        .find(|iface| iface.platform == t_koma_db::Platform::Email)
+        .map(|iface| iface.external_id))
+}
+
+// ---------------------------------------------------------------------------
+// PM notification for new operator registration
+// ---------------------------------------------------------------------------
+
+/// Tell puppet masters about a pending operator.
+///
+/// Mail has no buttons, so the notice points to the CLI or a chat interface
+/// for the actual review.
+pub async fn send_new_operator_notification_to_pms(
+    state: &AppState,
+    mailer: &Mailer,
+    new_operator: &t_koma_db::Operator,
+) {
+    let pms = match t_koma_db::OperatorRepository::list_puppet_masters_with_interface(
+        state.koma_db.pool(),
+        t_koma_db::Platform::Email,
+    )
+    .await
+    {
+        Ok(list) => list,
+        Err(e) => {
+            warn!(
+                "Failed to list PM operators for new-operator notification: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if pms.is_empty() {
+        debug!("No PM operators with email interfaces to notify");
+        return;
+    }
+
+    let text = super::render_message(
+        ids::EMAIL_NEW_OPERATOR_PENDING,
+        &[("operator_name", &new_operator.name)],
+    );
+    for (pm_op, address) in &pms {
+        let thread = Thread {
+            to: address.clone(),
+            subject: GATEWAY_SUBJECT.to_string(),
+            in_reply_to: None,
+            references: Vec::new(),
+            session_id: None,
+        };
+        if let Err(e) = send_mail(state, mailer, &thread, None, &text).await {
+            warn!(
+                "Failed to send new-operator notification to PM {}: {}",
+                pm_op.id, e
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Messages outside of a chat turn
+// ---------------------------------------------------------------------------
+
+/// Ask a freshly approved operator to name their first ghost.
+///
+/// Returns `Ok(false)` when there is nothing to do (no email interface,
+/// already welcomed or already has ghosts).
+pub async fn send_approved_operator_ghost_prompt(
+    state: &AppState,
+    mailer: &Mailer,
+    operator_id: &str,
+) -> Result<bool, String> {
+    let operator = t_koma_db::OperatorRepository::get_by_id(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("operator not found: {}", operator_id))?;
+
+    if operator.status != t_koma_db::OperatorStatus::Approved || operator.welcomed {
+        return Ok(false);
+    }
+
+    let ghosts = t_koma_db::GhostRepository::list_by_operator(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !ghosts.is_empty() {
+        return Ok(false);
+    }
+
+    let Some(address) = operator_address(state, operator_id).await? else {
+        return Ok(false);
+    };
+    let thread = Thread::for_session(state, &address, None).await?;
+    let text = super::render_message(ids::EMAIL_GHOST_NAME_PROMPT, &[]);
+    send_mail(state, mailer, &thread, None, &text)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    t_koma_db::OperatorRepository::mark_welcomed(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+/// Mail a gateway message to an operator outside of a chat turn.
+///
+/// With a `session_id` the mail continues that session's thread. Returns
+/// `Ok(false)` when the operator has no email interface.
+pub async fn send_operator_gateway_message(
+    state: &AppState,
+    mailer: &Mailer,
+    operator_id: &str,
+    session_id: Option<&str>,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<bool, String> {
+    let Some(address) = operator_address(state, operator_id).await? else {
+        return Ok(false);
+    };
+    let thread = Thread::for_session(state, &address, session_id).await?;
+    send_mail(state, mailer, &thread, None, &message.text_fallback)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
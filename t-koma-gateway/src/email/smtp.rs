@@ -0,0 +1,139 @@
+//! Minimal SMTP submission client (RFC 5321 + AUTH PLAIN).
+//!
+//! Connects with implicit TLS (port 465) or upgrades a plain connection
+//! with STARTTLS (port 587), authenticates and sends one message.
+
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use super::EmailError;
+
+/// Where to submit mail and how to log in.
+pub(super) struct Submission<'a> {
+    pub host: &'a str,
+    pub port: u16,
+    pub starttls: bool,
+    pub username: &'a str,
+    pub password: &'a str,
+}
+
+/// Deliver `data` (CRLF message bytes) from `from` to `to`.
+pub(super) async fn send(
+    submission: &Submission<'_>,
+    from: &str,
+    to: &str,
+    data: &[u8],
+) -> Result<(), EmailError> {
+    let helo = from.rsplit_once('@').map_or("localhost", |(_, d)| d);
+
+    if submission.starttls {
+        let tcp = TcpStream::connect((submission.host, submission.port)).await?;
+        let mut plain = BufReader::new(tcp);
+        expect(&mut plain, None, &[220]).await?;
+        expect(&mut plain, Some(&format!("EHLO {helo}")), &[250]).await?;
+        expect(&mut plain, Some("STARTTLS"), &[220]).await?;
+        let tls = super::upgrade_tls(plain.into_inner(), submission.host).await?;
+        let mut stream = BufReader::new(tls);
+        expect(&mut stream, Some(&format!("EHLO {helo}")), &[250]).await?;
+        transaction(&mut stream, submission, from, to, data).await
+    } else {
+        let tls = super::connect_tls(submission.host, submission.port).await?;
+        let mut stream = BufReader::new(tls);
+        expect(&mut stream, None, &[220]).await?;
+        expect(&mut stream, Some(&format!("EHLO {helo}")), &[250]).await?;
+        transaction(&mut stream, submission, from, to, data).await
+    }
+}
+
+async fn transaction<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    submission: &Submission<'_>,
+    from: &str,
+    to: &str,
+    data: &[u8],
+) -> Result<(), EmailError> {
+    let credentials = base64::engine::general_purpose::STANDARD.encode(format!(
+        "\0{}\0{}",
+        submission.username, submission.password
+    ));
+    // The reply to AUTH is checked without echoing the credentials.
+    write_line(stream, &format!("AUTH PLAIN {credentials}")).await?;
+    check(stream, "AUTH PLAIN", &[235]).await?;
+
+    expect(stream, Some(&format!("MAIL FROM:<{from}>")), &[250]).await?;
+    expect(stream, Some(&format!("RCPT TO:<{to}>")), &[250, 251]).await?;
+    expect(stream, Some("DATA"), &[354]).await?;
+
+    let writer = stream.get_mut();
+    writer.write_all(&dot_stuff(data)).await?;
+    writer.write_all(b".\r\n").await?;
+    writer.flush().await?;
+    check(stream, "DATA", &[250]).await?;
+
+    let _ = expect(stream, Some("QUIT"), &[221]).await;
+    Ok(())
+}
+
+async fn write_line<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    line: &str,
+) -> Result<(), EmailError> {
+    let writer = stream.get_mut();
+    writer.write_all(format!("{line}\r\n").as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Send `command` (if any) and require one of `codes` in the reply.
+async fn expect<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    command: Option<&str>,
+    codes: &[u16],
+) -> Result<(), EmailError> {
+    if let Some(command) = command {
+        write_line(stream, command).await?;
+    }
+    check(stream, command.unwrap_or("greeting"), codes).await
+}
+
+/// Read a (possibly multi-line) reply and require one of `codes`.
+async fn check<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    what: &str,
+    codes: &[u16],
+) -> Result<(), EmailError> {
+    let mut text = String::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Err(EmailError::Smtp(format!("{what}: connection closed")));
+        }
+        let line = line.trim_end();
+        let code = line.get(..3).and_then(|c| c.parse::<u16>().ok());
+        text.push_str(line.get(4..).unwrap_or_default());
+        // `250-` continues a multi-line reply, `250 ` ends it.
+        if line.as_bytes().get(3) == Some(&b'-') {
+            text.push(' ');
+            continue;
+        }
+        return match code {
+            Some(code) if codes.contains(&code) => Ok(()),
+            _ => Err(EmailError::Smtp(format!("{what}: {line}"))),
+        };
+    }
+}
+
+/// Normalize line endings to CRLF, escape leading dots and end with CRLF.
+fn dot_stuff(data: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(data);
+    let mut out = Vec::with_capacity(data.len() + 64);
+    for line in text.lines() {
+        if line.starts_with('.') {
+            out.push(b'.');
+        }
+        out.extend_from_slice(line.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
@@ -2,22 +2,28 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Utc};
 use tokio::fs;
 use tokio::time::{Instant, interval_at};
 use tracing::{info, warn};
 
 use crate::circuit_breaker::CooldownReason;
+use crate::job_queue::{JobFailure, QueuedWork};
+use crate::scheduler::JobKind;
 use crate::session::{ChatError, JobChatResult};
 use crate::state::{AppState, HeartbeatOverride, LogEntry};
+use crate::tools::{JobHandle, ToolManager};
+use t_koma_core::{CronSchedule, HeartbeatTimingSettings};
 use t_koma_db::{
     ContentBlock, GhostRepository, JobKind as DbJobKind, JobLog, JobLogRepository, MessageRole,
-    SessionRepository,
+    SessionRepository, TodoItem, TranscriptEntry, job_todos,
 };
 
 const HEARTBEAT_TOKEN: &str = "HEARTBEAT_OK";
 const HEARTBEAT_CONTINUE_TOKEN: &str = "HEARTBEAT_CONTINUE";
 const DEFAULT_HEARTBEAT_ACK_MAX_CHARS: usize = 300;
+/// Used when a continue schedule has no further occurrence.
+const DEFAULT_CONTINUE_FALLBACK_SECS: i64 = 30 * 60;
 
 struct StripResult {
     should_skip: bool,
@@ -172,7 +178,7 @@ pub fn next_heartbeat_due_for_session(
     session_updated_at: i64,
     had_ok_heartbeat: bool,
     override_entry: Option<HeartbeatOverride>,
-    idle_minutes: i64,
+    idle_secs: i64,
 ) -> Option<i64> {
     if let Some(override_entry) = override_entry {
         return Some(override_entry.next_due);
@@ -180,7 +186,112 @@ pub fn next_heartbeat_due_for_session(
     if had_ok_heartbeat {
         return None;
     }
-    Some(session_updated_at + idle_minutes * 60)
+    Some(session_updated_at + idle_secs)
+}
+
+/// Idle delay in seconds before a session's next heartbeat.
+///
+/// `recent_statuses` are the session's latest heartbeat outcomes, newest
+/// first, and `recent_messages` its message count over the activity window.
+/// With `[heartbeat_timing.adaptive]` enabled, every leading no-op ("ok")
+/// outcome backs `idle_minutes` off by one `backoff_factor` step and an
+/// active session (last heartbeat did work, or busy chat) tightens it by one.
+pub fn adaptive_idle_secs(
+    timing: &HeartbeatTimingSettings,
+    recent_statuses: &[String],
+    recent_messages: i64,
+) -> i64 {
+    let base = timing.idle_minutes as f64 * 60.0;
+    let adaptive = &timing.adaptive;
+    if !adaptive.enabled {
+        return base as i64;
+    }
+
+    let noop_streak = recent_statuses
+        .iter()
+        .take_while(|status| status.as_str() == "ok")
+        .count() as i32;
+    let active = recent_statuses.first().is_some_and(|status| status != "ok")
+        || recent_messages >= adaptive.active_messages as i64;
+    let steps = noop_streak - i32::from(active);
+    let delay = base * adaptive.backoff_factor.max(1.0).powi(steps);
+
+    let min = adaptive.min_idle_minutes as f64 * 60.0;
+    let max = (adaptive.max_idle_minutes as f64 * 60.0).max(min);
+    delay.clamp(min, max).round() as i64
+}
+
+/// Effective idle delay of a session, loading its recent heartbeat outcomes
+/// and activity when the adaptive cadence is enabled.
+async fn session_idle_secs(
+    state: &AppState,
+    timing: &HeartbeatTimingSettings,
+    ghost_id: &str,
+    session_id: &str,
+    now: i64,
+) -> i64 {
+    let adaptive = &timing.adaptive;
+    if !adaptive.enabled {
+        return adaptive_idle_secs(timing, &[], 0);
+    }
+
+    let pool = state.koma_db.pool();
+    let statuses = JobLogRepository::recent_statuses(
+        pool,
+        ghost_id,
+        session_id,
+        DbJobKind::Heartbeat,
+        adaptive.history,
+    )
+    .await
+    .unwrap_or_else(|err| {
+        warn!("heartbeat: failed to load recent outcomes of {session_id}: {err}");
+        Vec::new()
+    });
+    let since = now - adaptive.active_window_minutes as i64 * 60;
+    let recent_messages = SessionRepository::count_session_messages_since(pool, session_id, since)
+        .await
+        .unwrap_or_else(|err| {
+            warn!("heartbeat: failed to count recent messages of {session_id}: {err}");
+            0
+        });
+    adaptive_idle_secs(timing, &statuses, recent_messages)
+}
+
+/// When the heartbeat looks again after a HEARTBEAT_CONTINUE response.
+#[derive(Debug, Clone)]
+pub enum ContinueDelay {
+    Minutes(i64),
+    Schedule(Box<CronSchedule>),
+}
+
+impl ContinueDelay {
+    /// `continue_schedule` when set and valid, `continue_minutes` otherwise.
+    pub fn from_settings(timing: &HeartbeatTimingSettings) -> Self {
+        if let Some(expression) = &timing.continue_schedule {
+            match CronSchedule::parse(expression, None) {
+                Ok(schedule) => return Self::Schedule(Box::new(schedule)),
+                Err(err) => warn!("heartbeat: ignoring continue_schedule: {err}"),
+            }
+        }
+        Self::Minutes(timing.continue_minutes as i64)
+    }
+
+    fn next_due(&self, now: i64) -> i64 {
+        match self {
+            Self::Minutes(minutes) => now + minutes * 60,
+            Self::Schedule(schedule) => schedule
+                .next_after(now)
+                .unwrap_or(now + DEFAULT_CONTINUE_FALLBACK_SECS),
+        }
+    }
+
+    fn schedule(&self) -> Option<String> {
+        match self {
+            Self::Minutes(_) => None,
+            Self::Schedule(schedule) => Some(schedule.to_string()),
+        }
+    }
 }
 
 fn is_heartbeat_content_effectively_empty(content: &str) -> bool {
@@ -221,7 +332,9 @@ async fn run_heartbeat_for_session(
     session_id: &str,
     operator_id: &str,
     model: &crate::state::ModelEntry,
+    job_handle: JobHandle,
 ) -> Result<JobChatResult, ChatError> {
+    let model = model.with_generation_overrides(&state.job_generation().heartbeat);
     let model_info = format!(
         "# Model\n- {} ({}/{})\n",
         model.alias, model.provider, model.model
@@ -229,9 +342,24 @@ async fn run_heartbeat_for_session(
 
     let workspace_path = t_koma_db::ghosts::ghost_workspace_path(ghost_name)?;
     let heartbeat_path = workspace_path.join("HEARTBEAT.md");
-    let prompt = fs::read_to_string(&heartbeat_path)
+    let mut prompt = fs::read_to_string(&heartbeat_path)
         .await
-        .unwrap_or_default();
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let due = job_todos::due_todos(&job_handle.todos, Utc::now().timestamp());
+    if !due.is_empty() {
+        let due_list = format_due_todos(&due);
+        let section = crate::content::prompt_text(
+            crate::content::ids::PROMPT_HEARTBEAT_DUE_TODOS,
+            None,
+            &[("due_todos", due_list.as_str())],
+        )
+        .unwrap_or_else(|_| format!("## Due TODOs\n\n{due_list}"));
+        prompt = format!("{prompt}\n\n{}", section.trim()).trim().to_string();
+    }
+    let tool_manager = ToolManager::new_heartbeat(state.session_chat.skill_paths().to_vec());
 
     state
         .session_chat
@@ -244,10 +372,10 @@ async fn run_heartbeat_for_session(
             model.context_window,
             session_id,
             operator_id,
-            prompt.trim(),
+            &prompt,
             true, // load session history — ghost needs conversation context
-            None, // use default (chat) tool manager
-            None, // no job handle needed for heartbeat
+            Some(&tool_manager),
+            Some(job_handle),
             None, // use default loop limit
             model.retry_on_empty,
             &model_info,
@@ -255,11 +383,67 @@ async fn run_heartbeat_for_session(
         .await
 }
 
-pub async fn run_heartbeat_tick(state: Arc<AppState>, idle_minutes: i64, continue_minutes: i64) {
-    let threshold = Utc::now() - ChronoDuration::minutes(idle_minutes);
-    let threshold_ts = threshold.timestamp();
+fn format_due_todos(due: &[&TodoItem]) -> String {
+    due.iter()
+        .map(|item| {
+            let mut line = format!("- {}", item.title);
+            if let Some(due_at) = item.due_at.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+                line.push_str(&format!(" (due {}", due_at.format("%Y-%m-%d %H:%M UTC")));
+                if let Some(recurrence) = item.recurrence {
+                    line.push_str(&format!(", {recurrence}"));
+                }
+                line.push(')');
+            }
+            if let Some(description) = &item.description {
+                line.push_str(&format!(": {description}"));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether any carried TODO became due after the last successful heartbeat,
+/// i.e. hasn't been surfaced to the ghost yet.
+fn has_newly_due_todos(todos: &[TodoItem], now: i64, last_heartbeat_at: i64) -> bool {
+    job_todos::due_todos(todos, now)
+        .iter()
+        .any(|item| item.due_at.is_some_and(|at| at > last_heartbeat_at))
+}
+
+async fn finish_job_log(
+    state: &AppState,
+    job_id: &str,
+    status: &str,
+    transcript: &[TranscriptEntry],
+) {
+    if let Err(err) =
+        JobLogRepository::finish(state.koma_db.pool(), job_id, status, transcript, None).await
+    {
+        warn!("heartbeat: failed to finish job log {job_id}: {err}");
+    }
+    state
+        .log(LogEntry::JobFinished {
+            job_id: job_id.to_string(),
+            status: status.to_string(),
+        })
+        .await;
+}
+
+/// Transcript a job published before it was cancelled.
+pub(crate) async fn persisted_transcript(state: &AppState, job_id: &str) -> Vec<TranscriptEntry> {
+    match JobLogRepository::get(state.koma_db.pool(), job_id).await {
+        Ok(Some(log)) => log.transcript,
+        _ => Vec::new(),
+    }
+}
+
+pub async fn run_heartbeat_tick(state: Arc<AppState>, timing: &HeartbeatTimingSettings) {
+    let continue_after = ContinueDelay::from_settings(timing);
     let now_ts = Utc::now().timestamp();
 
+    crate::session_title::run_session_title_pass(&state).await;
+
     let ghosts = match GhostRepository::list_all(state.koma_db.pool()).await {
         Ok(list) => list,
         Err(err) => {
@@ -288,6 +472,14 @@ pub async fn run_heartbeat_tick(state: Arc<AppState>, idle_minutes: i64, continu
             if state.is_chat_in_flight(&chat_key).await {
                 continue;
             }
+            // Held for the rest of this session's pass; another gateway
+            // sharing the DB may be handling it already.
+            let Some(_lease) = state
+                .acquire_job_lease(&format!("heartbeat:{}", session.id))
+                .await
+            else {
+                continue;
+            };
 
             let mut override_entry = state.get_heartbeat_override(&chat_key).await;
             if let Some(entry) = override_entry
@@ -310,60 +502,82 @@ pub async fn run_heartbeat_tick(state: Arc<AppState>, idle_minutes: i64, continu
             .flatten()
             .is_some();
 
+            let idle_secs = session_idle_secs(&state, timing, &ghost.id, &session.id, now_ts).await;
             let next_due = next_heartbeat_due_for_session(
                 session.updated_at,
                 had_ok_heartbeat,
                 override_entry,
-                idle_minutes,
-            );
-            state.set_heartbeat_due(&chat_key, next_due).await;
-            let heartbeat_model = state.resolve_model_for_ghost_with_override_json(
-                &ghost,
-                ghost.heartbeat_model_aliases.as_deref(),
+                idle_secs,
             );
-
+            match (next_due, override_entry.and(continue_after.schedule())) {
+                (Some(due), Some(schedule)) => {
+                    state
+                        .scheduler_set_recurring(JobKind::Heartbeat, &chat_key, due, &schedule)
+                        .await
+                }
+                _ => {
+                    state
+                        .set_heartbeat_due(&chat_key, next_due, idle_secs)
+                        .await
+                }
+            }
             if let Some(entry) = override_entry
                 && now_ts < entry.next_due
             {
-                crate::reflection::maybe_run_reflection(
+                crate::reflection::maybe_queue_reflection(
                     &state,
-                    &ghost.name,
                     &ghost.id,
                     &session.id,
                     session.updated_at,
-                    &session.operator_id,
-                    ghost.reflection_model_aliases.as_deref(),
-                    None, // uses default reflection idle_minutes
                 )
                 .await;
                 continue;
             }
 
-            if override_entry.is_none() && session.updated_at > threshold_ts {
-                crate::reflection::maybe_run_reflection(
+            if override_entry.is_none() && session.updated_at > now_ts - idle_secs {
+                crate::reflection::maybe_queue_reflection(
                     &state,
-                    &ghost.name,
                     &ghost.id,
                     &session.id,
                     session.updated_at,
-                    &session.operator_id,
-                    ghost.reflection_model_aliases.as_deref(),
-                    None, // uses default reflection idle_minutes
                 )
                 .await;
                 continue;
             }
 
-            if override_entry.is_none() && had_ok_heartbeat {
-                crate::reflection::maybe_run_reflection(
+            // Scheduled TODOs that became due since the last heartbeat force a
+            // run even when the session has been handled or HEARTBEAT.md is empty.
+            let carried_todos =
+                match JobLogRepository::carried_todos(state.koma_db.pool(), &ghost.id, &session.id)
+                    .await
+                {
+                    Ok(todos) => todos,
+                    Err(err) => {
+                        warn!(
+                            "heartbeat: failed to load TODOs for {}:{}: {err}",
+                            ghost.name, session.id
+                        );
+                        Vec::new()
+                    }
+                };
+            let last_heartbeat_at = JobLogRepository::latest_ok(
+                state.koma_db.pool(),
+                &ghost.id,
+                &session.id,
+                DbJobKind::Heartbeat,
+            )
+            .await
+            .ok()
+            .flatten()
+            .map_or(0, |log| log.started_at);
+            let todos_due = has_newly_due_todos(&carried_todos, now_ts, last_heartbeat_at);
+
+            if override_entry.is_none() && had_ok_heartbeat && !todos_due {
+                crate::reflection::maybe_queue_reflection(
                     &state,
-                    &ghost.name,
                     &ghost.id,
                     &session.id,
                     session.updated_at,
-                    &session.operator_id,
-                    ghost.reflection_model_aliases.as_deref(),
-                    None, // uses default reflection idle_minutes
                 )
                 .await;
                 continue;
@@ -373,174 +587,263 @@ pub async fn run_heartbeat_tick(state: Arc<AppState>, idle_minutes: i64, continu
                 Ok(path) => path,
                 Err(_) => continue,
             };
-            if should_skip_empty_heartbeat_file(&workspace_path).await {
-                crate::reflection::maybe_run_reflection(
+            if !todos_due && should_skip_empty_heartbeat_file(&workspace_path).await {
+                crate::reflection::maybe_queue_reflection(
                     &state,
-                    &ghost.name,
                     &ghost.id,
                     &session.id,
                     session.updated_at,
-                    &session.operator_id,
-                    ghost.reflection_model_aliases.as_deref(),
-                    None, // uses default reflection idle_minutes
                 )
                 .await;
                 continue;
             }
 
-            state.set_chat_in_flight(&chat_key).await;
-            let result = run_heartbeat_for_session(
-                state.as_ref(),
-                &ghost.name,
-                &ghost.id,
-                &session.id,
-                &session.operator_id,
-                &heartbeat_model,
+            crate::job_queue::submit(
+                &state,
+                QueuedWork::Heartbeat {
+                    ghost_id: ghost.id.clone(),
+                    session_id: session.id.clone(),
+                },
             )
             .await;
-            state.clear_chat_in_flight(&chat_key).await;
-
-            match result {
-                Ok(job_result) => {
-                    state.circuit_breaker.record_success(&heartbeat_model.alias);
-                    let text = &job_result.response_text;
-
-                    // Determine status and write job log
-                    let status = if is_response_heartbeat_ok(text) {
-                        "ok"
-                    } else if is_heartbeat_continue(text) {
-                        "continue"
-                    } else {
-                        "ran"
-                    };
-
-                    let mut job_log = JobLog::start(&ghost.id, DbJobKind::Heartbeat, &session.id);
-                    job_log.transcript = job_result.transcript;
-                    job_log.finish(status);
-
-                    if let Err(err) = JobLogRepository::insert(state.koma_db.pool(), &job_log).await
-                    {
-                        warn!(
-                            "heartbeat: failed to write job log for {}:{}: {err}",
-                            ghost.name, session.id
-                        );
-                    }
+        }
+    }
+}
 
-                    if status == "continue" {
-                        let last_seen_updated_at = Utc::now().timestamp();
-                        let next_due = last_seen_updated_at + continue_minutes * 60;
-                        state
-                            .set_heartbeat_override(&chat_key, next_due, last_seen_updated_at)
-                            .await;
-
-                        state
-                            .log(LogEntry::Heartbeat {
-                                ghost_name: ghost.name.clone(),
-                                session_id: session.id.clone(),
-                                status: "continue".to_string(),
-                            })
-                            .await;
-                    } else if status == "ran" {
-                        // Post the final response to the session as a single ghost message
-                        if let Err(err) = SessionRepository::add_message(
-                            state.koma_db.pool(),
-                            &ghost.id,
-                            &session.id,
-                            MessageRole::Ghost,
-                            vec![ContentBlock::Text { text: text.clone() }],
-                            None,
-                        )
-                        .await
-                        {
-                            warn!(
-                                "heartbeat: failed to post summary to session {}:{}: {err}",
-                                ghost.name, session.id
-                            );
-                        }
-
-                        state
-                            .log(LogEntry::Heartbeat {
-                                ghost_name: ghost.name.clone(),
-                                session_id: session.id.clone(),
-                                status: "ran".to_string(),
-                            })
-                            .await;
-                    }
-                    // status == "ok" → silent, nothing to post
-
-                    // After heartbeat completes, check if reflection should run
-                    crate::reflection::maybe_run_reflection(
-                        &state,
-                        &ghost.name,
-                        &ghost.id,
-                        &session.id,
-                        session.updated_at,
-                        &session.operator_id,
-                        ghost.reflection_model_aliases.as_deref(),
-                        None,
-                    )
+/// Run a queued heartbeat for a session.
+///
+/// Skipped when a chat is running in the session: the next tick queues the
+/// heartbeat again if it is still due.
+pub async fn run_queued_heartbeat(
+    state: &AppState,
+    ghost_id: &str,
+    session_id: &str,
+) -> Result<(), JobFailure> {
+    let pool = state.koma_db.pool();
+    let ghost = match GhostRepository::get_by_id(pool, ghost_id).await {
+        Ok(Some(ghost)) => ghost,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("ghost lookup failed: {err}"))),
+    };
+    let session = match SessionRepository::get_by_id(pool, session_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("session lookup failed: {err}"))),
+    };
+    let chat_key = format!("{}:{}:{}", session.operator_id, ghost.name, session.id);
+    if state.is_chat_in_flight(&chat_key).await {
+        return Ok(());
+    }
+
+    let heartbeat_model = state.resolve_model_for_ghost_with_override_json(
+        &ghost,
+        ghost.heartbeat_model_aliases.as_deref(),
+    );
+    let continue_after = ContinueDelay::from_settings(&state.heartbeat_settings().timing);
+    let carried_todos = match JobLogRepository::carried_todos(pool, &ghost.id, &session.id).await {
+        Ok(todos) => todos,
+        Err(err) => {
+            warn!(
+                "heartbeat: failed to load TODOs for {}:{}: {err}",
+                ghost.name, session.id
+            );
+            Vec::new()
+        }
+    };
+
+    // Insert the job log up front so live viewers can follow the run.
+    let job_log = JobLog::start(&ghost.id, DbJobKind::Heartbeat, &session.id);
+    if let Err(err) = JobLogRepository::insert_started(pool, &job_log).await {
+        warn!(
+            "heartbeat: failed to write job log for {}:{}: {err}",
+            ghost.name, session.id
+        );
+    }
+    let mut job_handle = JobHandle::new(pool.clone(), job_log.id.clone());
+    job_handle.seed_todos(carried_todos).await;
+
+    state.set_chat_in_flight(&chat_key).await;
+    let cancel = state.register_job(&job_log.id).await;
+    let result = tokio::select! {
+        result = run_heartbeat_for_session(
+            state,
+            &ghost.name,
+            &ghost.id,
+            &session.id,
+            &session.operator_id,
+            &heartbeat_model,
+            job_handle,
+        ) => result,
+        _ = cancel.cancelled() => Err(ChatError::Cancelled),
+    };
+    state.clear_job(&job_log.id).await;
+    state.clear_chat_in_flight(&chat_key).await;
+
+    match result {
+        Ok(job_result) => {
+            state.circuit_breaker.record_success(&heartbeat_model.alias);
+            let text = &job_result.response_text;
+
+            // Determine status and write job log
+            let status = if is_response_heartbeat_ok(text) {
+                "ok"
+            } else if is_heartbeat_continue(text) {
+                "continue"
+            } else {
+                "ran"
+            };
+
+            finish_job_log(state, &job_log.id, status, &job_result.transcript).await;
+
+            if status == "continue" {
+                let last_seen_updated_at = Utc::now().timestamp();
+                let next_due = continue_after.next_due(last_seen_updated_at);
+                state
+                    .set_heartbeat_override(&chat_key, next_due, last_seen_updated_at)
+                    .await;
+
+                state
+                    .log(LogEntry::Heartbeat {
+                        ghost_name: ghost.name.clone(),
+                        session_id: session.id.clone(),
+                        status: "continue".to_string(),
+                    })
                     .await;
+            } else if status == "ran" {
+                // Post the final response to the session as a single ghost message
+                if let Err(err) = SessionRepository::add_message(
+                    pool,
+                    &ghost.id,
+                    &session.id,
+                    MessageRole::Ghost,
+                    vec![ContentBlock::Text { text: text.clone() }],
+                    None,
+                )
+                .await
+                {
+                    warn!(
+                        "heartbeat: failed to post summary to session {}:{}: {err}",
+                        ghost.name, session.id
+                    );
                 }
-                Err(err) => {
-                    // Update circuit breaker for retryable provider failures
-                    if let ChatError::Provider(ref e) = err
-                        && e.is_retryable()
-                    {
-                        let reason = if e.is_rate_limited() {
-                            CooldownReason::RateLimited
-                        } else {
-                            CooldownReason::ServerError
-                        };
-                        state
-                            .circuit_breaker
-                            .record_failure(&heartbeat_model.alias, reason);
-                    }
 
-                    // Write error job log
-                    let mut job_log = JobLog::start(&ghost.id, DbJobKind::Heartbeat, &session.id);
-                    job_log.finish(&format!("error: {err}"));
-                    let _ = JobLogRepository::insert(state.koma_db.pool(), &job_log).await;
+                state
+                    .log(LogEntry::Heartbeat {
+                        ghost_name: ghost.name.clone(),
+                        session_id: session.id.clone(),
+                        status: "ran".to_string(),
+                    })
+                    .await;
+            }
+            // status == "ok" → silent, nothing to post
 
-                    state
-                        .log(LogEntry::Heartbeat {
-                            ghost_name: ghost.name.clone(),
-                            session_id: session.id.clone(),
-                            status: format!("error: {err}"),
-                        })
-                        .await;
-                }
+            // After heartbeat completes, check if reflection should run
+            crate::reflection::maybe_queue_reflection(
+                state,
+                &ghost.id,
+                &session.id,
+                session.updated_at,
+            )
+            .await;
+            Ok(())
+        }
+        Err(ChatError::Cancelled) => {
+            let transcript = persisted_transcript(state, &job_log.id).await;
+            finish_job_log(state, &job_log.id, "cancelled", &transcript).await;
+            state
+                .log(LogEntry::Heartbeat {
+                    ghost_name: ghost.name.clone(),
+                    session_id: session.id.clone(),
+                    status: "cancelled".to_string(),
+                })
+                .await;
+            Ok(())
+        }
+        Err(err) => {
+            // Update circuit breaker for retryable provider failures
+            if let ChatError::Provider(ref e) = err
+                && e.is_retryable()
+            {
+                let reason = if e.is_rate_limited() {
+                    CooldownReason::RateLimited
+                } else {
+                    CooldownReason::ServerError
+                };
+                state
+                    .circuit_breaker
+                    .record_failure(&heartbeat_model.alias, reason);
             }
+
+            // Finish the job log with the error and any partial transcript
+            let partial_transcript = match &err {
+                ChatError::ToolLoopLimitReached(pending) => pending.partial_transcript.as_slice(),
+                _ => &[],
+            };
+            finish_job_log(
+                state,
+                &job_log.id,
+                &format!("error: {err}"),
+                partial_transcript,
+            )
+            .await;
+
+            state
+                .log(LogEntry::Heartbeat {
+                    ghost_name: ghost.name.clone(),
+                    session_id: session.id.clone(),
+                    status: format!("error: {err}"),
+                })
+                .await;
+            Err(JobFailure::from_chat_error(&err))
         }
     }
 }
 
-pub fn start_heartbeat_runner(
-    state: Arc<AppState>,
-    timing: t_koma_core::HeartbeatTimingSettings,
-) -> tokio::task::JoinHandle<()> {
-    let check_seconds = timing.check_seconds;
-    let idle_minutes = timing.idle_minutes as i64;
-    let continue_minutes = timing.continue_minutes as i64;
-
-    let mut interval = interval_at(
-        Instant::now() + Duration::from_secs(check_seconds),
-        Duration::from_secs(check_seconds),
-    );
+/// Start the heartbeat loop. Settings are re-read from `state` on every tick
+/// so a config reload takes effect without restarting the runner.
+pub fn start_heartbeat_runner(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    let timing = state.heartbeat_settings().timing;
+    let mut check_seconds = timing.check_seconds;
+    let mut interval = heartbeat_interval(check_seconds);
 
     let handle = tokio::spawn(async move {
         loop {
             interval.tick().await;
-            run_heartbeat_tick(Arc::clone(&state), idle_minutes, continue_minutes).await;
+            if state.is_shutting_down() {
+                info!("heartbeat runner stopped (shutting down)");
+                break;
+            }
+            let settings = state.heartbeat_settings();
+            run_heartbeat_tick(Arc::clone(&state), &settings.timing).await;
+            crate::job_log_retention::maybe_prune_job_logs(&state, &settings.retention).await;
+            crate::prompt_cache_eviction::maybe_evict_prompt_cache(&state, &settings.prompt_cache)
+                .await;
+            crate::session_archive::maybe_archive_sessions(&state, &settings.session_archive).await;
+            crate::reminders::deliver_due_reminders(&state).await;
+            crate::scheduled_tasks::run_due_tasks(&state).await;
+
+            if settings.timing.check_seconds != check_seconds {
+                check_seconds = settings.timing.check_seconds;
+                interval = heartbeat_interval(check_seconds);
+                info!("heartbeat runner rescheduled (check_seconds={check_seconds})");
+            }
         }
     });
 
     info!(
-        "heartbeat runner started (idle_minutes={}, check_seconds={})",
-        timing.idle_minutes, check_seconds
+        "heartbeat runner started (idle_minutes={}, adaptive={}, check_seconds={})",
+        timing.idle_minutes, timing.adaptive.enabled, check_seconds
     );
     handle
 }
 
+fn heartbeat_interval(check_seconds: u64) -> tokio::time::Interval {
+    interval_at(
+        Instant::now() + Duration::from_secs(check_seconds),
+        Duration::from_secs(check_seconds),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -579,14 +882,14 @@ mod tests {
     #[test]
     fn next_due_no_override_no_previous_heartbeat() {
         let updated_at = 1000;
-        let idle_minutes = 4;
-        let due = next_heartbeat_due_for_session(updated_at, false, None, idle_minutes);
-        assert_eq!(due, Some(updated_at + idle_minutes * 60));
+        let idle_secs = 4 * 60;
+        let due = next_heartbeat_due_for_session(updated_at, false, None, idle_secs);
+        assert_eq!(due, Some(updated_at + idle_secs));
     }
 
     #[test]
     fn next_due_had_ok_heartbeat() {
-        let due = next_heartbeat_due_for_session(1000, true, None, 4);
+        let due = next_heartbeat_due_for_session(1000, true, None, 240);
         assert_eq!(due, None);
     }
 
@@ -596,14 +899,92 @@ mod tests {
             next_due: 9999,
             last_seen_updated_at: 500,
         };
-        let due = next_heartbeat_due_for_session(1000, false, Some(entry), 4);
+        let due = next_heartbeat_due_for_session(1000, false, Some(entry), 240);
         assert_eq!(due, Some(9999));
     }
 
+    #[test]
+    fn continue_delay_follows_schedule() {
+        let mut timing = HeartbeatTimingSettings::default();
+        // 2026-03-06T10:00:00Z
+        let now = 1_772_791_200;
+        assert_eq!(
+            ContinueDelay::from_settings(&timing).next_due(now),
+            now + 30 * 60
+        );
+
+        timing.continue_schedule = Some("TZ=+01:00 0 9 * * *".to_string());
+        let delay = ContinueDelay::from_settings(&timing);
+        // Next 09:00 at UTC+1 is 08:00 UTC the following day.
+        assert_eq!(delay.next_due(now), now + 22 * 3600);
+        assert_eq!(delay.schedule().as_deref(), Some("0 9 * * * (UTC+01:00)"));
+
+        timing.continue_schedule = Some("sometime".to_string());
+        assert!(matches!(
+            ContinueDelay::from_settings(&timing),
+            ContinueDelay::Minutes(30)
+        ));
+    }
+
+    #[test]
+    fn adaptive_idle_backs_off_and_tightens() {
+        let statuses = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut timing = HeartbeatTimingSettings::default();
+        assert_eq!(
+            adaptive_idle_secs(&timing, &statuses(&["ok", "ok"]), 50),
+            240
+        );
+
+        timing.adaptive.enabled = true;
+        assert_eq!(adaptive_idle_secs(&timing, &[], 0), 240);
+        // Each no-op heartbeat doubles the delay.
+        assert_eq!(adaptive_idle_secs(&timing, &statuses(&["ok"]), 0), 480);
+        assert_eq!(
+            adaptive_idle_secs(&timing, &statuses(&["ok", "ok", "ran", "ok"]), 0),
+            960
+        );
+        // Work done or a busy session halves it, down to min_idle_minutes.
+        assert_eq!(
+            adaptive_idle_secs(&timing, &statuses(&["ran", "ok"]), 0),
+            120
+        );
+        assert_eq!(adaptive_idle_secs(&timing, &[], 10), 120);
+        assert_eq!(
+            adaptive_idle_secs(&timing, &statuses(&["continue"]), 10),
+            120
+        );
+        assert_eq!(adaptive_idle_secs(&timing, &statuses(&["ok"]), 10), 240);
+        // Long no-op streaks stop at max_idle_minutes.
+        timing.adaptive.max_idle_minutes = 10;
+        assert_eq!(
+            adaptive_idle_secs(&timing, &statuses(&["ok", "ok", "ok", "ok"]), 0),
+            600
+        );
+    }
+
     #[test]
     fn response_heartbeat_ok_detection() {
         assert!(is_response_heartbeat_ok("HEARTBEAT_OK"));
         assert!(is_response_heartbeat_ok("**HEARTBEAT_OK**"));
         assert!(!is_response_heartbeat_ok("Something else"));
     }
+
+    #[test]
+    fn due_todos_surface_once() {
+        let mut backup = TodoItem::pending("Backup", Some("Run the NAS backup".to_string()));
+        backup.due_at = Some(1_000);
+        backup.recurrence = Some(t_koma_db::TodoRecurrence::Daily);
+        let todos = vec![backup, TodoItem::pending("Unscheduled", None)];
+
+        assert!(!has_newly_due_todos(&todos, 999, 0));
+        assert!(has_newly_due_todos(&todos, 1_000, 0));
+        // Already surfaced by a heartbeat that ran after it became due.
+        assert!(!has_newly_due_todos(&todos, 5_000, 1_200));
+
+        let due = job_todos::due_todos(&todos, 1_000);
+        assert_eq!(
+            format_due_todos(&due),
+            "- Backup (due 1970-01-01 00:16 UTC, daily): Run the NAS backup"
+        );
+    }
 }
@@ -0,0 +1,202 @@
+//! Job-log tracking for knowledge bulk ingest runs.
+//!
+//! Ingest requests are queued (`crate::job_queue`); the worker running one
+//! calls `KnowledgeEngine::ingest_batch`, which reports progress over a
+//! channel. This module mirrors that progress into a `job_logs` row (one TODO
+//! per item) and the log broadcast so the TUI Jobs pane can follow along.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use t_koma_db::{
+    ContentBlock, JobKind, JobLog, JobLogRepository, MessageRole, TodoItem, TodoStatus,
+    TranscriptEntry,
+};
+use t_koma_knowledge::{IngestBatchRequest, IngestProgress};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::job_queue::{JobFailure, QueuedWork};
+use crate::state::{AppState, LogEntry, emit_global_log};
+
+/// Identity of the GHOST/session a bulk ingest job is attributed to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestJobOwner {
+    pub ghost_id: String,
+    pub ghost_name: String,
+    pub session_id: String,
+}
+
+/// Queue a bulk ingest of `request` for `owner`.
+///
+/// Returns the ID of the job log the run will write to. Ingest jobs get a
+/// single attempt: a retry would save the items that succeeded again.
+pub async fn queue_ingest_job(
+    pool: &SqlitePool,
+    owner: IngestJobOwner,
+    model: &str,
+    request: IngestBatchRequest,
+) -> Result<String, String> {
+    let job_log_id = format!("ingest_{}", Uuid::new_v4());
+    let work = QueuedWork::Ingest {
+        owner: owner.clone(),
+        job_log_id: job_log_id.clone(),
+        model: model.to_string(),
+        request,
+    };
+    crate::job_queue::enqueue(pool, &work, 1)
+        .await
+        .map_err(|e| format!("Failed to queue ingest job: {e}"))?;
+    emit_status(&owner, &job_log_id, "queued".to_string());
+    Ok(job_log_id)
+}
+
+/// Run a queued bulk ingest, following its progress until it finishes.
+pub async fn run_ingest_job(
+    state: &AppState,
+    owner: IngestJobOwner,
+    job_log_id: &str,
+    model: &str,
+    request: IngestBatchRequest,
+) -> Result<(), JobFailure> {
+    let pool = state.koma_db.pool();
+    let mut log = JobLog::start(&owner.ghost_id, JobKind::Ingest, &owner.session_id);
+    log.id = job_log_id.to_string();
+    JobLogRepository::insert_started(pool, &log)
+        .await
+        .map_err(|e| JobFailure::Retry(format!("Failed to record ingest job: {e}")))?;
+
+    let mut todos: Vec<TodoItem> = request
+        .items
+        .iter()
+        .map(|item| TodoItem::pending(item.label(), None))
+        .collect();
+    let _ = JobLogRepository::update_todos(pool, &log.id, &todos).await;
+
+    let job = match state
+        .knowledge_engine()
+        .ingest_batch(&owner.ghost_name, model, request)
+        .await
+    {
+        Ok(job) => job,
+        Err(e) => {
+            let status = format!("error: {e}");
+            finish(pool, &owner, &log.id, &status).await;
+            return Err(JobFailure::Fatal(e.to_string()));
+        }
+    };
+    emit_status(&owner, &log.id, format!("started ({} items)", job.total));
+
+    let mut summary = None;
+    let mut progress = job.progress;
+    while let Some(event) = progress.recv().await {
+        if let IngestProgress::Finished { saved, failed } = event {
+            summary = Some((saved, failed));
+            break;
+        }
+        apply_progress(&mut todos, &event);
+        emit_global_log(LogEntry::JobTodos {
+            job_id: log.id.clone(),
+            todos: todos.clone(),
+        });
+        if let Err(e) = JobLogRepository::update_todos(pool, &log.id, &todos).await {
+            warn!("ingest job {}: failed to persist progress: {e}", log.id);
+        }
+    }
+
+    let status = match summary {
+        Some((saved, 0)) => format!("ok: {saved} saved"),
+        Some((saved, failed)) => format!("ok: {saved} saved, {failed} failed"),
+        None => "error: ingest task ended unexpectedly".to_string(),
+    };
+    finish(pool, &owner, &log.id, &status).await;
+    match summary {
+        Some(_) => Ok(()),
+        None => Err(JobFailure::Fatal(
+            "ingest task ended unexpectedly".to_string(),
+        )),
+    }
+}
+
+async fn finish(pool: &SqlitePool, owner: &IngestJobOwner, job_id: &str, status: &str) {
+    let transcript = vec![TranscriptEntry {
+        role: MessageRole::Ghost,
+        content: vec![ContentBlock::Text {
+            text: format!("Bulk ingest {status}"),
+        }],
+        model: None,
+    }];
+    if let Err(e) = JobLogRepository::finish(pool, job_id, status, &transcript, None).await {
+        warn!("ingest job {job_id}: failed to finish job log: {e}");
+    }
+    emit_global_log(LogEntry::JobFinished {
+        job_id: job_id.to_string(),
+        status: status.to_string(),
+    });
+    emit_status(owner, job_id, status.to_string());
+}
+
+fn apply_progress(todos: &mut [TodoItem], event: &IngestProgress) {
+    match event {
+        IngestProgress::ItemStarted { index } => {
+            if let Some(todo) = todos.get_mut(*index) {
+                todo.status = TodoStatus::InProgress;
+            }
+        }
+        IngestProgress::ItemSaved { index, path, .. } => {
+            if let Some(todo) = todos.get_mut(*index) {
+                todo.status = TodoStatus::Done;
+                todo.note = Some(path.clone());
+            }
+        }
+        IngestProgress::ItemFailed { index, error } => {
+            if let Some(todo) = todos.get_mut(*index) {
+                todo.status = TodoStatus::Skipped;
+                todo.note = Some(error.clone());
+            }
+        }
+        IngestProgress::Finished { .. } => {}
+    }
+}
+
+fn emit_status(owner: &IngestJobOwner, job_id: &str, status: String) {
+    emit_global_log(LogEntry::Ingest {
+        ghost_name: owner.ghost_name.clone(),
+        session_id: owner.session_id.clone(),
+        job_id: job_id.to_string(),
+        status,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(title: &str) -> TodoItem {
+        TodoItem::pending(title, None)
+    }
+
+    #[test]
+    fn progress_updates_matching_todo() {
+        let mut todos = vec![todo("a"), todo("b")];
+        apply_progress(&mut todos, &IngestProgress::ItemStarted { index: 1 });
+        assert_eq!(todos[1].status, TodoStatus::InProgress);
+
+        apply_progress(
+            &mut todos,
+            &IngestProgress::ItemFailed {
+                index: 1,
+                error: "boom".to_string(),
+            },
+        );
+        assert_eq!(todos[1].status, TodoStatus::Skipped);
+        assert_eq!(todos[1].note.as_deref(), Some("boom"));
+        assert_eq!(todos[0].status, TodoStatus::Pending);
+    }
+
+    #[test]
+    fn out_of_range_index_is_ignored() {
+        let mut todos = vec![todo("a")];
+        apply_progress(&mut todos, &IngestProgress::ItemStarted { index: 5 });
+        assert_eq!(todos[0].status, TodoStatus::Pending);
+    }
+}
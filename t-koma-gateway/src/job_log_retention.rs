@@ -0,0 +1,74 @@
+//! Scheduled job log pruning.
+//!
+//! Runs from the heartbeat runner loop at `[job_logs].prune_interval_minutes`;
+//! the next due time lives in the shared scheduler state.
+
+use chrono::Utc;
+use tracing::warn;
+
+use crate::scheduler::JobKind;
+use crate::state::{AppState, LogEntry};
+use t_koma_core::JobLogRetentionSettings;
+use t_koma_db::{JobLogRepository, JobLogRetention};
+
+const SCHEDULER_KEY: &str = "job_logs";
+
+/// Map config values (0 = no limit) to a DB retention policy.
+pub fn retention_policy(settings: &JobLogRetentionSettings) -> JobLogRetention {
+    JobLogRetention {
+        max_age_days: (settings.max_age_days > 0).then_some(settings.max_age_days),
+        max_per_ghost: (settings.max_per_ghost > 0).then_some(settings.max_per_ghost),
+    }
+}
+
+/// Prune job logs if the scheduled prune is due (or has never run).
+pub async fn maybe_prune_job_logs(state: &AppState, settings: &JobLogRetentionSettings) {
+    let now = Utc::now().timestamp();
+    if let Some(due) = state
+        .scheduler_get(JobKind::JobLogPrune, SCHEDULER_KEY)
+        .await
+        && now < due
+    {
+        return;
+    }
+    let next_due = now + settings.prune_interval_minutes.max(1) as i64 * 60;
+    state
+        .scheduler_set(JobKind::JobLogPrune, SCHEDULER_KEY, Some(next_due))
+        .await;
+
+    match JobLogRepository::prune(state.koma_db.pool(), &retention_policy(settings)).await {
+        Ok(0) => {}
+        Ok(deleted) => {
+            state
+                .log(LogEntry::Info {
+                    message: format!("Pruned {} job log(s)", deleted),
+                })
+                .await;
+        }
+        Err(err) => warn!("job log prune failed: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_policy_zero_disables_limit() {
+        let policy = retention_policy(&JobLogRetentionSettings {
+            max_age_days: 0,
+            max_per_ghost: 200,
+            prune_interval_minutes: 60,
+        });
+        assert_eq!(policy.max_age_days, None);
+        assert_eq!(policy.max_per_ghost, Some(200));
+        assert!(
+            retention_policy(&JobLogRetentionSettings {
+                max_age_days: 0,
+                max_per_ghost: 0,
+                prune_interval_minutes: 60,
+            })
+            .is_unbounded()
+        );
+    }
+}
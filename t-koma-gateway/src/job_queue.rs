@@ -0,0 +1,339 @@
+//! Background job queue shared by heartbeat, reflection, bulk ingest and
+//! scheduled task runs.
+//!
+//! Producers submit a [`QueuedWork`]; a fixed pool of workers claims jobs
+//! from the koma DB (`t_koma_db::job_queue`) by priority, so a queued job
+//! survives a restart and gateways sharing the DB split the work. A run that
+//! fails with a [`JobFailure::Retry`] is retried with exponential backoff
+//! until `[job_queue].max_attempts`, then kept as a dead letter.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::ingest_job::IngestJobOwner;
+use crate::session::ChatError;
+use crate::state::{AppState, LogEntry};
+use t_koma_core::JobQueueSettings;
+use t_koma_db::{
+    JobKind as DbJobKind, JobQueueRepository, NewQueuedJob, QueueStatus, QueuedJob,
+    job_queue::retry_delay_secs,
+};
+use t_koma_knowledge::IngestBatchRequest;
+
+/// How long an idle worker waits before looking for new jobs.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Session upkeep the operator may be waiting on.
+const PRIORITY_SESSION: i64 = 20;
+/// Knowledge curation.
+const PRIORITY_REFLECTION: i64 = 10;
+/// Bulk work that can wait.
+const PRIORITY_BULK: i64 = 0;
+
+/// A unit of background work, stored as the job payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueuedWork {
+    Heartbeat {
+        ghost_id: String,
+        session_id: String,
+    },
+    Reflection {
+        ghost_id: String,
+        session_id: String,
+        /// Skip the run if the session became active again.
+        enforce_idle_gate: bool,
+    },
+    Ingest {
+        owner: IngestJobOwner,
+        /// ID of the job log the run writes to.
+        job_log_id: String,
+        model: String,
+        request: IngestBatchRequest,
+    },
+    Task {
+        task_id: String,
+    },
+}
+
+impl QueuedWork {
+    pub fn kind(&self) -> DbJobKind {
+        match self {
+            QueuedWork::Heartbeat { .. } => DbJobKind::Heartbeat,
+            QueuedWork::Reflection { .. } => DbJobKind::Reflection,
+            QueuedWork::Ingest { .. } => DbJobKind::Ingest,
+            QueuedWork::Task { .. } => DbJobKind::Task,
+        }
+    }
+
+    fn priority(&self) -> i64 {
+        match self {
+            QueuedWork::Heartbeat { .. } | QueuedWork::Task { .. } => PRIORITY_SESSION,
+            QueuedWork::Reflection { .. } => PRIORITY_REFLECTION,
+            QueuedWork::Ingest { .. } => PRIORITY_BULK,
+        }
+    }
+
+    /// Work with the same key is only queued once at a time.
+    fn dedupe_key(&self) -> Option<String> {
+        match self {
+            QueuedWork::Heartbeat { session_id, .. } => Some(format!("heartbeat:{session_id}")),
+            QueuedWork::Reflection { session_id, .. } => Some(format!("reflection:{session_id}")),
+            QueuedWork::Ingest { job_log_id, .. } => Some(format!("ingest:{job_log_id}")),
+            QueuedWork::Task { task_id } => Some(format!("task:{task_id}")),
+        }
+    }
+}
+
+/// Why a queued run didn't finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobFailure {
+    /// Worth another attempt after a backoff (rate limits, busy session).
+    Retry(String),
+    /// Would fail the same way again; goes straight to the dead letters.
+    Fatal(String),
+}
+
+impl JobFailure {
+    /// Retry transient provider errors, give up on anything else.
+    pub fn from_chat_error(err: &ChatError) -> Self {
+        match err {
+            ChatError::Provider(e) if e.is_retryable() => JobFailure::Retry(err.to_string()),
+            _ => JobFailure::Fatal(err.to_string()),
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            JobFailure::Retry(message) | JobFailure::Fatal(message) => message,
+        }
+    }
+}
+
+/// Queue `work` with up to `max_attempts` attempts. Returns the queue job
+/// ID, or `None` when the same work is already queued or running.
+pub async fn enqueue(
+    pool: &SqlitePool,
+    work: &QueuedWork,
+    max_attempts: u32,
+) -> Result<Option<String>, String> {
+    let now = Utc::now().timestamp();
+    let payload = serde_json::to_string(work).map_err(|e| e.to_string())?;
+    let job = NewQueuedJob {
+        kind: work.kind(),
+        dedupe_key: work.dedupe_key(),
+        payload,
+        priority: work.priority(),
+        max_attempts,
+        run_after: now,
+    };
+    JobQueueRepository::enqueue(pool, &job, now)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Queue `work` with the configured number of attempts, logging failures.
+pub async fn submit(state: &AppState, work: QueuedWork) {
+    let max_attempts = state.heartbeat_settings().job_queue.max_attempts;
+    if let Err(err) = enqueue(state.koma_db.pool(), &work, max_attempts).await {
+        warn!("job queue: failed to queue {} job: {err}", work.kind());
+    }
+}
+
+/// Start `[job_queue].workers` workers.
+pub fn start_job_queue_workers(state: Arc<AppState>) -> Vec<JoinHandle<()>> {
+    let workers = state.heartbeat_settings().job_queue.workers.max(1);
+    info!("job queue started ({workers} workers)");
+    (0..workers)
+        .map(|_| tokio::spawn(worker_loop(Arc::clone(&state))))
+        .collect()
+}
+
+async fn worker_loop(state: Arc<AppState>) {
+    loop {
+        if state.is_shutting_down() {
+            break;
+        }
+        let settings = state.heartbeat_settings().job_queue;
+        let pool = state.koma_db.pool();
+        let now = Utc::now().timestamp();
+        match JobQueueRepository::release_expired(pool, now).await {
+            Ok(0) => {}
+            Ok(count) => info!("job queue: released {count} job(s) with an expired lease"),
+            Err(err) => warn!("job queue: failed to release expired jobs: {err}"),
+        }
+        match JobQueueRepository::claim_next(pool, now, settings.lease_seconds as i64).await {
+            Ok(Some(job)) => run_claimed(&state, job, &settings).await,
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(err) => {
+                warn!("job queue: failed to claim a job: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn run_claimed(state: &Arc<AppState>, mut job: QueuedJob, settings: &JobQueueSettings) {
+    let pool = state.koma_db.pool();
+    let payload = job.payload.clone();
+    let work = async {
+        match serde_json::from_str::<QueuedWork>(&payload) {
+            Ok(work) => run_work(state, work).await,
+            Err(err) => Err(JobFailure::Fatal(format!("invalid payload: {err}"))),
+        }
+    };
+    let result = {
+        let lease_secs = settings.lease_seconds as i64;
+        let renew_every = renew_interval(settings.lease_seconds);
+        let mut renew =
+            tokio::time::interval_at(tokio::time::Instant::now() + renew_every, renew_every);
+        tokio::pin!(work);
+        // Keep the lease alive so a long run is not requeued under us.
+        loop {
+            tokio::select! {
+                result = &mut work => break result,
+                _ = renew.tick() => {
+                    match JobQueueRepository::renew(pool, &mut job, lease_secs, Utc::now().timestamp()).await {
+                        Ok(true) => {}
+                        Ok(false) => warn!("job queue: lost the lease on {} job {}", job.kind, job.id),
+                        Err(err) => warn!("job queue: failed to renew the lease on {}: {err}", job.id),
+                    }
+                }
+            }
+        }
+    };
+    let Err(failure) = result else {
+        match JobQueueRepository::complete(pool, &job).await {
+            Ok(true) => {}
+            Ok(false) => warn!(
+                "job queue: {} job {} finished after its lease was lost",
+                job.kind, job.id
+            ),
+            Err(err) => warn!("job queue: failed to complete {}: {err}", job.id),
+        }
+        return;
+    };
+
+    let now = Utc::now().timestamp();
+    let retry_at = match failure {
+        JobFailure::Retry(_) => Some(
+            now + retry_delay_secs(
+                job.attempts,
+                settings.backoff_base_seconds,
+                settings.backoff_max_seconds,
+            ),
+        ),
+        JobFailure::Fatal(_) => None,
+    };
+    let error = failure.message();
+    match JobQueueRepository::fail(pool, &job, error, retry_at, now).await {
+        Ok(Some(QueueStatus::Dead)) => {
+            warn!(
+                "job queue: {} job {} is dead after {} attempt(s): {error}",
+                job.kind, job.id, job.attempts
+            );
+            state
+                .log(LogEntry::Queue {
+                    job_id: job.id.clone(),
+                    job_kind: job.kind.to_string(),
+                    status: format!("dead after {} attempt(s): {error}", job.attempts),
+                })
+                .await;
+        }
+        Ok(None) => warn!(
+            "job queue: {} job {} failed after its lease was lost: {error}",
+            job.kind, job.id
+        ),
+        Ok(Some(_)) => info!(
+            "job queue: {} job {} failed (attempt {}/{}), retrying: {error}",
+            job.kind, job.id, job.attempts, job.max_attempts
+        ),
+        Err(err) => warn!("job queue: failed to record failure of {}: {err}", job.id),
+    }
+}
+
+/// How often a running job renews its lease: three times per lease, so one
+/// missed renewal does not let it expire.
+fn renew_interval(lease_seconds: u64) -> Duration {
+    Duration::from_secs((lease_seconds / 3).max(1))
+}
+
+async fn run_work(state: &Arc<AppState>, work: QueuedWork) -> Result<(), JobFailure> {
+    match work {
+        QueuedWork::Heartbeat {
+            ghost_id,
+            session_id,
+        } => crate::heartbeat::run_queued_heartbeat(state, &ghost_id, &session_id).await,
+        QueuedWork::Reflection {
+            ghost_id,
+            session_id,
+            enforce_idle_gate,
+        } => {
+            crate::reflection::run_queued_reflection(
+                state,
+                &ghost_id,
+                &session_id,
+                enforce_idle_gate,
+            )
+            .await
+        }
+        QueuedWork::Ingest {
+            owner,
+            job_log_id,
+            model,
+            request,
+        } => crate::ingest_job::run_ingest_job(state, owner, &job_log_id, &model, request).await,
+        QueuedWork::Task { task_id } => {
+            crate::scheduled_tasks::run_queued_task(state, &task_id).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_payload_roundtrip() {
+        let work = QueuedWork::Reflection {
+            ghost_id: "ghost_1".to_string(),
+            session_id: "sess_1".to_string(),
+            enforce_idle_gate: true,
+        };
+        let payload = serde_json::to_string(&work).unwrap();
+        assert!(payload.contains(r#""type":"reflection""#));
+        assert!(matches!(
+            serde_json::from_str::<QueuedWork>(&payload).unwrap(),
+            QueuedWork::Reflection {
+                enforce_idle_gate: true,
+                ..
+            }
+        ));
+        assert_eq!(work.dedupe_key().as_deref(), Some("reflection:sess_1"));
+    }
+
+    #[test]
+    fn test_session_work_runs_before_bulk_work() {
+        let heartbeat = QueuedWork::Heartbeat {
+            ghost_id: "ghost_1".to_string(),
+            session_id: "sess_1".to_string(),
+        };
+        let task = QueuedWork::Task {
+            task_id: "task_1".to_string(),
+        };
+        let reflection = QueuedWork::Reflection {
+            ghost_id: "ghost_1".to_string(),
+            session_id: "sess_1".to_string(),
+            enforce_idle_gate: false,
+        };
+        assert_eq!(heartbeat.priority(), task.priority());
+        assert!(heartbeat.priority() > reflection.priority());
+        assert!(reflection.priority() > PRIORITY_BULK);
+    }
+}
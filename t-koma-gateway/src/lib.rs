@@ -1,26 +1,48 @@
+pub mod api;
+pub mod attachments;
 pub mod chat;
 pub mod circuit_breaker;
+pub mod client_limits;
+pub mod config_watcher;
 pub mod content;
+pub mod coordination;
 pub mod cron;
 pub mod discord;
+pub mod email;
 pub mod gateway_message;
 pub mod heartbeat;
+pub mod ingest_job;
+pub mod job_log_retention;
+pub mod job_queue;
 pub mod log_bridge;
+pub mod mcp;
 pub mod model_registry;
 pub mod operator_flow;
 pub mod prompt;
+pub mod prompt_cache_eviction;
 pub mod providers;
 pub mod reflection;
+pub mod reflection_quality;
+pub mod reminders;
+pub mod replica;
+pub mod scheduled_tasks;
 pub mod scheduler;
 pub mod server;
 pub mod session;
+pub mod session_archive;
+pub mod session_title;
+pub mod slack;
 pub mod state;
 pub mod system_info;
+pub mod telegram;
 pub mod tools;
+pub mod transcription;
 pub mod web;
+#[cfg(feature = "web_ui")]
+pub mod web_ui;
 
 pub use providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    ImageSource, Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
     extract_all_text, extract_text, extract_tool_uses, has_tool_uses,
 };
 pub use session::{ChatError, SessionChat};
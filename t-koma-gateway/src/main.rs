@@ -1,15 +1,27 @@
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use t_koma_gateway::state::LogEntry;
 
 use t_koma_gateway::discord::start_discord_bot;
+use t_koma_gateway::email::{Mailer, start_email_bridge};
 use t_koma_gateway::server;
+use t_koma_gateway::slack::start_slack_bot;
 use t_koma_gateway::state::AppState;
+use t_koma_gateway::telegram::start_telegram_bot;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(cmd) = std::env::args().nth(1)
+        && cmd == "mcp-serve"
+    {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        return run_mcp_serve(&args).await;
+    }
+
     // Initialize tracing
     let env_filter =
         tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
@@ -28,37 +40,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.default_model_id()
     );
 
-    // Initialize database
-    let koma_db = t_koma_db::KomaDbPool::new().await?;
-    info!("T-KOMA database initialized");
+    // Initialize database. A read-only replica leaves writes (and every
+    // background job) to the primary gateway.
+    let read_only = config.settings.gateway.read_only;
+    let koma_db = if read_only {
+        let db = t_koma_db::KomaDbPool::open_read_only().await?;
+        info!("T-KOMA database opened read-only (replica mode)");
+        db
+    } else {
+        let db = t_koma_db::KomaDbPool::new().await?;
+        info!("T-KOMA database initialized");
+        db
+    };
 
     // Prune old pending users (older than 1 hour)
-    match t_koma_db::OperatorRepository::prune_pending(koma_db.pool(), 1).await {
-        Ok(count) => {
-            if count > 0 {
-                info!("Pruned {} expired pending operators", count);
+    if !read_only {
+        match t_koma_db::OperatorRepository::prune_pending(koma_db.pool(), 1).await {
+            Ok(count) => {
+                if count > 0 {
+                    info!("Pruned {} expired pending operators", count);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to prune pending operators: {}", e);
             }
-        }
-        Err(e) => {
-            tracing::warn!("Failed to prune pending operators: {}", e);
         }
     }
 
-    let registry = t_koma_gateway::model_registry::build_from_config(&config)?;
+    let mut registry = t_koma_gateway::model_registry::build_from_config(&config)?;
+    if !read_only
+        && let Err(e) = t_koma_gateway::model_registry::sync_pricing(koma_db.pool(), &config).await
+    {
+        tracing::warn!("Failed to sync model pricing: {}", e);
+    }
+    if let Err(e) =
+        t_koma_gateway::model_registry::apply_cached_catalog(koma_db.pool(), &mut registry.models)
+            .await
+    {
+        tracing::warn!("Failed to load cached model catalog: {}", e);
+    }
     let default_model_chain = registry.default_model_chain;
     let models = registry.models;
 
     // Get Discord token from secrets
     let discord_token = config.discord_bot_token().map(|s| s.to_string());
+    let telegram_token = config.telegram_bot_token().map(|s| s.to_string());
+    let slack_bot_token = config.slack_bot_token().map(|s| s.to_string());
+    let email_mailer = config
+        .email_enabled()
+        .then(|| config.email_password())
+        .flatten()
+        .map(|password| Mailer::new(config.settings.email.clone(), password.to_string()));
 
-    // Create shared application state
-    let knowledge_settings =
-        t_koma_knowledge::KnowledgeSettings::from(&config.settings.tools.knowledge);
-    let knowledge_engine = Arc::new(
-        t_koma_knowledge::KnowledgeEngine::open(knowledge_settings)
-            .await
-            .expect("failed to open knowledge store"),
-    );
+    // Create shared application state. Embeddings go through the gateway's
+    // provider layer so they share API keys, retries and the circuit breaker.
+    let circuit_breaker = Arc::new(t_koma_gateway::circuit_breaker::CircuitBreaker::new());
+    let knowledge_engine = open_knowledge_engine(&config, Arc::clone(&circuit_breaker)).await;
 
     // Check for embedding provider/model changes and reindex in the background
     {
@@ -99,32 +136,94 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         skill_paths.push(project_path.to_path_buf());
     }
 
-    let compaction_config = {
-        let cs = &config.settings.compaction;
-        t_koma_gateway::chat::compaction::CompactionConfig {
-            threshold: cs.threshold,
-            keep_window: cs.keep_window,
-            mask_preview_chars: cs.mask_preview_chars,
-        }
-    };
-    let state = Arc::new(AppState::new(
+    // Discover MCP server tools before chat tool managers are built
+    if !config.settings.mcp.servers.is_empty() {
+        let count = t_koma_gateway::mcp::start(&config.settings.mcp).await;
+        tracing::info!(count, "MCP tools registered");
+    }
+    if !config.settings.tools.webhooks.is_empty() {
+        t_koma_gateway::tools::webhook::register(
+            &config.settings.tools.webhooks,
+            &config.settings.tools.untrusted_content,
+        );
+    }
+
+    let compaction_config =
+        t_koma_gateway::chat::compaction::CompactionConfig::from(&config.settings.compaction);
+    let mut app_state = AppState::new(
         default_model_chain,
         models,
         koma_db,
         knowledge_engine,
         skill_paths,
         compaction_config,
-    ));
+    )
+    .with_circuit_breaker(circuit_breaker);
+    if config.settings.gateway.coordinate_jobs && !read_only {
+        app_state = app_state.with_job_coordination(config.settings.gateway.job_lease_secs);
+        info!(
+            "Job coordination enabled (lease {}s)",
+            config.settings.gateway.job_lease_secs
+        );
+    }
+    let state = Arc::new(app_state);
     state.set_discord_bot_token(discord_token.clone()).await;
-    state.start_shared_knowledge_watcher().await;
+    state.set_telegram_bot_token(telegram_token.clone()).await;
+    state.set_slack_bot_token(slack_bot_token.clone()).await;
+    state.set_email_mailer(email_mailer.clone()).await;
     state
-        .start_heartbeat_runner(config.settings.heartbeat_timing.clone())
+        .set_gateway_secret(config.gateway_secret().map(str::to_string))
         .await;
     state
-        .start_cron_runner(config.settings.heartbeat_timing.check_seconds)
+        .set_transcriber(t_koma_gateway::transcription::from_settings(
+            &config.settings,
+        ))
         .await;
+    state
+        .session_chat
+        .set_thinking_display(config.settings.thinking.display);
+    state
+        .session_chat
+        .set_tool_settings(config.settings.tools.clone());
+    state.set_job_generation(
+        t_koma_gateway::state::JobGenerationOverrides::from_settings(&config.settings),
+    );
+    state.set_output_filters(&config.settings.output_filters);
+    state.set_compaction(&config.settings);
+    state.set_heartbeat_settings(
+        t_koma_gateway::state::HeartbeatRunnerSettings::from_settings(&config.settings),
+    );
+    state
+        .client_limiter
+        .set_settings(config.settings.client_limits.clone());
+    state.set_loaded_settings(config.settings.clone());
+    state.start_shared_knowledge_watcher().await;
+    let _config_watcher = t_koma_gateway::config_watcher::start_config_watcher(Arc::clone(&state));
+    if read_only {
+        t_koma_gateway::replica::start_replica_follower(Arc::clone(&state));
+    } else {
+        match state.restore_scheduler().await {
+            Ok(0) => {}
+            Ok(count) => info!("Restored {} scheduled job(s) from last shutdown", count),
+            Err(e) => warn!("Failed to restore scheduler state: {}", e),
+        }
+        state.start_job_queue().await;
+        state.start_heartbeat_runner().await;
+        state
+            .start_cron_runner(config.settings.heartbeat_timing.check_seconds)
+            .await;
+        t_koma_gateway::circuit_breaker::start_half_open_timer(Arc::clone(&state));
+        if config.settings.openrouter.catalog_refresh_hours > 0 {
+            t_koma_gateway::model_registry::start_catalog_refresh(
+                Arc::clone(&state),
+                config.settings.openrouter.catalog_refresh_hours,
+            );
+        }
+    }
 
     // Start append-only JSONL log file writer if enabled
+    let log_writer_stop = CancellationToken::new();
+    let mut log_writer_task = None;
     if config.settings.logging.file_enabled {
         let log_path = config
             .settings
@@ -140,16 +239,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .append(true)
             .open(&log_path)?;
         let mut rx = state.subscribe_logs();
-        tokio::spawn(async move {
+        let stop = log_writer_stop.clone();
+        log_writer_task = Some(tokio::spawn(async move {
             use std::io::Write;
+            use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+            #[derive(serde::Serialize)]
+            struct Line {
+                ts: String,
+                #[serde(flatten)]
+                entry: LogEntry,
+            }
             let mut writer = std::io::BufWriter::new(file);
-            while let Ok(entry) = rx.recv().await {
-                #[derive(serde::Serialize)]
-                struct Line {
-                    ts: String,
-                    #[serde(flatten)]
-                    entry: LogEntry,
-                }
+            let mut write = |entry: LogEntry| {
                 let line = Line {
                     ts: chrono::Utc::now().to_rfc3339(),
                     entry,
@@ -158,14 +260,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     let _ = writeln!(writer, "{json}");
                     let _ = writer.flush();
                 }
+            };
+            loop {
+                tokio::select! {
+                    received = rx.recv() => match received {
+                        Ok(entry) => write(entry),
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = stop.cancelled() => {
+                        // Write whatever was logged before the stop request.
+                        loop {
+                            match rx.try_recv() {
+                                Ok(entry) => write(entry),
+                                Err(TryRecvError::Lagged(_)) => continue,
+                                Err(_) => break,
+                            }
+                        }
+                        break;
+                    }
+                }
             }
-        });
+        }));
         info!("JSONL log writer started: {}", log_path);
     }
 
     // Start Discord bot if enabled and token is present
-    let discord_client = if config.discord_enabled() {
-        match start_discord_bot(discord_token, Arc::clone(&state)).await? {
+    let discord_client = if read_only {
+        info!("Discord bot not started (read-only replica)");
+        None
+    } else if config.discord_enabled() {
+        match start_discord_bot(discord_token, &config.settings.discord, Arc::clone(&state)).await?
+        {
             Some(mut client) => {
                 info!("Discord bot started");
                 // Spawn Discord client in background
@@ -186,6 +312,84 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    // Start Telegram bot if enabled and token is present
+    let telegram_task = if read_only {
+        info!("Telegram bot not started (read-only replica)");
+        None
+    } else if config.telegram_enabled() {
+        match start_telegram_bot(
+            telegram_token,
+            config.settings.telegram.poll_timeout_secs,
+            Arc::clone(&state),
+        )
+        .await?
+        {
+            Some(bot) => {
+                info!("Telegram bot started");
+                Some(tokio::spawn(bot.run()))
+            }
+            None => {
+                info!("Telegram bot not started");
+                None
+            }
+        }
+    } else {
+        info!(
+            "Telegram bot not configured (set TELEGRAM_BOT_TOKEN and enable [telegram] in config to enable)"
+        );
+        None
+    };
+
+    // Start Slack app if enabled and both tokens are present
+    let slack_task = if read_only {
+        info!("Slack app not started (read-only replica)");
+        None
+    } else if config.slack_enabled() {
+        match start_slack_bot(
+            slack_bot_token,
+            config.slack_app_token().map(|s| s.to_string()),
+            Arc::clone(&state),
+        )
+        .await?
+        {
+            Some(bot) => {
+                info!("Slack app started");
+                Some(tokio::spawn(bot.run()))
+            }
+            None => {
+                info!("Slack app not started");
+                None
+            }
+        }
+    } else {
+        info!(
+            "Slack app not configured (set SLACK_BOT_TOKEN and SLACK_APP_TOKEN and enable [slack] in config to enable)"
+        );
+        None
+    };
+
+    // Start email bridge if enabled and the mailbox is configured
+    let email_task = if read_only {
+        info!("Email bridge not started (read-only replica)");
+        None
+    } else if email_mailer.is_some() {
+        match start_email_bridge(email_mailer, Arc::clone(&state)).await? {
+            Some(bridge) => {
+                info!("Email bridge started");
+                Some(tokio::spawn(bridge.run()))
+            }
+            None => {
+                info!("Email bridge not started");
+                None
+            }
+        }
+    } else {
+        info!(
+            "Email bridge not configured (set EMAIL_PASSWORD and enable [email] with address, imap_host and smtp_host to enable)"
+        );
+        None
+    };
+
     // Security: Verify localhost-only binding
     if config.settings.gateway.host != "127.0.0.1" && config.settings.gateway.host != "localhost" {
         tracing::warn!(
@@ -201,13 +405,162 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         bind_addr
     );
 
-    // Run server (this blocks)
-    let server_result = server::run(state, &bind_addr).await;
+    // Run server until it fails or a shutdown signal arrives. Dropping the
+    // server future closes the listener, so no new connections are accepted.
+    let server_result = tokio::select! {
+        result = server::run(Arc::clone(&state), &bind_addr) => result,
+        signal = shutdown_signal() => {
+            info!("Received {}, shutting down", signal);
+            Ok(())
+        }
+    };
+
+    // Let in-flight chats and jobs finish while interfaces answer new
+    // messages with a shutdown notice
+    state.begin_shutdown();
+    let drain_timeout = Duration::from_secs(config.settings.gateway.shutdown_drain_secs);
+    info!(
+        "Draining in-flight chats (up to {}s)",
+        drain_timeout.as_secs()
+    );
+    let cancelled = state.drain_in_flight(drain_timeout).await;
+    if cancelled == 0 {
+        info!("All in-flight chats finished");
+    }
 
-    // If we get here, the server stopped
     if let Some(task) = discord_client {
         task.abort();
     }
+    if let Some(task) = telegram_task {
+        task.abort();
+    }
+    if let Some(task) = slack_task {
+        task.abort();
+    }
+    if let Some(task) = email_task {
+        task.abort();
+    }
+
+    if !read_only {
+        match state.persist_scheduler().await {
+            Ok(count) => info!("Saved {} scheduled job(s)", count),
+            Err(e) => warn!("Failed to save scheduler state: {}", e),
+        }
+        state.release_job_leases().await;
+    }
+
+    state
+        .log(LogEntry::Info {
+            message: "Gateway stopped".to_string(),
+        })
+        .await;
+    log_writer_stop.cancel();
+    if let Some(task) = log_writer_task
+        && tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .is_err()
+    {
+        warn!("JSONL log writer did not finish in time");
+    }
+
+    state.close_pools().await;
+    info!("Shutdown complete");
 
     server_result
 }
+
+/// Resolves on SIGINT (Ctrl-C) or SIGTERM, with the signal's name.
+async fn shutdown_signal() -> &'static str {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+async fn open_knowledge_engine(
+    config: &t_koma_core::Config,
+    circuit_breaker: Arc<t_koma_gateway::circuit_breaker::CircuitBreaker>,
+) -> Arc<t_koma_knowledge::KnowledgeEngine> {
+    let knowledge_settings =
+        t_koma_knowledge::KnowledgeSettings::from(&config.settings.tools.knowledge);
+    let embedder = t_koma_knowledge::EmbeddingClient::with_provider(
+        &knowledge_settings,
+        Arc::new(
+            t_koma_gateway::providers::embeddings::GatewayEmbeddings::from_config(
+                config,
+                circuit_breaker,
+            ),
+        ),
+    );
+    Arc::new(
+        t_koma_knowledge::KnowledgeEngine::open_with_embedder(knowledge_settings, embedder)
+            .await
+            .expect("failed to open knowledge store"),
+    )
+}
+
+/// Serve a ghost's knowledge tools to an MCP client over stdin/stdout.
+///
+/// Usage: `t-koma-gateway mcp-serve --ghost <name>`. Stdout carries the
+/// protocol, so logs go to stderr.
+async fn run_mcp_serve(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let ghost_name = args
+        .iter()
+        .position(|arg| arg == "--ghost")
+        .and_then(|idx| args.get(idx + 1))
+        .ok_or("usage: t-koma-gateway mcp-serve --ghost <name>")?;
+
+    let config = t_koma_core::Config::load()?;
+    let koma_db = if config.settings.gateway.read_only {
+        t_koma_db::KomaDbPool::open_read_only().await?
+    } else {
+        t_koma_db::KomaDbPool::new().await?
+    };
+    let circuit_breaker = Arc::new(t_koma_gateway::circuit_breaker::CircuitBreaker::new());
+    let knowledge_engine = open_knowledge_engine(&config, circuit_breaker).await;
+
+    let server = t_koma_gateway::mcp::server::KnowledgeMcpServer::for_ghost(
+        &koma_db,
+        knowledge_engine,
+        ghost_name,
+    )
+    .await?
+    .with_tool_timeouts(config.settings.tools.timeouts.clone());
+    info!(
+        "Serving knowledge tools for ghost '{}' over MCP",
+        ghost_name
+    );
+    server
+        .serve(tokio::io::stdin(), tokio::io::stdout())
+        .await?;
+    Ok(())
+}
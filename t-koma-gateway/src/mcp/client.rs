@@ -0,0 +1,433 @@
+//! Minimal Model Context Protocol client over stdio.
+//!
+//! Messages are newline-delimited JSON-RPC 2.0. The client performs the
+//! `initialize` handshake, lists tools (following `nextCursor`) and calls
+//! them. Server-initiated `ping` requests are answered; any other server
+//! request gets "method not found", since no client capabilities are
+//! advertised.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use t_koma_core::config::McpServerSettings;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, oneshot};
+use tracing::debug;
+
+pub(super) const PROTOCOL_VERSION: &str = "2025-03-26";
+
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum McpError {
+    #[error("failed to start server: {0}")]
+    Launch(String),
+    #[error("server connection closed")]
+    Closed,
+    #[error("{0} timed out")]
+    Timeout(String),
+    #[error("server error: {0}")]
+    Server(String),
+    #[error("invalid server response: {0}")]
+    Protocol(String),
+}
+
+/// A tool advertised by an MCP server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+}
+
+fn default_input_schema() -> Value {
+    json!({"type": "object"})
+}
+
+/// Outcome of a `tools/call`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpCallResult {
+    pub text: String,
+    pub is_error: bool,
+}
+
+/// A connected MCP server.
+pub struct McpClient {
+    _child: Option<Child>,
+    /// Shared with the reader task, which answers server `ping`s.
+    writer: Arc<Mutex<Writer>>,
+    pending: Pending,
+    closed: Arc<AtomicBool>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl McpClient {
+    /// Launch the configured server process and complete the handshake.
+    pub async fn spawn(name: &str, settings: &McpServerSettings) -> Result<Self, McpError> {
+        let mut child = Command::new(&settings.command)
+            .args(&settings.args)
+            .envs(&settings.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| McpError::Launch(format!("{}: {e}", settings.command)))?;
+
+        let stdin = child.stdin.take().ok_or(McpError::Closed)?;
+        let stdout = child.stdout.take().ok_or(McpError::Closed)?;
+        if let Some(stderr) = child.stderr.take() {
+            let name = name.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!(server = %name, "mcp stderr: {line}");
+                }
+            });
+        }
+
+        let mut client =
+            Self::connect(stdout, stdin, Duration::from_secs(settings.timeout_seconds));
+        client._child = Some(child);
+        client.initialize().await?;
+        Ok(client)
+    }
+
+    /// Wrap an already-open transport. Call [`initialize`](Self::initialize) next.
+    pub fn connect(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        timeout: Duration,
+    ) -> Self {
+        let writer: Arc<Mutex<Writer>> = Arc::new(Mutex::new(Box::new(writer)));
+        let pending: Pending = Arc::default();
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let reader_pending = Arc::clone(&pending);
+        let reader_closed = Arc::clone(&closed);
+        let reply_writer = Arc::clone(&writer);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                match parse_message(&line) {
+                    Some(Incoming::Response(id, result)) => {
+                        let sender = reader_pending.lock().ok().and_then(|mut p| p.remove(&id));
+                        if let Some(sender) = sender {
+                            let _ = sender.send(result);
+                        }
+                    }
+                    Some(Incoming::Request(id, method)) => {
+                        let reply = if method == "ping" {
+                            json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                        } else {
+                            json!({"jsonrpc": "2.0", "id": id, "error": {
+                                "code": -32601,
+                                "message": format!("method not found: {method}"),
+                            }})
+                        };
+                        let _ = write_line(&mut **reply_writer.lock().await, &reply).await;
+                    }
+                    None => {}
+                }
+            }
+            // Transport closed: fail everything still waiting.
+            reader_closed.store(true, Ordering::Relaxed);
+            if let Ok(mut pending) = reader_pending.lock() {
+                pending.clear();
+            }
+        });
+
+        Self {
+            _child: None,
+            writer,
+            pending,
+            closed,
+            next_id: AtomicU64::new(1),
+            timeout,
+        }
+    }
+
+    /// Whether the transport is still open.
+    pub fn is_alive(&self) -> bool {
+        !self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Perform the `initialize` handshake.
+    pub async fn initialize(&self) -> Result<(), McpError> {
+        self.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "t-koma", "version": env!("CARGO_PKG_VERSION")},
+            }),
+        )
+        .await?;
+        self.notify("notifications/initialized").await
+    }
+
+    /// All tools the server advertises.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>, McpError> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            let page: Vec<McpToolInfo> = serde_json::from_value(
+                result.get("tools").cloned().unwrap_or(Value::Array(vec![])),
+            )
+            .map_err(|e| McpError::Protocol(format!("tools/list: {e}")))?;
+            tools.extend(page);
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Call a tool and flatten its content to text.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<McpCallResult, McpError> {
+        let result = self
+            .request("tools/call", json!({"name": name, "arguments": arguments}))
+            .await?;
+        Ok(McpCallResult {
+            text: content_to_text(&result),
+            is_error: result
+                .get("isError")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, McpError> {
+        if !self.is_alive() {
+            return Err(McpError::Closed);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|_| McpError::Closed)?
+            .insert(id, tx);
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if write_line(&mut **self.writer.lock().await, &message)
+            .await
+            .is_err()
+        {
+            self.forget(id);
+            return Err(McpError::Closed);
+        }
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(McpError::Server(message)),
+            Ok(Err(_)) => Err(McpError::Closed),
+            Err(_) => {
+                self.forget(id);
+                Err(McpError::Timeout(method.to_string()))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str) -> Result<(), McpError> {
+        let message = json!({"jsonrpc": "2.0", "method": method});
+        write_line(&mut **self.writer.lock().await, &message)
+            .await
+            .map_err(|_| McpError::Closed)
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+}
+
+pub(super) async fn write_line(
+    writer: &mut (dyn AsyncWrite + Send + Unpin),
+    message: &Value,
+) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await
+}
+
+enum Incoming {
+    Response(u64, Result<Value, String>),
+    Request(Value, String),
+}
+
+/// Classify one JSON-RPC line; notifications and garbage yield `None`.
+fn parse_message(line: &str) -> Option<Incoming> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let id = value.get("id")?;
+    if let Some(method) = value.get("method").and_then(Value::as_str) {
+        return Some(Incoming::Request(id.clone(), method.to_string()));
+    }
+    let id = id.as_u64()?;
+    if let Some(error) = value.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error")
+            .to_string();
+        return Some(Incoming::Response(id, Err(message)));
+    }
+    Some(Incoming::Response(
+        id,
+        Ok(value.get("result").cloned().unwrap_or(Value::Null)),
+    ))
+}
+
+/// Flatten `tools/call` content blocks to text.
+fn content_to_text(result: &Value) -> String {
+    let Some(blocks) = result.get("content").and_then(Value::as_array) else {
+        return result
+            .get("structuredContent")
+            .map(Value::to_string)
+            .unwrap_or_default();
+    };
+    blocks
+        .iter()
+        .map(|block| match block.get("type").and_then(Value::as_str) {
+            Some("text") => block
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            Some("resource") => block
+                .pointer("/resource/text")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    format!(
+                        "[resource: {}]",
+                        block
+                            .pointer("/resource/uri")
+                            .and_then(Value::as_str)
+                            .unwrap_or("unknown")
+                    )
+                }),
+            Some(kind) => format!(
+                "[{kind} content: {}]",
+                block
+                    .get("mimeType")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown type")
+            ),
+            None => block.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// In-memory MCP server with an `echo` tool and a failing `fail` tool,
+    /// listed over two pages.
+    async fn fake_server(stream: tokio::io::DuplexStream) {
+        let (read, mut write) = tokio::io::split(stream);
+        let mut lines = BufReader::new(read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let message: Value = serde_json::from_str(&line).unwrap();
+            let Some(id) = message.get("id").cloned() else {
+                continue;
+            };
+            let params = &message["params"];
+            let result = match message["method"].as_str().unwrap_or_default() {
+                "initialize" => {
+                    json!({"protocolVersion": PROTOCOL_VERSION, "capabilities": {"tools": {}}})
+                }
+                "tools/list" if params.get("cursor").is_none() => json!({
+                    "tools": [{"name": "echo", "description": "Echo text", "inputSchema": {
+                        "type": "object", "properties": {"text": {"type": "string"}}
+                    }}],
+                    "nextCursor": "page-2",
+                }),
+                "tools/list" => json!({"tools": [{"name": "fail"}]}),
+                "tools/call" if params["name"] == "echo" => json!({"content": [
+                    {"type": "text", "text": params["arguments"]["text"]},
+                    {"type": "image", "data": "", "mimeType": "image/png"},
+                ]}),
+                _ => json!({"content": [{"type": "text", "text": "boom"}], "isError": true}),
+            };
+            let reply = json!({"jsonrpc": "2.0", "id": id, "result": result});
+            write_line(&mut write, &reply).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_list_and_call() {
+        let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(fake_server(server_side));
+        let (read, write) = tokio::io::split(client_side);
+        let client = McpClient::connect(read, write, Duration::from_secs(5));
+        client.initialize().await.unwrap();
+
+        let tools = client.list_tools().await.unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "fail"]);
+        assert_eq!(tools[0].description.as_deref(), Some("Echo text"));
+        assert_eq!(tools[1].input_schema, json!({"type": "object"}));
+
+        let result = client
+            .call_tool("echo", json!({"text": "hello"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            McpCallResult {
+                text: "hello\n[image content: image/png]".to_string(),
+                is_error: false,
+            }
+        );
+        assert!(client.call_tool("fail", json!({})).await.unwrap().is_error);
+    }
+
+    #[tokio::test]
+    async fn test_closed_transport_fails_requests() {
+        let (client_side, server_side) = tokio::io::duplex(1024);
+        drop(server_side);
+        let (read, write) = tokio::io::split(client_side);
+        let client = McpClient::connect(read, write, Duration::from_secs(5));
+        assert!(client.initialize().await.is_err());
+    }
+
+    #[test]
+    fn test_parse_message() {
+        assert!(matches!(
+            parse_message(r#"{"jsonrpc":"2.0","id":7,"error":{"code":-1,"message":"nope"}}"#),
+            Some(Incoming::Response(7, Err(message))) if message == "nope"
+        ));
+        assert!(matches!(
+            parse_message(r#"{"jsonrpc":"2.0","id":"s1","method":"ping"}"#),
+            Some(Incoming::Request(_, method)) if method == "ping"
+        ));
+        assert!(
+            parse_message(r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#)
+                .is_none()
+        );
+        assert!(parse_message("not json").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! Model Context Protocol support.
+//!
+//! Client side: external MCP servers are configured under
+//! `[mcp.servers.<name>]`. At startup the gateway launches each enabled
+//! server, lists its tools and keeps them in a process-wide registry;
+//! `ToolManager::new_chat` adds them as [`McpTool`]s named
+//! `mcp__<server>__<tool>`.
+//!
+//! Server side: [`server::KnowledgeMcpServer`] exposes a ghost's knowledge
+//! tools to external agents over stdio.
+
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+use t_koma_core::config::McpSettings;
+use tracing::{info, warn};
+
+pub mod client;
+pub mod server;
+
+pub use client::{McpCallResult, McpClient, McpError, McpToolInfo};
+
+use crate::tools::mcp::McpTool;
+
+/// Longest tool name providers accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+static REGISTRY: OnceLock<Vec<McpTool>> = OnceLock::new();
+
+/// Discovered MCP tools (empty until [`start`] has run).
+pub fn tools() -> &'static [McpTool] {
+    REGISTRY.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Connect to every enabled server and register its tools.
+///
+/// Servers that fail to start are logged and skipped. Only the first call
+/// registers tools; returns the number of tools registered by this call.
+pub async fn start(settings: &McpSettings) -> usize {
+    if REGISTRY.get().is_some() {
+        return 0;
+    }
+
+    let connects = settings
+        .servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .map(|(name, server)| async move {
+            let result = async {
+                let client = McpClient::spawn(name, server).await?;
+                let tools = client.list_tools().await?;
+                Ok::<_, McpError>((Arc::new(client), tools))
+            }
+            .await;
+            (name, server, result)
+        });
+
+    let mut registered = Vec::new();
+    let mut seen = HashSet::new();
+    for (name, server, result) in futures::future::join_all(connects).await {
+        let (client, infos) = match result {
+            Ok(connected) => connected,
+            Err(err) => {
+                warn!("MCP server '{name}' unavailable: {err}");
+                continue;
+            }
+        };
+        info!("MCP server '{name}' connected with {} tool(s)", infos.len());
+        for info in infos {
+            let exposed = exposed_tool_name(name, &info.name);
+            if !seen.insert(exposed.clone()) {
+                warn!(
+                    "MCP tool '{}' from '{name}' skipped: duplicate name {exposed}",
+                    info.name
+                );
+                continue;
+            }
+            registered.push(McpTool::new(
+                exposed,
+                name.clone(),
+                info,
+                server.clone(),
+                Arc::clone(&client),
+            ));
+        }
+    }
+
+    let count = registered.len();
+    if REGISTRY.set(registered).is_err() {
+        return 0;
+    }
+    count
+}
+
+/// `mcp__<server>__<tool>`, restricted to `[A-Za-z0-9_-]` and 64 characters.
+pub fn exposed_tool_name(server: &str, tool: &str) -> String {
+    format!("mcp__{server}__{tool}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_TOOL_NAME_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposed_tool_name() {
+        assert_eq!(
+            exposed_tool_name("github", "create_issue"),
+            "mcp__github__create_issue"
+        );
+        assert_eq!(
+            exposed_tool_name("home.lab", "lights/on"),
+            "mcp__home_lab__lights_on"
+        );
+        assert_eq!(exposed_tool_name("s", &"x".repeat(100)).len(), 64);
+    }
+}
@@ -0,0 +1,280 @@
+//! MCP server exposing a ghost's knowledge base over stdio.
+//!
+//! Started with `t-koma-gateway mcp-serve --ghost <name>` so external agents
+//! (desktop assistants, editors) can search, read and write the same notes
+//! and references the ghost uses. Tools run through the regular
+//! `ToolManager` with the ghost's scope and its owner's permissions.
+
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use t_koma_core::config::ToolTimeoutSettings;
+use t_koma_db::{
+    GhostRepository, KomaDbPool, OperatorRepository, ToolPolicyRepository,
+    ghosts::ghost_workspace_path,
+};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, BufReader};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use super::client::{PROTOCOL_VERSION, write_line};
+use crate::tools::{ToolContext, ToolManager};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Knowledge tools served to MCP clients on behalf of one ghost.
+pub struct KnowledgeMcpServer {
+    tools: ToolManager,
+    context: Mutex<ToolContext>,
+}
+
+impl KnowledgeMcpServer {
+    pub fn new(context: ToolContext) -> Self {
+        Self {
+            tools: ToolManager::new_knowledge_mcp(),
+            context: Mutex::new(context),
+        }
+    }
+
+    /// Server acting as `ghost_name`, with its owner's permissions.
+    pub async fn for_ghost(
+        db: &KomaDbPool,
+        engine: Arc<t_koma_knowledge::KnowledgeEngine>,
+        ghost_name: &str,
+    ) -> Result<Self, String> {
+        let pool = db.pool();
+        let ghost = GhostRepository::get_by_name(pool, ghost_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Ghost '{}' not found", ghost_name))?;
+        let workspace_root = ghost_workspace_path(&ghost.name).map_err(|e| e.to_string())?;
+        let permissions = OperatorRepository::get_permissions(pool, &ghost.owner_operator_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let policies = ToolPolicyRepository::for_session(pool, &ghost.owner_operator_id, &ghost.id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut context =
+            ToolContext::new(ghost.name, workspace_root.clone(), workspace_root, false)
+                .with_knowledge_engine(engine);
+        context.set_model_id("mcp".to_string());
+        context.set_koma_scope(pool.clone(), &ghost.id);
+        context.set_permissions(permissions);
+        context.set_tool_policies(policies);
+        Ok(Self::new(context))
+    }
+
+    pub fn with_tool_timeouts(mut self, timeouts: ToolTimeoutSettings) -> Self {
+        self.context.get_mut().set_tool_timeouts(timeouts);
+        self
+    }
+
+    /// Answer newline-delimited JSON-RPC requests until `reader` closes.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> std::io::Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Send + Unpin,
+    {
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line).await {
+                write_line(&mut writer, &response).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Response to one message; notifications and stray responses get none.
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            return Some(error_response(Value::Null, PARSE_ERROR, "Parse error"));
+        };
+        let method = message.get("method").and_then(Value::as_str)?;
+        let Some(id) = message.get("id").cloned() else {
+            debug!("MCP notification: {method}");
+            return None;
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params).await),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {other}"))),
+        };
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn initialize(&self, params: &Value) -> Value {
+        // Attribute knowledge written over MCP to the connecting client.
+        if let Some(client) = params.pointer("/clientInfo/name").and_then(Value::as_str) {
+            self.context
+                .lock()
+                .await
+                .set_model_id(format!("mcp:{client}"));
+        }
+        let ghost = self.context.lock().await.ghost_name().to_string();
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {"tools": {"listChanged": false}},
+            "serverInfo": {"name": "t-koma", "version": env!("CARGO_PKG_VERSION")},
+            "instructions": format!(
+                "Knowledge base of the T-KOMA ghost '{ghost}'. Search before writing; \
+                 references must be saved under an existing shared topic note."
+            ),
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .get_tools()
+            .into_iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name(),
+                    "description": tool.description(),
+                    "inputSchema": tool.input_schema(),
+                })
+            })
+            .collect();
+        json!({"tools": tools})
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        if !self
+            .tools
+            .get_tools()
+            .iter()
+            .any(|tool| tool.name() == name)
+        {
+            return Err((INVALID_PARAMS, format!("Unknown tool: {name}")));
+        }
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        let mut context = self.context.lock().await;
+        let (text, is_error) = match self
+            .tools
+            .execute_with_context(name, arguments, &mut context)
+            .await
+        {
+            Ok(text) => (text, false),
+            Err(text) => (text, true),
+        };
+        Ok(json!({
+            "content": [{"type": "text", "text": text}],
+            "isError": is_error,
+        }))
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn server() -> KnowledgeMcpServer {
+        KnowledgeMcpServer::new(ToolContext::new_for_tests(Path::new("/tmp")))
+    }
+
+    #[tokio::test]
+    async fn test_serves_knowledge_tools() {
+        let server = server();
+        let (client, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let serving = tokio::spawn(async move { server.serve(server_read, server_write).await });
+
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_read).lines();
+        let requests = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize",
+                   "params": {"clientInfo": {"name": "editor"}}}),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+        ];
+        for request in &requests {
+            write_line(&mut client_write, request).await.unwrap();
+        }
+
+        let init: Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(init["id"], 1);
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let list: Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(list["id"], 2);
+        let names: Vec<&str> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "knowledge_search",
+                "knowledge_get",
+                "note_write",
+                "reference_write"
+            ]
+        );
+
+        drop((lines, client_write));
+        serving.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let server = server();
+        let response = server.handle_line("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"run_shell_command"}}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // Tool failures are results flagged with isError, not protocol errors.
+        let response = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"knowledge_search","arguments":{"query":"x"}}}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 5);
+        assert_eq!(response["result"]["isError"], true);
+
+        assert!(
+            server
+                .handle_line(r#"{"jsonrpc":"2.0","id":6,"result":{}}"#)
+                .await
+                .is_none()
+        );
+    }
+}
@@ -1,13 +1,21 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use tracing::info;
+use t_koma_db::{ModelCatalogEntry, ModelCatalogRepository};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
 use crate::providers::anthropic::AnthropicClient;
+use crate::providers::azure_openai::{AzureAdTokenSource, AzureAuth, AzureOpenAiClient};
 use crate::providers::gemini::GeminiClient;
 use crate::providers::openai_compatible::OpenAiCompatibleClient;
 use crate::providers::openrouter::OpenRouterClient;
-use crate::state::ModelEntry;
+use crate::providers::retry::RetryPolicy;
+use crate::state::{AppState, LogEntry, ModelEntry};
+
+/// Provider whose `/models` catalog is cached in `model_catalog`.
+const CATALOG_PROVIDER: &str = "openrouter";
 
 pub struct ModelRegistry {
     pub default_model_chain: Vec<String>,
@@ -18,11 +26,25 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
     let mut models: HashMap<String, ModelEntry> = HashMap::new();
 
     for (alias, model_config) in &config.settings.models {
+        let retry = RetryPolicy::for_provider(
+            &config.settings.provider_retry,
+            model_config.provider.as_str(),
+        );
+        let timeout = Duration::from_secs(
+            config
+                .settings
+                .provider_timeouts
+                .for_provider(model_config.provider.as_str()),
+        );
         match model_config.provider.as_str() {
             "anthropic" => {
                 if let Some(api_key) = config.anthropic_api_key() {
                     let client = AnthropicClient::new(api_key, &model_config.model)
-                        .with_dump_queries(config.settings.logging.dump_queries);
+                        .with_dump_queries(config.settings.logging.dump_queries)
+                        .with_retry(retry)
+                        .with_timeout(timeout)
+                        .with_thinking_budget(model_config.thinking_budget)
+                        .with_generation(model_config.generation.clone());
                     models.insert(
                         alias.clone(),
                         ModelEntry {
@@ -69,7 +91,11 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
                     app_name,
                     model_config.routing.clone(),
                 )
-                .with_dump_queries(config.settings.logging.dump_queries);
+                .with_dump_queries(config.settings.logging.dump_queries)
+                .with_retry(retry)
+                .with_timeout(timeout)
+                .with_thinking_budget(model_config.thinking_budget)
+                .with_generation(model_config.generation.clone());
                 models.insert(
                     alias.clone(),
                     ModelEntry {
@@ -103,7 +129,10 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
                     &model_config.model,
                     "openai_compatible",
                 )
-                .with_dump_queries(config.settings.logging.dump_queries);
+                .with_dump_queries(config.settings.logging.dump_queries)
+                .with_retry(retry)
+                .with_timeout(timeout)
+                .with_generation(model_config.generation.clone());
                 models.insert(
                     alias.clone(),
                     ModelEntry {
@@ -143,7 +172,10 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
                         "kimi_code",
                     )
                     .with_extra_headers(extra)
-                    .with_dump_queries(config.settings.logging.dump_queries);
+                    .with_dump_queries(config.settings.logging.dump_queries)
+                    .with_retry(retry)
+                    .with_timeout(timeout)
+                    .with_generation(model_config.generation.clone());
                     models.insert(
                         alias.clone(),
                         ModelEntry {
@@ -165,7 +197,10 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
             "gemini" => {
                 if let Some(api_key) = config.gemini_api_key() {
                     let client = GeminiClient::new(api_key, &model_config.model)
-                        .with_dump_queries(config.settings.logging.dump_queries);
+                        .with_dump_queries(config.settings.logging.dump_queries)
+                        .with_retry(retry)
+                        .with_timeout(timeout)
+                        .with_generation(model_config.generation.clone());
                     models.insert(
                         alias.clone(),
                         ModelEntry {
@@ -184,6 +219,55 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
                     );
                 }
             }
+            "azure_openai" => {
+                let endpoint = model_config
+                    .base_url
+                    .clone()
+                    .expect("azure_openai model.base_url must be validated by Config::load");
+                let auth = match config.api_key_for_alias(alias) {
+                    Ok(Some(key)) if !key.trim().is_empty() => AzureAuth::ApiKey(key),
+                    Ok(_) => match config.azure_ad_credentials() {
+                        Some(credentials) => AzureAuth::AzureAd(Arc::new(AzureAdTokenSource::new(
+                            credentials.clone(),
+                        ))),
+                        None => {
+                            info!(
+                                "Skipping model '{}' (azure_openai) - no AZURE_OPENAI_API_KEY or Azure AD credentials configured",
+                                alias
+                            );
+                            continue;
+                        }
+                    },
+                    Err(err) => {
+                        info!(
+                            "Skipping model '{}' (azure_openai) - API key resolution error: {}",
+                            alias, err
+                        );
+                        continue;
+                    }
+                };
+                let client = AzureOpenAiClient::new(
+                    endpoint,
+                    &model_config.model,
+                    model_config.api_version.as_deref(),
+                    auth,
+                )
+                .with_dump_queries(config.settings.logging.dump_queries)
+                .with_retry(retry)
+                .with_timeout(timeout)
+                .with_generation(model_config.generation.clone());
+                models.insert(
+                    alias.clone(),
+                    ModelEntry {
+                        alias: alias.clone(),
+                        provider: model_config.provider.to_string(),
+                        model: model_config.model.clone(),
+                        client: Arc::new(client),
+                        context_window: model_config.context_window,
+                        retry_on_empty: model_config.retry_on_empty.unwrap_or(0),
+                    },
+                );
+            }
             other => {
                 info!("Skipping model '{}' - unknown provider '{}'", alias, other);
             }
@@ -216,3 +300,254 @@ pub fn build_from_config(config: &t_koma_core::Config) -> Result<ModelRegistry,
         models,
     })
 }
+
+/// Store configured `[models.*].pricing` in the usage log pricing table.
+///
+/// Pricing is keyed by provider model id, which is what usage rows record.
+/// Models without a `pricing` entry keep whatever row they already have.
+pub async fn sync_pricing(
+    pool: &sqlx::SqlitePool,
+    config: &t_koma_core::Config,
+) -> Result<(), t_koma_db::DbError> {
+    for model_config in config.settings.models.values() {
+        let Some(pricing) = &model_config.pricing else {
+            continue;
+        };
+        t_koma_db::UsageLogRepository::upsert_pricing(
+            pool,
+            &t_koma_db::ModelPricing {
+                model: model_config.model.clone(),
+                input_per_mtok: pricing.input,
+                output_per_mtok: pricing.output,
+                cache_read_per_mtok: pricing.cache_read,
+                cache_write_per_mtok: pricing.cache_write,
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Apply a provider model catalog to the registry.
+///
+/// Aliases of the catalog's provider that do not set `context_window` get the
+/// listed context length. Returns the aliases whose model the catalog does not
+/// list (none when the catalog is empty, i.e. never fetched).
+pub fn apply_catalog(
+    models: &mut HashMap<String, ModelEntry>,
+    catalog: &[ModelCatalogEntry],
+) -> Vec<String> {
+    let Some(provider) = catalog.first().map(|entry| entry.provider.as_str()) else {
+        return Vec::new();
+    };
+    let by_model: HashMap<&str, &ModelCatalogEntry> = catalog
+        .iter()
+        .map(|entry| (entry.model.as_str(), entry))
+        .collect();
+
+    let mut unknown = Vec::new();
+    for entry in models.values_mut().filter(|e| e.provider == provider) {
+        match by_model.get(entry.model.as_str()) {
+            Some(listed) => {
+                if entry.context_window.is_none() {
+                    entry.context_window = listed.context_length;
+                }
+            }
+            None => unknown.push(entry.alias.clone()),
+        }
+    }
+    unknown.sort();
+    unknown
+}
+
+/// Apply the cached OpenRouter catalog to a freshly built registry, warning
+/// about aliases whose model OpenRouter does not list.
+pub async fn apply_cached_catalog(
+    pool: &sqlx::SqlitePool,
+    models: &mut HashMap<String, ModelEntry>,
+) -> Result<Vec<String>, t_koma_db::DbError> {
+    let catalog = ModelCatalogRepository::list(pool, CATALOG_PROVIDER).await?;
+    let unknown = apply_catalog(models, &catalog);
+    for alias in &unknown {
+        warn!("Model alias '{alias}' is not listed in the OpenRouter model catalog");
+    }
+    Ok(unknown)
+}
+
+/// Store catalog pricing for configured models of the catalog's provider
+/// that have no `[models.*].pricing` entry (configured pricing always wins).
+pub async fn sync_catalog_pricing(
+    pool: &sqlx::SqlitePool,
+    config: &t_koma_core::Config,
+    catalog: &[ModelCatalogEntry],
+) -> Result<(), t_koma_db::DbError> {
+    for model_config in config.settings.models.values() {
+        if model_config.pricing.is_some() {
+            continue;
+        }
+        let Some(listed) = catalog.iter().find(|entry| {
+            entry.provider == model_config.provider.as_str() && entry.model == model_config.model
+        }) else {
+            continue;
+        };
+        t_koma_db::UsageLogRepository::upsert_pricing(pool, &listed.pricing()).await?;
+    }
+    Ok(())
+}
+
+/// Fetch OpenRouter's `/models` and replace the cached catalog.
+///
+/// Uses the API key and base URL of the first configured OpenRouter alias.
+/// Returns `Ok(None)` when no OpenRouter alias is configured.
+pub async fn refresh_catalog(
+    pool: &sqlx::SqlitePool,
+    config: &t_koma_core::Config,
+) -> Result<Option<Vec<ModelCatalogEntry>>, String> {
+    let mut aliases: Vec<&String> = config
+        .settings
+        .models
+        .iter()
+        .filter(|(_, model)| model.provider.as_str() == CATALOG_PROVIDER)
+        .map(|(alias, _)| alias)
+        .collect();
+    aliases.sort();
+    let Some(alias) = aliases.first() else {
+        return Ok(None);
+    };
+    let model_config = &config.settings.models[*alias];
+    let api_key = config
+        .api_key_for_alias(alias)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+
+    let client = OpenRouterClient::new(
+        api_key,
+        &model_config.model,
+        model_config.base_url.clone(),
+        config.settings.openrouter.http_referer.clone(),
+        config.settings.openrouter.app_name.clone(),
+        None,
+    )
+    .with_retry(RetryPolicy::for_provider(
+        &config.settings.provider_retry,
+        CATALOG_PROVIDER,
+    ))
+    .with_timeout(Duration::from_secs(
+        config
+            .settings
+            .provider_timeouts
+            .for_provider(CATALOG_PROVIDER),
+    ));
+    let catalog: Vec<ModelCatalogEntry> = client
+        .fetch_models()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|model| model.to_catalog_entry())
+        .collect();
+
+    ModelCatalogRepository::replace_provider(pool, CATALOG_PROVIDER, &catalog)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(catalog))
+}
+
+/// Spawn the loop that refreshes the OpenRouter catalog every
+/// `refresh_hours` and applies it to the live registry and pricing table.
+///
+/// The first refresh is skipped when the cached catalog is younger than the
+/// refresh interval.
+pub fn start_catalog_refresh(state: Arc<AppState>, refresh_hours: u64) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let period = Duration::from_secs(refresh_hours.max(1) * 3600);
+        let pool = state.koma_db.pool().clone();
+        let mut tick = tokio::time::interval(period);
+        let mut first = true;
+        loop {
+            tick.tick().await;
+            if std::mem::take(&mut first)
+                && let Ok(Some(fetched_at)) =
+                    ModelCatalogRepository::last_fetched_at(&pool, CATALOG_PROVIDER).await
+                && chrono::Utc::now().timestamp() - fetched_at < period.as_secs() as i64
+            {
+                continue;
+            }
+
+            let config = match t_koma_core::Config::load() {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!("model catalog: failed to load config: {e}");
+                    continue;
+                }
+            };
+            let catalog = match refresh_catalog(&pool, &config).await {
+                Ok(Some(catalog)) => catalog,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("model catalog: OpenRouter refresh failed: {e}");
+                    continue;
+                }
+            };
+            if let Err(e) = sync_catalog_pricing(&pool, &config, &catalog).await {
+                warn!("model catalog: failed to sync pricing: {e}");
+            }
+            let unknown = state.apply_model_catalog(&catalog);
+            let mut message = format!(
+                "Refreshed OpenRouter model catalog ({} models)",
+                catalog.len()
+            );
+            if !unknown.is_empty() {
+                message.push_str(&format!("; not listed: {}", unknown.join(", ")));
+            }
+            state.log(LogEntry::Info { message }).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(alias: &str, provider: &str, model: &str, context_window: Option<u32>) -> ModelEntry {
+        ModelEntry {
+            alias: alias.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            client: Arc::new(OpenRouterClient::new("key", model, None, None, None, None)),
+            context_window,
+            retry_on_empty: 0,
+        }
+    }
+
+    fn listed(model: &str, context_length: Option<u32>) -> ModelCatalogEntry {
+        ModelCatalogEntry {
+            provider: "openrouter".to_string(),
+            model: model.to_string(),
+            name: model.to_string(),
+            context_length,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_catalog() {
+        let mut models: HashMap<String, ModelEntry> = [
+            entry("auto", "openrouter", "a/listed", None),
+            entry("pinned", "openrouter", "a/listed", Some(32_000)),
+            entry("typo", "openrouter", "a/missing", None),
+            entry("direct", "anthropic", "claude-sonnet-4-5", None),
+        ]
+        .into_iter()
+        .map(|e| (e.alias.clone(), e))
+        .collect();
+
+        assert!(apply_catalog(&mut models, &[]).is_empty());
+
+        let unknown = apply_catalog(&mut models, &[listed("a/listed", Some(200_000))]);
+        assert_eq!(unknown, vec!["typo".to_string()]);
+        assert_eq!(models["auto"].context_window, Some(200_000));
+        assert_eq!(models["pinned"].context_window, Some(32_000));
+        assert_eq!(models["typo"].context_window, None);
+        assert_eq!(models["direct"].context_window, None);
+    }
+}
@@ -1,5 +1,3 @@
-use std::sync::Arc;
-
 use t_koma_core::{GatewayMessage, GatewayMessageKind};
 
 use crate::content::ids;
@@ -52,6 +50,34 @@ pub fn approval_required_gateway_message(
             interface,
             &[("title", title), ("summary", summary)],
         ),
+        ApprovalReason::BrowserAction { action, url } => gateway_message::from_content(
+            ids::APPROVAL_BROWSER_ACTION,
+            interface,
+            &[("action", action), ("url", url)],
+        ),
+        ApprovalReason::McpTool {
+            server,
+            tool,
+            arguments,
+        } => gateway_message::from_content(
+            ids::APPROVAL_MCP_TOOL,
+            interface,
+            &[("server", server), ("tool", tool), ("arguments", arguments)],
+        ),
+        ApprovalReason::WebhookTool {
+            tool,
+            url,
+            arguments,
+        } => gateway_message::from_content(
+            ids::APPROVAL_WEBHOOK_TOOL,
+            interface,
+            &[("tool", tool), ("url", url), ("arguments", arguments)],
+        ),
+        ApprovalReason::WebDomain { tool, host, url } => gateway_message::from_content(
+            ids::APPROVAL_WEB_DOMAIN,
+            interface,
+            &[("tool", tool), ("host", host), ("url", url)],
+        ),
     }
 }
 
@@ -69,6 +95,130 @@ pub fn tool_loop_limit_reached_gateway_message(
     )
 }
 
+/// Render a budget warning (`USAGE_BUDGET_WARNING`) or block (`USAGE_BUDGET_EXCEEDED`).
+pub fn usage_budget_message(
+    id: &str,
+    report: &t_koma_db::BudgetReport,
+    interface: Option<&str>,
+) -> GatewayMessage {
+    let (used, limit) = if report.cost_is_binding() {
+        (
+            format!("${:.2}", report.used_cost_usd),
+            format!("${:.2}", report.cost_limit_usd.unwrap_or_default()),
+        )
+    } else {
+        let tokens = |count: i64| format_token_count(u32::try_from(count).unwrap_or(u32::MAX));
+        (
+            tokens(report.used_tokens),
+            tokens(report.token_limit.unwrap_or_default()),
+        )
+    };
+    let scope = report.scope.to_string();
+    gateway_message::from_content(
+        id,
+        interface,
+        &[
+            ("scope", scope.as_str()),
+            ("used", used.as_str()),
+            ("limit", limit.as_str()),
+        ],
+    )
+}
+
+/// Whether the operator holds `permission`; lookup failures count as denied.
+pub async fn operator_has_permission(
+    state: &AppState,
+    operator_id: &str,
+    permission: &t_koma_db::OperatorPermission,
+) -> bool {
+    match t_koma_db::OperatorRepository::has_permission(
+        state.koma_db.pool(),
+        operator_id,
+        permission,
+    )
+    .await
+    {
+        Ok(allowed) => allowed,
+        Err(e) => {
+            tracing::warn!(
+                "Permission lookup failed for operator {}: {}",
+                operator_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Ask puppet masters to review a new operator, on every chat bot that is
+/// configured (Discord DMs, Telegram chats, Slack DMs, email).
+pub async fn notify_new_operator(state: &AppState, operator: &t_koma_db::Operator) {
+    if let Some(token) = state.discord_bot_token().await {
+        let http = serenity::http::Http::new(&token);
+        crate::discord::send_new_operator_notification_to_pms(state, &http, operator).await;
+    }
+    if let Some(token) = state.telegram_bot_token().await {
+        crate::telegram::send_new_operator_notification_to_pms(state, &token, operator).await;
+    }
+    if let Some(token) = state.slack_bot_token().await {
+        crate::slack::send_new_operator_notification_to_pms(state, &token, operator).await;
+    }
+    if let Some(mailer) = state.email_mailer().await {
+        crate::email::send_new_operator_notification_to_pms(state, &mailer, operator).await;
+    }
+}
+
+/// Prompt a just-approved operator to name their first ghost, on whichever
+/// chat platform they signed up from.
+///
+/// Returns whether a Discord DM went out (reported back to the CLI).
+pub async fn welcome_approved_operator(state: &AppState, operator_id: &str) -> bool {
+    let mut discord_notified = false;
+    if let Some(token) = state.discord_bot_token().await {
+        match crate::discord::send_approved_operator_ghost_prompt_dm(state, &token, operator_id)
+            .await
+        {
+            Ok(notified) => discord_notified = notified,
+            Err(e) => tracing::warn!(
+                "Approved operator {}, but Discord notification failed: {}",
+                operator_id,
+                e
+            ),
+        }
+    }
+    if let Some(token) = state.telegram_bot_token().await
+        && let Err(e) =
+            crate::telegram::send_approved_operator_ghost_prompt(state, &token, operator_id).await
+    {
+        tracing::warn!(
+            "Approved operator {}, but Telegram notification failed: {}",
+            operator_id,
+            e
+        );
+    }
+    if let Some(token) = state.slack_bot_token().await
+        && let Err(e) =
+            crate::slack::send_approved_operator_ghost_prompt(state, &token, operator_id).await
+    {
+        tracing::warn!(
+            "Approved operator {}, but Slack notification failed: {}",
+            operator_id,
+            e
+        );
+    }
+    if let Some(mailer) = state.email_mailer().await
+        && let Err(e) =
+            crate::email::send_approved_operator_ghost_prompt(state, &mailer, operator_id).await
+    {
+        tracing::warn!(
+            "Approved operator {}, but email notification failed: {}",
+            operator_id,
+            e
+        );
+    }
+    discord_notified
+}
+
 pub fn gateway_info(id: &str, interface: Option<&str>) -> GatewayMessage {
     gateway_message::from_content(id, interface, &[])
 }
@@ -115,6 +265,25 @@ pub async fn run_chat_with_pending_and_attachments(
     tool_call_tx: Option<&tokio::sync::mpsc::UnboundedSender<Vec<ToolCallSummary>>>,
 ) -> Result<Vec<OutboundMessage>, ChatError> {
     let streamed = tool_call_tx.is_some();
+    if let Err(err) = t_koma_db::SessionRepository::set_origin(
+        state.koma_db.pool(),
+        session_id,
+        t_koma_db::SessionOrigin::from_interface(interface),
+    )
+    .await
+    {
+        tracing::warn!("failed to record session origin for {session_id}: {err}");
+    }
+    let attachments = match state.transcriber().await {
+        Some(transcriber) => {
+            crate::transcription::add_transcripts(transcriber.as_ref(), attachments).await
+        }
+        None => attachments,
+    };
+    // An explicitly requested model whose breaker is open falls back to the
+    // default chain, and the operator is told why.
+    let skipped_alias = model_alias.filter(|alias| !state.circuit_breaker.is_available(alias));
+    let model_alias = model_alias.filter(|_| skipped_alias.is_none());
 
     let result = match model_alias {
         Some(alias) => {
@@ -147,6 +316,20 @@ pub async fn run_chat_with_pending_and_attachments(
     match result {
         Ok(result) => {
             let mut out = Vec::new();
+            if let Some(alias) = skipped_alias {
+                out.push(OutboundMessage::gateway(gateway_message::from_content(
+                    ids::MODEL_CIRCUIT_OPEN,
+                    interface,
+                    &[("model", alias)],
+                )));
+            }
+            if let Some(report) = &result.budget_warning {
+                out.push(OutboundMessage::gateway(usage_budget_message(
+                    ids::USAGE_BUDGET_WARNING,
+                    report,
+                    interface,
+                )));
+            }
             if result.compaction_happened {
                 out.push(OutboundMessage::gateway(gateway_info(
                     ids::COMPACTION_HAPPENED,
@@ -186,6 +369,10 @@ pub async fn run_chat_with_pending_and_attachments(
                 ),
             )])
         }
+        Err(ChatError::Cancelled) => Ok(vec![OutboundMessage::gateway(gateway_info(
+            ids::CHAT_STOPPED,
+            interface,
+        ))]),
         Err(err) => Err(err),
     }
 }
@@ -240,6 +427,12 @@ pub async fn run_tool_control_command(
                     ),
                 )]));
             }
+            Err(ChatError::Cancelled) => {
+                return Ok(Some(vec![OutboundMessage::gateway(gateway_info(
+                    ids::CHAT_STOPPED,
+                    interface,
+                ))]));
+            }
             Err(err) => return Err(err),
         }
     }
@@ -290,6 +483,10 @@ pub async fn run_tool_control_command(
                 ),
             )]))
         }
+        Err(ChatError::Cancelled) => Ok(Some(vec![OutboundMessage::gateway(gateway_info(
+            ids::CHAT_STOPPED,
+            interface,
+        ))])),
         Err(err) => Err(err),
     }
 }
@@ -341,29 +538,13 @@ fn format_token_count(count: u32) -> String {
     }
 }
 
-pub fn spawn_reflection_for_previous_session(
-    state: &Arc<AppState>,
-    ghost_name: &str,
+/// Queue reflection for the session an operator just moved away from.
+pub async fn queue_reflection_for_previous_session(
+    state: &AppState,
     ghost_id: &str,
-    operator_id: &str,
     previous_session_id: &str,
 ) {
-    let state_for_reflection = Arc::clone(state);
-    let ghost_name_for_reflection = ghost_name.to_string();
-    let ghost_id_for_reflection = ghost_id.to_string();
-    let operator_id_for_reflection = operator_id.to_string();
-    let previous_session_id = previous_session_id.to_string();
-    tokio::spawn(async move {
-        crate::reflection::run_reflection_now(
-            &state_for_reflection,
-            &ghost_name_for_reflection,
-            &ghost_id_for_reflection,
-            &previous_session_id,
-            &operator_id_for_reflection,
-            None,
-        )
-        .await;
-    });
+    crate::reflection::queue_reflection_now(state, ghost_id, previous_session_id).await;
 }
 
 #[cfg(test)]
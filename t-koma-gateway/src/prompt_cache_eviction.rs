@@ -0,0 +1,82 @@
+//! Scheduled prompt cache eviction.
+//!
+//! Runs from the heartbeat runner loop at
+//! `[prompt_cache].evict_interval_minutes`; the next due time lives in the
+//! shared scheduler state. Also drops expired in-memory entries.
+
+use chrono::Utc;
+use tracing::{debug, warn};
+
+use crate::scheduler::JobKind;
+use crate::state::AppState;
+use t_koma_core::PromptCacheSettings;
+use t_koma_db::{PromptCacheEviction, PromptCacheRepository};
+
+const SCHEDULER_KEY: &str = "prompt_cache";
+
+/// Map config values (0 = no limit) to a DB eviction policy.
+pub fn eviction_policy(settings: &PromptCacheSettings) -> PromptCacheEviction {
+    PromptCacheEviction {
+        max_idle_secs: (settings.max_idle_minutes > 0)
+            .then(|| i64::from(settings.max_idle_minutes) * 60),
+        max_entries: (settings.max_entries > 0).then_some(settings.max_entries),
+        max_total_bytes: (settings.max_total_kib > 0)
+            .then(|| i64::from(settings.max_total_kib) * 1024),
+    }
+}
+
+/// Evict prompt cache entries if the scheduled eviction is due (or has never run).
+pub async fn maybe_evict_prompt_cache(state: &AppState, settings: &PromptCacheSettings) {
+    let now = Utc::now().timestamp();
+    if let Some(due) = state
+        .scheduler_get(JobKind::PromptCacheEvict, SCHEDULER_KEY)
+        .await
+        && now < due
+    {
+        return;
+    }
+    let next_due = now + settings.evict_interval_minutes.max(1) as i64 * 60;
+    state
+        .scheduler_set(JobKind::PromptCacheEvict, SCHEDULER_KEY, Some(next_due))
+        .await;
+
+    let expired = state.session_chat.prompt_cache().evict_expired().await;
+    if expired > 0 {
+        debug!("Dropped {} expired in-memory prompt cache entries", expired);
+    }
+
+    let policy = eviction_policy(settings);
+    if policy.is_unbounded() {
+        return;
+    }
+    if let Err(err) = PromptCacheRepository::evict(state.koma_db.pool(), &policy, now).await {
+        warn!("prompt cache eviction failed: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_policy_zero_disables_limit() {
+        let policy = eviction_policy(&PromptCacheSettings {
+            max_idle_minutes: 30,
+            max_entries: 0,
+            max_total_kib: 64,
+            evict_interval_minutes: 15,
+        });
+        assert_eq!(policy.max_idle_secs, Some(1800));
+        assert_eq!(policy.max_entries, None);
+        assert_eq!(policy.max_total_bytes, Some(65_536));
+        assert!(
+            eviction_policy(&PromptCacheSettings {
+                max_idle_minutes: 0,
+                max_entries: 0,
+                max_total_kib: 0,
+                evict_interval_minutes: 15,
+            })
+            .is_unbounded()
+        );
+    }
+}
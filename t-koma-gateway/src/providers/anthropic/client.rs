@@ -1,15 +1,20 @@
 //! Anthropic API client with session support and prompt caching.
 
+use std::time::Duration;
+
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use t_koma_core::GenerationParams;
 
-use crate::chat::history::ChatMessage;
+use crate::chat::history::{ChatContentBlock, ChatMessage};
 use crate::prompt::render::SystemBlock;
 use crate::providers::anthropic::history::AnthropicMessage;
+use crate::providers::generation;
 use crate::providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage, ResponseFormat,
 };
+use crate::providers::retry::{DEFAULT_REQUEST_TIMEOUT, RetryPolicy, send_with_retry};
 use crate::tools::Tool;
 
 /// Anthropic API client
@@ -20,6 +25,12 @@ pub struct AnthropicClient {
     model: String,
     base_url: String,
     dump_queries: bool,
+    retry: RetryPolicy,
+    /// Per-attempt request timeout
+    timeout: Duration,
+    /// Extended thinking budget; `None` disables thinking
+    thinking_budget: Option<u32>,
+    generation: GenerationParams,
 }
 
 /// Request body for the Messages API with prompt caching support
@@ -34,8 +45,17 @@ struct MessagesRequest {
     messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<ToolDefinition>>,
-    // TODO: Add tool_choice when we need to force specific tool usage.
-    // For now, the model decides based on tool definitions.
+    /// Forces a specific tool; only set for structured output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,6 +92,10 @@ pub enum ContentBlock {
         name: String,
         input: Value,
     },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
 }
 
 /// Usage information including prompt caching metrics
@@ -124,7 +148,6 @@ impl AnthropicClient {
 
         let http_client = reqwest::Client::builder()
             .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(120))
             .build()
             .expect("Failed to build HTTP client");
 
@@ -134,6 +157,10 @@ impl AnthropicClient {
             model: model.into(),
             base_url: "https://api.anthropic.com/v1".to_string(),
             dump_queries: false,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            thinking_budget: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -143,6 +170,30 @@ impl AnthropicClient {
         self
     }
 
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the per-attempt request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Enable extended thinking with this token budget (at least 1024)
+    pub fn with_thinking_budget(mut self, budget: Option<u32>) -> Self {
+        self.thinking_budget = budget;
+        self
+    }
+
+    /// Set the sampling parameters sent with every request
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
     /// Send a simple single-turn message.
     pub async fn send_message(
         &self,
@@ -184,16 +235,6 @@ impl AnthropicClient {
         message_limit: Option<usize>,
         _tool_choice: Option<String>,
     ) -> Result<(MessagesResponse, String), AnthropicError> {
-        let url = format!("{}/messages", self.base_url);
-
-        // Build messages: neutral history -> Anthropic API payload.
-        let messages = crate::providers::anthropic::history::to_anthropic_messages(
-            history,
-            new_message,
-            message_limit,
-        )
-        .await;
-
         // Build tool definitions
         let tool_definitions = if tools.is_empty() {
             None
@@ -210,13 +251,90 @@ impl AnthropicClient {
             )
         };
 
+        self.send_messages(
+            system,
+            history,
+            tool_definitions,
+            None,
+            new_message,
+            message_limit,
+        )
+        .await
+    }
+
+    /// Send a structured-output request: the schema becomes the only tool and
+    /// the model is forced to call it, so its input is the JSON reply.
+    async fn send_structured_messages(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<(MessagesResponse, String), AnthropicError> {
+        let tool = ToolDefinition {
+            name: format.name.clone(),
+            description: "Submit the reply. Its input is the whole answer.".to_string(),
+            input_schema: format.schema.clone(),
+        };
+        let tool_choice = serde_json::json!({"type": "tool", "name": format.name});
+        self.send_messages(
+            system,
+            history,
+            Some(vec![tool]),
+            Some(tool_choice),
+            new_message,
+            None,
+        )
+        .await
+    }
+
+    async fn send_messages(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        tools: Option<Vec<ToolDefinition>>,
+        tool_choice: Option<Value>,
+        new_message: Option<&str>,
+        message_limit: Option<usize>,
+    ) -> Result<(MessagesResponse, String), AnthropicError> {
+        let url = format!("{}/messages", self.base_url);
+
+        // Thinking cannot be combined with a forced tool.
+        let thinking_budget = self.thinking_budget.filter(|_| tool_choice.is_none());
+        let history = if thinking_budget.is_some() {
+            history
+        } else {
+            strip_thinking(history)
+        };
+
+        // Build messages: neutral history -> Anthropic API payload.
+        let messages = crate::providers::anthropic::history::to_anthropic_messages(
+            history,
+            new_message,
+            message_limit,
+        )
+        .await;
+
         let request_body = MessagesRequest {
             model: self.model.clone(),
-            max_tokens: 4096,
+            // `max_tokens` includes the thinking budget.
+            max_tokens: generation::max_output_tokens(&self.generation)
+                + thinking_budget.unwrap_or(0),
             system,
             messages,
-            tools: tool_definitions,
+            tools,
+            tool_choice,
+            thinking: thinking_budget
+                .map(|budget| serde_json::json!({"type": "enabled", "budget_tokens": budget})),
+            // Extended thinking rejects sampling changes.
+            temperature: self
+                .generation
+                .temperature
+                .filter(|_| thinking_budget.is_none()),
+            top_p: self.generation.top_p.filter(|_| thinking_budget.is_none()),
+            stop_sequences: self.generation.stop.clone(),
         };
+        let request_body = generation::request_body(&request_body, &self.generation)?;
 
         let dump = if self.dump_queries
             && let Ok(val) = serde_json::to_value(&request_body)
@@ -226,13 +344,14 @@ impl AnthropicClient {
             None
         };
 
-        let response = self
-            .http_client
-            .post(&url)
-            .header("x-api-key", &self.api_key)
-            .json(&request_body)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry, "anthropic", &self.model, || {
+            self.http_client
+                .post(&url)
+                .timeout(self.timeout)
+                .header("x-api-key", &self.api_key)
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -324,6 +443,19 @@ impl AnthropicClient {
                 ContentBlock::ToolUse { id, name, input } => {
                     ProviderContentBlock::ToolUse { id, name, input }
                 }
+                ContentBlock::Thinking {
+                    thinking,
+                    signature,
+                } => ProviderContentBlock::Thinking {
+                    thinking,
+                    signature: Some(signature),
+                    redacted: false,
+                },
+                ContentBlock::RedactedThinking { data } => ProviderContentBlock::Thinking {
+                    thinking: data,
+                    signature: None,
+                    redacted: true,
+                },
             })
             .collect();
 
@@ -347,6 +479,19 @@ impl AnthropicClient {
     }
 }
 
+/// Drop thinking blocks, which the API rejects when thinking is disabled.
+fn strip_thinking(history: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    history
+        .into_iter()
+        .map(|mut message| {
+            message
+                .content
+                .retain(|block| !matches!(block, ChatContentBlock::Thinking { .. }));
+            message
+        })
+        .collect()
+}
+
 #[async_trait::async_trait]
 impl Provider for AnthropicClient {
     fn name(&self) -> &str {
@@ -379,6 +524,38 @@ impl Provider for AnthropicClient {
         Ok(self.to_provider_response(response, &raw_json))
     }
 
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let (response, raw_json) = self
+            .send_structured_messages(system, history, new_message, format)
+            .await?;
+        let mut response = self.to_provider_response(response, &raw_json);
+        // Hand the forced tool call back as the JSON text reply.
+        response.content = response
+            .content
+            .into_iter()
+            .map(|block| match block {
+                ProviderContentBlock::ToolUse { name, input, .. } if name == format.name => {
+                    ProviderContentBlock::Text {
+                        text: input.to_string(),
+                    }
+                }
+                other => other,
+            })
+            .collect();
+        Ok(response)
+    }
+
+    fn with_generation_overrides(&self, overrides: &GenerationParams) -> Box<dyn Provider> {
+        let generation = self.generation.overlay(overrides);
+        Box::new(self.clone().with_generation(generation))
+    }
+
     fn clone_box(&self) -> Box<dyn Provider> {
         Box::new(self.clone())
     }
@@ -467,4 +644,54 @@ mod tests {
 
         assert_eq!(usage.cache_read_tokens, 10000);
     }
+
+    #[test]
+    fn test_thinking_blocks_round_trip() {
+        let client = AnthropicClient::new("test-key", "claude-sonnet-4-5");
+        let response: MessagesResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "thinking", "thinking": "Check the logs first.", "signature": "sig"},
+                {"type": "redacted_thinking", "data": "opaque"},
+                {"type": "text", "text": "Done."}
+            ],
+            "stop_reason": "end_turn"
+        }))
+        .unwrap();
+
+        let response = client.to_provider_response(response, "");
+        assert!(matches!(
+            &response.content[0],
+            ProviderContentBlock::Thinking { thinking, signature: Some(sig), redacted: false }
+                if thinking == "Check the logs first." && sig == "sig"
+        ));
+        assert!(matches!(
+            &response.content[1],
+            ProviderContentBlock::Thinking { redacted: true, .. }
+        ));
+        assert_eq!(
+            crate::providers::provider::extract_thinking(&response),
+            "Check the logs first."
+        );
+
+        let history = vec![ChatMessage {
+            role: crate::chat::ChatRole::Assistant,
+            content: vec![
+                ChatContentBlock::Thinking {
+                    thinking: "hm".to_string(),
+                    signature: Some("sig".to_string()),
+                    redacted: false,
+                },
+                ChatContentBlock::Text {
+                    text: "Done.".to_string(),
+                    cache_control: None,
+                },
+            ],
+        }];
+        let stripped = strip_thinking(history);
+        assert_eq!(stripped[0].content.len(), 1);
+    }
 }
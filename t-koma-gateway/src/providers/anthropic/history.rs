@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
 use crate::prompt::CacheControl;
+use crate::providers::provider::ImageSource;
 
 /// Anthropic API message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,7 +23,7 @@ pub enum AnthropicContentBlock {
         cache_control: Option<CacheControl>,
     },
     Image {
-        source: AnthropicImageSource,
+        source: ImageSource,
     },
     ToolUse {
         id: String,
@@ -38,15 +39,13 @@ pub enum AnthropicContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
-}
-
-/// Anthropic image source (base64-encoded).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AnthropicImageSource {
-    #[serde(rename = "type")]
-    pub source_type: String,
-    pub media_type: String,
-    pub data: String,
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
 }
 
 /// Convert provider-neutral history to Anthropic history, with optional
@@ -84,7 +83,7 @@ pub async fn to_anthropic_messages(
 async fn convert_message(message: ChatMessage) -> AnthropicMessage {
     let mut content = Vec::with_capacity(message.content.len());
     for block in message.content {
-        content.push(convert_content_block(block).await);
+        content.extend(convert_content_block(block).await);
     }
     AnthropicMessage {
         role: match message.role {
@@ -95,8 +94,10 @@ async fn convert_message(message: ChatMessage) -> AnthropicMessage {
     }
 }
 
-async fn convert_content_block(block: ChatContentBlock) -> AnthropicContentBlock {
-    match block {
+/// `None` for blocks Anthropic cannot take back: thinking from other providers
+/// carries no signature.
+async fn convert_content_block(block: ChatContentBlock) -> Option<AnthropicContentBlock> {
+    let block = match block {
         ChatContentBlock::Text {
             text,
             cache_control,
@@ -105,14 +106,9 @@ async fn convert_content_block(block: ChatContentBlock) -> AnthropicContentBlock
             cache_control,
         },
         ChatContentBlock::Image { path, filename, .. } => {
-            match crate::chat::history::load_image_base64(&path).await {
-                Some((data, media_type)) => AnthropicContentBlock::Image {
-                    source: AnthropicImageSource {
-                        source_type: "base64".to_string(),
-                        media_type,
-                        data,
-                    },
-                },
+            // `ImageSource` serializes to Anthropic's `base64`/`url` source shape.
+            match crate::chat::history::load_image_source(&path).await {
+                Some(source) => AnthropicContentBlock::Image { source },
                 None => AnthropicContentBlock::Text {
                     text: format!("(image unavailable: {})", filename),
                     cache_control: None,
@@ -137,5 +133,22 @@ async fn convert_content_block(block: ChatContentBlock) -> AnthropicContentBlock
             is_error,
             cache_control,
         },
-    }
+        ChatContentBlock::Thinking {
+            thinking,
+            redacted: true,
+            ..
+        } => AnthropicContentBlock::RedactedThinking { data: thinking },
+        ChatContentBlock::Thinking {
+            thinking,
+            signature: Some(signature),
+            ..
+        } => AnthropicContentBlock::Thinking {
+            thinking,
+            signature,
+        },
+        ChatContentBlock::Thinking {
+            signature: None, ..
+        } => return None,
+    };
+    Some(block)
 }
@@ -0,0 +1,284 @@
+//! Azure OpenAI client.
+//!
+//! Azure serves the chat completions API per deployment
+//! (`{endpoint}/openai/deployments/{deployment}/chat/completions?api-version=..`)
+//! and authenticates with an `api-key` header or an Azure AD bearer token.
+//! Request building and response parsing are shared with
+//! [`OpenAiCompatibleClient`].
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use serde::Deserialize;
+use t_koma_core::{AzureAdCredentials, GenerationParams};
+use tokio::sync::Mutex;
+
+use crate::chat::history::ChatMessage;
+use crate::prompt::render::SystemBlock;
+use crate::providers::openai_compatible::OpenAiCompatibleClient;
+use crate::providers::provider::{Provider, ProviderError, ProviderResponse, ResponseFormat};
+use crate::providers::retry::RetryPolicy;
+use crate::tools::Tool;
+
+/// `api-version` used when a model does not set one.
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+/// OAuth scope for Azure OpenAI data-plane access.
+const AD_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
+/// Refresh Azure AD tokens this long before they expire.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// How requests to the Azure resource are authenticated.
+#[derive(Clone)]
+pub enum AzureAuth {
+    /// Resource key, sent as the `api-key` header.
+    ApiKey(String),
+    /// Service principal; a bearer token is fetched and cached per client.
+    AzureAd(Arc<AzureAdTokenSource>),
+}
+
+impl AzureAuth {
+    async fn headers(&self) -> Result<HeaderMap, ProviderError> {
+        let (name, value) = match self {
+            Self::ApiKey(key) => ("api-key", key.clone()),
+            Self::AzureAd(source) => (
+                AUTHORIZATION.as_str(),
+                format!("Bearer {}", source.token().await?),
+            ),
+        };
+        let mut headers = HeaderMap::new();
+        let value = HeaderValue::from_str(&value)
+            .map_err(|e| ProviderError::InvalidFormat(format!("invalid Azure credential: {e}")))?;
+        headers.insert(name, value);
+        Ok(headers)
+    }
+}
+
+/// Azure AD client-credentials token source with an in-memory cache.
+pub struct AzureAdTokenSource {
+    http_client: reqwest::Client,
+    credentials: AzureAdCredentials,
+    cached: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+impl AzureAdTokenSource {
+    pub fn new(credentials: AzureAdCredentials) -> Self {
+        Self {
+            http_client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("Failed to build HTTP client"),
+            credentials,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// A valid access token, fetched from the tenant when the cached one is
+    /// missing or about to expire.
+    async fn token(&self) -> Result<String, ProviderError> {
+        let mut cached = self.cached.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref()
+            && Instant::now() + TOKEN_REFRESH_MARGIN < *expires_at
+        {
+            return Ok(token.clone());
+        }
+
+        let url = format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+            self.credentials.tenant_id
+        );
+        let response = self
+            .http_client
+            .post(&url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.credentials.client_id.as_str()),
+                ("client_secret", self.credentials.client_secret.as_str()),
+                ("scope", AD_SCOPE),
+            ])
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ProviderError::ApiError {
+                status: status.as_u16(),
+                message: format!("Azure AD token request failed: {error_text}"),
+            });
+        }
+        let body: TokenResponse = response.json().await?;
+        *cached = Some((
+            body.access_token.clone(),
+            Instant::now() + Duration::from_secs(body.expires_in),
+        ));
+        Ok(body.access_token)
+    }
+}
+
+/// Chat completions URL for a deployment on an Azure OpenAI resource.
+pub fn deployment_url(endpoint: &str, deployment: &str, api_version: &str) -> String {
+    format!(
+        "{}/openai/deployments/{}/chat/completions?api-version={}",
+        endpoint.trim_end_matches('/'),
+        deployment,
+        api_version
+    )
+}
+
+/// Azure OpenAI API client
+#[derive(Clone)]
+pub struct AzureOpenAiClient {
+    inner: OpenAiCompatibleClient,
+    auth: AzureAuth,
+}
+
+impl AzureOpenAiClient {
+    /// Create a client for `deployment` on the resource at `endpoint`
+    /// (e.g. `https://my-resource.openai.azure.com`).
+    pub fn new(
+        endpoint: impl Into<String>,
+        deployment: impl Into<String>,
+        api_version: Option<&str>,
+        auth: AzureAuth,
+    ) -> Self {
+        let endpoint = endpoint.into();
+        let deployment = deployment.into();
+        let url = deployment_url(
+            &endpoint,
+            &deployment,
+            api_version.unwrap_or(DEFAULT_API_VERSION),
+        );
+        Self {
+            inner: OpenAiCompatibleClient::new(endpoint, None, deployment, "azure_openai")
+                .with_endpoint_url(url),
+            auth,
+        }
+    }
+
+    /// Enable or disable debug query logging
+    pub fn with_dump_queries(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_dump_queries(enabled);
+        self
+    }
+
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry(retry);
+        self
+    }
+
+    /// Set the per-attempt request timeout
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.inner = self.inner.with_timeout(timeout);
+        self
+    }
+
+    /// Set the sampling parameters sent with every request
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.inner = self.inner.with_generation(generation);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for AzureOpenAiClient {
+    fn name(&self) -> &str {
+        "azure_openai"
+    }
+
+    /// The deployment name.
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn send_conversation(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        tools: Vec<&dyn Tool>,
+        new_message: Option<&str>,
+        _message_limit: Option<usize>,
+        _tool_choice: Option<String>,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let headers = self.auth.headers().await?;
+        self.inner
+            .send_chat_completion(headers, system, history, tools, new_message, None)
+            .await
+    }
+
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let headers = self.auth.headers().await?;
+        self.inner
+            .send_chat_completion(headers, system, history, vec![], new_message, Some(format))
+            .await
+    }
+
+    fn with_generation_overrides(&self, overrides: &GenerationParams) -> Box<dyn Provider> {
+        let generation = self.inner.generation().overlay(overrides);
+        Box::new(self.clone().with_generation(generation))
+    }
+
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deployment_url() {
+        assert_eq!(
+            deployment_url("https://res.openai.azure.com/", "gpt4o-prod", "2024-10-21"),
+            "https://res.openai.azure.com/openai/deployments/gpt4o-prod/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth_header() {
+        let client = AzureOpenAiClient::new(
+            "https://res.openai.azure.com",
+            "gpt4o-prod",
+            None,
+            AzureAuth::ApiKey("secret".to_string()),
+        );
+        assert_eq!(client.name(), "azure_openai");
+        assert_eq!(client.model(), "gpt4o-prod");
+
+        let headers = client.auth.headers().await.unwrap();
+        assert_eq!(headers.get("api-key").unwrap(), "secret");
+        assert!(headers.get(AUTHORIZATION).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_ad_token_is_reused() {
+        let source = AzureAdTokenSource::new(AzureAdCredentials {
+            tenant_id: "tenant".to_string(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+        });
+        *source.cached.lock().await = Some((
+            "cached".to_string(),
+            Instant::now() + Duration::from_secs(3600),
+        ));
+
+        let headers = AzureAuth::AzureAd(Arc::new(source))
+            .headers()
+            .await
+            .unwrap();
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Bearer cached");
+    }
+}
@@ -0,0 +1,5 @@
+//! Azure OpenAI integration (deployment URLs, `api-key` or Azure AD auth).
+
+pub mod client;
+
+pub use client::{AzureAdTokenSource, AzureAuth, AzureOpenAiClient};
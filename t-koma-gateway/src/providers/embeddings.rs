@@ -0,0 +1,195 @@
+//! Embeddings provider shared with the knowledge engine.
+//!
+//! The knowledge crate only knows the [`EmbeddingsProvider`] trait. The
+//! gateway implements it here so embedding requests use the same API keys,
+//! `[provider_retry]` policy and circuit breaker as chat requests. The
+//! breaker entry is keyed [`EMBEDDINGS_BREAKER_KEY`]; while it is open,
+//! indexing and search fail fast instead of hammering a broken endpoint.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use t_koma_core::Config;
+use t_koma_core::config::{EmbeddingProviderKind, KnowledgeSettings};
+use t_koma_knowledge::embeddings::{
+    EmbeddingsProvider, OllamaEmbedRequest, OllamaEmbedResponse, OpenAiEmbedRequest,
+    OpenAiEmbedResponse, parse_ollama_response, parse_openai_response,
+};
+use t_koma_knowledge::errors::{KnowledgeError, KnowledgeResult};
+
+use crate::circuit_breaker::{CircuitBreaker, CooldownReason};
+use crate::providers::retry::{RetryPolicy, send_with_retry};
+
+/// Circuit breaker key for embedding requests.
+pub const EMBEDDINGS_BREAKER_KEY: &str = "embeddings";
+
+/// Embeddings over Ollama or an OpenAI-style `/embeddings` API (OpenRouter,
+/// OpenAI, Voyage AI, local servers), with retries and circuit breaking.
+pub struct GatewayEmbeddings {
+    kind: EmbeddingProviderKind,
+    base_url: String,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+    circuit_breaker: Arc<CircuitBreaker>,
+    client: reqwest::Client,
+}
+
+impl GatewayEmbeddings {
+    /// Build from the gateway config and its shared circuit breaker.
+    pub fn from_config(config: &Config, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        let settings = KnowledgeSettings::from(&config.settings.tools.knowledge);
+        let kind = settings.embedding_provider;
+        Self {
+            kind,
+            base_url: settings.embedding_url.trim_end_matches('/').to_string(),
+            api_key: config
+                .embedding_api_key()
+                .filter(|key| !key.trim().is_empty()),
+            retry: RetryPolicy::for_provider(&config.settings.provider_retry, &kind.to_string()),
+            circuit_breaker,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(
+                    config
+                        .settings
+                        .provider_timeouts
+                        .for_provider(&kind.to_string()),
+                ))
+                .build()
+                .expect("Failed to build HTTP client"),
+        }
+    }
+
+    async fn send(
+        &self,
+        model: &str,
+        build: impl Fn() -> reqwest::RequestBuilder,
+    ) -> KnowledgeResult<reqwest::Response> {
+        if !self.circuit_breaker.is_available(EMBEDDINGS_BREAKER_KEY) {
+            return Err(KnowledgeError::Embedding(format!(
+                "{} embeddings are cooling down after repeated failures",
+                self.kind
+            )));
+        }
+
+        let response = match send_with_retry(&self.retry, self.name(), model, build).await {
+            Ok(response) => response,
+            Err(err) => {
+                if err.is_connect() || err.is_timeout() {
+                    self.circuit_breaker
+                        .record_failure(EMBEDDINGS_BREAKER_KEY, CooldownReason::ServerError);
+                }
+                return Err(err.into());
+            }
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                self.circuit_breaker
+                    .record_failure(EMBEDDINGS_BREAKER_KEY, CooldownReason::RateLimited);
+            } else if status.is_server_error() {
+                self.circuit_breaker
+                    .record_failure(EMBEDDINGS_BREAKER_KEY, CooldownReason::ServerError);
+            }
+            let text = response.text().await.unwrap_or_default();
+            return Err(KnowledgeError::Embedding(format!(
+                "{} embedding request failed: {status} {text}",
+                self.kind
+            )));
+        }
+
+        self.circuit_breaker.record_success(EMBEDDINGS_BREAKER_KEY);
+        Ok(response)
+    }
+
+    async fn embed_ollama(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.base_url);
+        let body = OllamaEmbedRequest {
+            model: model.to_string(),
+            input: inputs.to_vec(),
+        };
+
+        let response = self
+            .send(model, || self.client.post(&url).json(&body))
+            .await?;
+        let payload: OllamaEmbedResponse = response.json().await?;
+        parse_ollama_response(payload)
+    }
+
+    async fn embed_openai(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        // Local OpenAI-compatible servers usually need no key.
+        if self.api_key.is_none() && self.kind == EmbeddingProviderKind::OpenRouter {
+            return Err(KnowledgeError::Embedding(
+                "openrouter embedding provider requires OPENROUTER_API_KEY".to_string(),
+            ));
+        }
+
+        let url = format!("{}/embeddings", self.base_url);
+        let body = OpenAiEmbedRequest {
+            model: model.to_string(),
+            input: inputs.to_vec(),
+        };
+
+        let response = self
+            .send(model, || {
+                let request = self.client.post(&url).json(&body);
+                match &self.api_key {
+                    Some(api_key) => request.bearer_auth(api_key),
+                    None => request,
+                }
+            })
+            .await?;
+        let payload: OpenAiEmbedResponse = response.json().await?;
+        Ok(parse_openai_response(payload))
+    }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingsProvider for GatewayEmbeddings {
+    fn name(&self) -> &str {
+        match self.kind {
+            EmbeddingProviderKind::Ollama => "ollama",
+            EmbeddingProviderKind::OpenRouter => "openrouter",
+            EmbeddingProviderKind::OpenAi => "openai",
+        }
+    }
+
+    async fn embed(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        match self.kind {
+            EmbeddingProviderKind::Ollama => self.embed_ollama(model, inputs).await,
+            EmbeddingProviderKind::OpenRouter | EmbeddingProviderKind::OpenAi => {
+                self.embed_openai(model, inputs).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_open_breaker_fails_fast() {
+        let breaker = Arc::new(CircuitBreaker::new());
+        let embeddings = GatewayEmbeddings {
+            kind: EmbeddingProviderKind::OpenAi,
+            // Never contacted: the open breaker short-circuits the request.
+            base_url: "http://127.0.0.1:9".to_string(),
+            api_key: None,
+            retry: RetryPolicy::default(),
+            circuit_breaker: Arc::clone(&breaker),
+            client: reqwest::Client::new(),
+        };
+
+        breaker.record_failure(EMBEDDINGS_BREAKER_KEY, CooldownReason::RateLimited);
+        let err = embeddings
+            .embed("text-embedding-3-small", &["hello".to_string()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cooling down"), "{err}");
+
+        breaker.record_success(EMBEDDINGS_BREAKER_KEY);
+        assert!(breaker.is_available(EMBEDDINGS_BREAKER_KEY));
+    }
+}
@@ -1,15 +1,21 @@
 //! Google Gemini API client.
 
+use std::time::Duration;
+
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use t_koma_core::GenerationParams;
 
 use crate::chat::history::ChatMessage;
 use crate::prompt::render::SystemBlock;
-use crate::providers::gemini::history::{GeminiContent, to_gemini_contents};
+use crate::providers::gemini::history::{GeminiContent, GeminiInlineData, to_gemini_contents};
+use crate::providers::generation;
 use crate::providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    ImageSource, Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    ResponseFormat,
 };
+use crate::providers::retry::{DEFAULT_REQUEST_TIMEOUT, RetryPolicy, send_with_retry};
 use crate::tools::Tool;
 
 /// Gemini API client
@@ -20,6 +26,10 @@ pub struct GeminiClient {
     model: String,
     base_url: String,
     dump_queries: bool,
+    retry: RetryPolicy,
+    /// Per-attempt request timeout
+    timeout: Duration,
+    generation: GenerationParams,
 }
 
 /// Request body for the Gemini generateContent API
@@ -68,6 +78,16 @@ struct FunctionDeclaration {
 struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_json_schema: Option<Value>,
 }
 
 /// Response from the generateContent API
@@ -102,6 +122,10 @@ pub enum CandidatePart {
     Text {
         text: String,
     },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: GeminiInlineData,
+    },
     FunctionCall {
         #[serde(rename = "functionCall")]
         function_call: FunctionCallData,
@@ -133,7 +157,6 @@ impl GeminiClient {
 
         let http_client = reqwest::Client::builder()
             .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(120))
             .build()
             .expect("Failed to build HTTP client");
 
@@ -143,6 +166,9 @@ impl GeminiClient {
             model: model.into(),
             base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
             dump_queries: false,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -152,6 +178,24 @@ impl GeminiClient {
         self
     }
 
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the per-attempt request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the sampling parameters sent with every request
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
     /// Send a conversation with full history
     ///
     /// # Arguments
@@ -169,6 +213,21 @@ impl GeminiClient {
         new_message: Option<&str>,
         message_limit: Option<usize>,
         _tool_choice: Option<String>,
+    ) -> Result<(GenerateContentResponse, String), ProviderError> {
+        self.generate_content(system, history, tools, new_message, message_limit, None)
+            .await
+    }
+
+    /// Call `generateContent`, constraining the reply to `response_format`
+    /// (as JSON) when set.
+    async fn generate_content(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        tools: Vec<&dyn Tool>,
+        new_message: Option<&str>,
+        message_limit: Option<usize>,
+        response_format: Option<&ResponseFormat>,
     ) -> Result<(GenerateContentResponse, String), ProviderError> {
         let url = format!(
             "{}/models/{}:generateContent?key={}",
@@ -207,25 +266,30 @@ impl GeminiClient {
             system_instruction,
             tools: tool_declarations,
             generation_config: Some(GenerationConfig {
-                max_output_tokens: Some(8192),
+                max_output_tokens: Some(self.generation.max_output_tokens.unwrap_or(8192)),
+                temperature: self.generation.temperature,
+                top_p: self.generation.top_p,
+                stop_sequences: self.generation.stop.clone(),
+                response_mime_type: response_format.map(|_| "application/json".to_string()),
+                response_json_schema: response_format.map(|format| format.schema.clone()),
             }),
         };
 
-        let request_value: Value = serde_json::to_value(&request_body)?;
+        let request_body = generation::request_body(&request_body, &self.generation)?;
 
         let dump_handle = if self.dump_queries {
-            super::super::query_dump::QueryDump::request("gemini", &self.model, &request_value)
-                .await
+            super::super::query_dump::QueryDump::request("gemini", &self.model, &request_body).await
         } else {
             None
         };
 
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&request_body)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry, "gemini", &self.model, || {
+            self.http_client
+                .post(&url)
+                .timeout(self.timeout)
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         let response_text = response.text().await?;
@@ -278,7 +342,38 @@ impl Provider for GeminiClient {
                 tool_choice,
             )
             .await?;
+        self.to_provider_response(response, raw_json)
+    }
+
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let (response, raw_json) = self
+            .generate_content(system, history, vec![], new_message, None, Some(format))
+            .await?;
+        self.to_provider_response(response, raw_json)
+    }
+
+    fn with_generation_overrides(&self, overrides: &GenerationParams) -> Box<dyn Provider> {
+        let generation = self.generation.overlay(overrides);
+        Box::new(self.clone().with_generation(generation))
+    }
+
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+}
 
+impl GeminiClient {
+    fn to_provider_response(
+        &self,
+        response: GenerateContentResponse,
+        raw_json: String,
+    ) -> Result<ProviderResponse, ProviderError> {
         // Extract first candidate
         let candidate = response
             .candidates
@@ -292,6 +387,14 @@ impl Provider for GeminiClient {
                 CandidatePart::Text { text } => {
                     content.push(ProviderContentBlock::Text { text: text.clone() });
                 }
+                CandidatePart::InlineData { inline_data } => {
+                    content.push(ProviderContentBlock::Image {
+                        source: ImageSource::Base64 {
+                            media_type: inline_data.mime_type.clone(),
+                            data: inline_data.data.clone(),
+                        },
+                    });
+                }
                 CandidatePart::FunctionCall { function_call } => {
                     // Generate a unique ID for tool use
                     let id = format!("call_{}", uuid::Uuid::new_v4());
@@ -325,10 +428,6 @@ impl Provider for GeminiClient {
             },
         })
     }
-
-    fn clone_box(&self) -> Box<dyn Provider> {
-        Box::new(self.clone())
-    }
 }
 
 /// Recursively strip JSON Schema fields that Gemini's FunctionDeclaration
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
+use crate::providers::provider::ImageSource;
 
 /// Gemini API content structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +24,10 @@ pub enum GeminiPart {
         #[serde(rename = "inlineData")]
         inline_data: GeminiInlineData,
     },
+    FileData {
+        #[serde(rename = "fileData")]
+        file_data: GeminiFileData,
+    },
     FunctionCall {
         #[serde(rename = "functionCall")]
         function_call: FunctionCall,
@@ -41,6 +46,15 @@ pub struct GeminiInlineData {
     pub data: String,
 }
 
+/// Gemini file data for images referenced by URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFileData {
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(rename = "fileUri")]
+    pub file_uri: String,
+}
+
 /// Gemini function call structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionCall {
@@ -109,25 +123,41 @@ pub async fn to_gemini_contents(
                         function_call: FunctionCall { name, args: input },
                     });
                 }
-                ChatContentBlock::Image { path, filename, .. } => {
-                    match crate::chat::history::load_image_base64(&path).await {
-                        Some((data, mime_type)) => {
-                            parts.push(GeminiPart::InlineData {
-                                inline_data: GeminiInlineData { mime_type, data },
-                            });
-                        }
-                        None => {
-                            parts.push(GeminiPart::Text {
-                                text: format!("(image unavailable: {})", filename),
-                            });
-                        }
+                ChatContentBlock::Image {
+                    path,
+                    mime_type,
+                    filename,
+                    ..
+                } => match crate::chat::history::load_image_source(&path).await {
+                    Some(ImageSource::Base64 { media_type, data }) => {
+                        parts.push(GeminiPart::InlineData {
+                            inline_data: GeminiInlineData {
+                                mime_type: media_type,
+                                data,
+                            },
+                        });
                     }
-                }
+                    Some(ImageSource::Url { url }) => {
+                        parts.push(GeminiPart::FileData {
+                            file_data: GeminiFileData {
+                                mime_type,
+                                file_uri: url,
+                            },
+                        });
+                    }
+                    None => {
+                        parts.push(GeminiPart::Text {
+                            text: format!("(image unavailable: {})", filename),
+                        });
+                    }
+                },
                 ChatContentBlock::File { filename, size, .. } => {
                     parts.push(GeminiPart::Text {
                         text: format!("(attached file: {}, {} bytes)", filename, size),
                     });
                 }
+                // Earlier reasoning is not sent back.
+                ChatContentBlock::Thinking { .. } => {}
                 ChatContentBlock::ToolResult {
                     tool_use_id: _,
                     content,
@@ -0,0 +1,82 @@
+//! Per-model sampling parameters (`GenerationParams`) in request bodies.
+//!
+//! Clients map the common fields (temperature, top_p, stop, max tokens) onto
+//! their request structs; `extra_params` are deep-merged into the serialized
+//! body last, so they can also override what the client set.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use t_koma_core::GenerationParams;
+
+/// Reply token limit when a model does not set `max_output_tokens`.
+pub const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 4096;
+
+/// Reply token limit for a request.
+pub fn max_output_tokens(params: &GenerationParams) -> u32 {
+    params
+        .max_output_tokens
+        .unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS)
+}
+
+/// Serialize a request and deep-merge `extra_params` into it.
+pub fn request_body<T: Serialize>(
+    request: &T,
+    params: &GenerationParams,
+) -> Result<Value, serde_json::Error> {
+    let mut body = serde_json::to_value(request)?;
+    if let Some(extra) = &params.extra_params {
+        merge(&mut body, extra);
+    }
+    Ok(body)
+}
+
+fn merge(target: &mut Value, patch: &Map<String, Value>) {
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        match (target.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => merge(existing, nested),
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_body_merges_extra_params() {
+        let params = GenerationParams {
+            extra_params: json!({
+                "top_k": 40,
+                "generationConfig": { "topK": 10 },
+                "max_tokens": 100
+            })
+            .as_object()
+            .cloned(),
+            ..Default::default()
+        };
+        let request = json!({
+            "model": "m",
+            "max_tokens": 4096,
+            "generationConfig": { "temperature": 0.5 }
+        });
+
+        let body = request_body(&request, &params).unwrap();
+        assert_eq!(body["top_k"], 40);
+        assert_eq!(body["max_tokens"], 100);
+        assert_eq!(body["generationConfig"]["temperature"], 0.5);
+        assert_eq!(body["generationConfig"]["topK"], 10);
+
+        assert_eq!(
+            request_body(&request, &GenerationParams::default()).unwrap(),
+            request
+        );
+        assert_eq!(max_output_tokens(&GenerationParams::default()), 4096);
+    }
+}
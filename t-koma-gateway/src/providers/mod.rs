@@ -1,10 +1,17 @@
 pub mod anthropic;
+pub mod azure_openai;
+pub mod embeddings;
 pub mod gemini;
+pub mod generation;
 pub mod openai_compatible;
 pub mod openrouter;
 pub mod provider;
 pub mod query_dump;
+pub mod retry;
+pub mod structured;
 
 pub use provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    ImageSource, Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    ResponseFormat,
 };
+pub use retry::RetryPolicy;
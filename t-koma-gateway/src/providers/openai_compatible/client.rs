@@ -1,14 +1,20 @@
 //! OpenAI-compatible API client.
 
+use std::time::Duration;
+
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use t_koma_core::GenerationParams;
 
 use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
 use crate::prompt::render::SystemBlock;
+use crate::providers::generation;
 use crate::providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage, ResponseFormat,
 };
+use crate::providers::retry::{DEFAULT_REQUEST_TIMEOUT, RetryPolicy, send_with_retry};
+use crate::providers::structured::openai_response_format;
 use crate::tools::Tool;
 
 /// OpenAI-compatible API client.
@@ -21,6 +27,11 @@ pub struct OpenAiCompatibleClient {
     provider_name: String,
     dump_queries: bool,
     extra_headers: HeaderMap,
+    endpoint_url: Option<String>,
+    retry: RetryPolicy,
+    /// Per-attempt request timeout
+    timeout: Duration,
+    generation: GenerationParams,
 }
 
 /// Request body for the Chat Completions API
@@ -32,6 +43,14 @@ struct ChatCompletionsRequest {
     tools: Option<Vec<OpenAiToolDefinition>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
     max_tokens: u32,
 }
 
@@ -119,7 +138,6 @@ impl OpenAiCompatibleClient {
 
         let http_client = reqwest::Client::builder()
             .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(120))
             .build()
             .expect("Failed to build HTTP client");
 
@@ -131,6 +149,10 @@ impl OpenAiCompatibleClient {
             provider_name: provider_name.into(),
             dump_queries: false,
             extra_headers: HeaderMap::new(),
+            endpoint_url: None,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -146,6 +168,35 @@ impl OpenAiCompatibleClient {
         self
     }
 
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the per-attempt request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the sampling parameters sent with every request
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Sampling parameters sent with every request
+    pub(crate) fn generation(&self) -> &GenerationParams {
+        &self.generation
+    }
+
+    /// Send to this exact URL instead of `<base_url>/v1/chat/completions`.
+    pub fn with_endpoint_url(mut self, url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(url.into());
+        self
+    }
+
     /// Build request headers with optional auth and extra headers.
     fn build_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
@@ -171,6 +222,9 @@ impl OpenAiCompatibleClient {
     }
 
     fn chat_completions_url(&self) -> String {
+        if let Some(url) = &self.endpoint_url {
+            return url.clone();
+        }
         let base = self.normalized_base_url();
         if base.ends_with("/v1") {
             format!("{}/chat/completions", base)
@@ -218,14 +272,10 @@ impl OpenAiCompatibleClient {
                 match block {
                     ChatContentBlock::Text { text, .. } => text_parts.push(text),
                     ChatContentBlock::Image { path, filename, .. } => {
-                        if let Some((data, mime)) =
-                            crate::chat::history::load_image_base64(&path).await
-                        {
+                        if let Some(source) = crate::chat::history::load_image_source(&path).await {
                             image_parts.push(serde_json::json!({
                                 "type": "image_url",
-                                "image_url": {
-                                    "url": format!("data:{};base64,{}", mime, data)
-                                }
+                                "image_url": { "url": source.to_url() }
                             }));
                         } else {
                             text_parts.push(format!("(image unavailable: {})", filename));
@@ -234,6 +284,8 @@ impl OpenAiCompatibleClient {
                     ChatContentBlock::File { filename, size, .. } => {
                         text_parts.push(format!("(attached file: {}, {} bytes)", filename, size));
                     }
+                    // Earlier reasoning is not sent back.
+                    ChatContentBlock::Thinking { .. } => {}
                     ChatContentBlock::ToolUse { id, name, input } => {
                         let arguments =
                             serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
@@ -411,6 +463,50 @@ impl Provider for OpenAiCompatibleClient {
         new_message: Option<&str>,
         _message_limit: Option<usize>,
         _tool_choice: Option<String>,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_chat_completion(HeaderMap::new(), system, history, tools, new_message, None)
+            .await
+    }
+
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_chat_completion(
+            HeaderMap::new(),
+            system,
+            history,
+            vec![],
+            new_message,
+            Some(format),
+        )
+        .await
+    }
+
+    fn with_generation_overrides(&self, overrides: &GenerationParams) -> Box<dyn Provider> {
+        let generation = self.generation.overlay(overrides);
+        Box::new(self.clone().with_generation(generation))
+    }
+
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+}
+
+impl OpenAiCompatibleClient {
+    /// Send a chat completion request with `request_headers` added on top of
+    /// the client's own (used for short-lived auth such as Azure AD tokens).
+    pub(crate) async fn send_chat_completion(
+        &self,
+        request_headers: HeaderMap,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        tools: Vec<&dyn Tool>,
+        new_message: Option<&str>,
+        response_format: Option<&ResponseFormat>,
     ) -> Result<ProviderResponse, ProviderError> {
         let url = self.chat_completions_url();
 
@@ -439,8 +535,13 @@ impl Provider for OpenAiCompatibleClient {
             messages,
             tools: tool_definitions,
             tool_choice,
-            max_tokens: 4096,
+            response_format: response_format.map(openai_response_format),
+            temperature: self.generation.temperature,
+            top_p: self.generation.top_p,
+            stop: self.generation.stop.clone(),
+            max_tokens: generation::max_output_tokens(&self.generation),
         };
+        let request_body = generation::request_body(&request_body, &self.generation)?;
 
         let dump = if self.dump_queries
             && let Ok(val) = serde_json::to_value(&request_body)
@@ -451,13 +552,15 @@ impl Provider for OpenAiCompatibleClient {
             None
         };
 
-        let response = self
-            .http_client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request_body)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry, &self.provider_name, &self.model, || {
+            self.http_client
+                .post(&url)
+                .timeout(self.timeout)
+                .headers(self.build_headers())
+                .headers(request_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -489,10 +592,6 @@ impl Provider for OpenAiCompatibleClient {
             })?;
         Ok(self.convert_response(completions_response, &response_text))
     }
-
-    fn clone_box(&self) -> Box<dyn Provider> {
-        Box::new(self.clone())
-    }
 }
 
 #[cfg(test)]
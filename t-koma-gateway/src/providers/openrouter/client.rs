@@ -1,15 +1,21 @@
 //! OpenRouter API client with OpenAI-compatible format.
 
+use std::time::Duration;
+
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use t_koma_core::GenerationParams;
 
 use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
 use crate::prompt::render::SystemBlock;
+use crate::providers::generation;
 use crate::providers::openai_compatible::client::build_content_value;
 use crate::providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage, ResponseFormat,
 };
+use crate::providers::retry::{DEFAULT_REQUEST_TIMEOUT, RetryPolicy, send_with_retry};
+use crate::providers::structured::openai_response_format;
 use crate::tools::Tool;
 
 /// OpenRouter API client
@@ -23,6 +29,12 @@ pub struct OpenRouterClient {
     app_name: Option<String>,
     routing: Option<Vec<String>>,
     dump_queries: bool,
+    retry: RetryPolicy,
+    /// Per-attempt request timeout
+    timeout: Duration,
+    /// Reasoning token budget; `None` leaves reasoning to the model default
+    thinking_budget: Option<u32>,
+    generation: GenerationParams,
 }
 
 /// Request body for the Chat Completions API
@@ -35,7 +47,17 @@ struct ChatCompletionsRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     provider: Option<OpenRouterProviderRoutingRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
     max_tokens: u32,
 }
 
@@ -57,6 +79,9 @@ struct OpenAiMessage {
     tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
+    /// Reasoning text returned with the reply (never sent back)
+    #[serde(default, skip_serializing)]
+    reasoning: Option<String>,
 }
 
 /// OpenAI-compatible tool call
@@ -162,6 +187,61 @@ pub struct OpenRouterModel {
     pub description: Option<String>,
     #[serde(rename = "context_length")]
     pub context_length: Option<u32>,
+    #[serde(default)]
+    pub pricing: Option<OpenRouterModelPricing>,
+    #[serde(default)]
+    pub architecture: Option<OpenRouterModelArchitecture>,
+}
+
+/// Listed prices, in USD per token (decimal strings)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OpenRouterModelPricing {
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub completion: Option<String>,
+    #[serde(default)]
+    pub input_cache_read: Option<String>,
+    #[serde(default)]
+    pub input_cache_write: Option<String>,
+}
+
+/// Input and output modalities of a model
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OpenRouterModelArchitecture {
+    #[serde(default)]
+    pub input_modalities: Vec<String>,
+    #[serde(default)]
+    pub output_modalities: Vec<String>,
+}
+
+impl OpenRouterModel {
+    /// Convert to a `model_catalog` row (prices per million tokens).
+    pub fn to_catalog_entry(&self) -> t_koma_db::ModelCatalogEntry {
+        // Negative prices mark variable-priced routers (e.g. `openrouter/auto`).
+        let per_mtok = |price: &Option<String>| {
+            price
+                .as_deref()
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| *p > 0.0)
+                .map_or(0.0, |p| p * 1_000_000.0)
+        };
+        let pricing = self.pricing.clone().unwrap_or_default();
+        let architecture = self.architecture.clone().unwrap_or_default();
+        t_koma_db::ModelCatalogEntry {
+            provider: "openrouter".to_string(),
+            model: self.id.clone(),
+            name: self.name.clone(),
+            context_length: self.context_length,
+            input_per_mtok: per_mtok(&pricing.prompt),
+            output_per_mtok: per_mtok(&pricing.completion),
+            cache_read_per_mtok: per_mtok(&pricing.input_cache_read),
+            cache_write_per_mtok: per_mtok(&pricing.input_cache_write),
+            input_modalities: architecture.input_modalities,
+            output_modalities: architecture.output_modalities,
+            fetched_at: 0,
+        }
+    }
 }
 
 /// Response from the models endpoint
@@ -185,7 +265,6 @@ impl OpenRouterClient {
 
         let http_client = reqwest::Client::builder()
             .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(120))
             .build()
             .expect("Failed to build HTTP client");
 
@@ -198,6 +277,10 @@ impl OpenRouterClient {
             app_name,
             routing,
             dump_queries: false,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            thinking_budget: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -207,6 +290,30 @@ impl OpenRouterClient {
         self
     }
 
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the per-attempt request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the sampling parameters sent with every request
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Request reasoning with this token budget
+    pub fn with_thinking_budget(mut self, budget: Option<u32>) -> Self {
+        self.thinking_budget = budget;
+        self
+    }
+
     /// Update the model for this client
     pub fn set_model(&mut self, model: impl Into<String>) {
         self.model = model.into();
@@ -265,6 +372,7 @@ impl OpenRouterClient {
             content: Some(Value::String(content)),
             tool_calls: None,
             tool_call_id: None,
+            reasoning: None,
         })
     }
 
@@ -286,14 +394,10 @@ impl OpenRouterClient {
                 match block {
                     ChatContentBlock::Text { text, .. } => text_parts.push(text),
                     ChatContentBlock::Image { path, filename, .. } => {
-                        if let Some((data, mime)) =
-                            crate::chat::history::load_image_base64(&path).await
-                        {
+                        if let Some(source) = crate::chat::history::load_image_source(&path).await {
                             image_parts.push(serde_json::json!({
                                 "type": "image_url",
-                                "image_url": {
-                                    "url": format!("data:{};base64,{}", mime, data)
-                                }
+                                "image_url": { "url": source.to_url() }
                             }));
                         } else {
                             text_parts.push(format!("(image unavailable: {})", filename));
@@ -302,6 +406,8 @@ impl OpenRouterClient {
                     ChatContentBlock::File { filename, size, .. } => {
                         text_parts.push(format!("(attached file: {}, {} bytes)", filename, size));
                     }
+                    // Earlier reasoning is not sent back.
+                    ChatContentBlock::Thinking { .. } => {}
                     ChatContentBlock::ToolUse { id, name, input } => {
                         let arguments =
                             serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
@@ -321,6 +427,7 @@ impl OpenRouterClient {
                             content: Some(Value::String(content)),
                             tool_calls: None,
                             tool_call_id: Some(tool_use_id),
+                            reasoning: None,
                         });
                     }
                 }
@@ -340,6 +447,7 @@ impl OpenRouterClient {
                                 Some(tool_calls)
                             },
                             tool_call_id: None,
+                            reasoning: None,
                         });
                     }
                 }
@@ -350,6 +458,7 @@ impl OpenRouterClient {
                             content,
                             tool_calls: None,
                             tool_call_id: None,
+                            reasoning: None,
                         });
                     }
                 }
@@ -364,6 +473,7 @@ impl OpenRouterClient {
                 content: Some(Value::String(content.to_string())),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             });
         }
 
@@ -401,6 +511,16 @@ impl OpenRouterClient {
             Some(choice) => {
                 let mut blocks = Vec::new();
 
+                if let Some(reasoning) = choice.message.reasoning
+                    && !reasoning.trim().is_empty()
+                {
+                    blocks.push(ProviderContentBlock::Thinking {
+                        thinking: reasoning,
+                        signature: None,
+                        redacted: false,
+                    });
+                }
+
                 if let Some(Value::String(text)) = choice.message.content
                     && !text.is_empty()
                 {
@@ -462,12 +582,13 @@ impl OpenRouterClient {
     pub async fn fetch_models(&self) -> Result<Vec<OpenRouterModel>, ProviderError> {
         let url = format!("{}/models", self.base_url);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .headers(self.build_headers())
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry, "openrouter", &self.model, || {
+            self.http_client
+                .get(&url)
+                .timeout(self.timeout)
+                .headers(self.build_headers())
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -501,6 +622,40 @@ impl Provider for OpenRouterClient {
         new_message: Option<&str>,
         _message_limit: Option<usize>,
         _tool_choice: Option<String>,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_chat_completion(system, history, tools, new_message, None)
+            .await
+    }
+
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_chat_completion(system, history, vec![], new_message, Some(format))
+            .await
+    }
+
+    fn with_generation_overrides(&self, overrides: &GenerationParams) -> Box<dyn Provider> {
+        let generation = self.generation.overlay(overrides);
+        Box::new(self.clone().with_generation(generation))
+    }
+
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+}
+
+impl OpenRouterClient {
+    async fn send_chat_completion(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        tools: Vec<&dyn Tool>,
+        new_message: Option<&str>,
+        response_format: Option<&ResponseFormat>,
     ) -> Result<ProviderResponse, ProviderError> {
         let url = format!("{}/chat/completions", self.base_url);
 
@@ -532,9 +687,19 @@ impl Provider for OpenRouterClient {
             messages,
             tools: tool_definitions,
             tool_choice,
+            response_format: response_format.map(openai_response_format),
             provider: self.provider_routing_request(),
-            max_tokens: 4096,
+            reasoning: self
+                .thinking_budget
+                .map(|budget| serde_json::json!({"max_tokens": budget})),
+            temperature: self.generation.temperature,
+            top_p: self.generation.top_p,
+            stop: self.generation.stop.clone(),
+            // `max_tokens` includes reasoning tokens.
+            max_tokens: generation::max_output_tokens(&self.generation)
+                + self.thinking_budget.unwrap_or(0),
         };
+        let request_body = generation::request_body(&request_body, &self.generation)?;
 
         let dump = if self.dump_queries
             && let Ok(val) = serde_json::to_value(&request_body)
@@ -544,13 +709,14 @@ impl Provider for OpenRouterClient {
             None
         };
 
-        let response = self
-            .http_client
-            .post(&url)
-            .headers(self.build_headers())
-            .json(&request_body)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry, "openrouter", &self.model, || {
+            self.http_client
+                .post(&url)
+                .timeout(self.timeout)
+                .headers(self.build_headers())
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -582,10 +748,6 @@ impl Provider for OpenRouterClient {
             })?;
         Ok(self.convert_response(completions_response, &response_text))
     }
-
-    fn clone_box(&self) -> Box<dyn Provider> {
-        Box::new(self.clone())
-    }
 }
 
 impl Usage {
@@ -696,7 +858,12 @@ mod tests {
             messages: vec![],
             tools: None,
             tool_choice: None,
+            response_format: None,
             provider: client.provider_routing_request(),
+            reasoning: None,
+            temperature: None,
+            top_p: None,
+            stop: None,
             max_tokens: 10,
         };
         let json = serde_json::to_value(body).unwrap();
@@ -710,11 +877,112 @@ mod tests {
             messages: vec![],
             tools: None,
             tool_choice: None,
+            response_format: None,
             provider: None,
+            reasoning: None,
+            temperature: None,
+            top_p: None,
+            stop: None,
             max_tokens: 10,
         };
         let json = serde_json::to_value(body).unwrap();
         assert!(json.get("provider").is_none());
+        assert!(json.get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_chat_request_includes_response_format() {
+        let format = ResponseFormat {
+            name: "reply".to_string(),
+            schema: serde_json::json!({"type": "object"}),
+        };
+        let body = ChatCompletionsRequest {
+            model: "test-model".to_string(),
+            messages: vec![],
+            tools: None,
+            tool_choice: None,
+            response_format: Some(openai_response_format(&format)),
+            provider: None,
+            reasoning: None,
+            temperature: None,
+            top_p: None,
+            stop: None,
+            max_tokens: 10,
+        };
+        let json = serde_json::to_value(body).unwrap();
+        assert_eq!(json["response_format"]["type"], "json_schema");
+        assert_eq!(json["response_format"]["json_schema"]["name"], "reply");
+    }
+
+    #[test]
+    fn test_model_catalog_entry() {
+        let models: OpenRouterModelsResponse = serde_json::from_value(serde_json::json!({
+            "data": [{
+                "id": "anthropic/claude-sonnet-4",
+                "name": "Anthropic: Claude Sonnet 4",
+                "context_length": 200000,
+                "pricing": {
+                    "prompt": "0.000003",
+                    "completion": "0.000015",
+                    "input_cache_read": "0.0000003",
+                    "input_cache_write": "0.00000375"
+                },
+                "architecture": {
+                    "input_modalities": ["text", "image"],
+                    "output_modalities": ["text"]
+                }
+            }, {
+                "id": "openrouter/auto",
+                "name": "Auto Router",
+                "context_length": null,
+                "pricing": { "prompt": "-1", "completion": "-1" }
+            }]
+        }))
+        .unwrap();
+
+        let sonnet = models.data[0].to_catalog_entry();
+        assert_eq!(sonnet.context_length, Some(200_000));
+        assert!((sonnet.input_per_mtok - 3.0).abs() < 1e-9);
+        assert!((sonnet.output_per_mtok - 15.0).abs() < 1e-9);
+        assert!((sonnet.cache_read_per_mtok - 0.3).abs() < 1e-9);
+        assert!((sonnet.cache_write_per_mtok - 3.75).abs() < 1e-9);
+        assert_eq!(sonnet.input_modalities, vec!["text", "image"]);
+
+        let auto = models.data[1].to_catalog_entry();
+        assert_eq!(auto.context_length, None);
+        assert_eq!(auto.input_per_mtok, 0.0);
+        assert!(auto.output_modalities.is_empty());
+    }
+
+    #[test]
+    fn test_reasoning_request_and_response() {
+        let client =
+            OpenRouterClient::new("test-key", "deepseek/deepseek-r1", None, None, None, None)
+                .with_thinking_budget(Some(2048));
+        let raw = serde_json::json!({
+            "id": "gen-1",
+            "model": "deepseek/deepseek-r1",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "42",
+                    "reasoning": "Six times seven."
+                },
+                "finish_reason": "stop"
+            }]
+        });
+        let response: ChatCompletionsResponse = serde_json::from_value(raw.clone()).unwrap();
+        let response = client.convert_response(response, &raw.to_string());
+
+        assert!(matches!(
+            &response.content[0],
+            ProviderContentBlock::Thinking { thinking, signature: None, redacted: false }
+                if thinking == "Six times seven."
+        ));
+        assert!(matches!(
+            &response.content[1],
+            ProviderContentBlock::Text { text } if text == "42"
+        ));
     }
 
     #[test]
@@ -2,4 +2,7 @@
 
 pub mod client;
 
-pub use client::{OpenRouterClient, OpenRouterModel, OpenRouterModelsResponse};
+pub use client::{
+    OpenRouterClient, OpenRouterModel, OpenRouterModelArchitecture, OpenRouterModelPricing,
+    OpenRouterModelsResponse,
+};
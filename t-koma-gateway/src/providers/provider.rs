@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use t_koma_core::GenerationParams;
+
 use crate::chat::history::ChatMessage;
 use crate::prompt::render::SystemBlock;
 use crate::tools::Tool;
@@ -13,6 +15,8 @@ use crate::tools::Tool;
 pub enum ProviderContentBlock {
     /// Text content
     Text { text: String },
+    /// Image content
+    Image { source: ImageSource },
     /// Tool use request from assistant
     ToolUse {
         id: String,
@@ -27,6 +31,62 @@ pub enum ProviderContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
     },
+    /// Reasoning emitted before the answer (extended thinking)
+    Thinking {
+        thinking: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        redacted: bool,
+    },
+}
+
+/// Image payload, inline or by reference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    /// Base64-encoded bytes
+    Base64 { media_type: String, data: String },
+    /// Publicly reachable image URL
+    Url { url: String },
+}
+
+impl ImageSource {
+    /// Parse an `http(s)://` or `data:<mime>;base64,` image reference.
+    ///
+    /// Returns `None` for anything else (e.g. a local file path).
+    pub fn from_reference(reference: &str) -> Option<Self> {
+        if let Some(rest) = reference.strip_prefix("data:") {
+            let (media_type, data) = rest.split_once(";base64,")?;
+            return Some(Self::Base64 {
+                media_type: media_type.to_string(),
+                data: data.to_string(),
+            });
+        }
+        if reference.starts_with("https://") || reference.starts_with("http://") {
+            return Some(Self::Url {
+                url: reference.to_string(),
+            });
+        }
+        None
+    }
+
+    /// URL form: the URL itself, or a `data:` URL for inline bytes.
+    pub fn to_url(&self) -> String {
+        match self {
+            Self::Base64 { media_type, data } => format!("data:{};base64,{}", media_type, data),
+            Self::Url { url } => url.clone(),
+        }
+    }
+}
+
+/// JSON Schema a reply must match, for [`Provider::send_structured`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseFormat {
+    /// Schema name (`[a-zA-Z0-9_-]`), used by providers that label schemas
+    pub name: String,
+    /// JSON Schema of the reply
+    pub schema: Value,
 }
 
 /// Unified usage information across providers
@@ -131,6 +191,30 @@ pub trait Provider: Send + Sync {
         tool_choice: Option<String>,
     ) -> Result<ProviderResponse, ProviderError>;
 
+    /// Send a tool-less conversation whose reply must be JSON matching `format`.
+    ///
+    /// Providers with native structured output enforce the schema server-side
+    /// and return the JSON as a single text block. The default only relies on
+    /// the prompt, so callers should validate the reply (see
+    /// [`crate::providers::structured`]).
+    async fn send_structured(
+        &self,
+        system: Option<Vec<SystemBlock>>,
+        history: Vec<ChatMessage>,
+        new_message: Option<&str>,
+        _format: &ResponseFormat,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_conversation(system, history, vec![], new_message, None, None)
+            .await
+    }
+
+    /// A copy of this provider with `overrides` applied on top of its
+    /// sampling parameters. Providers without sampling support return a
+    /// plain copy.
+    fn with_generation_overrides(&self, _overrides: &GenerationParams) -> Box<dyn Provider> {
+        self.clone_box()
+    }
+
     /// Clone the provider (boxed)
     fn clone_box(&self) -> Box<dyn Provider>;
 }
@@ -141,6 +225,23 @@ impl Clone for Box<dyn Provider> {
     }
 }
 
+/// Concatenated reasoning of a response (redacted blocks excluded)
+pub fn extract_thinking(response: &ProviderResponse) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ProviderContentBlock::Thinking {
+                thinking,
+                redacted: false,
+                ..
+            } => Some(thinking.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 /// Extract text content from a response
 pub fn extract_text(response: &ProviderResponse) -> Option<String> {
     response
@@ -213,6 +314,25 @@ mod tests {
         assert_eq!(extract_text(&response), Some("Hello, world!".to_string()));
     }
 
+    #[test]
+    fn test_image_source_references() {
+        let inline = ImageSource::from_reference("data:image/png;base64,iVBORw0=").unwrap();
+        assert_eq!(
+            inline,
+            ImageSource::Base64 {
+                media_type: "image/png".to_string(),
+                data: "iVBORw0=".to_string(),
+            }
+        );
+        assert_eq!(inline.to_url(), "data:image/png;base64,iVBORw0=");
+
+        let remote = ImageSource::from_reference("https://example.com/cat.jpg").unwrap();
+        assert_eq!(remote.to_url(), "https://example.com/cat.jpg");
+
+        assert!(ImageSource::from_reference("/tmp/cat.jpg").is_none());
+        assert!(ImageSource::from_reference("data:image/png,raw").is_none());
+    }
+
     #[test]
     fn test_extract_tool_uses() {
         let response = ProviderResponse {
@@ -0,0 +1,198 @@
+//! Shared retry layer for provider HTTP requests.
+//!
+//! Every provider client sends through [`send_with_retry`], which retries
+//! transient failures (HTTP 429, 500-503, connection errors and timeouts)
+//! with exponential backoff and jitter, honouring `Retry-After`. Each retry is
+//! broadcast as a [`LogEntry::ProviderRetry`]. Once attempts run out the last
+//! response or error is returned unchanged, so the caller's error handling and
+//! the model fallback chain still apply.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::state::{LogEntry, emit_global_log};
+
+/// Per-attempt request timeout when `[provider_timeouts]` sets none.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Retry limits for one provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first.
+    pub max_attempts: u32,
+    /// Backoff before the first retry; doubled for each further retry.
+    pub backoff_base: Duration,
+    /// Longest wait between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::for_provider(&t_koma_core::ProviderRetrySettings::default(), "")
+    }
+}
+
+impl RetryPolicy {
+    /// Resolve the policy for a provider type from `[provider_retry]`.
+    pub fn for_provider(settings: &t_koma_core::ProviderRetrySettings, provider: &str) -> Self {
+        let (max_attempts, backoff_base_ms) = settings.for_provider(provider);
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff_base: Duration::from_millis(backoff_base_ms),
+            max_backoff: Duration::from_millis(settings.max_backoff_ms),
+        }
+    }
+
+    /// Jittered delay before retry number `retry` (1-based): a random value
+    /// between half and all of `backoff_base * 2^(retry - 1)`, capped at
+    /// `max_backoff`.
+    fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .backoff_base
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        let half = exponential / 2;
+        half + half.mul_f64(jitter_fraction())
+    }
+}
+
+/// Uniform value in `[0, 1)`.
+fn jitter_fraction() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() >> 75) as f64 / (1u64 << 53) as f64
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (500..=503).contains(&status.as_u16())
+}
+
+fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.status().is_none() && (error.is_connect() || error.is_timeout() || error.is_request())
+}
+
+/// Parse `Retry-After` as delta-seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Send a request built by `build`, retrying transient failures per `policy`.
+///
+/// `build` is called once per attempt. `provider` and `model` only label the
+/// retry log entries.
+pub async fn send_with_retry(
+    policy: &RetryPolicy,
+    provider: &str,
+    model: &str,
+    build: impl Fn() -> RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    let mut attempt = 1;
+    loop {
+        let outcome = build().send().await;
+        let (delay, reason) = match &outcome {
+            Ok(response) if is_transient_status(response.status()) => {
+                match retry_after(response.headers()) {
+                    // The server wants more than we are willing to wait.
+                    Some(delay) if delay > policy.max_backoff => return outcome,
+                    Some(delay) => (delay, format!("HTTP {} (Retry-After)", response.status())),
+                    None => (
+                        policy.backoff(attempt),
+                        format!("HTTP {}", response.status()),
+                    ),
+                }
+            }
+            Err(error) if is_transient_error(error) => (policy.backoff(attempt), error.to_string()),
+            _ => return outcome,
+        };
+        if attempt >= policy.max_attempts {
+            return outcome;
+        }
+        drop(outcome);
+
+        emit_global_log(LogEntry::ProviderRetry {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            attempt: attempt + 1,
+            max_attempts: policy.max_attempts,
+            delay_ms: delay.as_millis() as u64,
+            reason,
+        });
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            backoff_base: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_cap() {
+        let policy = policy();
+        for _ in 0..50 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let second = policy.backoff(2);
+            assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn test_transient_statuses() {
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_transient_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_transient_status(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn test_retry_after_parsing() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_policy_from_settings() {
+        let mut settings = t_koma_core::ProviderRetrySettings::default();
+        settings.providers.insert(
+            "gemini".to_string(),
+            t_koma_core::ProviderRetryOverride {
+                max_attempts: Some(0),
+                backoff_base_ms: None,
+            },
+        );
+        assert_eq!(
+            RetryPolicy::for_provider(&settings, "gemini").max_attempts,
+            1
+        );
+        assert_eq!(RetryPolicy::default().max_attempts, 3);
+    }
+}
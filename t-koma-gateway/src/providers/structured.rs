@@ -0,0 +1,453 @@
+//! Structured (JSON Schema) output.
+//!
+//! [`Provider::send_structured`] asks a model for a JSON reply matching a
+//! [`ResponseFormat`]. Native support varies (OpenAI-style `response_format`,
+//! Gemini `responseJsonSchema`, a forced tool on Anthropic, prompt-only
+//! elsewhere), so [`send_structured_with_repair`] validates the reply
+//! client-side and, when it does not match, asks the model once to fix it.
+//!
+//! The validator covers the schema subset background jobs use: `type`,
+//! `enum`, `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`/`maxItems`, `minLength`/`maxLength` and `minimum`/`maximum`.
+//! Other keywords are ignored.
+
+use serde_json::{Value, json};
+use tracing::warn;
+
+use crate::chat::history::{ChatContentBlock, ChatMessage, ChatRole};
+use crate::content::{self, ids};
+use crate::prompt::render::SystemBlock;
+use crate::providers::provider::{
+    Provider, ProviderError, ProviderResponse, ResponseFormat, extract_all_text,
+};
+
+/// `response_format` body for OpenAI-compatible chat completions.
+pub(crate) fn openai_response_format(format: &ResponseFormat) -> Value {
+    json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": format.name,
+            "schema": format.schema,
+            // Strict mode rejects optional properties; validation happens here.
+            "strict": false,
+        }
+    })
+}
+
+/// Parse the JSON value in a model reply, tolerating code fences and
+/// surrounding prose.
+pub fn extract_json(text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Ok(value);
+    }
+    let start = trimmed
+        .find(['{', '['])
+        .ok_or_else(|| "reply contains no JSON".to_string())?;
+    let close = if trimmed[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = trimmed
+        .rfind(close)
+        .filter(|end| *end > start)
+        .ok_or_else(|| "reply contains no complete JSON value".to_string())?;
+    serde_json::from_str(&trimmed[start..=end]).map_err(|e| format!("invalid JSON: {e}"))
+}
+
+/// Check `value` against `schema`; the error names the first failing path.
+pub fn validate(value: &Value, schema: &Value) -> Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn type_matches(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    match schema.get("type") {
+        Some(Value::String(ty)) if !type_matches(value, ty) => {
+            return Err(format!("{path}: expected {ty}"));
+        }
+        Some(Value::Array(types))
+            if !types
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|ty| type_matches(value, ty)) =>
+        {
+            let names: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            return Err(format!("{path}: expected one of {}", names.join(", ")));
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        return Err(format!(
+            "{path}: must be one of {}",
+            Value::from(allowed.clone())
+        ));
+    }
+
+    if let Some(text) = value.as_str() {
+        let len = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && len < min
+        {
+            return Err(format!("{path}: shorter than {min} characters"));
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && len > max
+        {
+            return Err(format!("{path}: longer than {max} characters"));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+            && number < min
+        {
+            return Err(format!("{path}: less than {min}"));
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+            && number > max
+        {
+            return Err(format!("{path}: greater than {max}"));
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        let len = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && len < min
+        {
+            return Err(format!("{path}: fewer than {min} items"));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && len > max
+        {
+            return Err(format!("{path}: more than {max} items"));
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_at(item, item_schema, &format!("{path}[{i}]"))?;
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{path}: missing required property `{key}`"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, child) in object {
+            let child_path = format!("{path}.{key}");
+            match (
+                properties.and_then(|p| p.get(key)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(child_schema), _) => validate_at(child, child_schema, &child_path)?,
+                (None, Some(Value::Bool(false))) => {
+                    return Err(format!("{path}: unexpected property `{key}`"));
+                }
+                (None, Some(extra)) => validate_at(child, extra, &child_path)?,
+                (None, None) => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse and validate a reply's text against `format`.
+pub fn parse_reply(text: &str, format: &ResponseFormat) -> Result<Value, String> {
+    let value = extract_json(text)?;
+    validate(&value, &format.schema)?;
+    Ok(value)
+}
+
+/// Outcome of [`send_structured_with_repair`].
+#[derive(Debug)]
+pub struct StructuredReply {
+    /// Every provider response, for usage accounting (two when repaired).
+    pub responses: Vec<ProviderResponse>,
+    /// The validated JSON value, or why the final reply was rejected.
+    pub value: Result<Value, String>,
+}
+
+fn repair_prompt(error: &str, format: &ResponseFormat) -> String {
+    let schema = serde_json::to_string_pretty(&format.schema).unwrap_or_default();
+    content::prompt_text(
+        ids::PROMPT_STRUCTURED_OUTPUT_REPAIR,
+        None,
+        &[("error", error), ("schema", &schema)],
+    )
+    .unwrap_or_else(|e| {
+        warn!("Failed to load structured output repair prompt: {e}, using fallback");
+        format!(
+            "Your previous reply was invalid ({error}). Reply with a single JSON object \
+             matching this JSON Schema and nothing else:\n\n{schema}"
+        )
+    })
+}
+
+fn text_message(role: ChatRole, text: String) -> ChatMessage {
+    ChatMessage {
+        role,
+        content: vec![ChatContentBlock::Text {
+            text,
+            cache_control: None,
+        }],
+    }
+}
+
+/// Request a structured reply to `message`, retrying once with the validation
+/// error when the first reply does not match the schema.
+///
+/// Only the first request's errors are returned; a failed repair request is
+/// logged and reported through [`StructuredReply::value`].
+pub async fn send_structured_with_repair(
+    provider: &dyn Provider,
+    system: Option<Vec<SystemBlock>>,
+    message: &str,
+    format: &ResponseFormat,
+) -> Result<StructuredReply, ProviderError> {
+    let first = provider
+        .send_structured(system.clone(), vec![], Some(message), format)
+        .await?;
+    let first_text = extract_all_text(&first);
+    let error = match parse_reply(&first_text, format) {
+        Ok(value) => {
+            return Ok(StructuredReply {
+                responses: vec![first],
+                value: Ok(value),
+            });
+        }
+        Err(error) => error,
+    };
+
+    warn!(
+        "{} reply failed `{}` schema ({}), asking for a repair",
+        provider.name(),
+        format.name,
+        error
+    );
+    let history = vec![
+        text_message(ChatRole::User, message.to_string()),
+        text_message(ChatRole::Assistant, first_text),
+    ];
+    let repair = repair_prompt(&error, format);
+    match provider
+        .send_structured(system, history, Some(&repair), format)
+        .await
+    {
+        Ok(second) => {
+            let value = parse_reply(&extract_all_text(&second), format);
+            Ok(StructuredReply {
+                responses: vec![first, second],
+                value,
+            })
+        }
+        Err(e) => {
+            warn!("{} structured output repair failed: {}", provider.name(), e);
+            Ok(StructuredReply {
+                responses: vec![first],
+                value: Err(error),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::providers::provider::ProviderContentBlock;
+    use crate::tools::Tool;
+
+    fn title_format() -> ResponseFormat {
+        ResponseFormat {
+            name: "session_title".to_string(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "title": {"type": "string", "minLength": 1},
+                    "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2},
+                    "mood": {"enum": ["calm", "busy"]},
+                    "score": {"type": ["integer", "null"], "minimum": 0}
+                },
+                "required": ["title"],
+                "additionalProperties": false
+            }),
+        }
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json("{\"a\": 1}").unwrap(), json!({"a": 1}));
+        assert_eq!(
+            extract_json("Sure!\n```json\n{\"a\": [1, 2]}\n```\nDone.").unwrap(),
+            json!({"a": [1, 2]})
+        );
+        assert_eq!(extract_json("[1, 2]").unwrap(), json!([1, 2]));
+        assert!(extract_json("no json here").is_err());
+        assert!(extract_json("{\"a\": ").is_err());
+    }
+
+    #[test]
+    fn test_validate_schema_subset() {
+        let schema = title_format().schema;
+        let ok = json!({"title": "Garden", "tags": ["a"], "mood": "calm", "score": 3});
+        assert!(validate(&ok, &schema).is_ok());
+        assert!(validate(&json!({"title": "Garden", "score": null}), &schema).is_ok());
+
+        let cases = [
+            (json!([]), "$: expected object"),
+            (json!({}), "$: missing required property `title`"),
+            (json!({"title": ""}), "$.title: shorter than 1 characters"),
+            (
+                json!({"title": "x", "tags": [1]}),
+                "$.tags[0]: expected string",
+            ),
+            (
+                json!({"title": "x", "tags": ["a", "b", "c"]}),
+                "$.tags: more than 2 items",
+            ),
+            (
+                json!({"title": "x", "mood": "angry"}),
+                "$.mood: must be one of [\"calm\",\"busy\"]",
+            ),
+            (
+                json!({"title": "x", "score": 1.5}),
+                "$.score: expected one of integer, null",
+            ),
+            (json!({"title": "x", "score": -1}), "$.score: less than 0"),
+            (
+                json!({"title": "x", "extra": true}),
+                "$: unexpected property `extra`",
+            ),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(validate(&value, &schema).unwrap_err(), expected);
+        }
+    }
+
+    #[test]
+    fn test_openai_response_format() {
+        let body = openai_response_format(&title_format());
+        assert_eq!(body["type"], "json_schema");
+        assert_eq!(body["json_schema"]["name"], "session_title");
+        assert_eq!(body["json_schema"]["schema"]["required"], json!(["title"]));
+    }
+
+    /// `(history length, new message)` of each request.
+    type SeenRequests = Vec<(usize, Option<String>)>;
+
+    /// Replies with canned texts and records the messages it was sent.
+    #[derive(Clone, Default)]
+    struct ScriptedProvider {
+        replies: std::sync::Arc<Mutex<Vec<&'static str>>>,
+        seen: std::sync::Arc<Mutex<SeenRequests>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn model(&self) -> &str {
+            "scripted-model"
+        }
+
+        async fn send_conversation(
+            &self,
+            _system: Option<Vec<SystemBlock>>,
+            history: Vec<ChatMessage>,
+            _tools: Vec<&dyn Tool>,
+            new_message: Option<&str>,
+            _message_limit: Option<usize>,
+            _tool_choice: Option<String>,
+        ) -> Result<ProviderResponse, ProviderError> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push((history.len(), new_message.map(str::to_string)));
+            let text = self.replies.lock().unwrap().remove(0);
+            Ok(ProviderResponse {
+                id: "r".to_string(),
+                model: "scripted-model".to_string(),
+                content: vec![ProviderContentBlock::Text {
+                    text: text.to_string(),
+                }],
+                usage: None,
+                stop_reason: None,
+                raw_json: None,
+            })
+        }
+
+        fn clone_box(&self) -> Box<dyn Provider> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_structured_with_repair() {
+        let provider = ScriptedProvider::default();
+        *provider.replies.lock().unwrap() = vec!["{\"title\": \"Garden\"}"];
+        let reply = send_structured_with_repair(&provider, None, "chat", &title_format())
+            .await
+            .unwrap();
+        assert_eq!(reply.responses.len(), 1);
+        assert_eq!(reply.value.unwrap(), json!({"title": "Garden"}));
+
+        let provider = ScriptedProvider::default();
+        *provider.replies.lock().unwrap() =
+            vec!["{\"name\": \"Garden\"}", "{\"title\": \"Garden plan\"}"];
+        let reply = send_structured_with_repair(&provider, None, "chat", &title_format())
+            .await
+            .unwrap();
+        assert_eq!(reply.responses.len(), 2);
+        assert_eq!(reply.value.unwrap(), json!({"title": "Garden plan"}));
+        let seen = provider.seen.lock().unwrap().clone();
+        assert_eq!(seen[0], (0, Some("chat".to_string())));
+        assert_eq!(seen[1].0, 2);
+        assert!(
+            seen[1]
+                .1
+                .as_deref()
+                .unwrap()
+                .contains("missing required property `title`")
+        );
+
+        let provider = ScriptedProvider::default();
+        *provider.replies.lock().unwrap() = vec!["nope", "still nope"];
+        let reply = send_structured_with_repair(&provider, None, "chat", &title_format())
+            .await
+            .unwrap();
+        assert_eq!(reply.responses.len(), 2);
+        assert_eq!(reply.value.unwrap_err(), "reply contains no JSON");
+    }
+}
@@ -1,16 +1,19 @@
 //! Reflection job: curate conversation insights into structured knowledge.
 //!
-//! After heartbeat completes for a session, the reflection runner checks if
-//! there are new messages since the last reflection. If so, it builds a
-//! filtered transcript and sends it through `chat_job()` with a dedicated
-//! reflection tool manager and a `JobHandle` for real-time TODO persistence.
+//! The heartbeat loop queues a reflection (`crate::job_queue`) for idle
+//! sessions with new messages since the last reflection. The queued run
+//! builds a filtered transcript and sends it through `chat_job()` with a
+//! dedicated reflection tool manager and a `JobHandle` for real-time TODO
+//! persistence.
 
 use std::path::Path;
-use std::sync::Arc;
 
 use chrono::Utc;
+use sqlx::SqlitePool;
 use tracing::{info, warn};
 
+use crate::job_queue::{JobFailure, QueuedWork};
+use crate::reflection_quality::{WriteJournal, assess, retry_section};
 use crate::scheduler::JobKind;
 use crate::state::{AppState, LogEntry};
 use crate::tools::{JobHandle, ToolManager};
@@ -22,66 +25,110 @@ use t_koma_db::{
 /// Default reflection idle minutes (overridden by config).
 const DEFAULT_REFLECTION_IDLE_MINUTES: i64 = 4;
 
-/// Check whether reflection should run for a ghost and, if so, execute it.
+/// Queue reflection for a session that is idle and has new messages.
 ///
-/// Called from the heartbeat loop after a heartbeat tick completes for a session.
-/// Conditions:
-/// 1. New messages exist since last reflection
-/// 2. Session has been idle for at least N minutes
-#[allow(clippy::too_many_arguments)]
-pub async fn maybe_run_reflection(
-    state: &Arc<AppState>,
-    ghost_name: &str,
+/// Called from the heartbeat loop for each session it looks at, and queued
+/// again by a heartbeat run once it completes.
+pub async fn maybe_queue_reflection(
+    state: &AppState,
     ghost_id: &str,
     session_id: &str,
     session_updated_at: i64,
-    operator_id: &str,
-    reflection_model_aliases_json: Option<&str>,
-    idle_minutes: Option<i64>,
 ) {
-    run_reflection(
+    if state.is_shutting_down() {
+        return;
+    }
+    let idle_secs = DEFAULT_REFLECTION_IDLE_MINUTES * 60;
+    if Utc::now().timestamp() - session_updated_at < idle_secs {
+        return;
+    }
+    match has_new_messages(state.koma_db.pool(), ghost_id, session_id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(err) => {
+            warn!("reflection: failed to check {session_id} for new messages: {err}");
+            return;
+        }
+    }
+    crate::job_queue::submit(
         state,
-        ghost_name,
-        ghost_id,
-        session_id,
-        session_updated_at,
-        operator_id,
-        reflection_model_aliases_json,
-        true,
-        idle_minutes.unwrap_or(DEFAULT_REFLECTION_IDLE_MINUTES),
+        QueuedWork::Reflection {
+            ghost_id: ghost_id.to_string(),
+            session_id: session_id.to_string(),
+            enforce_idle_gate: true,
+        },
     )
     .await;
 }
 
-/// Run reflection immediately for a specific session.
+/// Queue reflection for a specific session without waiting for it to idle.
 ///
 /// Used by explicit operator actions (for example creating a new session), where
 /// reflection should start right away for the previous session.
-pub async fn run_reflection_now(
-    state: &Arc<AppState>,
-    ghost_name: &str,
+pub async fn queue_reflection_now(state: &AppState, ghost_id: &str, session_id: &str) {
+    crate::job_queue::submit(
+        state,
+        QueuedWork::Reflection {
+            ghost_id: ghost_id.to_string(),
+            session_id: session_id.to_string(),
+            enforce_idle_gate: false,
+        },
+    )
+    .await;
+}
+
+/// Run a queued reflection for a session.
+pub async fn run_queued_reflection(
+    state: &AppState,
     ghost_id: &str,
     session_id: &str,
-    operator_id: &str,
-    reflection_model_aliases_json: Option<&str>,
-) {
+    enforce_idle_gate: bool,
+) -> Result<(), JobFailure> {
+    let pool = state.koma_db.pool();
+    let ghost = match GhostRepository::get_by_id(pool, ghost_id).await {
+        Ok(Some(ghost)) => ghost,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("ghost lookup failed: {err}"))),
+    };
+    let session = match SessionRepository::get_by_id(pool, session_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("session lookup failed: {err}"))),
+    };
     run_reflection(
         state,
-        ghost_name,
-        ghost_id,
-        session_id,
-        Utc::now().timestamp(),
-        operator_id,
-        reflection_model_aliases_json,
-        false,
-        0, // idle_minutes irrelevant when not enforced
+        &ghost.name,
+        &ghost.id,
+        &session.id,
+        session.updated_at,
+        &session.operator_id,
+        ghost.reflection_model_aliases.as_deref(),
+        enforce_idle_gate,
+        DEFAULT_REFLECTION_IDLE_MINUTES,
+    )
+    .await
+}
+
+/// Whether the session has messages newer than its last successful reflection.
+async fn has_new_messages(
+    pool: &SqlitePool,
+    ghost_id: &str,
+    session_id: &str,
+) -> t_koma_db::DbResult<bool> {
+    let since = JobLogRepository::latest_ok(pool, ghost_id, session_id, DbJobKind::Reflection)
+        .await?
+        .map(|log| log.finished_at.unwrap_or(log.started_at))
+        .unwrap_or(0);
+    Ok(
+        !SessionRepository::get_messages_since(pool, session_id, since)
+            .await?
+            .is_empty(),
     )
-    .await;
 }
 
 #[allow(clippy::too_many_arguments)]
 async fn run_reflection(
-    state: &Arc<AppState>,
+    state: &AppState,
     ghost_name: &str,
     ghost_id: &str,
     session_id: &str,
@@ -90,15 +137,34 @@ async fn run_reflection(
     reflection_model_aliases_json: Option<&str>,
     enforce_idle_gate: bool,
     idle_minutes: i64,
-) {
+) -> Result<(), JobFailure> {
+    if state.is_shutting_down() {
+        return Ok(());
+    }
+
     let now_ts = Utc::now().timestamp();
     let idle_secs = idle_minutes * 60;
 
     // Avoid reflection right after active chat messages.
     if enforce_idle_gate && now_ts - session_updated_at < idle_secs {
-        return;
+        return Ok(());
+    }
+    // A chat or heartbeat is running in the session; the heartbeat loop
+    // queues reflection again once it is idle.
+    let chat_key = format!("{operator_id}:{ghost_name}:{session_id}");
+    if state.is_chat_in_flight(&chat_key).await {
+        return Ok(());
     }
 
+    // Claimed before looking at the last run so two gateways never reflect
+    // on the same messages.
+    let Some(_lease) = state
+        .acquire_job_lease(&format!("reflection:{session_id}"))
+        .await
+    else {
+        return Ok(());
+    };
+
     let pool = state.koma_db.pool();
 
     // Find the last successful reflection for handoff note + timestamp.
@@ -111,7 +177,7 @@ async fn run_reflection(
     .await
     {
         Ok(log) => log,
-        Err(_) => return,
+        Err(err) => return Err(JobFailure::Retry(format!("job log lookup failed: {err}"))),
     };
 
     let last_reflection_ts = last_reflection
@@ -128,9 +194,9 @@ async fn run_reflection(
     // Check if new messages exist since last reflection.
     let recent_messages =
         match SessionRepository::get_messages_since(pool, session_id, last_reflection_ts).await {
-            Ok(msgs) if msgs.is_empty() => return,
+            Ok(msgs) if msgs.is_empty() => return Ok(()),
             Ok(msgs) => msgs,
-            Err(_) => return,
+            Err(err) => return Err(JobFailure::Retry(format!("message lookup failed: {err}"))),
         };
 
     info!(
@@ -145,12 +211,19 @@ async fn run_reflection(
 
     if let Err(err) = JobLogRepository::insert_started(pool, &job_log).await {
         warn!("reflection: failed to insert started job log: {err}");
-        return;
+        return Err(JobFailure::Retry(format!(
+            "failed to insert job log: {err}"
+        )));
     }
 
-    // Build reflection tool manager and job handle
     let reflection_tm = ToolManager::new_reflection(state.session_chat.skill_paths().to_vec());
-    let job_handle = JobHandle::new(pool.clone(), job_log_id.clone());
+    let carried_todos = match JobLogRepository::carried_todos(pool, ghost_id, session_id).await {
+        Ok(todos) => todos,
+        Err(err) => {
+            warn!("reflection: failed to load carried TODOs: {err}");
+            Vec::new()
+        }
+    };
 
     // Build the filtered transcript prompt
     let prompt = build_reflection_prompt(&recent_messages, &previous_handoff, ghost_name).await;
@@ -160,37 +233,74 @@ async fn run_reflection(
             state.resolve_model_for_ghost_with_override_json(&ghost, reflection_model_aliases_json)
         }
         _ => state.default_model(),
-    };
+    }
+    .with_generation_overrides(&state.job_generation().reflection);
     let model_info = format!(
         "# Model\n- {} ({}/{})\n",
         model.alias, model.provider, model.model
     );
 
-    let chat_key = format!("{operator_id}:{ghost_name}:{session_id}");
-    state.set_chat_in_flight(&chat_key).await;
-
-    let result = state
-        .session_chat
-        .chat_job(
-            &state.koma_db,
-            ghost_id,
-            model.client.as_ref(),
-            &model.provider,
-            &model.model,
-            model.context_window,
-            session_id,
-            operator_id,
-            &prompt,
-            false, // recent messages are embedded in the prompt
-            Some(&reflection_tm),
-            Some(job_handle),
-            Some(crate::session::REFLECTION_TOOL_LOOP_LIMIT),
-            model.retry_on_empty,
-            &model_info,
-        )
-        .await;
+    // With the quality gate on, a rejected run is undone and retried once;
+    // `degraded` holds the reason when the retry is rejected as well.
+    let quality = state.heartbeat_settings().reflection_quality;
+    let max_attempts = if quality.enabled { 2 } else { 1 };
+    let mut rejection: Option<String> = None;
+    let mut attempt = 0;
+    let (result, degraded) = loop {
+        attempt += 1;
+        let journal = quality.enabled.then(WriteJournal::new);
+        let mut job_handle = JobHandle::new(pool.clone(), job_log_id.clone());
+        if let Some(journal) = &journal {
+            job_handle = job_handle.with_journal(journal.clone());
+        }
+        job_handle.seed_todos(carried_todos.clone()).await;
+        let attempt_prompt = match &rejection {
+            Some(reason) => format!("{prompt}\n\n{}", retry_section(reason).trim()),
+            None => prompt.clone(),
+        };
+
+        state.set_chat_in_flight(&chat_key).await;
+        let cancel = state.register_job(&job_log_id).await;
+
+        let result = tokio::select! {
+            result = state.session_chat.chat_job(
+                &state.koma_db,
+                ghost_id,
+                model.client.as_ref(),
+                &model.provider,
+                &model.model,
+                model.context_window,
+                session_id,
+                operator_id,
+                &attempt_prompt,
+                false, // recent messages are embedded in the prompt
+                Some(&reflection_tm),
+                Some(job_handle),
+                Some(crate::session::REFLECTION_TOOL_LOOP_LIMIT),
+                model.retry_on_empty,
+                &model_info,
+            ) => result,
+            _ = cancel.cancelled() => Err(crate::session::ChatError::Cancelled),
+        };
+
+        state.clear_job(&job_log_id).await;
+        state.clear_chat_in_flight(&chat_key).await;
 
-    state.clear_chat_in_flight(&chat_key).await;
+        let (Ok(_), Some(journal)) = (&result, &journal) else {
+            break (result, None);
+        };
+        let Err(reason) = assess(state, &quality, &journal.outputs()).await else {
+            break (result, None);
+        };
+        for failure in journal.rollback(state.knowledge_engine(), ghost_name).await {
+            warn!("reflection: failed to undo a rejected write for {ghost_name}: {failure}");
+        }
+        if attempt >= max_attempts {
+            break (result, Some(reason));
+        }
+        info!("reflection: output for {ghost_name}:{session_id} rejected, retrying: {reason}");
+        rejection = Some(reason);
+    };
 
     // Update scheduler — no cooldown; reflection won't re-trigger until new messages appear
     let scheduler_key = format!("reflection:{ghost_name}");
@@ -200,9 +310,20 @@ async fn run_reflection(
 
     match result {
         Ok(job_result) => {
-            let status = format!("processed {} messages", recent_messages.len());
-            // Extract handoff note from the final response text
-            let handoff_note = Some(job_result.response_text.as_str());
+            let (status, handoff_note) = match &degraded {
+                // The writes were undone; keep the previous handoff note.
+                Some(reason) => (
+                    format!("degraded: {reason}"),
+                    last_reflection
+                        .as_ref()
+                        .and_then(|log| log.handoff_note.as_deref()),
+                ),
+                None => (
+                    format!("processed {} messages", recent_messages.len()),
+                    // Extract handoff note from the final response text
+                    Some(job_result.response_text.as_str()),
+                ),
+            };
 
             if let Err(err) = JobLogRepository::finish(
                 pool,
@@ -215,9 +336,17 @@ async fn run_reflection(
             {
                 warn!("reflection: failed to finish job log for {ghost_name}:{session_id}: {err}");
             }
+            state
+                .log(LogEntry::JobFinished {
+                    job_id: job_log_id.clone(),
+                    status: status.clone(),
+                })
+                .await;
 
             // Clear web cache after successful reflection
-            if let Ok(workspace) = t_koma_db::ghosts::ghost_workspace_path(ghost_name) {
+            if degraded.is_none()
+                && let Ok(workspace) = t_koma_db::ghosts::ghost_workspace_path(ghost_name)
+            {
                 let cache_dir = workspace.join(".web-cache");
                 if cache_dir.exists() {
                     let _ = tokio::fs::remove_dir_all(&cache_dir).await;
@@ -231,6 +360,29 @@ async fn run_reflection(
                     status,
                 })
                 .await;
+            Ok(())
+        }
+        Err(crate::session::ChatError::Cancelled) => {
+            let transcript = crate::heartbeat::persisted_transcript(state, &job_log_id).await;
+            if let Err(e) =
+                JobLogRepository::finish(pool, &job_log_id, "cancelled", &transcript, None).await
+            {
+                warn!("reflection: failed to finish cancelled job log: {e}");
+            }
+            state
+                .log(LogEntry::JobFinished {
+                    job_id: job_log_id.clone(),
+                    status: "cancelled".to_string(),
+                })
+                .await;
+            state
+                .log(LogEntry::Reflection {
+                    ghost_name: ghost_name.to_string(),
+                    session_id: session_id.to_string(),
+                    status: "cancelled".to_string(),
+                })
+                .await;
+            Ok(())
         }
         Err(err) => {
             warn!("reflection failed for ghost '{ghost_name}' session '{session_id}': {err:#}");
@@ -251,6 +403,12 @@ async fn run_reflection(
             {
                 warn!("reflection: failed to finish error job log: {e}");
             }
+            state
+                .log(LogEntry::JobFinished {
+                    job_id: job_log_id.clone(),
+                    status: status.clone(),
+                })
+                .await;
 
             state
                 .log(LogEntry::Reflection {
@@ -259,6 +417,7 @@ async fn run_reflection(
                     status,
                 })
                 .await;
+            Err(JobFailure::from_chat_error(&err))
         }
     }
 }
@@ -327,6 +486,7 @@ fn format_chat_transcript(messages: &[t_koma_db::Message]) -> String {
                         role, ts, filename, size
                     ));
                 }
+                ContentBlock::Thinking { .. } => {}
             }
         }
     }
@@ -0,0 +1,484 @@
+//! Quality gate for reflection output (`[reflection.quality]`).
+//!
+//! While a gated reflection runs, `note_write` and `diary_write` record what
+//! they change in the job's [`WriteJournal`]. Afterwards the notes and diary
+//! entries it wrote are checked against the structure the reflection prompt
+//! asks for and scored by a (cheap) model. A rejected run is undone from the
+//! journal; `crate::reflection` retries it once, then logs it as degraded.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use t_koma_core::ReflectionQualitySettings;
+use t_koma_knowledge::KnowledgeEngine;
+use tracing::warn;
+
+use crate::content::{self, ids};
+use crate::prompt::render::build_simple_system_prompt;
+use crate::providers::provider::{Provider, ResponseFormat};
+use crate::providers::structured::send_structured_with_repair;
+use crate::state::AppState;
+
+/// Shortest accepted note body, in characters.
+const MIN_NOTE_BODY_CHARS: usize = 40;
+/// Longest accepted note body, in words (the prompt's hard limit).
+const MAX_NOTE_WORDS: usize = 1000;
+
+/// A note as written by the run; fields are `None` when never set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteOutput {
+    pub note_id: String,
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// A diary entry as it reads after the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiaryOutput {
+    pub date: String,
+    pub content: String,
+}
+
+/// What a reflection run produced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReflectionOutputs {
+    pub notes: Vec<NoteOutput>,
+    pub diary: Vec<DiaryOutput>,
+}
+
+impl ReflectionOutputs {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.diary.is_empty()
+    }
+}
+
+#[derive(Debug)]
+enum Undo {
+    DeleteNote {
+        note_id: String,
+    },
+    RestoreNote {
+        note_id: String,
+        raw: String,
+    },
+    RestoreDiary {
+        path: PathBuf,
+        previous: Option<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Journal {
+    undo: Vec<Undo>,
+    outputs: ReflectionOutputs,
+}
+
+/// Knowledge writes of one run, with what it takes to undo them. Shared
+/// between the job runner and the tools through the `JobHandle`.
+#[derive(Debug, Clone, Default)]
+pub struct WriteJournal(Arc<Mutex<Journal>>);
+
+impl WriteJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Journal> {
+        self.0.lock().expect("write journal lock poisoned")
+    }
+
+    /// Whether the run already created or updated the note.
+    pub fn touches_note(&self, note_id: &str) -> bool {
+        self.lock()
+            .outputs
+            .notes
+            .iter()
+            .any(|note| note.note_id == note_id)
+    }
+
+    pub fn record_note_created(&self, output: NoteOutput) {
+        let mut journal = self.lock();
+        journal.undo.push(Undo::DeleteNote {
+            note_id: output.note_id.clone(),
+        });
+        journal.outputs.notes.push(output);
+    }
+
+    /// `previous` is the note file before the update; it is only needed the
+    /// first time the run touches the note.
+    pub fn record_note_updated(&self, previous: Option<String>, output: NoteOutput) {
+        let mut journal = self.lock();
+        if let Some(note) = journal
+            .outputs
+            .notes
+            .iter_mut()
+            .find(|note| note.note_id == output.note_id)
+        {
+            note.title = output.title.or(note.title.take());
+            note.body = output.body.or(note.body.take());
+            note.tags = output.tags.or(note.tags.take());
+            return;
+        }
+        if let Some(raw) = previous {
+            journal.undo.push(Undo::RestoreNote {
+                note_id: output.note_id.clone(),
+                raw,
+            });
+        }
+        journal.outputs.notes.push(output);
+    }
+
+    /// `previous` is the entry before this write (`None` when it was new).
+    pub fn record_diary(&self, path: PathBuf, previous: Option<String>, output: DiaryOutput) {
+        let mut journal = self.lock();
+        let touched = journal
+            .undo
+            .iter()
+            .any(|undo| matches!(undo, Undo::RestoreDiary { path: p, .. } if *p == path));
+        if !touched {
+            journal.undo.push(Undo::RestoreDiary { path, previous });
+        }
+        match journal
+            .outputs
+            .diary
+            .iter_mut()
+            .find(|entry| entry.date == output.date)
+        {
+            Some(entry) => entry.content = output.content,
+            None => journal.outputs.diary.push(output),
+        }
+    }
+
+    pub fn outputs(&self) -> ReflectionOutputs {
+        self.lock().outputs.clone()
+    }
+
+    /// Undo every recorded write, newest first. Returns what couldn't be undone.
+    pub async fn rollback(&self, engine: &KnowledgeEngine, ghost_name: &str) -> Vec<String> {
+        let undo = {
+            let mut journal = self.lock();
+            journal.outputs = ReflectionOutputs::default();
+            std::mem::take(&mut journal.undo)
+        };
+        let mut failures = Vec::new();
+        for step in undo.into_iter().rev() {
+            let result = match &step {
+                Undo::DeleteNote { note_id } => engine
+                    .note_delete(ghost_name, note_id)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Undo::RestoreNote { note_id, raw } => engine
+                    .note_restore(ghost_name, note_id, raw)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                Undo::RestoreDiary { path, previous } => restore_diary(path, previous.as_deref())
+                    .await
+                    .map_err(|e| e.to_string()),
+            };
+            if let Err(err) = result {
+                failures.push(format!("{step:?}: {err}"));
+            }
+        }
+        failures
+    }
+}
+
+/// Put a diary entry back as it was, removing it if the run created it.
+async fn restore_diary(path: &Path, previous: Option<&str>) -> std::io::Result<()> {
+    match previous {
+        Some(content) => tokio::fs::write(path, content).await,
+        None => tokio::fs::remove_file(path).await,
+    }
+}
+
+/// Ways the output departs from the structure the reflection prompt asks for.
+pub fn structure_problems(outputs: &ReflectionOutputs) -> Vec<String> {
+    let mut problems = Vec::new();
+    for note in &outputs.notes {
+        let id = &note.note_id;
+        if note.title.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            problems.push(format!("note {id} has an empty title"));
+        }
+        if let Some(body) = &note.body {
+            if body.trim().chars().count() < MIN_NOTE_BODY_CHARS {
+                problems.push(format!("note {id} has almost no body"));
+            }
+            if body.split_whitespace().count() > MAX_NOTE_WORDS {
+                problems.push(format!("note {id} is over {MAX_NOTE_WORDS} words"));
+            }
+        }
+        for tag in note.tags.iter().flatten() {
+            let valid = !tag.is_empty()
+                && !tag.chars().any(|c| c.is_whitespace() || c.is_uppercase())
+                && !tag.starts_with('/')
+                && !tag.ends_with('/');
+            if !valid {
+                problems.push(format!(
+                    "note {id} has tag '{tag}' (tags are lowercase and slash-separated)"
+                ));
+            }
+        }
+    }
+    for entry in &outputs.diary {
+        let date = &entry.date;
+        if entry.content.trim().is_empty() {
+            problems.push(format!("diary entry {date} is empty"));
+        } else if !entry.content.lines().any(|line| {
+            let line = line.trim_start();
+            line.starts_with("- ") || line.starts_with("* ")
+        }) {
+            problems.push(format!("diary entry {date} has no bullet points"));
+        }
+    }
+    problems
+}
+
+/// The output as shown to the scoring model.
+fn format_outputs(outputs: &ReflectionOutputs) -> String {
+    let mut out = String::new();
+    for note in &outputs.notes {
+        out.push_str(&format!(
+            "## Note: {}\n",
+            note.title.as_deref().unwrap_or(&note.note_id)
+        ));
+        if let Some(tags) = &note.tags {
+            out.push_str(&format!("Tags: {}\n", tags.join(", ")));
+        }
+        match &note.body {
+            Some(body) => out.push_str(&format!("\n{}\n\n", body.trim())),
+            None => out.push_str("\n(metadata update only)\n\n"),
+        }
+    }
+    for entry in &outputs.diary {
+        out.push_str(&format!(
+            "## Diary: {}\n\n{}\n\n",
+            entry.date,
+            entry.content.trim()
+        ));
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct QualityScore {
+    score: u8,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Schema the quality score must match.
+fn score_format() -> ResponseFormat {
+    ResponseFormat {
+        name: "reflection_quality".to_string(),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "score": {"type": "integer", "minimum": 0, "maximum": 10},
+                "reason": {"type": "string"}
+            },
+            "required": ["score"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+fn load_score_prompt() -> String {
+    content::prompt_text(ids::PROMPT_REFLECTION_QUALITY, None, &[]).unwrap_or_else(|e| {
+        warn!("Failed to load reflection quality prompt: {e}, using fallback");
+        "Score the knowledge notes and diary entries from 0 to 10 for accuracy, \
+         density and usefulness. Reply with a JSON object {\"score\": int, \"reason\": \"...\"}."
+            .to_string()
+    })
+}
+
+async fn score_outputs(
+    client: &dyn Provider,
+    outputs: &ReflectionOutputs,
+) -> Result<QualityScore, String> {
+    let system_blocks = build_simple_system_prompt(load_score_prompt());
+    let reply = send_structured_with_repair(
+        client,
+        Some(system_blocks),
+        &format_outputs(outputs),
+        &score_format(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    reply
+        .value
+        .and_then(|value| serde_json::from_value::<QualityScore>(value).map_err(|e| e.to_string()))
+}
+
+/// Accept or reject the output of a run, with the reason for a rejection.
+///
+/// Runs that wrote nothing pass. Scoring fails open: an unknown alias, a
+/// provider error or an unusable score accepts the output with a warning.
+pub async fn assess(
+    state: &AppState,
+    settings: &ReflectionQualitySettings,
+    outputs: &ReflectionOutputs,
+) -> Result<(), String> {
+    if outputs.is_empty() {
+        return Ok(());
+    }
+    let problems = structure_problems(outputs);
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+
+    let Some(alias) = &settings.model else {
+        return Ok(());
+    };
+    let Some(model) = state.get_model_by_alias(alias) else {
+        warn!("reflection quality: model '{alias}' not found, accepting output");
+        return Ok(());
+    };
+    match score_outputs(model.client.as_ref(), outputs).await {
+        Ok(score) if score.score >= settings.min_score => Ok(()),
+        Ok(score) => {
+            let reason = match score.reason.trim() {
+                "" => "no reason given",
+                reason => reason,
+            };
+            Err(format!(
+                "scored {}/10 (minimum {}): {reason}",
+                score.score, settings.min_score
+            ))
+        }
+        Err(err) => {
+            warn!("reflection quality: scoring with '{alias}' failed, accepting output: {err}");
+            Ok(())
+        }
+    }
+}
+
+/// Prompt section telling the retried run why the first one was undone.
+pub fn retry_section(reason: &str) -> String {
+    content::prompt_text(ids::PROMPT_REFLECTION_RETRY, None, &[("reason", reason)])
+        .unwrap_or_else(|_| format!("## Previous Attempt Rejected\n\n{reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str, body: &str, tags: &[&str]) -> NoteOutput {
+        NoteOutput {
+            note_id: id.to_string(),
+            title: Some(format!("Title {id}")),
+            body: Some(body.to_string()),
+            tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_structure_problems() {
+        let body = "Tokio tasks are cheap green threads scheduled on a work-stealing runtime.";
+        let good = ReflectionOutputs {
+            notes: vec![note("n1", body, &["rust/async"])],
+            diary: vec![DiaryOutput {
+                date: "2026-03-10".to_string(),
+                content: "- Discussed the async runtime".to_string(),
+            }],
+        };
+        assert!(structure_problems(&good).is_empty());
+
+        let bad = ReflectionOutputs {
+            notes: vec![
+                note("n1", "Too short.", &["rust/async"]),
+                note("n2", body, &["Rust Async", "misc/"]),
+            ],
+            diary: vec![DiaryOutput {
+                date: "2026-03-10".to_string(),
+                content: "A paragraph without bullets.".to_string(),
+            }],
+        };
+        let problems = structure_problems(&bad);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[0].contains("n1"));
+        assert!(problems[3].contains("no bullet points"));
+    }
+
+    #[test]
+    fn test_journal_merges_outputs() {
+        let journal = WriteJournal::new();
+        journal.record_note_created(note("n1", "first", &["a"]));
+        journal.record_note_updated(
+            None,
+            NoteOutput {
+                note_id: "n1".to_string(),
+                body: Some("second".to_string()),
+                ..Default::default()
+            },
+        );
+        journal.record_note_updated(
+            Some("+++\nold\n+++".to_string()),
+            NoteOutput {
+                note_id: "n2".to_string(),
+                tags: Some(vec!["b".to_string()]),
+                ..Default::default()
+            },
+        );
+        assert!(journal.touches_note("n1"));
+        assert!(!journal.touches_note("n3"));
+
+        let outputs = journal.outputs();
+        assert_eq!(outputs.notes.len(), 2);
+        assert_eq!(outputs.notes[0].title.as_deref(), Some("Title n1"));
+        assert_eq!(outputs.notes[0].body.as_deref(), Some("second"));
+        // Created, then restored: no second undo step for n1.
+        assert_eq!(journal.lock().undo.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_diary_undo_keeps_first_version() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let existing = temp_dir.path().join("2026-03-09.md");
+        let created = temp_dir.path().join("2026-03-10.md");
+
+        let journal = WriteJournal::new();
+        for (path, previous, content) in [
+            (
+                &existing,
+                Some("- morning"),
+                "- morning\n\n---\n\n- evening",
+            ),
+            (
+                &existing,
+                Some("- morning\n\n---\n\n- evening"),
+                "- rewritten",
+            ),
+            (&created, None, "- new day"),
+        ] {
+            tokio::fs::write(path, content).await.unwrap();
+            journal.record_diary(
+                path.clone(),
+                previous.map(str::to_string),
+                DiaryOutput {
+                    date: path.file_stem().unwrap().to_string_lossy().to_string(),
+                    content: content.to_string(),
+                },
+            );
+        }
+        let outputs = journal.outputs();
+        assert_eq!(outputs.diary.len(), 2);
+        assert_eq!(outputs.diary[0].content, "- rewritten");
+
+        let undo = std::mem::take(&mut journal.lock().undo);
+        assert_eq!(undo.len(), 2);
+        for step in undo.iter().rev() {
+            let Undo::RestoreDiary { path, previous } = step else {
+                panic!("unexpected undo step {step:?}");
+            };
+            restore_diary(path, previous.as_deref()).await.unwrap();
+        }
+        assert_eq!(
+            tokio::fs::read_to_string(&existing).await.unwrap(),
+            "- morning"
+        );
+        assert!(!created.exists());
+    }
+}
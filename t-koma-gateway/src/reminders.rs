@@ -0,0 +1,163 @@
+//! Delivery of due reminders.
+//!
+//! Runs from the heartbeat runner loop. Each due reminder is appended to its
+//! session as a ghost message and pushed to the interface the session was
+//! last chatted from: a Discord DM, or the operator's live WebSocket
+//! connections. The next due time lives in the shared scheduler state.
+
+use chrono::Utc;
+use tracing::warn;
+
+use crate::content::ids;
+use crate::gateway_message;
+use crate::scheduler::JobKind;
+use crate::state::{AppState, LogEntry, OperatorNotice};
+use t_koma_db::{
+    ContentBlock, GhostRepository, MessageRole, Reminder, ReminderRepository, SessionOrigin,
+    SessionRepository,
+};
+
+const SCHEDULER_KEY: &str = "reminders";
+/// Most reminders delivered per tick; the rest wait for the next one.
+const BATCH_SIZE: i64 = 50;
+
+/// Deliver every reminder that is due.
+pub async fn deliver_due_reminders(state: &AppState) {
+    let pool = state.koma_db.pool();
+    let now = Utc::now().timestamp();
+    match ReminderRepository::list_due(pool, now, BATCH_SIZE).await {
+        Ok(due) => {
+            for reminder in due {
+                deliver_reminder(state, &reminder, now).await;
+            }
+        }
+        Err(err) => warn!("reminder lookup failed: {err}"),
+    }
+
+    match ReminderRepository::next_due(pool).await {
+        Ok(next_due) => {
+            state
+                .scheduler_set(JobKind::Reminder, SCHEDULER_KEY, next_due)
+                .await
+        }
+        Err(err) => warn!("reminder schedule lookup failed: {err}"),
+    }
+}
+
+async fn deliver_reminder(state: &AppState, reminder: &Reminder, now: i64) {
+    let pool = state.koma_db.pool();
+    // Mark first: a failed push must not repeat the reminder every tick.
+    if let Err(err) = ReminderRepository::mark_delivered(pool, &reminder.id, now).await {
+        warn!("reminder {}: mark delivered failed: {err}", reminder.id);
+        return;
+    }
+
+    if let Err(err) = SessionRepository::add_message(
+        pool,
+        &reminder.ghost_id,
+        &reminder.session_id,
+        MessageRole::Ghost,
+        vec![ContentBlock::Text {
+            text: format!("Reminder: {}", reminder.message),
+        }],
+        None,
+    )
+    .await
+    {
+        warn!("reminder {}: session append failed: {err}", reminder.id);
+    }
+
+    let ghost_name = match GhostRepository::get_by_id(pool, &reminder.ghost_id).await {
+        Ok(Some(ghost)) => ghost.name,
+        _ => reminder.ghost_id.clone(),
+    };
+    let origin = SessionRepository::get_origin(pool, &reminder.session_id)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(SessionOrigin::Ws);
+    let interface = match origin {
+        SessionOrigin::Discord => Some("discord"),
+        SessionOrigin::Telegram => Some("telegram"),
+        SessionOrigin::Slack => Some("slack"),
+        SessionOrigin::Email => Some("email"),
+        SessionOrigin::Ws => None,
+    };
+    let message = gateway_message::from_content(
+        ids::REMINDER_DUE,
+        interface,
+        &[("ghost", &ghost_name), ("message", &reminder.message)],
+    );
+
+    let pushed = match origin {
+        SessionOrigin::Discord => match state.discord_bot_token().await {
+            Some(token) => {
+                crate::discord::send_session_gateway_message(
+                    state,
+                    &token,
+                    &reminder.operator_id,
+                    &reminder.session_id,
+                    &message,
+                )
+                .await
+            }
+            None => Err("Discord bot is not configured".to_string()),
+        },
+        SessionOrigin::Telegram => match state.telegram_bot_token().await {
+            Some(token) => {
+                crate::telegram::send_operator_gateway_message(
+                    state,
+                    &token,
+                    &reminder.operator_id,
+                    &message,
+                )
+                .await
+            }
+            None => Err("Telegram bot is not configured".to_string()),
+        },
+        SessionOrigin::Slack => match state.slack_bot_token().await {
+            Some(token) => {
+                crate::slack::send_operator_gateway_message(
+                    state,
+                    &token,
+                    &reminder.operator_id,
+                    &message,
+                )
+                .await
+            }
+            None => Err("Slack app is not configured".to_string()),
+        },
+        SessionOrigin::Email => match state.email_mailer().await {
+            Some(mailer) => {
+                crate::email::send_operator_gateway_message(
+                    state,
+                    &mailer,
+                    &reminder.operator_id,
+                    Some(&reminder.session_id),
+                    &message,
+                )
+                .await
+            }
+            None => Err("Email bridge is not configured".to_string()),
+        },
+        SessionOrigin::Ws => Ok(state.notify_operator(OperatorNotice {
+            operator_id: reminder.operator_id.clone(),
+            id: reminder.id.clone(),
+            message,
+        })),
+    };
+
+    let status = match pushed {
+        Ok(true) => format!("sent via {origin}"),
+        Ok(false) => format!("stored in session; no {origin} client to push to"),
+        Err(err) => {
+            warn!("reminder {}: {origin} push failed: {err}", reminder.id);
+            format!("stored in session; {origin} push failed")
+        }
+    };
+    state
+        .log(LogEntry::Info {
+            message: format!("Reminder {} for {}: {}", reminder.id, ghost_name, status),
+        })
+        .await;
+}
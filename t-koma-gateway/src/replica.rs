@@ -0,0 +1,179 @@
+//! Read-only replica follower.
+//!
+//! A replica gateway (`[gateway] read_only = true`) runs no background jobs,
+//! so nothing it does reaches `/logs`. This loop polls the replicated DB for
+//! new audit events and finished job runs written by the primary and
+//! rebroadcasts them as log entries.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::state::{AppState, LogEntry};
+use t_koma_db::{DbResult, Event, EventFilters, EventRepository, JobLogRepository};
+
+/// Seconds between DB polls.
+const POLL_SECONDS: u64 = 5;
+/// Recent job runs scanned per poll.
+const RECENT_JOBS: i64 = 100;
+
+/// What the follower has already broadcast.
+#[derive(Debug, Default)]
+struct ReplicaCursor {
+    last_event_id: i64,
+    finished_jobs: HashSet<String>,
+}
+
+impl ReplicaCursor {
+    /// Start past everything already in the DB.
+    async fn init(pool: &SqlitePool) -> DbResult<Self> {
+        let newest = EventRepository::query(
+            pool,
+            &EventFilters {
+                limit: Some(1),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let finished_jobs = JobLogRepository::list_recent(pool, RECENT_JOBS)
+            .await?
+            .into_iter()
+            .filter(|job| job.finished_at.is_some())
+            .map(|job| job.id)
+            .collect();
+        Ok(Self {
+            last_event_id: newest.last().map(|e| e.id).unwrap_or(0),
+            finished_jobs,
+        })
+    }
+
+    /// Log entries for events and job runs that appeared since the last poll.
+    async fn poll(&mut self, pool: &SqlitePool) -> DbResult<Vec<LogEntry>> {
+        let mut entries = Vec::new();
+
+        let events = EventRepository::query(
+            pool,
+            &EventFilters {
+                after_id: Some(self.last_event_id),
+                ..Default::default()
+            },
+        )
+        .await?;
+        if let Some(last) = events.last() {
+            self.last_event_id = last.id;
+        }
+        entries.extend(events.iter().map(|event| LogEntry::Info {
+            message: event_message(event),
+        }));
+
+        let recent = JobLogRepository::list_recent(pool, RECENT_JOBS).await?;
+        for job in &recent {
+            if job.finished_at.is_some() && self.finished_jobs.insert(job.id.clone()) {
+                entries.push(LogEntry::JobFinished {
+                    job_id: job.id.clone(),
+                    status: job.status.clone().unwrap_or_else(|| "finished".to_string()),
+                });
+            }
+        }
+        // Forget runs that fell out of the window so the set stays bounded.
+        self.finished_jobs
+            .retain(|id| recent.iter().any(|job| &job.id == id));
+
+        Ok(entries)
+    }
+}
+
+fn event_message(event: &Event) -> String {
+    format!(
+        "[replica] {} {} (by {})",
+        event.kind,
+        event.subject_id,
+        event.actor_id.as_deref().unwrap_or("system")
+    )
+}
+
+/// Spawn the follower loop for a read-only gateway.
+pub fn start_replica_follower(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let pool = state.koma_db.pool().clone();
+        let mut cursor = match ReplicaCursor::init(&pool).await {
+            Ok(cursor) => cursor,
+            Err(e) => {
+                warn!("replica: failed to read initial state: {e}");
+                ReplicaCursor::default()
+            }
+        };
+        let mut tick = tokio::time::interval(Duration::from_secs(POLL_SECONDS));
+        loop {
+            tick.tick().await;
+            match cursor.poll(&pool).await {
+                Ok(entries) => {
+                    for entry in entries {
+                        state.log(entry).await;
+                    }
+                }
+                Err(e) => warn!("replica: poll failed: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t_koma_db::{
+        JobKind, JobLog, OperatorAccessLevel, OperatorRepository, Platform,
+        test_helpers::create_test_pool,
+    };
+
+    #[tokio::test]
+    async fn test_replica_cursor_reports_only_new_rows() {
+        let db = create_test_pool().await.unwrap();
+        let pool = db.pool();
+
+        let existing = OperatorRepository::create_new(
+            pool,
+            "Old",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let mut cursor = ReplicaCursor::init(pool).await.unwrap();
+        assert!(cursor.poll(pool).await.unwrap().is_empty());
+
+        OperatorRepository::approve(pool, &existing.id)
+            .await
+            .unwrap();
+        let ghost = t_koma_db::GhostRepository::create(pool, &existing.id, "Ghost")
+            .await
+            .unwrap();
+        let session = t_koma_db::SessionRepository::create(pool, &ghost.id, &existing.id)
+            .await
+            .unwrap();
+        let mut log = JobLog::start(&ghost.id, JobKind::Heartbeat, &session.id);
+        log.finish("ok");
+        JobLogRepository::insert(pool, &log).await.unwrap();
+
+        let entries = cursor.poll(pool).await.unwrap();
+        let infos: Vec<&str> = entries
+            .iter()
+            .filter_map(|e| match e {
+                LogEntry::Info { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(infos.iter().any(|m| m.contains("operator.approved")));
+        assert!(infos.iter().any(|m| m.contains("ghost.created")));
+        assert!(entries.iter().any(|e| matches!(
+            e,
+            LogEntry::JobFinished { job_id, status } if job_id == &log.id && status == "ok"
+        )));
+
+        assert!(cursor.poll(pool).await.unwrap().is_empty());
+    }
+}
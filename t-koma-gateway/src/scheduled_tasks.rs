@@ -0,0 +1,266 @@
+//! Runs of the recurring tasks ghosts register with `schedule_task`.
+//!
+//! Due tasks are found from the heartbeat runner loop and queued
+//! (`crate::job_queue`). Each run is a background job with the ghost's
+//! heartbeat model, in the session the task was created in: the response is
+//! posted there and the transcript kept in `job_logs`.
+//! Tasks live in the koma DB (`t_koma_db::scheduled_tasks`); their next runs
+//! are mirrored into the shared scheduler state for display.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use crate::content::ids;
+use crate::job_queue::{JobFailure, QueuedWork};
+use crate::scheduler::JobKind;
+use crate::state::{AppState, LogEntry};
+use crate::tools::ToolManager;
+use t_koma_core::CronSchedule;
+use t_koma_db::{
+    ContentBlock, GhostRepository, JobKind as DbJobKind, JobLog, JobLogRepository, MessageRole,
+    ScheduledTask, ScheduledTaskRepository, SessionRepository,
+};
+
+/// Most tasks started per tick; the rest wait for the next one.
+const BATCH_SIZE: i64 = 10;
+
+/// Queue every task that is due, then refresh their scheduler entries.
+///
+/// A task that came due while the gateway was down runs once on the next
+/// tick, not once per missed occurrence; an occurrence that comes due while
+/// the previous run is still queued is skipped.
+pub async fn run_due_tasks(state: &Arc<AppState>) {
+    let pool = state.koma_db.pool();
+    let now = Utc::now().timestamp();
+    match ScheduledTaskRepository::list_due(pool, now, BATCH_SIZE).await {
+        Ok(due) => {
+            for task in due {
+                queue_due_task(state, &task, now).await;
+            }
+        }
+        Err(err) => warn!("scheduled task lookup failed: {err}"),
+    }
+
+    match ScheduledTaskRepository::list_all(pool).await {
+        Ok(tasks) => sync_scheduler(state, &tasks).await,
+        Err(err) => warn!("scheduled task listing failed: {err}"),
+    }
+}
+
+/// Next run of `schedule` after `after`, or `None` when the expression no
+/// longer parses or has no further occurrence.
+pub fn next_run(schedule: &str, after: i64) -> Option<i64> {
+    CronSchedule::parse(schedule, None)
+        .ok()
+        .and_then(|schedule| schedule.next_after(after))
+}
+
+async fn sync_scheduler(state: &AppState, tasks: &[ScheduledTask]) {
+    let mut live = HashSet::new();
+    for task in tasks {
+        let schedule = CronSchedule::parse(&task.schedule, None).ok();
+        match (task.enabled, task.next_due, schedule) {
+            (true, Some(next_due), Some(schedule)) => {
+                live.insert(task.id.as_str());
+                state
+                    .scheduler_set_recurring(
+                        JobKind::Task,
+                        &task.id,
+                        next_due,
+                        &schedule.to_string(),
+                    )
+                    .await;
+            }
+            _ => state.scheduler_set(JobKind::Task, &task.id, None).await,
+        }
+    }
+    for (kind, key, _) in state.scheduler_state().await {
+        if kind == JobKind::Task && !live.contains(key.as_str()) {
+            state.scheduler_set(JobKind::Task, &key, None).await;
+        }
+    }
+}
+
+/// Move a due task to its next occurrence and queue its run.
+async fn queue_due_task(state: &AppState, task: &ScheduledTask, now: i64) {
+    let pool = state.koma_db.pool();
+    let Some(due) = task.next_due else {
+        return;
+    };
+    // One lease per occurrence, left to expire, like CRON runs.
+    let Some(lease) = state
+        .acquire_job_lease(&format!("task:{}:{due}", task.id))
+        .await
+    else {
+        info!(
+            "scheduled task {} already queued on another gateway",
+            task.id
+        );
+        return;
+    };
+    lease.keep();
+
+    // Move on first: a failed run must not be queued again every tick.
+    let next_due = next_run(&task.schedule, now);
+    if let Err(err) = ScheduledTaskRepository::record_run(pool, &task.id, now, next_due).await {
+        warn!("scheduled task {}: failed to record run: {err}", task.id);
+        return;
+    }
+    crate::job_queue::submit(
+        state,
+        QueuedWork::Task {
+            task_id: task.id.clone(),
+        },
+    )
+    .await;
+}
+
+/// Run a queued task in its session and post the response there.
+pub async fn run_queued_task(state: &AppState, task_id: &str) -> Result<(), JobFailure> {
+    let pool = state.koma_db.pool();
+    let task = match ScheduledTaskRepository::get(pool, task_id).await {
+        Ok(Some(task)) => task,
+        // Deleted since it was queued.
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("task lookup failed: {err}"))),
+    };
+    let ghost = match GhostRepository::get_by_id(pool, &task.ghost_id).await {
+        Ok(Some(ghost)) => ghost,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(JobFailure::Retry(format!("ghost lookup failed: {err}"))),
+    };
+    let chat_key = format!("{}:{}:{}", task.operator_id, ghost.name, task.session_id);
+    if state.is_chat_in_flight(&chat_key).await {
+        return Err(JobFailure::Retry(format!(
+            "session {} is busy",
+            task.session_id
+        )));
+    }
+
+    let model = state
+        .resolve_model_for_ghost_with_override_json(
+            &ghost,
+            ghost.heartbeat_model_aliases.as_deref(),
+        )
+        .with_generation_overrides(&state.job_generation().heartbeat);
+    let tools = ToolManager::new_cron(state.session_chat.skill_paths().to_vec());
+    let prompt = build_task_prompt(&task);
+    let model_info = format!(
+        "# Model\n- {} ({}/{})\n",
+        model.alias, model.provider, model.model
+    );
+
+    state.set_chat_in_flight(&chat_key).await;
+    let result = state
+        .session_chat
+        .chat_job(
+            &state.koma_db,
+            &ghost.id,
+            model.client.as_ref(),
+            &model.provider,
+            &model.model,
+            model.context_window,
+            &task.session_id,
+            &task.operator_id,
+            &prompt,
+            true,
+            Some(&tools),
+            None,
+            None,
+            model.retry_on_empty,
+            &model_info,
+        )
+        .await;
+    state.clear_chat_in_flight(&chat_key).await;
+
+    let mut log = JobLog::start(&ghost.id, DbJobKind::Task, &task.session_id);
+    let (status, outcome) = match result {
+        Ok(job_result) => {
+            state.circuit_breaker.record_success(&model.alias);
+            log.transcript = job_result.transcript;
+            log.finish(&format!("ok [{}]", task.name));
+
+            if let Err(err) = SessionRepository::add_message(
+                pool,
+                &ghost.id,
+                &task.session_id,
+                MessageRole::Ghost,
+                vec![ContentBlock::Text {
+                    text: job_result.response_text,
+                }],
+                None,
+            )
+            .await
+            {
+                warn!(
+                    "scheduled task {}: failed to post to session {}: {err}",
+                    task.id, task.session_id
+                );
+            }
+            ("ran".to_string(), Ok(()))
+        }
+        Err(err) => {
+            log.finish(&format!("error [{}]: {err}", task.name));
+            (
+                format!("error: {err}"),
+                Err(JobFailure::from_chat_error(&err)),
+            )
+        }
+    };
+    if let Err(err) = JobLogRepository::insert(pool, &log).await {
+        warn!("scheduled task {}: failed to write job log: {err}", task.id);
+    }
+    state
+        .log(LogEntry::Task {
+            ghost_name: ghost.name.clone(),
+            session_id: task.session_id.clone(),
+            status,
+            task_name: task.name.clone(),
+        })
+        .await;
+    outcome
+}
+
+fn build_task_prompt(task: &ScheduledTask) -> String {
+    let schedule = CronSchedule::parse(&task.schedule, None)
+        .map(|schedule| schedule.to_string())
+        .unwrap_or_else(|_| task.schedule.clone());
+    let last_run = task
+        .last_run_at
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "(first run)".to_string());
+    crate::content::prompt_text(
+        ids::PROMPT_SCHEDULED_TASK,
+        None,
+        &[
+            ("task_name", task.name.as_str()),
+            ("schedule", schedule.as_str()),
+            ("last_run", last_run.as_str()),
+            ("task_prompt", task.prompt.as_str()),
+        ],
+    )
+    .unwrap_or_else(|_| {
+        format!(
+            "Scheduled task: {}\nSchedule: {}\nLast run: {}\n\nTask:\n{}",
+            task.name, schedule, last_run, task.prompt
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_run() {
+        // 2026-03-06T10:00:00Z
+        let now = 1_772_791_200;
+        assert_eq!(next_run("0 7 * * *", now), Some(now + 21 * 3600));
+        assert_eq!(next_run("TZ=+01:00 0 12 * * *", now), Some(now + 3600));
+        assert_eq!(next_run("whenever", now), None);
+    }
+}
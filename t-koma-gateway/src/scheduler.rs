@@ -5,11 +5,51 @@ pub enum JobKind {
     Heartbeat,
     Reflection,
     Cron,
+    JobLogPrune,
+    PromptCacheEvict,
+    SessionArchive,
+    Reminder,
+    Task,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl JobKind {
+    pub const ALL: [JobKind; 8] = [
+        JobKind::Heartbeat,
+        JobKind::Reflection,
+        JobKind::Cron,
+        JobKind::JobLogPrune,
+        JobKind::PromptCacheEvict,
+        JobKind::SessionArchive,
+        JobKind::Reminder,
+        JobKind::Task,
+    ];
+
+    /// Name stored in `scheduler_state`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobKind::Heartbeat => "heartbeat",
+            JobKind::Reflection => "reflection",
+            JobKind::Cron => "cron",
+            JobKind::JobLogPrune => "job_log_prune",
+            JobKind::PromptCacheEvict => "prompt_cache_evict",
+            JobKind::SessionArchive => "session_archive",
+            JobKind::Reminder => "reminder",
+            JobKind::Task => "task",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JobSchedule {
     pub next_due: i64,
+    /// Cron expression behind a recurring entry, as displayed.
+    pub schedule: Option<String>,
+    /// Idle delay in seconds behind a heartbeat entry, as adapted for the session.
+    pub interval_secs: Option<i64>,
 }
 
 #[derive(Debug, Default)]
@@ -22,13 +62,44 @@ impl SchedulerState {
         Self::default()
     }
 
+    /// Set the next run, keeping any cron expression already recorded.
     pub fn set_due(&mut self, kind: JobKind, key: &str, next_due: Option<i64>) {
-        if let Some(ts) = next_due {
-            self.schedules
-                .insert((kind, key.to_string()), JobSchedule { next_due: ts });
-        } else {
+        let Some(ts) = next_due else {
             self.schedules.remove(&(kind, key.to_string()));
-        }
+            return;
+        };
+        self.schedules
+            .entry((kind, key.to_string()))
+            .and_modify(|entry| entry.next_due = ts)
+            .or_insert(JobSchedule {
+                next_due: ts,
+                schedule: None,
+                interval_secs: None,
+            });
+    }
+
+    /// Set the next run of an entry that follows an idle delay.
+    pub fn set_interval(&mut self, kind: JobKind, key: &str, next_due: i64, interval_secs: i64) {
+        self.schedules.insert(
+            (kind, key.to_string()),
+            JobSchedule {
+                next_due,
+                schedule: None,
+                interval_secs: Some(interval_secs),
+            },
+        );
+    }
+
+    /// Set the next run of an entry driven by a cron expression.
+    pub fn set_recurring(&mut self, kind: JobKind, key: &str, next_due: i64, schedule: &str) {
+        self.schedules.insert(
+            (kind, key.to_string()),
+            JobSchedule {
+                next_due,
+                schedule: Some(schedule.to_string()),
+                interval_secs: None,
+            },
+        );
     }
 
     pub fn get_due(&self, kind: JobKind, key: &str) -> Option<i64> {
@@ -37,15 +108,73 @@ impl SchedulerState {
             .map(|entry| entry.next_due)
     }
 
+    pub fn get(&self, kind: JobKind, key: &str) -> Option<JobSchedule> {
+        self.schedules.get(&(kind, key.to_string())).cloned()
+    }
+
     pub fn clear(&mut self, kind: JobKind, key: &str) {
         self.schedules.remove(&(kind, key.to_string()));
     }
 
     /// List all scheduled entries (for admin/TUI display).
-    pub fn list_all(&self) -> Vec<(JobKind, String, i64)> {
+    pub fn list_all(&self) -> Vec<(JobKind, String, JobSchedule)> {
         self.schedules
             .iter()
-            .map(|((kind, key), schedule)| (*kind, key.clone(), schedule.next_due))
+            .map(|((kind, key), schedule)| (*kind, key.clone(), schedule.clone()))
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_due_keeps_recorded_schedule() {
+        let mut state = SchedulerState::new();
+        state.set_recurring(JobKind::Cron, "alpha:daily", 100, "@daily");
+        state.set_due(JobKind::Cron, "alpha:daily", Some(200));
+        state.set_due(JobKind::Reminder, "due", Some(50));
+
+        let mut all = state.list_all();
+        all.sort_by_key(|(_, key, _)| key.clone());
+        assert_eq!(
+            all,
+            vec![
+                (
+                    JobKind::Cron,
+                    "alpha:daily".to_string(),
+                    JobSchedule {
+                        next_due: 200,
+                        schedule: Some("@daily".to_string()),
+                        interval_secs: None,
+                    }
+                ),
+                (
+                    JobKind::Reminder,
+                    "due".to_string(),
+                    JobSchedule {
+                        next_due: 50,
+                        schedule: None,
+                        interval_secs: None,
+                    }
+                ),
+            ]
+        );
+
+        state.set_due(JobKind::Cron, "alpha:daily", None);
+        assert_eq!(state.get_due(JobKind::Cron, "alpha:daily"), None);
+    }
+
+    #[test]
+    fn set_interval_replaces_schedule() {
+        let mut state = SchedulerState::new();
+        state.set_recurring(JobKind::Heartbeat, "op:alpha:s1", 100, "@hourly");
+        state.set_interval(JobKind::Heartbeat, "op:alpha:s1", 300, 960);
+
+        let entry = state.get(JobKind::Heartbeat, "op:alpha:s1").unwrap();
+        assert_eq!(entry.next_due, 300);
+        assert_eq!(entry.schedule, None);
+        assert_eq!(entry.interval_secs, Some(960));
+    }
+}
@@ -1,18 +1,23 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
-    Json, Router,
-    extract::{Query, State, ws::WebSocketUpgrade},
-    response::IntoResponse,
+    Extension, Json, Router,
+    extract::{ConnectInfo, Query, Request, State, ws::WebSocketUpgrade},
+    http::{HeaderMap, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::get,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
+use crate::client_limits::{ClientPermit, ClientRejection};
 use crate::content::ids;
-use crate::discord;
 use crate::gateway_message;
 use crate::operator_flow::{self, OutboundMessage};
+use crate::session::ChatError;
 use crate::state::{AppState, LogEntry, RateLimitDecision};
 
 fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
@@ -58,7 +63,7 @@ fn ws_info_response(text: impl Into<String>) -> t_koma_core::WsResponse {
     ))
 }
 
-fn knowledge_results_to_dto(
+pub(crate) fn knowledge_results_to_dto(
     kr: &t_koma_knowledge::models::KnowledgeSearchResult,
 ) -> Vec<t_koma_core::KnowledgeResultInfo> {
     let mut infos = Vec::new();
@@ -105,7 +110,424 @@ fn knowledge_results_to_dto(
     infos
 }
 
-fn ws_from_outbound(message: OutboundMessage) -> t_koma_core::WsResponse {
+/// Run a CLI session export/import request against the DB.
+async fn session_transfer_response(
+    state: &AppState,
+    message: t_koma_core::WsMessage,
+) -> t_koma_core::WsResponse {
+    let pool = state.koma_db.pool();
+    match message {
+        t_koma_core::WsMessage::ExportSession { session_id } => {
+            match t_koma_db::SessionRepository::export(pool, &session_id).await {
+                Ok(jsonl) => t_koma_core::WsResponse::SessionExported { session_id, jsonl },
+                Err(e) => ws_error_response(format!("Session export failed: {}", e)),
+            }
+        }
+        t_koma_core::WsMessage::ImportSession { ghost_name, jsonl } => {
+            let ghost = match t_koma_db::GhostRepository::get_by_name(pool, &ghost_name).await {
+                Ok(Some(ghost)) => ghost,
+                Ok(None) => {
+                    return ws_error_response(render_message(ids::UNKNOWN_GHOST_NAME_SERVER, &[]));
+                }
+                Err(e) => return ws_error_response(format!("Session import failed: {}", e)),
+            };
+            match t_koma_db::SessionRepository::import(
+                pool,
+                &ghost.id,
+                &ghost.owner_operator_id,
+                &jsonl,
+            )
+            .await
+            {
+                Ok(session) => t_koma_core::WsResponse::SessionImported {
+                    session_id: session.id,
+                },
+                Err(e) => ws_error_response(format!("Session import failed: {}", e)),
+            }
+        }
+        _ => ws_error_response("unsupported session transfer request".to_string()),
+    }
+}
+
+/// Load a session transcript. `operator_id` restricts it to that operator's
+/// ghost and sessions; `None` is the CLI/admin view of any session.
+async fn session_messages_response(
+    state: &AppState,
+    ghost_name: &str,
+    session_id: &str,
+    operator_id: Option<&str>,
+) -> t_koma_core::WsResponse {
+    let pool = state.koma_db.pool();
+    let ghost = match t_koma_db::GhostRepository::get_by_name(pool, ghost_name).await {
+        Ok(Some(ghost)) => ghost,
+        Ok(None) => return ws_error_response(render_message(ids::UNKNOWN_GHOST_NAME_SERVER, &[])),
+        Err(e) => return ws_error_response(format!("Session load failed: {}", e)),
+    };
+    if operator_id.is_some_and(|op| ghost.owner_operator_id != op) {
+        return ws_error_response(render_message(ids::GHOST_NOT_OWNED, &[]));
+    }
+    match t_koma_db::SessionRepository::get_by_id_for_ghost(pool, session_id, &ghost.id).await {
+        Ok(Some(session)) if operator_id.is_none_or(|op| session.operator_id == op) => {}
+        Ok(_) => return ws_error_response(format!("Unknown session: {}", session_id)),
+        Err(e) => return ws_error_response(format!("Session load failed: {}", e)),
+    }
+    match t_koma_db::SessionRepository::get_messages(pool, session_id).await {
+        Ok(messages) => t_koma_core::WsResponse::SessionMessages {
+            session_id: session_id.to_string(),
+            messages: messages.into_iter().map(session_message_info).collect(),
+        },
+        Err(e) => ws_error_response(format!("Session load failed: {}", e)),
+    }
+}
+
+fn session_message_info(message: t_koma_db::Message) -> t_koma_core::SessionMessageInfo {
+    use chrono::{TimeZone, Utc};
+    use t_koma_core::TranscriptBlock;
+    use t_koma_db::ContentBlock;
+
+    let blocks = message
+        .content
+        .into_iter()
+        .map(|block| match block {
+            ContentBlock::Text { text } => TranscriptBlock::Text { text },
+            ContentBlock::Thinking {
+                thinking, redacted, ..
+            } => TranscriptBlock::Thinking {
+                text: thinking,
+                redacted,
+            },
+            ContentBlock::ToolUse { id, name, input } => {
+                TranscriptBlock::ToolUse { id, name, input }
+            }
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => TranscriptBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error: is_error == Some(true),
+            },
+            ContentBlock::Image {
+                path,
+                mime_type,
+                filename,
+            } => TranscriptBlock::Attachment {
+                filename,
+                path,
+                mime_type: Some(mime_type),
+            },
+            ContentBlock::File { path, filename, .. } => TranscriptBlock::Attachment {
+                filename,
+                path,
+                mime_type: None,
+            },
+        })
+        .collect();
+    t_koma_core::SessionMessageInfo {
+        id: message.id,
+        role: match message.role {
+            t_koma_db::MessageRole::Operator => t_koma_core::MessageRole::Operator,
+            t_koma_db::MessageRole::Ghost => t_koma_core::MessageRole::Ghost,
+        },
+        created_at: Utc
+            .timestamp_opt(message.created_at, 0)
+            .single()
+            .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap()),
+        model: message.model,
+        blocks,
+        usage: message
+            .usage
+            .as_ref()
+            .map(|usage| t_koma_core::message::UsageInfo {
+                input_tokens: usage.tokens.input_tokens,
+                output_tokens: usage.tokens.output_tokens,
+                cache_read_tokens: Some(usage.tokens.cache_read_tokens),
+                cache_creation_tokens: Some(usage.tokens.cache_creation_tokens),
+            }),
+        cost_usd: message.usage.and_then(|usage| usage.cost_usd),
+    }
+}
+
+/// Aggregate usage-log cost and tokens for a CLI usage report.
+async fn usage_report_response(
+    state: &AppState,
+    group_by: t_koma_core::UsageReportGrouping,
+    since_days: Option<u32>,
+    daily: bool,
+    include_budgets: bool,
+) -> t_koma_core::WsResponse {
+    let pool = state.koma_db.pool();
+    let grouping = match group_by {
+        t_koma_core::UsageReportGrouping::Operator => t_koma_db::UsageGrouping::Operator,
+        t_koma_core::UsageReportGrouping::Ghost => t_koma_db::UsageGrouping::Ghost,
+        t_koma_core::UsageReportGrouping::Model => t_koma_db::UsageGrouping::Model,
+        t_koma_core::UsageReportGrouping::Day => t_koma_db::UsageGrouping::Day,
+        t_koma_core::UsageReportGrouping::Message => t_koma_db::UsageGrouping::Message,
+    };
+    let since = since_days
+        .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400)
+        .unwrap_or(0);
+    let aggregates = if daily {
+        t_koma_db::UsageLogRepository::aggregate_daily(pool, grouping, since).await
+    } else {
+        t_koma_db::UsageLogRepository::aggregate(pool, grouping, since).await
+    };
+    let aggregates = match aggregates {
+        Ok(aggregates) => aggregates,
+        Err(e) => return ws_error_response(format!("Usage report failed: {}", e)),
+    };
+
+    let mut budgets = Vec::new();
+    if include_budgets {
+        let reports =
+            match t_koma_db::UsageBudgetRepository::list_reports(pool, chrono::Utc::now()).await {
+                Ok(reports) => reports,
+                Err(e) => return ws_error_response(format!("Usage report failed: {}", e)),
+            };
+        for (budget, report) in reports {
+            let (scope, label) = match budget.scope {
+                t_koma_db::BudgetScope::Operator => (
+                    t_koma_core::UsageBudgetScope::Operator,
+                    t_koma_db::OperatorRepository::get_by_id(pool, &budget.subject_id)
+                        .await
+                        .ok()
+                        .flatten()
+                        .map(|operator| operator.name),
+                ),
+                t_koma_db::BudgetScope::Ghost => (
+                    t_koma_core::UsageBudgetScope::Ghost,
+                    t_koma_db::GhostRepository::get_by_id(pool, &budget.subject_id)
+                        .await
+                        .ok()
+                        .flatten()
+                        .map(|ghost| ghost.name),
+                ),
+            };
+            budgets.push(t_koma_core::UsageBudgetInfo {
+                scope,
+                label: label.unwrap_or_else(|| budget.subject_id.clone()),
+                subject: budget.subject_id,
+                used_tokens: report.used_tokens,
+                token_limit: report.token_limit,
+                used_cost_usd: report.used_cost_usd,
+                cost_limit_usd: report.cost_limit_usd,
+                warn_ratio: budget.warn_ratio,
+            });
+        }
+    }
+
+    t_koma_core::WsResponse::UsageReport {
+        group_by,
+        rows: aggregates
+            .into_iter()
+            .map(|a| t_koma_core::UsageReportRow {
+                key: a.key,
+                label: a.label,
+                day: a.day,
+                request_count: a.totals.request_count,
+                input_tokens: a.totals.input_tokens,
+                output_tokens: a.totals.output_tokens,
+                cache_read_tokens: a.totals.cache_read_tokens,
+                cache_creation_tokens: a.totals.cache_creation_tokens,
+                cost_usd: a.totals.cost_usd,
+                unpriced_requests: a.totals.unpriced_requests,
+            })
+            .collect(),
+        budgets,
+    }
+}
+
+/// Store or clear a monthly usage budget for a CLI admin request.
+async fn usage_budget_response(
+    state: &AppState,
+    message: t_koma_core::WsMessage,
+) -> t_koma_core::WsResponse {
+    let t_koma_core::WsMessage::SetUsageBudget {
+        scope,
+        subject,
+        monthly_token_limit,
+        monthly_cost_limit_usd,
+        warn_ratio,
+    } = message
+    else {
+        return ws_error_response("unsupported usage budget request".to_string());
+    };
+    let pool = state.koma_db.pool();
+    let (db_scope, subject_id) = match scope {
+        t_koma_core::UsageBudgetScope::Operator => {
+            (t_koma_db::BudgetScope::Operator, subject.clone())
+        }
+        t_koma_core::UsageBudgetScope::Ghost => {
+            match t_koma_db::GhostRepository::get_by_name(pool, &subject).await {
+                Ok(Some(ghost)) => (t_koma_db::BudgetScope::Ghost, ghost.id),
+                Ok(None) => {
+                    return ws_error_response(render_message(ids::UNKNOWN_GHOST_NAME_SERVER, &[]));
+                }
+                Err(e) => return ws_error_response(format!("Usage budget update failed: {}", e)),
+            }
+        }
+    };
+
+    let cleared = monthly_token_limit.is_none() && monthly_cost_limit_usd.is_none();
+    let result = if cleared {
+        t_koma_db::UsageBudgetRepository::delete(pool, db_scope, &subject_id)
+            .await
+            .map(|_| ())
+    } else {
+        t_koma_db::UsageBudgetRepository::set(
+            pool,
+            &t_koma_db::UsageBudget {
+                scope: db_scope,
+                subject_id,
+                monthly_token_limit,
+                monthly_cost_limit_usd,
+                warn_ratio: warn_ratio.unwrap_or(t_koma_db::DEFAULT_BUDGET_WARN_RATIO),
+            },
+        )
+        .await
+    };
+    match result {
+        Ok(()) => t_koma_core::WsResponse::UsageBudgetSet {
+            scope,
+            subject,
+            cleared,
+        },
+        Err(e) => ws_error_response(format!("Usage budget update failed: {}", e)),
+    }
+}
+
+/// Clone a ghost for a CLI admin request.
+async fn clone_ghost_response(
+    state: &AppState,
+    source: &str,
+    new_name: &str,
+    owner_operator_id: Option<String>,
+    copy: Option<&[t_koma_core::GhostCloneScope]>,
+) -> t_koma_core::WsResponse {
+    let (options, knowledge_scopes) = ghost_clone_plan(owner_operator_id, copy);
+    match state
+        .clone_ghost(source, new_name, &options, &knowledge_scopes)
+        .await
+    {
+        Ok((ghost, knowledge_files)) => t_koma_core::WsResponse::GhostCloned {
+            source: source.to_string(),
+            name: ghost.name,
+            knowledge_files,
+        },
+        Err(e) => ws_error_response(format!("Ghost clone failed: {}", e)),
+    }
+}
+
+/// Clone options and knowledge scopes for the requested `copy` parts.
+pub(crate) fn ghost_clone_plan(
+    owner_operator_id: Option<String>,
+    copy: Option<&[t_koma_core::GhostCloneScope]>,
+) -> (
+    t_koma_db::GhostCloneOptions,
+    Vec<t_koma_knowledge::models::KnowledgeScope>,
+) {
+    use t_koma_core::GhostCloneScope;
+    use t_koma_knowledge::models::KnowledgeScope;
+
+    let copy = copy.unwrap_or(GhostCloneScope::DEFAULT);
+    let options = t_koma_db::GhostCloneOptions {
+        owner_operator_id,
+        soul: copy.contains(&GhostCloneScope::Soul),
+        skills: copy.contains(&GhostCloneScope::Skills),
+        settings: copy.contains(&GhostCloneScope::Settings),
+    };
+    let knowledge_scopes: Vec<KnowledgeScope> = copy
+        .iter()
+        .filter_map(|scope| match scope {
+            GhostCloneScope::Notes => Some(KnowledgeScope::GhostNote),
+            GhostCloneScope::Diary => Some(KnowledgeScope::GhostDiary),
+            GhostCloneScope::References => Some(KnowledgeScope::GhostReference),
+            GhostCloneScope::Soul | GhostCloneScope::Skills | GhostCloneScope::Settings => None,
+        })
+        .collect();
+    (options, knowledge_scopes)
+}
+
+/// Sessions of `ghost` owned by `operator_id`, with their next heartbeat.
+pub(crate) async fn session_infos(
+    state: &AppState,
+    operator_id: &str,
+    ghost: &t_koma_db::Ghost,
+) -> Result<Vec<t_koma_core::message::SessionInfo>, t_koma_db::DbError> {
+    use chrono::{TimeZone, Utc};
+
+    let timestamp = |ts: i64| {
+        Utc.timestamp_opt(ts, 0)
+            .single()
+            .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap())
+    };
+    let sessions =
+        t_koma_db::SessionRepository::list(state.koma_db.pool(), &ghost.id, operator_id).await?;
+    let mut session_infos = Vec::with_capacity(sessions.len());
+    for s in sessions {
+        let chat_key = format!("{}:{}:{}", operator_id, ghost.name, s.id);
+        let next_due = match state.get_heartbeat_due(&chat_key).await {
+            Some(ts) => Utc.timestamp_opt(ts, 0).single(),
+            None => {
+                let had_ok_heartbeat = t_koma_db::JobLogRepository::latest_ok_since(
+                    state.koma_db.pool(),
+                    &ghost.id,
+                    &s.id,
+                    t_koma_db::JobKind::Heartbeat,
+                    s.updated_at,
+                )
+                .await
+                .ok()
+                .flatten()
+                .is_some();
+                let override_entry = state.get_heartbeat_override(&chat_key).await;
+                crate::heartbeat::next_heartbeat_due_for_session(
+                    s.updated_at,
+                    had_ok_heartbeat,
+                    override_entry,
+                    4 * 60, // default idle delay for display
+                )
+                .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+            }
+        };
+
+        session_infos.push(t_koma_core::message::SessionInfo {
+            id: s.id,
+            created_at: timestamp(s.created_at),
+            updated_at: timestamp(s.updated_at),
+            next_heartbeat_due: next_due,
+            message_count: s.message_count,
+            is_active: s.is_active,
+            title: s.title,
+            tags: s.tags,
+        });
+    }
+    Ok(session_infos)
+}
+
+/// Map a chat failure to a WS response; budget blocks get a typed response.
+fn ws_chat_error_response(err: ChatError) -> t_koma_core::WsResponse {
+    match err {
+        ChatError::OverBudget(report) => t_koma_core::WsResponse::UsageBudgetExceeded {
+            scope: match report.scope {
+                t_koma_db::BudgetScope::Operator => t_koma_core::UsageBudgetScope::Operator,
+                t_koma_db::BudgetScope::Ghost => t_koma_core::UsageBudgetScope::Ghost,
+            },
+            used_tokens: report.used_tokens,
+            token_limit: report.token_limit,
+            used_cost_usd: report.used_cost_usd,
+            cost_limit_usd: report.cost_limit_usd,
+            message: operator_flow::usage_budget_message(ids::USAGE_BUDGET_EXCEEDED, &report, None),
+        },
+        err => {
+            error!("Provider API error: {}", err);
+            ws_error_response(format!("Chat error: {}", err))
+        }
+    }
+}
+
+pub(crate) fn ws_from_outbound(message: OutboundMessage) -> t_koma_core::WsResponse {
     match message {
         OutboundMessage::AssistantText(text) => ws_text_response(text),
         OutboundMessage::Gateway(message) => ws_gateway_response(*message),
@@ -131,6 +553,8 @@ pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub koma: String,
+    /// Per-model circuit breaker state
+    pub models: Vec<crate::circuit_breaker::BreakerSnapshot>,
 }
 
 /// Operator status response
@@ -143,9 +567,177 @@ pub struct OperatorStatusResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct WsQuery {
+    /// Client kind for token-less loopback connections (`cli` gets admin rights).
     pub client: Option<String>,
 }
 
+/// How a `/ws` connection authenticated.
+#[allow(clippy::large_enum_variant)]
+enum WsAuth {
+    /// Loopback connection without a token, or any connection presenting the
+    /// shared gateway secret: the interface flow keyed by `client`.
+    Local {
+        client_type: Option<String>,
+        via_secret: bool,
+    },
+    /// `Authorization: Bearer` API token bound to an operator.
+    Token {
+        operator: t_koma_db::Operator,
+        token: t_koma_db::ApiToken,
+    },
+}
+
+impl WsAuth {
+    /// Who is behind the connection, for log entries.
+    fn identity(&self) -> String {
+        match self {
+            WsAuth::Local {
+                via_secret: false, ..
+            } => "loopback".to_string(),
+            WsAuth::Local {
+                via_secret: true, ..
+            } => "secret".to_string(),
+            WsAuth::Token { operator, token } => {
+                format!("token:{} operator:{}", token.id, operator.id)
+            }
+        }
+    }
+}
+
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// WebSocket subprotocol clients send alongside `bearer.<token>`.
+const WS_PROTOCOL: &str = "t-koma";
+
+/// Token passed as a `bearer.<token>` WebSocket subprotocol.
+///
+/// Browsers cannot set headers on a WebSocket handshake, so the web
+/// dashboard sends its token this way instead of `Authorization`.
+fn protocol_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|protocol| protocol.trim().strip_prefix("bearer."))
+        .filter(|token| !token.is_empty())
+}
+
+/// Headers a reverse proxy adds to the requests it forwards.
+const FORWARDED_HEADERS: &[&str] = &["forwarded", "x-forwarded-for", "x-real-ip"];
+
+/// Whether the request came through a reverse proxy. Behind a proxy on the
+/// same host every client connects from loopback, so these requests never get
+/// loopback trust.
+fn is_proxied(headers: &HeaderMap) -> bool {
+    FORWARDED_HEADERS
+        .iter()
+        .any(|name| headers.contains_key(*name))
+}
+
+/// Whether the handshake's `Origin` is the gateway's own origin.
+///
+/// Browsers always send `Origin` on a WebSocket handshake, and any page can
+/// open one to `127.0.0.1`, so only pages served by the gateway itself (the web
+/// dashboard) get through. The origin must match `Host`, or `X-Forwarded-Host`
+/// behind a proxy that rewrites `Host`. Clients that send no `Origin` (CLI,
+/// TUI, scripts) are not browsers and pass.
+fn origin_allowed(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(origin) = origin
+        .to_str()
+        .ok()
+        .and_then(|origin| url::Url::parse(origin).ok())
+    else {
+        return false;
+    };
+    let Some(host) = origin.host_str() else {
+        return false;
+    };
+    let authority = match origin.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    [header::HOST.as_str(), "x-forwarded-host"]
+        .into_iter()
+        .filter_map(|name| headers.get(name)?.to_str().ok())
+        .filter_map(|value| value.split(',').next())
+        .any(|value| value.trim().eq_ignore_ascii_case(&authority))
+}
+
+/// Whether `Host` names the loopback interface (`localhost`, `127.0.0.1`,
+/// `[::1]`, with or without a port).
+///
+/// A peer address of 127.0.0.1 is not enough on its own: a page on a
+/// DNS-rebound name reaches the gateway over loopback with a matching
+/// `Origin` and `Host`, so only loopback host names keep tokenless trust.
+fn host_is_loopback(headers: &HeaderMap) -> bool {
+    let Some(host) = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+    else {
+        return false;
+    };
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(name, _)| name),
+        None => host.split_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1" || name == "::1"
+}
+
+/// Authenticate a `/ws` or `/logs` upgrade request.
+///
+/// Handshakes from a browser page on another origin are refused outright. A
+/// bearer token (header or subprotocol) always wins: either the shared gateway
+/// secret or an operator API token. Without one, only loopback peers that did
+/// not come through a reverse proxy and address a loopback `Host` are
+/// accepted, so the `client` query parameter is never trusted remotely.
+async fn authenticate_ws(
+    state: &AppState,
+    peer: SocketAddr,
+    headers: &HeaderMap,
+    client_type: Option<String>,
+) -> Result<WsAuth, (StatusCode, &'static str)> {
+    if !origin_allowed(headers) {
+        return Err((StatusCode::FORBIDDEN, "cross-origin WebSocket refused"));
+    }
+    match bearer_token(headers).or_else(|| protocol_token(headers)) {
+        Some(secret) if state.gateway_secret_matches(secret).await => Ok(WsAuth::Local {
+            client_type,
+            via_secret: true,
+        }),
+        Some(token) => {
+            match t_koma_db::OperatorRepository::authenticate_api_token(state.koma_db.pool(), token)
+                .await
+            {
+                Ok(Some((operator, token))) => Ok(WsAuth::Token { operator, token }),
+                Ok(None) => Err((StatusCode::UNAUTHORIZED, "invalid or revoked API token")),
+                Err(e) => {
+                    error!("API token lookup failed: {}", e);
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, "token lookup failed"))
+                }
+            }
+        }
+        None if peer.ip().is_loopback() && !is_proxied(headers) && host_is_loopback(headers) => {
+            Ok(WsAuth::Local {
+                client_type,
+                via_secret: false,
+            })
+        }
+        None => Err((StatusCode::UNAUTHORIZED, "API token required")),
+    }
+}
+
 /// Run the HTTP server
 pub async fn run(state: Arc<AppState>, bind_addr: &str) -> Result<(), Box<dyn std::error::Error>> {
     let app = create_router(state);
@@ -153,65 +745,167 @@ pub async fn run(state: Arc<AppState>, bind_addr: &str) -> Result<(), Box<dyn st
     let listener = tokio::net::TcpListener::bind(bind_addr).await?;
     info!("Server listening on {}", bind_addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
 /// Create the router with all routes
 fn create_router(state: Arc<AppState>) -> Router {
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health_handler))
         .route("/ws", get(ws_handler))
         .route("/logs", get(logs_ws_handler))
+        .merge(crate::api::chat::router())
+        .merge(crate::api::admin::router());
+    #[cfg(feature = "web_ui")]
+    let router = router.merge(crate::web_ui::router());
+    router
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            client_limit_middleware,
+        ))
         .with_state(state)
         .layer(tower_http::trace::TraceLayer::new_for_http())
 }
 
+/// Enforce `[client_limits]` on every route before its handler runs.
+///
+/// The granted [`ClientPermit`] rides along as a request extension; WebSocket
+/// handlers keep it for the life of the socket so it holds the connection
+/// slot and meters inbound messages.
+async fn client_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let token = bearer_token(headers).or_else(|| protocol_token(headers));
+    let upgrade = headers
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+
+    match state.client_limiter.admit(peer.ip(), token, upgrade) {
+        Ok(permit) => {
+            request.extensions_mut().insert(permit);
+            next.run(request).await
+        }
+        Err(ClientRejection::TooManyRequests { retry_after }) => {
+            warn!(
+                "Rate limited {} {} from {}",
+                request.method(),
+                request.uri().path(),
+                peer
+            );
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.as_secs().to_string())],
+                "too many requests",
+            )
+                .into_response()
+        }
+        Err(ClientRejection::TooManyConnections) => {
+            warn!(
+                "Rejected {} from {}: too many open connections",
+                request.uri().path(),
+                peer
+            );
+            (StatusCode::TOO_MANY_REQUESTS, "too many open connections").into_response()
+        }
+    }
+}
+
 /// Health check handler
-async fn health_handler() -> impl IntoResponse {
+async fn health_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     Json(HealthResponse {
         status: render_message(ids::HEALTH_STATUS, &[]),
         version: env!("CARGO_PKG_VERSION").to_string(),
         koma: render_message(ids::HEALTH_KOMA, &[]),
+        models: state.model_health(),
     })
 }
 
 /// WebSocket upgrade handler for chat
 async fn ws_handler(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(permit): Extension<ClientPermit>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
     Query(query): Query<WsQuery>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(socket, state, query.client))
+) -> Response {
+    if state.is_shutting_down() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "gateway is shutting down").into_response();
+    }
+    match authenticate_ws(&state, peer, &headers, query.client).await {
+        Ok(auth) => ws
+            .protocols([WS_PROTOCOL])
+            .on_upgrade(move |socket| handle_websocket(socket, state, auth, permit))
+            .into_response(),
+        Err((status, reason)) => {
+            warn!("Rejected /ws connection from {}: {}", peer, reason);
+            (status, reason).into_response()
+        }
+    }
 }
 
 /// WebSocket upgrade handler for logs
 async fn logs_ws_handler(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(permit): Extension<ClientPermit>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_logs_websocket(socket, state))
+) -> Response {
+    match authenticate_ws(&state, peer, &headers, None).await {
+        Ok(WsAuth::Token { token, .. }) if !token.has_scope(t_koma_db::ApiTokenScope::Admin) => {
+            (StatusCode::FORBIDDEN, "log stream requires an admin token").into_response()
+        }
+        Ok(auth) => ws
+            .protocols([WS_PROTOCOL])
+            .on_upgrade(move |socket| async move {
+                // Hold the connection slot until the stream ends.
+                let _permit = permit;
+                handle_logs_websocket(socket, state, auth.identity()).await
+            })
+            .into_response(),
+        Err((status, reason)) => {
+            warn!("Rejected /logs connection from {}: {}", peer, reason);
+            (status, reason).into_response()
+        }
+    }
 }
 
 /// Handle chat WebSocket connection
 async fn handle_websocket(
     socket: axum::extract::ws::WebSocket,
     state: Arc<AppState>,
-    client_type: Option<String>,
+    auth: WsAuth,
+    permit: ClientPermit,
 ) {
     use axum::extract::ws::Message;
     use chrono::{TimeZone, Utc};
     use futures::{sink::SinkExt, stream::StreamExt};
     use t_koma_core::message::GhostInfo;
-    use t_koma_core::{WsMessage, WsResponse};
+    use t_koma_core::{ApprovalDecision, WsMessage, WsResponse};
 
     let client_id = format!("client_{}", uuid::Uuid::new_v4());
-    info!("WebSocket puppet master connected: {}", client_id);
+    let identity = auth.identity();
+    info!(
+        "WebSocket puppet master connected: {} ({})",
+        client_id, identity
+    );
 
     state
         .log(LogEntry::WebSocket {
             event: "connected".to_string(),
             client_id: client_id.clone(),
+            identity: identity.clone(),
         })
         .await;
 
@@ -219,59 +913,52 @@ async fn handle_websocket(
 
     let mut selected_model_alias: Option<String> = None;
 
-    let platform = match client_type.as_deref() {
-        Some("cli") => t_koma_db::Platform::Cli,
-        _ => t_koma_db::Platform::Api,
+    let (platform, external_id, is_admin, can_chat) = match &auth {
+        WsAuth::Local { client_type, .. } => {
+            let platform = match client_type.as_deref() {
+                Some("cli") => t_koma_db::Platform::Cli,
+                _ => t_koma_db::Platform::Api,
+            };
+            let external_id = client_type.clone().unwrap_or_else(|| client_id.clone());
+            (
+                platform,
+                external_id,
+                platform == t_koma_db::Platform::Cli,
+                true,
+            )
+        }
+        WsAuth::Token { token, .. } => (
+            t_koma_db::Platform::Api,
+            token.id.clone(),
+            token.has_scope(t_koma_db::ApiTokenScope::Admin),
+            token.has_scope(t_koma_db::ApiTokenScope::Chat),
+        ),
     };
 
-    let external_id = client_type.clone().unwrap_or_else(|| client_id.clone());
-
     let mut operator_id: Option<String> = None;
     let mut operator_status: Option<t_koma_db::OperatorStatus> = None;
     let mut active_ghost: Option<String> = None;
 
-    let interface = match t_koma_db::InterfaceRepository::get_by_external_id(
-        state.koma_db.pool(),
-        platform,
-        &external_id,
-    )
-    .await
-    {
-        Ok(found) => found,
-        Err(e) => {
-            error!("Failed to load interface {}: {}", external_id, e);
-            let error_response = ws_error_response(render_message(ids::FAILED_LOAD_INTERFACE, &[]));
-            let _ = sender
-                .send(Message::Text(
-                    serde_json::to_string(&error_response).unwrap().into(),
-                ))
-                .await;
-            return;
-        }
-    };
-
-    if let Some(interface) = interface {
-        match t_koma_db::OperatorRepository::get_by_id(state.koma_db.pool(), &interface.operator_id)
-            .await
+    if let WsAuth::Token { operator, token } = &auth {
+        info!(
+            "WebSocket {} authenticated with API token {} ({})",
+            client_id, token.id, token.name
+        );
+        operator_id = Some(operator.id.clone());
+        operator_status = Some(operator.status);
+    } else {
+        let interface = match t_koma_db::InterfaceRepository::get_by_external_id(
+            state.koma_db.pool(),
+            platform,
+            &external_id,
+        )
+        .await
         {
-            Ok(Some(op)) => {
-                operator_id = Some(op.id.clone());
-                operator_status = Some(op.status);
-            }
-            Ok(None) => {
-                let error_response =
-                    ws_error_response(render_message(ids::INTERFACE_INVALID_OPERATOR, &[]));
-                let _ = sender
-                    .send(Message::Text(
-                        serde_json::to_string(&error_response).unwrap().into(),
-                    ))
-                    .await;
-                return;
-            }
+            Ok(found) => found,
             Err(e) => {
-                error!("Failed to load operator: {}", e);
+                error!("Failed to load interface {}: {}", external_id, e);
                 let error_response =
-                    ws_error_response(render_message(ids::FAILED_LOAD_OPERATOR, &[]));
+                    ws_error_response(render_message(ids::FAILED_LOAD_INTERFACE, &[]));
                 let _ = sender
                     .send(Message::Text(
                         serde_json::to_string(&error_response).unwrap().into(),
@@ -279,15 +966,50 @@ async fn handle_websocket(
                     .await;
                 return;
             }
+        };
+
+        if let Some(interface) = interface {
+            match t_koma_db::OperatorRepository::get_by_id(
+                state.koma_db.pool(),
+                &interface.operator_id,
+            )
+            .await
+            {
+                Ok(Some(op)) => {
+                    operator_id = Some(op.id.clone());
+                    operator_status = Some(op.status);
+                }
+                Ok(None) => {
+                    let error_response =
+                        ws_error_response(render_message(ids::INTERFACE_INVALID_OPERATOR, &[]));
+                    let _ = sender
+                        .send(Message::Text(
+                            serde_json::to_string(&error_response).unwrap().into(),
+                        ))
+                        .await;
+                    return;
+                }
+                Err(e) => {
+                    error!("Failed to load operator: {}", e);
+                    let error_response =
+                        ws_error_response(render_message(ids::FAILED_LOAD_OPERATOR, &[]));
+                    let _ = sender
+                        .send(Message::Text(
+                            serde_json::to_string(&error_response).unwrap().into(),
+                        ))
+                        .await;
+                    return;
+                }
+            }
+        } else {
+            state.set_interface_pending(platform, &external_id).await;
+            let response = ws_info_response(render_message(ids::INTERFACE_REQUIRED, &[]));
+            let _ = sender
+                .send(Message::Text(
+                    serde_json::to_string(&response).unwrap().into(),
+                ))
+                .await;
         }
-    } else {
-        state.set_interface_pending(platform, &external_id).await;
-        let response = ws_info_response(render_message(ids::INTERFACE_REQUIRED, &[]));
-        let _ = sender
-            .send(Message::Text(
-                serde_json::to_string(&response).unwrap().into(),
-            ))
-            .await;
     }
 
     if let Some(status) = operator_status
@@ -350,9 +1072,129 @@ async fn handle_websocket(
         }
     }
 
-    while let Some(Ok(msg)) = receiver.next().await {
+    /// Drive a chat turn while still reading the socket: a `stop` chat frame
+    /// cancels the turn, other frames are queued until it finishes. A dropped
+    /// connection stops the turn as well.
+    async fn while_watching_socket<T>(
+        state: &AppState,
+        chat_key: &str,
+        receiver: &mut (impl futures::Stream<Item = Result<Message, axum::Error>> + Unpin),
+        queued: &mut VecDeque<Message>,
+        work: impl std::future::Future<Output = T>,
+    ) -> T {
+        let mut work = std::pin::pin!(work);
+        let mut socket_open = true;
+        loop {
+            tokio::select! {
+                result = &mut work => return result,
+                frame = receiver.next(), if socket_open => match frame {
+                    Some(Ok(Message::Text(text))) if is_stop_frame(&text) => {
+                        state.stop_chat(chat_key).await;
+                    }
+                    Some(Ok(msg)) => queued.push_back(msg),
+                    _ => {
+                        socket_open = false;
+                        state.stop_chat(chat_key).await;
+                        queued.push_back(Message::Close(None));
+                    }
+                },
+            }
+        }
+    }
+
+    fn is_stop_frame(text: &str) -> bool {
+        matches!(
+            serde_json::from_str::<WsMessage>(text),
+            Ok(WsMessage::Chat { content, .. }) if content.trim().eq_ignore_ascii_case("stop")
+        )
+    }
+
+    // Frames that arrived while a chat turn was running.
+    let mut queued_frames: VecDeque<Message> = VecDeque::new();
+    // Notices (e.g. due reminders) pushed to this connection's operator.
+    let mut notices = state.subscribe_operator_notices();
+    // Operators whose pending approvals changed; this operator's get a snapshot.
+    let mut approval_changes = state.subscribe_approval_changes();
+    loop {
+        let msg = match queued_frames.pop_front() {
+            Some(msg) => msg,
+            None => tokio::select! {
+                frame = receiver.next() => match frame {
+                    Some(Ok(msg)) => msg,
+                    _ => break,
+                },
+                notice = notices.recv() => {
+                    if let Ok(notice) = notice
+                        && operator_id.as_deref() == Some(notice.operator_id.as_str())
+                    {
+                        let response = WsResponse::Response {
+                            id: notice.id,
+                            message: notice.message,
+                            done: true,
+                            usage: None,
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                    }
+                    continue;
+                }
+                changed = approval_changes.recv() => {
+                    if let Ok(changed) = changed
+                        && operator_id.as_deref() == Some(changed.as_str())
+                    {
+                        let response = WsResponse::PendingApprovals {
+                            approvals: state.pending_approvals(&changed).await,
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                    }
+                    continue;
+                }
+            },
+        };
+        if matches!(msg, Message::Text(_))
+            && let RateLimitDecision::Limited { retry_after } = permit.check_message()
+        {
+            let error_response = ws_error_response(format!(
+                "Too many messages; retry in {}s",
+                retry_after.as_secs()
+            ));
+            let _ = sender
+                .send(Message::Text(
+                    serde_json::to_string(&error_response).unwrap().into(),
+                ))
+                .await;
+            continue;
+        }
         match msg {
             Message::Text(text) => match serde_json::from_str::<WsMessage>(&text) {
+                Ok(message) if state.koma_db.is_read_only() && !message.is_read_only() => {
+                    let error_response = ws_error_response(
+                        "This gateway is a read-only replica; send writes to the primary"
+                            .to_string(),
+                    );
+                    let _ = sender
+                        .send(Message::Text(
+                            serde_json::to_string(&error_response).unwrap().into(),
+                        ))
+                        .await;
+                }
+                Ok(WsMessage::SelectInterface { .. }) if matches!(auth, WsAuth::Token { .. }) => {
+                    let error_response = ws_error_response(
+                        "API token connections are already bound to an operator".to_string(),
+                    );
+                    let _ = sender
+                        .send(Message::Text(
+                            serde_json::to_string(&error_response).unwrap().into(),
+                        ))
+                        .await;
+                }
                 Ok(WsMessage::SelectInterface { choice }) => {
                     let choice = choice.to_lowercase();
                     if choice == "existing" {
@@ -440,9 +1282,10 @@ async fn handle_websocket(
                         operator_id: target_operator_id,
                     } = other_message.clone()
                     {
-                        if platform != t_koma_db::Platform::Cli {
+                        if !is_admin {
                             let error_response = ws_error_response(
-                                "approve_operator requires CLI client context".to_string(),
+                                "approve_operator requires CLI client context or an admin token"
+                                    .to_string(),
                             );
                             let _ = sender
                                 .send(Message::Text(
@@ -468,30 +1311,205 @@ async fn handle_websocket(
                             continue;
                         }
 
-                        let mut discord_notified = false;
-                        if let Some(token) = state.discord_bot_token().await {
-                            match discord::send_approved_operator_ghost_prompt_dm(
-                                state.as_ref(),
-                                token.as_str(),
-                                &target_operator_id,
+                        let discord_notified = operator_flow::welcome_approved_operator(
+                            state.as_ref(),
+                            &target_operator_id,
+                        )
+                        .await;
+
+                        let response = WsResponse::OperatorApproved {
+                            operator_id: target_operator_id,
+                            discord_notified,
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: operators waiting for approval.
+                    if matches!(other_message, WsMessage::ListPendingOperators) {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "list_pending_operators requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            match t_koma_db::OperatorRepository::list_by_status(
+                                state.koma_db.pool(),
+                                t_koma_db::OperatorStatus::Pending,
+                                None,
                             )
                             .await
                             {
-                                Ok(notified) => {
-                                    discord_notified = notified;
-                                }
-                                Err(e) => {
-                                    warn!(
-                                        "Approved operator {}, but Discord notification failed: {}",
-                                        target_operator_id, e
-                                    );
-                                }
+                                Ok(operators) => WsResponse::PendingOperators {
+                                    operators: operators
+                                        .into_iter()
+                                        .map(|operator| t_koma_core::PendingOperatorInfo {
+                                            id: operator.id,
+                                            name: operator.name,
+                                            platform: operator.platform.to_string(),
+                                            created_at: chrono::DateTime::from_timestamp(
+                                                operator.created_at,
+                                                0,
+                                            )
+                                            .unwrap_or_default(),
+                                        })
+                                        .collect(),
+                                },
+                                Err(e) => ws_error_response(format!(
+                                    "Failed to list pending operators: {}",
+                                    e
+                                )),
                             }
-                        }
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
 
-                        let response = WsResponse::OperatorApproved {
-                            operator_id: target_operator_id,
-                            discord_notified,
+                    // CLI admin commands: session export/import across installs.
+                    if matches!(
+                        other_message,
+                        WsMessage::ExportSession { .. } | WsMessage::ImportSession { .. }
+                    ) {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "session export/import requires CLI client context or an admin token".to_string(),
+                            )
+                        } else {
+                            session_transfer_response(&state, other_message).await
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI/admin transcripts of any session; operators go through the
+                    // ownership checks below.
+                    if is_admin
+                        && let WsMessage::GetSessionMessages {
+                            ghost_name,
+                            session_id,
+                        } = &other_message
+                    {
+                        let response =
+                            session_messages_response(&state, ghost_name, session_id, None).await;
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: usage/cost report across operators and ghosts.
+                    if let WsMessage::GetUsageReport {
+                        group_by,
+                        since_days,
+                        daily,
+                        include_budgets,
+                    } = other_message
+                    {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "usage report requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            usage_report_response(
+                                &state,
+                                group_by,
+                                since_days,
+                                daily,
+                                include_budgets,
+                            )
+                            .await
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: per-operator/per-ghost monthly usage budgets.
+                    if matches!(other_message, WsMessage::SetUsageBudget { .. }) {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "usage budgets require CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            usage_budget_response(&state, other_message).await
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: clone a ghost into a new variant.
+                    if let WsMessage::CloneGhost {
+                        source,
+                        new_name,
+                        owner_operator_id,
+                        copy,
+                    } = &other_message
+                    {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "ghost cloning requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            clone_ghost_response(
+                                &state,
+                                source,
+                                new_name,
+                                owner_operator_id.clone(),
+                                copy.as_deref(),
+                            )
+                            .await
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: rename a ghost (workspace, knowledge index, mappings).
+                    if let WsMessage::RenameGhost {
+                        ghost_name,
+                        new_name,
+                    } = &other_message
+                    {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "ghost rename requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            match state.rename_ghost(ghost_name, new_name).await {
+                                Ok(ghost) => t_koma_core::WsResponse::GhostRenamed {
+                                    old_name: ghost_name.clone(),
+                                    new_name: ghost.name,
+                                },
+                                Err(e) => ws_error_response(format!("Ghost rename failed: {}", e)),
+                            }
                         };
                         let _ = sender
                             .send(Message::Text(
@@ -502,6 +1520,31 @@ async fn handle_websocket(
                     }
 
                     // Admin queries that don't need operator identity (ephemeral WS connections).
+                    // Token connections need the admin scope for them (and for restarts).
+                    if !is_admin
+                        && matches!(auth, WsAuth::Token { .. })
+                        && matches!(
+                            other_message,
+                            WsMessage::SearchKnowledge { .. }
+                                | WsMessage::ListRecentNotes { .. }
+                                | WsMessage::GetKnowledgeEntry { .. }
+                                | WsMessage::ListKnowledgeTopics { .. }
+                                | WsMessage::GetKnowledgeStats
+                                | WsMessage::GetSchedulerState
+                                | WsMessage::CancelJob { .. }
+                                | WsMessage::RestartGateway
+                                | WsMessage::ReloadConfig
+                        )
+                    {
+                        let error_response =
+                            ws_error_response("admin queries require an admin token".to_string());
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&error_response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
                     if let WsMessage::SearchKnowledge {
                         ghost_name: ref gn,
                         ref query,
@@ -578,7 +1621,11 @@ async fn handle_websocket(
                             path: None,
                             max_chars,
                         };
-                        let ghost = String::new();
+                        // Private entries are only readable as their owner ghost.
+                        let ghost = match state.knowledge_engine().note_owner(id).await {
+                            Ok((_, owner)) => owner.unwrap_or_default(),
+                            Err(_) => String::new(),
+                        };
                         let response =
                             match state.knowledge_engine().knowledge_get(&ghost, query).await {
                                 Ok(doc) => WsResponse::KnowledgeEntry {
@@ -586,6 +1633,8 @@ async fn handle_websocket(
                                     title: doc.title,
                                     entry_type: doc.entry_type,
                                     body: doc.body,
+                                    path: Some(doc.path.display().to_string()),
+                                    trust_score: Some(doc.trust_score),
                                 },
                                 Err(e) => ws_error_response(format!("Get entry failed: {}", e)),
                             };
@@ -597,6 +1646,150 @@ async fn handle_websocket(
                         continue;
                     }
 
+                    if let WsMessage::ListKnowledgeTopics { include_archived } = other_message {
+                        let engine = state.knowledge_engine();
+                        let response = match engine.topic_list(include_archived).await {
+                            Ok(entries) => {
+                                let mut topics = Vec::with_capacity(entries.len());
+                                for entry in entries {
+                                    let files = engine
+                                        .topic_files(&entry.topic_id)
+                                        .await
+                                        .unwrap_or_default()
+                                        .into_iter()
+                                        .map(|n| t_koma_core::KnowledgeResultInfo {
+                                            id: n.id,
+                                            title: n.title,
+                                            entry_type: n.entry_type,
+                                            scope: format!("{:?}", n.scope),
+                                            snippet: n.path.display().to_string(),
+                                            tags: Vec::new(),
+                                        })
+                                        .collect();
+                                    topics.push(t_koma_core::KnowledgeTopicInfo {
+                                        id: entry.topic_id,
+                                        title: entry.title,
+                                        tags: entry.tags,
+                                        archived: entry.archived,
+                                        files,
+                                    });
+                                }
+                                WsResponse::KnowledgeTopics { topics }
+                            }
+                            Err(e) => ws_error_response(format!("List topics failed: {}", e)),
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: cancel a running heartbeat/reflection job.
+                    if let WsMessage::CancelJob { ref job_id } = other_message {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "job cancellation requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else if state.cancel_job(job_id).await {
+                            WsResponse::JobCancelled {
+                                job_id: job_id.clone(),
+                            }
+                        } else {
+                            ws_error_response(format!("No running job with ID {job_id}"))
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: delete any ghost's note or a reference file.
+                    if let WsMessage::DeleteKnowledgeEntry { ref id } = other_message {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "knowledge deletion requires CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else {
+                            let engine = state.knowledge_engine();
+                            let result = match engine.note_owner(id).await {
+                                Ok((scope, _)) if scope.is_reference() => {
+                                    engine.reference_file_delete(id).await
+                                }
+                                Ok((_, owner)) => {
+                                    engine.note_delete(&owner.unwrap_or_default(), id).await
+                                }
+                                Err(e) => Err(e),
+                            };
+                            match result {
+                                Ok(()) => WsResponse::KnowledgeEntryDeleted { id: id.clone() },
+                                Err(e) => ws_error_response(format!("Delete entry failed: {}", e)),
+                            }
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
+                    // CLI admin command: set the trust score of any ghost's note.
+                    if let WsMessage::SetKnowledgeTrust {
+                        ref id,
+                        trust_score,
+                    } = other_message
+                    {
+                        let response = if !is_admin {
+                            ws_error_response(
+                                "trust changes require CLI client context or an admin token"
+                                    .to_string(),
+                            )
+                        } else if !(0..=10).contains(&trust_score) {
+                            ws_error_response("trust_score must be between 0 and 10".to_string())
+                        } else {
+                            let engine = state.knowledge_engine();
+                            let result = match engine.note_owner(id).await {
+                                Ok((scope, _)) if scope.is_reference() => {
+                                    Err("reference files have no trust score".to_string())
+                                }
+                                Ok((_, owner)) => {
+                                    let request = t_koma_knowledge::models::NoteUpdateRequest {
+                                        note_id: id.clone(),
+                                        title: None,
+                                        body: None,
+                                        tags: None,
+                                        trust_score: Some(trust_score),
+                                        parent: None,
+                                    };
+                                    engine
+                                        .note_update(&owner.unwrap_or_default(), request)
+                                        .await
+                                        .map_err(|e| e.to_string())
+                                }
+                                Err(e) => Err(e.to_string()),
+                            };
+                            match result {
+                                Ok(_) => WsResponse::KnowledgeTrustSet {
+                                    id: id.clone(),
+                                    trust_score,
+                                },
+                                Err(e) => ws_error_response(format!("Set trust failed: {}", e)),
+                            }
+                        };
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
                     if let WsMessage::GetKnowledgeStats = other_message {
                         let response = match state.knowledge_engine().index_stats().await {
                             Ok(s) => WsResponse::KnowledgeStats {
@@ -604,6 +1797,8 @@ async fn handle_websocket(
                                     total_notes: s.total_notes,
                                     total_chunks: s.total_chunks,
                                     total_embeddings: s.total_embeddings,
+                                    missing_embeddings: s.missing_embeddings,
+                                    orphaned_chunks: s.orphaned_chunks,
                                     embedding_model: s.embedding_model,
                                     embedding_dim: s.embedding_dim,
                                     recent_entries: s
@@ -632,10 +1827,12 @@ async fn handle_websocket(
                         let all = state.scheduler_state().await;
                         let entries: Vec<t_koma_core::SchedulerEntryInfo> = all
                             .into_iter()
-                            .map(|(kind, key, next_due)| t_koma_core::SchedulerEntryInfo {
+                            .map(|(kind, key, entry)| t_koma_core::SchedulerEntryInfo {
                                 kind: format!("{:?}", kind),
                                 key,
-                                next_due,
+                                next_due: entry.next_due,
+                                schedule: entry.schedule,
+                                interval_secs: entry.interval_secs,
                             })
                             .collect();
                         let response = WsResponse::SchedulerState { entries };
@@ -682,9 +1879,32 @@ async fn handle_websocket(
                         }
                     }
 
+                    if !can_chat
+                        && !matches!(
+                            other_message,
+                            WsMessage::RestartGateway | WsMessage::ReloadConfig
+                        )
+                    {
+                        let error_response =
+                            ws_error_response("API token lacks the chat scope".to_string());
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&error_response).unwrap().into(),
+                            ))
+                            .await;
+                        continue;
+                    }
+
                     // Control/admin commands that do not depend on active ghost routing.
                     match other_message.clone() {
-                        WsMessage::ApproveOperator { .. } => {}
+                        WsMessage::ApproveOperator { .. }
+                        | WsMessage::ListPendingOperators
+                        | WsMessage::ExportSession { .. }
+                        | WsMessage::ImportSession { .. }
+                        | WsMessage::GetUsageReport { .. }
+                        | WsMessage::SetUsageBudget { .. }
+                        | WsMessage::RenameGhost { .. }
+                        | WsMessage::CloneGhost { .. } => {}
                         WsMessage::SelectProvider { provider, model } => {
                             if let Err(err) = state.reload_model_registry().await {
                                 let error_response =
@@ -769,7 +1989,11 @@ async fn handle_websocket(
                         WsMessage::SearchKnowledge { .. }
                         | WsMessage::ListRecentNotes { .. }
                         | WsMessage::GetKnowledgeEntry { .. }
-                        | WsMessage::GetSchedulerState => {}
+                        | WsMessage::ListKnowledgeTopics { .. }
+                        | WsMessage::DeleteKnowledgeEntry { .. }
+                        | WsMessage::SetKnowledgeTrust { .. }
+                        | WsMessage::GetSchedulerState
+                        | WsMessage::CancelJob { .. } => {}
                         WsMessage::RestartGateway => {
                             match state.restart_gateway().await {
                                 Ok(()) => {
@@ -794,6 +2018,23 @@ async fn handle_websocket(
                             }
                             continue;
                         }
+                        WsMessage::ReloadConfig => {
+                            let response = match state.reload_config(Some(&op_id)).await {
+                                Ok(reload) => WsResponse::ConfigReloaded {
+                                    applied: reload.applied,
+                                    restart_required: reload.restart_required,
+                                },
+                                Err(e) => {
+                                    ws_error_response(format!("Failed to reload config: {}", e))
+                                }
+                            };
+                            let _ = sender
+                                .send(Message::Text(
+                                    serde_json::to_string(&response).unwrap().into(),
+                                ))
+                                .await;
+                            continue;
+                        }
                         WsMessage::Ping => {
                             let pong = WsResponse::Pong;
                             let pong_json = serde_json::to_string(&pong).unwrap();
@@ -961,20 +2202,33 @@ async fn handle_websocket(
                                 ))
                                 .await;
                         }
-                        WsMessage::ApproveOperator { .. } => {}
+                        WsMessage::ApproveOperator { .. }
+                        | WsMessage::ListPendingOperators
+                        | WsMessage::ExportSession { .. }
+                        | WsMessage::ImportSession { .. }
+                        | WsMessage::GetUsageReport { .. }
+                        | WsMessage::SetUsageBudget { .. }
+                        | WsMessage::RenameGhost { .. }
+                        | WsMessage::CloneGhost { .. } => {}
                         WsMessage::SelectProvider { .. }
                         | WsMessage::ListAvailableModels { .. }
                         | WsMessage::RestartGateway
+                        | WsMessage::ReloadConfig
                         | WsMessage::SearchKnowledge { .. }
                         | WsMessage::ListRecentNotes { .. }
                         | WsMessage::GetKnowledgeEntry { .. }
+                        | WsMessage::ListKnowledgeTopics { .. }
+                        | WsMessage::DeleteKnowledgeEntry { .. }
+                        | WsMessage::SetKnowledgeTrust { .. }
                         | WsMessage::GetKnowledgeStats
                         | WsMessage::GetSchedulerState
+                        | WsMessage::CancelJob { .. }
                         | WsMessage::Ping => {}
                         WsMessage::Chat {
                             ghost_name,
                             session_id,
                             content,
+                            attachments,
                         } => {
                             let ghost_name = if ghost_name == "active" {
                                 match active_ghost.clone() {
@@ -1159,6 +2413,35 @@ async fn handle_websocket(
                                 }
                             }
 
+                            let attachment_blocks = if attachments.is_empty() {
+                                vec![]
+                            } else {
+                                match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+                                    Ok(workspace_path) => {
+                                        crate::attachments::ws_attachments_to_content_blocks(
+                                            &attachments,
+                                            &workspace_path,
+                                        )
+                                        .await
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to get workspace path: {}", e);
+                                        let error_response = ws_error_response(render_message(
+                                            ids::ERROR_FAILED_INIT_GHOST_STORAGE,
+                                            &[],
+                                        ));
+                                        let _ = sender
+                                            .send(Message::Text(
+                                                serde_json::to_string(&error_response)
+                                                    .unwrap()
+                                                    .into(),
+                                            ))
+                                            .await;
+                                        continue;
+                                    }
+                                }
+                            };
+
                             let mut content_for_chat = content.clone();
                             if content.trim().eq_ignore_ascii_case("new") {
                                 let previous_session =
@@ -1228,13 +2511,12 @@ async fn handle_websocket(
                                     ))
                                     .await;
 
-                                operator_flow::spawn_reflection_for_previous_session(
+                                operator_flow::queue_reflection_for_previous_session(
                                     &state,
-                                    &ghost_name,
                                     &ghost.id,
-                                    &op_id,
                                     &previous_session.id,
-                                );
+                                )
+                                .await;
 
                                 target_session_id = new_session.id;
                                 content_for_chat = "hello".to_string();
@@ -1249,14 +2531,22 @@ async fn handle_websocket(
                                 })
                                 .await;
 
-                            match operator_flow::run_tool_control_command(
-                                state.as_ref(),
-                                None,
-                                selected_model_alias.as_deref(),
-                                &ghost_name,
-                                &target_session_id,
-                                &op_id,
-                                content.trim(),
+                            let chat_key =
+                                format!("{}:{}:{}", op_id, ghost_name, target_session_id);
+                            match while_watching_socket(
+                                &state,
+                                &chat_key,
+                                &mut receiver,
+                                &mut queued_frames,
+                                operator_flow::run_tool_control_command(
+                                    state.as_ref(),
+                                    None,
+                                    selected_model_alias.as_deref(),
+                                    &ghost_name,
+                                    &target_session_id,
+                                    &op_id,
+                                    content.trim(),
+                                ),
                             )
                             .await
                             {
@@ -1279,43 +2569,212 @@ async fn handle_websocket(
                                     let _ = sender.send(Message::Text(error_json.into())).await;
                                     continue;
                                 }
-                            }
+                            }
+
+                            match while_watching_socket(
+                                &state,
+                                &chat_key,
+                                &mut receiver,
+                                &mut queued_frames,
+                                operator_flow::run_chat_with_pending_and_attachments(
+                                    state.as_ref(),
+                                    None,
+                                    selected_model_alias.as_deref(),
+                                    &ghost_name,
+                                    &target_session_id,
+                                    &op_id,
+                                    &content_for_chat,
+                                    attachment_blocks,
+                                    None,
+                                ),
+                            )
+                            .await
+                            {
+                                Ok(messages) => {
+                                    for message in messages {
+                                        let ws = ws_from_outbound(message);
+                                        let response_json = serde_json::to_string(&ws).unwrap();
+                                        if let Err(e) =
+                                            sender.send(Message::Text(response_json.into())).await
+                                        {
+                                            error!("Failed to send response: {}", e);
+                                            break;
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    let error_response = ws_chat_error_response(e);
+                                    let error_json =
+                                        serde_json::to_string(&error_response).unwrap();
+                                    let _ = sender.send(Message::Text(error_json.into())).await;
+                                }
+                            }
+                        }
+                        WsMessage::ListSessions { ghost_name } => {
+                            if let Err(message) =
+                                ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
+                            {
+                                let error_response = ws_error_response(message);
+                                let _ = sender
+                                    .send(Message::Text(
+                                        serde_json::to_string(&error_response).unwrap().into(),
+                                    ))
+                                    .await;
+                                continue;
+                            }
+
+                            let ghost = match t_koma_db::GhostRepository::get_by_name(
+                                state.koma_db.pool(),
+                                &ghost_name,
+                            )
+                            .await
+                            {
+                                Ok(Some(g)) => g,
+                                Ok(None) => {
+                                    let error_response = ws_error_response(render_message(
+                                        ids::UNKNOWN_GHOST_NAME_SERVER,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
+                                    continue;
+                                }
+                                Err(e) => {
+                                    error!("Failed to load ghost: {}", e);
+                                    let error_response = ws_error_response(render_message(
+                                        ids::FAILED_LOAD_GHOST,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
+                                    continue;
+                                }
+                            };
+
+                            match session_infos(&state, &op_id, &ghost).await {
+                                Ok(session_infos) => {
+                                    let response = WsResponse::SessionList {
+                                        sessions: session_infos,
+                                    };
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&response).unwrap().into(),
+                                        ))
+                                        .await;
+                                }
+                                Err(e) => {
+                                    error!("Failed to list sessions: {}", e);
+                                    let error_response = ws_error_response(render_message(
+                                        ids::FAILED_LIST_SESSIONS,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
+                                }
+                            }
+                        }
+                        WsMessage::CreateSession { ghost_name } => {
+                            if let Err(message) =
+                                ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
+                            {
+                                let error_response = ws_error_response(message);
+                                let _ = sender
+                                    .send(Message::Text(
+                                        serde_json::to_string(&error_response).unwrap().into(),
+                                    ))
+                                    .await;
+                                continue;
+                            }
+
+                            let ghost = match t_koma_db::GhostRepository::get_by_name(
+                                state.koma_db.pool(),
+                                &ghost_name,
+                            )
+                            .await
+                            {
+                                Ok(Some(g)) => g,
+                                Ok(None) => {
+                                    let error_response = ws_error_response(render_message(
+                                        ids::UNKNOWN_GHOST_NAME_SERVER,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
+                                    continue;
+                                }
+                                Err(e) => {
+                                    error!("Failed to load ghost: {}", e);
+                                    let error_response = ws_error_response(render_message(
+                                        ids::FAILED_LOAD_GHOST,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
+                                    continue;
+                                }
+                            };
 
-                            match operator_flow::run_chat_with_pending(
-                                state.as_ref(),
-                                None,
-                                selected_model_alias.as_deref(),
-                                &ghost_name,
-                                &target_session_id,
+                            match t_koma_db::SessionRepository::create(
+                                state.koma_db.pool(),
+                                &ghost.id,
                                 &op_id,
-                                &content_for_chat,
-                                None,
                             )
                             .await
                             {
-                                Ok(messages) => {
-                                    for message in messages {
-                                        let ws = ws_from_outbound(message);
-                                        let response_json = serde_json::to_string(&ws).unwrap();
-                                        if let Err(e) =
-                                            sender.send(Message::Text(response_json.into())).await
-                                        {
-                                            error!("Failed to send response: {}", e);
-                                            break;
-                                        }
-                                    }
+                                Ok(new_session) => {
+                                    let response = WsResponse::SessionCreated {
+                                        session_id: new_session.id.clone(),
+                                    };
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&response).unwrap().into(),
+                                        ))
+                                        .await;
+                                    let session_started =
+                                        ws_gateway_response(gateway_message::from_content(
+                                            ids::SESSION_STARTED,
+                                            None,
+                                            &[],
+                                        ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&session_started).unwrap().into(),
+                                        ))
+                                        .await;
                                 }
                                 Err(e) => {
-                                    error!("Provider API error: {}", e);
-                                    let error_response =
-                                        ws_error_response(format!("Chat error: {}", e));
-                                    let error_json =
-                                        serde_json::to_string(&error_response).unwrap();
-                                    let _ = sender.send(Message::Text(error_json.into())).await;
+                                    error!("Failed to create session: {}", e);
+                                    let error_response = ws_error_response(render_message(
+                                        ids::FAILED_CREATE_SESSION,
+                                        &[],
+                                    ));
+                                    let _ = sender
+                                        .send(Message::Text(
+                                            serde_json::to_string(&error_response).unwrap().into(),
+                                        ))
+                                        .await;
                                 }
                             }
                         }
-                        WsMessage::ListSessions { ghost_name } => {
+                        WsMessage::SwitchSession {
+                            ghost_name,
+                            session_id,
+                        } => {
                             if let Err(message) =
                                 ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
                             {
@@ -1362,69 +2821,16 @@ async fn handle_websocket(
                                 }
                             };
 
-                            match t_koma_db::SessionRepository::list(
+                            match t_koma_db::SessionRepository::switch(
                                 state.koma_db.pool(),
                                 &ghost.id,
                                 &op_id,
+                                &session_id,
                             )
                             .await
                             {
-                                Ok(sessions) => {
-                                    let mut session_infos = Vec::with_capacity(sessions.len());
-                                    for s in sessions {
-                                        let chat_key = format!("{}:{}:{}", op_id, ghost_name, s.id);
-                                        let next_due = match state
-                                            .get_heartbeat_due(&chat_key)
-                                            .await
-                                        {
-                                            Some(ts) => Utc.timestamp_opt(ts, 0).single(),
-                                            None => {
-                                                let had_ok_heartbeat =
-                                                    t_koma_db::JobLogRepository::latest_ok_since(
-                                                        state.koma_db.pool(),
-                                                        &ghost.id,
-                                                        &s.id,
-                                                        t_koma_db::JobKind::Heartbeat,
-                                                        s.updated_at,
-                                                    )
-                                                    .await
-                                                    .ok()
-                                                    .flatten()
-                                                    .is_some();
-                                                let override_entry =
-                                                    state.get_heartbeat_override(&chat_key).await;
-                                                crate::heartbeat::next_heartbeat_due_for_session(
-                                                    s.updated_at,
-                                                    had_ok_heartbeat,
-                                                    override_entry,
-                                                    4, // default idle minutes for display
-                                                )
-                                                .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
-                                            }
-                                        };
-
-                                        session_infos.push(t_koma_core::message::SessionInfo {
-                                            id: s.id,
-                                            created_at: Utc
-                                                .timestamp_opt(s.created_at, 0)
-                                                .single()
-                                                .unwrap_or_else(|| {
-                                                    Utc.timestamp_opt(0, 0).unwrap()
-                                                }),
-                                            updated_at: Utc
-                                                .timestamp_opt(s.updated_at, 0)
-                                                .single()
-                                                .unwrap_or_else(|| {
-                                                    Utc.timestamp_opt(0, 0).unwrap()
-                                                }),
-                                            next_heartbeat_due: next_due,
-                                            message_count: s.message_count,
-                                            is_active: s.is_active,
-                                        });
-                                    }
-                                    let response = WsResponse::SessionList {
-                                        sessions: session_infos,
-                                    };
+                                Ok(_) => {
+                                    let response = WsResponse::SessionSwitched { session_id };
                                     let _ = sender
                                         .send(Message::Text(
                                             serde_json::to_string(&response).unwrap().into(),
@@ -1432,9 +2838,9 @@ async fn handle_websocket(
                                         .await;
                                 }
                                 Err(e) => {
-                                    error!("Failed to list sessions: {}", e);
+                                    error!("Failed to switch session: {}", e);
                                     let error_response = ws_error_response(render_message(
-                                        ids::FAILED_LIST_SESSIONS,
+                                        ids::FAILED_SWITCH_SESSION,
                                         &[],
                                     ));
                                     let _ = sender
@@ -1445,7 +2851,10 @@ async fn handle_websocket(
                                 }
                             }
                         }
-                        WsMessage::CreateSession { ghost_name } => {
+                        WsMessage::DeleteSession {
+                            ghost_name,
+                            session_id,
+                        } => {
                             if let Err(message) =
                                 ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
                             {
@@ -1492,38 +2901,26 @@ async fn handle_websocket(
                                 }
                             };
 
-                            match t_koma_db::SessionRepository::create(
+                            match t_koma_db::SessionRepository::delete(
                                 state.koma_db.pool(),
                                 &ghost.id,
                                 &op_id,
+                                &session_id,
                             )
                             .await
                             {
-                                Ok(new_session) => {
-                                    let response = WsResponse::SessionCreated {
-                                        session_id: new_session.id.clone(),
-                                    };
+                                Ok(_) => {
+                                    let response = WsResponse::SessionDeleted { session_id };
                                     let _ = sender
                                         .send(Message::Text(
                                             serde_json::to_string(&response).unwrap().into(),
                                         ))
                                         .await;
-                                    let session_started =
-                                        ws_gateway_response(gateway_message::from_content(
-                                            ids::SESSION_STARTED,
-                                            None,
-                                            &[],
-                                        ));
-                                    let _ = sender
-                                        .send(Message::Text(
-                                            serde_json::to_string(&session_started).unwrap().into(),
-                                        ))
-                                        .await;
                                 }
                                 Err(e) => {
-                                    error!("Failed to create session: {}", e);
+                                    error!("Failed to delete session: {}", e);
                                     let error_response = ws_error_response(render_message(
-                                        ids::FAILED_CREATE_SESSION,
+                                        ids::FAILED_DELETE_SESSION,
                                         &[],
                                     ));
                                     let _ = sender
@@ -1534,9 +2931,11 @@ async fn handle_websocket(
                                 }
                             }
                         }
-                        WsMessage::SwitchSession {
+                        WsMessage::SearchSessions {
                             ghost_name,
+                            query,
                             session_id,
+                            max_results,
                         } => {
                             if let Err(message) =
                                 ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
@@ -1584,16 +2983,44 @@ async fn handle_websocket(
                                 }
                             };
 
-                            match t_koma_db::SessionRepository::switch(
+                            let filters = t_koma_db::MessageSearchFilters {
+                                operator_id: Some(op_id.clone()),
+                                session_id,
+                                limit: max_results,
+                                ..Default::default()
+                            };
+                            match t_koma_db::SessionRepository::search_messages(
                                 state.koma_db.pool(),
                                 &ghost.id,
-                                &op_id,
-                                &session_id,
+                                &query,
+                                &filters,
                             )
                             .await
                             {
-                                Ok(_) => {
-                                    let response = WsResponse::SessionSwitched { session_id };
+                                Ok(hits) => {
+                                    let results = hits
+                                        .into_iter()
+                                        .map(|hit| t_koma_core::message::SessionSearchHit {
+                                            session_id: hit.session_id,
+                                            message_id: hit.message_id,
+                                            role: match hit.role {
+                                                t_koma_db::MessageRole::Operator => {
+                                                    t_koma_core::MessageRole::Operator
+                                                }
+                                                t_koma_db::MessageRole::Ghost => {
+                                                    t_koma_core::MessageRole::Ghost
+                                                }
+                                            },
+                                            created_at: Utc
+                                                .timestamp_opt(hit.created_at, 0)
+                                                .single()
+                                                .unwrap_or_else(|| {
+                                                    Utc.timestamp_opt(0, 0).unwrap()
+                                                }),
+                                            snippet: hit.snippet,
+                                        })
+                                        .collect();
+                                    let response = WsResponse::SessionSearchResults { results };
                                     let _ = sender
                                         .send(Message::Text(
                                             serde_json::to_string(&response).unwrap().into(),
@@ -1601,9 +3028,9 @@ async fn handle_websocket(
                                         .await;
                                 }
                                 Err(e) => {
-                                    error!("Failed to switch session: {}", e);
+                                    error!("Failed to search sessions: {}", e);
                                     let error_response = ws_error_response(render_message(
-                                        ids::FAILED_SWITCH_SESSION,
+                                        ids::FAILED_SEARCH_SESSIONS,
                                         &[],
                                     ));
                                     let _ = sender
@@ -1614,9 +3041,84 @@ async fn handle_websocket(
                                 }
                             }
                         }
-                        WsMessage::DeleteSession {
+                        WsMessage::GetSessionMessages {
+                            ghost_name,
+                            session_id,
+                        } => {
+                            let response = session_messages_response(
+                                &state,
+                                &ghost_name,
+                                &session_id,
+                                Some(&op_id),
+                            )
+                            .await;
+                            let _ = sender
+                                .send(Message::Text(
+                                    serde_json::to_string(&response).unwrap().into(),
+                                ))
+                                .await;
+                        }
+                        WsMessage::ListPendingApprovals => {
+                            let response = WsResponse::PendingApprovals {
+                                approvals: state.pending_approvals(&op_id).await,
+                            };
+                            let _ = sender
+                                .send(Message::Text(
+                                    serde_json::to_string(&response).unwrap().into(),
+                                ))
+                                .await;
+                        }
+                        WsMessage::ResolvePendingApproval {
+                            ghost_name,
+                            session_id,
+                            decision,
+                        } => {
+                            // Same path as typing the command in chat.
+                            let command = match decision {
+                                ApprovalDecision::Approve => "approve".to_string(),
+                                ApprovalDecision::Deny => "deny".to_string(),
+                                ApprovalDecision::Steps(steps) => format!("steps {}", steps),
+                            };
+                            let chat_key = format!("{}:{}:{}", op_id, ghost_name, session_id);
+                            let responses = match while_watching_socket(
+                                &state,
+                                &chat_key,
+                                &mut receiver,
+                                &mut queued_frames,
+                                operator_flow::run_tool_control_command(
+                                    state.as_ref(),
+                                    None,
+                                    selected_model_alias.as_deref(),
+                                    &ghost_name,
+                                    &session_id,
+                                    &op_id,
+                                    &command,
+                                ),
+                            )
+                            .await
+                            {
+                                Ok(messages) => messages
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .map(ws_from_outbound)
+                                    .collect(),
+                                Err(e) => {
+                                    error!("Provider API error: {}", e);
+                                    vec![ws_error_response(format!("Chat error: {}", e))]
+                                }
+                            };
+                            for response in responses {
+                                let _ = sender
+                                    .send(Message::Text(
+                                        serde_json::to_string(&response).unwrap().into(),
+                                    ))
+                                    .await;
+                            }
+                        }
+                        WsMessage::ForkSession {
                             ghost_name,
                             session_id,
+                            message_id,
                         } => {
                             if let Err(message) =
                                 ensure_operator_owns_ghost(&state, &op_id, &ghost_name).await
@@ -1664,16 +3166,38 @@ async fn handle_websocket(
                                 }
                             };
 
-                            match t_koma_db::SessionRepository::delete(
+                            let owned = matches!(
+                                t_koma_db::SessionRepository::get_by_id_for_ghost(
+                                    state.koma_db.pool(),
+                                    &session_id,
+                                    &ghost.id,
+                                )
+                                .await,
+                                Ok(Some(ref s)) if s.operator_id == op_id
+                            );
+                            if !owned {
+                                let error_response =
+                                    ws_error_response(render_message(ids::INVALID_SESSION, &[]));
+                                let _ = sender
+                                    .send(Message::Text(
+                                        serde_json::to_string(&error_response).unwrap().into(),
+                                    ))
+                                    .await;
+                                continue;
+                            }
+
+                            match t_koma_db::SessionRepository::fork(
                                 state.koma_db.pool(),
-                                &ghost.id,
-                                &op_id,
                                 &session_id,
+                                &message_id,
                             )
                             .await
                             {
-                                Ok(_) => {
-                                    let response = WsResponse::SessionDeleted { session_id };
+                                Ok(fork) => {
+                                    let response = WsResponse::SessionForked {
+                                        session_id: fork.id,
+                                        source_session_id: session_id,
+                                    };
                                     let _ = sender
                                         .send(Message::Text(
                                             serde_json::to_string(&response).unwrap().into(),
@@ -1681,9 +3205,9 @@ async fn handle_websocket(
                                         .await;
                                 }
                                 Err(e) => {
-                                    error!("Failed to delete session: {}", e);
+                                    error!("Failed to fork session: {}", e);
                                     let error_response = ws_error_response(render_message(
-                                        ids::FAILED_DELETE_SESSION,
+                                        ids::FAILED_FORK_SESSION,
                                         &[],
                                     ));
                                     let _ = sender
@@ -1710,6 +3234,7 @@ async fn handle_websocket(
                     .log(LogEntry::WebSocket {
                         event: "disconnected".to_string(),
                         client_id: client_id.clone(),
+                        identity: identity.clone(),
                     })
                     .await;
                 break;
@@ -1721,12 +3246,26 @@ async fn handle_websocket(
     info!("WebSocket connection closed: {}", client_id);
 }
 /// Handle logs WebSocket connection - streams log entries to client
-async fn handle_logs_websocket(socket: axum::extract::ws::WebSocket, state: Arc<AppState>) {
+async fn handle_logs_websocket(
+    socket: axum::extract::ws::WebSocket,
+    state: Arc<AppState>,
+    identity: String,
+) {
     use axum::extract::ws::Message;
     use futures::{sink::SinkExt, stream::StreamExt};
 
     let client_id = format!("log_client_{}", uuid::Uuid::new_v4());
-    info!("Log WebSocket client connected: {}", client_id);
+    info!(
+        "Log WebSocket client connected: {} ({})",
+        client_id, identity
+    );
+    state
+        .log(LogEntry::WebSocket {
+            event: "connected".to_string(),
+            client_id: client_id.clone(),
+            identity: identity.clone(),
+        })
+        .await;
 
     let (mut sender, mut receiver) = socket.split();
 
@@ -1773,4 +3312,214 @@ async fn handle_logs_websocket(socket: axum::extract::ws::WebSocket, state: Arc<
     }
 
     info!("Log WebSocket client disconnected: {}", client_id);
+    state
+        .log(LogEntry::WebSocket {
+            event: "disconnected".to_string(),
+            client_id,
+            identity,
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn loopback() -> SocketAddr {
+        "127.0.0.1:50000".parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_origin_allowed_only_for_own_origin() {
+        assert!(origin_allowed(&headers(&[("host", "127.0.0.1:3000")])));
+        assert!(origin_allowed(&headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "http://127.0.0.1:3000"),
+        ])));
+        assert!(origin_allowed(&headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("x-forwarded-host", "koma.example.com"),
+            ("origin", "https://koma.example.com"),
+        ])));
+        assert!(!origin_allowed(&headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "https://evil.example"),
+        ])));
+        assert!(!origin_allowed(&headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "http://127.0.0.1:8080"),
+        ])));
+        assert!(!origin_allowed(&headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "null"),
+        ])));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_ws_refuses_cross_origin_loopback() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        let request = headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "https://evil.example"),
+        ]);
+
+        let result = authenticate_ws(&state, loopback(), &request, Some("cli".to_string())).await;
+        assert!(matches!(result, Err((StatusCode::FORBIDDEN, _))));
+
+        let request = headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("origin", "http://127.0.0.1:3000"),
+        ]);
+        let result = authenticate_ws(&state, loopback(), &request, Some("cli".to_string())).await;
+        assert!(matches!(
+            result,
+            Ok(WsAuth::Local {
+                via_secret: false,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_ws_refuses_rebound_host_on_loopback() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+
+        // A DNS-rebound page: Origin matches Host, but Host is not loopback.
+        let request = headers(&[
+            ("host", "evil.example:3000"),
+            ("origin", "http://evil.example:3000"),
+        ]);
+        let result = authenticate_ws(&state, loopback(), &request, Some("cli".to_string())).await;
+        assert!(matches!(result, Err((StatusCode::UNAUTHORIZED, _))));
+
+        let result = authenticate_ws(&state, loopback(), &HeaderMap::new(), None).await;
+        assert!(matches!(result, Err((StatusCode::UNAUTHORIZED, _))));
+
+        for host in ["localhost:3000", "127.0.0.1", "[::1]:3000"] {
+            let request = headers(&[("host", host)]);
+            let result = authenticate_ws(&state, loopback(), &request, None).await;
+            assert!(result.is_ok(), "{host} should keep loopback trust");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_ws_requires_token_behind_proxy() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+
+        for proxy_header in FORWARDED_HEADERS {
+            let mut request = headers(&[("host", "127.0.0.1:3000")]);
+            request.insert(*proxy_header, HeaderValue::from_static("203.0.113.7"));
+            let result =
+                authenticate_ws(&state, loopback(), &request, Some("cli".to_string())).await;
+            assert!(
+                matches!(result, Err((StatusCode::UNAUTHORIZED, _))),
+                "{} should disable loopback trust",
+                proxy_header
+            );
+        }
+
+        let request = headers(&[("host", "127.0.0.1:3000")]);
+        let result = authenticate_ws(&state, loopback(), &request, Some("cli".to_string())).await;
+        assert!(result.is_ok());
+    }
+
+    /// Serve the full router on an ephemeral port, with every request
+    /// appearing to come from a remote (non-loopback) peer.
+    async fn serve_as_remote_peer(state: Arc<AppState>) -> SocketAddr {
+        let remote: SocketAddr = "203.0.113.7:50000".parse().unwrap();
+        let app = create_router(state).layer(axum::extract::connect_info::MockConnectInfo(remote));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app.into_make_service()).await });
+        addr
+    }
+
+    /// Open a WebSocket to `path` with `secret` as the bearer token; the HTTP
+    /// status is returned when the upgrade is refused.
+    async fn connect_with_secret(
+        addr: SocketAddr,
+        path: &str,
+        secret: &str,
+    ) -> Result<(), StatusCode> {
+        use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+
+        let mut request = format!("ws://{}{}", addr, path)
+            .into_client_request()
+            .unwrap();
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", secret)).unwrap(),
+        );
+        match tokio_tungstenite::connect_async(request).await {
+            Ok(_) => Ok(()),
+            Err(tungstenite::Error::Http(response)) => {
+                Err(StatusCode::from_u16(response.status().as_u16()).unwrap())
+            }
+            Err(err) => panic!("unexpected handshake error: {err}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gateway_secret_accepted_from_remote_peer() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        state
+            .set_gateway_secret(Some("s3cret-gateway".to_string()))
+            .await;
+        let mut logs = state.subscribe_logs();
+        let addr = serve_as_remote_peer(Arc::clone(&state)).await;
+
+        for path in ["/ws", "/logs"] {
+            connect_with_secret(addr, path, "s3cret-gateway")
+                .await
+                .unwrap_or_else(|status| panic!("{path} refused the secret: {status}"));
+            let entry = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+                loop {
+                    if let LogEntry::WebSocket {
+                        event, identity, ..
+                    } = logs.recv().await.unwrap()
+                        && event == "connected"
+                    {
+                        return identity;
+                    }
+                }
+            })
+            .await
+            .expect("connection was logged");
+            assert_eq!(entry, "secret", "{path} logged the wrong identity");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gateway_secret_rejects_wrong_or_unset_secret() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = crate::state::test_app_state(db).await;
+        let addr = serve_as_remote_peer(Arc::clone(&state)).await;
+
+        // No secret configured: presenting one is just an unknown API token.
+        for path in ["/ws", "/logs"] {
+            let result = connect_with_secret(addr, path, "s3cret-gateway").await;
+            assert_eq!(result, Err(StatusCode::UNAUTHORIZED), "{path}");
+        }
+
+        state
+            .set_gateway_secret(Some("s3cret-gateway".to_string()))
+            .await;
+        for path in ["/ws", "/logs"] {
+            let result = connect_with_secret(addr, path, "s3cret-gatewaz").await;
+            assert_eq!(result, Err(StatusCode::UNAUTHORIZED), "{path}");
+        }
+    }
 }
@@ -2,28 +2,34 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use chrono::NaiveDate;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
-use crate::chat::compaction::{CompactionConfig, compact_if_needed, mask_tool_results};
-use crate::chat::history::{
-    ChatContentBlock, ChatMessage, ChatRole, build_history_messages, build_transcript_messages,
+use crate::chat::compaction::{
+    CompactionConfig, compact_if_needed, mask_tool_results, summary_block,
 };
+use crate::chat::history::{ChatMessage, build_history_messages, build_transcript_messages};
+use crate::chat::output_filter::OutputFilters;
 use crate::chat::prompt_cache::{PromptCacheManager, hash_context};
+use crate::chat::thinking::render_reply_with_thinking;
+use crate::chat::token_budget::{RequestEstimate, compute_budget};
 use crate::prompt::SystemPrompt;
 use crate::prompt::render::{SystemBlock, build_system_prompt};
 use crate::providers::provider::{
-    Provider, ProviderContentBlock, ProviderError, ProviderResponse, extract_all_text,
-    has_tool_uses,
+    ImageSource, Provider, ProviderContentBlock, ProviderError, ProviderResponse, ProviderUsage,
+    extract_all_text, extract_thinking, has_tool_uses,
 };
-use crate::state::{ChatUsage, ToolCallSummary};
+use crate::state::{ChatUsage, LogEntry, ToolCallSummary, emit_global_log};
 use crate::system_info;
 use crate::tools::context::{ApprovalReason, is_within_workspace};
 use crate::tools::{JobHandle, ToolContext, ToolManager};
 use serde_json::Value;
-use t_koma_core::CronPreToolCall;
+use t_koma_core::config::ToolsSettings;
+use t_koma_core::{CronPreToolCall, ThinkingDisplay};
 use t_koma_db::{
-    ContentBlock as DbContentBlock, GhostRepository, KomaDbPool, MessageRole, OperatorRepository,
-    Session, SessionRepository, TokenUsage, TranscriptEntry, UsageLog, UsageLogRepository,
+    ContentBlock as DbContentBlock, EventKind, EventRepository, GhostRepository, KomaDbPool,
+    MessageRole, NewEvent, OperatorRepository, PathApprovalRepository, Session, SessionRepository,
+    TokenUsage, ToolPolicyRepository, TranscriptEntry, UsageLog, UsageLogRepository,
     ghosts::ghost_workspace_path,
 };
 
@@ -53,6 +59,22 @@ pub enum ChatError {
 
     #[error("All models in fallback chain exhausted")]
     AllModelsExhausted,
+
+    #[error("Usage budget exceeded for {} {}", .0.scope, .0.subject_id)]
+    OverBudget(t_koma_db::BudgetReport),
+
+    #[error(
+        "Conversation needs ~{prompt_tokens} tokens but {model} has a {context_window}-token \
+         context window; start a new session or send less content"
+    )]
+    ContextWindowExceeded {
+        model: String,
+        prompt_tokens: u32,
+        context_window: u32,
+    },
+
+    #[error("Generation stopped by operator")]
+    Cancelled,
 }
 
 #[derive(Debug, Clone)]
@@ -116,10 +138,14 @@ pub struct SessionChat {
     pub(crate) tool_manager: ToolManager,
     knowledge_engine: Option<Arc<t_koma_knowledge::KnowledgeEngine>>,
     prompt_cache: PromptCacheManager,
-    compaction_config: CompactionConfig,
+    compaction_config: std::sync::RwLock<CompactionConfig>,
+    compaction_summarizer: std::sync::RwLock<Option<Arc<dyn Provider>>>,
     system_info: String,
     skill_paths: Vec<std::path::PathBuf>,
     dump_queries: bool,
+    thinking_display: std::sync::RwLock<ThinkingDisplay>,
+    tool_settings: std::sync::RwLock<ToolsSettings>,
+    output_filters: std::sync::RwLock<Arc<OutputFilters>>,
 }
 
 async fn load_recent_active_diary_entries(
@@ -349,12 +375,31 @@ pub struct JobChatResult {
 }
 
 /// Convert provider response blocks to DB content blocks.
-fn provider_to_db_blocks(response: &ProviderResponse) -> Vec<DbContentBlock> {
+///
+/// Generated images are stored by reference (their URL, or a `data:` URL for
+/// inline bytes) rather than written to the workspace.
+pub(crate) fn provider_to_db_blocks(response: &ProviderResponse) -> Vec<DbContentBlock> {
     response
         .content
         .iter()
         .map(|block| match block {
             ProviderContentBlock::Text { text } => DbContentBlock::Text { text: text.clone() },
+            ProviderContentBlock::Image { source } => {
+                let (mime_type, filename) = match source {
+                    ImageSource::Base64 { media_type, .. } => {
+                        (media_type.clone(), "generated-image".to_string())
+                    }
+                    ImageSource::Url { url } => (
+                        crate::chat::history::mime_from_path(url).to_string(),
+                        url.rsplit('/').next().unwrap_or(url).to_string(),
+                    ),
+                };
+                DbContentBlock::Image {
+                    path: source.to_url(),
+                    mime_type,
+                    filename,
+                }
+            }
             ProviderContentBlock::ToolUse { id, name, input } => DbContentBlock::ToolUse {
                 id: id.clone(),
                 name: name.clone(),
@@ -369,10 +414,27 @@ fn provider_to_db_blocks(response: &ProviderResponse) -> Vec<DbContentBlock> {
                 content: content.clone(),
                 is_error: *is_error,
             },
+            ProviderContentBlock::Thinking {
+                thinking,
+                signature,
+                redacted,
+            } => DbContentBlock::Thinking {
+                thinking: thinking.clone(),
+                signature: signature.clone(),
+                redacted: *redacted,
+            },
         })
         .collect()
 }
 
+/// Thinking blocks of a response, for storing next to its final text.
+fn thinking_db_blocks(response: &ProviderResponse) -> Vec<DbContentBlock> {
+    provider_to_db_blocks(response)
+        .into_iter()
+        .filter(|block| matches!(block, DbContentBlock::Thinking { .. }))
+        .collect()
+}
+
 impl SessionChat {
     /// Create a new SessionChat instance.
     ///
@@ -388,19 +450,123 @@ impl SessionChat {
             tool_manager: ToolManager::new_chat(skill_paths.clone()),
             knowledge_engine,
             prompt_cache: PromptCacheManager::new(),
-            compaction_config,
+            compaction_config: std::sync::RwLock::new(compaction_config),
+            compaction_summarizer: std::sync::RwLock::new(None),
             system_info: system_info::build_system_info(),
             skill_paths,
             dump_queries: false,
+            thinking_display: std::sync::RwLock::new(ThinkingDisplay::default()),
+            tool_settings: std::sync::RwLock::new(ToolsSettings::default()),
+            output_filters: std::sync::RwLock::new(Arc::new(OutputFilters::default())),
         }
     }
 
+    /// Session system-prompt cache.
+    pub fn prompt_cache(&self) -> &PromptCacheManager {
+        &self.prompt_cache
+    }
+
     /// Enable writing empty-response debug logs (gated by `dump_queries` config).
     pub fn with_dump_queries(mut self, enabled: bool) -> Self {
         self.dump_queries = enabled;
         self
     }
 
+    /// Set how much of the model's thinking chat replies include.
+    pub fn set_thinking_display(&self, display: ThinkingDisplay) {
+        *self
+            .thinking_display
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = display;
+    }
+
+    fn thinking_display(&self) -> ThinkingDisplay {
+        *self
+            .thinking_display
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the tool settings (time budgets, shell limits) applied to tool calls.
+    pub fn set_tool_settings(&self, settings: ToolsSettings) {
+        *self
+            .tool_settings
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    fn tool_settings(&self) -> ToolsSettings {
+        self.tool_settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Set when and how history is compacted.
+    pub fn set_compaction_config(&self, config: CompactionConfig) {
+        *self
+            .compaction_config
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    fn compaction_config(&self) -> CompactionConfig {
+        self.compaction_config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Set the model that writes compaction summaries (`None` uses the
+    /// chat model).
+    pub fn set_compaction_summarizer(&self, summarizer: Option<Arc<dyn Provider>>) {
+        *self
+            .compaction_summarizer
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = summarizer;
+    }
+
+    fn compaction_summarizer(&self) -> Option<Arc<dyn Provider>> {
+        self.compaction_summarizer
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the redaction/moderation filters applied to final replies.
+    pub fn set_output_filters(&self, filters: OutputFilters) {
+        *self
+            .output_filters
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::new(filters);
+    }
+
+    /// Run `text` through the output filters of the ghost `ghost_id`.
+    async fn filter_output(&self, pool: &KomaDbPool, ghost_id: &str, text: String) -> String {
+        let filters = Arc::clone(
+            &self
+                .output_filters
+                .read()
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        if filters.is_empty() {
+            return text;
+        }
+        let ghost_name = if filters.has_ghost_overrides() {
+            GhostRepository::get_by_id(pool.pool(), ghost_id)
+                .await
+                .ok()
+                .flatten()
+                .map(|ghost| ghost.name)
+        } else {
+            None
+        };
+        filters
+            .for_ghost(ghost_name.as_deref().unwrap_or_default())
+            .run(text)
+            .await
+    }
+
     /// Skill search paths (for constructing alternate ToolManagers).
     pub fn skill_paths(&self) -> &[std::path::PathBuf] {
         &self.skill_paths
@@ -448,6 +614,7 @@ impl SessionChat {
         tool_call_tx: Option<&tokio::sync::mpsc::UnboundedSender<Vec<ToolCallSummary>>>,
         retry_on_empty: u32,
         model_info: &str,
+        cancel: &CancellationToken,
     ) -> Result<(String, Vec<ToolCallSummary>, ChatUsage), ChatError> {
         // Verify session exists and belongs to operator
         let session = SessionRepository::get_by_id(pool.pool(), session_id)
@@ -481,7 +648,7 @@ impl SessionChat {
         }
 
         // Build system prompt with ghost context (cached for 5 min)
-        let system_blocks = self
+        let mut system_blocks = self
             .build_cached_system_blocks(pool, ghost_id, session_id, model_info)
             .await?;
 
@@ -493,7 +660,7 @@ impl SessionChat {
                 model,
                 context_window_override,
                 &session,
-                &system_blocks,
+                &mut system_blocks,
             )
             .await?;
 
@@ -514,6 +681,7 @@ impl SessionChat {
             tool_call_tx,
             retry_on_empty,
             &self.tool_manager,
+            cancel,
         )
         .await
     }
@@ -567,7 +735,7 @@ impl SessionChat {
         );
 
         // Build system prompt (cached for 5 min)
-        let system_blocks = self
+        let mut system_blocks = self
             .build_cached_system_blocks(pool, ghost_id, session_id, model_info)
             .await?;
 
@@ -579,7 +747,7 @@ impl SessionChat {
                 model,
                 context_window_override,
                 &session,
-                &system_blocks,
+                &mut system_blocks,
             )
             .await?
         } else {
@@ -703,6 +871,7 @@ impl SessionChat {
             .load_tool_context(pool, ghost_id, operator_id, model)
             .await?;
         tool_context.job_handle = job_handle;
+        Self::publish_job_transcript(&tool_context, session_id, transcript, 0).await;
 
         for iteration in 0..max_iterations {
             if !has_tool_uses(&response) {
@@ -721,6 +890,13 @@ impl SessionChat {
                 content: provider_to_db_blocks(&response),
                 model: Some(model.to_string()),
             });
+            Self::publish_job_transcript(
+                &tool_context,
+                session_id,
+                transcript,
+                transcript.len() - 1,
+            )
+            .await;
 
             if iteration + 1 == max_iterations {
                 return Err(ChatError::ToolLoopLimitReached(PendingToolContinuation {
@@ -750,6 +926,13 @@ impl SessionChat {
                 content: tool_results,
                 model: None,
             });
+            Self::publish_job_transcript(
+                &tool_context,
+                session_id,
+                transcript,
+                transcript.len() - 1,
+            )
+            .await;
 
             // Rebuild API messages and re-send
             let mut api_messages: Vec<ChatMessage> = session_history.to_vec();
@@ -774,6 +957,7 @@ impl SessionChat {
             let text = extract_all_text(&response);
             let text = text.trim().to_string();
             if !text.is_empty() {
+                let text = self.filter_output(pool, ghost_id, text).await;
                 info!(
                     event_kind = "chat_io",
                     "[session:{session_id}] Job final response ({provider_name} / {model}): {}",
@@ -783,11 +967,20 @@ impl SessionChat {
                         &text
                     }
                 );
+                let mut content = thinking_db_blocks(&response);
+                content.push(DbContentBlock::Text { text: text.clone() });
                 transcript.push(TranscriptEntry {
                     role: MessageRole::Ghost,
-                    content: vec![DbContentBlock::Text { text: text.clone() }],
+                    content,
                     model: Some(model.to_string()),
                 });
+                Self::publish_job_transcript(
+                    &tool_context,
+                    session_id,
+                    transcript,
+                    transcript.len() - 1,
+                )
+                .await;
                 return Ok(text);
             }
 
@@ -834,6 +1027,10 @@ impl SessionChat {
     ///
     /// If `tool_call_tx` is provided, tool call summaries are sent incrementally
     /// after each iteration instead of only being returned at the end.
+    ///
+    /// Cancelling `cancel` drops the in-flight provider request or tool batch
+    /// and returns `ChatError::Cancelled`; tool uses left without a result are
+    /// answered with error results so the session history stays valid.
     #[allow(clippy::too_many_arguments)]
     async fn send_with_tool_loop(
         &self,
@@ -852,6 +1049,7 @@ impl SessionChat {
         tool_call_tx: Option<&tokio::sync::mpsc::UnboundedSender<Vec<ToolCallSummary>>>,
         retry_on_empty: u32,
         tool_manager: &ToolManager,
+        cancel: &CancellationToken,
     ) -> Result<(String, Vec<ToolCallSummary>, ChatUsage), ChatError> {
         let tools = tool_manager.get_tools();
         let mut tool_call_log: Vec<ToolCallSummary> = Vec::new();
@@ -859,17 +1057,29 @@ impl SessionChat {
         let mut usage = ChatUsage::default();
 
         // Initial request to the provider
-        let mut response = provider
-            .send_conversation(
+        self.preflight(
+            pool,
+            session_id,
+            provider_name,
+            model,
+            context_window_override,
+            &system_blocks,
+            &tools,
+            &api_messages,
+        )
+        .await?;
+        let mut response = until_cancelled(
+            cancel,
+            provider.send_conversation(
                 Some(system_blocks.clone()),
                 api_messages.clone(),
                 tools.clone(),
                 new_message,
                 None,
                 None,
-            )
-            .await
-            .map_err(ChatError::Provider)?;
+            ),
+        )
+        .await?;
         Self::log_usage(pool, ghost_id, session_id, model, &response).await;
         usage.accumulate(&response);
 
@@ -905,8 +1115,10 @@ impl SessionChat {
             // Execute tools and get results
             let tool_uses = collect_pending_tool_uses(&response);
             let mut tool_results_buf = Vec::new();
-            let tool_results = match self
-                .execute_tool_uses_with(
+            let executed = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => Err(ChatError::Cancelled),
+                result = self.execute_tool_uses_with(
                     session_id,
                     &tool_uses,
                     pool,
@@ -914,14 +1126,19 @@ impl SessionChat {
                     &mut tool_results_buf,
                     &mut tool_call_log,
                     tool_manager,
-                )
-                .await
-                .map(|()| tool_results_buf)
-            {
-                Ok(results) => results,
+                ) => result,
+            };
+            let tool_results = match executed {
+                Ok(()) => tool_results_buf,
                 Err(ChatError::ToolLoopLimitReached(pending)) => {
                     return Err(ChatError::ToolLoopLimitReached(pending));
                 }
+                Err(ChatError::Cancelled) => {
+                    let results = with_cancelled_results(tool_results_buf, &tool_uses);
+                    self.save_tool_results(pool, ghost_id, session_id, &results)
+                        .await?;
+                    return Err(ChatError::Cancelled);
+                }
                 Err(e) => return Err(e),
             };
 
@@ -951,17 +1168,29 @@ impl SessionChat {
             );
 
             // Send tool results back to the provider
-            response = provider
-                .send_conversation(
+            self.preflight(
+                pool,
+                session_id,
+                provider_name,
+                model,
+                context_window_override,
+                &system_blocks,
+                &tools,
+                &new_api_messages,
+            )
+            .await?;
+            response = until_cancelled(
+                cancel,
+                provider.send_conversation(
                     Some(system_blocks.clone()),
                     new_api_messages,
                     tools.clone(),
                     None,
                     None,
                     None,
-                )
-                .await
-                .map_err(ChatError::Provider)?;
+                ),
+            )
+            .await?;
             Self::log_usage(pool, ghost_id, session_id, model, &response).await;
             usage.accumulate(&response);
         }
@@ -989,17 +1218,29 @@ impl SessionChat {
                         &tool_refs,
                         raw_messages,
                     );
-                    response = provider
-                        .send_conversation(
+                    self.preflight(
+                        pool,
+                        session_id,
+                        provider_name,
+                        model,
+                        context_window_override,
+                        &system_blocks,
+                        &tools,
+                        &retry_messages,
+                    )
+                    .await?;
+                    response = until_cancelled(
+                        cancel,
+                        provider.send_conversation(
                             Some(system_blocks.clone()),
                             retry_messages,
                             tools.clone(),
                             None,
                             None,
                             None,
-                        )
-                        .await
-                        .map_err(ChatError::Provider)?;
+                        ),
+                    )
+                    .await?;
                     Self::log_usage(pool, ghost_id, session_id, model, &response).await;
                     usage.accumulate(&response);
                 }
@@ -1024,13 +1265,14 @@ impl SessionChat {
             .await?
             .ok_or(ChatError::SessionNotFound)?;
 
-        SessionRepository::add_message(
+        SessionRepository::add_message_with_usage(
             pool.pool(),
             &session.ghost_id,
             session_id,
             MessageRole::Ghost,
             ghost_content,
             Some(model),
+            response.usage.as_ref().map(token_usage),
         )
         .await?;
         Ok(())
@@ -1068,6 +1310,7 @@ impl SessionChat {
         tool_call_log: &mut Vec<ToolCallSummary>,
         tool_manager: &ToolManager,
     ) -> Result<(), ChatError> {
+        tool_context.set_session_id(session_id);
         for (index, tool_use) in tool_uses.iter().enumerate() {
             info!(
                 "[session:{}] Executing tool: {} (id: {})",
@@ -1099,6 +1342,7 @@ impl SessionChat {
                 input_preview,
                 output_preview: truncate_preview(&content, 100),
                 is_error,
+                output: (tool_use.name == "knowledge_search" && !is_error).then(|| content.clone()),
             });
 
             tool_results.push(DbContentBlock::ToolResult {
@@ -1128,11 +1372,32 @@ impl SessionChat {
         decision: ToolApprovalDecision,
         retry_on_empty: u32,
         model_info: &str,
+        cancel: &CancellationToken,
     ) -> Result<String, ChatError> {
         let mut tool_context = self
             .load_tool_context(pool, ghost_id, operator_id, model)
             .await?;
 
+        let kind = match decision {
+            ToolApprovalDecision::Approve => EventKind::ToolApproved,
+            ToolApprovalDecision::Deny => EventKind::ToolDenied,
+        };
+        let tools: Vec<&str> = pending
+            .pending_tool_uses
+            .iter()
+            .map(|tool_use| tool_use.name.as_str())
+            .collect();
+        let mut data = pending.reason.to_json();
+        data["session_id"] = session_id.into();
+        data["tools"] = tools.into();
+        EventRepository::record(
+            pool.pool(),
+            &NewEvent::new(kind, ghost_id)
+                .with_actor(Some(operator_id))
+                .with_data(data),
+        )
+        .await?;
+
         let mut tool_results = pending.completed_results;
         match decision {
             ToolApprovalDecision::Approve => {
@@ -1181,6 +1446,7 @@ impl SessionChat {
             DEFAULT_TOOL_LOOP_LIMIT,
             retry_on_empty,
             model_info,
+            cancel,
         )
         .await
     }
@@ -1200,6 +1466,7 @@ impl SessionChat {
         extra_iterations: usize,
         retry_on_empty: u32,
         model_info: &str,
+        cancel: &CancellationToken,
     ) -> Result<String, ChatError> {
         let mut tool_context = self
             .load_tool_context(pool, ghost_id, operator_id, model)
@@ -1231,6 +1498,7 @@ impl SessionChat {
             extra_iterations,
             retry_on_empty,
             model_info,
+            cancel,
         )
         .await
     }
@@ -1249,12 +1517,13 @@ impl SessionChat {
         max_iterations: usize,
         retry_on_empty: u32,
         model_info: &str,
+        cancel: &CancellationToken,
     ) -> Result<String, ChatError> {
         let session = SessionRepository::get_by_id(pool.pool(), session_id)
             .await?
             .ok_or(ChatError::SessionNotFound)?;
 
-        let system_blocks = self
+        let mut system_blocks = self
             .build_cached_system_blocks(pool, ghost_id, session_id, model_info)
             .await?;
 
@@ -1265,7 +1534,7 @@ impl SessionChat {
                 model,
                 context_window_override,
                 &session,
-                &system_blocks,
+                &mut system_blocks,
             )
             .await?;
 
@@ -1286,6 +1555,7 @@ impl SessionChat {
                 None,
                 retry_on_empty,
                 &self.tool_manager,
+                cancel,
             )
             .await?;
         Ok(text)
@@ -1318,10 +1588,21 @@ impl SessionChat {
 
         let mut context = ToolContext::new(ghost_name, workspace_root.clone(), cwd, false);
         context.set_model_id(model.to_string());
+        context.set_koma_scope(pool.pool().clone(), ghost_id);
         let operator = OperatorRepository::get_by_id(pool.pool(), operator_id)
             .await?
             .ok_or_else(|| t_koma_db::DbError::OperatorNotFound(operator_id.to_string()))?;
         context.set_operator_access_level(operator.access_level);
+        context
+            .set_permissions(OperatorRepository::get_permissions(pool.pool(), operator_id).await?);
+        context.set_tool_policies(
+            ToolPolicyRepository::for_session(pool.pool(), operator_id, ghost_id).await?,
+        );
+        context.set_path_approvals(PathApprovalRepository::list(pool.pool(), operator_id).await?);
+        let tool_settings = self.tool_settings();
+        context.set_tool_timeouts(tool_settings.timeouts);
+        context.set_shell_settings(tool_settings.shell);
+        context.set_output_settings(tool_settings.output);
         let allow_escape = operator.access_level == t_koma_db::OperatorAccessLevel::PuppetMaster
             || operator.allow_workspace_escape;
         context.set_allow_workspace_escape(allow_escape);
@@ -1363,11 +1644,12 @@ impl SessionChat {
     /// Load history messages with compaction awareness.
     ///
     /// If the session already has a compaction summary from a previous run,
-    /// loads only messages after the cursor and prepends the summary as a
-    /// synthetic user message. Then runs `compact_if_needed()` to handle
-    /// any further growth since the last compaction.
+    /// loads only messages after the cursor and pins the summary as the last
+    /// system block. Then runs `compact_if_needed()` to handle any further
+    /// growth since the last compaction.
     ///
-    /// Persists new compaction state to the DB if Phase 2 ran.
+    /// Persists new compaction state to the DB (and replaces the pinned
+    /// block) if Phase 2 ran.
     #[allow(clippy::too_many_arguments)]
     async fn load_compacted_history(
         &self,
@@ -1376,7 +1658,7 @@ impl SessionChat {
         model: &str,
         context_window_override: Option<u32>,
         session: &Session,
-        system_blocks: &[SystemBlock],
+        system_blocks: &mut Vec<SystemBlock>,
     ) -> Result<Vec<ChatMessage>, ChatError> {
         // Load messages: if we have a compaction cursor, load only newer messages
         let raw_messages = if let Some(cursor_id) = &session.compaction_cursor_id {
@@ -1385,86 +1667,87 @@ impl SessionChat {
             SessionRepository::get_messages(pool.pool(), &session.id).await?
         };
 
-        let mut api_messages = build_history_messages(&raw_messages, None);
+        let api_messages = build_history_messages(&raw_messages, None);
 
-        // Prepend existing compaction summary as a synthetic user message
-        if let Some(summary) = &session.compaction_summary {
-            let summary_msg = ChatMessage {
-                role: ChatRole::User,
-                content: vec![ChatContentBlock::Text {
-                    text: format!(
-                        "[Conversation summary — earlier messages compacted]\n\n{summary}"
-                    ),
-                    cache_control: None,
-                }],
-            };
-            api_messages.insert(0, summary_msg);
+        // Pin the existing compaction summary after the system prompt
+        let previous_summary = session.compaction_summary.as_deref();
+        if let Some(summary) = previous_summary {
+            system_blocks.push(summary_block(summary));
         }
 
-        // Run compaction if context budget is exceeded
+        // Run compaction if context budget is exceeded. The last exchange's
+        // reported usage corrects the estimate when the model counts higher.
         let tools = self.tool_manager.get_tools();
         let tool_refs: Vec<&dyn crate::tools::Tool> = tools.to_vec();
-
-        if let Some(result) = compact_if_needed(
+        let observed_context_tokens = raw_messages
+            .iter()
+            .rev()
+            .find_map(|m| m.usage.as_ref())
+            .map(|usage| usage.context_tokens());
+        let summarizer = self.compaction_summarizer();
+
+        let Some(result) = compact_if_needed(
             model,
             context_window_override,
             system_blocks,
             &tool_refs,
             &api_messages,
-            &self.compaction_config,
-            provider,
+            observed_context_tokens,
+            previous_summary,
+            &self.compaction_config(),
+            summarizer.as_deref().unwrap_or(provider),
         )
         .await
-        {
-            // If Phase 2 (LLM summarization) produced a summary, persist it.
-            // The cursor must point to the last raw message that was *summarized*,
-            // not the last message overall. Messages after the cursor are kept
-            // verbatim and loaded on the next request.
-            if let Some(ref summary) = result.summary {
-                let has_synthetic_prefix = session.compaction_summary.is_some();
-                let raw_summarized = if has_synthetic_prefix {
-                    // The synthetic summary prefix occupies one slot in compacted_count
-                    result.compacted_count.saturating_sub(1)
-                } else {
-                    result.compacted_count
-                };
+        else {
+            return Ok(api_messages);
+        };
 
-                if raw_summarized == 0 || raw_summarized > raw_messages.len() {
-                    warn!(
-                        session_id = session.id,
-                        compacted_count = result.compacted_count,
-                        raw_len = raw_messages.len(),
-                        has_synthetic_prefix,
-                        "Unexpected compaction count — skipping cursor update"
-                    );
-                } else if let Err(e) = SessionRepository::update_compaction(
-                    pool.pool(),
-                    &session.id,
-                    summary,
-                    &raw_messages[raw_summarized - 1].id,
-                )
-                .await
-                {
-                    warn!(
-                        session_id = session.id,
-                        error = %e,
-                        "Failed to persist compaction state"
-                    );
-                } else {
-                    info!(
-                        session_id = session.id,
-                        compacted_count = result.compacted_count,
-                        masked = result.masked,
-                        summarized = result.summarized,
-                        "Compaction state persisted"
-                    );
-                }
+        // If Phase 2 (LLM summarization) produced a summary, persist it and
+        // archive the compaction. The cursor must point to the last raw message
+        // that was *summarized*, not the last message overall. Messages after
+        // the cursor are kept verbatim and loaded on the next request.
+        if let Some(summary) = &result.summary {
+            let raw_summarized = result.compacted_count;
+            let rendered = summary.render();
+            if raw_summarized == 0 || raw_summarized > raw_messages.len() {
+                warn!(
+                    session_id = session.id,
+                    compacted_count = result.compacted_count,
+                    raw_len = raw_messages.len(),
+                    "Unexpected compaction count — skipping cursor update"
+                );
+            } else if let Err(e) = SessionRepository::record_compaction(
+                pool.pool(),
+                &session.id,
+                &rendered,
+                serde_json::to_string(summary).ok().as_deref(),
+                &raw_messages[raw_summarized - 1].id,
+                raw_summarized as i64,
+            )
+            .await
+            {
+                warn!(
+                    session_id = session.id,
+                    error = %e,
+                    "Failed to persist compaction state"
+                );
+            } else {
+                info!(
+                    session_id = session.id,
+                    compacted_count = result.compacted_count,
+                    masked = result.masked,
+                    summarized = result.summarized,
+                    "Compaction state persisted"
+                );
             }
 
-            Ok(result.messages)
-        } else {
-            Ok(api_messages)
+            if previous_summary.is_some() {
+                system_blocks.pop();
+            }
+            system_blocks.push(summary_block(&rendered));
         }
+
+        Ok(result.messages)
     }
 
     /// Apply Phase 1 masking only (no LLM calls) during tool loop iterations.
@@ -1479,24 +1762,81 @@ impl SessionChat {
         tools: &[&dyn crate::tools::Tool],
         messages: Vec<ChatMessage>,
     ) -> Vec<ChatMessage> {
-        use crate::chat::token_budget::compute_budget;
-
         let budget = compute_budget(
             model,
             context_window_override,
             system_blocks,
             tools,
             &messages,
-            self.compaction_config.threshold,
+            self.compaction_config().threshold,
         );
 
         if budget.needs_compaction {
-            mask_tool_results(&messages, &self.compaction_config)
+            mask_tool_results(&messages, &self.compaction_config())
         } else {
             messages
         }
     }
 
+    /// Estimate a chat request before dispatch.
+    ///
+    /// Broadcasts the prompt size and cost as a `LogEntry::RequestEstimate`
+    /// and refuses requests whose prompt alone overflows the model's context
+    /// window, instead of letting the provider reject them.
+    #[allow(clippy::too_many_arguments)]
+    async fn preflight(
+        &self,
+        pool: &KomaDbPool,
+        session_id: &str,
+        provider_name: &str,
+        model: &str,
+        context_window_override: Option<u32>,
+        system_blocks: &[SystemBlock],
+        tools: &[&dyn crate::tools::Tool],
+        messages: &[ChatMessage],
+    ) -> Result<RequestEstimate, ChatError> {
+        let budget = compute_budget(
+            model,
+            context_window_override,
+            system_blocks,
+            tools,
+            messages,
+            self.compaction_config().threshold,
+        );
+        let pricing = UsageLogRepository::get_pricing(pool.pool(), model)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load pricing for {model}: {e}");
+                None
+            });
+        let estimate = RequestEstimate::new(&budget, pricing.as_ref());
+
+        emit_global_log(LogEntry::RequestEstimate {
+            session_id: session_id.to_string(),
+            provider: provider_name.to_string(),
+            model: model.to_string(),
+            prompt_tokens: estimate.prompt_tokens,
+            context_window: estimate.context_window,
+            prompt_cost_usd: estimate.prompt_cost_usd,
+        });
+
+        if estimate.exceeds_context() {
+            warn!(
+                session_id,
+                model,
+                prompt_tokens = estimate.prompt_tokens,
+                context_window = estimate.context_window,
+                "Refusing request that exceeds the context window"
+            );
+            return Err(ChatError::ContextWindowExceeded {
+                model: model.to_string(),
+                prompt_tokens: estimate.prompt_tokens,
+                context_window: estimate.context_window,
+            });
+        }
+        Ok(estimate)
+    }
+
     /// Build system prompt blocks with caching.
     ///
     /// Returns cached blocks if the ghost context hasn't changed within the
@@ -1630,6 +1970,13 @@ impl SessionChat {
             return Err(ChatError::EmptyResponse);
         }
 
+        // Get ghost_id from session
+        let session = SessionRepository::get_by_id(pool.pool(), session_id)
+            .await?
+            .ok_or(ChatError::SessionNotFound)?;
+
+        let text = self.filter_output(pool, &session.ghost_id, text).await;
+
         info!(
             event_kind = "chat_io",
             "[session:{}] GHOST final response ({} / {}): {}",
@@ -1643,23 +1990,24 @@ impl SessionChat {
             }
         );
 
-        // Get ghost_id from session
-        let session = SessionRepository::get_by_id(pool.pool(), session_id)
-            .await?
-            .ok_or(ChatError::SessionNotFound)?;
-
-        let final_content = vec![DbContentBlock::Text { text: text.clone() }];
-        SessionRepository::add_message(
+        let mut final_content = thinking_db_blocks(response);
+        final_content.push(DbContentBlock::Text { text: text.clone() });
+        SessionRepository::add_message_with_usage(
             pool.pool(),
             &session.ghost_id,
             session_id,
             MessageRole::Ghost,
             final_content,
             Some(model),
+            response.usage.as_ref().map(token_usage),
         )
         .await?;
 
-        Ok(text)
+        Ok(render_reply_with_thinking(
+            self.thinking_display(),
+            &extract_thinking(response),
+            &text,
+        ))
     }
 
     /// Write raw JSON response to a debug log file when empty response is detected.
@@ -1684,8 +2032,22 @@ impl SessionChat {
         log_path
     }
 
+    /// Stream new job transcript entries to live viewers (jobs with a handle only).
+    async fn publish_job_transcript(
+        tool_context: &ToolContext,
+        session_id: &str,
+        transcript: &[TranscriptEntry],
+        from: usize,
+    ) {
+        if let Some(handle) = &tool_context.job_handle {
+            handle
+                .publish_transcript(session_id, transcript, from)
+                .await;
+        }
+    }
+
     /// Log API usage data (fire-and-forget; failures are warned, not propagated).
-    async fn log_usage(
+    pub(crate) async fn log_usage(
         pool: &KomaDbPool,
         ghost_id: &str,
         session_id: &str,
@@ -1695,18 +2057,7 @@ impl SessionChat {
         let Some(usage) = &response.usage else {
             return;
         };
-        let log = UsageLog::new(
-            ghost_id,
-            session_id,
-            None,
-            model,
-            TokenUsage {
-                input_tokens: usage.input_tokens,
-                output_tokens: usage.output_tokens,
-                cache_read_tokens: usage.cache_read_tokens.unwrap_or(0),
-                cache_creation_tokens: usage.cache_creation_tokens.unwrap_or(0),
-            },
-        );
+        let log = UsageLog::new(ghost_id, session_id, None, model, token_usage(usage));
         if let Err(e) = UsageLogRepository::insert(pool.pool(), &log).await {
             warn!(session_id, error = %e, "Failed to log API usage");
         }
@@ -1719,6 +2070,15 @@ impl Default for SessionChat {
     }
 }
 
+fn token_usage(usage: &ProviderUsage) -> TokenUsage {
+    TokenUsage {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_read_tokens: usage.cache_read_tokens.unwrap_or(0),
+        cache_creation_tokens: usage.cache_creation_tokens.unwrap_or(0),
+    }
+}
+
 /// Build a compact key=value preview of tool input JSON (~80 chars max).
 fn build_input_preview(input: &Value) -> String {
     let Some(obj) = input.as_object() else {
@@ -1770,6 +2130,35 @@ fn collect_pending_tool_uses(response: &ProviderResponse) -> Vec<PendingToolUse>
         .collect()
 }
 
+/// Await a provider call unless `cancel` fires first. Dropping the request
+/// future aborts the underlying HTTP request.
+async fn until_cancelled(
+    cancel: &CancellationToken,
+    request: impl Future<Output = Result<ProviderResponse, ProviderError>>,
+) -> Result<ProviderResponse, ChatError> {
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(ChatError::Cancelled),
+        result = request => result.map_err(ChatError::Provider),
+    }
+}
+
+/// Complete `results` with an error result for every tool use the operator
+/// stopped before it produced one.
+fn with_cancelled_results(
+    mut results: Vec<DbContentBlock>,
+    tool_uses: &[PendingToolUse],
+) -> Vec<DbContentBlock> {
+    for tool_use in &tool_uses[results.len().min(tool_uses.len())..] {
+        results.push(DbContentBlock::ToolResult {
+            tool_use_id: tool_use.id.clone(),
+            content: "Error: Cancelled by operator.".to_string(),
+            is_error: Some(true),
+        });
+    }
+    results
+}
+
 /// Retry a provider `send_conversation` call with exponential backoff.
 ///
 /// Used only in background job loops (reflection, heartbeat) where transient
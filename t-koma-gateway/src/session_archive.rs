@@ -0,0 +1,70 @@
+//! Scheduled cold-storage session archiving.
+//!
+//! Runs from the heartbeat runner loop at `[session_archive].interval_minutes`;
+//! the next due time lives in the shared scheduler state. Archived sessions
+//! rehydrate on their next read (see `t_koma_db::session_archive`).
+
+use chrono::Utc;
+use tracing::warn;
+
+use crate::scheduler::JobKind;
+use crate::state::{AppState, LogEntry};
+use t_koma_core::SessionArchiveSettings;
+use t_koma_db::SessionRepository;
+
+const SCHEDULER_KEY: &str = "session_archive";
+
+/// Unix cutoff before which inactive sessions are archived; `None` when disabled.
+pub fn archive_cutoff(settings: &SessionArchiveSettings, now: i64) -> Option<i64> {
+    (settings.max_idle_days > 0).then(|| now - i64::from(settings.max_idle_days) * 86_400)
+}
+
+/// Archive idle sessions if the scheduled run is due (or has never run).
+pub async fn maybe_archive_sessions(state: &AppState, settings: &SessionArchiveSettings) {
+    let now = Utc::now().timestamp();
+    let Some(cutoff) = archive_cutoff(settings, now) else {
+        return;
+    };
+    if let Some(due) = state
+        .scheduler_get(JobKind::SessionArchive, SCHEDULER_KEY)
+        .await
+        && now < due
+    {
+        return;
+    }
+    let next_due = now + settings.interval_minutes.max(1) as i64 * 60;
+    state
+        .scheduler_set(JobKind::SessionArchive, SCHEDULER_KEY, Some(next_due))
+        .await;
+
+    match SessionRepository::archive_idle(
+        state.koma_db.pool(),
+        cutoff,
+        i64::from(settings.batch_size.max(1)),
+    )
+    .await
+    {
+        Ok(0) => {}
+        Ok(archived) => {
+            state
+                .log(LogEntry::Info {
+                    message: format!("Archived {} idle session(s)", archived),
+                })
+                .await;
+        }
+        Err(err) => warn!("session archive failed: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_cutoff_zero_disables() {
+        let mut settings = SessionArchiveSettings::default();
+        assert_eq!(archive_cutoff(&settings, 100 * 86_400), Some(10 * 86_400));
+        settings.max_idle_days = 0;
+        assert_eq!(archive_cutoff(&settings, 100 * 86_400), None);
+    }
+}
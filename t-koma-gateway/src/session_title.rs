@@ -0,0 +1,373 @@
+//! Background session titling.
+//!
+//! Once a session holds a few exchanges, the ghost's heartbeat model is asked
+//! for a short title and a handful of tags, which are stored on the session
+//! row and shown in session listings. Runs at the start of every heartbeat
+//! tick; each session is titled once.
+
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::circuit_breaker::CooldownReason;
+use crate::content::{self, ids};
+use crate::prompt::render::build_simple_system_prompt;
+use crate::providers::provider::ResponseFormat;
+use crate::providers::structured::send_structured_with_repair;
+use crate::session::SessionChat;
+use crate::state::{AppState, ModelEntry};
+use t_koma_db::{ContentBlock, GhostRepository, Message, MessageRole, Session, SessionRepository};
+
+/// Messages a session needs before it is titled (about three exchanges).
+const TITLE_MIN_MESSAGES: i64 = 6;
+/// Sessions titled per tick, to keep a backlog from bursting the provider.
+const TITLE_BATCH_SIZE: i64 = 5;
+/// Leading messages sent to the model.
+const TITLE_TRANSCRIPT_MESSAGES: usize = 12;
+/// Per-message character cap in the transcript.
+const TITLE_TRANSCRIPT_MESSAGE_CHARS: usize = 500;
+const TITLE_MAX_CHARS: usize = 60;
+const MAX_TAGS: usize = 5;
+
+/// A generated title with its tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTitle {
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawSessionTitle {
+    title: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Schema the title reply must match.
+fn title_format() -> ResponseFormat {
+    ResponseFormat {
+        name: "session_title".to_string(),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string", "minLength": 1},
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "maxItems": MAX_TAGS
+                }
+            },
+            "required": ["title"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+/// Parse the model's JSON reply, tolerating code fences and surrounding text.
+pub fn parse_title_response(text: &str) -> Option<SessionTitle> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let raw: RawSessionTitle = serde_json::from_str(&text[start..=end]).ok()?;
+
+    let title = clean_title(&raw.title)?;
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.tags {
+        let tag = tag
+            .trim()
+            .trim_start_matches('#')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(MAX_TAGS);
+    Some(SessionTitle { title, tags })
+}
+
+/// Collapse whitespace, strip quotes/trailing punctuation and cap the length.
+fn clean_title(raw: &str) -> Option<String> {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '`')
+        .trim_end_matches(['.', '!', '?', ':', ';'])
+        .trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let mut title: String = trimmed.chars().take(TITLE_MAX_CHARS).collect();
+    if trimmed.chars().count() > TITLE_MAX_CHARS {
+        title = title.trim_end().to_string();
+        title.push('…');
+    }
+    Some(title)
+}
+
+fn message_text(message: &Message) -> String {
+    message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render the opening of a session as plain text (tool traffic omitted).
+fn render_transcript(messages: &[Message]) -> String {
+    let mut out = String::new();
+    for message in messages.iter().take(TITLE_TRANSCRIPT_MESSAGES) {
+        let text = message_text(message);
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let role = match message.role {
+            MessageRole::Operator => "Operator",
+            MessageRole::Ghost => "Ghost",
+        };
+        let text: String = text.chars().take(TITLE_TRANSCRIPT_MESSAGE_CHARS).collect();
+        out.push_str(&format!("[{role}] {text}\n\n"));
+    }
+    out
+}
+
+/// Title derived from the first operator message, used when the model's
+/// reply can't be parsed so the session isn't retried forever.
+fn fallback_title(messages: &[Message]) -> SessionTitle {
+    let title = messages
+        .iter()
+        .filter(|m| m.role == MessageRole::Operator)
+        .find_map(|m| {
+            let text = message_text(m);
+            text.lines().find_map(clean_title)
+        })
+        .unwrap_or_else(|| "Untitled session".to_string());
+    SessionTitle {
+        title,
+        tags: Vec::new(),
+    }
+}
+
+fn load_title_prompt() -> String {
+    content::prompt_text(ids::PROMPT_SESSION_TITLE, None, &[]).unwrap_or_else(|e| {
+        warn!("Failed to load session title prompt: {e}, using fallback");
+        "Reply with a JSON object {\"title\": \"...\", \"tags\": [\"...\"]} giving a \
+         3-8 word title and 1-5 lowercase topic tags for this conversation."
+            .to_string()
+    })
+}
+
+async fn title_session(
+    state: &AppState,
+    session: &Session,
+    model: &ModelEntry,
+) -> Result<SessionTitle, String> {
+    let messages = SessionRepository::list_messages(state.koma_db.pool(), &session.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let transcript = render_transcript(&messages);
+    if transcript.is_empty() {
+        return Ok(fallback_title(&messages));
+    }
+
+    let system_blocks = build_simple_system_prompt(load_title_prompt());
+    let reply = match send_structured_with_repair(
+        model.client.as_ref(),
+        Some(system_blocks),
+        &transcript,
+        &title_format(),
+    )
+    .await
+    {
+        Ok(reply) => reply,
+        Err(e) => {
+            if e.is_retryable() {
+                let reason = if e.is_rate_limited() {
+                    CooldownReason::RateLimited
+                } else {
+                    CooldownReason::ServerError
+                };
+                state.circuit_breaker.record_failure(&model.alias, reason);
+            }
+            return Err(e.to_string());
+        }
+    };
+    state.circuit_breaker.record_success(&model.alias);
+    for response in &reply.responses {
+        SessionChat::log_usage(
+            &state.koma_db,
+            &session.ghost_id,
+            &session.id,
+            &model.model,
+            response,
+        )
+        .await;
+    }
+
+    let parsed = match reply.value {
+        Ok(value) => parse_title_response(&value.to_string()),
+        Err(e) => {
+            warn!("session titles: unusable reply for {}: {e}", session.id);
+            None
+        }
+    };
+    Ok(parsed.unwrap_or_else(|| fallback_title(&messages)))
+}
+
+/// Title up to [`TITLE_BATCH_SIZE`] sessions that reached enough messages.
+///
+/// Provider failures leave the session untitled so the next tick retries.
+pub async fn run_session_title_pass(state: &AppState) {
+    let pool = state.koma_db.pool();
+    let sessions =
+        match SessionRepository::list_untitled(pool, TITLE_MIN_MESSAGES, TITLE_BATCH_SIZE).await {
+            Ok(list) => list,
+            Err(err) => {
+                warn!("session titles: failed to list untitled sessions: {err}");
+                return;
+            }
+        };
+
+    for session in sessions {
+        let ghost = match GhostRepository::get_by_id(pool, &session.ghost_id).await {
+            Ok(Some(ghost)) => ghost,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!(
+                    "session titles: failed to load ghost for {}: {err}",
+                    session.id
+                );
+                continue;
+            }
+        };
+        let model = state.resolve_model_for_ghost_with_override_json(
+            &ghost,
+            ghost.heartbeat_model_aliases.as_deref(),
+        );
+
+        match title_session(state, &session, &model).await {
+            Ok(generated) => {
+                if let Err(err) = SessionRepository::set_title(
+                    pool,
+                    &session.id,
+                    &generated.title,
+                    &generated.tags,
+                )
+                .await
+                {
+                    warn!(
+                        "session titles: failed to store title for {}: {err}",
+                        session.id
+                    );
+                    continue;
+                }
+                info!(
+                    "session titles: {}:{} → \"{}\" [{}]",
+                    ghost.name,
+                    session.id,
+                    generated.title,
+                    generated.tags.join(", ")
+                );
+            }
+            Err(err) => {
+                warn!(
+                    "session titles: titling {}:{} failed: {err}",
+                    ghost.name, session.id
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: MessageRole, text: &str) -> Message {
+        Message {
+            id: "m".to_string(),
+            session_id: "s".to_string(),
+            role,
+            content: vec![ContentBlock::Text {
+                text: text.to_string(),
+            }],
+            model: None,
+            created_at: 0,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_title_response() {
+        let parsed = parse_title_response(
+            "```json\n{\"title\": \"  \\\"Planning the garden.\\\" \", \
+             \"tags\": [\"Gardening\", \"#spring plans\", \"gardening\", \"\"]}\n```",
+        )
+        .unwrap();
+        assert_eq!(parsed.title, "Planning the garden");
+        assert_eq!(parsed.tags, vec!["gardening", "spring-plans"]);
+
+        assert!(parse_title_response("no json here").is_none());
+        assert!(parse_title_response("{\"title\": \"  \"}").is_none());
+
+        let long = format!("{{\"title\": \"{}\"}}", "word ".repeat(30));
+        let parsed = parse_title_response(&long).unwrap();
+        assert!(parsed.title.ends_with('…'));
+        assert!(parsed.title.chars().count() <= TITLE_MAX_CHARS + 1);
+        assert!(parsed.tags.is_empty());
+    }
+
+    #[test]
+    fn test_title_format_schema() {
+        use crate::providers::structured::validate;
+        let schema = title_format().schema;
+        assert!(
+            validate(
+                &serde_json::json!({"title": "Garden", "tags": ["a"]}),
+                &schema
+            )
+            .is_ok()
+        );
+        assert!(validate(&serde_json::json!({"tags": ["a"]}), &schema).is_err());
+        assert!(
+            validate(
+                &serde_json::json!({"title": "x", "tags": ["a", "b", "c", "d", "e", "f"]}),
+                &schema
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fallback_title_uses_first_operator_line() {
+        let messages = vec![
+            message(MessageRole::Ghost, "Hello!"),
+            message(MessageRole::Operator, "\nFix the backup script.\nIt fails."),
+        ];
+        assert_eq!(fallback_title(&messages).title, "Fix the backup script");
+        assert_eq!(fallback_title(&[]).title, "Untitled session");
+    }
+
+    #[test]
+    fn test_render_transcript_skips_tool_traffic() {
+        let mut tool = message(MessageRole::Ghost, "");
+        tool.content = vec![ContentBlock::ToolUse {
+            id: "t".to_string(),
+            name: "shell".to_string(),
+            input: serde_json::json!({}),
+        }];
+        let transcript = render_transcript(&[
+            message(MessageRole::Operator, "Hi"),
+            tool,
+            message(MessageRole::Ghost, "Hey"),
+        ]);
+        assert_eq!(transcript, "[Operator] Hi\n\n[Ghost] Hey\n\n");
+    }
+}
@@ -0,0 +1,287 @@
+//! Minimal Slack Web API client and Socket Mode payload types.
+//!
+//! Covers only what the app needs: opening a Socket Mode connection,
+//! posting and updating messages (plain mrkdwn or Block Kit), opening DMs,
+//! looking up user names and downloading shared files.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use super::SlackError;
+
+const API_BASE: &str = "https://slack.com/api";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Identity of the app's bot user (`auth.test`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthIdentity {
+    pub user_id: String,
+    pub user: String,
+}
+
+// ---------------------------------------------------------------------------
+// Socket Mode payloads
+// ---------------------------------------------------------------------------
+
+/// One Socket Mode frame. Frames with an `envelope_id` must be acknowledged.
+#[derive(Debug, Deserialize)]
+pub struct Envelope {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub envelope_id: Option<String>,
+    pub payload: Option<Value>,
+}
+
+/// `events_api` envelope payload.
+#[derive(Debug, Deserialize)]
+pub struct EventCallback {
+    pub event: Event,
+}
+
+/// A `message` or `app_mention` event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub subtype: Option<String>,
+    pub user: Option<String>,
+    pub bot_id: Option<String>,
+    pub channel: Option<String>,
+    pub channel_type: Option<String>,
+    pub text: Option<String>,
+    pub files: Option<Vec<File>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct File {
+    pub id: String,
+    pub name: Option<String>,
+    pub mimetype: Option<String>,
+    pub url_private_download: Option<String>,
+}
+
+/// `interactive` envelope payload for a `block_actions` interaction.
+#[derive(Debug, Deserialize)]
+pub struct BlockActions {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub user: IdRef,
+    pub channel: Option<IdRef>,
+    pub message: Option<ActionMessage>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdRef {
+    pub id: String,
+}
+
+/// The message that carried the clicked button.
+#[derive(Debug, Deserialize)]
+pub struct ActionMessage {
+    pub ts: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub blocks: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Action {
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SocketUrl {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenedConversation {
+    channel: IdRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    user: UserRecord,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserRecord {
+    name: String,
+    profile: Option<UserProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserProfile {
+    display_name: Option<String>,
+    real_name: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Web API client
+// ---------------------------------------------------------------------------
+
+/// Slack Web API client bound to one bot token.
+#[derive(Clone)]
+pub struct SlackApi {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl SlackApi {
+    pub fn new(token: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            token: token.to_string(),
+        }
+    }
+
+    /// Check Slack's `{ ok, error }` wrapper and decode the rest.
+    async fn decode<T: DeserializeOwned>(
+        method: &str,
+        response: reqwest::Response,
+    ) -> Result<T, SlackError> {
+        let body: Value = response.json().await?;
+        if body.get("ok").and_then(Value::as_bool) != Some(true) {
+            let error = body
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown_error");
+            return Err(SlackError::Api(format!("{method}: {error}")));
+        }
+        serde_json::from_value(body).map_err(|e| SlackError::Api(format!("{method}: {e}")))
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, body: Value) -> Result<T, SlackError> {
+        let response = self
+            .client
+            .post(format!("{API_BASE}/{method}"))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await?;
+        Self::decode(method, response).await
+    }
+
+    /// The bot user behind the token.
+    pub async fn auth_test(&self) -> Result<AuthIdentity, SlackError> {
+        self.call("auth.test", json!({})).await
+    }
+
+    /// Ask for a Socket Mode WebSocket URL (needs the app-level token).
+    pub async fn open_socket(&self, app_token: &str) -> Result<String, SlackError> {
+        let response = self
+            .client
+            .post(format!("{API_BASE}/apps.connections.open"))
+            .bearer_auth(app_token)
+            .send()
+            .await?;
+        let socket: SocketUrl = Self::decode("apps.connections.open", response).await?;
+        Ok(socket.url)
+    }
+
+    /// Post a message; `text` is the notification fallback when `blocks` are set.
+    pub async fn post_message(
+        &self,
+        channel: &str,
+        text: &str,
+        blocks: Option<&[Value]>,
+    ) -> Result<(), SlackError> {
+        let mut body = json!({
+            "channel": channel,
+            "text": text,
+            "unfurl_links": false,
+            "unfurl_media": false,
+        });
+        if let Some(blocks) = blocks {
+            body["blocks"] = json!(blocks);
+        }
+        self.call::<Value>("chat.postMessage", body)
+            .await
+            .map(|_| ())
+    }
+
+    /// Post a message only `user` can see.
+    pub async fn post_ephemeral(
+        &self,
+        channel: &str,
+        user: &str,
+        text: &str,
+    ) -> Result<(), SlackError> {
+        self.call::<Value>(
+            "chat.postEphemeral",
+            json!({ "channel": channel, "user": user, "text": text }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Replace a sent message's blocks (used to drop buttons after a click).
+    pub async fn update_message(
+        &self,
+        channel: &str,
+        ts: &str,
+        text: &str,
+        blocks: &[Value],
+    ) -> Result<(), SlackError> {
+        self.call::<Value>(
+            "chat.update",
+            json!({ "channel": channel, "ts": ts, "text": text, "blocks": blocks }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// DM channel with a user (opened if needed).
+    pub async fn open_dm(&self, user_id: &str) -> Result<String, SlackError> {
+        let opened: OpenedConversation = self
+            .call("conversations.open", json!({ "users": user_id }))
+            .await?;
+        Ok(opened.channel.id)
+    }
+
+    /// Display name of a user, falling back to the real name and handle.
+    pub async fn user_name(&self, user_id: &str) -> Result<String, SlackError> {
+        // users.info only takes form-encoded arguments.
+        let response = self
+            .client
+            .post(format!("{API_BASE}/users.info"))
+            .bearer_auth(&self.token)
+            .form(&[("user", user_id)])
+            .send()
+            .await?;
+        let info: UserInfo = Self::decode("users.info", response).await?;
+        let profile = info.user.profile;
+        let name = profile
+            .as_ref()
+            .and_then(|p| p.display_name.clone())
+            .filter(|name| !name.is_empty())
+            .or_else(|| profile.and_then(|p| p.real_name).filter(|n| !n.is_empty()))
+            .unwrap_or(info.user.name);
+        Ok(name)
+    }
+
+    /// Download a file shared with the app (`files:read` scope).
+    pub async fn download_file(&self, url: &str) -> Result<Vec<u8>, SlackError> {
+        let bytes = self
+            .client
+            .get(url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(bytes.to_vec())
+    }
+}
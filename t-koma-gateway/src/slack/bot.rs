@@ -0,0 +1,1053 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, error, info, warn};
+
+use crate::attachments;
+use crate::content::{self, ids};
+use crate::discord::{format_ghost_list_lines, parse_ghost_selection, persist_ghost_name_to_soul};
+use crate::operator_flow::{self, OutboundMessage};
+use crate::session::ChatError;
+use crate::state::{AppState, PendingGatewayAction, RateLimitDecision};
+
+use super::SlackError;
+use super::api::{
+    ActionMessage, AuthIdentity, BlockActions, Envelope, Event, EventCallback, File, SlackApi,
+};
+use super::format::unescape_mrkdwn;
+use super::send::{
+    button, send_assistant_text, send_gateway_text, send_ghost_name_prompt, send_interface_prompt,
+    send_outbound_messages, send_tool_calls,
+};
+
+/// Pause before reopening a Socket Mode connection that failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Slack app handler
+///
+/// Like the Discord bot, this only adapts Socket Mode events to the shared
+/// operator flow; all chat handling goes through `operator_flow`. Replies go
+/// to the channel or DM the message came from, while the session is the
+/// operator's active one, as on Discord.
+pub struct Bot {
+    state: Arc<AppState>,
+    api: SlackApi,
+    app_token: String,
+    me: AuthIdentity,
+}
+
+impl Bot {
+    pub(super) fn new(
+        state: Arc<AppState>,
+        api: SlackApi,
+        app_token: String,
+        me: AuthIdentity,
+    ) -> Self {
+        Self {
+            state,
+            api,
+            app_token,
+            me,
+        }
+    }
+
+    /// Hold a Socket Mode connection open until the task is aborted.
+    ///
+    /// Slack rotates connections every few hours (`disconnect` frames), so
+    /// a closed socket is simply reopened.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            match Arc::clone(&self).run_socket().await {
+                Ok(()) => debug!("Slack socket closed, reconnecting"),
+                Err(e) => {
+                    warn!("Slack socket failed: {}", e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    async fn run_socket(self: Arc<Self>) -> Result<(), SlackError> {
+        let url = self.api.open_socket(&self.app_token).await?;
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| SlackError::Socket(e.to_string()))?;
+        let (mut writer, mut reader) = socket.split();
+
+        while let Some(frame) = reader.next().await {
+            let text = match frame.map_err(|e| SlackError::Socket(e.to_string()))? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Ok(()),
+                _ => continue,
+            };
+            let envelope: Envelope = match serde_json::from_str(&text) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    warn!("Unreadable Slack socket frame: {}", e);
+                    continue;
+                }
+            };
+
+            // Ack first: Slack retries anything not acknowledged within 3s.
+            if let Some(envelope_id) = &envelope.envelope_id {
+                let ack = json!({ "envelope_id": envelope_id }).to_string();
+                writer
+                    .send(Message::Text(ack.into()))
+                    .await
+                    .map_err(|e| SlackError::Socket(e.to_string()))?;
+            }
+
+            match (envelope.kind.as_str(), envelope.payload) {
+                ("hello", _) => info!("Slack Socket Mode connected"),
+                ("disconnect", _) => return Ok(()),
+                ("events_api", Some(payload)) => {
+                    let bot = Arc::clone(&self);
+                    tokio::spawn(async move { bot.handle_event_payload(payload).await });
+                }
+                ("interactive", Some(payload)) => {
+                    let bot = Arc::clone(&self);
+                    tokio::spawn(async move { bot.handle_interaction(payload).await });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_text(&self, channel: &str, text: &str) {
+        if let Err(e) = send_gateway_text(&self.api, channel, text, Vec::new()).await {
+            error!("Failed to send Slack message: {}", e);
+        }
+    }
+
+    async fn send_content(&self, channel: &str, id: &str) {
+        self.send_text(channel, &super::render_message(id, &[]))
+            .await;
+    }
+
+    async fn handle_event_payload(&self, payload: Value) {
+        match serde_json::from_value::<EventCallback>(payload) {
+            Ok(callback) => self.handle_message(callback.event).await,
+            Err(e) => debug!("Ignoring Slack event: {}", e),
+        }
+    }
+
+    /// Message text meant for the app, without the `<@app>` mention.
+    ///
+    /// DMs always reach the app; in channels it only answers `app_mention`
+    /// events, so a mention is not also handled as a channel message.
+    fn addressed_content(&self, event: &Event) -> Option<String> {
+        let addressed = match event.kind.as_str() {
+            "app_mention" => true,
+            "message" => event.channel_type.as_deref() == Some("im"),
+            _ => false,
+        };
+        let plain = event.subtype.is_none() || event.subtype.as_deref() == Some("file_share");
+        if !addressed || !plain || event.bot_id.is_some() {
+            return None;
+        }
+        let mention = format!("<@{}>", self.me.user_id);
+        let text = event
+            .text
+            .as_deref()
+            .unwrap_or_default()
+            .replace(&mention, "");
+        Some(unescape_mrkdwn(text.trim()))
+    }
+
+    async fn handle_message(&self, event: Event) {
+        let Some(content) = self.addressed_content(&event) else {
+            return;
+        };
+        let (Some(operator_external_id), Some(channel)) =
+            (event.user.clone(), event.channel.clone())
+        else {
+            return;
+        };
+        if operator_external_id == self.me.user_id {
+            return;
+        }
+        let channel = channel.as_str();
+        let platform = t_koma_db::Platform::Slack;
+
+        info!(
+            event_kind = "chat_io",
+            "[session:-] Slack message from {} in {}: {}", operator_external_id, channel, content
+        );
+
+        let clean_content = content.as_str();
+        let files = event.files.clone().unwrap_or_default();
+
+        if clean_content.is_empty() && files.is_empty() {
+            return;
+        }
+
+        let interface = match t_koma_db::InterfaceRepository::get_by_external_id(
+            self.state.koma_db.pool(),
+            platform,
+            &operator_external_id,
+        )
+        .await
+        {
+            Ok(found) => found,
+            Err(e) => {
+                error!("Failed to load interface {}: {}", operator_external_id, e);
+                self.send_content(channel, ids::ERROR_GENERIC).await;
+                return;
+            }
+        };
+
+        let Some(interface) = interface else {
+            if !self
+                .state
+                .is_interface_pending(platform, &operator_external_id)
+                .await
+            {
+                self.state
+                    .set_interface_pending(platform, &operator_external_id)
+                    .await;
+                send_interface_prompt(&self.api, channel).await;
+                return;
+            }
+            self.handle_interface_choice(channel, &operator_external_id, clean_content)
+                .await;
+            return;
+        };
+
+        let operator = match t_koma_db::OperatorRepository::get_by_id(
+            self.state.koma_db.pool(),
+            &interface.operator_id,
+        )
+        .await
+        {
+            Ok(Some(op)) => op,
+            Ok(None) => {
+                error!(
+                    "Interface references missing operator {}",
+                    interface.operator_id
+                );
+                self.send_content(channel, ids::INTERFACE_INVALID_OPERATOR)
+                    .await;
+                return;
+            }
+            Err(e) => {
+                error!("Failed to load operator {}: {}", interface.operator_id, e);
+                self.send_content(channel, ids::FAILED_LOAD_OPERATOR).await;
+                return;
+            }
+        };
+
+        match operator.status {
+            t_koma_db::OperatorStatus::Pending => {
+                self.send_content(channel, ids::ACCESS_PENDING).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Denied => {
+                self.send_content(channel, ids::ACCESS_DENIED).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Approved => {}
+        }
+
+        let operator_id = operator.id.clone();
+
+        let ghosts = match t_koma_db::GhostRepository::list_by_operator(
+            self.state.koma_db.pool(),
+            &operator_id,
+        )
+        .await
+        {
+            Ok(list) => list,
+            Err(e) => {
+                error!("Failed to list ghosts for operator {}: {}", operator_id, e);
+                self.send_content(channel, ids::ERROR_FAILED_LOAD_GHOSTS)
+                    .await;
+                return;
+            }
+        };
+
+        if ghosts.is_empty() {
+            self.handle_no_ghosts(channel, &operator, clean_content)
+                .await;
+            return;
+        }
+
+        if let Some(selection) = parse_ghost_selection(clean_content) {
+            if let Some(ghost) = ghosts.iter().find(|g| g.name == selection) {
+                self.state.set_active_ghost(&operator_id, &ghost.name).await;
+                let response = super::render_message(
+                    ids::ACTIVE_GHOST_SET,
+                    &[("ghost_name", ghost.name.as_str())],
+                );
+                self.send_text(channel, &response).await;
+                return;
+            }
+
+            let list_rows = format_ghost_list_lines(&ghosts);
+            let list =
+                super::render_message(ids::GHOST_LIST, &[("ghost_list", list_rows.as_str())]);
+            let response =
+                super::render_message(ids::UNKNOWN_GHOST_NAME, &[("ghost_list", list.as_str())]);
+            self.send_text(channel, &response).await;
+            return;
+        }
+
+        let ghost_name = if ghosts.len() == 1 {
+            ghosts[0].name.clone()
+        } else if let Some(active) = self.state.get_active_ghost(&operator_id).await {
+            active
+        } else {
+            self.send_ghost_select_prompt(channel, &operator_id, &operator_external_id, &ghosts)
+                .await;
+            return;
+        };
+
+        let ghost =
+            match t_koma_db::GhostRepository::get_by_name(self.state.koma_db.pool(), &ghost_name)
+                .await
+            {
+                Ok(Some(g)) => g,
+                Ok(None) => {
+                    error!("Ghost not found: {}", ghost_name);
+                    self.send_content(channel, ids::ERROR_FAILED_LOAD_GHOSTS)
+                        .await;
+                    return;
+                }
+                Err(e) => {
+                    error!("Failed to load ghost {}: {}", ghost_name, e);
+                    self.send_content(channel, ids::ERROR_FAILED_LOAD_GHOSTS)
+                        .await;
+                    return;
+                }
+            };
+
+        let session = match t_koma_db::SessionRepository::get_or_create_active(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            &operator_id,
+        )
+        .await
+        {
+            Ok(s) => s,
+            Err(e) => {
+                error!(
+                    "Failed to create session for operator {}: {}",
+                    operator_id, e
+                );
+                self.send_content(channel, ids::FAILED_INIT_SESSION).await;
+                return;
+            }
+        };
+
+        match self.state.check_operator_rate_limit(&operator).await {
+            RateLimitDecision::Allowed => {}
+            RateLimitDecision::Limited { retry_after } => {
+                if !clean_content.eq_ignore_ascii_case("continue") {
+                    self.state
+                        .store_pending_message(
+                            &operator_id,
+                            &ghost_name,
+                            &session.id,
+                            clean_content,
+                        )
+                        .await;
+                }
+                let retry_after = retry_after.as_secs().to_string();
+                let message = super::render_message(
+                    ids::RATE_LIMITED,
+                    &[("retry_after", retry_after.as_str())],
+                );
+                self.send_text(channel, &message).await;
+                return;
+            }
+        }
+
+        if clean_content.eq_ignore_ascii_case("new") {
+            self.start_new_session(
+                channel,
+                &ghost,
+                &operator_id,
+                &operator_external_id,
+                &session.id,
+            )
+            .await;
+            return;
+        }
+
+        let attachment_blocks = if files.is_empty() {
+            vec![]
+        } else {
+            let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+                Ok(path) => path,
+                Err(e) => {
+                    error!("Failed to get workspace path: {}", e);
+                    self.send_content(channel, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                        .await;
+                    return;
+                }
+            };
+            self.download_to_content_blocks(&files, &workspace_path)
+                .await
+        };
+
+        self.state
+            .log(crate::LogEntry::Routing {
+                platform: "slack".to_string(),
+                operator_id: operator_id.clone(),
+                ghost_name: ghost_name.clone(),
+                session_id: session.id.clone(),
+            })
+            .await;
+
+        if clean_content.eq_ignore_ascii_case("approve")
+            || clean_content.eq_ignore_ascii_case("deny")
+            || operator_flow::parse_step_limit(clean_content).is_some()
+        {
+            match operator_flow::run_tool_control_command(
+                self.state.as_ref(),
+                Some("slack"),
+                None,
+                &ghost_name,
+                &session.id,
+                &operator_id,
+                clean_content,
+            )
+            .await
+            {
+                Ok(Some(messages)) => {
+                    send_outbound_messages(
+                        self.state.as_ref(),
+                        &self.api,
+                        channel,
+                        &operator_external_id,
+                        &operator_id,
+                        &ghost_name,
+                        &session.id,
+                        messages,
+                    )
+                    .await;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("[session:{}] Chat error: {}", session.id, e);
+                    self.send_content(channel, ids::ERROR_PROCESSING_REQUEST)
+                        .await;
+                }
+            }
+            return;
+        }
+
+        // Stream tool calls as they arrive when verbose mode is on
+        let (tool_tx, tool_stream_handle) = if self.state.is_verbose(&operator_id).await {
+            let (tx, mut rx) =
+                tokio::sync::mpsc::unbounded_channel::<Vec<crate::state::ToolCallSummary>>();
+            let api = self.api.clone();
+            let channel = channel.to_string();
+            let handle = tokio::spawn(async move {
+                while let Some(calls) = rx.recv().await {
+                    let _ = send_tool_calls(&api, &channel, &calls).await;
+                }
+            });
+            (Some(tx), Some(handle))
+        } else {
+            (None, None)
+        };
+
+        let result = operator_flow::run_chat_with_pending_and_attachments(
+            self.state.as_ref(),
+            Some("slack"),
+            None,
+            &ghost_name,
+            &session.id,
+            &operator_id,
+            clean_content,
+            attachment_blocks,
+            tool_tx.as_ref(),
+        )
+        .await;
+
+        // Drop the sender so the streaming task drains and exits
+        drop(tool_tx);
+        if let Some(handle) = tool_stream_handle {
+            let _ = handle.await;
+        }
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(ChatError::OverBudget(report)) => vec![OutboundMessage::gateway(
+                operator_flow::usage_budget_message(
+                    ids::USAGE_BUDGET_EXCEEDED,
+                    &report,
+                    Some("slack"),
+                ),
+            )],
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(channel, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+                return;
+            }
+        };
+        send_outbound_messages(
+            self.state.as_ref(),
+            &self.api,
+            channel,
+            &operator_external_id,
+            &operator_id,
+            &ghost_name,
+            &session.id,
+            messages,
+        )
+        .await;
+    }
+
+    async fn handle_interaction(&self, payload: Value) {
+        let interaction = match serde_json::from_value::<BlockActions>(payload) {
+            Ok(interaction) if interaction.kind == "block_actions" => interaction,
+            Ok(_) => return,
+            Err(e) => {
+                debug!("Ignoring Slack interaction: {}", e);
+                return;
+            }
+        };
+        let (Some(channel), Some(value)) = (
+            interaction.channel.as_ref().map(|c| c.id.clone()),
+            interaction.actions.first().and_then(|a| a.value.clone()),
+        ) else {
+            return;
+        };
+        let channel = channel.as_str();
+        let external_id = interaction.user.id.as_str();
+
+        if let Some(choice) = value.strip_prefix("tk:iface:") {
+            self.clear_buttons(channel, interaction.message.as_ref())
+                .await;
+            self.handle_interface_choice(channel, external_id, choice)
+                .await;
+            return;
+        }
+
+        let Some(token) = value.strip_prefix("tk:a:") else {
+            return;
+        };
+        let Some(pending) = self.state.take_pending_gateway_action(token).await else {
+            let _ = self
+                .api
+                .post_ephemeral(
+                    channel,
+                    external_id,
+                    "This action expired. Please send your command again.",
+                )
+                .await;
+            return;
+        };
+
+        if pending.external_id != external_id || pending.channel_id != channel {
+            // Someone else in a shared channel: leave the action to its owner.
+            self.state.set_pending_gateway_action(token, pending).await;
+            let _ = self
+                .api
+                .post_ephemeral(channel, external_id, "This action is not valid for you.")
+                .await;
+            return;
+        }
+
+        self.clear_buttons(channel, interaction.message.as_ref())
+            .await;
+
+        // Free-text answers come back as plain messages.
+        match pending.intent.as_str() {
+            "ghost.name_prompt" => {
+                if let Err(e) = send_ghost_name_prompt(&self.api, channel).await {
+                    error!("Failed to send Slack ghost name prompt: {}", e);
+                }
+            }
+            "tool_loop.set_steps" => {
+                self.send_content(channel, ids::SLACK_STEPS_PROMPT).await;
+            }
+            intent => {
+                let intent = intent.to_string();
+                self.run_action_intent(channel, pending, &intent).await;
+            }
+        }
+    }
+
+    /// Drop the buttons from a message once one of them was used.
+    async fn clear_buttons(&self, channel: &str, message: Option<&ActionMessage>) {
+        let Some(message) = message else {
+            return;
+        };
+        let blocks: Vec<Value> = message
+            .blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) != Some("actions"))
+            .cloned()
+            .collect();
+        if let Err(e) = self
+            .api
+            .update_message(channel, &message.ts, &message.text, &blocks)
+            .await
+        {
+            debug!("Failed to clear Slack buttons: {}", e);
+        }
+    }
+
+    async fn handle_interface_choice(
+        &self,
+        channel: &str,
+        operator_external_id: &str,
+        choice: &str,
+    ) {
+        let platform = t_koma_db::Platform::Slack;
+        let normalized = choice.trim().to_lowercase();
+
+        if normalized == "existing" {
+            self.send_content(channel, ids::EXISTING_OPERATOR_TODO)
+                .await;
+            return;
+        }
+
+        if normalized != "new" {
+            send_interface_prompt(&self.api, channel).await;
+            return;
+        }
+
+        let operator_name = match self.api.user_name(operator_external_id).await {
+            Ok(name) => name,
+            Err(e) => {
+                warn!(
+                    "Failed to look up Slack user {}: {}",
+                    operator_external_id, e
+                );
+                operator_external_id.to_string()
+            }
+        };
+
+        let operator = match t_koma_db::OperatorRepository::create_new(
+            self.state.koma_db.pool(),
+            &operator_name,
+            platform,
+            t_koma_db::OperatorAccessLevel::Standard,
+        )
+        .await
+        {
+            Ok(op) => op,
+            Err(e) => {
+                error!("Failed to create operator: {}", e);
+                self.send_content(channel, ids::FAILED_CREATE_OPERATOR)
+                    .await;
+                return;
+            }
+        };
+
+        if let Err(e) = t_koma_db::InterfaceRepository::create(
+            self.state.koma_db.pool(),
+            &operator.id,
+            platform,
+            operator_external_id,
+            &operator_name,
+        )
+        .await
+        {
+            error!("Failed to create interface: {}", e);
+            self.send_content(channel, ids::FAILED_CREATE_INTERFACE)
+                .await;
+            return;
+        }
+
+        self.state
+            .clear_interface_pending(platform, operator_external_id)
+            .await;
+
+        self.send_content(channel, ids::OPERATOR_CREATED_AWAITING_APPROVAL)
+            .await;
+
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            crate::operator_flow::notify_new_operator(&state, &operator).await;
+        });
+    }
+
+    async fn run_action_intent(&self, channel: &str, pending: PendingGatewayAction, intent: &str) {
+        let control_text = match intent {
+            "approval.approve" => "approve",
+            "approval.deny" => "deny",
+            "tool_loop.continue_default" => "steps 1",
+            "tool_loop.deny" => "deny",
+            "ghost.select" => {
+                if let Some(ghost_name) = pending.payload.as_deref() {
+                    self.state
+                        .set_active_ghost(&pending.operator_id, ghost_name)
+                        .await;
+                    let text =
+                        super::render_message(ids::ACTIVE_GHOST_SET, &[("ghost_name", ghost_name)]);
+                    self.send_text(channel, &text).await;
+                }
+                return;
+            }
+            "admin.approve_operator" | "admin.deny_operator" => {
+                self.review_operator(channel, &pending, intent == "admin.approve_operator")
+                    .await;
+                return;
+            }
+            _ => return,
+        };
+
+        match operator_flow::run_tool_control_command(
+            self.state.as_ref(),
+            Some("slack"),
+            None,
+            &pending.ghost_name,
+            &pending.session_id,
+            &pending.operator_id,
+            control_text,
+        )
+        .await
+        {
+            Ok(Some(messages)) => {
+                send_outbound_messages(
+                    self.state.as_ref(),
+                    &self.api,
+                    channel,
+                    &pending.external_id,
+                    &pending.operator_id,
+                    &pending.ghost_name,
+                    &pending.session_id,
+                    messages,
+                )
+                .await;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                error!("Slack action error: {}", err);
+                self.send_content(channel, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+            }
+        }
+    }
+
+    /// Approve or deny the operator named in `pending.payload` (PM buttons).
+    async fn review_operator(&self, channel: &str, pending: &PendingGatewayAction, approve: bool) {
+        let Some(target_id) = pending.payload.as_deref() else {
+            return;
+        };
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            &pending.operator_id,
+            &t_koma_db::OperatorPermission::ManageOperators,
+        )
+        .await
+        {
+            return;
+        }
+
+        let pool = self.state.koma_db.pool();
+        let result = if approve {
+            t_koma_db::OperatorRepository::approve(pool, target_id).await
+        } else {
+            t_koma_db::OperatorRepository::deny(pool, target_id).await
+        };
+        match result {
+            Ok(operator) => {
+                if approve {
+                    crate::operator_flow::welcome_approved_operator(&self.state, target_id).await;
+                }
+                let id = if approve {
+                    ids::ADMIN_OPERATOR_APPROVED
+                } else {
+                    ids::ADMIN_OPERATOR_DENIED
+                };
+                let text = super::render_message(id, &[("operator_name", &operator.name)]);
+                self.send_text(channel, &text).await;
+            }
+            Err(e) => {
+                warn!(
+                    "PM {} failed to review operator {}: {}",
+                    pending.operator_id, target_id, e
+                );
+                let verb = if approve { "Approve" } else { "Deny" };
+                self.send_text(channel, &format!("{verb} failed: {e}"))
+                    .await;
+            }
+        }
+    }
+
+    /// First contact after approval: ask for a ghost name, then take the
+    /// next plain message as that name.
+    async fn handle_no_ghosts(&self, channel: &str, operator: &t_koma_db::Operator, content: &str) {
+        let prompted =
+            t_koma_db::OperatorRepository::get_by_id(self.state.koma_db.pool(), &operator.id)
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|op| op.welcomed);
+
+        if prompted && !content.is_empty() {
+            self.boot_new_ghost(channel, &operator.id, content).await;
+            return;
+        }
+
+        if !prompted
+            && let Err(e) = t_koma_db::OperatorRepository::mark_welcomed(
+                self.state.koma_db.pool(),
+                &operator.id,
+            )
+            .await
+        {
+            error!("Failed to mark operator {} as welcomed: {}", operator.id, e);
+        }
+        if let Err(e) = send_ghost_name_prompt(&self.api, channel).await {
+            error!("Failed to send Slack ghost name prompt: {}", e);
+        }
+    }
+
+    async fn boot_new_ghost(&self, channel: &str, operator_id: &str, ghost_name: &str) {
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            operator_id,
+            &t_koma_db::OperatorPermission::CreateGhosts,
+        )
+        .await
+        {
+            self.send_content(channel, ids::GHOST_CREATION_NOT_PERMITTED)
+                .await;
+            return;
+        }
+
+        let ghost = match t_koma_db::GhostRepository::create(
+            self.state.koma_db.pool(),
+            operator_id,
+            ghost_name,
+        )
+        .await
+        {
+            Ok(ghost) => ghost,
+            Err(e) => {
+                let error_text = e.to_string();
+                let invalid = super::render_message(
+                    ids::INVALID_GHOST_NAME,
+                    &[("error", error_text.as_str()), ("ghost_name_prompt", "")],
+                );
+                self.send_text(channel, &invalid).await;
+                return;
+            }
+        };
+
+        let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Failed to get workspace path: {}", e);
+                self.send_content(channel, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                    .await;
+                return;
+            }
+        };
+
+        persist_ghost_name_to_soul(&workspace_path, &ghost.name).await;
+
+        let session = match t_koma_db::SessionRepository::create(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            operator_id,
+        )
+        .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                error!("Failed to create session: {}", e);
+                self.send_content(channel, ids::FAILED_CREATE_SESSION).await;
+                return;
+            }
+        };
+
+        let bootstrap = match content::prompt_text(ids::PROMPT_BOOTSTRAP, None, &[]) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to load prompts/system/bootstrap.md: {}", e);
+                self.send_content(channel, ids::ERROR_MISSING_BOOTSTRAP)
+                    .await;
+                return;
+            }
+        };
+
+        let ghost_response = match self
+            .state
+            .chat(&ghost.name, &session.id, operator_id, &bootstrap)
+            .await
+        {
+            Ok(text) => text,
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(channel, ids::ERROR_GHOST_BOOT_FAILED)
+                    .await;
+                return;
+            }
+        };
+
+        self.state.set_active_ghost(operator_id, &ghost.name).await;
+
+        let header = super::render_message(
+            ids::GHOST_CREATED_HEADER_WITH_NAME,
+            &[("ghost_name", ghost.name.as_str())],
+        );
+        self.send_text(channel, &header).await;
+        if let Err(e) = send_assistant_text(&self.api, channel, &ghost_response).await {
+            error!(
+                "[session:{}] Failed to send Slack message: {}",
+                session.id, e
+            );
+        }
+    }
+
+    async fn send_ghost_select_prompt(
+        &self,
+        channel: &str,
+        operator_id: &str,
+        operator_external_id: &str,
+        ghosts: &[t_koma_db::Ghost],
+    ) {
+        let list_rows = format_ghost_list_lines(ghosts);
+        let list = super::render_message(ids::GHOST_LIST, &[("ghost_list", list_rows.as_str())]);
+
+        // An actions block holds at most 25 elements.
+        let mut buttons = Vec::new();
+        for ghost in ghosts.iter().take(25) {
+            let token = uuid::Uuid::new_v4().to_string();
+            self.state
+                .set_pending_gateway_action(
+                    &token,
+                    PendingGatewayAction {
+                        operator_id: operator_id.to_string(),
+                        ghost_name: String::new(),
+                        session_id: "active".to_string(),
+                        external_id: operator_external_id.to_string(),
+                        channel_id: channel.to_string(),
+                        intent: "ghost.select".to_string(),
+                        payload: Some(ghost.name.clone()),
+                        expires_at: chrono::Utc::now().timestamp() + 900,
+                    },
+                )
+                .await;
+            buttons.push(button(&ghost.name, &format!("tk:a:{}", token), None));
+        }
+
+        let prompt =
+            super::render_message(ids::SELECT_GHOST_PROMPT, &[("ghost_list", list.as_str())]);
+        if let Err(e) = send_gateway_text(&self.api, channel, &prompt, buttons).await {
+            error!("Failed to send Slack ghost selection: {}", e);
+        }
+    }
+
+    /// Start a fresh session: reflect on the previous one and greet the ghost.
+    async fn start_new_session(
+        &self,
+        channel: &str,
+        ghost: &t_koma_db::Ghost,
+        operator_id: &str,
+        operator_external_id: &str,
+        previous_session_id: &str,
+    ) {
+        let new_session = match t_koma_db::SessionRepository::create(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            operator_id,
+        )
+        .await
+        {
+            Ok(s) => s,
+            Err(e) => {
+                error!(
+                    "Failed to create new session for operator {}: {}",
+                    operator_id, e
+                );
+                self.send_content(channel, ids::FAILED_INIT_SESSION).await;
+                return;
+            }
+        };
+
+        operator_flow::queue_reflection_for_previous_session(
+            &self.state,
+            &ghost.id,
+            previous_session_id,
+        )
+        .await;
+
+        let messages = match operator_flow::run_chat_with_pending(
+            self.state.as_ref(),
+            Some("slack"),
+            None,
+            &ghost.name,
+            &new_session.id,
+            operator_id,
+            "hello",
+            None,
+        )
+        .await
+        {
+            Ok(messages) => messages,
+            Err(ChatError::OverBudget(report)) => vec![OutboundMessage::gateway(
+                operator_flow::usage_budget_message(
+                    ids::USAGE_BUDGET_EXCEEDED,
+                    &report,
+                    Some("slack"),
+                ),
+            )],
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", new_session.id, e);
+                self.send_content(channel, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+                return;
+            }
+        };
+        send_outbound_messages(
+            self.state.as_ref(),
+            &self.api,
+            channel,
+            operator_external_id,
+            operator_id,
+            &ghost.name,
+            &new_session.id,
+            messages,
+        )
+        .await;
+    }
+
+    /// Download files shared with the message into the ghost workspace.
+    async fn download_to_content_blocks(
+        &self,
+        files: &[File],
+        workspace_path: &std::path::Path,
+    ) -> Vec<t_koma_db::ContentBlock> {
+        let download_dir = match attachments::downloads_dir(workspace_path).await {
+            Ok(dir) => dir,
+            Err(e) => {
+                error!("Failed to create downloads dir: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut blocks = Vec::new();
+        for file in files {
+            let Some(url) = file.url_private_download.as_deref() else {
+                continue;
+            };
+            let filename = file.name.clone().unwrap_or_else(|| file.id.clone());
+            match self.api.download_file(url).await {
+                Ok(bytes) => blocks.extend(
+                    attachments::store_attachment(
+                        &download_dir,
+                        &filename,
+                        file.mimetype.clone(),
+                        &bytes,
+                    )
+                    .await,
+                ),
+                Err(e) => error!("Failed to download Slack file {}: {}", filename, e),
+            }
+        }
+        blocks
+    }
+}
@@ -0,0 +1,246 @@
+//! Markdown-to-Slack-mrkdwn adapter.
+//!
+//! Slack's mrkdwn is close to markdown but differs in the details, so the
+//! conversion is line based, like the Telegram adapter:
+//! - Code fences keep their body but lose the language tag
+//! - Headings → bold lines
+//! - Tables → code blocks (Slack has no table markup)
+//! - List bullets → `•`
+//! - **bold** → `*bold*`, *italic* → `_italic_`, ~~strike~~ → `~strike~`
+//! - `[text](url)` → `<url|text>`
+//!
+//! `&`, `<` and `>` are escaped everywhere except the quote marker, as Slack
+//! asks for.
+
+/// Source characters per chunk; Block Kit sections hold at most 3000.
+pub const CHUNK_CHARS: usize = 2900;
+
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Undo Slack's escaping on inbound message text.
+pub fn unescape_mrkdwn(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Convert markdown text into Slack mrkdwn.
+pub fn markdown_to_mrkdwn(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<Vec<&str>> = None;
+    let mut table: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if let Some(body) = fence.as_mut() {
+            if trimmed.starts_with("```") {
+                out.push(code_block(body));
+                fence = None;
+            } else {
+                body.push(line);
+            }
+            continue;
+        }
+
+        if !table.is_empty() && !is_table_line(trimmed) {
+            out.push(code_block(&table));
+            table.clear();
+        }
+
+        if trimmed.starts_with("```") {
+            fence = Some(Vec::new());
+        } else if is_table_line(trimmed) {
+            table.push(trimmed);
+        } else if let Some(rest) = trimmed.strip_prefix('>') {
+            out.push(format!("> {}", render_inline(rest.trim_start())));
+        } else if let Some(heading) = heading_text(trimmed) {
+            out.push(format!("*{}*", render_inline(heading)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            out.push(format!("{indent}• {}", render_inline(item)));
+        } else {
+            out.push(render_inline(line));
+        }
+    }
+
+    // An unterminated fence still renders as code.
+    if let Some(body) = fence {
+        out.push(code_block(&body));
+    }
+    if !table.is_empty() {
+        out.push(code_block(&table));
+    }
+
+    out.join("\n")
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) {
+        line[hashes..].strip_prefix(' ').map(str::trim)
+    } else {
+        None
+    }
+}
+
+fn is_table_line(line: &str) -> bool {
+    line.starts_with('|') && line.ends_with('|') && line.len() > 1
+}
+
+fn code_block(body: &[&str]) -> String {
+    format!("```\n{}\n```", escape_mrkdwn(&body.join("\n")))
+}
+
+/// Markdown emphasis markers and their mrkdwn equivalents, longest first.
+const INLINE_MARKERS: &[(&str, &str)] = &[
+    ("**", "*"),
+    ("__", "*"),
+    ("~~", "~"),
+    ("*", "_"),
+    ("_", "_"),
+];
+
+fn render_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut prev: Option<char> = None;
+
+    'outer: while let Some(ch) = rest.chars().next() {
+        if ch == '`'
+            && let Some(end) = rest[1..].find('`')
+            && end > 0
+        {
+            out.push_str(&format!("`{}`", escape_mrkdwn(&rest[1..=end])));
+            prev = Some('`');
+            rest = &rest[end + 2..];
+            continue;
+        }
+
+        if ch == '['
+            && let Some((label, url, len)) = parse_link(rest)
+        {
+            out.push_str(&format!(
+                "<{}|{}>",
+                escape_mrkdwn(url),
+                escape_mrkdwn(&label.replace('|', "¦"))
+            ));
+            prev = Some(')');
+            rest = &rest[len..];
+            continue;
+        }
+
+        // `_` inside a word (snake_case) is not emphasis.
+        let word_boundary = !prev.is_some_and(char::is_alphanumeric);
+        for (marker, replacement) in INLINE_MARKERS {
+            if *marker == "_" && !word_boundary {
+                continue;
+            }
+            if let Some(after) = rest.strip_prefix(marker)
+                && let Some(end) = find_closing(after, marker)
+            {
+                out.push_str(&format!(
+                    "{replacement}{}{replacement}",
+                    render_inline(&after[..end])
+                ));
+                prev = Some(ch);
+                rest = &after[end + marker.len()..];
+                continue 'outer;
+            }
+        }
+
+        out.push_str(&escape_mrkdwn(&ch.to_string()));
+        prev = Some(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    out
+}
+
+/// Byte offset of the marker closing an emphasis span opened just before `text`.
+fn find_closing(text: &str, marker: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut from = 0;
+    while let Some(pos) = text[from..].find(marker) {
+        let end = from + pos;
+        let after = &text[end + marker.len()..];
+        let inner = &text[..end];
+        // A single `*` must not close on half of a `**`, and `_` must end a word.
+        let doubled = marker.len() == 1 && after.starts_with(marker);
+        let mid_word = marker == "_" && after.starts_with(char::is_alphanumeric);
+        if !inner.is_empty() && !inner.ends_with(char::is_whitespace) && !doubled && !mid_word {
+            return Some(end);
+        }
+        from = end + marker.len();
+        if doubled {
+            from += marker.len();
+        }
+    }
+    None
+}
+
+/// `[label](url)` at the start of `text`: label, url and byte length.
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let label = &text[1..label_end];
+    if label.is_empty() || label.contains('[') {
+        return None;
+    }
+    let url_start = label_end + 2;
+    let url_len = text[url_start..].find(')')?;
+    let url = &text[url_start..url_start + url_len];
+    let allowed = ["http://", "https://", "mailto:"];
+    if url.contains(char::is_whitespace) || !allowed.iter().any(|p| url.starts_with(p)) {
+        return None;
+    }
+    Some((label, url, url_start + url_len + 1))
+}
+
+/// Split markdown into chunks that each fit one Block Kit section.
+pub fn split_markdown(content: &str) -> Vec<String> {
+    crate::telegram::split_markdown_at(content, CHUNK_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_markup() {
+        assert_eq!(
+            markdown_to_mrkdwn("**bold** and *it* and `a<b>` ~~gone~~"),
+            "*bold* and _it_ and `a&lt;b&gt;` ~gone~"
+        );
+        assert_eq!(
+            markdown_to_mrkdwn("see [docs](https://example.com/?a=1&b=2)"),
+            "see <https://example.com/?a=1&amp;b=2|docs>"
+        );
+        assert_eq!(
+            markdown_to_mrkdwn("snake_case_name 2 * 3 <@U123>"),
+            "snake_case_name 2 * 3 &lt;@U123&gt;"
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let md = "### T-KOMA // ゲート\n- one\n> quoted\n```rust\nfn f() -> u8 { 1 }\n```\n| a | b |\n| 1 | 2 |";
+        assert_eq!(
+            markdown_to_mrkdwn(md),
+            "*T-KOMA // ゲート*\n• one\n> quoted\n```\nfn f() -&gt; u8 { 1 }\n```\n```\n| a | b |\n| 1 | 2 |\n```"
+        );
+    }
+
+    #[test]
+    fn test_unescape_round_trip() {
+        let text = "a < b && c > d";
+        assert_eq!(unescape_mrkdwn(&escape_mrkdwn(text)), text);
+    }
+}
@@ -0,0 +1,62 @@
+mod api;
+mod bot;
+mod format;
+mod send;
+
+use std::sync::Arc;
+
+use tracing::info;
+
+pub use bot::Bot;
+pub use send::{
+    send_approved_operator_ghost_prompt, send_new_operator_notification_to_pms,
+    send_operator_gateway_message,
+};
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    crate::gateway_message::from_content(id, Some("slack"), vars).text_fallback
+}
+
+/// Start the Slack app (optional - returns Ok(None) if a token is missing)
+///
+/// Checks the bot token with `auth.test`; the caller spawns [`Bot::run`] to
+/// open the Socket Mode connection.
+pub async fn start_slack_bot(
+    bot_token: Option<String>,
+    app_token: Option<String>,
+    state: Arc<crate::state::AppState>,
+) -> Result<Option<Arc<Bot>>, SlackError> {
+    let (bot_token, app_token) = match (bot_token, app_token) {
+        (Some(bot), Some(app)) if !bot.is_empty() && !app.is_empty() => (bot, app),
+        _ => {
+            info!("SLACK_BOT_TOKEN or SLACK_APP_TOKEN not set, skipping Slack app");
+            return Ok(None);
+        }
+    };
+
+    info!("Starting Slack app...");
+
+    let api = api::SlackApi::new(&bot_token);
+    let me = api.auth_test().await?;
+    info!("Slack app connected as {} ({})", me.user, me.user_id);
+
+    Ok(Some(Arc::new(Bot::new(state, api, app_token, me))))
+}
+
+/// Slack-related errors
+#[derive(Debug, thiserror::Error)]
+pub enum SlackError {
+    #[error("Slack request failed: {0}")]
+    Request(String),
+    #[error("Slack API error: {0}")]
+    Api(String),
+    #[error("Slack socket error: {0}")]
+    Socket(String),
+}
+
+impl From<reqwest::Error> for SlackError {
+    fn from(err: reqwest::Error) -> Self {
+        // Socket Mode URLs carry a connection ticket; keep them out of logs.
+        SlackError::Request(err.without_url().to_string())
+    }
+}
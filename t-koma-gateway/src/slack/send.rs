@@ -0,0 +1,417 @@
+use serde_json::{Value, json};
+use tracing::{debug, error, warn};
+
+use crate::content::ids;
+use crate::operator_flow::OutboundMessage;
+use crate::state::{AppState, PendingGatewayAction, ToolCallSummary};
+
+use super::SlackError;
+use super::api::SlackApi;
+use super::format::{CHUNK_CHARS, escape_mrkdwn, markdown_to_mrkdwn, split_markdown};
+
+const GATEWAY_HEADER: &str = "T-KOMA // ティコマ";
+
+/// Seconds a button stays usable.
+const ACTION_TTL_SECS: i64 = 900;
+
+/// Sections per message, well under Slack's 50-block limit.
+const MAX_SECTIONS: usize = 40;
+
+// ---------------------------------------------------------------------------
+// Block Kit
+// ---------------------------------------------------------------------------
+
+fn section(mrkdwn: &str) -> Value {
+    json!({ "type": "section", "text": { "type": "mrkdwn", "text": mrkdwn } })
+}
+
+fn header() -> Value {
+    json!({ "type": "header", "text": { "type": "plain_text", "text": GATEWAY_HEADER } })
+}
+
+/// A button whose `value` is routed back through `block_actions`.
+pub(super) fn button(
+    label: &str,
+    value: &str,
+    style: Option<t_koma_core::GatewayActionStyle>,
+) -> Value {
+    let mut button = json!({
+        "type": "button",
+        "text": { "type": "plain_text", "text": label },
+        "action_id": value,
+        "value": value,
+    });
+    match style {
+        Some(
+            t_koma_core::GatewayActionStyle::Primary | t_koma_core::GatewayActionStyle::Success,
+        ) => button["style"] = json!("primary"),
+        Some(t_koma_core::GatewayActionStyle::Danger) => button["style"] = json!("danger"),
+        _ => {}
+    }
+    button
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+/// Send ghost assistant text as plain mrkdwn messages.
+pub(super) async fn send_assistant_text(
+    api: &SlackApi,
+    channel: &str,
+    content: &str,
+) -> Result<(), SlackError> {
+    for chunk in split_markdown(content) {
+        api.post_message(channel, &markdown_to_mrkdwn(&chunk), None)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Send a gateway system message as Block Kit; buttons go on the last message.
+pub(super) async fn send_gateway_text(
+    api: &SlackApi,
+    channel: &str,
+    content: &str,
+    buttons: Vec<Value>,
+) -> Result<(), SlackError> {
+    let sections: Vec<String> = split_markdown(content)
+        .iter()
+        .map(|chunk| markdown_to_mrkdwn(chunk))
+        .collect();
+    let batches: Vec<&[String]> = sections.chunks(MAX_SECTIONS).collect();
+    let last = batches.len() - 1;
+    let mut buttons = Some(buttons).filter(|b| !b.is_empty());
+
+    for (index, batch) in batches.iter().enumerate() {
+        let mut blocks = Vec::with_capacity(batch.len() + 2);
+        if index == 0 {
+            blocks.push(header());
+        }
+        blocks.extend(batch.iter().map(|text| section(text)));
+        if index == last
+            && let Some(elements) = buttons.take()
+        {
+            blocks.push(json!({ "type": "actions", "elements": elements }));
+        }
+        api.post_message(channel, &batch.join("\n"), Some(&blocks))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Render tool call summaries (verbose mode) as one compact message.
+pub(super) async fn send_tool_calls(
+    api: &SlackApi,
+    channel: &str,
+    calls: &[ToolCallSummary],
+) -> Result<(), SlackError> {
+    if calls.is_empty() {
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    for call in calls {
+        let arrow = if call.is_error { "⚠" } else { "→" };
+        let line = format!(
+            "`{}({})` {} {}\n",
+            escape_mrkdwn(&call.name).replace('`', "'"),
+            escape_mrkdwn(&call.input_preview).replace('`', "'"),
+            arrow,
+            escape_mrkdwn(&call.output_preview)
+        );
+        // Never cut through a code span: drop whole lines instead.
+        if text.chars().count() + line.chars().count() > CHUNK_CHARS {
+            break;
+        }
+        text.push_str(&line);
+    }
+    api.post_message(channel, &text, None).await
+}
+
+/// Register a one-shot action and return its button value.
+async fn register_action(state: &AppState, action: PendingGatewayAction) -> String {
+    let token = uuid::Uuid::new_v4().to_string();
+    state.set_pending_gateway_action(&token, action).await;
+    format!("tk:a:{}", token)
+}
+
+pub(super) async fn send_interface_prompt(api: &SlackApi, channel: &str) {
+    let message =
+        crate::gateway_message::from_content(ids::SLACK_INTERFACE_PROMPT, Some("slack"), &[]);
+    let buttons = message
+        .actions
+        .iter()
+        .map(|action| {
+            button(
+                &action.label,
+                &format!("tk:iface:{}", action.id),
+                action.style,
+            )
+        })
+        .collect();
+    if let Err(e) = send_gateway_text(api, channel, &message.text_fallback, buttons).await {
+        error!("Failed to send Slack interface prompt: {}", e);
+    }
+}
+
+/// Ask for a ghost name; the operator answers with a plain message.
+pub(super) async fn send_ghost_name_prompt(
+    api: &SlackApi,
+    channel: &str,
+) -> Result<(), SlackError> {
+    let text = super::render_message(ids::SLACK_GHOST_NAME_PROMPT, &[]);
+    send_gateway_text(api, channel, &text, Vec::new()).await
+}
+
+// ---------------------------------------------------------------------------
+// Outbound message dispatch
+// ---------------------------------------------------------------------------
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn send_slack_gateway_message(
+    state: &AppState,
+    api: &SlackApi,
+    channel: &str,
+    external_id: &str,
+    operator_id: &str,
+    ghost_name: &str,
+    session_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<(), SlackError> {
+    let mut buttons = Vec::new();
+    for action in message.actions.iter().take(5) {
+        let value = register_action(
+            state,
+            PendingGatewayAction {
+                operator_id: operator_id.to_string(),
+                ghost_name: ghost_name.to_string(),
+                session_id: session_id.to_string(),
+                external_id: external_id.to_string(),
+                channel_id: channel.to_string(),
+                intent: action.intent.clone(),
+                payload: None,
+                expires_at: chrono::Utc::now().timestamp() + ACTION_TTL_SECS,
+            },
+        )
+        .await;
+        buttons.push(button(&action.label, &value, action.style));
+    }
+
+    send_gateway_text(api, channel, &message.text_fallback, buttons).await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn send_outbound_messages(
+    state: &AppState,
+    api: &SlackApi,
+    channel: &str,
+    external_id: &str,
+    operator_id: &str,
+    ghost_name: &str,
+    session_id: &str,
+    messages: Vec<OutboundMessage>,
+) {
+    debug!(
+        ghost = ghost_name,
+        channel,
+        message_count = messages.len(),
+        "sending outbound messages to Slack"
+    );
+
+    for message in &messages {
+        let result = match message {
+            OutboundMessage::AssistantText(text) => send_assistant_text(api, channel, text).await,
+            OutboundMessage::Gateway(msg) => {
+                send_slack_gateway_message(
+                    state,
+                    api,
+                    channel,
+                    external_id,
+                    operator_id,
+                    ghost_name,
+                    session_id,
+                    msg,
+                )
+                .await
+            }
+            OutboundMessage::ToolCalls(calls) => send_tool_calls(api, channel, calls).await,
+        };
+        if let Err(e) = result {
+            error!(
+                "[ghost:{}] Failed to send message to Slack: {}",
+                ghost_name, e
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PM notification for new operator registration
+// ---------------------------------------------------------------------------
+
+pub async fn send_new_operator_notification_to_pms(
+    state: &AppState,
+    slack_bot_token: &str,
+    new_operator: &t_koma_db::Operator,
+) {
+    let pms = match t_koma_db::OperatorRepository::list_puppet_masters_with_interface(
+        state.koma_db.pool(),
+        t_koma_db::Platform::Slack,
+    )
+    .await
+    {
+        Ok(list) => list,
+        Err(e) => {
+            warn!(
+                "Failed to list PM operators for new-operator notification: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if pms.is_empty() {
+        debug!("No PM operators with Slack interfaces to notify");
+        return;
+    }
+
+    let text = super::render_message(
+        ids::ADMIN_NEW_OPERATOR_PENDING,
+        &[("operator_name", &new_operator.name)],
+    );
+    let api = SlackApi::new(slack_bot_token);
+
+    for (pm_op, slack_user_id) in &pms {
+        let channel = match api.open_dm(slack_user_id).await {
+            Ok(channel) => channel,
+            Err(e) => {
+                warn!("Failed to open Slack DM with PM {}: {}", pm_op.id, e);
+                continue;
+            }
+        };
+
+        let mut buttons = Vec::new();
+        for (label, intent, style) in [
+            (
+                "APPROVE",
+                "admin.approve_operator",
+                t_koma_core::GatewayActionStyle::Success,
+            ),
+            (
+                "DENY",
+                "admin.deny_operator",
+                t_koma_core::GatewayActionStyle::Danger,
+            ),
+        ] {
+            let value = register_action(
+                state,
+                PendingGatewayAction {
+                    operator_id: pm_op.id.clone(),
+                    ghost_name: String::new(),
+                    session_id: String::new(),
+                    external_id: slack_user_id.clone(),
+                    channel_id: channel.clone(),
+                    intent: intent.to_string(),
+                    payload: Some(new_operator.id.clone()),
+                    expires_at: chrono::Utc::now().timestamp() + 3600,
+                },
+            )
+            .await;
+            buttons.push(button(label, &value, Some(style)));
+        }
+
+        if let Err(e) = send_gateway_text(&api, &channel, &text, buttons).await {
+            warn!(
+                "Failed to send new-operator notification to PM {}: {}",
+                pm_op.id, e
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Messages outside of a chat turn
+// ---------------------------------------------------------------------------
+
+/// Ask a freshly approved operator to name their first ghost.
+///
+/// Returns `Ok(false)` when there is nothing to do (no Slack interface,
+/// already welcomed or already has ghosts).
+pub async fn send_approved_operator_ghost_prompt(
+    state: &AppState,
+    slack_bot_token: &str,
+    operator_id: &str,
+) -> Result<bool, String> {
+    let operator = t_koma_db::OperatorRepository::get_by_id(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("operator not found: {}", operator_id))?;
+
+    if operator.status != t_koma_db::OperatorStatus::Approved || operator.welcomed {
+        return Ok(false);
+    }
+
+    let ghosts = t_koma_db::GhostRepository::list_by_operator(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !ghosts.is_empty() {
+        return Ok(false);
+    }
+
+    let api = SlackApi::new(slack_bot_token);
+    let Some(channel) = operator_dm_channel(state, &api, operator_id).await? else {
+        return Ok(false);
+    };
+    send_ghost_name_prompt(&api, &channel)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    t_koma_db::OperatorRepository::mark_welcomed(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+/// Send a gateway message to an operator outside of a chat turn.
+///
+/// Returns `Ok(false)` when the operator has no Slack interface.
+pub async fn send_operator_gateway_message(
+    state: &AppState,
+    slack_bot_token: &str,
+    operator_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<bool, String> {
+    let api = SlackApi::new(slack_bot_token);
+    let Some(channel) = operator_dm_channel(state, &api, operator_id).await? else {
+        return Ok(false);
+    };
+    send_gateway_text(&api, &channel, &message.text_fallback, Vec::new())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// DM channel with the operator's Slack user.
+async fn operator_dm_channel(
+    state: &AppState,
+    api: &SlackApi,
+    operator_id: &str,
+) -> Result<Option<String>, String> {
+    let interfaces =
+        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), operator_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(slack_iface) = interfaces
+        .into_iter()
+        .find(|iface| iface.platform == t_koma_db::Platform::Slack)
+    else {
+        return Ok(None);
+    };
+
+    api.open_dm(&slack_iface.external_id)
+        .await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
@@ -1,12 +1,14 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use chrono::Utc;
 use serde::Serialize;
 use tokio::sync::{RwLock, broadcast};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
 use crate::chat::compaction::CompactionConfig;
@@ -16,7 +18,7 @@ use crate::gateway_message;
 use crate::providers::provider::Provider;
 #[cfg(feature = "live-tests")]
 use crate::providers::provider::{ProviderResponse, extract_all_text};
-use crate::scheduler::{JobKind, SchedulerState};
+use crate::scheduler::{JobKind, JobSchedule, SchedulerState};
 use crate::session::{
     ChatError, DEFAULT_TOOL_LOOP_EXTRA, PendingToolApproval, PendingToolContinuation, SessionChat,
     ToolApprovalDecision,
@@ -32,6 +34,16 @@ struct PendingInterface {
     external_id: String,
 }
 
+/// A gateway message pushed to an operator's live WebSocket connections
+/// outside of a chat turn (e.g. a due reminder).
+#[derive(Debug, Clone)]
+pub struct OperatorNotice {
+    pub operator_id: String,
+    /// Response id sent to the client
+    pub id: String,
+    pub message: t_koma_core::GatewayMessage,
+}
+
 #[derive(Debug, Clone)]
 pub struct PendingGatewayAction {
     pub operator_id: String,
@@ -44,6 +56,14 @@ pub struct PendingGatewayAction {
     pub expires_at: i64,
 }
 
+/// Outcome of a reaction on a message offering gateway actions.
+#[derive(Debug, Clone)]
+pub enum ReactionAction {
+    Ready(PendingGatewayAction),
+    /// The action expired or was already used (e.g. through its button).
+    Expired,
+}
+
 #[derive(Debug, Default)]
 struct OperatorRateLimitState {
     last_5m: VecDeque<i64>,
@@ -69,6 +89,8 @@ pub struct ToolCallSummary {
     pub input_preview: String,
     pub output_preview: String,
     pub is_error: bool,
+    /// Full output of tools transports can render richly (`knowledge_search`)
+    pub output: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +104,8 @@ pub struct ChatResult {
     pub statusline: bool,
     /// Accumulated token usage across all provider calls in this chat.
     pub usage: ChatUsage,
+    /// Set when operator or ghost usage is past a budget's warning threshold.
+    pub budget_warning: Option<t_koma_db::BudgetReport>,
 }
 
 /// Accumulated token usage and turn count for a complete chat interaction.
@@ -125,7 +149,13 @@ pub enum LogEntry {
         status: u16,
     },
     /// WebSocket event
-    WebSocket { event: String, client_id: String },
+    WebSocket {
+        event: String,
+        client_id: String,
+        /// How the connection authenticated (`loopback`, `secret`, or
+        /// `token:<id> operator:<id>`)
+        identity: String,
+    },
     /// General info message
     Info { message: String },
     /// Operator message received via chat
@@ -155,6 +185,46 @@ pub enum LogEntry {
         status: String,
         job_name: String,
     },
+    /// Ghost-scheduled task status
+    Task {
+        ghost_name: String,
+        session_id: String,
+        status: String,
+        task_name: String,
+    },
+    /// Knowledge bulk ingest job status
+    Ingest {
+        ghost_name: String,
+        session_id: String,
+        job_id: String,
+        status: String,
+    },
+    /// Background job queue event (e.g. a job out of attempts)
+    Queue {
+        job_id: String,
+        job_kind: String,
+        status: String,
+    },
+    /// Transcript entry appended to a running background job
+    JobEntry {
+        job_id: String,
+        session_id: String,
+        entry: t_koma_db::TranscriptEntry,
+    },
+    /// TODO list of a running background job changed
+    JobTodos {
+        job_id: String,
+        todos: Vec<t_koma_db::TodoItem>,
+    },
+    /// Background job finished (its job log row is final)
+    JobFinished { job_id: String, status: String },
+    /// Output of a running tool, streamed before the tool returns
+    ToolOutput {
+        tool: String,
+        session_id: Option<String>,
+        job_id: Option<String>,
+        output: String,
+    },
     /// Routing decision for operator -> ghost/session
     Routing {
         platform: String,
@@ -162,6 +232,26 @@ pub enum LogEntry {
         ghost_name: String,
         session_id: String,
     },
+    /// Provider request retried after a transient failure
+    ProviderRetry {
+        provider: String,
+        model: String,
+        /// Upcoming attempt number (2 for the first retry)
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+        reason: String,
+    },
+    /// Pre-flight estimate of a chat request, before it is sent
+    RequestEstimate {
+        session_id: String,
+        provider: String,
+        model: String,
+        prompt_tokens: u32,
+        context_window: u32,
+        /// Prompt cost in USD; `None` when the model has no pricing
+        prompt_cost_usd: Option<f64>,
+    },
     /// Generic tracing event from gateway runtime
     Trace {
         level: String,
@@ -201,8 +291,16 @@ impl std::fmt::Display for LogEntry {
                 path,
                 status,
             } => write!(f, "[{}] [HTTP] {} {} {}", timestamp, method, path, status),
-            LogEntry::WebSocket { event, client_id } => {
-                write!(f, "[{}] [WS] {} {}", timestamp, event, client_id)
+            LogEntry::WebSocket {
+                event,
+                client_id,
+                identity,
+            } => {
+                write!(
+                    f,
+                    "[{}] [WS] {} {} ({})",
+                    timestamp, event, client_id, identity
+                )
             }
             LogEntry::Info { message } => {
                 write!(f, "[{}] [INFO] {}", timestamp, message)
@@ -252,6 +350,78 @@ impl std::fmt::Display for LogEntry {
                 "[{}] [CRON] {} ({}) [{}] {}",
                 timestamp, ghost_name, session_id, job_name, status
             ),
+            LogEntry::Task {
+                ghost_name,
+                session_id,
+                status,
+                task_name,
+            } => write!(
+                f,
+                "[{}] [TASK] {} ({}) [{}] {}",
+                timestamp, ghost_name, session_id, task_name, status
+            ),
+            LogEntry::Ingest {
+                ghost_name,
+                session_id,
+                job_id,
+                status,
+            } => write!(
+                f,
+                "[{}] [INGEST] {} ({}) [{}] {}",
+                timestamp, ghost_name, session_id, job_id, status
+            ),
+            LogEntry::Queue {
+                job_id,
+                job_kind,
+                status,
+            } => write!(
+                f,
+                "[{}] [QUEUE] {} {} {}",
+                timestamp, job_kind, job_id, status
+            ),
+            LogEntry::JobEntry {
+                job_id,
+                session_id,
+                entry,
+            } => write!(
+                f,
+                "[{}] [JOB] {} ({}) {} +{} block(s)",
+                timestamp,
+                job_id,
+                session_id,
+                entry.role,
+                entry.content.len()
+            ),
+            LogEntry::JobTodos { job_id, todos } => {
+                let done = todos
+                    .iter()
+                    .filter(|t| t.status == t_koma_db::TodoStatus::Done)
+                    .count();
+                write!(
+                    f,
+                    "[{}] [JOB] {} todos {}/{}",
+                    timestamp,
+                    job_id,
+                    done,
+                    todos.len()
+                )
+            }
+            LogEntry::JobFinished { job_id, status } => {
+                write!(f, "[{}] [JOB] {} finished: {}", timestamp, job_id, status)
+            }
+            LogEntry::ToolOutput {
+                tool,
+                session_id,
+                output,
+                ..
+            } => write!(
+                f,
+                "[{}] [TOOL] {} ({}) {}",
+                timestamp,
+                tool,
+                session_id.as_deref().unwrap_or("-"),
+                output.trim_end()
+            ),
             LogEntry::Routing {
                 platform,
                 operator_id,
@@ -262,6 +432,36 @@ impl std::fmt::Display for LogEntry {
                 "[{}] [ROUTE] {} {} -> {} ({})",
                 timestamp, platform, operator_id, ghost_name, session_id
             ),
+            LogEntry::ProviderRetry {
+                provider,
+                model,
+                attempt,
+                max_attempts,
+                delay_ms,
+                reason,
+            } => write!(
+                f,
+                "[{}] [RETRY] {}/{} attempt {}/{} in {}ms: {}",
+                timestamp, provider, model, attempt, max_attempts, delay_ms, reason
+            ),
+            LogEntry::RequestEstimate {
+                session_id,
+                provider,
+                model,
+                prompt_tokens,
+                context_window,
+                prompt_cost_usd,
+            } => {
+                write!(
+                    f,
+                    "[{}] [ESTIMATE] {}/{} ({}) ~{}/{} tokens",
+                    timestamp, provider, model, session_id, prompt_tokens, context_window
+                )?;
+                match prompt_cost_usd {
+                    Some(cost) => write!(f, ", ~${:.4}", cost),
+                    None => Ok(()),
+                }
+            }
             LogEntry::Trace {
                 level,
                 target,
@@ -292,10 +492,17 @@ pub struct AppState {
     default_model_chain: std::sync::RwLock<Vec<String>>,
     /// Model registry keyed by alias
     models: std::sync::RwLock<HashMap<String, ModelEntry>>,
-    /// Per-model circuit breaker for fallback decisions.
-    pub circuit_breaker: CircuitBreaker,
+    /// Per-model circuit breaker for fallback decisions (shared with the
+    /// knowledge engine's embeddings provider).
+    pub circuit_breaker: Arc<CircuitBreaker>,
+    /// Per-client HTTP/WS request and connection limits (`[client_limits]`)
+    pub client_limiter: Arc<crate::client_limits::ClientLimiter>,
     /// Log broadcast channel
     log_tx: broadcast::Sender<LogEntry>,
+    /// Operator notice broadcast channel (WebSocket push)
+    notice_tx: broadcast::Sender<OperatorNotice>,
+    /// Operators whose pending approvals changed (WebSocket push)
+    approvals_tx: broadcast::Sender<String>,
     /// T-KOMA database pool
     pub koma_db: t_koma_db::KomaDbPool,
     /// Active ghost name per operator
@@ -308,8 +515,19 @@ pub struct AppState {
     pending_tool_loops: RwLock<HashMap<String, PendingToolContinuation>>,
     /// Pending Discord gateway actions keyed by opaque token
     pending_gateway_actions: RwLock<HashMap<String, PendingGatewayAction>>,
-    /// Active chat requests keyed by operator/ghost/session
-    in_flight_chats: RwLock<HashSet<String>>,
+    /// Reactions standing in for gateway action buttons: `message:emoji` -> token
+    pending_reaction_actions: RwLock<HashMap<String, String>>,
+    /// Active chat requests keyed by operator/ghost/session, with the token
+    /// that stops them
+    in_flight_chats: RwLock<HashMap<String, CancellationToken>>,
+    /// Running heartbeat/reflection jobs keyed by job log ID, with the token
+    /// that cancels them
+    running_jobs: RwLock<HashMap<String, CancellationToken>>,
+    /// Set once a shutdown signal arrived: new chats, jobs and WebSocket
+    /// connections are refused while in-flight ones drain
+    shutting_down: AtomicBool,
+    /// Lease-based job coordination with other gateways (`None` when off)
+    job_coordination: Option<crate::coordination::JobCoordination>,
     /// Last ignored message keyed by operator/ghost/session
     ignored_messages: RwLock<HashMap<String, String>>,
     /// Per-operator message rate limit windows
@@ -330,6 +548,8 @@ pub struct AppState {
     heartbeat_runner: RwLock<Option<JoinHandle<()>>>,
     /// CRON runner handle
     cron_runner: RwLock<Option<JoinHandle<()>>>,
+    /// Job queue worker handles
+    job_queue_workers: RwLock<Vec<JoinHandle<()>>>,
 
     /// Heartbeat override schedule per session (chat_key)
     heartbeat_overrides: RwLock<HashMap<String, HeartbeatOverride>>,
@@ -338,8 +558,79 @@ pub struct AppState {
     scheduler: RwLock<SchedulerState>,
     /// Discord bot token (optional, used by server-side Discord notifications)
     discord_bot_token: RwLock<Option<String>>,
+    /// Telegram bot token (optional, used by server-side Telegram notifications)
+    telegram_bot_token: RwLock<Option<String>>,
+    /// Slack bot token (optional, used by server-side Slack notifications)
+    slack_bot_token: RwLock<Option<String>>,
+    /// Email bridge mailer (optional, used by server-side email notifications)
+    email_mailer: RwLock<Option<crate::email::Mailer>>,
+    /// Shared secret accepted on `/ws` and `/logs` (`T_KOMA_GATEWAY_SECRET`)
+    gateway_secret: RwLock<Option<String>>,
+    /// Audio transcription backend (`None` when `[transcription]` is disabled)
+    transcriber: RwLock<Option<Arc<dyn crate::transcription::Transcriber>>>,
+    /// Sampling overrides for background jobs
+    job_generation: std::sync::RwLock<JobGenerationOverrides>,
+    /// Settings the heartbeat runner reads on every tick
+    heartbeat_settings: std::sync::RwLock<HeartbeatRunnerSettings>,
+    /// Settings from the last successful config load, diffed on reload
+    loaded_settings: std::sync::RwLock<Option<t_koma_core::Settings>>,
+}
+
+/// Sampling overrides applied on top of the model's parameters for
+/// background jobs (`[heartbeat_timing.generation]`, `[reflection.generation]`).
+#[derive(Debug, Clone, Default)]
+pub struct JobGenerationOverrides {
+    pub heartbeat: t_koma_core::GenerationParams,
+    pub reflection: t_koma_core::GenerationParams,
+}
+
+impl JobGenerationOverrides {
+    pub fn from_settings(settings: &t_koma_core::Settings) -> Self {
+        Self {
+            heartbeat: settings.heartbeat_timing.generation.clone(),
+            reflection: settings.reflection.generation.clone(),
+        }
+    }
+}
+
+/// Timing and housekeeping settings consumed by the heartbeat runner.
+#[derive(Debug, Clone, Default)]
+pub struct HeartbeatRunnerSettings {
+    pub timing: t_koma_core::HeartbeatTimingSettings,
+    pub retention: t_koma_core::JobLogRetentionSettings,
+    pub prompt_cache: t_koma_core::PromptCacheSettings,
+    pub session_archive: t_koma_core::SessionArchiveSettings,
+    pub job_queue: t_koma_core::JobQueueSettings,
+    pub reflection_quality: t_koma_core::ReflectionQualitySettings,
+}
+
+impl HeartbeatRunnerSettings {
+    pub fn from_settings(settings: &t_koma_core::Settings) -> Self {
+        Self {
+            timing: settings.heartbeat_timing.clone(),
+            retention: settings.job_logs.clone(),
+            prompt_cache: settings.prompt_cache.clone(),
+            session_archive: settings.session_archive.clone(),
+            job_queue: settings.job_queue.clone(),
+            reflection_quality: settings.reflection.quality.clone(),
+        }
+    }
+}
+
+/// Outcome of [`AppState::reload_config`].
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReload {
+    /// Top-level config sections whose new values are now in effect.
+    pub applied: Vec<String>,
+    /// Changed settings that only take effect after a gateway restart.
+    pub restart_required: Vec<String>,
 }
 
+/// Config sections that are only read at startup.
+const RESTART_ONLY_SECTIONS: &[&str] = &[
+    "gateway", "discord", "telegram", "slack", "email", "logging", "mcp",
+];
+
 /// Model entry tracked by the gateway
 #[derive(Clone)]
 pub struct ModelEntry {
@@ -353,6 +644,20 @@ pub struct ModelEntry {
     pub retry_on_empty: u32,
 }
 
+impl ModelEntry {
+    /// This entry with `overrides` applied on top of the model's sampling
+    /// parameters.
+    pub fn with_generation_overrides(&self, overrides: &t_koma_core::GenerationParams) -> Self {
+        if overrides.is_empty() {
+            return self.clone();
+        }
+        Self {
+            client: Arc::from(self.client.with_generation_overrides(overrides)),
+            ..self.clone()
+        }
+    }
+}
+
 impl AppState {
     /// Create a new AppState with the given model registry and database.
     ///
@@ -368,6 +673,8 @@ impl AppState {
     ) -> Self {
         let (log_tx, _) = broadcast::channel(100);
         let _ = GLOBAL_LOG_TX.set(log_tx.clone());
+        let (notice_tx, _) = broadcast::channel(100);
+        let (approvals_tx, _) = broadcast::channel(100);
         let session_chat = SessionChat::new(
             Some(Arc::clone(&knowledge_engine)),
             skill_paths,
@@ -377,15 +684,24 @@ impl AppState {
         Self {
             default_model_chain: std::sync::RwLock::new(default_model_chain),
             models: std::sync::RwLock::new(models),
-            circuit_breaker: CircuitBreaker::new(),
+            circuit_breaker: Arc::new(CircuitBreaker::new()),
+            client_limiter: Arc::new(crate::client_limits::ClientLimiter::new(
+                t_koma_core::ClientLimitSettings::default(),
+            )),
             log_tx,
+            notice_tx,
+            approvals_tx,
             koma_db,
             active_ghosts: RwLock::new(HashMap::new()),
             pending_interfaces: RwLock::new(HashMap::new()),
             pending_tool_approvals: RwLock::new(HashMap::new()),
             pending_tool_loops: RwLock::new(HashMap::new()),
             pending_gateway_actions: RwLock::new(HashMap::new()),
-            in_flight_chats: RwLock::new(HashSet::new()),
+            pending_reaction_actions: RwLock::new(HashMap::new()),
+            in_flight_chats: RwLock::new(HashMap::new()),
+            running_jobs: RwLock::new(HashMap::new()),
+            shutting_down: AtomicBool::new(false),
+            job_coordination: None,
             ignored_messages: RwLock::new(HashMap::new()),
             operator_rate_limits: RwLock::new(HashMap::new()),
             session_chat,
@@ -394,9 +710,48 @@ impl AppState {
             ghost_knowledge_watchers: RwLock::new(HashMap::new()),
             heartbeat_runner: RwLock::new(None),
             cron_runner: RwLock::new(None),
+            job_queue_workers: RwLock::new(Vec::new()),
             heartbeat_overrides: RwLock::new(HashMap::new()),
             scheduler: RwLock::new(SchedulerState::new()),
             discord_bot_token: RwLock::new(None),
+            telegram_bot_token: RwLock::new(None),
+            slack_bot_token: RwLock::new(None),
+            email_mailer: RwLock::new(None),
+            gateway_secret: RwLock::new(None),
+            transcriber: RwLock::new(None),
+            job_generation: std::sync::RwLock::new(JobGenerationOverrides::default()),
+            heartbeat_settings: std::sync::RwLock::new(HeartbeatRunnerSettings::default()),
+            loaded_settings: std::sync::RwLock::new(None),
+        }
+    }
+
+    /// Use `circuit_breaker` instead of a fresh one, e.g. the breaker already
+    /// handed to the knowledge engine's embeddings provider.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Coordinate heartbeats and reflections with other gateways on the same
+    /// koma DB through leases lasting `lease_secs`.
+    pub fn with_job_coordination(mut self, lease_secs: u64) -> Self {
+        self.job_coordination = Some(crate::coordination::JobCoordination::new(lease_secs));
+        self
+    }
+
+    /// Claim a background job such as `heartbeat:<session_id>` for this
+    /// gateway. `None` means another gateway is already running it.
+    pub async fn acquire_job_lease(&self, key: &str) -> Option<crate::coordination::JobLease> {
+        match &self.job_coordination {
+            Some(coordination) => coordination.acquire(self.koma_db.pool(), key).await,
+            None => Some(crate::coordination::JobLease::local()),
+        }
+    }
+
+    /// Release all job leases held by this gateway (on shutdown).
+    pub async fn release_job_leases(&self) {
+        if let Some(coordination) = &self.job_coordination {
+            coordination.release_all(self.koma_db.pool()).await;
         }
     }
 
@@ -428,6 +783,119 @@ impl AppState {
         guard.clone()
     }
 
+    pub async fn set_telegram_bot_token(&self, token: Option<String>) {
+        *self.telegram_bot_token.write().await = token;
+    }
+
+    pub async fn telegram_bot_token(&self) -> Option<String> {
+        self.telegram_bot_token.read().await.clone()
+    }
+
+    pub async fn set_slack_bot_token(&self, token: Option<String>) {
+        *self.slack_bot_token.write().await = token;
+    }
+
+    pub async fn slack_bot_token(&self) -> Option<String> {
+        self.slack_bot_token.read().await.clone()
+    }
+
+    pub async fn set_email_mailer(&self, mailer: Option<crate::email::Mailer>) {
+        *self.email_mailer.write().await = mailer;
+    }
+
+    pub async fn email_mailer(&self) -> Option<crate::email::Mailer> {
+        self.email_mailer.read().await.clone()
+    }
+
+    pub async fn set_gateway_secret(&self, secret: Option<String>) {
+        *self.gateway_secret.write().await = secret.filter(|secret| !secret.is_empty());
+    }
+
+    /// Whether `candidate` is the shared gateway secret (constant time).
+    pub async fn gateway_secret_matches(&self, candidate: &str) -> bool {
+        let guard = self.gateway_secret.read().await;
+        let Some(secret) = guard.as_deref() else {
+            return false;
+        };
+        secret.len() == candidate.len()
+            && secret
+                .bytes()
+                .zip(candidate.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    pub async fn set_transcriber(
+        &self,
+        transcriber: Option<Arc<dyn crate::transcription::Transcriber>>,
+    ) {
+        *self.transcriber.write().await = transcriber;
+    }
+
+    pub async fn transcriber(&self) -> Option<Arc<dyn crate::transcription::Transcriber>> {
+        self.transcriber.read().await.clone()
+    }
+
+    /// Rebuild the reply output filters; moderation aliases resolve against
+    /// the current model registry.
+    pub fn set_output_filters(&self, settings: &t_koma_core::config::OutputFilterSettings) {
+        let filters = crate::chat::output_filter::OutputFilters::from_settings(settings, |alias| {
+            self.get_model_by_alias(alias).map(|model| model.client)
+        });
+        self.session_chat.set_output_filters(filters);
+    }
+
+    /// Apply `[compaction]` and point compaction summaries at the first
+    /// available `heartbeat_model` alias (the chat model when unset).
+    pub fn set_compaction(&self, settings: &t_koma_core::Settings) {
+        self.session_chat
+            .set_compaction_config(CompactionConfig::from(&settings.compaction));
+        let summarizer = settings
+            .heartbeat_model
+            .iter()
+            .flat_map(|aliases| aliases.iter())
+            .find_map(|alias| self.get_model_by_alias(alias.trim()))
+            .map(|model| model.client);
+        self.session_chat.set_compaction_summarizer(summarizer);
+    }
+
+    pub fn set_job_generation(&self, overrides: JobGenerationOverrides) {
+        *self
+            .job_generation
+            .write()
+            .expect("job generation lock poisoned") = overrides;
+    }
+
+    pub fn job_generation(&self) -> JobGenerationOverrides {
+        self.job_generation
+            .read()
+            .expect("job generation lock poisoned")
+            .clone()
+    }
+
+    pub fn set_heartbeat_settings(&self, settings: HeartbeatRunnerSettings) {
+        *self
+            .heartbeat_settings
+            .write()
+            .expect("heartbeat settings lock poisoned") = settings;
+    }
+
+    pub fn heartbeat_settings(&self) -> HeartbeatRunnerSettings {
+        self.heartbeat_settings
+            .read()
+            .expect("heartbeat settings lock poisoned")
+            .clone()
+    }
+
+    /// Record the settings the gateway started with so a later
+    /// [`reload_config`](Self::reload_config) can report what changed.
+    pub fn set_loaded_settings(&self, settings: t_koma_core::Settings) {
+        *self
+            .loaded_settings
+            .write()
+            .expect("loaded settings lock poisoned") = Some(settings);
+    }
+
     pub async fn set_heartbeat_override(
         &self,
         key: &str,
@@ -454,9 +922,14 @@ impl AppState {
         guard.get(key).copied()
     }
 
-    pub async fn set_heartbeat_due(&self, key: &str, next_due: Option<i64>) {
+    /// Set an idle-based heartbeat due time, dropping any continue schedule.
+    /// `idle_secs` is the session's effective idle delay, shown alongside.
+    pub async fn set_heartbeat_due(&self, key: &str, next_due: Option<i64>, idle_secs: i64) {
         let mut guard = self.scheduler.write().await;
-        guard.set_due(JobKind::Heartbeat, key, next_due);
+        match next_due {
+            Some(due) => guard.set_interval(JobKind::Heartbeat, key, due, idle_secs),
+            None => guard.clear(JobKind::Heartbeat, key),
+        }
     }
 
     pub async fn get_heartbeat_due(&self, key: &str) -> Option<i64> {
@@ -470,23 +943,41 @@ impl AppState {
         guard.set_due(kind, key, next_due);
     }
 
+    /// Record the next run of an entry driven by a cron expression.
+    pub async fn scheduler_set_recurring(
+        &self,
+        kind: JobKind,
+        key: &str,
+        next_due: i64,
+        schedule: &str,
+    ) {
+        let mut guard = self.scheduler.write().await;
+        guard.set_recurring(kind, key, next_due, schedule);
+    }
+
     /// Generic scheduler read for any job kind.
     pub async fn scheduler_get(&self, kind: JobKind, key: &str) -> Option<i64> {
         let guard = self.scheduler.read().await;
         guard.get_due(kind, key)
     }
 
+    /// Scheduler entry with its cron expression, if any.
+    pub async fn scheduler_entry(&self, kind: JobKind, key: &str) -> Option<JobSchedule> {
+        let guard = self.scheduler.read().await;
+        guard.get(kind, key)
+    }
+
     /// List all scheduler entries (for admin/TUI display).
-    pub async fn scheduler_state(&self) -> Vec<(JobKind, String, i64)> {
+    pub async fn scheduler_state(&self) -> Vec<(JobKind, String, JobSchedule)> {
         let guard = self.scheduler.read().await;
         guard.list_all()
     }
 
     /// Start the heartbeat runner if it isn't already running.
-    pub async fn start_heartbeat_runner(
-        self: &Arc<Self>,
-        timing: t_koma_core::HeartbeatTimingSettings,
-    ) {
+    ///
+    /// The runner reads [`heartbeat_settings`](Self::heartbeat_settings) on
+    /// every tick, so set them before starting it.
+    pub async fn start_heartbeat_runner(self: &Arc<Self>) {
         let mut guard = self.heartbeat_runner.write().await;
         if let Some(handle) = guard.as_ref()
             && !handle.is_finished()
@@ -494,10 +985,20 @@ impl AppState {
             return;
         }
 
-        let handle = crate::heartbeat::start_heartbeat_runner(Arc::clone(self), timing);
+        let handle = crate::heartbeat::start_heartbeat_runner(Arc::clone(self));
         *guard = Some(handle);
     }
 
+    /// Start the job queue workers if they aren't already running.
+    pub async fn start_job_queue(self: &Arc<Self>) {
+        let mut guard = self.job_queue_workers.write().await;
+        if guard.iter().any(|handle| !handle.is_finished()) {
+            return;
+        }
+
+        *guard = crate::job_queue::start_job_queue_workers(Arc::clone(self));
+    }
+
     /// Start the CRON runner if it isn't already running.
     pub async fn start_cron_runner(self: &Arc<Self>, check_seconds: u64) {
         let mut guard = self.cron_runner.write().await;
@@ -517,7 +1018,7 @@ impl AppState {
     }
 
     /// Access the knowledge settings (from the engine).
-    pub fn knowledge_settings(&self) -> &t_koma_knowledge::KnowledgeSettings {
+    pub fn knowledge_settings(&self) -> Arc<t_koma_knowledge::KnowledgeSettings> {
         self.knowledge_engine.settings()
     }
 
@@ -575,6 +1076,13 @@ impl AppState {
             .collect()
     }
 
+    /// Circuit breaker state of every configured model, sorted by alias.
+    pub fn model_health(&self) -> Vec<crate::circuit_breaker::BreakerSnapshot> {
+        let mut aliases = self.available_model_aliases();
+        aliases.sort();
+        self.circuit_breaker.snapshot(&aliases)
+    }
+
     /// Get a model entry by provider name and model id
     pub fn get_model_by_provider_and_id(
         &self,
@@ -601,7 +1109,7 @@ impl AppState {
                 id: entry.model.clone(),
                 name: entry.alias.clone(),
                 description: Some(format!("{} ({})", entry.model, entry.provider)),
-                context_length: None,
+                context_length: entry.context_window,
             })
             .collect();
 
@@ -609,10 +1117,35 @@ impl AppState {
         models
     }
 
+    /// Apply a provider model catalog to the live registry; returns the
+    /// aliases the catalog does not list.
+    pub fn apply_model_catalog(&self, catalog: &[t_koma_db::ModelCatalogEntry]) -> Vec<String> {
+        let mut models = self.models.write().expect("models lock poisoned");
+        crate::model_registry::apply_catalog(&mut models, catalog)
+    }
+
     /// Reload model registry from current config.toml without restarting the gateway.
     pub async fn reload_model_registry(&self) -> Result<(), String> {
+        self.reload_config(None).await.map(|_| ())
+    }
+
+    /// Reload `config.toml` and swap every runtime-adjustable setting in place:
+    /// models, compaction, heartbeat timing, knowledge search, tools, thinking,
+    /// transcription and output filters. Live sessions are kept.
+    ///
+    /// Returns which sections changed since the last load, split into those
+    /// now in effect and those that still need a restart. A reload that
+    /// changed something is recorded as a `config.reloaded` event, caused by
+    /// `actor_id` (`None` for the local admin or the config watcher).
+    pub async fn reload_config(&self, actor_id: Option<&str>) -> Result<ConfigReload, String> {
         let config = t_koma_core::Config::load().map_err(|e| e.to_string())?;
-        let registry = crate::model_registry::build_from_config(&config)?;
+        let mut registry = crate::model_registry::build_from_config(&config)?;
+        crate::model_registry::sync_pricing(self.koma_db.pool(), &config)
+            .await
+            .map_err(|e| e.to_string())?;
+        crate::model_registry::apply_cached_catalog(self.koma_db.pool(), &mut registry.models)
+            .await
+            .map_err(|e| e.to_string())?;
 
         {
             let mut chain = self
@@ -625,13 +1158,62 @@ impl AppState {
             let mut models = self.models.write().expect("models lock poisoned");
             *models = registry.models;
         }
+        self.set_transcriber(crate::transcription::from_settings(&config.settings))
+            .await;
+        self.session_chat
+            .set_thinking_display(config.settings.thinking.display);
+        self.session_chat
+            .set_tool_settings(config.settings.tools.clone());
+        self.set_job_generation(JobGenerationOverrides::from_settings(&config.settings));
+        self.set_output_filters(&config.settings.output_filters);
+        self.set_compaction(&config.settings);
+        self.set_heartbeat_settings(HeartbeatRunnerSettings::from_settings(&config.settings));
+        self.client_limiter
+            .set_settings(config.settings.client_limits.clone());
+        let knowledge =
+            self.knowledge_engine
+                .reload_settings(&t_koma_knowledge::KnowledgeSettings::from(
+                    &config.settings.tools.knowledge,
+                ));
+
+        let previous = self
+            .loaded_settings
+            .write()
+            .expect("loaded settings lock poisoned")
+            .replace(config.settings.clone());
+        let mut reload = diff_settings(previous.as_ref(), &config.settings);
+        reload.restart_required.extend(
+            knowledge
+                .restart_required
+                .iter()
+                .map(|field| format!("tools.knowledge.{field}")),
+        );
+
+        let changed = !reload.applied.is_empty() || !reload.restart_required.is_empty();
+        if changed && !self.koma_db.is_read_only() {
+            t_koma_db::EventRepository::record(
+                self.koma_db.pool(),
+                &t_koma_db::NewEvent::new(t_koma_db::EventKind::ConfigReloaded, "config")
+                    .with_actor(actor_id)
+                    .with_data(serde_json::json!({
+                        "applied": reload.applied,
+                        "restart_required": reload.restart_required,
+                    })),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
 
         self.log(LogEntry::Info {
-            message: "Reloaded model registry from config".to_string(),
+            message: format!(
+                "Reloaded config (applied: [{}], restart required: [{}])",
+                reload.applied.join(", "),
+                reload.restart_required.join(", ")
+            ),
         })
         .await;
 
-        Ok(())
+        Ok(reload)
     }
 
     /// Get a receiver for log entries
@@ -644,6 +1226,26 @@ impl AppState {
         let _ = self.log_tx.send(entry);
     }
 
+    /// Get a receiver for operator notices
+    pub fn subscribe_operator_notices(&self) -> broadcast::Receiver<OperatorNotice> {
+        self.notice_tx.subscribe()
+    }
+
+    /// Push a notice to connected WebSocket clients. Returns `false` when no
+    /// client is listening.
+    pub fn notify_operator(&self, notice: OperatorNotice) -> bool {
+        self.notice_tx.send(notice).is_ok()
+    }
+
+    /// Get a receiver for the ids of operators whose pending approvals changed
+    pub fn subscribe_approval_changes(&self) -> broadcast::Receiver<String> {
+        self.approvals_tx.subscribe()
+    }
+
+    fn approvals_changed(&self, operator_id: &str) {
+        let _ = self.approvals_tx.send(operator_id.to_string());
+    }
+
     /// Restart the gateway process by spawning a replacement process and exiting.
     pub async fn restart_gateway(&self) -> Result<(), String> {
         let executable = std::env::current_exe().map_err(|e| e.to_string())?;
@@ -667,6 +1269,102 @@ impl AppState {
         Ok(())
     }
 
+    /// Stop taking new work: chats get a shutdown notice, the heartbeat and
+    /// CRON runners stop after their current tick, job queue workers after
+    /// their current job, and `/ws` refuses upgrades.
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Wait for in-flight chats and background jobs to finish.
+    ///
+    /// After `timeout`, operator chats are cancelled (like `STOP`) and get a
+    /// short grace period to save what they have. Returns the number of
+    /// chats still running at that point.
+    pub async fn drain_in_flight(&self, timeout: Duration) -> usize {
+        const POLL: Duration = Duration::from_millis(100);
+        const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline {
+            if self.in_flight_chats.read().await.is_empty() {
+                return 0;
+            }
+            tokio::time::sleep(POLL).await;
+        }
+
+        let remaining = {
+            let guard = self.in_flight_chats.read().await;
+            for token in guard.values() {
+                token.cancel();
+            }
+            guard.len()
+        };
+        warn!(
+            "Drain timeout reached; cancelled {} in-flight chat(s)",
+            remaining
+        );
+        let deadline = tokio::time::Instant::now() + CANCEL_GRACE;
+        while tokio::time::Instant::now() < deadline {
+            if self.in_flight_chats.read().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(POLL).await;
+        }
+        remaining
+    }
+
+    /// Save the scheduler state so the next start keeps the same schedule.
+    pub async fn persist_scheduler(&self) -> Result<usize, DbError> {
+        let jobs: Vec<t_koma_db::ScheduledJob> = self
+            .scheduler_state()
+            .await
+            .into_iter()
+            .map(|(kind, key, entry)| t_koma_db::ScheduledJob {
+                kind: kind.as_str().to_string(),
+                key,
+                next_due: entry.next_due,
+                schedule: entry.schedule,
+            })
+            .collect();
+        t_koma_db::SchedulerStateRepository::save(self.koma_db.pool(), &jobs).await?;
+        Ok(jobs.len())
+    }
+
+    /// Load the scheduler state saved by the last graceful shutdown.
+    ///
+    /// Call before the runners start; entries of unknown job kinds are skipped.
+    pub async fn restore_scheduler(&self) -> Result<usize, DbError> {
+        let jobs = t_koma_db::SchedulerStateRepository::load(self.koma_db.pool()).await?;
+        let mut guard = self.scheduler.write().await;
+        let mut restored = 0;
+        for job in jobs {
+            match JobKind::from_name(&job.kind) {
+                Some(kind) => {
+                    match &job.schedule {
+                        Some(schedule) => {
+                            guard.set_recurring(kind, &job.key, job.next_due, schedule)
+                        }
+                        None => guard.set_due(kind, &job.key, Some(job.next_due)),
+                    }
+                    restored += 1;
+                }
+                None => warn!("Skipping saved schedule of unknown job kind {}", job.kind),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Close the koma and knowledge DB pools.
+    pub async fn close_pools(&self) {
+        self.koma_db.close().await;
+        self.knowledge_engine.pool().close().await;
+    }
+
     /// Send a chat message and get the AI response
     ///
     /// This is a convenience method that delegates to `session_chat.chat()`.
@@ -711,16 +1409,34 @@ impl AppState {
     ) -> Result<ChatResult, ChatError> {
         let chat_key = Self::chat_key(operator_id, ghost_name, session_id);
         if self.is_chat_in_flight(&chat_key).await {
-            if !Self::is_continue_message(message) {
-                self.set_ignored_message(&chat_key, message).await;
-            }
+            let text = if Self::is_stop_message(message) {
+                self.stop_chat(&chat_key).await;
+                render_message(ids::CHAT_STOP_REQUESTED, &[])
+            } else {
+                if !Self::is_continue_message(message) {
+                    self.set_ignored_message(&chat_key, message).await;
+                }
+                render_message(ids::CHAT_BUSY, &[])
+            };
+            return Ok(ChatResult {
+                text,
+                compaction_happened: false,
+                tool_calls: Vec::new(),
+                model_alias: String::new(),
+                statusline: false,
+                usage: ChatUsage::default(),
+                budget_warning: None,
+            });
+        }
+        if self.is_shutting_down() {
             return Ok(ChatResult {
-                text: render_message(ids::CHAT_BUSY, &[]),
+                text: render_message(ids::CHAT_SHUTTING_DOWN, &[]),
                 compaction_happened: false,
                 tool_calls: Vec::new(),
                 model_alias: String::new(),
                 statusline: false,
                 usage: ChatUsage::default(),
+                budget_warning: None,
             });
         }
 
@@ -735,6 +1451,7 @@ impl AppState {
                         model_alias: String::new(),
                         statusline: false,
                         usage: ChatUsage::default(),
+                        budget_warning: None,
                     });
                 }
             }
@@ -759,9 +1476,10 @@ impl AppState {
                 }
                 Err(e) => return Err(ChatError::Database(e)),
             };
+        let budget_warning = self.check_usage_budget(&ghost.id, operator_id).await?;
         let pre_compaction_state =
             t_koma_db::SessionRepository::get_by_id(self.koma_db.pool(), session_id).await?;
-        self.set_chat_in_flight(&chat_key).await;
+        let cancel = self.set_chat_in_flight(&chat_key).await;
 
         let workspace_path = t_koma_db::ghosts::ghost_workspace_path(&ghost.name)?;
         if let Err(err) = crate::heartbeat::ensure_heartbeat_file(&workspace_path) {
@@ -784,6 +1502,7 @@ impl AppState {
                 &message,
                 attachments,
                 tool_call_tx,
+                &cancel,
             )
             .await;
         self.clear_chat_in_flight(&chat_key).await;
@@ -813,9 +1532,33 @@ impl AppState {
             model_alias,
             statusline: ghost.statusline,
             usage,
+            budget_warning,
         })
     }
 
+    /// Enforce the operator and ghost monthly usage budgets before a provider call.
+    ///
+    /// Returns the report when usage is past a warning threshold and
+    /// `ChatError::OverBudget` once a limit is reached.
+    async fn check_usage_budget(
+        &self,
+        ghost_id: &str,
+        operator_id: &str,
+    ) -> Result<Option<t_koma_db::BudgetReport>, ChatError> {
+        match t_koma_db::UsageBudgetRepository::check(
+            self.koma_db.pool(),
+            ghost_id,
+            operator_id,
+            chrono::Utc::now(),
+        )
+        .await?
+        {
+            t_koma_db::BudgetStatus::WithinBudget => Ok(None),
+            t_koma_db::BudgetStatus::Warning(report) => Ok(Some(report)),
+            t_koma_db::BudgetStatus::Exceeded(report) => Err(ChatError::OverBudget(report)),
+        }
+    }
+
     /// Send a chat message using a specific model alias
     pub async fn chat_with_model_alias(
         &self,
@@ -853,16 +1596,34 @@ impl AppState {
     ) -> Result<ChatResult, ChatError> {
         let chat_key = Self::chat_key(operator_id, ghost_name, session_id);
         if self.is_chat_in_flight(&chat_key).await {
-            if !Self::is_continue_message(message) {
-                self.set_ignored_message(&chat_key, message).await;
-            }
+            let text = if Self::is_stop_message(message) {
+                self.stop_chat(&chat_key).await;
+                render_message(ids::CHAT_STOP_REQUESTED, &[])
+            } else {
+                if !Self::is_continue_message(message) {
+                    self.set_ignored_message(&chat_key, message).await;
+                }
+                render_message(ids::CHAT_BUSY, &[])
+            };
+            return Ok(ChatResult {
+                text,
+                compaction_happened: false,
+                tool_calls: Vec::new(),
+                model_alias: String::new(),
+                statusline: false,
+                usage: ChatUsage::default(),
+                budget_warning: None,
+            });
+        }
+        if self.is_shutting_down() {
             return Ok(ChatResult {
-                text: render_message(ids::CHAT_BUSY, &[]),
+                text: render_message(ids::CHAT_SHUTTING_DOWN, &[]),
                 compaction_happened: false,
                 tool_calls: Vec::new(),
                 model_alias: String::new(),
                 statusline: false,
                 usage: ChatUsage::default(),
+                budget_warning: None,
             });
         }
 
@@ -877,6 +1638,7 @@ impl AppState {
                         model_alias: String::new(),
                         statusline: false,
                         usage: ChatUsage::default(),
+                        budget_warning: None,
                     });
                 }
             }
@@ -901,9 +1663,10 @@ impl AppState {
                 }
                 Err(e) => return Err(ChatError::Database(e)),
             };
+        let budget_warning = self.check_usage_budget(&ghost.id, operator_id).await?;
         let pre_compaction_state =
             t_koma_db::SessionRepository::get_by_id(self.koma_db.pool(), session_id).await?;
-        self.set_chat_in_flight(&chat_key).await;
+        let cancel = self.set_chat_in_flight(&chat_key).await;
 
         let workspace_path = t_koma_db::ghosts::ghost_workspace_path(&ghost.name)?;
         if let Err(err) = crate::heartbeat::ensure_heartbeat_file(&workspace_path) {
@@ -926,6 +1689,7 @@ impl AppState {
                 &message,
                 attachments,
                 tool_call_tx,
+                &cancel,
             )
             .await;
         self.clear_chat_in_flight(&chat_key).await;
@@ -955,6 +1719,7 @@ impl AppState {
             model_alias,
             statusline: ghost.statusline,
             usage,
+            budget_warning,
         })
     }
 
@@ -967,11 +1732,17 @@ impl AppState {
             return;
         }
 
-        let settings = self.knowledge_engine.settings().clone();
+        let settings =
+            t_koma_knowledge::KnowledgeSettings::clone(&self.knowledge_engine.settings());
+        let embedder = self.knowledge_engine.embedder().clone();
         let handle = tokio::spawn(async move {
             let mut backoff = 2u64;
             loop {
-                let result = t_koma_knowledge::watcher::run_shared_watcher(settings.clone()).await;
+                let result = t_koma_knowledge::watcher::run_shared_watcher(
+                    settings.clone(),
+                    embedder.clone(),
+                )
+                .await;
                 if let Err(err) = result {
                     error!("shared knowledge watcher crashed: {err}");
                 }
@@ -991,7 +1762,9 @@ impl AppState {
             return;
         }
 
-        let settings = self.knowledge_engine.settings().clone();
+        let settings =
+            t_koma_knowledge::KnowledgeSettings::clone(&self.knowledge_engine.settings());
+        let embedder = self.knowledge_engine.embedder().clone();
         let ghost_name_key = ghost_name.to_string();
         let ghost_name_log = ghost_name_key.clone();
         let ghost_name_task = ghost_name_key.clone();
@@ -1001,6 +1774,7 @@ impl AppState {
                 let result = t_koma_knowledge::watcher::run_ghost_watcher(
                     settings.clone(),
                     ghost_name_task.clone(),
+                    embedder.clone(),
                 )
                 .await;
                 if let Err(err) = result {
@@ -1151,6 +1925,7 @@ impl AppState {
         message: &str,
         attachments: Vec<t_koma_db::ContentBlock>,
         tool_call_tx: Option<&tokio::sync::mpsc::UnboundedSender<Vec<ToolCallSummary>>>,
+        cancel: &CancellationToken,
     ) -> Result<(String, Vec<ToolCallSummary>, String, ChatUsage), ChatError> {
         let mut message_persisted = false;
         let mut last_error: Option<ChatError> = None;
@@ -1182,6 +1957,7 @@ impl AppState {
                     tool_call_tx,
                     model.retry_on_empty,
                     &model_info,
+                    cancel,
                 )
                 .await;
 
@@ -1216,6 +1992,11 @@ impl AppState {
                     message_persisted = true;
                     last_error = Some(ChatError::Provider(e));
                 }
+                // A later model in the chain may have a larger window.
+                Err(err @ ChatError::ContextWindowExceeded { .. }) => {
+                    message_persisted = true;
+                    last_error = Some(err);
+                }
                 Err(err) => return Err(err),
             }
         }
@@ -1235,6 +2016,173 @@ impl AppState {
         guard.get(operator_id).cloned()
     }
 
+    /// Rename a ghost everywhere its name is used as a key.
+    ///
+    /// The knowledge index is rewritten first and reverted if the DB row or
+    /// workspace move fails. Active-ghost mappings and pending approvals /
+    /// tool loops follow the new name, and SOUL.md's name line is updated.
+    /// Refused while a chat with the ghost is in flight.
+    pub async fn rename_ghost(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<t_koma_db::Ghost, String> {
+        let new_name =
+            t_koma_db::ghosts::validate_ghost_name(new_name).map_err(|e| e.to_string())?;
+        let ghost = t_koma_db::GhostRepository::get_by_name(self.koma_db.pool(), old_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Ghost not found: {}", old_name))?;
+        let old_name = ghost.name;
+        if old_name == new_name {
+            return Err(format!("Ghost is already named {}", new_name));
+        }
+        if self
+            .in_flight_chats
+            .read()
+            .await
+            .keys()
+            .any(|key| Self::key_ghost(key) == Some(old_name.as_str()))
+        {
+            return Err(format!("Ghost {} is busy; retry when idle", old_name));
+        }
+
+        self.knowledge_engine
+            .rename_ghost(&old_name, &new_name)
+            .await
+            .map_err(|e| e.to_string())?;
+        let renamed =
+            match t_koma_db::GhostRepository::rename(self.koma_db.pool(), &old_name, &new_name)
+                .await
+            {
+                Ok(ghost) => ghost,
+                Err(e) => {
+                    if let Err(revert) = self
+                        .knowledge_engine
+                        .rename_ghost(&new_name, &old_name)
+                        .await
+                    {
+                        error!(
+                            "Failed to revert knowledge rename of {}: {}",
+                            old_name, revert
+                        );
+                    }
+                    return Err(e.to_string());
+                }
+            };
+
+        for name in self.active_ghosts.write().await.values_mut() {
+            if *name == old_name {
+                *name = new_name.clone();
+            }
+        }
+        Self::rekey_ghost(
+            &mut *self.pending_tool_approvals.write().await,
+            &old_name,
+            &new_name,
+        );
+        Self::rekey_ghost(
+            &mut *self.pending_tool_loops.write().await,
+            &old_name,
+            &new_name,
+        );
+
+        if let Ok(workspace) = t_koma_db::ghosts::ghost_workspace_path(&new_name) {
+            let soul_path = workspace.join("SOUL.md");
+            if let Ok(soul) = tokio::fs::read_to_string(&soul_path).await {
+                let updated = soul.replace(
+                    &format!("I am called {}.", old_name),
+                    &format!("I am called {}.", new_name),
+                );
+                if updated != soul
+                    && let Err(e) = tokio::fs::write(&soul_path, updated).await
+                {
+                    warn!(
+                        "Failed to update SOUL.md after renaming {}: {}",
+                        old_name, e
+                    );
+                }
+            }
+        }
+
+        self.log(LogEntry::Info {
+            message: format!("Ghost {} renamed to {}", old_name, new_name),
+        })
+        .await;
+        Ok(renamed)
+    }
+
+    /// Create `new_name` as a copy of `source`, including selected private
+    /// knowledge scopes. A failed knowledge copy removes the new ghost again.
+    ///
+    /// Returns the new ghost and the number of knowledge files copied.
+    pub async fn clone_ghost(
+        &self,
+        source: &str,
+        new_name: &str,
+        options: &t_koma_db::GhostCloneOptions,
+        knowledge_scopes: &[t_koma_knowledge::models::KnowledgeScope],
+    ) -> Result<(t_koma_db::Ghost, usize), String> {
+        let ghost =
+            t_koma_db::GhostRepository::clone_from(self.koma_db.pool(), source, new_name, options)
+                .await
+                .map_err(|e| e.to_string())?;
+
+        let copied = match self
+            .knowledge_engine
+            .clone_ghost_knowledge(source, &ghost.name, knowledge_scopes)
+            .await
+        {
+            Ok(copied) => copied,
+            Err(e) => {
+                if let Ok(workspace) = t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+                    let _ = tokio::fs::remove_dir_all(workspace).await;
+                }
+                if let Err(cleanup) =
+                    t_koma_db::GhostRepository::delete_by_name(self.koma_db.pool(), &ghost.name)
+                        .await
+                {
+                    error!("Failed to remove partial clone {}: {}", ghost.name, cleanup);
+                }
+                return Err(e.to_string());
+            }
+        };
+
+        self.log(LogEntry::Info {
+            message: format!(
+                "Ghost {} cloned into {} ({} knowledge files)",
+                source, ghost.name, copied
+            ),
+        })
+        .await;
+        Ok((ghost, copied))
+    }
+
+    /// Ghost segment of an `operator:ghost:session` key.
+    fn key_ghost(key: &str) -> Option<&str> {
+        key.split(':').nth(1)
+    }
+
+    fn rekey_ghost<V>(map: &mut HashMap<String, V>, old_name: &str, new_name: &str) {
+        let keys: Vec<String> = map
+            .keys()
+            .filter(|key| Self::key_ghost(key) == Some(old_name))
+            .cloned()
+            .collect();
+        for key in keys {
+            let mut parts = key.splitn(3, ':');
+            let (Some(operator_id), _, Some(session_id)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let new_key = Self::approval_key(operator_id, new_name, session_id);
+            if let Some(value) = map.remove(&key) {
+                map.insert(new_key, value);
+            }
+        }
+    }
+
     fn interface_key(platform: t_koma_db::Platform, external_id: &str) -> String {
         format!("{}:{}", platform, external_id)
     }
@@ -1251,14 +2199,35 @@ impl AppState {
         message.trim().eq_ignore_ascii_case("continue")
     }
 
+    fn is_stop_message(message: &str) -> bool {
+        message.trim().eq_ignore_ascii_case("stop")
+    }
+
     pub async fn is_chat_in_flight(&self, key: &str) -> bool {
         let guard = self.in_flight_chats.read().await;
-        guard.contains(key)
+        guard.contains_key(key)
     }
 
-    pub async fn set_chat_in_flight(&self, key: &str) {
+    /// Mark a chat as in flight and return the token that stops it.
+    pub async fn set_chat_in_flight(&self, key: &str) -> CancellationToken {
+        let token = CancellationToken::new();
         let mut guard = self.in_flight_chats.write().await;
-        guard.insert(key.to_string());
+        guard.insert(key.to_string(), token.clone());
+        token
+    }
+
+    /// Cancel the in-flight chat for `key`. Returns false when nothing is
+    /// running. Background jobs (heartbeat, reflection, CRON) only hold the
+    /// key and are not interrupted.
+    pub async fn stop_chat(&self, key: &str) -> bool {
+        let guard = self.in_flight_chats.read().await;
+        match guard.get(key) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
     }
 
     pub async fn clear_chat_in_flight(&self, key: &str) {
@@ -1266,6 +2235,33 @@ impl AppState {
         guard.remove(key);
     }
 
+    /// Mark a heartbeat/reflection job as running and return the token that
+    /// cancels it.
+    pub async fn register_job(&self, job_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        let mut guard = self.running_jobs.write().await;
+        guard.insert(job_id.to_string(), token.clone());
+        token
+    }
+
+    /// Cancel a running heartbeat/reflection job. Returns false when no job
+    /// with that ID is running.
+    pub async fn cancel_job(&self, job_id: &str) -> bool {
+        let guard = self.running_jobs.read().await;
+        match guard.get(job_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn clear_job(&self, job_id: &str) {
+        let mut guard = self.running_jobs.write().await;
+        guard.remove(job_id);
+    }
+
     pub async fn set_ignored_message(&self, key: &str, message: &str) {
         let mut guard = self.ignored_messages.write().await;
         guard.insert(key.to_string(), message.to_string());
@@ -1390,8 +2386,11 @@ impl AppState {
         pending: PendingToolApproval,
     ) {
         let key = Self::approval_key(operator_id, ghost_name, session_id);
-        let mut guard = self.pending_tool_approvals.write().await;
-        guard.insert(key, pending);
+        self.pending_tool_approvals
+            .write()
+            .await
+            .insert(key, pending);
+        self.approvals_changed(operator_id);
     }
 
     pub async fn take_pending_tool_approval(
@@ -1401,8 +2400,11 @@ impl AppState {
         session_id: &str,
     ) -> Option<PendingToolApproval> {
         let key = Self::approval_key(operator_id, ghost_name, session_id);
-        let mut guard = self.pending_tool_approvals.write().await;
-        guard.remove(&key)
+        let pending = self.pending_tool_approvals.write().await.remove(&key);
+        if pending.is_some() {
+            self.approvals_changed(operator_id);
+        }
+        pending
     }
 
     pub async fn set_pending_tool_loop(
@@ -1413,8 +2415,8 @@ impl AppState {
         pending: PendingToolContinuation,
     ) {
         let key = Self::approval_key(operator_id, ghost_name, session_id);
-        let mut guard = self.pending_tool_loops.write().await;
-        guard.insert(key, pending);
+        self.pending_tool_loops.write().await.insert(key, pending);
+        self.approvals_changed(operator_id);
     }
 
     pub async fn take_pending_tool_loop(
@@ -1424,8 +2426,11 @@ impl AppState {
         session_id: &str,
     ) -> Option<PendingToolContinuation> {
         let key = Self::approval_key(operator_id, ghost_name, session_id);
-        let mut guard = self.pending_tool_loops.write().await;
-        guard.remove(&key)
+        let pending = self.pending_tool_loops.write().await.remove(&key);
+        if pending.is_some() {
+            self.approvals_changed(operator_id);
+        }
+        pending
     }
 
     pub async fn clear_pending_tool_loop(
@@ -1434,9 +2439,60 @@ impl AppState {
         ghost_name: &str,
         session_id: &str,
     ) -> bool {
-        let key = Self::approval_key(operator_id, ghost_name, session_id);
-        let mut guard = self.pending_tool_loops.write().await;
-        guard.remove(&key).is_some()
+        self.take_pending_tool_loop(operator_id, ghost_name, session_id)
+            .await
+            .is_some()
+    }
+
+    /// The operator's pending tool approvals and tool-loop continuations,
+    /// ordered by ghost and session.
+    pub async fn pending_approvals(
+        &self,
+        operator_id: &str,
+    ) -> Vec<t_koma_core::PendingApprovalInfo> {
+        fn split_key<'a>(key: &'a str, operator_id: &str) -> Option<(&'a str, &'a str)> {
+            let mut parts = key.splitn(3, ':');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(op), Some(ghost), Some(session)) if op == operator_id => {
+                    Some((ghost, session))
+                }
+                _ => None,
+            }
+        }
+
+        let mut approvals = Vec::new();
+        for (key, pending) in self.pending_tool_approvals.read().await.iter() {
+            if let Some((ghost_name, session_id)) = split_key(key, operator_id) {
+                approvals.push(t_koma_core::PendingApprovalInfo {
+                    ghost_name: ghost_name.to_string(),
+                    session_id: session_id.to_string(),
+                    kind: t_koma_core::PendingApprovalKind::ToolApproval,
+                    summary: crate::operator_flow::approval_required_gateway_message(
+                        &pending.reason,
+                        None,
+                    )
+                    .text_fallback,
+                });
+            }
+        }
+        for key in self.pending_tool_loops.read().await.keys() {
+            if let Some((ghost_name, session_id)) = split_key(key, operator_id) {
+                approvals.push(t_koma_core::PendingApprovalInfo {
+                    ghost_name: ghost_name.to_string(),
+                    session_id: session_id.to_string(),
+                    kind: t_koma_core::PendingApprovalKind::ToolLoop,
+                    summary: crate::operator_flow::tool_loop_limit_reached_gateway_message(
+                        None,
+                        crate::session::DEFAULT_TOOL_LOOP_LIMIT,
+                        DEFAULT_TOOL_LOOP_EXTRA,
+                    )
+                    .text_fallback,
+                });
+            }
+        }
+        approvals
+            .sort_by(|a, b| (&a.ghost_name, &a.session_id).cmp(&(&b.ghost_name, &b.session_id)));
+        approvals
     }
 
     pub async fn set_pending_gateway_action(&self, token: &str, pending: PendingGatewayAction) {
@@ -1451,6 +2507,61 @@ impl AppState {
         guard.remove(token)
     }
 
+    /// Let a reaction with `emoji` on `message_id` trigger the action behind `token`.
+    pub async fn set_reaction_action(&self, message_id: &str, emoji: &str, token: &str) {
+        let live: Vec<String> = {
+            let actions = self.pending_gateway_actions.read().await;
+            let now = Utc::now().timestamp();
+            actions
+                .iter()
+                .filter(|(_, action)| action.expires_at > now)
+                .map(|(token, _)| token.clone())
+                .collect()
+        };
+        let mut guard = self.pending_reaction_actions.write().await;
+        guard.retain(|_, token| live.contains(token));
+        guard.insert(format!("{}:{}", message_id, emoji), token.to_string());
+    }
+
+    /// Resolve a reaction to its gateway action.
+    ///
+    /// Returns `None` when the reaction is not mapped or comes from someone
+    /// other than the operator the action was offered to (the bot's own
+    /// reactions included); the action is left in place for them. Once
+    /// resolved, every reaction on the message is unmapped.
+    pub async fn take_reaction_action(
+        &self,
+        message_id: &str,
+        emoji: &str,
+        external_id: &str,
+    ) -> Option<ReactionAction> {
+        let key = format!("{}:{}", message_id, emoji);
+        let token = self
+            .pending_reaction_actions
+            .read()
+            .await
+            .get(&key)?
+            .clone();
+        {
+            let actions = self.pending_gateway_actions.read().await;
+            if let Some(action) = actions.get(&token)
+                && action.external_id != external_id
+            {
+                return None;
+            }
+        }
+
+        let prefix = format!("{}:", message_id);
+        self.pending_reaction_actions
+            .write()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix));
+        Some(match self.take_pending_gateway_action(&token).await {
+            Some(action) => ReactionAction::Ready(action),
+            None => ReactionAction::Expired,
+        })
+    }
+
     pub async fn handle_tool_approval(
         &self,
         ghost_name: &str,
@@ -1483,6 +2594,8 @@ impl AppState {
         let model = self
             .select_model_for_chain(&chain)
             .unwrap_or_else(|| self.default_model());
+        let chat_key = Self::chat_key(operator_id, ghost_name, session_id);
+        let cancel = self.set_chat_in_flight(&chat_key).await;
         let response = self
             .session_chat
             .resume_tool_approval(
@@ -1498,10 +2611,12 @@ impl AppState {
                 decision,
                 model.retry_on_empty,
                 &model_info,
+                &cancel,
             )
-            .await?;
+            .await;
+        self.clear_chat_in_flight(&chat_key).await;
 
-        Ok(Some(response))
+        Ok(Some(response?))
     }
 
     pub async fn handle_tool_loop_continue(
@@ -1536,6 +2651,8 @@ impl AppState {
         let model = self
             .select_model_for_chain(&chain)
             .unwrap_or_else(|| self.default_model());
+        let chat_key = Self::chat_key(operator_id, ghost_name, session_id);
+        let cancel = self.set_chat_in_flight(&chat_key).await;
         let response = self
             .session_chat
             .resume_tool_loop(
@@ -1551,10 +2668,12 @@ impl AppState {
                 extra_iterations.unwrap_or(DEFAULT_TOOL_LOOP_EXTRA),
                 model.retry_on_empty,
                 &model_info,
+                &cancel,
             )
-            .await?;
+            .await;
+        self.clear_chat_in_flight(&chat_key).await;
 
-        Ok(Some(response))
+        Ok(Some(response?))
     }
 
     /// Low-level conversation method with full tool use loop support
@@ -1689,31 +2808,8 @@ impl AppState {
     ) {
         use t_koma_db::{ContentBlock as DbContentBlock, MessageRole, SessionRepository};
 
-        let assistant_content: Vec<DbContentBlock> = response
-            .content
-            .iter()
-            .map(|block| match block {
-                crate::providers::provider::ProviderContentBlock::Text { text } => {
-                    DbContentBlock::Text { text: text.clone() }
-                }
-                crate::providers::provider::ProviderContentBlock::ToolUse { id, name, input } => {
-                    DbContentBlock::ToolUse {
-                        id: id.clone(),
-                        name: name.clone(),
-                        input: input.clone(),
-                    }
-                }
-                crate::providers::provider::ProviderContentBlock::ToolResult {
-                    tool_use_id,
-                    content,
-                    is_error,
-                } => DbContentBlock::ToolResult {
-                    tool_use_id: tool_use_id.clone(),
-                    content: content.clone(),
-                    is_error: *is_error,
-                },
-            })
-            .collect();
+        let assistant_content: Vec<DbContentBlock> =
+            crate::session::provider_to_db_blocks(response);
 
         if let Err(e) = SessionRepository::add_message(
             pool.pool(),
@@ -1861,10 +2957,192 @@ impl AppState {
     }
 }
 
+/// Compare two settings snapshots section by section. With no previous
+/// snapshot every section counts as applied.
+fn diff_settings(
+    previous: Option<&t_koma_core::Settings>,
+    current: &t_koma_core::Settings,
+) -> ConfigReload {
+    let to_map = |settings: &t_koma_core::Settings| match serde_json::to_value(settings) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let old = previous.map(to_map);
+    let new = to_map(current);
+
+    let mut reload = ConfigReload::default();
+    for (section, value) in &new {
+        // `[cli]` is read by the TUI only.
+        if section == "cli" || old.as_ref().and_then(|old| old.get(section)) == Some(value) {
+            continue;
+        }
+        if RESTART_ONLY_SECTIONS.contains(&section.as_str()) {
+            reload.restart_required.push(section.clone());
+        } else {
+            reload.applied.push(section.clone());
+        }
+    }
+
+    // Job queue workers are spawned once at startup.
+    if let Some(previous) = previous
+        && previous.job_queue.workers != current.job_queue.workers
+    {
+        reload
+            .restart_required
+            .push("job_queue.workers".to_string());
+    }
+    // Webhook tools are registered once at startup.
+    if let Some(previous) = previous
+        && previous.tools.webhooks != current.tools.webhooks
+    {
+        reload.restart_required.push("tools.webhooks".to_string());
+    }
+    reload
+}
+
+/// A model-less [`AppState`] on `koma_db` for handler tests, with its
+/// knowledge engine in a temporary directory that must outlive the state.
+#[cfg(test)]
+pub(crate) async fn test_app_state(
+    koma_db: t_koma_db::KomaDbPool,
+) -> (Arc<AppState>, tempfile::TempDir) {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let data_root = temp.path().join("data");
+    let settings = t_koma_knowledge::KnowledgeSettings {
+        knowledge_db_path_override: Some(data_root.join("shared").join("index.sqlite3")),
+        data_root_override: Some(data_root),
+        embedding_dim: Some(8),
+        ..Default::default()
+    };
+    let knowledge_engine = Arc::new(
+        t_koma_knowledge::KnowledgeEngine::open(settings)
+            .await
+            .expect("open knowledge engine"),
+    );
+    let state = AppState::new(
+        Vec::new(),
+        HashMap::new(),
+        koma_db,
+        knowledge_engine,
+        Vec::new(),
+        CompactionConfig::default(),
+    );
+    (Arc::new(state), temp)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn gateway_action(external_id: &str, expires_at: i64) -> PendingGatewayAction {
+        PendingGatewayAction {
+            operator_id: "op-1".to_string(),
+            ghost_name: "alpha".to_string(),
+            session_id: "sess-1".to_string(),
+            external_id: external_id.to_string(),
+            channel_id: "chan-1".to_string(),
+            intent: "approve".to_string(),
+            payload: None,
+            expires_at,
+        }
+    }
+
+    /// A state with one live action behind `token`, mapped to ✅ and ❌ on `msg-1`.
+    async fn state_with_reaction_action(token: &str) -> (Arc<AppState>, tempfile::TempDir) {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, temp) = test_app_state(db).await;
+        let expires_at = Utc::now().timestamp() + 300;
+        state
+            .set_pending_gateway_action(token, gateway_action("owner", expires_at))
+            .await;
+        state.set_reaction_action("msg-1", "✅", token).await;
+        state.set_reaction_action("msg-1", "❌", token).await;
+        (state, temp)
+    }
+
+    #[tokio::test]
+    async fn reaction_action_resolves_for_its_owner_only() {
+        let (state, _temp) = state_with_reaction_action("tok-1").await;
+
+        assert!(
+            state
+                .take_reaction_action("msg-1", "✅", "someone-else")
+                .await
+                .is_none()
+        );
+        assert!(
+            state
+                .take_reaction_action("msg-2", "✅", "owner")
+                .await
+                .is_none()
+        );
+
+        let resolved = state.take_reaction_action("msg-1", "✅", "owner").await;
+        assert!(matches!(
+            resolved,
+            Some(ReactionAction::Ready(action)) if action.intent == "approve"
+        ));
+        // Every reaction on the message is unmapped once one resolves.
+        assert!(
+            state
+                .take_reaction_action("msg-1", "❌", "owner")
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn reaction_action_reports_expired_action() {
+        let (state, _temp) = state_with_reaction_action("tok-1").await;
+        state
+            .set_pending_gateway_action("tok-1", gateway_action("owner", 0))
+            .await;
+
+        let resolved = state.take_reaction_action("msg-1", "✅", "owner").await;
+        assert!(matches!(resolved, Some(ReactionAction::Expired)));
+    }
+
+    #[tokio::test]
+    async fn gateway_secret_matches_only_the_configured_secret() {
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let (state, _temp) = test_app_state(db).await;
+        assert!(!state.gateway_secret_matches("").await);
+        assert!(!state.gateway_secret_matches("s3cret").await);
+
+        state.set_gateway_secret(Some("s3cret".to_string())).await;
+        assert!(state.gateway_secret_matches("s3cret").await);
+        assert!(!state.gateway_secret_matches("s3creT").await);
+        assert!(!state.gateway_secret_matches("s3cre").await);
+        assert!(!state.gateway_secret_matches("s3crets").await);
+
+        // An empty secret means none is configured.
+        state.set_gateway_secret(Some(String::new())).await;
+        assert!(!state.gateway_secret_matches("").await);
+    }
+
+    #[test]
+    fn diff_settings_splits_live_and_restart_sections() {
+        let previous = t_koma_core::Settings::default();
+        let mut current = previous.clone();
+        current.compaction.keep_window += 5;
+        current.heartbeat_timing.check_seconds += 30;
+        current.gateway.port += 1;
+
+        let reload = diff_settings(Some(&previous), &current);
+        assert_eq!(reload.applied, vec!["compaction", "heartbeat_timing"]);
+        assert_eq!(reload.restart_required, vec!["gateway"]);
+
+        let mut workers = previous.clone();
+        workers.job_queue.workers += 1;
+        let reload = diff_settings(Some(&previous), &workers);
+        assert_eq!(reload.applied, vec!["job_queue"]);
+        assert_eq!(reload.restart_required, vec!["job_queue.workers"]);
+
+        let unchanged = diff_settings(Some(&previous), &previous);
+        assert!(unchanged.applied.is_empty());
+        assert!(unchanged.restart_required.is_empty());
+    }
+
     #[test]
     fn test_log_entry_display() {
         let entry = LogEntry::DiscordMessage {
@@ -1876,5 +3154,14 @@ mod tests {
         assert!(s.contains("[DISCORD]"));
         assert!(s.contains("alice"));
         assert!(s.contains("Hello!"));
+
+        let entry = LogEntry::WebSocket {
+            event: "connected".to_string(),
+            client_id: "client_1".to_string(),
+            identity: "token:tok_1 operator:op_1".to_string(),
+        };
+        assert!(
+            format!("{}", entry).ends_with("[WS] connected client_1 (token:tok_1 operator:op_1)")
+        );
     }
 }
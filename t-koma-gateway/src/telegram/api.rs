@@ -0,0 +1,286 @@
+//! Minimal Telegram Bot API client.
+//!
+//! Covers only what the bot needs: long polling (`getUpdates`), sending
+//! HTML messages with inline keyboards, answering callback queries, chat
+//! actions and file downloads.
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::TelegramError;
+
+const API_BASE: &str = "https://api.telegram.org";
+
+/// Extra slack on top of the long-poll timeout before the HTTP request gives up.
+const HTTP_TIMEOUT_SLACK: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+    pub callback_query: Option<CallbackQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub message_id: i64,
+    pub from: Option<User>,
+    pub chat: Chat,
+    pub text: Option<String>,
+    pub caption: Option<String>,
+    pub photo: Option<Vec<PhotoSize>>,
+    pub document: Option<Document>,
+    pub reply_to_message: Option<Box<Message>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub id: i64,
+    pub is_bot: bool,
+    pub first_name: String,
+    pub username: Option<String>,
+}
+
+impl User {
+    /// `@username` when set, otherwise the first name.
+    pub fn display_name(&self) -> String {
+        match &self.username {
+            Some(username) => username.clone(),
+            None => self.first_name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+impl Chat {
+    pub fn is_private(&self) -> bool {
+        self.kind == "private"
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PhotoSize {
+    pub file_id: String,
+    pub file_unique_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Document {
+    pub file_id: String,
+    pub file_name: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallbackQuery {
+    pub id: String,
+    pub from: User,
+    pub message: Option<Message>,
+    pub data: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct File {
+    file_path: Option<String>,
+}
+
+/// One inline keyboard button carrying `callback_data`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InlineButton {
+    pub text: String,
+    pub callback_data: String,
+}
+
+impl InlineButton {
+    pub fn new(text: impl Into<String>, callback_data: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            callback_data: callback_data.into(),
+        }
+    }
+}
+
+/// Rows of inline buttons attached below a message.
+pub type InlineKeyboard = Vec<Vec<InlineButton>>;
+
+/// Telegram Bot API client bound to one bot token.
+#[derive(Clone)]
+pub struct TelegramApi {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl TelegramApi {
+    pub fn new(token: &str, poll_timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(poll_timeout + HTTP_TIMEOUT_SLACK)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            token: token.to_string(),
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        body: serde_json::Value,
+    ) -> Result<T, TelegramError> {
+        let url = format!("{API_BASE}/bot{}/{method}", self.token);
+        let response: ApiResponse<T> = self
+            .client
+            .post(url)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        match response {
+            ApiResponse {
+                ok: true,
+                result: Some(result),
+                ..
+            } => Ok(result),
+            ApiResponse { description, .. } => Err(TelegramError::Api(
+                description.unwrap_or_else(|| format!("{method} failed")),
+            )),
+        }
+    }
+
+    /// The bot's own user.
+    pub async fn get_me(&self) -> Result<User, TelegramError> {
+        self.call("getMe", json!({})).await
+    }
+
+    /// Long-poll for updates after `offset`, waiting up to `timeout_secs`.
+    pub async fn get_updates(
+        &self,
+        offset: i64,
+        timeout_secs: u64,
+    ) -> Result<Vec<Update>, TelegramError> {
+        self.call(
+            "getUpdates",
+            json!({
+                "offset": offset,
+                "timeout": timeout_secs,
+                "allowed_updates": ["message", "callback_query"],
+            }),
+        )
+        .await
+    }
+
+    /// Send one message. `html` selects the HTML parse mode.
+    pub async fn send_message(
+        &self,
+        chat_id: i64,
+        text: &str,
+        html: bool,
+        keyboard: Option<&InlineKeyboard>,
+    ) -> Result<Message, TelegramError> {
+        let mut body = json!({
+            "chat_id": chat_id,
+            "text": text,
+            "link_preview_options": { "is_disabled": true },
+        });
+        if html {
+            body["parse_mode"] = json!("HTML");
+        }
+        if let Some(keyboard) = keyboard {
+            body["reply_markup"] = json!({ "inline_keyboard": keyboard });
+        }
+        self.call("sendMessage", body).await
+    }
+
+    /// Send a message that opens the reply box, for free-text answers.
+    pub async fn send_force_reply(
+        &self,
+        chat_id: i64,
+        text: &str,
+        placeholder: &str,
+    ) -> Result<Message, TelegramError> {
+        self.call(
+            "sendMessage",
+            json!({
+                "chat_id": chat_id,
+                "text": text,
+                "parse_mode": "HTML",
+                "reply_markup": {
+                    "force_reply": true,
+                    "input_field_placeholder": placeholder,
+                },
+            }),
+        )
+        .await
+    }
+
+    pub async fn answer_callback_query(
+        &self,
+        callback_query_id: &str,
+        text: Option<&str>,
+    ) -> Result<bool, TelegramError> {
+        let mut body = json!({ "callback_query_id": callback_query_id });
+        if let Some(text) = text {
+            body["text"] = json!(text);
+        }
+        self.call("answerCallbackQuery", body).await
+    }
+
+    /// Drop the inline keyboard of a sent message (after a button was used).
+    pub async fn clear_keyboard(&self, chat_id: i64, message_id: i64) -> Result<(), TelegramError> {
+        // Returns the edited message, or `true` for inline messages.
+        self.call::<serde_json::Value>(
+            "editMessageReplyMarkup",
+            json!({
+                "chat_id": chat_id,
+                "message_id": message_id,
+                "reply_markup": { "inline_keyboard": [] },
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Show "typing…" in the chat for about five seconds.
+    pub async fn send_typing(&self, chat_id: i64) -> Result<bool, TelegramError> {
+        self.call(
+            "sendChatAction",
+            json!({ "chat_id": chat_id, "action": "typing" }),
+        )
+        .await
+    }
+
+    /// Download a file sent to the bot.
+    pub async fn download_file(&self, file_id: &str) -> Result<Vec<u8>, TelegramError> {
+        let file: File = self.call("getFile", json!({ "file_id": file_id })).await?;
+        let path = file
+            .file_path
+            .ok_or_else(|| TelegramError::Api(format!("no download path for file {file_id}")))?;
+        let url = format!("{API_BASE}/file/bot{}/{path}", self.token);
+        let bytes = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(bytes.to_vec())
+    }
+}
@@ -0,0 +1,1046 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::attachments;
+use crate::content::{self, ids};
+use crate::discord::{format_ghost_list_lines, parse_ghost_selection, persist_ghost_name_to_soul};
+use crate::operator_flow::{self, OutboundMessage};
+use crate::session::ChatError;
+use crate::state::{AppState, PendingGatewayAction, RateLimitDecision};
+
+use super::api::{CallbackQuery, InlineButton, Message, TelegramApi, Update, User};
+use super::send::{
+    send_assistant_text, send_gateway_text, send_ghost_name_prompt, send_interface_prompt,
+    send_outbound_messages, send_tool_calls,
+};
+
+/// Maximum duration for a typing indicator before it auto-stops.
+const TYPING_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Telegram's chat action lasts about five seconds; refresh a bit sooner.
+const TYPING_REFRESH: Duration = Duration::from_secs(4);
+
+/// Pause after a failed `getUpdates` call before polling again.
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Typing indicator with an automatic timeout.
+///
+/// Re-sends the `typing` chat action until dropped or until
+/// [`TYPING_TIMEOUT`] passes, whichever comes first.
+struct TimedTyping {
+    _handle: JoinHandle<()>,
+}
+
+impl TimedTyping {
+    fn start(api: &TelegramApi, chat_id: i64) -> Self {
+        let api = api.clone();
+        let handle = tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + TYPING_TIMEOUT;
+            while tokio::time::Instant::now() < deadline {
+                let _ = api.send_typing(chat_id).await;
+                tokio::time::sleep(TYPING_REFRESH).await;
+            }
+        });
+        Self { _handle: handle }
+    }
+}
+
+impl Drop for TimedTyping {
+    fn drop(&mut self) {
+        self._handle.abort();
+    }
+}
+
+/// Telegram bot handler
+///
+/// Like the Discord bot, this only adapts Telegram updates to the shared
+/// operator flow; all chat handling goes through `operator_flow`.
+pub struct Bot {
+    state: Arc<AppState>,
+    api: TelegramApi,
+    me: User,
+    poll_timeout_secs: u64,
+}
+
+impl Bot {
+    pub(super) fn new(
+        state: Arc<AppState>,
+        api: TelegramApi,
+        me: User,
+        poll_timeout_secs: u64,
+    ) -> Self {
+        Self {
+            state,
+            api,
+            me,
+            poll_timeout_secs,
+        }
+    }
+
+    /// Long-poll for updates until the task is aborted.
+    ///
+    /// Each update is handled on its own task so a long chat turn does not
+    /// hold up other operators.
+    pub async fn run(self: Arc<Self>) {
+        let mut offset = 0;
+        loop {
+            match self.api.get_updates(offset, self.poll_timeout_secs).await {
+                Ok(updates) => {
+                    for update in updates {
+                        offset = offset.max(update.update_id + 1);
+                        let bot = Arc::clone(&self);
+                        tokio::spawn(async move { bot.handle_update(update).await });
+                    }
+                }
+                Err(e) => {
+                    warn!("Telegram getUpdates failed: {}", e);
+                    tokio::time::sleep(POLL_RETRY_DELAY).await;
+                }
+            }
+        }
+    }
+
+    async fn handle_update(&self, update: Update) {
+        if let Some(query) = update.callback_query {
+            self.handle_callback(query).await;
+        } else if let Some(msg) = update.message {
+            self.handle_message(msg).await;
+        }
+    }
+
+    async fn send_text(&self, chat_id: i64, text: &str) {
+        if let Err(e) = send_gateway_text(&self.api, chat_id, text, None).await {
+            error!("Failed to send Telegram message: {}", e);
+        }
+    }
+
+    async fn send_content(&self, chat_id: i64, id: &str) {
+        self.send_text(chat_id, &super::render_message(id, &[]))
+            .await;
+    }
+
+    /// Message text meant for the bot, without the `@bot` mention.
+    ///
+    /// Private chats always reach the bot; in groups it only answers when
+    /// mentioned or replied to.
+    fn addressed_content(&self, msg: &Message) -> Option<String> {
+        let text = msg
+            .text
+            .as_deref()
+            .or(msg.caption.as_deref())
+            .unwrap_or_default();
+        let mention = self.me.username.as_deref().map(|name| format!("@{name}"));
+        let mentioned = mention.as_deref().is_some_and(|m| text.contains(m));
+        let replied_to_bot = msg
+            .reply_to_message
+            .as_ref()
+            .and_then(|reply| reply.from.as_ref())
+            .is_some_and(|user| user.id == self.me.id);
+        if !msg.chat.is_private() && !mentioned && !replied_to_bot {
+            return None;
+        }
+        let text = match &mention {
+            Some(mention) => text.replace(mention.as_str(), ""),
+            None => text.to_string(),
+        };
+        Some(text.trim().to_string())
+    }
+
+    async fn handle_message(&self, msg: Message) {
+        let Some(author) = msg.from.clone() else {
+            return;
+        };
+        if author.is_bot {
+            return;
+        }
+        let Some(content) = self.addressed_content(&msg) else {
+            return;
+        };
+
+        let chat_id = msg.chat.id;
+        let operator_external_id = author.id.to_string();
+        let operator_name = author.display_name();
+        let platform = t_koma_db::Platform::Telegram;
+
+        info!(
+            event_kind = "chat_io",
+            "[session:-] Telegram message from {} ({}): {}",
+            operator_name,
+            operator_external_id,
+            content
+        );
+
+        // Bot commands: `/start` only runs onboarding, `/new` is the `new` keyword.
+        let is_start = content == "/start";
+        let clean_content = match content.as_str() {
+            "/start" => "",
+            "/new" => "new",
+            other => other,
+        };
+        let has_files = msg.photo.is_some() || msg.document.is_some();
+
+        if clean_content.is_empty() && !has_files && !is_start {
+            return;
+        }
+
+        let interface = match t_koma_db::InterfaceRepository::get_by_external_id(
+            self.state.koma_db.pool(),
+            platform,
+            &operator_external_id,
+        )
+        .await
+        {
+            Ok(found) => found,
+            Err(e) => {
+                error!("Failed to load interface {}: {}", operator_external_id, e);
+                self.send_content(chat_id, ids::ERROR_GENERIC).await;
+                return;
+            }
+        };
+
+        let Some(interface) = interface else {
+            if !self
+                .state
+                .is_interface_pending(platform, &operator_external_id)
+                .await
+            {
+                self.state
+                    .set_interface_pending(platform, &operator_external_id)
+                    .await;
+                send_interface_prompt(&self.api, chat_id).await;
+                return;
+            }
+            self.handle_interface_choice(
+                chat_id,
+                &operator_external_id,
+                &operator_name,
+                clean_content,
+            )
+            .await;
+            return;
+        };
+
+        let operator = match t_koma_db::OperatorRepository::get_by_id(
+            self.state.koma_db.pool(),
+            &interface.operator_id,
+        )
+        .await
+        {
+            Ok(Some(op)) => op,
+            Ok(None) => {
+                error!(
+                    "Interface references missing operator {}",
+                    interface.operator_id
+                );
+                self.send_content(chat_id, ids::INTERFACE_INVALID_OPERATOR)
+                    .await;
+                return;
+            }
+            Err(e) => {
+                error!("Failed to load operator {}: {}", interface.operator_id, e);
+                self.send_content(chat_id, ids::FAILED_LOAD_OPERATOR).await;
+                return;
+            }
+        };
+
+        match operator.status {
+            t_koma_db::OperatorStatus::Pending => {
+                self.send_content(chat_id, ids::ACCESS_PENDING).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Denied => {
+                self.send_content(chat_id, ids::ACCESS_DENIED).await;
+                return;
+            }
+            t_koma_db::OperatorStatus::Approved => {}
+        }
+
+        let operator_id = operator.id.clone();
+
+        let ghosts = match t_koma_db::GhostRepository::list_by_operator(
+            self.state.koma_db.pool(),
+            &operator_id,
+        )
+        .await
+        {
+            Ok(list) => list,
+            Err(e) => {
+                error!("Failed to list ghosts for operator {}: {}", operator_id, e);
+                self.send_content(chat_id, ids::ERROR_FAILED_LOAD_GHOSTS)
+                    .await;
+                return;
+            }
+        };
+
+        if ghosts.is_empty() {
+            self.handle_no_ghosts(chat_id, &operator, clean_content)
+                .await;
+            return;
+        }
+
+        if let Some(selection) = parse_ghost_selection(clean_content) {
+            if let Some(ghost) = ghosts.iter().find(|g| g.name == selection) {
+                self.state.set_active_ghost(&operator_id, &ghost.name).await;
+                let response = super::render_message(
+                    ids::ACTIVE_GHOST_SET,
+                    &[("ghost_name", ghost.name.as_str())],
+                );
+                self.send_text(chat_id, &response).await;
+                return;
+            }
+
+            let list_rows = format_ghost_list_lines(&ghosts);
+            let list =
+                super::render_message(ids::GHOST_LIST, &[("ghost_list", list_rows.as_str())]);
+            let response =
+                super::render_message(ids::UNKNOWN_GHOST_NAME, &[("ghost_list", list.as_str())]);
+            self.send_text(chat_id, &response).await;
+            return;
+        }
+
+        let ghost_name = if ghosts.len() == 1 {
+            ghosts[0].name.clone()
+        } else if let Some(active) = self.state.get_active_ghost(&operator_id).await {
+            active
+        } else {
+            self.send_ghost_select_prompt(chat_id, &operator_id, &operator_external_id, &ghosts)
+                .await;
+            return;
+        };
+
+        if is_start {
+            let response = super::render_message(
+                ids::ACTIVE_GHOST_SET,
+                &[("ghost_name", ghost_name.as_str())],
+            );
+            self.send_text(chat_id, &response).await;
+            return;
+        }
+
+        let ghost =
+            match t_koma_db::GhostRepository::get_by_name(self.state.koma_db.pool(), &ghost_name)
+                .await
+            {
+                Ok(Some(g)) => g,
+                Ok(None) => {
+                    error!("Ghost not found: {}", ghost_name);
+                    self.send_content(chat_id, ids::ERROR_FAILED_LOAD_GHOSTS)
+                        .await;
+                    return;
+                }
+                Err(e) => {
+                    error!("Failed to load ghost {}: {}", ghost_name, e);
+                    self.send_content(chat_id, ids::ERROR_FAILED_LOAD_GHOSTS)
+                        .await;
+                    return;
+                }
+            };
+
+        let session = match t_koma_db::SessionRepository::get_or_create_active(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            &operator_id,
+        )
+        .await
+        {
+            Ok(s) => s,
+            Err(e) => {
+                error!(
+                    "Failed to create session for operator {}: {}",
+                    operator_id, e
+                );
+                self.send_content(chat_id, ids::FAILED_INIT_SESSION).await;
+                return;
+            }
+        };
+
+        match self.state.check_operator_rate_limit(&operator).await {
+            RateLimitDecision::Allowed => {}
+            RateLimitDecision::Limited { retry_after } => {
+                if !clean_content.eq_ignore_ascii_case("continue") {
+                    self.state
+                        .store_pending_message(
+                            &operator_id,
+                            &ghost_name,
+                            &session.id,
+                            clean_content,
+                        )
+                        .await;
+                }
+                let retry_after = retry_after.as_secs().to_string();
+                let message = super::render_message(
+                    ids::RATE_LIMITED,
+                    &[("retry_after", retry_after.as_str())],
+                );
+                self.send_text(chat_id, &message).await;
+                return;
+            }
+        }
+
+        if clean_content.eq_ignore_ascii_case("new") {
+            self.start_new_session(
+                chat_id,
+                &ghost,
+                &operator_id,
+                &operator_external_id,
+                &session.id,
+            )
+            .await;
+            return;
+        }
+
+        let attachment_blocks = if has_files {
+            let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+                Ok(path) => path,
+                Err(e) => {
+                    error!("Failed to get workspace path: {}", e);
+                    self.send_content(chat_id, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                        .await;
+                    return;
+                }
+            };
+            self.download_to_content_blocks(&msg, &workspace_path).await
+        } else {
+            vec![]
+        };
+
+        self.state
+            .log(crate::LogEntry::Routing {
+                platform: "telegram".to_string(),
+                operator_id: operator_id.clone(),
+                ghost_name: ghost_name.clone(),
+                session_id: session.id.clone(),
+            })
+            .await;
+
+        if clean_content.eq_ignore_ascii_case("approve")
+            || clean_content.eq_ignore_ascii_case("deny")
+            || operator_flow::parse_step_limit(clean_content).is_some()
+        {
+            let _typing = TimedTyping::start(&self.api, chat_id);
+            match operator_flow::run_tool_control_command(
+                self.state.as_ref(),
+                Some("telegram"),
+                None,
+                &ghost_name,
+                &session.id,
+                &operator_id,
+                clean_content,
+            )
+            .await
+            {
+                Ok(Some(messages)) => {
+                    send_outbound_messages(
+                        self.state.as_ref(),
+                        &self.api,
+                        chat_id,
+                        &operator_external_id,
+                        &operator_id,
+                        &ghost_name,
+                        &session.id,
+                        messages,
+                    )
+                    .await;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("[session:{}] Chat error: {}", session.id, e);
+                    self.send_content(chat_id, ids::ERROR_PROCESSING_REQUEST)
+                        .await;
+                }
+            }
+            return;
+        }
+
+        let _typing = TimedTyping::start(&self.api, chat_id);
+
+        // Stream tool calls as they arrive when verbose mode is on
+        let (tool_tx, tool_stream_handle) = if self.state.is_verbose(&operator_id).await {
+            let (tx, mut rx) =
+                tokio::sync::mpsc::unbounded_channel::<Vec<crate::state::ToolCallSummary>>();
+            let api = self.api.clone();
+            let handle = tokio::spawn(async move {
+                while let Some(calls) = rx.recv().await {
+                    let _ = send_tool_calls(&api, chat_id, &calls).await;
+                }
+            });
+            (Some(tx), Some(handle))
+        } else {
+            (None, None)
+        };
+
+        let result = operator_flow::run_chat_with_pending_and_attachments(
+            self.state.as_ref(),
+            Some("telegram"),
+            None,
+            &ghost_name,
+            &session.id,
+            &operator_id,
+            clean_content,
+            attachment_blocks,
+            tool_tx.as_ref(),
+        )
+        .await;
+
+        // Drop the sender so the streaming task drains and exits
+        drop(tool_tx);
+        if let Some(handle) = tool_stream_handle {
+            let _ = handle.await;
+        }
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(ChatError::OverBudget(report)) => vec![OutboundMessage::gateway(
+                operator_flow::usage_budget_message(
+                    ids::USAGE_BUDGET_EXCEEDED,
+                    &report,
+                    Some("telegram"),
+                ),
+            )],
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(chat_id, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+                return;
+            }
+        };
+        send_outbound_messages(
+            self.state.as_ref(),
+            &self.api,
+            chat_id,
+            &operator_external_id,
+            &operator_id,
+            &ghost_name,
+            &session.id,
+            messages,
+        )
+        .await;
+    }
+
+    async fn handle_callback(&self, query: CallbackQuery) {
+        let (Some(data), Some(message)) = (query.data.as_deref(), query.message.as_ref()) else {
+            let _ = self.api.answer_callback_query(&query.id, None).await;
+            return;
+        };
+        let chat_id = message.chat.id;
+        let external_id = query.from.id.to_string();
+
+        if let Some(choice) = data.strip_prefix("tk:iface:") {
+            let _ = self.api.answer_callback_query(&query.id, None).await;
+            let _ = self.api.clear_keyboard(chat_id, message.message_id).await;
+            self.handle_interface_choice(chat_id, &external_id, &query.from.display_name(), choice)
+                .await;
+            return;
+        }
+
+        let Some(token) = data.strip_prefix("tk:a:") else {
+            let _ = self.api.answer_callback_query(&query.id, None).await;
+            return;
+        };
+        let Some(pending) = self.state.take_pending_gateway_action(token).await else {
+            let _ = self
+                .api
+                .answer_callback_query(
+                    &query.id,
+                    Some("This action expired. Please send your command again."),
+                )
+                .await;
+            return;
+        };
+
+        if pending.external_id != external_id || pending.channel_id != chat_id.to_string() {
+            // Someone else in a group chat: leave the action to its owner.
+            self.state.set_pending_gateway_action(token, pending).await;
+            let _ = self
+                .api
+                .answer_callback_query(&query.id, Some("This action is not valid for you."))
+                .await;
+            return;
+        }
+
+        let _ = self.api.answer_callback_query(&query.id, None).await;
+        let _ = self.api.clear_keyboard(chat_id, message.message_id).await;
+
+        // Telegram has no modals: free-text answers come back as replies.
+        match pending.intent.as_str() {
+            "ghost.name_prompt" => {
+                if let Err(e) = send_ghost_name_prompt(&self.api, chat_id).await {
+                    error!("Failed to send Telegram ghost name prompt: {}", e);
+                }
+            }
+            "tool_loop.set_steps" => {
+                self.send_content(chat_id, ids::TELEGRAM_STEPS_PROMPT).await;
+            }
+            intent => {
+                let intent = intent.to_string();
+                self.run_action_intent(chat_id, pending, &intent).await;
+            }
+        }
+    }
+
+    async fn handle_interface_choice(
+        &self,
+        chat_id: i64,
+        operator_external_id: &str,
+        operator_name: &str,
+        choice: &str,
+    ) {
+        let platform = t_koma_db::Platform::Telegram;
+        let normalized = choice.trim().to_lowercase();
+
+        if normalized == "existing" {
+            self.send_content(chat_id, ids::EXISTING_OPERATOR_TODO)
+                .await;
+            return;
+        }
+
+        if normalized != "new" {
+            send_interface_prompt(&self.api, chat_id).await;
+            return;
+        }
+
+        let operator = match t_koma_db::OperatorRepository::create_new(
+            self.state.koma_db.pool(),
+            operator_name,
+            platform,
+            t_koma_db::OperatorAccessLevel::Standard,
+        )
+        .await
+        {
+            Ok(op) => op,
+            Err(e) => {
+                error!("Failed to create operator: {}", e);
+                self.send_content(chat_id, ids::FAILED_CREATE_OPERATOR)
+                    .await;
+                return;
+            }
+        };
+
+        if let Err(e) = t_koma_db::InterfaceRepository::create(
+            self.state.koma_db.pool(),
+            &operator.id,
+            platform,
+            operator_external_id,
+            operator_name,
+        )
+        .await
+        {
+            error!("Failed to create interface: {}", e);
+            self.send_content(chat_id, ids::FAILED_CREATE_INTERFACE)
+                .await;
+            return;
+        }
+
+        self.state
+            .clear_interface_pending(platform, operator_external_id)
+            .await;
+
+        self.send_content(chat_id, ids::OPERATOR_CREATED_AWAITING_APPROVAL)
+            .await;
+
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            crate::operator_flow::notify_new_operator(&state, &operator).await;
+        });
+    }
+
+    async fn run_action_intent(&self, chat_id: i64, pending: PendingGatewayAction, intent: &str) {
+        let control_text = match intent {
+            "approval.approve" => "approve",
+            "approval.deny" => "deny",
+            "tool_loop.continue_default" => "steps 1",
+            "tool_loop.deny" => "deny",
+            "ghost.select" => {
+                if let Some(ghost_name) = pending.payload.as_deref() {
+                    self.state
+                        .set_active_ghost(&pending.operator_id, ghost_name)
+                        .await;
+                    let text =
+                        super::render_message(ids::ACTIVE_GHOST_SET, &[("ghost_name", ghost_name)]);
+                    self.send_text(chat_id, &text).await;
+                }
+                return;
+            }
+            "admin.approve_operator" | "admin.deny_operator" => {
+                self.review_operator(chat_id, &pending, intent == "admin.approve_operator")
+                    .await;
+                return;
+            }
+            _ => return,
+        };
+
+        let _typing = TimedTyping::start(&self.api, chat_id);
+        match operator_flow::run_tool_control_command(
+            self.state.as_ref(),
+            Some("telegram"),
+            None,
+            &pending.ghost_name,
+            &pending.session_id,
+            &pending.operator_id,
+            control_text,
+        )
+        .await
+        {
+            Ok(Some(messages)) => {
+                send_outbound_messages(
+                    self.state.as_ref(),
+                    &self.api,
+                    chat_id,
+                    &pending.external_id,
+                    &pending.operator_id,
+                    &pending.ghost_name,
+                    &pending.session_id,
+                    messages,
+                )
+                .await;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                error!("Telegram action error: {}", err);
+                self.send_content(chat_id, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+            }
+        }
+    }
+
+    /// Approve or deny the operator named in `pending.payload` (PM buttons).
+    async fn review_operator(&self, chat_id: i64, pending: &PendingGatewayAction, approve: bool) {
+        let Some(target_id) = pending.payload.as_deref() else {
+            return;
+        };
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            &pending.operator_id,
+            &t_koma_db::OperatorPermission::ManageOperators,
+        )
+        .await
+        {
+            return;
+        }
+
+        let pool = self.state.koma_db.pool();
+        let result = if approve {
+            t_koma_db::OperatorRepository::approve(pool, target_id).await
+        } else {
+            t_koma_db::OperatorRepository::deny(pool, target_id).await
+        };
+        match result {
+            Ok(operator) => {
+                if approve {
+                    crate::operator_flow::welcome_approved_operator(&self.state, target_id).await;
+                }
+                let id = if approve {
+                    ids::ADMIN_OPERATOR_APPROVED
+                } else {
+                    ids::ADMIN_OPERATOR_DENIED
+                };
+                let text = super::render_message(id, &[("operator_name", &operator.name)]);
+                self.send_text(chat_id, &text).await;
+            }
+            Err(e) => {
+                warn!(
+                    "PM {} failed to review operator {}: {}",
+                    pending.operator_id, target_id, e
+                );
+                let verb = if approve { "Approve" } else { "Deny" };
+                self.send_text(chat_id, &format!("{verb} failed: {e}"))
+                    .await;
+            }
+        }
+    }
+
+    /// First contact after approval: ask for a ghost name, then take the
+    /// next plain message as that name.
+    async fn handle_no_ghosts(&self, chat_id: i64, operator: &t_koma_db::Operator, content: &str) {
+        let prompted =
+            t_koma_db::OperatorRepository::get_by_id(self.state.koma_db.pool(), &operator.id)
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|op| op.welcomed);
+
+        if prompted && !content.is_empty() && !content.starts_with('/') {
+            self.boot_new_ghost(chat_id, &operator.id, content).await;
+            return;
+        }
+
+        if !prompted
+            && let Err(e) = t_koma_db::OperatorRepository::mark_welcomed(
+                self.state.koma_db.pool(),
+                &operator.id,
+            )
+            .await
+        {
+            error!("Failed to mark operator {} as welcomed: {}", operator.id, e);
+        }
+        if let Err(e) = send_ghost_name_prompt(&self.api, chat_id).await {
+            error!("Failed to send Telegram ghost name prompt: {}", e);
+        }
+    }
+
+    async fn boot_new_ghost(&self, chat_id: i64, operator_id: &str, ghost_name: &str) {
+        if !crate::operator_flow::operator_has_permission(
+            self.state.as_ref(),
+            operator_id,
+            &t_koma_db::OperatorPermission::CreateGhosts,
+        )
+        .await
+        {
+            self.send_content(chat_id, ids::GHOST_CREATION_NOT_PERMITTED)
+                .await;
+            return;
+        }
+
+        let ghost = match t_koma_db::GhostRepository::create(
+            self.state.koma_db.pool(),
+            operator_id,
+            ghost_name,
+        )
+        .await
+        {
+            Ok(ghost) => ghost,
+            Err(e) => {
+                let error_text = e.to_string();
+                let invalid = super::render_message(
+                    ids::INVALID_GHOST_NAME,
+                    &[("error", error_text.as_str()), ("ghost_name_prompt", "")],
+                );
+                self.send_text(chat_id, &invalid).await;
+                return;
+            }
+        };
+
+        let workspace_path = match t_koma_db::ghosts::ghost_workspace_path(&ghost.name) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Failed to get workspace path: {}", e);
+                self.send_content(chat_id, ids::ERROR_FAILED_INIT_GHOST_STORAGE)
+                    .await;
+                return;
+            }
+        };
+
+        persist_ghost_name_to_soul(&workspace_path, &ghost.name).await;
+
+        let session = match t_koma_db::SessionRepository::create(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            operator_id,
+        )
+        .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                error!("Failed to create session: {}", e);
+                self.send_content(chat_id, ids::FAILED_CREATE_SESSION).await;
+                return;
+            }
+        };
+
+        let bootstrap = match content::prompt_text(ids::PROMPT_BOOTSTRAP, None, &[]) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to load prompts/system/bootstrap.md: {}", e);
+                self.send_content(chat_id, ids::ERROR_MISSING_BOOTSTRAP)
+                    .await;
+                return;
+            }
+        };
+
+        let _typing = TimedTyping::start(&self.api, chat_id);
+        let ghost_response = match self
+            .state
+            .chat(&ghost.name, &session.id, operator_id, &bootstrap)
+            .await
+        {
+            Ok(text) => text,
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", session.id, e);
+                self.send_content(chat_id, ids::ERROR_GHOST_BOOT_FAILED)
+                    .await;
+                return;
+            }
+        };
+
+        self.state.set_active_ghost(operator_id, &ghost.name).await;
+
+        let header = super::render_message(
+            ids::GHOST_CREATED_HEADER_WITH_NAME,
+            &[("ghost_name", ghost.name.as_str())],
+        );
+        self.send_text(chat_id, &header).await;
+        if let Err(e) = send_assistant_text(&self.api, chat_id, &ghost_response).await {
+            error!(
+                "[session:{}] Failed to send Telegram message: {}",
+                session.id, e
+            );
+        }
+    }
+
+    async fn send_ghost_select_prompt(
+        &self,
+        chat_id: i64,
+        operator_id: &str,
+        operator_external_id: &str,
+        ghosts: &[t_koma_db::Ghost],
+    ) {
+        let list_rows = format_ghost_list_lines(ghosts);
+        let list = super::render_message(ids::GHOST_LIST, &[("ghost_list", list_rows.as_str())]);
+
+        let mut keyboard = Vec::new();
+        for ghost in ghosts.iter().take(25) {
+            let token = uuid::Uuid::new_v4().to_string();
+            self.state
+                .set_pending_gateway_action(
+                    &token,
+                    PendingGatewayAction {
+                        operator_id: operator_id.to_string(),
+                        ghost_name: String::new(),
+                        session_id: "active".to_string(),
+                        external_id: operator_external_id.to_string(),
+                        channel_id: chat_id.to_string(),
+                        intent: "ghost.select".to_string(),
+                        payload: Some(ghost.name.clone()),
+                        expires_at: chrono::Utc::now().timestamp() + 900,
+                    },
+                )
+                .await;
+            keyboard.push(vec![InlineButton::new(
+                ghost.name.clone(),
+                format!("tk:a:{}", token),
+            )]);
+        }
+
+        let prompt =
+            super::render_message(ids::SELECT_GHOST_PROMPT, &[("ghost_list", list.as_str())]);
+        if let Err(e) = send_gateway_text(&self.api, chat_id, &prompt, Some(&keyboard)).await {
+            error!("Failed to send Telegram ghost selection: {}", e);
+        }
+    }
+
+    /// Start a fresh session: reflect on the previous one and greet the ghost.
+    async fn start_new_session(
+        &self,
+        chat_id: i64,
+        ghost: &t_koma_db::Ghost,
+        operator_id: &str,
+        operator_external_id: &str,
+        previous_session_id: &str,
+    ) {
+        let new_session = match t_koma_db::SessionRepository::create(
+            self.state.koma_db.pool(),
+            &ghost.id,
+            operator_id,
+        )
+        .await
+        {
+            Ok(s) => s,
+            Err(e) => {
+                error!(
+                    "Failed to create new session for operator {}: {}",
+                    operator_id, e
+                );
+                self.send_content(chat_id, ids::FAILED_INIT_SESSION).await;
+                return;
+            }
+        };
+
+        let _typing = TimedTyping::start(&self.api, chat_id);
+        operator_flow::queue_reflection_for_previous_session(
+            &self.state,
+            &ghost.id,
+            previous_session_id,
+        )
+        .await;
+
+        let messages = match operator_flow::run_chat_with_pending(
+            self.state.as_ref(),
+            Some("telegram"),
+            None,
+            &ghost.name,
+            &new_session.id,
+            operator_id,
+            "hello",
+            None,
+        )
+        .await
+        {
+            Ok(messages) => messages,
+            Err(ChatError::OverBudget(report)) => vec![OutboundMessage::gateway(
+                operator_flow::usage_budget_message(
+                    ids::USAGE_BUDGET_EXCEEDED,
+                    &report,
+                    Some("telegram"),
+                ),
+            )],
+            Err(e) => {
+                error!("[session:{}] Chat error: {}", new_session.id, e);
+                self.send_content(chat_id, ids::ERROR_PROCESSING_REQUEST)
+                    .await;
+                return;
+            }
+        };
+        send_outbound_messages(
+            self.state.as_ref(),
+            &self.api,
+            chat_id,
+            operator_external_id,
+            operator_id,
+            &ghost.name,
+            &new_session.id,
+            messages,
+        )
+        .await;
+    }
+
+    /// Download the message's photo or document into the ghost workspace.
+    async fn download_to_content_blocks(
+        &self,
+        msg: &Message,
+        workspace_path: &std::path::Path,
+    ) -> Vec<t_koma_db::ContentBlock> {
+        let download_dir = match attachments::downloads_dir(workspace_path).await {
+            Ok(dir) => dir,
+            Err(e) => {
+                error!("Failed to create downloads dir: {}", e);
+                return Vec::new();
+            }
+        };
+
+        // Photos come in several sizes; the last one is the largest.
+        let file = if let Some(photo) = msg.photo.as_ref().and_then(|sizes| sizes.last()) {
+            Some((
+                photo.file_id.clone(),
+                format!("{}.jpg", photo.file_unique_id),
+                Some("image/jpeg".to_string()),
+            ))
+        } else {
+            msg.document.as_ref().map(|doc| {
+                (
+                    doc.file_id.clone(),
+                    doc.file_name
+                        .clone()
+                        .unwrap_or_else(|| "document".to_string()),
+                    doc.mime_type.clone(),
+                )
+            })
+        };
+        let Some((file_id, filename, mime_type)) = file else {
+            return Vec::new();
+        };
+
+        match self.api.download_file(&file_id).await {
+            Ok(bytes) => attachments::store_attachment(&download_dir, &filename, mime_type, &bytes)
+                .await
+                .into_iter()
+                .collect(),
+            Err(e) => {
+                error!("Failed to download Telegram file {}: {}", filename, e);
+                Vec::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! Markdown-to-Telegram-HTML adapter.
+//!
+//! Telegram's HTML parse mode only knows a handful of inline tags, so the
+//! conversion is line based, like the Discord adapter:
+//! - Code fences → `<pre><code>` blocks
+//! - Headings → bold lines
+//! - Tables → `<pre>` blocks (Telegram has no table markup)
+//! - `>` quotes → `<blockquote>`
+//! - List bullets → `•`
+//! - Inline `code`, **bold**, *italic*, ~~strike~~ and `[text](url)` links
+//!
+//! Everything else is escaped, so unmatched markers come through literally.
+
+/// Telegram's limit on the text of one message (after entity parsing).
+pub const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// Source characters per chunk; leaves room for the gateway header.
+const CHUNK_CHARS: usize = 3800;
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Convert markdown text into Telegram HTML.
+pub fn markdown_to_html(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<(String, Vec<&str>)> = None;
+    let mut table: Vec<&str> = Vec::new();
+    let mut quote: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if let Some((lang, body)) = fence.as_mut() {
+            if trimmed.starts_with("```") {
+                out.push(code_block(lang, body));
+                fence = None;
+            } else {
+                body.push(line);
+            }
+            continue;
+        }
+
+        if !table.is_empty() && !is_table_line(trimmed) {
+            out.push(pre_block(&table));
+            table.clear();
+        }
+        if !quote.is_empty() && !trimmed.starts_with('>') {
+            out.push(format!("<blockquote>{}</blockquote>", quote.join("\n")));
+            quote.clear();
+        }
+
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            fence = Some((lang.trim().to_string(), Vec::new()));
+        } else if is_table_line(trimmed) {
+            table.push(trimmed);
+        } else if let Some(rest) = trimmed.strip_prefix('>') {
+            quote.push(render_inline(rest.trim_start()));
+        } else if let Some(heading) = heading_text(trimmed) {
+            out.push(format!("<b>{}</b>", render_inline(heading)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            out.push(format!("{indent}• {}", render_inline(item)));
+        } else {
+            out.push(render_inline(line));
+        }
+    }
+
+    // An unterminated fence still renders as code.
+    if let Some((lang, body)) = fence {
+        out.push(code_block(&lang, &body));
+    }
+    if !table.is_empty() {
+        out.push(pre_block(&table));
+    }
+    if !quote.is_empty() {
+        out.push(format!("<blockquote>{}</blockquote>", quote.join("\n")));
+    }
+
+    out.join("\n")
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) {
+        line[hashes..].strip_prefix(' ').map(str::trim)
+    } else {
+        None
+    }
+}
+
+fn is_table_line(line: &str) -> bool {
+    line.starts_with('|') && line.ends_with('|') && line.len() > 1
+}
+
+fn code_block(lang: &str, body: &[&str]) -> String {
+    let code = escape_html(&body.join("\n"));
+    if lang.is_empty() {
+        format!("<pre>{code}</pre>")
+    } else {
+        format!(
+            "<pre><code class=\"language-{}\">{code}</code></pre>",
+            escape_html(lang)
+        )
+    }
+}
+
+fn pre_block(lines: &[&str]) -> String {
+    format!("<pre>{}</pre>", escape_html(&lines.join("\n")))
+}
+
+/// Inline markers, longest first so `**` wins over `*`.
+const INLINE_MARKERS: &[(&str, &str)] = &[
+    ("**", "b"),
+    ("__", "b"),
+    ("~~", "s"),
+    ("*", "i"),
+    ("_", "i"),
+];
+
+fn render_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut prev: Option<char> = None;
+
+    'outer: while let Some(ch) = rest.chars().next() {
+        if ch == '`'
+            && let Some(end) = rest[1..].find('`')
+            && end > 0
+        {
+            out.push_str(&format!("<code>{}</code>", escape_html(&rest[1..=end])));
+            prev = Some('`');
+            rest = &rest[end + 2..];
+            continue;
+        }
+
+        if ch == '['
+            && let Some((label, url, len)) = parse_link(rest)
+        {
+            out.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(url),
+                render_inline(label)
+            ));
+            prev = Some(')');
+            rest = &rest[len..];
+            continue;
+        }
+
+        // `_` inside a word (snake_case) is not emphasis.
+        let word_boundary = !prev.is_some_and(char::is_alphanumeric);
+        for (marker, tag) in INLINE_MARKERS {
+            if *marker == "_" && !word_boundary {
+                continue;
+            }
+            if let Some(after) = rest.strip_prefix(marker)
+                && let Some(end) = find_closing(after, marker)
+            {
+                out.push_str(&format!("<{tag}>{}</{tag}>", render_inline(&after[..end])));
+                prev = Some(ch);
+                rest = &after[end + marker.len()..];
+                continue 'outer;
+            }
+        }
+
+        out.push_str(&escape_html(&ch.to_string()));
+        prev = Some(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    out
+}
+
+/// Byte offset of the marker closing an emphasis span opened just before `text`.
+fn find_closing(text: &str, marker: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut from = 0;
+    while let Some(pos) = text[from..].find(marker) {
+        let end = from + pos;
+        let after = &text[end + marker.len()..];
+        let inner = &text[..end];
+        // A single `*` must not close on half of a `**`, and `_` must end a word.
+        let doubled = marker.len() == 1 && after.starts_with(marker);
+        let mid_word = marker == "_" && after.starts_with(char::is_alphanumeric);
+        if !inner.is_empty() && !inner.ends_with(char::is_whitespace) && !doubled && !mid_word {
+            return Some(end);
+        }
+        from = end + marker.len();
+        if doubled {
+            from += marker.len();
+        }
+    }
+    None
+}
+
+/// `[label](url)` at the start of `text`: label, url and byte length.
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let label = &text[1..label_end];
+    if label.is_empty() || label.contains('[') {
+        return None;
+    }
+    let url_start = label_end + 2;
+    let url_len = text[url_start..].find(')')?;
+    let url = &text[url_start..url_start + url_len];
+    let allowed = ["http://", "https://", "tg://", "mailto:"];
+    if url.contains(char::is_whitespace) || !allowed.iter().any(|p| url.starts_with(p)) {
+        return None;
+    }
+    Some((label, url, url_start + url_len + 1))
+}
+
+/// Split markdown into chunks that each fit one Telegram message.
+pub fn split_markdown(content: &str) -> Vec<String> {
+    split_markdown_at(content, CHUNK_CHARS)
+}
+
+/// Split markdown into chunks of at most `max_chars` source characters.
+///
+/// Splits on line boundaries and re-opens code fences cut in half, so each
+/// chunk renders as well-formed markup on its own.
+pub fn split_markdown_at(content: &str, max_chars: usize) -> Vec<String> {
+    if content.chars().count() <= max_chars {
+        return vec![content.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0usize;
+    let mut open_fence: Option<String> = None;
+
+    for line in content.split_inclusive('\n') {
+        for piece in split_long_line(line, max_chars) {
+            let piece_len = piece.chars().count();
+            if current_len + piece_len > max_chars && !current.is_empty() {
+                if open_fence.is_some() {
+                    current.push_str("\n```");
+                }
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+                if let Some(fence) = &open_fence {
+                    current.push_str(fence);
+                    current.push('\n');
+                    current_len = fence.chars().count() + 1;
+                }
+            }
+            current.push_str(piece);
+            current_len += piece_len;
+        }
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            open_fence = match open_fence {
+                Some(_) => None,
+                None => Some(trimmed.to_string()),
+            };
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn split_long_line(line: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while rest.chars().count() > max_chars {
+        let (cut, _) = rest
+            .char_indices()
+            .nth(max_chars)
+            .expect("longer than limit");
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_markup() {
+        assert_eq!(
+            markdown_to_html("**bold** and *it* and `a<b>` ~~gone~~"),
+            "<b>bold</b> and <i>it</i> and <code>a&lt;b&gt;</code> <s>gone</s>"
+        );
+        assert_eq!(
+            markdown_to_html("see [docs](https://example.com/?a=1&b=2)"),
+            "see <a href=\"https://example.com/?a=1&amp;b=2\">docs</a>"
+        );
+        // Unmatched or intra-word markers stay literal.
+        assert_eq!(
+            markdown_to_html("snake_case_name 2 * 3 **open"),
+            "snake_case_name 2 * 3 **open"
+        );
+        assert_eq!(
+            markdown_to_html("[x](javascript:alert)"),
+            "[x](javascript:alert)"
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let md = "### T-KOMA // ゲート\n- one\n- `two`\n> quoted\n\n```rust\nfn f() -> u8 { 1 < 2 }\n```\n| a | b |\n|---|---|\n| 1 | 2 |";
+        assert_eq!(
+            markdown_to_html(md),
+            "<b>T-KOMA // ゲート</b>\n• one\n• <code>two</code>\n<blockquote>quoted</blockquote>\n\n\
+             <pre><code class=\"language-rust\">fn f() -&gt; u8 { 1 &lt; 2 }</code></pre>\n\
+             <pre>| a | b |\n|---|---|\n| 1 | 2 |</pre>"
+        );
+        assert_eq!(markdown_to_html("```\nunclosed"), "<pre>unclosed</pre>");
+    }
+
+    #[test]
+    fn test_split_markdown_keeps_fences_balanced() {
+        assert_eq!(split_markdown("short"), vec!["short".to_string()]);
+
+        let code = "let x = 1;\n".repeat(600);
+        let md = format!("intro\n```rust\n{code}```\noutro\n");
+        let chunks = split_markdown(&md);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= CHUNK_CHARS + 4);
+            assert_eq!(chunk.matches("```").count() % 2, 0, "{chunk}");
+        }
+        assert!(chunks[1].starts_with("```rust\n"));
+
+        let long_line = "x".repeat(CHUNK_CHARS * 2 + 10);
+        let chunks = split_markdown(&long_line);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), long_line);
+    }
+}
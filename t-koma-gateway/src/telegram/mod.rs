@@ -0,0 +1,65 @@
+mod api;
+mod bot;
+mod format;
+mod send;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::info;
+
+pub use bot::Bot;
+pub(crate) use format::split_markdown_at;
+pub use send::{
+    send_approved_operator_ghost_prompt, send_new_operator_notification_to_pms,
+    send_operator_gateway_message,
+};
+
+fn render_message(id: &str, vars: &[(&str, &str)]) -> String {
+    crate::gateway_message::from_content(id, Some("telegram"), vars).text_fallback
+}
+
+/// Start the Telegram bot (optional - returns Ok(None) if no token)
+///
+/// Checks the token with `getMe`; the caller spawns [`Bot::run`] to start
+/// long polling.
+pub async fn start_telegram_bot(
+    token: Option<String>,
+    poll_timeout_secs: u64,
+    state: Arc<crate::state::AppState>,
+) -> Result<Option<Arc<Bot>>, TelegramError> {
+    let token = match token {
+        Some(t) if !t.is_empty() => t,
+        _ => {
+            info!("No TELEGRAM_BOT_TOKEN set, skipping Telegram bot");
+            return Ok(None);
+        }
+    };
+
+    info!("Starting Telegram bot...");
+
+    let api = api::TelegramApi::new(&token, Duration::from_secs(poll_timeout_secs));
+    let me = api.get_me().await?;
+    info!(
+        "Telegram bot connected as @{}",
+        me.username.as_deref().unwrap_or(&me.first_name)
+    );
+
+    Ok(Some(Arc::new(Bot::new(state, api, me, poll_timeout_secs))))
+}
+
+/// Telegram-related errors
+#[derive(Debug, thiserror::Error)]
+pub enum TelegramError {
+    #[error("Telegram request failed: {0}")]
+    Request(String),
+    #[error("Telegram API error: {0}")]
+    Api(String),
+}
+
+impl From<reqwest::Error> for TelegramError {
+    fn from(err: reqwest::Error) -> Self {
+        // Request URLs embed the bot token; keep it out of errors and logs.
+        TelegramError::Request(err.without_url().to_string())
+    }
+}
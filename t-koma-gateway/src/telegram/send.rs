@@ -0,0 +1,388 @@
+use tracing::{debug, error, warn};
+
+use crate::content::ids;
+use crate::operator_flow::OutboundMessage;
+use crate::state::{AppState, PendingGatewayAction, ToolCallSummary};
+
+use super::TelegramError;
+use super::api::{InlineButton, InlineKeyboard, TelegramApi};
+use super::format::{TELEGRAM_MESSAGE_LIMIT, escape_html, markdown_to_html, split_markdown};
+
+const GATEWAY_HEADER: &str = "<b>T-KOMA // ティコマ</b>";
+
+/// Seconds a button stays usable.
+const ACTION_TTL_SECS: i64 = 900;
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+/// Send one HTML message, falling back to plain text if Telegram rejects
+/// the markup.
+async fn send_html(
+    api: &TelegramApi,
+    chat_id: i64,
+    html: &str,
+    plain: &str,
+    keyboard: Option<&InlineKeyboard>,
+) -> Result<(), TelegramError> {
+    match api.send_message(chat_id, html, true, keyboard).await {
+        Ok(_) => Ok(()),
+        Err(TelegramError::Api(reason)) => {
+            warn!(
+                chat_id,
+                "Telegram rejected HTML message ({}), falling back to plain text", reason
+            );
+            let plain: String = plain.chars().take(TELEGRAM_MESSAGE_LIMIT).collect();
+            api.send_message(chat_id, &plain, false, keyboard)
+                .await
+                .map(|_| ())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Send ghost assistant text, split to Telegram's message limit.
+pub(super) async fn send_assistant_text(
+    api: &TelegramApi,
+    chat_id: i64,
+    content: &str,
+) -> Result<(), TelegramError> {
+    for chunk in split_markdown(content) {
+        send_html(api, chat_id, &markdown_to_html(&chunk), &chunk, None).await?;
+    }
+    Ok(())
+}
+
+/// Send a gateway system message; the keyboard goes on the last chunk.
+pub(super) async fn send_gateway_text(
+    api: &TelegramApi,
+    chat_id: i64,
+    content: &str,
+    keyboard: Option<&InlineKeyboard>,
+) -> Result<(), TelegramError> {
+    let chunks = split_markdown(content);
+    let last = chunks.len() - 1;
+    for (index, chunk) in chunks.iter().enumerate() {
+        let html = markdown_to_html(chunk);
+        let html = if index == 0 {
+            format!("{GATEWAY_HEADER}\n\n{html}")
+        } else {
+            html
+        };
+        let keyboard = if index == last { keyboard } else { None };
+        send_html(api, chat_id, &html, chunk, keyboard).await?;
+    }
+    Ok(())
+}
+
+/// Render tool call summaries (verbose mode) as one compact message.
+pub(super) async fn send_tool_calls(
+    api: &TelegramApi,
+    chat_id: i64,
+    calls: &[ToolCallSummary],
+) -> Result<(), TelegramError> {
+    if calls.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = Vec::with_capacity(calls.len());
+    for call in calls {
+        let arrow = if call.is_error { "⚠" } else { "→" };
+        lines.push(format!(
+            "<code>{}({})</code> {} {}",
+            escape_html(&call.name),
+            escape_html(&call.input_preview),
+            arrow,
+            escape_html(&call.output_preview)
+        ));
+    }
+    let plain = lines.join("\n");
+    let html: String = if plain.chars().count() > TELEGRAM_MESSAGE_LIMIT {
+        // Never cut through a tag: drop whole lines instead.
+        let mut kept = String::new();
+        for line in &lines {
+            if kept.chars().count() + line.chars().count() + 1 > TELEGRAM_MESSAGE_LIMIT {
+                break;
+            }
+            kept.push_str(line);
+            kept.push('\n');
+        }
+        kept
+    } else {
+        plain.clone()
+    };
+    send_html(api, chat_id, &html, &plain, None).await
+}
+
+/// Register a one-shot action and return its `callback_data`.
+async fn register_action(state: &AppState, action: PendingGatewayAction) -> String {
+    let token = uuid::Uuid::new_v4().to_string();
+    state.set_pending_gateway_action(&token, action).await;
+    format!("tk:a:{}", token)
+}
+
+pub(super) async fn send_interface_prompt(api: &TelegramApi, chat_id: i64) {
+    let message =
+        crate::gateway_message::from_content(ids::TELEGRAM_INTERFACE_PROMPT, Some("telegram"), &[]);
+    let buttons = message
+        .actions
+        .iter()
+        .map(|action| InlineButton::new(action.label.clone(), format!("tk:iface:{}", action.id)))
+        .collect();
+    let _ = send_gateway_text(api, chat_id, &message.text_fallback, Some(&vec![buttons])).await;
+}
+
+/// Ask for a ghost name; the operator answers in a reply.
+pub(super) async fn send_ghost_name_prompt(
+    api: &TelegramApi,
+    chat_id: i64,
+) -> Result<(), TelegramError> {
+    let text = super::render_message(ids::TELEGRAM_GHOST_NAME_PROMPT, &[]);
+    let html = format!("{GATEWAY_HEADER}\n\n{}", markdown_to_html(&text));
+    api.send_force_reply(chat_id, &html, "ALPHA")
+        .await
+        .map(|_| ())
+}
+
+// ---------------------------------------------------------------------------
+// Outbound message dispatch
+// ---------------------------------------------------------------------------
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn send_telegram_gateway_message(
+    state: &AppState,
+    api: &TelegramApi,
+    chat_id: i64,
+    external_id: &str,
+    operator_id: &str,
+    ghost_name: &str,
+    session_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<(), TelegramError> {
+    let mut row = Vec::new();
+    for action in message.actions.iter().take(5) {
+        let callback_data = register_action(
+            state,
+            PendingGatewayAction {
+                operator_id: operator_id.to_string(),
+                ghost_name: ghost_name.to_string(),
+                session_id: session_id.to_string(),
+                external_id: external_id.to_string(),
+                channel_id: chat_id.to_string(),
+                intent: action.intent.clone(),
+                payload: None,
+                expires_at: chrono::Utc::now().timestamp() + ACTION_TTL_SECS,
+            },
+        )
+        .await;
+        row.push(InlineButton::new(action.label.clone(), callback_data));
+    }
+
+    let keyboard = (!row.is_empty()).then(|| vec![row]);
+    send_gateway_text(api, chat_id, &message.text_fallback, keyboard.as_ref()).await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn send_outbound_messages(
+    state: &AppState,
+    api: &TelegramApi,
+    chat_id: i64,
+    external_id: &str,
+    operator_id: &str,
+    ghost_name: &str,
+    session_id: &str,
+    messages: Vec<OutboundMessage>,
+) {
+    debug!(
+        ghost = ghost_name,
+        chat_id,
+        message_count = messages.len(),
+        "sending outbound messages to Telegram"
+    );
+
+    for message in &messages {
+        let result = match message {
+            OutboundMessage::AssistantText(text) => send_assistant_text(api, chat_id, text).await,
+            OutboundMessage::Gateway(msg) => {
+                send_telegram_gateway_message(
+                    state,
+                    api,
+                    chat_id,
+                    external_id,
+                    operator_id,
+                    ghost_name,
+                    session_id,
+                    msg,
+                )
+                .await
+            }
+            OutboundMessage::ToolCalls(calls) => send_tool_calls(api, chat_id, calls).await,
+        };
+        if let Err(e) = result {
+            error!(
+                "[ghost:{}] Failed to send message to Telegram: {}",
+                ghost_name, e
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PM notification for new operator registration
+// ---------------------------------------------------------------------------
+
+pub async fn send_new_operator_notification_to_pms(
+    state: &AppState,
+    telegram_bot_token: &str,
+    new_operator: &t_koma_db::Operator,
+) {
+    let pms = match t_koma_db::OperatorRepository::list_puppet_masters_with_interface(
+        state.koma_db.pool(),
+        t_koma_db::Platform::Telegram,
+    )
+    .await
+    {
+        Ok(list) => list,
+        Err(e) => {
+            warn!(
+                "Failed to list PM operators for new-operator notification: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if pms.is_empty() {
+        debug!("No PM operators with Telegram interfaces to notify");
+        return;
+    }
+
+    let text = super::render_message(
+        ids::ADMIN_NEW_OPERATOR_PENDING,
+        &[("operator_name", &new_operator.name)],
+    );
+    let api = TelegramApi::new(telegram_bot_token, std::time::Duration::ZERO);
+
+    for (pm_op, telegram_external_id) in &pms {
+        let Ok(chat_id) = telegram_external_id.parse::<i64>() else {
+            warn!(
+                "Invalid Telegram external_id '{}' for PM operator {}",
+                telegram_external_id, pm_op.id
+            );
+            continue;
+        };
+
+        let mut row = Vec::new();
+        for (label, intent) in [
+            ("APPROVE", "admin.approve_operator"),
+            ("DENY", "admin.deny_operator"),
+        ] {
+            let callback_data = register_action(
+                state,
+                PendingGatewayAction {
+                    operator_id: pm_op.id.clone(),
+                    ghost_name: String::new(),
+                    session_id: String::new(),
+                    external_id: telegram_external_id.clone(),
+                    channel_id: chat_id.to_string(),
+                    intent: intent.to_string(),
+                    payload: Some(new_operator.id.clone()),
+                    expires_at: chrono::Utc::now().timestamp() + 3600,
+                },
+            )
+            .await;
+            row.push(InlineButton::new(label, callback_data));
+        }
+
+        if let Err(e) = send_gateway_text(&api, chat_id, &text, Some(&vec![row])).await {
+            warn!(
+                "Failed to send new-operator notification to PM {}: {}",
+                pm_op.id, e
+            );
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Messages outside of a chat turn
+// ---------------------------------------------------------------------------
+
+/// Ask a freshly approved operator to name their first ghost.
+///
+/// Returns `Ok(false)` when there is nothing to do (no Telegram interface,
+/// already welcomed or already has ghosts).
+pub async fn send_approved_operator_ghost_prompt(
+    state: &AppState,
+    telegram_bot_token: &str,
+    operator_id: &str,
+) -> Result<bool, String> {
+    let operator = t_koma_db::OperatorRepository::get_by_id(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("operator not found: {}", operator_id))?;
+
+    if operator.status != t_koma_db::OperatorStatus::Approved || operator.welcomed {
+        return Ok(false);
+    }
+
+    let ghosts = t_koma_db::GhostRepository::list_by_operator(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !ghosts.is_empty() {
+        return Ok(false);
+    }
+
+    let Some(chat_id) = operator_chat_id(state, operator_id).await? else {
+        return Ok(false);
+    };
+    let api = TelegramApi::new(telegram_bot_token, std::time::Duration::ZERO);
+    send_ghost_name_prompt(&api, chat_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    t_koma_db::OperatorRepository::mark_welcomed(state.koma_db.pool(), operator_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+/// Send a gateway message to an operator outside of a chat turn.
+///
+/// Returns `Ok(false)` when the operator has no Telegram interface.
+pub async fn send_operator_gateway_message(
+    state: &AppState,
+    telegram_bot_token: &str,
+    operator_id: &str,
+    message: &t_koma_core::GatewayMessage,
+) -> Result<bool, String> {
+    let Some(chat_id) = operator_chat_id(state, operator_id).await? else {
+        return Ok(false);
+    };
+    let api = TelegramApi::new(telegram_bot_token, std::time::Duration::ZERO);
+    send_gateway_text(&api, chat_id, &message.text_fallback, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Private chat with the operator's Telegram user (same id as the user).
+async fn operator_chat_id(state: &AppState, operator_id: &str) -> Result<Option<i64>, String> {
+    let interfaces =
+        t_koma_db::InterfaceRepository::list_by_operator(state.koma_db.pool(), operator_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(telegram_iface) = interfaces
+        .into_iter()
+        .find(|iface| iface.platform == t_koma_db::Platform::Telegram)
+    else {
+        return Ok(None);
+    };
+
+    telegram_iface
+        .external_id
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("invalid telegram external_id for operator {}", operator_id))
+}
@@ -0,0 +1,632 @@
+//! `apply_patch`: edit files with a unified diff.
+//!
+//! The whole patch is checked against the current file contents before
+//! anything is written. Hunks must match line for line but may sit at a
+//! different line than their `@@` header says; header line counts are not
+//! trusted. Each touched file keeps a `.orig` backup of its previous
+//! contents, and new contents are staged next to the target, then renamed
+//! into place. If one rename fails, the files already replaced are restored
+//! and the remaining staged files are removed.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+use similar::TextDiff;
+use tokio::fs;
+
+use super::context::resolve_local_paths;
+use super::{Tool, ToolContext};
+
+/// Suffix of the backup written next to each patched file.
+const BACKUP_SUFFIX: &str = ".orig";
+
+/// Suffix of the staged new contents, renamed over the target when every
+/// file of the patch is staged.
+const STAGING_SUFFIX: &str = ".apply_patch.tmp";
+
+/// One file section of a unified diff.
+#[derive(Debug, PartialEq)]
+struct FilePatch {
+    /// `None` for `/dev/null` (file creation)
+    old_path: Option<String>,
+    /// `None` for `/dev/null` (file deletion)
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Hunk {
+    /// Line the hunk starts at in the old file (1-based; for a hunk without
+    /// old lines, the line it is inserted after)
+    old_start: usize,
+    /// Context and removed lines, with their line breaks
+    old_lines: Vec<String>,
+    /// Context and added lines, with their line breaks
+    new_lines: Vec<String>,
+}
+
+/// A validated change to one file.
+struct FileChange {
+    path: PathBuf,
+    /// Path as written in the patch, for the result diff
+    display: String,
+    /// `None` when the file is created
+    old: Option<String>,
+    /// `None` when the file is deleted
+    new: Option<String>,
+}
+
+pub struct ApplyPatchTool;
+
+#[async_trait::async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Applies a unified diff (as produced by `diff -u` or `git diff`) to one or more files. Prefer this over rewriting whole files. The patch is validated against the current contents and applied to all files or none; each changed file keeps a `.orig` backup. Use `/dev/null` as the old path to create a file and as the new path to delete one. Returns the diff actually applied."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff with '--- a/path' and '+++ b/path' headers and '@@' hunks. Paths are relative to the current working directory or absolute. Include a few unchanged context lines around each change."
+                }
+            },
+            "required": ["patch"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let patch = args["patch"]
+            .as_str()
+            .ok_or_else(|| "Missing or invalid 'patch' argument".to_string())?;
+        let files = parse_patch(patch)?;
+        let displays = files
+            .iter()
+            .map(display_path)
+            .collect::<Result<Vec<_>, _>>()?;
+        // Resolve every path up front so one approval covers the whole patch.
+        let display_refs: Vec<&str> = displays.iter().map(String::as_str).collect();
+        let paths = resolve_local_paths(context, &display_refs)?;
+
+        let mut changes: Vec<FileChange> = Vec::with_capacity(files.len());
+        for ((file, display), path) in files.iter().zip(displays).zip(paths) {
+            let change = prepare_change(file, display, path).await?;
+            if changes.iter().any(|other| other.path == change.path) {
+                return Err(format!(
+                    "'{}' appears more than once in the patch; combine its hunks into one file section.",
+                    change.display
+                ));
+            }
+            changes.push(change);
+        }
+
+        write_changes(&changes).await?;
+
+        let diff: String = changes.iter().map(render_diff).collect();
+        Ok(format!(
+            "Applied patch to {} file(s); previous contents kept as *{}.\n\n{}",
+            changes.len(),
+            BACKUP_SUFFIX,
+            diff
+        ))
+    }
+}
+
+/// Path a file section touches, as written in the patch.
+fn display_path(file: &FilePatch) -> Result<String, String> {
+    match (&file.old_path, &file.new_path) {
+        (Some(old), Some(new)) if old != new => Err(format!(
+            "Renaming '{}' to '{}' is not supported; move the file with run_shell_command first.",
+            old, new
+        )),
+        (_, Some(path)) | (Some(path), None) => Ok(path.clone()),
+        (None, None) => Err("A file section has /dev/null on both sides.".to_string()),
+    }
+}
+
+/// Read the current file at `path` and compute its patched contents.
+async fn prepare_change(
+    file: &FilePatch,
+    display: String,
+    path: PathBuf,
+) -> Result<FileChange, String> {
+    let old = match file.old_path {
+        Some(_) => Some(
+            fs::read_to_string(&path)
+                .await
+                .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?,
+        ),
+        None => {
+            if fs::try_exists(&path)
+                .await
+                .map_err(|e| format!("Failed to check file existence: {}", e))?
+            {
+                return Err(format!(
+                    "'{}' already exists; patch it instead of creating it.",
+                    display
+                ));
+            }
+            None
+        }
+    };
+
+    let patched = apply_hunks(old.as_deref().unwrap_or_default(), &file.hunks)
+        .map_err(|e| format!("'{}': {}", display, e))?;
+    let new = match file.new_path {
+        Some(_) => Some(patched),
+        None if patched.is_empty() => None,
+        None => {
+            return Err(format!(
+                "'{}': the deletion patch does not remove every line of the file.",
+                display
+            ));
+        }
+    };
+
+    Ok(FileChange {
+        path,
+        display,
+        old,
+        new,
+    })
+}
+
+/// Back up and stage every file, then replace the targets.
+async fn write_changes(changes: &[FileChange]) -> Result<(), String> {
+    let mut staged: Vec<PathBuf> = Vec::new();
+    for change in changes {
+        let staging = with_suffix(&change.path, STAGING_SUFFIX);
+        if let Err(e) = stage(change, &staging).await {
+            for path in staged.iter().chain([&staging]) {
+                let _ = fs::remove_file(path).await;
+            }
+            return Err(format!(
+                "Failed to write '{}': {}; no file was changed.",
+                change.display, e
+            ));
+        }
+        if change.new.is_some() {
+            staged.push(staging);
+        }
+    }
+
+    for (applied, change) in changes.iter().enumerate() {
+        let result = match &change.new {
+            Some(_) => fs::rename(with_suffix(&change.path, STAGING_SUFFIX), &change.path).await,
+            None => fs::remove_file(&change.path).await,
+        };
+        if let Err(e) = result {
+            roll_back(&changes[..applied], &changes[applied..]).await;
+            return Err(format!(
+                "Failed to update '{}': {}; earlier files were restored.",
+                change.display, e
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Undo the `applied` changes from their backed-up contents and drop the
+/// staged files of the `pending` ones.
+async fn roll_back(applied: &[FileChange], pending: &[FileChange]) {
+    for change in applied {
+        let _ = match &change.old {
+            Some(old) => fs::write(&change.path, old).await,
+            None => fs::remove_file(&change.path).await,
+        };
+    }
+    for change in pending {
+        let _ = fs::remove_file(with_suffix(&change.path, STAGING_SUFFIX)).await;
+    }
+}
+
+/// Write the backup, and the new contents to `staging` with the permissions
+/// of the file they replace.
+async fn stage(change: &FileChange, staging: &Path) -> std::io::Result<()> {
+    if let Some(old) = &change.old {
+        fs::write(with_suffix(&change.path, BACKUP_SUFFIX), old).await?;
+    }
+    let Some(new) = &change.new else {
+        return Ok(());
+    };
+    if let Some(parent) = change.path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::write(staging, new).await?;
+    if change.old.is_some() {
+        let permissions = fs::metadata(&change.path).await?.permissions();
+        fs::set_permissions(staging, permissions).await?;
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn render_diff(change: &FileChange) -> String {
+    let old_header = match change.old {
+        Some(_) => format!("a/{}", change.display),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = match change.new {
+        Some(_) => format!("b/{}", change.display),
+        None => "/dev/null".to_string(),
+    };
+    let old = change.old.as_deref().unwrap_or_default();
+    let new = change.new.as_deref().unwrap_or_default();
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string()
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let lines: Vec<&str> = patch.lines().collect();
+    let is_file_header = |i: usize| {
+        lines[i].starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.starts_with("+++ "))
+    };
+
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if is_file_header(i) {
+            files.push(FilePatch {
+                old_path: header_path(&lines[i][4..]),
+                new_path: header_path(&lines[i + 1][4..]),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        if !lines[i].starts_with("@@") {
+            // `diff --git`, `index` and other preamble lines
+            i += 1;
+            continue;
+        }
+
+        let file = files
+            .last_mut()
+            .ok_or("Hunk found before any '--- a/path' / '+++ b/path' header.")?;
+        let mut hunk = Hunk {
+            old_start: hunk_old_start(lines[i])?,
+            old_lines: Vec::new(),
+            new_lines: Vec::new(),
+        };
+        // Sides (old, new) the previous line went to, for "\ No newline".
+        let mut last_sides = (false, false);
+        i += 1;
+        while i < lines.len()
+            && !lines[i].starts_with("@@")
+            && !lines[i].starts_with("diff ")
+            && !is_file_header(i)
+        {
+            let line = lines[i];
+            let text = line.get(1..).unwrap_or_default();
+            last_sides = match line.chars().next() {
+                Some(' ') | None => {
+                    hunk.old_lines.push(format!("{text}\n"));
+                    hunk.new_lines.push(format!("{text}\n"));
+                    (true, true)
+                }
+                Some('-') => {
+                    hunk.old_lines.push(format!("{text}\n"));
+                    (true, false)
+                }
+                Some('+') => {
+                    hunk.new_lines.push(format!("{text}\n"));
+                    (false, true)
+                }
+                Some('\\') => {
+                    if last_sides.0 {
+                        strip_last_newline(&mut hunk.old_lines);
+                    }
+                    if last_sides.1 {
+                        strip_last_newline(&mut hunk.new_lines);
+                    }
+                    (false, false)
+                }
+                Some(_) => {
+                    return Err(format!(
+                        "Unexpected line in hunk (lines must start with ' ', '-' or '+'): {}",
+                        line
+                    ));
+                }
+            };
+            i += 1;
+        }
+        if hunk.old_lines.is_empty() && hunk.new_lines.is_empty() {
+            return Err(format!("Empty hunk: {}", lines[i - 1]));
+        }
+        file.hunks.push(hunk);
+    }
+
+    if files.is_empty() {
+        return Err(
+            "No file headers found; expected '--- a/path' and '+++ b/path' lines.".to_string(),
+        );
+    }
+    if let Some(file) = files.iter().find(|file| file.hunks.is_empty()) {
+        return Err(format!(
+            "No hunks for '{}'.",
+            file.new_path
+                .as_ref()
+                .or(file.old_path.as_ref())
+                .map_or("/dev/null", String::as_str)
+        ));
+    }
+    Ok(files)
+}
+
+/// Path from a `---`/`+++` header: timestamps and `a/`/`b/` prefixes removed.
+fn header_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or_default().trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Old start line from `@@ -12,5 +12,6 @@`.
+fn hunk_old_start(header: &str) -> Result<usize, String> {
+    header
+        .strip_prefix("@@ -")
+        .and_then(|rest| rest.split([',', ' ']).next())
+        .and_then(|start| start.parse().ok())
+        .ok_or_else(|| format!("Invalid hunk header: {}", header))
+}
+
+fn strip_last_newline(lines: &mut [String]) {
+    if let Some(last) = lines.last_mut()
+        && last.ends_with('\n')
+    {
+        last.pop();
+    }
+}
+
+/// Apply `hunks` in order to `content`.
+fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let crlf = content.contains("\r\n");
+    let mut patched = String::with_capacity(content.len());
+    let mut next = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let at = find_hunk(&lines, hunk, next).ok_or_else(|| {
+            format!(
+                "hunk {} (starting at line {}) does not match the current file contents; re-read the file and regenerate the patch.",
+                index + 1,
+                hunk.old_start
+            )
+        })?;
+        patched.extend(lines[next..at].iter().copied());
+        for line in &hunk.new_lines {
+            match line.strip_suffix('\n') {
+                Some(text) if crlf => {
+                    patched.push_str(text);
+                    patched.push_str("\r\n");
+                }
+                _ => patched.push_str(line),
+            }
+        }
+        next = at + hunk.old_lines.len();
+    }
+    patched.extend(lines[next..].iter().copied());
+    Ok(patched)
+}
+
+/// Index where the hunk's old lines appear, closest to its header position
+/// and not before `from`.
+fn find_hunk(lines: &[&str], hunk: &Hunk, from: usize) -> Option<usize> {
+    let len = hunk.old_lines.len();
+    let last = lines.len().checked_sub(len)?;
+    if from > last {
+        return None;
+    }
+    let expected = if len == 0 {
+        hunk.old_start
+    } else {
+        hunk.old_start.saturating_sub(1)
+    }
+    .clamp(from, last);
+    let matches = |at: usize| {
+        lines[at..at + len]
+            .iter()
+            .zip(&hunk.old_lines)
+            .all(|(line, old)| same_line(line, old))
+    };
+    (0..=last - from)
+        .flat_map(|offset| [expected.checked_add(offset), expected.checked_sub(offset)])
+        .flatten()
+        .filter(|at| (from..=last).contains(at))
+        .find(|&at| matches(at))
+}
+
+/// Lines are equal up to their line break (`\n`, `\r\n` or none).
+fn same_line(line: &str, patch_line: &str) -> bool {
+    line.trim_end_matches(['\r', '\n']) == patch_line.trim_end_matches(['\r', '\n'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::context::ApprovalReason;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n";
+
+    #[test]
+    fn test_parse_and_apply_with_offset() {
+        let patch = "diff --git a/src/main.rs b/src/main.rs\n\
+                     --- a/src/main.rs\n\
+                     +++ b/src/main.rs\n\
+                     @@ -10,3 +10,3 @@ fn main() {\n\
+                     \x20fn main() {\n\
+                     -    let x = 1;\n\
+                     +    let x = 2;\n\
+                     \x20    println!(\"{}\", x);\n";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(files[0].hunks[0].old_start, 10);
+
+        let patched = apply_hunks(SOURCE, &files[0].hunks).unwrap();
+        assert_eq!(patched, SOURCE.replace("x = 1", "x = 2"));
+
+        let stale = apply_hunks(&SOURCE.replace("x = 1", "y = 1"), &files[0].hunks);
+        assert!(stale.unwrap_err().contains("hunk 1"));
+    }
+
+    #[test]
+    fn test_missing_newline_and_crlf() {
+        let patch = "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(apply_hunks("a\nb", &files[0].hunks).unwrap(), "a\nc");
+        assert_eq!(
+            apply_hunks("a\r\nb\r\n", &files[0].hunks).unwrap(),
+            "a\r\nc"
+        );
+
+        assert!(parse_patch("@@ -1 +1 @@\n-a\n+b\n").is_err());
+        assert!(parse_patch("--- a/f\n+++ b/f\n@@ -1 +1 @@\n?a\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_applies_all_files_or_none() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        std::fs::write(temp_dir.path().join("main.rs"), SOURCE).unwrap();
+        std::fs::write(temp_dir.path().join("old.txt"), "gone\n").unwrap();
+        let tool = ApplyPatchTool;
+
+        // The second file does not match: nothing is written.
+        let patch = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n\
+                     --- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-other\n";
+        let error = tool
+            .execute(json!({ "patch": patch }), &mut context)
+            .await
+            .unwrap_err();
+        assert!(error.contains("'old.txt'"));
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("main.rs")).unwrap(),
+            SOURCE
+        );
+
+        let patch = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n\
+                     --- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n\
+                     --- /dev/null\n+++ b/new/notes.md\n@@ -0,0 +1 @@\n+# Notes\n";
+        let output = tool
+            .execute(json!({ "patch": patch }), &mut context)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Applied patch to 3 file(s)"));
+        assert!(output.contains("-    let x = 1;\n+    let x = 2;\n"));
+        assert!(output.contains("+++ b/new/notes.md"));
+
+        let root = temp_dir.path();
+        assert_eq!(
+            std::fs::read_to_string(root.join("main.rs")).unwrap(),
+            SOURCE.replace("x = 1", "x = 2")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("main.rs.orig")).unwrap(),
+            SOURCE
+        );
+        assert!(!root.join("old.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(root.join("old.txt.orig")).unwrap(),
+            "gone\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("new/notes.md")).unwrap(),
+            "# Notes\n"
+        );
+        assert!(!root.join("main.rs.apply_patch.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_one_approval_covers_files_outside_workspace() {
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(workspace.path());
+        let first = outside.path().join("a.txt");
+        let second = outside.path().join("b.txt");
+        std::fs::write(&first, "a\n").unwrap();
+        std::fs::write(&second, "b\n").unwrap();
+        let patch = format!(
+            "--- {a}\n+++ {a}\n@@ -1 +1 @@\n-a\n+A\n--- {b}\n+++ {b}\n@@ -1 +1 @@\n-b\n+B\n",
+            a = first.display(),
+            b = second.display()
+        );
+        let tool = ApplyPatchTool;
+
+        let error = tool
+            .execute(json!({ "patch": patch }), &mut context)
+            .await
+            .unwrap_err();
+        context.apply_approval(&ApprovalReason::parse(&error).unwrap());
+        tool.execute(json!({ "patch": patch }), &mut context)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "A\n");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "B\n");
+    }
+
+    #[tokio::test]
+    async fn test_failed_rename_rolls_back_applied_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("main.rs"), SOURCE).unwrap();
+        std::fs::write(root.join("old.txt"), "gone\n").unwrap();
+        // A non-empty directory where a created file goes: its rename fails.
+        std::fs::create_dir_all(root.join("blocked/inner")).unwrap();
+        let changes = [
+            FileChange {
+                path: root.join("main.rs"),
+                display: "main.rs".to_string(),
+                old: Some(SOURCE.to_string()),
+                new: Some("patched\n".to_string()),
+            },
+            FileChange {
+                path: root.join("old.txt"),
+                display: "old.txt".to_string(),
+                old: Some("gone\n".to_string()),
+                new: None,
+            },
+            FileChange {
+                path: root.join("blocked"),
+                display: "blocked".to_string(),
+                old: None,
+                new: Some("new\n".to_string()),
+            },
+        ];
+
+        let error = write_changes(&changes).await.unwrap_err();
+        assert!(error.contains("'blocked'"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("main.rs")).unwrap(),
+            SOURCE
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("old.txt")).unwrap(),
+            "gone\n"
+        );
+        assert!(!root.join("main.rs.apply_patch.tmp").exists());
+        assert!(!root.join("blocked.apply_patch.tmp").exists());
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::tools::{ApprovalReason, Tool, ToolContext};
+use crate::web::browser::{BrowserError, BrowserService};
+use crate::web::sanitize::ContentSanitizer;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BrowserAction {
+    Navigate,
+    Extract,
+    Click,
+    Screenshot,
+}
+
+#[derive(Debug, Deserialize)]
+struct BrowserInput {
+    action: BrowserAction,
+    url: Option<String>,
+    selector: Option<String>,
+    #[serde(default)]
+    full_page: bool,
+}
+
+pub struct BrowserTool;
+
+impl BrowserTool {
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["navigate", "extract", "click", "screenshot"],
+                    "description": "navigate: open a URL. extract: read page text. click: click an element (may require operator approval). screenshot: save a PNG of the page."
+                },
+                "url": {"type": "string", "description": "URL to open (navigate only)."},
+                "selector": {"type": "string", "description": "CSS selector (required for click, optional for extract)."},
+                "full_page": {"type": "boolean", "description": "Capture the whole page instead of the viewport (screenshot only). Default false."}
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    fn format_error(err: BrowserError) -> String {
+        match err {
+            BrowserError::DomainNotAllowed(host) => {
+                format!("browser: domain '{}' is not in the allowlist", host)
+            }
+            other => format!("browser: {}", other),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for BrowserTool {
+    fn name(&self) -> &str {
+        "browser"
+    }
+
+    fn description(&self) -> &str {
+        "Drive a headless browser for JavaScript-heavy pages that web_fetch cannot render. The page stays open between calls within this session."
+    }
+
+    fn input_schema(&self) -> Value {
+        Self::schema()
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let input: BrowserInput = serde_json::from_value(args).map_err(|e| e.to_string())?;
+
+        t_koma_core::load_dotenv();
+        let settings = t_koma_core::Settings::load().map_err(|e| e.to_string())?;
+
+        if !settings.tools.web.enabled || !settings.tools.web.browser.enabled {
+            return Err("browser tool is disabled in config".to_string());
+        }
+
+        let approve_interactive = settings.tools.web.browser.approve_interactive;
+        let mut policy = settings.tools.web.domains.for_ghost(context.ghost_name());
+        let key = context
+            .session_id()
+            .unwrap_or(context.ghost_name())
+            .to_string();
+        // Clicks on a page reached through an approved blocked domain need
+        // their own approval; otherwise the tab would be reset after the click.
+        let target_url = match input.action {
+            BrowserAction::Navigate => input.url.clone(),
+            BrowserAction::Click => {
+                BrowserService::new(settings.tools.web.browser.clone())
+                    .current_url(&key)
+                    .await
+            }
+            BrowserAction::Extract | BrowserAction::Screenshot => None,
+        };
+        if let Some(url) = target_url.as_deref() {
+            context.authorize_web_url(&mut policy, "browser", url)?;
+        }
+        let service = BrowserService::new(settings.tools.web.browser).with_domain_policy(policy);
+
+        let page = match input.action {
+            BrowserAction::Navigate => {
+                let url = input.url.ok_or("navigate requires 'url'")?;
+                service.navigate(&key, &url).await
+            }
+            BrowserAction::Extract => service.extract(&key, input.selector.as_deref()).await,
+            BrowserAction::Click => {
+                let selector = input.selector.ok_or("click requires 'selector'")?;
+                if approve_interactive && !context.has_approval("browser_interact") {
+                    let url = service.current_url(&key).await.unwrap_or_default();
+                    return Err(ApprovalReason::BrowserAction {
+                        action: format!("click `{}`", selector),
+                        url,
+                    }
+                    .to_error());
+                }
+                service.click(&key, &selector).await
+            }
+            BrowserAction::Screenshot => {
+                let png = service
+                    .screenshot(&key, input.full_page)
+                    .await
+                    .map_err(Self::format_error)?;
+                let dir = context.workspace_root().join("screenshots");
+                tokio::fs::create_dir_all(&dir)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let path = dir.join(format!(
+                    "browser-{}.png",
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S%3f")
+                ));
+                tokio::fs::write(&path, png)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                return Ok(format!("Screenshot saved to {}", path.display()));
+            }
+        };
+        let mut page = page.map_err(Self::format_error)?;
+
+        let sanitizer = ContentSanitizer::from_settings(&settings.tools.untrusted_content);
+        let source = format!("browser {}", page.url);
+        if let Some(content) = &page.content {
+            page.content = Some(sanitizer.clean(&source, content));
+        }
+        let serialized = serde_json::to_string(&page).map_err(|e| e.to_string())?;
+        let guarded = sanitizer.wrap(&source, &serialized);
+        let ref_id = context.cache_tool_result("browser", &guarded);
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_parsing() {
+        let input: BrowserInput = serde_json::from_value(json!({
+            "action": "click",
+            "selector": "#submit"
+        }))
+        .unwrap();
+        assert!(matches!(input.action, BrowserAction::Click));
+        assert_eq!(input.selector.as_deref(), Some("#submit"));
+        assert!(!input.full_page);
+
+        assert!(serde_json::from_value::<BrowserInput>(json!({"action": "type"})).is_err());
+    }
+}
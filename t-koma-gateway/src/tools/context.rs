@@ -1,8 +1,14 @@
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use sqlx::SqlitePool;
-use t_koma_db::job_logs::{JobLogRepository, TodoItem};
+use t_koma_core::WebDomainPolicy;
+use t_koma_core::config::{ShellToolSettings, ToolOutputSettings, ToolTimeoutSettings};
+use t_koma_db::job_logs::{JobLogRepository, TodoItem, TranscriptEntry};
+use tokio::time::Instant;
+
+use crate::reflection_quality::WriteJournal;
 
 /// Reason why a tool requires operator approval before proceeding.
 ///
@@ -14,6 +20,26 @@ pub enum ApprovalReason {
     WorkspaceEscape(String),
     /// Tool wants to import external sources into a reference topic (potentially large fetch).
     ReferenceImport { title: String, summary: String },
+    /// Browser tool wants to interact with a page (e.g. click an element).
+    BrowserAction { action: String, url: String },
+    /// Ghost wants to call a tool on an external MCP server.
+    McpTool {
+        server: String,
+        tool: String,
+        arguments: String,
+    },
+    /// Ghost wants to call a webhook tool defined in config.
+    WebhookTool {
+        tool: String,
+        url: String,
+        arguments: String,
+    },
+    /// Web tool wants to reach a domain the domain policy blocks.
+    WebDomain {
+        tool: String,
+        host: String,
+        url: String,
+    },
 }
 
 impl ApprovalReason {
@@ -33,6 +59,14 @@ impl ApprovalReason {
             && let Ok(value) = serde_json::from_str::<serde_json::Value>(payload)
             && let Some(reason_type) = value.get("reason").and_then(|v| v.as_str())
         {
+            // String field of the payload, empty when missing.
+            let field = |key: &str| {
+                value
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string()
+            };
             return match reason_type {
                 "reference_import" => Some(ApprovalReason::ReferenceImport {
                     title: value
@@ -46,6 +80,33 @@ impl ApprovalReason {
                         .unwrap_or("")
                         .to_string(),
                 }),
+                "browser_action" => Some(ApprovalReason::BrowserAction {
+                    action: value
+                        .get("action")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    url: value
+                        .get("url")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                }),
+                "mcp_tool" => Some(ApprovalReason::McpTool {
+                    server: field("server"),
+                    tool: field("tool"),
+                    arguments: field("arguments"),
+                }),
+                "webhook_tool" => Some(ApprovalReason::WebhookTool {
+                    tool: field("tool"),
+                    url: field("url"),
+                    arguments: field("arguments"),
+                }),
+                "web_domain" => Some(ApprovalReason::WebDomain {
+                    tool: field("tool"),
+                    host: field("host"),
+                    url: field("url"),
+                }),
                 _ => None,
             };
         }
@@ -68,6 +129,65 @@ impl ApprovalReason {
                 });
                 format!("{}{}", APPROVAL_REQUIRED_PREFIX, json)
             }
+            ApprovalReason::BrowserAction { action, url } => {
+                let json = serde_json::json!({
+                    "reason": "browser_action",
+                    "action": action,
+                    "url": url,
+                });
+                format!("{}{}", APPROVAL_REQUIRED_PREFIX, json)
+            }
+            ApprovalReason::McpTool { .. }
+            | ApprovalReason::WebhookTool { .. }
+            | ApprovalReason::WebDomain { .. } => {
+                format!("{}{}", APPROVAL_REQUIRED_PREFIX, self.to_json())
+            }
+        }
+    }
+
+    /// Structured form recorded in the audit trail.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ApprovalReason::WorkspaceEscape(path) => serde_json::json!({
+                "reason": "workspace_escape",
+                "path": path,
+            }),
+            ApprovalReason::ReferenceImport { title, summary } => serde_json::json!({
+                "reason": "reference_import",
+                "title": title,
+                "summary": summary,
+            }),
+            ApprovalReason::BrowserAction { action, url } => serde_json::json!({
+                "reason": "browser_action",
+                "action": action,
+                "url": url,
+            }),
+            ApprovalReason::McpTool {
+                server,
+                tool,
+                arguments,
+            } => serde_json::json!({
+                "reason": "mcp_tool",
+                "server": server,
+                "tool": tool,
+                "arguments": arguments,
+            }),
+            ApprovalReason::WebhookTool {
+                tool,
+                url,
+                arguments,
+            } => serde_json::json!({
+                "reason": "webhook_tool",
+                "tool": tool,
+                "url": url,
+                "arguments": arguments,
+            }),
+            ApprovalReason::WebDomain { tool, host, url } => serde_json::json!({
+                "reason": "web_domain",
+                "tool": tool,
+                "host": host,
+                "url": url,
+            }),
         }
     }
 
@@ -80,19 +200,33 @@ impl ApprovalReason {
             ApprovalReason::ReferenceImport { .. } => {
                 "Error: Operator denied approval to import this reference topic."
             }
+            ApprovalReason::BrowserAction { .. } => {
+                "Error: Operator denied approval for this browser action."
+            }
+            ApprovalReason::McpTool { .. } => {
+                "Error: Operator denied approval for this MCP tool call."
+            }
+            ApprovalReason::WebhookTool { .. } => {
+                "Error: Operator denied approval for this webhook tool call."
+            }
+            ApprovalReason::WebDomain { .. } => {
+                "Error: Operator denied access to this blocked domain."
+            }
         }
     }
 }
 
-/// Handle for persisting reflection job state (TODO list) mid-run.
+/// Handle for persisting background job state (TODO list, transcript) mid-run.
 ///
-/// Owned by the reflection runner and injected into `ToolContext` before
-/// the job tool loop starts. The `reflection_todo` tool reads/writes the
-/// in-memory `todos` vec, then flushes to DB via `persist()`.
+/// Owned by the job runner and injected into `ToolContext` before the job
+/// tool loop starts. The `reflection_todo` tool reads/writes the in-memory
+/// `todos` vec, then flushes to DB via `persist_todos()`; the tool loop
+/// publishes transcript entries via `publish_transcript()`.
 pub struct JobHandle {
     pool: SqlitePool,
     job_log_id: String,
     pub todos: Vec<TodoItem>,
+    journal: Option<WriteJournal>,
 }
 
 impl JobHandle {
@@ -102,11 +236,64 @@ impl JobHandle {
             pool,
             job_log_id,
             todos: Vec::new(),
+            journal: None,
+        }
+    }
+
+    /// Have note and diary writes of the job recorded in `journal`.
+    pub fn with_journal(mut self, journal: WriteJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn job_log_id(&self) -> &str {
+        &self.job_log_id
+    }
+
+    pub fn journal(&self) -> Option<&WriteJournal> {
+        self.journal.as_ref()
+    }
+
+    /// Broadcast `transcript[from..]` as `LogEntry::JobEntry` and persist the
+    /// transcript so far, so live viewers and late DB readers both see it.
+    pub async fn publish_transcript(
+        &self,
+        session_id: &str,
+        transcript: &[TranscriptEntry],
+        from: usize,
+    ) {
+        for entry in transcript.iter().skip(from) {
+            crate::state::emit_global_log(crate::state::LogEntry::JobEntry {
+                job_id: self.job_log_id.clone(),
+                session_id: session_id.to_string(),
+                entry: entry.clone(),
+            });
+        }
+        if let Err(e) =
+            JobLogRepository::update_transcript(&self.pool, &self.job_log_id, transcript).await
+        {
+            tracing::warn!("Failed to persist transcript for {}: {e}", self.job_log_id);
         }
     }
 
-    /// Persist the current TODO list to the database.
+    /// Start from TODO items carried over from earlier runs.
+    pub async fn seed_todos(&mut self, todos: Vec<TodoItem>) {
+        if todos.is_empty() {
+            return;
+        }
+        self.todos = todos;
+        if let Err(e) = self.persist_todos().await {
+            tracing::warn!("{e} ({})", self.job_log_id);
+        }
+    }
+
+    /// Persist the current TODO list to the database and broadcast it as
+    /// `LogEntry::JobTodos` for live viewers.
     pub async fn persist_todos(&self) -> Result<(), String> {
+        crate::state::emit_global_log(crate::state::LogEntry::JobTodos {
+            job_id: self.job_log_id.clone(),
+            todos: self.todos.clone(),
+        });
         JobLogRepository::update_todos(&self.pool, &self.job_log_id, &self.todos)
             .await
             .map_err(|e| format!("Failed to persist todos: {e}"))
@@ -118,6 +305,7 @@ impl std::fmt::Debug for JobHandle {
         f.debug_struct("JobHandle")
             .field("job_log_id", &self.job_log_id)
             .field("todos", &self.todos.len())
+            .field("journal", &self.journal.is_some())
             .finish()
     }
 }
@@ -138,16 +326,56 @@ pub struct ToolContext {
     cwd: PathBuf,
     allow_outside_workspace: bool,
     operator_access_level: t_koma_db::OperatorAccessLevel,
+    permissions: t_koma_db::OperatorPermissions,
+    tool_policies: t_koma_db::ToolPolicies,
+    /// Operator path approval rules, with symlinks in their paths resolved.
+    path_approvals: t_koma_db::PathApprovals,
+    /// Access of the running tool, set by `ToolManager`.
+    path_access: t_koma_db::PathAccess,
+    tool_timeouts: ToolTimeoutSettings,
+    shell_settings: ShellToolSettings,
+    output_settings: ToolOutputSettings,
+    /// When the running tool call must stop, set by `ToolManager`.
+    deadline: Option<Instant>,
     allow_workspace_escape: bool,
     approved_actions: Vec<String>,
     dirty: bool,
     knowledge_engine: Option<Arc<t_koma_knowledge::KnowledgeEngine>>,
     tool_result_cache: Vec<CachedToolResult>,
     pub job_handle: Option<JobHandle>,
+    koma_pool: Option<SqlitePool>,
+    ghost_id: Option<String>,
+    session_id: Option<String>,
 }
 
 pub const APPROVAL_REQUIRED_PREFIX: &str = "APPROVAL_REQUIRED:";
 
+/// Marks output cut short because a tool ran out of time.
+pub const TOOL_TIMEOUT_MARKER: &str = "[TOOL TIMEOUT]";
+
+/// Line appended to the partial output of a timed-out tool call.
+pub fn timeout_notice(tool: &str, budget: Duration) -> String {
+    format!(
+        "{TOOL_TIMEOUT_MARKER} {tool} was stopped after {}s; any output above is partial.",
+        budget.as_secs()
+    )
+}
+
+/// Named approval granted for one call of an MCP server tool.
+pub fn mcp_approval_key(server: &str, tool: &str) -> String {
+    format!("mcp:{server}:{tool}")
+}
+
+/// Named approval granted for one call of a webhook tool.
+pub fn webhook_approval_key(tool: &str) -> String {
+    format!("webhook:{tool}")
+}
+
+/// Named approval granted for one visit to a domain the policy blocks.
+pub fn web_domain_approval_key(host: &str) -> String {
+    format!("web_domain:{host}")
+}
+
 impl ToolContext {
     pub fn new(
         ghost_name: String,
@@ -162,12 +390,23 @@ impl ToolContext {
             cwd,
             allow_outside_workspace,
             operator_access_level: t_koma_db::OperatorAccessLevel::Standard,
+            permissions: t_koma_db::OperatorPermissions::default(),
+            tool_policies: t_koma_db::ToolPolicies::default(),
+            path_approvals: t_koma_db::PathApprovals::default(),
+            path_access: t_koma_db::PathAccess::Write,
+            tool_timeouts: ToolTimeoutSettings::default(),
+            shell_settings: ShellToolSettings::default(),
+            output_settings: ToolOutputSettings::default(),
+            deadline: None,
             allow_workspace_escape: false,
             approved_actions: Vec::new(),
             dirty: false,
             knowledge_engine: None,
             tool_result_cache: Vec::new(),
             job_handle: None,
+            koma_pool: None,
+            ghost_id: None,
+            session_id: None,
         }
     }
 
@@ -184,6 +423,42 @@ impl ToolContext {
         &self.ghost_name
     }
 
+    /// Attach the koma DB and GHOST identity (needed by tools that start background jobs).
+    pub fn set_koma_scope(&mut self, pool: SqlitePool, ghost_id: &str) {
+        self.koma_pool = Some(pool);
+        self.ghost_id = Some(ghost_id.to_string());
+    }
+
+    /// Koma DB pool, if the session layer attached one.
+    pub fn koma_pool(&self) -> Option<&SqlitePool> {
+        self.koma_pool.as_ref()
+    }
+
+    /// Koma DB pool and GHOST id, if the session layer attached them.
+    pub fn koma_scope(&self) -> Option<(&SqlitePool, &str)> {
+        Some((self.koma_pool.as_ref()?, self.ghost_id.as_deref()?))
+    }
+
+    pub fn set_session_id(&mut self, session_id: &str) {
+        self.session_id = Some(session_id.to_string());
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Owner identity for background jobs, if the session layer provided one.
+    pub fn ingest_job_owner(&self) -> Option<(SqlitePool, crate::ingest_job::IngestJobOwner)> {
+        Some((
+            self.koma_pool.clone()?,
+            crate::ingest_job::IngestJobOwner {
+                ghost_id: self.ghost_id.clone()?,
+                ghost_name: self.ghost_name.clone(),
+                session_id: self.session_id.clone()?,
+            },
+        ))
+    }
+
     /// The model ID powering this session (e.g. "claude-sonnet-4-5-20250929").
     /// Set by the session layer — never by the model itself.
     pub fn model_id(&self) -> &str {
@@ -214,6 +489,86 @@ impl ToolContext {
         self.operator_access_level = level;
     }
 
+    /// Permissions of the operator driving this session, checked by `ToolManager`.
+    pub fn permissions(&self) -> &t_koma_db::OperatorPermissions {
+        &self.permissions
+    }
+
+    pub fn set_permissions(&mut self, permissions: t_koma_db::OperatorPermissions) {
+        self.permissions = permissions;
+    }
+
+    /// Operator and ghost tool policies, enforced by `ToolManager`.
+    pub fn tool_policies(&self) -> &t_koma_db::ToolPolicies {
+        &self.tool_policies
+    }
+
+    pub fn set_tool_policies(&mut self, policies: t_koma_db::ToolPolicies) {
+        self.tool_policies = policies;
+    }
+
+    /// Pre-approved paths outside the workspace, consulted by
+    /// `resolve_local_path` before asking for approval.
+    pub fn set_path_approvals(&mut self, approvals: t_koma_db::PathApprovals) {
+        self.path_approvals = t_koma_db::PathApprovals {
+            rules: approvals
+                .rules
+                .into_iter()
+                .map(|(path, access)| {
+                    let path = canonicalize_for_boundary_check(Path::new(&path));
+                    (path.to_string_lossy().into_owned(), access)
+                })
+                .collect(),
+        };
+    }
+
+    pub(crate) fn set_path_access(&mut self, access: t_koma_db::PathAccess) {
+        self.path_access = access;
+    }
+
+    /// Whether a path approval rule covers `path` for the running tool.
+    pub fn path_pre_approved(&self, path: &Path) -> bool {
+        !self.path_approvals.is_empty()
+            && self
+                .path_approvals
+                .allows(&canonicalize_for_boundary_check(path), self.path_access)
+    }
+
+    pub fn set_tool_timeouts(&mut self, timeouts: ToolTimeoutSettings) {
+        self.tool_timeouts = timeouts;
+    }
+
+    pub fn shell_settings(&self) -> &ShellToolSettings {
+        &self.shell_settings
+    }
+
+    pub fn set_shell_settings(&mut self, settings: ShellToolSettings) {
+        self.shell_settings = settings;
+    }
+
+    pub fn output_settings(&self) -> &ToolOutputSettings {
+        &self.output_settings
+    }
+
+    pub fn set_output_settings(&mut self, settings: ToolOutputSettings) {
+        self.output_settings = settings;
+    }
+
+    /// Time budget for one call of `tool`; `None` means no limit.
+    pub fn tool_budget(&self, tool: &str) -> Option<Duration> {
+        self.tool_timeouts.budget_for(tool)
+    }
+
+    /// Deadline of the tool call in progress. Tools that can stream or be
+    /// interrupted stop here and return their partial output.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub(crate) fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
     pub fn allow_workspace_escape(&self) -> bool {
         self.allow_workspace_escape
     }
@@ -257,9 +612,51 @@ impl ToolContext {
             ApprovalReason::ReferenceImport { .. } => {
                 self.grant_approval("reference_import");
             }
+            ApprovalReason::BrowserAction { .. } => {
+                self.grant_approval("browser_interact");
+            }
+            ApprovalReason::McpTool { server, tool, .. } => {
+                self.grant_approval(&mcp_approval_key(server, tool));
+            }
+            ApprovalReason::WebhookTool { tool, .. } => {
+                self.grant_approval(&webhook_approval_key(tool));
+            }
+            ApprovalReason::WebDomain { host, .. } => {
+                self.grant_approval(&web_domain_approval_key(host));
+            }
         }
     }
 
+    /// Check `url` against the ghost's domain `policy` before `tool` reaches
+    /// it. A blocked host asks for operator approval (or is refused when the
+    /// policy has no approval escape hatch); once approved, it is let through
+    /// `policy` for this call.
+    pub fn authorize_web_url(
+        &mut self,
+        policy: &mut WebDomainPolicy,
+        tool: &str,
+        url: &str,
+    ) -> Result<(), String> {
+        let Some(host) = crate::web::policy::blocked_host(policy, url) else {
+            return Ok(());
+        };
+        if self.has_approval(&web_domain_approval_key(&host)) {
+            policy.approve_host(&host);
+            return Ok(());
+        }
+        if !policy.approve_blocked {
+            return Err(format!(
+                "{tool}: domain '{host}' is blocked by the web domain policy"
+            ));
+        }
+        Err(ApprovalReason::WebDomain {
+            tool: tool.to_string(),
+            host,
+            url: url.to_string(),
+        }
+        .to_error())
+    }
+
     pub fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -325,12 +722,23 @@ impl ToolContext {
             cwd: root.to_path_buf(),
             allow_outside_workspace: false,
             operator_access_level: t_koma_db::OperatorAccessLevel::Standard,
+            permissions: t_koma_db::OperatorPermissions::default(),
+            tool_policies: t_koma_db::ToolPolicies::default(),
+            path_approvals: t_koma_db::PathApprovals::default(),
+            path_access: t_koma_db::PathAccess::Write,
+            tool_timeouts: ToolTimeoutSettings::default(),
+            shell_settings: ShellToolSettings::default(),
+            output_settings: ToolOutputSettings::default(),
+            deadline: None,
             allow_workspace_escape: false,
             approved_actions: Vec::new(),
             dirty: false,
             knowledge_engine: None,
             tool_result_cache: Vec::new(),
             job_handle: None,
+            koma_pool: None,
+            ghost_id: None,
+            session_id: None,
         }
     }
 }
@@ -347,27 +755,14 @@ pub fn resolve_local_path_unchecked(context: &ToolContext, raw_path: &str) -> Pa
 }
 
 pub fn resolve_local_path(context: &mut ToolContext, raw_path: &str) -> Result<PathBuf, String> {
-    let input_path = Path::new(raw_path);
-    let absolute = if input_path.is_absolute() {
-        input_path.to_path_buf()
-    } else {
-        context.cwd().join(input_path)
-    };
-
-    let normalized = normalize_absolute_path(&absolute);
+    let normalized = resolve_local_path_unchecked(context, raw_path);
 
-    if !is_within_workspace(context, &normalized) {
+    if needs_workspace_approval(context, &normalized) {
         if context.allow_outside_workspace() {
             context.set_allow_outside_workspace(false);
             return Ok(normalized);
         }
 
-        let normalized_cwd = normalize_absolute_path(context.cwd());
-        if !is_within_workspace(context, &normalized_cwd) && normalized.starts_with(&normalized_cwd)
-        {
-            return Ok(normalized);
-        }
-
         return Err(format!(
             "{}{}",
             APPROVAL_REQUIRED_PREFIX,
@@ -378,6 +773,61 @@ pub fn resolve_local_path(context: &mut ToolContext, raw_path: &str) -> Result<P
     Ok(normalized)
 }
 
+/// Resolve several paths for one tool call under a single approval.
+///
+/// Every path is checked before any is returned: paths outside the workspace
+/// ask once for their distinct parent directories, and one approval covers
+/// them all.
+pub fn resolve_local_paths(
+    context: &mut ToolContext,
+    raw_paths: &[&str],
+) -> Result<Vec<PathBuf>, String> {
+    let resolved: Vec<PathBuf> = raw_paths
+        .iter()
+        .map(|raw_path| resolve_local_path_unchecked(context, raw_path))
+        .collect();
+
+    let mut outside_dirs: Vec<String> = Vec::new();
+    for path in resolved
+        .iter()
+        .filter(|path| needs_workspace_approval(context, path))
+    {
+        let dir = path.parent().unwrap_or(path).display().to_string();
+        if !outside_dirs.contains(&dir) {
+            outside_dirs.push(dir);
+        }
+    }
+
+    if !outside_dirs.is_empty() {
+        if !context.allow_outside_workspace() {
+            return Err(format!(
+                "{}{}",
+                APPROVAL_REQUIRED_PREFIX,
+                outside_dirs.join(", ")
+            ));
+        }
+        context.set_allow_outside_workspace(false);
+    }
+
+    Ok(resolved)
+}
+
+/// Whether reaching `normalized` needs operator approval: it is outside the
+/// workspace, not under an approved out-of-workspace cwd and not covered by
+/// a path approval rule.
+fn needs_workspace_approval(context: &ToolContext, normalized: &Path) -> bool {
+    if is_within_workspace(context, normalized) {
+        return false;
+    }
+
+    let normalized_cwd = normalize_absolute_path(context.cwd());
+    if !is_within_workspace(context, &normalized_cwd) && normalized.starts_with(&normalized_cwd) {
+        return false;
+    }
+
+    !context.path_pre_approved(normalized)
+}
+
 pub fn approval_required_path(error: &str) -> Option<&str> {
     error
         .strip_prefix(APPROVAL_REQUIRED_PREFIX)
@@ -463,6 +913,115 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn mcp_tool_approval_is_scoped_to_the_tool() {
+        let reason = ApprovalReason::McpTool {
+            server: "github".to_string(),
+            tool: "create_issue".to_string(),
+            arguments: r#"{"title":"Bug"}"#.to_string(),
+        };
+        let Some(ApprovalReason::McpTool {
+            server,
+            tool,
+            arguments,
+        }) = ApprovalReason::parse(&reason.to_error())
+        else {
+            panic!("expected MCP tool approval");
+        };
+        assert_eq!((server.as_str(), tool.as_str()), ("github", "create_issue"));
+        assert_eq!(arguments, r#"{"title":"Bug"}"#);
+
+        let mut context = ToolContext::new_for_tests(Path::new("/tmp"));
+        context.apply_approval(&reason);
+        assert!(!context.has_approval(&mcp_approval_key("github", "delete_repo")));
+        assert!(context.has_approval(&mcp_approval_key("github", "create_issue")));
+        assert!(!context.has_approval(&mcp_approval_key("github", "create_issue")));
+    }
+
+    #[test]
+    fn webhook_tool_approval_round_trips() {
+        let reason = ApprovalReason::WebhookTool {
+            tool: "lights_on".to_string(),
+            url: "http://homeassistant.local/hooks/lights".to_string(),
+            arguments: r#"{"room":"kitchen"}"#.to_string(),
+        };
+        let Some(ApprovalReason::WebhookTool { tool, url, .. }) =
+            ApprovalReason::parse(&reason.to_error())
+        else {
+            panic!("expected webhook tool approval");
+        };
+        assert_eq!(tool, "lights_on");
+        assert_eq!(url, "http://homeassistant.local/hooks/lights");
+
+        let mut context = ToolContext::new_for_tests(Path::new("/tmp"));
+        context.apply_approval(&reason);
+        assert!(context.has_approval(&webhook_approval_key("lights_on")));
+        assert!(!context.has_approval(&webhook_approval_key("lights_on")));
+    }
+
+    #[test]
+    fn blocked_web_domain_needs_one_shot_approval() {
+        let mut context = ToolContext::new_for_tests(Path::new("/tmp"));
+        let mut policy = WebDomainPolicy {
+            deny: vec!["example.com".to_string()],
+            approve_blocked: true,
+            ..Default::default()
+        };
+        let url = "https://www.example.com/docs";
+
+        assert!(
+            context
+                .authorize_web_url(&mut policy, "web_fetch", "https://docs.rs/")
+                .is_ok()
+        );
+        let error = context
+            .authorize_web_url(&mut policy, "web_fetch", url)
+            .unwrap_err();
+        let reason = ApprovalReason::parse(&error).expect("approval error");
+        let ApprovalReason::WebDomain { host, .. } = &reason else {
+            panic!("expected web domain approval");
+        };
+        assert_eq!(host, "www.example.com");
+
+        context.apply_approval(&reason);
+        assert!(
+            context
+                .authorize_web_url(&mut policy, "web_fetch", url)
+                .is_ok()
+        );
+        assert!(policy.allows_host("www.example.com"));
+
+        let mut strict = WebDomainPolicy {
+            deny: vec!["example.com".to_string()],
+            approve_blocked: false,
+            ..Default::default()
+        };
+        let error = context
+            .authorize_web_url(&mut strict, "web_fetch", url)
+            .unwrap_err();
+        assert!(ApprovalReason::parse(&error).is_none());
+    }
+
+    #[test]
+    fn browser_action_approval_round_trips() {
+        let reason = ApprovalReason::BrowserAction {
+            action: "click `#buy`".to_string(),
+            url: "https://shop.example/cart".to_string(),
+        };
+        let Some(ApprovalReason::BrowserAction { action, url }) =
+            ApprovalReason::parse(&reason.to_error())
+        else {
+            panic!("expected browser action approval");
+        };
+        assert_eq!(action, "click `#buy`");
+        assert_eq!(url, "https://shop.example/cart");
+
+        let mut context = ToolContext::new_for_tests(Path::new("/tmp"));
+        context.apply_approval(&reason);
+        assert!(context.has_approval("browser_interact"));
+        assert!(!context.has_approval("browser_interact"));
+    }
+
     #[test]
     fn resolve_local_path_blocks_outside_workspace() {
         let workspace = TempDir::new().unwrap();
@@ -516,4 +1075,63 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    #[test]
+    fn resolve_local_paths_shares_one_approval() {
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(workspace.path());
+        let inside = workspace.path().join("notes.md");
+        let first = outside.path().join("a.pdf");
+        let second = outside.path().join("b.pdf");
+        let paths = [
+            inside.to_str().unwrap(),
+            first.to_str().unwrap(),
+            second.to_str().unwrap(),
+        ];
+
+        let error = resolve_local_paths(&mut context, &paths).unwrap_err();
+        assert_eq!(
+            approval_required_path(&error),
+            Some(normalize_absolute_path(outside.path()).to_str().unwrap())
+        );
+
+        context.apply_approval(&ApprovalReason::parse(&error).unwrap());
+        let resolved = resolve_local_paths(&mut context, &paths).unwrap();
+        assert_eq!(resolved.len(), 3);
+        assert!(!context.allow_outside_workspace());
+        assert!(resolve_local_paths(&mut context, &paths).is_err());
+    }
+
+    #[test]
+    fn resolve_local_path_honors_path_approvals() {
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let project = outside.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let mut context = ToolContext::new_for_tests(workspace.path());
+        context.set_path_approvals(t_koma_db::PathApprovals {
+            rules: [(
+                project.to_string_lossy().into_owned(),
+                t_koma_db::PathAccess::Read,
+            )]
+            .into_iter()
+            .collect(),
+        });
+
+        let inside_rule = project.join("src/main.rs");
+        context.set_path_access(t_koma_db::PathAccess::Read);
+        assert!(resolve_local_path(&mut context, inside_rule.to_str().unwrap()).is_ok());
+        let sibling = outside.path().join("other.txt");
+        assert!(resolve_local_path(&mut context, sibling.to_str().unwrap()).is_err());
+        let parent_escape = project.join("../other.txt");
+        assert!(resolve_local_path(&mut context, parent_escape.to_str().unwrap()).is_err());
+
+        context.set_path_access(t_koma_db::PathAccess::Write);
+        let error = resolve_local_path(&mut context, inside_rule.to_str().unwrap()).unwrap_err();
+        assert!(matches!(
+            ApprovalReason::parse(&error),
+            Some(ApprovalReason::WorkspaceEscape(_))
+        ));
+    }
 }
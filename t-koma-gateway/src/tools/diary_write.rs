@@ -4,6 +4,7 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 
 use super::{Tool, ToolContext};
+use crate::reflection_quality::DiaryOutput;
 
 #[derive(Debug, Deserialize)]
 struct DiaryWriteInput {
@@ -64,12 +65,11 @@ impl Tool for DiaryWriteTool {
 
         let file_path = diary_dir.join(format!("{}.md", input.date));
         let action = input.action.as_deref().unwrap_or("write");
+        let previous = tokio::fs::read_to_string(&file_path).await.ok();
 
-        match action {
+        let (new_content, message) = match action {
             "append" => {
-                let existing = tokio::fs::read_to_string(&file_path)
-                    .await
-                    .unwrap_or_default();
+                let existing = previous.as_deref().unwrap_or_default();
 
                 let new_content = if existing.is_empty() {
                     input.content.clone()
@@ -81,28 +81,44 @@ impl Tool for DiaryWriteTool {
                     .await
                     .map_err(|e| format!("Failed to write diary entry: {e}"))?;
 
-                Ok(format!(
+                let message = format!(
                     "Appended to diary entry {} ({} bytes total)",
                     input.date,
                     new_content.len()
-                ))
+                );
+                (new_content, message)
             }
             "write" => {
                 tokio::fs::write(&file_path, &input.content)
                     .await
                     .map_err(|e| format!("Failed to write diary entry: {e}"))?;
 
-                Ok(format!(
+                let message = format!(
                     "Wrote diary entry {} ({} bytes)",
                     input.date,
                     input.content.len()
-                ))
+                );
+                (input.content, message)
+            }
+            other => {
+                return Err(format!(
+                    "Unknown action '{}'. Use 'write' or 'append'.",
+                    other
+                ));
             }
-            other => Err(format!(
-                "Unknown action '{}'. Use 'write' or 'append'.",
-                other
-            )),
+        };
+
+        if let Some(journal) = context.job_handle.as_ref().and_then(|h| h.journal()) {
+            journal.record_diary(
+                file_path,
+                previous,
+                DiaryOutput {
+                    date: input.date,
+                    content: new_content,
+                },
+            );
         }
+        Ok(message)
     }
 }
 
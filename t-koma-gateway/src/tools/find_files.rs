@@ -1,6 +1,7 @@
 use ignore::WalkBuilder;
 use serde_json::{Value, json};
 use std::path::Path;
+use t_koma_db::PathAccess;
 
 use super::context::resolve_local_path;
 use super::{Tool, ToolContext};
@@ -34,6 +35,10 @@ impl Tool for FindFilesTool {
         })
     }
 
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Read
+    }
+
     async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
         let pattern = args["pattern"]
             .as_str()
@@ -0,0 +1,379 @@
+//! Ripgrep-style content search with structured results.
+//!
+//! Unlike `search`, which prints `path:line: text` rows, `grep` returns JSON:
+//! one object per matching line with its surrounding context, plus counters
+//! and a `truncated` flag so the model knows when to narrow the query.
+
+use std::path::{Path, PathBuf};
+
+use grep::regex::RegexMatcherBuilder;
+use grep::searcher::{
+    BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind, SinkMatch,
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use t_koma_db::PathAccess;
+
+use super::context::resolve_local_path;
+use super::{Tool, ToolContext};
+
+const DEFAULT_MAX_RESULTS: usize = 100;
+const MAX_RESULTS_LIMIT: usize = 1000;
+const MAX_CONTEXT_LINES: usize = 10;
+
+/// Longest line text returned, so minified files do not flood the output.
+const MAX_LINE_CHARS: usize = 400;
+
+pub struct GrepTool;
+
+#[derive(Debug, Serialize)]
+struct GrepMatch {
+    path: String,
+    line: u64,
+    text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    after: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GrepResults {
+    matches: Vec<GrepMatch>,
+    files_searched: usize,
+    files_matched: usize,
+    truncated: bool,
+}
+
+struct GrepQuery {
+    root: PathBuf,
+    /// Paths in results are shown relative to this directory when possible.
+    display_base: PathBuf,
+    globs: Vec<String>,
+    max_results: usize,
+    max_per_file: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &str {
+        "grep"
+    }
+
+    fn description(&self) -> &str {
+        "Searches file contents with a regex, like ripgrep. Respects .gitignore and skips binary files. Returns JSON: a `matches` array of {path, line, text, before, after} plus `files_searched`, `files_matched` and `truncated`."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression (Rust regex syntax)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory or file to search. Defaults to current working directory."
+                },
+                "glob": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Glob filters on file paths (e.g. ['*.rs', '!*_test.rs']). A leading '!' excludes. Optional."
+                },
+                "case_insensitive": {
+                    "type": "boolean",
+                    "description": "Ignore case when matching. Defaults to false.",
+                    "default": false
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context before and after each match (max 10). Defaults to 0.",
+                    "default": 0
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum matches returned (max 1000). Defaults to 100.",
+                    "default": 100
+                },
+                "max_per_file": {
+                    "type": "integer",
+                    "description": "Maximum matches returned per file. Optional."
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Read
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let pattern = args["pattern"]
+            .as_str()
+            .ok_or_else(|| "Missing or invalid 'pattern' argument".to_string())?;
+        let path = args["path"].as_str().unwrap_or(".");
+        let root = resolve_local_path(context, path)?;
+        if !root.exists() {
+            return Err(format!("Path not found: {}", root.display()));
+        }
+
+        let globs = match &args["glob"] {
+            Value::Null => Vec::new(),
+            Value::String(glob) => vec![glob.clone()],
+            Value::Array(globs) => globs
+                .iter()
+                .map(|glob| {
+                    glob.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| "'glob' entries must be strings".to_string())
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err("'glob' must be a string or an array of strings".to_string()),
+        };
+        let context_lines = (args["context"].as_u64().unwrap_or(0) as usize).min(MAX_CONTEXT_LINES);
+        let max_results = args["max_results"]
+            .as_u64()
+            .map_or(DEFAULT_MAX_RESULTS, |n| n as usize)
+            .clamp(1, MAX_RESULTS_LIMIT);
+        let max_per_file = args["max_per_file"].as_u64().map(|n| (n as usize).max(1));
+
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(args["case_insensitive"].as_bool().unwrap_or(false))
+            .build(pattern)
+            .map_err(|e| format!("Invalid regex pattern '{}': {}", pattern, e))?;
+        let searcher = SearcherBuilder::new()
+            .line_number(true)
+            .before_context(context_lines)
+            .after_context(context_lines)
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .build();
+
+        let query = GrepQuery {
+            root,
+            display_base: context.cwd().to_path_buf(),
+            globs,
+            max_results,
+            max_per_file,
+        };
+        let results = tokio::task::spawn_blocking(move || query.run(&matcher, searcher))
+            .await
+            .map_err(|e| format!("Search task failed: {}", e))??;
+
+        serde_json::to_string_pretty(&results).map_err(|e| e.to_string())
+    }
+}
+
+impl GrepQuery {
+    fn run(
+        &self,
+        matcher: &grep::regex::RegexMatcher,
+        mut searcher: Searcher,
+    ) -> Result<GrepResults, String> {
+        let mut walker = ignore::WalkBuilder::new(&self.root);
+        walker
+            .hidden(false)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .require_git(false)
+            .sort_by_file_path(|a, b| a.cmp(b));
+        if !self.globs.is_empty() {
+            let mut overrides = ignore::overrides::OverrideBuilder::new(&self.root);
+            for glob in &self.globs {
+                overrides
+                    .add(glob)
+                    .map_err(|e| format!("Invalid glob pattern '{}': {}", glob, e))?;
+            }
+            walker.overrides(
+                overrides
+                    .build()
+                    .map_err(|e| format!("Failed to build glob filter: {}", e))?,
+            );
+        }
+
+        let mut results = GrepResults {
+            matches: Vec::new(),
+            files_searched: 0,
+            files_matched: 0,
+            truncated: false,
+        };
+        for entry in walker.build().flatten() {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            let remaining = self.max_results - results.matches.len();
+            let mut sink = MatchSink {
+                path: self.display_path(entry.path()),
+                limit: self.max_per_file.map_or(remaining, |n| n.min(remaining)),
+                matches: Vec::new(),
+                pending_before: Vec::new(),
+                limited: false,
+            };
+            if searcher
+                .search_path(matcher, entry.path(), &mut sink)
+                .is_err()
+            {
+                continue;
+            }
+            results.files_searched += 1;
+            if !sink.matches.is_empty() {
+                results.files_matched += 1;
+            }
+            results.truncated |= sink.limited;
+            results.matches.extend(sink.matches);
+            // Once full, later files are searched with a limit of zero only
+            // to learn whether anything was left out.
+            if results.truncated && results.matches.len() >= self.max_results {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.display_base)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+/// Collects one file's matches. A context line shared by two matches is
+/// reported once, as the earlier match's `after`.
+struct MatchSink {
+    path: String,
+    limit: usize,
+    matches: Vec<GrepMatch>,
+    pending_before: Vec<String>,
+    /// Set when the file had more matches than `limit`.
+    limited: bool,
+}
+
+impl Sink for MatchSink {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        if self.matches.len() >= self.limit {
+            self.limited = true;
+            return Ok(false);
+        }
+        self.matches.push(GrepMatch {
+            path: self.path.clone(),
+            line: mat.line_number().unwrap_or(0),
+            text: line_text(mat.bytes()),
+            before: std::mem::take(&mut self.pending_before),
+            after: Vec::new(),
+        });
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        _searcher: &Searcher,
+        context: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let text = line_text(context.bytes());
+        match context.kind() {
+            SinkContextKind::After => {
+                if let Some(last) = self.matches.last_mut() {
+                    last.after.push(text);
+                }
+            }
+            _ => self.pending_before.push(text),
+        }
+        Ok(true)
+    }
+}
+
+fn line_text(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end_matches(['\n', '\r']);
+    match text.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn grep(dir: &TempDir, args: Value) -> Value {
+        let mut context = ToolContext::new_for_tests(dir.path());
+        let output = GrepTool.execute(args, &mut context).await.unwrap();
+        serde_json::from_str(&output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_grep_returns_matches_with_context() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "use std::fmt;\n\nfn alpha() {}\nfn beta() {}\n\nstruct Gamma;\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.md"), "fn in prose\n").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), b"fn \0binary").unwrap();
+
+        let results = grep(
+            &dir,
+            json!({"pattern": "^fn \\w+", "glob": ["*.rs"], "context": 1}),
+        )
+        .await;
+        assert_eq!(results["files_searched"], 1);
+        assert_eq!(results["files_matched"], 1);
+        assert_eq!(results["truncated"], false);
+        assert_eq!(
+            results["matches"],
+            json!([
+                {"path": "lib.rs", "line": 3, "text": "fn alpha() {}", "before": [""]},
+                {"path": "lib.rs", "line": 4, "text": "fn beta() {}", "after": [""]}
+            ])
+        );
+
+        // Binary files are skipped; case folding is opt-in.
+        let results = grep(&dir, json!({"pattern": "FN", "case_insensitive": true})).await;
+        let paths: Vec<&str> = results["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["lib.rs", "lib.rs", "notes.md"]);
+        let results = grep(&dir, json!({"pattern": "FN"})).await;
+        assert_eq!(results["matches"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_grep_caps_results() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hit\nhit\nhit\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "hit\n").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "c.txt\n").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "hit\n").unwrap();
+
+        let results = grep(&dir, json!({"pattern": "hit", "max_per_file": 1})).await;
+        assert_eq!(results["matches"].as_array().unwrap().len(), 2);
+        assert_eq!(results["truncated"], true);
+
+        let results = grep(&dir, json!({"pattern": "hit", "max_results": 2})).await;
+        assert_eq!(results["matches"].as_array().unwrap().len(), 2);
+        assert_eq!(results["files_matched"], 1);
+        assert_eq!(results["truncated"], true);
+
+        let results = grep(&dir, json!({"pattern": "hit"})).await;
+        assert_eq!(results["matches"].as_array().unwrap().len(), 4);
+        assert_eq!(results["truncated"], false);
+
+        let mut context = ToolContext::new_for_tests(dir.path());
+        assert!(
+            GrepTool
+                .execute(json!({"pattern": "("}), &mut context)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::tools::context::timeout_notice;
+use crate::tools::{Tool, ToolContext};
+use crate::web::request::{HttpMethod, HttpRequest, HttpRequestService, RequestError};
+use crate::web::sanitize::ContentSanitizer;
+
+#[derive(Debug, Deserialize)]
+struct HttpRequestInput {
+    #[serde(default = "default_method")]
+    method: HttpMethod,
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+    json: Option<Value>,
+}
+
+fn default_method() -> HttpMethod {
+    HttpMethod::Get
+}
+
+impl HttpRequestInput {
+    /// Request to send; a `json` body is serialized with a JSON content type.
+    fn into_request(self) -> Result<HttpRequest, String> {
+        let mut headers = self.headers;
+        let body = match (self.body, self.json) {
+            (Some(_), Some(_)) => return Err("Set either 'body' or 'json', not both".into()),
+            (Some(body), None) => Some(body),
+            (None, Some(value)) => {
+                if !headers
+                    .keys()
+                    .any(|name| name.eq_ignore_ascii_case("content-type"))
+                {
+                    headers.insert("Content-Type".to_string(), "application/json".to_string());
+                }
+                Some(value.to_string())
+            }
+            (None, None) => None,
+        };
+        Ok(HttpRequest {
+            method: self.method,
+            url: self.url,
+            headers,
+            body,
+        })
+    }
+}
+
+pub struct HttpRequestTool;
+
+impl HttpRequestTool {
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {"type": "string", "enum": ["GET", "POST"], "description": "Default GET."},
+                "url": {"type": "string", "description": "http(s) URL on an allowed domain."},
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "Request headers, e.g. {\"Authorization\": \"Bearer ...\"}."
+                },
+                "body": {"type": "string", "description": "Raw request body (POST only)."},
+                "json": {"description": "JSON request body (POST only); sets Content-Type: application/json."}
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        })
+    }
+
+    fn format_error(err: RequestError) -> String {
+        match err {
+            RequestError::DomainNotAllowed(host) => format!(
+                "http_request: domain '{}' is not in [tools.http_request].allowed_domains",
+                host
+            ),
+            other => format!("http_request: {}", other),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Send a GET or POST request to an API on an operator-approved domain (home automation, internal services) and return the status and body."
+    }
+
+    fn input_schema(&self) -> Value {
+        Self::schema()
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let input: HttpRequestInput = serde_json::from_value(args).map_err(|e| e.to_string())?;
+        let request = input.into_request()?;
+
+        t_koma_core::load_dotenv();
+        let settings = t_koma_core::Settings::load().map_err(|e| e.to_string())?;
+
+        if !settings.tools.http_request.enabled {
+            return Err("http_request tool is disabled in config".to_string());
+        }
+
+        let service =
+            HttpRequestService::new(settings.tools.http_request).map_err(Self::format_error)?;
+        let budget = context.tool_budget(self.name()).unwrap_or_default();
+        let mut response = match service.send_until(request, context.deadline()).await {
+            Err(RequestError::TimedOut) => return Err(timeout_notice(self.name(), budget)),
+            result => result.map_err(Self::format_error)?,
+        };
+
+        let sanitizer = ContentSanitizer::from_settings(&settings.tools.untrusted_content);
+        let source = format!("http_request {}", response.url);
+        response.body = sanitizer.clean(&source, &response.body);
+        let serialized = serde_json::to_string(&response).map_err(|e| e.to_string())?;
+        let guarded = sanitizer.wrap(&source, &serialized);
+        let ref_id = context.cache_tool_result("http_request", &guarded);
+        if response.timed_out {
+            return Ok(format!(
+                "[Result #{}] {}\n{}",
+                ref_id,
+                guarded,
+                timeout_notice(self.name(), budget)
+            ));
+        }
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_body_sets_content_type() {
+        let input: HttpRequestInput = serde_json::from_value(json!({
+            "method": "POST",
+            "url": "http://homeassistant.local/api/services/light/turn_on",
+            "json": {"entity_id": "light.kitchen"}
+        }))
+        .unwrap();
+        let request = input.into_request().unwrap();
+        assert_eq!(request.method, HttpMethod::Post);
+        assert_eq!(
+            request.body.as_deref(),
+            Some(r#"{"entity_id":"light.kitchen"}"#)
+        );
+        assert_eq!(
+            request.headers.get("Content-Type").map(String::as_str),
+            Some("application/json")
+        );
+
+        let input: HttpRequestInput =
+            serde_json::from_value(json!({"url": "http://x", "body": "a", "json": {}})).unwrap();
+        assert!(input.into_request().is_err());
+        assert!(
+            serde_json::from_value::<HttpRequestInput>(json!({"url": "x", "method": "DELETE"}))
+                .is_err()
+        );
+    }
+}
@@ -2,6 +2,7 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 
 use crate::tools::{Tool, ToolContext};
+use crate::web::sanitize::ContentSanitizer;
 
 #[derive(Debug, Deserialize)]
 struct KnowledgeGetInput {
@@ -68,11 +69,20 @@ impl Tool for KnowledgeGetTool {
             max_chars: input.max_chars,
         };
 
-        let doc = engine
+        let mut doc = engine
             .knowledge_get(context.ghost_name(), query)
             .await
             .map_err(|e| e.to_string())?;
 
+        // Reference files are imported from external sources.
+        if doc.scope.is_reference() {
+            let settings = t_koma_core::Settings::load()
+                .map(|s| s.tools.untrusted_content)
+                .unwrap_or_default();
+            let source = format!("reference {}", doc.path.display());
+            doc.body = ContentSanitizer::from_settings(&settings).guard(&source, &doc.body);
+        }
+
         serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())
     }
 }
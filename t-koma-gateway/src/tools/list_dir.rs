@@ -1,4 +1,5 @@
 use serde_json::{Value, json};
+use t_koma_db::PathAccess;
 use tokio::fs;
 
 use super::context::resolve_local_path;
@@ -29,6 +30,10 @@ impl Tool for ListDirTool {
         })
     }
 
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Read
+    }
+
     async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
         let path = args["path"]
             .as_str()
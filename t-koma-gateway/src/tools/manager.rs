@@ -1,23 +1,72 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde_json::Value;
+use t_koma_db::{
+    EventKind, EventRepository, NewEvent, NewToolInvocation, ToolInvocationRepository,
+};
+use tokio::time::Instant;
+use tracing::warn;
 
 use super::{
-    Tool, ToolContext, change_directory::ChangeDirectoryTool, create_file::CreateFileTool,
+    ApprovalReason, Tool, ToolContext, apply_patch::ApplyPatchTool, browser::BrowserTool,
+    change_directory::ChangeDirectoryTool, create_file::CreateFileTool,
     diary_write::DiaryWriteTool, file_edit::FileEditTool, find_files::FindFilesTool,
-    identity_edit::IdentityEditTool, knowledge_get::KnowledgeGetTool,
-    knowledge_search::KnowledgeSearchTool, list_dir::ListDirTool, load_skill::LoadSkillTool,
-    note_write::NoteWriteTool, read_file::ReadFileTool, reference_import::ReferenceImportTool,
-    reference_manage::ReferenceManageTool, reference_write::ReferenceWriteTool,
-    reflection_todo::ReflectionTodoTool, search::SearchTool, shell::ShellTool,
-    web_fetch::WebFetchTool, web_search::WebSearchTool,
+    grep::GrepTool, http_request::HttpRequestTool, identity_edit::IdentityEditTool,
+    knowledge_get::KnowledgeGetTool, knowledge_search::KnowledgeSearchTool, list_dir::ListDirTool,
+    load_skill::LoadSkillTool, note_write::NoteWriteTool, read_file::ReadFileTool,
+    reference_import::ReferenceImportTool, reference_manage::ReferenceManageTool,
+    reference_write::ReferenceWriteTool, reflection_todo::ReflectionTodoTool,
+    reminder::ReminderTool, schedule_task::ScheduleTaskTool, search::SearchTool, shell::ShellTool,
+    sql_query::SqlQueryTool, tool_output_continue::ToolOutputContinueTool, web_fetch::WebFetchTool,
+    web_search::WebSearchTool,
 };
+use crate::tools::context::timeout_notice;
+use crate::tools::output_pages::{CONTINUE_TOOL, paginate};
+
+/// Extra time a tool gets after its deadline to return partial output
+/// before the call is cancelled.
+const TIMEOUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Tools that always write into shared knowledge (reference topics are shared notes).
+const SHARED_KNOWLEDGE_WRITE_TOOLS: &[&str] =
+    &["reference_import", "reference_write", "reference_manage"];
+
+/// Check the session operator's permissions before running a tool.
+fn check_permissions(name: &str, input: &Value, context: &ToolContext) -> Result<(), String> {
+    let permissions = context.permissions();
+    if !permissions.allows_tool(name) {
+        return Err(format!(
+            "Permission denied: the operator is not allowed to use {}.",
+            name
+        ));
+    }
+    if !context.tool_policies().allows(name) {
+        return Err(format!(
+            "Permission denied: {} is disabled by a tool policy.",
+            name
+        ));
+    }
+    if name == "run_shell_command" && !permissions.can_use_shell {
+        return Err("Permission denied: the operator is not allowed to run shell commands.".into());
+    }
+    let shared_write = SHARED_KNOWLEDGE_WRITE_TOOLS.contains(&name)
+        || (name == "note_write" && input.get("scope").and_then(Value::as_str) == Some("shared"));
+    if shared_write && !permissions.can_write_shared_knowledge {
+        return Err(
+            "Permission denied: the operator is not allowed to write shared knowledge.".into(),
+        );
+    }
+    Ok(())
+}
 
 /// Central manager for AI tools.
 ///
-/// Two constructors produce distinct tool sets for each role:
+/// Constructors produce distinct tool sets for each role:
 /// - `new_chat()`: interactive ghost sessions (conversation + query)
+/// - `new_heartbeat()`: heartbeat jobs (chat tools + the job TODO list)
 /// - `new_reflection()`: autonomous reflection jobs (knowledge curation)
+/// - `new_knowledge_mcp()`: knowledge tools served to external MCP clients
 ///
 /// Each instance provides a single `get_tools()` method — the right set is
 /// determined at construction time, not at query time.
@@ -28,29 +77,59 @@ pub struct ToolManager {
 impl ToolManager {
     /// Tools for interactive ghost chat sessions.
     ///
-    /// Includes filesystem, web, knowledge query, and skill tools.
+    /// Includes filesystem, web, knowledge query, and skill tools, plus
+    /// any tools discovered on MCP servers at startup and the webhook tools
+    /// defined in config.
     /// Does NOT include write tools (note_write, reference_write, etc.)
     /// — those belong to reflection.
     pub fn new_chat(skill_paths: Vec<PathBuf>) -> Self {
-        let tools: Vec<Box<dyn Tool>> = vec![
+        let mut tools: Vec<Box<dyn Tool>> = vec![
             Box::new(ShellTool),
             Box::new(ChangeDirectoryTool),
             Box::new(FileEditTool),
+            Box::new(ApplyPatchTool),
             Box::new(ReadFileTool),
             Box::new(CreateFileTool),
             Box::new(SearchTool),
+            Box::new(GrepTool),
             Box::new(FindFilesTool),
             Box::new(ListDirTool),
             Box::new(WebSearchTool),
             Box::new(WebFetchTool),
+            Box::new(BrowserTool),
+            Box::new(HttpRequestTool),
             Box::new(KnowledgeSearchTool),
             Box::new(KnowledgeGetTool),
+            Box::new(SqlQueryTool),
+            Box::new(ReminderTool),
+            Box::new(ScheduleTaskTool),
             Box::new(ReferenceImportTool),
             Box::new(LoadSkillTool::new(skill_paths)),
+            Box::new(ToolOutputContinueTool),
         ];
+        tools.extend(
+            crate::mcp::tools()
+                .iter()
+                .map(|tool| Box::new(tool.clone()) as Box<dyn Tool>),
+        );
+        tools.extend(
+            super::webhook::tools()
+                .iter()
+                .map(|tool| Box::new(tool.clone()) as Box<dyn Tool>),
+        );
         Self { tools }
     }
 
+    /// Tools for heartbeat jobs.
+    ///
+    /// Chat tools plus `reflection_todo`, so the ghost can work through and
+    /// check off scheduled TODO items surfaced by the heartbeat.
+    pub fn new_heartbeat(skill_paths: Vec<PathBuf>) -> Self {
+        let mut manager = Self::new_chat(skill_paths);
+        manager.tools.push(Box::new(ReflectionTodoTool));
+        manager
+    }
+
     /// Tools for autonomous reflection/curator jobs.
     ///
     /// Includes knowledge query + write tools, reference management,
@@ -67,11 +146,27 @@ impl ToolManager {
             Box::new(IdentityEditTool),
             Box::new(DiaryWriteTool),
             Box::new(ReflectionTodoTool),
+            Box::new(SqlQueryTool),
             Box::new(WebSearchTool),
             Box::new(WebFetchTool),
             Box::new(ReadFileTool),
             Box::new(FindFilesTool),
             Box::new(LoadSkillTool::new(skill_paths)),
+            Box::new(ToolOutputContinueTool),
+        ];
+        Self { tools }
+    }
+
+    /// Tools served to external agents by `t-koma-gateway mcp-serve`.
+    ///
+    /// Knowledge query and write tools only, so an external agent can share
+    /// a ghost's notes and references without touching its workspace.
+    pub fn new_knowledge_mcp() -> Self {
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(KnowledgeSearchTool),
+            Box::new(KnowledgeGetTool),
+            Box::new(NoteWriteTool),
+            Box::new(ReferenceWriteTool),
         ];
         Self { tools }
     }
@@ -91,18 +186,140 @@ impl ToolManager {
     }
 
     /// Execute a tool by name with the given input and context.
+    ///
+    /// Fails without running the tool when the operator's permissions
+    /// (per-tool grants, shell, shared knowledge writes) or a tool policy
+    /// forbid it. When a policy auto-approves the tool, an approval request
+    /// is granted and the call retried once without asking the operator.
+    ///
+    /// Each call is recorded for tool usage reports. When this manager
+    /// offers `tool_output_continue`, results longer than a page are cut to
+    /// their first page (see `output_pages`).
     pub async fn execute_with_context(
         &self,
         name: &str,
         input: Value,
         context: &mut ToolContext,
     ) -> Result<String, String> {
-        for tool in &self.tools {
-            if tool.name() == name {
-                return tool.execute(input, context).await;
-            }
+        let started = std::time::Instant::now();
+        let result = self.execute_unpaged(name, input, context).await;
+        record_invocation(context, name, started.elapsed(), &result).await;
+        if name == CONTINUE_TOOL || !self.tools.iter().any(|tool| tool.name() == CONTINUE_TOOL) {
+            return result;
+        }
+        match result {
+            Ok(output) => Ok(paginate(context, name, output)),
+            Err(error) => Err(paginate(context, name, error)),
         }
-        Err(format!("Unknown tool: {}", name))
+    }
+
+    async fn execute_unpaged(
+        &self,
+        name: &str,
+        input: Value,
+        context: &mut ToolContext,
+    ) -> Result<String, String> {
+        let Some(tool) = self.tools.iter().find(|tool| tool.name() == name) else {
+            return Err(format!("Unknown tool: {}", name));
+        };
+        check_permissions(name, &input, context)?;
+        context.set_path_access(tool.path_access());
+
+        let retry_input = context
+            .tool_policies()
+            .auto_approves(name)
+            .then(|| input.clone());
+        let result = run_with_budget(tool.as_ref(), input, context).await;
+        let (Err(error), Some(retry_input)) = (&result, retry_input) else {
+            return result;
+        };
+        let Some(reason) = ApprovalReason::parse(error) else {
+            return result;
+        };
+        record_auto_approval(context, name, &reason).await;
+        context.apply_approval(&reason);
+        run_with_budget(tool.as_ref(), retry_input, context).await
+    }
+}
+
+/// Run `tool` within its time budget.
+///
+/// The tool sees the deadline through `ToolContext::deadline` and can stop
+/// there with partial output; a tool still running after the grace period
+/// is cancelled (dropping its future kills child processes and requests).
+async fn run_with_budget(
+    tool: &dyn Tool,
+    input: Value,
+    context: &mut ToolContext,
+) -> Result<String, String> {
+    let Some(budget) = context.tool_budget(tool.name()) else {
+        context.set_deadline(None);
+        return tool.execute(input, context).await;
+    };
+    let deadline = Instant::now() + budget;
+    context.set_deadline(Some(deadline));
+    let result =
+        tokio::time::timeout_at(deadline + TIMEOUT_GRACE, tool.execute(input, context)).await;
+    context.set_deadline(None);
+    result.unwrap_or_else(|_| {
+        warn!(
+            event_kind = "tool_timeout",
+            "{} cancelled after its {}s budget",
+            tool.name(),
+            budget.as_secs()
+        );
+        Err(timeout_notice(tool.name(), budget))
+    })
+}
+
+/// Record a finished call for tool usage reports. Calls stopped to ask the
+/// operator for approval are not recorded; their retry is.
+async fn record_invocation(
+    context: &ToolContext,
+    name: &str,
+    duration: Duration,
+    result: &Result<String, String>,
+) {
+    let Some((pool, ghost_id)) = context.koma_scope() else {
+        return;
+    };
+    let (output, error) = match result {
+        Ok(output) => (output, None),
+        Err(error) if ApprovalReason::parse(error).is_some() => return,
+        Err(error) => (error, Some(error.clone())),
+    };
+    let invocation = NewToolInvocation {
+        ghost_id: ghost_id.to_string(),
+        session_id: context.session_id().map(str::to_string),
+        tool_name: name.to_string(),
+        duration_ms: duration.as_millis() as i64,
+        success: error.is_none(),
+        output_bytes: output.len() as i64,
+        error,
+    };
+    if let Err(e) = ToolInvocationRepository::record(pool, &invocation).await {
+        warn!("Failed to record {} call: {}", name, e);
+    }
+}
+
+/// Audit an approval granted by an auto-approve policy.
+async fn record_auto_approval(context: &ToolContext, name: &str, reason: &ApprovalReason) {
+    let Some((pool, ghost_id)) = context.koma_scope() else {
+        return;
+    };
+    let mut data = reason.to_json();
+    data["tools"] = vec![name].into();
+    data["auto_approved"] = true.into();
+    if let Some(session_id) = context.session_id() {
+        data["session_id"] = session_id.into();
+    }
+    if let Err(e) = EventRepository::record(
+        pool,
+        &NewEvent::new(EventKind::ToolApproved, ghost_id).with_data(data),
+    )
+    .await
+    {
+        warn!("Failed to record auto-approval for {}: {}", name, e);
     }
 }
 
@@ -183,6 +400,219 @@ mod tests {
         assert!(result.unwrap().contains("hello from tool manager"));
     }
 
+    #[tokio::test]
+    async fn test_large_results_are_paged() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lines: String = (1..=200).map(|n| format!("line {n}\n")).collect();
+        std::fs::write(temp_dir.path().join("big.txt"), lines).unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        context.set_output_settings(t_koma_core::ToolOutputSettings {
+            page_chars: 1000,
+            ..Default::default()
+        });
+        let input = json!({ "file_path": "big.txt" });
+
+        let first = ToolManager::new_chat(vec![])
+            .execute_with_context("read_file", input, &mut context)
+            .await
+            .unwrap();
+        assert!(first.contains("[OUTPUT PAGE 1/"));
+        assert!(!first.contains("line 200"));
+        let token = first
+            .rsplit("token \"")
+            .next()
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        let manager = ToolManager::new_chat(vec![]);
+        let mut pages = vec![first.clone()];
+        while !pages.last().unwrap().contains("end of output") {
+            let page = manager
+                .execute_with_context(CONTINUE_TOOL, json!({ "token": token }), &mut context)
+                .await
+                .unwrap();
+            pages.push(page);
+        }
+        assert!(pages.last().unwrap().contains("line 200"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_manager_enforces_permissions() {
+        let manager = ToolManager::new_chat(vec![]);
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        let mut permissions = t_koma_db::OperatorPermissions {
+            can_use_shell: false,
+            ..Default::default()
+        };
+        context.set_permissions(permissions.clone());
+
+        let result = manager
+            .execute_with_context(
+                "run_shell_command",
+                json!({ "command": "echo hi" }),
+                &mut context,
+            )
+            .await;
+        assert!(result.unwrap_err().contains("shell"));
+
+        permissions.can_use_shell = true;
+        permissions
+            .tool_grants
+            .insert("list_dir".to_string(), false);
+        context.set_permissions(permissions);
+        let result = manager
+            .execute_with_context("list_dir", json!({}), &mut context)
+            .await;
+        assert!(result.unwrap_err().contains("list_dir"));
+
+        context.set_permissions(t_koma_db::OperatorPermissions {
+            can_write_shared_knowledge: false,
+            ..Default::default()
+        });
+        let shared = json!({ "action": "create", "scope": "shared" });
+        let private = json!({ "action": "create", "scope": "private" });
+        assert!(check_permissions("note_write", &shared, &context).is_err());
+        assert!(check_permissions("note_write", &private, &context).is_ok());
+        assert!(check_permissions("reference_write", &private, &context).is_err());
+    }
+
+    struct StuckTool;
+
+    #[async_trait::async_trait]
+    impl Tool for StuckTool {
+        fn name(&self) -> &str {
+            "stuck"
+        }
+
+        fn description(&self) -> &str {
+            "Never finishes"
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(
+            &self,
+            _args: Value,
+            _context: &mut ToolContext,
+        ) -> Result<String, String> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stuck_tool_is_cancelled_after_budget() {
+        let manager = ToolManager {
+            tools: vec![Box::new(StuckTool)],
+        };
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        context.set_tool_timeouts(t_koma_core::ToolTimeoutSettings {
+            default_seconds: 1,
+            ..Default::default()
+        });
+
+        let error = manager
+            .execute_with_context("stuck", json!({}), &mut context)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "[TOOL TIMEOUT] stuck was stopped after 1s; any output above is partial."
+        );
+        assert!(context.deadline().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tool_policies_deny_and_auto_approve() {
+        use t_koma_db::{ToolPolicies, ToolPolicy};
+
+        let manager = ToolManager::new_chat(vec![]);
+        let workspace = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let file = outside.path().join("notes.txt");
+        std::fs::write(&file, "outside").unwrap();
+        let input = json!({ "file_path": file.to_string_lossy() });
+
+        let mut context = ToolContext::new_for_tests(workspace.path());
+        let error = manager
+            .execute_with_context("read_file", input.clone(), &mut context)
+            .await
+            .unwrap_err();
+        assert!(ApprovalReason::parse(&error).is_some());
+
+        let mut policies = ToolPolicies::default();
+        policies
+            .operator
+            .insert("read_*".to_string(), ToolPolicy::AutoApprove);
+        context.set_tool_policies(policies.clone());
+        let output = manager
+            .execute_with_context("read_file", input.clone(), &mut context)
+            .await
+            .unwrap();
+        assert!(output.contains("outside"));
+
+        policies
+            .ghost
+            .insert("read_file".to_string(), ToolPolicy::Deny);
+        context.set_tool_policies(policies);
+        let error = manager
+            .execute_with_context("read_file", input, &mut context)
+            .await
+            .unwrap_err();
+        assert!(error.contains("tool policy"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_recorded() {
+        use t_koma_db::{
+            GhostRepository, OperatorAccessLevel, OperatorRepository, Platform,
+            ToolInvocationRepository, ToolUsageFilters,
+        };
+
+        let db = t_koma_db::test_helpers::create_test_pool().await.unwrap();
+        let operator = OperatorRepository::create_new(
+            db.pool(),
+            "UsageOp",
+            Platform::Api,
+            OperatorAccessLevel::Standard,
+        )
+        .await
+        .unwrap();
+        let ghost = GhostRepository::create(db.pool(), &operator.id, "Usage")
+            .await
+            .unwrap();
+
+        let manager = ToolManager::new_chat(vec![]);
+        let workspace = tempfile::TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("notes.txt"), "inside").unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(workspace.path());
+        context.set_koma_scope(db.pool().clone(), &ghost.id);
+
+        for input in [
+            json!({ "file_path": "notes.txt" }),
+            json!({ "file_path": "missing.txt" }),
+            // Needs approval: not recorded
+            json!({ "file_path": outside.path().join("x").to_string_lossy() }),
+        ] {
+            let _ = manager
+                .execute_with_context("read_file", input, &mut context)
+                .await;
+        }
+
+        let stats = ToolInvocationRepository::stats(db.pool(), &ToolUsageFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].tool_name, "read_file");
+        assert_eq!((stats[0].calls, stats[0].failures), (2, 1));
+    }
+
     #[tokio::test]
     async fn test_tool_manager_execute_unknown() {
         let manager = ToolManager::new_chat(vec![]);
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use t_koma_core::config::McpServerSettings;
+
+use crate::mcp::{McpClient, McpToolInfo};
+use crate::tools::context::mcp_approval_key;
+use crate::tools::{ApprovalReason, Tool, ToolContext};
+use crate::web::sanitize::ContentSanitizer;
+
+/// Longest argument preview shown in an approval request.
+const MAX_ARGUMENTS_PREVIEW: usize = 500;
+
+/// A tool discovered on an external MCP server.
+#[derive(Clone)]
+pub struct McpTool {
+    /// Name exposed to the model (`mcp__<server>__<tool>`)
+    name: String,
+    server: String,
+    /// Tool name on the server
+    tool: String,
+    description: String,
+    input_schema: Value,
+    settings: McpServerSettings,
+    client: Arc<McpClient>,
+}
+
+impl McpTool {
+    pub fn new(
+        name: String,
+        server: String,
+        info: McpToolInfo,
+        settings: McpServerSettings,
+        client: Arc<McpClient>,
+    ) -> Self {
+        let description = match info.description {
+            Some(description) if !description.trim().is_empty() => {
+                format!("[MCP server '{server}'] {description}")
+            }
+            _ => format!("[MCP server '{server}'] {}", info.name),
+        };
+        let input_schema = if info.input_schema.is_object() {
+            info.input_schema
+        } else {
+            json!({"type": "object", "properties": {}})
+        };
+        Self {
+            name,
+            server,
+            tool: info.name,
+            description,
+            input_schema,
+            settings,
+            client,
+        }
+    }
+
+    fn arguments_preview(args: &Value) -> String {
+        let compact = args.to_string();
+        if compact.len() <= MAX_ARGUMENTS_PREVIEW {
+            return compact;
+        }
+        let mut end = MAX_ARGUMENTS_PREVIEW;
+        while !compact.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}…", &compact[..end])
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.input_schema.clone()
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        if self.settings.needs_approval(&self.tool)
+            && !context.has_approval(&mcp_approval_key(&self.server, &self.tool))
+        {
+            return Err(ApprovalReason::McpTool {
+                server: self.server.clone(),
+                tool: self.tool.clone(),
+                arguments: Self::arguments_preview(&args),
+            }
+            .to_error());
+        }
+
+        let result = self
+            .client
+            .call_tool(&self.tool, args)
+            .await
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+
+        t_koma_core::load_dotenv();
+        let settings = t_koma_core::Settings::load().map_err(|e| e.to_string())?;
+        let sanitizer = ContentSanitizer::from_settings(&settings.tools.untrusted_content);
+        let source = format!("MCP {}/{}", self.server, self.tool);
+        let guarded = sanitizer.wrap(&source, &sanitizer.clean(&source, &result.text));
+        if result.is_error {
+            return Err(guarded);
+        }
+        let ref_id = context.cache_tool_result(&self.name, &guarded);
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arguments_preview_truncates_on_char_boundary() {
+        let short = json!({"q": "lights"});
+        assert_eq!(McpTool::arguments_preview(&short), r#"{"q":"lights"}"#);
+
+        let long = json!({"text": "é".repeat(400)});
+        let preview = McpTool::arguments_preview(&long);
+        assert!(preview.ends_with('…'));
+        assert!(preview.len() <= MAX_ARGUMENTS_PREVIEW + '…'.len_utf8());
+    }
+}
@@ -1,30 +1,42 @@
+pub mod apply_patch;
+pub mod browser;
 pub mod change_directory;
 pub mod context;
 pub mod create_file;
 pub mod diary_write;
 pub mod file_edit;
 pub mod find_files;
+pub mod grep;
+pub mod http_request;
 pub mod identity_edit;
 pub mod knowledge_get;
 pub mod knowledge_search;
 pub mod list_dir;
 pub mod load_skill;
 pub mod manager;
+pub mod mcp;
 pub mod note_write;
+pub mod output_pages;
 pub mod read_file;
 pub mod reference_import;
 pub mod reference_manage;
 pub mod reference_write;
 pub mod reflection_todo;
+pub mod reminder;
+pub mod schedule_task;
 pub mod search;
 pub mod shell;
+pub mod sql_query;
+pub mod tool_output_continue;
 pub mod web_fetch;
 pub mod web_search;
+pub mod webhook;
 pub use context::{ApprovalReason, JobHandle, ToolContext};
 
 pub use manager::ToolManager;
 
 use serde_json::Value;
+use t_koma_db::PathAccess;
 
 /// Trait that all tools must implement
 #[async_trait::async_trait]
@@ -38,6 +50,12 @@ pub trait Tool: Send + Sync {
     /// JSON Schema for the tool's input
     fn input_schema(&self) -> Value;
 
+    /// What the tool may do with paths it resolves, matched against the
+    /// operator's path approval rules. Tools that only read override this.
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Write
+    }
+
     /// Execute the tool with the given arguments
     async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String>;
 }
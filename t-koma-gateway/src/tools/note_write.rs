@@ -1,6 +1,9 @@
 use serde::Deserialize;
 use serde_json::{Value, json};
 
+use t_koma_knowledge::models::OwnershipScope;
+
+use crate::reflection_quality::NoteOutput;
 use crate::tools::{Tool, ToolContext};
 
 #[derive(Debug, Deserialize)]
@@ -109,6 +112,11 @@ impl Tool for NoteWriteTool {
         let engine = context
             .knowledge_engine()
             .ok_or("knowledge engine not available")?;
+        let journal = context
+            .job_handle
+            .as_ref()
+            .and_then(|handle| handle.journal())
+            .cloned();
 
         match input.action.as_str() {
             "create" => {
@@ -125,14 +133,41 @@ impl Tool for NoteWriteTool {
                     source: input.source,
                     trust_score: input.trust_score,
                 };
+                let output = journal.as_ref().map(|_| NoteOutput {
+                    note_id: String::new(),
+                    title: Some(request.title.clone()),
+                    body: Some(request.body.clone()),
+                    tags: request.tags.clone(),
+                });
                 let result = engine
                     .note_create(context.ghost_name(), context.model_id(), request)
                     .await
                     .map_err(|e| e.to_string())?;
+                if let (Some(journal), Some(output)) = (&journal, output) {
+                    journal.record_note_created(NoteOutput {
+                        note_id: result.note_id.clone(),
+                        ..output
+                    });
+                }
                 serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
             }
             "update" => {
                 let note_id = input.note_id.ok_or("'note_id' is required for update")?;
+                // Keep the file as it was so a rejected reflection can put it back.
+                let previous = match &journal {
+                    Some(journal) if !journal.touches_note(&note_id) => {
+                        let doc = engine
+                            .memory_get(context.ghost_name(), &note_id, OwnershipScope::All)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        Some(
+                            tokio::fs::read_to_string(&doc.path)
+                                .await
+                                .map_err(|e| e.to_string())?,
+                        )
+                    }
+                    _ => None,
+                };
                 let request = t_koma_knowledge::NoteUpdateRequest {
                     note_id,
                     title: input.title,
@@ -141,10 +176,19 @@ impl Tool for NoteWriteTool {
                     trust_score: input.trust_score,
                     parent: input.parent,
                 };
+                let output = NoteOutput {
+                    note_id: request.note_id.clone(),
+                    title: request.title.clone(),
+                    body: request.body.clone(),
+                    tags: request.tags.clone(),
+                };
                 let result = engine
                     .note_update(context.ghost_name(), request)
                     .await
                     .map_err(|e| e.to_string())?;
+                if let Some(journal) = &journal {
+                    journal.record_note_updated(previous, output);
+                }
                 serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
             }
             "validate" => {
@@ -0,0 +1,173 @@
+//! Paging of large tool results.
+//!
+//! `ToolManager` passes results through [`paginate`]: output longer than
+//! `[tools.output].page_chars` is split into pages and kept in memory, and
+//! the ghost gets the first page with a continuation token to hand to
+//! `tool_output_continue`. Tokens only resolve for the ghost that produced
+//! the output; the oldest outputs are dropped once `cached_outputs` is
+//! reached.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use super::ToolContext;
+
+/// Name of the tool that returns the following pages.
+pub const CONTINUE_TOOL: &str = "tool_output_continue";
+
+struct PagedOutput {
+    token: String,
+    ghost: String,
+    tool: String,
+    pages: Vec<String>,
+    total_chars: usize,
+    /// Page returned when no page number is given (0-based).
+    next_page: usize,
+}
+
+fn store() -> &'static Mutex<VecDeque<PagedOutput>> {
+    static STORE: OnceLock<Mutex<VecDeque<PagedOutput>>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+/// `output` as is when it fits in one page, else its first page followed by
+/// a continuation notice.
+pub fn paginate(context: &ToolContext, tool: &str, output: String) -> String {
+    let settings = context.output_settings();
+    if settings.page_chars == 0 || settings.cached_outputs == 0 {
+        return output;
+    }
+    let total_chars = output.chars().count();
+    if total_chars <= settings.page_chars {
+        return output;
+    }
+
+    let mut paged = PagedOutput {
+        token: format!("out-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]),
+        ghost: context.ghost_name().to_string(),
+        tool: tool.to_string(),
+        pages: split_pages(&output, settings.page_chars),
+        total_chars,
+        next_page: 1,
+    };
+    let first = render_page(&mut paged, 0);
+
+    let mut outputs = store().lock().unwrap_or_else(|e| e.into_inner());
+    while outputs.len() >= settings.cached_outputs {
+        outputs.pop_front();
+    }
+    outputs.push_back(paged);
+    first
+}
+
+/// Page `page` (1-based) of a cached output, or its next unread page.
+pub fn continue_output(ghost: &str, token: &str, page: Option<usize>) -> Result<String, String> {
+    let mut outputs = store().lock().unwrap_or_else(|e| e.into_inner());
+    let paged = outputs
+        .iter_mut()
+        .find(|paged| paged.token == token && paged.ghost == ghost)
+        .ok_or_else(|| {
+            format!(
+                "Unknown or expired output token '{}'. Re-run the original tool call.",
+                token
+            )
+        })?;
+    let index = match page {
+        Some(page) => page.saturating_sub(1),
+        None => paged.next_page,
+    };
+    if page == Some(0) || index >= paged.pages.len() {
+        return Err(format!(
+            "Output '{}' has pages 1 to {}.",
+            token,
+            paged.pages.len()
+        ));
+    }
+    Ok(render_page(paged, index))
+}
+
+fn render_page(paged: &mut PagedOutput, index: usize) -> String {
+    paged.next_page = index + 1;
+    let count = paged.pages.len();
+    let notice = if index + 1 < count {
+        format!(
+            "[OUTPUT PAGE {}/{} of {}, {} chars in all. Call {} with token \"{}\" for page {}.]",
+            index + 1,
+            count,
+            paged.tool,
+            paged.total_chars,
+            CONTINUE_TOOL,
+            paged.token,
+            index + 2
+        )
+    } else {
+        format!(
+            "[OUTPUT PAGE {}/{} of {}: end of output.]",
+            count, count, paged.tool
+        )
+    };
+    format!("{}\n\n{}", paged.pages[index], notice)
+}
+
+/// Split `text` into pages of at most `page_chars` characters, breaking
+/// after a newline when one falls in the second half of the page.
+fn split_pages(text: &str, page_chars: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = match rest.char_indices().nth(page_chars) {
+            Some((end, _)) => rest[..end]
+                .rfind('\n')
+                .filter(|&newline| newline >= end / 2)
+                .map_or(end, |newline| newline + 1),
+            None => rest.len(),
+        };
+        pages.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t_koma_core::ToolOutputSettings;
+
+    #[test]
+    fn test_split_pages_prefers_line_breaks() {
+        assert_eq!(split_pages("aaaa\nbbbb\ncc", 7), ["aaaa\n", "bbbb\ncc"]);
+        assert_eq!(split_pages("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(split_pages("\nééééé", 3), ["\néé", "ééé"]);
+    }
+
+    #[test]
+    fn test_paginate_and_continue() {
+        let mut context = ToolContext::new_for_tests(std::path::Path::new("/tmp"));
+        context.set_output_settings(ToolOutputSettings {
+            page_chars: 10,
+            ..Default::default()
+        });
+        assert_eq!(paginate(&context, "read_file", "short".into()), "short");
+
+        let first = paginate(&context, "read_file", "0123456789abcdefghijXYZ".into());
+        assert!(first.starts_with("0123456789\n\n[OUTPUT PAGE 1/3 of read_file, 23 chars"));
+        let token = first.split('"').nth(1).unwrap();
+
+        let second = continue_output("test-ghost", token, None).unwrap();
+        assert!(second.starts_with("abcdefghij\n\n[OUTPUT PAGE 2/3"));
+        let third = continue_output("test-ghost", token, None).unwrap();
+        assert_eq!(
+            third,
+            "XYZ\n\n[OUTPUT PAGE 3/3 of read_file: end of output.]"
+        );
+        assert!(continue_output("test-ghost", token, None).is_err());
+        assert!(
+            continue_output("test-ghost", token, Some(1))
+                .unwrap()
+                .starts_with("0123456789")
+        );
+
+        assert!(continue_output("other-ghost", token, Some(1)).is_err());
+        assert!(continue_output("test-ghost", "out-missing", None).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use serde_json::{Value, json};
+use t_koma_db::PathAccess;
 use tokio::fs;
 
 use super::context::resolve_local_path;
@@ -40,6 +41,10 @@ impl Tool for ReadFileTool {
         })
     }
 
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Read
+    }
+
     async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
         let file_path = args["file_path"]
             .as_str()
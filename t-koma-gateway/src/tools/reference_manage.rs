@@ -1,8 +1,18 @@
 use serde::Deserialize;
 use serde_json::{Value, json};
 
+use crate::ingest_job::queue_ingest_job;
+use crate::tools::context::resolve_local_paths;
 use crate::tools::{Tool, ToolContext};
 
+#[derive(Debug, Deserialize)]
+struct IngestSourceInput {
+    url: Option<String>,
+    file: Option<String>,
+    path: Option<String>,
+    title: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ReferenceManageInput {
     action: String,
@@ -18,6 +28,8 @@ struct ReferenceManageInput {
     target_collection: Option<String>,
     // web-cache source (alternative to note_id/topic+path)
     cache_file: Option<String>,
+    // ingest fields
+    sources: Option<Vec<IngestSourceInput>>,
 }
 
 pub struct ReferenceManageTool;
@@ -29,7 +41,7 @@ impl Tool for ReferenceManageTool {
     }
 
     fn description(&self) -> &str {
-        "Manage reference files. Actions: update (change file status), delete (remove file), move (relocate file between topics without copying content). To update topic metadata, use note_write instead."
+        "Manage reference files. Actions: update (change file status), delete (remove file), move (relocate file between topics without copying content), ingest (save many files/URLs into a topic as a background job), archive (hide a whole topic from search, keeping its files), restore (un-archive a topic). To update topic metadata, use note_write instead."
     }
 
     fn input_schema(&self) -> Value {
@@ -38,12 +50,12 @@ impl Tool for ReferenceManageTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["update", "delete", "move"],
-                    "description": "update: change file status. delete: remove a reference file. move: relocate a file to another topic (content stays server-side)."
+                    "enum": ["update", "delete", "move", "ingest", "archive", "restore"],
+                    "description": "update: change file status. delete: remove a reference file. move: relocate a file to another topic (content stays server-side). ingest: bulk-save sources into target_topic in the background (returns a job ID immediately). archive: hide a topic from search and recent topics (files are kept). restore: make an archived topic searchable again."
                 },
                 "topic": {
                     "type": "string",
-                    "description": "Source topic name. Required for path-based lookups. Optional for delete/move when note_id is provided."
+                    "description": "Source topic name. Required for path-based lookups and archive/restore. Optional for delete/move when note_id is provided."
                 },
                 "note_id": {
                     "type": "string",
@@ -60,11 +72,11 @@ impl Tool for ReferenceManageTool {
                 },
                 "reason": {
                     "type": "string",
-                    "description": "Why the file is being updated/deleted/moved."
+                    "description": "Why the file is being updated/deleted/moved, or the topic archived."
                 },
                 "target_topic": {
                     "type": "string",
-                    "description": "Destination topic (move action). Must exist as a shared note."
+                    "description": "Destination topic (move/ingest actions). Must exist as a shared note."
                 },
                 "target_filename": {
                     "type": "string",
@@ -77,6 +89,20 @@ impl Tool for ReferenceManageTool {
                 "cache_file": {
                     "type": "string",
                     "description": "Path to a web-cache file (e.g. '.web-cache/file.md'). Use instead of note_id for cached web results not yet in the DB."
+                },
+                "sources": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "url": {"type": "string", "description": "Web page to fetch."},
+                            "file": {"type": "string", "description": "Workspace file to read."},
+                            "path": {"type": "string", "description": "Target path within the topic. Derived from the source if omitted."},
+                            "title": {"type": "string", "description": "Optional title for the reference file."}
+                        },
+                        "additionalProperties": false
+                    },
+                    "description": "Items to ingest (ingest action). Each needs exactly one of url or file."
                 }
             },
             "required": ["action"],
@@ -101,8 +127,11 @@ impl Tool for ReferenceManageTool {
             "update" => execute_update(&engine, input).await,
             "delete" => execute_delete(&engine, &workspace_root, input).await,
             "move" => execute_move(&engine, &ghost_name, &model_id, &workspace_root, input).await,
+            "ingest" => execute_ingest(&engine, context, input).await,
+            "archive" => execute_archive(&engine, &ghost_name, input).await,
+            "restore" => execute_restore(&engine, input).await,
             other => Err(format!(
-                "Unknown action '{}'. Use update, delete, or move.",
+                "Unknown action '{}'. Use update, delete, move, ingest, archive, or restore.",
                 other
             )),
         }
@@ -245,6 +274,99 @@ async fn execute_move(
     .to_string())
 }
 
+async fn execute_ingest(
+    engine: &t_koma_knowledge::KnowledgeEngine,
+    context: &mut ToolContext,
+    input: ReferenceManageInput,
+) -> Result<String, String> {
+    let target_topic = input
+        .target_topic
+        .ok_or("'target_topic' is required for ingest")?;
+    let sources = input
+        .sources
+        .filter(|s| !s.is_empty())
+        .ok_or("'sources' must list at least one item for ingest")?;
+    let (pool, owner) = context
+        .ingest_job_owner()
+        .ok_or("background jobs are not available in this context")?;
+
+    if sources
+        .iter()
+        .any(|source| source.url.is_some() == source.file.is_some())
+    {
+        return Err("each source needs exactly one of 'url' or 'file'".into());
+    }
+    // Resolve every file up front so one approval covers the whole batch.
+    let files: Vec<&str> = sources
+        .iter()
+        .filter_map(|source| source.file.as_deref())
+        .collect();
+    let mut file_paths = resolve_local_paths(context, &files)?.into_iter();
+
+    let mut items = Vec::with_capacity(sources.len());
+    for source in sources {
+        let ingest_source = match source.url {
+            Some(url) => t_koma_knowledge::IngestSource::Url { url },
+            None => t_koma_knowledge::IngestSource::File {
+                path: file_paths
+                    .next()
+                    .expect("one resolved path per file source"),
+            },
+        };
+        items.push(t_koma_knowledge::IngestItem {
+            source: ingest_source,
+            path: source.path,
+            title: source.title,
+            role: None,
+        });
+    }
+    let total = items.len();
+    let request = t_koma_knowledge::IngestBatchRequest {
+        topic: target_topic.clone(),
+        items,
+    };
+    engine
+        .check_ingest_batch(&request)
+        .await
+        .map_err(|e| e.to_string())?;
+    let job_id = queue_ingest_job(&pool, owner, context.model_id(), request).await?;
+
+    Ok(json!({
+        "job_id": job_id,
+        "target_topic": target_topic,
+        "items": total,
+        "status": "queued",
+    })
+    .to_string())
+}
+
+async fn execute_archive(
+    engine: &t_koma_knowledge::KnowledgeEngine,
+    ghost_name: &str,
+    input: ReferenceManageInput,
+) -> Result<String, String> {
+    let topic = input.topic.ok_or("'topic' is required for archive")?;
+    let (topic_id, title) = engine
+        .topic_archive(ghost_name, &topic, input.reason.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(json!({"archived": topic_id, "title": title}).to_string())
+}
+
+async fn execute_restore(
+    engine: &t_koma_knowledge::KnowledgeEngine,
+    input: ReferenceManageInput,
+) -> Result<String, String> {
+    let topic = input.topic.ok_or("'topic' is required for restore")?;
+    let (topic_id, title) = engine
+        .topic_restore(&topic)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(json!({"restored": topic_id, "title": title}).to_string())
+}
+
 // ── Web-cache helpers ─────────────────────────────────────────────
 
 /// Validate that a `cache_file` path resolves to within `.web-cache/`.
@@ -1,12 +1,17 @@
-//! Structured TODO list for reflection jobs.
+//! Structured TODO list for background jobs (reflection, heartbeat).
 //!
-//! The reflection agent uses this tool to plan work, track progress, and
-//! provide real-time observability via the TUI. The TODO list is persisted
-//! to the `job_logs.todo_list` column after each mutation.
+//! The agent uses this tool to plan work, track progress, and provide
+//! real-time observability via the TUI. The TODO list is persisted to the
+//! `job_logs.todo_list` column after each mutation.
+//!
+//! Items may carry a due date, a recurrence rule, and blockers. Scheduled
+//! items carry over into later runs until done (see `t_koma_db::job_todos`).
 
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{Value, json};
-use t_koma_db::job_logs::TodoStatus;
+use t_koma_db::job_logs::{TodoItem, TodoStatus};
+use t_koma_db::job_todos::{carry_over, open_blockers};
 
 use super::{Tool, ToolContext};
 
@@ -19,6 +24,9 @@ struct ReflectionTodoInput {
     note: Option<String>,
     title: Option<String>,
     description: Option<String>,
+    due: Option<String>,
+    recurrence: Option<String>,
+    blocked_by: Option<Vec<String>>,
     updates: Option<Vec<BatchUpdateItem>>,
 }
 
@@ -26,6 +34,9 @@ struct ReflectionTodoInput {
 struct TodoPlanItem {
     title: String,
     description: Option<String>,
+    due: Option<String>,
+    recurrence: Option<String>,
+    blocked_by: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,7 +49,7 @@ struct BatchUpdateItem {
 pub struct ReflectionTodoTool;
 
 impl ReflectionTodoTool {
-    fn format_todo_list(todos: &[t_koma_db::job_logs::TodoItem]) -> String {
+    fn format_todo_list(todos: &[TodoItem]) -> String {
         if todos.is_empty() {
             return "No TODO items.".to_string();
         }
@@ -60,6 +71,16 @@ impl ReflectionTodoTool {
             if let Some(note) = &item.note {
                 out.push_str(&format!(" ({})", note));
             }
+            if let Some(due_at) = item.due_at.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+                out.push_str(&format!(" due {}", due_at.format("%Y-%m-%d %H:%M UTC")));
+            }
+            if let Some(recurrence) = item.recurrence {
+                out.push_str(&format!(" ({})", recurrence));
+            }
+            let blockers = open_blockers(todos, item);
+            if !blockers.is_empty() {
+                out.push_str(&format!(" [blocked by: {}]", blockers.join(", ")));
+            }
             out.push('\n');
         }
         out
@@ -77,6 +98,62 @@ impl ReflectionTodoTool {
             )),
         }
     }
+
+    fn build_item(
+        title: String,
+        description: Option<String>,
+        due: Option<&str>,
+        recurrence: Option<&str>,
+        blocked_by: Option<Vec<String>>,
+    ) -> Result<TodoItem, String> {
+        let mut item = TodoItem::pending(title, description);
+        item.due_at = due
+            .map(|due| {
+                DateTime::parse_from_rfc3339(due)
+                    .map(|dt| dt.timestamp())
+                    .map_err(|e| format!("Invalid due date '{}': {} (use RFC 3339)", due, e))
+            })
+            .transpose()?;
+        item.recurrence = recurrence
+            .map(|r| r.parse().map_err(|e: t_koma_db::DbError| e.to_string()))
+            .transpose()?;
+        if item.recurrence.is_some() && item.due_at.is_none() {
+            return Err(format!(
+                "'{}' has a recurrence but no due date; set 'due' for the first occurrence",
+                item.title
+            ));
+        }
+        item.blocked_by = blocked_by.unwrap_or_default();
+        Ok(item)
+    }
+
+    /// Apply a status change, refusing to start or finish blocked items.
+    fn apply_status(
+        todos: &mut [TodoItem],
+        index: usize,
+        status: TodoStatus,
+        note: Option<String>,
+    ) -> Result<(), String> {
+        if index == 0 || index > todos.len() {
+            return Err(format!("Index {} out of range (1-{})", index, todos.len()));
+        }
+        if matches!(status, TodoStatus::InProgress | TodoStatus::Done) {
+            let blockers = open_blockers(todos, &todos[index - 1]);
+            if !blockers.is_empty() {
+                return Err(format!(
+                    "Item {} is blocked by: {}",
+                    index,
+                    blockers.join(", ")
+                ));
+            }
+        }
+        let item = &mut todos[index - 1];
+        item.set_status(status, Utc::now().timestamp());
+        if let Some(note) = note {
+            item.note = Some(note);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -86,7 +163,7 @@ impl Tool for ReflectionTodoTool {
     }
 
     fn description(&self) -> &str {
-        "Manage the reflection TODO list. Plan work, track progress, add discovered items."
+        "Manage the job TODO list. Plan work, track progress, add discovered items. Items with a due date or recurrence carry over to later runs until done."
     }
 
     fn input_schema(&self) -> Value {
@@ -96,7 +173,7 @@ impl Tool for ReflectionTodoTool {
                 "action": {
                     "type": "string",
                     "enum": ["plan", "update", "add", "batch_update"],
-                    "description": "plan: create/replace the full TODO list. update: change status of one item. batch_update: change status of multiple items at once. add: append a new item."
+                    "description": "plan: replace the unscheduled TODO items (open scheduled items are kept). update: change status of one item. batch_update: change status of multiple items at once. add: append a new item. Marking a recurring item done reschedules it."
                 },
                 "items": {
                     "type": "array",
@@ -104,7 +181,10 @@ impl Tool for ReflectionTodoTool {
                         "type": "object",
                         "properties": {
                             "title": {"type": "string"},
-                            "description": {"type": "string"}
+                            "description": {"type": "string"},
+                            "due": {"type": "string", "description": "RFC 3339 due date."},
+                            "recurrence": {"type": "string", "enum": ["hourly", "daily", "weekly", "monthly"]},
+                            "blocked_by": {"type": "array", "items": {"type": "string"}, "description": "Titles of items that must be done first."}
                         },
                         "required": ["title"]
                     },
@@ -132,6 +212,20 @@ impl Tool for ReflectionTodoTool {
                     "type": "string",
                     "description": "Optional description for 'add' action."
                 },
+                "due": {
+                    "type": "string",
+                    "description": "Optional RFC 3339 due date for 'add' action."
+                },
+                "recurrence": {
+                    "type": "string",
+                    "enum": ["hourly", "daily", "weekly", "monthly"],
+                    "description": "Optional recurrence for 'add' action (requires 'due')."
+                },
+                "blocked_by": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Optional titles of blocking items for 'add' action."
+                },
                 "updates": {
                     "type": "array",
                     "items": {
@@ -162,15 +256,17 @@ impl Tool for ReflectionTodoTool {
             "plan" => {
                 let items = input.items.ok_or("'items' is required for plan action")?;
 
-                handle.todos = items
-                    .into_iter()
-                    .map(|item| t_koma_db::job_logs::TodoItem {
-                        title: item.title,
-                        description: item.description,
-                        status: TodoStatus::Pending,
-                        note: None,
-                    })
-                    .collect();
+                let mut todos = carry_over(&handle.todos);
+                for item in items {
+                    todos.push(Self::build_item(
+                        item.title,
+                        item.description,
+                        item.due.as_deref(),
+                        item.recurrence.as_deref(),
+                        item.blocked_by,
+                    )?);
+                }
+                handle.todos = todos;
 
                 handle.persist_todos().await?;
                 Ok(Self::format_todo_list(&handle.todos))
@@ -182,19 +278,7 @@ impl Tool for ReflectionTodoTool {
                     .ok_or("'status' is required for update action")?;
                 let status = Self::parse_status(&status_str)?;
 
-                if index == 0 || index > handle.todos.len() {
-                    return Err(format!(
-                        "Index {} out of range (1-{})",
-                        index,
-                        handle.todos.len()
-                    ));
-                }
-
-                let item = &mut handle.todos[index - 1];
-                item.status = status;
-                if let Some(note) = input.note {
-                    item.note = Some(note);
-                }
+                Self::apply_status(&mut handle.todos, index, status, input.note)?;
 
                 handle.persist_todos().await?;
                 Ok(Self::format_todo_list(&handle.todos))
@@ -202,12 +286,13 @@ impl Tool for ReflectionTodoTool {
             "add" => {
                 let title = input.title.ok_or("'title' is required for add action")?;
 
-                handle.todos.push(t_koma_db::job_logs::TodoItem {
+                handle.todos.push(Self::build_item(
                     title,
-                    description: input.description,
-                    status: TodoStatus::Pending,
-                    note: None,
-                });
+                    input.description,
+                    input.due.as_deref(),
+                    input.recurrence.as_deref(),
+                    input.blocked_by,
+                )?);
 
                 handle.persist_todos().await?;
                 Ok(Self::format_todo_list(&handle.todos))
@@ -217,20 +302,9 @@ impl Tool for ReflectionTodoTool {
                     .updates
                     .ok_or("'updates' is required for batch_update action")?;
 
-                for update in &updates {
-                    if update.index == 0 || update.index > handle.todos.len() {
-                        return Err(format!(
-                            "Index {} out of range (1-{})",
-                            update.index,
-                            handle.todos.len()
-                        ));
-                    }
+                for update in updates {
                     let status = Self::parse_status(&update.status)?;
-                    let item = &mut handle.todos[update.index - 1];
-                    item.status = status;
-                    if let Some(note) = &update.note {
-                        item.note = Some(note.clone());
-                    }
+                    Self::apply_status(&mut handle.todos, update.index, status, update.note)?;
                 }
 
                 handle.persist_todos().await?;
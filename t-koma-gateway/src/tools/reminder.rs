@@ -0,0 +1,195 @@
+//! One-shot reminders for the operator.
+//!
+//! Reminders are stored in the koma DB (`t_koma_db::reminders`) and delivered
+//! by the scheduler (see `crate::reminders`) into the current session and
+//! the interface the operator is chatting from.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use t_koma_db::{Reminder, ReminderRepository};
+
+use super::{Tool, ToolContext};
+
+/// Longest accepted reminder text, in characters.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ReminderAction {
+    Create,
+    List,
+    Cancel,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReminderInput {
+    action: ReminderAction,
+    message: Option<String>,
+    due: Option<String>,
+    in_minutes: Option<i64>,
+    id: Option<String>,
+}
+
+pub struct ReminderTool;
+
+impl ReminderTool {
+    /// Resolve `due` (RFC 3339) or `in_minutes` to a future unix timestamp.
+    fn resolve_due(due: Option<&str>, in_minutes: Option<i64>, now: i64) -> Result<i64, String> {
+        let due_at = match (due, in_minutes) {
+            (Some(_), Some(_)) => return Err("Set either 'due' or 'in_minutes', not both".into()),
+            (Some(due), None) => DateTime::parse_from_rfc3339(due)
+                .map(|dt| dt.timestamp())
+                .map_err(|e| format!("Invalid due date '{}': {} (use RFC 3339)", due, e))?,
+            (None, Some(minutes)) if minutes > 0 => now + minutes * 60,
+            (None, Some(_)) => return Err("'in_minutes' must be positive".into()),
+            (None, None) => return Err("create requires 'due' or 'in_minutes'".into()),
+        };
+        if due_at <= now {
+            return Err(format!(
+                "Due time {} is in the past (now is {})",
+                format_ts(due_at),
+                format_ts(now)
+            ));
+        }
+        Ok(due_at)
+    }
+
+    fn format_list(reminders: &[Reminder], now: i64) -> String {
+        let mut out = format!("Now: {}\n", format_ts(now));
+        if reminders.is_empty() {
+            out.push_str("No pending reminders.");
+            return out;
+        }
+        for reminder in reminders {
+            out.push_str(&format!(
+                "- {} due {}: {}\n",
+                reminder.id,
+                format_ts(reminder.due_at),
+                reminder.message
+            ));
+        }
+        out
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    DateTime::<Utc>::from_timestamp(ts, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC (%A)").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+#[async_trait::async_trait]
+impl Tool for ReminderTool {
+    fn name(&self) -> &str {
+        "reminder"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a one-shot reminder for the operator, delivered at the due time through the interface they chat from. Also lists and cancels pending reminders."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "cancel"],
+                    "description": "create: schedule a reminder. list: show pending reminders and the current time. cancel: remove a pending reminder."
+                },
+                "message": {"type": "string", "description": "Reminder text sent to the operator (create only)."},
+                "due": {"type": "string", "description": "RFC 3339 due time with offset, e.g. 2026-03-06T09:00:00+01:00 (create only)."},
+                "in_minutes": {"type": "integer", "minimum": 1, "description": "Due time relative to now, instead of 'due' (create only)."},
+                "id": {"type": "string", "description": "Reminder id (cancel only)."}
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let input: ReminderInput = serde_json::from_value(args).map_err(|e| e.to_string())?;
+        let (pool, ghost_id) = context
+            .koma_scope()
+            .ok_or("reminder is not available in this session")?;
+        let now = Utc::now().timestamp();
+
+        match input.action {
+            ReminderAction::Create => {
+                let session_id = context
+                    .session_id()
+                    .ok_or("reminder is not available in this session")?;
+                let message = input
+                    .message
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .ok_or("create requires 'message'")?;
+                if message.chars().count() > MAX_MESSAGE_CHARS {
+                    return Err(format!(
+                        "Reminder message is too long (max {} characters)",
+                        MAX_MESSAGE_CHARS
+                    ));
+                }
+                let due_at = Self::resolve_due(input.due.as_deref(), input.in_minutes, now)?;
+                let reminder =
+                    ReminderRepository::create(pool, ghost_id, session_id, message, due_at)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Reminder {} scheduled for {}.",
+                    reminder.id,
+                    format_ts(reminder.due_at)
+                ))
+            }
+            ReminderAction::List => {
+                let reminders = ReminderRepository::list_pending(pool, ghost_id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::format_list(&reminders, now))
+            }
+            ReminderAction::Cancel => {
+                let id = input.id.ok_or("cancel requires 'id'")?;
+                let removed = ReminderRepository::cancel(pool, ghost_id, &id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if removed {
+                    Ok(format!("Reminder {} cancelled.", id))
+                } else {
+                    Err(format!("No pending reminder with id '{}'", id))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_due() {
+        let now = DateTime::parse_from_rfc3339("2026-03-02T08:00:00Z")
+            .unwrap()
+            .timestamp();
+        assert_eq!(
+            ReminderTool::resolve_due(Some("2026-03-06T09:00:00+01:00"), None, now),
+            Ok(now + 4 * 86_400)
+        );
+        assert_eq!(
+            ReminderTool::resolve_due(None, Some(30), now),
+            Ok(now + 1_800)
+        );
+        assert!(ReminderTool::resolve_due(Some("2026-03-01T09:00:00Z"), None, now).is_err());
+        assert!(ReminderTool::resolve_due(Some("friday 9am"), None, now).is_err());
+        assert!(ReminderTool::resolve_due(None, Some(0), now).is_err());
+        assert!(ReminderTool::resolve_due(None, None, now).is_err());
+        assert!(ReminderTool::resolve_due(Some("2026-03-06T09:00:00Z"), Some(5), now).is_err());
+    }
+
+    #[test]
+    fn test_format_ts() {
+        assert_eq!(format_ts(0), "1970-01-01 00:00 UTC (Thursday)");
+    }
+}
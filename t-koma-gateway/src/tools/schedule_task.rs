@@ -0,0 +1,224 @@
+//! Recurring tasks the ghost runs for itself.
+//!
+//! Tasks are stored in the koma DB (`t_koma_db::scheduled_tasks`) and run by
+//! the scheduler (see `crate::scheduled_tasks`) as background jobs whose
+//! responses land in the session the task was created in.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use t_koma_core::CronSchedule;
+use t_koma_db::{ScheduledTask, ScheduledTaskRepository};
+
+use super::{Tool, ToolContext};
+
+/// Longest accepted task name, in characters.
+const MAX_NAME_CHARS: usize = 80;
+/// Longest accepted task instructions, in characters.
+const MAX_PROMPT_CHARS: usize = 4000;
+/// Most tasks a ghost may have at once.
+const MAX_TASKS_PER_GHOST: usize = 20;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ScheduleTaskAction {
+    Create,
+    List,
+    Delete,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleTaskInput {
+    action: ScheduleTaskAction,
+    name: Option<String>,
+    prompt: Option<String>,
+    schedule: Option<String>,
+    timezone: Option<String>,
+    id: Option<String>,
+}
+
+pub struct ScheduleTaskTool;
+
+impl ScheduleTaskTool {
+    /// Fold `timezone` into the expression so the stored schedule is self-contained.
+    fn resolve_schedule(
+        schedule: &str,
+        timezone: Option<&str>,
+    ) -> Result<(String, CronSchedule), String> {
+        let parsed = CronSchedule::parse(schedule, timezone).map_err(|e| e.to_string())?;
+        let stored = match timezone.map(str::trim).filter(|tz| !tz.is_empty()) {
+            Some(tz) => format!("TZ={tz} {}", parsed.expression()),
+            None => schedule.trim().to_string(),
+        };
+        Ok((stored, parsed))
+    }
+
+    fn format_list(tasks: &[ScheduledTask], now: i64) -> String {
+        let mut out = format!("Now: {}\n", format_ts(now));
+        if tasks.is_empty() {
+            out.push_str("No scheduled tasks.");
+            return out;
+        }
+        for task in tasks {
+            let next = match (task.enabled, task.next_due) {
+                (false, _) => "paused by the operator".to_string(),
+                (true, Some(due)) => format!("next {}", format_ts(due)),
+                (true, None) => "no further runs".to_string(),
+            };
+            out.push_str(&format!(
+                "- {} \"{}\" [{}] {}: {}\n",
+                task.id, task.name, task.schedule, next, task.prompt
+            ));
+        }
+        out
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    DateTime::<Utc>::from_timestamp(ts, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC (%A)").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+fn required<'a>(value: Option<&'a str>, field: &str, max_chars: usize) -> Result<&'a str, String> {
+    let value = value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("create requires '{}'", field))?;
+    if value.chars().count() > max_chars {
+        return Err(format!(
+            "Task {} is too long (max {} characters)",
+            field, max_chars
+        ));
+    }
+    Ok(value)
+}
+
+#[async_trait::async_trait]
+impl Tool for ScheduleTaskTool {
+    fn name(&self) -> &str {
+        "schedule_task"
+    }
+
+    fn description(&self) -> &str {
+        "Register a recurring task you run on a cron schedule (e.g. summarize an RSS topic every morning, check CI nightly). Each run posts its result into this session. Also lists and deletes your tasks."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "delete"],
+                    "description": "create: register a recurring task. list: show your tasks and the current time. delete: remove a task."
+                },
+                "name": {"type": "string", "description": "Short task name (create only)."},
+                "prompt": {"type": "string", "description": "Self-contained instructions you will follow on each run (create only)."},
+                "schedule": {"type": "string", "description": "Cron expression: 5 fields 'minute hour day month weekday' or @hourly/@daily/@weekly/@monthly, e.g. '0 7 * * *' (create only)."},
+                "timezone": {"type": "string", "description": "Zone the schedule is evaluated in: 'UTC' (default), 'local' or an offset like '+02:00' (create only)."},
+                "id": {"type": "string", "description": "Task id (delete only)."}
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let input: ScheduleTaskInput = serde_json::from_value(args).map_err(|e| e.to_string())?;
+        let (pool, ghost_id) = context
+            .koma_scope()
+            .ok_or("schedule_task is not available in this session")?;
+        let now = Utc::now().timestamp();
+
+        match input.action {
+            ScheduleTaskAction::Create => {
+                let session_id = context
+                    .session_id()
+                    .ok_or("schedule_task is not available in this session")?;
+                let name = required(input.name.as_deref(), "name", MAX_NAME_CHARS)?;
+                let prompt = required(input.prompt.as_deref(), "prompt", MAX_PROMPT_CHARS)?;
+                let schedule = input
+                    .schedule
+                    .as_deref()
+                    .ok_or("create requires 'schedule'")?;
+                let (stored, parsed) = Self::resolve_schedule(schedule, input.timezone.as_deref())?;
+                let next_due = parsed
+                    .next_after(now)
+                    .ok_or_else(|| format!("Schedule '{}' never runs again", parsed))?;
+
+                let existing = ScheduledTaskRepository::list_for_ghost(pool, ghost_id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if existing.len() >= MAX_TASKS_PER_GHOST {
+                    return Err(format!(
+                        "You already have {} scheduled tasks; delete one first",
+                        existing.len()
+                    ));
+                }
+
+                let task = ScheduledTaskRepository::create(
+                    pool, ghost_id, session_id, name, prompt, &stored, next_due,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Task {} scheduled ({}); first run {}.",
+                    task.id,
+                    parsed,
+                    format_ts(next_due)
+                ))
+            }
+            ScheduleTaskAction::List => {
+                let tasks = ScheduledTaskRepository::list_for_ghost(pool, ghost_id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::format_list(&tasks, now))
+            }
+            ScheduleTaskAction::Delete => {
+                let id = input.id.ok_or("delete requires 'id'")?;
+                let removed = ScheduledTaskRepository::delete(pool, ghost_id, &id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if removed {
+                    Ok(format!("Task {} deleted.", id))
+                } else {
+                    Err(format!("No scheduled task with id '{}'", id))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_schedule() {
+        let (stored, parsed) = ScheduleTaskTool::resolve_schedule(" 0 7 * * * ", None).unwrap();
+        assert_eq!(stored, "0 7 * * *");
+        assert_eq!(parsed.to_string(), "0 7 * * *");
+
+        let (stored, parsed) =
+            ScheduleTaskTool::resolve_schedule("0 7 * * 1-5", Some("+02:00")).unwrap();
+        assert_eq!(stored, "TZ=+02:00 0 7 * * 1-5");
+        assert_eq!(parsed.to_string(), "0 7 * * 1-5 (UTC+02:00)");
+        // The stored form parses back to the same schedule.
+        assert_eq!(
+            CronSchedule::parse(&stored, None).unwrap().to_string(),
+            parsed.to_string()
+        );
+
+        assert!(ScheduleTaskTool::resolve_schedule("every morning", None).is_err());
+        assert!(ScheduleTaskTool::resolve_schedule("@daily", Some("Europe/Paris")).is_err());
+    }
+
+    #[test]
+    fn test_required_field() {
+        assert_eq!(required(Some("  RSS  "), "name", 10), Ok("RSS"));
+        assert!(required(Some("  "), "name", 10).is_err());
+        assert!(required(None, "name", 10).is_err());
+        assert!(required(Some("a very long name"), "name", 5).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use grep::searcher::sinks::UTF8;
 use serde_json::{Value, json};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use t_koma_db::PathAccess;
 
 use super::context::resolve_local_path;
 use super::{Tool, ToolContext};
@@ -46,6 +47,10 @@ impl Tool for SearchTool {
         })
     }
 
+    fn path_access(&self) -> PathAccess {
+        PathAccess::Read
+    }
+
     async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
         let pattern = args["pattern"]
             .as_str()
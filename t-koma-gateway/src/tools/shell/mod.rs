@@ -1,10 +1,15 @@
 use serde_json::{Value, json};
-use std::process::Stdio;
 use tokio::fs;
-use tokio::process::Command;
 
 use super::{Tool, ToolContext};
-use crate::tools::context::{APPROVAL_REQUIRED_PREFIX, resolve_local_path};
+use crate::state::{LogEntry, emit_global_log};
+use crate::tools::context::{APPROVAL_REQUIRED_PREFIX, resolve_local_path, timeout_notice};
+
+mod process;
+#[cfg(unix)]
+mod pty;
+
+use process::{Outcome, ShellRun};
 
 pub struct ShellTool;
 
@@ -15,7 +20,7 @@ impl Tool for ShellTool {
     }
 
     fn description(&self) -> &str {
-        "Executes a shell command on the host system. Use with caution. Returns stdout and stderr."
+        "Executes a shell command on the host system in a terminal. Use with caution. Returns the combined stdout and stderr. Commands cannot answer interactive prompts; use non-interactive flags (e.g. --yes) instead."
     }
 
     fn input_schema(&self) -> Value {
@@ -70,35 +75,57 @@ impl Tool for ShellTool {
             ));
         }
 
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&cwd)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn command: {}", e))?
-            .wait_with_output()
-            .await
-            .map_err(|e| format!("Failed to wait for command: {}", e))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let settings = context.shell_settings().clone();
+        let run = ShellRun {
+            command,
+            cwd: &cwd,
+            pty: settings.pty,
+            max_output_bytes: settings.max_output_bytes,
+            deadline: context.deadline(),
+        };
+        let session_id = context.session_id().map(str::to_string);
+        let job_id = context
+            .job_handle
+            .as_ref()
+            .map(|handle| handle.job_log_id().to_string());
+        let result = run
+            .run(|output| {
+                if settings.stream_progress && !output.is_empty() {
+                    emit_global_log(LogEntry::ToolOutput {
+                        tool: self.name().to_string(),
+                        session_id: session_id.clone(),
+                        job_id: job_id.clone(),
+                        output: output.to_string(),
+                    });
+                }
+            })
+            .await?;
 
-        if output.status.success() {
-            if let Some(new_cwd) = pending_cwd {
-                context.set_cwd(new_cwd);
+        let output = result.output;
+        match result.outcome {
+            Outcome::Exited(status) if status.success() => {
+                if let Some(new_cwd) = pending_cwd {
+                    context.set_cwd(new_cwd);
+                }
+                Ok(output)
             }
-            if stderr.is_empty() {
-                Ok(stdout.to_string())
-            } else {
-                Ok(format!("{}\nSTDERR:\n{}", stdout, stderr))
+            Outcome::Exited(status) => Err(format!(
+                "Command failed with exit code {}.\nOUTPUT:\n{}",
+                status, output
+            )),
+            Outcome::TimedOut => {
+                let budget = context.tool_budget(self.name()).unwrap_or_default();
+                Ok(format!(
+                    "{}\n{}",
+                    output,
+                    timeout_notice(self.name(), budget)
+                ))
             }
-        } else {
-            Err(format!(
-                "Command failed with exit code {}.\nSTDOUT:\n{}\nSTDERR:\n{}",
-                output.status, stdout, stderr
-            ))
+            Outcome::Prompt(prompt) => Err(format!(
+                "Command stopped: it is waiting for input at `{}`, which this tool cannot answer. \
+                 Re-run it non-interactively (e.g. --yes, -y, or piping the answer in).\nOUTPUT:\n{}",
+                prompt, output
+            )),
         }
     }
 }
@@ -200,6 +227,67 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_shell_tool_timeout_returns_partial_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        context.set_tool_timeouts(t_koma_core::ToolTimeoutSettings {
+            per_tool: [("run_shell_command".to_string(), 1)].into(),
+            ..Default::default()
+        });
+        let manager = crate::tools::ToolManager::new_chat(vec![]);
+        let started = std::time::Instant::now();
+        let output = manager
+            .execute_with_context(
+                "run_shell_command",
+                json!({ "command": "echo started; sleep 5; echo finished" }),
+                &mut context,
+            )
+            .await
+            .unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(4));
+        assert!(output.starts_with("started\n"));
+        assert!(!output.contains("finished"));
+        assert!(output.contains("[TOOL TIMEOUT] run_shell_command was stopped after 1s"));
+        assert!(context.deadline().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shell_tool_runs_in_a_terminal_unless_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        let tool = ShellTool;
+        let args = json!({ "command": "test -t 1 && echo tty || echo pipe; echo oops >&2" });
+
+        let output = tool.execute(args.clone(), &mut context).await.unwrap();
+        assert_eq!(output, "tty\noops\n");
+
+        context.set_shell_settings(t_koma_core::ShellToolSettings {
+            pty: false,
+            max_output_bytes: 8,
+            ..Default::default()
+        });
+        let output = tool
+            .execute(
+                json!({ "command": "echo pipe; echo 0123456789" }),
+                &mut context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output, "pipe\n[... 8 bytes of output omitted ...]\n789\n");
+    }
+
+    #[tokio::test]
+    async fn test_shell_tool_stops_at_interactive_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut context = ToolContext::new_for_tests(temp_dir.path());
+        let tool = ShellTool;
+        let args = json!({ "command": "echo installing; printf 'Continue? [y/N] '; read answer; echo done" });
+        let error = tool.execute(args, &mut context).await.unwrap_err();
+        assert!(error.contains("waiting for input at `Continue? [y/N]`"));
+        assert!(error.ends_with("installing\nContinue? [y/N] "));
+    }
+
     #[tokio::test]
     async fn test_shell_tool_requires_approval_for_parent_paths() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,305 @@
+//! Shell command runs with streamed, size-limited output.
+//!
+//! Commands run under `sh -c`, in a pseudo-terminal when `[tools.shell].pty`
+//! is set (unix only) and with plain pipes otherwise. Output is read as it
+//! arrives, so it can be broadcast as progress, kept within
+//! `max_output_bytes`, and checked for interactive prompts: a command that
+//! stops to ask for a password or a confirmation is killed right away
+//! instead of waiting for its deadline.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use regex::Regex;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+/// Silence after which a trailing partial line is checked for a prompt.
+const PROMPT_IDLE: Duration = Duration::from_millis(1500);
+
+/// Longest trailing partial line kept for prompt detection.
+const MAX_PENDING_LINE: usize = 512;
+
+/// CSI and OSC sequences plus two-byte escapes (colors, cursor movement,
+/// terminal titles).
+static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])")
+        .expect("valid ANSI escape regex")
+});
+
+/// One command to run.
+pub(super) struct ShellRun<'a> {
+    pub command: &'a str,
+    pub cwd: &'a Path,
+    pub pty: bool,
+    pub max_output_bytes: usize,
+    pub deadline: Option<Instant>,
+}
+
+/// How a run ended.
+#[derive(Debug)]
+pub(super) enum Outcome {
+    Exited(ExitStatus),
+    /// The deadline passed and the command was killed.
+    TimedOut,
+    /// The command waited for input at this prompt and was killed.
+    Prompt(String),
+}
+
+#[derive(Debug)]
+pub(super) struct RunOutput {
+    /// Combined stdout and stderr, cleaned of terminal escapes.
+    pub output: String,
+    pub outcome: Outcome,
+}
+
+impl ShellRun<'_> {
+    /// Run the command, passing each cleaned output chunk to `on_output`.
+    pub(super) async fn run(&self, mut on_output: impl FnMut(&str)) -> Result<RunOutput, String> {
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(64);
+        let mut readers = JoinSet::new();
+        let mut child = self.spawn(&tx, &mut readers)?;
+        drop(tx);
+
+        let mut buffer = OutputBuffer::new(self.max_output_bytes);
+        let stopped = loop {
+            let deadline = async {
+                match self.deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                chunk = rx.recv() => match chunk {
+                    Some(chunk) => {
+                        on_output(&clean(&String::from_utf8_lossy(&chunk)));
+                        buffer.push(&chunk);
+                    }
+                    None => break None,
+                },
+                () = deadline => break Some(Outcome::TimedOut),
+                () = tokio::time::sleep(PROMPT_IDLE) => {
+                    if let Some(prompt) = detect_prompt(&buffer.pending_line) {
+                        break Some(Outcome::Prompt(prompt));
+                    }
+                }
+            }
+        };
+
+        let outcome = match stopped {
+            Some(outcome) => {
+                kill(&mut child).await;
+                outcome
+            }
+            // All output is in; wait for the exit status within the deadline.
+            None => {
+                let status = match self.deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline, child.wait()).await.ok(),
+                    None => Some(child.wait().await),
+                };
+                match status {
+                    Some(status) => Outcome::Exited(
+                        status.map_err(|e| format!("Failed to wait for command: {}", e))?,
+                    ),
+                    None => {
+                        kill(&mut child).await;
+                        Outcome::TimedOut
+                    }
+                }
+            }
+        };
+        readers.abort_all();
+
+        Ok(RunOutput {
+            output: buffer.render(),
+            outcome,
+        })
+    }
+
+    fn spawn(
+        &self,
+        tx: &mpsc::Sender<Vec<u8>>,
+        readers: &mut JoinSet<()>,
+    ) -> Result<Child, String> {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(self.command)
+            .current_dir(self.cwd)
+            .kill_on_drop(true);
+
+        #[cfg(unix)]
+        if self.pty {
+            let master = super::pty::attach(&mut command)
+                .map_err(|e| format!("Failed to open a pseudo-terminal: {}", e))?;
+            let child = command.spawn().map_err(spawn_error)?;
+            readers.spawn(super::pty::forward(master, tx.clone()));
+            return Ok(child);
+        }
+
+        // Own process group, so a timeout kills what the command started too.
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(stdout) = child.stdout.take() {
+            readers.spawn(forward_pipe(stdout, tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.spawn(forward_pipe(stderr, tx.clone()));
+        }
+        Ok(child)
+    }
+}
+
+fn spawn_error(e: std::io::Error) -> String {
+    format!("Failed to spawn command: {}", e)
+}
+
+async fn forward_pipe(mut pipe: impl AsyncRead + Unpin, tx: mpsc::Sender<Vec<u8>>) {
+    let mut buf = [0u8; 8192];
+    while let Ok(n) = pipe.read(&mut buf).await {
+        if n == 0 || tx.send(buf[..n].to_vec()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Kill the command with everything it started.
+async fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let _ = nix::sys::signal::killpg(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGKILL,
+        );
+    }
+    let _ = child.kill().await;
+}
+
+/// Output within a byte limit: the start and the end are kept, the middle
+/// is dropped.
+struct OutputBuffer {
+    limit: usize,
+    head: Vec<u8>,
+    tail: VecDeque<u8>,
+    omitted: usize,
+    /// Bytes after the last line break, for prompt detection.
+    pending_line: Vec<u8>,
+}
+
+impl OutputBuffer {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            omitted: 0,
+            pending_line: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        match bytes.iter().rposition(|&b| b == b'\n' || b == b'\r') {
+            Some(pos) => {
+                self.pending_line.clear();
+                self.pending_line.extend_from_slice(&bytes[pos + 1..]);
+            }
+            None => self.pending_line.extend_from_slice(bytes),
+        }
+        if self.pending_line.len() > MAX_PENDING_LINE {
+            let excess = self.pending_line.len() - MAX_PENDING_LINE;
+            self.pending_line.drain(..excess);
+        }
+
+        let head_room = (self.limit / 2).saturating_sub(self.head.len());
+        let (head, rest) = bytes.split_at(head_room.min(bytes.len()));
+        self.head.extend_from_slice(head);
+        self.tail.extend(rest);
+        let tail_limit = self.limit - self.limit / 2;
+        if self.tail.len() > tail_limit {
+            let excess = self.tail.len() - tail_limit;
+            self.tail.drain(..excess);
+            self.omitted += excess;
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut output = clean(&String::from_utf8_lossy(&self.head));
+        if self.omitted > 0 {
+            output.push_str(&format!(
+                "\n[... {} bytes of output omitted ...]\n",
+                self.omitted
+            ));
+        }
+        let tail: Vec<u8> = self.tail.iter().copied().collect();
+        output.push_str(&clean(&String::from_utf8_lossy(&tail)));
+        output
+    }
+}
+
+/// Drop terminal escapes and carriage returns.
+fn clean(text: &str) -> String {
+    ANSI_ESCAPE
+        .replace_all(text, "")
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+}
+
+/// The prompt a command is waiting at, if its last partial line looks like
+/// one.
+fn detect_prompt(pending_line: &[u8]) -> Option<String> {
+    let line = clean(&String::from_utf8_lossy(pending_line));
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let lower = trimmed.to_lowercase();
+    let asks_secret =
+        (lower.contains("password") || lower.contains("passphrase")) && lower.ends_with(':');
+    let asks_confirmation = lower.contains("y/n") || lower.contains("yes/no");
+    let asks_input = line.ends_with("? ") || line.ends_with(": ");
+    (asks_secret || asks_confirmation || asks_input).then(|| trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_buffer_keeps_start_and_end() {
+        let mut buffer = OutputBuffer::new(10);
+        buffer.push(b"abc");
+        buffer.push(b"defghijklmnop");
+        assert_eq!(
+            buffer.render(),
+            "abcde\n[... 6 bytes of output omitted ...]\nlmnop"
+        );
+
+        let mut small = OutputBuffer::new(100);
+        small.push(b"\x1b[32mok\x1b[0m\r\n");
+        assert_eq!(small.render(), "ok\n");
+    }
+
+    #[test]
+    fn test_detect_prompt() {
+        assert_eq!(
+            detect_prompt(b"[sudo] password for ghost:"),
+            Some("[sudo] password for ghost:".to_string())
+        );
+        assert!(detect_prompt(b"Do you want to continue? [Y/n] ").is_some());
+        assert!(detect_prompt(b"Username: ").is_some());
+        assert!(detect_prompt(b"Compiling crate").is_none());
+        assert!(detect_prompt(b"").is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! Pseudo-terminal plumbing for shell commands (unix only).
+
+use std::io;
+use std::os::fd::OwnedFd;
+use std::process::Stdio;
+
+use nix::fcntl::{FcntlArg, FdFlag, OFlag, fcntl};
+use nix::pty::{Winsize, openpty};
+use tokio::io::unix::AsyncFd;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Terminal size reported to commands; wide so output lines are not wrapped.
+const WINSIZE: Winsize = Winsize {
+    ws_row: 50,
+    ws_col: 200,
+    ws_xpixel: 0,
+    ws_ypixel: 0,
+};
+
+/// Run `command` in a new session whose controlling terminal and stdio are a
+/// fresh pseudo-terminal. Returns the non-blocking master side.
+pub(super) fn attach(command: &mut Command) -> io::Result<OwnedFd> {
+    let pty = openpty(&WINSIZE, None)?;
+    fcntl(&pty.master, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+    fcntl(&pty.slave, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+    fcntl(&pty.master, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+
+    command
+        .stdin(Stdio::from(pty.slave.try_clone()?))
+        .stdout(Stdio::from(pty.slave.try_clone()?))
+        .stderr(Stdio::from(pty.slave))
+        // Keep output plain and never page it.
+        .env("TERM", "dumb")
+        .env("NO_COLOR", "1")
+        .env("PAGER", "cat")
+        .env("GIT_PAGER", "cat");
+    // SAFETY: runs in the forked child before exec and only makes
+    // async-signal-safe calls (setsid, ioctl).
+    unsafe {
+        command.pre_exec(|| {
+            nix::unistd::setsid()?;
+            if nix::libc::ioctl(0, nix::libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    Ok(pty.master)
+}
+
+/// Send terminal output to `tx` until every slave fd is closed (reads then
+/// fail with EIO).
+pub(super) async fn forward(master: OwnedFd, tx: mpsc::Sender<Vec<u8>>) {
+    let Ok(master) = AsyncFd::new(master) else {
+        return;
+    };
+    let mut buf = [0u8; 8192];
+    loop {
+        let Ok(mut guard) = master.readable().await else {
+            return;
+        };
+        match guard.try_io(|fd| nix::unistd::read(fd.get_ref(), &mut buf).map_err(io::Error::from))
+        {
+            Ok(Ok(0)) | Ok(Err(_)) => return,
+            Ok(Ok(n)) => {
+                if tx.send(buf[..n].to_vec()).await.is_err() {
+                    return;
+                }
+            }
+            Err(_would_block) => continue,
+        }
+    }
+}
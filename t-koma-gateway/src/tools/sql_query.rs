@@ -0,0 +1,597 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::TryStreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::{Column, Connection, Executor, Row, SqlitePool, TypeInfo, ValueRef};
+
+use crate::tools::{Tool, ToolContext};
+
+const DEFAULT_MAX_ROWS: usize = 100;
+const MAX_ROWS_LIMIT: usize = 1000;
+const MAX_CELL_CHARS: usize = 1000;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// SQLite VM instructions between deadline checks.
+const PROGRESS_OPS: i32 = 10_000;
+
+/// Statement keywords that never belong in a read-only query.
+const DENIED_KEYWORDS: &[&str] = &[
+    "insert",
+    "update",
+    "delete",
+    "drop",
+    "create",
+    "alter",
+    "attach",
+    "detach",
+    "pragma",
+    "vacuum",
+    "reindex",
+    "analyze",
+    "begin",
+    "commit",
+    "rollback",
+    "savepoint",
+    "release",
+    "load_extension",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Database {
+    Knowledge,
+    Sessions,
+}
+
+impl Database {
+    fn as_str(self) -> &'static str {
+        match self {
+            Database::Knowledge => "knowledge",
+            Database::Sessions => "sessions",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SqlQueryInput {
+    database: Database,
+    query: String,
+    max_rows: Option<usize>,
+}
+
+pub struct SqlQueryTool;
+
+impl SqlQueryTool {
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "database": {
+                    "type": "string",
+                    "enum": ["knowledge", "sessions"],
+                    "description": "knowledge: notes, note_tags, note_links, chunks, reference_files (timestamps are RFC 3339 text). sessions: sessions, messages, usage_log, job_logs (timestamps are unix seconds)."
+                },
+                "query": {"type": "string", "description": "A single SELECT (or WITH ... SELECT) statement."},
+                "max_rows": {"type": "integer", "minimum": 1, "maximum": MAX_ROWS_LIMIT, "description": "Default 100."}
+            },
+            "required": ["database", "query"],
+            "additionalProperties": false
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &str {
+        "sql_query"
+    }
+
+    fn description(&self) -> &str {
+        "Run a read-only SQL SELECT over your own knowledge or session history (SQLite). Only your notes, shared knowledge and your own sessions are visible."
+    }
+
+    fn input_schema(&self) -> Value {
+        Self::schema()
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let input: SqlQueryInput = serde_json::from_value(args).map_err(|e| e.to_string())?;
+        let max_rows = input
+            .max_rows
+            .unwrap_or(DEFAULT_MAX_ROWS)
+            .clamp(1, MAX_ROWS_LIMIT);
+
+        let (pool, views) = match input.database {
+            Database::Knowledge => {
+                let engine = context
+                    .knowledge_engine()
+                    .ok_or("knowledge engine not available")?;
+                (engine.pool().clone(), knowledge_views(context.ghost_name()))
+            }
+            Database::Sessions => {
+                let (pool, ghost_id) = context
+                    .koma_scope()
+                    .ok_or("session database not available")?;
+                (pool.clone(), session_views(ghost_id))
+            }
+        };
+
+        let mut result =
+            run_scoped_query(&pool, &views, &input.query, max_rows, QUERY_TIMEOUT).await?;
+        result["database"] = json!(input.database.as_str());
+        serde_json::to_string(&result).map_err(|e| e.to_string())
+    }
+}
+
+/// A ghost-scoped view shadowing the base table of the same name.
+struct ScopedView {
+    name: &'static str,
+    select: String,
+}
+
+fn sql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn knowledge_views(ghost_name: &str) -> Vec<ScopedView> {
+    let owner = sql_literal(ghost_name);
+    let visible = format!("(n.owner_ghost IS NULL OR n.owner_ghost = {owner})");
+    vec![
+        ScopedView {
+            name: "notes",
+            select: format!(
+                "SELECT n.id, n.title, n.entry_type, n.archetype, n.path, n.scope, \
+                 n.owner_ghost, n.created_at, n.created_by_ghost, n.created_by_model, \
+                 n.trust_score, n.last_validated_at, n.version, n.parent_id, n.updated_at \
+                 FROM main.notes n WHERE {visible}"
+            ),
+        },
+        ScopedView {
+            name: "note_tags",
+            select: format!(
+                "SELECT t.note_id, t.tag FROM main.note_tags t \
+                 JOIN main.notes n ON n.id = t.note_id WHERE {visible}"
+            ),
+        },
+        ScopedView {
+            name: "note_links",
+            select: format!(
+                "SELECT l.source_id, l.target_title, l.target_id, l.alias FROM main.note_links l \
+                 JOIN main.notes n ON n.id = l.source_id WHERE {visible}"
+            ),
+        },
+        ScopedView {
+            name: "chunks",
+            select: format!(
+                "SELECT c.id, c.note_id, c.chunk_index, c.title, c.content, c.updated_at \
+                 FROM main.chunks c JOIN main.notes n ON n.id = c.note_id WHERE {visible}"
+            ),
+        },
+        ScopedView {
+            name: "reference_files",
+            select: format!(
+                "SELECT r.topic_id, r.note_id, r.path, r.role, r.status, r.source_url, \
+                 r.source_type, r.fetched_at FROM main.reference_files r \
+                 JOIN main.notes n ON n.id = r.note_id WHERE {visible}"
+            ),
+        },
+    ]
+}
+
+fn session_views(ghost_id: &str) -> Vec<ScopedView> {
+    let ghost = sql_literal(ghost_id);
+    vec![
+        ScopedView {
+            name: "sessions",
+            select: format!(
+                "SELECT id, operator_id, created_at, updated_at, is_active \
+                 FROM main.sessions WHERE ghost_id = {ghost}"
+            ),
+        },
+        ScopedView {
+            name: "messages",
+            select: format!(
+                "SELECT id, session_id, role, content, model, created_at \
+                 FROM main.messages WHERE ghost_id = {ghost}"
+            ),
+        },
+        ScopedView {
+            name: "usage_log",
+            select: format!(
+                "SELECT id, session_id, message_id, model, input_tokens, output_tokens, \
+                 cache_read_tokens, cache_creation_tokens, created_at \
+                 FROM main.usage_log WHERE ghost_id = {ghost}"
+            ),
+        },
+        ScopedView {
+            name: "job_logs",
+            select: format!(
+                "SELECT id, job_kind, session_id, started_at, finished_at, status \
+                 FROM main.job_logs WHERE ghost_id = {ghost}"
+            ),
+        },
+    ]
+}
+
+/// Run `query` on a private connection that only exposes `views`.
+///
+/// The connection is detached from the pool, so the TEMP views and
+/// `query_only` pragma never leak into connections used for writes. A
+/// progress handler interrupts the statement inside SQLite once `timeout`
+/// has passed; dropping the future alone would leave it running.
+async fn run_scoped_query(
+    pool: &SqlitePool,
+    views: &[ScopedView],
+    query: &str,
+    max_rows: usize,
+    timeout: Duration,
+) -> Result<Value, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?.detach();
+
+    let result = async {
+        for view in views {
+            conn.execute(format!("CREATE TEMP VIEW {} AS {}", view.name, view.select).as_str())
+                .await
+                .map_err(|e| format!("failed to prepare view {}: {e}", view.name))?;
+        }
+        let base_tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM main.sqlite_master WHERE type IN ('table', 'view')",
+        )
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        conn.execute("PRAGMA query_only = ON")
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let visible: Vec<&str> = views.iter().map(|v| v.name).collect();
+        let hidden: HashSet<String> = base_tables
+            .into_iter()
+            .map(|name| name.to_ascii_lowercase())
+            .filter(|name| !visible.contains(&name.as_str()))
+            .collect();
+        validate_query(query, &hidden)
+            .map_err(|e| format!("{e}. Queryable tables: {}", visible.join(", ")))?;
+
+        let deadline = Instant::now() + timeout;
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&timed_out);
+        conn.lock_handle()
+            .await
+            .map_err(|e| e.to_string())?
+            .set_progress_handler(PROGRESS_OPS, move || {
+                let expired = Instant::now() >= deadline;
+                flag.store(expired, Ordering::Relaxed);
+                !expired
+            });
+
+        fetch_rows(&mut conn, query, max_rows).await.map_err(|e| {
+            if timed_out.load(Ordering::Relaxed) {
+                format!("query timed out after {}s", timeout.as_secs_f32())
+            } else {
+                e
+            }
+        })
+    }
+    .await;
+
+    let _ = conn.close().await;
+    result
+}
+
+async fn fetch_rows(
+    conn: &mut SqliteConnection,
+    query: &str,
+    max_rows: usize,
+) -> Result<Value, String> {
+    let columns: Vec<String> = conn
+        .describe(query)
+        .await
+        .map_err(|e| e.to_string())?
+        .columns()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut stream = sqlx::query(query).fetch(&mut *conn);
+    while let Some(row) = stream.try_next().await.map_err(|e| e.to_string())? {
+        if rows.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        rows.push(row_to_json(&row)?);
+    }
+
+    Ok(json!({
+        "columns": columns,
+        "row_count": rows.len(),
+        "rows": rows,
+        "truncated": truncated,
+    }))
+}
+
+fn row_to_json(row: &SqliteRow) -> Result<Value, String> {
+    let mut values = Vec::with_capacity(row.len());
+    for index in 0..row.len() {
+        let raw = row.try_get_raw(index).map_err(|e| e.to_string())?;
+        if raw.is_null() {
+            values.push(Value::Null);
+            continue;
+        }
+        let type_name = raw.type_info().name().to_string();
+        let value = match type_name.as_str() {
+            "INTEGER" => json!(row.try_get::<i64, _>(index).map_err(|e| e.to_string())?),
+            "REAL" => json!(row.try_get::<f64, _>(index).map_err(|e| e.to_string())?),
+            "BLOB" => {
+                let bytes: Vec<u8> = row.try_get(index).map_err(|e| e.to_string())?;
+                json!(format!("<blob {} bytes>", bytes.len()))
+            }
+            _ => {
+                let text: String = row.try_get(index).map_err(|e| e.to_string())?;
+                json!(truncate_cell(text))
+            }
+        };
+        values.push(value);
+    }
+    Ok(Value::Array(values))
+}
+
+fn truncate_cell(text: String) -> String {
+    if text.chars().count() <= MAX_CELL_CHARS {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(MAX_CELL_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Keyword or identifier (quoted identifiers unquoted), lowercased.
+    Word(String),
+    Dot,
+    LParen,
+    Semicolon,
+    Other,
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let end = (i + 2..chars.len().saturating_sub(1))
+                    .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                    .ok_or("unterminated comment")?;
+                i = end + 2;
+            }
+            '\'' => {
+                i = skip_quoted(&chars, i, '\'').ok_or("unterminated string literal")?;
+                tokens.push(Token::Other);
+            }
+            '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                let end = skip_quoted(&chars, i, close).ok_or("unterminated identifier")?;
+                let ident: String = chars[i + 1..end - 1].iter().collect();
+                let doubled = format!("{close}{close}");
+                tokens.push(Token::Word(
+                    ident.replace(&doubled, &close.to_string()).to_lowercase(),
+                ));
+                i = end;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(Token::Word(word.to_lowercase()));
+            }
+            '.' if !chars.get(i + 1).is_some_and(char::is_ascii_digit) => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            _ => {
+                tokens.push(Token::Other);
+                i += 1;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Index just past the closing quote of the quoted run starting at `start`.
+fn skip_quoted(chars: &[char], start: usize, close: char) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == close {
+            // A doubled quote is an escaped quote (not for `[...]`).
+            if close != ']' && chars.get(i + 1) == Some(&close) {
+                i += 2;
+                continue;
+            }
+            return Some(i + 1);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Accept a single read-only SELECT that only touches the scoped views.
+///
+/// `hidden` holds the lowercased names of base tables that are not shadowed
+/// by a view; any reference to them is rejected, as is any schema-qualified
+/// name, so the query can only resolve to the ghost's TEMP views.
+fn validate_query(query: &str, hidden: &HashSet<String>) -> Result<(), String> {
+    let mut tokens = tokenize(query)?;
+    while tokens.last() == Some(&Token::Semicolon) {
+        tokens.pop();
+    }
+    if tokens.contains(&Token::Semicolon) {
+        return Err("only a single statement is allowed".to_string());
+    }
+    match tokens.first() {
+        Some(Token::Word(word)) if word == "select" || word == "with" => {}
+        _ => return Err("only SELECT queries are allowed".to_string()),
+    }
+
+    for (index, token) in tokens.iter().enumerate() {
+        let Token::Word(word) = token else {
+            continue;
+        };
+        let next = tokens.get(index + 1);
+        if DENIED_KEYWORDS.contains(&word.as_str())
+            || (word == "replace" && next != Some(&Token::LParen))
+        {
+            return Err(format!("'{}' is not allowed in a read-only query", word));
+        }
+        if word.starts_with("sqlite_") || word.starts_with("pragma_") {
+            return Err(format!("'{}' is not queryable", word));
+        }
+        if next == Some(&Token::Dot) && matches!(word.as_str(), "main" | "temp" | "temporary") {
+            return Err("schema-qualified names are not allowed".to_string());
+        }
+        if hidden.contains(word) {
+            return Err(format!("table '{}' is not queryable", word));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqliteConnectOptions;
+
+    fn hidden() -> HashSet<String> {
+        ["operators", "api_tokens"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_accepts_analytical_selects() {
+        let ok = [
+            "SELECT strftime('%Y-%W', created_at) AS week, COUNT(*) FROM notes GROUP BY week;",
+            "WITH recent AS (SELECT * FROM messages) SELECT role, count(*) FROM recent GROUP BY 1",
+            "select replace(title, 'a', 'b') from notes -- drop table notes",
+            "SELECT n.title FROM notes n WHERE n.title = 'delete; from operators'",
+        ];
+        for query in ok {
+            assert_eq!(validate_query(query, &hidden()), Ok(()), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_writes_and_escapes() {
+        let rejected = [
+            "DELETE FROM notes",
+            "SELECT 1; DROP TABLE notes",
+            "WITH x AS (SELECT 1) INSERT INTO notes SELECT * FROM x",
+            "PRAGMA table_info(notes)",
+            "SELECT * FROM main.notes",
+            "SELECT * FROM \"main\".notes",
+            "SELECT * FROM operators",
+            "SELECT * FROM [API_TOKENS]",
+            "SELECT sql FROM sqlite_master",
+            "SELECT * FROM pragma_table_info('notes')",
+            "SELECT load_extension('x')",
+            "SELECT 'unterminated",
+        ];
+        for query in rejected {
+            assert!(validate_query(query, &hidden()).is_err(), "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scoped_query_only_sees_own_rows() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pool = SqlitePool::connect_with(
+            SqliteConnectOptions::new()
+                .filename(dir.path().join("koma.sqlite3"))
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE sessions (id TEXT, ghost_id TEXT, operator_id TEXT, \
+               created_at INTEGER, updated_at INTEGER, is_active INTEGER);
+             CREATE TABLE messages (id TEXT, ghost_id TEXT, session_id TEXT, role TEXT, \
+               content TEXT, model TEXT, created_at INTEGER);
+             CREATE TABLE usage_log (id TEXT, ghost_id TEXT, session_id TEXT, message_id TEXT, \
+               model TEXT, input_tokens INTEGER, output_tokens INTEGER, \
+               cache_read_tokens INTEGER, cache_creation_tokens INTEGER, created_at INTEGER);
+             CREATE TABLE job_logs (id TEXT, ghost_id TEXT, job_kind TEXT, session_id TEXT, \
+               started_at INTEGER, finished_at INTEGER, status TEXT);
+             CREATE TABLE operators (id TEXT);
+             INSERT INTO messages VALUES ('m1', 'g1', 's1', 'ghost', 'mine', NULL, 1);
+             INSERT INTO messages VALUES ('m2', 'g2', 's2', 'ghost', 'theirs', NULL, 2);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let views = session_views("g1");
+        let result = run_scoped_query(
+            &pool,
+            &views,
+            "SELECT content FROM messages",
+            10,
+            QUERY_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["columns"], json!(["content"]));
+        assert_eq!(result["rows"], json!([["mine"]]));
+        assert_eq!(result["truncated"], json!(false));
+
+        let err = run_scoped_query(&pool, &views, "SELECT * FROM operators", 10, QUERY_TIMEOUT)
+            .await
+            .unwrap_err();
+        assert!(err.contains("not queryable"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_runaway_query_is_interrupted() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let runaway = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x+1 FROM c) \
+                       SELECT count(*) FROM c";
+
+        // Without the progress handler the statement never finishes, and the
+        // outer guard fails the test instead of hanging.
+        let err = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_scoped_query(&pool, &[], runaway, 10, Duration::from_millis(200)),
+        )
+        .await
+        .expect("statement was not interrupted")
+        .unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+    }
+}
@@ -0,0 +1,43 @@
+use serde_json::{Value, json};
+
+use super::output_pages::{CONTINUE_TOOL, continue_output};
+use super::{Tool, ToolContext};
+
+pub struct ToolOutputContinueTool;
+
+#[async_trait::async_trait]
+impl Tool for ToolOutputContinueTool {
+    fn name(&self) -> &str {
+        CONTINUE_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Fetches the next page of a tool result that was too large to return at once. Use the token from the [OUTPUT PAGE ...] notice at the end of the previous page."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "token": {
+                    "type": "string",
+                    "description": "Continuation token from the [OUTPUT PAGE ...] notice"
+                },
+                "page": {
+                    "type": "integer",
+                    "description": "Page to fetch (1-based). Defaults to the page after the last one returned.",
+                    "minimum": 1
+                }
+            },
+            "required": ["token"]
+        })
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        let token = args["token"]
+            .as_str()
+            .ok_or_else(|| "Missing or invalid 'token' argument".to_string())?;
+        let page = args["page"].as_u64().map(|page| page as usize);
+        continue_output(context.ghost_name(), token, page)
+    }
+}
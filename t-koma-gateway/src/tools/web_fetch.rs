@@ -2,7 +2,12 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 
 use crate::tools::{Tool, ToolContext};
-use crate::web::fetch::{FetchError, WebFetchRequest, WebFetchService, http::HttpFetchProvider};
+use crate::web::cache::WebCache;
+use crate::web::fetch::render::{RenderFetchProvider, Renderer};
+use crate::web::fetch::{
+    FetchError, FetchProvider, WebFetchRequest, WebFetchService, http::HttpFetchProvider,
+};
+use crate::web::sanitize::ContentSanitizer;
 
 /// Generate a filename from a URL for web-cache dedup.
 pub(crate) fn url_to_cache_filename(url: &str, ext: &str) -> String {
@@ -38,7 +43,7 @@ impl WebFetchTool {
             "type": "object",
             "properties": {
                 "url": {"type": "string"},
-                "mode": {"type": "string", "enum": ["text", "markdown"]},
+                "mode": {"type": "string", "enum": ["text", "markdown", "render"], "description": "render runs the page's JavaScript first; use it for single-page apps that come back empty."},
                 "max_chars": {"type": "integer", "minimum": 1},
                 "raw": {"type": "boolean", "description": "Return full page content instead of extracted article. Default false."}
             },
@@ -47,6 +52,22 @@ impl WebFetchTool {
         })
     }
 
+    fn renderer(settings: &t_koma_core::Settings) -> Result<Renderer, String> {
+        let render = &settings.tools.web.fetch.render;
+        match render.backend.as_str() {
+            "browser" => Ok(Renderer::Browser(settings.tools.web.browser.clone())),
+            "reader" => Renderer::reader(
+                render.reader_url.clone(),
+                std::env::var("READER_API_KEY").ok(),
+            )
+            .map_err(Self::format_error),
+            other => Err(format!(
+                "web_fetch render backend '{}' is not supported (use 'browser' or 'reader')",
+                other
+            )),
+        }
+    }
+
     fn format_error(err: FetchError) -> String {
         match err {
             FetchError::Disabled => "web_fetch is disabled in configuration".to_string(),
@@ -58,6 +79,9 @@ impl WebFetchTool {
             FetchError::UnsupportedContentType(ct) => {
                 format!("unsupported content type for web_fetch: {}", ct)
             }
+            FetchError::DomainBlocked(host) => {
+                format!("web_fetch: domain '{host}' is blocked by the web domain policy")
+            }
             FetchError::RequestFailed(msg) => format!("web_fetch request failed: {}", msg),
         }
     }
@@ -70,7 +94,7 @@ impl Tool for WebFetchTool {
     }
 
     fn description(&self) -> &str {
-        "Fetch a web page as text or markdown. Successful fetches are automatically saved for later curation."
+        "Fetch a web page as text or markdown, optionally rendering its JavaScript first. Successful fetches are automatically saved for later curation."
     }
 
     fn input_schema(&self) -> Value {
@@ -95,27 +119,49 @@ impl Tool for WebFetchTool {
             ));
         }
 
-        let provider = HttpFetchProvider::new(
-            std::time::Duration::from_secs(settings.tools.web.fetch.timeout_seconds),
+        let mut policy = settings.tools.web.domains.for_ghost(context.ghost_name());
+        context.authorize_web_url(&mut policy, "web_fetch", &input.url)?;
+
+        let timeout = std::time::Duration::from_secs(settings.tools.web.fetch.timeout_seconds);
+        let mode = input
+            .mode
+            .unwrap_or_else(|| settings.tools.web.fetch.mode.clone());
+        let http = HttpFetchProvider::new(
+            timeout,
             settings.tools.web.fetch.mode.clone(),
             settings.tools.web.fetch.max_chars,
         )
         .map_err(Self::format_error)?;
+        let provider: Box<dyn FetchProvider> = if mode == "render" {
+            Box::new(RenderFetchProvider::new(
+                Self::renderer(&settings)?,
+                http,
+                timeout,
+                settings.tools.web.fetch.max_chars,
+            ))
+        } else {
+            Box::new(http)
+        };
 
-        let service = WebFetchService::new(
-            Box::new(provider),
-            std::time::Duration::from_secs(settings.tools.web.fetch.cache_ttl_minutes * 60),
-        );
+        let cache = context.koma_pool().map(|pool| {
+            WebCache::new(
+                pool.clone(),
+                "fetch",
+                std::time::Duration::from_secs(settings.tools.web.fetch.cache_ttl_minutes * 60),
+                settings.tools.web.cache.clone(),
+            )
+        });
+        let service = WebFetchService::new(provider, cache).with_domain_policy(policy);
 
         let url = input.url;
         let request = WebFetchRequest {
             url: url.clone(),
-            mode: input.mode,
+            mode: Some(mode),
             max_chars: input.max_chars,
             raw: input.raw,
         };
 
-        let response = service.fetch(request).await.map_err(Self::format_error)?;
+        let mut response = service.fetch(request).await.map_err(Self::format_error)?;
 
         // Auto-save fetched content to _web-cache reference topic (skip non-2xx)
         if (200..300).contains(&response.status) {
@@ -125,8 +171,12 @@ impl Tool for WebFetchTool {
                 .await;
         }
 
+        let sanitizer = ContentSanitizer::from_settings(&settings.tools.untrusted_content);
+        let source = format!("web_fetch {url}");
+        response.content = sanitizer.clean(&source, &response.content);
         let serialized = serde_json::to_string(&response).map_err(|e| e.to_string())?;
-        let ref_id = context.cache_tool_result("web_fetch", &serialized);
-        Ok(format!("[Result #{}] {}", ref_id, serialized))
+        let guarded = sanitizer.wrap(&source, &serialized);
+        let ref_id = context.cache_tool_result("web_fetch", &guarded);
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
     }
 }
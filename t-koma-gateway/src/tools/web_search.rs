@@ -3,9 +3,12 @@ use serde_json::{Value, json};
 
 use crate::tools::web_fetch::url_to_cache_filename;
 use crate::tools::{Tool, ToolContext};
+use crate::web::cache::WebCache;
+use crate::web::sanitize::ContentSanitizer;
 use crate::web::search::{
     SearchError, SearchProvider, WebSearchQuery, WebSearchService, brave::BraveSearchProvider,
-    perplexity::PerplexitySearchProvider,
+    google::GoogleSearchProvider, perplexity::PerplexitySearchProvider,
+    searxng::SearxngSearchProvider, tavily::TavilySearchProvider,
 };
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +18,7 @@ struct WebSearchInput {
     country: Option<String>,
     search_lang: Option<String>,
     freshness: Option<String>,
+    provider: Option<String>,
 }
 
 pub struct WebSearchTool;
@@ -28,7 +32,11 @@ impl WebSearchTool {
                 "count": {"type": "integer", "minimum": 1},
                 "country": {"type": "string"},
                 "search_lang": {"type": "string"},
-                "freshness": {"type": "string"}
+                "freshness": {"type": "string"},
+                "provider": {
+                    "type": "string",
+                    "description": "Search provider for this query (brave, perplexity, tavily, searxng or google). Only providers allowed in config work; omit to use the default."
+                }
             },
             "required": ["query"],
             "additionalProperties": false
@@ -52,9 +60,11 @@ impl WebSearchTool {
 
     fn build_provider(
         provider_name: &str,
-        timeout: std::time::Duration,
-        min_interval: std::time::Duration,
+        settings: &t_koma_core::WebSearchSettings,
     ) -> Result<Box<dyn SearchProvider>, String> {
+        let timeout = std::time::Duration::from_secs(settings.timeout_seconds);
+        let min_interval = std::time::Duration::from_millis(settings.min_interval_ms.max(1000));
+
         match provider_name {
             "brave" => {
                 let key = std::env::var("BRAVE_API_KEY").map_err(|_| {
@@ -72,8 +82,43 @@ impl WebSearchTool {
                     .map_err(Self::format_error)?;
                 Ok(Box::new(provider))
             }
+            "tavily" => {
+                let key = std::env::var("TAVILY_API_KEY").map_err(|_| {
+                    "TAVILY_API_KEY is not set (required for web_search with provider 'tavily')"
+                        .to_string()
+                })?;
+                let provider = TavilySearchProvider::new(key, timeout, min_interval)
+                    .map_err(Self::format_error)?;
+                Ok(Box::new(provider))
+            }
+            "searxng" => {
+                let url = settings.searxng_url.as_deref().ok_or_else(|| {
+                    "tools.web.search.searxng_url is not set (required for web_search with provider 'searxng')"
+                        .to_string()
+                })?;
+                let provider = SearxngSearchProvider::new(url, timeout, min_interval)
+                    .map_err(Self::format_error)?;
+                Ok(Box::new(provider))
+            }
+            "google" => {
+                let key = std::env::var("GOOGLE_CSE_API_KEY").map_err(|_| {
+                    "GOOGLE_CSE_API_KEY is not set (required for web_search with provider 'google')"
+                        .to_string()
+                })?;
+                let engine_id = settings
+                    .google_cse_id
+                    .clone()
+                    .or_else(|| std::env::var("GOOGLE_CSE_ID").ok())
+                    .ok_or_else(|| {
+                        "tools.web.search.google_cse_id (or GOOGLE_CSE_ID) is not set (required for web_search with provider 'google')"
+                            .to_string()
+                    })?;
+                let provider = GoogleSearchProvider::new(key, engine_id, timeout, min_interval)
+                    .map_err(Self::format_error)?;
+                Ok(Box::new(provider))
+            }
             other => Err(format!(
-                "web_search provider '{}' is not supported (use 'brave' or 'perplexity')",
+                "web_search provider '{}' is not supported (use 'brave', 'perplexity', 'tavily', 'searxng' or 'google')",
                 other
             )),
         }
@@ -120,24 +165,40 @@ impl Tool for WebSearchTool {
             return Err("web_search tool is disabled in config".to_string());
         }
 
-        let timeout = std::time::Duration::from_secs(settings.tools.web.search.timeout_seconds);
-        let min_interval =
-            std::time::Duration::from_millis(settings.tools.web.search.min_interval_ms.max(1000));
-
-        let provider =
-            Self::build_provider(&settings.tools.web.search.provider, timeout, min_interval)?;
-
-        let service = WebSearchService::new(
-            provider,
-            std::time::Duration::from_secs(settings.tools.web.search.cache_ttl_minutes * 60),
-        );
+        let provider_name = settings
+            .tools
+            .web
+            .search
+            .resolve_provider(context.ghost_name(), input.provider.as_deref())?;
+        let provider = Self::build_provider(provider_name, &settings.tools.web.search)?;
+
+        let cache = context.koma_pool().map(|pool| {
+            WebCache::new(
+                pool.clone(),
+                "search",
+                std::time::Duration::from_secs(settings.tools.web.search.cache_ttl_minutes * 60),
+                settings.tools.web.cache.clone(),
+            )
+        });
+        let policy = settings.tools.web.domains.for_ghost(context.ghost_name());
+        let service = WebSearchService::new(provider, cache).with_domain_policy(policy);
 
         let search_query = input.query.clone();
         let query = Self::build_query(input, settings.tools.web.search.max_results);
-        let response = service.search(query).await.map_err(Self::format_error)?;
+        let mut response = service.search(query).await.map_err(Self::format_error)?;
+
+        let sanitizer = ContentSanitizer::from_settings(&settings.tools.untrusted_content);
+        let source = format!("web_search {search_query}");
+        for result in &mut response.results {
+            result.title = sanitizer.clean(&result.url, &result.title);
+            if let Some(snippet) = &result.snippet {
+                result.snippet = Some(sanitizer.clean(&result.url, snippet));
+            }
+        }
 
         let serialized = serde_json::to_string(&response).map_err(|e| e.to_string())?;
-        let ref_id = context.cache_tool_result("web_search", &serialized);
+        let guarded = sanitizer.wrap(&source, &serialized);
+        let ref_id = context.cache_tool_result("web_search", &guarded);
 
         // Auto-save search results to _web-cache for reflection to curate
         let cache_key = format!("search:{search_query}");
@@ -146,6 +207,6 @@ impl Tool for WebSearchTool {
             .auto_save_web_result(&cache_key, &serialized, &filename)
             .await;
 
-        Ok(format!("[Result #{}] {}", ref_id, serialized))
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
     }
 }
@@ -0,0 +1,344 @@
+//! Tools defined in config and served by operator HTTP endpoints.
+//!
+//! Each `[tools.webhooks.<name>]` entry becomes a tool named `<name>`. A
+//! call `POST`s the ghost's arguments as JSON to the configured URL and
+//! returns the response body. Requests carry `X-T-Koma-Ghost` and
+//! `X-T-Koma-Tool`; with a signing secret they are also signed:
+//! `X-T-Koma-Signature: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">`,
+//! with the Unix timestamp in `X-T-Koma-Timestamp`.
+//!
+//! Webhook tools are registered once at startup, after MCP tools;
+//! `ToolManager::new_chat` adds them.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use t_koma_core::config::{UntrustedContentSettings, WebhookToolSettings};
+use tracing::{info, warn};
+
+use super::context::webhook_approval_key;
+use super::{ApprovalReason, Tool, ToolContext, ToolManager};
+use crate::web::sanitize::ContentSanitizer;
+
+/// Largest response body kept; the rest is dropped.
+const MAX_RESPONSE_BYTES: usize = 256 * 1024;
+
+/// Longest argument preview shown in an approval request.
+const MAX_ARGUMENTS_PREVIEW: usize = 500;
+
+static REGISTRY: OnceLock<Vec<WebhookTool>> = OnceLock::new();
+
+/// Registered webhook tools (empty until [`register`] has run).
+pub fn tools() -> &'static [WebhookTool] {
+    REGISTRY.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Register every enabled webhook tool.
+///
+/// Entries with an invalid or taken name, or whose secrets are missing from
+/// the environment, are logged and skipped. Only the first call registers
+/// tools; returns the number of tools registered by this call.
+pub fn register(
+    webhooks: &BTreeMap<String, WebhookToolSettings>,
+    untrusted_content: &UntrustedContentSettings,
+) -> usize {
+    if REGISTRY.get().is_some() {
+        return 0;
+    }
+    let taken: HashSet<String> = ToolManager::new_chat(Vec::new())
+        .get_tools()
+        .iter()
+        .map(|tool| tool.name().to_string())
+        .collect();
+
+    let mut registered = Vec::new();
+    for (name, settings) in webhooks.iter().filter(|(_, settings)| settings.enabled) {
+        if !is_valid_tool_name(name) || taken.contains(name) {
+            warn!("Webhook tool '{name}' skipped: invalid or already used tool name");
+            continue;
+        }
+        match WebhookTool::new(name, settings, untrusted_content) {
+            Ok(tool) => registered.push(tool),
+            Err(e) => warn!("Webhook tool '{name}' skipped: {e}"),
+        }
+    }
+
+    let count = registered.len();
+    if REGISTRY.set(registered).is_err() {
+        return 0;
+    }
+    info!("{count} webhook tool(s) registered");
+    count
+}
+
+/// Tool names match `^[a-zA-Z0-9_-]{1,64}$`; `mcp__` is reserved.
+fn is_valid_tool_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && !name.starts_with("mcp__")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A tool proxied to an operator's HTTP endpoint.
+#[derive(Clone)]
+pub struct WebhookTool {
+    name: String,
+    settings: WebhookToolSettings,
+    /// Header name and value resolved from the environment.
+    auth: Option<(String, String)>,
+    signing_secret: Option<String>,
+    untrusted_content: UntrustedContentSettings,
+    client: reqwest::Client,
+}
+
+impl WebhookTool {
+    pub fn new(
+        name: &str,
+        settings: &WebhookToolSettings,
+        untrusted_content: &UntrustedContentSettings,
+    ) -> Result<Self, String> {
+        let env = |var: &str| {
+            std::env::var(var).map_err(|_| format!("environment variable {var} is not set"))
+        };
+        let auth = match (&settings.auth_header, &settings.auth_value_env) {
+            (Some(header), Some(var)) => Some((header.clone(), env(var)?)),
+            (None, None) => None,
+            _ => return Err("auth_header and auth_value_env must be set together".to_string()),
+        };
+        let signing_secret = settings
+            .signing_secret_env
+            .as_deref()
+            .map(env)
+            .transpose()?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_seconds))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            name: name.to_string(),
+            settings: settings.clone(),
+            auth,
+            signing_secret,
+            untrusted_content: untrusted_content.clone(),
+            client,
+        })
+    }
+
+    fn arguments_preview(args: &Value) -> String {
+        let compact = args.to_string();
+        match compact.char_indices().nth(MAX_ARGUMENTS_PREVIEW) {
+            Some((end, _)) => format!("{}…", &compact[..end]),
+            None => compact,
+        }
+    }
+
+    async fn send(&self, body: String, ghost: &str) -> Result<(u16, String), String> {
+        let mut request = self
+            .client
+            .post(&self.settings.url)
+            .header("Content-Type", "application/json")
+            .header("X-T-Koma-Ghost", ghost)
+            .header("X-T-Koma-Tool", &self.name);
+        if let Some((header, value)) = &self.auth {
+            request = request.header(header, value);
+        }
+        if let Some(secret) = &self.signing_secret {
+            let timestamp = chrono::Utc::now().timestamp().to_string();
+            request = request
+                .header("X-T-Koma-Signature", sign(secret, &timestamp, &body))
+                .header("X-T-Koma-Timestamp", timestamp);
+        }
+
+        let mut response = request.body(body).send().await.map_err(|e| e.to_string())?;
+        let status = response.status().as_u16();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            let room = MAX_RESPONSE_BYTES - bytes.len();
+            bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            if chunk.len() >= room {
+                break;
+            }
+        }
+        Ok((status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `<timestamp>.<body>`.
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[async_trait::async_trait]
+impl Tool for WebhookTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.settings.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.settings.input_schema.clone()
+    }
+
+    async fn execute(&self, args: Value, context: &mut ToolContext) -> Result<String, String> {
+        if self.settings.require_approval
+            && !context.has_approval(&webhook_approval_key(&self.name))
+        {
+            return Err(ApprovalReason::WebhookTool {
+                tool: self.name.clone(),
+                url: self.settings.url.clone(),
+                arguments: Self::arguments_preview(&args),
+            }
+            .to_error());
+        }
+
+        let (status, body) = self
+            .send(args.to_string(), context.ghost_name())
+            .await
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+
+        let sanitizer = ContentSanitizer::from_settings(&self.untrusted_content);
+        let source = format!("webhook {}", self.name);
+        let guarded = sanitizer.wrap(&source, &sanitizer.clean(&source, &body));
+        if !(200..300).contains(&status) {
+            return Err(format!("{}: HTTP {}\n{}", self.name, status, guarded));
+        }
+        let ref_id = context.cache_tool_result(&self.name, &guarded);
+        Ok(format!("[Result #{}] {}", ref_id, guarded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn settings(url: &str) -> WebhookToolSettings {
+        toml::from_str(&format!(
+            "description = \"Turn on lights\"\nurl = \"{url}\"\nrequire_approval = false"
+        ))
+        .unwrap()
+    }
+
+    /// Serve one request with `response`; yields the raw request received.
+    async fn serve_once(response: &'static str) -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            line.to_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(str::to_string)
+                        })
+                        .and_then(|len| len.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            let _ = stream.shutdown().await;
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn test_sign_and_tool_names() {
+        assert_eq!(
+            sign("secret", "1700000000", "{}"),
+            "sha256=b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163"
+        );
+        assert!(is_valid_tool_name("lights_on"));
+        assert!(!is_valid_tool_name("mcp__lights"));
+        assert!(!is_valid_tool_name("lights on"));
+        assert!(!is_valid_tool_name(""));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_call_is_signed_and_returns_body() {
+        let (port, request) = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 11\r\n\r\n{\"ok\":true}",
+        )
+        .await;
+        let mut tool = WebhookTool::new(
+            "lights_on",
+            &settings(&format!("http://127.0.0.1:{port}/hooks/lights")),
+            &UntrustedContentSettings::default(),
+        )
+        .unwrap();
+        tool.auth = Some(("Authorization".to_string(), "Bearer abc".to_string()));
+        tool.signing_secret = Some("s3cret".to_string());
+
+        let mut context = ToolContext::new_for_tests(std::path::Path::new("/tmp"));
+        let output = tool
+            .execute(json!({"room": "kitchen"}), &mut context)
+            .await
+            .unwrap();
+        assert!(output.contains("{\"ok\":true}"));
+
+        let request = request.await.unwrap();
+        let header = |name: &str| {
+            request
+                .lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(": ")?;
+                    key.eq_ignore_ascii_case(name).then(|| value.to_string())
+                })
+                .unwrap()
+        };
+        assert!(request.starts_with("POST /hooks/lights "));
+        assert_eq!(header("authorization"), "Bearer abc");
+        assert_eq!(header("x-t-koma-ghost"), "test-ghost");
+        let body = r#"{"room":"kitchen"}"#;
+        assert!(request.ends_with(body));
+        assert_eq!(
+            header("x-t-koma-signature"),
+            sign("s3cret", &header("x-t-koma-timestamp"), body)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_needs_approval_and_reports_http_errors() {
+        let (port, _request) =
+            serve_once("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 4\r\n\r\ndown").await;
+        let mut settings = settings(&format!("http://127.0.0.1:{port}/"));
+        settings.require_approval = true;
+        let tool =
+            WebhookTool::new("lights_on", &settings, &UntrustedContentSettings::default()).unwrap();
+
+        let mut context = ToolContext::new_for_tests(std::path::Path::new("/tmp"));
+        let error = tool.execute(json!({}), &mut context).await.unwrap_err();
+        let reason = ApprovalReason::parse(&error).unwrap();
+        assert!(matches!(reason, ApprovalReason::WebhookTool { .. }));
+
+        context.apply_approval(&reason);
+        let error = tool.execute(json!({}), &mut context).await.unwrap_err();
+        assert!(error.starts_with("lights_on: HTTP 503"));
+        assert!(error.contains("down"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Audio transcription for voice messages and audio attachments.
+//!
+//! When `[transcription] enabled = true`, audio files attached to an operator
+//! message are sent to a [`Transcriber`] and the transcript is appended to the
+//! message as text, right after the stored attachment. The model sees what was
+//! said; the original recording stays in the workspace `downloads/` folder.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use t_koma_core::TranscriptionSettings;
+use t_koma_db::ContentBlock;
+use tracing::{info, warn};
+
+use crate::attachments::{is_audio_mime, mime_type_for_filename};
+use crate::providers::retry::{RetryPolicy, send_with_retry};
+
+/// Transcription errors
+#[derive(Debug, thiserror::Error)]
+pub enum TranscriptionError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("API error ({status}): {message}")]
+    Api { status: u16, message: String },
+    #[error("failed to read audio file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Speech-to-text backend.
+#[async_trait::async_trait]
+pub trait Transcriber: Send + Sync {
+    /// Backend name, for logs.
+    fn name(&self) -> &str;
+
+    /// Transcribe an audio file; `filename` carries the format hint.
+    async fn transcribe(
+        &self,
+        audio: Vec<u8>,
+        filename: &str,
+    ) -> Result<String, TranscriptionError>;
+}
+
+/// Client for OpenAI's `/audio/transcriptions` API and compatible servers
+/// (faster-whisper-server, whisper.cpp, LocalAI, ...).
+pub struct WhisperClient {
+    http_client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    language: Option<String>,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+impl WhisperClient {
+    /// Build a client from settings, reading the API key from `api_key_env`.
+    pub fn from_settings(settings: &TranscriptionSettings) -> Self {
+        Self {
+            http_client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(settings.timeout_seconds))
+                .build()
+                .expect("Failed to build HTTP client"),
+            base_url: settings.base_url.trim_end_matches('/').to_string(),
+            model: settings.model.clone(),
+            api_key: std::env::var(&settings.api_key_env)
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+            language: settings.language.clone(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Set the transient-error retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Transcriber for WhisperClient {
+    fn name(&self) -> &str {
+        "whisper"
+    }
+
+    async fn transcribe(
+        &self,
+        audio: Vec<u8>,
+        filename: &str,
+    ) -> Result<String, TranscriptionError> {
+        let url = format!("{}/audio/transcriptions", self.base_url);
+        let response = send_with_retry(&self.retry, "transcription", &self.model, || {
+            let file =
+                reqwest::multipart::Part::bytes(audio.clone()).file_name(filename.to_string());
+            let mut form = reqwest::multipart::Form::new()
+                .text("model", self.model.clone())
+                .text("response_format", "json")
+                .part("file", file);
+            if let Some(language) = &self.language {
+                form = form.text("language", language.clone());
+            }
+            let request = self.http_client.post(&url).multipart(form);
+            match &self.api_key {
+                Some(key) => request.bearer_auth(key),
+                None => request,
+            }
+        })
+        .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(TranscriptionError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        let body: TranscriptionResponse = response.json().await?;
+        Ok(body.text.trim().to_string())
+    }
+}
+
+/// Build the configured transcriber, or `None` when transcription is disabled.
+pub fn from_settings(settings: &t_koma_core::Settings) -> Option<Arc<dyn Transcriber>> {
+    let transcription = &settings.transcription;
+    if !transcription.enabled {
+        return None;
+    }
+    let retry = RetryPolicy::for_provider(&settings.provider_retry, "transcription");
+    Some(Arc::new(
+        WhisperClient::from_settings(transcription).with_retry(retry),
+    ))
+}
+
+/// Insert a transcript text block after every audio attachment.
+///
+/// Audio that cannot be transcribed is left as a plain file attachment.
+pub async fn add_transcripts(
+    transcriber: &dyn Transcriber,
+    attachments: Vec<ContentBlock>,
+) -> Vec<ContentBlock> {
+    let mut blocks = Vec::with_capacity(attachments.len());
+    for block in attachments {
+        let audio = match &block {
+            ContentBlock::File { path, filename, .. }
+                if is_audio_mime(&mime_type_for_filename(filename)) =>
+            {
+                Some((path.clone(), filename.clone()))
+            }
+            _ => None,
+        };
+        blocks.push(block);
+        let Some((path, filename)) = audio else {
+            continue;
+        };
+
+        let result = match tokio::fs::read(&path).await {
+            Ok(bytes) => transcriber.transcribe(bytes, &filename).await,
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(text) if !text.is_empty() => {
+                info!(
+                    "Transcribed {} with {} ({} chars)",
+                    filename,
+                    transcriber.name(),
+                    text.len()
+                );
+                blocks.push(ContentBlock::Text {
+                    text: format!("[transcript of {}]\n{}", filename, text),
+                });
+            }
+            Ok(_) => warn!("Transcription of {} was empty", filename),
+            Err(e) => warn!("Failed to transcribe {}: {}", filename, e),
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedTranscriber;
+
+    #[async_trait::async_trait]
+    impl Transcriber for FixedTranscriber {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn transcribe(
+            &self,
+            audio: Vec<u8>,
+            filename: &str,
+        ) -> Result<String, TranscriptionError> {
+            Ok(format!("{} bytes from {}", audio.len(), filename))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_transcripts_after_audio_attachments() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio_path = dir.path().join("voice-message.ogg");
+        std::fs::write(&audio_path, b"OggS....").unwrap();
+
+        let attachments = vec![
+            ContentBlock::File {
+                path: audio_path.to_string_lossy().to_string(),
+                filename: "voice-message.ogg".to_string(),
+                size: 8,
+            },
+            ContentBlock::File {
+                path: dir.path().join("notes.txt").to_string_lossy().to_string(),
+                filename: "notes.txt".to_string(),
+                size: 3,
+            },
+            ContentBlock::File {
+                path: dir.path().join("missing.mp3").to_string_lossy().to_string(),
+                filename: "missing.mp3".to_string(),
+                size: 3,
+            },
+        ];
+
+        let blocks = add_transcripts(&FixedTranscriber, attachments).await;
+        assert_eq!(blocks.len(), 4);
+        assert!(
+            matches!(&blocks[0], ContentBlock::File { filename, .. } if filename == "voice-message.ogg")
+        );
+        assert!(matches!(
+            &blocks[1],
+            ContentBlock::Text { text } if text == "[transcript of voice-message.ogg]\n8 bytes from voice-message.ogg"
+        ));
+        assert!(
+            matches!(&blocks[2], ContentBlock::File { filename, .. } if filename == "notes.txt")
+        );
+        assert!(
+            matches!(&blocks[3], ContentBlock::File { filename, .. } if filename == "missing.mp3")
+        );
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        assert!(from_settings(&t_koma_core::Settings::default()).is_none());
+    }
+}
@@ -0,0 +1,250 @@
+//! Minimal Chrome DevTools Protocol client.
+//!
+//! Launches a headless Chromium with `--remote-debugging-port=0`, reads the
+//! DevTools WebSocket URL from its stderr and multiplexes commands over that
+//! single socket. Page commands are routed with a flattened `sessionId`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::BrowserError;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type Pending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+const DEFAULT_EXECUTABLES: &[&str] = &["chromium", "chromium-browser", "google-chrome"];
+const DEVTOOLS_PREFIX: &str = "DevTools listening on ";
+
+/// A running headless browser and its DevTools connection.
+pub struct CdpBrowser {
+    _child: Child,
+    profile_dir: PathBuf,
+    writer: Mutex<SplitSink<Socket, Message>>,
+    pending: Pending,
+    closed: Arc<AtomicBool>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl CdpBrowser {
+    /// Launch Chromium and connect to its DevTools endpoint.
+    pub async fn launch(executable: Option<&str>, timeout: Duration) -> Result<Self, BrowserError> {
+        let profile_dir =
+            std::env::temp_dir().join(format!("t-koma-browser-{}", uuid::Uuid::new_v4()));
+        let mut child = spawn_browser(executable, &profile_dir)?;
+
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| BrowserError::Launch("browser stderr unavailable".to_string()))?;
+        let ws_url = tokio::time::timeout(timeout, read_devtools_url(stderr))
+            .await
+            .map_err(|_| BrowserError::Launch("timed out waiting for DevTools".to_string()))??;
+
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url.as_str())
+            .await
+            .map_err(|e| BrowserError::Launch(format!("DevTools connect failed: {e}")))?;
+        let (writer, mut reader) = socket.split();
+
+        let pending: Pending = Arc::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader_pending = Arc::clone(&pending);
+        let reader_closed = Arc::clone(&closed);
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = reader.next().await {
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let Some((id, result)) = parse_response(&text) else {
+                    continue;
+                };
+                let sender = reader_pending.lock().ok().and_then(|mut p| p.remove(&id));
+                if let Some(sender) = sender {
+                    let _ = sender.send(result);
+                }
+            }
+            // Socket closed: fail everything still waiting.
+            reader_closed.store(true, Ordering::Relaxed);
+            if let Ok(mut pending) = reader_pending.lock() {
+                pending.clear();
+            }
+        });
+
+        Ok(Self {
+            _child: child,
+            profile_dir,
+            writer: Mutex::new(writer),
+            pending,
+            closed,
+            next_id: AtomicU64::new(1),
+            timeout,
+        })
+    }
+
+    /// Whether the DevTools socket is still open.
+    pub fn is_alive(&self) -> bool {
+        !self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Send one CDP command and wait for its result.
+    pub async fn call(
+        &self,
+        method: &str,
+        params: Value,
+        session_id: Option<&str>,
+    ) -> Result<Value, BrowserError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut command = json!({"id": id, "method": method, "params": params});
+        if let Some(session_id) = session_id {
+            command["sessionId"] = json!(session_id);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|_| BrowserError::Closed)?
+            .insert(id, tx);
+
+        let sent = self
+            .writer
+            .lock()
+            .await
+            .send(Message::Text(command.to_string().into()))
+            .await;
+        if sent.is_err() {
+            self.forget(id);
+            return Err(BrowserError::Closed);
+        }
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(BrowserError::Protocol(format!("{method}: {message}"))),
+            Ok(Err(_)) => Err(BrowserError::Closed),
+            Err(_) => {
+                self.forget(id);
+                Err(BrowserError::Timeout(method.to_string()))
+            }
+        }
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+}
+
+impl Drop for CdpBrowser {
+    fn drop(&mut self) {
+        // The child is killed on drop; the profile is throwaway.
+        let _ = std::fs::remove_dir_all(&self.profile_dir);
+    }
+}
+
+fn spawn_browser(
+    executable: Option<&str>,
+    profile_dir: &std::path::Path,
+) -> Result<Child, BrowserError> {
+    let candidates: Vec<&str> = match executable {
+        Some(executable) => vec![executable],
+        None => DEFAULT_EXECUTABLES.to_vec(),
+    };
+    let mut last_error = String::new();
+    for candidate in candidates {
+        let spawned = Command::new(candidate)
+            .arg("--headless=new")
+            .arg("--disable-gpu")
+            .arg("--no-first-run")
+            .arg("--no-default-browser-check")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile_dir.display()))
+            .arg("about:blank")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        match spawned {
+            Ok(child) => return Ok(child),
+            Err(e) => last_error = format!("{candidate}: {e}"),
+        }
+    }
+    Err(BrowserError::Launch(last_error))
+}
+
+async fn read_devtools_url(stderr: tokio::process::ChildStderr) -> Result<String, BrowserError> {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(url) = parse_devtools_line(&line) {
+            // Keep draining stderr so the browser never blocks on a full pipe.
+            tokio::spawn(async move { while let Ok(Some(_)) = lines.next_line().await {} });
+            return Ok(url);
+        }
+    }
+    Err(BrowserError::Launch(
+        "browser exited before exposing DevTools".to_string(),
+    ))
+}
+
+fn parse_devtools_line(line: &str) -> Option<String> {
+    let url = line.trim().strip_prefix(DEVTOOLS_PREFIX)?;
+    url.starts_with("ws://").then(|| url.to_string())
+}
+
+/// Split a CDP frame into `(id, result)`; events (no `id`) yield `None`.
+fn parse_response(text: &str) -> Option<(u64, Result<Value, String>)> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    let id = value.get("id")?.as_u64()?;
+    if let Some(error) = value.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error")
+            .to_string();
+        return Some((id, Err(message)));
+    }
+    Some((id, Ok(value.get("result").cloned().unwrap_or(Value::Null))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devtools_line() {
+        assert_eq!(
+            parse_devtools_line(
+                "DevTools listening on ws://127.0.0.1:40213/devtools/browser/abc\n"
+            ),
+            Some("ws://127.0.0.1:40213/devtools/browser/abc".to_string())
+        );
+        assert_eq!(parse_devtools_line("[0101/000000.0:ERROR] gpu"), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let (id, result) = parse_response(r#"{"id":3,"result":{"frameId":"F"}}"#).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(result.unwrap()["frameId"], "F");
+
+        let (_, result) =
+            parse_response(r#"{"id":4,"error":{"code":-32000,"message":"No node"}}"#).unwrap();
+        assert_eq!(result.unwrap_err(), "No node");
+
+        assert!(parse_response(r#"{"method":"Page.loadEventFired","params":{}}"#).is_none());
+    }
+}
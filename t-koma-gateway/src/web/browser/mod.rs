@@ -0,0 +1,494 @@
+//! Headless browser for JS-heavy pages the HTTP fetcher cannot render.
+//!
+//! One Chromium process is shared by the whole gateway and launched on first
+//! use. Each chat session gets its own tab, so a GHOST can navigate, click and
+//! then extract from the same page across tool calls.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use t_koma_core::WebDomainPolicy;
+use t_koma_core::config::BrowserSettings;
+use tokio::sync::Mutex;
+
+pub mod cdp;
+
+use cdp::CdpBrowser;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserPage {
+    pub url: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BrowserError {
+    #[error("browser launch failed: {0}")]
+    Launch(String),
+    #[error("browser connection closed")]
+    Closed,
+    #[error("browser command timed out: {0}")]
+    Timeout(String),
+    #[error("browser protocol error: {0}")]
+    Protocol(String),
+    #[error("page script failed: {0}")]
+    Script(String),
+    #[error("invalid url")]
+    InvalidUrl,
+    #[error("domain not allowed: {0}")]
+    DomainNotAllowed(String),
+    #[error("no page open, navigate first")]
+    NoPage,
+    #[error("no element matches selector: {0}")]
+    ElementNotFound(String),
+}
+
+#[derive(Default)]
+struct BrowserState {
+    browser: Option<Arc<CdpBrowser>>,
+    /// Chat session key -> CDP session attached to that session's tab.
+    pages: HashMap<String, String>,
+}
+
+/// Browser actions scoped to one chat session's tab.
+pub struct BrowserService {
+    settings: BrowserSettings,
+    policy: Option<WebDomainPolicy>,
+    state: &'static Mutex<BrowserState>,
+}
+
+impl BrowserService {
+    pub fn new(settings: BrowserSettings) -> Self {
+        static STATE: OnceLock<Mutex<BrowserState>> = OnceLock::new();
+        let state = STATE.get_or_init(|| Mutex::new(BrowserState::default()));
+        Self {
+            settings,
+            policy: None,
+            state,
+        }
+    }
+
+    /// Also keep the tab off domains `policy` blocks.
+    pub fn with_domain_policy(mut self, policy: WebDomainPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.timeout_seconds)
+    }
+
+    /// Reject non-http(s) URLs and hosts outside the allowlist or blocked by
+    /// the domain policy.
+    pub fn check_url(&self, url: &str) -> Result<(), BrowserError> {
+        let parsed = reqwest::Url::parse(url).map_err(|_| BrowserError::InvalidUrl)?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(BrowserError::InvalidUrl);
+        }
+        let host = parsed.host_str().ok_or(BrowserError::InvalidUrl)?;
+        if !self.settings.allows_host(host)
+            || self
+                .policy
+                .as_ref()
+                .is_some_and(|policy| !policy.allows_host(host))
+        {
+            return Err(BrowserError::DomainNotAllowed(host.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Open `url` in the session's tab and return its text.
+    pub async fn navigate(&self, key: &str, url: &str) -> Result<BrowserPage, BrowserError> {
+        self.check_url(url)?;
+        let mut state = self.state.lock().await;
+        let (browser, session) = self.open_page(&mut state, key).await?;
+
+        let result = browser
+            .call("Page.navigate", json!({"url": url}), Some(&session))
+            .await?;
+        if let Some(error) = result.get("errorText").and_then(Value::as_str) {
+            return Err(BrowserError::Protocol(format!(
+                "navigation failed: {error}"
+            )));
+        }
+        self.wait_for_load(&browser, &session).await?;
+        self.ensure_allowed(&browser, &session).await?;
+        self.read_page(&browser, &session, None).await
+    }
+
+    /// Text of the whole page, or of the first element matching `selector`.
+    pub async fn extract(
+        &self,
+        key: &str,
+        selector: Option<&str>,
+    ) -> Result<BrowserPage, BrowserError> {
+        let state = self.state.lock().await;
+        let (browser, session) = existing_page(&state, key)?;
+        self.read_page(&browser, &session, selector).await
+    }
+
+    /// Click the first element matching `selector` and return the resulting page.
+    pub async fn click(&self, key: &str, selector: &str) -> Result<BrowserPage, BrowserError> {
+        let state = self.state.lock().await;
+        let (browser, session) = existing_page(&state, key)?;
+
+        let selector_json = json!(selector).to_string();
+        let clicked = evaluate(
+            &browser,
+            &session,
+            &format!(
+                "(() => {{ const el = document.querySelector({selector_json}); \
+                 if (!el) return false; el.scrollIntoView({{block: 'center'}}); \
+                 el.click(); return true; }})()"
+            ),
+        )
+        .await?;
+        if clicked != Value::Bool(true) {
+            return Err(BrowserError::ElementNotFound(selector.to_string()));
+        }
+
+        // Give click handlers a moment to start any navigation.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        self.wait_for_load(&browser, &session).await?;
+        self.ensure_allowed(&browser, &session).await?;
+        self.read_page(&browser, &session, None).await
+    }
+
+    /// PNG screenshot of the viewport, or of the whole page.
+    pub async fn screenshot(&self, key: &str, full_page: bool) -> Result<Vec<u8>, BrowserError> {
+        let state = self.state.lock().await;
+        let (browser, session) = existing_page(&state, key)?;
+        let result = browser
+            .call(
+                "Page.captureScreenshot",
+                json!({"format": "png", "captureBeyondViewport": full_page}),
+                Some(&session),
+            )
+            .await?;
+        let data = result
+            .get("data")
+            .and_then(Value::as_str)
+            .ok_or_else(|| BrowserError::Protocol("screenshot returned no data".to_string()))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| BrowserError::Protocol(format!("invalid screenshot data: {e}")))
+    }
+
+    /// Load `url` in a throwaway tab, wait for scripts to settle and return
+    /// its text and the HTTP status of the document. Used by `web_fetch` in
+    /// `render` mode; the tab is closed afterwards.
+    pub async fn render(&self, url: &str) -> Result<(BrowserPage, u16), BrowserError> {
+        self.check_url(url)?;
+        let mut state = self.state.lock().await;
+        let browser = self.launched(&mut state).await?;
+        let (target_id, session) = new_tab(&browser).await?;
+
+        let result = self.render_in(&browser, &session, url).await;
+        let _ = browser
+            .call("Target.closeTarget", json!({"targetId": target_id}), None)
+            .await;
+        result
+    }
+
+    async fn render_in(
+        &self,
+        browser: &CdpBrowser,
+        session: &str,
+        url: &str,
+    ) -> Result<(BrowserPage, u16), BrowserError> {
+        let result = browser
+            .call("Page.navigate", json!({"url": url}), Some(session))
+            .await?;
+        if let Some(error) = result.get("errorText").and_then(Value::as_str) {
+            return Err(BrowserError::Protocol(format!(
+                "navigation failed: {error}"
+            )));
+        }
+        self.wait_for_load(browser, session).await?;
+        self.ensure_allowed(browser, session).await?;
+        self.wait_for_settled_text(browser, session).await?;
+
+        let status = evaluate(
+            browser,
+            session,
+            "(performance.getEntriesByType('navigation')[0] || {}).responseStatus || 0",
+        )
+        .await?
+        .as_u64()
+        .filter(|status| (100..600).contains(status))
+        .unwrap_or(200) as u16;
+        let page = self.read_page(browser, session, None).await?;
+        Ok((page, status))
+    }
+
+    /// Single-page apps keep rendering after `load`; wait until the page text
+    /// stops changing (or the timeout passes).
+    async fn wait_for_settled_text(
+        &self,
+        browser: &CdpBrowser,
+        session: &str,
+    ) -> Result<(), BrowserError> {
+        let deadline = Instant::now() + self.timeout();
+        let mut last_len = None;
+        loop {
+            let len = evaluate(
+                browser,
+                session,
+                "document.body ? document.body.innerText.length : 0",
+            )
+            .await?
+            .as_u64();
+            if len.is_some_and(|len| len > 0) && len == last_len {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Ok(());
+            }
+            last_len = len;
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// URL currently open in the session's tab, if any.
+    pub async fn current_url(&self, key: &str) -> Option<String> {
+        let state = self.state.lock().await;
+        let (browser, session) = existing_page(&state, key).ok()?;
+        evaluate(&browser, &session, "location.href")
+            .await
+            .ok()?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// The shared browser, launched (or relaunched after a crash) as needed.
+    async fn launched(&self, state: &mut BrowserState) -> Result<Arc<CdpBrowser>, BrowserError> {
+        match &state.browser {
+            Some(browser) if browser.is_alive() => Ok(Arc::clone(browser)),
+            _ => {
+                let browser = Arc::new(
+                    CdpBrowser::launch(self.settings.executable.as_deref(), self.timeout()).await?,
+                );
+                state.browser = Some(Arc::clone(&browser));
+                state.pages.clear();
+                Ok(browser)
+            }
+        }
+    }
+
+    /// Session tab, launching the browser and creating the tab as needed.
+    async fn open_page(
+        &self,
+        state: &mut BrowserState,
+        key: &str,
+    ) -> Result<(Arc<CdpBrowser>, String), BrowserError> {
+        let browser = self.launched(state).await?;
+        if let Some(session) = state.pages.get(key) {
+            return Ok((browser, session.clone()));
+        }
+
+        let (_, session) = new_tab(&browser).await?;
+        state.pages.insert(key.to_string(), session.clone());
+        Ok((browser, session))
+    }
+
+    async fn wait_for_load(&self, browser: &CdpBrowser, session: &str) -> Result<(), BrowserError> {
+        let deadline = Instant::now() + self.timeout();
+        loop {
+            let ready = evaluate(browser, session, "document.readyState").await?;
+            if ready == "complete" {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(BrowserError::Timeout("page load".to_string()));
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    /// Leave the page (to `about:blank`) if a redirect or click took it off the allowlist.
+    async fn ensure_allowed(
+        &self,
+        browser: &CdpBrowser,
+        session: &str,
+    ) -> Result<(), BrowserError> {
+        let url = evaluate(browser, session, "location.href").await?;
+        let url = url.as_str().unwrap_or_default();
+        match self.check_url(url) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let _ = browser
+                    .call(
+                        "Page.navigate",
+                        json!({"url": "about:blank"}),
+                        Some(session),
+                    )
+                    .await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn read_page(
+        &self,
+        browser: &CdpBrowser,
+        session: &str,
+        selector: Option<&str>,
+    ) -> Result<BrowserPage, BrowserError> {
+        let selector_json = json!(selector).to_string();
+        let page = evaluate(
+            browser,
+            session,
+            &format!(
+                "(() => {{ const sel = {selector_json}; \
+                 const el = sel === null ? document.body : document.querySelector(sel); \
+                 return {{url: location.href, title: document.title, \
+                 text: el ? el.innerText : null}}; }})()"
+            ),
+        )
+        .await?;
+
+        let text = match (page.get("text").and_then(Value::as_str), selector) {
+            (Some(text), _) => text,
+            (None, Some(selector)) => return Err(BrowserError::ElementNotFound(selector.into())),
+            (None, None) => "",
+        };
+        let (content, truncated) = trim_text(text, self.settings.max_chars);
+        Ok(BrowserPage {
+            url: string_field(&page, "url"),
+            title: string_field(&page, "title"),
+            content: Some(content),
+            truncated,
+        })
+    }
+}
+
+/// Open a blank tab and attach to it. Returns the target and session IDs.
+async fn new_tab(browser: &CdpBrowser) -> Result<(String, String), BrowserError> {
+    let target = browser
+        .call("Target.createTarget", json!({"url": "about:blank"}), None)
+        .await?;
+    let target_id = target
+        .get("targetId")
+        .and_then(Value::as_str)
+        .ok_or_else(|| BrowserError::Protocol("createTarget returned no targetId".into()))?
+        .to_string();
+    let attached = browser
+        .call(
+            "Target.attachToTarget",
+            json!({"targetId": target_id, "flatten": true}),
+            None,
+        )
+        .await?;
+    let session = attached
+        .get("sessionId")
+        .and_then(Value::as_str)
+        .ok_or_else(|| BrowserError::Protocol("attachToTarget returned no sessionId".into()))?
+        .to_string();
+    Ok((target_id, session))
+}
+
+fn existing_page(
+    state: &BrowserState,
+    key: &str,
+) -> Result<(Arc<CdpBrowser>, String), BrowserError> {
+    let browser = state
+        .browser
+        .as_ref()
+        .filter(|browser| browser.is_alive())
+        .ok_or(BrowserError::NoPage)?;
+    let session = state.pages.get(key).ok_or(BrowserError::NoPage)?;
+    Ok((Arc::clone(browser), session.clone()))
+}
+
+async fn evaluate(
+    browser: &CdpBrowser,
+    session: &str,
+    expression: &str,
+) -> Result<Value, BrowserError> {
+    let result = browser
+        .call(
+            "Runtime.evaluate",
+            json!({"expression": expression, "returnByValue": true, "awaitPromise": true}),
+            Some(session),
+        )
+        .await?;
+    if let Some(details) = result.get("exceptionDetails") {
+        let message = details
+            .pointer("/exception/description")
+            .or_else(|| details.get("text"))
+            .and_then(Value::as_str)
+            .unwrap_or("unknown exception");
+        return Err(BrowserError::Script(message.to_string()));
+    }
+    Ok(result
+        .pointer("/result/value")
+        .cloned()
+        .unwrap_or(Value::Null))
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn trim_text(text: &str, max_chars: usize) -> (String, bool) {
+    let mut chars = text.chars();
+    let trimmed: String = chars.by_ref().take(max_chars).collect();
+    (trimmed, chars.next().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(allowed: &[&str]) -> BrowserService {
+        BrowserService::new(BrowserSettings {
+            allowed_domains: allowed.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_check_url_enforces_scheme_and_allowlist() {
+        let service = service(&["example.com"]);
+        assert!(service.check_url("https://docs.example.com/page").is_ok());
+        assert!(matches!(
+            service.check_url("https://evil.test/"),
+            Err(BrowserError::DomainNotAllowed(host)) if host == "evil.test"
+        ));
+        assert!(matches!(
+            service.check_url("file:///etc/passwd"),
+            Err(BrowserError::InvalidUrl)
+        ));
+        assert!(matches!(
+            service.check_url("not a url"),
+            Err(BrowserError::InvalidUrl)
+        ));
+    }
+
+    #[test]
+    fn test_trim_text() {
+        assert_eq!(trim_text("héllo", 10), ("héllo".to_string(), false));
+        assert_eq!(trim_text("héllo", 2), ("hé".to_string(), true));
+    }
+
+    #[tokio::test]
+    async fn test_actions_without_page_fail() {
+        let service = service(&[]);
+        assert!(matches!(
+            service.extract("no-such-session", None).await,
+            Err(BrowserError::NoPage)
+        ));
+        assert!(service.current_url("no-such-session").await.is_none());
+    }
+}
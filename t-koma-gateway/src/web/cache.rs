@@ -1,82 +1,203 @@
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{Duration, Instant};
+//! Persistent cache for web search and fetch results.
+//!
+//! Backed by the koma DB's `web_cache` table so repeated research survives
+//! gateway restarts and is shared by every GHOST. Entries older than the
+//! tool's TTL are kept around with their HTTP validators so a fetch can be
+//! revalidated (`304 Not Modified`) instead of downloaded again.
 
-use tokio::sync::RwLock;
+use std::time::Duration;
 
-#[derive(Debug, Clone)]
-struct CacheEntry<V> {
-    value: V,
-    inserted: Instant,
+use chrono::Utc;
+use serde::{Serialize, de::DeserializeOwned};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use t_koma_core::WebCacheSettings;
+use t_koma_db::{WebCacheEntry, WebCacheRepository};
+
+/// HTTP validators of a cached response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
+impl CacheValidators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Result of a cache lookup.
 #[derive(Debug)]
-pub struct TimedCache<K, V> {
-    ttl_ms: AtomicU64,
-    map: RwLock<HashMap<K, CacheEntry<V>>>,
+pub enum CacheLookup<V> {
+    /// Within the TTL; use as is.
+    Fresh(V),
+    /// Past the TTL but revalidatable with the origin.
+    Stale {
+        value: V,
+        validators: CacheValidators,
+    },
+    Miss,
+}
+
+/// One tool's view of the shared web cache.
+pub struct WebCache {
+    pool: SqlitePool,
+    kind: &'static str,
+    ttl: Duration,
+    limits: WebCacheSettings,
 }
 
-impl<K, V> TimedCache<K, V>
-where
-    K: Eq + Hash + Clone,
-    V: Clone,
-{
-    pub fn new(ttl: Duration) -> Self {
+impl WebCache {
+    pub fn new(
+        pool: SqlitePool,
+        kind: &'static str,
+        ttl: Duration,
+        limits: WebCacheSettings,
+    ) -> Self {
         Self {
-            ttl_ms: AtomicU64::new(ttl.as_millis() as u64),
-            map: RwLock::new(HashMap::new()),
+            pool,
+            kind,
+            ttl,
+            limits,
         }
     }
 
-    pub fn set_ttl(&self, ttl: Duration) {
-        self.ttl_ms.store(ttl.as_millis() as u64, Ordering::Relaxed);
-    }
+    pub async fn lookup<V: DeserializeOwned>(&self, key: &str) -> CacheLookup<V> {
+        let now = Utc::now().timestamp();
+        let entry = match WebCacheRepository::get(&self.pool, self.kind, key, now).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return CacheLookup::Miss,
+            Err(err) => {
+                warn!("web cache ({}): lookup failed: {err}", self.kind);
+                return CacheLookup::Miss;
+            }
+        };
+        let Ok(value) = serde_json::from_str::<V>(&entry.value) else {
+            return CacheLookup::Miss;
+        };
 
-    fn ttl(&self) -> Duration {
-        Duration::from_millis(self.ttl_ms.load(Ordering::Relaxed))
+        if now - entry.stored_at <= self.ttl.as_secs() as i64 {
+            return CacheLookup::Fresh(value);
+        }
+        let validators = CacheValidators {
+            etag: entry.etag,
+            last_modified: entry.last_modified,
+        };
+        if validators.is_empty() {
+            CacheLookup::Miss
+        } else {
+            CacheLookup::Stale { value, validators }
+        }
     }
 
-    pub async fn get(&self, key: &K) -> Option<V> {
-        let ttl = self.ttl();
-        let map = self.map.read().await;
-        map.get(key).and_then(|entry| {
-            if entry.inserted.elapsed() <= ttl {
-                Some(entry.value.clone())
-            } else {
-                None
-            }
-        })
+    /// Store `value` and trim the table to the configured size limits.
+    pub async fn store<V: Serialize>(&self, key: &str, value: &V, validators: CacheValidators) {
+        let Ok(serialized) = serde_json::to_string(value) else {
+            return;
+        };
+        let now = Utc::now().timestamp();
+        let entry = WebCacheEntry {
+            kind: self.kind.to_string(),
+            cache_key: key.to_string(),
+            value: serialized,
+            etag: validators.etag,
+            last_modified: validators.last_modified,
+            stored_at: now,
+            accessed_at: now,
+        };
+        if let Err(err) = WebCacheRepository::put(&self.pool, &entry).await {
+            warn!("web cache ({}): store failed: {err}", self.kind);
+            return;
+        }
+
+        let max_entries = (self.limits.max_entries > 0).then_some(self.limits.max_entries);
+        let max_total_bytes =
+            (self.limits.max_total_kib > 0).then(|| i64::from(self.limits.max_total_kib) * 1024);
+        if let Err(err) = WebCacheRepository::evict(&self.pool, max_entries, max_total_bytes).await
+        {
+            warn!("web cache ({}): eviction failed: {err}", self.kind);
+        }
     }
 
-    pub async fn set(&self, key: K, value: V) {
-        let mut map = self.map.write().await;
-        map.insert(
-            key,
-            CacheEntry {
-                value,
-                inserted: Instant::now(),
-            },
-        );
+    /// Restart the TTL of an entry the origin reported unchanged.
+    pub async fn mark_revalidated(&self, key: &str) {
+        let now = Utc::now().timestamp();
+        if let Err(err) =
+            WebCacheRepository::mark_revalidated(&self.pool, self.kind, key, now).await
+        {
+            warn!(
+                "web cache ({}): revalidation update failed: {err}",
+                self.kind
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use t_koma_db::test_helpers::create_test_pool;
 
     #[tokio::test]
     async fn test_cache_get_set() {
-        let cache = TimedCache::new(Duration::from_millis(50));
-        cache.set("key", "value").await;
-        assert_eq!(cache.get(&"key").await, Some("value"));
+        let db = create_test_pool().await.unwrap();
+        let cache = WebCache::new(
+            db.pool().clone(),
+            "search",
+            Duration::from_secs(60),
+            WebCacheSettings::default(),
+        );
+
+        assert!(matches!(
+            cache.lookup::<String>("key").await,
+            CacheLookup::Miss
+        ));
+        cache
+            .store("key", &"value".to_string(), CacheValidators::default())
+            .await;
+        assert!(matches!(
+            cache.lookup::<String>("key").await,
+            CacheLookup::Fresh(value) if value == "value"
+        ));
     }
 
     #[tokio::test]
-    async fn test_cache_expiry() {
-        let cache = TimedCache::new(Duration::from_millis(10));
-        cache.set("key", "value").await;
-        tokio::time::sleep(Duration::from_millis(20)).await;
-        assert_eq!(cache.get(&"key").await, None);
+    async fn test_cache_expiry_keeps_revalidatable_entries() {
+        let db = create_test_pool().await.unwrap();
+        let cache = WebCache::new(
+            db.pool().clone(),
+            "fetch",
+            Duration::ZERO,
+            WebCacheSettings::default(),
+        );
+
+        cache
+            .store("plain", &"value".to_string(), CacheValidators::default())
+            .await;
+        let validators = CacheValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+        cache
+            .store("tagged", &"value".to_string(), validators.clone())
+            .await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        assert!(matches!(
+            cache.lookup::<String>("plain").await,
+            CacheLookup::Miss
+        ));
+        match cache.lookup::<String>("tagged").await {
+            CacheLookup::Stale {
+                value,
+                validators: found,
+            } => {
+                assert_eq!(value, "value");
+                assert_eq!(found, validators);
+            }
+            other => panic!("expected stale entry, got {other:?}"),
+        }
     }
 }
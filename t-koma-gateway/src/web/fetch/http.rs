@@ -1,9 +1,11 @@
 use std::time::Duration;
 
-use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use url::Url;
 
-use super::{FetchError, FetchProvider, WebFetchRequest, WebFetchResponse};
+use super::{FetchError, FetchOutcome, FetchProvider, WebFetchRequest, WebFetchResponse};
+use crate::web::cache::CacheValidators;
 
 #[derive(Debug, Clone)]
 pub struct HttpFetchProvider {
@@ -39,6 +41,19 @@ impl HttpFetchProvider {
             .map(|value| value.split(';').next().unwrap_or(value).trim().to_string())
     }
 
+    fn parse_validators(headers: &reqwest::header::HeaderMap) -> CacheValidators {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        CacheValidators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
     fn is_text_content(content_type: &str) -> bool {
         content_type.starts_with("text/")
             || content_type == "application/json"
@@ -75,23 +90,38 @@ impl HttpFetchProvider {
 
 #[async_trait::async_trait]
 impl FetchProvider for HttpFetchProvider {
-    async fn fetch(&self, request: &WebFetchRequest) -> Result<WebFetchResponse, FetchError> {
+    async fn fetch(
+        &self,
+        request: &WebFetchRequest,
+        validators: Option<&CacheValidators>,
+    ) -> Result<FetchOutcome, FetchError> {
         let parsed = reqwest::Url::parse(&request.url).map_err(|_| FetchError::InvalidUrl)?;
         match parsed.scheme() {
             "http" | "https" => {}
             _ => return Err(FetchError::InvalidUrl),
         }
 
-        let response = self
-            .client
-            .get(parsed)
-            .timeout(self.timeout)
+        let mut builder = self.client.get(parsed).timeout(self.timeout);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                builder = builder.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                builder = builder.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = builder
             .send()
             .await
             .map_err(|e| FetchError::RequestFailed(e.to_string()))?;
 
+        if validators.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(FetchOutcome::NotModified);
+        }
+
         let status = response.status().as_u16();
         let content_type = Self::parse_content_type(response.headers());
+        let validators = Self::parse_validators(response.headers());
 
         if let Some(ref ct) = content_type
             && !Self::is_text_content(ct)
@@ -130,13 +160,16 @@ impl FetchProvider for HttpFetchProvider {
 
         let (content, truncated) = Self::trim_content(content, max_chars);
 
-        Ok(WebFetchResponse {
-            provider: "http".to_string(),
-            url: request.url.clone(),
-            status,
-            content_type,
-            content,
-            truncated,
+        Ok(FetchOutcome::Fetched {
+            response: WebFetchResponse {
+                provider: "http".to_string(),
+                url: request.url.clone(),
+                status,
+                content_type,
+                content,
+                truncated,
+            },
+            validators,
         })
     }
 }
@@ -1,11 +1,11 @@
-use std::sync::OnceLock;
-use std::time::Duration;
-
 use serde::{Deserialize, Serialize};
+use t_koma_core::WebDomainPolicy;
 
-use crate::web::cache::TimedCache;
+use crate::web::cache::{CacheLookup, CacheValidators, WebCache};
+use crate::web::policy::blocked_host;
 
 pub mod http;
+pub mod render;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebFetchRequest {
@@ -38,39 +38,108 @@ pub enum FetchError {
     InvalidUrl,
     #[error("unsupported content type: {0}")]
     UnsupportedContentType(String),
+    #[error("domain blocked by policy: {0}")]
+    DomainBlocked(String),
     #[error("request failed: {0}")]
     RequestFailed(String),
 }
 
+/// Outcome of a (possibly conditional) fetch.
+#[derive(Debug)]
+pub enum FetchOutcome {
+    Fetched {
+        response: WebFetchResponse,
+        validators: CacheValidators,
+    },
+    /// The origin answered `304 Not Modified` to a conditional request.
+    NotModified,
+}
+
 #[async_trait::async_trait]
 pub trait FetchProvider: Send + Sync {
-    async fn fetch(&self, request: &WebFetchRequest) -> Result<WebFetchResponse, FetchError>;
+    /// Fetch `request`; with `validators`, ask the origin to answer
+    /// `304 Not Modified` if the cached copy is still current.
+    async fn fetch(
+        &self,
+        request: &WebFetchRequest,
+        validators: Option<&CacheValidators>,
+    ) -> Result<FetchOutcome, FetchError>;
 }
 
 pub struct WebFetchService {
     provider: Box<dyn FetchProvider>,
-    cache: &'static TimedCache<String, WebFetchResponse>,
+    cache: Option<WebCache>,
+    policy: Option<WebDomainPolicy>,
 }
 
 impl WebFetchService {
-    pub fn new(provider: Box<dyn FetchProvider>, cache_ttl: Duration) -> Self {
-        static CACHE: OnceLock<TimedCache<String, WebFetchResponse>> = OnceLock::new();
-        let cache = CACHE.get_or_init(|| TimedCache::new(cache_ttl));
-        cache.set_ttl(cache_ttl);
-        Self { provider, cache }
+    /// Without a `cache` every request goes to the origin.
+    pub fn new(provider: Box<dyn FetchProvider>, cache: Option<WebCache>) -> Self {
+        Self {
+            provider,
+            cache,
+            policy: None,
+        }
+    }
+
+    /// Refuse URLs on domains `policy` blocks, including pages that ended
+    /// up on such a domain.
+    pub fn with_domain_policy(mut self, policy: WebDomainPolicy) -> Self {
+        self.policy = Some(policy);
+        self
     }
 
     pub async fn fetch(&self, request: WebFetchRequest) -> Result<WebFetchResponse, FetchError> {
+        self.check_domain(&request.url)?;
+        let response = self.fetch_cached(request).await?;
+        self.check_domain(&response.url)?;
+        Ok(response)
+    }
+
+    fn check_domain(&self, url: &str) -> Result<(), FetchError> {
+        match self.policy.as_ref().and_then(|p| blocked_host(p, url)) {
+            Some(host) => Err(FetchError::DomainBlocked(host)),
+            None => Ok(()),
+        }
+    }
+
+    async fn fetch_cached(&self, request: WebFetchRequest) -> Result<WebFetchResponse, FetchError> {
         let cache_key = format!(
             "{}|{:?}|{:?}|raw={}",
             request.url, request.mode, request.max_chars, request.raw
         );
-        if let Some(cached) = self.cache.get(&cache_key).await {
-            return Ok(cached);
-        }
+        let Some(cache) = &self.cache else {
+            return match self.provider.fetch(&request, None).await? {
+                FetchOutcome::Fetched { response, .. } => Ok(response),
+                FetchOutcome::NotModified => Err(FetchError::RequestFailed(
+                    "unexpected 304 Not Modified".to_string(),
+                )),
+            };
+        };
 
-        let response = self.provider.fetch(&request).await?;
-        self.cache.set(cache_key, response.clone()).await;
-        Ok(response)
+        let (stale, validators) = match cache.lookup::<WebFetchResponse>(&cache_key).await {
+            CacheLookup::Fresh(cached) => return Ok(cached),
+            CacheLookup::Stale { value, validators } => (Some(value), Some(validators)),
+            CacheLookup::Miss => (None, None),
+        };
+
+        match self.provider.fetch(&request, validators.as_ref()).await? {
+            FetchOutcome::Fetched {
+                response,
+                validators,
+            } => {
+                cache.store(&cache_key, &response, validators).await;
+                Ok(response)
+            }
+            FetchOutcome::NotModified => match stale {
+                Some(cached) => {
+                    cache.mark_revalidated(&cache_key).await;
+                    Ok(cached)
+                }
+                None => Err(FetchError::RequestFailed(
+                    "unexpected 304 Not Modified".to_string(),
+                )),
+            },
+        }
     }
 }
@@ -0,0 +1,275 @@
+//! `render` mode: pages rendered with JavaScript, for single-page apps whose
+//! HTML is an empty shell.
+//!
+//! Rendering goes through the shared headless browser or a Jina-style reader
+//! API (`GET {reader_url}{page_url}`, returning the page as markdown). When it
+//! fails the page is fetched over plain HTTP instead, so the tool still
+//! answers with whatever the server sent.
+
+use std::time::Duration;
+
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use t_koma_core::config::BrowserSettings;
+use tracing::warn;
+
+use super::{
+    FetchError, FetchOutcome, FetchProvider, WebFetchRequest, WebFetchResponse,
+    http::HttpFetchProvider,
+};
+use crate::web::browser::BrowserService;
+use crate::web::cache::CacheValidators;
+
+/// Where rendered pages come from.
+#[derive(Debug, Clone)]
+pub enum Renderer {
+    Browser(BrowserSettings),
+    Reader {
+        client: reqwest::Client,
+        base_url: String,
+        api_key: Option<String>,
+    },
+}
+
+impl Renderer {
+    pub fn reader(base_url: String, api_key: Option<String>) -> Result<Self, FetchError> {
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| FetchError::RequestFailed(e.to_string()))?;
+        Ok(Self::Reader {
+            client,
+            base_url,
+            api_key,
+        })
+    }
+}
+
+pub struct RenderFetchProvider {
+    renderer: Renderer,
+    fallback: HttpFetchProvider,
+    timeout: Duration,
+    default_max_chars: usize,
+}
+
+impl RenderFetchProvider {
+    pub fn new(
+        renderer: Renderer,
+        fallback: HttpFetchProvider,
+        timeout: Duration,
+        default_max_chars: usize,
+    ) -> Self {
+        Self {
+            renderer,
+            fallback,
+            timeout,
+            default_max_chars,
+        }
+    }
+
+    async fn render(
+        &self,
+        request: &WebFetchRequest,
+        max_chars: usize,
+    ) -> Result<WebFetchResponse, FetchError> {
+        let parsed = reqwest::Url::parse(&request.url).map_err(|_| FetchError::InvalidUrl)?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(FetchError::InvalidUrl);
+        }
+
+        match &self.renderer {
+            Renderer::Browser(settings) => {
+                let browser = BrowserService::new(BrowserSettings {
+                    max_chars,
+                    timeout_seconds: self.timeout.as_secs().max(1),
+                    ..settings.clone()
+                });
+                let (page, status) = browser
+                    .render(&request.url)
+                    .await
+                    .map_err(|e| FetchError::RequestFailed(e.to_string()))?;
+                let content = page.content.unwrap_or_default();
+                let content = if page.title.is_empty() {
+                    content
+                } else {
+                    format!("# {}\n\n{content}", page.title)
+                };
+                let (content, truncated) = trim_content(&content, max_chars);
+                Ok(WebFetchResponse {
+                    provider: "render".to_string(),
+                    url: page.url,
+                    status,
+                    content_type: Some("text/html".to_string()),
+                    content,
+                    truncated: truncated || page.truncated,
+                })
+            }
+            Renderer::Reader {
+                client,
+                base_url,
+                api_key,
+            } => {
+                let mut builder = client
+                    .get(format!("{base_url}{}", request.url))
+                    .timeout(self.timeout)
+                    .header("X-Return-Format", "markdown");
+                if let Some(key) = api_key {
+                    builder = builder.header(AUTHORIZATION, format!("Bearer {key}"));
+                }
+                let response = builder
+                    .send()
+                    .await
+                    .map_err(|e| FetchError::RequestFailed(e.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(FetchError::RequestFailed(format!(
+                        "reader API HTTP {status}"
+                    )));
+                }
+                let content_type = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.split(';').next().unwrap_or(value).trim().to_string());
+                let body = response
+                    .text()
+                    .await
+                    .map_err(|e| FetchError::RequestFailed(e.to_string()))?;
+                let (content, truncated) = trim_content(&body.replace('\0', ""), max_chars);
+                Ok(WebFetchResponse {
+                    provider: "reader".to_string(),
+                    url: request.url.clone(),
+                    status: status.as_u16(),
+                    content_type,
+                    content,
+                    truncated,
+                })
+            }
+        }
+    }
+}
+
+fn trim_content(content: &str, max_chars: usize) -> (String, bool) {
+    let mut chars = content.chars();
+    let trimmed: String = chars.by_ref().take(max_chars).collect();
+    (trimmed, chars.next().is_some())
+}
+
+#[async_trait::async_trait]
+impl FetchProvider for RenderFetchProvider {
+    async fn fetch(
+        &self,
+        request: &WebFetchRequest,
+        validators: Option<&CacheValidators>,
+    ) -> Result<FetchOutcome, FetchError> {
+        if request.mode.as_deref() != Some("render") {
+            return self.fallback.fetch(request, validators).await;
+        }
+
+        let max_chars = request.max_chars.unwrap_or(self.default_max_chars);
+        match self.render(request, max_chars).await {
+            // Rendered output depends on scripts, so it has no validators.
+            Ok(response) => Ok(FetchOutcome::Fetched {
+                response,
+                validators: CacheValidators::default(),
+            }),
+            Err(FetchError::InvalidUrl) => Err(FetchError::InvalidUrl),
+            Err(err) => {
+                warn!(
+                    "web_fetch render of {} failed, using plain HTTP: {err}",
+                    request.url
+                );
+                let plain = WebFetchRequest {
+                    mode: Some("markdown".to_string()),
+                    ..request.clone()
+                };
+                self.fallback.fetch(&plain, None).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve each canned response to one connection, in order.
+    async fn serve(responses: Vec<&'static str>) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+        port
+    }
+
+    fn provider(reader_url: String) -> RenderFetchProvider {
+        let timeout = Duration::from_secs(5);
+        RenderFetchProvider::new(
+            Renderer::reader(reader_url, None).unwrap(),
+            HttpFetchProvider::new(timeout, "markdown".to_string(), 1000).unwrap(),
+            timeout,
+            1000,
+        )
+    }
+
+    fn render_request(url: String) -> WebFetchRequest {
+        WebFetchRequest {
+            url,
+            mode: Some("render".to_string()),
+            max_chars: None,
+            raw: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reader_output_is_returned() {
+        let port = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 16\r\n\r\n# Docs\n\nRendered",
+        ])
+        .await;
+        let provider = provider(format!("http://127.0.0.1:{port}/"));
+
+        let outcome = provider
+            .fetch(
+                &render_request("https://docs.example.com/".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        let FetchOutcome::Fetched { response, .. } = outcome else {
+            panic!("expected a fetched page");
+        };
+        assert_eq!(response.provider, "reader");
+        assert_eq!(response.url, "https://docs.example.com/");
+        assert_eq!(response.content, "# Docs\n\nRendered");
+    }
+
+    #[tokio::test]
+    async fn test_failed_render_falls_back_to_http() {
+        let reader = serve(vec![
+            "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\n\r\n",
+        ])
+        .await;
+        let origin = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nplain",
+        ])
+        .await;
+        let provider = provider(format!("http://127.0.0.1:{reader}/"));
+
+        let outcome = provider
+            .fetch(&render_request(format!("http://127.0.0.1:{origin}/")), None)
+            .await
+            .unwrap();
+        let FetchOutcome::Fetched { response, .. } = outcome else {
+            panic!("expected a fetched page");
+        };
+        assert_eq!(response.provider, "http");
+        assert_eq!(response.content, "plain");
+    }
+}
@@ -1,3 +1,7 @@
+pub mod browser;
 pub mod cache;
 pub mod fetch;
+pub mod policy;
+pub mod request;
+pub mod sanitize;
 pub mod search;
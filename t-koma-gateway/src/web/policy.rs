@@ -0,0 +1,33 @@
+//! Domain policy checks shared by web search, `web_fetch` and the browser.
+//!
+//! The policy itself ([`WebDomainPolicy`]) is resolved per ghost from
+//! `[tools.web.domains]`; this module only maps URLs onto it.
+
+use t_koma_core::WebDomainPolicy;
+
+/// Host of `url` when the policy blocks it. URLs without a host are left to
+/// the caller's own validation.
+pub fn blocked_host(policy: &WebDomainPolicy, url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    (!policy.allows_host(host)).then(|| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocked_host() {
+        let policy = WebDomainPolicy {
+            deny: vec!["example.com".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            blocked_host(&policy, "https://www.example.com/page"),
+            Some("www.example.com".to_string())
+        );
+        assert_eq!(blocked_host(&policy, "https://docs.rs/"), None);
+        assert_eq!(blocked_host(&policy, "not a url"), None);
+    }
+}
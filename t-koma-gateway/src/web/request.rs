@@ -0,0 +1,394 @@
+//! Generic HTTP requests to operator-approved hosts.
+//!
+//! Backs the `http_request` tool: GET/POST calls to user APIs (home
+//! automation, internal services). Every URL, including redirect targets,
+//! must be on `[tools.http_request].allowed_domains`; request and response
+//! bodies are capped in size.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use t_koma_core::config::HttpRequestSettings;
+use tokio::time::Instant;
+
+/// Redirect hops followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Headers the client manages itself.
+const RESERVED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", "connection"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Final URL after redirects
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub body: String,
+    pub truncated: bool,
+    /// The deadline passed while the body was streaming; `body` is partial.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("invalid url")]
+    InvalidUrl,
+    #[error("domain not allowed: {0}")]
+    DomainNotAllowed(String),
+    #[error("request body is {size} bytes, limit is {max}")]
+    BodyTooLarge { size: usize, max: usize },
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("GET requests cannot have a body")]
+    BodyNotAllowed,
+    #[error("request failed: {0}")]
+    Failed(String),
+    #[error("no response before the deadline")]
+    TimedOut,
+}
+
+/// HTTP client bound to the configured allowlist and limits.
+pub struct HttpRequestService {
+    settings: HttpRequestSettings,
+    client: reqwest::Client,
+}
+
+impl HttpRequestService {
+    pub fn new(settings: HttpRequestSettings) -> Result<Self, RequestError> {
+        let redirect_settings = settings.clone();
+        let redirect = reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() > MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match attempt.url().host_str() {
+                Some(host) if redirect_settings.allows_host(host) => attempt.follow(),
+                // Hand the redirect itself back instead of leaving the allowlist.
+                _ => attempt.stop(),
+            }
+        });
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_seconds))
+            .redirect(redirect)
+            .build()
+            .map_err(|e| RequestError::Failed(e.to_string()))?;
+        Ok(Self { settings, client })
+    }
+
+    /// Reject non-http(s) URLs and hosts outside the allowlist.
+    pub fn check_url(&self, url: &str) -> Result<reqwest::Url, RequestError> {
+        let parsed = reqwest::Url::parse(url).map_err(|_| RequestError::InvalidUrl)?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(RequestError::InvalidUrl);
+        }
+        let host = parsed.host_str().ok_or(RequestError::InvalidUrl)?;
+        if !self.settings.allows_host(host) {
+            return Err(RequestError::DomainNotAllowed(host.to_string()));
+        }
+        Ok(parsed)
+    }
+
+    pub async fn send(&self, request: HttpRequest) -> Result<HttpResponse, RequestError> {
+        self.send_until(request, None).await
+    }
+
+    /// Like [`send`](Self::send), but stop reading the body at `deadline`
+    /// and return what arrived so far with `timed_out` set.
+    pub async fn send_until(
+        &self,
+        request: HttpRequest,
+        deadline: Option<Instant>,
+    ) -> Result<HttpResponse, RequestError> {
+        let url = self.check_url(&request.url)?;
+        let headers = build_headers(&request.headers)?;
+        if let Some(body) = &request.body {
+            if request.method == HttpMethod::Get {
+                return Err(RequestError::BodyNotAllowed);
+            }
+            if body.len() > self.settings.max_request_bytes {
+                return Err(RequestError::BodyTooLarge {
+                    size: body.len(),
+                    max: self.settings.max_request_bytes,
+                });
+            }
+        }
+
+        let builder = match request.method {
+            HttpMethod::Get => self.client.get(url),
+            HttpMethod::Post => self.client.post(url),
+        };
+        let builder = match request.body {
+            Some(body) => builder.body(body),
+            None => builder,
+        };
+        let sending = builder.headers(headers).send();
+        let sent = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, sending)
+                .await
+                .map_err(|_| RequestError::TimedOut)?,
+            None => sending.await,
+        };
+        let mut response = sent.map_err(|e| RequestError::Failed(e.to_string()))?;
+
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let max = self.settings.max_response_bytes;
+        let mut bytes = Vec::new();
+        let mut truncated = false;
+        let mut timed_out = false;
+        loop {
+            let next = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, response.chunk()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        timed_out = true;
+                        break;
+                    }
+                },
+                None => response.chunk().await,
+            };
+            let Some(chunk) = next.map_err(|e| RequestError::Failed(e.to_string()))? else {
+                break;
+            };
+            let room = max - bytes.len();
+            if chunk.len() > room {
+                bytes.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let body = if content_type.as_deref().is_none_or(is_text_content) {
+            String::from_utf8_lossy(&bytes).into_owned()
+        } else {
+            format!("[{} bytes of binary content omitted]", bytes.len())
+        };
+        Ok(HttpResponse {
+            status,
+            url: final_url,
+            content_type,
+            body,
+            truncated,
+            timed_out,
+        })
+    }
+}
+
+fn build_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap, RequestError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(RequestError::InvalidHeader(format!(
+                "{name} is set by the client"
+            )));
+        }
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| RequestError::InvalidHeader(name.clone()))?;
+        let header_value =
+            HeaderValue::from_str(value).map_err(|_| RequestError::InvalidHeader(name.clone()))?;
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+fn is_text_content(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/x-www-form-urlencoded"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn service(allowed: &[&str], max_response_bytes: usize) -> HttpRequestService {
+        HttpRequestService::new(HttpRequestSettings {
+            enabled: true,
+            allowed_domains: allowed.iter().map(|d| d.to_string()).collect(),
+            max_request_bytes: 16,
+            max_response_bytes,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn request(method: HttpMethod, url: &str, body: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method,
+            url: url.to_string(),
+            headers: BTreeMap::new(),
+            body: body.map(str::to_string),
+        }
+    }
+
+    /// Serve each canned response to one connection, in order.
+    async fn serve(responses: Vec<String>) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_rejects_before_sending() {
+        let service = service(&["homeassistant.local"], 1024);
+        assert!(matches!(
+            service.check_url("http://example.com/api"),
+            Err(RequestError::DomainNotAllowed(host)) if host == "example.com"
+        ));
+        assert!(matches!(
+            service.check_url("ftp://homeassistant.local/"),
+            Err(RequestError::InvalidUrl)
+        ));
+        assert!(matches!(
+            service
+                .send(request(
+                    HttpMethod::Post,
+                    "http://homeassistant.local/api",
+                    Some("{\"entity_id\": \"light.kitchen\"}")
+                ))
+                .await,
+            Err(RequestError::BodyTooLarge { max: 16, .. })
+        ));
+        assert!(matches!(
+            service
+                .send(request(
+                    HttpMethod::Get,
+                    "http://homeassistant.local/api",
+                    Some("x")
+                ))
+                .await,
+            Err(RequestError::BodyNotAllowed)
+        ));
+
+        let mut with_host = request(HttpMethod::Get, "http://homeassistant.local/", None);
+        with_host
+            .headers
+            .insert("Host".to_string(), "evil.test".to_string());
+        assert!(matches!(
+            service.send(with_host).await,
+            Err(RequestError::InvalidHeader(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_truncates_and_stops_redirects_off_allowlist() {
+        let body = "{\"state\": \"on\", \"x\": 123}";
+        let port = serve(vec![
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+            "HTTP/1.1 302 Found\r\nLocation: http://evil.test/\r\nContent-Length: 0\r\n\r\n"
+                .to_string(),
+        ])
+        .await;
+        let service = service(&["127.0.0.1"], 10);
+
+        let response = service
+            .send(request(
+                HttpMethod::Get,
+                &format!("http://127.0.0.1:{port}/api/states"),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "{\"state\": ");
+        assert!(response.truncated);
+
+        let response = service
+            .send(request(
+                HttpMethod::Get,
+                &format!("http://127.0.0.1:{port}/redirect"),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 302);
+        assert!(response.url.starts_with("http://127.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_returns_partial_body() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 100\r\n\r\nfirst part")
+                .await;
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+        let service = service(&["127.0.0.1"], 1024);
+
+        let deadline = Instant::now() + Duration::from_millis(300);
+        let response = service
+            .send_until(
+                request(HttpMethod::Get, &format!("http://127.0.0.1:{port}/"), None),
+                Some(deadline),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.body, "first part");
+        assert!(response.timed_out);
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn test_is_text_content() {
+        assert!(is_text_content("application/json; charset=utf-8"));
+        assert!(is_text_content("application/vnd.api+json"));
+        assert!(is_text_content("text/plain"));
+        assert!(!is_text_content("image/png"));
+    }
+}
@@ -152,6 +152,10 @@ impl BraveSearchProvider {
 
 #[async_trait::async_trait]
 impl SearchProvider for BraveSearchProvider {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
     async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
         self.execute_with_backoff(query).await
     }
@@ -0,0 +1,203 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use super::{
+    SearchError, SearchProvider, WebSearchQuery, WebSearchResponse, WebSearchResult,
+    freshness_window, retry_after, wait_for_slot,
+};
+
+static GOOGLE_LAST_REQUEST: OnceLock<Mutex<std::time::Instant>> = OnceLock::new();
+
+fn google_last_request() -> &'static Mutex<std::time::Instant> {
+    GOOGLE_LAST_REQUEST
+        .get_or_init(|| Mutex::new(std::time::Instant::now() - Duration::from_secs(60)))
+}
+
+/// Google Programmable Search (Custom Search JSON API). Returns at most 10
+/// results per request.
+#[derive(Debug, Clone)]
+pub struct GoogleSearchProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    engine_id: String,
+    timeout: Duration,
+    min_interval: Duration,
+}
+
+impl GoogleSearchProvider {
+    pub fn new(
+        api_key: String,
+        engine_id: String,
+        timeout: Duration,
+        min_interval: Duration,
+    ) -> Result<Self, SearchError> {
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: "https://www.googleapis.com/customsearch/v1".to_string(),
+            api_key,
+            engine_id,
+            timeout,
+            min_interval,
+        })
+    }
+
+    async fn execute_request(
+        &self,
+        query: &WebSearchQuery,
+    ) -> Result<WebSearchResponse, SearchError> {
+        wait_for_slot(google_last_request(), self.min_interval).await;
+
+        let mut request = self
+            .client
+            .get(&self.base_url)
+            .timeout(self.timeout)
+            .query(&[
+                ("key", self.api_key.as_str()),
+                ("cx", self.engine_id.as_str()),
+                ("q", query.query.as_str()),
+            ]);
+
+        if let Some(count) = query.count {
+            request = request.query(&[("num", count.clamp(1, 10).to_string())]);
+        }
+        if let Some(country) = query.country.as_ref() {
+            request = request.query(&[("gl", country.to_ascii_lowercase())]);
+        }
+        if let Some(search_lang) = query.search_lang.as_ref() {
+            request = request.query(&[("lr", format!("lang_{search_lang}"))]);
+        }
+        if let Some(window) = query.freshness.as_deref().and_then(freshness_window) {
+            request = request.query(&[("dateRestrict", date_restrict(window))]);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SearchError::RateLimited(retry_after(
+                &response,
+                Duration::from_millis(2000),
+            )));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(SearchError::RequestFailed(format!(
+                "HTTP {}: {}",
+                status, body
+            )));
+        }
+
+        let payload: GoogleSearchResponse = response
+            .json()
+            .await
+            .map_err(|e| SearchError::RequestFailed(format!("JSON parse error: {e}")))?;
+
+        Ok(WebSearchResponse {
+            provider: "google".to_string(),
+            results: normalize_results(payload),
+        })
+    }
+}
+
+fn date_restrict(window: &str) -> &'static str {
+    match window {
+        "day" => "d1",
+        "week" => "w1",
+        "month" => "m1",
+        _ => "y1",
+    }
+}
+
+fn normalize_results(payload: GoogleSearchResponse) -> Vec<WebSearchResult> {
+    payload
+        .items
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| {
+            let url = item.link.filter(|url| !url.is_empty())?;
+            Some(WebSearchResult {
+                title: item.title.unwrap_or_else(|| "(untitled)".to_string()),
+                url,
+                // Google wraps snippets at fixed widths with hard newlines.
+                snippet: item
+                    .snippet
+                    .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|s| !s.is_empty()),
+            })
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for GoogleSearchProvider {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
+        match self.execute_request(query).await {
+            Err(SearchError::RateLimited(delay)) => {
+                sleep(delay).await;
+                self.execute_request(query).await
+            }
+            other => other,
+        }
+    }
+}
+
+// ── Custom Search JSON API response wire types ───────────────────
+
+#[derive(Debug, Deserialize)]
+struct GoogleSearchResponse {
+    /// Absent when the query has no results.
+    #[serde(default)]
+    items: Option<Vec<GoogleResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleResult {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    snippet: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_results() {
+        let payload: GoogleSearchResponse = serde_json::from_str(
+            r#"{"kind":"customsearch#search","items":[
+                {"title":"Rust","link":"https://www.rust-lang.org","snippet":"A language\nempowering everyone"}
+            ]}"#,
+        )
+        .unwrap();
+        let results = normalize_results(payload);
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("A language empowering everyone")
+        );
+
+        let empty: GoogleSearchResponse =
+            serde_json::from_str(r#"{"kind":"customsearch#search"}"#).unwrap();
+        assert!(normalize_results(empty).is_empty());
+    }
+}
@@ -1,12 +1,18 @@
-use std::sync::OnceLock;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
+use t_koma_core::WebDomainPolicy;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
 
-use crate::web::cache::TimedCache;
+use crate::web::cache::{CacheLookup, CacheValidators, WebCache};
+use crate::web::policy::blocked_host;
 
 pub mod brave;
+pub mod google;
 pub mod perplexity;
+pub mod searxng;
+pub mod tavily;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSearchQuery {
@@ -48,25 +54,85 @@ pub enum SearchError {
 
 #[async_trait::async_trait]
 pub trait SearchProvider: Send + Sync {
+    /// Provider name as used in config and in `WebSearchResponse::provider`.
+    fn name(&self) -> &'static str;
+
     async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError>;
 }
 
+/// Coarse recency window shared by providers that only accept day, week,
+/// month or year. Takes Brave-style codes (`pd`, `pw`, `pm`, `py`) as well as
+/// the plain words; anything else (e.g. date ranges) yields `None`.
+pub(crate) fn freshness_window(freshness: &str) -> Option<&'static str> {
+    match freshness.trim().to_ascii_lowercase().as_str() {
+        "pd" | "day" => Some("day"),
+        "pw" | "week" => Some("week"),
+        "pm" | "month" => Some("month"),
+        "py" | "year" => Some("year"),
+        _ => None,
+    }
+}
+
+/// Sleep until `min_interval` has passed since the last request recorded in
+/// `last`, then record this one.
+pub(crate) async fn wait_for_slot(last: &Mutex<Instant>, min_interval: Duration) {
+    let mut last = last.lock().await;
+    let elapsed = last.elapsed();
+    if elapsed < min_interval {
+        sleep(min_interval - elapsed).await;
+    }
+    *last = Instant::now();
+}
+
+/// `Retry-After` of a 429 response, or `fallback` when absent.
+pub(crate) fn retry_after(response: &reqwest::Response, fallback: Duration) -> Duration {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(fallback)
+}
+
 pub struct WebSearchService {
     provider: Box<dyn SearchProvider>,
-    cache: &'static TimedCache<String, WebSearchResponse>,
+    cache: Option<WebCache>,
+    policy: Option<WebDomainPolicy>,
 }
 
 impl WebSearchService {
-    pub fn new(provider: Box<dyn SearchProvider>, cache_ttl: Duration) -> Self {
-        static CACHE: OnceLock<TimedCache<String, WebSearchResponse>> = OnceLock::new();
-        let cache = CACHE.get_or_init(|| TimedCache::new(cache_ttl));
-        cache.set_ttl(cache_ttl);
-        Self { provider, cache }
+    /// Without a `cache` every query goes to the provider.
+    pub fn new(provider: Box<dyn SearchProvider>, cache: Option<WebCache>) -> Self {
+        Self {
+            provider,
+            cache,
+            policy: None,
+        }
+    }
+
+    /// Drop results on domains `policy` blocks.
+    pub fn with_domain_policy(mut self, policy: WebDomainPolicy) -> Self {
+        self.policy = Some(policy);
+        self
     }
 
     pub async fn search(&self, query: WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
+        let mut response = self.search_cached(query).await?;
+        if let Some(policy) = &self.policy {
+            response
+                .results
+                .retain(|result| blocked_host(policy, &result.url).is_none());
+        }
+        Ok(response)
+    }
+
+    /// The cache holds unfiltered results, so ghosts with different policies
+    /// can share it.
+    async fn search_cached(&self, query: WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
         let cache_key = format!(
-            "{}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            "{}|{}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            self.provider.name(),
             query.query,
             query.count,
             query.country,
@@ -75,12 +141,31 @@ impl WebSearchService {
             query.freshness
         );
 
-        if let Some(cached) = self.cache.get(&cache_key).await {
+        if let Some(cache) = &self.cache
+            && let CacheLookup::Fresh(cached) = cache.lookup(&cache_key).await
+        {
             return Ok(cached);
         }
 
         let response = self.provider.search(&query).await?;
-        self.cache.set(cache_key, response.clone()).await;
+        if let Some(cache) = &self.cache {
+            cache
+                .store(&cache_key, &response, CacheValidators::default())
+                .await;
+        }
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freshness_window() {
+        assert_eq!(freshness_window("pd"), Some("day"));
+        assert_eq!(freshness_window("Week"), Some("week"));
+        assert_eq!(freshness_window("py"), Some("year"));
+        assert_eq!(freshness_window("2024-01-01to2024-02-01"), None);
+    }
+}
@@ -147,6 +147,10 @@ impl PerplexitySearchProvider {
 
 #[async_trait::async_trait]
 impl SearchProvider for PerplexitySearchProvider {
+    fn name(&self) -> &'static str {
+        "perplexity"
+    }
+
     async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
         self.execute_with_backoff(query).await
     }
@@ -0,0 +1,179 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use super::{
+    SearchError, SearchProvider, WebSearchQuery, WebSearchResponse, WebSearchResult,
+    freshness_window, retry_after, wait_for_slot,
+};
+
+static SEARXNG_LAST_REQUEST: OnceLock<Mutex<std::time::Instant>> = OnceLock::new();
+
+fn searxng_last_request() -> &'static Mutex<std::time::Instant> {
+    SEARXNG_LAST_REQUEST
+        .get_or_init(|| Mutex::new(std::time::Instant::now() - Duration::from_secs(60)))
+}
+
+/// A self-hosted SearxNG instance. The instance must have `json` listed in
+/// `search.formats` of its `settings.yml`. SearxNG has no result count
+/// parameter, so results are truncated locally.
+#[derive(Debug, Clone)]
+pub struct SearxngSearchProvider {
+    client: reqwest::Client,
+    base_url: String,
+    timeout: Duration,
+    min_interval: Duration,
+}
+
+impl SearxngSearchProvider {
+    pub fn new(
+        instance_url: &str,
+        timeout: Duration,
+        min_interval: Duration,
+    ) -> Result<Self, SearchError> {
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: format!("{}/search", instance_url.trim_end_matches('/')),
+            timeout,
+            min_interval,
+        })
+    }
+
+    async fn execute_request(
+        &self,
+        query: &WebSearchQuery,
+    ) -> Result<WebSearchResponse, SearchError> {
+        wait_for_slot(searxng_last_request(), self.min_interval).await;
+
+        let mut request = self
+            .client
+            .get(&self.base_url)
+            .timeout(self.timeout)
+            .query(&[("q", query.query.as_str()), ("format", "json")]);
+
+        if let Some(search_lang) = query.search_lang.as_ref() {
+            request = request.query(&[("language", search_lang)]);
+        }
+        if let Some(window) = query.freshness.as_deref().and_then(freshness_window) {
+            request = request.query(&[("time_range", window)]);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SearchError::RateLimited(retry_after(
+                &response,
+                Duration::from_millis(2000),
+            )));
+        }
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(SearchError::RequestFailed(
+                "HTTP 403: the SearxNG instance does not allow the json format \
+                 (add it to search.formats in settings.yml)"
+                    .to_string(),
+            ));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(SearchError::RequestFailed(format!(
+                "HTTP {}: {}",
+                status, body
+            )));
+        }
+
+        let payload: SearxngSearchResponse = response
+            .json()
+            .await
+            .map_err(|e| SearchError::RequestFailed(format!("JSON parse error: {e}")))?;
+
+        Ok(WebSearchResponse {
+            provider: "searxng".to_string(),
+            results: normalize_results(payload, query.count),
+        })
+    }
+}
+
+fn normalize_results(payload: SearxngSearchResponse, count: Option<usize>) -> Vec<WebSearchResult> {
+    payload
+        .results
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| {
+            let url = item.url.filter(|url| !url.is_empty())?;
+            Some(WebSearchResult {
+                title: item.title.unwrap_or_else(|| "(untitled)".to_string()),
+                url,
+                snippet: item.content.filter(|s| !s.is_empty()),
+            })
+        })
+        .take(count.unwrap_or(usize::MAX))
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for SearxngSearchProvider {
+    fn name(&self) -> &'static str {
+        "searxng"
+    }
+
+    async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
+        match self.execute_request(query).await {
+            Err(SearchError::RateLimited(delay)) => {
+                sleep(delay).await;
+                self.execute_request(query).await
+            }
+            other => other,
+        }
+    }
+}
+
+// ── SearxNG JSON response wire types ─────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct SearxngSearchResponse {
+    #[serde(default)]
+    results: Option<Vec<SearxngResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_results_truncates_to_count() {
+        let payload: SearxngSearchResponse = serde_json::from_str(
+            r#"{"query":"rust","number_of_results":0,"results":[
+                {"title":"One","url":"https://one.example","content":"","engine":"duckduckgo"},
+                {"title":"Two","url":"https://two.example","content":"second"},
+                {"title":"Three","url":"https://three.example"}
+            ]}"#,
+        )
+        .unwrap();
+        let results = normalize_results(payload, Some(2));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].snippet, None);
+        assert_eq!(results[1].snippet.as_deref(), Some("second"));
+    }
+}
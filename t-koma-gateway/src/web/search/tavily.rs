@@ -0,0 +1,180 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use super::{
+    SearchError, SearchProvider, WebSearchQuery, WebSearchResponse, WebSearchResult,
+    freshness_window, retry_after, wait_for_slot,
+};
+
+static TAVILY_LAST_REQUEST: OnceLock<Mutex<std::time::Instant>> = OnceLock::new();
+
+fn tavily_last_request() -> &'static Mutex<std::time::Instant> {
+    TAVILY_LAST_REQUEST
+        .get_or_init(|| Mutex::new(std::time::Instant::now() - Duration::from_secs(60)))
+}
+
+/// Tavily has no country or language filters; `country` and `search_lang`
+/// are ignored. Tavily caps `max_results` at 20.
+#[derive(Debug, Clone)]
+pub struct TavilySearchProvider {
+    client: reqwest::Client,
+    base_url: String,
+    timeout: Duration,
+    min_interval: Duration,
+}
+
+impl TavilySearchProvider {
+    pub fn new(
+        api_key: String,
+        timeout: Duration,
+        min_interval: Duration,
+    ) -> Result<Self, SearchError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {api_key}"))
+                .map_err(|_| SearchError::MissingApiKey("TAVILY_API_KEY"))?,
+        );
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: "https://api.tavily.com/search".to_string(),
+            timeout,
+            min_interval,
+        })
+    }
+
+    async fn execute_request(
+        &self,
+        query: &WebSearchQuery,
+    ) -> Result<WebSearchResponse, SearchError> {
+        wait_for_slot(tavily_last_request(), self.min_interval).await;
+
+        let mut body = serde_json::json!({
+            "query": query.query,
+            "search_depth": "basic",
+        });
+        if let Some(count) = query.count {
+            body["max_results"] = serde_json::json!(count.min(20));
+        }
+        if let Some(window) = query.freshness.as_deref().and_then(freshness_window) {
+            body["time_range"] = serde_json::json!(window);
+        }
+
+        let response = self
+            .client
+            .post(&self.base_url)
+            .timeout(self.timeout)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| SearchError::RequestFailed(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(SearchError::RateLimited(retry_after(
+                &response,
+                Duration::from_millis(2000),
+            )));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(SearchError::RequestFailed(format!(
+                "HTTP {}: {}",
+                status, body
+            )));
+        }
+
+        let payload: TavilySearchResponse = response
+            .json()
+            .await
+            .map_err(|e| SearchError::RequestFailed(format!("JSON parse error: {e}")))?;
+
+        Ok(WebSearchResponse {
+            provider: "tavily".to_string(),
+            results: normalize_results(payload),
+        })
+    }
+}
+
+fn normalize_results(payload: TavilySearchResponse) -> Vec<WebSearchResult> {
+    payload
+        .results
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| {
+            let url = item.url.filter(|url| !url.is_empty())?;
+            Some(WebSearchResult {
+                title: item.title.unwrap_or_else(|| "(untitled)".to_string()),
+                url,
+                snippet: item.content.filter(|s| !s.is_empty()),
+            })
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for TavilySearchProvider {
+    fn name(&self) -> &'static str {
+        "tavily"
+    }
+
+    async fn search(&self, query: &WebSearchQuery) -> Result<WebSearchResponse, SearchError> {
+        match self.execute_request(query).await {
+            Err(SearchError::RateLimited(delay)) => {
+                sleep(delay).await;
+                self.execute_request(query).await
+            }
+            other => other,
+        }
+    }
+}
+
+// ── Tavily Search API response wire types ────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct TavilySearchResponse {
+    #[serde(default)]
+    results: Option<Vec<TavilyResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TavilyResult {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_results() {
+        let payload: TavilySearchResponse = serde_json::from_str(
+            r#"{"query":"rust","results":[
+                {"title":"Rust","url":"https://www.rust-lang.org","content":"A language","score":0.9},
+                {"title":"No URL","content":"dropped"}
+            ]}"#,
+        )
+        .unwrap();
+        let results = normalize_results(payload);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].url, "https://www.rust-lang.org");
+        assert_eq!(results[0].snippet.as_deref(), Some("A language"));
+    }
+}
@@ -0,0 +1,57 @@
+//! Bundled web dashboard (`web_ui` feature).
+//!
+//! Serves a small single-page app under `/ui` for operators who want neither
+//! the TUI nor Discord. The page holds no server-side logic: it connects to
+//! `/ws` with an API token (sent as a `bearer.<token>` subprotocol) and
+//! speaks the same `WsMessage`/`WsResponse` protocol as the CLI.
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    http::{HeaderValue, header},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+
+use crate::state::AppState;
+
+const INDEX_HTML: &str = include_str!("../web-ui/index.html");
+const APP_JS: &str = include_str!("../web-ui/app.js");
+const APP_CSS: &str = include_str!("../web-ui/app.css");
+
+/// Only same-origin assets and WebSocket connections back to the gateway.
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; connect-src 'self' ws: wss:; \
+     img-src 'self' data:; frame-ancestors 'none'";
+
+/// Routes serving the dashboard assets.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/ui", get(|| async { Redirect::permanent("/ui/") }))
+        .route("/ui/", get(|| async { asset("text/html", INDEX_HTML) }))
+        .route(
+            "/ui/app.js",
+            get(|| async { asset("text/javascript", APP_JS) }),
+        )
+        .route("/ui/app.css", get(|| async { asset("text/css", APP_CSS) }))
+}
+
+fn asset(content_type: &'static str, body: &'static str) -> Response {
+    let mut response = body.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&format!("{content_type}; charset=utf-8"))
+            .expect("static content type"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(CONTENT_SECURITY_POLICY),
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    response
+}
@@ -132,7 +132,7 @@ async fn test_pwd_tool_execution() {
                     input: input.clone(),
                 }
             }
-            ProviderContentBlock::ToolResult { .. } => {
+            ProviderContentBlock::Image { .. } | ProviderContentBlock::ToolResult { .. } => {
                 t_koma_gateway::chat::history::ChatContentBlock::Text {
                     text: String::new(),
                     cache_control: None,
@@ -0,0 +1,385 @@
+// T-KOMA web dashboard.
+//
+// Talks to the gateway over the same /ws protocol as the CLI. The API token
+// is kept in localStorage and sent as a `bearer.<token>` subprotocol, since
+// browsers cannot set headers on a WebSocket handshake.
+'use strict';
+
+const TOKEN_KEY = 't-koma.token';
+const PING_MS = 30000;
+
+// Action intents of approval and tool-loop prompts, as the control text the
+// gateway expects in chat (same mapping as the Discord buttons).
+const INTENT_TEXT = {
+  'approval.approve': 'approve',
+  'approval.deny': 'deny',
+  'tool_loop.continue_default': 'steps 1',
+  'tool_loop.deny': 'deny',
+};
+
+const $ = (id) => document.getElementById(id);
+
+const state = {
+  socket: null,
+  ping: null,
+  ghost: null,
+  session: null,
+  pending: new Map(),
+};
+
+function el(tag, props = {}, ...children) {
+  const node = document.createElement(tag);
+  for (const [key, value] of Object.entries(props)) {
+    if (key === 'className') node.className = value;
+    else if (key.startsWith('on')) node.addEventListener(key.slice(2), value);
+    else node.setAttribute(key, value);
+  }
+  for (const child of children) {
+    node.append(child);
+  }
+  return node;
+}
+
+function setStatus(text) {
+  $('status').textContent = text;
+}
+
+function send(message) {
+  if (!state.socket || state.socket.readyState !== WebSocket.OPEN) {
+    setStatus('not connected');
+    return;
+  }
+  state.socket.send(JSON.stringify(message));
+}
+
+// --- connection -----------------------------------------------------------
+
+function connect(token) {
+  const scheme = location.protocol === 'https:' ? 'wss' : 'ws';
+  const socket = new WebSocket(`${scheme}://${location.host}/ws`, [
+    't-koma',
+    `bearer.${token}`,
+  ]);
+  state.socket = socket;
+  setStatus('connecting…');
+
+  socket.addEventListener('open', () => {
+    localStorage.setItem(TOKEN_KEY, token);
+    $('login').hidden = true;
+    $('app').hidden = false;
+    $('logout').hidden = false;
+    setStatus('connected');
+    send({ type: 'list_ghosts' });
+    state.ping = setInterval(() => send({ type: 'ping' }), PING_MS);
+  });
+  socket.addEventListener('message', (event) => {
+    handle(JSON.parse(event.data));
+  });
+  socket.addEventListener('close', () => {
+    clearInterval(state.ping);
+    state.socket = null;
+    setStatus('disconnected');
+    if ($('app').hidden) {
+      showLogin('Connection refused: check the token.');
+    }
+  });
+}
+
+function showLogin(error) {
+  $('app').hidden = true;
+  $('logout').hidden = true;
+  $('login').hidden = false;
+  if (error) setStatus(error);
+}
+
+// --- responses ------------------------------------------------------------
+
+function handle(response) {
+  switch (response.type) {
+    case 'ghost_list':
+      renderGhosts(response.ghosts);
+      break;
+    case 'session_list':
+      renderSessions(response.sessions);
+      break;
+    case 'session_created':
+      state.session = response.session_id;
+      $('messages').replaceChildren();
+      send({ type: 'list_sessions', ghost_name: state.ghost });
+      break;
+    case 'response':
+      // Gateway errors arrive as messages of kind `error`; surface them in
+      // the header too, since they may answer a request from another view.
+      if (response.message.kind === 'error') setStatus(response.message.text_fallback);
+      renderMessage(response.id, response.message, response.done);
+      break;
+    case 'usage_budget_exceeded':
+      renderMessage(response.message.id, response.message, true);
+      break;
+    case 'knowledge_search_results':
+      renderKnowledge(response.results);
+      break;
+    case 'usage_report':
+      renderUsage(response.rows);
+      break;
+    case 'pending_operators':
+      renderOperators(response.operators);
+      break;
+    case 'operator_approved':
+      setStatus(`approved ${response.operator_id}`);
+      send({ type: 'list_pending_operators' });
+      break;
+    case 'pong':
+      break;
+    default:
+      console.debug('unhandled response', response);
+  }
+}
+
+// --- chat -----------------------------------------------------------------
+
+function renderGhosts(ghosts) {
+  const select = $('ghost');
+  select.replaceChildren(...ghosts.map((g) => el('option', { value: g.name }, g.name)));
+  if (ghosts.length === 0) {
+    setStatus('no ghosts: create one from the TUI or Discord first');
+    return;
+  }
+  selectGhost(ghosts[0].name);
+}
+
+function selectGhost(name) {
+  state.ghost = name;
+  state.session = null;
+  $('ghost').value = name;
+  $('messages').replaceChildren();
+  send({ type: 'list_sessions', ghost_name: name });
+}
+
+function sessionLabel(session) {
+  return session.title || new Date(session.updated_at).toLocaleString();
+}
+
+function renderSessions(sessions) {
+  if (!state.session) {
+    const active = sessions.find((s) => s.is_active) || sessions[0];
+    state.session = active ? active.id : null;
+  }
+  $('sessions').replaceChildren(
+    ...sessions.map((session) =>
+      el(
+        'li',
+        {
+          className: session.id === state.session ? 'selected' : '',
+          onclick: () => {
+            state.session = session.id;
+            $('messages').replaceChildren();
+            renderSessions(sessions);
+          },
+        },
+        sessionLabel(session),
+        el('small', {}, ` ${session.message_count} msgs`),
+      ),
+    ),
+  );
+}
+
+function appendBubble(role, text) {
+  const bubble = el('div', { className: `message ${role}` }, text);
+  $('messages').append(bubble);
+  bubble.scrollIntoView({ block: 'end' });
+  return bubble;
+}
+
+function renderMessage(id, message, done) {
+  let bubble = state.pending.get(id);
+  if (!bubble) {
+    bubble = appendBubble(message.kind, '');
+    state.pending.set(id, bubble);
+  }
+  bubble.className = `message ${message.kind}`;
+  bubble.replaceChildren(message.text_fallback);
+  if (message.actions.length > 0) {
+    bubble.append(
+      el('div', { className: 'actions' }, ...message.actions.map((a) => actionButton(a))),
+    );
+  }
+  if (done) state.pending.delete(id);
+}
+
+function actionButton(action) {
+  return el(
+    'button',
+    {
+      className: action.style || 'secondary',
+      onclick: (event) => {
+        let text = INTENT_TEXT[action.intent];
+        if (action.intent === 'tool_loop.set_steps') {
+          const steps = prompt('Extra tool steps', '10');
+          text = steps ? `steps ${steps}` : null;
+        }
+        if (!text) return;
+        event.target.parentElement.remove();
+        sendChat(text);
+      },
+    },
+    action.label,
+  );
+}
+
+function sendChat(content) {
+  if (!state.ghost) return;
+  appendBubble('operator', content);
+  send({
+    type: 'chat',
+    ghost_name: state.ghost,
+    session_id: state.session || 'active',
+    content,
+  });
+}
+
+// --- knowledge ------------------------------------------------------------
+
+function renderKnowledge(results) {
+  if (results.length === 0) {
+    $('knowledge-results').replaceChildren(el('li', {}, 'No matches.'));
+    return;
+  }
+  $('knowledge-results').replaceChildren(
+    ...results.map((r) =>
+      el(
+        'li',
+        {},
+        el('strong', {}, r.title),
+        el('small', {}, ` ${r.entry_type} · ${r.scope}`),
+        el('p', {}, r.snippet),
+      ),
+    ),
+  );
+}
+
+// --- usage ----------------------------------------------------------------
+
+function renderUsage(rows) {
+  const max = Math.max(...rows.map((r) => r.cost_usd), 0);
+  if (rows.length === 0) {
+    $('usage-chart').replaceChildren(el('p', {}, 'No usage recorded.'));
+    return;
+  }
+  $('usage-chart').replaceChildren(
+    ...rows.map((row) => {
+      const bar = el('div', { className: 'bar' });
+      bar.style.width = max > 0 ? `${(row.cost_usd / max) * 100}%` : '0';
+      const tokens = row.input_tokens + row.output_tokens;
+      return el(
+        'div',
+        { className: 'usage-row' },
+        el('span', { className: 'label' }, row.label),
+        el('div', { className: 'track' }, bar),
+        el(
+          'span',
+          { className: 'value' },
+          `$${row.cost_usd.toFixed(2)} · ${tokens.toLocaleString()} tok · ${row.request_count} req`,
+        ),
+      );
+    }),
+  );
+}
+
+function requestUsage() {
+  const days = $('usage-days').value;
+  const message = { type: 'get_usage_report', group_by: $('usage-group').value };
+  if (days) message.since_days = Number(days);
+  send(message);
+}
+
+// --- operators ------------------------------------------------------------
+
+function renderOperators(operators) {
+  if (operators.length === 0) {
+    $('operators').replaceChildren(el('li', {}, 'No operators waiting for approval.'));
+    return;
+  }
+  $('operators').replaceChildren(
+    ...operators.map((op) =>
+      el(
+        'li',
+        {},
+        el('strong', {}, op.name),
+        el('small', {}, ` ${op.platform} · ${new Date(op.created_at).toLocaleString()} · ${op.id}`),
+        el(
+          'button',
+          {
+            className: 'success',
+            onclick: () => send({ type: 'approve_operator', operator_id: op.id }),
+          },
+          'Approve',
+        ),
+      ),
+    ),
+  );
+}
+
+// --- wiring ---------------------------------------------------------------
+
+function showView(name) {
+  for (const button of document.querySelectorAll('nav button')) {
+    button.classList.toggle('active', button.dataset.view === name);
+  }
+  for (const view of document.querySelectorAll('.view')) {
+    view.hidden = view.id !== `view-${name}`;
+  }
+  if (name === 'usage') requestUsage();
+  if (name === 'operators') send({ type: 'list_pending_operators' });
+}
+
+document.addEventListener('DOMContentLoaded', () => {
+  for (const button of document.querySelectorAll('nav button')) {
+    button.addEventListener('click', () => showView(button.dataset.view));
+  }
+
+  $('login-form').addEventListener('submit', (event) => {
+    event.preventDefault();
+    const token = $('token').value.trim();
+    if (token) connect(token);
+  });
+  $('logout').addEventListener('click', () => {
+    localStorage.removeItem(TOKEN_KEY);
+    if (state.socket) state.socket.close();
+    showLogin();
+  });
+
+  $('ghost').addEventListener('change', (event) => selectGhost(event.target.value));
+  $('new-session').addEventListener('click', () => {
+    if (state.ghost) send({ type: 'create_session', ghost_name: state.ghost });
+  });
+  $('chat-form').addEventListener('submit', (event) => {
+    event.preventDefault();
+    const content = $('chat-input').value.trim();
+    if (!content) return;
+    $('chat-input').value = '';
+    sendChat(content);
+  });
+  $('chat-input').addEventListener('keydown', (event) => {
+    if (event.key === 'Enter' && event.ctrlKey) $('chat-form').requestSubmit();
+  });
+
+  $('knowledge-form').addEventListener('submit', (event) => {
+    event.preventDefault();
+    const query = $('knowledge-query').value.trim();
+    if (query) {
+      send({ type: 'search_knowledge', ghost_name: state.ghost, query, max_results: 20 });
+    }
+  });
+  $('usage-form').addEventListener('submit', (event) => {
+    event.preventDefault();
+    requestUsage();
+  });
+  $('operators-refresh').addEventListener('click', () =>
+    send({ type: 'list_pending_operators' }),
+  );
+
+  const token = localStorage.getItem(TOKEN_KEY);
+  if (token) connect(token);
+  else showLogin();
+});
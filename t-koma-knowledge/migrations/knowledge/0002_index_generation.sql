@@ -0,0 +1,33 @@
+-- Monotonic counter bumped on every index write. Used to invalidate cached
+-- search results regardless of which pool (engine, watcher) did the write.
+INSERT OR IGNORE INTO meta (key, value) VALUES ('index_generation', '0');
+
+CREATE TRIGGER IF NOT EXISTS notes_generation_insert AFTER INSERT ON notes BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS notes_generation_update AFTER UPDATE ON notes BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS notes_generation_delete AFTER DELETE ON notes BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+
+CREATE TRIGGER IF NOT EXISTS chunks_generation_insert AFTER INSERT ON chunks BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_generation_update AFTER UPDATE ON chunks BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_generation_delete AFTER DELETE ON chunks BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+
+CREATE TRIGGER IF NOT EXISTS reference_files_generation_insert AFTER INSERT ON reference_files BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS reference_files_generation_update AFTER UPDATE ON reference_files BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS reference_files_generation_delete AFTER DELETE ON reference_files BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
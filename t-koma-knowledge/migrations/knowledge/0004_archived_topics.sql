@@ -0,0 +1,16 @@
+-- Archived reference topics. Distinct from per-file `obsolete` status:
+-- an archived topic keeps its files and index rows but is hidden from
+-- search and topic listings until restored.
+CREATE TABLE IF NOT EXISTS archived_topics (
+  topic_id TEXT PRIMARY KEY,
+  archived_at TEXT NOT NULL,
+  archived_by_ghost TEXT NOT NULL,
+  reason TEXT
+);
+
+CREATE TRIGGER IF NOT EXISTS archived_topics_generation_insert AFTER INSERT ON archived_topics BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
+CREATE TRIGGER IF NOT EXISTS archived_topics_generation_delete AFTER DELETE ON archived_topics BEGIN
+  UPDATE meta SET value = CAST(value AS INTEGER) + 1 WHERE key = 'index_generation';
+END;
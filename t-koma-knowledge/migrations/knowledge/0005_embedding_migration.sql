@@ -0,0 +1,20 @@
+-- Gradual re-embedding after an embedding model change. While a migration
+-- is open, chunks still tagged with `from_model` keep their old vectors and
+-- are re-embedded in the background, most recently accessed notes first.
+CREATE TABLE IF NOT EXISTS embedding_migrations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  from_model TEXT NOT NULL,
+  to_model TEXT NOT NULL,
+  started_at TEXT NOT NULL,
+  completed_at TEXT,
+  total_chunks INTEGER NOT NULL,
+  migrated_chunks INTEGER NOT NULL DEFAULT 0
+);
+
+-- Last explicit read of a note (get/fetch), used to prioritize re-embedding.
+-- Kept out of `notes` so reads do not bump the index generation.
+CREATE TABLE IF NOT EXISTS note_access (
+  note_id TEXT PRIMARY KEY,
+  last_accessed_at TEXT NOT NULL,
+  access_count INTEGER NOT NULL DEFAULT 1
+);
@@ -1,33 +1,72 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
 use serde::Deserialize;
 use t_koma_core::config::EmbeddingProviderKind;
 
 use crate::KnowledgeSettings;
 use crate::errors::{KnowledgeError, KnowledgeResult};
 
-#[derive(Debug, Clone)]
+/// Backend that turns text into embedding vectors.
+///
+/// The gateway supplies its own implementation (shared API keys, retries and
+/// circuit breaking); [`HttpEmbeddings`] is the standalone default.
+#[async_trait::async_trait]
+pub trait EmbeddingsProvider: Send + Sync {
+    /// Backend name, for logs and errors.
+    fn name(&self) -> &str;
+
+    /// Embed `inputs` with `model`, one vector per input, in order.
+    async fn embed(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>>;
+}
+
+/// Embedding client used by indexing and search: model routing on top of an
+/// [`EmbeddingsProvider`].
+#[derive(Clone)]
 pub struct EmbeddingClient {
-    provider: EmbeddingProviderKind,
-    base_url: String,
+    provider_kind: EmbeddingProviderKind,
     model: String,
-    api_key: Option<String>,
-    client: reqwest::Client,
+    /// Per-language model overrides (ISO 639-1 code → model).
+    language_models: BTreeMap<String, String>,
+    provider: Arc<dyn EmbeddingsProvider>,
+}
+
+impl fmt::Debug for EmbeddingClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbeddingClient")
+            .field("provider", &self.provider.name())
+            .field("model", &self.model)
+            .field("language_models", &self.language_models)
+            .finish()
+    }
 }
 
 impl EmbeddingClient {
+    /// Client backed by the built-in [`HttpEmbeddings`].
     pub fn new(settings: &KnowledgeSettings) -> Self {
-        let base_url = settings.embedding_url.trim_end_matches('/').to_string();
-
-        let api_key = match settings.embedding_provider {
-            EmbeddingProviderKind::OpenRouter => std::env::var("OPENROUTER_API_KEY").ok(),
-            EmbeddingProviderKind::Ollama => None,
-        };
+        Self::with_provider(settings, Arc::new(HttpEmbeddings::new(settings)))
+    }
 
+    /// Client backed by an external provider.
+    pub fn with_provider(
+        settings: &KnowledgeSettings,
+        provider: Arc<dyn EmbeddingsProvider>,
+    ) -> Self {
         Self {
-            provider: settings.embedding_provider,
-            base_url,
+            provider_kind: settings.embedding_provider,
             model: settings.embedding_model.clone(),
-            api_key,
-            client: reqwest::Client::new(),
+            language_models: settings
+                .languages
+                .iter()
+                .filter_map(|(code, overrides)| {
+                    overrides
+                        .embedding_model
+                        .clone()
+                        .map(|model| (code.clone(), model))
+                })
+                .collect(),
+            provider,
         }
     }
 
@@ -36,24 +75,77 @@ impl EmbeddingClient {
     }
 
     pub fn provider_kind(&self) -> EmbeddingProviderKind {
-        self.provider
+        self.provider_kind
+    }
+
+    /// Whether any language routes to a model other than the default.
+    pub fn routes_languages(&self) -> bool {
+        !self.language_models.is_empty()
+    }
+
+    /// Embedding model for text in `language` (falls back to the default model).
+    pub fn model_for_language(&self, language: Option<&str>) -> &str {
+        language
+            .and_then(|code| self.language_models.get(code))
+            .map(String::as_str)
+            .unwrap_or(&self.model)
     }
 
     pub async fn embed_batch(&self, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        self.embed_batch_with_model(&self.model, inputs).await
+    }
+
+    /// Embed `inputs` with an explicit model on the configured provider.
+    pub async fn embed_batch_with_model(
+        &self,
+        model: &str,
+        inputs: &[String],
+    ) -> KnowledgeResult<Vec<Vec<f32>>> {
         if inputs.is_empty() {
             return Ok(Vec::new());
         }
 
-        match self.provider {
-            EmbeddingProviderKind::Ollama => self.embed_ollama(inputs).await,
-            EmbeddingProviderKind::OpenRouter => self.embed_openrouter(inputs).await,
+        let embeddings = self.provider.embed(model, inputs).await?;
+        if embeddings.len() != inputs.len() {
+            return Err(KnowledgeError::Embedding(format!(
+                "{} returned {} embeddings for {} inputs",
+                self.provider.name(),
+                embeddings.len(),
+                inputs.len()
+            )));
         }
+        Ok(embeddings)
     }
+}
 
-    async fn embed_ollama(&self, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+/// Plain HTTP embeddings backend for Ollama and OpenAI-style `/embeddings`
+/// APIs (OpenRouter, OpenAI and compatible servers), without retries.
+#[derive(Debug, Clone)]
+pub struct HttpEmbeddings {
+    provider: EmbeddingProviderKind,
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpEmbeddings {
+    /// Build from settings, reading the API key from the configured env var.
+    pub fn new(settings: &KnowledgeSettings) -> Self {
+        Self {
+            provider: settings.embedding_provider,
+            base_url: settings.embedding_url.trim_end_matches('/').to_string(),
+            api_key: settings
+                .embedding_api_key_env()
+                .and_then(|env| std::env::var(env).ok())
+                .filter(|key| !key.trim().is_empty()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn embed_ollama(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
         let url = format!("{}/api/embed", self.base_url);
         let body = OllamaEmbedRequest {
-            model: self.model.clone(),
+            model: model.to_string(),
             input: inputs.to_vec(),
         };
 
@@ -66,89 +158,117 @@ impl EmbeddingClient {
             )));
         }
 
-        let payload: OllamaEmbedResponse = response.json().await?;
-
-        if let Some(embeddings) = payload.embeddings {
-            return Ok(embeddings);
-        }
-        if let Some(embedding) = payload.embedding {
-            return Ok(vec![embedding]);
-        }
-
-        Err(KnowledgeError::Embedding(
-            "ollama embedding response missing vectors".to_string(),
-        ))
+        parse_ollama_response(response.json().await?)
     }
 
-    async fn embed_openrouter(&self, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
-        let api_key = self.api_key.as_deref().ok_or_else(|| {
-            KnowledgeError::Embedding(
-                "OpenRouter embedding provider requires OPENROUTER_API_KEY".to_string(),
-            )
-        })?;
-
+    async fn embed_openai(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
         let url = format!("{}/embeddings", self.base_url);
-        let body = OpenRouterEmbedRequest {
-            model: self.model.clone(),
+        let body = OpenAiEmbedRequest {
+            model: model.to_string(),
             input: inputs.to_vec(),
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(api_key)
-            .json(&body)
-            .send()
-            .await?;
+        let mut request = self.client.post(&url).json(&body);
+        match (&self.api_key, self.provider) {
+            (Some(api_key), _) => request = request.bearer_auth(api_key),
+            // Local OpenAI-compatible servers usually need no key.
+            (None, EmbeddingProviderKind::OpenAi) => {}
+            (None, _) => {
+                return Err(KnowledgeError::Embedding(format!(
+                    "{} embedding provider requires an API key",
+                    self.provider
+                )));
+            }
+        }
 
+        let response = request.send().await?;
         let status = response.status();
         if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
             return Err(KnowledgeError::Embedding(format!(
-                "openrouter embedding request failed: {status} {text}"
+                "{} embedding request failed: {status} {text}",
+                self.provider
             )));
         }
 
-        let payload: OpenRouterEmbedResponse = response.json().await?;
-        let mut result: Vec<(usize, Vec<f32>)> = payload
-            .data
-            .into_iter()
-            .map(|d| (d.index, d.embedding))
-            .collect();
-        result.sort_by_key(|(idx, _)| *idx);
-        Ok(result.into_iter().map(|(_, v)| v).collect())
+        Ok(parse_openai_response(response.json().await?))
+    }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingsProvider for HttpEmbeddings {
+    fn name(&self) -> &str {
+        match self.provider {
+            EmbeddingProviderKind::Ollama => "ollama",
+            EmbeddingProviderKind::OpenRouter => "openrouter",
+            EmbeddingProviderKind::OpenAi => "openai",
+        }
+    }
+
+    async fn embed(&self, model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        match self.provider {
+            EmbeddingProviderKind::Ollama => self.embed_ollama(model, inputs).await,
+            EmbeddingProviderKind::OpenRouter | EmbeddingProviderKind::OpenAi => {
+                self.embed_openai(model, inputs).await
+            }
+        }
+    }
+}
+
+/// Vectors from an Ollama `/api/embed` response.
+pub fn parse_ollama_response(payload: OllamaEmbedResponse) -> KnowledgeResult<Vec<Vec<f32>>> {
+    if let Some(embeddings) = payload.embeddings {
+        return Ok(embeddings);
     }
+    if let Some(embedding) = payload.embedding {
+        return Ok(vec![embedding]);
+    }
+
+    Err(KnowledgeError::Embedding(
+        "ollama embedding response missing vectors".to_string(),
+    ))
+}
+
+/// Vectors from an OpenAI-style `/embeddings` response, in input order.
+pub fn parse_openai_response(payload: OpenAiEmbedResponse) -> Vec<Vec<f32>> {
+    let mut result: Vec<(usize, Vec<f32>)> = payload
+        .data
+        .into_iter()
+        .map(|d| (d.index, d.embedding))
+        .collect();
+    result.sort_by_key(|(idx, _)| *idx);
+    result.into_iter().map(|(_, v)| v).collect()
 }
 
 // ── Ollama wire types ─────────────────────────────────────────────
 
 #[derive(Debug, Clone, serde::Serialize)]
-struct OllamaEmbedRequest {
-    model: String,
-    input: Vec<String>,
+pub struct OllamaEmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct OllamaEmbedResponse {
+pub struct OllamaEmbedResponse {
     embeddings: Option<Vec<Vec<f32>>>,
     embedding: Option<Vec<f32>>,
 }
 
-// ── OpenRouter wire types ─────────────────────────────────────────
+// ── OpenAI / OpenRouter wire types ────────────────────────────────
 
 #[derive(Debug, Clone, serde::Serialize)]
-struct OpenRouterEmbedRequest {
-    model: String,
-    input: Vec<String>,
+pub struct OpenAiEmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct OpenRouterEmbedResponse {
-    data: Vec<OpenRouterEmbedding>,
+pub struct OpenAiEmbedResponse {
+    data: Vec<OpenAiEmbedding>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct OpenRouterEmbedding {
+struct OpenAiEmbedding {
     index: usize,
     embedding: Vec<f32>,
 }
@@ -0,0 +1,138 @@
+//! Background bulk ingestion into a reference topic.
+//!
+//! `ingest_batch` validates the target topic up-front, then processes items
+//! sequentially on a spawned task, reporting progress over a channel. The
+//! caller (gateway) owns job tracking; this crate only reports events.
+
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::errors::{KnowledgeError, KnowledgeResult};
+use crate::models::{
+    IngestBatchRequest, IngestItem, IngestProgress, IngestSource, ReferenceSaveRequest, SourceRole,
+};
+use crate::sources::{fetch_web_markdown, url_to_filename};
+
+use super::KnowledgeEngine;
+use super::save;
+
+/// Handle to a running bulk ingest job. The caller's job log row identifies
+/// the job; this only carries its size and progress.
+#[derive(Debug)]
+pub struct IngestJob {
+    pub total: usize,
+    pub progress: mpsc::UnboundedReceiver<IngestProgress>,
+}
+
+/// Reject a batch that would fail every item. Returns the topic title.
+pub(crate) async fn check_ingest_batch(
+    engine: &KnowledgeEngine,
+    request: &IngestBatchRequest,
+) -> KnowledgeResult<String> {
+    if request.items.is_empty() {
+        return Err(KnowledgeError::MissingField("items"));
+    }
+    let (_, topic_title) = save::find_existing_topic(engine.pool(), &request.topic)
+        .await?
+        .ok_or_else(|| KnowledgeError::UnknownNote(format!("topic '{}'", request.topic)))?;
+    Ok(topic_title)
+}
+
+pub(crate) async fn ingest_batch(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    model: &str,
+    request: IngestBatchRequest,
+) -> KnowledgeResult<IngestJob> {
+    // Fail fast on an unknown topic instead of failing every item later.
+    let topic_title = check_ingest_batch(engine, &request).await?;
+
+    let total = request.items.len();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    let engine = engine.clone();
+    let ghost_name = ghost_name.to_string();
+    let model = model.to_string();
+    tokio::spawn(async move {
+        let mut saved = 0;
+        let mut failed = 0;
+        for (index, item) in request.items.into_iter().enumerate() {
+            let _ = tx.send(IngestProgress::ItemStarted { index });
+            match ingest_item(&engine, &ghost_name, &model, &topic_title, item).await {
+                Ok((note_id, path)) => {
+                    saved += 1;
+                    let _ = tx.send(IngestProgress::ItemSaved {
+                        index,
+                        note_id,
+                        path,
+                    });
+                }
+                Err(e) => {
+                    failed += 1;
+                    warn!("bulk ingest item {index} failed: {e}");
+                    let _ = tx.send(IngestProgress::ItemFailed {
+                        index,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        let _ = tx.send(IngestProgress::Finished { saved, failed });
+    });
+
+    Ok(IngestJob {
+        total,
+        progress: rx,
+    })
+}
+
+async fn ingest_item(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    model: &str,
+    topic: &str,
+    item: IngestItem,
+) -> KnowledgeResult<(String, String)> {
+    let (content, default_path, default_role) = match &item.source {
+        IngestSource::File { path } => {
+            let content = tokio::fs::read_to_string(path).await?;
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("file")
+                .to_string();
+            let role = if name.ends_with(".md") || name.ends_with(".txt") {
+                SourceRole::Docs
+            } else {
+                SourceRole::Code
+            };
+            (content, name, role)
+        }
+        IngestSource::Url { url } => {
+            let content = fetch_web_markdown(url).await?;
+            (content, url_to_filename(url), SourceRole::Docs)
+        }
+    };
+
+    let source_url = match &item.source {
+        IngestSource::Url { url } => Some(url.clone()),
+        IngestSource::File { .. } => None,
+    };
+
+    let result = save::reference_save(
+        engine,
+        ghost_name,
+        model,
+        ReferenceSaveRequest {
+            topic: topic.to_string(),
+            path: item.path.unwrap_or(default_path),
+            content,
+            source_url,
+            role: Some(item.role.unwrap_or(default_role)),
+            title: item.title,
+        },
+    )
+    .await?;
+
+    Ok((result.note_id, result.path))
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::{KnowledgeSearchQuery, KnowledgeSearchResult};
+
+/// In-memory cache of `knowledge_search` results.
+///
+/// Entries are keyed by ghost + serialized query and tagged with the index
+/// generation they were computed at. Any index write bumps the generation,
+/// so stale entries are never served even before their TTL expires.
+#[derive(Debug)]
+pub(crate) struct SearchCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    generation: i64,
+    inserted_at: Instant,
+    result: KnowledgeSearchResult,
+}
+
+impl SearchCache {
+    pub(crate) fn new(ttl_seconds: u64, max_entries: usize) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl_seconds),
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    pub(crate) fn key(ghost_name: &str, query: &KnowledgeSearchQuery) -> String {
+        let query_json = serde_json::to_string(query).unwrap_or_default();
+        format!("{ghost_name}\u{1f}{query_json}")
+    }
+
+    pub(crate) fn get(&self, key: &str, generation: i64) -> Option<KnowledgeSearchResult> {
+        if !self.enabled() {
+            return None;
+        }
+        let mut entries = self.entries.lock().ok()?;
+        let entry = entries.get(key)?;
+        if entry.generation == generation && entry.inserted_at.elapsed() < self.ttl {
+            return Some(entry.result.clone());
+        }
+        entries.remove(key);
+        None
+    }
+
+    pub(crate) fn insert(&self, key: String, generation: i64, result: &KnowledgeSearchResult) {
+        if !self.enabled() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        let ttl = self.ttl;
+        entries.retain(|_, e| e.generation == generation && e.inserted_at.elapsed() < ttl);
+        if entries.len() >= self.max_entries
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, e)| e.inserted_at)
+                .map(|(k, _)| k.clone())
+        {
+            entries.remove(&oldest);
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                generation,
+                inserted_at: Instant::now(),
+                result: result.clone(),
+            },
+        );
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ReferenceSearchOutput;
+
+    fn empty_result() -> KnowledgeSearchResult {
+        KnowledgeSearchResult {
+            notes: Vec::new(),
+            diary: Vec::new(),
+            references: ReferenceSearchOutput {
+                matched_topic: None,
+                results: Vec::new(),
+            },
+            topics: Vec::new(),
+        }
+    }
+
+    fn query(text: &str) -> KnowledgeSearchQuery {
+        KnowledgeSearchQuery {
+            query: text.to_string(),
+            categories: None,
+            scope: Default::default(),
+            topic: None,
+            archetype: None,
+            options: Default::default(),
+        }
+    }
+
+    #[test]
+    fn hit_requires_same_generation() {
+        let cache = SearchCache::new(60, 8);
+        let key = SearchCache::key("ghost", &query("rust"));
+        cache.insert(key.clone(), 3, &empty_result());
+
+        assert!(cache.get(&key, 3).is_some());
+        assert!(cache.get(&key, 4).is_none());
+        assert!(cache.get(&key, 3).is_none(), "stale entry is evicted");
+    }
+
+    #[test]
+    fn key_is_ghost_scoped() {
+        let q = query("rust");
+        assert_ne!(SearchCache::key("a", &q), SearchCache::key("b", &q));
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = SearchCache::new(0, 8);
+        let key = SearchCache::key("ghost", &query("rust"));
+        cache.insert(key.clone(), 1, &empty_result());
+        assert!(cache.get(&key, 1).is_none());
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let cache = SearchCache::new(60, 2);
+        for text in ["a", "b", "c"] {
+            cache.insert(SearchCache::key("ghost", &query(text)), 1, &empty_result());
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(
+            cache
+                .get(&SearchCache::key("ghost", &query("a")), 1)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Copy a ghost's private knowledge into another ghost.
+//!
+//! Note IDs live in front matter and are global primary keys, so cloned
+//! notes get fresh IDs (parent references are remapped to match). Diary
+//! IDs derive from the ghost name and reference files are not indexed per
+//! ghost, so both are copied verbatim. The target ghost's index catches up
+//! on its next reconcile.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::errors::KnowledgeResult;
+use crate::models::{KnowledgeScope, generate_note_id};
+use crate::parser::parse_note;
+use crate::paths::{ghost_diary_root, ghost_notes_root, ghost_references_root};
+
+use super::KnowledgeEngine;
+use super::notes::rebuild_front_matter;
+
+/// Copy the selected ghost scopes from `source` to `target`.
+///
+/// Shared scopes are skipped (every ghost already sees them). Returns the
+/// number of files written.
+pub(crate) async fn clone_ghost_knowledge(
+    engine: &KnowledgeEngine,
+    source: &str,
+    target: &str,
+    scopes: &[KnowledgeScope],
+) -> KnowledgeResult<usize> {
+    let settings = &engine.settings();
+    let mut copied = 0;
+    for scope in scopes {
+        copied += match scope {
+            KnowledgeScope::GhostNote => {
+                copy_notes(
+                    &ghost_notes_root(settings, source)?,
+                    &ghost_notes_root(settings, target)?,
+                )
+                .await?
+            }
+            KnowledgeScope::GhostDiary => {
+                copy_verbatim(
+                    &ghost_diary_root(settings, source)?,
+                    &ghost_diary_root(settings, target)?,
+                )
+                .await?
+            }
+            KnowledgeScope::GhostReference => {
+                copy_verbatim(
+                    &ghost_references_root(settings, source)?,
+                    &ghost_references_root(settings, target)?,
+                )
+                .await?
+            }
+            KnowledgeScope::SharedNote | KnowledgeScope::SharedReference => 0,
+        };
+    }
+    Ok(copied)
+}
+
+fn files_under(root: &Path) -> Vec<(PathBuf, PathBuf)> {
+    if !root.exists() {
+        return Vec::new();
+    }
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?.to_path_buf();
+            Some((entry.path().to_path_buf(), relative))
+        })
+        .collect()
+}
+
+async fn write_file(path: &Path, content: &[u8]) -> KnowledgeResult<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, content).await?;
+    Ok(())
+}
+
+async fn copy_verbatim(from: &Path, to: &Path) -> KnowledgeResult<usize> {
+    let files = files_under(from);
+    for (path, relative) in &files {
+        let content = tokio::fs::read(path).await?;
+        write_file(&to.join(relative), &content).await?;
+    }
+    Ok(files.len())
+}
+
+async fn copy_notes(from: &Path, to: &Path) -> KnowledgeResult<usize> {
+    let mut files = Vec::new();
+    for (path, relative) in files_under(from) {
+        files.push((relative, tokio::fs::read(&path).await?));
+    }
+
+    let is_markdown = |path: &Path| path.extension().and_then(|v| v.to_str()) == Some("md");
+    let mut parsed = HashMap::new();
+    for (relative, content) in &files {
+        if is_markdown(relative)
+            && let Ok(note) = parse_note(&String::from_utf8_lossy(content))
+        {
+            parsed.insert(relative.clone(), note);
+        }
+    }
+    let id_map: HashMap<String, String> = parsed
+        .values()
+        .map(|note| (note.front.id.clone(), generate_note_id()))
+        .collect();
+
+    for (relative, content) in &files {
+        let target = to.join(relative);
+        match parsed.remove(relative) {
+            Some(mut note) => {
+                note.front.id = id_map[&note.front.id].clone();
+                if let Some(parent) = note.front.parent.as_ref().and_then(|p| id_map.get(p)) {
+                    note.front.parent = Some(parent.clone());
+                }
+                let rendered = format!(
+                    "+++\n{}\n+++\n\n{}\n",
+                    rebuild_front_matter(&note.front),
+                    note.body
+                );
+                write_file(&target, rendered.as_bytes()).await?;
+            }
+            None => write_file(&target, content).await?,
+        }
+    }
+    Ok(files.len())
+}
@@ -0,0 +1,253 @@
+//! Export of the note graph for external visualization tools.
+//!
+//! Nodes are shared/ghost notes visible to the ghost (topics are shared notes
+//! that own reference files). Edges are resolved wiki links and parent
+//! relations between exported nodes. Diary entries and reference files are
+//! left out to keep the graph readable.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::errors::KnowledgeResult;
+use crate::models::{GraphFormat, OwnershipScope};
+
+use super::KnowledgeEngine;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub entry_type: String,
+    pub scope: String,
+    pub is_topic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// `link` or `parent`.
+    pub kind: &'static str,
+}
+
+pub(crate) async fn export_graph(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    scope: OwnershipScope,
+    format: GraphFormat,
+) -> KnowledgeResult<String> {
+    let (nodes, edges) = load_graph(engine, ghost_name, scope).await?;
+    Ok(match format {
+        GraphFormat::Dot => render_dot(&nodes, &edges),
+        GraphFormat::GraphMl => render_graphml(&nodes, &edges),
+    })
+}
+
+async fn load_graph(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    scope: OwnershipScope,
+) -> KnowledgeResult<(Vec<GraphNode>, Vec<GraphEdge>)> {
+    let pool = engine.pool();
+    let include_shared = !matches!(scope, OwnershipScope::Private);
+    let include_private = !matches!(scope, OwnershipScope::Shared);
+
+    let rows = sqlx::query_as::<_, (String, String, String, String, Option<String>, i64)>(
+        r#"SELECT n.id, n.title, n.entry_type, n.scope, n.parent_id,
+                  EXISTS(SELECT 1 FROM reference_files rf WHERE rf.topic_id = n.id)
+           FROM notes n
+           WHERE (? AND n.scope = 'shared_note' AND n.owner_ghost IS NULL)
+              OR (? AND n.scope = 'ghost_note' AND n.owner_ghost = ?)
+           ORDER BY n.title"#,
+    )
+    .bind(include_shared)
+    .bind(include_private)
+    .bind(ghost_name)
+    .fetch_all(pool)
+    .await?;
+
+    let ids: HashSet<String> = rows.iter().map(|(id, ..)| id.clone()).collect();
+    let mut nodes = Vec::with_capacity(rows.len());
+    let mut edges = Vec::new();
+    for (id, title, entry_type, scope, parent_id, is_topic) in rows {
+        if let Some(parent) = parent_id.filter(|p| ids.contains(p)) {
+            edges.push(GraphEdge {
+                source: id.clone(),
+                target: parent,
+                kind: "parent",
+            });
+        }
+        nodes.push(GraphNode {
+            id,
+            title,
+            entry_type,
+            scope,
+            is_topic: is_topic != 0,
+        });
+    }
+
+    let links = sqlx::query_as::<_, (String, String)>(
+        "SELECT DISTINCT source_id, target_id FROM note_links WHERE target_id IS NOT NULL \
+         ORDER BY source_id, target_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    edges.extend(
+        links
+            .into_iter()
+            .filter(|(source, target)| ids.contains(source) && ids.contains(target))
+            .map(|(source, target)| GraphEdge {
+                source,
+                target,
+                kind: "link",
+            }),
+    );
+
+    Ok((nodes, edges))
+}
+
+fn node_kind(node: &GraphNode) -> &'static str {
+    if node.is_topic { "topic" } else { "note" }
+}
+
+pub(crate) fn render_dot(nodes: &[GraphNode], edges: &[GraphEdge]) -> String {
+    let mut out = String::from("digraph knowledge {\n");
+    for node in nodes {
+        let shape = if node.is_topic { "box" } else { "ellipse" };
+        let _ = writeln!(
+            out,
+            "  \"{}\" [label=\"{}\", kind=\"{}\", type=\"{}\", scope=\"{}\", shape={}];",
+            dot_escape(&node.id),
+            dot_escape(&node.title),
+            node_kind(node),
+            dot_escape(&node.entry_type),
+            node.scope,
+            shape,
+        );
+    }
+    for edge in edges {
+        let style = if edge.kind == "parent" {
+            "dashed"
+        } else {
+            "solid"
+        };
+        let _ = writeln!(
+            out,
+            "  \"{}\" -> \"{}\" [kind=\"{}\", style={}];",
+            dot_escape(&edge.source),
+            dot_escape(&edge.target),
+            edge.kind,
+            style,
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub(crate) fn render_graphml(nodes: &[GraphNode], edges: &[GraphEdge]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+         \x20 <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"scope\" for=\"node\" attr.name=\"scope\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"edge_kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+         \x20 <graph id=\"knowledge\" edgedefault=\"directed\">\n",
+    );
+    for node in nodes {
+        let _ = writeln!(
+            out,
+            "    <node id=\"{}\"><data key=\"title\">{}</data><data key=\"kind\">{}</data>\
+             <data key=\"type\">{}</data><data key=\"scope\">{}</data></node>",
+            xml_escape(&node.id),
+            xml_escape(&node.title),
+            node_kind(node),
+            xml_escape(&node.entry_type),
+            node.scope,
+        );
+    }
+    for (index, edge) in edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"edge_kind\">{}</data></edge>",
+            index,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            edge.kind,
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn dot_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<GraphNode>, Vec<GraphEdge>) {
+        let nodes = vec![
+            GraphNode {
+                id: "t1".to_string(),
+                title: "Rust \"Async\"".to_string(),
+                entry_type: "Note".to_string(),
+                scope: "shared_note".to_string(),
+                is_topic: true,
+            },
+            GraphNode {
+                id: "n1".to_string(),
+                title: "Tokio & <friends>".to_string(),
+                entry_type: "Concept".to_string(),
+                scope: "ghost_note".to_string(),
+                is_topic: false,
+            },
+        ];
+        let edges = vec![
+            GraphEdge {
+                source: "n1".to_string(),
+                target: "t1".to_string(),
+                kind: "link",
+            },
+            GraphEdge {
+                source: "n1".to_string(),
+                target: "t1".to_string(),
+                kind: "parent",
+            },
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn dot_escapes_labels_and_marks_topics() {
+        let (nodes, edges) = sample();
+        let dot = render_dot(&nodes, &edges);
+        assert!(dot.starts_with("digraph knowledge {"));
+        assert!(dot.contains(r#"label="Rust \"Async\"", kind="topic""#));
+        assert!(dot.contains(r#""n1" -> "t1" [kind="parent", style=dashed];"#));
+    }
+
+    #[test]
+    fn graphml_escapes_xml() {
+        let (nodes, edges) = sample();
+        let xml = render_graphml(&nodes, &edges);
+        assert!(xml.contains("Tokio &amp; &lt;friends&gt;"));
+        assert!(xml.contains(r#"<edge id="e1" source="n1" target="t1">"#));
+        assert!(xml.trim_end().ends_with("</graphml>"));
+    }
+}
@@ -32,7 +32,7 @@ pub(crate) async fn memory_capture(
     scope: WriteScope,
     source: Option<&str>,
 ) -> KnowledgeResult<String> {
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let target_path = match scope {
         WriteScope::SharedNote => crate::paths::shared_notes_root(settings)?,
         WriteScope::GhostNote => ghost_inbox_path(settings, ghost_name)?,
@@ -1,3 +1,5 @@
+use std::sync::{Arc, RwLock};
+
 use chrono::{DateTime, NaiveDate, Utc};
 use sqlx::SqlitePool;
 
@@ -9,40 +11,86 @@ use crate::index::{
     check_embedding_provider_change, reconcile_ghost, reconcile_shared, reindex_embeddings,
 };
 use crate::models::{
-    DiaryQuery, DiarySearchResult, IndexStats, IndexStatsEntry, KnowledgeGetQuery, KnowledgeScope,
-    KnowledgeSearchQuery, KnowledgeSearchResult, MatchedTopic, NoteCreateRequest, NoteDocument,
-    NoteQuery, NoteResult, NoteSummary, NoteUpdateRequest, NoteWriteResult, OwnershipScope,
-    ReferenceFileStatus, ReferenceQuery, ReferenceSaveRequest, ReferenceSaveResult,
-    ReferenceSearchOutput, ReferenceSearchResult, SearchCategory, TopicCreateRequest,
-    TopicCreateResult, TopicListEntry, TopicSearchResult, WriteScope,
+    DiaryQuery, DiarySearchResult, GraphFormat, IndexStats, IndexStatsEntry, IngestBatchRequest,
+    KnowledgeGetQuery, KnowledgeScope, KnowledgeSearchQuery, KnowledgeSearchResult, MatchedTopic,
+    NoteCreateRequest, NoteDocument, NoteQuery, NoteResult, NoteSummary, NoteUpdateRequest,
+    NoteWriteResult, OwnershipScope, ReferenceFileStatus, ReferenceQuery, ReferenceSaveRequest,
+    ReferenceSaveResult, ReferenceSearchOutput, ReferenceSearchResult, SearchCategory,
+    TopicCreateRequest, TopicCreateResult, TopicListEntry, TopicSearchResult, WriteScope,
 };
 use crate::paths::knowledge_db_path;
-use crate::storage::KnowledgeStore;
+use crate::storage::{KnowledgeStore, index_generation, record_note_access};
 
+pub(crate) mod batch;
+pub(crate) mod cache;
+pub(crate) mod clone;
+pub(crate) mod export;
 pub(crate) mod get;
 pub(crate) mod notes;
 pub(crate) mod reference;
+pub(crate) mod rename;
 pub(crate) mod save;
 pub(crate) mod search;
 pub(crate) mod topics;
 
+pub use batch::IngestJob;
 pub use reference::RecentRefSummary;
 
 #[derive(Debug, Clone)]
 pub struct KnowledgeEngine {
-    settings: KnowledgeSettings,
+    live: Arc<RwLock<LiveSettings>>,
     embedder: EmbeddingClient,
     store: KnowledgeStore,
 }
 
+/// Settings and the search cache built from them, swapped together by
+/// [`KnowledgeEngine::reload_settings`].
+#[derive(Debug)]
+struct LiveSettings {
+    settings: Arc<KnowledgeSettings>,
+    search_cache: Arc<cache::SearchCache>,
+}
+
+impl LiveSettings {
+    fn new(settings: KnowledgeSettings) -> Self {
+        let search_cache = Arc::new(cache::SearchCache::new(
+            settings.search.cache_ttl_seconds,
+            settings.search.cache_max_entries,
+        ));
+        Self {
+            settings: Arc::new(settings),
+            search_cache,
+        }
+    }
+}
+
+/// Outcome of [`KnowledgeEngine::reload_settings`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsReload {
+    /// Whether search, reconcile or batch settings changed and now apply.
+    pub applied: bool,
+    /// Changed settings that only take effect after a restart (embedding
+    /// provider/model/dimension, storage paths, language overrides).
+    pub restart_required: Vec<&'static str>,
+}
+
 impl KnowledgeEngine {
     /// Open a persistent KnowledgeEngine that reuses a single DB pool.
     pub async fn open(settings: KnowledgeSettings) -> KnowledgeResult<Self> {
+        let embedder = EmbeddingClient::new(&settings);
+        Self::open_with_embedder(settings, embedder).await
+    }
+
+    /// Open with an externally configured embedding client (e.g. the
+    /// gateway's shared embeddings provider).
+    pub async fn open_with_embedder(
+        settings: KnowledgeSettings,
+        embedder: EmbeddingClient,
+    ) -> KnowledgeResult<Self> {
         let path = knowledge_db_path(&settings)?;
         let store = KnowledgeStore::open(&path, settings.embedding_dim).await?;
-        let embedder = EmbeddingClient::new(&settings);
         Ok(Self {
-            settings,
+            live: Arc::new(RwLock::new(LiveSettings::new(settings))),
             embedder,
             store,
         })
@@ -53,13 +101,75 @@ impl KnowledgeEngine {
         self.store.pool()
     }
 
-    /// Access the knowledge settings.
-    pub fn settings(&self) -> &KnowledgeSettings {
-        &self.settings
+    /// The current knowledge settings.
+    pub fn settings(&self) -> Arc<KnowledgeSettings> {
+        Arc::clone(&self.live.read().unwrap_or_else(|e| e.into_inner()).settings)
+    }
+
+    fn search_cache(&self) -> Arc<cache::SearchCache> {
+        Arc::clone(
+            &self
+                .live
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .search_cache,
+        )
+    }
+
+    /// Apply reloaded settings to the running engine.
+    ///
+    /// Search defaults, the reconcile interval and the embedding batch size
+    /// change in place (the search cache is rebuilt). Everything tied to the
+    /// open store or the embedder is kept and listed in `restart_required`.
+    pub fn reload_settings(&self, new: &KnowledgeSettings) -> SettingsReload {
+        let mut live = self.live.write().unwrap_or_else(|e| e.into_inner());
+        let current = &live.settings;
+
+        let mut restart_required = Vec::new();
+        if current.embedding_provider != new.embedding_provider {
+            restart_required.push("embedding_provider");
+        }
+        if current.embedding_url != new.embedding_url {
+            restart_required.push("embedding_url");
+        }
+        if current.embedding_model != new.embedding_model {
+            restart_required.push("embedding_model");
+        }
+        if current.embedding_api_key_env != new.embedding_api_key_env {
+            restart_required.push("embedding_api_key_env");
+        }
+        if current.embedding_dim != new.embedding_dim {
+            restart_required.push("embedding_dim");
+        }
+        if current.reembed_mode != new.reembed_mode {
+            restart_required.push("reembed_mode");
+        }
+        if current.knowledge_db_path_override != new.knowledge_db_path_override
+            || current.data_root_override != new.data_root_override
+        {
+            restart_required.push("paths");
+        }
+        if current.languages != new.languages {
+            restart_required.push("languages");
+        }
+
+        let mut updated = KnowledgeSettings::clone(current);
+        updated.search = new.search.clone();
+        updated.reconcile_seconds = new.reconcile_seconds;
+        updated.embedding_batch = new.embedding_batch;
+        let applied = updated != **current;
+        if applied {
+            *live = LiveSettings::new(updated);
+        }
+
+        SettingsReload {
+            applied,
+            restart_required,
+        }
     }
 
-    /// Access the embedding client (crate-internal).
-    pub(crate) fn embedder(&self) -> &EmbeddingClient {
+    /// Access the embedding client.
+    pub fn embedder(&self) -> &EmbeddingClient {
         &self.embedder
     }
 
@@ -74,7 +184,7 @@ impl KnowledgeEngine {
         for scope in scopes {
             self.maybe_reconcile(ghost_name, scope).await?;
             let partial = search::search_store(
-                &self.settings,
+                &self.settings(),
                 &self.embedder,
                 self.store.pool(),
                 &query,
@@ -89,7 +199,7 @@ impl KnowledgeEngine {
         let max_results = query
             .options
             .max_results
-            .unwrap_or(self.settings.search.max_results);
+            .unwrap_or(self.settings().search.max_results);
         results.sort_by(|a, b| {
             b.summary
                 .score
@@ -110,7 +220,7 @@ impl KnowledgeEngine {
         self.maybe_reconcile(ghost_name, KnowledgeScope::GhostDiary)
             .await?;
         search::search_diary(
-            &self.settings,
+            &self.settings(),
             &self.embedder,
             self.store.pool(),
             &query,
@@ -125,7 +235,9 @@ impl KnowledgeEngine {
         note_id_or_title: &str,
         scope: OwnershipScope,
     ) -> KnowledgeResult<NoteDocument> {
-        get::memory_get(self, ghost_name, note_id_or_title, scope).await
+        let doc = get::memory_get(self, ghost_name, note_id_or_title, scope).await?;
+        self.note_accessed(&doc.id).await;
+        Ok(doc)
     }
 
     pub async fn memory_capture(
@@ -157,6 +269,16 @@ impl KnowledgeEngine {
         notes::note_update(self, ghost_name, request).await
     }
 
+    /// Put back a note's earlier raw file content (e.g. to undo an update).
+    pub async fn note_restore(
+        &self,
+        ghost_name: &str,
+        note_id: &str,
+        raw: &str,
+    ) -> KnowledgeResult<NoteWriteResult> {
+        notes::note_restore(self, ghost_name, note_id, raw).await
+    }
+
     /// Record validation metadata and optionally adjust trust score.
     pub async fn note_validate(
         &self,
@@ -179,6 +301,26 @@ impl KnowledgeEngine {
         notes::note_comment(self, ghost_name, model, note_id, text).await
     }
 
+    /// Scope and owning ghost (`None` for shared entries) of a note, for
+    /// admin tools that act on any ghost's knowledge.
+    pub async fn note_owner(
+        &self,
+        note_id: &str,
+    ) -> KnowledgeResult<(KnowledgeScope, Option<String>)> {
+        let row = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT scope, owner_ghost FROM notes WHERE id = ? LIMIT 1",
+        )
+        .bind(note_id)
+        .fetch_optional(self.pool())
+        .await?;
+        let (scope, owner_ghost) =
+            row.ok_or_else(|| KnowledgeError::UnknownNote(note_id.to_string()))?;
+        let scope = scope
+            .parse()
+            .map_err(|_| KnowledgeError::UnknownNote(note_id.to_string()))?;
+        Ok((scope, owner_ghost))
+    }
+
     /// Delete a note and all associated DB records (chunks, tags, links).
     pub async fn note_delete(&self, ghost_name: &str, note_id: &str) -> KnowledgeResult<()> {
         notes::note_delete(self, ghost_name, note_id).await
@@ -242,7 +384,9 @@ impl KnowledgeEngine {
         file_path: Option<&str>,
         max_chars: Option<usize>,
     ) -> KnowledgeResult<NoteDocument> {
-        reference::reference_get(self, note_id, topic, file_path, max_chars).await
+        let doc = reference::reference_get(self, note_id, topic, file_path, max_chars).await?;
+        self.note_accessed(&doc.id).await;
+        Ok(doc)
     }
 
     /// Get reference files saved since a given RFC3339 timestamp.
@@ -272,6 +416,25 @@ impl KnowledgeEngine {
         save::reference_save(self, ghost_name, model, request).await
     }
 
+    /// Check that a bulk ingest request targets an existing topic and has items,
+    /// so callers queuing it for later can reject it right away.
+    pub async fn check_ingest_batch(&self, request: &IngestBatchRequest) -> KnowledgeResult<()> {
+        batch::check_ingest_batch(self, request).await.map(|_| ())
+    }
+
+    /// Ingest many files/URLs into an existing reference topic in the background.
+    ///
+    /// Returns immediately with a job handle; progress events arrive on
+    /// `IngestJob::progress` until a final `IngestProgress::Finished`.
+    pub async fn ingest_batch(
+        &self,
+        ghost_name: &str,
+        model: &str,
+        request: IngestBatchRequest,
+    ) -> KnowledgeResult<IngestJob> {
+        batch::ingest_batch(self, ghost_name, model, request).await
+    }
+
     /// Build an approval summary for a topic creation request (Phase 1).
     pub async fn topic_approval_summary(
         &self,
@@ -295,9 +458,59 @@ impl KnowledgeEngine {
         topics::topic_search(self, query).await
     }
 
-    /// List all reference topics with staleness info.
-    pub async fn topic_list(&self, include_obsolete: bool) -> KnowledgeResult<Vec<TopicListEntry>> {
-        topics::topic_list(self, include_obsolete).await
+    /// List reference topics, optionally including archived ones.
+    pub async fn topic_list(&self, include_archived: bool) -> KnowledgeResult<Vec<TopicListEntry>> {
+        topics::topic_list(self, include_archived).await
+    }
+
+    /// Archive a reference topic by ID or title.
+    ///
+    /// Archived topics keep their files and index rows but are excluded from
+    /// search, topic listings, and recent topics until restored.
+    pub async fn topic_archive(
+        &self,
+        ghost_name: &str,
+        topic: &str,
+        reason: Option<&str>,
+    ) -> KnowledgeResult<(String, String)> {
+        topics::topic_archive(self, ghost_name, topic, reason).await
+    }
+
+    /// Files of a reference topic, ordered by path within the topic.
+    pub async fn topic_files(&self, topic_id: &str) -> KnowledgeResult<Vec<NoteSummary>> {
+        let rows =
+            sqlx::query_as::<_, (String, String, String, Option<String>, String, i64, String)>(
+                "SELECT n.id, n.title, n.entry_type, n.archetype, rf.path, n.trust_score, n.scope
+             FROM reference_files rf
+             JOIN notes n ON n.id = rf.note_id
+             WHERE rf.topic_id = ?
+             ORDER BY rf.path",
+            )
+            .bind(topic_id)
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, title, entry_type, archetype, path, trust_score, scope)| NoteSummary {
+                    id,
+                    title,
+                    entry_type,
+                    archetype,
+                    path: path.into(),
+                    scope: scope.parse().unwrap_or(KnowledgeScope::SharedReference),
+                    trust_score,
+                    score: 0.0,
+                    snippet: String::new(),
+                },
+            )
+            .collect())
+    }
+
+    /// Restore an archived reference topic. Returns `(topic_id, title)`.
+    pub async fn topic_restore(&self, topic: &str) -> KnowledgeResult<(String, String)> {
+        topics::topic_restore(self, topic).await
     }
 
     /// Get recent reference topics for system prompt injection.
@@ -312,16 +525,15 @@ impl KnowledgeEngine {
     /// Searches active categories in parallel, then merges results using a
     /// min-per-category budget algorithm: each non-empty category gets at
     /// least 1 result, remaining budget is filled by global score ranking.
+    ///
+    /// Results are cached per (ghost, query) and invalidated whenever the
+    /// index generation changes or the configured TTL expires.
     pub async fn knowledge_search(
         &self,
         ghost_name: &str,
         query: KnowledgeSearchQuery,
     ) -> KnowledgeResult<KnowledgeSearchResult> {
         let categories = query.categories.clone().unwrap_or_else(SearchCategory::all);
-        let max_results = query
-            .options
-            .max_results
-            .unwrap_or(self.settings.search.max_results);
 
         // Reconcile scopes that will be queried
         let needs_shared = categories.iter().any(|c| {
@@ -344,6 +556,30 @@ impl KnowledgeEngine {
                 .await?;
         }
 
+        let generation = index_generation(self.pool()).await?;
+        let cache_key = cache::SearchCache::key(ghost_name, &query);
+        if let Some(cached) = self.search_cache().get(&cache_key, generation) {
+            return Ok(cached);
+        }
+
+        let result = self
+            .search_categories(ghost_name, &query, &categories)
+            .await?;
+        self.search_cache().insert(cache_key, generation, &result);
+        Ok(result)
+    }
+
+    async fn search_categories(
+        &self,
+        ghost_name: &str,
+        query: &KnowledgeSearchQuery,
+        categories: &[SearchCategory],
+    ) -> KnowledgeResult<KnowledgeSearchResult> {
+        let max_results = query
+            .options
+            .max_results
+            .unwrap_or(self.settings().search.max_results);
+
         // ── Per-category search ─────────────────────────────────────
 
         let mut notes = Vec::new();
@@ -356,7 +592,7 @@ impl KnowledgeEngine {
             };
             for scope in scopes {
                 let partial = search::search_store(
-                    &self.settings,
+                    &self.settings(),
                     &self.embedder,
                     self.store.pool(),
                     &note_query,
@@ -367,6 +603,11 @@ impl KnowledgeEngine {
                 .await?;
                 notes.extend(partial);
             }
+            // Archived topic notes stay indexed but are hidden from search.
+            let archived = topics::archived_topic_ids(self.pool()).await?;
+            if !archived.is_empty() {
+                notes.retain(|r| !archived.contains(&r.summary.id));
+            }
             notes.sort_by(|a, b| {
                 b.summary
                     .score
@@ -382,7 +623,7 @@ impl KnowledgeEngine {
                 options: query.options.clone(),
             };
             diary = search::search_diary(
-                &self.settings,
+                &self.settings(),
                 &self.embedder,
                 self.store.pool(),
                 &diary_query,
@@ -631,6 +872,7 @@ impl KnowledgeEngine {
                 {
                     doc.body = doc.body.chars().take(limit).collect();
                 }
+                self.note_accessed(&doc.id).await;
                 return Ok(doc);
             }
         }
@@ -638,6 +880,15 @@ impl KnowledgeEngine {
         Err(KnowledgeError::UnknownNote(id.to_string()))
     }
 
+    /// Record a read so gradual re-embedding can prioritize recently used notes.
+    ///
+    /// Best effort: a failed write never fails the read itself.
+    async fn note_accessed(&self, note_id: &str) {
+        if let Err(e) = record_note_access(self.pool(), note_id).await {
+            tracing::debug!("failed to record access for note {note_id}: {e}");
+        }
+    }
+
     /// List recently updated notes across all scopes (no embeddings needed).
     ///
     /// If `ghost_name` is non-empty, reconciles both shared and ghost scopes
@@ -683,7 +934,49 @@ impl KnowledgeEngine {
             .collect())
     }
 
-    /// Retrieve index statistics: note/chunk/embedding counts and latest entries.
+    /// Export notes, topics, and their link/parent edges as DOT or GraphML.
+    ///
+    /// `scope` selects shared notes, the ghost's private notes, or both.
+    pub async fn export_graph(
+        &self,
+        ghost_name: &str,
+        scope: OwnershipScope,
+        format: GraphFormat,
+    ) -> KnowledgeResult<String> {
+        if !matches!(scope, OwnershipScope::Private) {
+            self.maybe_reconcile(ghost_name, KnowledgeScope::SharedNote)
+                .await?;
+        }
+        if !matches!(scope, OwnershipScope::Shared) {
+            self.maybe_reconcile(ghost_name, KnowledgeScope::GhostNote)
+                .await?;
+        }
+        export::export_graph(self, ghost_name, scope, format).await
+    }
+
+    /// Copy the selected private scopes of `source` into `target`.
+    ///
+    /// Cloned notes get fresh IDs; shared scopes are skipped. Returns the
+    /// number of files written.
+    pub async fn clone_ghost_knowledge(
+        &self,
+        source: &str,
+        target: &str,
+        scopes: &[KnowledgeScope],
+    ) -> KnowledgeResult<usize> {
+        clone::clone_ghost_knowledge(self, source, target, scopes).await
+    }
+
+    /// Rewrite every index row keyed by a ghost's name after a rename.
+    ///
+    /// Callers move the workspace directory themselves; this only updates
+    /// ownership, paths, diary IDs and the reconcile bookkeeping.
+    pub async fn rename_ghost(&self, old_name: &str, new_name: &str) -> KnowledgeResult<()> {
+        rename::rename_ghost(self, old_name, new_name).await
+    }
+
+    /// Retrieve index statistics: note/chunk/embedding counts, index health
+    /// and latest entries.
     pub async fn index_stats(&self) -> KnowledgeResult<IndexStats> {
         let pool = self.pool();
 
@@ -702,6 +995,13 @@ impl KnowledgeEngine {
             .map(|(c,)| c)
             .unwrap_or(0);
 
+        let missing_embeddings = crate::storage::count_chunks_needing_embedding(pool).await?;
+        let (orphaned_chunks,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM chunks WHERE note_id NOT IN (SELECT id FROM notes)",
+        )
+        .fetch_one(pool)
+        .await?;
+
         let recent = sqlx::query_as::<_, (String, String, String, String)>(
             "SELECT title, entry_type, scope, updated_at FROM notes ORDER BY updated_at DESC LIMIT 10",
         )
@@ -712,8 +1012,10 @@ impl KnowledgeEngine {
             total_notes,
             total_chunks,
             total_embeddings,
-            embedding_model: self.settings.embedding_model.clone(),
-            embedding_dim: self.settings.embedding_dim.unwrap_or(0) as u32,
+            missing_embeddings,
+            orphaned_chunks,
+            embedding_model: self.settings().embedding_model.clone(),
+            embedding_dim: self.settings().embedding_dim.unwrap_or(0) as u32,
             recent_entries: recent
                 .into_iter()
                 .map(|(title, entry_type, scope, updated_at)| IndexStatsEntry {
@@ -730,7 +1032,7 @@ impl KnowledgeEngine {
     ///
     /// Returns `true` if embeddings were invalidated and a reindex is needed.
     pub async fn check_embedding_change(&self) -> KnowledgeResult<bool> {
-        check_embedding_provider_change(&self.settings, self.store.pool()).await
+        check_embedding_provider_change(&self.settings(), self.store.pool()).await
     }
 
     /// Re-embed chunks that are missing embedding vectors.
@@ -738,7 +1040,7 @@ impl KnowledgeEngine {
     /// Processes up to `max_chunks` in batches. Returns count of re-embedded chunks.
     pub async fn reindex_embeddings(&self, max_chunks: usize) -> KnowledgeResult<usize> {
         reindex_embeddings(
-            &self.settings,
+            &self.settings(),
             self.store.pool(),
             &self.embedder,
             max_chunks,
@@ -773,7 +1075,7 @@ impl KnowledgeEngine {
             Some((value,)) => DateTime::parse_from_rfc3339(&value)
                 .map(|dt| {
                     (now - dt.with_timezone(&Utc)).num_seconds() as u64
-                        > self.settings.reconcile_seconds
+                        > self.settings().reconcile_seconds
                 })
                 .unwrap_or(true),
             None => true,
@@ -782,10 +1084,10 @@ impl KnowledgeEngine {
         if should_run {
             match scope {
                 KnowledgeScope::SharedNote | KnowledgeScope::SharedReference => {
-                    reconcile_shared(&self.settings, pool, &self.embedder).await?;
+                    reconcile_shared(&self.settings(), pool, &self.embedder).await?;
                 }
                 _ => {
-                    reconcile_ghost(&self.settings, pool, &self.embedder, ghost_name).await?;
+                    reconcile_ghost(&self.settings(), pool, &self.embedder, ghost_name).await?;
                 }
             }
             sqlx::query("INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)")
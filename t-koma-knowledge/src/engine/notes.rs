@@ -21,7 +21,7 @@ pub(crate) async fn note_create(
     let note_id = generate_note_id();
     let now = Utc::now();
     let (target_dir, scope, owner_ghost) =
-        resolve_write_target(engine.settings(), ghost_name, &request.scope)?;
+        resolve_write_target(&engine.settings(), ghost_name, &request.scope)?;
 
     // Derive subfolder from first tag (creation-time only, files don't move on tag change)
     let target_dir = if let Some(tags) = &request.tags {
@@ -60,7 +60,7 @@ pub(crate) async fn note_create(
 
     // Index inline
     let ingested =
-        crate::ingest::ingest_markdown(engine.settings(), scope, owner_ghost, &path, &content)
+        crate::ingest::ingest_markdown(&engine.settings(), scope, owner_ghost, &path, &content)
             .await?;
     let pool = engine.pool();
     crate::storage::upsert_note(pool, &ingested.note).await?;
@@ -82,7 +82,7 @@ pub(crate) async fn note_create(
     )
     .await?;
     crate::index::embed_chunks(
-        engine.settings(),
+        &engine.settings(),
         engine.embedder(),
         pool,
         &ingested.chunks,
@@ -133,7 +133,47 @@ pub(crate) async fn note_update(
     tokio::fs::write(&tmp_path, &content).await?;
     tokio::fs::rename(&tmp_path, &doc.path).await?;
 
-    // Re-index
+    reindex_note(engine, ghost_name, &request.note_id, &doc, &content).await?;
+
+    Ok(NoteWriteResult {
+        note_id: request.note_id,
+        path: doc.path,
+    })
+}
+
+/// Put back the raw file content a note had before a write and re-index it.
+pub(crate) async fn note_restore(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    note_id: &str,
+    raw: &str,
+) -> KnowledgeResult<NoteWriteResult> {
+    let doc = engine
+        .memory_get(ghost_name, note_id, OwnershipScope::All)
+        .await?;
+    verify_write_access(ghost_name, &doc)?;
+    crate::parser::parse_note(raw)?;
+
+    let tmp_path = doc.path.with_extension("md.tmp");
+    tokio::fs::write(&tmp_path, raw).await?;
+    tokio::fs::rename(&tmp_path, &doc.path).await?;
+
+    reindex_note(engine, ghost_name, note_id, &doc, raw).await?;
+
+    Ok(NoteWriteResult {
+        note_id: note_id.to_string(),
+        path: doc.path,
+    })
+}
+
+/// Re-index the rewritten `content` of `doc`: note row, tags, links and chunks.
+async fn reindex_note(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    note_id: &str,
+    doc: &NoteDocument,
+    content: &str,
+) -> KnowledgeResult<()> {
     let scope = doc.scope;
     let owner_ghost = if scope.is_shared() {
         None
@@ -141,21 +181,21 @@ pub(crate) async fn note_update(
         Some(ghost_name.to_string())
     };
     let ingested =
-        crate::ingest::ingest_markdown(engine.settings(), scope, owner_ghost, &doc.path, &content)
+        crate::ingest::ingest_markdown(&engine.settings(), scope, owner_ghost, &doc.path, content)
             .await?;
     let pool = engine.pool();
     crate::storage::upsert_note(pool, &ingested.note).await?;
-    crate::storage::replace_tags(pool, &request.note_id, &ingested.tags).await?;
+    crate::storage::replace_tags(pool, note_id, &ingested.tags).await?;
     crate::storage::replace_links(
         pool,
-        &request.note_id,
+        note_id,
         ingested.note.owner_ghost.as_deref(),
         &ingested.links,
     )
     .await?;
     let chunk_ids = crate::storage::replace_chunks(
         pool,
-        &request.note_id,
+        note_id,
         &ingested.note.title,
         &ingested.note.entry_type,
         ingested.note.archetype.as_deref(),
@@ -163,18 +203,13 @@ pub(crate) async fn note_update(
     )
     .await?;
     crate::index::embed_chunks(
-        engine.settings(),
+        &engine.settings(),
         engine.embedder(),
         pool,
         &ingested.chunks,
         &chunk_ids,
     )
-    .await?;
-
-    Ok(NoteWriteResult {
-        note_id: request.note_id,
-        path: doc.path,
-    })
+    .await
 }
 
 pub(crate) async fn note_validate(
@@ -218,7 +253,7 @@ pub(crate) async fn note_validate(
         Some(ghost_name.to_string())
     };
     let ingested =
-        crate::ingest::ingest_markdown(engine.settings(), scope, owner_ghost, &doc.path, &content)
+        crate::ingest::ingest_markdown(&engine.settings(), scope, owner_ghost, &doc.path, &content)
             .await?;
     crate::storage::upsert_note(engine.pool(), &ingested.note).await?;
 
@@ -268,7 +303,7 @@ pub(crate) async fn note_comment(
         Some(ghost_name.to_string())
     };
     let ingested =
-        crate::ingest::ingest_markdown(engine.settings(), scope, owner_ghost, &doc.path, &content)
+        crate::ingest::ingest_markdown(&engine.settings(), scope, owner_ghost, &doc.path, &content)
             .await?;
     crate::storage::upsert_note(engine.pool(), &ingested.note).await?;
 
@@ -20,7 +20,7 @@ pub(crate) async fn reference_search(
     query: &ReferenceQuery,
 ) -> KnowledgeResult<ReferenceSearchResult> {
     let pool = engine.pool();
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let embedder = engine.embedder();
 
     let topics = search_reference_topics(settings, embedder, pool, query).await?;
@@ -110,7 +110,7 @@ async fn search_reference_files(
         filter_ids
     );
 
-    let safe_question = sanitize_fts5_query(&query.question);
+    let safe_question = sanitize_fts5_query(&query.question, settings);
     let mut query_builder = sqlx::query_as::<_, (i64, f32)>(&sql);
     query_builder = query_builder.bind(&safe_question);
     for id in &note_ids {
@@ -182,11 +182,12 @@ async fn search_reference_topics(
     pool: &SqlitePool,
     query: &ReferenceQuery,
 ) -> KnowledgeResult<Vec<NoteResult>> {
-    // Topics are shared notes that have reference files
+    // Topics are shared notes that have reference files; archived ones are skipped
     let topic_ids = sqlx::query_as::<_, (String,)>(
         "SELECT DISTINCT n.id FROM notes n \
          JOIN reference_files rf ON rf.topic_id = n.id \
-         WHERE n.scope = 'shared_note'",
+         WHERE n.scope = 'shared_note' \
+         AND n.id NOT IN (SELECT topic_id FROM archived_topics)",
     )
     .fetch_all(pool)
     .await?
@@ -203,7 +204,7 @@ async fn search_reference_topics(
         "SELECT chunk_id, bm25(chunk_fts) as score FROM chunk_fts JOIN notes ON notes.id = chunk_fts.note_id WHERE chunk_fts MATCH ? AND notes.id IN ({}) ORDER BY score ASC LIMIT ?",
         placeholders
     );
-    let safe_topic = sanitize_fts5_query(&query.topic);
+    let safe_topic = sanitize_fts5_query(&query.topic, settings);
     let mut query_builder = sqlx::query_as::<_, (i64, f32)>(&sql);
     query_builder = query_builder.bind(&safe_topic);
     for id in &topic_ids {
@@ -261,7 +262,7 @@ async fn search_reference_topics(
     Ok(results)
 }
 
-/// Search ALL non-obsolete reference files across all topics.
+/// Search ALL non-obsolete reference files across all non-archived topics.
 ///
 /// Unlike `search_reference_files` which is scoped to a single topic,
 /// this searches the entire reference corpus. Used by the unified
@@ -272,12 +273,13 @@ pub(crate) async fn search_all_reference_files(
     options: &SearchOptions,
 ) -> KnowledgeResult<Vec<NoteResult>> {
     let pool = engine.pool();
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let embedder = engine.embedder();
 
-    // Fetch all non-obsolete reference file note_ids (excluding topic/collection notes)
+    // Fetch all non-obsolete reference file note_ids of non-archived topics
     let rows = sqlx::query_as::<_, (String, String)>(
-        "SELECT note_id, status FROM reference_files WHERE status != 'obsolete'",
+        "SELECT note_id, status FROM reference_files WHERE status != 'obsolete' \
+         AND topic_id NOT IN (SELECT topic_id FROM archived_topics)",
     )
     .fetch_all(pool)
     .await?;
@@ -307,7 +309,7 @@ pub(crate) async fn search_all_reference_files(
          ORDER BY score ASC LIMIT ?",
         filter_ids
     );
-    let safe_query = sanitize_fts5_query(query_str);
+    let safe_query = sanitize_fts5_query(query_str, settings);
     let mut qb = sqlx::query_as::<_, (i64, f32)>(&sql);
     qb = qb.bind(&safe_query);
     for id in &note_ids {
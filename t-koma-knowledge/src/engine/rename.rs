@@ -0,0 +1,96 @@
+//! Index rewrite for a ghost rename.
+//!
+//! Ghost names appear in ownership/provenance columns, in file paths under
+//! `$DATA/ghosts/$name/`, in deterministic diary note IDs
+//! (`diary:{ghost}:{date}`) and in the per-ghost reconcile meta key. All of
+//! them are rewritten in one transaction so the index matches the moved
+//! workspace without re-embedding anything.
+
+use crate::errors::KnowledgeResult;
+use crate::paths::ghost_root;
+
+use super::KnowledgeEngine;
+
+/// Tables and columns holding note IDs (diary IDs embed the ghost name).
+const NOTE_ID_COLUMNS: &[(&str, &str)] = &[
+    ("notes", "id"),
+    ("notes", "parent_id"),
+    ("note_tags", "note_id"),
+    ("note_links", "source_id"),
+    ("note_links", "target_id"),
+    ("chunks", "note_id"),
+    ("chunk_fts", "note_id"),
+    ("note_access", "note_id"),
+];
+
+/// Columns holding a ghost name.
+const GHOST_NAME_COLUMNS: &[(&str, &str)] = &[
+    ("notes", "owner_ghost"),
+    ("notes", "created_by_ghost"),
+    ("notes", "last_validated_by_ghost"),
+    ("note_links", "owner_ghost"),
+    ("archived_topics", "archived_by_ghost"),
+];
+
+/// Columns holding absolute file paths.
+const PATH_COLUMNS: &[(&str, &str)] = &[("notes", "path"), ("reference_files", "path")];
+
+pub(crate) async fn rename_ghost(
+    engine: &KnowledgeEngine,
+    old_name: &str,
+    new_name: &str,
+) -> KnowledgeResult<()> {
+    let settings = &engine.settings();
+    let old_root = format!("{}/", ghost_root(settings, old_name)?.display());
+    let new_root = format!("{}/", ghost_root(settings, new_name)?.display());
+    let old_diary = format!("diary:{old_name}:");
+    let new_diary = format!("diary:{new_name}:");
+
+    let mut tx = engine.pool().begin().await?;
+
+    for (table, column) in GHOST_NAME_COLUMNS {
+        sqlx::query(&format!(
+            "UPDATE {table} SET {column} = ? WHERE {column} = ?"
+        ))
+        .bind(new_name)
+        .bind(old_name)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let prefixed = [
+        (PATH_COLUMNS, old_root.as_str(), new_root.as_str()),
+        (NOTE_ID_COLUMNS, old_diary.as_str(), new_diary.as_str()),
+    ];
+    for (columns, old_prefix, new_prefix) in prefixed {
+        // substr() instead of LIKE: ghost names may contain `_`.
+        let len = old_prefix.chars().count() as i64;
+        for (table, column) in columns {
+            sqlx::query(&format!(
+                "UPDATE {table} SET {column} = ? || substr({column}, ?)
+                 WHERE substr({column}, 1, ?) = ?"
+            ))
+            .bind(new_prefix)
+            .bind(len + 1)
+            .bind(len)
+            .bind(old_prefix)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    let old_key = format!("last_reconcile_ghost:{old_name}");
+    let new_key = format!("last_reconcile_ghost:{new_name}");
+    sqlx::query("DELETE FROM meta WHERE key = ?")
+        .bind(&new_key)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE meta SET key = ? WHERE key = ?")
+        .bind(&new_key)
+        .bind(&old_key)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
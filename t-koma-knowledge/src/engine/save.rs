@@ -22,7 +22,7 @@ pub(crate) async fn reference_save(
     request: ReferenceSaveRequest,
 ) -> KnowledgeResult<ReferenceSaveResult> {
     let pool = engine.pool();
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let embedder = engine.embedder();
 
     // 1. Resolve topic — must exist as a shared note (or auto-create _web-cache)
@@ -7,10 +7,12 @@ use crate::KnowledgeSettings;
 use crate::embeddings::EmbeddingClient;
 use crate::errors::{KnowledgeError, KnowledgeResult};
 use crate::graph::{load_links_in, load_links_out, load_parent, load_tags};
+use crate::language::{detect_language, prepare_bm25_text};
 use crate::models::{
     DiaryQuery, DiarySearchResult, KnowledgeScope, NoteQuery, NoteResult, NoteSummary,
     OwnershipScope, SearchOptions,
 };
+use crate::storage::active_migration_source;
 
 pub(crate) async fn search_store(
     settings: &KnowledgeSettings,
@@ -23,6 +25,7 @@ pub(crate) async fn search_store(
 ) -> KnowledgeResult<Vec<NoteResult>> {
     let options = merge_options(settings, &query.options);
     let bm25_hits = bm25_search(
+        settings,
         pool,
         &query.query,
         options.bm25_limit,
@@ -108,6 +111,7 @@ pub(crate) async fn search_diary(
     let scope = KnowledgeScope::GhostDiary;
     let options = merge_options(settings, &query.options);
     let bm25_hits = bm25_search(
+        settings,
         pool,
         &query.query,
         options.bm25_limit,
@@ -152,8 +156,11 @@ pub(crate) async fn search_diary(
 /// raw user input can cause SQLite parse errors. We split on whitespace
 /// and wrap each non-empty token in double quotes, joining with spaces
 /// so FTS5 treats them as an implicit AND of literal terms.
-pub(crate) fn sanitize_fts5_query(raw: &str) -> String {
-    let tokens: Vec<String> = raw
+pub(crate) fn sanitize_fts5_query(raw: &str, settings: &KnowledgeSettings) -> String {
+    // Apply the same tokenization the matching chunks were indexed with.
+    let tokenizer = settings.bm25_tokenizer_for(detect_language(raw));
+    let prepared = prepare_bm25_text(raw, tokenizer);
+    let tokens: Vec<String> = prepared
         .split_whitespace()
         .filter(|t| !t.is_empty())
         .map(|t| format!("\"{}\"", t.replace('"', "")))
@@ -165,6 +172,7 @@ pub(crate) fn sanitize_fts5_query(raw: &str) -> String {
 }
 
 pub(crate) async fn bm25_search(
+    settings: &KnowledgeSettings,
     pool: &SqlitePool,
     query: &str,
     limit: usize,
@@ -172,7 +180,7 @@ pub(crate) async fn bm25_search(
     ghost_name: &str,
     archetype: Option<&str>,
 ) -> KnowledgeResult<Vec<(i64, f32)>> {
-    let safe_query = sanitize_fts5_query(query);
+    let safe_query = sanitize_fts5_query(query, settings);
     let scope_value = scope.as_str();
     let archetype_clause = if archetype.is_some() {
         " AND notes.archetype = ?"
@@ -238,7 +246,85 @@ pub(crate) async fn dense_search(
         return Ok(Vec::new());
     }
 
-    let embeddings = embedder.embed_batch(&[query.to_string()]).await?;
+    // Vectors from different models are not comparable: embed the query with
+    // the model for its language and only match chunks embedded the same way.
+    let model = embedder.model_for_language(detect_language(query));
+
+    // During a gradual migration, chunks still on the old model are matched
+    // with a query vector from that model and the passes are merged.
+    let migration_source = active_migration_source(pool)
+        .await?
+        .filter(|source| source != model);
+    let Some(source) = migration_source else {
+        let model_filter = embedder.routes_languages().then_some(model);
+        return dense_knn(
+            embedder,
+            pool,
+            query,
+            model,
+            model_filter,
+            limit,
+            note_filter,
+            scope,
+            ghost_name,
+            archetype,
+        )
+        .await;
+    };
+
+    let mut rows = dense_knn(
+        embedder,
+        pool,
+        query,
+        model,
+        Some(model),
+        limit,
+        note_filter,
+        scope,
+        ghost_name,
+        archetype,
+    )
+    .await?;
+    match dense_knn(
+        embedder,
+        pool,
+        query,
+        &source,
+        Some(&source),
+        limit,
+        note_filter,
+        scope,
+        ghost_name,
+        archetype,
+    )
+    .await
+    {
+        Ok(old_rows) => rows.extend(old_rows),
+        Err(e) => warn!("dense search on pre-migration model '{source}' failed: {e}"),
+    }
+    rows.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    rows.truncate(limit);
+    Ok(rows)
+}
+
+/// One KNN pass: embed `query` with `model` and match chunks, optionally
+/// restricted to those embedded with `model_filter`.
+#[allow(clippy::too_many_arguments)]
+async fn dense_knn(
+    embedder: &EmbeddingClient,
+    pool: &SqlitePool,
+    query: &str,
+    model: &str,
+    model_filter: Option<&str>,
+    limit: usize,
+    note_filter: Option<&[String]>,
+    scope: KnowledgeScope,
+    ghost_name: &str,
+    archetype: Option<&str>,
+) -> KnowledgeResult<Vec<(i64, f32)>> {
+    let embeddings = embedder
+        .embed_batch_with_model(model, &[query.to_string()])
+        .await?;
     if embeddings.is_empty() {
         return Ok(Vec::new());
     }
@@ -249,11 +335,19 @@ pub(crate) async fn dense_search(
     // through JOINs. We overfetch in the CTE, then filter+limit in the outer query.
     let knn_k = limit * 4; // overfetch to allow for scope/owner filtering
 
-    let archetype_clause = if archetype.is_some() {
-        " AND n.archetype = ?"
-    } else {
-        ""
-    };
+    let archetype_clause = format!(
+        "{}{}",
+        if archetype.is_some() {
+            " AND n.archetype = ?"
+        } else {
+            ""
+        },
+        if model_filter.is_some() {
+            " AND (c.embedding_model IS NULL OR c.embedding_model = ?)"
+        } else {
+            ""
+        }
+    );
 
     let rows = if let Some(note_ids) = note_filter {
         let placeholders = note_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
@@ -290,6 +384,9 @@ pub(crate) async fn dense_search(
         if let Some(arch) = archetype {
             query_builder = query_builder.bind(arch);
         }
+        if let Some(model) = model_filter {
+            query_builder = query_builder.bind(model);
+        }
         query_builder = query_builder.bind(limit as i64);
         query_builder.fetch_all(pool).await?
     } else {
@@ -322,6 +419,9 @@ pub(crate) async fn dense_search(
         if let Some(arch) = archetype {
             qb = qb.bind(arch);
         }
+        if let Some(model) = model_filter {
+            qb = qb.bind(model);
+        }
         qb = qb.bind(limit as i64);
         qb.fetch_all(pool).await?
     };
@@ -1,13 +1,15 @@
 //! Engine methods for reference topic management.
 //!
 //! Topics are regular shared notes. This module handles topic creation
-//! (two-phase with approval), searching, and listing via joins against
-//! the `reference_files` table.
+//! (two-phase with approval), searching, listing, and archiving via joins
+//! against the `reference_files` and `archived_topics` tables.
+
+use std::collections::HashSet;
 
 use chrono::Utc;
 use sqlx::SqlitePool;
 
-use crate::errors::KnowledgeResult;
+use crate::errors::{KnowledgeError, KnowledgeResult};
 use crate::models::{
     KnowledgeScope, NoteCreateRequest, SourceRole, TopicCreateRequest, TopicCreateResult,
     TopicListEntry, TopicSearchResult, WriteScope, generate_note_id,
@@ -36,7 +38,7 @@ pub(crate) async fn topic_create_execute(
     model: &str,
     request: TopicCreateRequest,
 ) -> KnowledgeResult<TopicCreateResult> {
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let pool = engine.pool();
     let embedder = engine.embedder();
 
@@ -170,14 +172,15 @@ pub(crate) async fn topic_search(
     query: &str,
 ) -> KnowledgeResult<Vec<TopicSearchResult>> {
     let pool = engine.pool();
-    let settings = engine.settings();
+    let settings = &engine.settings();
     let embedder = engine.embedder();
 
-    // Find topic IDs: shared notes that have reference files
+    // Find topic IDs: shared notes that have reference files, minus archived ones
     let topic_ids = sqlx::query_as::<_, (String,)>(
         "SELECT DISTINCT n.id FROM notes n \
          JOIN reference_files rf ON rf.topic_id = n.id \
-         WHERE n.scope = 'shared_note'",
+         WHERE n.scope = 'shared_note' \
+         AND n.id NOT IN (SELECT topic_id FROM archived_topics)",
     )
     .fetch_all(pool)
     .await?
@@ -195,7 +198,7 @@ pub(crate) async fn topic_search(
         "SELECT chunk_id, bm25(chunk_fts) as score FROM chunk_fts JOIN notes ON notes.id = chunk_fts.note_id WHERE chunk_fts MATCH ? AND notes.id IN ({}) ORDER BY score ASC LIMIT ?",
         placeholders
     );
-    let safe_query = sanitize_fts5_query(query);
+    let safe_query = sanitize_fts5_query(query, settings);
     let mut qb = sqlx::query_as::<_, (i64, f32)>(&sql);
     qb = qb.bind(&safe_query);
     for id in &topic_ids {
@@ -239,25 +242,29 @@ pub(crate) async fn topic_search(
     Ok(results)
 }
 
-/// List all reference topics (shared notes with reference files).
+/// List reference topics (shared notes with reference files).
+///
+/// Archived topics are only included when `include_archived` is set.
 pub(crate) async fn topic_list(
     engine: &KnowledgeEngine,
-    _include_obsolete: bool,
+    include_archived: bool,
 ) -> KnowledgeResult<Vec<TopicListEntry>> {
     let pool = engine.pool();
 
-    let rows = sqlx::query_as::<_, (String, String, String)>(
-        "SELECT DISTINCT n.id, n.title, n.created_by_ghost \
+    let rows = sqlx::query_as::<_, (String, String, String, bool)>(
+        "SELECT DISTINCT n.id, n.title, n.created_by_ghost, a.topic_id IS NOT NULL \
          FROM notes n \
          JOIN reference_files rf ON rf.topic_id = n.id \
-         WHERE n.scope = 'shared_note' \
+         LEFT JOIN archived_topics a ON a.topic_id = n.id \
+         WHERE n.scope = 'shared_note' AND (? OR a.topic_id IS NULL) \
          ORDER BY n.created_at DESC",
     )
+    .bind(include_archived)
     .fetch_all(pool)
     .await?;
 
     let mut entries = Vec::new();
-    for (id, title, ghost) in rows {
+    for (id, title, ghost, archived) in rows {
         let file_count =
             sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM reference_files WHERE topic_id = ?")
                 .bind(&id)
@@ -291,13 +298,14 @@ pub(crate) async fn topic_list(
             file_count,
             collection_dirs,
             tags,
+            archived,
         });
     }
 
     Ok(entries)
 }
 
-/// Load the 10 most recent reference topics for system prompt injection.
+/// Load the 10 most recent non-archived reference topics for system prompt injection.
 pub(crate) async fn recent_topics(
     pool: &SqlitePool,
 ) -> KnowledgeResult<Vec<(String, String, Vec<String>)>> {
@@ -306,6 +314,7 @@ pub(crate) async fn recent_topics(
          FROM notes n \
          JOIN reference_files rf ON rf.topic_id = n.id \
          WHERE n.scope = 'shared_note' \
+         AND n.id NOT IN (SELECT topic_id FROM archived_topics) \
          ORDER BY n.created_at DESC LIMIT 10",
     )
     .fetch_all(pool)
@@ -320,8 +329,99 @@ pub(crate) async fn recent_topics(
     Ok(results)
 }
 
+/// Archive a topic: hide it from search and listings, keeping its files.
+///
+/// Returns the topic `(id, title)`. Archiving twice refreshes the metadata.
+pub(crate) async fn topic_archive(
+    engine: &KnowledgeEngine,
+    ghost_name: &str,
+    topic: &str,
+    reason: Option<&str>,
+) -> KnowledgeResult<(String, String)> {
+    let pool = engine.pool();
+    let (topic_id, title) = resolve_reference_topic(pool, topic).await?;
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO archived_topics (topic_id, archived_at, archived_by_ghost, reason) \
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(&topic_id)
+    .bind(Utc::now().to_rfc3339())
+    .bind(ghost_name)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok((topic_id, title))
+}
+
+/// Restore an archived topic so it shows up in search and listings again.
+pub(crate) async fn topic_restore(
+    engine: &KnowledgeEngine,
+    topic: &str,
+) -> KnowledgeResult<(String, String)> {
+    let pool = engine.pool();
+    let (topic_id, title) = resolve_reference_topic(pool, topic).await?;
+
+    let result = sqlx::query("DELETE FROM archived_topics WHERE topic_id = ?")
+        .bind(&topic_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(KnowledgeError::UnknownNote(format!(
+            "archived topic '{}'",
+            title
+        )));
+    }
+
+    Ok((topic_id, title))
+}
+
+/// IDs of all archived topics.
+pub(crate) async fn archived_topic_ids(pool: &SqlitePool) -> KnowledgeResult<HashSet<String>> {
+    let rows = sqlx::query_as::<_, (String,)>("SELECT topic_id FROM archived_topics")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
 // ── Internal helpers ────────────────────────────────────────────────
 
+/// Resolve a topic by ID or title, requiring it to own reference files.
+async fn resolve_reference_topic(
+    pool: &SqlitePool,
+    topic: &str,
+) -> KnowledgeResult<(String, String)> {
+    let by_id = sqlx::query_as::<_, (String, String)>(
+        "SELECT id, title FROM notes WHERE id = ? AND scope = 'shared_note' LIMIT 1",
+    )
+    .bind(topic)
+    .fetch_optional(pool)
+    .await?;
+    let (topic_id, title) = match by_id {
+        Some(found) => found,
+        None => super::save::find_existing_topic(pool, topic)
+            .await?
+            .ok_or_else(|| KnowledgeError::UnknownNote(format!("topic '{}'", topic)))?,
+    };
+
+    let has_files =
+        sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM reference_files WHERE topic_id = ?")
+            .bind(&topic_id)
+            .fetch_one(pool)
+            .await?
+            .0
+            > 0;
+    if !has_files {
+        return Err(KnowledgeError::UnknownNote(format!(
+            "'{}' is a note, not a reference topic",
+            title
+        )));
+    }
+
+    Ok((topic_id, title))
+}
+
 async fn load_topic_tags(pool: &SqlitePool, topic_id: &str) -> KnowledgeResult<Vec<String>> {
     let rows = sqlx::query_as::<_, (String,)>("SELECT tag FROM note_tags WHERE note_id = ?")
         .bind(topic_id)
@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
 
 use sqlx::SqlitePool;
+use t_koma_core::config::ReembedMode;
 use tracing::info;
 use walkdir::WalkDir;
 
@@ -12,9 +14,10 @@ use crate::ingest::{ingest_diary_entry, ingest_markdown, ingest_reference_file_w
 use crate::models::{KnowledgeScope, SourceRole};
 use crate::paths::{ghost_diary_root, ghost_notes_root, shared_notes_root, shared_references_root};
 use crate::storage::{
-    chunks_missing_embeddings, clear_chunk_embedding_metadata, count_chunks_needing_embedding,
-    drop_vec_table, ensure_vec_table_dim, get_embedding_fingerprint, mark_chunk_embedded,
-    replace_chunks, replace_links, replace_tags, set_embedding_fingerprint, upsert_note,
+    advance_embedding_migration, chunks_missing_embeddings, clear_chunk_embedding_metadata,
+    count_chunks_needing_embedding, drop_vec_table, ensure_vec_table_dim,
+    get_embedding_fingerprint, mark_chunk_embedded, replace_chunks, replace_links, replace_tags,
+    set_embedding_fingerprint, start_embedding_migration, stored_embedding_dim, upsert_note,
     upsert_vec,
 };
 
@@ -326,40 +329,49 @@ pub async fn embed_chunks(
         return Ok(());
     }
 
-    let batch_size = settings.embedding_batch.max(1);
-    let mut offset = 0;
-    while offset < chunks.len() {
-        let end = (offset + batch_size).min(chunks.len());
-        let inputs = chunks[offset..end]
-            .iter()
-            .map(|chunk| chunk.content.clone())
-            .collect::<Vec<_>>();
-
-        let embeddings = embedder.embed_batch(&inputs).await?;
-        if embeddings.is_empty() {
-            return Ok(());
-        }
+    // Chunks carry the model chosen for their language; embed each group
+    // with its own model.
+    let mut by_model: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let model = chunk
+            .embedding_model
+            .as_deref()
+            .unwrap_or(embedder.model_id());
+        by_model.entry(model).or_default().push(idx);
+    }
 
-        let dim = embeddings[0].len();
-        if let Some(expected) = settings.embedding_dim
-            && expected != dim
-        {
-            return Err(crate::errors::KnowledgeError::EmbeddingDimMismatch {
-                expected,
-                actual: dim,
-            });
-        }
-        ensure_vec_table_dim(store, dim).await?;
+    let batch_size = settings.embedding_batch.max(1);
+    for (model, indices) in by_model {
+        for batch in indices.chunks(batch_size) {
+            let inputs = batch
+                .iter()
+                .map(|&idx| chunks[idx].content.clone())
+                .collect::<Vec<_>>();
+
+            let embeddings = embedder.embed_batch_with_model(model, &inputs).await?;
+            if embeddings.is_empty() {
+                return Ok(());
+            }
 
-        for (idx, embedding) in embeddings.into_iter().enumerate() {
-            let chunk_id = *chunk_ids.get(offset + idx).unwrap_or(&0);
-            if chunk_id == 0 {
-                continue;
+            let dim = embeddings[0].len();
+            if let Some(expected) = settings.embedding_dim
+                && expected != dim
+            {
+                return Err(crate::errors::KnowledgeError::EmbeddingDimMismatch {
+                    expected,
+                    actual: dim,
+                });
+            }
+            ensure_vec_table_dim(store, dim).await?;
+
+            for (&idx, embedding) in batch.iter().zip(embeddings) {
+                let chunk_id = *chunk_ids.get(idx).unwrap_or(&0);
+                if chunk_id == 0 {
+                    continue;
+                }
+                upsert_vec(store, chunk_id, &embedding).await?;
             }
-            upsert_vec(store, chunk_id, &embedding).await?;
         }
-
-        offset = end;
     }
 
     Ok(())
@@ -395,6 +407,16 @@ pub async fn check_embedding_provider_change(
     match stored_fp {
         Some(ref fp) if fp == &current_fp => Ok(false),
         Some(ref old_fp) => {
+            if let Some(from_model) = gradual_migration_source(settings, store, old_fp).await? {
+                info!(
+                    old = %old_fp,
+                    new = %current_fp,
+                    "embedding model changed — starting gradual re-embedding"
+                );
+                start_embedding_migration(store, &from_model, &settings.embedding_model).await?;
+                set_embedding_fingerprint(store, &current_fp).await?;
+                return Ok(true);
+            }
             info!(
                 old = %old_fp,
                 new = %current_fp,
@@ -413,7 +435,37 @@ pub async fn check_embedding_provider_change(
     }
 }
 
-/// Re-embed chunks that have no embedding vector.
+/// Decide whether a fingerprint change can be migrated gradually.
+///
+/// Only the default model may differ: same provider (so the old model stays
+/// queryable), same language overrides, and a configured dimension matching
+/// the existing vector table. Returns the old default model when eligible.
+async fn gradual_migration_source(
+    settings: &KnowledgeSettings,
+    store: &SqlitePool,
+    old_fp: &str,
+) -> KnowledgeResult<Option<String>> {
+    if settings.reembed_mode != ReembedMode::Gradual {
+        return Ok(None);
+    }
+    let new_fp = settings.embedding_fingerprint();
+    let (old_base, old_overrides) = old_fp.split_once(';').unwrap_or((old_fp, ""));
+    let (_, new_overrides) = new_fp.split_once(';').unwrap_or((&new_fp, ""));
+    let Some((old_provider, old_model)) = old_base.split_once(':') else {
+        return Ok(None);
+    };
+    if old_provider != settings.embedding_provider.to_string() || old_overrides != new_overrides {
+        return Ok(None);
+    }
+    let stored_dim = stored_embedding_dim(store).await?;
+    if stored_dim.is_none() || stored_dim != settings.embedding_dim {
+        return Ok(None);
+    }
+    Ok(Some(old_model.to_string()))
+}
+
+/// Re-embed chunks that have no embedding vector or are still on the source
+/// model of a gradual migration (most recently accessed notes first).
 ///
 /// Processes up to `max_chunks` in batches. Returns the number of chunks re-embedded.
 pub async fn reindex_embeddings(
@@ -436,25 +488,37 @@ pub async fn reindex_embeddings(
             break;
         }
 
-        let inputs: Vec<String> = stale.iter().map(|(_, content)| content.clone()).collect();
-        let chunk_ids: Vec<i64> = stale.iter().map(|(id, _)| *id).collect();
-
-        let embeddings = embedder.embed_batch(&inputs).await?;
-        if embeddings.is_empty() {
-            break;
+        let mut by_model: BTreeMap<&str, Vec<(i64, String)>> = BTreeMap::new();
+        for (id, content, language) in &stale {
+            by_model
+                .entry(embedder.model_for_language(language.as_deref()))
+                .or_default()
+                .push((*id, content.clone()));
         }
 
-        let dim = embeddings[0].len();
-        ensure_vec_table_dim(store, dim).await?;
+        let mut embedded = 0;
+        for (model, group) in by_model {
+            let inputs: Vec<String> = group.iter().map(|(_, content)| content.clone()).collect();
+            let embeddings = embedder.embed_batch_with_model(model, &inputs).await?;
+            if embeddings.is_empty() {
+                continue;
+            }
+
+            let dim = embeddings[0].len();
+            ensure_vec_table_dim(store, dim).await?;
 
-        let model = embedder.model_id();
-        for (i, embedding) in embeddings.into_iter().enumerate() {
-            let chunk_id = chunk_ids[i];
-            upsert_vec(store, chunk_id, &embedding).await?;
-            mark_chunk_embedded(store, chunk_id, model, dim).await?;
+            for ((chunk_id, _), embedding) in group.iter().zip(embeddings) {
+                upsert_vec(store, *chunk_id, &embedding).await?;
+                mark_chunk_embedded(store, *chunk_id, model, dim).await?;
+                embedded += 1;
+            }
+        }
+        if embedded == 0 {
+            break;
         }
+        advance_embedding_migration(store, embedded).await?;
 
-        total += chunk_ids.len();
+        total += embedded;
     }
 
     if total > 0 {
@@ -6,6 +6,7 @@ use sha2::{Digest, Sha256};
 use crate::KnowledgeSettings;
 use crate::chunker::{Chunk, chunk_code, chunk_markdown};
 use crate::errors::KnowledgeResult;
+use crate::language::detect_language;
 use crate::models::KnowledgeScope;
 use crate::parser::{ParsedNote, extract_links, parse_note};
 use crate::storage::{ChunkRecord, NoteRecord};
@@ -155,15 +156,13 @@ pub async fn ingest_reference_file_with_context(
                 Some(prefix) => format!("{}\n\n{}", prefix, chunk.content),
                 None => chunk.content.clone(),
             };
-            ChunkRecord {
-                note_id: note.id.clone(),
-                chunk_index: chunk.index as i64,
-                title: chunk.title,
-                content: enriched_content.clone(),
-                content_hash: compute_hash(&enriched_content),
-                embedding_model: Some(settings.embedding_model.clone()),
-                embedding_dim: settings.embedding_dim.map(|d| d as i64),
-            }
+            build_chunk_record(
+                settings,
+                &note.id,
+                chunk.index,
+                chunk.title,
+                enriched_content,
+            )
         })
         .collect();
 
@@ -226,14 +225,8 @@ pub async fn ingest_diary_entry(
     let chunks = chunk_markdown(raw);
     let chunk_records = chunks
         .into_iter()
-        .map(|chunk| ChunkRecord {
-            note_id: note.id.clone(),
-            chunk_index: chunk.index as i64,
-            title: chunk.title,
-            content: chunk.content.clone(),
-            content_hash: compute_hash(&chunk.content),
-            embedding_model: Some(settings.embedding_model.clone()),
-            embedding_dim: settings.embedding_dim.map(|d| d as i64),
+        .map(|chunk| {
+            build_chunk_record(settings, &note.id, chunk.index, chunk.title, chunk.content)
         })
         .collect();
 
@@ -276,19 +269,34 @@ fn build_markdown_chunks(
             } else {
                 chunk.content.clone()
             };
-            ChunkRecord {
-                note_id: note.id.clone(),
-                chunk_index: chunk.index as i64,
-                title: chunk.title,
-                content: content.clone(),
-                content_hash: compute_hash(&content),
-                embedding_model: Some(settings.embedding_model.clone()),
-                embedding_dim: settings.embedding_dim.map(|d| d as i64),
-            }
+            build_chunk_record(settings, &note.id, chunk.index, chunk.title, content)
         })
         .collect()
 }
 
+/// Build a chunk record, routing embedding model and BM25 tokenization by
+/// the chunk's detected language.
+fn build_chunk_record(
+    settings: &KnowledgeSettings,
+    note_id: &str,
+    index: usize,
+    title: String,
+    content: String,
+) -> ChunkRecord {
+    let language = detect_language(&content);
+    ChunkRecord {
+        note_id: note_id.to_string(),
+        chunk_index: index as i64,
+        title,
+        content_hash: compute_hash(&content),
+        content,
+        embedding_model: Some(settings.embedding_model_for(language).to_string()),
+        embedding_dim: settings.embedding_dim.map(|d| d as i64),
+        language: language.map(str::to_string),
+        bm25_tokenizer: settings.bm25_tokenizer_for(language),
+    }
+}
+
 fn compute_hash(input: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(input.as_bytes());
@@ -0,0 +1,242 @@
+//! Lightweight language detection and BM25 text preparation.
+//!
+//! Detection is heuristic: non-Latin scripts map directly to a language,
+//! Latin text is scored against small stopword lists. Returns ISO 639-1
+//! codes, or `None` when the text is too short or ambiguous; callers then
+//! fall back to the default embedding model and word tokenization.
+
+use std::borrow::Cow;
+
+use t_koma_core::config::Bm25Tokenizer;
+
+/// Minimum stopword hits before a Latin-script guess is trusted.
+const MIN_STOPWORD_HITS: usize = 2;
+
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "that", "it", "with", "for", "this",
+            "was", "not", "be", "on", "you", "have",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "est", "des", "une", "un", "du", "que", "qui", "dans", "pour",
+            "pas", "sur", "avec", "ce", "sont",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "mit", "ein", "eine", "den", "zu", "von",
+            "auf", "sich", "auch", "wir", "ich", "sind",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "y", "es", "del", "una", "que", "en", "por", "con", "para", "no",
+            "se", "su", "como", "pero", "está",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "gli", "della", "che", "è", "sono", "di", "per", "una", "non", "con", "del",
+            "nel", "anche", "questo", "come", "alla", "più",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "que", "não", "uma", "um", "do", "da", "em", "para", "com", "são",
+            "mais", "por", "como", "mas", "é",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "van", "niet", "dat", "op", "zijn", "met", "voor",
+            "ook", "maar", "bij", "wordt", "naar", "ik",
+        ],
+    ),
+];
+
+#[derive(Debug, Default)]
+struct ScriptCounts {
+    latin: usize,
+    han: usize,
+    kana: usize,
+    hangul: usize,
+    cyrillic: usize,
+    arabic: usize,
+    greek: usize,
+    hebrew: usize,
+    devanagari: usize,
+    thai: usize,
+}
+
+/// Detect the dominant language of `text`.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut counts = ScriptCounts::default();
+    for ch in text.chars().filter(|c| c.is_alphabetic()) {
+        match ch as u32 {
+            0x0041..=0x024F => counts.latin += 1,
+            0x0370..=0x03FF => counts.greek += 1,
+            0x0400..=0x04FF => counts.cyrillic += 1,
+            0x0590..=0x05FF => counts.hebrew += 1,
+            0x0600..=0x06FF | 0x0750..=0x077F => counts.arabic += 1,
+            0x0900..=0x097F => counts.devanagari += 1,
+            0x0E00..=0x0E7F => counts.thai += 1,
+            0x3040..=0x30FF | 0x31F0..=0x31FF => counts.kana += 1,
+            0xAC00..=0xD7AF | 0x1100..=0x11FF | 0x3130..=0x318F => counts.hangul += 1,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => counts.han += 1,
+            _ => {}
+        }
+    }
+
+    let cjk = counts.han + counts.kana + counts.hangul;
+    let scripts = [
+        (cjk, "cjk"),
+        (counts.cyrillic, "ru"),
+        (counts.arabic, "ar"),
+        (counts.greek, "el"),
+        (counts.hebrew, "he"),
+        (counts.devanagari, "hi"),
+        (counts.thai, "th"),
+        (counts.latin, "latin"),
+    ];
+    let (count, script) = scripts.into_iter().max_by_key(|(count, _)| *count)?;
+    if count == 0 {
+        return None;
+    }
+
+    match script {
+        // Kana only appears in Japanese; Hangul only in Korean.
+        "cjk" if counts.kana > 0 => Some("ja"),
+        "cjk" if counts.hangul > counts.han => Some("ko"),
+        "cjk" => Some("zh"),
+        "latin" => detect_latin(text),
+        other => Some(other),
+    }
+}
+
+fn detect_latin(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let (language, hits) = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words
+                .iter()
+                .filter(|w| stopwords.contains(&w.as_str()))
+                .count();
+            (*language, hits)
+        })
+        .max_by_key(|(_, hits)| *hits)?;
+
+    (hits >= MIN_STOPWORD_HITS).then_some(language)
+}
+
+/// Prepare text for FTS5 indexing or querying with `tokenizer`.
+///
+/// `Bigram` rewrites runs of CJK characters as space-separated overlapping
+/// bigrams so FTS5's `unicode61` tokenizer can match inside them. Applied to
+/// both stored chunks and queries, a quoted query phrase then matches the
+/// same bigram sequence in the index.
+pub fn prepare_bm25_text(text: &str, tokenizer: Bm25Tokenizer) -> Cow<'_, str> {
+    if tokenizer == Bm25Tokenizer::Words || !text.chars().any(is_cjk) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut run: Vec<char> = Vec::new();
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            run.push(ch);
+            continue;
+        }
+        flush_bigrams(&mut out, &mut run);
+        out.push(ch);
+    }
+    flush_bigrams(&mut out, &mut run);
+    Cow::Owned(out)
+}
+
+fn flush_bigrams(out: &mut String, run: &mut Vec<char>) {
+    if run.is_empty() {
+        return;
+    }
+    out.push(' ');
+    if run.len() == 1 {
+        out.push(run[0]);
+    } else {
+        let bigrams: Vec<String> = run.windows(2).map(|w| w.iter().collect()).collect();
+        out.push_str(&bigrams.join(" "));
+    }
+    out.push(' ');
+    run.clear();
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x3040..=0x30FF
+            | 0x31F0..=0x31FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xF900..=0xFAFF
+            | 0xAC00..=0xD7AF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_latin_languages_by_stopwords() {
+        assert_eq!(
+            detect_language("The cat is on the mat and it is happy."),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language("Le chat est sur la table et les enfants sont dans le jardin."),
+            Some("fr")
+        );
+        assert_eq!(
+            detect_language("Der Hund ist nicht mit der Katze auf dem Sofa."),
+            Some("de")
+        );
+    }
+
+    #[test]
+    fn detects_non_latin_scripts() {
+        assert_eq!(detect_language("これは日本語の文章です"), Some("ja"));
+        assert_eq!(detect_language("这是中文句子"), Some("zh"));
+        assert_eq!(detect_language("한국어 문장입니다"), Some("ko"));
+        assert_eq!(detect_language("Это русский текст"), Some("ru"));
+    }
+
+    #[test]
+    fn short_or_ambiguous_text_is_undetermined() {
+        assert_eq!(detect_language("tokio runtime"), None);
+        assert_eq!(detect_language("1234 !!"), None);
+    }
+
+    #[test]
+    fn bigram_segments_cjk_runs_only() {
+        let prepared = prepare_bm25_text("東京都 in Japan", Bm25Tokenizer::Bigram);
+        let tokens: Vec<&str> = prepared.split_whitespace().collect();
+        assert_eq!(tokens, vec!["東京", "京都", "in", "Japan"]);
+
+        let words = prepare_bm25_text("東京都", Bm25Tokenizer::Words);
+        assert!(matches!(words, Cow::Borrowed("東京都")));
+    }
+}
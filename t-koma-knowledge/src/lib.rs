@@ -8,6 +8,7 @@ pub mod errors;
 pub mod graph;
 pub mod index;
 pub mod ingest;
+pub mod language;
 pub mod models;
 pub mod parser;
 pub mod paths;
@@ -15,16 +16,19 @@ pub mod sources;
 pub mod storage;
 pub mod watcher;
 
-pub use embeddings::EmbeddingClient;
+pub use embeddings::{EmbeddingClient, EmbeddingsProvider, HttpEmbeddings};
+pub use engine::IngestJob;
 pub use engine::KnowledgeEngine;
 pub use engine::RecentRefSummary;
+pub use engine::SettingsReload;
 pub use errors::KnowledgeError;
 pub use models::{
-    DiaryQuery, DiarySearchResult, IndexStats, IndexStatsEntry, KnowledgeGetQuery, KnowledgeScope,
+    DiaryQuery, DiarySearchResult, GraphFormat, IndexStats, IndexStatsEntry, IngestBatchRequest,
+    IngestItem, IngestProgress, IngestSource, KnowledgeGetQuery, KnowledgeScope,
     KnowledgeSearchQuery, KnowledgeSearchResult, MatchedTopic, NoteCreateRequest, NoteDocument,
     NoteQuery, NoteResult, NoteSummary, NoteUpdateRequest, NoteWriteResult, OwnershipScope,
     ReferenceFileStatus, ReferenceQuery, ReferenceSaveRequest, ReferenceSaveResult,
     ReferenceSearchOutput, ReferenceSearchResult, SearchCategory, SourceRole, TopicCreateRequest,
     TopicCreateResult, TopicListEntry, TopicSearchResult, TopicSourceInput, WriteScope,
 };
-pub use t_koma_core::config::{KnowledgeSettings, SearchDefaults};
+pub use t_koma_core::config::{KnowledgeSettings, ReembedMode, SearchDefaults};
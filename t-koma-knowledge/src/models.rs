@@ -194,6 +194,10 @@ pub struct IndexStats {
     pub total_notes: i64,
     pub total_chunks: i64,
     pub total_embeddings: i64,
+    /// Chunks not yet embedded with the current model.
+    pub missing_embeddings: i64,
+    /// Chunks whose note is no longer indexed.
+    pub orphaned_chunks: i64,
     pub embedding_model: String,
     pub embedding_dim: u32,
     pub recent_entries: Vec<IndexStatsEntry>,
@@ -442,6 +446,9 @@ pub struct TopicListEntry {
     /// Subdirectory names derived from reference file paths.
     pub collection_dirs: Vec<String>,
     pub tags: Vec<String>,
+    /// Hidden from search and recent topics until restored.
+    #[serde(default)]
+    pub archived: bool,
 }
 
 /// Result of a topic search query.
@@ -484,6 +491,88 @@ pub struct ReferenceSaveResult {
     pub path: String,
 }
 
+/// Where a bulk-ingest item's content comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum IngestSource {
+    /// Local file (absolute path, already approved by the caller).
+    File { path: PathBuf },
+    /// Web page fetched over HTTP and converted to text.
+    Url { url: String },
+}
+
+/// A single item of a bulk ingest batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestItem {
+    pub source: IngestSource,
+    /// Relative path within the topic directory. Derived from the source if omitted.
+    pub path: Option<String>,
+    pub title: Option<String>,
+    pub role: Option<SourceRole>,
+}
+
+impl IngestItem {
+    /// Human-readable label for progress reporting.
+    pub fn label(&self) -> String {
+        match &self.source {
+            IngestSource::File { path } => path.display().to_string(),
+            IngestSource::Url { url } => url.clone(),
+        }
+    }
+}
+
+/// Request to ingest many files/URLs into one reference topic in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestBatchRequest {
+    pub topic: String,
+    pub items: Vec<IngestItem>,
+}
+
+/// Progress event emitted by a running bulk ingest job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IngestProgress {
+    ItemStarted {
+        index: usize,
+    },
+    ItemSaved {
+        index: usize,
+        note_id: String,
+        path: String,
+    },
+    ItemFailed {
+        index: usize,
+        error: String,
+    },
+    Finished {
+        saved: usize,
+        failed: usize,
+    },
+}
+
+/// Output format for `KnowledgeEngine::export_graph`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// Graphviz DOT.
+    #[default]
+    Dot,
+    /// GraphML (XML), readable by Gephi, yEd, Cytoscape.
+    GraphMl,
+}
+
+impl FromStr for GraphFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dot" | "gv" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Result of a `reference_search` query, including full topic context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReferenceSearchResult {
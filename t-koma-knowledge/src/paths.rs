@@ -32,6 +32,11 @@ pub fn shared_references_root(settings: &KnowledgeSettings) -> KnowledgeResult<P
 
 // ── Ghost paths ─────────────────────────────────────────────────────
 
+/// Ghost root (the ghost workspace): `$DATA/ghosts/$slug/`
+pub fn ghost_root(settings: &KnowledgeSettings, slug: &str) -> KnowledgeResult<PathBuf> {
+    Ok(data_root(settings)?.join("ghosts").join(slug))
+}
+
 /// Ghost inbox (not indexed): `$DATA/ghosts/$slug/inbox/`
 pub fn ghost_inbox_path(settings: &KnowledgeSettings, slug: &str) -> KnowledgeResult<PathBuf> {
     Ok(data_root(settings)?.join("ghosts").join(slug).join("inbox"))
@@ -197,23 +197,48 @@ pub async fn fetch_web_source(
     source: &TopicSourceInput,
     topic_dir: &Path,
 ) -> KnowledgeResult<FetchedSource> {
+    let markdown = fetch_web_markdown(&source.url).await?;
+
+    let filename = url_to_filename(&source.url);
+    let path = topic_dir.join(&filename);
+    tokio::fs::write(&path, &markdown)
+        .await
+        .map_err(|e| KnowledgeError::SourceFetch(format!("write {}: {}", path.display(), e)))?;
+
+    info!("Fetched web source {}: saved as {}", source.url, filename);
+
+    Ok(FetchedSource {
+        source: TopicSource {
+            source_type: "web".to_string(),
+            url: source.url.clone(),
+            ref_name: None,
+            commit: None,
+            paths: None,
+            role: source.role,
+        },
+        files: vec![filename],
+    })
+}
+
+/// Fetch a single web page and convert it to plain markdown text.
+pub(crate) async fn fetch_web_markdown(url: &str) -> KnowledgeResult<String> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| KnowledgeError::SourceFetch(format!("reqwest client: {}", e)))?;
 
     let response = client
-        .get(&source.url)
+        .get(url)
         .header("User-Agent", "t-koma-knowledge/0.1")
         .send()
         .await
-        .map_err(|e| KnowledgeError::SourceFetch(format!("HTTP fetch {}: {}", source.url, e)))?;
+        .map_err(|e| KnowledgeError::SourceFetch(format!("HTTP fetch {}: {}", url, e)))?;
 
     if !response.status().is_success() {
         return Err(KnowledgeError::SourceFetch(format!(
             "HTTP {} for {}",
             response.status(),
-            source.url
+            url
         )));
     }
 
@@ -222,27 +247,7 @@ pub async fn fetch_web_source(
         .await
         .map_err(|e| KnowledgeError::SourceFetch(format!("read body: {}", e)))?;
 
-    let markdown = html2text::from_read(html.as_bytes(), 80);
-
-    let filename = url_to_filename(&source.url);
-    let path = topic_dir.join(&filename);
-    tokio::fs::write(&path, &markdown)
-        .await
-        .map_err(|e| KnowledgeError::SourceFetch(format!("write {}: {}", path.display(), e)))?;
-
-    info!("Fetched web source {}: saved as {}", source.url, filename);
-
-    Ok(FetchedSource {
-        source: TopicSource {
-            source_type: "web".to_string(),
-            url: source.url.clone(),
-            ref_name: None,
-            commit: None,
-            paths: None,
-            role: source.role,
-        },
-        files: vec![filename],
-    })
+    Ok(html2text::from_read(html.as_bytes(), 80))
 }
 
 /// Fetch a crawl source (multi-page BFS) and save pages as markdown files.
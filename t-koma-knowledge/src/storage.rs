@@ -6,8 +6,10 @@ use libsqlite3_sys::{SQLITE_OK, sqlite3, sqlite3_api_routines, sqlite3_auto_exte
 use sqlite_vec::sqlite3_vec_init;
 use sqlx::SqlitePool;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use t_koma_core::config::Bm25Tokenizer;
 
 use crate::errors::{KnowledgeError, KnowledgeResult};
+use crate::language::prepare_bm25_text;
 
 static SQLITE_VEC_INIT_RC: OnceLock<i32> = OnceLock::new();
 
@@ -172,6 +174,10 @@ pub struct ChunkRecord {
     pub content_hash: String,
     pub embedding_model: Option<String>,
     pub embedding_dim: Option<i64>,
+    /// Detected ISO 639-1 language code, if any.
+    pub language: Option<String>,
+    /// Tokenization applied to `content` before FTS indexing.
+    pub bm25_tokenizer: Bm25Tokenizer,
 }
 
 pub async fn upsert_note(pool: &SqlitePool, record: &NoteRecord) -> KnowledgeResult<()> {
@@ -341,8 +347,8 @@ pub async fn replace_chunks(
     let mut ids = Vec::new();
     for chunk in chunks {
         let result = sqlx::query(
-            r#"INSERT INTO chunks (note_id, chunk_index, title, content, content_hash, embedding_model, embedding_dim, updated_at)
-               VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            r#"INSERT INTO chunks (note_id, chunk_index, title, content, content_hash, embedding_model, embedding_dim, language, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&chunk.note_id)
@@ -352,6 +358,7 @@ pub async fn replace_chunks(
         .bind(&chunk.content_hash)
         .bind(&chunk.embedding_model)
         .bind(chunk.embedding_dim)
+        .bind(&chunk.language)
         .bind(Utc::now().to_rfc3339())
         .execute(pool)
         .await?;
@@ -363,7 +370,7 @@ pub async fn replace_chunks(
             r#"INSERT INTO chunk_fts (content, title, note_title, entry_type, archetype, note_id, chunk_id)
                VALUES (?, ?, ?, ?, ?, ?, ?)"#,
         )
-        .bind(&chunk.content)
+        .bind(prepare_bm25_text(&chunk.content, chunk.bm25_tokenizer).as_ref())
         .bind(&chunk.title)
         .bind(note_title)
         .bind(entry_type)
@@ -442,30 +449,143 @@ pub async fn clear_chunk_embedding_metadata(pool: &SqlitePool) -> KnowledgeResul
     Ok(())
 }
 
-/// Return chunk IDs that have no embedding vector yet.
+/// Return chunks (id, content, language) that need (re-)embedding.
+///
+/// Includes chunks with no embedding and, during a gradual migration, chunks
+/// still on the migration's source model. Most recently accessed notes come
+/// first; never-accessed notes follow in insertion order.
 pub async fn chunks_missing_embeddings(
     pool: &SqlitePool,
     batch_size: usize,
-) -> KnowledgeResult<Vec<(i64, String)>> {
-    let rows: Vec<(i64, String)> = sqlx::query_as(
-        "SELECT c.id, c.content FROM chunks c \
-         WHERE c.embedding_model IS NULL \
-         ORDER BY c.id ASC \
+) -> KnowledgeResult<Vec<(i64, String, Option<String>)>> {
+    let from_model = active_migration_source(pool).await?;
+    let rows: Vec<(i64, String, Option<String>)> = sqlx::query_as(
+        "SELECT c.id, c.content, c.language FROM chunks c \
+         LEFT JOIN note_access a ON a.note_id = c.note_id \
+         WHERE c.embedding_model IS NULL OR c.embedding_model = ? \
+         ORDER BY a.last_accessed_at DESC, c.id ASC \
          LIMIT ?",
     )
+    .bind(from_model)
     .bind(batch_size as i64)
     .fetch_all(pool)
     .await?;
     Ok(rows)
 }
 
-/// Count chunks that still need embedding.
+/// Count chunks that still need (re-)embedding.
 pub async fn count_chunks_needing_embedding(pool: &SqlitePool) -> KnowledgeResult<i64> {
-    let (count,): (i64,) =
-        sqlx::query_as("SELECT COUNT(*) FROM chunks WHERE embedding_model IS NULL")
+    let from_model = active_migration_source(pool).await?;
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM chunks WHERE embedding_model IS NULL OR embedding_model = ?",
+    )
+    .bind(from_model)
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+/// Dimension of the current `chunk_vec` table, if one exists.
+pub async fn stored_embedding_dim(pool: &SqlitePool) -> KnowledgeResult<Option<usize>> {
+    let table_exists: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'chunk_vec'",
+    )
+    .fetch_optional(pool)
+    .await?;
+    if table_exists.is_none() {
+        return Ok(None);
+    }
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM meta WHERE key = 'embedding_dim' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(v,)| v.parse().ok()))
+}
+
+/// Open a gradual embedding migration from `from_model` to `to_model`.
+///
+/// Any previously open migration is closed first: its leftover chunks are
+/// picked up by the new one only if they share `from_model`.
+pub async fn start_embedding_migration(
+    pool: &SqlitePool,
+    from_model: &str,
+    to_model: &str,
+) -> KnowledgeResult<()> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query("UPDATE embedding_migrations SET completed_at = ? WHERE completed_at IS NULL")
+        .bind(&now)
+        .execute(pool)
+        .await?;
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM chunks WHERE embedding_model = ?")
+        .bind(from_model)
+        .fetch_one(pool)
+        .await?;
+    sqlx::query(
+        "INSERT INTO embedding_migrations (from_model, to_model, started_at, total_chunks) \
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(from_model)
+    .bind(to_model)
+    .bind(&now)
+    .bind(total)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Source model of the open gradual migration, if any.
+pub async fn active_migration_source(pool: &SqlitePool) -> KnowledgeResult<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT from_model FROM embedding_migrations WHERE completed_at IS NULL \
+         ORDER BY id DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(m,)| m))
+}
+
+/// Record progress on the open migration, closing it once no source chunks remain.
+pub async fn advance_embedding_migration(
+    pool: &SqlitePool,
+    migrated: usize,
+) -> KnowledgeResult<()> {
+    let Some(from_model) = active_migration_source(pool).await? else {
+        return Ok(());
+    };
+    sqlx::query(
+        "UPDATE embedding_migrations SET migrated_chunks = migrated_chunks + ? \
+         WHERE completed_at IS NULL",
+    )
+    .bind(migrated as i64)
+    .execute(pool)
+    .await?;
+
+    let (remaining,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM chunks WHERE embedding_model = ?")
+            .bind(&from_model)
             .fetch_one(pool)
             .await?;
-    Ok(count)
+    if remaining == 0 {
+        sqlx::query("UPDATE embedding_migrations SET completed_at = ? WHERE completed_at IS NULL")
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Record an explicit read of a note (for re-embedding priority).
+pub async fn record_note_access(pool: &SqlitePool, note_id: &str) -> KnowledgeResult<()> {
+    sqlx::query(
+        "INSERT INTO note_access (note_id, last_accessed_at) VALUES (?, ?) \
+         ON CONFLICT(note_id) DO UPDATE SET last_accessed_at = excluded.last_accessed_at, \
+         access_count = access_count + 1",
+    )
+    .bind(note_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
 }
 
 /// Update the embedding metadata on a chunk after successful embedding.
@@ -483,3 +603,12 @@ pub async fn mark_chunk_embedded(
         .await?;
     Ok(())
 }
+
+/// Read the index generation counter (bumped by triggers on every index write).
+pub async fn index_generation(pool: &SqlitePool) -> KnowledgeResult<i64> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM meta WHERE key = 'index_generation' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(v,)| v.parse().ok()).unwrap_or(0))
+}
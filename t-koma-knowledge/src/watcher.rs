@@ -13,10 +13,12 @@ use crate::models::KnowledgeScope;
 use crate::paths::{knowledge_db_path, shared_notes_root, shared_references_root};
 use crate::storage::KnowledgeStore;
 
-pub async fn run_shared_watcher(settings: KnowledgeSettings) -> KnowledgeResult<()> {
+pub async fn run_shared_watcher(
+    settings: KnowledgeSettings,
+    embedder: EmbeddingClient,
+) -> KnowledgeResult<()> {
     let store =
         KnowledgeStore::open(&knowledge_db_path(&settings)?, settings.embedding_dim).await?;
-    let embedder = EmbeddingClient::new(&settings);
     let root = shared_notes_root(&settings)?;
     let reference = shared_references_root(&settings)?;
     run_watcher(
@@ -33,10 +35,10 @@ pub async fn run_shared_watcher(settings: KnowledgeSettings) -> KnowledgeResult<
 pub async fn run_ghost_watcher(
     settings: KnowledgeSettings,
     ghost_name: String,
+    embedder: EmbeddingClient,
 ) -> KnowledgeResult<()> {
     let store =
         KnowledgeStore::open(&knowledge_db_path(&settings)?, settings.embedding_dim).await?;
-    let embedder = EmbeddingClient::new(&settings);
     let notes = crate::paths::ghost_notes_root(&settings, &ghost_name)?;
     let diary = crate::paths::ghost_diary_root(&settings, &ghost_name)?;
     let references = crate::paths::ghost_references_root(&settings, &ghost_name)?;
@@ -0,0 +1,186 @@
+//! Bulk reference ingestion (`KnowledgeEngine::ingest_batch`) with a stub
+//! embeddings backend, so no Ollama instance is needed.
+
+use std::sync::Arc;
+
+use tempfile::TempDir;
+
+use t_koma_knowledge::errors::KnowledgeResult;
+use t_koma_knowledge::models::{IngestBatchRequest, IngestItem, IngestProgress, IngestSource};
+use t_koma_knowledge::storage::{NoteRecord, upsert_note};
+use t_koma_knowledge::{
+    EmbeddingClient, EmbeddingsProvider, KnowledgeEngine, KnowledgeError, KnowledgeSettings,
+};
+
+/// Embeddings backend returning the same 8-dim vector for every input.
+struct FixedEmbeddings;
+
+#[async_trait::async_trait]
+impl EmbeddingsProvider for FixedEmbeddings {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    async fn embed(&self, _model: &str, inputs: &[String]) -> KnowledgeResult<Vec<Vec<f32>>> {
+        Ok(inputs.iter().map(|_| vec![0.5; 8]).collect())
+    }
+}
+
+/// Engine with one shared topic note titled "Library Docs".
+async fn setup() -> (KnowledgeEngine, TempDir) {
+    let temp = TempDir::new().expect("tempdir");
+    let data_root = temp.path().join("data");
+    let settings = KnowledgeSettings {
+        data_root_override: Some(data_root.clone()),
+        knowledge_db_path_override: Some(data_root.join("shared").join("index.sqlite3")),
+        embedding_dim: Some(8),
+        reconcile_seconds: 999_999,
+        ..Default::default()
+    };
+    let embedder = EmbeddingClient::with_provider(&settings, Arc::new(FixedEmbeddings));
+    let engine = KnowledgeEngine::open_with_embedder(settings, embedder)
+        .await
+        .expect("open engine");
+
+    let topic = NoteRecord {
+        id: "topic-lib".to_string(),
+        title: "Library Docs".to_string(),
+        entry_type: "Note".to_string(),
+        archetype: None,
+        path: data_root.join("shared").join("notes").join("topic-lib.md"),
+        scope: "shared_note".to_string(),
+        owner_ghost: None,
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        created_by_ghost: "ghost-a".to_string(),
+        created_by_model: "model".to_string(),
+        trust_score: 8,
+        last_validated_at: None,
+        last_validated_by_ghost: None,
+        last_validated_by_model: None,
+        version: Some(1),
+        parent_id: None,
+        comments_json: None,
+        content_hash: "hash".to_string(),
+    };
+    upsert_note(engine.pool(), &topic).await.unwrap();
+
+    (engine, temp)
+}
+
+fn file_item(path: std::path::PathBuf) -> IngestItem {
+    IngestItem {
+        source: IngestSource::File { path },
+        path: None,
+        title: None,
+        role: None,
+    }
+}
+
+#[tokio::test]
+async fn test_ingest_batch_saves_files_and_reports_progress() {
+    let (engine, temp) = setup().await;
+    let sources = temp.path().join("sources");
+    tokio::fs::create_dir_all(&sources).await.unwrap();
+    tokio::fs::write(
+        sources.join("guide.md"),
+        "# Guide\n\nHow to use the library.\n",
+    )
+    .await
+    .unwrap();
+    tokio::fs::write(
+        sources.join("lib.rs"),
+        "pub fn answer() -> u32 {\n    42\n}\n",
+    )
+    .await
+    .unwrap();
+
+    let request = IngestBatchRequest {
+        topic: "Library Docs".to_string(),
+        items: vec![
+            file_item(sources.join("guide.md")),
+            file_item(sources.join("lib.rs")),
+            file_item(sources.join("missing.md")),
+        ],
+    };
+    let mut job = engine
+        .ingest_batch("ghost-a", "model", request)
+        .await
+        .expect("batch accepted");
+    assert_eq!(job.total, 3);
+
+    let mut events = Vec::new();
+    while let Some(event) = job.progress.recv().await {
+        events.push(event);
+    }
+
+    let mut saved_ids = Vec::new();
+    let mut outcomes = Vec::new();
+    for event in &events {
+        match event {
+            IngestProgress::ItemStarted { .. } => {}
+            IngestProgress::ItemSaved {
+                index,
+                note_id,
+                path,
+            } => {
+                saved_ids.push(note_id.clone());
+                outcomes.push(format!("saved {index} {path}"));
+            }
+            IngestProgress::ItemFailed { index, .. } => outcomes.push(format!("failed {index}")),
+            IngestProgress::Finished { saved, failed } => {
+                outcomes.push(format!("finished {saved}/{failed}"))
+            }
+        }
+    }
+    assert_eq!(
+        outcomes,
+        vec![
+            "saved 0 guide.md",
+            "saved 1 lib.rs",
+            "failed 2",
+            "finished 2/1"
+        ]
+    );
+
+    let files: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT note_id, path, role FROM reference_files WHERE topic_id = 'topic-lib' ORDER BY path",
+    )
+    .fetch_all(engine.pool())
+    .await
+    .unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        (files[0].1.as_str(), files[0].2.as_str()),
+        ("guide.md", "docs")
+    );
+    assert_eq!(
+        (files[1].1.as_str(), files[1].2.as_str()),
+        ("lib.rs", "code")
+    );
+    for (note_id, _, _) in &files {
+        assert!(saved_ids.contains(note_id), "{note_id} was not reported");
+    }
+
+    let (note_path,): (String,) = sqlx::query_as("SELECT path FROM notes WHERE id = ?")
+        .bind(&files[1].0)
+        .fetch_one(engine.pool())
+        .await
+        .unwrap();
+    let saved = tokio::fs::read_to_string(&note_path).await.unwrap();
+    assert!(saved.contains("pub fn answer"));
+}
+
+#[tokio::test]
+async fn test_ingest_batch_rejects_unknown_topic() {
+    let (engine, temp) = setup().await;
+    let request = IngestBatchRequest {
+        topic: "No Such Topic".to_string(),
+        items: vec![file_item(temp.path().join("any.md"))],
+    };
+
+    let result = engine.ingest_batch("ghost-a", "model", request).await;
+    assert!(
+        matches!(result, Err(KnowledgeError::UnknownNote(_))),
+        "{result:?}"
+    );
+}
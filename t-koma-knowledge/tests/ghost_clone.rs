@@ -0,0 +1,98 @@
+use tempfile::TempDir;
+
+use t_koma_knowledge::models::KnowledgeScope;
+use t_koma_knowledge::parser::parse_note;
+use t_koma_knowledge::{KnowledgeEngine, KnowledgeSettings};
+
+fn note_file(id: &str, title: &str, parent: Option<&str>) -> String {
+    let parent = parent
+        .map(|p| format!("parent = \"{p}\"\n"))
+        .unwrap_or_default();
+    format!(
+        "+++\nid = \"{id}\"\ntitle = \"{title}\"\ncreated_at = \"2025-01-01T00:00:00Z\"\n\
+         trust_score = 5\n{parent}\n[created_by]\nghost = \"source\"\nmodel = \"m\"\n+++\n\n\
+         Body of {title}.\n"
+    )
+}
+
+#[tokio::test]
+async fn test_clone_ghost_knowledge_assigns_fresh_ids() {
+    let temp = TempDir::new().expect("tempdir");
+    let data_root = temp.path().join("data");
+    let settings = KnowledgeSettings {
+        data_root_override: Some(data_root.clone()),
+        knowledge_db_path_override: Some(data_root.join("shared").join("index.sqlite3")),
+        embedding_dim: Some(8),
+        ..Default::default()
+    };
+
+    let source = data_root.join("ghosts").join("source");
+    let notes = source.join("notes");
+    tokio::fs::create_dir_all(notes.join("nested"))
+        .await
+        .unwrap();
+    tokio::fs::create_dir_all(source.join("diary"))
+        .await
+        .unwrap();
+    tokio::fs::write(notes.join("parent.md"), note_file("p-1", "Parent", None))
+        .await
+        .unwrap();
+    tokio::fs::write(
+        notes.join("nested").join("child.md"),
+        note_file("c-1", "Child", Some("p-1")),
+    )
+    .await
+    .unwrap();
+    tokio::fs::write(source.join("diary").join("2025-01-01.md"), "Dear diary.")
+        .await
+        .unwrap();
+
+    let engine = KnowledgeEngine::open(settings).await.expect("open engine");
+
+    let copied = engine
+        .clone_ghost_knowledge("source", "variant", &[KnowledgeScope::GhostNote])
+        .await
+        .unwrap();
+    assert_eq!(copied, 2);
+
+    let target = data_root.join("ghosts").join("variant");
+    let parent = parse_note(
+        &tokio::fs::read_to_string(target.join("notes").join("parent.md"))
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    let child = parse_note(
+        &tokio::fs::read_to_string(target.join("notes").join("nested").join("child.md"))
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_ne!(parent.front.id, "p-1");
+    assert_ne!(child.front.id, "c-1");
+    assert_eq!(
+        child.front.parent.as_deref(),
+        Some(parent.front.id.as_str())
+    );
+    assert!(child.body.contains("Body of Child."));
+    assert!(
+        !target.join("diary").exists(),
+        "diary was not selected and must not be copied"
+    );
+
+    let copied = engine
+        .clone_ghost_knowledge(
+            "source",
+            "variant",
+            &[KnowledgeScope::GhostDiary, KnowledgeScope::SharedNote],
+        )
+        .await
+        .unwrap();
+    assert_eq!(copied, 1);
+    assert_eq!(
+        tokio::fs::read_to_string(target.join("diary").join("2025-01-01.md"))
+            .await
+            .unwrap(),
+        "Dear diary."
+    );
+}
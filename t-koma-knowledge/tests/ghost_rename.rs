@@ -0,0 +1,159 @@
+use tempfile::TempDir;
+
+use t_koma_core::config::Bm25Tokenizer;
+use t_koma_knowledge::storage::{
+    ChunkRecord, KnowledgeStore, NoteRecord, replace_chunks, upsert_note,
+};
+use t_koma_knowledge::{KnowledgeEngine, KnowledgeSettings};
+
+fn note(id: &str, path: std::path::PathBuf, scope: &str, owner: Option<&str>) -> NoteRecord {
+    NoteRecord {
+        id: id.to_string(),
+        title: id.to_string(),
+        entry_type: "Idea".to_string(),
+        archetype: None,
+        path,
+        scope: scope.to_string(),
+        owner_ghost: owner.map(str::to_string),
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        created_by_ghost: "old_ghost".to_string(),
+        created_by_model: "model".to_string(),
+        trust_score: 5,
+        last_validated_at: None,
+        last_validated_by_ghost: None,
+        last_validated_by_model: None,
+        version: None,
+        parent_id: None,
+        comments_json: None,
+        content_hash: format!("hash-{id}"),
+    }
+}
+
+#[tokio::test]
+async fn test_rename_ghost_rewrites_index() {
+    let temp = TempDir::new().expect("tempdir");
+    let data_root = temp.path().join("data");
+    let db_path = data_root.join("shared").join("index.sqlite3");
+    let settings = KnowledgeSettings {
+        data_root_override: Some(data_root.clone()),
+        knowledge_db_path_override: Some(db_path.clone()),
+        embedding_dim: Some(8),
+        ..Default::default()
+    };
+    let store = KnowledgeStore::open(&db_path, settings.embedding_dim)
+        .await
+        .unwrap();
+    let pool = store.pool();
+
+    let old_root = data_root.join("ghosts").join("old_ghost");
+    // A sibling ghost whose name shares the prefix must be left alone.
+    let other_root = data_root.join("ghosts").join("old_ghost2");
+    upsert_note(
+        pool,
+        &note(
+            "private-note",
+            old_root.join("notes").join("idea.md"),
+            "ghost_note",
+            Some("old_ghost"),
+        ),
+    )
+    .await
+    .unwrap();
+    upsert_note(
+        pool,
+        &note(
+            "diary:old_ghost:2025-01-01",
+            old_root.join("diary").join("2025-01-01.md"),
+            "ghost_diary",
+            Some("old_ghost"),
+        ),
+    )
+    .await
+    .unwrap();
+    upsert_note(
+        pool,
+        &note(
+            "other-note",
+            other_root.join("notes").join("idea.md"),
+            "ghost_note",
+            Some("old_ghost2"),
+        ),
+    )
+    .await
+    .unwrap();
+    replace_chunks(
+        pool,
+        "diary:old_ghost:2025-01-01",
+        "2025-01-01",
+        "Diary",
+        None,
+        &[ChunkRecord {
+            note_id: "diary:old_ghost:2025-01-01".to_string(),
+            chunk_index: 0,
+            title: "2025-01-01".to_string(),
+            content: "walked the dog".to_string(),
+            content_hash: "chunk-hash".to_string(),
+            embedding_model: None,
+            embedding_dim: None,
+            language: None,
+            bm25_tokenizer: Bm25Tokenizer::Words,
+        }],
+    )
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO meta (key, value) VALUES ('last_reconcile_ghost:old_ghost', 'x')")
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let engine = KnowledgeEngine::open(settings).await.expect("open engine");
+    engine.rename_ghost("old_ghost", "New Ghost").await.unwrap();
+
+    let new_root = data_root.join("ghosts").join("New Ghost");
+    let (path, owner, created_by): (String, String, String) = sqlx::query_as(
+        "SELECT path, owner_ghost, created_by_ghost FROM notes WHERE id = 'private-note'",
+    )
+    .fetch_one(engine.pool())
+    .await
+    .unwrap();
+    assert_eq!(
+        path,
+        new_root.join("notes").join("idea.md").display().to_string()
+    );
+    assert_eq!(owner, "New Ghost");
+    assert_eq!(created_by, "New Ghost");
+
+    let (chunk_note,): (String,) = sqlx::query_as("SELECT note_id FROM chunks")
+        .fetch_one(engine.pool())
+        .await
+        .unwrap();
+    assert_eq!(chunk_note, "diary:New Ghost:2025-01-01");
+    let (fts_note,): (String,) =
+        sqlx::query_as("SELECT note_id FROM chunk_fts WHERE chunk_fts MATCH 'dog'")
+            .fetch_one(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(fts_note, "diary:New Ghost:2025-01-01");
+
+    let (other_path, other_owner): (String, String) =
+        sqlx::query_as("SELECT path, owner_ghost FROM notes WHERE id = 'other-note'")
+            .fetch_one(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(
+        other_path,
+        other_root
+            .join("notes")
+            .join("idea.md")
+            .display()
+            .to_string()
+    );
+    assert_eq!(other_owner, "old_ghost2");
+
+    let keys: Vec<(String,)> =
+        sqlx::query_as("SELECT key FROM meta WHERE key LIKE 'last_reconcile_ghost:%'")
+            .fetch_all(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(keys, vec![("last_reconcile_ghost:New Ghost".to_string(),)]);
+}
@@ -6,9 +6,47 @@
 
 use tempfile::TempDir;
 
-use t_koma_knowledge::models::{NoteCreateRequest, NoteUpdateRequest, OwnershipScope, WriteScope};
-use t_koma_knowledge::storage::{KnowledgeStore, NoteRecord, replace_tags, upsert_note};
-use t_koma_knowledge::{KnowledgeEngine, KnowledgeSettings};
+use t_koma_knowledge::index::check_embedding_provider_change;
+use t_koma_knowledge::ingest::ingest_markdown;
+use t_koma_knowledge::models::{
+    GraphFormat, KnowledgeGetQuery, KnowledgeScope, NoteCreateRequest, NoteUpdateRequest,
+    OwnershipScope, WriteScope,
+};
+use t_koma_knowledge::storage::{
+    KnowledgeStore, NoteRecord, active_migration_source, chunks_missing_embeddings,
+    ensure_vec_table_dim, index_generation, mark_chunk_embedded, replace_chunks, replace_links,
+    replace_tags, set_embedding_fingerprint, upsert_note,
+};
+use t_koma_knowledge::{KnowledgeEngine, KnowledgeSettings, ReembedMode};
+
+/// A note record with test defaults; override fields with struct update syntax.
+fn test_note(id: &str, title: &str, owner: Option<&str>, path: std::path::PathBuf) -> NoteRecord {
+    NoteRecord {
+        id: id.to_string(),
+        title: title.to_string(),
+        entry_type: "Concept".to_string(),
+        archetype: None,
+        path,
+        scope: if owner.is_some() {
+            "ghost_note"
+        } else {
+            "shared_note"
+        }
+        .to_string(),
+        owner_ghost: owner.map(str::to_string),
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        created_by_ghost: owner.unwrap_or("ghost-a").to_string(),
+        created_by_model: "model".to_string(),
+        trust_score: 5,
+        last_validated_at: None,
+        last_validated_by_ghost: None,
+        last_validated_by_model: None,
+        version: None,
+        parent_id: None,
+        comments_json: None,
+        content_hash: "hash".to_string(),
+    }
+}
 
 /// Build a test engine with temp dirs. Returns (engine, ghost_name, temp).
 async fn setup() -> (KnowledgeEngine, String, TempDir) {
@@ -376,6 +414,66 @@ Ghost B content.
     assert!(result.is_err(), "ghost-a should not update ghost-b's note");
 }
 
+#[tokio::test]
+async fn restore_puts_back_earlier_content() {
+    let (engine, ghost_name, temp) = setup().await;
+    let data_root = temp.path().join("data");
+    let shared_root = data_root.join("shared").join("notes");
+
+    let db_path = data_root.join("shared").join("index.sqlite3");
+    let store = KnowledgeStore::open(&db_path, Some(8)).await.unwrap();
+
+    let note_path = shared_root.join("restorable.md");
+    let original = r#"+++
+id = "restorable-note"
+title = "Original Title"
+type = "Concept"
+created_at = "2025-01-01T00:00:00Z"
+trust_score = 5
+[created_by]
+ghost = "ghost-a"
+model = "model"
++++
+
+Original body.
+"#;
+    tokio::fs::write(&note_path, original).await.unwrap();
+
+    let note = NoteRecord {
+        version: Some(1),
+        ..test_note(
+            "restorable-note",
+            "Original Title",
+            Some("ghost-a"),
+            note_path.clone(),
+        )
+    };
+    upsert_note(store.pool(), &note).await.unwrap();
+
+    let request = NoteUpdateRequest {
+        note_id: "restorable-note".to_string(),
+        body: Some("Rewritten body.".to_string()),
+        ..Default::default()
+    };
+    // Embedding fails with the bogus URL after the file is written.
+    let _ = engine.note_update(&ghost_name, request).await;
+    let updated = tokio::fs::read_to_string(&note_path).await.unwrap();
+    assert!(updated.contains("Rewritten body."));
+
+    let _ = engine
+        .note_restore(&ghost_name, "restorable-note", original)
+        .await;
+    let restored = tokio::fs::read_to_string(&note_path).await.unwrap();
+    assert_eq!(restored, original);
+
+    let result = engine
+        .note_restore(&ghost_name, "restorable-note", "not a note")
+        .await;
+    assert!(result.is_err(), "unparseable content must not be written");
+    let unchanged = tokio::fs::read_to_string(&note_path).await.unwrap();
+    assert_eq!(unchanged, original);
+}
+
 // ── note_validate ────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -688,6 +786,60 @@ async fn topic_list_returns_inserted_topics() {
     assert_eq!(list_all.len(), 2);
 }
 
+#[tokio::test]
+async fn archived_topic_hidden_until_restored() {
+    let (engine, _ghost_name, temp) = setup().await;
+    let data_root = temp.path().join("data");
+    let shared_root = data_root.join("shared").join("notes");
+
+    let db_path = data_root.join("shared").join("index.sqlite3");
+    let store = KnowledgeStore::open(&db_path, Some(8)).await.unwrap();
+
+    insert_topic_note(
+        &store,
+        &shared_root,
+        "topic-old",
+        "Old Library",
+        "ghost-a",
+        "active",
+        0,
+        "2025-06-01T00:00:00Z",
+        &["legacy"],
+    )
+    .await;
+
+    let (topic_id, _) = engine
+        .topic_archive("ghost-a", "Old Library", Some("superseded"))
+        .await
+        .unwrap();
+    assert_eq!(topic_id, "topic-old");
+
+    assert!(engine.topic_list(false).await.unwrap().is_empty());
+    assert!(engine.recent_topics().await.unwrap().is_empty());
+    let all = engine.topic_list(true).await.unwrap();
+    assert_eq!(all.len(), 1);
+    assert!(all[0].archived);
+
+    // Files are retained while archived.
+    let (files,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM reference_files WHERE topic_id = 'topic-old'")
+            .fetch_one(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(files, 1);
+
+    engine.topic_restore("topic-old").await.unwrap();
+    let list = engine.topic_list(false).await.unwrap();
+    assert_eq!(list.len(), 1);
+    assert!(!list[0].archived);
+    assert_eq!(engine.recent_topics().await.unwrap().len(), 1);
+
+    assert!(
+        engine.topic_restore("topic-old").await.is_err(),
+        "restoring a non-archived topic should fail"
+    );
+}
+
 #[tokio::test]
 async fn recent_topics_returns_most_recent() {
     let (engine, _ghost_name, temp) = setup().await;
@@ -800,3 +952,273 @@ mod slow {
         );
     }
 }
+
+// ── search cache invalidation ────────────────────────────────────────
+
+#[tokio::test]
+async fn index_writes_bump_generation() {
+    let (engine, _ghost_name, temp) = setup().await;
+    let shared_root = temp.path().join("data").join("shared").join("notes");
+
+    let before = index_generation(engine.pool()).await.unwrap();
+
+    let note = NoteRecord {
+        entry_type: "Note".to_string(),
+        ..test_note(
+            "gen-note",
+            "Generation Note",
+            None,
+            shared_root.join("gen-note.md"),
+        )
+    };
+    upsert_note(engine.pool(), &note).await.unwrap();
+
+    let after = index_generation(engine.pool()).await.unwrap();
+    assert!(after > before, "generation should advance on note upsert");
+}
+
+// ── settings reload ──────────────────────────────────────────────────
+
+#[tokio::test]
+async fn reload_applies_search_settings_and_flags_embedding_changes() {
+    let (engine, _ghost_name, _temp) = setup().await;
+    let current = KnowledgeSettings::clone(&engine.settings());
+
+    let unchanged = engine.reload_settings(&current);
+    assert!(!unchanged.applied);
+    assert!(unchanged.restart_required.is_empty());
+
+    let mut new = current.clone();
+    new.search.max_results = current.search.max_results + 3;
+    new.embedding_model = "other-model".to_string();
+    let reload = engine.reload_settings(&new);
+
+    assert!(reload.applied);
+    assert_eq!(reload.restart_required, vec!["embedding_model"]);
+    let live = engine.settings();
+    assert_eq!(live.search.max_results, current.search.max_results + 3);
+    assert_eq!(live.embedding_model, current.embedding_model);
+}
+
+// ── graph export ─────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn export_graph_respects_scope_and_links() {
+    let (engine, ghost_name, temp) = setup().await;
+    let data_root = temp.path().join("data");
+    let shared_root = data_root.join("shared").join("notes");
+    let ghost_root = data_root.join("ghosts").join("ghost-a").join("notes");
+    let pool = engine.pool();
+
+    upsert_note(
+        pool,
+        &test_note(
+            "shared-1",
+            "Shared Note",
+            None,
+            shared_root.join("shared-1.md"),
+        ),
+    )
+    .await
+    .unwrap();
+    let mut child = test_note(
+        "private-1",
+        "Private Note",
+        Some("ghost-a"),
+        ghost_root.join("private-1.md"),
+    );
+    child.parent_id = Some("shared-1".to_string());
+    upsert_note(pool, &child).await.unwrap();
+    upsert_note(
+        pool,
+        &test_note(
+            "other-1",
+            "Other Ghost Note",
+            Some("ghost-b"),
+            ghost_root.join("other-1.md"),
+        ),
+    )
+    .await
+    .unwrap();
+    replace_links(
+        pool,
+        "private-1",
+        Some("ghost-a"),
+        &[("Shared Note".to_string(), None)],
+    )
+    .await
+    .unwrap();
+
+    let dot = engine
+        .export_graph(&ghost_name, OwnershipScope::All, GraphFormat::Dot)
+        .await
+        .unwrap();
+    assert!(dot.contains(r#""private-1" -> "shared-1" [kind="link""#));
+    assert!(dot.contains(r#""private-1" -> "shared-1" [kind="parent""#));
+    assert!(!dot.contains("other-1"), "other ghost notes must not leak");
+
+    let shared_only = engine
+        .export_graph(&ghost_name, OwnershipScope::Shared, GraphFormat::GraphMl)
+        .await
+        .unwrap();
+    assert!(shared_only.contains(r#"<node id="shared-1">"#));
+    assert!(!shared_only.contains("private-1"));
+    assert!(!shared_only.contains("<edge "));
+}
+
+// ── language detection ───────────────────────────────────────────────
+
+#[tokio::test]
+async fn cjk_chunks_store_language_and_bigram_fts() {
+    let (engine, _ghost_name, temp) = setup().await;
+    let path = temp
+        .path()
+        .join("data")
+        .join("shared")
+        .join("notes")
+        .join("tokyo.md");
+    let raw = r#"+++
+id = "tokyo"
+title = "Tokyo"
+type = "Concept"
+created_at = "2025-01-01T00:00:00Z"
+trust_score = 5
+[created_by]
+ghost = "ghost-a"
+model = "model"
++++
+
+東京都は日本の首都です。
+"#;
+
+    let ingested = ingest_markdown(
+        &engine.settings(),
+        KnowledgeScope::SharedNote,
+        None,
+        &path,
+        raw,
+    )
+    .await
+    .unwrap();
+    assert_eq!(ingested.chunks[0].language.as_deref(), Some("ja"));
+
+    upsert_note(engine.pool(), &ingested.note).await.unwrap();
+    replace_chunks(
+        engine.pool(),
+        &ingested.note.id,
+        &ingested.note.title,
+        &ingested.note.entry_type,
+        None,
+        &ingested.chunks,
+    )
+    .await
+    .unwrap();
+
+    let (language,): (Option<String>,) =
+        sqlx::query_as("SELECT language FROM chunks WHERE note_id = 'tokyo'")
+            .fetch_one(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(language.as_deref(), Some("ja"));
+
+    // "京都" sits inside the run "東京都" and is only reachable via bigrams.
+    let hits: Vec<(String,)> =
+        sqlx::query_as("SELECT note_id FROM chunk_fts WHERE chunk_fts MATCH '\"京都\"'")
+            .fetch_all(engine.pool())
+            .await
+            .unwrap();
+    assert_eq!(hits, vec![("tokyo".to_string(),)]);
+}
+
+// ── gradual re-embedding ─────────────────────────────────────────────
+
+async fn embedded_note(engine: &KnowledgeEngine, root: &std::path::Path, id: &str, model: &str) {
+    let raw = format!(
+        "+++\nid = \"{id}\"\ntitle = \"{id}\"\ntype = \"Concept\"\n\
+         created_at = \"2025-01-01T00:00:00Z\"\ntrust_score = 5\n\
+         [created_by]\nghost = \"ghost-a\"\nmodel = \"model\"\n+++\n\nBody of {id}.\n"
+    );
+    let ingested = ingest_markdown(
+        &engine.settings(),
+        KnowledgeScope::SharedNote,
+        None,
+        &root.join(format!("{id}.md")),
+        &raw,
+    )
+    .await
+    .unwrap();
+    upsert_note(engine.pool(), &ingested.note).await.unwrap();
+    let chunk_ids = replace_chunks(
+        engine.pool(),
+        &ingested.note.id,
+        &ingested.note.title,
+        &ingested.note.entry_type,
+        None,
+        &ingested.chunks,
+    )
+    .await
+    .unwrap();
+    for chunk_id in chunk_ids {
+        mark_chunk_embedded(engine.pool(), chunk_id, model, 8)
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn gradual_reembed_keeps_vectors_and_prioritizes_recent_notes() {
+    let (engine, ghost_name, temp) = setup().await;
+    let shared_root = temp.path().join("data").join("shared").join("notes");
+    let pool = engine.pool();
+
+    ensure_vec_table_dim(pool, 8).await.unwrap();
+    set_embedding_fingerprint(pool, "ollama:old-model")
+        .await
+        .unwrap();
+    embedded_note(&engine, &shared_root, "cold", "old-model").await;
+    embedded_note(&engine, &shared_root, "hot", "old-model").await;
+
+    engine
+        .knowledge_get(
+            &ghost_name,
+            KnowledgeGetQuery {
+                id: Some("hot".to_string()),
+                topic: None,
+                path: None,
+                max_chars: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let settings = KnowledgeSettings {
+        embedding_model: "new-model".to_string(),
+        embedding_dim: Some(8),
+        reembed_mode: ReembedMode::Gradual,
+        ..KnowledgeSettings::clone(&engine.settings())
+    };
+    assert!(
+        check_embedding_provider_change(&settings, pool)
+            .await
+            .unwrap()
+    );
+
+    let (vec_tables,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'chunk_vec'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(vec_tables, 1, "old vectors must stay queryable");
+    assert_eq!(
+        active_migration_source(pool).await.unwrap().as_deref(),
+        Some("old-model")
+    );
+
+    let pending = chunks_missing_embeddings(pool, 10).await.unwrap();
+    assert!(!pending.is_empty());
+    assert!(
+        pending[0].1.contains("Body of hot"),
+        "recently accessed notes are re-embedded first"
+    );
+}